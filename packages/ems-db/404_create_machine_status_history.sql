@@ -0,0 +1,65 @@
+-- Migration: Create machine status history table
+-- This migration records every machine status transition so downtime can be reported over time
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, and 403_create_machine_tables.sql first
+
+-- Create machine_status_history table
+CREATE TABLE public.machine_status_history (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  previous_status VARCHAR(20),
+  new_status VARCHAR(20) NOT NULL CHECK (new_status IN ('offline', 'idle', 'busy', 'maintenance', 'error')),
+  changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for machine_status_history table
+CREATE INDEX idx_machine_status_history_tenant_id ON public.machine_status_history(tenant_id);
+CREATE INDEX idx_machine_status_history_machine_changed_at ON public.machine_status_history(machine_id, changed_at);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_status_history ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_status_history_tenant_isolation" ON public.machine_status_history
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Create trigger function for machine status change logging
+CREATE OR REPLACE FUNCTION public.log_machine_status_change()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO public.machine_status_history (machine_id, tenant_id, previous_status, new_status)
+        VALUES (NEW.id, NEW.tenant_id, NULL, NEW.status);
+    -- Only log if status has changed
+    ELSIF OLD.status IS DISTINCT FROM NEW.status THEN
+        INSERT INTO public.machine_status_history (machine_id, tenant_id, previous_status, new_status)
+        VALUES (NEW.id, NEW.tenant_id, OLD.status, NEW.status);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create trigger for automatic machine status change logging
+CREATE TRIGGER log_machine_status_change_trigger
+    AFTER INSERT OR UPDATE ON public.machines
+    FOR EACH ROW
+    EXECUTE FUNCTION public.log_machine_status_change();
+
+-- Seed history with the current status of existing machines
+INSERT INTO public.machine_status_history (machine_id, tenant_id, previous_status, new_status, changed_at)
+SELECT id, tenant_id, NULL, status, COALESCE(updated_at, created_at, NOW())
+FROM public.machines;
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_status_history TO authenticated, service_role;
+GRANT EXECUTE ON FUNCTION public.log_machine_status_change() TO postgres, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_status_history IS 'Log of machine status transitions used for downtime reporting';
+COMMENT ON COLUMN public.machine_status_history.previous_status IS 'Status before the change (NULL for the initial status)';
+COMMENT ON COLUMN public.machine_status_history.new_status IS 'Status after the change';
+COMMENT ON COLUMN public.machine_status_history.changed_at IS 'Timestamp at which the new status took effect';
@@ -0,0 +1,55 @@
+-- Migration: Create report schedules table
+-- This migration lets tenants schedule canned reports to be rendered and emailed on a cron expression
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 404_create_machine_status_history.sql first
+
+-- Create report_schedules table
+CREATE TABLE public.report_schedules (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  report_key VARCHAR(50) NOT NULL CHECK (report_key IN ('inventory_valuation', 'open_order_book', 'machine_downtime', 'job_margin')),
+  cron_expression VARCHAR(100) NOT NULL,
+  timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+  parameters JSONB NOT NULL DEFAULT '{}',
+  recipients TEXT[] NOT NULL CHECK (cardinality(recipients) > 0),
+  format VARCHAR(10) NOT NULL DEFAULT 'csv' CHECK (format IN ('csv', 'json')),
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  next_run_at TIMESTAMP WITH TIME ZONE,
+  last_run_at TIMESTAMP WITH TIME ZONE,
+  last_status VARCHAR(20) CHECK (last_status IN ('success', 'failed')),
+  last_error TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for report_schedules table
+CREATE INDEX idx_report_schedules_tenant_id ON public.report_schedules(tenant_id);
+CREATE INDEX idx_report_schedules_report_key ON public.report_schedules(report_key);
+CREATE INDEX idx_report_schedules_due ON public.report_schedules(next_run_at) WHERE is_active;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_report_schedules_updated_at
+  BEFORE UPDATE ON public.report_schedules
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.report_schedules ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "report_schedules_tenant_isolation" ON public.report_schedules
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.report_schedules TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.report_schedules IS 'Scheduled report deliveries rendered and emailed by the server scheduler';
+COMMENT ON COLUMN public.report_schedules.report_key IS 'Key of the canned report to run';
+COMMENT ON COLUMN public.report_schedules.cron_expression IS 'Cron expression (5 fields, or 6 with leading seconds) evaluated in the schedule timezone';
+COMMENT ON COLUMN public.report_schedules.timezone IS 'IANA timezone name used to evaluate the cron expression';
+COMMENT ON COLUMN public.report_schedules.parameters IS 'Report parameters as a JSON object of string values';
+COMMENT ON COLUMN public.report_schedules.recipients IS 'Email addresses that receive the rendered report';
+COMMENT ON COLUMN public.report_schedules.next_run_at IS 'Next time the scheduler will deliver this report';
+COMMENT ON COLUMN public.report_schedules.last_status IS 'Outcome of the most recent delivery attempt';
@@ -0,0 +1,47 @@
+-- Migration: Create stock movements ledger
+-- This migration records every change to inventory quantities as an append-only ledger
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 401_create_item_tables.sql first
+
+-- Create stock_movements table
+CREATE TABLE public.stock_movements (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  inventory_item_id UUID NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+  context VARCHAR(20) NOT NULL CHECK (context IN ('finished_goods', 'store', 'vendor')),
+  movement_type VARCHAR(20) NOT NULL CHECK (movement_type IN ('receipt', 'issue', 'adjustment', 'return')),
+  quantity INTEGER NOT NULL CHECK (quantity <> 0),
+  quantity_after INTEGER NOT NULL,
+  reference_type VARCHAR(20) CHECK (reference_type IN ('job', 'order', 'manual')),
+  reference_id UUID,
+  person_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  notes TEXT,
+  occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for stock_movements table
+CREATE INDEX idx_stock_movements_tenant_id ON public.stock_movements(tenant_id);
+CREATE INDEX idx_stock_movements_item_occurred_at ON public.stock_movements(item_id, occurred_at);
+CREATE INDEX idx_stock_movements_inventory_item_id ON public.stock_movements(inventory_item_id);
+CREATE INDEX idx_stock_movements_movement_type ON public.stock_movements(movement_type);
+CREATE INDEX idx_stock_movements_reference ON public.stock_movements(reference_type, reference_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.stock_movements ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "stock_movements_tenant_isolation" ON public.stock_movements
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.stock_movements TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.stock_movements IS 'Append-only ledger of inventory quantity changes';
+COMMENT ON COLUMN public.stock_movements.movement_type IS 'Kind of movement (receipt, issue, adjustment, return)';
+COMMENT ON COLUMN public.stock_movements.quantity IS 'Signed quantity change applied to the inventory record';
+COMMENT ON COLUMN public.stock_movements.quantity_after IS 'Inventory quantity after the movement was applied';
+COMMENT ON COLUMN public.stock_movements.reference_type IS 'Type of document that caused the movement (job, order, manual)';
+COMMENT ON COLUMN public.stock_movements.occurred_at IS 'Business time of the movement, used for consumption history';
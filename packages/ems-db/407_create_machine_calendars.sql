@@ -0,0 +1,97 @@
+-- Migration: Create shift patterns and machine calendars
+-- This migration records when machines are actually available: weekly shift patterns per tenant,
+-- the pattern each machine follows, and calendar exceptions (planned downtime, holidays, extra shifts)
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, and 403_create_machine_tables.sql first
+
+-- Create shift_patterns table
+CREATE TABLE public.shift_patterns (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  description TEXT,
+  shifts JSONB NOT NULL DEFAULT '[]',
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create machine_calendars table (one calendar per machine)
+CREATE TABLE public.machine_calendars (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL UNIQUE REFERENCES public.machines(id) ON DELETE CASCADE,
+  shift_pattern_id UUID REFERENCES public.shift_patterns(id) ON DELETE SET NULL,
+  timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create calendar_exceptions table (machine_id NULL applies to every machine of the tenant)
+CREATE TABLE public.calendar_exceptions (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID REFERENCES public.machines(id) ON DELETE CASCADE,
+  exception_type VARCHAR(20) NOT NULL CHECK (exception_type IN ('planned_downtime', 'holiday', 'extra_shift')),
+  starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  reason TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  CHECK (ends_at > starts_at)
+);
+
+-- Create indexes
+CREATE INDEX idx_shift_patterns_tenant_id ON public.shift_patterns(tenant_id);
+CREATE INDEX idx_machine_calendars_tenant_id ON public.machine_calendars(tenant_id);
+CREATE INDEX idx_machine_calendars_shift_pattern_id ON public.machine_calendars(shift_pattern_id);
+CREATE INDEX idx_calendar_exceptions_tenant_id ON public.calendar_exceptions(tenant_id);
+CREATE INDEX idx_calendar_exceptions_machine_id ON public.calendar_exceptions(machine_id);
+CREATE INDEX idx_calendar_exceptions_period ON public.calendar_exceptions(starts_at, ends_at);
+
+-- Create triggers for updated_at timestamps (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_shift_patterns_updated_at
+  BEFORE UPDATE ON public.shift_patterns
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_machine_calendars_updated_at
+  BEFORE UPDATE ON public.machine_calendars
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_calendar_exceptions_updated_at
+  BEFORE UPDATE ON public.calendar_exceptions
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.shift_patterns ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_calendars ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.calendar_exceptions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "shift_patterns_tenant_isolation" ON public.shift_patterns
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_calendars_tenant_isolation" ON public.machine_calendars
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "calendar_exceptions_tenant_isolation" ON public.calendar_exceptions
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.shift_patterns TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_calendars TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.calendar_exceptions TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.shift_patterns IS 'Weekly shift patterns defining working hours';
+COMMENT ON COLUMN public.shift_patterns.shifts IS 'Array of shifts: {"day_of_week": 1-7 (Monday = 1), "start_time": "HH:MM", "end_time": "HH:MM"}; an end at or before the start crosses midnight';
+COMMENT ON TABLE public.machine_calendars IS 'Shift pattern and timezone each machine works to';
+COMMENT ON COLUMN public.machine_calendars.timezone IS 'IANA timezone name the shift pattern is evaluated in';
+COMMENT ON TABLE public.calendar_exceptions IS 'Planned downtime, holidays and extra shifts overriding the shift pattern';
+COMMENT ON COLUMN public.calendar_exceptions.machine_id IS 'Machine the exception applies to; NULL applies it to every machine';
+COMMENT ON COLUMN public.calendar_exceptions.exception_type IS 'Type of exception (planned_downtime, holiday, extra_shift)';
@@ -0,0 +1,98 @@
+-- Migration: Create operator skill matrix
+-- This migration adds a per-tenant skill catalog, certifications held by internal persons,
+-- and the skills each machine requires from its operators
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 403_create_machine_tables.sql first
+
+-- Create skills table
+CREATE TABLE public.skills (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  description TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create person_skills table for certifications held by internal persons
+CREATE TABLE public.person_skills (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  skill_id UUID NOT NULL REFERENCES public.skills(id) ON DELETE CASCADE,
+  certified_at TIMESTAMP WITH TIME ZONE,
+  expires_at TIMESTAMP WITH TIME ZONE,
+  certificate_number VARCHAR(100),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(person_id, skill_id)
+);
+
+-- Create machine_skill_requirements table for skills a machine requires from its operators
+CREATE TABLE public.machine_skill_requirements (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  skill_id UUID NOT NULL REFERENCES public.skills(id) ON DELETE CASCADE,
+  enforcement VARCHAR(10) NOT NULL DEFAULT 'block' CHECK (enforcement IN ('warn', 'block')),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(machine_id, skill_id)
+);
+
+-- Create indexes
+CREATE INDEX idx_skills_tenant_id ON public.skills(tenant_id);
+CREATE INDEX idx_person_skills_tenant_id ON public.person_skills(tenant_id);
+CREATE INDEX idx_person_skills_person_id ON public.person_skills(person_id);
+CREATE INDEX idx_person_skills_skill_id ON public.person_skills(skill_id);
+CREATE INDEX idx_person_skills_expires_at ON public.person_skills(expires_at);
+CREATE INDEX idx_machine_skill_requirements_machine_id ON public.machine_skill_requirements(machine_id);
+CREATE INDEX idx_machine_skill_requirements_skill_id ON public.machine_skill_requirements(skill_id);
+
+-- Create triggers for updated_at timestamps (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_skills_updated_at
+  BEFORE UPDATE ON public.skills
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_person_skills_updated_at
+  BEFORE UPDATE ON public.person_skills
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_machine_skill_requirements_updated_at
+  BEFORE UPDATE ON public.machine_skill_requirements
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.skills ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.person_skills ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_skill_requirements ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "skills_tenant_isolation" ON public.skills
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "person_skills_tenant_isolation" ON public.person_skills
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_skill_requirements_tenant_isolation" ON public.machine_skill_requirements
+    FOR ALL USING (
+        machine_id IN (
+            SELECT id FROM public.machines
+            WHERE tenant_id = public.get_current_tenant_id()
+        )
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.skills TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.person_skills TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_skill_requirements TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.skills IS 'Skills and certifications operators can hold';
+COMMENT ON TABLE public.person_skills IS 'Certifications held by internal persons';
+COMMENT ON COLUMN public.person_skills.expires_at IS 'When the certification lapses; NULL means it does not expire';
+COMMENT ON TABLE public.machine_skill_requirements IS 'Skills a machine requires from its operators';
+COMMENT ON COLUMN public.machine_skill_requirements.enforcement IS 'How an uncertified operator assignment is handled (warn, block)';
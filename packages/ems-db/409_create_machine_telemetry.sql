@@ -0,0 +1,35 @@
+-- Migration: Create machine telemetry table
+-- This migration stores numeric telemetry readings (energy, air pressure, coolant level) extracted
+-- from machine heartbeats as a time series for charting
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, and 403_create_machine_tables.sql first
+
+-- Create machine_telemetry table
+CREATE TABLE public.machine_telemetry (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  channel VARCHAR(20) NOT NULL CHECK (channel IN ('energy_kwh', 'air_pressure', 'coolant_level')),
+  value DOUBLE PRECISION NOT NULL,
+  recorded_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for machine_telemetry table
+CREATE INDEX idx_machine_telemetry_tenant_id ON public.machine_telemetry(tenant_id);
+CREATE INDEX idx_machine_telemetry_machine_channel_recorded_at ON public.machine_telemetry(machine_id, channel, recorded_at);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_telemetry ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_telemetry_tenant_isolation" ON public.machine_telemetry
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_telemetry TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_telemetry IS 'Time series of telemetry readings reported in machine heartbeats';
+COMMENT ON COLUMN public.machine_telemetry.channel IS 'Telemetry channel (energy_kwh, air_pressure, coolant_level)';
+COMMENT ON COLUMN public.machine_telemetry.value IS 'Reading in the channel unit (kWh, bar, percent)';
+COMMENT ON COLUMN public.machine_telemetry.recorded_at IS 'Time of the heartbeat that carried the reading';
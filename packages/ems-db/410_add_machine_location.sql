@@ -0,0 +1,19 @@
+-- Migration: Add location to machines
+-- This migration adds optional coordinates and a site reference to machines so distributed
+-- equipment can be plotted on a map
+-- PREREQUISITE: Run 403_create_machine_tables.sql first
+
+-- Add location columns to machines table
+ALTER TABLE public.machines
+  ADD COLUMN latitude DOUBLE PRECISION CHECK (latitude >= -90 AND latitude <= 90),
+  ADD COLUMN longitude DOUBLE PRECISION CHECK (longitude >= -180 AND longitude <= 180),
+  ADD COLUMN site VARCHAR(100),
+  ADD CONSTRAINT machines_coordinates_pair CHECK ((latitude IS NULL) = (longitude IS NULL));
+
+-- Create index for site lookups
+CREATE INDEX idx_machines_site ON public.machines(site);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.machines.latitude IS 'WGS 84 latitude in decimal degrees';
+COMMENT ON COLUMN public.machines.longitude IS 'WGS 84 longitude in decimal degrees';
+COMMENT ON COLUMN public.machines.site IS 'Free-form site reference (plant, building, customer location)';
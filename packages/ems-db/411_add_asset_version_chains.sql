@@ -0,0 +1,46 @@
+-- Migration: Add version chains to assets
+-- This migration links each asset version to its predecessor, groups versions of the same logical
+-- asset under a lineage with exactly one current version, and lets machine-asset relationships pin
+-- either the latest version or a specific one
+-- PREREQUISITE: Run 402_create_asset_tables.sql and 403_create_machine_tables.sql first
+
+-- Add version chain columns to assets table
+ALTER TABLE public.assets
+  ADD COLUMN lineage_id UUID,
+  ADD COLUMN previous_version_id UUID REFERENCES public.assets(id) ON DELETE SET NULL,
+  ADD COLUMN is_current BOOLEAN NOT NULL DEFAULT true;
+
+-- Every existing asset starts its own lineage
+UPDATE public.assets SET lineage_id = id WHERE lineage_id IS NULL;
+
+ALTER TABLE public.assets ALTER COLUMN lineage_id SET NOT NULL;
+
+-- A new asset without a lineage starts one named after itself
+CREATE OR REPLACE FUNCTION public.set_asset_lineage_id()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NEW.lineage_id IS NULL THEN
+    NEW.lineage_id := NEW.id;
+  END IF;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_assets_lineage_id
+  BEFORE INSERT ON public.assets
+  FOR EACH ROW EXECUTE FUNCTION public.set_asset_lineage_id();
+
+-- Create indexes for version chain lookups
+CREATE INDEX idx_assets_lineage_id ON public.assets(lineage_id);
+CREATE INDEX idx_assets_previous_version_id ON public.assets(previous_version_id);
+CREATE UNIQUE INDEX idx_assets_lineage_id_current ON public.assets(lineage_id) WHERE is_current;
+
+-- Add pin mode to machine_asset_relationships table
+ALTER TABLE public.machine_asset_relationships
+  ADD COLUMN pin_mode VARCHAR(10) NOT NULL DEFAULT 'specific' CHECK (pin_mode IN ('latest', 'specific'));
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.assets.lineage_id IS 'Logical asset shared by all versions; the id of the first version';
+COMMENT ON COLUMN public.assets.previous_version_id IS 'Version this one superseded';
+COMMENT ON COLUMN public.assets.is_current IS 'Whether this is the current version of its lineage (one per lineage)';
+COMMENT ON COLUMN public.machine_asset_relationships.pin_mode IS 'latest follows the current version of the lineage, specific stays on asset_id';
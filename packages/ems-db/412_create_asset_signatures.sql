@@ -0,0 +1,50 @@
+-- Migration: Add document control to assets
+-- This migration adds a release status and retention period to assets and records electronic
+-- signatures, so certificates and controlled documents are released only after approval
+-- (ISO 9001 document control)
+-- PREREQUISITE: Run 101_create_person_tables.sql, 402_create_asset_tables.sql and 411_add_asset_version_chains.sql first
+
+-- Add document control columns to assets table; existing assets stay released
+ALTER TABLE public.assets
+  ADD COLUMN release_status VARCHAR(20) NOT NULL DEFAULT 'released' CHECK (release_status IN ('draft', 'released')),
+  ADD COLUMN released_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN retain_until TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_assets_release_status ON public.assets(release_status);
+
+-- Create asset_signatures table
+CREATE TABLE public.asset_signatures (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  asset_id UUID NOT NULL REFERENCES public.assets(id) ON DELETE CASCADE,
+  signed_by_id UUID NOT NULL REFERENCES public.person(id) ON DELETE RESTRICT,
+  meaning VARCHAR(20) NOT NULL CHECK (meaning IN ('authored', 'reviewed', 'approved')),
+  comment TEXT,
+  content_checksum VARCHAR(64),
+  signed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  UNIQUE(asset_id, signed_by_id, meaning)
+);
+
+-- Create indexes for asset_signatures table
+CREATE INDEX idx_asset_signatures_tenant_id ON public.asset_signatures(tenant_id);
+CREATE INDEX idx_asset_signatures_asset_id ON public.asset_signatures(asset_id);
+CREATE INDEX idx_asset_signatures_signed_by_id ON public.asset_signatures(signed_by_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.asset_signatures ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "asset_signatures_tenant_isolation" ON public.asset_signatures
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.asset_signatures TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.assets.release_status IS 'Document control status (draft, released); controlled documents start as draft';
+COMMENT ON COLUMN public.assets.released_at IS 'When the approval signature released the asset';
+COMMENT ON COLUMN public.assets.retain_until IS 'The asset cannot be deleted before this time';
+COMMENT ON TABLE public.asset_signatures IS 'Electronic signatures on assets; signatures are never edited';
+COMMENT ON COLUMN public.asset_signatures.meaning IS 'Meaning of the signature (authored, reviewed, approved); approved releases the asset';
+COMMENT ON COLUMN public.asset_signatures.content_checksum IS 'Asset checksum at signing, binding the signature to the signed content';
@@ -0,0 +1,90 @@
+-- Migration: Create label printing tables
+-- This migration configures networked ZPL label printers per tenant and location and queues print
+-- jobs so failed sends are retried by the server print worker
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 401_create_item_tables.sql first
+
+-- Create printers table
+CREATE TABLE public.printers (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  location VARCHAR(100),
+  host VARCHAR(255) NOT NULL,
+  port INTEGER NOT NULL DEFAULT 9100 CHECK (port > 0 AND port <= 65535),
+  dpi INTEGER NOT NULL DEFAULT 203 CHECK (dpi IN (152, 203, 300, 600)),
+  label_width_mm INTEGER NOT NULL DEFAULT 100 CHECK (label_width_mm > 0 AND label_width_mm <= 300),
+  label_height_mm INTEGER NOT NULL DEFAULT 50 CHECK (label_height_mm > 0 AND label_height_mm <= 300),
+  is_default BOOLEAN NOT NULL DEFAULT false,
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create print_jobs table
+CREATE TABLE public.print_jobs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  printer_id UUID NOT NULL REFERENCES public.printers(id) ON DELETE CASCADE,
+  item_id UUID REFERENCES public.items(id) ON DELETE SET NULL,
+  copies INTEGER NOT NULL DEFAULT 1 CHECK (copies > 0 AND copies <= 100),
+  zpl TEXT NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'printing', 'printed', 'failed')),
+  attempts INTEGER NOT NULL DEFAULT 0,
+  max_attempts INTEGER NOT NULL DEFAULT 5 CHECK (max_attempts > 0),
+  next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  last_error TEXT,
+  requested_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  printed_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for printers table
+CREATE INDEX idx_printers_tenant_id ON public.printers(tenant_id);
+CREATE INDEX idx_printers_location ON public.printers(location);
+CREATE UNIQUE INDEX idx_printers_tenant_id_default ON public.printers(tenant_id) WHERE is_default;
+
+-- Create indexes for print_jobs table
+CREATE INDEX idx_print_jobs_tenant_id ON public.print_jobs(tenant_id);
+CREATE INDEX idx_print_jobs_printer_id ON public.print_jobs(printer_id);
+CREATE INDEX idx_print_jobs_item_id ON public.print_jobs(item_id);
+CREATE INDEX idx_print_jobs_due ON public.print_jobs(next_attempt_at) WHERE status IN ('queued', 'printing');
+
+-- Create triggers for updated_at timestamps (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_printers_updated_at
+  BEFORE UPDATE ON public.printers
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_print_jobs_updated_at
+  BEFORE UPDATE ON public.print_jobs
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.printers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.print_jobs ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "printers_tenant_isolation" ON public.printers
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "print_jobs_tenant_isolation" ON public.print_jobs
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.printers TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.print_jobs TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.printers IS 'Networked ZPL label printers (e.g. Zebra) reachable over raw TCP';
+COMMENT ON COLUMN public.printers.location IS 'Site, building or station the printer serves';
+COMMENT ON COLUMN public.printers.port IS 'Raw TCP print port, 9100 on Zebra printers';
+COMMENT ON COLUMN public.printers.dpi IS 'Print head resolution in dots per inch';
+COMMENT ON COLUMN public.printers.is_default IS 'Printer used when a print request names none (one per tenant)';
+COMMENT ON TABLE public.print_jobs IS 'Queue of ZPL label jobs sent to printers, retried by the server print worker';
+COMMENT ON COLUMN public.print_jobs.zpl IS 'Rendered ZPL sent to the printer';
+COMMENT ON COLUMN public.print_jobs.attempts IS 'Number of send attempts so far';
+COMMENT ON COLUMN public.print_jobs.next_attempt_at IS 'Earliest time the print worker will retry the job';
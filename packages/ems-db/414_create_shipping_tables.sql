@@ -0,0 +1,104 @@
+-- Migration: Create shipping tables
+-- This migration adds shipments for order fulfillment with their packages and carrier tracking
+-- events, so labels and tracking numbers can be requested from a carrier and delivery can complete
+-- the order
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 301_create_orders_tables.sql first
+
+-- Create shipments table
+CREATE TABLE public.shipments (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  order_id UUID NOT NULL REFERENCES public.orders(id) ON DELETE CASCADE,
+  carrier VARCHAR(50) NOT NULL,
+  service_level VARCHAR(50),
+  status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'label_created', 'in_transit', 'delivered', 'exception', 'cancelled')),
+  ship_to JSONB NOT NULL,
+  tracking_number VARCHAR(100),
+  carrier_shipment_id VARCHAR(100),
+  label_url TEXT,
+  shipped_at TIMESTAMP WITH TIME ZONE,
+  delivered_at TIMESTAMP WITH TIME ZONE,
+  notes TEXT,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create shipment_packages table
+CREATE TABLE public.shipment_packages (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  shipment_id UUID NOT NULL REFERENCES public.shipments(id) ON DELETE CASCADE,
+  weight_kg DOUBLE PRECISION NOT NULL CHECK (weight_kg > 0),
+  length_cm DOUBLE PRECISION CHECK (length_cm > 0),
+  width_cm DOUBLE PRECISION CHECK (width_cm > 0),
+  height_cm DOUBLE PRECISION CHECK (height_cm > 0),
+  reference VARCHAR(100),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create shipment_tracking_events table
+CREATE TABLE public.shipment_tracking_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  shipment_id UUID NOT NULL REFERENCES public.shipments(id) ON DELETE CASCADE,
+  status VARCHAR(20) NOT NULL CHECK (status IN ('label_created', 'in_transit', 'delivered', 'exception')),
+  description TEXT,
+  location VARCHAR(200),
+  occurred_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(shipment_id, status, occurred_at)
+);
+
+-- Create indexes for shipments table
+CREATE INDEX idx_shipments_tenant_id ON public.shipments(tenant_id);
+CREATE INDEX idx_shipments_order_id ON public.shipments(order_id);
+CREATE INDEX idx_shipments_status ON public.shipments(status);
+CREATE INDEX idx_shipments_tracking_number ON public.shipments(tracking_number);
+
+-- Create indexes for shipment_packages table
+CREATE INDEX idx_shipment_packages_tenant_id ON public.shipment_packages(tenant_id);
+CREATE INDEX idx_shipment_packages_shipment_id ON public.shipment_packages(shipment_id);
+
+-- Create indexes for shipment_tracking_events table
+CREATE INDEX idx_shipment_tracking_events_tenant_id ON public.shipment_tracking_events(tenant_id);
+CREATE INDEX idx_shipment_tracking_events_shipment_id_occurred_at ON public.shipment_tracking_events(shipment_id, occurred_at);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_shipments_updated_at
+  BEFORE UPDATE ON public.shipments
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.shipments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.shipment_packages ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.shipment_tracking_events ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "shipments_tenant_isolation" ON public.shipments
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "shipment_packages_tenant_isolation" ON public.shipment_packages
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "shipment_tracking_events_tenant_isolation" ON public.shipment_tracking_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.shipments TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.shipment_packages TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.shipment_tracking_events TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.shipments IS 'Outbound shipments fulfilling an order';
+COMMENT ON COLUMN public.shipments.carrier IS 'Carrier provider that issues labels and tracking (e.g. webhook)';
+COMMENT ON COLUMN public.shipments.status IS 'Shipment status (pending, label_created, in_transit, delivered, exception, cancelled)';
+COMMENT ON COLUMN public.shipments.ship_to IS 'Destination address as JSON (name, street, city, postal_code, country, ...)';
+COMMENT ON COLUMN public.shipments.carrier_shipment_id IS 'Identifier of the shipment in the carrier system';
+COMMENT ON TABLE public.shipment_packages IS 'Packages in a shipment with their weight and dimensions';
+COMMENT ON TABLE public.shipment_tracking_events IS 'Carrier tracking history of a shipment';
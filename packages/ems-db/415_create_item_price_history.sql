@@ -0,0 +1,43 @@
+-- Migration: Create item price history table
+-- This migration records every vendor price change per item (unit price, price breaks and lead
+-- time) so cost trends can be charted per part
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 401_create_item_tables.sql first
+
+-- Create item_price_history table
+CREATE TABLE public.item_price_history (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  inventory_item_id UUID NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+  vendor_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  unit_price DOUBLE PRECISION NOT NULL CHECK (unit_price >= 0),
+  price_breaks JSONB NOT NULL DEFAULT '[]',
+  currency VARCHAR(3),
+  lead_time INTEGER CHECK (lead_time >= 0),
+  source VARCHAR(20) NOT NULL DEFAULT 'price_list' CHECK (source IN ('price_list')),
+  recorded_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  recorded_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for item_price_history table
+CREATE INDEX idx_item_price_history_tenant_id ON public.item_price_history(tenant_id);
+CREATE INDEX idx_item_price_history_item_id_recorded_at ON public.item_price_history(item_id, recorded_at);
+CREATE INDEX idx_item_price_history_vendor_id ON public.item_price_history(vendor_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.item_price_history ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "item_price_history_tenant_isolation" ON public.item_price_history
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.item_price_history TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.item_price_history IS 'Vendor price changes per item, one row per change';
+COMMENT ON COLUMN public.item_price_history.unit_price IS 'Price at the smallest break quantity';
+COMMENT ON COLUMN public.item_price_history.price_breaks IS 'Price breaks as [{"quantity": n, "unit_price": p}], ascending by quantity';
+COMMENT ON COLUMN public.item_price_history.lead_time IS 'Vendor lead time in days';
+COMMENT ON COLUMN public.item_price_history.source IS 'How the price was recorded (price_list)';
@@ -0,0 +1,69 @@
+-- Migration: Create item lifecycle watch tables
+-- This migration records the lifecycle status reported for each manufacturer part number by an
+-- external part data API, and the alerts raised when an item should move to NRND or obsolete
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 401_create_item_tables.sql first
+
+-- Create item_lifecycle_checks table (global, like items)
+CREATE TABLE public.item_lifecycle_checks (
+  item_id UUID PRIMARY KEY REFERENCES public.items(id) ON DELETE CASCADE,
+  provider VARCHAR(50) NOT NULL,
+  reported_status VARCHAR(100),
+  suggested_lifecycle VARCHAR(20) CHECK (suggested_lifecycle IN ('nrfnd', 'obsolete')),
+  last_error TEXT,
+  checked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  next_check_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Create item_lifecycle_alerts table
+CREATE TABLE public.item_lifecycle_alerts (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  current_lifecycle VARCHAR(20),
+  suggested_lifecycle VARCHAR(20) NOT NULL CHECK (suggested_lifecycle IN ('nrfnd', 'obsolete')),
+  reported_status VARCHAR(100) NOT NULL,
+  provider VARCHAR(50) NOT NULL,
+  substitutes UUID[] NOT NULL DEFAULT ARRAY[]::UUID[],
+  status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'accepted', 'dismissed')),
+  resolved_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  resolved_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for item_lifecycle_checks table
+CREATE INDEX idx_item_lifecycle_checks_next_check_at ON public.item_lifecycle_checks(next_check_at);
+
+-- Create indexes for item_lifecycle_alerts table
+CREATE INDEX idx_item_lifecycle_alerts_tenant_id ON public.item_lifecycle_alerts(tenant_id);
+CREATE INDEX idx_item_lifecycle_alerts_item_id ON public.item_lifecycle_alerts(item_id);
+CREATE INDEX idx_item_lifecycle_alerts_status ON public.item_lifecycle_alerts(status);
+-- One open alert per item and suggested lifecycle, so repeated checks do not pile up alerts
+CREATE UNIQUE INDEX idx_item_lifecycle_alerts_open ON public.item_lifecycle_alerts(tenant_id, item_id, suggested_lifecycle)
+  WHERE status = 'open';
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_item_lifecycle_alerts_updated_at
+  BEFORE UPDATE ON public.item_lifecycle_alerts
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+-- Note: item_lifecycle_checks is global (no tenant isolation needed)
+ALTER TABLE public.item_lifecycle_alerts ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "item_lifecycle_alerts_tenant_isolation" ON public.item_lifecycle_alerts
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.item_lifecycle_checks TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.item_lifecycle_alerts TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.item_lifecycle_checks IS 'Latest lifecycle lookup per item against the external part data API';
+COMMENT ON COLUMN public.item_lifecycle_checks.reported_status IS 'Lifecycle status as reported by the provider (e.g. Active, NRND, Obsolete)';
+COMMENT ON COLUMN public.item_lifecycle_checks.next_check_at IS 'When the lifecycle watch looks the part up again';
+COMMENT ON TABLE public.item_lifecycle_alerts IS 'Items whose lifecycle should move to NRND or obsolete, with suggested substitutes';
+COMMENT ON COLUMN public.item_lifecycle_alerts.substitutes IS 'Substitute items taken from BOM lines using the item';
+COMMENT ON COLUMN public.item_lifecycle_alerts.status IS 'Alert status (open, accepted, dismissed); accepting applies the suggested lifecycle';
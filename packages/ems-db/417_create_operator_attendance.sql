@@ -0,0 +1,76 @@
+-- Migration: Create operator shift and attendance tables
+-- This migration records which shift pattern each operator works and when they are absent or
+-- available outside their shifts, so scheduling can account for the people running machines
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 407_create_machine_calendars.sql first
+
+-- Create operator_shifts table (an operator may follow different patterns over time)
+CREATE TABLE public.operator_shifts (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  shift_pattern_id UUID NOT NULL REFERENCES public.shift_patterns(id) ON DELETE CASCADE,
+  timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+  effective_from DATE NOT NULL,
+  effective_to DATE,
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  CHECK (effective_to IS NULL OR effective_to >= effective_from)
+);
+
+-- Create operator_attendance table (absences and extra availability)
+CREATE TABLE public.operator_attendance (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  attendance_type VARCHAR(20) NOT NULL CHECK (attendance_type IN ('absent', 'sick', 'vacation', 'training', 'available')),
+  starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  reason TEXT,
+  recorded_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  CHECK (ends_at > starts_at)
+);
+
+-- Create indexes
+CREATE INDEX idx_operator_shifts_tenant_id ON public.operator_shifts(tenant_id);
+CREATE INDEX idx_operator_shifts_person_id ON public.operator_shifts(person_id);
+CREATE INDEX idx_operator_shifts_shift_pattern_id ON public.operator_shifts(shift_pattern_id);
+CREATE INDEX idx_operator_attendance_tenant_id ON public.operator_attendance(tenant_id);
+CREATE INDEX idx_operator_attendance_person_id ON public.operator_attendance(person_id);
+CREATE INDEX idx_operator_attendance_period ON public.operator_attendance(starts_at, ends_at);
+
+-- Create triggers for updated_at timestamps (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_operator_shifts_updated_at
+  BEFORE UPDATE ON public.operator_shifts
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_operator_attendance_updated_at
+  BEFORE UPDATE ON public.operator_attendance
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.operator_shifts ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.operator_attendance ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "operator_shifts_tenant_isolation" ON public.operator_shifts
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "operator_attendance_tenant_isolation" ON public.operator_attendance
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.operator_shifts TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.operator_attendance TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.operator_shifts IS 'Shift pattern an operator works over an effective date range';
+COMMENT ON COLUMN public.operator_shifts.timezone IS 'IANA timezone the shift pattern is evaluated in';
+COMMENT ON COLUMN public.operator_shifts.effective_to IS 'Last day the pattern applies (inclusive); NULL while it is current';
+COMMENT ON TABLE public.operator_attendance IS 'Operator absences and availability outside their shifts';
+COMMENT ON COLUMN public.operator_attendance.attendance_type IS 'absent, sick, vacation or training take the operator out; available adds working time';
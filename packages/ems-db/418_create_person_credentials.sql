@@ -0,0 +1,37 @@
+-- Migration: Create cached credentials for degraded sign-in
+-- Stores a local bcrypt hash of each password Supabase last accepted so already registered users
+-- can sign in while Supabase is unavailable
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, and 101_create_person_tables.sql first
+
+CREATE TABLE public.person_credentials (
+  person_id UUID PRIMARY KEY REFERENCES public.person(id) ON DELETE CASCADE,
+  password_hash VARCHAR(255) NOT NULL,
+  verified_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_person_credentials_verified_at ON public.person_credentials(verified_at);
+
+CREATE TRIGGER update_person_credentials_updated_at
+  BEFORE UPDATE ON public.person_credentials
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) for tenant isolation
+ALTER TABLE public.person_credentials ENABLE ROW LEVEL SECURITY;
+
+-- Credentials are visible through the same tenant_person relationship as the person
+CREATE POLICY "person_credentials_tenant_isolation" ON public.person_credentials
+    FOR ALL USING (
+        person_id IN (
+            SELECT person_id FROM public.tenant_person
+            WHERE tenant_id = public.get_current_tenant_id()
+        )
+    );
+
+-- Only the server reads password hashes
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.person_credentials TO service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.person_credentials IS 'Local password hashes used to sign in while Supabase is unavailable';
+COMMENT ON COLUMN public.person_credentials.verified_at IS 'When Supabase last accepted this password';
@@ -0,0 +1,40 @@
+-- Migration: Add local password authentication
+-- Lets EMS run without Supabase (AUTH_BACKEND=local): argon2 password hashes are stored on the
+-- person and email addresses are confirmed with single-use verification tokens
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, and 101_create_person_tables.sql first
+
+ALTER TABLE public.person
+  ADD COLUMN password_hash VARCHAR(255),
+  ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE;
+
+CREATE TABLE public.email_verification_tokens (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  token_hash VARCHAR(255) NOT NULL UNIQUE, -- SHA256 of the token sent by email
+  expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  used_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_email_verification_tokens_person_id ON public.email_verification_tokens(person_id);
+CREATE INDEX idx_email_verification_tokens_expires_at ON public.email_verification_tokens(expires_at);
+
+-- Add RLS (Row Level Security) for tenant isolation
+ALTER TABLE public.email_verification_tokens ENABLE ROW LEVEL SECURITY;
+
+-- Tokens are visible through the same tenant_person relationship as the person
+CREATE POLICY "email_verification_tokens_tenant_isolation" ON public.email_verification_tokens
+    FOR ALL USING (
+        person_id IN (
+            SELECT person_id FROM public.tenant_person
+            WHERE tenant_id = public.get_current_tenant_id()
+        )
+    );
+
+-- Only the server issues and redeems tokens
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.email_verification_tokens TO service_role;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.person.password_hash IS 'Argon2 password hash; only set when the local auth backend manages the account';
+COMMENT ON COLUMN public.person.email_verified_at IS 'When the person confirmed their email address with the local auth backend';
+COMMENT ON TABLE public.email_verification_tokens IS 'Single-use email verification links issued by the local auth backend';
@@ -0,0 +1,132 @@
+-- Migration: Create order return (RMA) tables
+-- This migration adds returns against shipped orders with a reason per line, receipt of returned
+-- stock into a quarantine location, per-line dispositions (restock, scrap, repair) and the credit
+-- notes issued for a return
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 301_create_orders_tables.sql, 401_create_item_tables.sql, and 406_create_stock_movements.sql first
+
+-- Create order_returns table
+CREATE TABLE public.order_returns (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  order_id UUID NOT NULL REFERENCES public.orders(id) ON DELETE CASCADE,
+  rma_number VARCHAR(50) NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'requested' CHECK (status IN ('requested', 'partially_received', 'received', 'closed', 'cancelled')),
+  quarantine_location VARCHAR(100) NOT NULL DEFAULT 'QUARANTINE',
+  notes TEXT,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  received_at TIMESTAMP WITH TIME ZONE,
+  closed_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, rma_number)
+);
+
+-- Create order_return_lines table
+CREATE TABLE public.order_return_lines (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  return_id UUID NOT NULL REFERENCES public.order_returns(id) ON DELETE CASCADE,
+  order_item_id UUID NOT NULL REFERENCES public.order_items(id) ON DELETE CASCADE,
+  item_id UUID REFERENCES public.items(id) ON DELETE SET NULL,
+  quantity INTEGER NOT NULL CHECK (quantity > 0),
+  reason VARCHAR(20) NOT NULL CHECK (reason IN ('damaged', 'defective', 'wrong_item', 'not_needed', 'other')),
+  reason_notes TEXT,
+  received_quantity INTEGER NOT NULL DEFAULT 0 CHECK (received_quantity >= 0 AND received_quantity <= quantity),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(return_id, order_item_id)
+);
+
+-- Create order_return_dispositions table
+CREATE TABLE public.order_return_dispositions (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  return_line_id UUID NOT NULL REFERENCES public.order_return_lines(id) ON DELETE CASCADE,
+  disposition VARCHAR(20) NOT NULL CHECK (disposition IN ('restock', 'scrap', 'repair')),
+  quantity INTEGER NOT NULL CHECK (quantity > 0),
+  stock_movement_id UUID REFERENCES public.stock_movements(id) ON DELETE SET NULL,
+  person_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create order_return_credit_notes table
+CREATE TABLE public.order_return_credit_notes (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  return_id UUID NOT NULL REFERENCES public.order_returns(id) ON DELETE CASCADE,
+  credit_note_number VARCHAR(50) NOT NULL,
+  amount DOUBLE PRECISION NOT NULL CHECK (amount > 0),
+  issued_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, credit_note_number)
+);
+
+-- Restocked returns are referenced from the stock ledger
+ALTER TABLE public.stock_movements DROP CONSTRAINT stock_movements_reference_type_check;
+ALTER TABLE public.stock_movements ADD CONSTRAINT stock_movements_reference_type_check
+  CHECK (reference_type IN ('job', 'order', 'manual', 'return'));
+
+-- Create indexes for order_returns table
+CREATE INDEX idx_order_returns_tenant_id ON public.order_returns(tenant_id);
+CREATE INDEX idx_order_returns_order_id ON public.order_returns(order_id);
+CREATE INDEX idx_order_returns_status ON public.order_returns(status);
+
+-- Create indexes for order_return_lines table
+CREATE INDEX idx_order_return_lines_tenant_id ON public.order_return_lines(tenant_id);
+CREATE INDEX idx_order_return_lines_return_id ON public.order_return_lines(return_id);
+CREATE INDEX idx_order_return_lines_order_item_id ON public.order_return_lines(order_item_id);
+
+-- Create indexes for order_return_dispositions table
+CREATE INDEX idx_order_return_dispositions_tenant_id ON public.order_return_dispositions(tenant_id);
+CREATE INDEX idx_order_return_dispositions_return_line_id ON public.order_return_dispositions(return_line_id);
+
+-- Create indexes for order_return_credit_notes table
+CREATE INDEX idx_order_return_credit_notes_tenant_id ON public.order_return_credit_notes(tenant_id);
+CREATE INDEX idx_order_return_credit_notes_return_id ON public.order_return_credit_notes(return_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_order_returns_updated_at
+  BEFORE UPDATE ON public.order_returns
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.order_returns ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.order_return_lines ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.order_return_dispositions ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.order_return_credit_notes ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "order_returns_tenant_isolation" ON public.order_returns
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "order_return_lines_tenant_isolation" ON public.order_return_lines
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "order_return_dispositions_tenant_isolation" ON public.order_return_dispositions
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "order_return_credit_notes_tenant_isolation" ON public.order_return_credit_notes
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.order_returns TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.order_return_lines TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.order_return_dispositions TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.order_return_credit_notes TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.order_returns IS 'Return merchandise authorizations (RMAs) raised against shipped orders';
+COMMENT ON COLUMN public.order_returns.status IS 'Return status (requested, partially_received, received, closed, cancelled)';
+COMMENT ON COLUMN public.order_returns.quarantine_location IS 'Location holding received stock until it is dispositioned';
+COMMENT ON TABLE public.order_return_lines IS 'Order items being returned with the reason and quantity received so far';
+COMMENT ON COLUMN public.order_return_lines.reason IS 'Return reason (damaged, defective, wrong_item, not_needed, other)';
+COMMENT ON TABLE public.order_return_dispositions IS 'What happened to received return stock; restocks link the stock movement they posted';
+COMMENT ON TABLE public.order_return_credit_notes IS 'Credit notes issued to the customer for a return';
@@ -0,0 +1,87 @@
+-- Migration: Create quote tables
+-- This migration adds customer quotes with lines priced from items (or from a BOM cost rollup
+-- plus margin), their send/accept/reject lifecycle and the sales order an accepted quote was
+-- converted into
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 301_create_orders_tables.sql, and 401_create_item_tables.sql first
+
+-- Create quotes table
+CREATE TABLE public.quotes (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  quote_number VARCHAR(50) NOT NULL,
+  customer_id UUID NOT NULL REFERENCES public.person(id),
+  status VARCHAR(20) NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'sent', 'accepted', 'rejected', 'converted')),
+  valid_until TIMESTAMP WITH TIME ZONE,
+  total_amount DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (total_amount >= 0),
+  notes TEXT,
+  sent_at TIMESTAMP WITH TIME ZONE,
+  responded_at TIMESTAMP WITH TIME ZONE,
+  rejection_reason TEXT,
+  order_id UUID REFERENCES public.orders(id) ON DELETE SET NULL,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, quote_number)
+);
+
+-- Create quote_items table
+CREATE TABLE public.quote_items (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  quote_id UUID NOT NULL REFERENCES public.quotes(id) ON DELETE CASCADE,
+  line_number INTEGER NOT NULL CHECK (line_number > 0),
+  item_id UUID REFERENCES public.items(id) ON DELETE SET NULL,
+  item_name VARCHAR(200) NOT NULL,
+  item_description TEXT,
+  quantity INTEGER NOT NULL CHECK (quantity > 0),
+  unit_price DOUBLE PRECISION NOT NULL CHECK (unit_price >= 0),
+  extended_price DOUBLE PRECISION NOT NULL CHECK (extended_price >= 0),
+  unit_cost DOUBLE PRECISION CHECK (unit_cost >= 0),
+  margin_percent DOUBLE PRECISION,
+  pricing_source VARCHAR(20) NOT NULL CHECK (pricing_source IN ('manual', 'item', 'bom_cost')),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(quote_id, line_number)
+);
+
+-- Create indexes for quotes table
+CREATE INDEX idx_quotes_tenant_id ON public.quotes(tenant_id);
+CREATE INDEX idx_quotes_customer_id ON public.quotes(customer_id);
+CREATE INDEX idx_quotes_status ON public.quotes(status);
+CREATE INDEX idx_quotes_order_id ON public.quotes(order_id);
+
+-- Create indexes for quote_items table
+CREATE INDEX idx_quote_items_tenant_id ON public.quote_items(tenant_id);
+CREATE INDEX idx_quote_items_quote_id ON public.quote_items(quote_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_quotes_updated_at
+  BEFORE UPDATE ON public.quotes
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.quotes ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.quote_items ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "quotes_tenant_isolation" ON public.quotes
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "quote_items_tenant_isolation" ON public.quote_items
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.quotes TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.quote_items TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.quotes IS 'Customer quotes that convert into sales orders once accepted';
+COMMENT ON COLUMN public.quotes.status IS 'Quote status (draft, sent, accepted, rejected, converted)';
+COMMENT ON COLUMN public.quotes.valid_until IS 'Quotes cannot be accepted after this time';
+COMMENT ON COLUMN public.quotes.order_id IS 'Sales order the quote was converted into';
+COMMENT ON TABLE public.quote_items IS 'Quoted lines with their price and how it was derived';
+COMMENT ON COLUMN public.quote_items.pricing_source IS 'How the unit price was set (manual, item price, BOM cost plus margin)';
+COMMENT ON COLUMN public.quote_items.unit_cost IS 'BOM cost rollup the price was derived from';
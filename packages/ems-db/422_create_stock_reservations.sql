@@ -0,0 +1,51 @@
+-- Migration: Create stock reservations table
+-- This migration adds soft allocations of inventory to confirmed orders and jobs. Reserved stock
+-- stays on hand but is no longer available to other orders, and issues against the reserving
+-- order or job consume its reservation
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 401_create_item_tables.sql, and 406_create_stock_movements.sql first
+
+-- Create stock_reservations table
+CREATE TABLE public.stock_reservations (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  inventory_item_id UUID NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+  context VARCHAR(20) NOT NULL CHECK (context IN ('finished_goods', 'store', 'vendor')),
+  quantity INTEGER NOT NULL CHECK (quantity >= 0),
+  reference_type VARCHAR(20) NOT NULL CHECK (reference_type IN ('order', 'job')),
+  reference_id UUID NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'released', 'consumed')),
+  notes TEXT,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  released_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for stock_reservations table
+CREATE INDEX idx_stock_reservations_tenant_id ON public.stock_reservations(tenant_id);
+CREATE INDEX idx_stock_reservations_item_id ON public.stock_reservations(item_id);
+CREATE INDEX idx_stock_reservations_reference ON public.stock_reservations(reference_type, reference_id);
+CREATE INDEX idx_stock_reservations_active ON public.stock_reservations(inventory_item_id) WHERE status = 'active';
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_stock_reservations_updated_at
+  BEFORE UPDATE ON public.stock_reservations
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.stock_reservations ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "stock_reservations_tenant_isolation" ON public.stock_reservations
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.stock_reservations TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.stock_reservations IS 'Inventory soft-allocated to orders and jobs; available = on hand - active reservations';
+COMMENT ON COLUMN public.stock_reservations.quantity IS 'Quantity still reserved; issues against the reference draw it down';
+COMMENT ON COLUMN public.stock_reservations.status IS 'Reservation status (active, released, consumed)';
+COMMENT ON COLUMN public.stock_reservations.reference_id IS 'Order or job the stock is reserved for';
@@ -0,0 +1,17 @@
+-- Migration: Add backorder tracking to order lines
+-- This migration adds an expected date to order lines (receipt date on purchase orders, ship
+-- date on customer and distributor orders) and a flag marking sales lines that are waiting on
+-- supply. Available-to-promise uses the expected dates to place open supply and demand in time
+-- PREREQUISITE: Run 301_create_orders_tables.sql first
+
+-- Add backorder columns to order_items table
+ALTER TABLE public.order_items
+  ADD COLUMN expected_date TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN backordered BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Create index for backorder lookups
+CREATE INDEX idx_order_items_backordered ON public.order_items(item_id) WHERE backordered;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.order_items.expected_date IS 'Expected receipt (purchase orders) or ship date (sales orders); when unset the order date, plus the item lead time on purchase orders';
+COMMENT ON COLUMN public.order_items.backordered IS 'Sales line waiting on supply; expected_date is then the promise date from available-to-promise';
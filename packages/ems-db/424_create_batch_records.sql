@@ -0,0 +1,48 @@
+-- Migration: Create batch records table
+-- This migration adds the production batch record assembled when a manufacturing job completes:
+-- a frozen snapshot of the materials, lots and serials consumed, the machines used and their
+-- firmware at the time, the operators involved and the inspections of the batch. QA jobs gain a
+-- link to the job they inspect
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 201_create_jobs_tables.sql, and 401_create_item_tables.sql first
+
+-- Link QA jobs to the production job they inspect
+ALTER TABLE public.qa_job
+  ADD COLUMN inspected_job_id UUID REFERENCES public.jobs(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_qa_job_inspected_job_id ON public.qa_job(inspected_job_id);
+
+-- Create batch_records table
+CREATE TABLE public.batch_records (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  job_id UUID NOT NULL UNIQUE REFERENCES public.jobs(id),
+  batch_number VARCHAR(50) NOT NULL,
+  item_id UUID REFERENCES public.items(id) ON DELETE SET NULL,
+  quantity INTEGER NOT NULL,
+  completed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  completed_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  record JSONB NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for batch_records table
+CREATE INDEX idx_batch_records_tenant_id ON public.batch_records(tenant_id);
+CREATE INDEX idx_batch_records_item_id ON public.batch_records(item_id);
+CREATE INDEX idx_batch_records_completed_at ON public.batch_records(completed_at);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.batch_records ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "batch_records_tenant_isolation" ON public.batch_records
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Batch records are append-only
+GRANT SELECT, INSERT ON public.batch_records TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.batch_records IS 'Device history record of a completed manufacturing job; never updated once written';
+COMMENT ON COLUMN public.batch_records.job_id IS 'Completed job; jobs with a batch record cannot be deleted';
+COMMENT ON COLUMN public.batch_records.record IS 'Snapshot of materials, machines with firmware, operators and inspections at completion';
+COMMENT ON COLUMN public.qa_job.inspected_job_id IS 'Production job this inspection covers; listed in its batch record';
@@ -0,0 +1,97 @@
+-- Migration: Create non-conformance (NCR) and CAPA tables
+-- This migration adds non-conformance reports raised against a job, a lot of an item or a
+-- machine, classified by severity with an owner and due date, and the corrective and preventive
+-- actions (CAPA) taken on them: containment, root cause analysis, correction and prevention
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 201_create_jobs_tables.sql, 401_create_item_tables.sql, and 403_create_machine_tables.sql first
+
+-- Create non_conformances table
+CREATE TABLE public.non_conformances (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  ncr_number VARCHAR(50) NOT NULL,
+  title VARCHAR(200) NOT NULL,
+  description TEXT,
+  source_type VARCHAR(20) NOT NULL CHECK (source_type IN ('job', 'lot', 'machine')),
+  job_id UUID REFERENCES public.jobs(id) ON DELETE SET NULL,
+  machine_id UUID REFERENCES public.machines(id) ON DELETE SET NULL,
+  item_id UUID REFERENCES public.items(id) ON DELETE SET NULL,
+  lot_number VARCHAR(100),
+  quantity_affected INTEGER CHECK (quantity_affected >= 0),
+  severity VARCHAR(20) NOT NULL CHECK (severity IN ('minor', 'major', 'critical')),
+  status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'closed', 'cancelled')),
+  owner_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  due_date TIMESTAMP WITH TIME ZONE,
+  raised_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  closed_at TIMESTAMP WITH TIME ZONE,
+  closed_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, ncr_number)
+);
+
+-- Create capa_actions table
+CREATE TABLE public.capa_actions (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  ncr_id UUID NOT NULL REFERENCES public.non_conformances(id) ON DELETE CASCADE,
+  action_type VARCHAR(20) NOT NULL CHECK (action_type IN ('containment', 'root_cause', 'corrective', 'preventive')),
+  description TEXT NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'completed', 'cancelled')),
+  owner_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  due_date TIMESTAMP WITH TIME ZONE,
+  outcome TEXT,
+  completed_at TIMESTAMP WITH TIME ZONE,
+  completed_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for non_conformances table
+CREATE INDEX idx_non_conformances_tenant_id ON public.non_conformances(tenant_id);
+CREATE INDEX idx_non_conformances_job_id ON public.non_conformances(job_id);
+CREATE INDEX idx_non_conformances_machine_id ON public.non_conformances(machine_id);
+CREATE INDEX idx_non_conformances_item_id ON public.non_conformances(item_id);
+CREATE INDEX idx_non_conformances_status ON public.non_conformances(status);
+
+-- Create indexes for capa_actions table
+CREATE INDEX idx_capa_actions_tenant_id ON public.capa_actions(tenant_id);
+CREATE INDEX idx_capa_actions_ncr_id ON public.capa_actions(ncr_id);
+CREATE INDEX idx_capa_actions_owner_id ON public.capa_actions(owner_id);
+CREATE INDEX idx_capa_actions_open_due ON public.capa_actions(due_date) WHERE status IN ('open', 'in_progress');
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_non_conformances_updated_at
+  BEFORE UPDATE ON public.non_conformances
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_capa_actions_updated_at
+  BEFORE UPDATE ON public.capa_actions
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.non_conformances ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.capa_actions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "non_conformances_tenant_isolation" ON public.non_conformances
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "capa_actions_tenant_isolation" ON public.capa_actions
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.non_conformances TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.capa_actions TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.non_conformances IS 'Non-conformance reports (NCRs) raised against a job, an item lot or a machine';
+COMMENT ON COLUMN public.non_conformances.source_type IS 'What the non-conformance was found on (job, lot, machine)';
+COMMENT ON COLUMN public.non_conformances.severity IS 'Severity classification (minor, major, critical)';
+COMMENT ON COLUMN public.non_conformances.status IS 'NCR status (open, closed, cancelled)';
+COMMENT ON TABLE public.capa_actions IS 'Containment, root cause, corrective and preventive actions taken on an NCR';
+COMMENT ON COLUMN public.capa_actions.action_type IS 'Action type (containment, root_cause, corrective, preventive)';
+COMMENT ON COLUMN public.capa_actions.status IS 'Action status (open, in_progress, completed, cancelled); open actions past due_date are overdue';
+COMMENT ON COLUMN public.capa_actions.outcome IS 'What was found or done, e.g. the root cause identified';
@@ -0,0 +1,14 @@
+-- Migration: Attribute non-conformances to vendors
+-- This migration lets an NCR name the vendor responsible for the non-conformance, e.g. for a
+-- defective lot of purchased parts, so supplier quality can be scored
+-- PREREQUISITE: Run 101_create_person_tables.sql and 425_create_non_conformances.sql first
+
+-- Add vendor column to non_conformances table
+ALTER TABLE public.non_conformances
+  ADD COLUMN vendor_id UUID REFERENCES public.person(id) ON DELETE SET NULL;
+
+-- Create index for vendor lookups
+CREATE INDEX idx_non_conformances_vendor_id ON public.non_conformances(vendor_id);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.non_conformances.vendor_id IS 'Vendor the non-conformance is attributed to, counted on the vendor scorecard';
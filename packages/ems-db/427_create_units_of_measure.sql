@@ -0,0 +1,125 @@
+-- Migration: Create unit of measure tables
+-- This migration adds per-tenant units of measure with conversion factors, the base unit stock of
+-- an item is counted in and the unit it is bought in (e.g. wire bought by the spool and issued by
+-- the meter), and records the unit quantities were entered in on stock movements, BOM lines and
+-- order lines. Every tenant starts with each, meter, kilogram and liter.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 301_create_orders_tables.sql, 401_create_item_tables.sql, and 406_create_stock_movements.sql first
+
+-- Create units_of_measure table
+CREATE TABLE public.units_of_measure (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  code VARCHAR(20) NOT NULL,
+  name VARCHAR(100) NOT NULL,
+  dimension VARCHAR(20) NOT NULL CHECK (dimension IN ('count', 'length', 'mass', 'volume')),
+  factor DOUBLE PRECISION NOT NULL CHECK (factor > 0),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, code)
+);
+
+-- Create item_units table
+CREATE TABLE public.item_units (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  base_uom_id UUID NOT NULL REFERENCES public.units_of_measure(id),
+  purchase_uom_id UUID REFERENCES public.units_of_measure(id),
+  purchase_factor DOUBLE PRECISION CHECK (purchase_factor > 0),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, item_id)
+);
+
+-- Record the unit quantities were entered in
+ALTER TABLE public.stock_movements
+  ADD COLUMN uom_id UUID REFERENCES public.units_of_measure(id),
+  ADD COLUMN uom_quantity INTEGER;
+
+ALTER TABLE public.item_bom
+  ADD COLUMN uom_id UUID REFERENCES public.units_of_measure(id);
+
+ALTER TABLE public.order_items
+  ADD COLUMN uom_id UUID REFERENCES public.units_of_measure(id),
+  ADD COLUMN base_quantity INTEGER;
+
+UPDATE public.order_items SET base_quantity = quantity;
+
+ALTER TABLE public.order_items ALTER COLUMN base_quantity SET NOT NULL;
+
+-- Create indexes for unit of measure tables
+CREATE INDEX idx_units_of_measure_tenant_id ON public.units_of_measure(tenant_id);
+CREATE INDEX idx_item_units_tenant_id ON public.item_units(tenant_id);
+CREATE INDEX idx_item_units_item_id ON public.item_units(item_id);
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_units_of_measure_updated_at
+  BEFORE UPDATE ON public.units_of_measure
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_item_units_updated_at
+  BEFORE UPDATE ON public.item_units
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.units_of_measure ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.item_units ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "units_of_measure_tenant_isolation" ON public.units_of_measure
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "item_units_tenant_isolation" ON public.item_units
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Create trigger function giving new tenants the standard units
+CREATE OR REPLACE FUNCTION public.seed_units_of_measure()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    INSERT INTO public.units_of_measure (tenant_id, code, name, dimension, factor)
+    VALUES
+        (NEW.id, 'ea', 'Each', 'count', 1),
+        (NEW.id, 'm', 'Meter', 'length', 1),
+        (NEW.id, 'kg', 'Kilogram', 'mass', 1),
+        (NEW.id, 'l', 'Liter', 'volume', 1);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER seed_units_of_measure_trigger
+    AFTER INSERT ON public.tenants
+    FOR EACH ROW
+    EXECUTE FUNCTION public.seed_units_of_measure();
+
+-- Give existing tenants the standard units
+INSERT INTO public.units_of_measure (tenant_id, code, name, dimension, factor)
+SELECT t.id, u.code, u.name, u.dimension, 1
+FROM public.tenants t
+CROSS JOIN (VALUES
+    ('ea', 'Each', 'count'),
+    ('m', 'Meter', 'length'),
+    ('kg', 'Kilogram', 'mass'),
+    ('l', 'Liter', 'volume')
+) AS u(code, name, dimension);
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.units_of_measure TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.item_units TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.units_of_measure IS 'Units quantities can be entered in, per tenant';
+COMMENT ON COLUMN public.units_of_measure.dimension IS 'What the unit measures (count, length, mass, volume)';
+COMMENT ON COLUMN public.units_of_measure.factor IS 'Size of the unit in the reference unit of its dimension: each, meter, kilogram or liter';
+COMMENT ON TABLE public.item_units IS 'Base unit stock of an item is counted in and the unit it is bought in';
+COMMENT ON COLUMN public.item_units.purchase_factor IS 'Base units in one purchase unit, e.g. 305 (m) in a spool; required when the purchase unit measures something else than the base unit';
+COMMENT ON COLUMN public.stock_movements.uom_id IS 'Unit the movement was entered in; quantity is always in the item''s base unit';
+COMMENT ON COLUMN public.stock_movements.uom_quantity IS 'Quantity as entered in uom_id';
+COMMENT ON COLUMN public.item_bom.uom_id IS 'Unit of the component quantity; the component''s base unit when NULL';
+COMMENT ON COLUMN public.order_items.uom_id IS 'Unit the line quantity is in; the item''s base unit when NULL';
+COMMENT ON COLUMN public.order_items.base_quantity IS 'Line quantity in the item''s base unit';
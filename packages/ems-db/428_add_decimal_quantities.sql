@@ -0,0 +1,26 @@
+-- Migration: Store inventory, BOM and stock movement quantities as decimals
+-- This migration changes stock quantities from whole numbers to NUMERIC(18, 6) so items counted
+-- by length, mass or volume can hold fractional amounts, e.g. 0.35 kg of adhesive per unit.
+-- Existing whole quantities convert unchanged; items counted in whole units keep being checked
+-- for whole quantities by the application.
+-- PREREQUISITE: Run 401_create_item_tables.sql, 406_create_stock_movements.sql, 422_create_stock_reservations.sql, and 427_create_units_of_measure.sql first
+
+-- Change inventory and BOM quantities
+ALTER TABLE public.inventory_items
+  ALTER COLUMN quantity TYPE NUMERIC(18, 6);
+
+ALTER TABLE public.item_bom
+  ALTER COLUMN quantity TYPE NUMERIC(18, 6);
+
+-- Change stock ledger and reservation quantities
+ALTER TABLE public.stock_movements
+  ALTER COLUMN quantity TYPE NUMERIC(18, 6),
+  ALTER COLUMN quantity_after TYPE NUMERIC(18, 6),
+  ALTER COLUMN uom_quantity TYPE NUMERIC(18, 6);
+
+ALTER TABLE public.stock_reservations
+  ALTER COLUMN quantity TYPE NUMERIC(18, 6);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.inventory_items.quantity IS 'On-hand quantity in the item''s base unit, up to six decimal places';
+COMMENT ON COLUMN public.item_bom.quantity IS 'Component quantity per assembly in uom_id, up to six decimal places';
@@ -0,0 +1,47 @@
+-- Migration: Create item images table
+-- This migration adds photos of items so pickers can confirm they grabbed the right part. Image
+-- files and their thumbnails live in object storage; rows record where they are kept and their
+-- size. Each item has at most one primary image, shown first in pickers and item lists.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, and 401_create_item_tables.sql first
+
+-- Create item_images table
+CREATE TABLE public.item_images (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  storage_path TEXT NOT NULL,
+  thumbnail_path TEXT NOT NULL,
+  content_type VARCHAR(50) NOT NULL,
+  width INTEGER NOT NULL CHECK (width > 0),
+  height INTEGER NOT NULL CHECK (height > 0),
+  size_bytes BIGINT NOT NULL CHECK (size_bytes > 0),
+  caption TEXT,
+  is_primary BOOLEAN NOT NULL DEFAULT FALSE,
+  uploaded_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for item_images table
+CREATE INDEX idx_item_images_tenant_id ON public.item_images(tenant_id);
+CREATE INDEX idx_item_images_item_id ON public.item_images(item_id);
+CREATE UNIQUE INDEX idx_item_images_primary ON public.item_images(item_id) WHERE is_primary;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_item_images_updated_at
+  BEFORE UPDATE ON public.item_images
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.item_images ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "item_images_tenant_isolation" ON public.item_images
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.item_images IS 'Photos of items with server-generated thumbnails';
+COMMENT ON COLUMN public.item_images.storage_path IS 'Object storage path of the uploaded image';
+COMMENT ON COLUMN public.item_images.thumbnail_path IS 'Object storage path of the resized thumbnail';
+COMMENT ON COLUMN public.item_images.is_primary IS 'Image shown first for the item; at most one per item';
@@ -0,0 +1,14 @@
+-- Migration: Add tenant suspension
+-- This migration records when and why a platform operator suspended a tenant. Suspended tenants
+-- have is_active = false, so their requests are refused until the tenant is reactivated; the
+-- data itself is kept.
+-- PREREQUISITE: Run 001_create_tenants_table.sql first
+
+-- Add suspension columns to tenants
+ALTER TABLE public.tenants
+  ADD COLUMN suspended_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN suspended_reason TEXT;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.tenants.suspended_at IS 'When a platform operator suspended the tenant; NULL while it is not suspended';
+COMMENT ON COLUMN public.tenants.suspended_reason IS 'Reason given for the suspension';
@@ -0,0 +1,61 @@
+-- Migration: Create tenant billing tables
+-- This migration links tenants to their Stripe customer and subscription and records the plan
+-- they are on. Stripe reports payments through webhooks; each delivered event is recorded so a
+-- retried delivery is only applied once. Tenants suspended for non-payment are flagged, so
+-- billing only ever reactivates tenants it suspended itself.
+-- PREREQUISITE: Run 000_supabase_setup.sql and 001_create_tenants_table.sql first
+
+-- Create tenant_billing table
+CREATE TABLE public.tenant_billing (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL UNIQUE REFERENCES public.tenants(id) ON DELETE CASCADE,
+  plan VARCHAR(100) NOT NULL DEFAULT 'free',
+  stripe_customer_id VARCHAR(255) UNIQUE,
+  stripe_subscription_id VARCHAR(255),
+  subscription_status VARCHAR(50),
+  current_period_end TIMESTAMP WITH TIME ZONE,
+  cancel_at_period_end BOOLEAN NOT NULL DEFAULT FALSE,
+  billing_suspended BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create billing_webhook_events table
+CREATE TABLE public.billing_webhook_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  stripe_event_id VARCHAR(255) NOT NULL UNIQUE,
+  event_type VARCHAR(100) NOT NULL,
+  tenant_id UUID REFERENCES public.tenants(id) ON DELETE SET NULL,
+  received_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for billing tables
+CREATE INDEX idx_tenant_billing_subscription ON public.tenant_billing(stripe_subscription_id);
+CREATE INDEX idx_billing_webhook_events_tenant_id ON public.billing_webhook_events(tenant_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_tenant_billing_updated_at
+  BEFORE UPDATE ON public.tenant_billing
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.tenant_billing ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "tenant_billing_tenant_isolation" ON public.tenant_billing
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+ALTER TABLE public.billing_webhook_events ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "billing_webhook_events_tenant_isolation" ON public.billing_webhook_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.tenant_billing IS 'Plan and Stripe subscription of each tenant';
+COMMENT ON COLUMN public.tenant_billing.plan IS 'Plan the tenant is on: the lookup key (or id) of its subscription price, free without one';
+COMMENT ON COLUMN public.tenant_billing.subscription_status IS 'Stripe subscription status, e.g. active, past_due or canceled';
+COMMENT ON COLUMN public.tenant_billing.billing_suspended IS 'The tenant is suspended because payment failed; cleared when billing reactivates it';
+COMMENT ON TABLE public.billing_webhook_events IS 'Stripe webhook events already applied, so retried deliveries are skipped';
@@ -0,0 +1,55 @@
+-- Migration: Create feature flag tables
+-- This migration adds feature flags so risky features can ship dark and be turned on gradually.
+-- A flag is off everywhere until it is enabled; an enabled flag is on for the given percentage
+-- of tenants, and per-tenant overrides turn it on or off for single tenants regardless of the
+-- percentage. Flags themselves are platform-wide; only overrides belong to a tenant.
+-- PREREQUISITE: Run 000_supabase_setup.sql and 001_create_tenants_table.sql first
+
+-- Create feature_flags table
+CREATE TABLE public.feature_flags (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  key VARCHAR(100) NOT NULL UNIQUE CHECK (key ~ '^[a-z][a-z0-9_]*$'),
+  description TEXT,
+  enabled BOOLEAN NOT NULL DEFAULT FALSE,
+  rollout_percentage INTEGER NOT NULL DEFAULT 0 CHECK (rollout_percentage BETWEEN 0 AND 100),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create feature_flag_overrides table
+CREATE TABLE public.feature_flag_overrides (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  flag_id UUID NOT NULL REFERENCES public.feature_flags(id) ON DELETE CASCADE,
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  enabled BOOLEAN NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (flag_id, tenant_id)
+);
+
+-- Create indexes for feature flag tables
+CREATE INDEX idx_feature_flag_overrides_tenant_id ON public.feature_flag_overrides(tenant_id);
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_feature_flags_updated_at
+  BEFORE UPDATE ON public.feature_flags
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_feature_flag_overrides_updated_at
+  BEFORE UPDATE ON public.feature_flag_overrides
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.feature_flag_overrides ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "feature_flag_overrides_tenant_isolation" ON public.feature_flag_overrides
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.feature_flags IS 'Platform-wide feature flags with percentage rollouts';
+COMMENT ON COLUMN public.feature_flags.key IS 'Name code checks the flag by, e.g. mqtt_bridge';
+COMMENT ON COLUMN public.feature_flags.enabled IS 'Kill switch: while false the flag is off for every tenant, overrides included';
+COMMENT ON COLUMN public.feature_flags.rollout_percentage IS 'Share of tenants the enabled flag is on for; tenants keep their place as it grows';
+COMMENT ON TABLE public.feature_flag_overrides IS 'Per-tenant feature flag settings that take precedence over the rollout percentage';
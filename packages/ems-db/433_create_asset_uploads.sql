@@ -0,0 +1,72 @@
+-- Migration: Create asset upload tables
+-- This migration adds resumable uploads for asset files too large to send in one request, such as
+-- firmware images. An upload is started with the file's size, its chunks are sent (and retried)
+-- independently, each checked against its SHA-256, and the server assembles them into the
+-- asset's file once every chunk has arrived. Each received chunk is recorded so an interrupted
+-- upload resumes with the chunks still missing.
+-- PREREQUISITE: Run 000_supabase_setup.sql and the asset migrations first
+
+-- Create asset_uploads table
+CREATE TABLE public.asset_uploads (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  asset_id UUID NOT NULL REFERENCES public.assets(id) ON DELETE CASCADE,
+  file_name VARCHAR(255) NOT NULL,
+  content_type VARCHAR(50) NOT NULL,
+  size_bytes BIGINT NOT NULL CHECK (size_bytes > 0),
+  chunk_size INTEGER NOT NULL CHECK (chunk_size > 0),
+  checksum VARCHAR(64),
+  status VARCHAR(20) NOT NULL DEFAULT 'uploading'
+    CHECK (status IN ('uploading', 'assembling', 'completed', 'failed', 'aborted')),
+  storage_path VARCHAR(500) NOT NULL,
+  error TEXT,
+  created_by_id UUID NOT NULL REFERENCES public.person(id),
+  expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  completed_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create asset_upload_chunks table
+CREATE TABLE public.asset_upload_chunks (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  upload_id UUID NOT NULL REFERENCES public.asset_uploads(id) ON DELETE CASCADE,
+  chunk_index INTEGER NOT NULL CHECK (chunk_index >= 0),
+  size_bytes INTEGER NOT NULL CHECK (size_bytes > 0),
+  checksum VARCHAR(64) NOT NULL,
+  received_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (upload_id, chunk_index)
+);
+
+-- Create indexes for asset upload tables
+CREATE INDEX idx_asset_uploads_tenant_id ON public.asset_uploads(tenant_id);
+CREATE INDEX idx_asset_uploads_asset_id ON public.asset_uploads(asset_id);
+CREATE INDEX idx_asset_upload_chunks_tenant_id ON public.asset_upload_chunks(tenant_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_asset_uploads_updated_at
+  BEFORE UPDATE ON public.asset_uploads
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.asset_uploads ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "asset_uploads_tenant_isolation" ON public.asset_uploads
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+ALTER TABLE public.asset_upload_chunks ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "asset_upload_chunks_tenant_isolation" ON public.asset_upload_chunks
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.asset_uploads IS 'Resumable uploads of asset files, sent in chunks and assembled by the server';
+COMMENT ON COLUMN public.asset_uploads.checksum IS 'SHA-256 (hex) the assembled file must have, when the client gave one';
+COMMENT ON COLUMN public.asset_uploads.status IS 'uploading, assembling, completed, failed (assembly can be retried) or aborted';
+COMMENT ON COLUMN public.asset_uploads.storage_path IS 'Storage path the assembled file is written to';
+COMMENT ON TABLE public.asset_upload_chunks IS 'Chunks of an asset upload received so far, with their SHA-256';
@@ -0,0 +1,18 @@
+-- Migration: Add content scanning to assets
+-- This migration records the antivirus scan of each uploaded asset file. A file is quarantined
+-- (scan_status 'pending') from the moment it is uploaded until the configured scanner passes it;
+-- files found infected, or whose scan could not complete, stay quarantined and cannot be
+-- downloaded. NULL means the file was never scanned, for example because no scanner is configured.
+-- PREREQUISITE: Run 402_create_asset_tables.sql and 433_create_asset_uploads.sql first
+
+-- Add scan columns to assets table
+ALTER TABLE public.assets
+  ADD COLUMN scan_status VARCHAR(20) CHECK (scan_status IN ('pending', 'clean', 'infected', 'failed')),
+  ADD COLUMN scanned_at TIMESTAMP WITH TIME ZONE;
+
+-- Create index for finding quarantined assets
+CREATE INDEX idx_assets_scan_status ON public.assets(scan_status) WHERE scan_status <> 'clean';
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.assets.scan_status IS 'pending, clean, infected or failed; files not clean are quarantined, NULL when never scanned';
+COMMENT ON COLUMN public.assets.scanned_at IS 'When the current file finished scanning';
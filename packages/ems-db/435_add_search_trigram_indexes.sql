@@ -0,0 +1,24 @@
+-- Migration: Add trigram indexes for search suggestions
+-- This migration indexes the fields typeahead suggestions match against with trigram (pg_trgm)
+-- GIN indexes, so substring matches on part numbers, names and order numbers use an index
+-- instead of scanning the table on every keystroke.
+-- PREREQUISITE: Run the item, person, machine and order migrations first
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm SCHEMA public;
+
+-- Create trigram indexes for item suggestions
+CREATE INDEX idx_items_internal_part_number_trgm
+  ON public.items USING gin (internal_part_number public.gin_trgm_ops);
+CREATE INDEX idx_items_mfr_part_number_trgm
+  ON public.items USING gin (mfr_part_number public.gin_trgm_ops);
+CREATE INDEX idx_items_manufacturer_trgm
+  ON public.items USING gin (manufacturer public.gin_trgm_ops);
+
+-- Create trigram indexes for person suggestions
+CREATE INDEX idx_person_name_trgm ON public.person USING gin (name public.gin_trgm_ops);
+CREATE INDEX idx_person_email_trgm ON public.person USING gin (email public.gin_trgm_ops);
+
+-- Create trigram indexes for machine and order suggestions
+CREATE INDEX idx_machines_name_trgm ON public.machines USING gin (name public.gin_trgm_ops);
+CREATE INDEX idx_orders_order_number_trgm
+  ON public.orders USING gin (order_number public.gin_trgm_ops);
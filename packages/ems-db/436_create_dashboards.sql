@@ -0,0 +1,42 @@
+-- Migration: Create dashboards table
+-- This migration stores each user's dashboard configuration: the dashboard's grid layout and its
+-- widgets, each with a position on the grid and the parameters of the data it shows (for
+-- example machines at one site, or open purchase orders). Dashboards belong to the user who
+-- created them; each user can mark one of theirs as the default shown at login.
+-- PREREQUISITE: Run 000_supabase_setup.sql and the person migrations first
+
+-- Create dashboards table
+CREATE TABLE public.dashboards (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  owner_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  is_default BOOLEAN NOT NULL DEFAULT FALSE,
+  layout JSONB NOT NULL DEFAULT '{}',
+  widgets JSONB NOT NULL DEFAULT '[]',
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for dashboards
+CREATE INDEX idx_dashboards_tenant_owner ON public.dashboards(tenant_id, owner_id);
+CREATE UNIQUE INDEX idx_dashboards_owner_default ON public.dashboards(owner_id) WHERE is_default;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_dashboards_updated_at
+  BEFORE UPDATE ON public.dashboards
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.dashboards ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "dashboards_tenant_isolation" ON public.dashboards
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.dashboards IS 'Per-user dashboard configurations: grid layout and widgets';
+COMMENT ON COLUMN public.dashboards.layout IS 'Grid settings, such as the number of columns';
+COMMENT ON COLUMN public.dashboards.widgets IS 'Widgets in display order: id, title, grid position, data kind and its parameters';
+COMMENT ON COLUMN public.dashboards.is_default IS 'Shown to its owner at login; at most one per owner';
@@ -0,0 +1,103 @@
+-- Migration: Create machine alert rules tables
+-- This migration lets tenants define alert rules over machine heartbeats: conditions on the
+-- reported status (optionally held for a number of minutes) and on telemetry channels, and the
+-- actions run when every condition holds (email notification, webhook call, maintenance job).
+-- Heartbeats of tenants with enabled rules are queued for the server alert worker; each rule
+-- keeps at most one open alert per machine until its conditions clear. It also adds the
+-- temperature telemetry channel.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql, 403_create_machine_tables.sql, 404_create_machine_status_history.sql and 409_create_machine_telemetry.sql first
+
+-- Create machine_alert_rules table
+CREATE TABLE public.machine_alert_rules (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  machine_id UUID REFERENCES public.machines(id) ON DELETE CASCADE,
+  conditions JSONB NOT NULL DEFAULT '[]',
+  actions JSONB NOT NULL DEFAULT '[]',
+  is_enabled BOOLEAN NOT NULL DEFAULT true,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create machine_alerts table
+CREATE TABLE public.machine_alerts (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  rule_id UUID NOT NULL REFERENCES public.machine_alert_rules(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  triggered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  resolved_at TIMESTAMP WITH TIME ZONE,
+  observations JSONB NOT NULL DEFAULT '[]',
+  action_results JSONB NOT NULL DEFAULT '[]',
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create machine_heartbeat_events table
+CREATE TABLE public.machine_heartbeat_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  status VARCHAR(20) NOT NULL,
+  payload JSONB,
+  metadata JSONB,
+  received_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  processed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Create indexes for machine_alert_rules table
+CREATE INDEX idx_machine_alert_rules_tenant_id ON public.machine_alert_rules(tenant_id);
+CREATE INDEX idx_machine_alert_rules_machine_id ON public.machine_alert_rules(machine_id);
+
+-- Create indexes for machine_alerts table
+CREATE INDEX idx_machine_alerts_tenant_id_triggered_at ON public.machine_alerts(tenant_id, triggered_at);
+CREATE INDEX idx_machine_alerts_machine_id ON public.machine_alerts(machine_id);
+CREATE UNIQUE INDEX idx_machine_alerts_open ON public.machine_alerts(rule_id, machine_id) WHERE resolved_at IS NULL;
+
+-- Create indexes for machine_heartbeat_events table
+CREATE INDEX idx_machine_heartbeat_events_pending ON public.machine_heartbeat_events(received_at) WHERE processed_at IS NULL;
+CREATE INDEX idx_machine_heartbeat_events_machine_id ON public.machine_heartbeat_events(machine_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_machine_alert_rules_updated_at
+  BEFORE UPDATE ON public.machine_alert_rules
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_alert_rules ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_alerts ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_heartbeat_events ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_alert_rules_tenant_isolation" ON public.machine_alert_rules
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_alerts_tenant_isolation" ON public.machine_alerts
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_heartbeat_events_tenant_isolation" ON public.machine_heartbeat_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add the temperature telemetry channel
+ALTER TABLE public.machine_telemetry DROP CONSTRAINT machine_telemetry_channel_check;
+ALTER TABLE public.machine_telemetry ADD CONSTRAINT machine_telemetry_channel_check
+  CHECK (channel IN ('energy_kwh', 'air_pressure', 'coolant_level', 'temperature'));
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_alert_rules IS 'Tenant-defined alert rules evaluated on each machine heartbeat';
+COMMENT ON COLUMN public.machine_alert_rules.machine_id IS 'Machine the rule watches; every machine of the tenant when empty';
+COMMENT ON COLUMN public.machine_alert_rules.conditions IS 'Conditions that must all hold: status (optionally held for some minutes) or telemetry channel comparisons';
+COMMENT ON COLUMN public.machine_alert_rules.actions IS 'Actions run when the rule fires: notify, webhook or create_maintenance_order';
+COMMENT ON TABLE public.machine_alerts IS 'Alerts raised by machine alert rules; open until the rule''s conditions clear';
+COMMENT ON COLUMN public.machine_alerts.observations IS 'What each condition saw on the heartbeat that raised the alert';
+COMMENT ON COLUMN public.machine_alerts.action_results IS 'Outcome of each action run when the alert was raised';
+COMMENT ON TABLE public.machine_heartbeat_events IS 'Heartbeats queued for alert rule evaluation by the server alert worker';
+COMMENT ON COLUMN public.machine_telemetry.channel IS 'Telemetry channel (energy_kwh, air_pressure, coolant_level, temperature)';
+COMMENT ON COLUMN public.machine_telemetry.value IS 'Reading in the channel unit (kWh, bar, percent, degrees Celsius)';
@@ -0,0 +1,61 @@
+-- Migration: Create machine groups tables
+-- This migration adds machine groups such as production lines: an ordered list of machines, some
+-- of them critical, so a line can be watched as one unit. A line is down while any critical
+-- member is offline, faulted or in maintenance.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 403_create_machine_tables.sql first
+
+-- Create machine_groups table
+CREATE TABLE public.machine_groups (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  description TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create machine_group_members table
+CREATE TABLE public.machine_group_members (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  group_id UUID NOT NULL REFERENCES public.machine_groups(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  position INTEGER NOT NULL CHECK (position >= 0),
+  is_critical BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(group_id, machine_id),
+  UNIQUE(group_id, position)
+);
+
+-- Create indexes for machine_groups table
+CREATE INDEX idx_machine_groups_tenant_id ON public.machine_groups(tenant_id);
+
+-- Create indexes for machine_group_members table
+CREATE INDEX idx_machine_group_members_tenant_id ON public.machine_group_members(tenant_id);
+CREATE INDEX idx_machine_group_members_machine_id ON public.machine_group_members(machine_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_machine_groups_updated_at
+  BEFORE UPDATE ON public.machine_groups
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_groups ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_group_members ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_groups_tenant_isolation" ON public.machine_groups
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_group_members_tenant_isolation" ON public.machine_group_members
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_groups IS 'Groups of machines watched as one unit, such as production lines';
+COMMENT ON TABLE public.machine_group_members IS 'Machines of a group in line order';
+COMMENT ON COLUMN public.machine_group_members.position IS 'Place of the machine in the line, starting at 0';
+COMMENT ON COLUMN public.machine_group_members.is_critical IS 'The line is down while a critical member is offline, faulted or in maintenance';
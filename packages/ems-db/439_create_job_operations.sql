@@ -0,0 +1,54 @@
+-- Migration: Create job operations table
+-- This migration adds routings to jobs: an ordered list of operations making up the job
+-- traveler, each with the machine group it runs on, its standard time, work instructions and the
+-- item documents the operator needs at hand. Operators start and complete operations in order
+-- and record the actual times; job progress is the share of standard time completed.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql, 201_create_jobs_tables.sql, 402_create_asset_tables.sql and 438_create_machine_groups.sql first
+
+-- Create job_operations table
+CREATE TABLE public.job_operations (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  job_id UUID NOT NULL REFERENCES public.jobs(id) ON DELETE CASCADE,
+  sequence INTEGER NOT NULL CHECK (sequence >= 0),
+  name VARCHAR(100) NOT NULL,
+  machine_group_id UUID REFERENCES public.machine_groups(id) ON DELETE SET NULL,
+  standard_minutes DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (standard_minutes >= 0),
+  instructions TEXT,
+  required_asset_ids UUID[] NOT NULL DEFAULT '{}',
+  status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'in_progress', 'completed')),
+  started_at TIMESTAMP WITH TIME ZONE,
+  started_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  completed_at TIMESTAMP WITH TIME ZONE,
+  completed_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  actual_minutes DOUBLE PRECISION CHECK (actual_minutes >= 0),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(job_id, sequence)
+);
+
+-- Create indexes for job_operations table
+CREATE INDEX idx_job_operations_tenant_id ON public.job_operations(tenant_id);
+CREATE INDEX idx_job_operations_machine_group_id ON public.job_operations(machine_group_id);
+CREATE INDEX idx_job_operations_status ON public.job_operations(status);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_job_operations_updated_at
+  BEFORE UPDATE ON public.job_operations
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.job_operations ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "job_operations_tenant_isolation" ON public.job_operations
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.job_operations IS 'Routing of a job: the operations of its traveler in order';
+COMMENT ON COLUMN public.job_operations.sequence IS 'Place of the operation in the routing, starting at 0';
+COMMENT ON COLUMN public.job_operations.standard_minutes IS 'Planned time for the operation; weights job progress';
+COMMENT ON COLUMN public.job_operations.required_asset_ids IS 'Item documents the operator needs for the operation';
+COMMENT ON COLUMN public.job_operations.actual_minutes IS 'Recorded time; defaults to the time between start and completion';
@@ -0,0 +1,76 @@
+-- Migration: Create statistical process control tables
+-- This migration adds item characteristics (a measured dimension or property with its nominal
+-- value and tolerances) and the measurements taken of them, entered by inspectors or read from
+-- the heartbeat payload of the QA machine measuring the characteristic. Measurements are charted
+-- in consecutive subgroups of the characteristic's subgroup size.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql, 201_create_jobs_tables.sql, 401_create_item_tables.sql and 403_create_machine_tables.sql first
+
+-- Create item_characteristics table
+CREATE TABLE public.item_characteristics (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  unit VARCHAR(20),
+  nominal DOUBLE PRECISION NOT NULL,
+  lower_tolerance DOUBLE PRECISION CHECK (lower_tolerance >= 0),
+  upper_tolerance DOUBLE PRECISION CHECK (upper_tolerance >= 0),
+  subgroup_size INTEGER NOT NULL DEFAULT 1 CHECK (subgroup_size BETWEEN 1 AND 10),
+  machine_id UUID REFERENCES public.machines(id) ON DELETE SET NULL,
+  payload_pointer VARCHAR(200),
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(item_id, name),
+  CHECK (lower_tolerance IS NOT NULL OR upper_tolerance IS NOT NULL),
+  CHECK ((machine_id IS NULL) = (payload_pointer IS NULL))
+);
+
+-- Create spc_measurements table
+CREATE TABLE public.spc_measurements (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  characteristic_id UUID NOT NULL REFERENCES public.item_characteristics(id) ON DELETE CASCADE,
+  value DOUBLE PRECISION NOT NULL,
+  source VARCHAR(20) NOT NULL DEFAULT 'manual' CHECK (source IN ('manual', 'heartbeat')),
+  machine_id UUID REFERENCES public.machines(id) ON DELETE SET NULL,
+  job_id UUID REFERENCES public.jobs(id) ON DELETE SET NULL,
+  recorded_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  measured_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for item_characteristics table
+CREATE INDEX idx_item_characteristics_tenant_id ON public.item_characteristics(tenant_id);
+CREATE INDEX idx_item_characteristics_machine_id ON public.item_characteristics(machine_id) WHERE machine_id IS NOT NULL;
+
+-- Create indexes for spc_measurements table
+CREATE INDEX idx_spc_measurements_tenant_id ON public.spc_measurements(tenant_id);
+CREATE INDEX idx_spc_measurements_characteristic_measured_at ON public.spc_measurements(characteristic_id, measured_at);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_item_characteristics_updated_at
+  BEFORE UPDATE ON public.item_characteristics
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.item_characteristics ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.spc_measurements ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "item_characteristics_tenant_isolation" ON public.item_characteristics
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "spc_measurements_tenant_isolation" ON public.spc_measurements
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.item_characteristics IS 'Measured characteristics of an item with their specification';
+COMMENT ON COLUMN public.item_characteristics.lower_tolerance IS 'Allowed deviation below nominal; empty for an upper limit only';
+COMMENT ON COLUMN public.item_characteristics.upper_tolerance IS 'Allowed deviation above nominal; empty for a lower limit only';
+COMMENT ON COLUMN public.item_characteristics.subgroup_size IS 'Consecutive measurements charted together; 1 charts individual values';
+COMMENT ON COLUMN public.item_characteristics.payload_pointer IS 'JSON pointer to the measured value in the heartbeat payload of the QA machine';
+COMMENT ON TABLE public.spc_measurements IS 'Measurements of item characteristics for statistical process control';
@@ -0,0 +1,82 @@
+-- Migration: Create calibration tables
+-- This migration adds measuring instruments (gauges, calipers, torque wrenches) and calibration
+-- records for instruments and machines. Each record carries the date the next calibration is
+-- due and may link the calibration certificate stored as an asset. Equipment whose latest
+-- calibration failed or is past due is out of calibration: measurements taken with it are
+-- refused, and the server calibration check flags the overdue records. SPC measurements record
+-- the instrument used.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql, 402_create_asset_tables.sql, 403_create_machine_tables.sql and 440_create_spc_tables.sql first
+
+-- Create instruments table
+CREATE TABLE public.instruments (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  serial_number VARCHAR(100) NOT NULL,
+  instrument_type VARCHAR(50),
+  location VARCHAR(200),
+  calibration_interval_days INTEGER CHECK (calibration_interval_days > 0),
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, serial_number)
+);
+
+-- Create calibration_records table
+CREATE TABLE public.calibration_records (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID REFERENCES public.machines(id) ON DELETE CASCADE,
+  instrument_id UUID REFERENCES public.instruments(id) ON DELETE CASCADE,
+  calibrated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  due_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  result VARCHAR(20) NOT NULL CHECK (result IN ('pass', 'adjusted', 'fail')),
+  performed_by VARCHAR(200),
+  certificate_number VARCHAR(100),
+  certificate_asset_id UUID REFERENCES public.assets(id) ON DELETE SET NULL,
+  notes TEXT,
+  recorded_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  overdue_flagged_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  CHECK ((machine_id IS NULL) <> (instrument_id IS NULL)),
+  CHECK (due_at > calibrated_at)
+);
+
+-- Record the instrument used for a measurement
+ALTER TABLE public.spc_measurements
+  ADD COLUMN instrument_id UUID REFERENCES public.instruments(id) ON DELETE SET NULL;
+
+-- Create indexes for instruments table
+CREATE INDEX idx_instruments_tenant_id ON public.instruments(tenant_id);
+
+-- Create indexes for calibration_records table
+CREATE INDEX idx_calibration_records_tenant_id ON public.calibration_records(tenant_id);
+CREATE INDEX idx_calibration_records_machine_id ON public.calibration_records(machine_id, calibrated_at DESC) WHERE machine_id IS NOT NULL;
+CREATE INDEX idx_calibration_records_instrument_id ON public.calibration_records(instrument_id, calibrated_at DESC) WHERE instrument_id IS NOT NULL;
+CREATE INDEX idx_calibration_records_unflagged_due_at ON public.calibration_records(due_at) WHERE overdue_flagged_at IS NULL;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_instruments_updated_at
+  BEFORE UPDATE ON public.instruments
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.instruments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.calibration_records ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "instruments_tenant_isolation" ON public.instruments
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "calibration_records_tenant_isolation" ON public.calibration_records
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.instruments IS 'Measuring instruments under calibration control';
+COMMENT ON COLUMN public.instruments.calibration_interval_days IS 'Default time between calibrations';
+COMMENT ON TABLE public.calibration_records IS 'Calibrations of machines and instruments; the latest record decides the calibration status';
+COMMENT ON COLUMN public.calibration_records.certificate_asset_id IS 'Asset holding the calibration certificate';
+COMMENT ON COLUMN public.calibration_records.overdue_flagged_at IS 'When the calibration check found the record past due';
@@ -0,0 +1,70 @@
+-- Migration: Create ingest source tables
+-- This migration adds ingest sources: external systems (a marketplace pushing order updates, a
+-- gateway pushing sensor data) that post events to /api/v1/ingest/{source_token}. Each source
+-- has its own token, stored hashed, a field schema the incoming payload is checked against, and
+-- a template that maps the payload onto the request of the service it feeds. Every event
+-- received is logged with its outcome so integrations can be debugged. Sources live in the
+-- shared database, as the token is resolved before the tenant is known.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create ingest_sources table
+CREATE TABLE public.ingest_sources (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  token_hash VARCHAR(64) NOT NULL UNIQUE,
+  target VARCHAR(50) NOT NULL CHECK (target IN ('order_update', 'machine_heartbeat')),
+  field_schema JSONB NOT NULL DEFAULT '{}'::jsonb,
+  template JSONB NOT NULL,
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  last_received_at TIMESTAMP WITH TIME ZONE,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create ingest_events table
+CREATE TABLE public.ingest_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  source_id UUID NOT NULL REFERENCES public.ingest_sources(id) ON DELETE CASCADE,
+  status VARCHAR(20) NOT NULL CHECK (status IN ('processed', 'rejected', 'failed')),
+  error TEXT,
+  payload JSONB NOT NULL,
+  entity_id UUID,
+  received_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for ingest tables
+CREATE INDEX idx_ingest_sources_tenant_id ON public.ingest_sources(tenant_id);
+CREATE INDEX idx_ingest_events_source_id ON public.ingest_events(source_id, received_at DESC);
+CREATE INDEX idx_ingest_events_tenant_id ON public.ingest_events(tenant_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_ingest_sources_updated_at
+  BEFORE UPDATE ON public.ingest_sources
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.ingest_sources ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.ingest_events ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "ingest_sources_tenant_isolation" ON public.ingest_sources
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "ingest_events_tenant_isolation" ON public.ingest_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.ingest_sources IS 'External systems pushing events to the generic ingest endpoint';
+COMMENT ON COLUMN public.ingest_sources.token_hash IS 'SHA-256 of the source token sent in the ingest URL';
+COMMENT ON COLUMN public.ingest_sources.target IS 'Service the events are routed to: order_update or machine_heartbeat';
+COMMENT ON COLUMN public.ingest_sources.field_schema IS 'JSON pointers the payload must (or, with a trailing ?, may) carry, mapped to their type';
+COMMENT ON COLUMN public.ingest_sources.template IS 'Request for the target service; "{{/pointer}}" placeholders are filled from the payload';
+COMMENT ON TABLE public.ingest_events IS 'Events received from ingest sources and their outcome';
+COMMENT ON COLUMN public.ingest_events.entity_id IS 'Order or machine the event was applied to';
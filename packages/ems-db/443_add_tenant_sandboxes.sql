@@ -0,0 +1,24 @@
+-- Migration: Add tenant sandboxes
+-- This migration lets a tenant be cloned into a sandbox tenant where users can trial workflows
+-- without touching live data. A sandbox records the tenant it was cloned from and when it
+-- expires; the server sandbox cleanup deletes expired sandboxes with all their data. Sandboxes
+-- always live in the shared database.
+-- PREREQUISITE: Run 001_create_tenants_table.sql first
+
+-- Add sandbox columns to tenants
+ALTER TABLE public.tenants
+  ADD COLUMN is_sandbox BOOLEAN NOT NULL DEFAULT false,
+  ADD COLUMN sandbox_of UUID REFERENCES public.tenants(id) ON DELETE CASCADE,
+  ADD COLUMN sandbox_expires_at TIMESTAMP WITH TIME ZONE,
+  ADD CONSTRAINT tenants_sandbox_check CHECK (
+    is_sandbox = (sandbox_of IS NOT NULL) AND is_sandbox = (sandbox_expires_at IS NOT NULL)
+  );
+
+-- Create index for the sandbox cleanup
+CREATE INDEX idx_tenants_sandbox_expires_at ON public.tenants(sandbox_expires_at) WHERE is_sandbox;
+CREATE INDEX idx_tenants_sandbox_of ON public.tenants(sandbox_of) WHERE is_sandbox;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.tenants.is_sandbox IS 'Non-production tenant cloned for trying out changes';
+COMMENT ON COLUMN public.tenants.sandbox_of IS 'Tenant the sandbox was cloned from';
+COMMENT ON COLUMN public.tenants.sandbox_expires_at IS 'When the sandbox cleanup deletes the sandbox and its data';
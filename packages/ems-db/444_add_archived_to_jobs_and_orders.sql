@@ -0,0 +1,25 @@
+-- Migration: Add archiving of jobs and orders
+-- This migration adds a soft-archive flag to jobs and orders. Archived records are left out of
+-- list endpoints unless asked for. The server archive task archives jobs and orders closed
+-- longer than the tenant's `archive_after_days` setting; records unarchived by hand keep their
+-- archived_at and are not archived automatically again.
+-- PREREQUISITE: Run 201_create_jobs_tables.sql and 301_create_orders_tables.sql first
+
+-- Add archive columns to jobs and orders
+ALTER TABLE public.jobs
+  ADD COLUMN archived BOOLEAN NOT NULL DEFAULT false,
+  ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE public.orders
+  ADD COLUMN archived BOOLEAN NOT NULL DEFAULT false,
+  ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+-- Create indexes for the default lists and the archive task
+CREATE INDEX idx_jobs_tenant_archived ON public.jobs(tenant_id, archived);
+CREATE INDEX idx_orders_tenant_archived ON public.orders(tenant_id, archived);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.jobs.archived IS 'Left out of job lists unless include_archived is set';
+COMMENT ON COLUMN public.jobs.archived_at IS 'When the job was last archived';
+COMMENT ON COLUMN public.orders.archived IS 'Left out of order lists unless include_archived is set';
+COMMENT ON COLUMN public.orders.archived_at IS 'When the order was last archived';
@@ -0,0 +1,52 @@
+-- Migration: Create recalculation tasks table
+-- This migration queues tenant-wide recalculations (inventory valuation, order totals, demand
+-- forecasts) run by the server recalculation worker after costing or BOM changes. Tasks report
+-- their progress and can be cancelled while queued or running.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create recalculation_tasks table
+CREATE TABLE public.recalculation_tasks (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  kind VARCHAR(30) NOT NULL CHECK (kind IN ('inventory_valuation', 'order_totals', 'forecast_refresh')),
+  status VARCHAR(20) NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'completed', 'failed', 'cancelled')),
+  total INTEGER,
+  processed INTEGER NOT NULL DEFAULT 0 CHECK (processed >= 0),
+  updated_records INTEGER NOT NULL DEFAULT 0 CHECK (updated_records >= 0),
+  cancel_requested BOOLEAN NOT NULL DEFAULT false,
+  error TEXT,
+  requested_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  started_at TIMESTAMP WITH TIME ZONE,
+  finished_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for recalculation_tasks table
+CREATE INDEX idx_recalculation_tasks_tenant_id ON public.recalculation_tasks(tenant_id, created_at DESC);
+CREATE INDEX idx_recalculation_tasks_pending ON public.recalculation_tasks(created_at) WHERE status IN ('queued', 'running');
+-- One pending task per kind and tenant
+CREATE UNIQUE INDEX idx_recalculation_tasks_tenant_kind_pending ON public.recalculation_tasks(tenant_id, kind) WHERE status IN ('queued', 'running');
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_recalculation_tasks_updated_at
+  BEFORE UPDATE ON public.recalculation_tasks
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.recalculation_tasks ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "recalculation_tasks_tenant_isolation" ON public.recalculation_tasks
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.recalculation_tasks TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.recalculation_tasks IS 'Queued tenant-wide recalculations and their progress';
+COMMENT ON COLUMN public.recalculation_tasks.total IS 'Records to go through; set once the task starts';
+COMMENT ON COLUMN public.recalculation_tasks.processed IS 'Records gone through so far; a restarted task resumes here';
+COMMENT ON COLUMN public.recalculation_tasks.updated_records IS 'Records whose values changed';
+COMMENT ON COLUMN public.recalculation_tasks.cancel_requested IS 'Set to stop a running task after its current batch';
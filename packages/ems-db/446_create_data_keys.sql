@@ -0,0 +1,54 @@
+-- Migration: Create data keys table
+-- This migration adds the data keys sensitive columns are encrypted with (AES-256-GCM) in the
+-- server. Every tenant gets its own keys, and records shared between tenants (persons) use the
+-- platform keys, which have no tenant. Keys are stored wrapped by the master key from the
+-- server config, never in plain. Rotating a scope's key adds a new version and retires the old
+-- one; retired keys are kept so values written before the rotation can still be read.
+-- Encrypted values no longer fit person.phone, so it becomes TEXT.
+-- PREREQUISITE: Run 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create data_keys table
+CREATE TABLE public.data_keys (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID REFERENCES public.tenants(id) ON DELETE CASCADE,
+  version INTEGER NOT NULL CHECK (version > 0),
+  wrapped_key TEXT NOT NULL,
+  master_key_id VARCHAR(100) NOT NULL,
+  retired_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for data_keys table
+CREATE UNIQUE INDEX idx_data_keys_scope_version ON public.data_keys(COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'::uuid), version);
+-- One active key per tenant, and one for the platform
+CREATE UNIQUE INDEX idx_data_keys_scope_active ON public.data_keys(COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'::uuid)) WHERE retired_at IS NULL;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_data_keys_updated_at
+  BEFORE UPDATE ON public.data_keys
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation. Platform keys are only visible
+-- to the server.
+ALTER TABLE public.data_keys ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "data_keys_tenant_isolation" ON public.data_keys
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.data_keys TO service_role;
+
+-- Encrypted phone numbers are longer than the plain ones
+ALTER TABLE public.person ALTER COLUMN phone TYPE TEXT;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.data_keys IS 'Per-tenant and platform data keys for encrypting sensitive columns, wrapped by the master key';
+COMMENT ON COLUMN public.data_keys.tenant_id IS 'Tenant the key belongs to; NULL for the platform key used for persons';
+COMMENT ON COLUMN public.data_keys.version IS 'Referenced by encrypted values (enc:v1:<version>:...) so they find their key after a rotation';
+COMMENT ON COLUMN public.data_keys.wrapped_key IS 'Data key encrypted with the master key, base64';
+COMMENT ON COLUMN public.data_keys.master_key_id IS 'Master key the data key is wrapped with; rewrapping moves keys to the current master key';
+COMMENT ON COLUMN public.data_keys.retired_at IS 'Set when a newer version replaced the key; retired keys only decrypt';
+COMMENT ON COLUMN public.person.phone IS 'Encrypted with the platform data key';
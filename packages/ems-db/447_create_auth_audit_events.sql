@@ -0,0 +1,39 @@
+-- Migration: Create auth audit events table
+-- This migration records security-relevant sign-in events for each tenant, starting with
+-- refresh tokens used from a client (user agent and network) other than the one they were
+-- issued to. Events are written by the server and read by tenant admins.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create auth_audit_events table
+CREATE TABLE public.auth_audit_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  event VARCHAR(40) NOT NULL CHECK (event IN ('refresh_fingerprint_mismatch')),
+  outcome VARCHAR(20) NOT NULL CHECK (outcome IN ('allowed', 'rejected')),
+  ip_prefix VARCHAR(64),
+  user_agent TEXT,
+  details JSONB,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for auth_audit_events table
+CREATE INDEX idx_auth_audit_events_tenant_id ON public.auth_audit_events(tenant_id, created_at DESC);
+CREATE INDEX idx_auth_audit_events_person_id ON public.auth_audit_events(person_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.auth_audit_events ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "auth_audit_events_tenant_isolation" ON public.auth_audit_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions (events are append-only)
+GRANT SELECT, INSERT ON public.auth_audit_events TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.auth_audit_events IS 'Security-relevant sign-in events, e.g. refresh tokens used from another client';
+COMMENT ON COLUMN public.auth_audit_events.outcome IS 'Whether the request was let through or refused';
+COMMENT ON COLUMN public.auth_audit_events.ip_prefix IS 'Network the request came from: the /24 of an IPv4 address or the /48 of an IPv6 address';
+COMMENT ON COLUMN public.auth_audit_events.details IS 'Event specific data, e.g. which parts of the client fingerprint changed';
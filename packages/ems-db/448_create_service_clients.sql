@@ -0,0 +1,50 @@
+-- Migration: Create service clients table
+-- This migration registers internal services (analytics, MES adapters) that call the EMS API
+-- with machine tokens from the client credentials grant at /api/v1/auth/token. Each client has
+-- a public client id, a secret stored hashed, and the scopes its tokens may carry. Clients act
+-- on behalf of the admin who registered them and are removed with them. They live in the
+-- shared database, as the client id is resolved before the tenant is known.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create service_clients table
+CREATE TABLE public.service_clients (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  client_id VARCHAR(64) NOT NULL UNIQUE,
+  secret_hash VARCHAR(255) NOT NULL,
+  scopes TEXT[] NOT NULL DEFAULT '{}',
+  owner_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  is_active BOOLEAN NOT NULL DEFAULT true,
+  last_used_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, name)
+);
+
+-- Create indexes for service_clients table
+CREATE INDEX idx_service_clients_tenant_id ON public.service_clients(tenant_id);
+CREATE INDEX idx_service_clients_owner_id ON public.service_clients(owner_id);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_service_clients_updated_at
+  BEFORE UPDATE ON public.service_clients
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.service_clients ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "service_clients_tenant_isolation" ON public.service_clients
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.service_clients TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.service_clients IS 'Internal services calling the API with client credentials';
+COMMENT ON COLUMN public.service_clients.client_id IS 'Public identifier sent with the client secret to /api/v1/auth/token';
+COMMENT ON COLUMN public.service_clients.secret_hash IS 'Argon2 hash of the client secret';
+COMMENT ON COLUMN public.service_clients.scopes IS 'Scopes tokens may carry: <area>:read, <area>:write or admin';
+COMMENT ON COLUMN public.service_clients.owner_id IS 'Admin the client acts on behalf of; its writes are attributed to them';
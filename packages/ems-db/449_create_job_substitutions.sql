@@ -0,0 +1,41 @@
+-- Migration: Create job substitutions table
+-- This migration records substitute components consumed by jobs. When a BOM component is short
+-- while kitting a job, the approved substitutes listed on the BOM line are proposed with their
+-- available stock; issuing one records which substitute stood in for which component, with the
+-- stock movement that issued it, so batch records can trace the part actually used.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql, 201_create_jobs_tables.sql, 401_create_item_tables.sql and 406_create_stock_movements.sql first
+
+-- Create job_substitutions table
+CREATE TABLE public.job_substitutions (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  job_id UUID NOT NULL REFERENCES public.jobs(id) ON DELETE CASCADE,
+  bom_id UUID REFERENCES public.item_bom(id) ON DELETE SET NULL,
+  component_item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  substitute_item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  quantity NUMERIC(18, 6) NOT NULL CHECK (quantity > 0),
+  stock_movement_id UUID REFERENCES public.stock_movements(id) ON DELETE SET NULL,
+  person_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  CHECK (component_item_id <> substitute_item_id)
+);
+
+-- Create indexes for job_substitutions table
+CREATE INDEX idx_job_substitutions_tenant_id ON public.job_substitutions(tenant_id);
+CREATE INDEX idx_job_substitutions_job_id ON public.job_substitutions(job_id);
+CREATE INDEX idx_job_substitutions_substitute_item_id ON public.job_substitutions(substitute_item_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.job_substitutions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "job_substitutions_tenant_isolation" ON public.job_substitutions
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.job_substitutions IS 'Substitute components consumed by jobs in place of BOM components';
+COMMENT ON COLUMN public.job_substitutions.bom_id IS 'BOM line whose approved substitute was used';
+COMMENT ON COLUMN public.job_substitutions.quantity IS 'Quantity of the substitute issued, in its base unit';
+COMMENT ON COLUMN public.job_substitutions.stock_movement_id IS 'Issue of the substitute against the job';
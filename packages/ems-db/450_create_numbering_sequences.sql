@@ -0,0 +1,52 @@
+-- Migration: Create numbering sequences table
+-- This migration adds per-tenant numbering sequences for document numbers. Each sequence formats
+-- its counter with a prefix template (where {YYYY} and {YY} stand for the current year) and a
+-- zero padding, e.g. 'PO-{YYYY}-' with padding 4 gives PO-2025-0042, and can restart at 1 every
+-- year. Numbers are drawn under a row lock in the same transaction as the document they number,
+-- skipping numbers already in use, so a rolled back document leaves no gap.
+-- Order numbers become unique per tenant rather than across tenants, like job and quote numbers.
+-- PREREQUISITE: Run 000_supabase_setup.sql and 301_create_orders_tables.sql first
+
+-- Create numbering_sequences table
+CREATE TABLE public.numbering_sequences (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  entity VARCHAR(30) NOT NULL CHECK (entity IN ('purchase_order', 'customer_order', 'distributor_order', 'job', 'item', 'quote', 'return')),
+  prefix VARCHAR(30) NOT NULL DEFAULT '',
+  padding INTEGER NOT NULL DEFAULT 4 CHECK (padding BETWEEN 1 AND 12),
+  yearly_reset BOOLEAN NOT NULL DEFAULT FALSE,
+  next_value BIGINT NOT NULL DEFAULT 1 CHECK (next_value > 0),
+  period_year INTEGER,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, entity)
+);
+
+-- Create indexes for numbering_sequences table
+CREATE INDEX idx_numbering_sequences_tenant_id ON public.numbering_sequences(tenant_id);
+
+-- Order numbers are unique within a tenant
+ALTER TABLE public.orders DROP CONSTRAINT orders_order_number_key;
+ALTER TABLE public.orders ADD CONSTRAINT orders_tenant_order_number_key UNIQUE (tenant_id, order_number);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.numbering_sequences ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "numbering_sequences_tenant_isolation" ON public.numbering_sequences
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add updated_at trigger
+CREATE TRIGGER update_numbering_sequences_updated_at
+    BEFORE UPDATE ON public.numbering_sequences
+    FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add comments for documentation
+COMMENT ON TABLE public.numbering_sequences IS 'Per-tenant sequences generating document numbers';
+COMMENT ON COLUMN public.numbering_sequences.entity IS 'Kind of document numbered: purchase_order, customer_order, distributor_order, job, item, quote or return';
+COMMENT ON COLUMN public.numbering_sequences.prefix IS 'Text before the counter; {YYYY} and {YY} are replaced by the year';
+COMMENT ON COLUMN public.numbering_sequences.padding IS 'Minimum digits of the counter, zero padded';
+COMMENT ON COLUMN public.numbering_sequences.yearly_reset IS 'Whether the counter restarts at 1 each calendar year';
+COMMENT ON COLUMN public.numbering_sequences.next_value IS 'Counter value the next number is drawn from';
+COMMENT ON COLUMN public.numbering_sequences.period_year IS 'Year the last number was drawn in';
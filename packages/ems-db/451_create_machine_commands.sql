@@ -0,0 +1,81 @@
+-- Migration: Create machine commands and machine configs tables
+-- This migration lets the server drive machines through their heartbeats. Commands queued for a
+-- machine are returned with each heartbeat response until the machine reports their result or
+-- they expire; the machine's desired configuration is returned whenever the version it reports
+-- is not the current one, along with the interval it should next report in (the machine's own,
+-- else the server default, lengthened while the server is under load).
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql and 403_create_machine_tables.sql first
+
+-- Create machine_commands table
+CREATE TABLE public.machine_commands (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  command VARCHAR(50) NOT NULL,
+  parameters JSONB,
+  status VARCHAR(20) NOT NULL DEFAULT 'pending'
+    CHECK (status IN ('pending', 'delivered', 'succeeded', 'failed', 'cancelled', 'expired')),
+  expires_at TIMESTAMP WITH TIME ZONE,
+  delivered_at TIMESTAMP WITH TIME ZONE,
+  delivery_count INTEGER NOT NULL DEFAULT 0,
+  completed_at TIMESTAMP WITH TIME ZONE,
+  result JSONB,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create machine_configs table
+CREATE TABLE public.machine_configs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  config JSONB NOT NULL DEFAULT '{}',
+  version INTEGER NOT NULL DEFAULT 1 CHECK (version > 0),
+  reporting_interval_seconds INTEGER CHECK (reporting_interval_seconds BETWEEN 5 AND 86400),
+  updated_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(machine_id)
+);
+
+-- Create indexes for machine_commands table
+CREATE INDEX idx_machine_commands_tenant_id ON public.machine_commands(tenant_id);
+CREATE INDEX idx_machine_commands_machine_id_created_at ON public.machine_commands(machine_id, created_at);
+CREATE INDEX idx_machine_commands_open ON public.machine_commands(machine_id) WHERE status IN ('pending', 'delivered');
+
+-- Create indexes for machine_configs table
+CREATE INDEX idx_machine_configs_tenant_id ON public.machine_configs(tenant_id);
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_machine_commands_updated_at
+  BEFORE UPDATE ON public.machine_commands
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_machine_configs_updated_at
+  BEFORE UPDATE ON public.machine_configs
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_commands ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_configs ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_commands_tenant_isolation" ON public.machine_commands
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_configs_tenant_isolation" ON public.machine_configs
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_commands IS 'Commands queued for machines, delivered in heartbeat responses';
+COMMENT ON COLUMN public.machine_commands.command IS 'Command name the machine firmware understands, e.g. reboot or calibrate';
+COMMENT ON COLUMN public.machine_commands.status IS 'pending until first delivered; delivered commands are sent again until the machine reports a result';
+COMMENT ON COLUMN public.machine_commands.delivery_count IS 'Heartbeat responses the command was sent in';
+COMMENT ON COLUMN public.machine_commands.result IS 'Result the machine reported for the command';
+COMMENT ON TABLE public.machine_configs IS 'Desired configuration of machines, delivered in heartbeat responses';
+COMMENT ON COLUMN public.machine_configs.version IS 'Raised on every change; machines report the version they run in heartbeats';
+COMMENT ON COLUMN public.machine_configs.reporting_interval_seconds IS 'Seconds between heartbeats; the server default when NULL';
@@ -0,0 +1,53 @@
+-- Migration: Create item datasheets table
+-- This migration records snapshots of item datasheets. The server fetches an item's datasheet
+-- URL on request, stores the file as a `datasheet` asset of the item and serves it from there,
+-- so browsers avoid cross-origin fetches and links that have since died. Each item keeps one
+-- snapshot with the date it was fetched, so snapshots past their maximum age can be refreshed,
+-- and the last failed attempt, so a dead link can be told apart from a stale copy.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 401_create_item_tables.sql and 402_create_asset_tables.sql first
+
+-- Create item_datasheets table
+CREATE TABLE public.item_datasheets (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  source_url VARCHAR(500) NOT NULL,
+  asset_id UUID REFERENCES public.assets(id) ON DELETE SET NULL,
+  content_type VARCHAR(100),
+  size_bytes BIGINT,
+  checksum VARCHAR(64),
+  fetched_at TIMESTAMP WITH TIME ZONE,
+  last_attempt_at TIMESTAMP WITH TIME ZONE,
+  last_error TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(item_id)
+);
+
+-- Create indexes for item_datasheets table
+CREATE INDEX idx_item_datasheets_tenant_id_fetched_at ON public.item_datasheets(tenant_id, fetched_at);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_item_datasheets_updated_at
+  BEFORE UPDATE ON public.item_datasheets
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.item_datasheets ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "item_datasheets_tenant_isolation" ON public.item_datasheets
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Asset type datasheet snapshots are stored under
+INSERT INTO public.asset_types (name, description) VALUES
+('datasheet', 'Manufacturer datasheets fetched from item datasheet links')
+ON CONFLICT (name) DO NOTHING;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.item_datasheets IS 'Snapshots of item datasheets fetched from their datasheet URL';
+COMMENT ON COLUMN public.item_datasheets.source_url IS 'Datasheet URL the snapshot was fetched from; a changed item URL is fetched again';
+COMMENT ON COLUMN public.item_datasheets.asset_id IS 'Datasheet asset of the item holding the snapshot file';
+COMMENT ON COLUMN public.item_datasheets.fetched_at IS 'When the snapshot was last fetched successfully';
+COMMENT ON COLUMN public.item_datasheets.last_error IS 'Why the last fetch failed, cleared by a successful one';
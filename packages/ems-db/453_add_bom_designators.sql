@@ -0,0 +1,13 @@
+-- Migration: Add reference designators to BOM lines
+-- This migration records the reference designators (R1, C12, U3) a BOM line places its
+-- component at. BOMs exported from EDA tools such as Altium and KiCad list a line per component
+-- with its designators; keeping them lets a BOM be imported, edited and exported again without
+-- losing placement.
+-- PREREQUISITE: Run 401_create_item_tables.sql first
+
+-- Add designators column to item_bom
+ALTER TABLE public.item_bom
+  ADD COLUMN designators TEXT[] DEFAULT ARRAY[]::TEXT[];
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.item_bom.designators IS 'Reference designators the component is placed at, in file order';
@@ -0,0 +1,54 @@
+-- Migration: Create machine credentials table
+-- This migration records the credentials handed to machines in provisioning bundles. Each
+-- bundle carries an API key that only reaches the machine's own heartbeat endpoint and, for
+-- MQTT machines, broker credentials; both are returned once and stored hashed. Provisioning a
+-- machine again revokes its previous bundle, so a machine has at most one active credential.
+-- Credentials live in the shared database, as the API key is resolved by the auth middleware
+-- before any tenant database is used; machine_id has no foreign key for the same reason.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create machine_credentials table
+CREATE TABLE public.machine_credentials (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL,
+  key_prefix VARCHAR(16) NOT NULL,
+  key_hash VARCHAR(64) NOT NULL UNIQUE,
+  broker_username VARCHAR(100),
+  broker_password_hash VARCHAR(64),
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  last_used_at TIMESTAMP WITH TIME ZONE,
+  revoked_at TIMESTAMP WITH TIME ZONE,
+  revoked_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for machine_credentials table
+CREATE INDEX idx_machine_credentials_tenant_machine ON public.machine_credentials(tenant_id, machine_id);
+CREATE UNIQUE INDEX idx_machine_credentials_active_machine
+  ON public.machine_credentials(machine_id) WHERE revoked_at IS NULL;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_machine_credentials_updated_at
+  BEFORE UPDATE ON public.machine_credentials
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_credentials ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_credentials_tenant_isolation" ON public.machine_credentials
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_credentials TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_credentials IS 'Credentials issued to machines in provisioning bundles';
+COMMENT ON COLUMN public.machine_credentials.key_prefix IS 'Start of the API key, to tell keys apart without the key itself';
+COMMENT ON COLUMN public.machine_credentials.key_hash IS 'SHA-256 hash of the machine API key';
+COMMENT ON COLUMN public.machine_credentials.broker_username IS 'MQTT broker user of the machine; NULL for machines not on MQTT';
+COMMENT ON COLUMN public.machine_credentials.broker_password_hash IS 'SHA-256 hash of the broker password, for the broker''s auth backend';
+COMMENT ON COLUMN public.machine_credentials.revoked_at IS 'When the credential stopped working; set by revocation or by provisioning again';
@@ -0,0 +1,44 @@
+-- Migration: Create API access logs table
+-- This migration records every API request made in a tenant: the endpoint, who made it, where
+-- from, how it was answered and how long it took, so tenant admins can answer questions like
+-- who deleted a machine. Rows are written by the server in batches and pruned once older than
+-- the tenant's `access_log_retention_days` setting. The table lives in the shared database.
+-- PREREQUISITE: Run 000_supabase_setup.sql and 001_create_tenants_table.sql first
+
+-- Create api_access_logs table
+CREATE TABLE public.api_access_logs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  method VARCHAR(10) NOT NULL,
+  path TEXT NOT NULL,
+  endpoint TEXT NOT NULL,
+  status INTEGER NOT NULL,
+  latency_ms INTEGER NOT NULL,
+  actor_type VARCHAR(20) CHECK (actor_type IN ('person', 'service_client', 'machine')),
+  actor_id UUID,
+  ip VARCHAR(64),
+  user_agent TEXT,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for api_access_logs table
+CREATE INDEX idx_api_access_logs_tenant_id ON public.api_access_logs(tenant_id, created_at DESC);
+CREATE INDEX idx_api_access_logs_actor_id ON public.api_access_logs(tenant_id, actor_id, created_at DESC);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.api_access_logs ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "api_access_logs_tenant_isolation" ON public.api_access_logs
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions (logs are append-only; the retention worker deletes old rows)
+GRANT SELECT, INSERT, DELETE ON public.api_access_logs TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.api_access_logs IS 'API requests made in each tenant, kept for the tenant''s access log retention';
+COMMENT ON COLUMN public.api_access_logs.path IS 'Request path, including the IDs of the records it touched';
+COMMENT ON COLUMN public.api_access_logs.endpoint IS 'Method and path with IDs replaced by :id, for grouping requests by endpoint';
+COMMENT ON COLUMN public.api_access_logs.actor_type IS 'Who made the request: a person, a service client or a provisioned machine; NULL when it was not authenticated';
+COMMENT ON COLUMN public.api_access_logs.ip IS 'Client address: the last X-Forwarded-For entry or the peer address';
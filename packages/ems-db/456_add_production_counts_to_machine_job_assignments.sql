@@ -0,0 +1,22 @@
+-- Migration: Add production counts to machine job assignments
+-- This migration lets machines report the good and scrap parts they make against their active
+-- job assignment. Counts read from heartbeat payloads are added to the assignment, good parts
+-- are received into finished-goods stock, and the assignment completes once its target quantity
+-- is made. Machines reporting running totals rather than increments have the last totals kept,
+-- so each heartbeat only adds what was made since the one before.
+-- PREREQUISITE: Run 403_create_machine_tables.sql first
+
+-- Add production count columns to machine_job_assignments
+ALTER TABLE public.machine_job_assignments
+  ADD COLUMN target_quantity INTEGER CHECK (target_quantity > 0),
+  ADD COLUMN produced_quantity INTEGER NOT NULL DEFAULT 0 CHECK (produced_quantity >= 0),
+  ADD COLUMN scrap_quantity INTEGER NOT NULL DEFAULT 0 CHECK (scrap_quantity >= 0),
+  ADD COLUMN last_produced_total BIGINT,
+  ADD COLUMN last_scrap_total BIGINT;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.machine_job_assignments.target_quantity IS 'Good parts that complete the assignment; NULL uses the job quantity';
+COMMENT ON COLUMN public.machine_job_assignments.produced_quantity IS 'Good parts the machine reported making on the assignment';
+COMMENT ON COLUMN public.machine_job_assignments.scrap_quantity IS 'Scrapped parts the machine reported on the assignment';
+COMMENT ON COLUMN public.machine_job_assignments.last_produced_total IS 'Last running total of good parts reported by a machine counting in totals';
+COMMENT ON COLUMN public.machine_job_assignments.last_scrap_total IS 'Last running total of scrapped parts reported by a machine counting in totals';
@@ -0,0 +1,217 @@
+-- Migration: Create watches, entity comments and notifications tables
+-- This migration lets people watch specific machines, orders and items and be notified of what
+-- happens to them: a machine or order changing status, an item's stock falling to its reorder
+-- point, or someone commenting on it. Notifications are written by triggers, so every change
+-- is covered whichever code path made it, and only go to the record's watchers. A person is not
+-- notified of their own comments.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql,
+-- 301_create_orders_tables.sql, 401_create_item_tables.sql and 403_create_machine_tables.sql first
+
+-- Create watches table
+CREATE TABLE public.watches (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('machine', 'order', 'item')),
+  entity_id UUID NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (tenant_id, person_id, entity_type, entity_id)
+);
+
+-- Create entity_comments table
+CREATE TABLE public.entity_comments (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('machine', 'order', 'item')),
+  entity_id UUID NOT NULL,
+  author_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  body TEXT NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create notifications table
+CREATE TABLE public.notifications (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('machine', 'order', 'item')),
+  entity_id UUID NOT NULL,
+  event_type VARCHAR(20) NOT NULL CHECK (event_type IN ('status_changed', 'low_stock', 'comment_added')),
+  title TEXT NOT NULL,
+  detail JSONB NOT NULL DEFAULT '{}',
+  read_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes
+CREATE INDEX idx_watches_entity ON public.watches(tenant_id, entity_type, entity_id);
+CREATE INDEX idx_watches_person_id ON public.watches(person_id);
+CREATE INDEX idx_entity_comments_entity ON public.entity_comments(tenant_id, entity_type, entity_id, created_at);
+CREATE INDEX idx_notifications_person ON public.notifications(tenant_id, person_id, created_at DESC);
+CREATE INDEX idx_notifications_unread ON public.notifications(tenant_id, person_id) WHERE read_at IS NULL;
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.watches ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.entity_comments ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.notifications ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "watches_tenant_isolation" ON public.watches
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "entity_comments_tenant_isolation" ON public.entity_comments
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "notifications_tenant_isolation" ON public.notifications
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Notifies everyone watching a record, except the person who caused the event
+CREATE OR REPLACE FUNCTION public.notify_watchers(
+    p_tenant_id UUID,
+    p_entity_type VARCHAR,
+    p_entity_id UUID,
+    p_event_type VARCHAR,
+    p_title TEXT,
+    p_detail JSONB,
+    p_actor_id UUID
+)
+RETURNS VOID
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    INSERT INTO public.notifications (tenant_id, person_id, entity_type, entity_id, event_type, title, detail)
+    SELECT w.tenant_id, w.person_id, w.entity_type, w.entity_id, p_event_type, p_title, p_detail
+    FROM public.watches w
+    WHERE w.tenant_id = p_tenant_id
+      AND w.entity_type = p_entity_type
+      AND w.entity_id = p_entity_id
+      AND w.person_id IS DISTINCT FROM p_actor_id;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Machine status changes
+CREATE OR REPLACE FUNCTION public.notify_machine_status_change()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    IF OLD.status IS DISTINCT FROM NEW.status THEN
+        PERFORM public.notify_watchers(
+            NEW.tenant_id, 'machine', NEW.id, 'status_changed',
+            'Machine ' || NEW.name || ' is now ' || NEW.status,
+            jsonb_build_object('previous_status', OLD.status, 'status', NEW.status),
+            NULL
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_machine_status_change_trigger
+    AFTER UPDATE OF status ON public.machines
+    FOR EACH ROW
+    EXECUTE FUNCTION public.notify_machine_status_change();
+
+-- Order status changes
+CREATE OR REPLACE FUNCTION public.notify_order_status_change()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    IF OLD.status IS DISTINCT FROM NEW.status THEN
+        PERFORM public.notify_watchers(
+            NEW.tenant_id, 'order', NEW.id, 'status_changed',
+            'Order ' || NEW.order_number || ' is now ' || NEW.status,
+            jsonb_build_object('previous_status', OLD.status, 'status', NEW.status),
+            NULL
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_order_status_change_trigger
+    AFTER UPDATE OF status ON public.orders
+    FOR EACH ROW
+    EXECUTE FUNCTION public.notify_order_status_change();
+
+-- Stock falling to the reorder point, or to the minimum stock level without one
+CREATE OR REPLACE FUNCTION public.notify_low_stock()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+DECLARE
+    threshold NUMERIC := COALESCE(NEW.reorder_point, NEW.min_stock_level);
+    part_number VARCHAR;
+BEGIN
+    IF threshold IS NOT NULL
+       AND COALESCE(NEW.quantity, 0) <= threshold
+       AND COALESCE(OLD.quantity, 0) > threshold THEN
+        SELECT internal_part_number INTO part_number FROM public.items WHERE id = NEW.item_id;
+        PERFORM public.notify_watchers(
+            NEW.tenant_id, 'item', NEW.item_id, 'low_stock',
+            'Stock of ' || COALESCE(part_number, 'item') || ' is low in ' || NEW.context,
+            jsonb_build_object('context', NEW.context, 'quantity', NEW.quantity, 'threshold', threshold),
+            NULL
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_low_stock_trigger
+    AFTER UPDATE OF quantity ON public.inventory_items
+    FOR EACH ROW
+    EXECUTE FUNCTION public.notify_low_stock();
+
+-- Comments
+CREATE OR REPLACE FUNCTION public.notify_entity_comment()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+DECLARE
+    author_name VARCHAR;
+BEGIN
+    SELECT name INTO author_name FROM public.person WHERE id = NEW.author_id;
+    PERFORM public.notify_watchers(
+        NEW.tenant_id, NEW.entity_type, NEW.entity_id, 'comment_added',
+        COALESCE(author_name, 'Someone') || ' commented on the ' || NEW.entity_type,
+        jsonb_build_object('comment_id', NEW.id, 'body', LEFT(NEW.body, 200)),
+        NEW.author_id
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER notify_entity_comment_trigger
+    AFTER INSERT ON public.entity_comments
+    FOR EACH ROW
+    EXECUTE FUNCTION public.notify_entity_comment();
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, DELETE ON public.watches TO authenticated, service_role;
+GRANT SELECT, INSERT, DELETE ON public.entity_comments TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.notifications TO authenticated, service_role;
+GRANT EXECUTE ON FUNCTION public.notify_watchers(UUID, VARCHAR, UUID, VARCHAR, TEXT, JSONB, UUID) TO postgres, service_role;
+GRANT EXECUTE ON FUNCTION public.notify_machine_status_change() TO postgres, service_role;
+GRANT EXECUTE ON FUNCTION public.notify_order_status_change() TO postgres, service_role;
+GRANT EXECUTE ON FUNCTION public.notify_low_stock() TO postgres, service_role;
+GRANT EXECUTE ON FUNCTION public.notify_entity_comment() TO postgres, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.watches IS 'Machines, orders and items people asked to be notified about';
+COMMENT ON TABLE public.entity_comments IS 'Comments people leave on machines, orders and items';
+COMMENT ON TABLE public.notifications IS 'Notification center: events on watched records, per watcher';
+COMMENT ON COLUMN public.notifications.title IS 'One-line description of the event, written when it happened';
+COMMENT ON COLUMN public.notifications.detail IS 'Event data: previous and new status, stock level and threshold, or the comment';
+COMMENT ON COLUMN public.notifications.read_at IS 'When the watcher marked the notification read; NULL while unread';
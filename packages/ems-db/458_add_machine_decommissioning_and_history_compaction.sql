@@ -0,0 +1,72 @@
+-- Migration: Add decommissioned machine status and daily machine history aggregates
+-- This migration lets platform operators retire machines that stopped sending heartbeats
+-- months ago, and compact old telemetry readings and status transitions into one row per
+-- machine and day. Compaction deletes the raw rows it summarises; the status a machine was in
+-- at the compaction cutoff is kept as a single history row so uptime and alert rules still
+-- know it.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 403_create_machine_tables.sql,
+-- 404_create_machine_status_history.sql and 409_create_machine_telemetry.sql first
+
+-- Allow the decommissioned status
+ALTER TABLE public.machines DROP CONSTRAINT IF EXISTS machines_status_check;
+ALTER TABLE public.machines ADD CONSTRAINT machines_status_check
+  CHECK (status IN ('offline', 'idle', 'busy', 'maintenance', 'error', 'decommissioned'));
+
+ALTER TABLE public.machine_status_history DROP CONSTRAINT IF EXISTS machine_status_history_new_status_check;
+ALTER TABLE public.machine_status_history ADD CONSTRAINT machine_status_history_new_status_check
+  CHECK (new_status IN ('offline', 'idle', 'busy', 'maintenance', 'error', 'decommissioned'));
+
+-- Create machine_telemetry_daily table
+CREATE TABLE public.machine_telemetry_daily (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  channel VARCHAR(20) NOT NULL,
+  day DATE NOT NULL,
+  samples BIGINT NOT NULL CHECK (samples > 0),
+  min_value DOUBLE PRECISION NOT NULL,
+  max_value DOUBLE PRECISION NOT NULL,
+  avg_value DOUBLE PRECISION NOT NULL,
+  UNIQUE (machine_id, channel, day)
+);
+
+-- Create machine_status_daily table
+CREATE TABLE public.machine_status_daily (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  day DATE NOT NULL,
+  status VARCHAR(20) NOT NULL,
+  transitions INT NOT NULL DEFAULT 0 CHECK (transitions >= 0),
+  seconds DOUBLE PRECISION NOT NULL DEFAULT 0 CHECK (seconds >= 0),
+  UNIQUE (machine_id, day, status)
+);
+
+-- Create indexes
+CREATE INDEX idx_machine_telemetry_daily_tenant_id ON public.machine_telemetry_daily(tenant_id);
+CREATE INDEX idx_machine_status_daily_tenant_id ON public.machine_status_daily(tenant_id);
+CREATE INDEX idx_machines_last_heartbeat ON public.machines(last_heartbeat) WHERE status <> 'decommissioned';
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_telemetry_daily ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_status_daily ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_telemetry_daily_tenant_isolation" ON public.machine_telemetry_daily
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_status_daily_tenant_isolation" ON public.machine_status_daily
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_telemetry_daily TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_status_daily TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_telemetry_daily IS 'Telemetry readings compacted to one row per machine, channel and day';
+COMMENT ON TABLE public.machine_status_daily IS 'Status history compacted to one row per machine, day and status';
+COMMENT ON COLUMN public.machine_status_daily.transitions IS 'Times the machine changed into the status that day';
+COMMENT ON COLUMN public.machine_status_daily.seconds IS 'Time the machine spent in the status that day';
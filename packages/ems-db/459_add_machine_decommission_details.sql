@@ -0,0 +1,45 @@
+-- Migration: Add decommission details to machines
+-- This migration records why and when a machine was decommissioned, who did it and its final
+-- meter readings. A decommissioned machine keeps its data but is frozen: its status can no
+-- longer change, so no further heartbeats or status history are recorded for it.
+-- PREREQUISITE: Run 101_create_person_tables.sql, 403_create_machine_tables.sql and
+-- 458_add_machine_decommissioning_and_history_compaction.sql first
+
+-- Add decommission columns to machines
+ALTER TABLE public.machines
+  ADD COLUMN decommissioned_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN decommission_reason TEXT,
+  ADD COLUMN decommissioned_by UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  ADD COLUMN final_meter_readings JSONB;
+
+-- Machines decommissioned before this migration date from their last update
+UPDATE public.machines
+SET decommissioned_at = COALESCE(updated_at, NOW())
+WHERE status = 'decommissioned' AND decommissioned_at IS NULL;
+
+-- Create trigger function freezing decommissioned machines
+CREATE OR REPLACE FUNCTION public.freeze_decommissioned_machine()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    IF OLD.status = 'decommissioned' AND NEW.status IS DISTINCT FROM OLD.status THEN
+        RAISE EXCEPTION 'Machine % is decommissioned', OLD.id;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER freeze_decommissioned_machine_trigger
+    BEFORE UPDATE OF status ON public.machines
+    FOR EACH ROW
+    EXECUTE FUNCTION public.freeze_decommissioned_machine();
+
+-- Grant necessary permissions
+GRANT EXECUTE ON FUNCTION public.freeze_decommissioned_machine() TO postgres, service_role;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.machines.decommissioned_at IS 'When the machine was taken out of service; NULL while in service';
+COMMENT ON COLUMN public.machines.decommission_reason IS 'Why the machine was decommissioned';
+COMMENT ON COLUMN public.machines.final_meter_readings IS 'Last value of each meter when the machine was decommissioned, by channel';
@@ -0,0 +1,120 @@
+-- Migration: Create content-addressed asset blobs
+-- This migration stores each distinct uploaded file once per tenant. A blob is identified by the
+-- SHA-256 of its content; assets uploading a file the tenant already holds reference the existing
+-- blob instead of keeping another copy. Blobs count the assets referencing them, and a blob no
+-- asset references any more is removed together with its file.
+-- PREREQUISITE: Run 402_create_asset_tables.sql, 433_create_asset_uploads.sql and
+-- 434_add_asset_scanning.sql first
+
+-- Create asset_blobs table
+CREATE TABLE public.asset_blobs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  checksum VARCHAR(64) NOT NULL CHECK (checksum ~ '^[0-9a-f]{64}$'),
+  storage_path VARCHAR(500) NOT NULL,
+  size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+  content_type VARCHAR(50),
+  ref_count INTEGER NOT NULL DEFAULT 0 CHECK (ref_count >= 0),
+  scan_status VARCHAR(20) CHECK (scan_status IN ('pending', 'clean', 'infected', 'failed')),
+  scanned_at TIMESTAMP WITH TIME ZONE,
+  scan JSONB,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (tenant_id, checksum)
+);
+
+-- Link assets and uploads to blobs
+ALTER TABLE public.assets
+  ADD COLUMN blob_id UUID REFERENCES public.asset_blobs(id);
+
+ALTER TABLE public.asset_uploads
+  ADD COLUMN deduplicated BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Create indexes for asset blobs
+CREATE INDEX idx_asset_blobs_unreferenced ON public.asset_blobs(tenant_id) WHERE ref_count = 0;
+CREATE INDEX idx_assets_blob_id ON public.assets(blob_id);
+
+-- Existing files become blobs; assets sharing a checksum but stored at another path keep their
+-- own file and stay unlinked
+INSERT INTO public.asset_blobs (tenant_id, checksum, storage_path, size_bytes, content_type, scan_status, scanned_at)
+SELECT DISTINCT ON (tenant_id, LOWER(checksum))
+  tenant_id, LOWER(checksum), file_path, COALESCE(file_size, 0), file_type, scan_status, scanned_at
+FROM public.assets
+WHERE file_path IS NOT NULL AND checksum ~* '^[0-9a-f]{64}$'
+ORDER BY tenant_id, LOWER(checksum), created_at;
+
+UPDATE public.assets a
+SET blob_id = b.id
+FROM public.asset_blobs b
+WHERE b.tenant_id = a.tenant_id
+  AND b.checksum = LOWER(a.checksum)
+  AND b.storage_path = a.file_path;
+
+UPDATE public.asset_blobs b
+SET ref_count = (SELECT COUNT(*) FROM public.assets a WHERE a.blob_id = b.id);
+
+-- Create trigger function detaching assets whose file is replaced without a blob
+CREATE OR REPLACE FUNCTION public.detach_asset_blob()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.file_path IS DISTINCT FROM OLD.file_path AND NEW.blob_id IS NOT DISTINCT FROM OLD.blob_id THEN
+        NEW.blob_id := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create trigger function counting the assets referencing each blob
+CREATE OR REPLACE FUNCTION public.count_asset_blob_references()
+RETURNS TRIGGER
+SECURITY DEFINER
+SET search_path = public
+AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') AND OLD.blob_id IS NOT NULL THEN
+        UPDATE public.asset_blobs
+        SET ref_count = ref_count - 1, updated_at = NOW()
+        WHERE id = OLD.blob_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.blob_id IS NOT NULL THEN
+        UPDATE public.asset_blobs
+        SET ref_count = ref_count + 1, updated_at = NOW()
+        WHERE id = NEW.blob_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER detach_asset_blob_trigger
+    BEFORE UPDATE OF file_path ON public.assets
+    FOR EACH ROW
+    EXECUTE FUNCTION public.detach_asset_blob();
+
+CREATE TRIGGER count_asset_blob_references_trigger
+    AFTER INSERT OR DELETE OR UPDATE OF blob_id ON public.assets
+    FOR EACH ROW
+    EXECUTE FUNCTION public.count_asset_blob_references();
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_asset_blobs_updated_at
+  BEFORE UPDATE ON public.asset_blobs
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.asset_blobs ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "asset_blobs_tenant_isolation" ON public.asset_blobs
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.asset_blobs TO authenticated, service_role;
+GRANT EXECUTE ON FUNCTION public.count_asset_blob_references() TO postgres, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.asset_blobs IS 'Uploaded asset files stored once per tenant, by SHA-256 of their content';
+COMMENT ON COLUMN public.asset_blobs.ref_count IS 'Assets referencing the blob; blobs at zero are removed with their file';
+COMMENT ON COLUMN public.asset_blobs.scan IS 'Result of the content scan, copied to the metadata of assets referencing the blob';
+COMMENT ON COLUMN public.assets.blob_id IS 'Stored file the asset references; NULL for files not stored through an upload';
+COMMENT ON COLUMN public.asset_uploads.deduplicated IS 'Whether the tenant already held the file, so the asset references the stored copy';
@@ -0,0 +1,40 @@
+-- Migration: Create firmware delta cache
+-- This migration caches binary deltas between firmware images, so a device reporting the image it
+-- runs downloads only the changes to the current version. Deltas are keyed by the SHA-256 of both
+-- images and generated once per pair; a pair whose delta is no smaller than the new image is
+-- recorded without a file, so the image is sent whole without generating the delta again.
+-- PREREQUISITE: Run 460_create_asset_blobs.sql first
+
+-- Create firmware_deltas table
+CREATE TABLE public.firmware_deltas (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  source_checksum VARCHAR(64) NOT NULL CHECK (source_checksum ~ '^[0-9a-f]{64}$'),
+  target_checksum VARCHAR(64) NOT NULL CHECK (target_checksum ~ '^[0-9a-f]{64}$'),
+  storage_path VARCHAR(500),
+  size_bytes BIGINT NOT NULL CHECK (size_bytes >= 0),
+  checksum VARCHAR(64) NOT NULL CHECK (checksum ~ '^[0-9a-f]{64}$'),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (tenant_id, source_checksum, target_checksum),
+  CHECK (source_checksum <> target_checksum)
+);
+
+-- Create indexes for firmware deltas
+CREATE INDEX idx_firmware_deltas_target ON public.firmware_deltas(tenant_id, target_checksum);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.firmware_deltas ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "firmware_deltas_tenant_isolation" ON public.firmware_deltas
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.firmware_deltas TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.firmware_deltas IS 'Binary deltas between firmware images, generated once per pair of images';
+COMMENT ON COLUMN public.firmware_deltas.storage_path IS 'Stored delta; NULL when the delta is no smaller than the new image, which is sent whole';
+COMMENT ON COLUMN public.firmware_deltas.size_bytes IS 'Size of the generated delta, kept when it is not stored';
+COMMENT ON COLUMN public.firmware_deltas.checksum IS 'SHA-256 of the delta';
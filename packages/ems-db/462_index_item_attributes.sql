@@ -0,0 +1,11 @@
+-- Migration: Index item attributes for parametric search
+-- This migration indexes the attributes items keep under `attributes` in their metadata, so
+-- parametric searches such as `package=0402` within a category are answered from the index.
+-- Numeric attributes are stored in base units (ohms, farads, volts) for range filters and sorting.
+-- PREREQUISITE: Run 401_create_item_tables.sql first
+
+-- Create index for attribute filters; queries must use the same `metadata -> 'attributes'` expression
+CREATE INDEX idx_items_attributes ON public.items USING GIN ((metadata -> 'attributes') jsonb_path_ops);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.items.metadata IS 'Free-form item data; `attributes` holds parametric attributes, numbers in base units';
@@ -0,0 +1,151 @@
+-- Migration: Create RFQ tables
+-- This migration adds vendor requests for quote: the items and quantities asked for, the vendors
+-- invited (each answering through an emailed link), the prices and lead times they quoted and the
+-- purchase order the winning quote was awarded as
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 301_create_orders_tables.sql, 401_create_item_tables.sql, 415_create_item_price_history.sql, and 450_create_numbering_sequences.sql first
+
+-- Create rfqs table
+CREATE TABLE public.rfqs (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  rfq_number VARCHAR(50) NOT NULL,
+  status VARCHAR(20) NOT NULL DEFAULT 'draft' CHECK (status IN ('draft', 'sent', 'awarded', 'cancelled')),
+  respond_by TIMESTAMP WITH TIME ZONE,
+  notes TEXT,
+  sent_at TIMESTAMP WITH TIME ZONE,
+  awarded_vendor_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  awarded_at TIMESTAMP WITH TIME ZONE,
+  order_id UUID REFERENCES public.orders(id) ON DELETE SET NULL,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, rfq_number)
+);
+
+-- Create rfq_items table
+CREATE TABLE public.rfq_items (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  rfq_id UUID NOT NULL REFERENCES public.rfqs(id) ON DELETE CASCADE,
+  line_number INTEGER NOT NULL CHECK (line_number > 0),
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  item_name VARCHAR(200) NOT NULL,
+  item_description TEXT,
+  quantity INTEGER NOT NULL CHECK (quantity > 0),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(rfq_id, line_number)
+);
+
+-- Create rfq_vendors table
+CREATE TABLE public.rfq_vendors (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  rfq_id UUID NOT NULL REFERENCES public.rfqs(id) ON DELETE CASCADE,
+  vendor_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'responded', 'declined', 'awarded', 'lost')),
+  token_hash VARCHAR(64) UNIQUE,
+  currency VARCHAR(3),
+  notes TEXT,
+  sent_at TIMESTAMP WITH TIME ZONE,
+  responded_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(rfq_id, vendor_id)
+);
+
+-- Create rfq_quotes table
+CREATE TABLE public.rfq_quotes (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  rfq_vendor_id UUID NOT NULL REFERENCES public.rfq_vendors(id) ON DELETE CASCADE,
+  rfq_item_id UUID NOT NULL REFERENCES public.rfq_items(id) ON DELETE CASCADE,
+  unit_price DOUBLE PRECISION NOT NULL CHECK (unit_price >= 0),
+  lead_time INTEGER CHECK (lead_time >= 0),
+  notes TEXT,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(rfq_vendor_id, rfq_item_id)
+);
+
+-- Create indexes for rfqs table
+CREATE INDEX idx_rfqs_tenant_id ON public.rfqs(tenant_id);
+CREATE INDEX idx_rfqs_status ON public.rfqs(status);
+CREATE INDEX idx_rfqs_order_id ON public.rfqs(order_id);
+
+-- Create indexes for rfq_items table
+CREATE INDEX idx_rfq_items_tenant_id ON public.rfq_items(tenant_id);
+CREATE INDEX idx_rfq_items_rfq_id ON public.rfq_items(rfq_id);
+
+-- Create indexes for rfq_vendors table
+CREATE INDEX idx_rfq_vendors_tenant_id ON public.rfq_vendors(tenant_id);
+CREATE INDEX idx_rfq_vendors_rfq_id ON public.rfq_vendors(rfq_id);
+CREATE INDEX idx_rfq_vendors_vendor_id ON public.rfq_vendors(vendor_id);
+
+-- Create indexes for rfq_quotes table
+CREATE INDEX idx_rfq_quotes_tenant_id ON public.rfq_quotes(tenant_id);
+CREATE INDEX idx_rfq_quotes_rfq_vendor_id ON public.rfq_quotes(rfq_vendor_id);
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_rfqs_updated_at
+  BEFORE UPDATE ON public.rfqs
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_rfq_vendors_updated_at
+  BEFORE UPDATE ON public.rfq_vendors
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Number RFQs from their own sequence
+ALTER TABLE public.numbering_sequences DROP CONSTRAINT IF EXISTS numbering_sequences_entity_check;
+ALTER TABLE public.numbering_sequences ADD CONSTRAINT numbering_sequences_entity_check
+  CHECK (entity IN ('purchase_order', 'customer_order', 'distributor_order', 'job', 'item', 'quote', 'return', 'rfq'));
+
+-- Record prices taken from awarded RFQs in the price history
+ALTER TABLE public.item_price_history DROP CONSTRAINT IF EXISTS item_price_history_source_check;
+ALTER TABLE public.item_price_history ADD CONSTRAINT item_price_history_source_check
+  CHECK (source IN ('price_list', 'rfq'));
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.rfqs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.rfq_items ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.rfq_vendors ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.rfq_quotes ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "rfqs_tenant_isolation" ON public.rfqs
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "rfq_items_tenant_isolation" ON public.rfq_items
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "rfq_vendors_tenant_isolation" ON public.rfq_vendors
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "rfq_quotes_tenant_isolation" ON public.rfq_quotes
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.rfqs TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.rfq_items TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.rfq_vendors TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.rfq_quotes TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.rfqs IS 'Requests for quote sent to vendors, awarded as a purchase order';
+COMMENT ON COLUMN public.rfqs.status IS 'RFQ status (draft, sent, awarded, cancelled)';
+COMMENT ON COLUMN public.rfqs.respond_by IS 'Vendors cannot respond after this time';
+COMMENT ON COLUMN public.rfqs.order_id IS 'Purchase order the winning quote was converted into';
+COMMENT ON TABLE public.rfq_items IS 'Items and quantities vendors are asked to quote';
+COMMENT ON TABLE public.rfq_vendors IS 'Vendors invited to an RFQ and where their response stands';
+COMMENT ON COLUMN public.rfq_vendors.status IS 'Vendor status (pending, sent, responded, declined, awarded, lost)';
+COMMENT ON COLUMN public.rfq_vendors.token_hash IS 'SHA-256 of the token in the response link last emailed to the vendor';
+COMMENT ON TABLE public.rfq_quotes IS 'Unit price and lead time a vendor quoted for an RFQ line';
+COMMENT ON COLUMN public.rfq_quotes.lead_time IS 'Quoted lead time in days';
+COMMENT ON COLUMN public.numbering_sequences.entity IS 'Kind of document numbered: purchase_order, customer_order, distributor_order, job, item, quote, return or rfq';
+COMMENT ON COLUMN public.item_price_history.source IS 'How the price was recorded (price_list, rfq)';
@@ -0,0 +1,94 @@
+-- Migration: Add consignment stock
+-- This migration adds an ownership dimension to inventory. Stock a vendor or customer keeps at our
+-- site stays theirs: consignment_stock holds each partner's share of an inventory record, the rest
+-- of the on-hand quantity is our own. Issuing consignment stock creates a self-billing record for
+-- the partner instead of reducing the value of our own stock
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 401_create_item_tables.sql, 406_create_stock_movements.sql, and 428_add_decimal_quantities.sql first
+
+-- Record whose stock a movement was entered against
+ALTER TABLE public.stock_movements
+  ADD COLUMN owner_id UUID REFERENCES public.person(id) ON DELETE SET NULL;
+
+-- Create consignment_stock table
+CREATE TABLE public.consignment_stock (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  inventory_item_id UUID NOT NULL REFERENCES public.inventory_items(id) ON DELETE CASCADE,
+  owner_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  owner_type VARCHAR(20) NOT NULL CHECK (owner_type IN ('vendor', 'customer')),
+  quantity NUMERIC(18, 6) NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+  unit_price DOUBLE PRECISION CHECK (unit_price >= 0),
+  currency VARCHAR(3),
+  notes TEXT,
+  last_received_date TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(inventory_item_id, owner_id)
+);
+
+-- Create consignment_billings table
+CREATE TABLE public.consignment_billings (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  consignment_stock_id UUID NOT NULL REFERENCES public.consignment_stock(id) ON DELETE CASCADE,
+  stock_movement_id UUID NOT NULL REFERENCES public.stock_movements(id) ON DELETE CASCADE,
+  item_id UUID NOT NULL REFERENCES public.items(id) ON DELETE CASCADE,
+  owner_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  quantity NUMERIC(18, 6) NOT NULL CHECK (quantity > 0),
+  unit_price DOUBLE PRECISION NOT NULL CHECK (unit_price >= 0),
+  amount DOUBLE PRECISION NOT NULL,
+  currency VARCHAR(3),
+  status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'invoiced')),
+  invoice_reference VARCHAR(100),
+  invoiced_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create indexes for consignment tables
+CREATE INDEX idx_stock_movements_owner_id ON public.stock_movements(owner_id) WHERE owner_id IS NOT NULL;
+CREATE INDEX idx_consignment_stock_tenant_id ON public.consignment_stock(tenant_id);
+CREATE INDEX idx_consignment_stock_owner_id ON public.consignment_stock(owner_id);
+CREATE INDEX idx_consignment_stock_item_id ON public.consignment_stock(item_id);
+CREATE INDEX idx_consignment_billings_tenant_id ON public.consignment_billings(tenant_id);
+CREATE INDEX idx_consignment_billings_owner_status ON public.consignment_billings(owner_id, status);
+CREATE INDEX idx_consignment_billings_stock_movement_id ON public.consignment_billings(stock_movement_id);
+
+-- Create triggers for updated_at timestamps (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_consignment_stock_updated_at
+  BEFORE UPDATE ON public.consignment_stock
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_consignment_billings_updated_at
+  BEFORE UPDATE ON public.consignment_billings
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.consignment_stock ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.consignment_billings ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "consignment_stock_tenant_isolation" ON public.consignment_stock
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "consignment_billings_tenant_isolation" ON public.consignment_billings
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.consignment_stock TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.consignment_billings TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.stock_movements.owner_id IS 'Vendor or customer whose consignment stock the movement was entered against; NULL for our own stock';
+COMMENT ON TABLE public.consignment_stock IS 'Share of an inventory record owned by a vendor or customer; own stock = on hand - consignment stock';
+COMMENT ON COLUMN public.consignment_stock.owner_type IS 'Kind of partner owning the stock (vendor, customer)';
+COMMENT ON COLUMN public.consignment_stock.quantity IS 'Consignment quantity on hand in the item''s base unit';
+COMMENT ON COLUMN public.consignment_stock.unit_price IS 'Agreed price per base unit billed on consumption; the inventory record''s unit cost when NULL';
+COMMENT ON TABLE public.consignment_billings IS 'Self-billing records for consignment stock consumed, owed to its owner';
+COMMENT ON COLUMN public.consignment_billings.amount IS 'quantity x unit_price, rounded to cents';
+COMMENT ON COLUMN public.consignment_billings.status IS 'Billing status (pending, invoiced)';
+COMMENT ON COLUMN public.consignment_billings.invoice_reference IS 'Self-billing invoice the record was settled on';
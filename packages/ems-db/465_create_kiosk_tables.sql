@@ -0,0 +1,133 @@
+-- Migration: Create kiosk terminal, operator badge, clock event and downtime report tables
+-- This migration adds shop-floor kiosks. A kiosk terminal is provisioned with a long-lived API
+-- key that only reaches the kiosk endpoints for the actions the terminal allows; the key is
+-- returned once and stored hashed. Operators badge in on the terminal for each action with
+-- their badge id, or by picking themselves from the operator list and entering their PIN.
+-- Terminals live in the shared database, as the key is resolved by the auth middleware before
+-- any tenant database is used; kiosk_terminal_id has no foreign key elsewhere for that reason.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 403_create_machine_tables.sql and 455_create_api_access_logs.sql first
+
+-- Create kiosk_terminals table
+CREATE TABLE public.kiosk_terminals (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  location VARCHAR(100),
+  actions TEXT[] NOT NULL DEFAULT '{}'
+    CHECK (actions <@ ARRAY['clock', 'complete_operation', 'report_downtime']::TEXT[]),
+  key_prefix VARCHAR(16) NOT NULL,
+  key_hash VARCHAR(64) NOT NULL UNIQUE,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  last_used_at TIMESTAMP WITH TIME ZONE,
+  revoked_at TIMESTAMP WITH TIME ZONE,
+  revoked_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create operator_badges table
+CREATE TABLE public.operator_badges (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  badge_id VARCHAR(64),
+  pin_hash VARCHAR(255),
+  failed_pin_attempts INTEGER NOT NULL DEFAULT 0 CHECK (failed_pin_attempts >= 0),
+  locked_until TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(tenant_id, person_id),
+  UNIQUE(tenant_id, badge_id)
+);
+
+-- Create operator_clock_events table
+CREATE TABLE public.operator_clock_events (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  person_id UUID NOT NULL REFERENCES public.person(id) ON DELETE CASCADE,
+  event_type VARCHAR(20) NOT NULL CHECK (event_type IN ('clock_in', 'clock_out')),
+  occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  kiosk_terminal_id UUID,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create machine_downtime_reports table
+CREATE TABLE public.machine_downtime_reports (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  reported_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  previous_status VARCHAR(20),
+  new_status VARCHAR(20) NOT NULL CHECK (new_status IN ('maintenance', 'error')),
+  reason TEXT NOT NULL,
+  kiosk_terminal_id UUID,
+  reported_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Requests made with a kiosk key are logged against the terminal
+ALTER TABLE public.api_access_logs DROP CONSTRAINT api_access_logs_actor_type_check;
+ALTER TABLE public.api_access_logs ADD CONSTRAINT api_access_logs_actor_type_check
+  CHECK (actor_type IN ('person', 'service_client', 'machine', 'kiosk'));
+
+-- Create indexes for kiosk tables
+CREATE INDEX idx_kiosk_terminals_tenant_id ON public.kiosk_terminals(tenant_id);
+CREATE INDEX idx_operator_badges_tenant_id ON public.operator_badges(tenant_id);
+CREATE INDEX idx_operator_clock_events_person_occurred_at ON public.operator_clock_events(person_id, occurred_at);
+CREATE INDEX idx_operator_clock_events_tenant_occurred_at ON public.operator_clock_events(tenant_id, occurred_at);
+CREATE INDEX idx_machine_downtime_reports_machine_reported_at ON public.machine_downtime_reports(machine_id, reported_at);
+CREATE INDEX idx_machine_downtime_reports_tenant_id ON public.machine_downtime_reports(tenant_id);
+
+-- Create triggers for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_kiosk_terminals_updated_at
+  BEFORE UPDATE ON public.kiosk_terminals
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+CREATE TRIGGER update_operator_badges_updated_at
+  BEFORE UPDATE ON public.operator_badges
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.kiosk_terminals ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.operator_badges ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.operator_clock_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE public.machine_downtime_reports ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "kiosk_terminals_tenant_isolation" ON public.kiosk_terminals
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "operator_badges_tenant_isolation" ON public.operator_badges
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "operator_clock_events_tenant_isolation" ON public.operator_clock_events
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+CREATE POLICY "machine_downtime_reports_tenant_isolation" ON public.machine_downtime_reports
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.kiosk_terminals TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.operator_badges TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.operator_clock_events TO authenticated, service_role;
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.machine_downtime_reports TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.kiosk_terminals IS 'Shop-floor terminals operators badge in on';
+COMMENT ON COLUMN public.kiosk_terminals.actions IS 'What operators may do on the terminal: clock, complete_operation, report_downtime';
+COMMENT ON COLUMN public.kiosk_terminals.key_prefix IS 'Start of the API key, to tell keys apart without the key itself';
+COMMENT ON COLUMN public.kiosk_terminals.key_hash IS 'SHA-256 hash of the kiosk API key';
+COMMENT ON COLUMN public.kiosk_terminals.revoked_at IS 'When the terminal''s key stopped working';
+COMMENT ON TABLE public.operator_badges IS 'How operators identify themselves on kiosk terminals';
+COMMENT ON COLUMN public.operator_badges.badge_id IS 'Badge scanned on the terminal; identifies the operator on its own';
+COMMENT ON COLUMN public.operator_badges.pin_hash IS 'Argon2 hash of the PIN entered after picking oneself from the operator list';
+COMMENT ON COLUMN public.operator_badges.locked_until IS 'PIN entry is refused until then after too many wrong PINs';
+COMMENT ON TABLE public.operator_clock_events IS 'Operators clocking in and out';
+COMMENT ON TABLE public.machine_downtime_reports IS 'Unplanned downtime reported by operators, with the machine status it set';
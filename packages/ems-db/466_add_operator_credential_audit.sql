@@ -0,0 +1,16 @@
+-- Migration: Audit operator kiosk credentials
+-- This migration records when operators last badged in on a kiosk and adds kiosk events to the
+-- sign-in audit trail: badge-ins refused for an unknown badge, a wrong PIN or a PIN lockout,
+-- and admins setting, removing or unlocking an operator's badge and PIN.
+-- PREREQUISITE: Run 447_create_auth_audit_events.sql and 465_create_kiosk_tables.sql first
+
+-- Track when each operator last badged in
+ALTER TABLE public.operator_badges ADD COLUMN last_used_at TIMESTAMP WITH TIME ZONE;
+
+-- Kiosk badge-ins and credential changes go to the sign-in audit trail
+ALTER TABLE public.auth_audit_events DROP CONSTRAINT auth_audit_events_event_check;
+ALTER TABLE public.auth_audit_events ADD CONSTRAINT auth_audit_events_event_check
+  CHECK (event IN ('refresh_fingerprint_mismatch', 'kiosk_badge_in', 'operator_credentials_changed'));
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.operator_badges.last_used_at IS 'When the operator last badged in on a kiosk';
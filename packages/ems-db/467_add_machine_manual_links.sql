@@ -0,0 +1,10 @@
+-- Migration: Link manuals to machines
+-- This migration lets machines link manuals alongside firmware, configuration and calibration data,
+-- so the machine documentation bundle can hand technicians everything they need in one response
+-- PREREQUISITE: Run 403_create_machine_tables.sql first
+
+-- Allow manual relationships
+ALTER TABLE public.machine_asset_relationships DROP CONSTRAINT machine_asset_relationships_relationship_type_check;
+ALTER TABLE public.machine_asset_relationships ADD CONSTRAINT machine_asset_relationships_relationship_type_check
+  CHECK (relationship_type IN ('firmware', 'configuration', 'calibration_data', 'manual'));
+
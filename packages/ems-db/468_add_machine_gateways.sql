@@ -0,0 +1,15 @@
+-- Migration: Add machine gateways
+-- This migration lets machines report through a gateway device. A gateway is itself a machine; its
+-- API key may also send heartbeats for the machines whose gateway it is, one batch for all of them
+-- PREREQUISITE: Run 403_create_machine_tables.sql first
+
+-- Gateway each machine reports through
+ALTER TABLE public.machines
+  ADD COLUMN gateway_id UUID REFERENCES public.machines(id) ON DELETE SET NULL,
+  ADD CONSTRAINT machines_gateway_check CHECK (gateway_id IS NULL OR gateway_id <> id);
+
+-- Create index for the machines behind a gateway
+CREATE INDEX idx_machines_gateway_id ON public.machines(gateway_id);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.machines.gateway_id IS 'Gateway machine whose API key also sends this machine''s heartbeats';
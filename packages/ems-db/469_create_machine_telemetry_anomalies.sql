@@ -0,0 +1,43 @@
+-- Migration: Create machine_telemetry_anomalies table
+-- This migration adds anomaly detection on machine telemetry. Alert rules gain an anomaly
+-- condition, which the alert worker evaluates by scoring a channel's latest reading against the
+-- mean and standard deviation of its readings over a trailing baseline window (a rolling
+-- z-score). Readings further from the mean than the tenant's anomaly_sensitivity setting, in
+-- standard deviations, are recorded here whether or not the rule fires.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 403_create_machine_tables.sql and 437_create_machine_alert_rules.sql first
+
+-- Create machine_telemetry_anomalies table
+CREATE TABLE public.machine_telemetry_anomalies (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  machine_id UUID NOT NULL REFERENCES public.machines(id) ON DELETE CASCADE,
+  channel VARCHAR(20) NOT NULL CHECK (channel IN ('energy_kwh', 'air_pressure', 'coolant_level', 'temperature')),
+  value DOUBLE PRECISION NOT NULL,
+  baseline_mean DOUBLE PRECISION NOT NULL,
+  baseline_std_dev DOUBLE PRECISION NOT NULL CHECK (baseline_std_dev > 0),
+  z_score DOUBLE PRECISION NOT NULL,
+  sensitivity DOUBLE PRECISION NOT NULL CHECK (sensitivity > 0),
+  baseline_minutes INTEGER NOT NULL CHECK (baseline_minutes > 0),
+  recorded_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  detected_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+  UNIQUE(machine_id, channel, recorded_at)
+);
+
+-- Create indexes for machine_telemetry_anomalies table
+CREATE INDEX idx_machine_telemetry_anomalies_tenant_id ON public.machine_telemetry_anomalies(tenant_id);
+CREATE INDEX idx_machine_telemetry_anomalies_machine_recorded_at ON public.machine_telemetry_anomalies(machine_id, recorded_at);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.machine_telemetry_anomalies ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "machine_telemetry_anomalies_tenant_isolation" ON public.machine_telemetry_anomalies
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.machine_telemetry_anomalies IS 'Telemetry readings the alert worker found anomalous against their channel''s trailing baseline';
+COMMENT ON COLUMN public.machine_telemetry_anomalies.z_score IS 'Standard deviations between the reading and the baseline mean; negative below it';
+COMMENT ON COLUMN public.machine_telemetry_anomalies.sensitivity IS 'Tenant anomaly_sensitivity the reading was scored against';
+COMMENT ON COLUMN public.machine_telemetry_anomalies.baseline_minutes IS 'Minutes of readings before the reading the baseline was taken from';
+COMMENT ON COLUMN public.machine_alert_rules.conditions IS 'Conditions that must all hold: status (optionally held for some minutes), telemetry channel comparisons or telemetry anomalies';
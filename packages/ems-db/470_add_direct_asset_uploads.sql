@@ -0,0 +1,15 @@
+-- Migration: Add direct asset uploads
+-- This migration lets clients send large asset files straight to S3-compatible storage instead of
+-- through the API. A direct upload records the file's size and SHA-256 up front and hands out a
+-- presigned PUT URL with both signed in; confirming the upload has the server check the stored
+-- object against them before the asset references it
+-- PREREQUISITE: Run 433_create_asset_uploads.sql first
+
+-- Whether the client sends the file to storage itself rather than in chunks through the server
+ALTER TABLE public.asset_uploads
+  ADD COLUMN direct BOOLEAN NOT NULL DEFAULT FALSE,
+  ADD CONSTRAINT asset_uploads_direct_checksum_check CHECK (NOT direct OR checksum IS NOT NULL);
+
+-- Add comments for documentation
+COMMENT ON COLUMN public.asset_uploads.direct IS 'Whether the file goes straight to storage through a presigned URL and is confirmed afterwards';
+COMMENT ON COLUMN public.asset_uploads.status IS 'uploading, assembling (or verifying a direct upload), completed, failed (can be retried) or aborted';
@@ -0,0 +1,53 @@
+-- Migration: Create status pages
+-- This migration lets a tenant publish the availability of chosen machines and lines to its
+-- customers. A tenant has at most one status page, which is off until it is enabled. It lists
+-- the machines and machine groups (lines) the tenant picked, and optionally the planned
+-- maintenance windows coming up for them, at a public URL holding a token. The token starts
+-- with the tenant id so the page can be served from the tenant's own database; it is returned
+-- once when issued and stored hashed, and issuing a new one stops the old one working.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql, 101_create_person_tables.sql, 403_create_machine_tables.sql, 407_create_machine_calendars.sql and 438_create_machine_groups.sql first
+
+-- Create status_pages table
+CREATE TABLE public.status_pages (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL UNIQUE REFERENCES public.tenants(id) ON DELETE CASCADE,
+  enabled BOOLEAN NOT NULL DEFAULT FALSE,
+  title VARCHAR(100) NOT NULL,
+  description TEXT,
+  machine_ids UUID[] NOT NULL DEFAULT '{}',
+  group_ids UUID[] NOT NULL DEFAULT '{}',
+  show_maintenance BOOLEAN NOT NULL DEFAULT TRUE,
+  maintenance_days INTEGER NOT NULL DEFAULT 14 CHECK (maintenance_days BETWEEN 1 AND 90),
+  token_prefix VARCHAR(16),
+  token_hash VARCHAR(64) UNIQUE,
+  token_issued_at TIMESTAMP WITH TIME ZONE,
+  updated_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_status_pages_updated_at
+  BEFORE UPDATE ON public.status_pages
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policy for tenant isolation
+ALTER TABLE public.status_pages ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "status_pages_tenant_isolation" ON public.status_pages
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.status_pages TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.status_pages IS 'Public page showing customers the availability of a tenant''s chosen machines and lines';
+COMMENT ON COLUMN public.status_pages.enabled IS 'Whether the page is served; a disabled page answers as if it did not exist';
+COMMENT ON COLUMN public.status_pages.machine_ids IS 'Machines shown on the page, in order';
+COMMENT ON COLUMN public.status_pages.group_ids IS 'Machine groups shown on the page as lines, in order; their members are not listed unless shown themselves';
+COMMENT ON COLUMN public.status_pages.show_maintenance IS 'Whether planned downtime of the shown machines and lines, and site-wide planned downtime, is listed';
+COMMENT ON COLUMN public.status_pages.maintenance_days IS 'How many days ahead planned downtime is listed';
+COMMENT ON COLUMN public.status_pages.token_prefix IS 'Start of the page token, to tell tokens apart without the token itself';
+COMMENT ON COLUMN public.status_pages.token_hash IS 'SHA-256 hash of the token in the page URL';
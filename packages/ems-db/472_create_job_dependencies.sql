@@ -0,0 +1,35 @@
+-- Migration: Create job dependencies table
+-- This migration adds finish-to-start dependencies between jobs: the successor job cannot start
+-- before its predecessor has finished. Dependencies are drawn as links on the jobs Gantt chart,
+-- and the critical path through them is computed from the jobs' scheduled times. The API refuses
+-- dependencies that would make a cycle.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 101_create_person_tables.sql and 201_create_jobs_tables.sql first
+
+-- Create job_dependencies table
+CREATE TABLE public.job_dependencies (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  predecessor_job_id UUID NOT NULL REFERENCES public.jobs(id) ON DELETE CASCADE,
+  successor_job_id UUID NOT NULL REFERENCES public.jobs(id) ON DELETE CASCADE,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE(predecessor_job_id, successor_job_id),
+  CHECK (predecessor_job_id <> successor_job_id)
+);
+
+-- Create indexes for job_dependencies table
+CREATE INDEX idx_job_dependencies_tenant_id ON public.job_dependencies(tenant_id);
+CREATE INDEX idx_job_dependencies_successor_job_id ON public.job_dependencies(successor_job_id);
+
+-- Add RLS (Row Level Security) policies for tenant isolation
+ALTER TABLE public.job_dependencies ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "job_dependencies_tenant_isolation" ON public.job_dependencies
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Add comments for documentation
+COMMENT ON TABLE public.job_dependencies IS 'Finish-to-start links between jobs: the successor starts once the predecessor has finished';
+COMMENT ON COLUMN public.job_dependencies.predecessor_job_id IS 'Job that must finish first';
+COMMENT ON COLUMN public.job_dependencies.successor_job_id IS 'Job that waits for the predecessor';
@@ -0,0 +1,51 @@
+-- Migration: Create validation rules
+-- This migration lets a tenant add its own business rules to the checks orders and items go
+-- through when they are created or updated, such as orders above an amount needing a project
+-- code, or items in a category needing a datasheet. A rule applies to one kind of record; its
+-- requirement is an expression the record must satisfy, optionally only when its condition
+-- holds. Expressions are checked when the rule is saved and evaluated by the server; a record
+-- breaking an enabled rule is rejected with the rule's message.
+-- PREREQUISITE: Run 000_supabase_setup.sql, 001_create_tenants_table.sql and 101_create_person_tables.sql first
+
+-- Create validation_rules table
+CREATE TABLE public.validation_rules (
+  id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+  tenant_id UUID NOT NULL REFERENCES public.tenants(id) ON DELETE CASCADE,
+  name VARCHAR(100) NOT NULL,
+  entity VARCHAR(20) NOT NULL CHECK (entity IN ('order', 'item')),
+  condition TEXT,
+  requirement TEXT NOT NULL,
+  message VARCHAR(500),
+  enabled BOOLEAN NOT NULL DEFAULT TRUE,
+  created_by_id UUID REFERENCES public.person(id) ON DELETE SET NULL,
+  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+  UNIQUE (tenant_id, name)
+);
+
+-- Create indexes for performance
+CREATE INDEX idx_validation_rules_tenant_entity ON public.validation_rules(tenant_id, entity) WHERE enabled;
+
+-- Create trigger for updated_at timestamp (uses function from 000_supabase_setup.sql)
+CREATE TRIGGER update_validation_rules_updated_at
+  BEFORE UPDATE ON public.validation_rules
+  FOR EACH ROW EXECUTE FUNCTION public.update_updated_at_column();
+
+-- Add RLS (Row Level Security) policy for tenant isolation
+ALTER TABLE public.validation_rules ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "validation_rules_tenant_isolation" ON public.validation_rules
+    FOR ALL USING (
+        tenant_id = public.get_current_tenant_id()
+    );
+
+-- Grant necessary permissions
+GRANT SELECT, INSERT, UPDATE, DELETE ON public.validation_rules TO authenticated, service_role;
+
+-- Add comments for documentation
+COMMENT ON TABLE public.validation_rules IS 'Tenant business rules orders and items must satisfy when created or updated';
+COMMENT ON COLUMN public.validation_rules.entity IS 'Kind of record the rule applies to: order or item';
+COMMENT ON COLUMN public.validation_rules.condition IS 'Expression deciding whether the rule applies to a record; the rule always applies when empty';
+COMMENT ON COLUMN public.validation_rules.requirement IS 'Expression a record the rule applies to must satisfy';
+COMMENT ON COLUMN public.validation_rules.message IS 'Message a breaking record is rejected with; made from the rule name when empty';
+COMMENT ON COLUMN public.validation_rules.enabled IS 'Whether the rule is enforced';
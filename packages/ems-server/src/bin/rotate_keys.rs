@@ -0,0 +1,60 @@
+//! Rotates the keys sensitive columns are encrypted with.
+//!
+//! ```text
+//! rotate_keys data-key --platform       new platform key; re-encrypts person phone numbers
+//! rotate_keys data-key --tenant <id>    new key for one tenant; re-encrypts its setting secrets
+//! rotate_keys data-key --all            both, for the platform and every tenant
+//! rotate_keys rewrap                    wraps every data key with the current master key
+//! ```
+//!
+//! To rotate the master key, move the old one to ENCRYPTION_PREVIOUS_MASTER_KEYS as
+//! `<old id>:<old key>`, set the new ENCRYPTION_MASTER_KEY and ENCRYPTION_MASTER_KEY_ID, run
+//! `rewrap` and then drop the old key. Running `data-key --platform` once after turning
+//! encryption on encrypts phone numbers stored before.
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use std::env;
+use uuid::Uuid;
+
+use ems_server::services::{DatabaseService, EncryptionService};
+
+const USAGE: &str = "usage: rotate_keys data-key (--platform | --tenant <id> | --all) | rewrap";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    EncryptionService::check_config()?;
+    let encryption = EncryptionService::new(DatabaseService::new().await?);
+
+    let scopes = match args.as_slice() {
+        ["rewrap"] => {
+            let rewrapped = encryption.rewrap().await?;
+            println!("Rewrapped {} data keys", rewrapped);
+            return Ok(());
+        }
+        ["data-key", "--platform"] => vec![None],
+        ["data-key", "--tenant", tenant_id] => vec![Some(
+            Uuid::parse_str(tenant_id).map_err(|_| anyhow!("Invalid tenant id"))?,
+        )],
+        ["data-key", "--all"] => encryption.scopes().await?,
+        _ => return Err(anyhow!(USAGE)),
+    };
+
+    for scope in scopes {
+        let rotation = encryption.rotate(scope).await?;
+        let scope = rotation
+            .tenant_id
+            .map(|tenant_id| format!("tenant {}", tenant_id))
+            .unwrap_or_else(|| "platform".to_string());
+        println!(
+            "Rotated {} to data key version {}; re-encrypted {} rows",
+            scope, rotation.version, rotation.reencrypted
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,320 @@
+//! Drives multi-tenant load against a running server and reports latency percentiles.
+//!
+//! ```text
+//! soak <accounts.json>
+//! ```
+//!
+//! The accounts file lists the users to sign in as, one per tenant:
+//! `[{"email": "...", "password": "...", "tenant_subdomain": "..."}]`. For the whole run each
+//! account signs in again every SOAK_LOGIN_INTERVAL_SECS, lists the tenant's machines, items and
+//! orders every SOAK_LIST_INTERVAL_MS, and sends a heartbeat for each of up to
+//! SOAK_MACHINES_PER_TENANT of its machines every SOAK_HEARTBEAT_INTERVAL_MS. Tenants seeded
+//! through the admin seed endpoint come with machines to beat for.
+//!
+//! The report is printed as a table and, with SOAK_REPORT set, written there as JSON. With
+//! SOAK_MAX_P99_MS or SOAK_MAX_ERROR_RATE set the run fails when any operation exceeds them,
+//! so it can gate CI on performance.
+use anyhow::{anyhow, Context, Result};
+use dotenv::dotenv;
+use reqwest::{Client, RequestBuilder};
+use serde_json::json;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::{Instant, MissedTickBehavior};
+use uuid::Uuid;
+
+use ems_server::models::{AuthResponse, HeartbeatRequest, LoginRequest, MachineStatus};
+use ems_server::utils::soak::{LatencyRecorder, SoakThresholds};
+
+const USAGE: &str = "usage: soak <accounts.json>";
+
+// List endpoints each tenant cycles through
+const LIST_QUERIES: [(&str, &str); 3] = [
+    ("list_machines", "/api/v1/machine"),
+    ("list_items", "/api/v1/item"),
+    ("list_orders", "/api/v1/order"),
+];
+
+#[derive(Debug, Clone)]
+struct SoakConfig {
+    base_url: String,
+    duration: Duration,
+    machines_per_tenant: usize,
+    heartbeat_interval: Duration,
+    list_interval: Duration,
+    login_interval: Duration,
+    request_timeout: Duration,
+    thresholds: SoakThresholds,
+    report_path: Option<String>,
+}
+
+impl SoakConfig {
+    fn from_env() -> Result<Self> {
+        let config = Self {
+            base_url: env::var("SOAK_BASE_URL")
+                .unwrap_or_else(|_| "http://localhost:5002".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            duration: Duration::from_secs(env_or("SOAK_DURATION_SECS", 60)?),
+            machines_per_tenant: env_or("SOAK_MACHINES_PER_TENANT", 100)?,
+            heartbeat_interval: Duration::from_millis(env_or("SOAK_HEARTBEAT_INTERVAL_MS", 1000)?),
+            list_interval: Duration::from_millis(env_or("SOAK_LIST_INTERVAL_MS", 2000)?),
+            login_interval: Duration::from_secs(env_or("SOAK_LOGIN_INTERVAL_SECS", 300)?),
+            request_timeout: Duration::from_secs(env_or("SOAK_REQUEST_TIMEOUT_SECS", 10)?),
+            thresholds: SoakThresholds {
+                max_p99_ms: env_opt("SOAK_MAX_P99_MS")?,
+                max_error_rate: env_opt("SOAK_MAX_ERROR_RATE")?,
+            },
+            report_path: env::var("SOAK_REPORT").ok().filter(|path| !path.is_empty()),
+        };
+        if [
+            config.heartbeat_interval,
+            config.list_interval,
+            config.login_interval,
+        ]
+        .contains(&Duration::ZERO)
+        {
+            return Err(anyhow!("Soak intervals must be greater than zero"));
+        }
+        Ok(config)
+    }
+}
+
+fn env_opt<T: FromStr>(key: &str) -> Result<Option<T>> {
+    match env::var(key) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid {}: {}", key, value)),
+        _ => Ok(None),
+    }
+}
+
+fn env_or<T: FromStr>(key: &str, default: T) -> Result<T> {
+    Ok(env_opt(key)?.unwrap_or(default))
+}
+
+// A signed-in tenant; the token is replaced on every sign-in
+struct Session {
+    tenant_id: Uuid,
+    token: RwLock<String>,
+}
+
+impl Session {
+    async fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .bearer_auth(self.token.read().await.as_str())
+            .header("X-Tenant-ID", self.tenant_id.to_string())
+    }
+}
+
+// Sends a request, recording its latency under `operation`
+async fn timed(
+    recorder: &LatencyRecorder,
+    operation: &str,
+    request: RequestBuilder,
+) -> Option<reqwest::Response> {
+    let started = Instant::now();
+    let response = request.send().await;
+    let ok = matches!(&response, Ok(response) if response.status().is_success());
+    recorder.record(operation, started.elapsed(), ok);
+    response
+        .ok()
+        .filter(|response| response.status().is_success())
+}
+
+async fn login(
+    client: &Client,
+    config: &SoakConfig,
+    recorder: &LatencyRecorder,
+    account: &LoginRequest,
+) -> Option<AuthResponse> {
+    let request = client
+        .post(format!("{}/api/v1/auth/login", config.base_url))
+        .json(account);
+    timed(recorder, "login", request).await?.json().await.ok()
+}
+
+// Runs one tenant's load until the deadline, returning how many machines sent heartbeats
+async fn run_tenant(
+    client: Client,
+    config: Arc<SoakConfig>,
+    recorder: Arc<LatencyRecorder>,
+    account: LoginRequest,
+    deadline: Instant,
+) -> usize {
+    let Some(auth) = login(&client, &config, &recorder, &account).await else {
+        tracing::warn!("Sign-in failed for {}", account.email);
+        return 0;
+    };
+    let session = Arc::new(Session {
+        tenant_id: auth.tenant.id,
+        token: RwLock::new(auth.access_token),
+    });
+
+    let request = session
+        .authorize(client.get(format!("{}/api/v1/machine", config.base_url)))
+        .await;
+    let machine_ids: Vec<Uuid> = match timed(&recorder, "list_machines", request).await {
+        Some(response) => response
+            .json::<Vec<serde_json::Value>>()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|machine| machine["id"].as_str()?.parse().ok())
+            .take(config.machines_per_tenant)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut tasks = Vec::new();
+
+    // Heartbeats, staggered so the machines of a tenant don't all beat at once
+    for (index, machine_id) in machine_ids.iter().copied().enumerate() {
+        let (client, config, recorder, session) = (
+            client.clone(),
+            config.clone(),
+            recorder.clone(),
+            session.clone(),
+        );
+        let offset = config.heartbeat_interval * index as u32 / machine_ids.len() as u32;
+        tasks.push(tokio::spawn(async move {
+            let mut ticks =
+                tokio::time::interval_at(Instant::now() + offset, config.heartbeat_interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let url = format!(
+                "{}/api/v1/machine/{}/heartbeat",
+                config.base_url, machine_id
+            );
+            let mut sequence = 0u64;
+            while ticks.tick().await < deadline {
+                sequence += 1;
+                let heartbeat = HeartbeatRequest {
+                    status: MachineStatus::Idle,
+                    action: None,
+                    payload: Some(json!({ "soak": true, "sequence": sequence })),
+                    metadata: None,
+                    config_version: None,
+                    command_results: None,
+                };
+                let request = session.authorize(client.post(&url).json(&heartbeat)).await;
+                timed(&recorder, "heartbeat", request).await;
+            }
+        }));
+    }
+
+    // List queries
+    {
+        let (client, config, recorder, session) = (
+            client.clone(),
+            config.clone(),
+            recorder.clone(),
+            session.clone(),
+        );
+        tasks.push(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(config.list_interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            for (operation, path) in LIST_QUERIES.iter().cycle() {
+                if ticks.tick().await >= deadline {
+                    break;
+                }
+                let request = session
+                    .authorize(client.get(format!("{}{}", config.base_url, path)))
+                    .await;
+                timed(&recorder, operation, request).await;
+            }
+        }));
+    }
+
+    // Repeated sign-ins
+    {
+        let (client, config, recorder, session) = (
+            client.clone(),
+            config.clone(),
+            recorder.clone(),
+            session.clone(),
+        );
+        tasks.push(tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(
+                Instant::now() + config.login_interval,
+                config.login_interval,
+            );
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            while ticks.tick().await < deadline {
+                if let Some(auth) = login(&client, &config, &recorder, &account).await {
+                    *session.token.write().await = auth.access_token;
+                }
+            }
+        }));
+    }
+
+    futures::future::join_all(tasks).await;
+    machine_ids.len()
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [accounts_path] = args.as_slice() else {
+        return Err(anyhow!(USAGE));
+    };
+
+    let config = Arc::new(SoakConfig::from_env()?);
+    let accounts: Vec<LoginRequest> = serde_json::from_str(
+        &std::fs::read_to_string(accounts_path)
+            .with_context(|| format!("Failed to read {}", accounts_path))?,
+    )
+    .with_context(|| format!("Invalid accounts file {}", accounts_path))?;
+    if accounts.is_empty() {
+        return Err(anyhow!("No accounts in {}", accounts_path));
+    }
+
+    let client = Client::builder().timeout(config.request_timeout).build()?;
+    let recorder = Arc::new(LatencyRecorder::default());
+    let tenants = accounts.len();
+
+    tracing::info!(
+        "Soaking {} for {}s with {} tenants",
+        config.base_url,
+        config.duration.as_secs(),
+        tenants
+    );
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let runs = accounts.into_iter().map(|account| {
+        tokio::spawn(run_tenant(
+            client.clone(),
+            config.clone(),
+            recorder.clone(),
+            account,
+            deadline,
+        ))
+    });
+    let machines = futures::future::join_all(runs)
+        .await
+        .into_iter()
+        .map(|machines| machines.unwrap_or(0))
+        .sum();
+
+    let report = recorder.report(started.elapsed(), tenants, machines);
+    print!("{}", report.to_table());
+    if let Some(path) = &config.report_path {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)
+            .with_context(|| format!("Failed to write {}", path))?;
+    }
+
+    let violations = config.thresholds.violations(&report);
+    if !violations.is_empty() {
+        return Err(anyhow!(
+            "Soak thresholds exceeded: {}",
+            violations.join("; ")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,252 @@
+//! Deterministic test fixtures, built through the real services so they stay in step with the
+//! models. Only compiled with the `fixtures` feature; integration tests get it through the
+//! dev-dependency on this crate, and the platform admin seed endpoint is mounted with it.
+//!
+//! ```ignore
+//! let fixture = FixtureBuilder::tenant()
+//!     .with_machines(5)
+//!     .with_items(100)
+//!     .build(&database)
+//!     .await?;
+//! ```
+use anyhow::Result;
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    AccessLevel, CallerContext, CreateItemRequest, CreateMachineRequest, CreateTenantRequest,
+    ItemContext, ItemStatus, MachineProtocol, MachineStatus, NewPerson, NewTenantPerson, Person,
+    PersonRole, Quantity, Tenant,
+};
+use crate::schema::{person, tenant_person};
+use crate::services::{DatabaseService, ItemService, MachineService, TenantService};
+
+// Categories fixture items cycle through
+const ITEM_CATEGORIES: [&str; 4] = ["resistor", "capacitor", "connector", "ic"];
+
+/// Builds a tenant with an admin and, on request, machines and items. Everything but the
+/// tenant's subdomain, the admin's email and item part numbers is derived from the record's
+/// index, so two builds hold the same data; the key keeps those unique across builds and can be
+/// fixed with [`FixtureBuilder::with_key`].
+#[derive(Debug, Clone)]
+pub struct FixtureBuilder {
+    name: String,
+    key: String,
+    settings: Option<serde_json::Value>,
+    machines: usize,
+    items: usize,
+}
+
+/// What a [`FixtureBuilder`] created. Machines and items are in index order.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub tenant: Tenant,
+    pub admin: Person,
+    pub machine_ids: Vec<Uuid>,
+    pub item_ids: Vec<Uuid>,
+}
+
+impl Fixture {
+    /// The admin as a caller, for services that check permissions.
+    pub fn caller(&self) -> CallerContext {
+        CallerContext {
+            person_id: self.admin.id,
+            access_level: AccessLevel::Admin,
+        }
+    }
+}
+
+impl FixtureBuilder {
+    pub fn tenant() -> Self {
+        Self {
+            name: "Fixture tenant".to_string(),
+            key: Uuid::new_v4().simple().to_string()[..12].to_string(),
+            settings: None,
+            machines: 0,
+            items: 0,
+        }
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Fixes the key the subdomain (`fixture-<key>`), admin email and item part numbers
+    /// (`FX-<KEY>-<number>`) are made unique with.
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = key.to_lowercase();
+        self
+    }
+
+    pub fn with_settings(mut self, settings: serde_json::Value) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    pub fn with_machines(mut self, count: usize) -> Self {
+        self.machines = count;
+        self
+    }
+
+    pub fn with_items(mut self, count: usize) -> Self {
+        self.items = count;
+        self
+    }
+
+    pub fn tenant_request(&self) -> CreateTenantRequest {
+        CreateTenantRequest {
+            name: self.name.clone(),
+            subdomain: format!("fixture-{}", self.key),
+            settings: self.settings.clone(),
+        }
+    }
+
+    /// The machine at `index`: offline TCP machines spread over three lines, with addresses
+    /// counting up from 10.0.0.1.
+    pub fn machine_request(index: usize) -> CreateMachineRequest {
+        CreateMachineRequest {
+            name: format!("Machine {:03}", index + 1),
+            ip: format!("10.0.{}.{}", index / 250, index % 250 + 1),
+            port: 502,
+            protocol: MachineProtocol::Tcp,
+            status: Some(MachineStatus::Offline),
+            action: None,
+            payload: None,
+            metadata: Some(json!({ "fixture": index })),
+            latitude: None,
+            longitude: None,
+            site: Some(format!("Line {}", index % 3 + 1)),
+            gateway_id: None,
+        }
+    }
+
+    /// The item at `index`: a store part with stock, a unit price and a reorder point. Part
+    /// numbers are unique across tenants, so the internal one carries the key.
+    pub fn item_request(&self, index: usize) -> CreateItemRequest {
+        let number = index + 1;
+        CreateItemRequest {
+            internal_part_number: Some(format!("FX-{}-{:05}", self.key.to_uppercase(), number)),
+            mfr_part_number: Some(format!("MFR-{:05}", number)),
+            manufacturer: "Fixture Parts".to_string(),
+            datasheet: None,
+            lifecycle: None,
+            description: Some(format!("Fixture part {}", number)),
+            category: Some(ITEM_CATEGORIES[index % ITEM_CATEGORIES.len()].to_string()),
+            metadata: None,
+            linked_resources: None,
+            context: ItemContext::Store,
+            quantity: Some(Quantity::from((index % 10) as i32 * 25)),
+            location: Some(format!("Bin {}", index % 20 + 1)),
+            pricing: Some(json!({ "unit_price": (number % 50) as f64 * 0.25 + 0.1 })),
+            lead_time: Some(7),
+            min_stock_level: Some(10),
+            max_stock_level: Some(500),
+            reorder_point: Some(25),
+            vendor_id: None,
+            last_received_date: None,
+            status: Some(ItemStatus::Active),
+            notes: None,
+            inventory_metadata: None,
+        }
+    }
+
+    /// Creates the fixture. Requests are validated like the API does, so a fixture never holds
+    /// data the API would reject.
+    pub async fn build(self, database: &DatabaseService) -> Result<Fixture> {
+        let request = self.tenant_request();
+        request.validate()?;
+        let tenant = TenantService::new(database.clone())
+            .create_tenant(request)
+            .await?;
+
+        let mut conn = database.get_connection().await?;
+        let admin: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Fixture Admin".to_string(),
+                email: format!("admin-{}@fixtures.example.com", self.key),
+                phone: None,
+                global_access: None,
+                is_active: Some(true),
+            })
+            .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+            .get_result(&mut conn)
+            .await?;
+        diesel::insert_into(tenant_person::table)
+            .values(&NewTenantPerson {
+                person_id: admin.id,
+                tenant_id: tenant.id,
+                role: PersonRole::Internal.to_string(),
+                access_level: Some(vec![Some(AccessLevel::Admin.to_string())]),
+                is_primary: Some(true),
+            })
+            .execute(&mut conn)
+            .await?;
+        drop(conn);
+
+        let machines = MachineService::new(database.clone());
+        let mut machine_ids = Vec::with_capacity(self.machines);
+        for index in 0..self.machines {
+            let request = Self::machine_request(index);
+            request.validate()?;
+            machine_ids.push(machines.create_machine(tenant.id, request).await?.id);
+        }
+
+        let items = ItemService::new(database.clone());
+        let mut item_ids = Vec::with_capacity(self.items);
+        for index in 0..self.items {
+            let request = self.item_request(index);
+            request.validate()?;
+            item_ids.push(items.create_item(tenant.id, request).await?.id);
+        }
+
+        Ok(Fixture {
+            tenant,
+            admin,
+            machine_ids,
+            item_ids,
+        })
+    }
+}
+
+// Seed endpoint DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SeedRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    #[validate(range(max = 1000))]
+    pub machines: usize,
+
+    #[serde(default)]
+    #[validate(range(max = 10000))]
+    pub items: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeedResponse {
+    pub tenant_id: Uuid,
+    pub subdomain: String,
+    pub admin_id: Uuid,
+    pub admin_email: String,
+    pub machine_ids: Vec<Uuid>,
+    pub item_ids: Vec<Uuid>,
+}
+
+impl From<Fixture> for SeedResponse {
+    fn from(fixture: Fixture) -> Self {
+        Self {
+            tenant_id: fixture.tenant.id,
+            subdomain: fixture.tenant.subdomain,
+            admin_id: fixture.admin.id,
+            admin_email: fixture.admin.email,
+            machine_ids: fixture.machine_ids,
+            item_ids: fixture.item_ids,
+        }
+    }
+}
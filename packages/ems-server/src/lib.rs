@@ -1,3 +1,5 @@
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 pub mod middleware;
 pub mod models;
 pub mod routes;
@@ -6,27 +8,74 @@ pub mod services;
 pub mod utils;
 
 use anyhow::Result;
-use services::{DatabaseService, SupabaseService};
+use models::AuthBackendKind;
+use services::{
+    auth_backend_from_env, auth_backend_kind_from_env, AccessLevelCache, AuthBackend,
+    DatabaseService, FeatureFlags, HeartbeatBuffer, SupabaseService, TenantCache,
+};
 use std::env;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppState {
     pub database: DatabaseService,
     pub supabase: SupabaseService,
+    pub auth_backend: Arc<dyn AuthBackend>,
+    pub tenant_cache: TenantCache,
+    /// Callers' access levels, so authenticating a request does not look them up each time
+    pub access_level_cache: AccessLevelCache,
+    /// Feature flags, e.g. `state.flags.enabled("mqtt_bridge", tenant_id).await`
+    pub flags: FeatureFlags,
+    /// Domain tenant subdomains live under (TENANT_BASE_DOMAIN), e.g. `ems.example.com` so that
+    /// `acme.ems.example.com` resolves to the `acme` tenant when no X-Tenant-ID is sent
+    pub tenant_base_domain: Option<String>,
+    /// Buffer machine heartbeats are written through in batches; `None` writes each heartbeat
+    /// directly. Started by the server unless HEARTBEAT_BUFFER_ENABLED=false
+    pub heartbeat_buffer: Option<HeartbeatBuffer>,
 }
 
 impl AppState {
     pub async fn new() -> Result<Self> {
         let database = DatabaseService::new().await?;
 
-        // Initialize Supabase service
-        let supabase_url = env::var("SUPABASE_URL")
-            .map_err(|_| anyhow::anyhow!("SUPABASE_URL environment variable is required"))?;
-        let supabase_key = env::var("SUPABASE_ANON_KEY")
-            .map_err(|_| anyhow::anyhow!("SUPABASE_ANON_KEY environment variable is required"))?;
+        // Initialize Supabase service; the local auth backend runs without it
+        let standalone = auth_backend_kind_from_env()? == AuthBackendKind::Local;
+        let (supabase_url, supabase_key) = if standalone {
+            (
+                env::var("SUPABASE_URL").unwrap_or_default(),
+                env::var("SUPABASE_ANON_KEY").unwrap_or_default(),
+            )
+        } else {
+            (
+                env::var("SUPABASE_URL").map_err(|_| {
+                    anyhow::anyhow!("SUPABASE_URL environment variable is required")
+                })?,
+                env::var("SUPABASE_ANON_KEY").map_err(|_| {
+                    anyhow::anyhow!("SUPABASE_ANON_KEY environment variable is required")
+                })?,
+            )
+        };
 
         let supabase = SupabaseService::new(&supabase_url, &supabase_key).await?;
+        let auth_backend = auth_backend_from_env(supabase.clone())?;
+        tracing::info!("Auth backend: {}", auth_backend.kind());
 
-        Ok(Self { database, supabase })
+        let tenant_base_domain = env::var("TENANT_BASE_DOMAIN")
+            .ok()
+            .map(|domain| domain.trim_start_matches('.').to_lowercase())
+            .filter(|domain| !domain.is_empty());
+
+        let flags = FeatureFlags::from_env(database.clone());
+
+        Ok(Self {
+            database,
+            supabase,
+            auth_backend,
+            tenant_cache: TenantCache::from_env(),
+            access_level_cache: AccessLevelCache::from_env(),
+            flags,
+            tenant_base_domain,
+            heartbeat_buffer: None,
+        })
     }
 }
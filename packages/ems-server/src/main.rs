@@ -1,13 +1,38 @@
-use axum::{middleware as axum_middleware, routing::get, Router};
+use axum::{
+    extract::State, http::StatusCode, middleware as axum_middleware, routing::get, Json, Router,
+};
 use dotenv::dotenv;
-use std::{env, path::Path};
+use std::{env, net::SocketAddr, path::Path};
 use tower::ServiceBuilder;
-use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
+use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber;
 
 use ems_server::{
-    middleware::{auth::auth_middleware, tenant::tenant_middleware},
-    routes::{asset, auth, item, job, machine, order, person, tenants},
+    middleware::{
+        access_log::access_log_middleware,
+        auth::{auth_middleware, platform_admin_middleware},
+        etag::{etag_middleware, CachePolicy},
+        instrumentation::query_metrics_middleware,
+        limits::RequestLimits,
+        security::{security_headers_middleware, SecurityHeaders},
+        tenant::tenant_middleware,
+    },
+    models::FingerprintMode,
+    routes::{
+        admin, asset, auth, billing, calendar, consignment, dashboard,
+        frontend::{self, FrontendConfig},
+        ingest, item, job, kiosk, machine, machine_group, notification, numbering, order,
+        order_return, person, printer, quality, quote, recalculation, report, rfq, search,
+        service_client, shipment, skill, status_page, storage, tenants, trace, validation_rule,
+        watch,
+    },
+    services::{
+        AccessLogRetentionWorker, AccessLogWriter, ArchiveWorker, CalibrationWorker,
+        EncryptionService, HeartbeatBuffer, IntegrityCheckWorker, LifecycleWatchWorker,
+        MachineAlertWorker, PrintQueueWorker, RecalculationWorker, ReportScheduler, RlsService,
+        SandboxCleanupWorker,
+    },
+    utils::circuit_breaker::CircuitState,
     AppState,
 };
 
@@ -20,14 +45,110 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     // Initialize App State
-    let app_state = AppState::new().await?;
+    let mut app_state = AppState::new().await?;
+
+    // Refuse to start with a malformed master key rather than failing the first encrypted write
+    if EncryptionService::check_config()? {
+        tracing::info!("Encryption of sensitive columns enabled");
+    } else {
+        tracing::warn!("ENCRYPTION_MASTER_KEY not set; sensitive columns are stored unencrypted");
+    }
+
+    // Reject a misspelled refresh fingerprint mode instead of failing every refresh
+    let fingerprint_mode = FingerprintMode::from_env()?;
+    tracing::info!("Refresh token fingerprint mode: {}", fingerprint_mode);
+
+    // Verify tenant isolation policies before serving
+    RlsService::new(app_state.database.clone())
+        .check_on_startup(RlsService::mode_from_env()?)
+        .await?;
+
+    // Start the scheduled report delivery worker unless disabled
+    if env::var("REPORT_SCHEDULER_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        ReportScheduler::from_env(app_state.database.clone())?.spawn();
+        tracing::info!("Report scheduler started");
+    }
+
+    // Start the label print queue worker unless disabled
+    if env::var("PRINT_QUEUE_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        PrintQueueWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Print queue worker started");
+    }
+
+    // Start the machine alert rule worker unless disabled
+    if env::var("MACHINE_ALERTS_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        MachineAlertWorker::from_env(app_state.database.clone())?.spawn();
+        tracing::info!("Machine alert worker started");
+    }
+
+    // Start the calibration due date check unless disabled
+    if env::var("CALIBRATION_CHECK_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        CalibrationWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Calibration check started");
+    }
+
+    // Start archiving of closed jobs and orders unless disabled
+    if env::var("ARCHIVE_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        ArchiveWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Archiving of closed jobs and orders started");
+    }
+
+    // Start the expired sandbox cleanup unless disabled
+    if env::var("SANDBOX_CLEANUP_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        SandboxCleanupWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Sandbox cleanup started");
+    }
+
+    // Start the recalculation task worker unless disabled
+    if env::var("RECALCULATION_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        RecalculationWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Recalculation worker started");
+    }
+
+    // Start the scheduled reference integrity check unless disabled
+    if env::var("INTEGRITY_CHECK_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        IntegrityCheckWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Integrity check started");
+    }
+
+    // Start deleting access log entries past their tenant's retention unless disabled
+    if env::var("ACCESS_LOG_RETENTION_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        AccessLogRetentionWorker::from_env(app_state.database.clone()).spawn();
+        tracing::info!("Access log retention started");
+    }
+
+    // Start the part lifecycle watch when a part data provider is configured, unless disabled
+    if env::var("LIFECYCLE_WATCH_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        match LifecycleWatchWorker::from_env(app_state.database.clone())? {
+            Some(worker) => {
+                worker.spawn();
+                tracing::info!("Lifecycle watch started");
+            }
+            None => tracing::warn!("PART_DATA_API_URL not set; lifecycle watch disabled"),
+        }
+    }
+
+    // Write heartbeats through the buffer unless disabled
+    if env::var("HEARTBEAT_BUFFER_ENABLED").unwrap_or_else(|_| "true".to_string()) != "false" {
+        app_state.heartbeat_buffer = Some(HeartbeatBuffer::spawn(app_state.database.clone()));
+        tracing::info!("Heartbeat write buffer started");
+    }
 
     // Get static files directory from environment
     let static_files_dir = env::var("STATIC_FILES_DIR").unwrap_or_else(|_| "./static".to_string());
 
+    // Body size and time limits for every request
+    let request_limits = RequestLimits::from_env();
+
+    // Tenant API requests are logged unless disabled
+    let access_log = (env::var("ACCESS_LOG_ENABLED").unwrap_or_else(|_| "true".to_string())
+        != "false")
+        .then(|| AccessLogWriter::spawn(app_state.database.clone()));
+
     // Build the application with routes and middleware
     let mut app = Router::new()
         .route("/health", get(health_check))
+        .route("/health/ready", get(readiness_check))
         // API routes
         .nest("/api/v1/auth", auth::routes())
         // Protected API routes (require auth and tenant isolation)
@@ -52,42 +173,261 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 auth_middleware,
             )),
         )
+        // Orders and machines change status while they are on screen, so clients revalidate
+        // them on every use; the item and asset catalogues may be reused for a short while
         .nest(
             "/api/v1/order",
-            order::routes().layer(axum_middleware::from_fn_with_state(
+            order::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    CachePolicy::revalidate(),
+                    etag_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/item",
+            item::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    CachePolicy::max_age(30),
+                    etag_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/asset",
+            asset::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    CachePolicy::max_age(30),
+                    etag_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/machine",
+            machine::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    CachePolicy::revalidate(),
+                    etag_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/machine-groups",
+            machine_group::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    CachePolicy::revalidate(),
+                    etag_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/calendar",
+            calendar::routes().layer(axum_middleware::from_fn_with_state(
                 app_state.clone(),
                 auth_middleware,
             )),
         )
         .nest(
-            "/api/v1/item",
-            item::routes().layer(axum_middleware::from_fn_with_state(
+            "/api/v1/consignment",
+            consignment::routes().layer(axum_middleware::from_fn_with_state(
                 app_state.clone(),
                 auth_middleware,
             )),
         )
+        // Shop-floor kiosks (terminals call in with their kiosk key, limited to kiosk actions)
         .nest(
-            "/api/v1/asset",
-            asset::routes().layer(axum_middleware::from_fn_with_state(
+            "/api/v1/kiosk",
+            kiosk::routes().layer(axum_middleware::from_fn_with_state(
                 app_state.clone(),
                 auth_middleware,
             )),
         )
         .nest(
-            "/api/v1/machine",
-            machine::routes().layer(axum_middleware::from_fn_with_state(
+            "/api/v1/dashboards",
+            dashboard::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/report",
+            report::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/search",
+            search::routes().layer(axum_middleware::from_fn_with_state(
                 app_state.clone(),
                 auth_middleware,
             )),
-        );
+        )
+        .nest(
+            "/api/v1/skill",
+            skill::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/trace",
+            trace::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/watches",
+            watch::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/notifications",
+            notification::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/shipment",
+            shipment::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/return",
+            order_return::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/quote",
+            quote::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/rfq",
+            rfq::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/quality",
+            quality::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/status-page",
+            status_page::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/validation-rules",
+            validation_rule::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/printer",
+            printer::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        // Platform operator API (requires the platform_admin global access scope)
+        .nest(
+            "/api/v1/admin",
+            admin::routes()
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    platform_admin_middleware,
+                ))
+                .layer(axum_middleware::from_fn_with_state(
+                    app_state.clone(),
+                    auth_middleware,
+                )),
+        )
+        .nest(
+            "/api/v1/billing",
+            billing::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/ingest-sources",
+            ingest::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/recalculations",
+            recalculation::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/numbering-sequences",
+            numbering::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        .nest(
+            "/api/v1/service-clients",
+            service_client::routes().layer(axum_middleware::from_fn_with_state(
+                app_state.clone(),
+                auth_middleware,
+            )),
+        )
+        // Payment provider webhooks (signed by the provider; no tenant or auth)
+        .nest("/api/v1/webhooks", billing::webhook_routes())
+        // Events pushed by external systems (the source token decides the tenant; no auth)
+        .nest("/api/v1/ingest", ingest::ingest_routes())
+        // Vendor answers to RFQs (the token in the emailed link decides the tenant; no auth)
+        .nest("/api/v1/rfq-responses", rfq::response_routes())
+        // Public status pages (the token in the URL decides the tenant; no auth)
+        .nest("/api/v1/public-status", status_page::public_routes())
+        // Downloads through local storage signed URLs (the signature grants access; no auth)
+        .nest("/api/v1/storage", storage::routes());
 
     // Only add static file serving if the directory exists
     if Path::new(&static_files_dir).exists() {
         tracing::info!("Static files directory found: {}", static_files_dir);
         tracing::info!("Adding static file serving for frontend");
-        app = app.fallback_service(
-            ServeDir::new(&static_files_dir).append_index_html_on_directories(true),
-        );
+        app = app.fallback_service(frontend::routes(FrontendConfig::from_env(
+            &static_files_dir,
+        )));
     } else {
         tracing::warn!("Static files directory not found: {}", static_files_dir);
         tracing::info!("Running in API-only mode (no frontend static files)");
@@ -95,17 +435,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         app = app.fallback(api_only_fallback);
     }
 
+    // Inside the tenant middleware, which decides the tenant a request is logged for
+    if let Some(access_log) = access_log {
+        app = app.layer(axum_middleware::from_fn_with_state(
+            access_log,
+            access_log_middleware,
+        ));
+    }
+
     let app = app
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive()),
+                .layer(CorsLayer::permissive())
+                .layer(CompressionLayer::new())
+                .layer(request_limits.timeout_layer()),
         )
+        .layer(request_limits.body_limit())
         // Tenant middleware should run before auth middleware
         .layer(axum_middleware::from_fn_with_state(
             app_state.clone(),
             tenant_middleware,
         ))
+        // Outside the tenant middleware, so its tenant lookups count against the endpoint
+        .layer(axum_middleware::from_fn(query_metrics_middleware))
+        // Outermost, so responses the tenant middleware rejects carry the headers too
+        .layer(axum_middleware::from_fn_with_state(
+            SecurityHeaders::from_env(),
+            security_headers_middleware,
+        ))
         .with_state(app_state);
 
     // Start server
@@ -123,7 +481,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let listener = tokio::net::TcpListener::bind(&address).await?;
-    axum::serve(listener, app).await?;
+    // Peer addresses feed the client fingerprints refresh tokens are bound to
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -132,6 +495,29 @@ async fn health_check() -> &'static str {
     "OK"
 }
 
+// Ready while the database answers. An open Supabase circuit only degrades sign-in, so it is
+// reported without failing the check.
+async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let database_ready = state.database.shared().get_connection().await.is_ok();
+    let supabase = state.supabase.breaker_status();
+
+    let (code, status) = match (database_ready, supabase.state) {
+        (false, _) => (StatusCode::SERVICE_UNAVAILABLE, "unavailable"),
+        (true, CircuitState::Closed) => (StatusCode::OK, "ready"),
+        (true, _) => (StatusCode::OK, "degraded"),
+    };
+
+    (
+        code,
+        Json(serde_json::json!({
+            "status": status,
+            "database": if database_ready { "ok" } else { "unreachable" },
+            "supabase": supabase,
+            "degraded_login": state.supabase.degraded_login_max_age.is_some(),
+        })),
+    )
+}
+
 // Fallback handler for when running without frontend static files
 async fn api_only_fallback() -> (axum::http::StatusCode, &'static str) {
     (
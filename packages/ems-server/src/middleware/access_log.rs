@@ -0,0 +1,57 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use std::{net::SocketAddr, time::Instant};
+
+use crate::middleware::tenant::TenantContext;
+use crate::models::{AccessLogActor, NewApiAccessLog};
+use crate::services::AccessLogWriter;
+use crate::utils::{fingerprint::ClientInfo, query_metrics::endpoint_label};
+
+/// Records the tenant API requests in the tenant's access log, with the caller the auth
+/// middleware identified. Runs inside the tenant middleware, which decides the tenant.
+pub async fn access_log_middleware(
+    State(writer): State<AccessLogWriter>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let tenant_id = req
+        .extensions()
+        .get::<TenantContext>()
+        .map(|tenant_context| tenant_context.tenant_id);
+    let Some(tenant_id) = tenant_id.filter(|_| path.starts_with("/api/")) else {
+        return next.run(req).await;
+    };
+
+    let method = req.method().to_string();
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| *peer);
+    let client = ClientInfo::from_parts(req.headers(), peer);
+    let created_at = Utc::now();
+    let started = Instant::now();
+
+    let response = next.run(req).await;
+
+    let actor = response.extensions().get::<AccessLogActor>().copied();
+    writer.record(NewApiAccessLog {
+        tenant_id,
+        endpoint: endpoint_label(&method, &path),
+        method,
+        path,
+        status: i32::from(response.status().as_u16()),
+        latency_ms: i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX),
+        actor_type: actor.map(|actor| actor.actor_type.to_string()),
+        actor_id: actor.map(|actor| actor.actor_id),
+        ip: client.ip.map(|ip| ip.to_string()),
+        user_agent: client.user_agent,
+        created_at,
+    });
+
+    response
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Request, State},
+    extract::{OriginalUri, Request, State},
     http::{HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
@@ -7,7 +7,23 @@ use axum::{
 use uuid::Uuid;
 
 use crate::middleware::tenant::TenantContext;
-use crate::{services::AuthService, utils::AuthUtils, AppState};
+use crate::{
+    models::{
+        AccessLevel, AccessLogActor, AccessLogActorType, CallerContext, KioskContext,
+        MachineKeyContext,
+    },
+    services::{
+        AdminService, AuthService, KioskService, MachineCredentialService, PersonService,
+        ServiceClientService,
+    },
+    utils::{
+        kiosk::{kiosk_key_allows, KIOSK_KEY_PREFIX},
+        machine_credential::{machine_key_allows, MACHINE_KEY_PREFIX},
+        service_scope::{parse_scopes, required_scope, scopes_allow, ADMIN_SCOPE, SERVICE_ROLE},
+        AuthUtils,
+    },
+    AppState,
+};
 
 pub async fn auth_middleware(
     State(state): State<AppState>,
@@ -28,6 +44,50 @@ pub async fn auth_middleware(
 
     let token = &auth_str[7..]; // Remove "Bearer " prefix
 
+    // Provisioned machines send their API key instead of a JWT; it only reaches their own
+    // endpoints in the tenant it was issued in, and gateways' heartbeat batches
+    if token.starts_with(MACHINE_KEY_PREFIX) {
+        let credential = MachineCredentialService::new(state.database)
+            .authenticate(token)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        match req.extensions().get::<TenantContext>() {
+            Some(tenant_context) if tenant_context.tenant_id == credential.tenant_id => {}
+            _ => return Err(StatusCode::FORBIDDEN),
+        }
+        if !machine_key_allows(req.method(), &request_path(&req), credential.machine_id) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        req.extensions_mut().insert(MachineKeyContext {
+            machine_id: credential.machine_id,
+        });
+        let actor = AccessLogActor::new(AccessLogActorType::Machine, credential.machine_id);
+        return Ok(with_actor(next.run(req).await, actor));
+    }
+
+    // Kiosk terminals send their API key too; it only reaches the kiosk endpoints for the
+    // actions the terminal allows, with operators badging in for each action
+    if token.starts_with(KIOSK_KEY_PREFIX) {
+        let terminal = KioskService::new(state.database)
+            .authenticate(token)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        match req.extensions().get::<TenantContext>() {
+            Some(tenant_context) if tenant_context.tenant_id == terminal.tenant_id => {}
+            _ => return Err(StatusCode::FORBIDDEN),
+        }
+        if !kiosk_key_allows(req.method(), &request_path(&req), &terminal.actions) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        req.extensions_mut().insert(KioskContext {
+            terminal_id: terminal.id,
+        });
+        let actor = AccessLogActor::new(AccessLogActorType::Kiosk, terminal.id);
+        return Ok(with_actor(next.run(req).await, actor));
+    }
+
     // Verify JWT token
     let claims = AuthUtils::verify_jwt_token(token).map_err(|_| StatusCode::UNAUTHORIZED)?;
 
@@ -50,7 +110,7 @@ pub async fn auth_middleware(
     }
 
     // Check if token is blacklisted (for access tokens, we could also check refresh tokens)
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service = AuthService::new(state.database.clone(), state.supabase, state.auth_backend);
     if auth_service
         .is_token_blacklisted(token, token_tenant_id)
         .await
@@ -59,8 +119,86 @@ pub async fn auth_middleware(
         return Err(StatusCode::UNAUTHORIZED);
     }
 
+    // Look up the caller's access level so services can check permissions
+    let person_id = Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let mut access_level = PersonService::new(state.database.clone())
+        .with_cache(state.access_level_cache)
+        .get_access_level(token_tenant_id, person_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Machine tokens act for the client's owner, limited to the endpoints their scopes cover
+    let mut actor = AccessLogActor::new(AccessLogActorType::Person, person_id);
+    if claims.role == SERVICE_ROLE {
+        let client_id = claims
+            .azp
+            .as_deref()
+            .and_then(|azp| Uuid::parse_str(azp).ok())
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        ServiceClientService::new(state.database)
+            .active_client(token_tenant_id, client_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+
+        let scopes = parse_scopes(claims.scope.as_deref().unwrap_or_default());
+        match required_scope(req.method(), &request_path(&req)) {
+            Some(required) if scopes_allow(&scopes, &required) => {}
+            _ => return Err(StatusCode::FORBIDDEN),
+        }
+
+        // Only the admin scope passes on the owner's admin permissions
+        if !scopes.iter().any(|scope| scope == ADMIN_SCOPE) {
+            access_level = AccessLevel::Standard;
+        }
+        actor = AccessLogActor::new(AccessLogActorType::ServiceClient, client_id);
+    }
+
     // Add claims to request extensions for later use
     req.extensions_mut().insert(claims);
+    req.extensions_mut().insert(CallerContext {
+        person_id,
+        access_level,
+    });
+
+    Ok(with_actor(next.run(req).await, actor))
+}
+
+// The access log middleware runs outside the nested routers and learns the caller from the
+// response
+fn with_actor(mut response: Response, actor: AccessLogActor) -> Response {
+    response.extensions_mut().insert(actor);
+    response
+}
+
+// Nested routers see their path without the prefix, so access is decided on the original one
+fn request_path(req: &Request) -> String {
+    req.extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string())
+}
+
+/// Lets only platform admins through; runs after [`auth_middleware`], which identifies the caller.
+pub async fn platform_admin_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    // Pending users have no caller context and are never platform admins
+    let person_id = req
+        .extensions()
+        .get::<CallerContext>()
+        .map(|caller| caller.person_id)
+        .ok_or(StatusCode::FORBIDDEN)?;
+
+    let is_platform_admin = AdminService::new(state.database)
+        .is_platform_admin(person_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !is_platform_admin {
+        return Err(StatusCode::FORBIDDEN);
+    }
 
     Ok(next.run(req).await)
 }
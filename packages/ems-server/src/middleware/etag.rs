@@ -0,0 +1,225 @@
+use axum::{
+    async_trait,
+    body::{Body, HttpBody},
+    extract::{FromRequestParts, OriginalUri, Request, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+use uuid::Uuid;
+
+use crate::models::DataVersion;
+
+/// Bodies of handlers that set no ETag are hashed instead when they are at most this large;
+/// larger ones (or ones streamed without a known length) are passed through untagged
+pub const MAX_ETAG_BODY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Responses differ per user, tenant and language, so caches must key on all three
+const VARY_HEADERS: &str = "Authorization, X-Tenant-ID, Accept-Language";
+
+/// How long clients may reuse a GET response from one router before revalidating it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    max_age: u32,
+}
+
+impl CachePolicy {
+    /// Revalidate on every use, for data that changes while it is on screen.
+    pub const fn revalidate() -> Self {
+        Self { max_age: 0 }
+    }
+
+    /// Reuse for `seconds` before revalidating, for data that rarely changes.
+    pub const fn max_age(seconds: u32) -> Self {
+        Self { max_age: seconds }
+    }
+
+    pub fn cache_control(&self) -> String {
+        if self.max_age == 0 {
+            "private, no-cache".to_string()
+        } else {
+            format!("private, max-age={}, must-revalidate", self.max_age)
+        }
+    }
+}
+
+/// Strong ETag for a response body: the first half of its SHA-256, quoted.
+pub fn body_etag(body: &[u8]) -> String {
+    let digest = format!("{:x}", Sha256::digest(body));
+    format!("\"{}\"", &digest[..32])
+}
+
+/// Weak ETag for a version of the data behind `scope`, the request's path and query, so every
+/// page and filter of a list gets its own tag. Weak because the body is not hashed: two
+/// responses with the same tag carry the same data, not necessarily the same bytes.
+pub fn version_etag(scope: &str, tenant_id: Uuid, version: &DataVersion) -> String {
+    let stamp = format!(
+        "{}\n{}\n{}\n{}",
+        scope,
+        tenant_id,
+        version.rows,
+        version
+            .updated_at
+            .map(|at| at.timestamp_micros())
+            .unwrap_or_default()
+    );
+    let digest = format!("{:x}", Sha256::digest(stamp.as_bytes()));
+    format!("W/\"{}\"", &digest[..32])
+}
+
+/// Lets a GET handler tag its response from the data's [`DataVersion`] and answer 304 Not
+/// Modified before loading the data, rather than have the middleware hash the whole body.
+pub struct Revalidate {
+    scope: String,
+    if_none_match: Option<String>,
+}
+
+impl Revalidate {
+    pub fn etag(&self, tenant_id: Uuid, version: &DataVersion) -> String {
+        version_etag(&self.scope, tenant_id, version)
+    }
+
+    /// 304 Not Modified when the client already holds `etag`.
+    pub fn not_modified(&self, etag: &str) -> Option<Response> {
+        let if_none_match = self.if_none_match.as_deref()?;
+        if !etag_matches(if_none_match, etag) {
+            return None;
+        }
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        set_etag(response.headers_mut(), etag);
+        Some(response)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Revalidate
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Nested routers see the path without their prefix, so scope by the full one
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(&parts.uri, |original| &original.0);
+        Ok(Self {
+            scope: uri
+                .path_and_query()
+                .map(|path| path.as_str().to_string())
+                .unwrap_or_default(),
+            if_none_match: parts
+                .headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// `response` with `etag` set, which the middleware keeps rather than hashing the body.
+pub fn with_etag(etag: &str, response: impl IntoResponse) -> Response {
+    let mut response = response.into_response();
+    set_etag(response.headers_mut(), etag);
+    response
+}
+
+fn set_etag(headers: &mut HeaderMap, etag: &str) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        headers.insert(header::ETAG, value);
+    }
+}
+
+/// Whether an If-None-Match header matches `etag`. Uses the weak comparison RFC 9110 asks for,
+/// so `W/"abc"` matches `"abc"`.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Adds the router's Cache-Control to successful GET responses and answers 304 Not Modified
+/// when the client already holds the tagged version. Handlers tag their responses from the
+/// data's version with [`Revalidate`]; responses they leave untagged are tagged with a hash of
+/// the body instead. Handlers that set their own Cache-Control keep it.
+pub async fn etag_middleware(
+    State(policy): State<CachePolicy>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return next.run(req).await;
+    }
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let mut response = next.run(req).await;
+    // Handlers that answered 304 themselves still need the caching headers
+    if response.status() == StatusCode::NOT_MODIFIED
+        && response.headers().contains_key(header::ETAG)
+    {
+        add_cache_headers(response.headers_mut(), policy);
+        return response;
+    }
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let (etag, body) = match parts.headers.get(header::ETAG).cloned() {
+        Some(etag) => (etag, body),
+        None => {
+            let fits = body
+                .size_hint()
+                .exact()
+                .is_some_and(|len| len <= MAX_ETAG_BODY_BYTES);
+            if !fits {
+                return Response::from_parts(parts, body);
+            }
+            let bytes = match axum::body::to_bytes(body, MAX_ETAG_BODY_BYTES as usize).await {
+                Ok(bytes) => bytes,
+                Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            };
+            let etag = HeaderValue::from_str(&body_etag(&bytes))
+                .expect("hex digest is a valid header value");
+            (etag, Body::from(bytes))
+        }
+    };
+
+    add_cache_headers(&mut parts.headers, policy);
+
+    let not_modified = match (&if_none_match, etag.to_str()) {
+        (Some(if_none_match), Ok(etag)) => etag_matches(if_none_match, etag),
+        _ => false,
+    };
+    parts.headers.insert(header::ETAG, etag);
+
+    if not_modified {
+        let mut response = StatusCode::NOT_MODIFIED.into_response();
+        for name in [header::ETAG, header::CACHE_CONTROL, header::VARY] {
+            for value in parts.headers.get_all(&name) {
+                response.headers_mut().append(name.clone(), value.clone());
+            }
+        }
+        return response;
+    }
+
+    Response::from_parts(parts, body)
+}
+
+fn add_cache_headers(headers: &mut HeaderMap, policy: CachePolicy) {
+    if !headers.contains_key(header::CACHE_CONTROL) {
+        if let Ok(value) = HeaderValue::from_str(&policy.cache_control()) {
+            headers.insert(header::CACHE_CONTROL, value);
+        }
+    }
+    headers.append(header::VARY, HeaderValue::from_static(VARY_HEADERS));
+}
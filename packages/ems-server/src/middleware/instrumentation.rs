@@ -0,0 +1,10 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::utils::query_metrics::{endpoint_label, scope_endpoint};
+
+/// Counts the database queries a request runs against its endpoint, for the slow-query log and
+/// the platform query metrics.
+pub async fn query_metrics_middleware(req: Request, next: Next) -> Response {
+    let endpoint = endpoint_label(req.method().as_str(), req.uri().path());
+    scope_endpoint(endpoint, next.run(req)).await
+}
@@ -0,0 +1,64 @@
+use axum::extract::DefaultBodyLimit;
+use std::{env, time::Duration};
+use tower_http::timeout::TimeoutLayer;
+
+/// Largest request body accepted by default when MAX_REQUEST_BODY_BYTES is not set
+pub const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Largest body accepted by the bulk import routes (people, vendor price lists)
+pub const MAX_IMPORT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Seconds a request may take by default when REQUEST_TIMEOUT_SECONDS is not set
+pub const DEFAULT_REQUEST_TIMEOUT_SECONDS: u64 = 60;
+
+/// Body size and time limits applied to every request. Bodies over the limit are refused with
+/// 413 while they are read, so an oversized upload never sits in memory whole; routes taking
+/// uploads raise the limit for themselves with [`DefaultBodyLimit`]. Requests still running
+/// after the timeout get 408. The timeout covers the handler, not streaming the response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequestLimits {
+    pub max_body_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl RequestLimits {
+    pub fn new(max_body_bytes: usize, timeout: Duration) -> Self {
+        Self {
+            max_body_bytes,
+            timeout,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let max_body_bytes = env::var("MAX_REQUEST_BODY_BYTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+        let timeout = env::var("REQUEST_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECONDS);
+        Self::new(max_body_bytes, Duration::from_secs(timeout))
+    }
+
+    /// Default body limit for every route that does not set its own
+    pub fn body_limit(&self) -> DefaultBodyLimit {
+        DefaultBodyLimit::max(self.max_body_bytes)
+    }
+
+    /// Answers 408 Request Timeout for requests running longer than the timeout
+    pub fn timeout_layer(&self) -> TimeoutLayer {
+        TimeoutLayer::new(self.timeout)
+    }
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_BODY_BYTES,
+            Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECONDS),
+        )
+    }
+}
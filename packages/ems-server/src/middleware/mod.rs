@@ -1,5 +1,17 @@
+pub mod access_log;
 pub mod auth;
+pub mod etag;
+pub mod instrumentation;
+pub mod limits;
+pub mod security;
 pub mod tenant;
+pub mod validation;
 
+pub use access_log::*;
 pub use auth::*;
+pub use etag::*;
+pub use instrumentation::*;
+pub use limits::*;
+pub use security::*;
 pub use tenant::*;
+pub use validation::*;
@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::env;
+
+/// HSTS lifetime used when HSTS_MAX_AGE_SECONDS is not set: one year
+pub const DEFAULT_HSTS_MAX_AGE_SECONDS: u64 = 365 * 24 * 60 * 60;
+
+/// Security headers sent with every response.
+///
+/// The API authenticates with bearer tokens, which browsers never attach on their own, so
+/// cross-site requests cannot act as a signed-in user and no CSRF token is needed. Cookie
+/// sessions would change that and need double-submit tokens on mutating routes.
+#[derive(Clone, Debug)]
+pub struct SecurityHeaders {
+    hsts_max_age: u64,
+}
+
+impl SecurityHeaders {
+    /// `hsts_max_age` of 0 leaves Strict-Transport-Security out, for plain-HTTP development.
+    pub fn new(hsts_max_age: u64) -> Self {
+        Self { hsts_max_age }
+    }
+
+    pub fn from_env() -> Self {
+        let hsts_max_age = env::var("HSTS_MAX_AGE_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_HSTS_MAX_AGE_SECONDS);
+        Self::new(hsts_max_age)
+    }
+
+    /// Headers to add, in the order they are sent.
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = vec![
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+            (
+                header::CONTENT_SECURITY_POLICY,
+                HeaderValue::from_static("frame-ancestors 'none'"),
+            ),
+            // For browsers that predate frame-ancestors
+            (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+            (
+                header::REFERRER_POLICY,
+                HeaderValue::from_static("strict-origin-when-cross-origin"),
+            ),
+        ];
+        if self.hsts_max_age > 0 {
+            let hsts = format!("max-age={}; includeSubDomains", self.hsts_max_age);
+            if let Ok(value) = HeaderValue::from_str(&hsts) {
+                headers.insert(0, (header::STRICT_TRANSPORT_SECURITY, value));
+            }
+        }
+        headers
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new(DEFAULT_HSTS_MAX_AGE_SECONDS)
+    }
+}
+
+/// Adds the security headers to every response, keeping any a handler set itself.
+pub async fn security_headers_middleware(
+    State(security): State<SecurityHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    for (name, value) in security.headers() {
+        if !response.headers().contains_key(&name) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+    response
+}
@@ -1,12 +1,16 @@
 use axum::{
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
 };
 use uuid::Uuid;
 
-use crate::{services::TenantService, AppState};
+use crate::{
+    services::{DatabaseService, TenantService},
+    utils::i18n::{negotiate, scope_locale, Locale},
+    AppState,
+};
 
 pub struct TenantMiddleware;
 
@@ -27,23 +31,61 @@ pub async fn tenant_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Extract tenant ID from X-Tenant-ID header
-    let tenant_id = if let Some(tenant_header) = headers.get("X-Tenant-ID") {
+    let tenant_service =
+        TenantService::new(state.database.clone()).with_cache(state.tenant_cache.clone());
+    let accept_language = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok());
+
+    // Resolve the tenant from the X-Tenant-ID header, falling back to the Host subdomain
+    let lookup = if let Some(tenant_header) = headers.get("X-Tenant-ID") {
         let tenant_id_str = tenant_header
             .to_str()
             .map_err(|_| StatusCode::BAD_REQUEST)?;
 
         let tenant_uuid = Uuid::parse_str(tenant_id_str).map_err(|_| StatusCode::BAD_REQUEST)?;
 
+        Some(tenant_service.get_tenant_by_id(tenant_uuid).await)
+    } else if let Some(subdomain) = headers
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .and_then(|host| tenant_subdomain(host, state.tenant_base_domain.as_deref()))
+    {
+        Some(tenant_service.get_tenant_by_subdomain(&subdomain).await)
+    } else {
+        None
+    };
+
+    let (tenant_id, tenant_locale) = if let Some(lookup) = lookup {
         // Validate that the tenant exists and is active
-        let tenant_service = TenantService::new(state.database.clone());
-        match tenant_service.get_tenant_by_id(tenant_uuid).await {
-            Ok(Some(tenant)) if tenant.is_active.unwrap_or(false) => tenant_uuid,
+        let tenant = match lookup {
+            Ok(Some(tenant)) if tenant.is_active.unwrap_or(false) => tenant,
+            // Admins of a suspended tenant can still reach billing to settle payment
+            Ok(Some(tenant)) if is_billing_path(req.uri().path()) => tenant,
             Ok(Some(_)) => return Err(StatusCode::FORBIDDEN), // Tenant exists but inactive
             Ok(None) => return Err(StatusCode::NOT_FOUND),    // Tenant doesn't exist
-            Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+            Err(e) => {
+                tracing::error!("Tenant lookup failed: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        // Open the tenant's dedicated database on first use
+        if let Some(database_url) = &tenant.database_url {
+            if state.database.dedicated_databases_enabled() {
+                state
+                    .database
+                    .register_tenant_database(tenant.id, database_url)
+                    .await
+                    .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+            }
         }
+
+        (tenant.id, tenant.locale())
     } else {
+        // Requests made without a tenant only have the client's language to go on
+        let locale = negotiate(accept_language, Locale::default());
+
         // For certain routes (like auth), we might not require tenant header
         // Check if this is an auth route
         let path = req.uri().path();
@@ -51,20 +93,48 @@ pub async fn tenant_middleware(
             && (path.ends_with("/login")
                 || path.ends_with("/register")
                 || path.ends_with("/person-register")
-                || path.ends_with("/refresh"))
+                || path.ends_with("/refresh")
+                || path.ends_with("/token")
+                || path.ends_with("/verify-email")
+                || path.ends_with("/resend-verification"))
         {
             // Allow auth routes without tenant header
-            return Ok(next.run(req).await);
+            return Ok(scope_locale(locale, next.run(req)).await);
+        }
+
+        // Allow health check endpoints without tenant header
+        if path == "/health" || path == "/health/ready" {
+            return Ok(scope_locale(locale, next.run(req)).await);
+        }
+
+        // Allow payment provider webhooks, which are signed rather than sent for a tenant
+        if path.starts_with("/api/v1/webhooks/") {
+            return Ok(scope_locale(locale, next.run(req)).await);
         }
 
-        // Allow health check endpoint without tenant header
-        if path == "/health" {
-            return Ok(next.run(req).await);
+        // Allow events from ingest sources, whose token decides the tenant
+        if path.starts_with("/api/v1/ingest/") {
+            return Ok(scope_locale(locale, next.run(req)).await);
+        }
+
+        // Allow vendor answers to RFQs, whose token decides the tenant
+        if path.starts_with("/api/v1/rfq-responses/") {
+            return Ok(scope_locale(locale, next.run(req)).await);
+        }
+
+        // Allow public status pages, whose token decides the tenant
+        if path.starts_with("/api/v1/public-status/") {
+            return Ok(scope_locale(locale, next.run(req)).await);
+        }
+
+        // Allow downloads through local storage signed URLs, whose signature grants access
+        if path.starts_with("/api/v1/storage/") {
+            return Ok(scope_locale(locale, next.run(req)).await);
         }
 
         // Allow frontend/static routes (anything not starting with /api/) without tenant header
         if !path.starts_with("/api/") {
-            return Ok(next.run(req).await);
+            return Ok(scope_locale(locale, next.run(req)).await);
         }
 
         // For all other API routes, tenant header is required
@@ -75,5 +145,26 @@ pub async fn tenant_middleware(
     let tenant_context = TenantContext { tenant_id };
     req.extensions_mut().insert(tenant_context);
 
-    Ok(next.run(req).await)
+    // Messages follow the client's language, falling back to the tenant's locale
+    let locale = negotiate(accept_language, tenant_locale);
+
+    // Route the request's connections to the tenant's database when it has one
+    Ok(DatabaseService::scope_tenant(tenant_id, scope_locale(locale, next.run(req))).await)
+}
+
+fn is_billing_path(path: &str) -> bool {
+    path == "/api/v1/billing" || path.starts_with("/api/v1/billing/")
+}
+
+/// The tenant subdomain in a Host header under the base domain: with a base domain of
+/// `ems.example.com`, `acme.ems.example.com:8080` gives `acme`. Hosts outside the base domain
+/// and nested subdomains give nothing.
+pub fn tenant_subdomain(host: &str, base_domain: Option<&str>) -> Option<String> {
+    let base_domain = base_domain?;
+    let host = host.split(':').next()?.trim_end_matches('.').to_lowercase();
+    let subdomain = host.strip_suffix(base_domain)?.strip_suffix('.')?;
+    if subdomain.is_empty() || subdomain.contains('.') {
+        return None;
+    }
+    Some(subdomain.to_string())
 }
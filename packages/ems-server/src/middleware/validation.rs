@@ -0,0 +1,171 @@
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        FromRequest, FromRequestParts, Query, Request,
+    },
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::collections::BTreeMap;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::utils::i18n::{current_locale, Locale, Text};
+
+/// JSON body that has been deserialized and passed its `validator` rules. Invalid payloads are
+/// rejected with 422 and the problems for each field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+/// Query string counterpart of [`ValidatedJson`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedQuery<T>(pub T);
+
+#[derive(Debug)]
+pub enum ValidationRejection {
+    Json(JsonRejection),
+    Query(QueryRejection),
+    Fields(ValidationErrors),
+}
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ValidationRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(ValidationRejection::Json)?;
+        value.validate().map_err(ValidationRejection::Fields)?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ValidationRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(ValidationRejection::Query)?;
+        value.validate().map_err(ValidationRejection::Fields)?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+impl IntoResponse for ValidationRejection {
+    fn into_response(self) -> Response {
+        match self {
+            // Malformed JSON keeps axum's status (400, 415 or 422) with a JSON body
+            ValidationRejection::Json(rejection) => (
+                rejection.status(),
+                Json(json!({ "error": rejection.body_text() })),
+            )
+                .into_response(),
+            ValidationRejection::Query(rejection) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": rejection.body_text() })),
+            )
+                .into_response(),
+            ValidationRejection::Fields(errors) => {
+                let locale = current_locale();
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(json!({
+                        "error": locale.text(Text::ValidationFailed),
+                        "fields": localized_field_errors(&errors, locale),
+                    })),
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// Messages for each invalid field, keyed by its path (`items[0].quantity` for nested lists).
+pub fn field_errors(errors: &ValidationErrors) -> BTreeMap<String, Vec<String>> {
+    localized_field_errors(errors, Locale::En)
+}
+
+/// [`field_errors`] in `locale`. Messages set on a rule with `message = ...` are kept as
+/// written.
+pub fn localized_field_errors(
+    errors: &ValidationErrors,
+    locale: Locale,
+) -> BTreeMap<String, Vec<String>> {
+    let mut fields = BTreeMap::new();
+    collect_field_errors(errors, None, locale, &mut fields);
+    fields
+}
+
+fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: Option<&str>,
+    locale: Locale,
+    fields: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, field),
+            None => field.to_string(),
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => fields
+                .entry(path)
+                .or_default()
+                .extend(errors.iter().map(|error| error_message(error, locale))),
+            ValidationErrorsKind::Struct(errors) => {
+                collect_field_errors(errors, Some(&path), locale, fields)
+            }
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    let path = format!("{}[{}]", path, index);
+                    collect_field_errors(errors, Some(&path), locale, fields);
+                }
+            }
+        }
+    }
+}
+
+fn error_message(error: &ValidationError, locale: Locale) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    // Range bounds are stored as floats; whole numbers read better without the ".0"
+    let param = |name: &str| {
+        error.params.get(name).map(|value| match value.as_f64() {
+            Some(number) if number.fract() == 0.0 => format!("{}", number as i64),
+            _ => value.to_string(),
+        })
+    };
+    let bounds = || match (param("min"), param("max"), param("equal")) {
+        (_, _, Some(equal)) => locale.format(Text::Exactly, &[&equal]),
+        (Some(min), Some(max), _) => locale.format(Text::Between, &[&min, &max]),
+        (Some(min), None, _) => locale.format(Text::AtLeast, &[&min]),
+        (None, Some(max), _) => locale.format(Text::AtMost, &[&max]),
+        (None, None, _) => locale.text(Text::OutOfRange).to_string(),
+    };
+
+    match error.code.as_ref() {
+        "length" => locale.format(Text::Length, &[&bounds()]),
+        "range" => bounds(),
+        "email" => locale.text(Text::InvalidEmail).to_string(),
+        "url" => locale.text(Text::InvalidUrl).to_string(),
+        "regex" => locale.text(Text::InvalidFormat).to_string(),
+        "required" => locale.text(Text::Required).to_string(),
+        code => locale.format(Text::Invalid, &[code]),
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::api_access_logs;
+
+// API access log models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = api_access_logs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ApiAccessLog {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub endpoint: String,
+    pub status: i32,
+    pub latency_ms: i32,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = api_access_logs)]
+pub struct NewApiAccessLog {
+    pub tenant_id: Uuid,
+    pub method: String,
+    pub path: String,
+    pub endpoint: String,
+    pub status: i32,
+    pub latency_ms: i32,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Kind of caller an access log entry is attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccessLogActorType {
+    #[serde(rename = "person")]
+    Person,
+    /// A service client's token, logged with the client's ID
+    #[serde(rename = "service_client")]
+    ServiceClient,
+    /// A provisioned machine's API key, logged with the machine's ID
+    #[serde(rename = "machine")]
+    Machine,
+    /// A kiosk terminal's API key, logged with the terminal's ID
+    #[serde(rename = "kiosk")]
+    Kiosk,
+}
+
+impl std::fmt::Display for AccessLogActorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessLogActorType::Person => write!(f, "person"),
+            AccessLogActorType::ServiceClient => write!(f, "service_client"),
+            AccessLogActorType::Machine => write!(f, "machine"),
+            AccessLogActorType::Kiosk => write!(f, "kiosk"),
+        }
+    }
+}
+
+impl From<AccessLogActorType> for String {
+    fn from(actor_type: AccessLogActorType) -> Self {
+        actor_type.to_string()
+    }
+}
+
+/// Who made a request, set on the response by the auth middleware for the access log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessLogActor {
+    pub actor_type: AccessLogActorType,
+    pub actor_id: Uuid,
+}
+
+impl AccessLogActor {
+    pub fn new(actor_type: AccessLogActorType, actor_id: Uuid) -> Self {
+        Self {
+            actor_type,
+            actor_id,
+        }
+    }
+}
+
+// API DTOs
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListAccessLogsQuery {
+    pub actor_type: Option<AccessLogActorType>,
+    pub actor_id: Option<Uuid>,
+    pub method: Option<String>,
+    /// Matches requests whose path contains this, e.g. a machine's ID
+    #[validate(length(min = 1, max = 200))]
+    pub path: Option<String>,
+    pub status: Option<i32>,
+    /// Only requests answered with a status of at least 400
+    pub errors_only: Option<bool>,
+    pub ip: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
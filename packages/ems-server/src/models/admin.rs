@@ -0,0 +1,415 @@
+use chrono::{DateTime, Duration, DurationRound, Months, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{MachineStatus, Tenant};
+use crate::utils::circuit_breaker::CircuitBreakerStatus;
+use crate::utils::diagnostics::{TenantCacheStats, WorkerRunStats};
+use crate::utils::query_metrics::EndpointQueryStats;
+
+/// Entry in a person's `global_access` that lets them operate the platform across tenants.
+/// Unlike a tenant's `admin` access level it is never granted by sign-up; it has to be set on
+/// the person directly.
+pub const PLATFORM_ADMIN_SCOPE: &str = "platform_admin";
+
+/// Whether a person's `global_access` includes the platform admin scope.
+pub fn has_platform_admin_scope(global_access: &[Option<String>]) -> bool {
+    global_access
+        .iter()
+        .flatten()
+        .any(|scope| scope == PLATFORM_ADMIN_SCOPE)
+}
+
+// Enums
+/// How rows with a dangling reference are repaired: deleted when the row means nothing without
+/// what it references, otherwise kept with the reference cleared.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IntegrityRepair {
+    #[serde(rename = "delete")]
+    Delete,
+    #[serde(rename = "clear")]
+    Clear,
+}
+
+impl std::fmt::Display for IntegrityRepair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityRepair::Delete => write!(f, "delete"),
+            IntegrityRepair::Clear => write!(f, "clear"),
+        }
+    }
+}
+
+impl From<IntegrityRepair> for String {
+    fn from(repair: IntegrityRepair) -> Self {
+        repair.to_string()
+    }
+}
+
+impl TryFrom<String> for IntegrityRepair {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "delete" => Ok(IntegrityRepair::Delete),
+            "clear" => Ok(IntegrityRepair::Clear),
+            _ => Err(format!("Invalid integrity repair: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TenantStatus {
+    #[serde(rename = "active")]
+    Active,
+    #[serde(rename = "suspended")]
+    Suspended,
+}
+
+impl std::fmt::Display for TenantStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TenantStatus::Active => write!(f, "active"),
+            TenantStatus::Suspended => write!(f, "suspended"),
+        }
+    }
+}
+
+impl From<TenantStatus> for String {
+    fn from(status: TenantStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for TenantStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "active" => Ok(TenantStatus::Active),
+            "suspended" => Ok(TenantStatus::Suspended),
+            _ => Err(format!("Invalid tenant status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListAdminTenantsQuery {
+    pub status: Option<TenantStatus>,
+    /// Matches tenant names and subdomains containing the text
+    #[validate(length(min = 1, max = 100))]
+    pub search: Option<String>,
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SuspendTenantRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResetTenantAdminRequest {
+    /// Member of the tenant who becomes its admin
+    #[validate(email)]
+    pub email: String,
+    /// Drops every other admin of the tenant to standard access (default true), for when the
+    /// current admins can no longer be trusted or reached
+    pub demote_others: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, QueryableByName)]
+pub struct TenantUsage {
+    #[diesel(sql_type = BigInt)]
+    pub persons: i64,
+    #[diesel(sql_type = BigInt)]
+    pub items: i64,
+    #[diesel(sql_type = BigInt)]
+    pub orders: i64,
+    #[diesel(sql_type = BigInt)]
+    pub jobs: i64,
+    /// Latest order, job or stock movement recorded for the tenant
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminTenantResponse {
+    #[serde(flatten)]
+    pub tenant: Tenant,
+    pub status: TenantStatus,
+    pub dedicated_database: bool,
+    /// `None` when the tenant's dedicated database could not be read
+    pub usage: Option<TenantUsage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetTenantAdminResponse {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub email: String,
+    /// Admins dropped to standard access
+    pub demoted_person_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlatformTenantCounts {
+    pub total: i64,
+    pub active: i64,
+    pub suspended: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlatformDatabaseHealth {
+    pub reachable: bool,
+    /// Size of the shared database
+    pub size_bytes: Option<i64>,
+    pub pool_connections: u32,
+    pub pool_idle_connections: u32,
+    /// Tenants with a dedicated database and how many of those have an open pool
+    pub dedicated_databases: i64,
+    pub dedicated_databases_connected: usize,
+}
+
+/// Connections of one database pool; the shared database has no tenant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabasePoolStats {
+    pub tenant_id: Option<Uuid>,
+    pub max_size: u32,
+    pub connections: u32,
+    pub idle_connections: u32,
+}
+
+/// Work waiting for the background workers, summed over the shared and dedicated databases.
+#[derive(Debug, Default, Serialize, Deserialize, QueryableByName)]
+pub struct QueueDepths {
+    /// Active report schedules past their next run
+    #[diesel(sql_type = BigInt)]
+    pub report_schedules_due: i64,
+    #[diesel(sql_type = BigInt)]
+    pub print_jobs_queued: i64,
+    /// Heartbeats not yet evaluated against machine alert rules
+    #[diesel(sql_type = BigInt)]
+    pub heartbeat_events_pending: i64,
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    pub oldest_pending_heartbeat_at: Option<DateTime<Utc>>,
+    /// Recalculation tasks queued or running
+    #[diesel(sql_type = BigInt)]
+    pub recalculations_queued: i64,
+}
+
+impl QueueDepths {
+    /// Adds the depths of another database's queues.
+    pub fn merge(self, other: QueueDepths) -> QueueDepths {
+        QueueDepths {
+            report_schedules_due: self.report_schedules_due + other.report_schedules_due,
+            print_jobs_queued: self.print_jobs_queued + other.print_jobs_queued,
+            heartbeat_events_pending: self.heartbeat_events_pending
+                + other.heartbeat_events_pending,
+            oldest_pending_heartbeat_at: match (
+                self.oldest_pending_heartbeat_at,
+                other.oldest_pending_heartbeat_at,
+            ) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+            recalculations_queued: self.recalculations_queued + other.recalculations_queued,
+        }
+    }
+}
+
+/// Machine alert webhook deliveries of one tenant within the diagnostics window.
+#[derive(Debug, Serialize, Deserialize, QueryableByName)]
+pub struct TenantWebhookDeliveries {
+    #[diesel(sql_type = diesel::sql_types::Uuid)]
+    pub tenant_id: Uuid,
+    #[diesel(sql_type = BigInt)]
+    pub delivered: i64,
+    #[diesel(sql_type = BigInt)]
+    pub failed: i64,
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    pub last_failure_at: Option<DateTime<Utc>>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryStats {
+    pub window_hours: i64,
+    pub delivered: i64,
+    pub failed: i64,
+    /// Tenants with failed deliveries, most failures first
+    pub failing_tenants: Vec<TenantWebhookDeliveries>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosticsResponse {
+    pub database_pools: Vec<DatabasePoolStats>,
+    /// `None` when the shared database could not be read
+    pub queues: Option<QueueDepths>,
+    /// Background worker runs since the server started
+    pub workers: Vec<WorkerRunStats>,
+    /// `None` when the shared database could not be read
+    pub webhooks: Option<WebhookDeliveryStats>,
+    pub supabase: CircuitBreakerStatus,
+    /// Tenant cache lookups since the server started, the busiest tenants first
+    pub tenant_caches: Vec<TenantCacheStats>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryMetricsResponse {
+    /// Queries at least this long are logged as slow
+    pub slow_query_ms: u64,
+    /// Per-endpoint totals since the server started, most database time first
+    pub endpoints: Vec<EndpointQueryStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlatformHealthResponse {
+    pub tenants: PlatformTenantCounts,
+    pub persons: i64,
+    pub database: PlatformDatabaseHealth,
+    pub supabase: CircuitBreakerStatus,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Runs the integrity checks named, or all of them, and repairs what they find when `repair` is
+/// set.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct IntegrityCheckRequest {
+    /// Check names as `table.column`; every check when left out
+    #[validate(length(min = 1, max = 50))]
+    pub checks: Option<Vec<String>>,
+    pub repair: Option<bool>,
+}
+
+/// Dangling references one check found in one database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityCheckResult {
+    pub check: String,
+    pub description: String,
+    pub references: String,
+    pub repair: IntegrityRepair,
+    /// Dedicated tenant database checked; `None` for the shared database
+    pub database_tenant_id: Option<Uuid>,
+    pub dangling: i64,
+    /// Ids of the first dangling rows
+    pub sample_ids: Vec<Uuid>,
+    /// Rows deleted or cleared; `None` when not repairing
+    pub repaired: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Checks that found dangling references; clean checks are left out
+    pub findings: Vec<IntegrityCheckResult>,
+    pub checks_run: usize,
+    pub dangling: i64,
+    pub repaired: i64,
+    /// Dedicated tenant databases that could not be checked
+    pub failed_databases: Vec<Uuid>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.dangling == 0 && self.failed_databases.is_empty()
+    }
+}
+
+/// Expired tokens purged from the token blacklist.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenBlacklistPurgeReport {
+    pub purged: i64,
+    /// Dedicated tenant databases that could not be purged
+    pub failed_databases: Vec<Uuid>,
+    pub purged_at: DateTime<Utc>,
+}
+
+/// Decommissions machines with no heartbeat for `months` months, or none ever and created that
+/// long ago. `dry_run` lists them without changing anything.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DecommissionStaleMachinesRequest {
+    #[validate(range(min = 1, max = 120))]
+    pub months: u32,
+    /// Only this tenant's machines; every tenant's when left out
+    pub tenant_id: Option<Uuid>,
+    pub dry_run: Option<bool>,
+}
+
+impl DecommissionStaleMachinesRequest {
+    /// Machines last heard from before this are stale
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now.checked_sub_months(Months::new(self.months))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleMachine {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    /// Status before the machine was decommissioned
+    pub status: MachineStatus,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecommissionReport {
+    pub cutoff: DateTime<Utc>,
+    pub machines: Vec<StaleMachine>,
+    /// Machines marked decommissioned; 0 on a dry run
+    pub decommissioned: i64,
+    pub dry_run: bool,
+    /// Dedicated tenant databases that could not be checked
+    pub failed_databases: Vec<Uuid>,
+}
+
+/// Compacts machine telemetry readings and status transitions from days ending more than
+/// `older_than_days` days ago into daily aggregates, deleting the raw rows.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CompactMachineHistoryRequest {
+    #[validate(range(min = 1, max = 3650))]
+    pub older_than_days: u32,
+    /// Only this tenant's machines; every tenant's when left out
+    pub tenant_id: Option<Uuid>,
+}
+
+impl CompactMachineHistoryRequest {
+    /// Start of the UTC day `older_than_days` days ago, so only whole days are compacted
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let before = now - Duration::days(self.older_than_days as i64);
+        before.duration_trunc(Duration::days(1)).unwrap_or(before)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MachineHistoryCompaction {
+    /// Raw telemetry readings folded into daily aggregates
+    pub telemetry_rows: i64,
+    /// Machine, channel and day aggregates written or updated
+    pub telemetry_days: i64,
+    /// Raw status transitions folded into daily aggregates
+    pub status_rows: i64,
+    /// Machine, day and status aggregates written or updated
+    pub status_days: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineHistoryCompactionReport {
+    pub cutoff: DateTime<Utc>,
+    #[serde(flatten)]
+    pub compacted: MachineHistoryCompaction,
+    /// Dedicated tenant databases that could not be compacted
+    pub failed_databases: Vec<Uuid>,
+    pub compacted_at: DateTime<Utc>,
+}
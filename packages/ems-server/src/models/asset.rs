@@ -51,6 +51,15 @@ pub struct Asset {
     pub created_by_id: Uuid,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub lineage_id: Uuid,
+    pub previous_version_id: Option<Uuid>,
+    pub is_current: bool,
+    pub release_status: String,
+    pub released_at: Option<DateTime<Utc>>,
+    pub retain_until: Option<DateTime<Utc>>,
+    pub scan_status: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub blob_id: Option<Uuid>,
 }
 
 #[derive(Debug, Insertable)]
@@ -69,6 +78,13 @@ pub struct NewAsset {
     pub is_active: Option<bool>,
     pub metadata: Option<serde_json::Value>,
     pub created_by_id: Uuid,
+    /// Omit to start a new lineage named after the asset itself
+    pub lineage_id: Option<Uuid>,
+    pub previous_version_id: Option<Uuid>,
+    pub is_current: Option<bool>,
+    /// Omit for released; controlled documents start as draft
+    pub release_status: Option<String>,
+    pub retain_until: Option<DateTime<Utc>>,
 }
 
 #[derive(
@@ -104,6 +120,32 @@ pub struct NewFirmwareSpecific {
     pub requires_manual_update: Option<bool>,
 }
 
+// Electronic signature models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = asset_signatures)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetSignature {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub asset_id: Uuid,
+    pub signed_by_id: Uuid,
+    pub meaning: String,
+    pub comment: Option<String>,
+    pub content_checksum: Option<String>,
+    pub signed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_signatures)]
+pub struct NewAssetSignature {
+    pub tenant_id: Uuid,
+    pub asset_id: Uuid,
+    pub signed_by_id: Uuid,
+    pub meaning: String,
+    pub comment: Option<String>,
+    pub content_checksum: Option<String>,
+}
+
 // Asset Type Enum for common asset types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum AssetTypeEnum {
@@ -117,6 +159,16 @@ pub enum AssetTypeEnum {
     Document,
     #[serde(rename = "certificate")]
     Certificate,
+    /// Snapshots of item datasheets, fetched from the item's datasheet link
+    #[serde(rename = "datasheet")]
+    Datasheet,
+}
+
+impl AssetTypeEnum {
+    /// Controlled documents are released only after an approval signature.
+    pub fn is_controlled(&self) -> bool {
+        matches!(self, AssetTypeEnum::Certificate | AssetTypeEnum::Document)
+    }
 }
 
 impl std::fmt::Display for AssetTypeEnum {
@@ -127,6 +179,7 @@ impl std::fmt::Display for AssetTypeEnum {
             AssetTypeEnum::Report => write!(f, "report"),
             AssetTypeEnum::Document => write!(f, "document"),
             AssetTypeEnum::Certificate => write!(f, "certificate"),
+            AssetTypeEnum::Datasheet => write!(f, "datasheet"),
         }
     }
 }
@@ -147,11 +200,138 @@ impl TryFrom<String> for AssetTypeEnum {
             "report" => Ok(AssetTypeEnum::Report),
             "document" => Ok(AssetTypeEnum::Document),
             "certificate" => Ok(AssetTypeEnum::Certificate),
+            "datasheet" => Ok(AssetTypeEnum::Datasheet),
             _ => Err(format!("Invalid asset type: {}", value)),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AssetReleaseStatus {
+    #[serde(rename = "draft")]
+    Draft,
+    #[serde(rename = "released")]
+    Released,
+}
+
+impl std::fmt::Display for AssetReleaseStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetReleaseStatus::Draft => write!(f, "draft"),
+            AssetReleaseStatus::Released => write!(f, "released"),
+        }
+    }
+}
+
+impl From<AssetReleaseStatus> for String {
+    fn from(status: AssetReleaseStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for AssetReleaseStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "draft" => Ok(AssetReleaseStatus::Draft),
+            "released" => Ok(AssetReleaseStatus::Released),
+            _ => Err(format!("Invalid asset release status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AssetScanStatus {
+    /// Uploaded and waiting for the scanner; the file is quarantined meanwhile
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "clean")]
+    Clean,
+    #[serde(rename = "infected")]
+    Infected,
+    /// The scan could not complete, so the file stays quarantined
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl AssetScanStatus {
+    /// Quarantined files cannot be downloaded
+    pub fn is_quarantined(&self) -> bool {
+        !matches!(self, AssetScanStatus::Clean)
+    }
+}
+
+impl std::fmt::Display for AssetScanStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetScanStatus::Pending => write!(f, "pending"),
+            AssetScanStatus::Clean => write!(f, "clean"),
+            AssetScanStatus::Infected => write!(f, "infected"),
+            AssetScanStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<AssetScanStatus> for String {
+    fn from(status: AssetScanStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for AssetScanStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(AssetScanStatus::Pending),
+            "clean" => Ok(AssetScanStatus::Clean),
+            "infected" => Ok(AssetScanStatus::Infected),
+            "failed" => Ok(AssetScanStatus::Failed),
+            _ => Err(format!("Invalid asset scan status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SignatureMeaning {
+    #[serde(rename = "authored")]
+    Authored,
+    #[serde(rename = "reviewed")]
+    Reviewed,
+    #[serde(rename = "approved")]
+    Approved,
+}
+
+impl std::fmt::Display for SignatureMeaning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureMeaning::Authored => write!(f, "authored"),
+            SignatureMeaning::Reviewed => write!(f, "reviewed"),
+            SignatureMeaning::Approved => write!(f, "approved"),
+        }
+    }
+}
+
+impl From<SignatureMeaning> for String {
+    fn from(meaning: SignatureMeaning) -> Self {
+        meaning.to_string()
+    }
+}
+
+impl TryFrom<String> for SignatureMeaning {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "authored" => Ok(SignatureMeaning::Authored),
+            "reviewed" => Ok(SignatureMeaning::Reviewed),
+            "approved" => Ok(SignatureMeaning::Approved),
+            _ => Err(format!("Invalid signature meaning: {}", value)),
+        }
+    }
+}
+
 // Request/Response DTOs
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateAssetRequest {
@@ -182,6 +362,9 @@ pub struct CreateAssetRequest {
     pub is_active: Option<bool>,
     pub metadata: Option<serde_json::Value>,
 
+    /// The asset cannot be deleted before this time
+    pub retain_until: Option<DateTime<Utc>>,
+
     // Firmware-specific fields (optional)
     pub firmware_details: Option<CreateFirmwareSpecificRequest>,
 }
@@ -229,6 +412,9 @@ pub struct UpdateAssetRequest {
     pub is_active: Option<bool>,
     pub metadata: Option<serde_json::Value>,
 
+    /// The asset cannot be deleted before this time
+    pub retain_until: Option<DateTime<Utc>>,
+
     // Firmware-specific fields (optional)
     pub firmware_details: Option<UpdateFirmwareSpecificRequest>,
 }
@@ -266,6 +452,15 @@ pub struct AssetResponse {
     pub is_active: bool,
     pub metadata: Option<serde_json::Value>,
     pub created_by: PersonSummary,
+    pub lineage_id: Uuid,
+    pub previous_version_id: Option<Uuid>,
+    pub is_current: bool,
+    pub release_status: AssetReleaseStatus,
+    pub released_at: Option<DateTime<Utc>>,
+    pub retain_until: Option<DateTime<Utc>>,
+    /// Antivirus scan of the current file; `None` when it was never scanned
+    pub scan_status: Option<AssetScanStatus>,
+    pub scanned_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
@@ -273,6 +468,13 @@ pub struct AssetResponse {
     pub firmware_details: Option<FirmwareSpecificResponse>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetDownloadResponse {
+    /// Short-lived signed URL of the asset's file
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AssetTypeResponse {
     pub id: Uuid,
@@ -311,6 +513,9 @@ pub struct AssetSummary {
     pub asset_type: String,
     pub file_type: Option<String>,
     pub is_active: bool,
+    pub lineage_id: Uuid,
+    pub is_current: bool,
+    pub release_status: AssetReleaseStatus,
     pub created_at: DateTime<Utc>,
 }
 
@@ -319,6 +524,121 @@ pub struct CreateAssetIdResponse {
     pub id: Uuid,
 }
 
+// Version chain DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAssetVersionRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub version: String,
+
+    /// Omit to keep the description of the superseded version
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+
+    #[validate(length(max = 500))]
+    pub file_path: Option<String>,
+
+    #[validate(range(min = 0))]
+    pub file_size: Option<i64>,
+
+    #[validate(length(max = 50))]
+    pub file_type: Option<String>,
+
+    #[validate(length(max = 64))]
+    pub checksum: Option<String>,
+
+    /// Omit to keep the metadata of the superseded version
+    pub metadata: Option<serde_json::Value>,
+
+    /// Omit to keep the retention period of the superseded version
+    pub retain_until: Option<DateTime<Utc>>,
+
+    /// Omit to carry over the firmware details of the superseded version
+    pub firmware_details: Option<CreateFirmwareSpecificRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetVersionSummary {
+    pub id: Uuid,
+    pub version: Option<String>,
+    pub previous_version_id: Option<Uuid>,
+    pub is_current: bool,
+    pub release_status: AssetReleaseStatus,
+    pub is_active: bool,
+    pub file_path: Option<String>,
+    pub file_size: Option<i64>,
+    pub checksum: Option<String>,
+    pub created_by_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Asset> for AssetVersionSummary {
+    fn from(asset: Asset) -> Self {
+        Self {
+            id: asset.id,
+            version: asset.version,
+            previous_version_id: asset.previous_version_id,
+            is_current: asset.is_current,
+            release_status: AssetReleaseStatus::try_from(asset.release_status)
+                .unwrap_or(AssetReleaseStatus::Released),
+            is_active: asset.is_active.unwrap_or(true),
+            file_path: asset.file_path,
+            file_size: asset.file_size,
+            checksum: asset.checksum,
+            created_by_id: asset.created_by_id,
+            created_at: asset.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetVersionHistoryResponse {
+    pub lineage_id: Uuid,
+    pub current_version_id: Option<Uuid>,
+    /// Newest first
+    pub versions: Vec<AssetVersionSummary>,
+}
+
+// Electronic signature DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SignAssetRequest {
+    /// Approved releases a draft asset
+    pub meaning: SignatureMeaning,
+
+    #[validate(length(max = 1000))]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetSignatureResponse {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub signed_by: PersonSummary,
+    pub meaning: SignatureMeaning,
+    pub comment: Option<String>,
+    /// Asset checksum at signing
+    pub content_checksum: Option<String>,
+    pub signed_at: DateTime<Utc>,
+}
+
+impl AssetSignatureResponse {
+    pub fn new(signature: AssetSignature, signer: Person) -> Self {
+        Self {
+            id: signature.id,
+            asset_id: signature.asset_id,
+            signed_by: PersonSummary {
+                id: signer.id,
+                name: signer.name,
+                email: signer.email,
+            },
+            meaning: SignatureMeaning::try_from(signature.meaning)
+                .unwrap_or(SignatureMeaning::Authored),
+            comment: signature.comment,
+            content_checksum: signature.content_checksum,
+            signed_at: signature.signed_at,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateAssetTypeRequest {
     #[validate(length(min = 1, max = 50))]
@@ -0,0 +1,308 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::AssetScanStatus;
+use crate::schema::*;
+
+lazy_static::lazy_static! {
+    static ref SHA256_HEX_REGEX: regex::Regex = regex::Regex::new(r"^[0-9a-fA-F]{64}$").unwrap();
+}
+
+// Asset upload models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = asset_uploads)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetUpload {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub asset_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub chunk_size: i32,
+    pub checksum: Option<String>,
+    pub status: String,
+    pub storage_path: String,
+    pub error: Option<String>,
+    pub created_by_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deduplicated: bool,
+    pub direct: bool,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_uploads)]
+pub struct NewAssetUpload {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub asset_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub chunk_size: i32,
+    pub checksum: Option<String>,
+    pub storage_path: String,
+    pub created_by_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub direct: bool,
+}
+
+/// A file stored once per tenant, identified by the SHA-256 of its content
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = asset_blobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetBlob {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub checksum: String,
+    pub storage_path: String,
+    pub size_bytes: i64,
+    pub content_type: Option<String>,
+    pub ref_count: i32,
+    pub scan_status: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub scan: Option<serde_json::Value>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_blobs)]
+pub struct NewAssetBlob {
+    pub tenant_id: Uuid,
+    pub checksum: String,
+    pub storage_path: String,
+    pub size_bytes: i64,
+    pub content_type: Option<String>,
+    pub scan_status: Option<String>,
+}
+
+/// Delta between two firmware images, generated once per pair
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = firmware_deltas)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FirmwareDelta {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub source_checksum: String,
+    pub target_checksum: String,
+    /// `None` when the delta is no smaller than the new image
+    pub storage_path: Option<String>,
+    pub size_bytes: i64,
+    pub checksum: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = firmware_deltas)]
+pub struct NewFirmwareDelta {
+    pub tenant_id: Uuid,
+    pub source_checksum: String,
+    pub target_checksum: String,
+    pub storage_path: Option<String>,
+    pub size_bytes: i64,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = asset_upload_chunks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AssetUploadChunk {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub upload_id: Uuid,
+    pub chunk_index: i32,
+    pub size_bytes: i32,
+    pub checksum: String,
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = asset_upload_chunks)]
+pub struct NewAssetUploadChunk {
+    pub tenant_id: Uuid,
+    pub upload_id: Uuid,
+    pub chunk_index: i32,
+    pub size_bytes: i32,
+    pub checksum: String,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AssetUploadStatus {
+    /// Chunks are still being sent
+    #[serde(rename = "uploading")]
+    Uploading,
+    /// Every chunk arrived and the file is being assembled, or a direct upload is being
+    /// checked
+    #[serde(rename = "assembling")]
+    Assembling,
+    #[serde(rename = "completed")]
+    Completed,
+    /// Assembly or the check of a direct upload failed; completing or confirming the upload
+    /// again retries it
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "aborted")]
+    Aborted,
+}
+
+impl std::fmt::Display for AssetUploadStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetUploadStatus::Uploading => write!(f, "uploading"),
+            AssetUploadStatus::Assembling => write!(f, "assembling"),
+            AssetUploadStatus::Completed => write!(f, "completed"),
+            AssetUploadStatus::Failed => write!(f, "failed"),
+            AssetUploadStatus::Aborted => write!(f, "aborted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FirmwareUpdateKind {
+    /// The device already runs the current version
+    #[serde(rename = "up_to_date")]
+    UpToDate,
+    /// A delta to apply to the image the device runs
+    #[serde(rename = "delta")]
+    Delta,
+    /// The whole image
+    #[serde(rename = "full")]
+    Full,
+}
+
+impl From<AssetUploadStatus> for String {
+    fn from(status: AssetUploadStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for AssetUploadStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "uploading" => Ok(AssetUploadStatus::Uploading),
+            "assembling" => Ok(AssetUploadStatus::Assembling),
+            "completed" => Ok(AssetUploadStatus::Completed),
+            "failed" => Ok(AssetUploadStatus::Failed),
+            "aborted" => Ok(AssetUploadStatus::Aborted),
+            _ => Err(format!("Invalid asset upload status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAssetUploadRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub file_name: String,
+    #[validate(length(min = 1, max = 50))]
+    pub content_type: String,
+    /// Size of the whole file in bytes
+    #[validate(range(min = 1))]
+    pub size_bytes: i64,
+    /// SHA-256 (hex) of the whole file, checked once it is assembled; required for direct
+    /// uploads
+    #[validate(regex(path = "SHA256_HEX_REGEX"))]
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetUploadResponse {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub checksum: Option<String>,
+    pub status: AssetUploadStatus,
+    /// Every chunk but the last is exactly this many bytes
+    pub chunk_size: i32,
+    pub chunk_count: u32,
+    pub received_bytes: i64,
+    /// Chunk indexes still to send, so an interrupted upload resumes where it stopped
+    pub missing_chunks: Vec<u32>,
+    pub error: Option<String>,
+    /// The tenant already held the file, so the asset references the stored copy
+    pub deduplicated: bool,
+    /// The file goes straight to storage through `upload_target` rather than in chunks
+    pub direct: bool,
+    /// Where to PUT the file of a direct upload still waiting for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_target: Option<DirectUploadTarget>,
+    pub expires_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Presigned URL a direct upload's file is sent to, bypassing the server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectUploadTarget {
+    pub method: String,
+    pub url: String,
+    /// Headers the PUT must carry with exactly these values; storage refuses it otherwise
+    pub headers: BTreeMap<String, String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct FirmwareUpdateRequest {
+    /// SHA-256 (hex) of the image the device runs
+    #[validate(regex(path = "SHA256_HEX_REGEX"))]
+    pub current_checksum: String,
+}
+
+/// What a device downloads to move to the current version of a firmware asset
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FirmwareUpdateResponse {
+    pub kind: FirmwareUpdateKind,
+    /// The current version
+    pub asset_id: Uuid,
+    pub version: Option<String>,
+    /// SHA-256 and size of the current version's image, to check it once rebuilt
+    pub checksum: String,
+    pub size_bytes: i64,
+    /// Short-lived signed URL of the delta or image; `None` when the device is up to date
+    pub url: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub delta: Option<FirmwareDeltaSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FirmwareDeltaSummary {
+    /// SHA-256 of the image the delta applies to
+    pub source_checksum: String,
+    pub size_bytes: i64,
+    /// SHA-256 of the delta itself
+    pub checksum: String,
+}
+
+/// A stored file looked up by its SHA-256, with the assets referencing it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetBlobResponse {
+    pub checksum: String,
+    pub size_bytes: i64,
+    pub content_type: Option<String>,
+    pub ref_count: i32,
+    /// Antivirus scan of the file; `None` when it was never scanned
+    pub scan_status: Option<AssetScanStatus>,
+    pub assets: Vec<AssetBlobReference>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable)]
+pub struct AssetBlobReference {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub name: String,
+    pub version: Option<String>,
+}
@@ -0,0 +1,258 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{parse_timezone, AvailabilityWindow};
+use crate::schema::*;
+
+// Operator shift models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = operator_shifts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OperatorShift {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub shift_pattern_id: Uuid,
+    pub timezone: String,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = operator_shifts)]
+pub struct NewOperatorShift {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub shift_pattern_id: Uuid,
+    pub timezone: String,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+// Operator attendance models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = operator_attendance)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OperatorAttendance {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub attendance_type: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = operator_attendance)]
+pub struct NewOperatorAttendance {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub attendance_type: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AttendanceType {
+    #[serde(rename = "absent")]
+    Absent,
+    #[serde(rename = "sick")]
+    Sick,
+    #[serde(rename = "vacation")]
+    Vacation,
+    #[serde(rename = "training")]
+    Training,
+    /// Available outside the operator's shifts, e.g. overtime
+    #[serde(rename = "available")]
+    Available,
+}
+
+impl AttendanceType {
+    /// Whether the record takes the operator out (as opposed to adding working time).
+    pub fn is_absence(&self) -> bool {
+        !matches!(self, AttendanceType::Available)
+    }
+}
+
+impl std::fmt::Display for AttendanceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttendanceType::Absent => write!(f, "absent"),
+            AttendanceType::Sick => write!(f, "sick"),
+            AttendanceType::Vacation => write!(f, "vacation"),
+            AttendanceType::Training => write!(f, "training"),
+            AttendanceType::Available => write!(f, "available"),
+        }
+    }
+}
+
+impl From<AttendanceType> for String {
+    fn from(attendance_type: AttendanceType) -> Self {
+        attendance_type.to_string()
+    }
+}
+
+impl TryFrom<String> for AttendanceType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "absent" => Ok(AttendanceType::Absent),
+            "sick" => Ok(AttendanceType::Sick),
+            "vacation" => Ok(AttendanceType::Vacation),
+            "training" => Ok(AttendanceType::Training),
+            "available" => Ok(AttendanceType::Available),
+            _ => Err(format!("Invalid attendance type: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AssignOperatorShiftRequest {
+    pub person_id: Uuid,
+    pub shift_pattern_id: Uuid,
+
+    /// Timezone the pattern is evaluated in (default UTC)
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+
+    pub effective_from: NaiveDate,
+
+    /// Last day the pattern applies; omit while it is current
+    pub effective_to: Option<NaiveDate>,
+
+    pub notes: Option<String>,
+}
+
+impl AssignOperatorShiftRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(timezone) = &self.timezone {
+            parse_timezone(timezone)?;
+        }
+        if self.effective_to.is_some_and(|to| to < self.effective_from) {
+            return Err("Shift assignment must end on or after its first day".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListOperatorShiftsQuery {
+    pub person_id: Option<Uuid>,
+    pub shift_pattern_id: Option<Uuid>,
+    /// Only assignments in effect on this day
+    pub on: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperatorShiftResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub shift_pattern_id: Uuid,
+    pub timezone: String,
+    pub effective_from: NaiveDate,
+    pub effective_to: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<OperatorShift> for OperatorShiftResponse {
+    fn from(shift: OperatorShift) -> Self {
+        Self {
+            id: shift.id,
+            person_id: shift.person_id,
+            shift_pattern_id: shift.shift_pattern_id,
+            timezone: shift.timezone,
+            effective_from: shift.effective_from,
+            effective_to: shift.effective_to,
+            notes: shift.notes,
+            created_at: shift.created_at.unwrap_or_else(Utc::now),
+            updated_at: shift.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAttendanceRequest {
+    pub person_id: Uuid,
+    pub attendance_type: AttendanceType,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+impl CreateAttendanceRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.ends_at <= self.starts_at {
+            return Err("Attendance must end after it starts".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListAttendanceQuery {
+    pub person_id: Option<Uuid>,
+    pub attendance_type: Option<AttendanceType>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttendanceResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub attendance_type: AttendanceType,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<OperatorAttendance> for AttendanceResponse {
+    fn from(attendance: OperatorAttendance) -> Self {
+        Self {
+            id: attendance.id,
+            person_id: attendance.person_id,
+            attendance_type: AttendanceType::try_from(attendance.attendance_type)
+                .unwrap_or(AttendanceType::Absent),
+            starts_at: attendance.starts_at,
+            ends_at: attendance.ends_at,
+            reason: attendance.reason,
+            recorded_by_id: attendance.recorded_by_id,
+            created_at: attendance.created_at.unwrap_or_else(Utc::now),
+            updated_at: attendance.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperatorAvailabilityResponse {
+    pub person_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// False when no shift assignment covers the range, in which case the operator is treated
+    /// as available around the clock apart from absences
+    pub has_shift: bool,
+    pub available_hours: f64,
+    pub windows: Vec<AvailabilityWindow>,
+    pub absences: Vec<AttendanceResponse>,
+}
@@ -1,4 +1,5 @@
 use crate::models::person::PersonRole;
+use chrono::{DateTime, Utc};
 use regex;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -144,6 +145,73 @@ pub struct Claims {
     pub role: String,
     pub exp: usize, // Expiration time
     pub iat: usize, // Issued at
+    /// Client a refresh token was issued to; absent on access tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fp: Option<ClientFingerprint>,
+    /// Service client a machine token was issued to (role `service`); `sub` is its owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azp: Option<String>,
+    /// Space separated scopes of a machine token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Keyed hashes of the user agent and network a refresh token was issued to. Parts the
+/// client did not reveal are left out and never count as a change.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientFingerprint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ua: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net: Option<String>,
+}
+
+/// How refreshes from a client other than the one the token was issued to are handled.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FingerprintMode {
+    /// Tokens are not bound to a client
+    #[serde(rename = "off")]
+    Off,
+    /// Changes are recorded in the audit trail and the refresh goes ahead
+    #[serde(rename = "audit")]
+    Audit,
+    /// Refreshes from a different user agent on a different network are refused
+    #[serde(rename = "lenient")]
+    Lenient,
+    /// Refreshes after any change of user agent or network are refused
+    #[serde(rename = "strict")]
+    Strict,
+}
+
+impl std::fmt::Display for FingerprintMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FingerprintMode::Off => write!(f, "off"),
+            FingerprintMode::Audit => write!(f, "audit"),
+            FingerprintMode::Lenient => write!(f, "lenient"),
+            FingerprintMode::Strict => write!(f, "strict"),
+        }
+    }
+}
+
+impl From<FingerprintMode> for String {
+    fn from(mode: FingerprintMode) -> Self {
+        mode.to_string()
+    }
+}
+
+impl TryFrom<String> for FingerprintMode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "off" => Ok(FingerprintMode::Off),
+            "audit" => Ok(FingerprintMode::Audit),
+            "lenient" => Ok(FingerprintMode::Lenient),
+            "strict" => Ok(FingerprintMode::Strict),
+            _ => Err(format!("Invalid refresh fingerprint mode: {}", value)),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -217,3 +285,236 @@ pub struct CreateAndJoinTenantRequest {
     #[validate(length(min = 1, max = 50), regex(path = "SUBDOMAIN_REGEX"))]
     pub tenant_subdomain: String,
 }
+
+// Where passwords are checked and sign-in identities live
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AuthBackendKind {
+    #[serde(rename = "supabase")]
+    Supabase,
+    /// Argon2 password hashes on the person, for running without Supabase
+    #[serde(rename = "local")]
+    Local,
+}
+
+impl std::fmt::Display for AuthBackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthBackendKind::Supabase => write!(f, "supabase"),
+            AuthBackendKind::Local => write!(f, "local"),
+        }
+    }
+}
+
+impl From<AuthBackendKind> for String {
+    fn from(kind: AuthBackendKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for AuthBackendKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "supabase" => Ok(AuthBackendKind::Supabase),
+            "local" => Ok(AuthBackendKind::Local),
+            _ => Err(format!("Invalid auth backend: {}", value)),
+        }
+    }
+}
+
+// Email verification (local auth backend)
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailResponse {
+    pub person_id: Uuid,
+    pub email: String,
+    pub email_verified_at: DateTime<Utc>,
+}
+
+// Access levels and permissions
+/// Level stored in `tenant_person.access_level` for the person's membership of a tenant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AccessLevel {
+    #[serde(rename = "standard")]
+    Standard,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl AccessLevel {
+    /// The highest level among the stored values; unknown values grant nothing extra.
+    pub fn highest(levels: &[Option<String>]) -> AccessLevel {
+        levels
+            .iter()
+            .flatten()
+            .filter_map(|level| AccessLevel::try_from(level.clone()).ok())
+            .max()
+            .unwrap_or(AccessLevel::Standard)
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            AccessLevel::Standard => &[],
+            AccessLevel::Admin => &[
+                Permission::DeleteMachine,
+                Permission::PurgePerson,
+                Permission::ChangeRoles,
+                Permission::ManageBilling,
+                Permission::ManageIntegrations,
+                Permission::ConfigureNumbering,
+                Permission::ManageSandboxes,
+                Permission::RunRecalculations,
+                Permission::ViewAuditLog,
+                Permission::CommandMachines,
+                Permission::ProvisionMachines,
+                Permission::ManageKiosks,
+                Permission::ManageBranding,
+                Permission::ManageStatusPage,
+                Permission::ManageValidationRules,
+                Permission::ManageAlertRules,
+                Permission::ManagePrinters,
+            ],
+        }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+impl std::fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessLevel::Standard => write!(f, "standard"),
+            AccessLevel::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl From<AccessLevel> for String {
+    fn from(level: AccessLevel) -> Self {
+        level.to_string()
+    }
+}
+
+impl TryFrom<String> for AccessLevel {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "standard" => Ok(AccessLevel::Standard),
+            "admin" => Ok(AccessLevel::Admin),
+            _ => Err(format!("Invalid access level: {}", value)),
+        }
+    }
+}
+
+/// Operations that need more than standard access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    DeleteMachine,
+    /// Removing a person from the tenant, or merging them into another person
+    PurgePerson,
+    /// Changing a person's role or global access
+    ChangeRoles,
+    /// Viewing the tenant's plan and opening the billing portal
+    ManageBilling,
+    /// Setting up the external systems that push events to the ingest endpoint
+    ManageIntegrations,
+    /// Changing the sequences documents are numbered from
+    ConfigureNumbering,
+    /// Cloning the tenant into sandboxes
+    ManageSandboxes,
+    /// Queueing and cancelling tenant-wide recalculations
+    RunRecalculations,
+    /// Reading the tenant's sign-in audit trail
+    ViewAuditLog,
+    /// Queueing commands for machines and changing their desired configuration
+    CommandMachines,
+    /// Issuing and revoking the credentials machines connect with
+    ProvisionMachines,
+    /// Provisioning shop-floor kiosk terminals and setting operators' badges and PINs
+    ManageKiosks,
+    /// Changing the branding customer documents and emails are sent with
+    ManageBranding,
+    /// Choosing what the public status page shows and issuing its token
+    ManageStatusPage,
+    /// Adding and changing the business rules orders and items are checked against
+    ManageValidationRules,
+    /// Adding and changing machine alert rules, whose actions post to webhooks and open
+    /// maintenance orders
+    ManageAlertRules,
+    /// Adding and changing label printers, whose host and port print jobs are sent to
+    ManagePrinters,
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::DeleteMachine => write!(f, "delete machines"),
+            Permission::PurgePerson => write!(f, "remove people"),
+            Permission::ChangeRoles => write!(f, "change roles"),
+            Permission::ManageBilling => write!(f, "manage billing"),
+            Permission::ManageIntegrations => write!(f, "manage integrations"),
+            Permission::ConfigureNumbering => write!(f, "configure numbering"),
+            Permission::ManageSandboxes => write!(f, "manage sandboxes"),
+            Permission::RunRecalculations => write!(f, "run recalculations"),
+            Permission::ViewAuditLog => write!(f, "view the audit log"),
+            Permission::CommandMachines => write!(f, "command machines"),
+            Permission::ProvisionMachines => write!(f, "provision machines"),
+            Permission::ManageKiosks => write!(f, "manage kiosks"),
+            Permission::ManageBranding => write!(f, "manage branding"),
+            Permission::ManageStatusPage => write!(f, "manage status page"),
+            Permission::ManageValidationRules => write!(f, "manage validation rules"),
+            Permission::ManageAlertRules => write!(f, "manage alert rules"),
+            Permission::ManagePrinters => write!(f, "manage printers"),
+        }
+    }
+}
+
+/// The authenticated caller, added to request extensions by the auth middleware.
+#[derive(Debug, Clone)]
+pub struct CallerContext {
+    pub person_id: Uuid,
+    pub access_level: AccessLevel,
+}
+
+impl CallerContext {
+    pub fn require(&self, permission: Permission) -> anyhow::Result<()> {
+        if self.access_level.allows(permission) {
+            Ok(())
+        } else {
+            Err(AccessDenied {
+                permission,
+                access_level: self.access_level,
+            }
+            .into())
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Access denied: {access_level} access cannot {permission}")]
+pub struct AccessDenied {
+    pub permission: Permission,
+    pub access_level: AccessLevel,
+}
+
+impl AccessDenied {
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<AccessDenied>().is_some()
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::auth_audit_events;
+
+// Event types
+/// A refresh token was used from another user agent or network than it was issued to
+pub const REFRESH_FINGERPRINT_MISMATCH: &str = "refresh_fingerprint_mismatch";
+/// An operator was refused on a kiosk terminal: unknown badge, wrong PIN or PIN locked
+pub const KIOSK_BADGE_IN: &str = "kiosk_badge_in";
+/// An admin set, removed or unlocked an operator's kiosk badge and PIN
+pub const OPERATOR_CREDENTIALS_CHANGED: &str = "operator_credentials_changed";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = auth_audit_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct AuthAuditEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Option<Uuid>,
+    pub event: String,
+    pub outcome: String,
+    pub ip_prefix: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = auth_audit_events)]
+pub struct NewAuthAuditEvent {
+    pub tenant_id: Uuid,
+    pub person_id: Option<Uuid>,
+    pub event: String,
+    pub outcome: String,
+    pub ip_prefix: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: Option<serde_json::Value>,
+}
+
+// API DTOs
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListAuthAuditQuery {
+    pub event: Option<String>,
+    pub person_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
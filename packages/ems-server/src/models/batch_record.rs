@@ -0,0 +1,157 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::Quantity;
+use crate::schema::*;
+
+// Batch record models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = batch_records)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BatchRecord {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub batch_number: String,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub completed_at: DateTime<Utc>,
+    pub completed_by_id: Option<Uuid>,
+    pub record: serde_json::Value,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = batch_records)]
+pub struct NewBatchRecord {
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub batch_number: String,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub completed_at: DateTime<Utc>,
+    pub completed_by_id: Option<Uuid>,
+    pub record: serde_json::Value,
+}
+
+// Snapshot stored in `batch_records.record`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchRecordContent {
+    pub product: BatchProduct,
+    pub materials: Vec<BatchMaterial>,
+    pub machines: Vec<BatchMachine>,
+    pub operators: Vec<BatchOperator>,
+    pub inspections: Vec<BatchInspection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchProduct {
+    pub item_id: Option<Uuid>,
+    pub part_number: Option<String>,
+    pub description: Option<String>,
+    pub work_order_number: Option<String>,
+    pub production_line: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// One consumed component. `quantity` is the net issued quantity from the stock ledger, or the
+/// quantity recorded on the job when nothing was issued against it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchMaterial {
+    pub item_id: Uuid,
+    pub part_number: Option<String>,
+    pub quantity: Quantity,
+    pub lot_numbers: Vec<String>,
+    pub serial_numbers: Vec<String>,
+    /// BOM component this material was consumed in place of, when it was a substitute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub substitute_for: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchMachine {
+    pub machine_id: Uuid,
+    pub name: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub firmware: Vec<BatchFirmware>,
+}
+
+/// Firmware asset the machine ran at completion, resolved through its pin mode.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchFirmware {
+    pub asset_id: Uuid,
+    pub name: String,
+    pub version: Option<String>,
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchOperator {
+    pub person_id: Uuid,
+    pub name: String,
+    /// `assigned`, `supervisor`, or the machine assignment type (`primary`, `backup`, `maintenance`)
+    pub role: String,
+    pub machine_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchInspection {
+    pub job_id: Uuid,
+    pub job_number: String,
+    pub status: String,
+    pub inspection_type: Option<String>,
+    pub test_procedure_id: Option<String>,
+    pub acceptance_criteria: Option<String>,
+    pub sampling_size: Option<i32>,
+    pub inspector_id: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+// Request/Response Models
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ListBatchRecordsQuery {
+    pub item_id: Option<Uuid>,
+    pub completed_from: Option<DateTime<Utc>>,
+    pub completed_to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<u32>,
+
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchRecordResponse {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub batch_number: String,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub completed_at: DateTime<Utc>,
+    pub completed_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub content: BatchRecordContent,
+}
+
+impl TryFrom<BatchRecord> for BatchRecordResponse {
+    type Error = serde_json::Error;
+
+    fn try_from(record: BatchRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: record.id,
+            job_id: record.job_id,
+            batch_number: record.batch_number,
+            item_id: record.item_id,
+            quantity: record.quantity,
+            completed_at: record.completed_at,
+            completed_by_id: record.completed_by_id,
+            created_at: record.created_at.unwrap_or_else(Utc::now),
+            content: serde_json::from_value(record.record)?,
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+/// Plan of tenants without a subscription.
+pub const DEFAULT_PLAN: &str = "free";
+
+// Tenant billing models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = tenant_billing)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TenantBilling {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub plan: String,
+    pub stripe_customer_id: Option<String>,
+    pub stripe_subscription_id: Option<String>,
+    pub subscription_status: Option<String>,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: bool,
+    /// Set while the tenant is suspended for non-payment
+    pub billing_suspended: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = tenant_billing)]
+pub struct NewTenantBilling {
+    pub tenant_id: Uuid,
+    pub stripe_customer_id: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = billing_webhook_events)]
+pub struct NewBillingWebhookEvent {
+    pub stripe_event_id: String,
+    pub event_type: String,
+    pub tenant_id: Option<Uuid>,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillingResponse {
+    pub tenant_id: Uuid,
+    pub plan: String,
+    /// Stripe subscription status; `None` until the tenant subscribes
+    pub subscription_status: Option<String>,
+    pub current_period_end: Option<DateTime<Utc>>,
+    pub cancel_at_period_end: bool,
+    /// Whether the tenant has a Stripe customer, i.e. has opened the customer portal before
+    pub customer_linked: bool,
+    /// The tenant is suspended for non-payment
+    pub suspended: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct BillingPortalRequest {
+    /// Page the portal sends the admin back to; defaults to BILLING_PORTAL_RETURN_URL
+    #[validate(url)]
+    pub return_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillingPortalResponse {
+    pub url: String,
+}
+
+// Stripe webhook payloads, limited to the fields billing reads
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeSubscription {
+    pub id: String,
+    pub customer: String,
+    pub status: String,
+    /// Unix time; newer API versions report it on the subscription items instead
+    pub current_period_end: Option<i64>,
+    #[serde(default)]
+    pub cancel_at_period_end: bool,
+    pub items: StripeList<StripeSubscriptionItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeList<T> {
+    pub data: Vec<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeSubscriptionItem {
+    pub price: StripePrice,
+    pub current_period_end: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripePrice {
+    pub id: String,
+    pub lookup_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StripeInvoice {
+    pub id: String,
+    pub customer: String,
+    /// Unix time of Stripe's next collection attempt; `None` once it has stopped retrying
+    pub next_payment_attempt: Option<i64>,
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::quantity::Quantity;
+
+// BOM import and export
+
+/// A component line parsed from a BOM file. Rows naming the same part are merged into one line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomImportRow {
+    /// 1-based line number in the uploaded file of the first row for the part
+    pub line: usize,
+    pub internal_part_number: Option<String>,
+    pub mfr_part_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub description: Option<String>,
+    pub quantity: Quantity,
+    /// Ranges such as R1-R4 are expanded
+    pub designators: Vec<String>,
+    /// Alternate MPNs, from numbered MPN columns or a substitutes column
+    pub substitutes: Vec<String>,
+    /// Do not populate; stored as an optional line
+    pub is_optional: bool,
+    pub notes: Option<String>,
+}
+
+impl BomImportRow {
+    /// Part number the line is reported under, the internal one when given.
+    pub fn part_number(&self) -> Option<String> {
+        self.internal_part_number
+            .clone()
+            .or_else(|| self.mfr_part_number.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BomImportStatus {
+    /// The component matched an item of the tenant
+    #[serde(rename = "matched")]
+    Matched,
+    /// No item matched; one is created for the component
+    #[serde(rename = "new")]
+    New,
+    /// No item matched and `create_missing` was not set
+    #[serde(rename = "unknown")]
+    Unknown,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl std::fmt::Display for BomImportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BomImportStatus::Matched => write!(f, "matched"),
+            BomImportStatus::New => write!(f, "new"),
+            BomImportStatus::Unknown => write!(f, "unknown"),
+            BomImportStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ImportBomRequest {
+    /// CSV with a header row in the column conventions of Altium or KiCad BOM exports: a
+    /// designator or quantity column and an MPN or internal part number column, optionally
+    /// `manufacturer`, `description`, numbered or `substitutes` alternates, `dnp` and `notes`
+    #[validate(length(min = 1))]
+    pub csv: String,
+
+    /// Match the file against existing items without saving anything
+    pub dry_run: Option<bool>,
+
+    /// Create items for components that match none. Without it, unknown components are
+    /// reported and nothing is saved, so they can be reviewed before confirming.
+    pub create_missing: Option<bool>,
+
+    /// Remove BOM lines whose component the file does not list
+    pub replace: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BomImportLineResult {
+    pub line: usize,
+    pub part_number: Option<String>,
+    pub designators: Vec<String>,
+    pub quantity: Option<Quantity>,
+    pub status: BomImportStatus,
+    /// Not set for new components until the import is saved
+    pub component_item_id: Option<Uuid>,
+    /// Alternate MPNs that matched no item, left off the line
+    pub unknown_substitutes: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl BomImportLineResult {
+    pub fn failed(line: usize, part_number: Option<String>, errors: Vec<String>) -> Self {
+        Self {
+            line,
+            part_number,
+            designators: Vec::new(),
+            quantity: None,
+            status: BomImportStatus::Failed,
+            component_item_id: None,
+            unknown_substitutes: Vec::new(),
+            errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportBomResponse {
+    pub dry_run: bool,
+    /// False when any line failed or has an unknown component; the import is all-or-nothing
+    pub imported: bool,
+    pub total_lines: usize,
+    pub matched: usize,
+    pub new: usize,
+    pub unknown: usize,
+    pub failed: usize,
+    /// BOM lines removed because the file no longer lists their component
+    pub removed: usize,
+    pub lines: Vec<BomImportLineResult>,
+}
+
+/// Column conventions a BOM is exported in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BomExportFormat {
+    /// Every field the import reads, so an export can be edited and imported again
+    #[default]
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "altium")]
+    Altium,
+    #[serde(rename = "kicad")]
+    Kicad,
+}
+
+impl std::fmt::Display for BomExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BomExportFormat::Csv => write!(f, "csv"),
+            BomExportFormat::Altium => write!(f, "altium"),
+            BomExportFormat::Kicad => write!(f, "kicad"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BomExportQuery {
+    pub format: Option<BomExportFormat>,
+}
+
+/// A part named on an exported BOM line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomExportPart {
+    pub internal_part_number: String,
+    pub mfr_part_number: Option<String>,
+    pub manufacturer: String,
+}
+
+/// A BOM line as written to an export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomExportLine {
+    pub component: BomExportPart,
+    pub description: Option<String>,
+    pub quantity: Quantity,
+    pub designators: Vec<String>,
+    pub substitutes: Vec<BomExportPart>,
+    pub is_optional: bool,
+    pub notes: Option<String>,
+}
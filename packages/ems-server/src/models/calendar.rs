@@ -0,0 +1,323 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::parse_timezone;
+use crate::schema::*;
+
+// Shift pattern models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = shift_patterns)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ShiftPattern {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub shifts: serde_json::Value,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ShiftPattern {
+    /// Shifts stored on the pattern; malformed entries are ignored.
+    pub fn shift_definitions(&self) -> Vec<ShiftDefinition> {
+        serde_json::from_value(self.shifts.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = shift_patterns)]
+pub struct NewShiftPattern {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub shifts: serde_json::Value,
+}
+
+// Machine calendar models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_calendars)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineCalendar {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub shift_pattern_id: Option<Uuid>,
+    pub timezone: String,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_calendars)]
+pub struct NewMachineCalendar {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub shift_pattern_id: Option<Uuid>,
+    pub timezone: String,
+    pub notes: Option<String>,
+}
+
+// Calendar exception models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = calendar_exceptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CalendarException {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub exception_type: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = calendar_exceptions)]
+pub struct NewCalendarException {
+    pub tenant_id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub exception_type: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CalendarExceptionType {
+    #[serde(rename = "planned_downtime")]
+    PlannedDowntime,
+    #[serde(rename = "holiday")]
+    Holiday,
+    #[serde(rename = "extra_shift")]
+    ExtraShift,
+}
+
+impl CalendarExceptionType {
+    /// Whether the exception takes the machine out of service (as opposed to adding working time).
+    pub fn is_blocking(&self) -> bool {
+        !matches!(self, CalendarExceptionType::ExtraShift)
+    }
+}
+
+impl std::fmt::Display for CalendarExceptionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalendarExceptionType::PlannedDowntime => write!(f, "planned_downtime"),
+            CalendarExceptionType::Holiday => write!(f, "holiday"),
+            CalendarExceptionType::ExtraShift => write!(f, "extra_shift"),
+        }
+    }
+}
+
+impl From<CalendarExceptionType> for String {
+    fn from(exception_type: CalendarExceptionType) -> Self {
+        exception_type.to_string()
+    }
+}
+
+impl TryFrom<String> for CalendarExceptionType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "planned_downtime" => Ok(CalendarExceptionType::PlannedDowntime),
+            "holiday" => Ok(CalendarExceptionType::Holiday),
+            "extra_shift" => Ok(CalendarExceptionType::ExtraShift),
+            _ => Err(format!("Invalid calendar exception type: {}", value)),
+        }
+    }
+}
+
+/// A recurring weekly shift. `day_of_week` follows ISO numbering (Monday = 1, Sunday = 7);
+/// a shift whose end is at or before its start runs past midnight into the next day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShiftDefinition {
+    pub day_of_week: u32,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+fn check_shifts(shifts: &[ShiftDefinition]) -> Result<(), String> {
+    match shifts.iter().find(|s| !(1..=7).contains(&s.day_of_week)) {
+        Some(invalid) => Err(format!("Invalid day of week: {}", invalid.day_of_week)),
+        None => Ok(()),
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateShiftPatternRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub description: Option<String>,
+
+    pub shifts: Vec<ShiftDefinition>,
+}
+
+impl CreateShiftPatternRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_shifts(&self.shifts)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateShiftPatternRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    pub description: Option<String>,
+
+    pub shifts: Option<Vec<ShiftDefinition>>,
+}
+
+impl UpdateShiftPatternRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match &self.shifts {
+            Some(shifts) => check_shifts(shifts),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftPatternResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub shifts: Vec<ShiftDefinition>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ShiftPattern> for ShiftPatternResponse {
+    fn from(pattern: ShiftPattern) -> Self {
+        Self {
+            shifts: pattern.shift_definitions(),
+            id: pattern.id,
+            name: pattern.name,
+            description: pattern.description,
+            created_at: pattern.created_at.unwrap_or_else(Utc::now),
+            updated_at: pattern.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetMachineCalendarRequest {
+    pub shift_pattern_id: Option<Uuid>,
+
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+
+    pub notes: Option<String>,
+}
+
+impl SetMachineCalendarRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(timezone) = &self.timezone {
+            parse_timezone(timezone)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineCalendarResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub shift_pattern_id: Option<Uuid>,
+    pub timezone: String,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<MachineCalendar> for MachineCalendarResponse {
+    fn from(calendar: MachineCalendar) -> Self {
+        Self {
+            id: calendar.id,
+            machine_id: calendar.machine_id,
+            shift_pattern_id: calendar.shift_pattern_id,
+            timezone: calendar.timezone,
+            notes: calendar.notes,
+            created_at: calendar.created_at.unwrap_or_else(Utc::now),
+            updated_at: calendar.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCalendarExceptionRequest {
+    /// Machine the exception applies to; omit to apply it to every machine
+    pub machine_id: Option<Uuid>,
+    pub exception_type: CalendarExceptionType,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+impl CreateCalendarExceptionRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.ends_at <= self.starts_at {
+            return Err("Exception must end after it starts".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarExceptionResponse {
+    pub id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub exception_type: CalendarExceptionType,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<CalendarException> for CalendarExceptionResponse {
+    fn from(exception: CalendarException) -> Self {
+        Self {
+            id: exception.id,
+            machine_id: exception.machine_id,
+            exception_type: CalendarExceptionType::try_from(exception.exception_type)
+                .unwrap_or(CalendarExceptionType::PlannedDowntime),
+            starts_at: exception.starts_at,
+            ends_at: exception.ends_at,
+            reason: exception.reason,
+            created_at: exception.created_at.unwrap_or_else(Utc::now),
+            updated_at: exception.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub hours: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineAvailabilityResponse {
+    pub machine_id: Uuid,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// False when the machine has no shift pattern, in which case it is treated as
+    /// available around the clock apart from downtime and holidays
+    pub has_calendar: bool,
+    pub timezone: String,
+    pub available_hours: f64,
+    pub windows: Vec<AvailabilityWindow>,
+}
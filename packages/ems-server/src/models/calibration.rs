@@ -0,0 +1,322 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Days before the due date that a calibration is reported as due soon
+pub const CALIBRATION_DUE_SOON_DAYS: i64 = 14;
+
+// Instrument models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = instruments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Instrument {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub serial_number: String,
+    pub instrument_type: Option<String>,
+    pub location: Option<String>,
+    pub calibration_interval_days: Option<i32>,
+    pub is_active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = instruments)]
+pub struct NewInstrument {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub serial_number: String,
+    pub instrument_type: Option<String>,
+    pub location: Option<String>,
+    pub calibration_interval_days: Option<i32>,
+}
+
+// Calibration record models
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, QueryableByName, Selectable, Identifiable,
+)]
+#[diesel(table_name = calibration_records)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CalibrationRecord {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub calibrated_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub result: String,
+    pub performed_by: Option<String>,
+    pub certificate_number: Option<String>,
+    pub certificate_asset_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    pub overdue_flagged_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = calibration_records)]
+pub struct NewCalibrationRecord {
+    pub tenant_id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub calibrated_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub result: String,
+    pub performed_by: Option<String>,
+    pub certificate_number: Option<String>,
+    pub certificate_asset_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CalibrationResult {
+    #[serde(rename = "pass")]
+    Pass,
+    /// Out of tolerance as found, brought back within tolerance
+    #[serde(rename = "adjusted")]
+    Adjusted,
+    #[serde(rename = "fail")]
+    Fail,
+}
+
+impl std::fmt::Display for CalibrationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalibrationResult::Pass => write!(f, "pass"),
+            CalibrationResult::Adjusted => write!(f, "adjusted"),
+            CalibrationResult::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+impl TryFrom<String> for CalibrationResult {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pass" => Ok(CalibrationResult::Pass),
+            "adjusted" => Ok(CalibrationResult::Adjusted),
+            "fail" => Ok(CalibrationResult::Fail),
+            _ => Err(format!("Invalid calibration result: {}", value)),
+        }
+    }
+}
+
+/// Calibration status of a machine or instrument, from its latest calibration.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CalibrationStatus {
+    /// Never calibrated
+    #[serde(rename = "uncalibrated")]
+    Uncalibrated,
+    #[serde(rename = "current")]
+    Current,
+    /// Due within `CALIBRATION_DUE_SOON_DAYS`
+    #[serde(rename = "due_soon")]
+    DueSoon,
+    #[serde(rename = "overdue")]
+    Overdue,
+    /// The latest calibration failed
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl CalibrationStatus {
+    /// Measurements taken with out of calibration equipment are refused.
+    pub fn is_out_of_calibration(&self) -> bool {
+        matches!(self, CalibrationStatus::Overdue | CalibrationStatus::Failed)
+    }
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateInstrumentRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(min = 1, max = 100))]
+    pub serial_number: String,
+
+    #[validate(length(min = 1, max = 50))]
+    pub instrument_type: Option<String>,
+
+    #[validate(length(max = 200))]
+    pub location: Option<String>,
+
+    /// Default time between calibrations
+    #[validate(range(min = 1, max = 3650))]
+    pub calibration_interval_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateInstrumentRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub serial_number: Option<String>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub instrument_type: Option<String>,
+
+    #[validate(length(max = 200))]
+    pub location: Option<String>,
+
+    #[validate(range(min = 1, max = 3650))]
+    pub calibration_interval_days: Option<i32>,
+
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListInstrumentsQuery {
+    pub instrument_type: Option<String>,
+    pub is_active: Option<bool>,
+    pub status: Option<CalibrationStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstrumentResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub serial_number: String,
+    pub instrument_type: Option<String>,
+    pub location: Option<String>,
+    pub calibration_interval_days: Option<i32>,
+    pub is_active: bool,
+    pub calibration_status: CalibrationStatus,
+    pub latest_calibration: Option<CalibrationRecordResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RecordCalibrationRequest {
+    /// Calibrated machine; set either this or `instrument_id`
+    pub machine_id: Option<Uuid>,
+
+    /// Calibrated instrument; set either this or `machine_id`
+    pub instrument_id: Option<Uuid>,
+
+    /// Defaults to now
+    pub calibrated_at: Option<DateTime<Utc>>,
+
+    /// Next calibration due; defaults to `interval_days`, or the instrument's interval, after
+    /// `calibrated_at`
+    pub due_at: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 3650))]
+    pub interval_days: Option<i32>,
+
+    pub result: CalibrationResult,
+
+    /// Person or laboratory that performed the calibration
+    #[validate(length(min = 1, max = 200))]
+    pub performed_by: Option<String>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub certificate_number: Option<String>,
+
+    /// Asset holding the calibration certificate
+    pub certificate_asset_id: Option<Uuid>,
+
+    #[validate(length(max = 10000))]
+    pub notes: Option<String>,
+}
+
+impl RecordCalibrationRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.machine_id.is_some() == self.instrument_id.is_some() {
+            return Err("Set either machine_id or instrument_id".to_string());
+        }
+        if self.due_at.is_some() && self.interval_days.is_some() {
+            return Err("Set either due_at or interval_days".to_string());
+        }
+        if let (Some(calibrated_at), Some(due_at)) = (self.calibrated_at, self.due_at) {
+            if due_at <= calibrated_at {
+                return Err("due_at must be after calibrated_at".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AttachCalibrationCertificateRequest {
+    pub certificate_asset_id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub certificate_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListCalibrationsQuery {
+    pub machine_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub result: Option<CalibrationResult>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalibrationRecordResponse {
+    pub id: Uuid,
+    pub machine_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub calibrated_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub result: CalibrationResult,
+    pub performed_by: Option<String>,
+    pub certificate_number: Option<String>,
+    pub certificate_asset_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    /// When the calibration check found the calibration overdue
+    pub overdue_flagged_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<CalibrationRecord> for CalibrationRecordResponse {
+    fn from(record: CalibrationRecord) -> Self {
+        Self {
+            result: CalibrationResult::try_from(record.result).unwrap_or(CalibrationResult::Fail),
+            id: record.id,
+            machine_id: record.machine_id,
+            instrument_id: record.instrument_id,
+            calibrated_at: record.calibrated_at,
+            due_at: record.due_at,
+            performed_by: record.performed_by,
+            certificate_number: record.certificate_number,
+            certificate_asset_id: record.certificate_asset_id,
+            notes: record.notes,
+            recorded_by_id: record.recorded_by_id,
+            overdue_flagged_at: record.overdue_flagged_at,
+            created_at: record.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListEquipmentCalibrationQuery {
+    pub status: Option<CalibrationStatus>,
+}
+
+/// Calibration status of a machine with calibration records, or of an active instrument.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EquipmentCalibrationResponse {
+    pub machine_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub name: String,
+    pub calibration_status: CalibrationStatus,
+    pub latest_calibration: Option<CalibrationRecordResponse>,
+}
@@ -0,0 +1,287 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::Quantity;
+use crate::schema::*;
+
+// Consignment Stock Models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = consignment_stock)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ConsignmentStock {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub owner_id: Uuid,
+    pub owner_type: String,
+    pub quantity: Quantity,
+    pub unit_price: Option<f64>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub last_received_date: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = consignment_stock)]
+pub struct NewConsignmentStock {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub owner_id: Uuid,
+    pub owner_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = consignment_billings)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ConsignmentBilling {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub consignment_stock_id: Uuid,
+    pub stock_movement_id: Uuid,
+    pub item_id: Uuid,
+    pub owner_id: Uuid,
+    pub quantity: Quantity,
+    pub unit_price: f64,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub status: String,
+    pub invoice_reference: Option<String>,
+    pub invoiced_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = consignment_billings)]
+pub struct NewConsignmentBilling {
+    pub tenant_id: Uuid,
+    pub consignment_stock_id: Uuid,
+    pub stock_movement_id: Uuid,
+    pub item_id: Uuid,
+    pub owner_id: Uuid,
+    pub quantity: Quantity,
+    pub unit_price: f64,
+    pub amount: f64,
+    pub currency: Option<String>,
+}
+
+// Enums
+/// Kind of partner owning consignment stock.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConsignmentOwnerType {
+    #[serde(rename = "vendor")]
+    Vendor,
+    #[serde(rename = "customer")]
+    Customer,
+}
+
+impl std::fmt::Display for ConsignmentOwnerType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsignmentOwnerType::Vendor => write!(f, "vendor"),
+            ConsignmentOwnerType::Customer => write!(f, "customer"),
+        }
+    }
+}
+
+impl From<ConsignmentOwnerType> for String {
+    fn from(owner_type: ConsignmentOwnerType) -> Self {
+        owner_type.to_string()
+    }
+}
+
+impl TryFrom<String> for ConsignmentOwnerType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "vendor" => Ok(ConsignmentOwnerType::Vendor),
+            "customer" => Ok(ConsignmentOwnerType::Customer),
+            _ => Err(format!("Invalid consignment owner type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConsignmentBillingStatus {
+    /// Owed to the partner, not on a self-billing invoice yet
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "invoiced")]
+    Invoiced,
+}
+
+impl std::fmt::Display for ConsignmentBillingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsignmentBillingStatus::Pending => write!(f, "pending"),
+            ConsignmentBillingStatus::Invoiced => write!(f, "invoiced"),
+        }
+    }
+}
+
+impl From<ConsignmentBillingStatus> for String {
+    fn from(status: ConsignmentBillingStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for ConsignmentBillingStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(ConsignmentBillingStatus::Pending),
+            "invoiced" => Ok(ConsignmentBillingStatus::Invoiced),
+            _ => Err(format!("Invalid consignment billing status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListConsignmentStockQuery {
+    pub owner_id: Option<Uuid>,
+    pub owner_type: Option<ConsignmentOwnerType>,
+    pub item_id: Option<Uuid>,
+
+    /// Include balances that were used up; left out, only stock on hand is listed
+    #[serde(default)]
+    pub include_empty: bool,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateConsignmentStockRequest {
+    /// Agreed price per base unit billed when the stock is consumed
+    #[validate(range(min = 0.0))]
+    pub unit_price: Option<f64>,
+
+    #[validate(length(equal = 3))]
+    pub currency: Option<String>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsignmentStockResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub owner_id: Uuid,
+    pub owner_name: String,
+    pub owner_type: ConsignmentOwnerType,
+    pub quantity: Quantity,
+    /// Agreed price per base unit; consumption is billed at the inventory unit cost without one
+    pub unit_price: Option<f64>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub last_received_date: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ConsignmentStockResponse {
+    pub fn new(stock: ConsignmentStock, owner_name: String) -> Self {
+        Self {
+            id: stock.id,
+            item_id: stock.item_id,
+            inventory_item_id: stock.inventory_item_id,
+            owner_id: stock.owner_id,
+            owner_name,
+            owner_type: ConsignmentOwnerType::try_from(stock.owner_type)
+                .unwrap_or(ConsignmentOwnerType::Vendor),
+            quantity: stock.quantity,
+            unit_price: stock.unit_price,
+            currency: stock.currency,
+            notes: stock.notes,
+            last_received_date: stock.last_received_date,
+            created_at: stock.created_at.unwrap_or_else(Utc::now),
+            updated_at: stock.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListConsignmentBillingsQuery {
+    pub owner_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub status: Option<ConsignmentBillingStatus>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsignmentBillingResponse {
+    pub id: Uuid,
+    pub consignment_stock_id: Uuid,
+    pub stock_movement_id: Uuid,
+    pub item_id: Uuid,
+    pub owner_id: Uuid,
+    pub quantity: Quantity,
+    pub unit_price: f64,
+    pub amount: f64,
+    pub currency: Option<String>,
+    pub status: ConsignmentBillingStatus,
+    pub invoice_reference: Option<String>,
+    pub invoiced_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ConsignmentBilling> for ConsignmentBillingResponse {
+    fn from(billing: ConsignmentBilling) -> Self {
+        Self {
+            id: billing.id,
+            consignment_stock_id: billing.consignment_stock_id,
+            stock_movement_id: billing.stock_movement_id,
+            item_id: billing.item_id,
+            owner_id: billing.owner_id,
+            quantity: billing.quantity,
+            unit_price: billing.unit_price,
+            amount: billing.amount,
+            currency: billing.currency,
+            status: ConsignmentBillingStatus::try_from(billing.status)
+                .unwrap_or(ConsignmentBillingStatus::Pending),
+            invoice_reference: billing.invoice_reference,
+            invoiced_at: billing.invoiced_at,
+            created_at: billing.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Settles a partner's pending billings on one self-billing invoice.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct InvoiceConsignmentBillingsRequest {
+    pub owner_id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub invoice_reference: String,
+
+    /// Billings to settle; every pending billing of the owner when left out
+    #[validate(length(min = 1, max = 1000))]
+    pub billing_ids: Option<Vec<Uuid>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsignmentInvoiceResponse {
+    pub owner_id: Uuid,
+    pub invoice_reference: String,
+    /// Amount per currency; billings without one are under an empty key
+    pub totals: std::collections::BTreeMap<String, f64>,
+    pub billings: Vec<ConsignmentBillingResponse>,
+}
@@ -0,0 +1,332 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    JobStatus, JobType, MachineStatus, OrderStatus, OrderType, ReportDefinition, ReportKind,
+    ReportParameters,
+};
+use crate::schema::*;
+
+pub const DEFAULT_DASHBOARD_COLUMNS: u32 = 12;
+pub const MAX_DASHBOARD_COLUMNS: u32 = 24;
+pub const MAX_DASHBOARD_WIDGETS: usize = 50;
+
+/// Rows a widget shows when its parameters give no limit
+pub const DEFAULT_WIDGET_LIMIT: u32 = 10;
+pub const MAX_WIDGET_LIMIT: u32 = 100;
+
+// Dashboard models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = dashboards)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Dashboard {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub owner_id: Uuid,
+    pub name: String,
+    pub is_default: bool,
+    pub layout: serde_json::Value,
+    pub widgets: serde_json::Value,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Dashboard {
+    pub fn layout(&self) -> DashboardLayout {
+        serde_json::from_value(self.layout.clone()).unwrap_or_default()
+    }
+
+    pub fn widgets(&self) -> Vec<DashboardWidget> {
+        serde_json::from_value(self.widgets.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = dashboards)]
+pub struct NewDashboard {
+    pub tenant_id: Uuid,
+    pub owner_id: Uuid,
+    pub name: String,
+    pub is_default: bool,
+    pub layout: serde_json::Value,
+    pub widgets: serde_json::Value,
+}
+
+// Widget configuration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DashboardLayout {
+    /// Width of the grid widgets are placed on
+    pub columns: u32,
+}
+
+impl Default for DashboardLayout {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_DASHBOARD_COLUMNS,
+        }
+    }
+}
+
+/// Where a widget sits on the grid, in grid cells from the top-left corner.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WidgetPosition {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DashboardWidget {
+    /// Chosen by the client and unique within the dashboard
+    pub id: String,
+    pub title: Option<String>,
+    pub position: WidgetPosition,
+    #[serde(flatten)]
+    pub source: WidgetSource,
+}
+
+/// The data a widget shows, as `"kind"` and its `"params"`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", content = "params")]
+pub enum WidgetSource {
+    #[serde(rename = "machines")]
+    Machines(MachineWidgetParams),
+    #[serde(rename = "orders")]
+    Orders(OrderWidgetParams),
+    #[serde(rename = "jobs")]
+    Jobs(JobWidgetParams),
+    #[serde(rename = "overdue_capas")]
+    OverdueCapas(OverdueCapaWidgetParams),
+    #[serde(rename = "report")]
+    Report(ReportWidgetParams),
+}
+
+impl WidgetSource {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WidgetSource::Machines(_) => "machines",
+            WidgetSource::Orders(_) => "orders",
+            WidgetSource::Jobs(_) => "jobs",
+            WidgetSource::OverdueCapas(_) => "overdue_capas",
+            WidgetSource::Report(_) => "report",
+        }
+    }
+
+    pub fn limit(&self) -> Option<u32> {
+        match self {
+            WidgetSource::Machines(params) => params.limit,
+            WidgetSource::Orders(params) => params.limit,
+            WidgetSource::Jobs(params) => params.limit,
+            WidgetSource::OverdueCapas(params) => params.limit,
+            WidgetSource::Report(params) => params.limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MachineWidgetParams {
+    pub site: Option<String>,
+    pub status: Option<MachineStatus>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OrderWidgetParams {
+    pub order_type: Option<OrderType>,
+    pub status: Option<OrderStatus>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct JobWidgetParams {
+    pub job_type: Option<JobType>,
+    pub status: Option<JobStatus>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OverdueCapaWidgetParams {
+    pub owner_id: Option<Uuid>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportWidgetParams {
+    pub report: ReportKind,
+    #[serde(default)]
+    pub parameters: HashMap<String, String>,
+    /// Report rows shown; the rest are dropped
+    pub limit: Option<u32>,
+}
+
+/// Checks a dashboard's layout and widgets: widget ids are unique, every widget fits inside the
+/// grid, limits are in range and report widgets carry valid report parameters.
+pub fn check_dashboard(
+    layout: &DashboardLayout,
+    widgets: &[DashboardWidget],
+    report_definition: impl Fn(ReportKind) -> ReportDefinition,
+) -> Result<(), String> {
+    if layout.columns == 0 || layout.columns > MAX_DASHBOARD_COLUMNS {
+        return Err(format!(
+            "Dashboard columns must be between 1 and {}",
+            MAX_DASHBOARD_COLUMNS
+        ));
+    }
+    if widgets.len() > MAX_DASHBOARD_WIDGETS {
+        return Err(format!(
+            "A dashboard holds at most {} widgets",
+            MAX_DASHBOARD_WIDGETS
+        ));
+    }
+
+    let mut ids = HashSet::new();
+    for widget in widgets {
+        if widget.id.trim().is_empty() || widget.id.len() > 50 {
+            return Err("Widget id must be 1 to 50 characters".to_string());
+        }
+        if !ids.insert(widget.id.as_str()) {
+            return Err(format!("Duplicate widget id: {}", widget.id));
+        }
+        if widget.title.as_ref().is_some_and(|t| t.len() > 100) {
+            return Err(format!("Title of widget {} is too long", widget.id));
+        }
+
+        let position = &widget.position;
+        if position.w == 0 || position.h == 0 || position.x + position.w > layout.columns {
+            return Err(format!(
+                "Widget {} does not fit a {}-column grid",
+                widget.id, layout.columns
+            ));
+        }
+
+        if let Some(limit) = widget.source.limit() {
+            if limit == 0 || limit > MAX_WIDGET_LIMIT {
+                return Err(format!(
+                    "Limit of widget {} must be between 1 and {}",
+                    widget.id, MAX_WIDGET_LIMIT
+                ));
+            }
+        }
+
+        match &widget.source {
+            WidgetSource::Machines(params) => {
+                if params
+                    .site
+                    .as_ref()
+                    .is_some_and(|s| s.is_empty() || s.len() > 100)
+                {
+                    return Err(format!("Site of widget {} is invalid", widget.id));
+                }
+            }
+            WidgetSource::Report(params) => {
+                ReportParameters::parse(&report_definition(params.report), &params.parameters)?;
+            }
+            WidgetSource::Orders(_) | WidgetSource::Jobs(_) | WidgetSource::OverdueCapas(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateDashboardRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub layout: Option<DashboardLayout>,
+
+    #[serde(default)]
+    pub widgets: Vec<DashboardWidget>,
+
+    /// Defaults to true for a user's first dashboard
+    pub is_default: Option<bool>,
+}
+
+impl CreateDashboardRequest {
+    pub fn check(
+        &self,
+        report_definition: impl Fn(ReportKind) -> ReportDefinition,
+    ) -> Result<(), String> {
+        check_dashboard(
+            &self.layout.unwrap_or_default(),
+            &self.widgets,
+            report_definition,
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateDashboardRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    pub layout: Option<DashboardLayout>,
+
+    /// Replaces the whole widget list
+    pub widgets: Option<Vec<DashboardWidget>>,
+
+    pub is_default: Option<bool>,
+}
+
+impl UpdateDashboardRequest {
+    /// Checks the dashboard as it will be once the update applies to `dashboard`.
+    pub fn check(
+        &self,
+        dashboard: &Dashboard,
+        report_definition: impl Fn(ReportKind) -> ReportDefinition,
+    ) -> Result<(), String> {
+        let layout = self.layout.unwrap_or_else(|| dashboard.layout());
+        match &self.widgets {
+            Some(widgets) => check_dashboard(&layout, widgets, report_definition),
+            None => check_dashboard(&layout, &dashboard.widgets(), report_definition),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DashboardResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub is_default: bool,
+    pub layout: DashboardLayout,
+    pub widgets: Vec<DashboardWidget>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Dashboard> for DashboardResponse {
+    fn from(dashboard: Dashboard) -> Self {
+        Self {
+            id: dashboard.id,
+            name: dashboard.name.clone(),
+            is_default: dashboard.is_default,
+            layout: dashboard.layout(),
+            widgets: dashboard.widgets(),
+            created_at: dashboard.created_at.unwrap_or_else(Utc::now),
+            updated_at: dashboard.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// One widget's data. A widget whose data cannot be loaded carries an error instead, so the
+/// rest of the dashboard still renders.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WidgetData {
+    pub id: String,
+    pub kind: String,
+    pub data: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DashboardDataResponse {
+    pub dashboard_id: Uuid,
+    pub generated_at: DateTime<Utc>,
+    pub widgets: Vec<WidgetData>,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kind of document in an order's document pack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocumentPackKind {
+    #[serde(rename = "order_confirmation")]
+    OrderConfirmation,
+    /// Batch record of a lot shipped on the order
+    #[serde(rename = "lot_certificate")]
+    LotCertificate,
+    #[serde(rename = "inspection_report")]
+    InspectionReport,
+    #[serde(rename = "firmware_release_notes")]
+    FirmwareReleaseNotes,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentPackEntry {
+    /// Path of the file in the archive
+    pub path: String,
+    pub kind: DocumentPackKind,
+    /// Order, batch record or firmware asset the document was made from
+    pub source_id: Uuid,
+}
+
+/// Contents of an order's document pack, stored in the archive as `manifest.json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DocumentPackManifest {
+    pub order_id: Uuid,
+    pub order_number: String,
+    pub generated_at: DateTime<Utc>,
+    pub documents: Vec<DocumentPackEntry>,
+}
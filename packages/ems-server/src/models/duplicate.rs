@@ -0,0 +1,91 @@
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ReferenceCountRow {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub count: i64,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DuplicateReason {
+    #[serde(rename = "same_email")]
+    SameEmail,
+    #[serde(rename = "same_phone")]
+    SamePhone,
+    #[serde(rename = "similar_name")]
+    SimilarName,
+    #[serde(rename = "same_mpn_manufacturer")]
+    SameMpnManufacturer,
+    #[serde(rename = "similar_mpn")]
+    SimilarMpn,
+    #[serde(rename = "similar_description")]
+    SimilarDescription,
+}
+
+impl std::fmt::Display for DuplicateReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicateReason::SameEmail => write!(f, "same_email"),
+            DuplicateReason::SamePhone => write!(f, "same_phone"),
+            DuplicateReason::SimilarName => write!(f, "similar_name"),
+            DuplicateReason::SameMpnManufacturer => write!(f, "same_mpn_manufacturer"),
+            DuplicateReason::SimilarMpn => write!(f, "similar_mpn"),
+            DuplicateReason::SimilarDescription => write!(f, "similar_description"),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DuplicatesQuery {
+    /// Minimum similarity (0-1) for fuzzy matches
+    #[validate(range(min = 0.5, max = 1.0))]
+    pub min_score: Option<f64>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<usize>,
+}
+
+/// A likely duplicate pair. The older record is suggested as the survivor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DuplicateMatch {
+    pub survivor_id: Uuid,
+    pub survivor_label: String,
+    pub duplicate_id: Uuid,
+    pub duplicate_label: String,
+    pub reasons: Vec<DuplicateReason>,
+    /// 1.0 for exact matches, otherwise the fuzzy similarity
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MergeRequest {
+    /// Record that is kept
+    pub survivor_id: Uuid,
+    /// Record whose references move to the survivor before it is deleted
+    pub duplicate_id: Uuid,
+}
+
+impl MergeRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.survivor_id == self.duplicate_id {
+            return Err("A record cannot be merged into itself".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeResponse {
+    pub survivor_id: Uuid,
+    pub merged_id: Uuid,
+    /// Rows moved to the survivor per `table.column`
+    pub moved: BTreeMap<String, usize>,
+    /// Rows of the duplicate dropped because the survivor already had an equivalent row
+    pub dropped: BTreeMap<String, usize>,
+}
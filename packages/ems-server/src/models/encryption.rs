@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::*;
+
+// Data key models
+#[derive(Debug, Clone, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = data_keys)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DataKeyRecord {
+    pub id: Uuid,
+    /// `None` for the platform key
+    pub tenant_id: Option<Uuid>,
+    pub version: i32,
+    /// Base64 of the data key encrypted with the master key
+    pub wrapped_key: String,
+    pub master_key_id: String,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = data_keys)]
+pub struct NewDataKeyRecord {
+    pub tenant_id: Option<Uuid>,
+    pub version: i32,
+    pub wrapped_key: String,
+    pub master_key_id: String,
+}
+
+/// Outcome of rotating a scope's data key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// `None` for the platform key
+    pub tenant_id: Option<Uuid>,
+    pub version: i32,
+    /// Rows whose values were encrypted again with the new key, including values stored
+    /// before encryption was turned on
+    pub reencrypted: usize,
+}
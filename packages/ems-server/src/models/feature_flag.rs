@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+lazy_static::lazy_static! {
+    static ref FLAG_KEY_REGEX: regex::Regex = regex::Regex::new(r"^[a-z][a-z0-9_]*$").unwrap();
+}
+
+// Feature flag models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = feature_flags)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FeatureFlag {
+    pub id: Uuid,
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub rollout_percentage: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = feature_flags)]
+pub struct NewFeatureFlag {
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub rollout_percentage: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = feature_flag_overrides)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FeatureFlagOverride {
+    pub id: Uuid,
+    pub flag_id: Uuid,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = feature_flag_overrides)]
+pub struct NewFeatureFlagOverride {
+    pub flag_id: Uuid,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+}
+
+/// Why a flag is on or off for a tenant, in the order the rules are checked.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagDecision {
+    /// No flag has the key
+    Unknown,
+    /// The flag's kill switch is off
+    Disabled,
+    /// The tenant has an override
+    Override,
+    /// The tenant falls inside (or outside) the rollout percentage
+    Rollout,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateFeatureFlagRequest {
+    /// Lower-case name code checks the flag by, e.g. `mqtt_bridge`
+    #[validate(length(min = 1, max = 100), regex(path = "FLAG_KEY_REGEX"))]
+    pub key: String,
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    /// Defaults to true; a new flag is still off for tenants outside its rollout
+    pub enabled: Option<bool>,
+    /// Defaults to 0, so the flag ships dark
+    #[validate(range(min = 0, max = 100))]
+    pub rollout_percentage: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateFeatureFlagRequest {
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    #[validate(range(min = 0, max = 100))]
+    pub rollout_percentage: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetFlagOverrideRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlagOverrideResponse {
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeatureFlagResponse {
+    #[serde(flatten)]
+    pub flag: FeatureFlag,
+    pub overrides: Vec<FlagOverrideResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagEvaluation {
+    pub key: String,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub decision: FlagDecision,
+}
+
+impl From<FeatureFlagOverride> for FlagOverrideResponse {
+    fn from(tenant_override: FeatureFlagOverride) -> Self {
+        Self {
+            tenant_id: tenant_override.tenant_id,
+            enabled: tenant_override.enabled,
+            updated_at: tenant_override.updated_at,
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{HeartbeatRequest, UpdateOrderRequest};
+use crate::schema::*;
+use crate::utils::ingest::{check_template, parse_field_schema};
+
+// Ingest source models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = ingest_sources)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct IngestSource {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub target: String,
+    pub field_schema: Value,
+    pub template: Value,
+    pub is_active: bool,
+    pub last_received_at: Option<DateTime<Utc>>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = ingest_sources)]
+pub struct NewIngestSource {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub target: String,
+    pub field_schema: Value,
+    pub template: Value,
+    pub created_by_id: Option<Uuid>,
+}
+
+// Ingest event models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = ingest_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct IngestEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub source_id: Uuid,
+    pub status: String,
+    pub error: Option<String>,
+    pub payload: Value,
+    pub entity_id: Option<Uuid>,
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = ingest_events)]
+pub struct NewIngestEvent {
+    pub tenant_id: Uuid,
+    pub source_id: Uuid,
+    pub status: String,
+    pub error: Option<String>,
+    pub payload: Value,
+    pub entity_id: Option<Uuid>,
+}
+
+/// Service the events of an ingest source are routed to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IngestTarget {
+    /// Updates an order; the template renders an `IngestOrderUpdate`
+    #[serde(rename = "order_update")]
+    OrderUpdate,
+    /// Records a machine heartbeat; the template renders an `IngestMachineHeartbeat`
+    #[serde(rename = "machine_heartbeat")]
+    MachineHeartbeat,
+}
+
+impl std::fmt::Display for IngestTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestTarget::OrderUpdate => write!(f, "order_update"),
+            IngestTarget::MachineHeartbeat => write!(f, "machine_heartbeat"),
+        }
+    }
+}
+
+impl TryFrom<String> for IngestTarget {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "order_update" => Ok(IngestTarget::OrderUpdate),
+            "machine_heartbeat" => Ok(IngestTarget::MachineHeartbeat),
+            _ => Err(format!("Invalid ingest target: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IngestEventStatus {
+    #[serde(rename = "processed")]
+    Processed,
+    /// The payload did not match the field schema, or the rendered request was invalid
+    #[serde(rename = "rejected")]
+    Rejected,
+    /// The target service refused the request or could not be reached
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl std::fmt::Display for IngestEventStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestEventStatus::Processed => write!(f, "processed"),
+            IngestEventStatus::Rejected => write!(f, "rejected"),
+            IngestEventStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl TryFrom<String> for IngestEventStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "processed" => Ok(IngestEventStatus::Processed),
+            "rejected" => Ok(IngestEventStatus::Rejected),
+            "failed" => Ok(IngestEventStatus::Failed),
+            _ => Err(format!("Invalid ingest event status: {}", value)),
+        }
+    }
+}
+
+/// Type a field schema expects at a pointer.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestFieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+    /// Any value other than null
+    Any,
+}
+
+impl IngestFieldType {
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            IngestFieldType::String => value.is_string(),
+            IngestFieldType::Number => value.is_number(),
+            IngestFieldType::Integer => value.is_i64() || value.is_u64(),
+            IngestFieldType::Boolean => value.is_boolean(),
+            IngestFieldType::Object => value.is_object(),
+            IngestFieldType::Array => value.is_array(),
+            IngestFieldType::Any => !value.is_null(),
+        }
+    }
+}
+
+impl std::fmt::Display for IngestFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IngestFieldType::String => write!(f, "string"),
+            IngestFieldType::Number => write!(f, "number"),
+            IngestFieldType::Integer => write!(f, "integer"),
+            IngestFieldType::Boolean => write!(f, "boolean"),
+            IngestFieldType::Object => write!(f, "object"),
+            IngestFieldType::Array => write!(f, "array"),
+            IngestFieldType::Any => write!(f, "any"),
+        }
+    }
+}
+
+impl TryFrom<String> for IngestFieldType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "string" => Ok(IngestFieldType::String),
+            "number" => Ok(IngestFieldType::Number),
+            "integer" => Ok(IngestFieldType::Integer),
+            "boolean" => Ok(IngestFieldType::Boolean),
+            "object" => Ok(IngestFieldType::Object),
+            "array" => Ok(IngestFieldType::Array),
+            "any" => Ok(IngestFieldType::Any),
+            _ => Err(format!("Invalid field type: {}", value)),
+        }
+    }
+}
+
+/// Request an `order_update` template renders: the order, by id or number, and the changes.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct IngestOrderUpdate {
+    pub order_id: Option<Uuid>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub order_number: Option<String>,
+
+    #[serde(flatten)]
+    #[validate]
+    pub update: UpdateOrderRequest,
+}
+
+/// Request a `machine_heartbeat` template renders: the machine and its heartbeat.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct IngestMachineHeartbeat {
+    pub machine_id: Uuid,
+
+    #[serde(flatten)]
+    #[validate]
+    pub heartbeat: HeartbeatRequest,
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateIngestSourceRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub target: IngestTarget,
+
+    /// JSON pointers into the payload mapped to their type; defaults to no checks
+    pub field_schema: Option<Value>,
+
+    /// Request for the target service, with `{{/pointer}}` placeholders
+    pub template: Value,
+}
+
+impl CreateIngestSourceRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(field_schema) = &self.field_schema {
+            parse_field_schema(field_schema)?;
+        }
+        check_template(&self.template)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateIngestSourceRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    pub field_schema: Option<Value>,
+
+    pub template: Option<Value>,
+
+    pub is_active: Option<bool>,
+}
+
+impl UpdateIngestSourceRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if let Some(field_schema) = &self.field_schema {
+            parse_field_schema(field_schema)?;
+        }
+        if let Some(template) = &self.template {
+            check_template(template)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestSourceResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub target: IngestTarget,
+    pub field_schema: Value,
+    pub template: Value,
+    pub is_active: bool,
+    pub last_received_at: Option<DateTime<Utc>>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<IngestSource> for IngestSourceResponse {
+    fn from(source: IngestSource) -> Self {
+        Self {
+            target: IngestTarget::try_from(source.target).unwrap_or(IngestTarget::OrderUpdate),
+            id: source.id,
+            name: source.name,
+            field_schema: source.field_schema,
+            template: source.template,
+            is_active: source.is_active,
+            last_received_at: source.last_received_at,
+            created_by_id: source.created_by_id,
+            created_at: source.created_at.unwrap_or_else(Utc::now),
+            updated_at: source.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A source with its token, returned when the source is created or its token rotated. The
+/// token is only stored hashed and cannot be read back later.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestSourceTokenResponse {
+    #[serde(flatten)]
+    pub source: IngestSourceResponse,
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListIngestEventsQuery {
+    pub status: Option<IngestEventStatus>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestEventResponse {
+    pub id: Uuid,
+    pub source_id: Uuid,
+    pub status: IngestEventStatus,
+    pub error: Option<String>,
+    pub payload: Value,
+    pub entity_id: Option<Uuid>,
+    pub received_at: DateTime<Utc>,
+}
+
+impl From<IngestEvent> for IngestEventResponse {
+    fn from(event: IngestEvent) -> Self {
+        Self {
+            status: IngestEventStatus::try_from(event.status).unwrap_or(IngestEventStatus::Failed),
+            id: event.id,
+            source_id: event.source_id,
+            error: event.error,
+            payload: event.payload,
+            entity_id: event.entity_id,
+            received_at: event.received_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Outcome of an ingested event, returned to the pushing system.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestResponse {
+    pub event_id: Uuid,
+    pub target: IngestTarget,
+    /// Order or machine the event was applied to
+    pub entity_id: Uuid,
+}
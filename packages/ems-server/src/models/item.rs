@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::quantity::{validate_bom_quantity, validate_stock_quantity, Quantity};
 use crate::models::tenant::Tenant;
 use crate::schema::*;
 
@@ -51,7 +52,7 @@ pub struct InventoryItem {
     pub item_id: Uuid,
     pub tenant_id: Uuid,
     pub context: String,
-    pub quantity: Option<i32>,
+    pub quantity: Option<Quantity>,
     pub location: Option<String>,
     pub pricing: Option<serde_json::Value>,
     pub lead_time: Option<i32>,
@@ -73,7 +74,7 @@ pub struct NewInventoryItem {
     pub item_id: Uuid,
     pub tenant_id: Uuid,
     pub context: String,
-    pub quantity: Option<i32>,
+    pub quantity: Option<Quantity>,
     pub location: Option<String>,
     pub pricing: Option<serde_json::Value>,
     pub lead_time: Option<i32>,
@@ -98,13 +99,15 @@ pub struct ItemBom {
     pub tenant_id: Uuid,
     pub parent_item_id: Uuid,
     pub component_item_id: Uuid,
-    pub quantity: Option<i32>,
+    pub quantity: Option<Quantity>,
     pub notes: Option<String>,
     pub is_optional: Option<bool>,
     pub substitutes: Option<Vec<Option<Uuid>>>,
     pub assembly_order: Option<i32>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub uom_id: Option<Uuid>,
+    pub designators: Option<Vec<Option<String>>>,
 }
 
 #[derive(Debug, Insertable)]
@@ -113,11 +116,13 @@ pub struct NewItemBom {
     pub tenant_id: Uuid,
     pub parent_item_id: Uuid,
     pub component_item_id: Uuid,
-    pub quantity: Option<i32>,
+    pub quantity: Option<Quantity>,
     pub notes: Option<String>,
     pub is_optional: Option<bool>,
     pub substitutes: Option<Vec<Option<Uuid>>>,
     pub assembly_order: Option<i32>,
+    pub uom_id: Option<Uuid>,
+    pub designators: Option<Vec<Option<String>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -243,8 +248,9 @@ impl TryFrom<String> for ItemStatus {
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateItemRequest {
+    /// Drawn from the item numbering sequence when left out
     #[validate(length(min = 1, max = 50))]
-    pub internal_part_number: String,
+    pub internal_part_number: Option<String>,
 
     #[validate(length(max = 50))]
     pub mfr_part_number: Option<String>,
@@ -269,8 +275,8 @@ pub struct CreateItemRequest {
     // Inventory context data
     pub context: ItemContext,
 
-    #[validate(range(min = 0))]
-    pub quantity: Option<i32>,
+    #[validate(custom = "validate_stock_quantity")]
+    pub quantity: Option<Quantity>,
 
     #[validate(length(max = 100))]
     pub location: Option<String>,
@@ -323,8 +329,8 @@ pub struct UpdateItemRequest {
     pub linked_resources: Option<serde_json::Value>,
 
     // Inventory context data
-    #[validate(range(min = 0))]
-    pub quantity: Option<i32>,
+    #[validate(custom = "validate_stock_quantity")]
+    pub quantity: Option<Quantity>,
 
     #[validate(length(max = 100))]
     pub location: Option<String>,
@@ -371,7 +377,7 @@ pub struct ItemResponse {
 
     // Inventory context data
     pub context: ItemContext,
-    pub quantity: i32,
+    pub quantity: Quantity,
     pub location: Option<String>,
     pub pricing: Option<serde_json::Value>,
     pub lead_time: Option<i32>,
@@ -396,7 +402,7 @@ pub struct FinishedGoodsItemResponse {
     pub description: Option<String>,
     pub category: Option<String>,
     pub lifecycle: ItemLifecycle,
-    pub quantity: i32,
+    pub quantity: Quantity,
     pub location: Option<String>,
     pub pricing: Option<serde_json::Value>,
     pub min_stock_level: i32,
@@ -416,7 +422,7 @@ pub struct StoreItemResponse {
     pub description: Option<String>,
     pub category: Option<String>,
     pub lifecycle: ItemLifecycle,
-    pub quantity: i32,
+    pub quantity: Quantity,
     pub location: Option<String>,
     pub min_stock_level: i32,
     pub max_stock_level: Option<i32>,
@@ -435,7 +441,7 @@ pub struct VendorItemResponse {
     pub description: Option<String>,
     pub category: Option<String>,
     pub lifecycle: ItemLifecycle,
-    pub quantity: i32,
+    pub quantity: Quantity,
     pub location: Option<String>,
     pub pricing: Option<serde_json::Value>,
     pub lead_time: Option<i32>,
@@ -451,23 +457,32 @@ pub struct CreateBomItemRequest {
     pub parent_item_id: Uuid,
     pub component_item_id: Uuid,
 
-    #[validate(range(min = 1))]
-    pub quantity: Option<i32>,
+    #[validate(custom = "validate_bom_quantity")]
+    pub quantity: Option<Quantity>,
 
     pub notes: Option<String>,
     pub is_optional: Option<bool>,
     pub substitutes: Option<Vec<Uuid>>,
+    #[validate(range(min = 0))]
     pub assembly_order: Option<i32>,
+
+    /// Unit of the component quantity, e.g. centimeters of a wire stocked in meters; the
+    /// component's base unit when left out
+    pub uom_id: Option<Uuid>,
+
+    /// Reference designators the component is placed at, e.g. R1 and R2
+    pub designators: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateBomItemRequest {
-    #[validate(range(min = 1))]
-    pub quantity: Option<i32>,
+    #[validate(custom = "validate_bom_quantity")]
+    pub quantity: Option<Quantity>,
 
     pub notes: Option<String>,
     pub is_optional: Option<bool>,
     pub substitutes: Option<Vec<Uuid>>,
+    #[validate(range(min = 0))]
     pub assembly_order: Option<i32>,
 }
 
@@ -476,10 +491,14 @@ pub struct BomItemResponse {
     pub id: Uuid,
     pub parent_item: ItemSummary,
     pub component_item: ItemSummary,
-    pub quantity: i32,
+    pub quantity: Quantity,
+    pub uom_id: Option<Uuid>,
+    /// Quantity in the component's base unit
+    pub base_quantity: Quantity,
     pub notes: Option<String>,
     pub is_optional: bool,
     pub substitutes: Vec<Uuid>,
+    pub designators: Vec<String>,
     pub assembly_order: Option<i32>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Item datasheet models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_datasheets)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemDatasheet {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub source_url: String,
+    pub asset_id: Option<Uuid>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub checksum: Option<String>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_datasheets)]
+pub struct NewItemDatasheet {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub source_url: String,
+    pub asset_id: Option<Uuid>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub checksum: Option<String>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// A datasheet file ready to be served, from the snapshot or fetched for this request.
+#[derive(Debug, Clone)]
+pub struct DatasheetFile {
+    pub content: Vec<u8>,
+    pub content_type: String,
+    pub file_name: String,
+    pub fetched_at: DateTime<Utc>,
+    /// The snapshot is past its maximum age and could not be refreshed
+    pub stale: bool,
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetDatasheetQuery {
+    /// Fetches the datasheet again even when the snapshot is still fresh
+    pub refresh: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListDatasheetsQuery {
+    /// Only snapshots past their maximum age, or never fetched successfully
+    pub stale: Option<bool>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemDatasheetResponse {
+    pub item_id: Uuid,
+    pub source_url: String,
+    pub asset_id: Option<Uuid>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub checksum: Option<String>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub is_stale: bool,
+}
+
+/// Refreshes stale snapshots, oldest first.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshDatasheetsRequest {
+    #[validate(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatasheetRefreshFailure {
+    pub item_id: Uuid,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshDatasheetsResponse {
+    pub refreshed: Vec<ItemDatasheetResponse>,
+    pub failures: Vec<DatasheetRefreshFailure>,
+}
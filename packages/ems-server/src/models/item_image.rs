@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Item image models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_images)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemImage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub storage_path: String,
+    pub thumbnail_path: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+    pub caption: Option<String>,
+    pub is_primary: bool,
+    pub uploaded_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_images)]
+pub struct NewItemImage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub storage_path: String,
+    pub thumbnail_path: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+    pub caption: Option<String>,
+    pub is_primary: bool,
+    pub uploaded_by_id: Option<Uuid>,
+}
+
+// Request/Response DTOs
+/// Query of an image upload; the image itself is the request body, typed by its Content-Type.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UploadItemImageQuery {
+    #[validate(length(max = 500))]
+    pub caption: Option<String>,
+    /// Makes the new image the item's primary image. An item's first image is always primary.
+    pub primary: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemImageResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub size_bytes: i64,
+    pub caption: Option<String>,
+    pub is_primary: bool,
+    pub uploaded_by_id: Option<Uuid>,
+    /// Signed URL of the full-size image
+    pub url: String,
+    /// Signed URL of the JPEG thumbnail
+    pub thumbnail_url: String,
+    /// When `url` and `thumbnail_url` stop working; list the images again for fresh ones
+    pub urls_expire_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
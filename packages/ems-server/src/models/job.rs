@@ -31,6 +31,9 @@ pub struct Job {
     pub metadata: Option<serde_json::Value>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Left out of lists unless archived records are asked for
+    pub archived: bool,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Insertable)]
@@ -113,6 +116,7 @@ pub struct QaJob {
     pub environmental_conditions: Option<serde_json::Value>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub inspected_job_id: Option<Uuid>,
 }
 
 #[derive(Debug, Insertable)]
@@ -127,6 +131,7 @@ pub struct NewQaJob {
     pub test_equipment: Option<serde_json::Value>,
     pub calibration_required: Option<bool>,
     pub environmental_conditions: Option<serde_json::Value>,
+    pub inspected_job_id: Option<Uuid>,
 }
 
 #[derive(
@@ -249,6 +254,15 @@ pub enum JobStatus {
     Cancelled,
 }
 
+impl JobStatus {
+    /// Statuses after which the job is closed and can be archived.
+    pub const CLOSED: [JobStatus; 2] = [JobStatus::Completed, JobStatus::Cancelled];
+
+    pub fn is_closed(&self) -> bool {
+        Self::CLOSED.contains(self)
+    }
+}
+
 impl std::fmt::Display for JobStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -327,8 +341,9 @@ impl TryFrom<String> for JobPriority {
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateJobRequest {
+    /// Drawn from the job numbering sequence when left out
     #[validate(length(min = 1, max = 50))]
-    pub job_number: String,
+    pub job_number: Option<String>,
 
     pub item_id: Option<Uuid>,
 
@@ -357,6 +372,7 @@ pub struct CreateJobRequest {
 
     pub materials_consumed: Option<serde_json::Value>,
 
+    #[validate(range(min = 0.0))]
     pub labor_hours: Option<f64>,
 
     pub metadata: Option<serde_json::Value>,
@@ -371,12 +387,15 @@ pub struct CreateJobRequest {
     #[validate(length(max = 50))]
     pub machine_id: Option<String>,
 
+    #[validate(range(min = 0.0))]
     pub setup_time_hours: Option<f64>,
 
+    #[validate(range(min = 0.0))]
     pub cycle_time_minutes: Option<f64>,
 
     pub quality_check_required: Option<bool>,
 
+    #[validate(range(min = 1))]
     pub batch_size: Option<i32>,
 
     pub tool_requirements: Option<serde_json::Value>,
@@ -390,6 +409,7 @@ pub struct CreateJobRequest {
 
     pub acceptance_criteria: Option<String>,
 
+    #[validate(range(min = 1))]
     pub sampling_size: Option<i32>,
 
     pub test_equipment: Option<serde_json::Value>,
@@ -398,6 +418,9 @@ pub struct CreateJobRequest {
 
     pub environmental_conditions: Option<serde_json::Value>,
 
+    /// Production job this QA job inspects
+    pub inspected_job_id: Option<Uuid>,
+
     // Service job specific fields
     #[validate(length(max = 50))]
     pub service_type: Option<String>,
@@ -415,9 +438,63 @@ pub struct CreateJobRequest {
 
     pub safety_requirements: Option<serde_json::Value>,
 
+    #[validate(range(min = 0.0))]
     pub travel_time_hours: Option<f64>,
 }
 
+impl CreateJobRequest {
+    /// A single-unit service job carrying out maintenance, with every other field left empty.
+    pub fn maintenance(
+        job_number: String,
+        priority: JobPriority,
+        maintenance_type: String,
+        comments: String,
+        metadata: serde_json::Value,
+    ) -> Self {
+        Self {
+            job_number: Some(job_number),
+            item_id: None,
+            quantity: 1,
+            assigned_person_id: None,
+            supervisor_id: None,
+            customer_id: None,
+            job_type: JobType::Service,
+            priority: Some(priority),
+            start_date: None,
+            end_date: None,
+            due_date: None,
+            status: None,
+            comments: Some(comments),
+            materials_consumed: None,
+            labor_hours: None,
+            metadata: Some(metadata),
+            work_order_number: None,
+            production_line: None,
+            machine_id: None,
+            setup_time_hours: None,
+            cycle_time_minutes: None,
+            quality_check_required: None,
+            batch_size: None,
+            tool_requirements: None,
+            inspection_type: None,
+            test_procedure_id: None,
+            acceptance_criteria: None,
+            sampling_size: None,
+            test_equipment: None,
+            calibration_required: None,
+            environmental_conditions: None,
+            inspected_job_id: None,
+            service_type: Some("maintenance".to_string()),
+            location: None,
+            equipment_serial_number: None,
+            maintenance_type: Some(maintenance_type),
+            parts_required: None,
+            safety_requirements: None,
+            travel_time_hours: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateJobRequest {
     #[validate(range(min = 1))]
@@ -443,6 +520,7 @@ pub struct UpdateJobRequest {
 
     pub materials_consumed: Option<serde_json::Value>,
 
+    #[validate(range(min = 0.0))]
     pub labor_hours: Option<f64>,
 
     pub metadata: Option<serde_json::Value>,
@@ -457,12 +535,15 @@ pub struct UpdateJobRequest {
     #[validate(length(max = 50))]
     pub machine_id: Option<String>,
 
+    #[validate(range(min = 0.0))]
     pub setup_time_hours: Option<f64>,
 
+    #[validate(range(min = 0.0))]
     pub cycle_time_minutes: Option<f64>,
 
     pub quality_check_required: Option<bool>,
 
+    #[validate(range(min = 1))]
     pub batch_size: Option<i32>,
 
     pub tool_requirements: Option<serde_json::Value>,
@@ -476,6 +557,7 @@ pub struct UpdateJobRequest {
 
     pub acceptance_criteria: Option<String>,
 
+    #[validate(range(min = 1))]
     pub sampling_size: Option<i32>,
 
     pub test_equipment: Option<serde_json::Value>,
@@ -501,9 +583,26 @@ pub struct UpdateJobRequest {
 
     pub safety_requirements: Option<serde_json::Value>,
 
+    #[validate(range(min = 0.0))]
     pub travel_time_hours: Option<f64>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CompleteJobRequest {
+    /// Completion time; defaults to now
+    pub end_date: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 0.0))]
+    pub labor_hours: Option<f64>,
+
+    /// Replaces the job's consumption list. Entries are `{item_id, quantity}` and may carry
+    /// `lot_number` and `serial_numbers` for the batch record
+    pub materials_consumed: Option<serde_json::Value>,
+
+    #[validate(length(max = 1000))]
+    pub comments: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JobResponse {
     pub id: Uuid,
@@ -525,6 +624,10 @@ pub struct JobResponse {
     pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived: bool,
+
+    /// Share of the routing's standard time completed; empty without a routing
+    pub progress_percent: Option<f64>,
 
     // Type-specific data
     pub manufacturing: Option<ManufacturingJobData>,
@@ -577,6 +680,7 @@ pub struct QaJobResponse {
     pub test_equipment: Option<serde_json::Value>,
     pub calibration_required: Option<bool>,
     pub environmental_conditions: Option<serde_json::Value>,
+    pub inspected_job_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -626,6 +730,7 @@ pub struct QaJobData {
     pub test_equipment: Option<serde_json::Value>,
     pub calibration_required: Option<bool>,
     pub environmental_conditions: Option<serde_json::Value>,
+    pub inspected_job_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{JobAssignmentStatus, JobPriority, JobStatus, JobType};
+use crate::schema::*;
+
+// Default and maximum length of a Gantt window, in days
+pub const DEFAULT_GANTT_WINDOW_DAYS: i64 = 28;
+pub const MAX_GANTT_WINDOW_DAYS: i64 = 366;
+
+// Job dependency models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = job_dependencies)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobDependency {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub predecessor_job_id: Uuid,
+    pub successor_job_id: Uuid,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = job_dependencies)]
+pub struct NewJobDependency {
+    pub tenant_id: Uuid,
+    pub predecessor_job_id: Uuid,
+    pub successor_job_id: Uuid,
+    pub created_by_id: Option<Uuid>,
+}
+
+// Request/Response DTOs
+
+/// Makes the job wait for another job to finish before it starts.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateJobDependencyRequest {
+    pub predecessor_job_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobDependencyResponse {
+    pub id: Uuid,
+    pub predecessor_job_id: Uuid,
+    pub predecessor_job_number: String,
+    pub successor_job_id: Uuid,
+    pub successor_job_number: String,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The jobs a job waits for and the jobs waiting for it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobDependenciesResponse {
+    pub job_id: Uuid,
+    pub predecessors: Vec<JobDependencyResponse>,
+    pub successors: Vec<JobDependencyResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct GanttQuery {
+    /// Start of the window; defaults to now
+    pub from: Option<DateTime<Utc>>,
+    /// End of the window; defaults to four weeks after `from`
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// A machine assignment of a job, as a bar in the job's row.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GanttAssignment {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub machine_name: String,
+    pub status: JobAssignmentStatus,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+}
+
+/// A scheduled job, spanning from its start date, or its first machine assignment, to its end
+/// date, or its last machine assignment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GanttJob {
+    pub id: Uuid,
+    pub job_number: String,
+    pub job_type: JobType,
+    pub status: JobStatus,
+    pub priority: JobPriority,
+    pub item_id: Option<Uuid>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub assignments: Vec<GanttAssignment>,
+    /// How long the job could slip without delaying the last job to finish
+    pub slack_minutes: i64,
+    pub is_critical: bool,
+    /// Scheduled to end after its due date
+    pub is_late: bool,
+}
+
+/// A finish-to-start link between two jobs in the chart.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GanttDependency {
+    pub id: Uuid,
+    pub predecessor_job_id: Uuid,
+    pub successor_job_id: Uuid,
+    pub is_critical: bool,
+    /// The successor is scheduled to start before the predecessor ends
+    pub is_violated: bool,
+}
+
+/// Jobs scheduled within the window, in start order, with the dependencies between them and the
+/// critical path: the chain of jobs that decides when the last of them finishes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GanttResponse {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub jobs: Vec<GanttJob>,
+    pub dependencies: Vec<GanttDependency>,
+    /// Jobs on the critical path, first to last
+    pub critical_path: Vec<Uuid>,
+    /// When the last job finishes once every dependency is respected
+    pub finish: Option<DateTime<Utc>>,
+}
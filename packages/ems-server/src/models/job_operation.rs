@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::job::Job;
+use crate::models::tenant::Tenant;
+use crate::schema::*;
+
+pub const MAX_JOB_OPERATIONS: usize = 200;
+
+// Job operation models
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(belongs_to(Job, foreign_key = job_id))]
+#[diesel(belongs_to(Tenant, foreign_key = tenant_id))]
+#[diesel(table_name = job_operations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobOperation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub sequence: i32,
+    pub name: String,
+    pub machine_group_id: Option<Uuid>,
+    pub standard_minutes: f64,
+    pub instructions: Option<String>,
+    pub required_asset_ids: Vec<Option<Uuid>>,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub started_by_id: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub completed_by_id: Option<Uuid>,
+    pub actual_minutes: Option<f64>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = job_operations)]
+pub struct NewJobOperation {
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub sequence: i32,
+    pub name: String,
+    pub machine_group_id: Option<Uuid>,
+    pub standard_minutes: f64,
+    pub instructions: Option<String>,
+    pub required_asset_ids: Vec<Option<Uuid>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JobOperationStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "in_progress")]
+    InProgress,
+    #[serde(rename = "completed")]
+    Completed,
+}
+
+impl std::fmt::Display for JobOperationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobOperationStatus::Pending => write!(f, "pending"),
+            JobOperationStatus::InProgress => write!(f, "in_progress"),
+            JobOperationStatus::Completed => write!(f, "completed"),
+        }
+    }
+}
+
+impl From<JobOperationStatus> for String {
+    fn from(status: JobOperationStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for JobOperationStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(JobOperationStatus::Pending),
+            "in_progress" => Ok(JobOperationStatus::InProgress),
+            "completed" => Ok(JobOperationStatus::Completed),
+            _ => Err(format!("Invalid job operation status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobOperationRequest {
+    pub name: String,
+
+    /// Machine group the operation runs on
+    pub machine_group_id: Option<Uuid>,
+
+    /// Planned time for the operation
+    #[serde(default)]
+    pub standard_minutes: f64,
+
+    pub instructions: Option<String>,
+
+    /// Item documents (drawings, programs, work instructions) the operator needs
+    #[serde(default)]
+    pub required_asset_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetJobRoutingRequest {
+    /// Operations in routing order; replaces the job's routing
+    pub operations: Vec<JobOperationRequest>,
+}
+
+impl SetJobRoutingRequest {
+    /// Checks what field validation cannot reach inside the operation list.
+    pub fn check(&self) -> Result<(), String> {
+        if self.operations.len() > MAX_JOB_OPERATIONS {
+            return Err(format!(
+                "A routing has at most {} operations",
+                MAX_JOB_OPERATIONS
+            ));
+        }
+        for (sequence, operation) in self.operations.iter().enumerate() {
+            let name_length = operation.name.trim().chars().count();
+            if name_length == 0 || name_length > 100 {
+                return Err(format!(
+                    "Operation {} needs a name of 1 to 100 characters",
+                    sequence
+                ));
+            }
+            if !operation.standard_minutes.is_finite() || operation.standard_minutes < 0.0 {
+                return Err(format!(
+                    "Operation {} has an invalid standard time",
+                    sequence
+                ));
+            }
+            if operation
+                .instructions
+                .as_ref()
+                .is_some_and(|instructions| instructions.len() > 10000)
+            {
+                return Err(format!("Operation {} instructions are too long", sequence));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct StartJobOperationRequest {
+    /// Start time; defaults to now
+    pub started_at: Option<DateTime<Utc>>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CompleteJobOperationRequest {
+    /// Completion time; defaults to now
+    pub completed_at: Option<DateTime<Utc>>,
+
+    /// Time spent on the operation; defaults to the time since it was started
+    #[validate(range(min = 0.0))]
+    pub actual_minutes: Option<f64>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+/// An item document the operator needs for an operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobOperationAttachment {
+    pub asset_id: Uuid,
+    pub name: String,
+    pub version: Option<String>,
+    pub file_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobOperationResponse {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub sequence: i32,
+    pub name: String,
+    pub machine_group_id: Option<Uuid>,
+    pub standard_minutes: f64,
+    pub instructions: Option<String>,
+    pub required_attachments: Vec<JobOperationAttachment>,
+    pub status: JobOperationStatus,
+    pub started_at: Option<DateTime<Utc>>,
+    pub started_by_id: Option<Uuid>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub completed_by_id: Option<Uuid>,
+    pub actual_minutes: Option<f64>,
+    pub notes: Option<String>,
+}
+
+/// The job traveler: every operation of the routing in order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobRoutingResponse {
+    pub job_id: Uuid,
+    pub operations: Vec<JobOperationResponse>,
+    pub completed_count: usize,
+    /// Share of standard time completed; empty without a routing
+    pub progress_percent: Option<f64>,
+    pub standard_minutes: f64,
+    /// Recorded time of the completed operations
+    pub actual_minutes: f64,
+}
@@ -0,0 +1,451 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::MachineStatus;
+use crate::schema::*;
+use crate::utils::kiosk::is_valid_pin;
+
+// Kiosk terminal models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = kiosk_terminals)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct KioskTerminal {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub actions: Vec<String>,
+    pub key_prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub created_by_id: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = kiosk_terminals)]
+pub struct NewKioskTerminal {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub actions: Vec<String>,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub created_by_id: Option<Uuid>,
+}
+
+// Operator badge models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = operator_badges)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OperatorBadge {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub badge_id: Option<String>,
+    #[serde(skip_serializing)]
+    pub pin_hash: Option<String>,
+    pub failed_pin_attempts: i32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = operator_badges)]
+pub struct NewOperatorBadge {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub badge_id: Option<String>,
+    pub pin_hash: Option<String>,
+}
+
+// Operator clock event models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = operator_clock_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OperatorClockEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub kiosk_terminal_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = operator_clock_events)]
+pub struct NewOperatorClockEvent {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub kiosk_terminal_id: Option<Uuid>,
+}
+
+// Machine downtime report models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_downtime_reports)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineDowntimeReport {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub reported_by_id: Option<Uuid>,
+    pub previous_status: Option<String>,
+    pub new_status: String,
+    pub reason: String,
+    pub kiosk_terminal_id: Option<Uuid>,
+    pub reported_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_downtime_reports)]
+pub struct NewMachineDowntimeReport {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub reported_by_id: Option<Uuid>,
+    pub previous_status: Option<String>,
+    pub new_status: String,
+    pub reason: String,
+    pub kiosk_terminal_id: Option<Uuid>,
+    pub reported_at: DateTime<Utc>,
+}
+
+// Enums
+/// What operators may do on a kiosk terminal.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum KioskAction {
+    /// Clocking in and out
+    #[serde(rename = "clock")]
+    Clock,
+    #[serde(rename = "complete_operation")]
+    CompleteOperation,
+    #[serde(rename = "report_downtime")]
+    ReportDowntime,
+}
+
+impl std::fmt::Display for KioskAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KioskAction::Clock => write!(f, "clock"),
+            KioskAction::CompleteOperation => write!(f, "complete_operation"),
+            KioskAction::ReportDowntime => write!(f, "report_downtime"),
+        }
+    }
+}
+
+impl From<KioskAction> for String {
+    fn from(action: KioskAction) -> Self {
+        action.to_string()
+    }
+}
+
+impl TryFrom<String> for KioskAction {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "clock" => Ok(KioskAction::Clock),
+            "complete_operation" => Ok(KioskAction::CompleteOperation),
+            "report_downtime" => Ok(KioskAction::ReportDowntime),
+            _ => Err(format!("Invalid kiosk action: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ClockEventType {
+    #[serde(rename = "clock_in")]
+    ClockIn,
+    #[serde(rename = "clock_out")]
+    ClockOut,
+}
+
+impl std::fmt::Display for ClockEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClockEventType::ClockIn => write!(f, "clock_in"),
+            ClockEventType::ClockOut => write!(f, "clock_out"),
+        }
+    }
+}
+
+impl From<ClockEventType> for String {
+    fn from(event_type: ClockEventType) -> Self {
+        event_type.to_string()
+    }
+}
+
+impl TryFrom<String> for ClockEventType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "clock_in" => Ok(ClockEventType::ClockIn),
+            "clock_out" => Ok(ClockEventType::ClockOut),
+            _ => Err(format!("Invalid clock event type: {}", value)),
+        }
+    }
+}
+
+/// The kiosk terminal making the request, added to request extensions by the auth middleware
+/// when it authenticates a kiosk key.
+#[derive(Debug, Clone, Copy)]
+pub struct KioskContext {
+    pub terminal_id: Uuid,
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateKioskTerminalRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(max = 100))]
+    pub location: Option<String>,
+
+    /// What operators may do on the terminal
+    #[validate(length(min = 1, max = 3))]
+    pub actions: Vec<KioskAction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KioskTerminalResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub actions: Vec<KioskAction>,
+    pub key_prefix: String,
+    pub created_by_id: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_id: Option<Uuid>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<KioskTerminal> for KioskTerminalResponse {
+    fn from(terminal: KioskTerminal) -> Self {
+        Self {
+            id: terminal.id,
+            name: terminal.name,
+            location: terminal.location,
+            actions: terminal
+                .actions
+                .into_iter()
+                .filter_map(|action| KioskAction::try_from(action).ok())
+                .collect(),
+            key_prefix: terminal.key_prefix,
+            created_by_id: terminal.created_by_id,
+            last_used_at: terminal.last_used_at,
+            is_active: terminal.revoked_at.is_none(),
+            revoked_at: terminal.revoked_at,
+            revoked_by_id: terminal.revoked_by_id,
+            created_at: terminal.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A terminal with its API key, returned when the terminal is provisioned. The key is only
+/// stored hashed and cannot be read back later.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KioskTerminalKeyResponse {
+    #[serde(flatten)]
+    pub terminal: KioskTerminalResponse,
+    /// Sent as `Authorization: Bearer <api_key>` with the tenant in `X-Tenant-ID`
+    pub api_key: String,
+}
+
+/// Replaces how an operator identifies on kiosks; leaving the badge or PIN out removes it.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetOperatorBadgeRequest {
+    #[validate(length(min = 1, max = 64))]
+    pub badge_id: Option<String>,
+
+    /// 4 to 12 digits
+    pub pin: Option<String>,
+}
+
+impl SetOperatorBadgeRequest {
+    /// Checks the PIN is made of 4 to 12 digits
+    pub fn check(&self) -> Result<(), String> {
+        match &self.pin {
+            Some(pin) if !is_valid_pin(pin) => Err("PIN must be 4 to 12 digits".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperatorBadgeResponse {
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub badge_id: Option<String>,
+    pub has_pin: bool,
+    /// Wrong PINs entered since the last successful one
+    pub failed_pin_attempts: i32,
+    /// PIN entry is refused until then after too many wrong PINs
+    pub locked_until: Option<DateTime<Utc>>,
+    /// When the operator last badged in on a kiosk
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OperatorBadgeResponse {
+    pub fn new(badge: OperatorBadge, person_name: String) -> Self {
+        Self {
+            person_id: badge.person_id,
+            person_name,
+            badge_id: badge.badge_id,
+            has_pin: badge.pin_hash.is_some(),
+            failed_pin_attempts: badge.failed_pin_attempts,
+            locked_until: badge.locked_until,
+            last_used_at: badge.last_used_at,
+            updated_at: badge.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// An operator to pick on the kiosk before entering a PIN.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KioskOperatorResponse {
+    pub person_id: Uuid,
+    pub name: String,
+}
+
+/// How the operator identifies for a kiosk action: a scanned badge, or the person picked from
+/// the operator list with their PIN.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct KioskBadgeIn {
+    #[validate(length(min = 1, max = 64))]
+    pub badge_id: Option<String>,
+    pub person_id: Option<Uuid>,
+    #[validate(length(min = 1, max = 12))]
+    pub pin: Option<String>,
+}
+
+impl KioskBadgeIn {
+    /// Checks exactly one of a badge or a person with PIN is given
+    pub fn check(&self) -> Result<(), String> {
+        match (&self.badge_id, &self.person_id, &self.pin) {
+            (Some(_), None, None) | (None, Some(_), Some(_)) => Ok(()),
+            _ => Err("Give either a badge_id, or a person_id with a pin".to_string()),
+        }
+    }
+
+    /// How the operator identified, for the audit trail: `badge` or `pin`
+    pub fn method(&self) -> &'static str {
+        if self.badge_id.is_some() {
+            "badge"
+        } else {
+            "pin"
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct KioskClockRequest {
+    #[validate]
+    pub operator: KioskBadgeIn,
+    pub event_type: ClockEventType,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperatorClockEventResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub event_type: ClockEventType,
+    pub occurred_at: DateTime<Utc>,
+    pub kiosk_terminal_id: Option<Uuid>,
+}
+
+impl From<OperatorClockEvent> for OperatorClockEventResponse {
+    fn from(event: OperatorClockEvent) -> Self {
+        Self {
+            id: event.id,
+            person_id: event.person_id,
+            event_type: ClockEventType::try_from(event.event_type)
+                .unwrap_or(ClockEventType::ClockIn),
+            occurred_at: event.occurred_at,
+            kiosk_terminal_id: event.kiosk_terminal_id,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct KioskCompleteOperationRequest {
+    #[validate]
+    pub operator: KioskBadgeIn,
+    pub job_id: Uuid,
+    pub operation_id: Uuid,
+
+    /// Time spent on the operation; defaults to the time since it was started
+    #[validate(range(min = 0.0))]
+    pub actual_minutes: Option<f64>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct KioskDowntimeRequest {
+    #[validate]
+    pub operator: KioskBadgeIn,
+    pub machine_id: Uuid,
+
+    /// `error` or `maintenance`; defaults to `error`
+    pub status: Option<MachineStatus>,
+
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+impl KioskDowntimeRequest {
+    /// Checks the operator identifies one way and the status takes the machine down
+    pub fn check(&self) -> Result<(), String> {
+        self.operator.check()?;
+        match &self.status {
+            None | Some(MachineStatus::Error) | Some(MachineStatus::Maintenance) => Ok(()),
+            Some(status) => Err(format!("Downtime cannot be reported as {}", status)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineDowntimeReportResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub reported_by_id: Option<Uuid>,
+    pub previous_status: Option<String>,
+    pub new_status: String,
+    pub reason: String,
+    pub kiosk_terminal_id: Option<Uuid>,
+    pub reported_at: DateTime<Utc>,
+}
+
+impl From<MachineDowntimeReport> for MachineDowntimeReportResponse {
+    fn from(report: MachineDowntimeReport) -> Self {
+        Self {
+            id: report.id,
+            machine_id: report.machine_id,
+            reported_by_id: report.reported_by_id,
+            previous_status: report.previous_status,
+            new_status: report.new_status,
+            reason: report.reason,
+            kiosk_terminal_id: report.kiosk_terminal_id,
+            reported_at: report.reported_at,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::job::Job;
+use crate::models::tenant::Tenant;
+use crate::models::{validate_reserved_quantity, ItemContext, Quantity};
+use crate::schema::*;
+
+// Job substitution models
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(belongs_to(Job, foreign_key = job_id))]
+#[diesel(belongs_to(Tenant, foreign_key = tenant_id))]
+#[diesel(table_name = job_substitutions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobSubstitution {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub bom_id: Option<Uuid>,
+    pub component_item_id: Uuid,
+    pub substitute_item_id: Uuid,
+    pub quantity: Quantity,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = job_substitutions)]
+pub struct NewJobSubstitution {
+    pub tenant_id: Uuid,
+    pub job_id: Uuid,
+    pub bom_id: Option<Uuid>,
+    pub component_item_id: Uuid,
+    pub substitute_item_id: Uuid,
+    pub quantity: Quantity,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+// Request/Response DTOs
+
+/// Issues an approved substitute of a BOM line to the job in place of its component.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ConsumeSubstituteRequest {
+    /// BOM line of the job's item whose component is replaced
+    pub bom_id: Uuid,
+    /// One of the line's substitutes
+    pub substitute_item_id: Uuid,
+
+    /// Quantity of the substitute, in its base unit
+    #[validate(custom = "validate_reserved_quantity")]
+    pub quantity: Quantity,
+
+    /// Inventory the substitute is issued from; store when left out
+    pub context: Option<ItemContext>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobSubstitutionResponse {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub bom_id: Option<Uuid>,
+    pub component_item_id: Uuid,
+    pub substitute_item_id: Uuid,
+    pub quantity: Quantity,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<JobSubstitution> for JobSubstitutionResponse {
+    fn from(substitution: JobSubstitution) -> Self {
+        Self {
+            id: substitution.id,
+            job_id: substitution.job_id,
+            bom_id: substitution.bom_id,
+            component_item_id: substitution.component_item_id,
+            substitute_item_id: substitution.substitute_item_id,
+            quantity: substitution.quantity,
+            stock_movement_id: substitution.stock_movement_id,
+            person_id: substitution.person_id,
+            notes: substitution.notes,
+            created_at: substitution.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct KitCheckQuery {
+    /// Inventory the job is kitted from; store when left out
+    pub context: Option<ItemContext>,
+}
+
+/// An approved substitute proposed for a short component, with the stock it has free.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SubstituteProposal {
+    pub item_id: Uuid,
+    pub part_number: Option<String>,
+    /// On hand less reserved for other orders and jobs
+    pub available: Quantity,
+    /// Share of the shortfall to take from this substitute; substitutes with the most stock
+    /// are drawn on first
+    pub suggested_quantity: Quantity,
+    /// Whether this substitute alone covers the shortfall
+    pub covers_shortfall: bool,
+}
+
+/// One BOM line of the job's item, scaled to the job quantity. Quantities are in the
+/// component's base unit.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KitComponent {
+    pub bom_id: Uuid,
+    pub item_id: Uuid,
+    pub part_number: Option<String>,
+    pub is_optional: bool,
+    pub required: Quantity,
+    /// Net quantity of the component issued to the job so far
+    pub issued: Quantity,
+    /// Quantity of substitutes issued in its place
+    pub substituted: Quantity,
+    pub outstanding: Quantity,
+    /// On hand less reserved for other orders and jobs
+    pub available: Quantity,
+    pub shortfall: Quantity,
+    /// Proposed when the component is short
+    pub substitutes: Vec<SubstituteProposal>,
+}
+
+/// Whether the job can be kitted from stock, component by component.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KitCheckResponse {
+    pub job_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub context: ItemContext,
+    /// No required component is short, counting the proposed substitutes
+    pub kittable: bool,
+    pub components: Vec<KitComponent>,
+}
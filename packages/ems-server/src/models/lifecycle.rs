@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::ItemLifecycle;
+use crate::schema::*;
+
+// Lifecycle check models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_lifecycle_checks)]
+#[diesel(primary_key(item_id))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemLifecycleCheck {
+    pub item_id: Uuid,
+    pub provider: String,
+    pub reported_status: Option<String>,
+    pub suggested_lifecycle: Option<String>,
+    pub last_error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    pub next_check_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, AsChangeset)]
+#[diesel(table_name = item_lifecycle_checks)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewItemLifecycleCheck {
+    pub item_id: Uuid,
+    pub provider: String,
+    pub reported_status: Option<String>,
+    pub suggested_lifecycle: Option<String>,
+    pub last_error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    pub next_check_at: DateTime<Utc>,
+}
+
+// Lifecycle alert models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_lifecycle_alerts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemLifecycleAlert {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub current_lifecycle: Option<String>,
+    pub suggested_lifecycle: String,
+    pub reported_status: String,
+    pub provider: String,
+    pub substitutes: Vec<Option<Uuid>>,
+    pub status: String,
+    pub resolved_by_id: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_lifecycle_alerts)]
+pub struct NewItemLifecycleAlert {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub current_lifecycle: Option<String>,
+    pub suggested_lifecycle: String,
+    pub reported_status: String,
+    pub provider: String,
+    pub substitutes: Vec<Option<Uuid>>,
+}
+
+/// Lifecycle of a part as reported by an external part data provider.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PartLifecycle {
+    /// Status text as the provider reports it (e.g. "Active", "NRND", "Obsolete")
+    pub lifecycle_status: String,
+    pub manufacturer: Option<String>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LifecycleAlertStatus {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "accepted")]
+    Accepted,
+    #[serde(rename = "dismissed")]
+    Dismissed,
+}
+
+impl std::fmt::Display for LifecycleAlertStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LifecycleAlertStatus::Open => write!(f, "open"),
+            LifecycleAlertStatus::Accepted => write!(f, "accepted"),
+            LifecycleAlertStatus::Dismissed => write!(f, "dismissed"),
+        }
+    }
+}
+
+impl From<LifecycleAlertStatus> for String {
+    fn from(status: LifecycleAlertStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for LifecycleAlertStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "open" => Ok(LifecycleAlertStatus::Open),
+            "accepted" => Ok(LifecycleAlertStatus::Accepted),
+            "dismissed" => Ok(LifecycleAlertStatus::Dismissed),
+            _ => Err(format!("Invalid lifecycle alert status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListLifecycleAlertsQuery {
+    pub status: Option<LifecycleAlertStatus>,
+    pub item_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LifecycleAlertResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub current_lifecycle: Option<ItemLifecycle>,
+    pub suggested_lifecycle: ItemLifecycle,
+    pub reported_status: String,
+    pub provider: String,
+    /// Substitute items from BOM lines using this item
+    pub substitutes: Vec<Uuid>,
+    pub status: LifecycleAlertStatus,
+    pub resolved_by_id: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl From<ItemLifecycleAlert> for LifecycleAlertResponse {
+    fn from(alert: ItemLifecycleAlert) -> Self {
+        Self {
+            id: alert.id,
+            item_id: alert.item_id,
+            current_lifecycle: alert
+                .current_lifecycle
+                .and_then(|l| ItemLifecycle::try_from(l).ok()),
+            suggested_lifecycle: ItemLifecycle::try_from(alert.suggested_lifecycle)
+                .unwrap_or(ItemLifecycle::Obsolete),
+            reported_status: alert.reported_status,
+            provider: alert.provider,
+            substitutes: alert.substitutes.into_iter().flatten().collect(),
+            status: LifecycleAlertStatus::try_from(alert.status)
+                .unwrap_or(LifecycleAlertStatus::Open),
+            resolved_by_id: alert.resolved_by_id,
+            resolved_at: alert.resolved_at,
+            created_at: alert.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LifecycleCheckResponse {
+    pub item_id: Uuid,
+    pub provider: String,
+    pub reported_status: Option<String>,
+    pub suggested_lifecycle: Option<ItemLifecycle>,
+    pub last_error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    pub next_check_at: DateTime<Utc>,
+    /// Open alert for the item in the current tenant, if any
+    pub alert: Option<LifecycleAlertResponse>,
+}
+
+impl LifecycleCheckResponse {
+    pub fn new(check: ItemLifecycleCheck, alert: Option<ItemLifecycleAlert>) -> Self {
+        Self {
+            item_id: check.item_id,
+            provider: check.provider,
+            reported_status: check.reported_status,
+            suggested_lifecycle: check
+                .suggested_lifecycle
+                .and_then(|l| ItemLifecycle::try_from(l).ok()),
+            last_error: check.last_error,
+            checked_at: check.checked_at,
+            next_check_at: check.next_check_at,
+            alert: alert.map(|a| a.into()),
+        }
+    }
+}
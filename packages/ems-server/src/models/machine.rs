@@ -1,10 +1,15 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Jsonb};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::models::{Asset, Item, Person, Tenant};
+use crate::models::{
+    Asset, CalibrationRecordResponse, HeartbeatResponse, Item, JobPriority, JobStatus,
+    MachineCommandResult, MachineDowntimeReportResponse, Person, SkillGap, Tenant,
+};
 use crate::schema::*;
 
 // Core machine models
@@ -29,6 +34,14 @@ pub struct Machine {
     pub metadata: Option<serde_json::Value>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub site: Option<String>,
+    pub decommissioned_at: Option<DateTime<Utc>>,
+    pub decommission_reason: Option<String>,
+    pub decommissioned_by: Option<Uuid>,
+    pub final_meter_readings: Option<serde_json::Value>,
+    pub gateway_id: Option<Uuid>,
 }
 
 #[derive(Debug, Insertable)]
@@ -44,6 +57,10 @@ pub struct NewMachine {
     pub payload: Option<serde_json::Value>,
     pub last_heartbeat: Option<DateTime<Utc>>,
     pub metadata: Option<serde_json::Value>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub site: Option<String>,
+    pub gateway_id: Option<Uuid>,
 }
 
 // Machine-Item relationship models
@@ -91,6 +108,7 @@ pub struct MachineAssetRelationship {
     pub notes: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub pin_mode: String,
 }
 
 #[derive(Debug, Insertable)]
@@ -100,6 +118,7 @@ pub struct NewMachineAssetRelationship {
     pub asset_id: Uuid,
     pub relationship_type: String,
     pub notes: Option<String>,
+    pub pin_mode: String,
 }
 
 // Machine-Person operator assignment models
@@ -148,6 +167,11 @@ pub struct MachineJobAssignment {
     pub notes: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub target_quantity: Option<i32>,
+    pub produced_quantity: i32,
+    pub scrap_quantity: i32,
+    pub last_produced_total: Option<i64>,
+    pub last_scrap_total: Option<i64>,
 }
 
 #[derive(Debug, Insertable)]
@@ -159,6 +183,7 @@ pub struct NewMachineJobAssignment {
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub notes: Option<String>,
+    pub target_quantity: Option<i32>,
 }
 
 // Enums for better type safety
@@ -226,6 +251,22 @@ pub enum MachineStatus {
     Maintenance,
     #[serde(rename = "error")]
     Error,
+    /// Retired; left out of machine lists unless asked for
+    #[serde(rename = "decommissioned")]
+    Decommissioned,
+}
+
+impl MachineStatus {
+    /// Offline, faulted, in maintenance or retired, so not available for production
+    pub fn is_down(&self) -> bool {
+        matches!(
+            self,
+            MachineStatus::Offline
+                | MachineStatus::Error
+                | MachineStatus::Maintenance
+                | MachineStatus::Decommissioned
+        )
+    }
 }
 
 impl std::fmt::Display for MachineStatus {
@@ -236,6 +277,7 @@ impl std::fmt::Display for MachineStatus {
             MachineStatus::Busy => write!(f, "busy"),
             MachineStatus::Maintenance => write!(f, "maintenance"),
             MachineStatus::Error => write!(f, "error"),
+            MachineStatus::Decommissioned => write!(f, "decommissioned"),
         }
     }
 }
@@ -256,6 +298,7 @@ impl TryFrom<String> for MachineStatus {
             "busy" => Ok(MachineStatus::Busy),
             "maintenance" => Ok(MachineStatus::Maintenance),
             "error" => Ok(MachineStatus::Error),
+            "decommissioned" => Ok(MachineStatus::Decommissioned),
             _ => Err(format!("Invalid machine status: {}", value)),
         }
     }
@@ -355,6 +398,8 @@ pub enum AssetRelationshipType {
     Configuration,
     #[serde(rename = "calibration_data")]
     CalibrationData,
+    #[serde(rename = "manual")]
+    Manual,
 }
 
 impl std::fmt::Display for AssetRelationshipType {
@@ -363,6 +408,7 @@ impl std::fmt::Display for AssetRelationshipType {
             AssetRelationshipType::Firmware => write!(f, "firmware"),
             AssetRelationshipType::Configuration => write!(f, "configuration"),
             AssetRelationshipType::CalibrationData => write!(f, "calibration_data"),
+            AssetRelationshipType::Manual => write!(f, "manual"),
         }
     }
 }
@@ -381,11 +427,47 @@ impl TryFrom<String> for AssetRelationshipType {
             "firmware" => Ok(AssetRelationshipType::Firmware),
             "configuration" => Ok(AssetRelationshipType::Configuration),
             "calibration_data" => Ok(AssetRelationshipType::CalibrationData),
+            "manual" => Ok(AssetRelationshipType::Manual),
             _ => Err(format!("Invalid asset relationship type: {}", value)),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AssetPinMode {
+    #[serde(rename = "latest")]
+    Latest,
+    #[serde(rename = "specific")]
+    Specific,
+}
+
+impl std::fmt::Display for AssetPinMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetPinMode::Latest => write!(f, "latest"),
+            AssetPinMode::Specific => write!(f, "specific"),
+        }
+    }
+}
+
+impl From<AssetPinMode> for String {
+    fn from(pin_mode: AssetPinMode) -> Self {
+        pin_mode.to_string()
+    }
+}
+
+impl TryFrom<String> for AssetPinMode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "latest" => Ok(AssetPinMode::Latest),
+            "specific" => Ok(AssetPinMode::Specific),
+            _ => Err(format!("Invalid asset pin mode: {}", value)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum OperatorAssignmentType {
     #[serde(rename = "primary")]
@@ -490,6 +572,25 @@ pub struct CreateMachineRequest {
     pub payload: Option<serde_json::Value>,
 
     pub metadata: Option<serde_json::Value>,
+
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub site: Option<String>,
+
+    /// Gateway machine the machine reports through
+    pub gateway_id: Option<Uuid>,
+}
+
+impl CreateMachineRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_coordinates(self.latitude, self.longitude)?;
+        check_settable_status(self.status.as_ref())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -512,6 +613,74 @@ pub struct UpdateMachineRequest {
     pub payload: Option<serde_json::Value>,
 
     pub metadata: Option<serde_json::Value>,
+
+    #[validate(range(min = -90.0, max = 90.0))]
+    pub latitude: Option<f64>,
+
+    #[validate(range(min = -180.0, max = 180.0))]
+    pub longitude: Option<f64>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub site: Option<String>,
+
+    /// Gateway machine the machine reports through
+    pub gateway_id: Option<Uuid>,
+}
+
+impl UpdateMachineRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_coordinates(self.latitude, self.longitude)?;
+        check_settable_status(self.status.as_ref())
+    }
+}
+
+// Coordinates are only meaningful as a pair
+fn check_coordinates(latitude: Option<f64>, longitude: Option<f64>) -> Result<(), String> {
+    if latitude.is_some() != longitude.is_some() {
+        return Err("Latitude and longitude must be provided together".to_string());
+    }
+    Ok(())
+}
+
+// Only decommissioning a machine sets it decommissioned, so the details are always recorded
+fn check_settable_status(status: Option<&MachineStatus>) -> Result<(), String> {
+    if status == Some(&MachineStatus::Decommissioned) {
+        return Err("Machines are decommissioned through the decommission endpoint".to_string());
+    }
+    Ok(())
+}
+
+/// Takes a machine out of service for good. Readings given are kept alongside the latest
+/// value of each telemetry channel, which is captured automatically.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DecommissionMachineRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+
+    /// When the machine left service; now when left out
+    pub decommissioned_at: Option<DateTime<Utc>>,
+
+    pub final_meter_readings: Option<BTreeMap<String, f64>>,
+}
+
+impl DecommissionMachineRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.reason.trim().is_empty() {
+            return Err("Decommission reason is required".to_string());
+        }
+        if self.decommissioned_at.is_some_and(|at| at > Utc::now()) {
+            return Err("Decommission date cannot be in the future".to_string());
+        }
+        if let Some(readings) = &self.final_meter_readings {
+            if let Some((meter, _)) = readings
+                .iter()
+                .find(|(meter, value)| meter.trim().is_empty() || !value.is_finite())
+            {
+                return Err(format!("Invalid meter reading: {}", meter));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -527,10 +696,28 @@ pub struct MachineResponse {
     pub payload: Option<serde_json::Value>,
     pub last_heartbeat: Option<DateTime<Utc>>,
     pub metadata: Option<serde_json::Value>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub site: Option<String>,
+    pub decommissioned_at: Option<DateTime<Utc>>,
+    pub decommission_reason: Option<String>,
+    pub decommissioned_by: Option<Uuid>,
+    pub final_meter_readings: Option<serde_json::Value>,
+    /// Gateway machine whose API key also sends this machine's heartbeats
+    pub gateway_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineDecommissionResponse {
+    pub machine: MachineResponse,
+    /// Operator assignments removed from the machine
+    pub detached_operators: usize,
+    /// Pending and in-progress job assignments closed as failed
+    pub closed_assignments: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateMachineItemRelationshipRequest {
     pub item_id: Uuid,
@@ -543,6 +730,8 @@ pub struct CreateMachineAssetRelationshipRequest {
     pub asset_id: Uuid,
     pub relationship_type: AssetRelationshipType,
     pub notes: Option<String>,
+    /// Defaults to specific; latest follows the current version of the asset's lineage
+    pub pin_mode: Option<AssetPinMode>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -559,6 +748,9 @@ pub struct CreateMachineJobAssignmentRequest {
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub notes: Option<String>,
+    /// Good parts that complete the assignment; the job quantity when left out
+    #[validate(range(min = 1))]
+    pub target_quantity: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -567,6 +759,8 @@ pub struct UpdateMachineJobAssignmentRequest {
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub notes: Option<String>,
+    #[validate(range(min = 1))]
+    pub target_quantity: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -587,6 +781,11 @@ pub struct MachineAssetRelationshipResponse {
     pub asset_id: Uuid,
     pub relationship_type: AssetRelationshipType,
     pub notes: Option<String>,
+    pub pin_mode: AssetPinMode,
+    /// Version the relationship points at now: asset_id when pinned to a specific version, the
+    /// current version of its lineage when pinned to latest
+    pub resolved_asset_id: Uuid,
+    pub resolved_version: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -598,10 +797,19 @@ pub struct MachineOperatorAssignmentResponse {
     pub person_id: Uuid,
     pub assignment_type: OperatorAssignmentType,
     pub notes: Option<String>,
+    /// Required skills the operator is missing or whose certification has expired
+    pub skill_gaps: Vec<SkillGap>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineOperatorAssignmentCreateResponse {
+    pub id: Uuid,
+    /// Unmet requirements with warn enforcement; unmet block requirements reject the assignment
+    pub skill_warnings: Vec<SkillGap>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MachineJobAssignmentResponse {
     pub id: Uuid,
@@ -611,19 +819,343 @@ pub struct MachineJobAssignmentResponse {
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub notes: Option<String>,
+    pub target_quantity: Option<i32>,
+    pub produced_quantity: i32,
+    pub scrap_quantity: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct HeartbeatRequest {
     pub status: MachineStatus,
     pub action: Option<MachineAction>,
     pub payload: Option<serde_json::Value>,
     pub metadata: Option<serde_json::Value>,
+
+    /// Version of the configuration the machine runs; the current one is sent back when it
+    /// differs
+    pub config_version: Option<i32>,
+
+    /// Results of commands received in earlier heartbeat responses
+    #[validate(length(max = 100))]
+    pub command_results: Option<Vec<MachineCommandResult>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeartbeatBatchEntry {
+    pub machine_id: Uuid,
+    #[serde(flatten)]
+    pub heartbeat: HeartbeatRequest,
+}
+
+/// Heartbeats a gateway collected from the machines behind it. Each entry is validated on its
+/// own, so one bad entry does not hold back the others.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct HeartbeatBatchRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub heartbeats: Vec<HeartbeatBatchEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HeartbeatBatchOutcome {
+    #[serde(rename = "applied")]
+    Applied,
+    #[serde(rename = "invalid")]
+    Invalid,
+    #[serde(rename = "forbidden")]
+    Forbidden,
+    #[serde(rename = "not_found")]
+    NotFound,
+    #[serde(rename = "decommissioned")]
+    Decommissioned,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeartbeatBatchResult {
+    pub machine_id: Uuid,
+    pub outcome: HeartbeatBatchOutcome,
+    /// Why the heartbeat was not applied
+    pub error: Option<String>,
+    /// Commands and configuration for the machine, as a single heartbeat would return them
+    pub response: Option<HeartbeatResponse>,
+}
+
+/// One result per heartbeat, in the order of the request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeartbeatBatchResponse {
+    pub results: Vec<HeartbeatBatchResult>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MachineCreateIdResponse {
     pub id: Uuid,
 }
+
+// Capacity planning DTOs
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CapacityQuery {
+    /// First day of the planning window (UTC, inclusive); defaults to today
+    pub from: Option<NaiveDate>,
+    /// Last day of the planning window (UTC, inclusive); defaults to two weeks from `from`
+    pub to: Option<NaiveDate>,
+
+    #[validate(range(min = 0.0, max = 24.0))]
+    pub hours_per_day: Option<f64>,
+
+    pub include_weekends: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacitySlot {
+    pub date: NaiveDate,
+    pub available_hours: f64,
+    pub scheduled_hours: f64,
+    pub overbooked_hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineCapacity {
+    pub machine_id: Uuid,
+    pub machine_name: String,
+    pub status: MachineStatus,
+    pub assignment_count: usize,
+    pub available_hours: f64,
+    pub scheduled_hours: f64,
+    pub load_percent: Option<f64>,
+    pub overbooked_slots: Vec<CapacitySlot>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CapacityPlanResponse {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub available_hours: f64,
+    pub scheduled_hours: f64,
+    pub load_percent: Option<f64>,
+    pub machines: Vec<MachineCapacity>,
+}
+
+// Map DTOs (GeoJSON, RFC 7946)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineMapQuery {
+    pub status: Option<MachineStatus>,
+    pub site: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoJsonPoint {
+    #[serde(rename = "type")]
+    pub geometry_type: String,
+    /// Longitude first, as required by GeoJSON
+    pub coordinates: [f64; 2],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineFeatureProperties {
+    pub name: String,
+    pub status: MachineStatus,
+    pub protocol: MachineProtocol,
+    pub site: Option<String>,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineFeature {
+    #[serde(rename = "type")]
+    pub feature_type: String,
+    pub id: Uuid,
+    pub geometry: GeoJsonPoint,
+    pub properties: MachineFeatureProperties,
+}
+
+impl MachineFeature {
+    /// Map feature for a machine; `None` when it has no coordinates.
+    pub fn from_machine(machine: Machine) -> Option<Self> {
+        let (latitude, longitude) = (machine.latitude?, machine.longitude?);
+        Some(Self {
+            feature_type: "Feature".to_string(),
+            id: machine.id,
+            geometry: GeoJsonPoint {
+                geometry_type: "Point".to_string(),
+                coordinates: [longitude, latitude],
+            },
+            properties: MachineFeatureProperties {
+                name: machine.name,
+                status: MachineStatus::try_from(machine.status).unwrap_or(MachineStatus::Offline),
+                protocol: MachineProtocol::try_from(machine.protocol)
+                    .unwrap_or(MachineProtocol::Http),
+                site: machine.site,
+                last_heartbeat: machine.last_heartbeat,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineFeatureCollection {
+    #[serde(rename = "type")]
+    pub collection_type: String,
+    pub features: Vec<MachineFeature>,
+}
+
+impl MachineFeatureCollection {
+    pub fn new(features: Vec<MachineFeature>) -> Self {
+        Self {
+            collection_type: "FeatureCollection".to_string(),
+            features,
+        }
+    }
+}
+
+// Fleet summary DTOs
+/// Machines listed in each section of the fleet summary when the query gives no limit
+pub const DEFAULT_FLEET_SUMMARY_LIMIT: u32 = 5;
+/// Minutes without a heartbeat after which a running machine's status is no longer trusted
+pub const DEFAULT_STALE_HEARTBEAT_MINUTES: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct FleetSummaryQuery {
+    #[validate(range(min = 1, max = 50))]
+    pub limit: Option<u32>,
+
+    #[validate(range(min = 1, max = 10080))]
+    pub stale_after_minutes: Option<u32>,
+}
+
+#[derive(Debug, QueryableByName)]
+pub struct FleetSummaryRow {
+    #[diesel(sql_type = BigInt)]
+    pub total: i64,
+    #[diesel(sql_type = Jsonb)]
+    pub by_status: serde_json::Value,
+    #[diesel(sql_type = Jsonb)]
+    pub by_protocol: serde_json::Value,
+    #[diesel(sql_type = Jsonb)]
+    pub by_category: serde_json::Value,
+    #[diesel(sql_type = Jsonb)]
+    pub recently_faulted: serde_json::Value,
+    #[diesel(sql_type = BigInt)]
+    pub stale_heartbeat_count: i64,
+    #[diesel(sql_type = Jsonb)]
+    pub stale_heartbeats: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultedMachine {
+    pub id: Uuid,
+    pub name: String,
+    /// Current status, which may have recovered since the fault
+    pub status: MachineStatus,
+    pub site: Option<String>,
+    /// When the machine last entered the error status
+    pub faulted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleHeartbeatMachine {
+    pub id: Uuid,
+    pub name: String,
+    pub status: MachineStatus,
+    pub site: Option<String>,
+    /// `None` when the machine has never sent a heartbeat
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+/// Fleet overview. Categories come from each machine's `metadata.category`; machines without
+/// one are counted as `uncategorized`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineFleetSummary {
+    pub total: i64,
+    pub by_status: BTreeMap<String, i64>,
+    pub by_protocol: BTreeMap<String, i64>,
+    pub by_category: BTreeMap<String, i64>,
+    pub recently_faulted: Vec<FaultedMachine>,
+    /// Idle or busy machines whose last heartbeat is older than `stale_after_minutes`
+    pub stale_heartbeat_count: i64,
+    pub stale_heartbeats: Vec<StaleHeartbeatMachine>,
+    pub stale_after_minutes: u32,
+    pub generated_at: DateTime<Utc>,
+}
+
+// Machine documentation bundle DTOs
+/// Calibrations and downtime reports listed in a machine's documentation bundle
+pub const MACHINE_DOCS_HISTORY_LIMIT: i64 = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineDocumentAsset {
+    pub id: Uuid,
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub file_type: Option<String>,
+    pub file_size: Option<i64>,
+    pub release_status: String,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl From<Asset> for MachineDocumentAsset {
+    fn from(asset: Asset) -> Self {
+        Self {
+            id: asset.id,
+            name: asset.name,
+            version: asset.version,
+            description: asset.description,
+            file_type: asset.file_type,
+            file_size: asset.file_size,
+            release_status: asset.release_status,
+            released_at: asset.released_at,
+        }
+    }
+}
+
+/// A manual, firmware, configuration or calibration data asset linked to the machine, resolved
+/// to the version the link points at
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineDocument {
+    pub relationship_id: Uuid,
+    pub relationship_type: AssetRelationshipType,
+    pub pin_mode: AssetPinMode,
+    pub notes: Option<String>,
+    pub asset: MachineDocumentAsset,
+    /// Release notes of firmware assets
+    pub release_notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineCalibrationDocument {
+    #[serde(flatten)]
+    pub calibration: CalibrationRecordResponse,
+    /// The calibration certificate, when one is attached
+    pub certificate: Option<MachineDocumentAsset>,
+}
+
+/// An open service job assigned to the machine
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineMaintenanceOrder {
+    pub job_id: Uuid,
+    pub job_number: String,
+    pub status: JobStatus,
+    pub priority: Option<JobPriority>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub service_type: Option<String>,
+    pub maintenance_type: Option<String>,
+    pub assignment_id: Uuid,
+    pub assignment_status: JobAssignmentStatus,
+    pub start_time: Option<DateTime<Utc>>,
+}
+
+/// Everything a technician needs at the machine, for the mobile view
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineDocsResponse {
+    pub machine: MachineResponse,
+    pub documents: Vec<MachineDocument>,
+    /// Latest calibrations first
+    pub calibrations: Vec<MachineCalibrationDocument>,
+    /// Latest downtime reports first
+    pub recent_downtime: Vec<MachineDowntimeReportResponse>,
+    /// Earliest due first
+    pub open_maintenance_orders: Vec<MachineMaintenanceOrder>,
+}
@@ -0,0 +1,536 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{JobPriority, MachineStatus, TelemetryChannel};
+use crate::schema::*;
+use crate::utils::public_url::check_public_url;
+
+pub const MAX_ALERT_CONDITIONS: usize = 10;
+pub const MAX_ALERT_ACTIONS: usize = 10;
+pub const MAX_ALERT_RECIPIENTS: usize = 20;
+/// Longest a condition can be required to hold, one day
+pub const MAX_HELD_MINUTES: u32 = 1440;
+/// Minutes of readings an anomaly condition takes its baseline from when it names none
+pub const DEFAULT_BASELINE_MINUTES: u32 = 60;
+/// Shortest baseline an anomaly condition can take
+pub const MIN_BASELINE_MINUTES: u32 = 5;
+
+// Machine alert rule models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_alert_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineAlertRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub machine_id: Option<Uuid>,
+    pub conditions: serde_json::Value,
+    pub actions: serde_json::Value,
+    pub is_enabled: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl MachineAlertRule {
+    pub fn conditions(&self) -> Vec<AlertCondition> {
+        serde_json::from_value(self.conditions.clone()).unwrap_or_default()
+    }
+
+    pub fn actions(&self) -> Vec<AlertAction> {
+        serde_json::from_value(self.actions.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_alert_rules)]
+pub struct NewMachineAlertRule {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub machine_id: Option<Uuid>,
+    pub conditions: serde_json::Value,
+    pub actions: serde_json::Value,
+    pub is_enabled: bool,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_alerts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineAlert {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub rule_id: Uuid,
+    pub machine_id: Uuid,
+    pub triggered_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub observations: serde_json::Value,
+    pub action_results: serde_json::Value,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_alerts)]
+pub struct NewMachineAlert {
+    pub tenant_id: Uuid,
+    pub rule_id: Uuid,
+    pub machine_id: Uuid,
+    pub triggered_at: DateTime<Utc>,
+    pub observations: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_telemetry_anomalies)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineTelemetryAnomaly {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub channel: String,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_std_dev: f64,
+    pub z_score: f64,
+    pub sensitivity: f64,
+    pub baseline_minutes: i32,
+    pub recorded_at: DateTime<Utc>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_telemetry_anomalies)]
+pub struct NewMachineTelemetryAnomaly {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub channel: String,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_std_dev: f64,
+    pub z_score: f64,
+    pub sensitivity: f64,
+    pub baseline_minutes: i32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A heartbeat waiting for the alert worker, as the machine reported it.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_heartbeat_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineHeartbeatEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub status: String,
+    pub payload: Option<serde_json::Value>,
+    pub metadata: Option<serde_json::Value>,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_heartbeat_events)]
+pub struct NewMachineHeartbeatEvent {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub status: String,
+    pub payload: Option<serde_json::Value>,
+    pub metadata: Option<serde_json::Value>,
+    pub received_at: DateTime<Utc>,
+}
+
+// Rule configuration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    #[serde(rename = "gt")]
+    Gt,
+    #[serde(rename = "gte")]
+    Gte,
+    #[serde(rename = "lt")]
+    Lt,
+    #[serde(rename = "lte")]
+    Lte,
+}
+
+impl ComparisonOperator {
+    pub fn compare(&self, observed: f64, threshold: f64) -> bool {
+        match self {
+            ComparisonOperator::Gt => observed > threshold,
+            ComparisonOperator::Gte => observed >= threshold,
+            ComparisonOperator::Lt => observed < threshold,
+            ComparisonOperator::Lte => observed <= threshold,
+        }
+    }
+}
+
+impl std::fmt::Display for ComparisonOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComparisonOperator::Gt => write!(f, ">"),
+            ComparisonOperator::Gte => write!(f, ">="),
+            ComparisonOperator::Lt => write!(f, "<"),
+            ComparisonOperator::Lte => write!(f, "<="),
+        }
+    }
+}
+
+/// Something a heartbeat is checked for, as `"type"` and its fields. With `for_minutes` the
+/// condition must have held without a break for at least that long. An anomaly holds when the
+/// channel's latest reading is further from the mean of its readings over the baseline window
+/// than the tenant's anomaly sensitivity, in standard deviations.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AlertCondition {
+    #[serde(rename = "status")]
+    Status {
+        status: MachineStatus,
+        for_minutes: Option<u32>,
+    },
+    #[serde(rename = "telemetry")]
+    Telemetry {
+        channel: TelemetryChannel,
+        operator: ComparisonOperator,
+        value: f64,
+        for_minutes: Option<u32>,
+    },
+    #[serde(rename = "anomaly")]
+    Anomaly {
+        channel: TelemetryChannel,
+        baseline_minutes: Option<u32>,
+    },
+}
+
+impl AlertCondition {
+    pub fn for_minutes(&self) -> Option<u32> {
+        match self {
+            AlertCondition::Status { for_minutes, .. }
+            | AlertCondition::Telemetry { for_minutes, .. } => *for_minutes,
+            AlertCondition::Anomaly { .. } => None,
+        }
+    }
+
+    /// Minutes of readings before the heartbeat the condition looks at.
+    pub fn window_minutes(&self) -> u32 {
+        match self {
+            AlertCondition::Anomaly {
+                baseline_minutes, ..
+            } => baseline_minutes.unwrap_or(DEFAULT_BASELINE_MINUTES),
+            _ => self.for_minutes().unwrap_or(0),
+        }
+    }
+
+    /// Telemetry channel the condition reads, if any.
+    pub fn channel(&self) -> Option<TelemetryChannel> {
+        match self {
+            AlertCondition::Status { .. } => None,
+            AlertCondition::Telemetry { channel, .. } | AlertCondition::Anomaly { channel, .. } => {
+                Some(*channel)
+            }
+        }
+    }
+}
+
+/// What runs when a rule fires, as `"type"` and its fields.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum AlertAction {
+    /// Emails the recipients
+    #[serde(rename = "notify")]
+    Notify { recipients: Vec<String> },
+    /// POSTs the alert as JSON to the URL
+    #[serde(rename = "webhook")]
+    Webhook { url: String },
+    /// Opens a service job to look at the machine
+    #[serde(rename = "create_maintenance_order")]
+    CreateMaintenanceOrder {
+        priority: Option<JobPriority>,
+        maintenance_type: Option<String>,
+    },
+}
+
+impl AlertAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AlertAction::Notify { .. } => "notify",
+            AlertAction::Webhook { .. } => "webhook",
+            AlertAction::CreateMaintenanceOrder { .. } => "create_maintenance_order",
+        }
+    }
+}
+
+/// Checks a rule's conditions and actions: at least one condition, limits in range, email
+/// recipients that look like addresses and webhook URLs over HTTP(S).
+pub fn check_alert_rule(
+    conditions: &[AlertCondition],
+    actions: &[AlertAction],
+) -> Result<(), String> {
+    if conditions.is_empty() || conditions.len() > MAX_ALERT_CONDITIONS {
+        return Err(format!(
+            "A rule needs between 1 and {} conditions",
+            MAX_ALERT_CONDITIONS
+        ));
+    }
+    if actions.len() > MAX_ALERT_ACTIONS {
+        return Err(format!("A rule runs at most {} actions", MAX_ALERT_ACTIONS));
+    }
+
+    for condition in conditions {
+        if let Some(minutes) = condition.for_minutes() {
+            if minutes == 0 || minutes > MAX_HELD_MINUTES {
+                return Err(format!(
+                    "for_minutes must be between 1 and {}",
+                    MAX_HELD_MINUTES
+                ));
+            }
+        }
+        match condition {
+            AlertCondition::Telemetry { value, .. } if !value.is_finite() => {
+                return Err("Telemetry thresholds must be finite numbers".to_string());
+            }
+            AlertCondition::Anomaly {
+                baseline_minutes: Some(minutes),
+                ..
+            } if *minutes < MIN_BASELINE_MINUTES || *minutes > MAX_HELD_MINUTES => {
+                return Err(format!(
+                    "baseline_minutes must be between {} and {}",
+                    MIN_BASELINE_MINUTES, MAX_HELD_MINUTES
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for action in actions {
+        match action {
+            AlertAction::Notify { recipients } => {
+                if recipients.is_empty() || recipients.len() > MAX_ALERT_RECIPIENTS {
+                    return Err(format!(
+                        "Notifications need between 1 and {} recipients",
+                        MAX_ALERT_RECIPIENTS
+                    ));
+                }
+                if let Some(recipient) = recipients
+                    .iter()
+                    .find(|r| r.len() > 100 || !r.contains('@'))
+                {
+                    return Err(format!("Invalid recipient: {}", recipient));
+                }
+            }
+            AlertAction::Webhook { url } => {
+                if url.len() > 500 {
+                    return Err("Webhook URLs must be at most 500 characters".to_string());
+                }
+                // Hooks never reach the server's own network; resolved hosts are checked on send
+                check_public_url(url, "webhook")?;
+            }
+            AlertAction::CreateMaintenanceOrder {
+                maintenance_type, ..
+            } => {
+                if maintenance_type
+                    .as_ref()
+                    .is_some_and(|t| t.is_empty() || t.len() > 50)
+                {
+                    return Err("maintenance_type must be 1 to 50 characters".to_string());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// What one condition saw on a heartbeat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConditionObservation {
+    pub condition: AlertCondition,
+    pub matched: bool,
+    pub detail: String,
+}
+
+/// Outcome of one action run for an alert.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActionResult {
+    pub action: String,
+    pub succeeded: bool,
+    pub detail: Option<String>,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMachineAlertRuleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    /// Watches every machine of the tenant when left out
+    pub machine_id: Option<Uuid>,
+
+    pub conditions: Vec<AlertCondition>,
+
+    #[serde(default)]
+    pub actions: Vec<AlertAction>,
+
+    /// Defaults to true
+    pub is_enabled: Option<bool>,
+}
+
+impl CreateMachineAlertRuleRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_alert_rule(&self.conditions, &self.actions)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateMachineAlertRuleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    pub machine_id: Option<Uuid>,
+
+    /// Replaces every condition
+    pub conditions: Option<Vec<AlertCondition>>,
+
+    /// Replaces every action
+    pub actions: Option<Vec<AlertAction>>,
+
+    pub is_enabled: Option<bool>,
+}
+
+impl UpdateMachineAlertRuleRequest {
+    /// Checks the rule as it will be once the update applies to `rule`.
+    pub fn check(&self, rule: &MachineAlertRule) -> Result<(), String> {
+        let conditions = self.conditions.clone().unwrap_or_else(|| rule.conditions());
+        let actions = self.actions.clone().unwrap_or_else(|| rule.actions());
+        check_alert_rule(&conditions, &actions)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineAlertRuleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub machine_id: Option<Uuid>,
+    pub conditions: Vec<AlertCondition>,
+    pub actions: Vec<AlertAction>,
+    pub is_enabled: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<MachineAlertRule> for MachineAlertRuleResponse {
+    fn from(rule: MachineAlertRule) -> Self {
+        Self {
+            id: rule.id,
+            name: rule.name.clone(),
+            machine_id: rule.machine_id,
+            conditions: rule.conditions(),
+            actions: rule.actions(),
+            is_enabled: rule.is_enabled,
+            created_by_id: rule.created_by_id,
+            created_at: rule.created_at.unwrap_or_else(Utc::now),
+            updated_at: rule.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ListMachineAlertsQuery {
+    pub machine_id: Option<Uuid>,
+    pub rule_id: Option<Uuid>,
+    /// Only alerts still open (true) or already resolved (false)
+    pub open: Option<bool>,
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineAlertResponse {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub machine_id: Uuid,
+    pub triggered_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub observations: Vec<ConditionObservation>,
+    pub action_results: Vec<ActionResult>,
+}
+
+impl From<MachineAlert> for MachineAlertResponse {
+    fn from(alert: MachineAlert) -> Self {
+        Self {
+            id: alert.id,
+            rule_id: alert.rule_id,
+            machine_id: alert.machine_id,
+            triggered_at: alert.triggered_at,
+            resolved_at: alert.resolved_at,
+            observations: serde_json::from_value(alert.observations).unwrap_or_default(),
+            action_results: serde_json::from_value(alert.action_results).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ListTelemetryAnomaliesQuery {
+    pub channel: Option<TelemetryChannel>,
+    /// Only anomalies in readings recorded from this time on
+    pub since: Option<DateTime<Utc>>,
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<i64>,
+}
+
+/// A telemetry reading scored against the mean and standard deviation of the channel's readings
+/// over the baseline window before it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TelemetryAnomalyResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub channel: String,
+    pub value: f64,
+    pub baseline_mean: f64,
+    pub baseline_std_dev: f64,
+    /// Standard deviations from the baseline mean; negative below it
+    pub z_score: f64,
+    /// Tenant sensitivity the reading was scored against
+    pub sensitivity: f64,
+    pub baseline_minutes: i32,
+    pub recorded_at: DateTime<Utc>,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl From<MachineTelemetryAnomaly> for TelemetryAnomalyResponse {
+    fn from(anomaly: MachineTelemetryAnomaly) -> Self {
+        Self {
+            id: anomaly.id,
+            machine_id: anomaly.machine_id,
+            channel: anomaly.channel,
+            value: anomaly.value,
+            baseline_mean: anomaly.baseline_mean,
+            baseline_std_dev: anomaly.baseline_std_dev,
+            z_score: anomaly.z_score,
+            sensitivity: anomaly.sensitivity,
+            baseline_minutes: anomaly.baseline_minutes,
+            recorded_at: anomaly.recorded_at,
+            detected_at: anomaly.detected_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TestMachineAlertRuleRequest {
+    /// Required for rules that watch every machine
+    pub machine_id: Option<Uuid>,
+}
+
+/// Result of evaluating a rule against a machine's latest heartbeat. Nothing is recorded and
+/// no action runs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineAlertRuleTestResponse {
+    pub rule_id: Uuid,
+    pub machine_id: Uuid,
+    pub would_fire: bool,
+    pub observations: Vec<ConditionObservation>,
+    /// Actions that would run, by kind
+    pub actions: Vec<String>,
+    pub evaluated_at: DateTime<Utc>,
+}
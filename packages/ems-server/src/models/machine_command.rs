@@ -0,0 +1,271 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+/// Most commands sent in one heartbeat response; the rest follow in later ones
+pub const MAX_COMMANDS_PER_HEARTBEAT: i64 = 20;
+
+// Machine command models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_commands)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineCommand {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub command: String,
+    pub parameters: Option<serde_json::Value>,
+    pub status: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub delivery_count: i32,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub result: Option<serde_json::Value>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_commands)]
+pub struct NewMachineCommand {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub command: String,
+    pub parameters: Option<serde_json::Value>,
+    pub status: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_configs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineConfig {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub config: serde_json::Value,
+    pub version: i32,
+    pub reporting_interval_seconds: Option<i32>,
+    pub updated_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_configs)]
+pub struct NewMachineConfig {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub config: serde_json::Value,
+    pub version: i32,
+    pub reporting_interval_seconds: Option<i32>,
+    pub updated_by_id: Option<Uuid>,
+}
+
+/// Where a command is in its delivery. Pending and delivered commands are open and go out with
+/// every heartbeat response until the machine reports a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MachineCommandStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "delivered")]
+    Delivered,
+    #[serde(rename = "succeeded")]
+    Succeeded,
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+    #[serde(rename = "expired")]
+    Expired,
+}
+
+impl MachineCommandStatus {
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self,
+            MachineCommandStatus::Pending | MachineCommandStatus::Delivered
+        )
+    }
+}
+
+impl std::fmt::Display for MachineCommandStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MachineCommandStatus::Pending => write!(f, "pending"),
+            MachineCommandStatus::Delivered => write!(f, "delivered"),
+            MachineCommandStatus::Succeeded => write!(f, "succeeded"),
+            MachineCommandStatus::Failed => write!(f, "failed"),
+            MachineCommandStatus::Cancelled => write!(f, "cancelled"),
+            MachineCommandStatus::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+impl From<MachineCommandStatus> for String {
+    fn from(status: MachineCommandStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for MachineCommandStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(MachineCommandStatus::Pending),
+            "delivered" => Ok(MachineCommandStatus::Delivered),
+            "succeeded" => Ok(MachineCommandStatus::Succeeded),
+            "failed" => Ok(MachineCommandStatus::Failed),
+            "cancelled" => Ok(MachineCommandStatus::Cancelled),
+            "expired" => Ok(MachineCommandStatus::Expired),
+            _ => Err(format!("Invalid machine command status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMachineCommandRequest {
+    /// Command name the machine firmware understands, e.g. `reboot`
+    #[validate(length(min = 1, max = 50))]
+    pub command: String,
+
+    pub parameters: Option<serde_json::Value>,
+
+    /// Seconds the command may wait for delivery before it expires; never when left out
+    #[validate(range(min = 1, max = 604800))]
+    pub expires_in_seconds: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListMachineCommandsQuery {
+    pub status: Option<MachineCommandStatus>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineCommandResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub command: String,
+    pub parameters: Option<serde_json::Value>,
+    pub status: MachineCommandStatus,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub delivery_count: i32,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub result: Option<serde_json::Value>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MachineCommand> for MachineCommandResponse {
+    fn from(command: MachineCommand) -> Self {
+        Self {
+            id: command.id,
+            machine_id: command.machine_id,
+            command: command.command,
+            parameters: command.parameters,
+            status: MachineCommandStatus::try_from(command.status)
+                .unwrap_or(MachineCommandStatus::Pending),
+            expires_at: command.expires_at,
+            delivered_at: command.delivered_at,
+            delivery_count: command.delivery_count,
+            completed_at: command.completed_at,
+            result: command.result,
+            created_by_id: command.created_by_id,
+            created_at: command.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Replaces a machine's desired configuration; the version is raised when the config changes.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PutMachineConfigRequest {
+    pub config: serde_json::Value,
+
+    /// Seconds between heartbeats; the server default when left out
+    #[validate(range(min = 5, max = 86400))]
+    pub reporting_interval_seconds: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineConfigResponse {
+    pub machine_id: Uuid,
+    pub config: serde_json::Value,
+    pub version: i32,
+    pub reporting_interval_seconds: Option<i32>,
+    pub updated_by_id: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<MachineConfig> for MachineConfigResponse {
+    fn from(config: MachineConfig) -> Self {
+        Self {
+            machine_id: config.machine_id,
+            config: config.config,
+            version: config.version,
+            reporting_interval_seconds: config.reporting_interval_seconds,
+            updated_by_id: config.updated_by_id,
+            updated_at: config.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+// Heartbeat exchange
+
+/// Outcome of a command the machine ran, reported with its next heartbeat.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineCommandResult {
+    pub id: Uuid,
+    pub succeeded: bool,
+    pub result: Option<serde_json::Value>,
+}
+
+/// A command for the machine to run, as sent in a heartbeat response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeartbeatCommand {
+    pub id: Uuid,
+    pub command: String,
+    pub parameters: Option<serde_json::Value>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<MachineCommand> for HeartbeatCommand {
+    fn from(command: MachineCommand) -> Self {
+        Self {
+            id: command.id,
+            command: command.command,
+            parameters: command.parameters,
+            expires_at: command.expires_at,
+        }
+    }
+}
+
+/// Configuration for the machine to apply, sent when the version it reports is not current.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeartbeatConfig {
+    pub version: i32,
+    pub config: serde_json::Value,
+}
+
+/// What the server wants from a machine, returned for each heartbeat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeartbeatResponse {
+    /// Open commands, oldest first; sent again until the machine reports their result
+    pub commands: Vec<HeartbeatCommand>,
+    pub config: Option<HeartbeatConfig>,
+    /// Seconds until the machine should next report, longer while the server is under load or
+    /// the tenant's buffered heartbeat writes are queueing up
+    pub reporting_interval_seconds: i32,
+}
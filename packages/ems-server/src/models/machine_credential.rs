@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::machine_credentials;
+
+// Machine credential models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_credentials)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineCredential {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub key_prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub broker_username: Option<String>,
+    #[serde(skip_serializing)]
+    pub broker_password_hash: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_credentials)]
+pub struct NewMachineCredential {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub broker_username: Option<String>,
+    pub broker_password_hash: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+/// The machine whose API key made the request, added to request extensions by the auth
+/// middleware when it authenticates a machine key.
+#[derive(Debug, Clone, Copy)]
+pub struct MachineKeyContext {
+    pub machine_id: Uuid,
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineCredentialResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub key_prefix: String,
+    pub broker_username: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by_id: Option<Uuid>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<MachineCredential> for MachineCredentialResponse {
+    fn from(credential: MachineCredential) -> Self {
+        Self {
+            id: credential.id,
+            machine_id: credential.machine_id,
+            key_prefix: credential.key_prefix,
+            broker_username: credential.broker_username,
+            created_by_id: credential.created_by_id,
+            last_used_at: credential.last_used_at,
+            is_active: credential.revoked_at.is_none(),
+            revoked_at: credential.revoked_at,
+            revoked_by_id: credential.revoked_by_id,
+            created_at: credential.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// MQTT broker connection of a provisioned machine.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BrokerCredentials {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Topic filter the machine publishes and subscribes under
+    pub topic: String,
+}
+
+/// Everything a machine needs to connect, returned once when it is provisioned. The API key and
+/// broker password are not shown again.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineProvisioningBundle {
+    pub credential: MachineCredentialResponse,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    /// Sent as `Authorization: Bearer <api_key>` with the tenant in `X-Tenant-ID`
+    pub api_key: String,
+    pub api_url: String,
+    pub heartbeat_url: String,
+    /// Set for machines speaking MQTT when a broker is configured
+    pub broker: Option<BrokerCredentials>,
+    /// PEM certificate of the CA the server and broker certificates are signed by, when set
+    pub ca_certificate: Option<String>,
+}
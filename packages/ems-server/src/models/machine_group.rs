@@ -0,0 +1,262 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::MachineStatus;
+use crate::schema::*;
+
+pub const MAX_GROUP_MEMBERS: usize = 100;
+
+// Default and maximum length of a utilization window, in days
+pub const DEFAULT_UTILIZATION_WINDOW_DAYS: i64 = 7;
+pub const MAX_UTILIZATION_WINDOW_DAYS: i64 = 92;
+
+// Machine group models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_groups)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineGroup {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_groups)]
+pub struct NewMachineGroup {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_group_members)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineGroupMember {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub group_id: Uuid,
+    pub machine_id: Uuid,
+    pub position: i32,
+    pub is_critical: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_group_members)]
+pub struct NewMachineGroupMember {
+    pub tenant_id: Uuid,
+    pub group_id: Uuid,
+    pub machine_id: Uuid,
+    pub position: i32,
+    pub is_critical: bool,
+}
+
+/// A member machine with the state it last reported.
+#[derive(Debug, Clone)]
+pub struct MachineGroupMemberRow {
+    pub member: MachineGroupMember,
+    pub name: String,
+    pub status: String,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+impl MachineGroupMemberRow {
+    pub fn status(&self) -> MachineStatus {
+        MachineStatus::try_from(self.status.clone()).unwrap_or(MachineStatus::Offline)
+    }
+}
+
+/// State of a group as a whole.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LineStatus {
+    /// Every member is up and at least one is busy
+    #[serde(rename = "running")]
+    Running,
+    /// Every member is up and none is busy
+    #[serde(rename = "idle")]
+    Idle,
+    /// A non-critical member is down
+    #[serde(rename = "degraded")]
+    Degraded,
+    /// A critical member is down
+    #[serde(rename = "down")]
+    Down,
+}
+
+impl std::fmt::Display for LineStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineStatus::Running => write!(f, "running"),
+            LineStatus::Idle => write!(f, "idle"),
+            LineStatus::Degraded => write!(f, "degraded"),
+            LineStatus::Down => write!(f, "down"),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineGroupMemberRequest {
+    pub machine_id: Uuid,
+    /// Defaults to true
+    pub is_critical: Option<bool>,
+}
+
+/// Checks a member list: at most `MAX_GROUP_MEMBERS` machines, none listed twice.
+fn check_members(members: &[MachineGroupMemberRequest]) -> Result<(), String> {
+    if members.len() > MAX_GROUP_MEMBERS {
+        return Err(format!(
+            "A group has at most {} machines",
+            MAX_GROUP_MEMBERS
+        ));
+    }
+    let mut seen = HashSet::new();
+    if let Some(member) = members.iter().find(|m| !seen.insert(m.machine_id)) {
+        return Err(format!("Machine {} is listed twice", member.machine_id));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateMachineGroupRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+
+    /// Machines in line order
+    #[serde(default)]
+    pub members: Vec<MachineGroupMemberRequest>,
+}
+
+impl CreateMachineGroupRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_members(&self.members)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateMachineGroupRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+
+    /// Replaces every member, in line order
+    pub members: Option<Vec<MachineGroupMemberRequest>>,
+}
+
+impl UpdateMachineGroupRequest {
+    pub fn check(&self) -> Result<(), String> {
+        self.members.as_deref().map_or(Ok(()), check_members)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupMemberResponse {
+    pub machine_id: Uuid,
+    pub name: String,
+    pub position: i32,
+    pub is_critical: bool,
+    pub status: MachineStatus,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+impl From<MachineGroupMemberRow> for MachineGroupMemberResponse {
+    fn from(row: MachineGroupMemberRow) -> Self {
+        Self {
+            status: row.status(),
+            machine_id: row.member.machine_id,
+            name: row.name,
+            position: row.member.position,
+            is_critical: row.member.is_critical,
+            last_heartbeat: row.last_heartbeat,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: LineStatus,
+    pub members: Vec<MachineGroupMemberResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MachineGroupHeartbeatQuery {
+    #[validate(range(min = 1, max = 10080))]
+    pub stale_after_minutes: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupMemberHeartbeat {
+    pub machine_id: Uuid,
+    pub name: String,
+    pub position: i32,
+    pub is_critical: bool,
+    pub status: MachineStatus,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+    /// Whole minutes since the last heartbeat; empty when the machine never sent one
+    pub minutes_since_heartbeat: Option<i64>,
+    /// Reports itself idle or busy without a heartbeat within the stale window
+    pub stale: bool,
+}
+
+/// Heartbeats of every member. A member whose heartbeat went stale counts as offline for the
+/// line status.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupHeartbeatResponse {
+    pub group_id: Uuid,
+    pub status: LineStatus,
+    pub members: Vec<MachineGroupMemberHeartbeat>,
+    pub stale_count: usize,
+    pub stale_after_minutes: u32,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct MachineGroupUtilizationQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Hours a member spent in each state over the window. Time before its first recorded status
+/// is not counted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupMemberUtilization {
+    pub machine_id: Uuid,
+    pub name: String,
+    pub position: i32,
+    pub is_critical: bool,
+    pub busy_hours: f64,
+    pub idle_hours: f64,
+    pub down_hours: f64,
+    /// Busy hours as a share of the window
+    pub utilization_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineGroupUtilizationResponse {
+    pub group_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub members: Vec<MachineGroupMemberUtilization>,
+    /// Hours during which at least one critical member was down
+    pub line_down_hours: f64,
+    pub line_availability_percent: f64,
+    /// Mean utilization of the members
+    pub line_utilization_percent: f64,
+}
@@ -1,19 +1,125 @@
+pub mod access_log;
+pub mod admin;
 pub mod asset;
+pub mod asset_upload;
+pub mod attendance;
 pub mod auth;
+pub mod auth_audit;
+pub mod batch_record;
+pub mod billing;
+pub mod bom_import;
+pub mod calendar;
+pub mod calibration;
+pub mod consignment;
+pub mod dashboard;
+pub mod document_pack;
+pub mod duplicate;
+pub mod encryption;
+pub mod feature_flag;
+pub mod ingest;
 pub mod item;
+pub mod item_datasheet;
+pub mod item_image;
 pub mod job;
+pub mod job_dependency;
+pub mod job_operation;
+pub mod kiosk;
+pub mod kitting;
+pub mod lifecycle;
 pub mod machine;
+pub mod machine_alert;
+pub mod machine_command;
+pub mod machine_credential;
+pub mod machine_group;
+pub mod notification;
+pub mod numbering;
 pub mod order;
+pub mod order_return;
 pub mod person;
+pub mod pricing;
+pub mod print;
+pub mod quality;
+pub mod quantity;
+pub mod quote;
+pub mod recalculation;
+pub mod report;
+pub mod rfq;
+pub mod rls;
+pub mod scorecard;
+pub mod search;
+pub mod service_client;
+pub mod shipping;
+pub mod skill;
+pub mod spc;
+pub mod status_page;
+pub mod stock;
+pub mod telemetry;
 pub mod tenant;
 pub mod token_blacklist;
+pub mod trace;
+pub mod uom;
+pub mod validation_rule;
+pub mod version;
 
+pub use access_log::*;
+pub use admin::*;
 pub use asset::*;
+pub use asset_upload::*;
+pub use attendance::*;
 pub use auth::*;
+pub use auth_audit::*;
+pub use batch_record::*;
+pub use billing::*;
+pub use bom_import::*;
+pub use calendar::*;
+pub use calibration::*;
+pub use consignment::*;
+pub use dashboard::*;
+pub use document_pack::*;
+pub use duplicate::*;
+pub use encryption::*;
+pub use feature_flag::*;
+pub use ingest::*;
 pub use item::*;
+pub use item_datasheet::*;
+pub use item_image::*;
 pub use job::*;
+pub use job_dependency::*;
+pub use job_operation::*;
+pub use kiosk::*;
+pub use kitting::*;
+pub use lifecycle::*;
 pub use machine::*;
+pub use machine_alert::*;
+pub use machine_command::*;
+pub use machine_credential::*;
+pub use machine_group::*;
+pub use notification::*;
+pub use numbering::*;
 pub use order::*;
+pub use order_return::*;
 pub use person::*;
+pub use pricing::*;
+pub use print::*;
+pub use quality::*;
+pub use quantity::*;
+pub use quote::*;
+pub use recalculation::*;
+pub use report::*;
+pub use rfq::*;
+pub use rls::*;
+pub use scorecard::*;
+pub use search::*;
+pub use service_client::*;
+pub use shipping::*;
+pub use skill::*;
+pub use spc::*;
+pub use status_page::*;
+pub use stock::*;
+pub use telemetry::*;
 pub use tenant::*;
 pub use token_blacklist::*;
+pub use trace::*;
+pub use uom::*;
+pub use validation_rule::*;
+pub use version::*;
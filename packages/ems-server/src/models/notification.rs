@@ -0,0 +1,248 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::{entity_comments, notifications, watches};
+
+// Watch, comment and notification models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = watches)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Watch {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = watches)]
+pub struct NewWatch {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = entity_comments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct EntityComment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = entity_comments)]
+pub struct NewEntityComment {
+    pub tenant_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = notifications)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Notification {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub event_type: String,
+    pub title: String,
+    pub detail: serde_json::Value,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Kind of record people can watch and comment on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchEntityType {
+    #[serde(rename = "machine")]
+    Machine,
+    #[serde(rename = "order")]
+    Order,
+    #[serde(rename = "item")]
+    Item,
+}
+
+impl std::fmt::Display for WatchEntityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchEntityType::Machine => write!(f, "machine"),
+            WatchEntityType::Order => write!(f, "order"),
+            WatchEntityType::Item => write!(f, "item"),
+        }
+    }
+}
+
+impl From<WatchEntityType> for String {
+    fn from(entity_type: WatchEntityType) -> Self {
+        entity_type.to_string()
+    }
+}
+
+impl TryFrom<String> for WatchEntityType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "machine" => Ok(WatchEntityType::Machine),
+            "order" => Ok(WatchEntityType::Order),
+            "item" => Ok(WatchEntityType::Item),
+            _ => Err(format!("Invalid watch entity type: {}", value)),
+        }
+    }
+}
+
+/// What happened to a watched record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationEventType {
+    /// A machine or order changed status
+    #[serde(rename = "status_changed")]
+    StatusChanged,
+    /// An item's stock fell to its reorder point, or its minimum level without one
+    #[serde(rename = "low_stock")]
+    LowStock,
+    #[serde(rename = "comment_added")]
+    CommentAdded,
+}
+
+impl std::fmt::Display for NotificationEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationEventType::StatusChanged => write!(f, "status_changed"),
+            NotificationEventType::LowStock => write!(f, "low_stock"),
+            NotificationEventType::CommentAdded => write!(f, "comment_added"),
+        }
+    }
+}
+
+impl TryFrom<String> for NotificationEventType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "status_changed" => Ok(NotificationEventType::StatusChanged),
+            "low_stock" => Ok(NotificationEventType::LowStock),
+            "comment_added" => Ok(NotificationEventType::CommentAdded),
+            _ => Err(format!("Invalid notification event type: {}", value)),
+        }
+    }
+}
+
+// API DTOs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchResponse {
+    pub entity_type: WatchEntityType,
+    pub entity_id: Uuid,
+    pub watching: bool,
+    pub watcher_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WatchedEntityResponse {
+    pub id: Uuid,
+    pub entity_type: WatchEntityType,
+    pub entity_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListWatchesQuery {
+    pub entity_type: Option<WatchEntityType>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, max = 4000))]
+    pub body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentResponse {
+    pub id: Uuid,
+    pub entity_type: WatchEntityType,
+    pub entity_id: Uuid,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<EntityComment> for CommentResponse {
+    type Error = String;
+
+    fn try_from(comment: EntityComment) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: comment.id,
+            entity_type: WatchEntityType::try_from(comment.entity_type)?,
+            entity_id: comment.entity_id,
+            author_id: comment.author_id,
+            body: comment.body,
+            created_at: comment.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListNotificationsQuery {
+    /// Only notifications not yet marked read
+    pub unread: Option<bool>,
+    pub entity_type: Option<WatchEntityType>,
+    pub event_type: Option<NotificationEventType>,
+
+    #[validate(range(min = 1, max = 200))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub entity_type: WatchEntityType,
+    pub entity_id: Uuid,
+    pub event_type: NotificationEventType,
+    pub title: String,
+    pub detail: serde_json::Value,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<Notification> for NotificationResponse {
+    type Error = String;
+
+    fn try_from(notification: Notification) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: notification.id,
+            entity_type: WatchEntityType::try_from(notification.entity_type)?,
+            entity_id: notification.entity_id,
+            event_type: NotificationEventType::try_from(notification.event_type)?,
+            title: notification.title,
+            detail: notification.detail,
+            read_at: notification.read_at,
+            created_at: notification.created_at,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationCountResponse {
+    pub unread: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkNotificationsReadResponse {
+    pub marked: usize,
+}
@@ -0,0 +1,175 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::order::OrderType;
+use crate::models::tenant::Tenant;
+use crate::schema::numbering_sequences;
+use crate::utils::numbering::check_prefix;
+
+// Numbering sequence models
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(belongs_to(Tenant, foreign_key = tenant_id))]
+#[diesel(table_name = numbering_sequences)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NumberingSequence {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub entity: String,
+    pub prefix: String,
+    pub padding: i32,
+    pub yearly_reset: bool,
+    pub next_value: i64,
+    pub period_year: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = numbering_sequences)]
+pub struct NewNumberingSequence {
+    pub tenant_id: Uuid,
+    pub entity: String,
+    pub prefix: String,
+    pub padding: i32,
+    pub yearly_reset: bool,
+    pub next_value: i64,
+}
+
+#[derive(Debug, AsChangeset)]
+#[diesel(table_name = numbering_sequences)]
+pub struct UpdateNumberingSequence {
+    pub prefix: Option<String>,
+    pub padding: Option<i32>,
+    pub yearly_reset: Option<bool>,
+    pub next_value: Option<i64>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Kind of document a sequence numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NumberingEntity {
+    #[serde(rename = "purchase_order")]
+    PurchaseOrder,
+    #[serde(rename = "customer_order")]
+    CustomerOrder,
+    #[serde(rename = "distributor_order")]
+    DistributorOrder,
+    #[serde(rename = "job")]
+    Job,
+    #[serde(rename = "item")]
+    Item,
+    #[serde(rename = "quote")]
+    Quote,
+    #[serde(rename = "return")]
+    Return,
+    #[serde(rename = "rfq")]
+    Rfq,
+}
+
+impl From<&OrderType> for NumberingEntity {
+    fn from(order_type: &OrderType) -> Self {
+        match order_type {
+            OrderType::PurchaseOrder => NumberingEntity::PurchaseOrder,
+            OrderType::CustomerOrder => NumberingEntity::CustomerOrder,
+            OrderType::DistributorOrder => NumberingEntity::DistributorOrder,
+        }
+    }
+}
+
+impl std::fmt::Display for NumberingEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberingEntity::PurchaseOrder => write!(f, "purchase_order"),
+            NumberingEntity::CustomerOrder => write!(f, "customer_order"),
+            NumberingEntity::DistributorOrder => write!(f, "distributor_order"),
+            NumberingEntity::Job => write!(f, "job"),
+            NumberingEntity::Item => write!(f, "item"),
+            NumberingEntity::Quote => write!(f, "quote"),
+            NumberingEntity::Return => write!(f, "return"),
+            NumberingEntity::Rfq => write!(f, "rfq"),
+        }
+    }
+}
+
+impl From<NumberingEntity> for String {
+    fn from(entity: NumberingEntity) -> Self {
+        entity.to_string()
+    }
+}
+
+impl TryFrom<String> for NumberingEntity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "purchase_order" => Ok(NumberingEntity::PurchaseOrder),
+            "customer_order" => Ok(NumberingEntity::CustomerOrder),
+            "distributor_order" => Ok(NumberingEntity::DistributorOrder),
+            "job" => Ok(NumberingEntity::Job),
+            "item" => Ok(NumberingEntity::Item),
+            "quote" => Ok(NumberingEntity::Quote),
+            "return" => Ok(NumberingEntity::Return),
+            "rfq" => Ok(NumberingEntity::Rfq),
+            _ => Err(format!("Invalid numbering entity: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+
+/// Creates the sequence of an entity, or changes the fields given of an existing one.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PutNumberingSequenceRequest {
+    /// Text before the counter; `{YYYY}` and `{YY}` are replaced by the year
+    #[validate(length(max = 30))]
+    pub prefix: Option<String>,
+
+    /// Minimum digits of the counter; 4 when left out
+    #[validate(range(min = 1, max = 12))]
+    pub padding: Option<i32>,
+
+    /// Restart the counter at 1 each calendar year
+    pub yearly_reset: Option<bool>,
+
+    /// Counter value the next number is drawn from
+    #[validate(range(min = 1))]
+    pub next_value: Option<i64>,
+}
+
+impl PutNumberingSequenceRequest {
+    /// Checks the prefix only uses the year placeholders
+    pub fn check(&self) -> Result<(), String> {
+        match &self.prefix {
+            Some(prefix) => check_prefix(prefix),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NumberingSequenceResponse {
+    pub id: Uuid,
+    pub entity: String,
+    pub prefix: String,
+    pub padding: i32,
+    pub yearly_reset: bool,
+    pub next_value: i64,
+    pub period_year: Option<i32>,
+    /// Number the next document would get, not counting numbers already in use
+    pub preview: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A number drawn from a sequence.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NextNumberResponse {
+    pub entity: String,
+    pub number: String,
+    pub value: i64,
+}
@@ -5,6 +5,7 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::schema::*;
+use crate::utils::i18n::Locale;
 
 // Core Order Models
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
@@ -25,6 +26,9 @@ pub struct Order {
     pub metadata: Option<serde_json::Value>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Left out of lists unless archived records are asked for
+    pub archived: bool,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Insertable)]
@@ -59,6 +63,10 @@ pub struct OrderItem {
     pub notes: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub expected_date: Option<DateTime<Utc>>,
+    pub backordered: bool,
+    pub uom_id: Option<Uuid>,
+    pub base_quantity: i32,
 }
 
 #[derive(Debug, Insertable)]
@@ -72,6 +80,9 @@ pub struct NewOrderItem {
     pub unit_price: f64,
     pub extended_price: f64,
     pub notes: Option<String>,
+    pub expected_date: Option<DateTime<Utc>>,
+    pub uom_id: Option<Uuid>,
+    pub base_quantity: i32,
 }
 
 // Order History Models
@@ -160,6 +171,19 @@ pub enum OrderStatus {
     Paid,
 }
 
+impl OrderStatus {
+    /// Statuses after which the order is closed and can be archived.
+    pub const CLOSED: [OrderStatus; 3] = [
+        OrderStatus::Fulfilled,
+        OrderStatus::Cancelled,
+        OrderStatus::Paid,
+    ];
+
+    pub fn is_closed(&self) -> bool {
+        Self::CLOSED.contains(self)
+    }
+}
+
 impl std::fmt::Display for OrderStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -239,8 +263,9 @@ impl TryFrom<String> for ExternalEntityType {
 // Request/Response Models
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateOrderRequest {
+    /// Drawn from the order type's numbering sequence when left out
     #[validate(length(min = 1, max = 50))]
-    pub order_number: String,
+    pub order_number: Option<String>,
 
     pub order_type: OrderType,
 
@@ -262,6 +287,7 @@ pub struct CreateOrderRequest {
 
     pub metadata: Option<serde_json::Value>,
 
+    #[validate]
     pub items: Vec<CreateOrderItemRequest>,
 }
 
@@ -283,6 +309,13 @@ pub struct CreateOrderItemRequest {
 
     #[validate(length(max = 500))]
     pub notes: Option<String>,
+
+    /// Expected receipt date on purchase orders, requested ship date on sales orders
+    pub expected_date: Option<DateTime<Utc>>,
+
+    /// Unit of the quantity; defaults to the item's purchase unit on purchase orders and its
+    /// base unit otherwise
+    pub uom_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -320,6 +353,7 @@ pub struct OrderResponse {
     pub metadata: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived: bool,
     pub items: Vec<OrderItemResponse>,
 }
 
@@ -333,10 +367,36 @@ pub struct OrderItemResponse {
     pub unit_price: f64,
     pub extended_price: f64,
     pub notes: Option<String>,
+    pub expected_date: Option<DateTime<Utc>>,
+    pub backordered: bool,
+    pub uom_id: Option<Uuid>,
+    /// Quantity in the item's base unit, the unit stock is counted in
+    pub base_quantity: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl From<OrderItem> for OrderItemResponse {
+    fn from(item: OrderItem) -> Self {
+        Self {
+            id: item.id,
+            item_id: item.item_id,
+            item_name: item.item_name,
+            item_description: item.item_description,
+            quantity: item.quantity,
+            unit_price: item.unit_price,
+            extended_price: item.extended_price,
+            notes: item.notes,
+            expected_date: item.expected_date,
+            backordered: item.backordered,
+            uom_id: item.uom_id,
+            base_quantity: item.base_quantity,
+            created_at: item.created_at.unwrap_or_else(Utc::now),
+            updated_at: item.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PurchaseOrderResponse {
     pub id: Uuid,
@@ -386,3 +446,177 @@ pub struct DistributorOrderResponse {
 pub struct CreateOrderIdResponse {
     pub id: Uuid,
 }
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SendOrderConfirmationRequest {
+    /// Addresses the confirmation is emailed to; defaults to the customer's email
+    #[validate(length(max = 50))]
+    pub recipients: Option<Vec<String>>,
+
+    #[validate(length(max = 5000))]
+    pub message: Option<String>,
+
+    /// Language of the email and PDF; defaults to the tenant's locale
+    pub locale: Option<Locale>,
+}
+
+impl SendOrderConfirmationRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match self
+            .recipients
+            .iter()
+            .flatten()
+            .find(|r| !validator::validate_email(r.as_str()))
+        {
+            Some(invalid) => Err(format!("Invalid recipient email: {}", invalid)),
+            None => Ok(()),
+        }
+    }
+}
+
+// Order analytics models
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AnalyticsGranularity {
+    #[serde(rename = "day")]
+    Day,
+    #[serde(rename = "week")]
+    Week,
+    #[serde(rename = "month")]
+    Month,
+    #[serde(rename = "quarter")]
+    Quarter,
+}
+
+impl AnalyticsGranularity {
+    /// Shortest length of one period, used to bound how many periods a window spans
+    pub fn min_days(&self) -> i64 {
+        match self {
+            AnalyticsGranularity::Day => 1,
+            AnalyticsGranularity::Week => 7,
+            AnalyticsGranularity::Month => 28,
+            AnalyticsGranularity::Quarter => 89,
+        }
+    }
+}
+
+impl std::fmt::Display for AnalyticsGranularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalyticsGranularity::Day => write!(f, "day"),
+            AnalyticsGranularity::Week => write!(f, "week"),
+            AnalyticsGranularity::Month => write!(f, "month"),
+            AnalyticsGranularity::Quarter => write!(f, "quarter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OrderAnalyticsGroup {
+    /// The order's external entity: its customer, vendor or distributor
+    #[serde(rename = "customer")]
+    Customer,
+    /// Category of the items on each line; an order counts once in every category it contains
+    #[serde(rename = "item_category")]
+    ItemCategory,
+}
+
+/// Most periods one analytics query may return per group
+pub const MAX_ANALYTICS_PERIODS: i64 = 400;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct OrderAnalyticsQuery {
+    /// Defaults to week
+    pub granularity: Option<AnalyticsGranularity>,
+    /// Start of the window; defaults to 90 days before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// End of the window (exclusive); defaults to now
+    pub to: Option<DateTime<Utc>>,
+    /// Defaults to customer orders
+    #[serde(rename = "type")]
+    pub order_type: Option<OrderType>,
+    pub group_by: Option<OrderAnalyticsGroup>,
+}
+
+impl OrderAnalyticsQuery {
+    pub fn check(&self) -> Result<(), String> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from >= to {
+                return Err("from must be before to".to_string());
+            }
+            let granularity = self.granularity.unwrap_or(AnalyticsGranularity::Week);
+            if (to - from).num_days() / granularity.min_days() > MAX_ANALYTICS_PERIODS {
+                return Err(format!(
+                    "The window spans more than {} periods of a {}",
+                    MAX_ANALYTICS_PERIODS, granularity
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct OrderAnalyticsRow {
+    #[diesel(sql_type = diesel::sql_types::Timestamptz)]
+    pub period_start: DateTime<Utc>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub group_key: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+    pub group_label: Option<String>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub order_count: i64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub revenue: f64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub fulfilled_count: i64,
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Double>)]
+    pub avg_lead_time_days: Option<f64>,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub due_count: i64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub on_time_count: i64,
+}
+
+/// Orders placed in one period (and group). Lead time runs from the order date to the
+/// order's first fulfillment; an order is on time when that is no later than the latest
+/// expected date of its lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAnalyticsBucket {
+    pub period_start: DateTime<Utc>,
+    pub group: Option<String>,
+    pub group_label: Option<String>,
+    pub order_count: i64,
+    pub revenue: f64,
+    pub fulfilled_count: i64,
+    pub avg_lead_time_days: Option<f64>,
+    /// Share of fulfilled orders with an expected date that were fulfilled by it, 0 to 100
+    pub on_time_percent: Option<f64>,
+}
+
+impl From<OrderAnalyticsRow> for OrderAnalyticsBucket {
+    fn from(row: OrderAnalyticsRow) -> Self {
+        Self {
+            period_start: row.period_start,
+            group: row.group_key,
+            group_label: row.group_label,
+            order_count: row.order_count,
+            revenue: row.revenue,
+            fulfilled_count: row.fulfilled_count,
+            avg_lead_time_days: row.avg_lead_time_days,
+            on_time_percent: (row.due_count > 0)
+                .then(|| 100.0 * row.on_time_count as f64 / row.due_count as f64),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderAnalyticsResponse {
+    pub granularity: AnalyticsGranularity,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub order_type: OrderType,
+    pub group_by: Option<OrderAnalyticsGroup>,
+    /// By period, then group. Without grouping every period in the window is present, even
+    /// when no orders were placed in it.
+    pub buckets: Vec<OrderAnalyticsBucket>,
+}
@@ -0,0 +1,508 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::ItemContext;
+use crate::schema::*;
+
+// Order return (RMA) models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = order_returns)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrderReturn {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub order_id: Uuid,
+    pub rma_number: String,
+    pub status: String,
+    pub quarantine_location: String,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub received_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = order_returns)]
+pub struct NewOrderReturn {
+    pub tenant_id: Uuid,
+    pub order_id: Uuid,
+    pub rma_number: String,
+    pub quarantine_location: String,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = order_return_lines)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrderReturnLine {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub return_id: Uuid,
+    pub order_item_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub reason: String,
+    pub reason_notes: Option<String>,
+    pub received_quantity: i32,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = order_return_lines)]
+pub struct NewOrderReturnLine {
+    pub tenant_id: Uuid,
+    pub return_id: Uuid,
+    pub order_item_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub quantity: i32,
+    pub reason: String,
+    pub reason_notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = order_return_dispositions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrderReturnDisposition {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub return_line_id: Uuid,
+    pub disposition: String,
+    pub quantity: i32,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = order_return_dispositions)]
+pub struct NewOrderReturnDisposition {
+    pub tenant_id: Uuid,
+    pub return_line_id: Uuid,
+    pub disposition: String,
+    pub quantity: i32,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = order_return_credit_notes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct OrderReturnCreditNote {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub return_id: Uuid,
+    pub credit_note_number: String,
+    pub amount: f64,
+    pub issued_at: DateTime<Utc>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = order_return_credit_notes)]
+pub struct NewOrderReturnCreditNote {
+    pub tenant_id: Uuid,
+    pub return_id: Uuid,
+    pub credit_note_number: String,
+    pub amount: f64,
+    pub issued_at: DateTime<Utc>,
+    pub notes: Option<String>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReturnStatus {
+    #[serde(rename = "requested")]
+    Requested,
+    #[serde(rename = "partially_received")]
+    PartiallyReceived,
+    #[serde(rename = "received")]
+    Received,
+    #[serde(rename = "closed")]
+    Closed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl std::fmt::Display for ReturnStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnStatus::Requested => write!(f, "requested"),
+            ReturnStatus::PartiallyReceived => write!(f, "partially_received"),
+            ReturnStatus::Received => write!(f, "received"),
+            ReturnStatus::Closed => write!(f, "closed"),
+            ReturnStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<ReturnStatus> for String {
+    fn from(status: ReturnStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for ReturnStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "requested" => Ok(ReturnStatus::Requested),
+            "partially_received" => Ok(ReturnStatus::PartiallyReceived),
+            "received" => Ok(ReturnStatus::Received),
+            "closed" => Ok(ReturnStatus::Closed),
+            "cancelled" => Ok(ReturnStatus::Cancelled),
+            _ => Err(format!("Invalid return status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReturnReason {
+    #[serde(rename = "damaged")]
+    Damaged,
+    #[serde(rename = "defective")]
+    Defective,
+    #[serde(rename = "wrong_item")]
+    WrongItem,
+    #[serde(rename = "not_needed")]
+    NotNeeded,
+    #[serde(rename = "other")]
+    Other,
+}
+
+impl std::fmt::Display for ReturnReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnReason::Damaged => write!(f, "damaged"),
+            ReturnReason::Defective => write!(f, "defective"),
+            ReturnReason::WrongItem => write!(f, "wrong_item"),
+            ReturnReason::NotNeeded => write!(f, "not_needed"),
+            ReturnReason::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl From<ReturnReason> for String {
+    fn from(reason: ReturnReason) -> Self {
+        reason.to_string()
+    }
+}
+
+impl TryFrom<String> for ReturnReason {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "damaged" => Ok(ReturnReason::Damaged),
+            "defective" => Ok(ReturnReason::Defective),
+            "wrong_item" => Ok(ReturnReason::WrongItem),
+            "not_needed" => Ok(ReturnReason::NotNeeded),
+            "other" => Ok(ReturnReason::Other),
+            _ => Err(format!("Invalid return reason: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReturnDisposition {
+    #[serde(rename = "restock")]
+    Restock,
+    #[serde(rename = "scrap")]
+    Scrap,
+    #[serde(rename = "repair")]
+    Repair,
+}
+
+impl std::fmt::Display for ReturnDisposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnDisposition::Restock => write!(f, "restock"),
+            ReturnDisposition::Scrap => write!(f, "scrap"),
+            ReturnDisposition::Repair => write!(f, "repair"),
+        }
+    }
+}
+
+impl From<ReturnDisposition> for String {
+    fn from(disposition: ReturnDisposition) -> Self {
+        disposition.to_string()
+    }
+}
+
+impl TryFrom<String> for ReturnDisposition {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "restock" => Ok(ReturnDisposition::Restock),
+            "scrap" => Ok(ReturnDisposition::Scrap),
+            "repair" => Ok(ReturnDisposition::Repair),
+            _ => Err(format!("Invalid return disposition: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateReturnLineRequest {
+    pub order_item_id: Uuid,
+
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+
+    pub reason: ReturnReason,
+
+    #[validate(length(max = 1000))]
+    pub reason_notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateOrderReturnRequest {
+    pub order_id: Uuid,
+
+    /// RMA number quoted to the customer; drawn from the return numbering sequence, or random,
+    /// when left out
+    #[validate(length(min = 1, max = 50))]
+    pub rma_number: Option<String>,
+
+    /// Where received stock is held until dispositioned; defaults to `QUARANTINE`
+    #[validate(length(min = 1, max = 100))]
+    pub quarantine_location: Option<String>,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 200))]
+    #[validate]
+    pub lines: Vec<CreateReturnLineRequest>,
+}
+
+impl CreateOrderReturnRequest {
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        match self.lines.iter().find(|l| !seen.insert(l.order_item_id)) {
+            Some(line) => Err(format!("Order item returned twice: {}", line.order_item_id)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ReceiveReturnLineRequest {
+    pub line_id: Uuid,
+
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ReceiveReturnRequest {
+    /// Moves the return's quarantine location, e.g. to the bay the goods arrived at
+    #[validate(length(min = 1, max = 100))]
+    pub quarantine_location: Option<String>,
+
+    #[validate(length(min = 1, max = 200))]
+    #[validate]
+    pub lines: Vec<ReceiveReturnLineRequest>,
+}
+
+impl ReceiveReturnRequest {
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        match self.lines.iter().find(|l| !seen.insert(l.line_id)) {
+            Some(line) => Err(format!("Return line received twice: {}", line.line_id)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DispositionReturnRequest {
+    pub line_id: Uuid,
+
+    pub disposition: ReturnDisposition,
+
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+
+    /// Inventory restocked into; defaults to finished goods
+    pub context: Option<ItemContext>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+impl DispositionReturnRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.context.is_some() && self.disposition != ReturnDisposition::Restock {
+            return Err("context only applies to restock".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCreditNoteRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub credit_note_number: String,
+
+    #[validate(range(min = 0.01))]
+    pub amount: f64,
+
+    pub issued_at: Option<DateTime<Utc>>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListOrderReturnsQuery {
+    pub order_id: Option<Uuid>,
+    pub status: Option<ReturnStatus>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReturnDispositionResponse {
+    pub id: Uuid,
+    pub disposition: ReturnDisposition,
+    pub quantity: i32,
+    pub stock_movement_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OrderReturnDisposition> for ReturnDispositionResponse {
+    fn from(disposition: OrderReturnDisposition) -> Self {
+        Self {
+            id: disposition.id,
+            disposition: ReturnDisposition::try_from(disposition.disposition)
+                .unwrap_or(ReturnDisposition::Scrap),
+            quantity: disposition.quantity,
+            stock_movement_id: disposition.stock_movement_id,
+            person_id: disposition.person_id,
+            notes: disposition.notes,
+            created_at: disposition.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderReturnLineResponse {
+    pub id: Uuid,
+    pub order_item_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub item_name: String,
+    pub unit_price: f64,
+    pub quantity: i32,
+    pub reason: ReturnReason,
+    pub reason_notes: Option<String>,
+    pub received_quantity: i32,
+    /// Received but not yet dispositioned, i.e. still in quarantine
+    pub quarantined_quantity: i32,
+    pub dispositions: Vec<ReturnDispositionResponse>,
+}
+
+impl OrderReturnLineResponse {
+    pub fn new(
+        line: OrderReturnLine,
+        item_name: String,
+        unit_price: f64,
+        dispositions: Vec<OrderReturnDisposition>,
+    ) -> Self {
+        let dispositioned: i32 = dispositions.iter().map(|d| d.quantity).sum();
+        Self {
+            id: line.id,
+            order_item_id: line.order_item_id,
+            item_id: line.item_id,
+            item_name,
+            unit_price,
+            quantity: line.quantity,
+            reason: ReturnReason::try_from(line.reason).unwrap_or(ReturnReason::Other),
+            reason_notes: line.reason_notes,
+            received_quantity: line.received_quantity,
+            quarantined_quantity: line.received_quantity - dispositioned,
+            dispositions: dispositions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreditNoteResponse {
+    pub id: Uuid,
+    pub credit_note_number: String,
+    pub amount: f64,
+    pub issued_at: DateTime<Utc>,
+    pub notes: Option<String>,
+}
+
+impl From<OrderReturnCreditNote> for CreditNoteResponse {
+    fn from(credit_note: OrderReturnCreditNote) -> Self {
+        Self {
+            id: credit_note.id,
+            credit_note_number: credit_note.credit_note_number,
+            amount: credit_note.amount,
+            issued_at: credit_note.issued_at,
+            notes: credit_note.notes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderReturnResponse {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub rma_number: String,
+    pub status: ReturnStatus,
+    pub quarantine_location: String,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub received_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub lines: Vec<OrderReturnLineResponse>,
+    pub credit_notes: Vec<CreditNoteResponse>,
+    /// Value of the returned quantities at their order prices
+    pub return_value: f64,
+    pub credited_amount: f64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OrderReturnResponse {
+    pub fn new(
+        order_return: OrderReturn,
+        lines: Vec<OrderReturnLineResponse>,
+        credit_notes: Vec<OrderReturnCreditNote>,
+    ) -> Self {
+        Self {
+            id: order_return.id,
+            order_id: order_return.order_id,
+            rma_number: order_return.rma_number,
+            status: ReturnStatus::try_from(order_return.status).unwrap_or(ReturnStatus::Requested),
+            quarantine_location: order_return.quarantine_location,
+            notes: order_return.notes,
+            created_by_id: order_return.created_by_id,
+            received_at: order_return.received_at,
+            closed_at: order_return.closed_at,
+            return_value: lines.iter().map(|l| l.quantity as f64 * l.unit_price).sum(),
+            credited_amount: credit_notes.iter().map(|c| c.amount).sum(),
+            lines,
+            credit_notes: credit_notes.into_iter().map(Into::into).collect(),
+            created_at: order_return.created_at.unwrap_or_else(Utc::now),
+            updated_at: order_return.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -5,7 +5,9 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::models::tenant::Tenant;
+use crate::models::OperatorAssignmentType;
 use crate::schema::*;
+use crate::utils::encryption::FieldCipher;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = person)]
@@ -21,6 +23,19 @@ pub struct Person {
     pub last_login: Option<DateTime<Utc>>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Set only for accounts managed by the local auth backend
+    #[serde(skip_serializing)]
+    pub password_hash: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+}
+
+impl Person {
+    /// The person with their phone number decrypted. Phone numbers are stored encrypted with
+    /// the platform key, since a person can belong to several tenants.
+    pub fn open_phone(mut self, cipher: &FieldCipher) -> anyhow::Result<Self> {
+        self.phone = cipher.open_opt(self.phone)?;
+        Ok(self)
+    }
 }
 
 #[derive(Debug, Insertable)]
@@ -182,6 +197,23 @@ pub struct NewDistributorPerson {
     pub commission_rate: Option<String>,
 }
 
+// Cached password hash for signing in while Supabase is unavailable
+#[derive(Debug, Insertable)]
+#[diesel(table_name = person_credentials)]
+pub struct NewPersonCredential {
+    pub person_id: Uuid,
+    pub password_hash: String,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = email_verification_tokens)]
+pub struct NewEmailVerificationToken {
+    pub person_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PersonRole {
     #[serde(rename = "pending")]
@@ -456,3 +488,115 @@ pub struct DistributorPersonData {
 pub struct CreatePersonIdResponse {
     pub id: Uuid,
 }
+
+// Bulk import
+
+/// A person parsed from an import CSV.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonImportRow {
+    /// 1-based line number in the uploaded file
+    pub line: usize,
+    pub name: String,
+    /// Lowercased
+    pub email: String,
+    pub phone: Option<String>,
+    pub role: PersonRole,
+    pub department: Option<String>,
+    pub position: Option<String>,
+    pub employee_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PersonImportStatus {
+    /// A new person was created
+    #[serde(rename = "created")]
+    Created,
+    /// A person from another tenant was added to this tenant
+    #[serde(rename = "linked")]
+    Linked,
+    /// The person already belongs to this tenant; only machine assignments are added
+    #[serde(rename = "existing")]
+    Existing,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl std::fmt::Display for PersonImportStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersonImportStatus::Created => write!(f, "created"),
+            PersonImportStatus::Linked => write!(f, "linked"),
+            PersonImportStatus::Existing => write!(f, "existing"),
+            PersonImportStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ImportMachineAssignment {
+    /// Email of a person in the CSV
+    #[validate(email)]
+    pub email: String,
+    pub machine_id: Uuid,
+    pub assignment_type: OperatorAssignmentType,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ImportPersonsRequest {
+    /// CSV with a header row: `name`, `email`, and optionally `role` (default internal),
+    /// `department`, `position`, `employee_id` and `phone`
+    #[validate(length(min = 1))]
+    pub csv: String,
+
+    /// Email each created or linked person an invitation
+    pub send_invites: Option<bool>,
+
+    /// Validate the file and assignments without saving anything
+    pub dry_run: Option<bool>,
+
+    #[validate]
+    pub assignments: Option<Vec<ImportMachineAssignment>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PersonImportRowResult {
+    pub line: usize,
+    pub email: Option<String>,
+    pub status: PersonImportStatus,
+    /// Not set for new persons until the import is saved
+    pub person_id: Option<Uuid>,
+    pub machines_assigned: usize,
+    pub invited: bool,
+    pub invite_error: Option<String>,
+    pub errors: Vec<String>,
+}
+
+impl PersonImportRowResult {
+    pub fn failed(line: usize, email: Option<String>, errors: Vec<String>) -> Self {
+        Self {
+            line,
+            email,
+            status: PersonImportStatus::Failed,
+            person_id: None,
+            machines_assigned: 0,
+            invited: false,
+            invite_error: None,
+            errors,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportPersonsResponse {
+    pub dry_run: bool,
+    /// False when any row failed; the import is all-or-nothing
+    pub imported: bool,
+    pub total_rows: usize,
+    pub created: usize,
+    pub linked: usize,
+    pub existing: usize,
+    pub failed: usize,
+    pub invited: usize,
+    pub rows: Vec<PersonImportRowResult>,
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Price history models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_price_history)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemPriceHistory {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub vendor_id: Option<Uuid>,
+    pub unit_price: f64,
+    pub price_breaks: serde_json::Value,
+    pub currency: Option<String>,
+    pub lead_time: Option<i32>,
+    pub source: String,
+    pub recorded_by_id: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_price_history)]
+pub struct NewItemPriceHistory {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub vendor_id: Option<Uuid>,
+    pub unit_price: f64,
+    pub price_breaks: serde_json::Value,
+    pub currency: Option<String>,
+    pub lead_time: Option<i32>,
+    pub source: String,
+    pub recorded_by_id: Option<Uuid>,
+}
+
+// Price list models
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PriceBreak {
+    pub quantity: i32,
+    pub unit_price: f64,
+}
+
+/// One parsed row of a vendor price list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceListRow {
+    /// 1-based line number in the uploaded file
+    pub line: usize,
+    pub mfr_part_number: String,
+    pub manufacturer: Option<String>,
+    /// Ascending by quantity, at least one entry
+    pub price_breaks: Vec<PriceBreak>,
+    pub lead_time: Option<i32>,
+    pub currency: Option<String>,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceListImportQuery {
+    /// Vendor the price list comes from; only that vendor's records (or records without a
+    /// vendor) are updated
+    pub vendor_id: Option<Uuid>,
+
+    /// Validate and match the file without saving anything
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriceListRowIssue {
+    pub line: usize,
+    pub mfr_part_number: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PriceListImportResponse {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    /// Rows matched to a vendor inventory record
+    pub matched: usize,
+    /// Matched rows that changed price or lead time
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Rows that could not be parsed or matched
+    pub issues: Vec<PriceListRowIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PriceHistoryQuery {
+    pub vendor_id: Option<Uuid>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemPriceHistoryResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub vendor_id: Option<Uuid>,
+    pub unit_price: f64,
+    pub price_breaks: Vec<PriceBreak>,
+    pub currency: Option<String>,
+    pub lead_time: Option<i32>,
+    pub source: String,
+    pub recorded_by_id: Option<Uuid>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl From<ItemPriceHistory> for ItemPriceHistoryResponse {
+    fn from(history: ItemPriceHistory) -> Self {
+        Self {
+            id: history.id,
+            item_id: history.item_id,
+            vendor_id: history.vendor_id,
+            unit_price: history.unit_price,
+            price_breaks: serde_json::from_value(history.price_breaks).unwrap_or_default(),
+            currency: history.currency,
+            lead_time: history.lead_time,
+            source: history.source,
+            recorded_by_id: history.recorded_by_id,
+            recorded_at: history.recorded_at,
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::ItemContext;
+use crate::schema::*;
+use crate::utils::label::{check_printer_port, printer_url, DEFAULT_PRINTER_PORT};
+
+// Printer models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = printers)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Printer {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub host: String,
+    pub port: i32,
+    pub dpi: i32,
+    pub label_width_mm: i32,
+    pub label_height_mm: i32,
+    pub is_default: bool,
+    pub is_active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = printers)]
+pub struct NewPrinter {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub host: String,
+    pub port: Option<i32>,
+    pub dpi: Option<i32>,
+    pub label_width_mm: Option<i32>,
+    pub label_height_mm: Option<i32>,
+    pub is_default: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+// Print queue models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = print_jobs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PrintJob {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub printer_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub copies: i32,
+    pub zpl: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub requested_by_id: Option<Uuid>,
+    pub printed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = print_jobs)]
+pub struct NewPrintJob {
+    pub tenant_id: Uuid,
+    pub printer_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub copies: i32,
+    pub zpl: String,
+    pub requested_by_id: Option<Uuid>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PrintJobStatus {
+    #[serde(rename = "queued")]
+    Queued,
+    #[serde(rename = "printing")]
+    Printing,
+    #[serde(rename = "printed")]
+    Printed,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl std::fmt::Display for PrintJobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrintJobStatus::Queued => write!(f, "queued"),
+            PrintJobStatus::Printing => write!(f, "printing"),
+            PrintJobStatus::Printed => write!(f, "printed"),
+            PrintJobStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<PrintJobStatus> for String {
+    fn from(status: PrintJobStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for PrintJobStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "queued" => Ok(PrintJobStatus::Queued),
+            "printing" => Ok(PrintJobStatus::Printing),
+            "printed" => Ok(PrintJobStatus::Printed),
+            "failed" => Ok(PrintJobStatus::Failed),
+            _ => Err(format!("Invalid print job status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreatePrinterRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(max = 100))]
+    pub location: Option<String>,
+
+    /// Hostname or IP address of the printer; never one on the server's own network
+    #[validate(length(min = 1, max = 255), custom = "validate_printer_host")]
+    pub host: String,
+
+    /// Defaults to 9100, the only port allowed unless PRINTER_ALLOWED_PORTS says otherwise
+    #[validate(custom = "validate_printer_port")]
+    pub port: Option<i32>,
+
+    /// Defaults to 203; one of 152, 203, 300 or 600
+    #[validate(custom = "validate_printer_dpi")]
+    pub dpi: Option<i32>,
+
+    #[validate(range(min = 1, max = 300))]
+    pub label_width_mm: Option<i32>,
+
+    #[validate(range(min = 1, max = 300))]
+    pub label_height_mm: Option<i32>,
+
+    pub is_default: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdatePrinterRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 100))]
+    pub location: Option<String>,
+
+    #[validate(length(min = 1, max = 255), custom = "validate_printer_host")]
+    pub host: Option<String>,
+
+    #[validate(custom = "validate_printer_port")]
+    pub port: Option<i32>,
+
+    #[validate(custom = "validate_printer_dpi")]
+    pub dpi: Option<i32>,
+
+    #[validate(range(min = 1, max = 300))]
+    pub label_width_mm: Option<i32>,
+
+    #[validate(range(min = 1, max = 300))]
+    pub label_height_mm: Option<i32>,
+
+    pub is_default: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+// Print head resolutions supported by ZPL printers
+pub const SUPPORTED_DPI: [i32; 4] = [152, 203, 300, 600];
+
+fn validate_printer_dpi(dpi: i32) -> Result<(), ValidationError> {
+    if SUPPORTED_DPI.contains(&dpi) {
+        Ok(())
+    } else {
+        Err(invalid_printer(
+            "dpi",
+            format!("Unsupported printer resolution: {} dpi", dpi),
+        ))
+    }
+}
+
+fn validate_printer_port(port: i32) -> Result<(), ValidationError> {
+    let port = u16::try_from(port)
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| invalid_printer("port", format!("Invalid printer port: {}", port)))?;
+    check_printer_port(port).map_err(|message| invalid_printer("port", message))
+}
+
+// The port is checked on its own; the host is checked as if on the default port
+fn validate_printer_host(host: &str) -> Result<(), ValidationError> {
+    printer_url(host, DEFAULT_PRINTER_PORT)
+        .map(|_| ())
+        .map_err(|message| invalid_printer("host", message))
+}
+
+fn invalid_printer(code: &'static str, message: String) -> ValidationError {
+    let mut error = ValidationError::new(code);
+    error.message = Some(message.into());
+    error
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrinterResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub location: Option<String>,
+    pub host: String,
+    pub port: i32,
+    pub dpi: i32,
+    pub label_width_mm: i32,
+    pub label_height_mm: i32,
+    pub is_default: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Printer> for PrinterResponse {
+    fn from(printer: Printer) -> Self {
+        Self {
+            id: printer.id,
+            name: printer.name,
+            location: printer.location,
+            host: printer.host,
+            port: printer.port,
+            dpi: printer.dpi,
+            label_width_mm: printer.label_width_mm,
+            label_height_mm: printer.label_height_mm,
+            is_default: printer.is_default,
+            is_active: printer.is_active,
+            created_at: printer.created_at.unwrap_or_else(Utc::now),
+            updated_at: printer.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PrintLabelQuery {
+    /// Omit to use the default printer for `location`, then the tenant default printer
+    pub printer: Option<Uuid>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub location: Option<String>,
+
+    #[validate(range(min = 1, max = 100))]
+    pub copies: Option<i32>,
+
+    /// Inventory record whose storage location is printed; defaults to the first one found
+    pub context: Option<ItemContext>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListPrintJobsQuery {
+    pub status: Option<PrintJobStatus>,
+    pub printer_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrintJobResponse {
+    pub id: Uuid,
+    pub printer_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub copies: i32,
+    pub status: PrintJobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    /// Next retry; only meaningful while the job is queued
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub requested_by_id: Option<Uuid>,
+    pub printed_at: Option<DateTime<Utc>>,
+    pub zpl: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<PrintJob> for PrintJobResponse {
+    fn from(job: PrintJob) -> Self {
+        Self {
+            id: job.id,
+            printer_id: job.printer_id,
+            item_id: job.item_id,
+            copies: job.copies,
+            status: PrintJobStatus::try_from(job.status).unwrap_or(PrintJobStatus::Queued),
+            attempts: job.attempts,
+            max_attempts: job.max_attempts,
+            next_attempt_at: job.next_attempt_at,
+            last_error: job.last_error,
+            requested_by_id: job.requested_by_id,
+            printed_at: job.printed_at,
+            zpl: job.zpl,
+            created_at: job.created_at.unwrap_or_else(Utc::now),
+            updated_at: job.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Non-conformance (NCR) and CAPA models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = non_conformances)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct NonConformance {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ncr_number: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub source_type: String,
+    pub job_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub lot_number: Option<String>,
+    pub quantity_affected: Option<i32>,
+    pub severity: String,
+    pub status: String,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub raised_by_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closed_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub vendor_id: Option<Uuid>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = non_conformances)]
+pub struct NewNonConformance {
+    pub tenant_id: Uuid,
+    pub ncr_number: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub source_type: String,
+    pub job_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub lot_number: Option<String>,
+    pub quantity_affected: Option<i32>,
+    pub severity: String,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub raised_by_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = capa_actions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CapaAction {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub ncr_id: Uuid,
+    pub action_type: String,
+    pub description: String,
+    pub status: String,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub outcome: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub completed_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = capa_actions)]
+pub struct NewCapaAction {
+    pub tenant_id: Uuid,
+    pub ncr_id: Uuid,
+    pub action_type: String,
+    pub description: String,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NcrSourceType {
+    #[serde(rename = "job")]
+    Job,
+    #[serde(rename = "lot")]
+    Lot,
+    #[serde(rename = "machine")]
+    Machine,
+}
+
+impl std::fmt::Display for NcrSourceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NcrSourceType::Job => write!(f, "job"),
+            NcrSourceType::Lot => write!(f, "lot"),
+            NcrSourceType::Machine => write!(f, "machine"),
+        }
+    }
+}
+
+impl From<NcrSourceType> for String {
+    fn from(source_type: NcrSourceType) -> Self {
+        source_type.to_string()
+    }
+}
+
+impl TryFrom<String> for NcrSourceType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "job" => Ok(NcrSourceType::Job),
+            "lot" => Ok(NcrSourceType::Lot),
+            "machine" => Ok(NcrSourceType::Machine),
+            _ => Err(format!("Invalid NCR source type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NcrSeverity {
+    #[serde(rename = "minor")]
+    Minor,
+    #[serde(rename = "major")]
+    Major,
+    #[serde(rename = "critical")]
+    Critical,
+}
+
+impl std::fmt::Display for NcrSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NcrSeverity::Minor => write!(f, "minor"),
+            NcrSeverity::Major => write!(f, "major"),
+            NcrSeverity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl From<NcrSeverity> for String {
+    fn from(severity: NcrSeverity) -> Self {
+        severity.to_string()
+    }
+}
+
+impl TryFrom<String> for NcrSeverity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "minor" => Ok(NcrSeverity::Minor),
+            "major" => Ok(NcrSeverity::Major),
+            "critical" => Ok(NcrSeverity::Critical),
+            _ => Err(format!("Invalid NCR severity: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NcrStatus {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "closed")]
+    Closed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl std::fmt::Display for NcrStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NcrStatus::Open => write!(f, "open"),
+            NcrStatus::Closed => write!(f, "closed"),
+            NcrStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<NcrStatus> for String {
+    fn from(status: NcrStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for NcrStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "open" => Ok(NcrStatus::Open),
+            "closed" => Ok(NcrStatus::Closed),
+            "cancelled" => Ok(NcrStatus::Cancelled),
+            _ => Err(format!("Invalid NCR status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CapaActionType {
+    #[serde(rename = "containment")]
+    Containment,
+    #[serde(rename = "root_cause")]
+    RootCause,
+    #[serde(rename = "corrective")]
+    Corrective,
+    #[serde(rename = "preventive")]
+    Preventive,
+}
+
+impl std::fmt::Display for CapaActionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapaActionType::Containment => write!(f, "containment"),
+            CapaActionType::RootCause => write!(f, "root_cause"),
+            CapaActionType::Corrective => write!(f, "corrective"),
+            CapaActionType::Preventive => write!(f, "preventive"),
+        }
+    }
+}
+
+impl From<CapaActionType> for String {
+    fn from(action_type: CapaActionType) -> Self {
+        action_type.to_string()
+    }
+}
+
+impl TryFrom<String> for CapaActionType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "containment" => Ok(CapaActionType::Containment),
+            "root_cause" => Ok(CapaActionType::RootCause),
+            "corrective" => Ok(CapaActionType::Corrective),
+            "preventive" => Ok(CapaActionType::Preventive),
+            _ => Err(format!("Invalid CAPA action type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CapaStatus {
+    #[serde(rename = "open")]
+    Open,
+    #[serde(rename = "in_progress")]
+    InProgress,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl CapaStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, CapaStatus::Open | CapaStatus::InProgress)
+    }
+}
+
+impl std::fmt::Display for CapaStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CapaStatus::Open => write!(f, "open"),
+            CapaStatus::InProgress => write!(f, "in_progress"),
+            CapaStatus::Completed => write!(f, "completed"),
+            CapaStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<CapaStatus> for String {
+    fn from(status: CapaStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for CapaStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "open" => Ok(CapaStatus::Open),
+            "in_progress" => Ok(CapaStatus::InProgress),
+            "completed" => Ok(CapaStatus::Completed),
+            "cancelled" => Ok(CapaStatus::Cancelled),
+            _ => Err(format!("Invalid CAPA status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCapaActionRequest {
+    pub action_type: CapaActionType,
+
+    #[validate(length(min = 1, max = 2000))]
+    pub description: String,
+
+    /// Defaults to the owner of the NCR
+    pub owner_id: Option<Uuid>,
+
+    /// Defaults to the due date of the NCR
+    pub due_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateNcrRequest {
+    /// NCR number quoted in correspondence; generated when left out
+    #[validate(length(min = 1, max = 50))]
+    pub ncr_number: Option<String>,
+
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+
+    #[validate(length(max = 5000))]
+    pub description: Option<String>,
+
+    pub source_type: NcrSourceType,
+    pub job_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub lot_number: Option<String>,
+
+    #[validate(range(min = 0))]
+    pub quantity_affected: Option<i32>,
+
+    pub severity: NcrSeverity,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+
+    /// Vendor responsible, e.g. for a defective lot of purchased parts
+    pub vendor_id: Option<Uuid>,
+
+    /// Actions known when the NCR is raised, typically the immediate containment
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    #[validate]
+    pub actions: Vec<CreateCapaActionRequest>,
+}
+
+impl CreateNcrRequest {
+    /// The source type decides which reference is required: a job, a machine, or an item lot.
+    pub fn check(&self) -> Result<(), String> {
+        match self.source_type {
+            NcrSourceType::Job if self.job_id.is_none() => {
+                Err("job_id is required for a job NCR".to_string())
+            }
+            NcrSourceType::Machine if self.machine_id.is_none() => {
+                Err("machine_id is required for a machine NCR".to_string())
+            }
+            NcrSourceType::Lot if self.item_id.is_none() || self.lot_number.is_none() => {
+                Err("item_id and lot_number are required for a lot NCR".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateNcrRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub title: Option<String>,
+
+    #[validate(length(max = 5000))]
+    pub description: Option<String>,
+
+    #[validate(range(min = 0))]
+    pub quantity_affected: Option<i32>,
+
+    pub severity: Option<NcrSeverity>,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub vendor_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateCapaActionRequest {
+    pub status: Option<CapaStatus>,
+
+    #[validate(length(min = 1, max = 2000))]
+    pub description: Option<String>,
+
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+
+    /// What was found or done; required to complete a root cause action
+    #[validate(length(max = 5000))]
+    pub outcome: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListNcrsQuery {
+    pub status: Option<NcrStatus>,
+    pub severity: Option<NcrSeverity>,
+    pub source_type: Option<NcrSourceType>,
+    pub job_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    pub vendor_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListOverdueCapaQuery {
+    pub owner_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CapaActionResponse {
+    pub id: Uuid,
+    pub ncr_id: Uuid,
+    pub action_type: CapaActionType,
+    pub description: String,
+    pub status: CapaStatus,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub outcome: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub completed_by_id: Option<Uuid>,
+    pub overdue: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CapaActionResponse {
+    pub fn new(action: CapaAction, now: DateTime<Utc>) -> Self {
+        let status = CapaStatus::try_from(action.status).unwrap_or(CapaStatus::Open);
+        Self {
+            id: action.id,
+            ncr_id: action.ncr_id,
+            action_type: CapaActionType::try_from(action.action_type)
+                .unwrap_or(CapaActionType::Corrective),
+            description: action.description,
+            overdue: status.is_active() && action.due_date.is_some_and(|due| due < now),
+            status,
+            owner_id: action.owner_id,
+            due_date: action.due_date,
+            outcome: action.outcome,
+            completed_at: action.completed_at,
+            completed_by_id: action.completed_by_id,
+            created_at: action.created_at.unwrap_or_else(Utc::now),
+            updated_at: action.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NcrResponse {
+    pub id: Uuid,
+    pub ncr_number: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub source_type: NcrSourceType,
+    pub job_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub item_id: Option<Uuid>,
+    pub lot_number: Option<String>,
+    pub quantity_affected: Option<i32>,
+    pub severity: NcrSeverity,
+    pub status: NcrStatus,
+    pub owner_id: Option<Uuid>,
+    pub due_date: Option<DateTime<Utc>>,
+    pub vendor_id: Option<Uuid>,
+    pub raised_by_id: Option<Uuid>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub closed_by_id: Option<Uuid>,
+    /// Open past its due date
+    pub overdue: bool,
+    pub actions: Vec<CapaActionResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NcrResponse {
+    pub fn new(ncr: NonConformance, actions: Vec<CapaAction>, now: DateTime<Utc>) -> Self {
+        let status = NcrStatus::try_from(ncr.status).unwrap_or(NcrStatus::Open);
+        Self {
+            id: ncr.id,
+            ncr_number: ncr.ncr_number,
+            title: ncr.title,
+            description: ncr.description,
+            source_type: NcrSourceType::try_from(ncr.source_type).unwrap_or(NcrSourceType::Job),
+            job_id: ncr.job_id,
+            machine_id: ncr.machine_id,
+            item_id: ncr.item_id,
+            lot_number: ncr.lot_number,
+            quantity_affected: ncr.quantity_affected,
+            severity: NcrSeverity::try_from(ncr.severity).unwrap_or(NcrSeverity::Minor),
+            overdue: status == NcrStatus::Open && ncr.due_date.is_some_and(|due| due < now),
+            status,
+            owner_id: ncr.owner_id,
+            due_date: ncr.due_date,
+            vendor_id: ncr.vendor_id,
+            raised_by_id: ncr.raised_by_id,
+            closed_at: ncr.closed_at,
+            closed_by_id: ncr.closed_by_id,
+            actions: actions
+                .into_iter()
+                .map(|action| CapaActionResponse::new(action, now))
+                .collect(),
+            created_at: ncr.created_at.unwrap_or_else(Utc::now),
+            updated_at: ncr.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// An open CAPA action past its due date, with enough of its NCR to act on it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OverdueCapaResponse {
+    pub ncr_id: Uuid,
+    pub ncr_number: String,
+    pub ncr_title: String,
+    pub severity: NcrSeverity,
+    #[serde(flatten)]
+    pub action: CapaActionResponse,
+    pub days_overdue: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NcrSeverityCounts {
+    pub minor: i64,
+    pub major: i64,
+    pub critical: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QualityDashboardResponse {
+    pub open_ncrs: i64,
+    pub open_ncrs_by_severity: NcrSeverityCounts,
+    pub overdue_ncrs: i64,
+    pub open_capa_actions: i64,
+    pub overdue_capa_actions: i64,
+    /// Most overdue first
+    pub overdue_capas: Vec<OverdueCapaResponse>,
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Numeric;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::ValidationError;
+
+/// Decimal places stored for stock, BOM and movement quantities (NUMERIC(18, 6))
+pub const QUANTITY_SCALE: u32 = 6;
+
+/// Largest quantity a single movement or reservation may carry
+pub const MAX_QUANTITY: f64 = 1_000_000.0;
+
+// Smallest amount six decimal places can hold
+const MIN_POSITIVE: f64 = 0.000001;
+
+/// Quantity of stock in an item's base unit, e.g. 0.35 kg of adhesive. Whole quantities are
+/// written to JSON as integers so tenants that only count in whole units see no change; requests
+/// may give quantities as integers, decimals or strings with up to six decimal places.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Numeric)]
+pub struct Quantity(Decimal);
+
+impl Quantity {
+    pub const ZERO: Quantity = Quantity(Decimal::ZERO);
+
+    /// `value` as a quantity, or `None` when it has more than six decimal places.
+    pub fn new(value: Decimal) -> Option<Self> {
+        let value = value.normalize();
+        (value.scale() <= QUANTITY_SCALE).then_some(Quantity(value))
+    }
+
+    /// The quantity times a unit conversion factor, rounded to six decimal places; `None` when
+    /// the factor is not finite or the product does not fit.
+    pub fn scaled(self, factor: f64) -> Option<Self> {
+        let factor = Decimal::from_f64(factor)?;
+        let product = self.0.checked_mul(factor)?;
+        Some(Quantity(product.round_dp(QUANTITY_SCALE).normalize()))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0.to_f64().unwrap_or(0.0)
+    }
+
+    /// The quantity as a whole number when it is one.
+    pub fn whole(self) -> Option<i32> {
+        if self.0.fract().is_zero() {
+            self.0.to_i32()
+        } else {
+            None
+        }
+    }
+
+    /// Whole units in the quantity, rounding down; what can be promised of a fractional stock.
+    pub fn whole_units(self) -> i32 {
+        self.0
+            .floor()
+            .to_i32()
+            .unwrap_or(if self.0.is_sign_negative() {
+                i32::MIN
+            } else {
+                i32::MAX
+            })
+    }
+
+    pub fn abs(self) -> Self {
+        Quantity(self.0.abs())
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl From<i32> for Quantity {
+    fn from(value: i32) -> Self {
+        Quantity(Decimal::from(value))
+    }
+}
+
+impl From<i64> for Quantity {
+    fn from(value: i64) -> Self {
+        Quantity(Decimal::from(value))
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let decimal =
+            Decimal::from_str(value.trim()).map_err(|_| format!("Invalid quantity: {}", value))?;
+        Quantity::new(decimal).ok_or_else(|| {
+            format!(
+                "Invalid quantity: {} has more than {} decimal places",
+                value, QUANTITY_SCALE
+            )
+        })
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.normalize())
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+
+    fn add(self, other: Quantity) -> Quantity {
+        Quantity(self.0 + other.0)
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Quantity;
+
+    fn sub(self, other: Quantity) -> Quantity {
+        Quantity(self.0 - other.0)
+    }
+}
+
+impl Neg for Quantity {
+    type Output = Quantity;
+
+    fn neg(self) -> Quantity {
+        Quantity(-self.0)
+    }
+}
+
+impl AddAssign for Quantity {
+    fn add_assign(&mut self, other: Quantity) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Quantity {
+    fn sub_assign(&mut self, other: Quantity) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Quantity {
+    fn sum<I: Iterator<Item = Quantity>>(iter: I) -> Self {
+        iter.fold(Quantity::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Quantity> for Quantity {
+    fn sum<I: Iterator<Item = &'a Quantity>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.fract().is_zero().then(|| self.0.to_i64()).flatten() {
+            Some(whole) => serializer.serialize_i64(whole),
+            None => serializer.serialize_f64(self.to_f64()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(QuantityVisitor)
+    }
+}
+
+struct QuantityVisitor;
+
+impl Visitor<'_> for QuantityVisitor {
+    type Value = Quantity;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a number with at most {} decimal places", QUANTITY_SCALE)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Quantity, E> {
+        Ok(Quantity::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Quantity, E> {
+        Ok(Quantity(Decimal::from(value)))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Quantity, E> {
+        // Parse the shortest representation so 0.35 stays 0.35 rather than its binary neighbour
+        value.to_string().parse().map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Quantity, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+impl ToSql<Numeric, Pg> for Quantity {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <Decimal as ToSql<Numeric, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<Numeric, Pg> for Quantity {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <Decimal as FromSql<Numeric, Pg>>::from_sql(bytes)?;
+        Ok(Quantity(value.normalize()))
+    }
+}
+
+/// Validator for on-hand stock, which never goes negative.
+pub fn validate_stock_quantity(quantity: &Quantity) -> Result<(), ValidationError> {
+    check_range(*quantity, 0.0, None)
+}
+
+/// Validator for component quantities per assembly.
+pub fn validate_bom_quantity(quantity: &Quantity) -> Result<(), ValidationError> {
+    check_range(*quantity, MIN_POSITIVE, None)
+}
+
+/// Validator for the quantity held by a reservation.
+pub fn validate_reserved_quantity(quantity: &Quantity) -> Result<(), ValidationError> {
+    check_range(*quantity, MIN_POSITIVE, Some(MAX_QUANTITY))
+}
+
+/// Validator for signed quantity changes; the sign is ignored for receipts and issues.
+pub fn validate_movement_quantity(quantity: &Quantity) -> Result<(), ValidationError> {
+    check_range(*quantity, -MAX_QUANTITY, Some(MAX_QUANTITY))
+}
+
+// Reported like a `range` rule so the message names the bounds
+fn check_range(quantity: Quantity, min: f64, max: Option<f64>) -> Result<(), ValidationError> {
+    let value = quantity.to_f64();
+    if value >= min && max.is_none_or(|max| value <= max) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("range");
+    error.add_param("min".into(), &min);
+    if let Some(max) = max {
+        error.add_param("max".into(), &max);
+    }
+    error.add_param("value".into(), &value);
+    Err(error)
+}
@@ -0,0 +1,415 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+use crate::utils::i18n::Locale;
+
+// Quote models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = quotes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Quote {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub quote_number: String,
+    pub customer_id: Uuid,
+    pub status: String,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub total_amount: f64,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+    pub order_id: Option<Uuid>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = quotes)]
+pub struct NewQuote {
+    pub tenant_id: Uuid,
+    pub quote_number: String,
+    pub customer_id: Uuid,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub total_amount: f64,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = quote_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct QuoteItem {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub quote_id: Uuid,
+    pub line_number: i32,
+    pub item_id: Option<Uuid>,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub extended_price: f64,
+    pub unit_cost: Option<f64>,
+    pub margin_percent: Option<f64>,
+    pub pricing_source: String,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = quote_items)]
+pub struct NewQuoteItem {
+    pub tenant_id: Uuid,
+    pub quote_id: Uuid,
+    pub line_number: i32,
+    pub item_id: Option<Uuid>,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub extended_price: f64,
+    pub unit_cost: Option<f64>,
+    pub margin_percent: Option<f64>,
+    pub pricing_source: String,
+    pub notes: Option<String>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuoteStatus {
+    #[serde(rename = "draft")]
+    Draft,
+    #[serde(rename = "sent")]
+    Sent,
+    #[serde(rename = "accepted")]
+    Accepted,
+    #[serde(rename = "rejected")]
+    Rejected,
+    #[serde(rename = "converted")]
+    Converted,
+}
+
+impl std::fmt::Display for QuoteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuoteStatus::Draft => write!(f, "draft"),
+            QuoteStatus::Sent => write!(f, "sent"),
+            QuoteStatus::Accepted => write!(f, "accepted"),
+            QuoteStatus::Rejected => write!(f, "rejected"),
+            QuoteStatus::Converted => write!(f, "converted"),
+        }
+    }
+}
+
+impl From<QuoteStatus> for String {
+    fn from(status: QuoteStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for QuoteStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "draft" => Ok(QuoteStatus::Draft),
+            "sent" => Ok(QuoteStatus::Sent),
+            "accepted" => Ok(QuoteStatus::Accepted),
+            "rejected" => Ok(QuoteStatus::Rejected),
+            "converted" => Ok(QuoteStatus::Converted),
+            _ => Err(format!("Invalid quote status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QuotePricing {
+    /// Unit price given on the line
+    #[serde(rename = "manual")]
+    Manual,
+    /// Finished goods selling price of the item, honouring its price breaks
+    #[serde(rename = "item")]
+    Item,
+    /// Cost of the item's BOM components plus a margin
+    #[serde(rename = "bom_cost")]
+    BomCost,
+}
+
+impl std::fmt::Display for QuotePricing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotePricing::Manual => write!(f, "manual"),
+            QuotePricing::Item => write!(f, "item"),
+            QuotePricing::BomCost => write!(f, "bom_cost"),
+        }
+    }
+}
+
+impl From<QuotePricing> for String {
+    fn from(pricing: QuotePricing) -> Self {
+        pricing.to_string()
+    }
+}
+
+impl TryFrom<String> for QuotePricing {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "manual" => Ok(QuotePricing::Manual),
+            "item" => Ok(QuotePricing::Item),
+            "bom_cost" => Ok(QuotePricing::BomCost),
+            _ => Err(format!("Invalid quote pricing: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateQuoteItemRequest {
+    pub item_id: Option<Uuid>,
+
+    /// Defaults to the item's part number when the line references an item
+    #[validate(length(min = 1, max = 200))]
+    pub item_name: Option<String>,
+
+    pub item_description: Option<String>,
+
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+
+    /// Defaults to `manual` when a unit price is given and `item` otherwise
+    pub pricing: Option<QuotePricing>,
+
+    #[validate(range(min = 0.0))]
+    pub unit_price: Option<f64>,
+
+    /// Markup on the BOM cost, e.g. 25.0 prices a 10.00 cost at 12.50
+    #[validate(range(min = 0.0, max = 1000.0))]
+    pub margin_percent: Option<f64>,
+
+    pub notes: Option<String>,
+}
+
+impl CreateQuoteItemRequest {
+    pub fn pricing(&self) -> QuotePricing {
+        self.pricing.unwrap_or(if self.unit_price.is_some() {
+            QuotePricing::Manual
+        } else {
+            QuotePricing::Item
+        })
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        match self.pricing() {
+            QuotePricing::Manual => {
+                if self.unit_price.is_none() {
+                    return Err("unit_price is required for manual pricing".to_string());
+                }
+                if self.item_id.is_none() && self.item_name.is_none() {
+                    return Err("item_name is required for lines without an item".to_string());
+                }
+            }
+            QuotePricing::Item | QuotePricing::BomCost => {
+                if self.item_id.is_none() {
+                    return Err("item_id is required for item and bom_cost pricing".to_string());
+                }
+                if self.unit_price.is_some() {
+                    return Err("unit_price only applies to manual pricing".to_string());
+                }
+            }
+        }
+        if self.margin_percent.is_some() != (self.pricing() == QuotePricing::BomCost) {
+            return Err(
+                "margin_percent is required for, and only applies to, bom_cost pricing".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateQuoteRequest {
+    pub customer_id: Uuid,
+
+    /// Quote number shown to the customer; drawn from the quote numbering sequence, or random,
+    /// when left out
+    #[validate(length(min = 1, max = 50))]
+    pub quote_number: Option<String>,
+
+    pub valid_until: Option<DateTime<Utc>>,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub items: Vec<CreateQuoteItemRequest>,
+}
+
+impl CreateQuoteRequest {
+    pub fn check(&self) -> Result<(), String> {
+        self.items.iter().try_for_each(|item| item.check())
+    }
+}
+
+/// Changes to a draft quote. `items`, when given, replaces every line on the quote.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateQuoteRequest {
+    pub valid_until: Option<DateTime<Utc>>,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub items: Option<Vec<CreateQuoteItemRequest>>,
+}
+
+impl UpdateQuoteRequest {
+    pub fn check(&self) -> Result<(), String> {
+        self.items
+            .iter()
+            .flatten()
+            .try_for_each(|item| item.check())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SendQuoteRequest {
+    /// Addresses the quote PDF is emailed to; defaults to the customer's email
+    #[validate(length(max = 50))]
+    pub recipients: Option<Vec<String>>,
+
+    #[validate(length(max = 5000))]
+    pub message: Option<String>,
+
+    /// Language of the email and PDF; defaults to the tenant's locale
+    pub locale: Option<Locale>,
+}
+
+impl SendQuoteRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match self
+            .recipients
+            .iter()
+            .flatten()
+            .find(|r| !validator::validate_email(r.as_str()))
+        {
+            Some(invalid) => Err(format!("Invalid recipient email: {}", invalid)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RejectQuoteRequest {
+    #[validate(length(max = 1000))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ConvertQuoteRequest {
+    /// Number of the sales order created; drawn from the customer order numbering sequence, or
+    /// `SO-<quote number>` without one, when left out
+    #[validate(length(min = 1, max = 50))]
+    pub order_number: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListQuotesQuery {
+    pub customer_id: Option<Uuid>,
+    pub status: Option<QuoteStatus>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteItemResponse {
+    pub id: Uuid,
+    pub line_number: i32,
+    pub item_id: Option<Uuid>,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub unit_price: f64,
+    pub extended_price: f64,
+    pub unit_cost: Option<f64>,
+    pub margin_percent: Option<f64>,
+    pub pricing: QuotePricing,
+    pub notes: Option<String>,
+}
+
+impl From<QuoteItem> for QuoteItemResponse {
+    fn from(item: QuoteItem) -> Self {
+        Self {
+            id: item.id,
+            line_number: item.line_number,
+            item_id: item.item_id,
+            item_name: item.item_name,
+            item_description: item.item_description,
+            quantity: item.quantity,
+            unit_price: item.unit_price,
+            extended_price: item.extended_price,
+            unit_cost: item.unit_cost,
+            margin_percent: item.margin_percent,
+            pricing: QuotePricing::try_from(item.pricing_source).unwrap_or(QuotePricing::Manual),
+            notes: item.notes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuoteResponse {
+    pub id: Uuid,
+    pub quote_number: String,
+    pub customer_id: Uuid,
+    pub customer_name: String,
+    pub status: QuoteStatus,
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Past `valid_until` without having been accepted
+    pub is_expired: bool,
+    pub total_amount: f64,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+    /// Sales order the quote was converted into
+    pub order_id: Option<Uuid>,
+    pub created_by_id: Option<Uuid>,
+    pub items: Vec<QuoteItemResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl QuoteResponse {
+    pub fn new(quote: Quote, customer_name: String, items: Vec<QuoteItem>) -> Self {
+        let status = QuoteStatus::try_from(quote.status).unwrap_or(QuoteStatus::Draft);
+        Self {
+            id: quote.id,
+            quote_number: quote.quote_number,
+            customer_id: quote.customer_id,
+            customer_name,
+            status,
+            valid_until: quote.valid_until,
+            is_expired: matches!(status, QuoteStatus::Draft | QuoteStatus::Sent)
+                && quote.valid_until.is_some_and(|until| until < Utc::now()),
+            total_amount: quote.total_amount,
+            notes: quote.notes,
+            sent_at: quote.sent_at,
+            responded_at: quote.responded_at,
+            rejection_reason: quote.rejection_reason,
+            order_id: quote.order_id,
+            created_by_id: quote.created_by_id,
+            items: items.into_iter().map(Into::into).collect(),
+            created_at: quote.created_at.unwrap_or_else(Utc::now),
+            updated_at: quote.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+use crate::utils::recalculation::progress_percent;
+
+// Recalculation task models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = recalculation_tasks)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RecalculationTask {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub kind: String,
+    pub status: String,
+    pub total: Option<i32>,
+    pub processed: i32,
+    pub updated_records: i32,
+    pub cancel_requested: bool,
+    pub error: Option<String>,
+    pub requested_by_id: Option<Uuid>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = recalculation_tasks)]
+pub struct NewRecalculationTask {
+    pub tenant_id: Uuid,
+    pub kind: String,
+    pub requested_by_id: Option<Uuid>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RecalculationKind {
+    /// Unit cost of BOM items rolled up from their components
+    #[serde(rename = "inventory_valuation")]
+    InventoryValuation,
+    /// Line and order totals of open orders
+    #[serde(rename = "order_totals")]
+    OrderTotals,
+    /// Demand forecasts and reorder suggestions of stocked items
+    #[serde(rename = "forecast_refresh")]
+    ForecastRefresh,
+}
+
+impl std::fmt::Display for RecalculationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecalculationKind::InventoryValuation => write!(f, "inventory_valuation"),
+            RecalculationKind::OrderTotals => write!(f, "order_totals"),
+            RecalculationKind::ForecastRefresh => write!(f, "forecast_refresh"),
+        }
+    }
+}
+
+impl From<RecalculationKind> for String {
+    fn from(kind: RecalculationKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for RecalculationKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "inventory_valuation" => Ok(RecalculationKind::InventoryValuation),
+            "order_totals" => Ok(RecalculationKind::OrderTotals),
+            "forecast_refresh" => Ok(RecalculationKind::ForecastRefresh),
+            _ => Err(format!("Invalid recalculation kind: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RecalculationTaskStatus {
+    #[serde(rename = "queued")]
+    Queued,
+    #[serde(rename = "running")]
+    Running,
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "failed")]
+    Failed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl std::fmt::Display for RecalculationTaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecalculationTaskStatus::Queued => write!(f, "queued"),
+            RecalculationTaskStatus::Running => write!(f, "running"),
+            RecalculationTaskStatus::Completed => write!(f, "completed"),
+            RecalculationTaskStatus::Failed => write!(f, "failed"),
+            RecalculationTaskStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<RecalculationTaskStatus> for String {
+    fn from(status: RecalculationTaskStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for RecalculationTaskStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "queued" => Ok(RecalculationTaskStatus::Queued),
+            "running" => Ok(RecalculationTaskStatus::Running),
+            "completed" => Ok(RecalculationTaskStatus::Completed),
+            "failed" => Ok(RecalculationTaskStatus::Failed),
+            "cancelled" => Ok(RecalculationTaskStatus::Cancelled),
+            _ => Err(format!("Invalid recalculation task status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRecalculationRequest {
+    pub kind: RecalculationKind,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListRecalculationsQuery {
+    pub kind: Option<RecalculationKind>,
+    pub status: Option<RecalculationTaskStatus>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecalculationTaskResponse {
+    pub id: Uuid,
+    pub kind: RecalculationKind,
+    pub status: RecalculationTaskStatus,
+    /// Records to go through; known once the task starts
+    pub total: Option<i32>,
+    pub processed: i32,
+    pub updated_records: i32,
+    /// Share of records gone through, 0-100; known once the task starts
+    pub progress_percent: Option<f64>,
+    pub cancel_requested: bool,
+    pub error: Option<String>,
+    pub requested_by_id: Option<Uuid>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<RecalculationTask> for RecalculationTaskResponse {
+    fn from(task: RecalculationTask) -> Self {
+        Self {
+            id: task.id,
+            kind: RecalculationKind::try_from(task.kind)
+                .unwrap_or(RecalculationKind::InventoryValuation),
+            status: RecalculationTaskStatus::try_from(task.status)
+                .unwrap_or(RecalculationTaskStatus::Queued),
+            total: task.total,
+            processed: task.processed,
+            updated_records: task.updated_records,
+            progress_percent: progress_percent(task.processed, task.total),
+            cancel_requested: task.cancel_requested,
+            error: task.error,
+            requested_by_id: task.requested_by_id,
+            started_at: task.started_at,
+            finished_at: task.finished_at,
+            created_at: task.created_at.unwrap_or_else(Utc::now),
+            updated_at: task.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
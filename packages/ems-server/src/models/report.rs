@@ -0,0 +1,629 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{
+    BigInt, Double, Integer, Nullable, Numeric, Text, Timestamptz, Uuid as SqlUuid,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::Quantity;
+use crate::schema::*;
+
+// Report registry models
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReportKind {
+    #[serde(rename = "inventory_valuation")]
+    InventoryValuation,
+    #[serde(rename = "open_order_book")]
+    OpenOrderBook,
+    #[serde(rename = "machine_downtime")]
+    MachineDowntime,
+    #[serde(rename = "job_margin")]
+    JobMargin,
+    #[serde(rename = "consignment_balances")]
+    ConsignmentBalances,
+}
+
+impl ReportKind {
+    pub fn all() -> Vec<ReportKind> {
+        vec![
+            ReportKind::InventoryValuation,
+            ReportKind::OpenOrderBook,
+            ReportKind::MachineDowntime,
+            ReportKind::JobMargin,
+            ReportKind::ConsignmentBalances,
+        ]
+    }
+}
+
+impl std::fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportKind::InventoryValuation => write!(f, "inventory_valuation"),
+            ReportKind::OpenOrderBook => write!(f, "open_order_book"),
+            ReportKind::MachineDowntime => write!(f, "machine_downtime"),
+            ReportKind::JobMargin => write!(f, "job_margin"),
+            ReportKind::ConsignmentBalances => write!(f, "consignment_balances"),
+        }
+    }
+}
+
+impl From<ReportKind> for String {
+    fn from(kind: ReportKind) -> Self {
+        kind.to_string()
+    }
+}
+
+impl TryFrom<String> for ReportKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "inventory_valuation" => Ok(ReportKind::InventoryValuation),
+            "open_order_book" => Ok(ReportKind::OpenOrderBook),
+            "machine_downtime" => Ok(ReportKind::MachineDowntime),
+            "job_margin" => Ok(ReportKind::JobMargin),
+            "consignment_balances" => Ok(ReportKind::ConsignmentBalances),
+            _ => Err(format!("Invalid report: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "csv")]
+    Csv,
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Json => write!(f, "json"),
+            ReportFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+impl From<ReportFormat> for String {
+    fn from(format: ReportFormat) -> Self {
+        format.to_string()
+    }
+}
+
+impl TryFrom<String> for ReportFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(format!("Invalid report format: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReportParameterType {
+    #[serde(rename = "string")]
+    String,
+    #[serde(rename = "date")]
+    Date,
+    #[serde(rename = "number")]
+    Number,
+    #[serde(rename = "enum")]
+    Enum,
+}
+
+impl std::fmt::Display for ReportParameterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportParameterType::String => write!(f, "string"),
+            ReportParameterType::Date => write!(f, "date"),
+            ReportParameterType::Number => write!(f, "number"),
+            ReportParameterType::Enum => write!(f, "enum"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: ReportParameterType,
+    pub required: bool,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDefinition {
+    pub key: ReportKind,
+    pub name: String,
+    pub description: String,
+    pub parameters: Vec<ReportParameter>,
+    pub columns: Vec<String>,
+}
+
+// Validated report parameters, keyed by parameter name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportParameters {
+    values: BTreeMap<String, String>,
+}
+
+impl ReportParameters {
+    /// Validates raw query parameters against a report definition.
+    /// Unknown parameters are ignored; missing required or malformed values are rejected.
+    pub fn parse(
+        definition: &ReportDefinition,
+        raw: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let mut values = BTreeMap::new();
+
+        for parameter in &definition.parameters {
+            let value = match raw.get(&parameter.name).map(|v| v.trim()) {
+                Some(v) if !v.is_empty() => v,
+                _ => {
+                    if parameter.required {
+                        return Err(format!("Missing required parameter: {}", parameter.name));
+                    }
+                    continue;
+                }
+            };
+
+            match parameter.param_type {
+                ReportParameterType::String => {}
+                ReportParameterType::Date => {
+                    parse_report_date(value).ok_or_else(|| {
+                        format!(
+                            "Invalid date for parameter {}: {} (expected YYYY-MM-DD or RFC 3339)",
+                            parameter.name, value
+                        )
+                    })?;
+                }
+                ReportParameterType::Number => {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite())
+                        .ok_or_else(|| {
+                            format!("Invalid number for parameter {}: {}", parameter.name, value)
+                        })?;
+                }
+                ReportParameterType::Enum => {
+                    let allowed = parameter.allowed_values.as_deref().unwrap_or_default();
+                    if !allowed.iter().any(|a| a == value) {
+                        return Err(format!(
+                            "Invalid value for parameter {}: {} (allowed: {})",
+                            parameter.name,
+                            value,
+                            allowed.join(", ")
+                        ));
+                    }
+                }
+            }
+
+            values.insert(parameter.name.clone(), value.to_string());
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn get_str(&self, name: &str) -> Option<String> {
+        self.values.get(name).cloned()
+    }
+
+    pub fn get_date(&self, name: &str) -> Option<DateTime<Utc>> {
+        self.values.get(name).and_then(|v| parse_report_date(v))
+    }
+
+    pub fn get_f64(&self, name: &str) -> Option<f64> {
+        self.values.get(name).and_then(|v| v.parse().ok())
+    }
+
+    pub fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+}
+
+// Dates are accepted either as a plain calendar date (midnight UTC) or a full RFC 3339 timestamp
+pub fn parse_report_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportResult {
+    pub report: ReportKind,
+    pub name: String,
+    pub generated_at: DateTime<Utc>,
+    pub parameters: BTreeMap<String, String>,
+    pub columns: Vec<String>,
+    pub rows: Vec<serde_json::Value>,
+}
+
+// Report row models
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct InventoryValuationRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub inventory_item_id: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    pub item_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub internal_part_number: String,
+    #[diesel(sql_type = Text)]
+    pub manufacturer: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub category: Option<String>,
+    #[diesel(sql_type = Text)]
+    pub context: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub location: Option<String>,
+    #[diesel(sql_type = Numeric)]
+    pub quantity: Quantity,
+    /// Part of the quantity owned by vendors or customers, left out of the value
+    #[diesel(sql_type = Numeric)]
+    pub consigned_quantity: Quantity,
+    #[diesel(sql_type = Double)]
+    pub unit_cost: f64,
+    #[diesel(sql_type = Double)]
+    pub total_value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct OpenOrderBookRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub order_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub order_number: String,
+    #[diesel(sql_type = Text)]
+    pub order_type: String,
+    #[diesel(sql_type = Text)]
+    pub status: String,
+    #[diesel(sql_type = Timestamptz)]
+    pub order_date: DateTime<Utc>,
+    #[diesel(sql_type = SqlUuid)]
+    pub external_entity_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub external_entity_type: String,
+    #[diesel(sql_type = BigInt)]
+    pub line_count: i64,
+    #[diesel(sql_type = BigInt)]
+    pub total_quantity: i64,
+    #[diesel(sql_type = Double)]
+    pub total_amount: f64,
+    #[diesel(sql_type = Integer)]
+    pub age_days: i32,
+}
+
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct MachineDowntimeRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub machine_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub machine_name: String,
+    #[diesel(sql_type = Text)]
+    pub current_status: String,
+    #[diesel(sql_type = Double)]
+    pub offline_hours: f64,
+    #[diesel(sql_type = Double)]
+    pub error_hours: f64,
+    #[diesel(sql_type = Double)]
+    pub maintenance_hours: f64,
+    #[diesel(sql_type = Double)]
+    pub downtime_hours: f64,
+    #[diesel(sql_type = BigInt)]
+    pub downtime_events: i64,
+    #[diesel(sql_type = Double)]
+    pub availability_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct JobMarginRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub job_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub job_number: String,
+    #[diesel(sql_type = Text)]
+    pub job_type: String,
+    #[diesel(sql_type = Nullable<SqlUuid>)]
+    pub item_id: Option<Uuid>,
+    #[diesel(sql_type = Integer)]
+    pub quantity: i32,
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[diesel(sql_type = Double)]
+    pub revenue: f64,
+    #[diesel(sql_type = Double)]
+    pub labor_hours: f64,
+    #[diesel(sql_type = Double)]
+    pub labor_cost: f64,
+    #[diesel(sql_type = Double)]
+    pub material_cost: f64,
+    #[diesel(sql_type = Double)]
+    pub margin: f64,
+    #[diesel(sql_type = Nullable<Double>)]
+    pub margin_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+pub struct ConsignmentBalanceRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub owner_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub owner_name: String,
+    #[diesel(sql_type = Text)]
+    pub owner_type: String,
+    #[diesel(sql_type = SqlUuid)]
+    pub item_id: Uuid,
+    #[diesel(sql_type = Text)]
+    pub internal_part_number: String,
+    #[diesel(sql_type = Text)]
+    pub context: String,
+    #[diesel(sql_type = Numeric)]
+    pub quantity: Quantity,
+    #[diesel(sql_type = Double)]
+    pub unit_price: f64,
+    #[diesel(sql_type = Double)]
+    pub total_value: f64,
+    #[diesel(sql_type = Numeric)]
+    pub pending_quantity: Quantity,
+    #[diesel(sql_type = Double)]
+    pub pending_amount: f64,
+}
+
+// Report schedule models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = report_schedules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ReportSchedule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub report_key: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub parameters: serde_json::Value,
+    pub recipients: Vec<Option<String>>,
+    pub format: String,
+    pub is_active: bool,
+    pub created_by_id: Option<Uuid>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = report_schedules)]
+pub struct NewReportSchedule {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub report_key: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub parameters: serde_json::Value,
+    pub recipients: Vec<Option<String>>,
+    pub format: String,
+    pub is_active: bool,
+    pub created_by_id: Option<Uuid>,
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReportDeliveryStatus {
+    #[serde(rename = "success")]
+    Success,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+impl std::fmt::Display for ReportDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportDeliveryStatus::Success => write!(f, "success"),
+            ReportDeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl From<ReportDeliveryStatus> for String {
+    fn from(status: ReportDeliveryStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for ReportDeliveryStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "success" => Ok(ReportDeliveryStatus::Success),
+            "failed" => Ok(ReportDeliveryStatus::Failed),
+            _ => Err(format!("Invalid report delivery status: {}", value)),
+        }
+    }
+}
+
+// Cron expressions may use the classic 5 fields or include a leading seconds field
+pub fn parse_cron_expression(expression: &str) -> Result<cron::Schedule, String> {
+    let fields = expression.split_whitespace().count();
+    let normalized = match fields {
+        5 => format!("0 {}", expression.trim()),
+        6 | 7 => expression.trim().to_string(),
+        _ => return Err(format!("Invalid cron expression: {}", expression)),
+    };
+
+    normalized
+        .parse::<cron::Schedule>()
+        .map_err(|_| format!("Invalid cron expression: {}", expression))
+}
+
+pub fn parse_timezone(timezone: &str) -> Result<chrono_tz::Tz, String> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map_err(|_| format!("Invalid timezone: {}", timezone))
+}
+
+// Next occurrence of a cron expression strictly after `after`, evaluated in the given timezone
+pub fn next_cron_run(
+    expression: &str,
+    timezone: &str,
+    after: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>, String> {
+    let schedule = parse_cron_expression(expression)?;
+    let tz = parse_timezone(timezone)?;
+
+    Ok(schedule
+        .after(&after.with_timezone(&tz))
+        .next()
+        .map(|next| next.with_timezone(&Utc)))
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateReportScheduleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub report: ReportKind,
+
+    #[validate(length(min = 1, max = 100))]
+    pub cron_expression: String,
+
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+
+    pub parameters: Option<HashMap<String, String>>,
+
+    #[validate(length(min = 1))]
+    pub recipients: Vec<String>,
+
+    pub format: Option<ReportFormat>,
+
+    pub is_active: Option<bool>,
+}
+
+impl CreateReportScheduleRequest {
+    /// Checks the fields that `Validate` cannot express: cron syntax, timezone,
+    /// recipient addresses and the report parameters themselves.
+    pub fn check(&self, definition: &ReportDefinition) -> Result<(), String> {
+        parse_cron_expression(&self.cron_expression)?;
+        if let Some(timezone) = &self.timezone {
+            parse_timezone(timezone)?;
+        }
+        check_recipients(&self.recipients)?;
+        ReportParameters::parse(definition, &self.parameters.clone().unwrap_or_default())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateReportScheduleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub cron_expression: Option<String>,
+
+    #[validate(length(min = 1, max = 64))]
+    pub timezone: Option<String>,
+
+    pub parameters: Option<HashMap<String, String>>,
+
+    #[validate(length(min = 1))]
+    pub recipients: Option<Vec<String>>,
+
+    pub format: Option<ReportFormat>,
+
+    pub is_active: Option<bool>,
+}
+
+impl UpdateReportScheduleRequest {
+    pub fn check(&self, definition: &ReportDefinition) -> Result<(), String> {
+        if let Some(expression) = &self.cron_expression {
+            parse_cron_expression(expression)?;
+        }
+        if let Some(timezone) = &self.timezone {
+            parse_timezone(timezone)?;
+        }
+        if let Some(recipients) = &self.recipients {
+            check_recipients(recipients)?;
+        }
+        if let Some(parameters) = &self.parameters {
+            ReportParameters::parse(definition, parameters)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_recipients(recipients: &[String]) -> Result<(), String> {
+    match recipients
+        .iter()
+        .find(|r| !validator::validate_email(r.as_str()))
+    {
+        Some(invalid) => Err(format!("Invalid recipient email: {}", invalid)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReportScheduleIdResponse {
+    pub id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReportScheduleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub report: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub parameters: serde_json::Value,
+    pub recipients: Vec<String>,
+    pub format: String,
+    pub is_active: bool,
+    pub created_by_id: Option<Uuid>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<ReportDeliveryStatus>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ReportSchedule> for ReportScheduleResponse {
+    fn from(schedule: ReportSchedule) -> Self {
+        Self {
+            id: schedule.id,
+            name: schedule.name,
+            report: schedule.report_key,
+            cron_expression: schedule.cron_expression,
+            timezone: schedule.timezone,
+            parameters: schedule.parameters,
+            recipients: schedule.recipients.into_iter().flatten().collect(),
+            format: schedule.format,
+            is_active: schedule.is_active,
+            created_by_id: schedule.created_by_id,
+            next_run_at: schedule.next_run_at,
+            last_run_at: schedule.last_run_at,
+            last_status: schedule
+                .last_status
+                .and_then(|s| ReportDeliveryStatus::try_from(s).ok()),
+            last_error: schedule.last_error,
+            created_at: schedule.created_at.unwrap_or_else(Utc::now),
+            updated_at: schedule.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -0,0 +1,554 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+use crate::utils::i18n::Locale;
+
+// RFQ models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = rfqs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Rfq {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub rfq_number: String,
+    pub status: String,
+    pub respond_by: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub awarded_vendor_id: Option<Uuid>,
+    pub awarded_at: Option<DateTime<Utc>>,
+    pub order_id: Option<Uuid>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = rfqs)]
+pub struct NewRfq {
+    pub tenant_id: Uuid,
+    pub rfq_number: String,
+    pub respond_by: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = rfq_items)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RfqItem {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub rfq_id: Uuid,
+    pub line_number: i32,
+    pub item_id: Uuid,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = rfq_items)]
+pub struct NewRfqItem {
+    pub tenant_id: Uuid,
+    pub rfq_id: Uuid,
+    pub line_number: i32,
+    pub item_id: Uuid,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = rfq_vendors)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RfqVendor {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub rfq_id: Uuid,
+    pub vendor_id: Uuid,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub token_hash: Option<String>,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = rfq_vendors)]
+pub struct NewRfqVendor {
+    pub tenant_id: Uuid,
+    pub rfq_id: Uuid,
+    pub vendor_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = rfq_quotes)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RfqQuote {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub rfq_vendor_id: Uuid,
+    pub rfq_item_id: Uuid,
+    pub unit_price: f64,
+    pub lead_time: Option<i32>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = rfq_quotes)]
+pub struct NewRfqQuote {
+    pub tenant_id: Uuid,
+    pub rfq_vendor_id: Uuid,
+    pub rfq_item_id: Uuid,
+    pub unit_price: f64,
+    pub lead_time: Option<i32>,
+    pub notes: Option<String>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RfqStatus {
+    #[serde(rename = "draft")]
+    Draft,
+    #[serde(rename = "sent")]
+    Sent,
+    #[serde(rename = "awarded")]
+    Awarded,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl std::fmt::Display for RfqStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RfqStatus::Draft => write!(f, "draft"),
+            RfqStatus::Sent => write!(f, "sent"),
+            RfqStatus::Awarded => write!(f, "awarded"),
+            RfqStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<RfqStatus> for String {
+    fn from(status: RfqStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for RfqStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "draft" => Ok(RfqStatus::Draft),
+            "sent" => Ok(RfqStatus::Sent),
+            "awarded" => Ok(RfqStatus::Awarded),
+            "cancelled" => Ok(RfqStatus::Cancelled),
+            _ => Err(format!("Invalid RFQ status: {}", value)),
+        }
+    }
+}
+
+/// Where a vendor's answer to an RFQ stands.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RfqVendorStatus {
+    /// Invited but not emailed yet
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "sent")]
+    Sent,
+    #[serde(rename = "responded")]
+    Responded,
+    #[serde(rename = "declined")]
+    Declined,
+    #[serde(rename = "awarded")]
+    Awarded,
+    /// Another vendor was awarded the RFQ
+    #[serde(rename = "lost")]
+    Lost,
+}
+
+impl std::fmt::Display for RfqVendorStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RfqVendorStatus::Pending => write!(f, "pending"),
+            RfqVendorStatus::Sent => write!(f, "sent"),
+            RfqVendorStatus::Responded => write!(f, "responded"),
+            RfqVendorStatus::Declined => write!(f, "declined"),
+            RfqVendorStatus::Awarded => write!(f, "awarded"),
+            RfqVendorStatus::Lost => write!(f, "lost"),
+        }
+    }
+}
+
+impl From<RfqVendorStatus> for String {
+    fn from(status: RfqVendorStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for RfqVendorStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(RfqVendorStatus::Pending),
+            "sent" => Ok(RfqVendorStatus::Sent),
+            "responded" => Ok(RfqVendorStatus::Responded),
+            "declined" => Ok(RfqVendorStatus::Declined),
+            "awarded" => Ok(RfqVendorStatus::Awarded),
+            "lost" => Ok(RfqVendorStatus::Lost),
+            _ => Err(format!("Invalid RFQ vendor status: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRfqItemRequest {
+    pub item_id: Uuid,
+
+    /// Defaults to the item's part number
+    #[validate(length(min = 1, max = 200))]
+    pub item_name: Option<String>,
+
+    pub item_description: Option<String>,
+
+    #[validate(range(min = 1))]
+    pub quantity: i32,
+
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateRfqRequest {
+    /// RFQ number shown to vendors; drawn from the RFQ numbering sequence, or random, when left
+    /// out
+    #[validate(length(min = 1, max = 50))]
+    pub rfq_number: Option<String>,
+
+    pub respond_by: Option<DateTime<Utc>>,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub items: Vec<CreateRfqItemRequest>,
+
+    /// Vendors (persons with a vendor record) asked to quote
+    #[validate(length(min = 1, max = 50))]
+    pub vendor_ids: Vec<Uuid>,
+}
+
+impl CreateRfqRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_vendor_ids(&self.vendor_ids)
+    }
+}
+
+/// Changes to a draft RFQ. `items` and `vendor_ids`, when given, replace the existing ones.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateRfqRequest {
+    pub respond_by: Option<DateTime<Utc>>,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub items: Option<Vec<CreateRfqItemRequest>>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub vendor_ids: Option<Vec<Uuid>>,
+}
+
+impl UpdateRfqRequest {
+    pub fn check(&self) -> Result<(), String> {
+        self.vendor_ids.as_deref().map_or(Ok(()), check_vendor_ids)
+    }
+}
+
+fn check_vendor_ids(vendor_ids: &[Uuid]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    match vendor_ids.iter().find(|id| !seen.insert(**id)) {
+        Some(duplicate) => Err(format!("Vendor listed twice: {}", duplicate)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SendRfqRequest {
+    #[validate(length(max = 5000))]
+    pub message: Option<String>,
+
+    /// Language of the email; defaults to the tenant's locale
+    pub locale: Option<Locale>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RfqQuoteLineRequest {
+    pub rfq_item_id: Uuid,
+
+    #[validate(range(min = 0.0))]
+    pub unit_price: f64,
+
+    /// Lead time in days
+    #[validate(range(min = 0, max = 3650))]
+    pub lead_time: Option<i32>,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+/// A vendor's quote. Lines left out are not quoted; a new response replaces the previous one.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SubmitRfqQuoteRequest {
+    #[validate(length(equal = 3))]
+    pub currency: Option<String>,
+
+    #[validate(length(max = 5000))]
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    #[validate]
+    pub lines: Vec<RfqQuoteLineRequest>,
+}
+
+impl SubmitRfqQuoteRequest {
+    pub fn check(&self) -> Result<(), String> {
+        let mut seen = std::collections::HashSet::new();
+        match self
+            .lines
+            .iter()
+            .find(|line| !seen.insert(line.rfq_item_id))
+        {
+            Some(duplicate) => Err(format!("Line quoted twice: {}", duplicate.rfq_item_id)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct DeclineRfqRequest {
+    #[validate(length(max = 1000))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct AwardRfqRequest {
+    /// Vendor whose quote wins; it must have quoted every line
+    pub vendor_id: Uuid,
+
+    /// Number of the purchase order created; drawn from the purchase order numbering sequence,
+    /// or `PO-<RFQ number>` without one, when left out
+    #[validate(length(min = 1, max = 50))]
+    pub order_number: Option<String>,
+
+    /// Whether the quoted prices and lead times are written to the vendor's item pricing;
+    /// defaults to true
+    pub update_price_list: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListRfqsQuery {
+    pub status: Option<RfqStatus>,
+    /// RFQs the vendor was invited to
+    pub vendor_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RfqItemResponse {
+    pub id: Uuid,
+    pub line_number: i32,
+    pub item_id: Uuid,
+    pub item_name: String,
+    pub item_description: Option<String>,
+    pub quantity: i32,
+    pub notes: Option<String>,
+}
+
+impl From<RfqItem> for RfqItemResponse {
+    fn from(item: RfqItem) -> Self {
+        Self {
+            id: item.id,
+            line_number: item.line_number,
+            item_id: item.item_id,
+            item_name: item.item_name,
+            item_description: item.item_description,
+            quantity: item.quantity,
+            notes: item.notes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RfqQuoteResponse {
+    pub rfq_item_id: Uuid,
+    pub unit_price: f64,
+    /// Lead time in days
+    pub lead_time: Option<i32>,
+    pub notes: Option<String>,
+}
+
+impl From<RfqQuote> for RfqQuoteResponse {
+    fn from(quote: RfqQuote) -> Self {
+        Self {
+            rfq_item_id: quote.rfq_item_id,
+            unit_price: quote.unit_price,
+            lead_time: quote.lead_time,
+            notes: quote.notes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RfqVendorResponse {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub status: RfqVendorStatus,
+    pub currency: Option<String>,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub quotes: Vec<RfqQuoteResponse>,
+}
+
+impl RfqVendorResponse {
+    pub fn new(vendor: RfqVendor, vendor_name: String, quotes: Vec<RfqQuote>) -> Self {
+        Self {
+            vendor_id: vendor.vendor_id,
+            vendor_name,
+            status: RfqVendorStatus::try_from(vendor.status).unwrap_or(RfqVendorStatus::Pending),
+            currency: vendor.currency,
+            notes: vendor.notes,
+            sent_at: vendor.sent_at,
+            responded_at: vendor.responded_at,
+            quotes: quotes.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RfqResponse {
+    pub id: Uuid,
+    pub rfq_number: String,
+    pub status: RfqStatus,
+    pub respond_by: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub awarded_vendor_id: Option<Uuid>,
+    pub awarded_at: Option<DateTime<Utc>>,
+    /// Purchase order the winning quote was converted into
+    pub order_id: Option<Uuid>,
+    pub created_by_id: Option<Uuid>,
+    pub items: Vec<RfqItemResponse>,
+    pub vendors: Vec<RfqVendorResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RfqResponse {
+    pub fn new(rfq: Rfq, items: Vec<RfqItem>, vendors: Vec<RfqVendorResponse>) -> Self {
+        Self {
+            id: rfq.id,
+            rfq_number: rfq.rfq_number,
+            status: RfqStatus::try_from(rfq.status).unwrap_or(RfqStatus::Draft),
+            respond_by: rfq.respond_by,
+            notes: rfq.notes,
+            sent_at: rfq.sent_at,
+            awarded_vendor_id: rfq.awarded_vendor_id,
+            awarded_at: rfq.awarded_at,
+            order_id: rfq.order_id,
+            created_by_id: rfq.created_by_id,
+            items: items.into_iter().map(Into::into).collect(),
+            vendors,
+            created_at: rfq.created_at.unwrap_or_else(Utc::now),
+            updated_at: rfq.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// One vendor's offer for an RFQ line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RfqOffer {
+    pub vendor_id: Uuid,
+    pub unit_price: f64,
+    pub extended_price: f64,
+    pub lead_time: Option<i32>,
+    /// No other vendor quoted the line for less
+    pub is_lowest_price: bool,
+    /// No other vendor quoted the line with a shorter lead time
+    pub is_shortest_lead_time: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RfqComparisonLine {
+    pub rfq_item_id: Uuid,
+    pub line_number: i32,
+    pub item_id: Uuid,
+    pub item_name: String,
+    pub quantity: i32,
+    /// Cheapest first
+    pub offers: Vec<RfqOffer>,
+}
+
+/// One vendor's quote as a whole.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RfqVendorTotal {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub status: RfqVendorStatus,
+    pub currency: Option<String>,
+    pub quoted_lines: usize,
+    /// Quoted every line, so the vendor can be awarded the RFQ
+    pub is_complete: bool,
+    /// Sum of the extended prices of the quoted lines
+    pub total_amount: f64,
+    /// Longest lead time quoted, which decides when the whole order can arrive
+    pub max_lead_time: Option<i32>,
+    /// Complete and cheapest of the complete quotes
+    pub is_lowest_total: bool,
+}
+
+/// Vendor quotes side by side: every line with each vendor's offer, and each vendor's total.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RfqComparisonResponse {
+    pub rfq_id: Uuid,
+    pub rfq_number: String,
+    pub lines: Vec<RfqComparisonLine>,
+    /// Complete quotes first, cheapest first
+    pub vendors: Vec<RfqVendorTotal>,
+}
+
+/// What a vendor sees through their response link.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RfqInvitationResponse {
+    pub rfq_number: String,
+    pub company: String,
+    pub vendor_name: String,
+    pub status: RfqVendorStatus,
+    pub respond_by: Option<DateTime<Utc>>,
+    /// Whether a quote can still be submitted or the RFQ declined
+    pub is_open: bool,
+    pub notes: Option<String>,
+    pub items: Vec<RfqItemResponse>,
+    pub currency: Option<String>,
+    /// The vendor's current quote, if any
+    pub quotes: Vec<RfqQuoteResponse>,
+}
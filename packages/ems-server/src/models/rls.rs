@@ -0,0 +1,125 @@
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool, Text};
+use serde::{Deserialize, Serialize};
+
+// Row level security catalog row for one table
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RlsTableRow {
+    #[diesel(sql_type = Text)]
+    pub table_name: String,
+    #[diesel(sql_type = Bool)]
+    pub rls_enabled: bool,
+    #[diesel(sql_type = Bool)]
+    pub rls_forced: bool,
+    #[diesel(sql_type = BigInt)]
+    pub policy_count: i64,
+    /// Policies whose expressions read the current tenant setting
+    #[diesel(sql_type = BigInt)]
+    pub tenant_policy_count: i64,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RlsRoleRow {
+    #[diesel(sql_type = Text)]
+    pub role: String,
+    #[diesel(sql_type = Bool)]
+    pub bypasses_rls: bool,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RlsCheckMode {
+    /// Skip the startup check
+    #[serde(rename = "off")]
+    Off,
+    /// Log tables missing policies and keep serving
+    #[serde(rename = "warn")]
+    Warn,
+    /// Refuse to start while any table is missing policies
+    #[serde(rename = "enforce")]
+    Enforce,
+}
+
+impl std::fmt::Display for RlsCheckMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RlsCheckMode::Off => write!(f, "off"),
+            RlsCheckMode::Warn => write!(f, "warn"),
+            RlsCheckMode::Enforce => write!(f, "enforce"),
+        }
+    }
+}
+
+impl From<RlsCheckMode> for String {
+    fn from(mode: RlsCheckMode) -> Self {
+        mode.to_string()
+    }
+}
+
+impl TryFrom<String> for RlsCheckMode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "off" => Ok(RlsCheckMode::Off),
+            "warn" => Ok(RlsCheckMode::Warn),
+            "enforce" => Ok(RlsCheckMode::Enforce),
+            _ => Err(format!("Invalid RLS check mode: {}", value)),
+        }
+    }
+}
+
+// Response DTOs
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RlsTableStatus {
+    pub table: String,
+    /// False for tables deliberately shared by all tenants
+    pub tenant_scoped: bool,
+    pub rls_enabled: bool,
+    /// Whether policies also apply to the table owner
+    pub rls_forced: bool,
+    pub policy_count: i64,
+    pub tenant_policy_count: i64,
+    pub problems: Vec<String>,
+}
+
+impl RlsTableStatus {
+    /// Evaluates a catalog row. Tenant-scoped tables need row level security enabled and at
+    /// least one policy that reads the current tenant.
+    pub fn from_row(row: RlsTableRow, tenant_scoped: bool) -> Self {
+        let mut problems = Vec::new();
+        if tenant_scoped {
+            if !row.rls_enabled {
+                problems.push("Row level security is not enabled".to_string());
+            }
+            if row.policy_count == 0 {
+                problems.push("No policies".to_string());
+            } else if row.tenant_policy_count == 0 {
+                problems.push("No policy filters on the current tenant".to_string());
+            }
+        }
+
+        Self {
+            table: row.table_name,
+            tenant_scoped,
+            rls_enabled: row.rls_enabled,
+            rls_forced: row.rls_forced,
+            policy_count: row.policy_count,
+            tenant_policy_count: row.tenant_policy_count,
+            problems,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RlsAuditResponse {
+    /// Database role the server connects as
+    pub role: String,
+    /// True when the role is a superuser or has BYPASSRLS, so policies are not applied to the
+    /// server's own queries and isolation rests on the services' tenant filters
+    pub role_bypasses_rls: bool,
+    pub passed: bool,
+    /// Tenant-scoped tables with problems
+    pub failing: Vec<String>,
+    pub tables: Vec<RlsTableStatus>,
+}
@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::Quantity;
+
+// Supplier scorecard models
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct VendorScorecardQuery {
+    /// Start of the period; defaults to 90 days before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// End of the period (exclusive); defaults to now
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl VendorScorecardQuery {
+    pub fn check(&self) -> Result<(), String> {
+        match (self.from, self.to) {
+            (Some(from), Some(to)) if from >= to => Err("from must be before to".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Purchase order lines promised for the period, scored against the date they were fully
+/// received.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryScore {
+    pub lines_scored: i64,
+    pub on_time: i64,
+    pub late: i64,
+    /// Past the promised date and still not fully received
+    pub overdue: i64,
+    /// Share of scored lines received on time, 0 to 1
+    pub on_time_rate: Option<f64>,
+    pub average_days_late: Option<f64>,
+    /// Lines in the period without a promised date, which cannot be scored
+    pub lines_without_promise_date: i64,
+}
+
+/// Non-conformances attributed to the vendor in the period.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct QualityScore {
+    pub ncrs: i64,
+    pub minor: i64,
+    pub major: i64,
+    pub critical: i64,
+    pub quantity_affected: i64,
+    /// Units received from the vendor in the period
+    pub units_received: Quantity,
+    /// Affected units per million received
+    pub defect_ppm: Option<f64>,
+}
+
+/// Prices paid on the period's purchase orders against the vendor's recorded price for the
+/// item at the order date. Positive variance means paying more than the recorded price.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PriceScore {
+    pub lines: i64,
+    /// Lines with a recorded price to compare against
+    pub compared_lines: i64,
+    pub ordered_value: f64,
+    pub reference_value: f64,
+    pub variance: f64,
+    pub variance_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VendorScorecardResponse {
+    pub vendor_id: Uuid,
+    pub name: String,
+    pub company: Option<String>,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub purchase_orders: i64,
+    pub delivery: DeliveryScore,
+    pub quality: QualityScore,
+    pub price: PriceScore,
+}
@@ -0,0 +1,86 @@
+use diesel::prelude::*;
+use diesel::sql_types::{Double, Nullable, Uuid as SqlUuid, Varchar};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct SuggestionRow {
+    #[diesel(sql_type = SqlUuid)]
+    pub id: Uuid,
+    #[diesel(sql_type = Varchar)]
+    pub label: String,
+    #[diesel(sql_type = Nullable<Varchar>)]
+    pub detail: Option<String>,
+    #[diesel(sql_type = Double)]
+    pub score: f64,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SuggestionType {
+    #[serde(rename = "item")]
+    Item,
+    #[serde(rename = "person")]
+    Person,
+    #[serde(rename = "machine")]
+    Machine,
+    #[serde(rename = "order")]
+    Order,
+}
+
+impl SuggestionType {
+    pub const ALL: [SuggestionType; 4] = [
+        SuggestionType::Item,
+        SuggestionType::Person,
+        SuggestionType::Machine,
+        SuggestionType::Order,
+    ];
+}
+
+impl std::fmt::Display for SuggestionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuggestionType::Item => write!(f, "item"),
+            SuggestionType::Person => write!(f, "person"),
+            SuggestionType::Machine => write!(f, "machine"),
+            SuggestionType::Order => write!(f, "order"),
+        }
+    }
+}
+
+impl TryFrom<&str> for SuggestionType {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "item" => Ok(SuggestionType::Item),
+            "person" => Ok(SuggestionType::Person),
+            "machine" => Ok(SuggestionType::Machine),
+            "order" => Ok(SuggestionType::Order),
+            _ => Err(format!("Invalid suggestion type: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Deserialize, Validate)]
+pub struct SuggestQuery {
+    /// Text typed so far
+    #[validate(length(max = 100))]
+    pub q: String,
+    /// Comma-separated types to suggest, e.g. `item,person`; all types when omitted
+    pub types: Option<String>,
+    #[validate(range(min = 1, max = 25))]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: Uuid,
+    pub label: String,
+    /// Secondary text telling similar labels apart, such as a manufacturer or an email
+    pub detail: Option<String>,
+    #[serde(rename = "type")]
+    pub suggestion_type: SuggestionType,
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::service_clients;
+use crate::utils::service_scope::is_valid_scope;
+
+// Service client models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = service_clients)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ServiceClient {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub client_id: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    pub scopes: Vec<String>,
+    pub owner_id: Uuid,
+    pub is_active: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = service_clients)]
+pub struct NewServiceClient {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub client_id: String,
+    pub secret_hash: String,
+    pub scopes: Vec<String>,
+    pub owner_id: Uuid,
+}
+
+// Request/Response DTOs
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateServiceClientRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    /// `<area>:read`, `<area>:write` or `admin`
+    #[validate(length(min = 1, max = 50))]
+    pub scopes: Vec<String>,
+}
+
+impl CreateServiceClientRequest {
+    /// Checks every scope is one service clients can be granted
+    pub fn check(&self) -> Result<(), String> {
+        check_scopes(&self.scopes)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateServiceClientRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 1, max = 50))]
+    pub scopes: Option<Vec<String>>,
+
+    pub is_active: Option<bool>,
+}
+
+impl UpdateServiceClientRequest {
+    /// Checks every scope is one service clients can be granted
+    pub fn check(&self) -> Result<(), String> {
+        match &self.scopes {
+            Some(scopes) => check_scopes(scopes),
+            None => Ok(()),
+        }
+    }
+}
+
+fn check_scopes(scopes: &[String]) -> Result<(), String> {
+    match scopes.iter().find(|scope| !is_valid_scope(scope)) {
+        Some(scope) => Err(format!("Invalid scope: {}", scope)),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceClientResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub owner_id: Uuid,
+    pub is_active: bool,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ServiceClient> for ServiceClientResponse {
+    fn from(client: ServiceClient) -> Self {
+        Self {
+            id: client.id,
+            name: client.name,
+            client_id: client.client_id,
+            scopes: client.scopes,
+            owner_id: client.owner_id,
+            is_active: client.is_active,
+            last_used_at: client.last_used_at,
+            created_at: client.created_at.unwrap_or_else(Utc::now),
+            updated_at: client.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A client with its secret, returned when it is created or its secret is replaced. The secret
+/// is not shown again.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceClientSecretResponse {
+    #[serde(flatten)]
+    pub client: ServiceClientResponse,
+    pub client_secret: String,
+}
+
+/// Token request of the OAuth 2.0 client credentials grant (RFC 6749 section 4.4), sent form
+/// encoded. The client may authenticate with HTTP Basic instead of the body fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenRequest {
+    #[serde(default)]
+    pub grant_type: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Space separated subset of the client's scopes; all of them when left out
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+/// Token endpoint errors, with the error codes of RFC 6749 section 5.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum TokenError {
+    #[error("invalid_request")]
+    InvalidRequest,
+    #[error("invalid_client")]
+    InvalidClient,
+    #[error("unsupported_grant_type")]
+    UnsupportedGrantType,
+    #[error("invalid_scope")]
+    InvalidScope,
+}
@@ -0,0 +1,374 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Shipment models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = shipments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Shipment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub order_id: Uuid,
+    pub carrier: String,
+    pub service_level: Option<String>,
+    pub status: String,
+    pub ship_to: serde_json::Value,
+    pub tracking_number: Option<String>,
+    pub carrier_shipment_id: Option<String>,
+    pub label_url: Option<String>,
+    pub shipped_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = shipments)]
+pub struct NewShipment {
+    pub tenant_id: Uuid,
+    pub order_id: Uuid,
+    pub carrier: String,
+    pub service_level: Option<String>,
+    pub ship_to: serde_json::Value,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = shipment_packages)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ShipmentPackage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub shipment_id: Uuid,
+    pub weight_kg: f64,
+    pub length_cm: Option<f64>,
+    pub width_cm: Option<f64>,
+    pub height_cm: Option<f64>,
+    pub reference: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = shipment_packages)]
+pub struct NewShipmentPackage {
+    pub tenant_id: Uuid,
+    pub shipment_id: Uuid,
+    pub weight_kg: f64,
+    pub length_cm: Option<f64>,
+    pub width_cm: Option<f64>,
+    pub height_cm: Option<f64>,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = shipment_tracking_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ShipmentTrackingEvent {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub shipment_id: Uuid,
+    pub status: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = shipment_tracking_events)]
+pub struct NewShipmentTrackingEvent {
+    pub tenant_id: Uuid,
+    pub shipment_id: Uuid,
+    pub status: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ShipmentStatus {
+    #[serde(rename = "pending")]
+    Pending,
+    #[serde(rename = "label_created")]
+    LabelCreated,
+    #[serde(rename = "in_transit")]
+    InTransit,
+    #[serde(rename = "delivered")]
+    Delivered,
+    #[serde(rename = "exception")]
+    Exception,
+    #[serde(rename = "cancelled")]
+    Cancelled,
+}
+
+impl ShipmentStatus {
+    /// Whether carriers report this status in tracking; pending and cancelled are set locally.
+    pub fn is_tracking_status(&self) -> bool {
+        !matches!(self, ShipmentStatus::Pending | ShipmentStatus::Cancelled)
+    }
+}
+
+impl std::fmt::Display for ShipmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShipmentStatus::Pending => write!(f, "pending"),
+            ShipmentStatus::LabelCreated => write!(f, "label_created"),
+            ShipmentStatus::InTransit => write!(f, "in_transit"),
+            ShipmentStatus::Delivered => write!(f, "delivered"),
+            ShipmentStatus::Exception => write!(f, "exception"),
+            ShipmentStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl From<ShipmentStatus> for String {
+    fn from(status: ShipmentStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for ShipmentStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "pending" => Ok(ShipmentStatus::Pending),
+            "label_created" => Ok(ShipmentStatus::LabelCreated),
+            "in_transit" => Ok(ShipmentStatus::InTransit),
+            "delivered" => Ok(ShipmentStatus::Delivered),
+            "exception" => Ok(ShipmentStatus::Exception),
+            "cancelled" => Ok(ShipmentStatus::Cancelled),
+            _ => Err(format!("Invalid shipment status: {}", value)),
+        }
+    }
+}
+
+// Carrier DTOs, exchanged with carrier providers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarrierPackage {
+    pub weight_kg: f64,
+    pub length_cm: Option<f64>,
+    pub width_cm: Option<f64>,
+    pub height_cm: Option<f64>,
+    pub reference: Option<String>,
+}
+
+impl From<&ShipmentPackage> for CarrierPackage {
+    fn from(package: &ShipmentPackage) -> Self {
+        Self {
+            weight_kg: package.weight_kg,
+            length_cm: package.length_cm,
+            width_cm: package.width_cm,
+            height_cm: package.height_cm,
+            reference: package.reference.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarrierLabelRequest {
+    pub shipment_id: Uuid,
+    pub order_number: String,
+    pub service_level: Option<String>,
+    pub ship_to: serde_json::Value,
+    pub packages: Vec<CarrierPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarrierLabel {
+    pub tracking_number: String,
+    pub carrier_shipment_id: Option<String>,
+    pub label_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TrackingUpdate {
+    pub status: ShipmentStatus,
+
+    pub description: Option<String>,
+
+    #[validate(length(max = 200))]
+    pub location: Option<String>,
+
+    pub occurred_at: DateTime<Utc>,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateShipmentPackageRequest {
+    #[validate(range(min = 0.001))]
+    pub weight_kg: f64,
+
+    #[validate(range(min = 0.1))]
+    pub length_cm: Option<f64>,
+
+    #[validate(range(min = 0.1))]
+    pub width_cm: Option<f64>,
+
+    #[validate(range(min = 0.1))]
+    pub height_cm: Option<f64>,
+
+    #[validate(length(max = 100))]
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateShipmentRequest {
+    pub order_id: Uuid,
+
+    /// Carrier provider that issues the label; defaults to the webhook carrier
+    #[validate(length(min = 1, max = 50))]
+    pub carrier: Option<String>,
+
+    #[validate(length(max = 50))]
+    pub service_level: Option<String>,
+
+    /// Destination address (name, street, city, postal_code, country, ...)
+    pub ship_to: serde_json::Value,
+
+    pub notes: Option<String>,
+
+    #[validate(length(min = 1, max = 50))]
+    #[validate]
+    pub packages: Vec<CreateShipmentPackageRequest>,
+}
+
+impl CreateShipmentRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if !self.ship_to.is_object() {
+            return Err("ship_to must be an address object".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RecordTrackingRequest {
+    #[validate(length(min = 1, max = 100))]
+    #[validate]
+    pub events: Vec<TrackingUpdate>,
+}
+
+impl RecordTrackingRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match self.events.iter().find(|e| !e.status.is_tracking_status()) {
+            Some(event) => Err(format!("Not a tracking status: {}", event.status)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListShipmentsQuery {
+    pub order_id: Option<Uuid>,
+    pub status: Option<ShipmentStatus>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShipmentPackageResponse {
+    pub id: Uuid,
+    pub weight_kg: f64,
+    pub length_cm: Option<f64>,
+    pub width_cm: Option<f64>,
+    pub height_cm: Option<f64>,
+    pub reference: Option<String>,
+}
+
+impl From<ShipmentPackage> for ShipmentPackageResponse {
+    fn from(package: ShipmentPackage) -> Self {
+        Self {
+            id: package.id,
+            weight_kg: package.weight_kg,
+            length_cm: package.length_cm,
+            width_cm: package.width_cm,
+            height_cm: package.height_cm,
+            reference: package.reference,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShipmentTrackingEventResponse {
+    pub id: Uuid,
+    pub status: ShipmentStatus,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<ShipmentTrackingEvent> for ShipmentTrackingEventResponse {
+    fn from(event: ShipmentTrackingEvent) -> Self {
+        Self {
+            id: event.id,
+            status: ShipmentStatus::try_from(event.status).unwrap_or(ShipmentStatus::InTransit),
+            description: event.description,
+            location: event.location,
+            occurred_at: event.occurred_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShipmentResponse {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub carrier: String,
+    pub service_level: Option<String>,
+    pub status: ShipmentStatus,
+    pub ship_to: serde_json::Value,
+    pub tracking_number: Option<String>,
+    pub carrier_shipment_id: Option<String>,
+    pub label_url: Option<String>,
+    pub shipped_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub total_weight_kg: f64,
+    pub packages: Vec<ShipmentPackageResponse>,
+    /// Tracking history, oldest first
+    pub tracking_events: Vec<ShipmentTrackingEventResponse>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ShipmentResponse {
+    pub fn new(
+        shipment: Shipment,
+        packages: Vec<ShipmentPackage>,
+        tracking_events: Vec<ShipmentTrackingEvent>,
+    ) -> Self {
+        Self {
+            id: shipment.id,
+            order_id: shipment.order_id,
+            carrier: shipment.carrier,
+            service_level: shipment.service_level,
+            status: ShipmentStatus::try_from(shipment.status).unwrap_or(ShipmentStatus::Pending),
+            ship_to: shipment.ship_to,
+            tracking_number: shipment.tracking_number,
+            carrier_shipment_id: shipment.carrier_shipment_id,
+            label_url: shipment.label_url,
+            shipped_at: shipment.shipped_at,
+            delivered_at: shipment.delivered_at,
+            notes: shipment.notes,
+            created_by_id: shipment.created_by_id,
+            total_weight_kg: packages.iter().map(|p| p.weight_kg).sum(),
+            packages: packages.into_iter().map(Into::into).collect(),
+            tracking_events: tracking_events.into_iter().map(Into::into).collect(),
+            created_at: shipment.created_at.unwrap_or_else(Utc::now),
+            updated_at: shipment.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
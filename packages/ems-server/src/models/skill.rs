@@ -0,0 +1,346 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::OperatorAssignmentType;
+use crate::schema::*;
+
+// Skill models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = skills)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Skill {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = skills)]
+pub struct NewSkill {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// Person certification models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = person_skills)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PersonSkill {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub certified_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PersonSkill {
+    /// Whether the certification has lapsed at the given time.
+    pub fn is_expired_at(&self, at: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= at)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = person_skills)]
+pub struct NewPersonSkill {
+    pub tenant_id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub certified_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+}
+
+// Machine skill requirement models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_skill_requirements)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineSkillRequirement {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub skill_id: Uuid,
+    pub enforcement: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_skill_requirements)]
+pub struct NewMachineSkillRequirement {
+    pub machine_id: Uuid,
+    pub skill_id: Uuid,
+    pub enforcement: String,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SkillEnforcement {
+    #[serde(rename = "warn")]
+    Warn,
+    #[serde(rename = "block")]
+    Block,
+}
+
+impl std::fmt::Display for SkillEnforcement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillEnforcement::Warn => write!(f, "warn"),
+            SkillEnforcement::Block => write!(f, "block"),
+        }
+    }
+}
+
+impl From<SkillEnforcement> for String {
+    fn from(enforcement: SkillEnforcement) -> Self {
+        enforcement.to_string()
+    }
+}
+
+impl TryFrom<String> for SkillEnforcement {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "warn" => Ok(SkillEnforcement::Warn),
+            "block" => Ok(SkillEnforcement::Block),
+            _ => Err(format!("Invalid skill enforcement: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SkillGapReason {
+    #[serde(rename = "missing")]
+    Missing,
+    #[serde(rename = "expired")]
+    Expired,
+}
+
+impl std::fmt::Display for SkillGapReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillGapReason::Missing => write!(f, "missing"),
+            SkillGapReason::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateSkillRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateSkillRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Skill> for SkillResponse {
+    fn from(skill: Skill) -> Self {
+        Self {
+            id: skill.id,
+            name: skill.name,
+            description: skill.description,
+            created_at: skill.created_at.unwrap_or_else(Utc::now),
+            updated_at: skill.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetPersonSkillRequest {
+    pub certified_at: Option<DateTime<Utc>>,
+
+    /// When the certification lapses; omit for certifications that do not expire
+    pub expires_at: Option<DateTime<Utc>>,
+
+    #[validate(length(min = 1, max = 100))]
+    pub certificate_number: Option<String>,
+
+    pub notes: Option<String>,
+}
+
+impl SetPersonSkillRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if let (Some(certified_at), Some(expires_at)) = (self.certified_at, self.expires_at) {
+            if expires_at <= certified_at {
+                return Err("Certification must expire after it was issued".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PersonSkillResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub certified_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+    pub is_expired: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PersonSkillResponse {
+    pub fn new(certification: PersonSkill, skill_name: String) -> Self {
+        Self {
+            is_expired: certification.is_expired_at(Utc::now()),
+            id: certification.id,
+            person_id: certification.person_id,
+            skill_id: certification.skill_id,
+            skill_name,
+            certified_at: certification.certified_at,
+            expires_at: certification.expires_at,
+            certificate_number: certification.certificate_number,
+            notes: certification.notes,
+            created_at: certification.created_at.unwrap_or_else(Utc::now),
+            updated_at: certification.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetMachineSkillRequirementRequest {
+    /// Defaults to block
+    pub enforcement: Option<SkillEnforcement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineSkillRequirementResponse {
+    pub id: Uuid,
+    pub machine_id: Uuid,
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub enforcement: SkillEnforcement,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MachineSkillRequirementResponse {
+    pub fn new(requirement: MachineSkillRequirement, skill_name: String) -> Self {
+        Self {
+            enforcement: SkillEnforcement::try_from(requirement.enforcement)
+                .unwrap_or(SkillEnforcement::Block),
+            id: requirement.id,
+            machine_id: requirement.machine_id,
+            skill_id: requirement.skill_id,
+            skill_name,
+            created_at: requirement.created_at.unwrap_or_else(Utc::now),
+            updated_at: requirement.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A machine requirement the operator does not currently satisfy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillGap {
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub enforcement: SkillEnforcement,
+    pub reason: SkillGapReason,
+    /// Set when the operator holds the certification but it has lapsed
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl SkillGap {
+    pub fn is_blocking(&self) -> bool {
+        self.enforcement == SkillEnforcement::Block
+    }
+}
+
+/// Compares a machine's requirements against an operator's certifications at the given time.
+pub fn skill_gaps(
+    requirements: &[MachineSkillRequirementResponse],
+    certifications: &[PersonSkill],
+    at: DateTime<Utc>,
+) -> Vec<SkillGap> {
+    requirements
+        .iter()
+        .filter_map(|requirement| {
+            let certification = certifications
+                .iter()
+                .find(|c| c.skill_id == requirement.skill_id);
+            let (reason, expires_at) = match certification {
+                None => (SkillGapReason::Missing, None),
+                Some(c) if c.is_expired_at(at) => (SkillGapReason::Expired, c.expires_at),
+                Some(_) => return None,
+            };
+            Some(SkillGap {
+                skill_id: requirement.skill_id,
+                skill_name: requirement.skill_name.clone(),
+                enforcement: requirement.enforcement,
+                reason,
+                expires_at,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillCheckResponse {
+    pub machine_id: Uuid,
+    pub person_id: Uuid,
+    /// False when any blocking requirement is unmet
+    pub allowed: bool,
+    pub gaps: Vec<SkillGap>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillMatrixRow {
+    pub person_id: Uuid,
+    pub name: String,
+    pub department: Option<String>,
+    pub position: Option<String>,
+    pub certifications: Vec<PersonSkillResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillMatrixResponse {
+    pub skills: Vec<SkillResponse>,
+    pub persons: Vec<SkillMatrixRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FlaggedOperatorAssignment {
+    pub assignment_id: Uuid,
+    pub machine_id: Uuid,
+    pub machine_name: String,
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub assignment_type: OperatorAssignmentType,
+    pub gaps: Vec<SkillGap>,
+}
@@ -0,0 +1,405 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Default and maximum number of points on an SPC chart
+pub const DEFAULT_SPC_POINTS: i64 = 100;
+pub const MAX_SPC_POINTS: i64 = 500;
+
+// Item characteristic models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_characteristics)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemCharacteristic {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub name: String,
+    pub unit: Option<String>,
+    pub nominal: f64,
+    pub lower_tolerance: Option<f64>,
+    pub upper_tolerance: Option<f64>,
+    pub subgroup_size: i32,
+    pub machine_id: Option<Uuid>,
+    pub payload_pointer: Option<String>,
+    pub is_active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ItemCharacteristic {
+    pub fn lower_spec_limit(&self) -> Option<f64> {
+        self.lower_tolerance
+            .map(|tolerance| self.nominal - tolerance)
+    }
+
+    pub fn upper_spec_limit(&self) -> Option<f64> {
+        self.upper_tolerance
+            .map(|tolerance| self.nominal + tolerance)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_characteristics)]
+pub struct NewItemCharacteristic {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub name: String,
+    pub unit: Option<String>,
+    pub nominal: f64,
+    pub lower_tolerance: Option<f64>,
+    pub upper_tolerance: Option<f64>,
+    pub subgroup_size: i32,
+    pub machine_id: Option<Uuid>,
+    pub payload_pointer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = spc_measurements)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SpcMeasurement {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub characteristic_id: Uuid,
+    pub value: f64,
+    pub source: String,
+    pub machine_id: Option<Uuid>,
+    pub job_id: Option<Uuid>,
+    pub recorded_by_id: Option<Uuid>,
+    pub measured_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub instrument_id: Option<Uuid>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = spc_measurements)]
+pub struct NewSpcMeasurement {
+    pub tenant_id: Uuid,
+    pub characteristic_id: Uuid,
+    pub value: f64,
+    pub source: String,
+    pub machine_id: Option<Uuid>,
+    pub job_id: Option<Uuid>,
+    pub recorded_by_id: Option<Uuid>,
+    pub measured_at: DateTime<Utc>,
+    pub instrument_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MeasurementSource {
+    #[serde(rename = "manual")]
+    Manual,
+    #[serde(rename = "heartbeat")]
+    Heartbeat,
+}
+
+impl std::fmt::Display for MeasurementSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MeasurementSource::Manual => write!(f, "manual"),
+            MeasurementSource::Heartbeat => write!(f, "heartbeat"),
+        }
+    }
+}
+
+impl TryFrom<String> for MeasurementSource {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "manual" => Ok(MeasurementSource::Manual),
+            "heartbeat" => Ok(MeasurementSource::Heartbeat),
+            _ => Err(format!("Invalid measurement source: {}", value)),
+        }
+    }
+}
+
+/// Western Electric run rules signalling a process out of statistical control.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SpcRule {
+    /// A point beyond the control limits
+    #[serde(rename = "beyond_control_limits")]
+    BeyondControlLimits,
+    /// Two of three consecutive points beyond two sigma on the same side
+    #[serde(rename = "two_of_three_beyond_two_sigma")]
+    TwoOfThreeBeyondTwoSigma,
+    /// Four of five consecutive points beyond one sigma on the same side
+    #[serde(rename = "four_of_five_beyond_one_sigma")]
+    FourOfFiveBeyondOneSigma,
+    /// Eight consecutive points on the same side of the center line
+    #[serde(rename = "eight_on_one_side")]
+    EightOnOneSide,
+    /// Six consecutive points steadily increasing or decreasing
+    #[serde(rename = "six_trending")]
+    SixTrending,
+}
+
+// Request/Response DTOs
+
+/// Checks that a heartbeat mapping names both the machine and a JSON pointer into its payload.
+fn check_heartbeat_mapping(
+    machine_id: Option<Uuid>,
+    payload_pointer: Option<&str>,
+) -> Result<(), String> {
+    if machine_id.is_some() != payload_pointer.is_some() {
+        return Err("machine_id and payload_pointer are set together".to_string());
+    }
+    if payload_pointer.is_some_and(|pointer| !pointer.starts_with('/')) {
+        return Err("payload_pointer must be a JSON pointer such as /bore/diameter".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateItemCharacteristicRequest {
+    pub item_id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(min = 1, max = 20))]
+    pub unit: Option<String>,
+
+    pub nominal: f64,
+
+    /// Allowed deviation below nominal; leave out for an upper limit only
+    #[validate(range(min = 0.0))]
+    pub lower_tolerance: Option<f64>,
+
+    /// Allowed deviation above nominal; leave out for a lower limit only
+    #[validate(range(min = 0.0))]
+    pub upper_tolerance: Option<f64>,
+
+    /// Defaults to 1, charting individual values
+    #[validate(range(min = 1, max = 10))]
+    pub subgroup_size: Option<i32>,
+
+    /// QA machine reporting the characteristic in its heartbeat payload
+    pub machine_id: Option<Uuid>,
+
+    /// Where the value sits in the heartbeat payload
+    #[validate(length(min = 2, max = 200))]
+    pub payload_pointer: Option<String>,
+}
+
+impl CreateItemCharacteristicRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.lower_tolerance.is_none() && self.upper_tolerance.is_none() {
+            return Err("A characteristic needs a lower or an upper tolerance".to_string());
+        }
+        check_heartbeat_mapping(self.machine_id, self.payload_pointer.as_deref())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateItemCharacteristicRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    #[validate(length(min = 1, max = 20))]
+    pub unit: Option<String>,
+
+    pub nominal: Option<f64>,
+
+    #[validate(range(min = 0.0))]
+    pub lower_tolerance: Option<f64>,
+
+    #[validate(range(min = 0.0))]
+    pub upper_tolerance: Option<f64>,
+
+    /// Set together with `payload_pointer` to map a heartbeat value
+    pub machine_id: Option<Uuid>,
+
+    #[validate(length(min = 2, max = 200))]
+    pub payload_pointer: Option<String>,
+
+    /// Stops reading the characteristic from heartbeats
+    pub remove_heartbeat_mapping: Option<bool>,
+
+    pub is_active: Option<bool>,
+}
+
+impl UpdateItemCharacteristicRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.remove_heartbeat_mapping == Some(true)
+            && (self.machine_id.is_some() || self.payload_pointer.is_some())
+        {
+            return Err("A heartbeat mapping cannot be set and removed at once".to_string());
+        }
+        check_heartbeat_mapping(self.machine_id, self.payload_pointer.as_deref())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListItemCharacteristicsQuery {
+    pub item_id: Option<Uuid>,
+    pub machine_id: Option<Uuid>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemCharacteristicResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub name: String,
+    pub unit: Option<String>,
+    pub nominal: f64,
+    pub lower_tolerance: Option<f64>,
+    pub upper_tolerance: Option<f64>,
+    pub lower_spec_limit: Option<f64>,
+    pub upper_spec_limit: Option<f64>,
+    pub subgroup_size: i32,
+    pub machine_id: Option<Uuid>,
+    pub payload_pointer: Option<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ItemCharacteristic> for ItemCharacteristicResponse {
+    fn from(characteristic: ItemCharacteristic) -> Self {
+        Self {
+            lower_spec_limit: characteristic.lower_spec_limit(),
+            upper_spec_limit: characteristic.upper_spec_limit(),
+            id: characteristic.id,
+            item_id: characteristic.item_id,
+            name: characteristic.name,
+            unit: characteristic.unit,
+            nominal: characteristic.nominal,
+            lower_tolerance: characteristic.lower_tolerance,
+            upper_tolerance: characteristic.upper_tolerance,
+            subgroup_size: characteristic.subgroup_size,
+            machine_id: characteristic.machine_id,
+            payload_pointer: characteristic.payload_pointer,
+            is_active: characteristic.is_active,
+            created_at: characteristic.created_at.unwrap_or_else(Utc::now),
+            updated_at: characteristic.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementInput {
+    pub value: f64,
+    /// Defaults to now
+    pub measured_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RecordMeasurementsRequest {
+    #[validate(length(min = 1, max = 500))]
+    pub measurements: Vec<MeasurementInput>,
+
+    /// Machine the parts were measured on
+    pub machine_id: Option<Uuid>,
+
+    /// Job the measured parts belong to
+    pub job_id: Option<Uuid>,
+
+    /// Instrument the parts were measured with
+    pub instrument_id: Option<Uuid>,
+}
+
+impl RecordMeasurementsRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match self.measurements.iter().position(|m| !m.value.is_finite()) {
+            Some(index) => Err(format!("Measurement {} is not a number", index)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListMeasurementsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 1, max = 1000))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpcMeasurementResponse {
+    pub id: Uuid,
+    pub characteristic_id: Uuid,
+    pub value: f64,
+    pub source: MeasurementSource,
+    pub machine_id: Option<Uuid>,
+    pub job_id: Option<Uuid>,
+    pub instrument_id: Option<Uuid>,
+    pub recorded_by_id: Option<Uuid>,
+    pub measured_at: DateTime<Utc>,
+}
+
+impl From<SpcMeasurement> for SpcMeasurementResponse {
+    fn from(measurement: SpcMeasurement) -> Self {
+        Self {
+            source: MeasurementSource::try_from(measurement.source)
+                .unwrap_or(MeasurementSource::Manual),
+            id: measurement.id,
+            characteristic_id: measurement.characteristic_id,
+            value: measurement.value,
+            machine_id: measurement.machine_id,
+            job_id: measurement.job_id,
+            instrument_id: measurement.instrument_id,
+            recorded_by_id: measurement.recorded_by_id,
+            measured_at: measurement.measured_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SpcQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    /// Most recent points charted
+    #[validate(range(min = 2, max = 500))]
+    pub points: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpcChartPoint {
+    /// Individual value, or the subgroup mean
+    pub value: f64,
+    /// Moving range, or the subgroup range
+    pub range: Option<f64>,
+    /// Time of the last measurement in the point
+    pub measured_at: DateTime<Utc>,
+    pub violations: Vec<SpcRule>,
+}
+
+/// Control chart and capability of a characteristic. Statistics are empty until there are at
+/// least two points to chart.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpcStatisticsResponse {
+    pub characteristic_id: Uuid,
+    pub subgroup_size: i32,
+    pub measurement_count: usize,
+    pub nominal: f64,
+    pub lower_spec_limit: Option<f64>,
+    pub upper_spec_limit: Option<f64>,
+    pub mean: Option<f64>,
+    /// Overall sample standard deviation
+    pub std_dev: Option<f64>,
+    /// Within-subgroup standard deviation used for the limits and capability
+    pub sigma_within: Option<f64>,
+    pub center_line: Option<f64>,
+    pub upper_control_limit: Option<f64>,
+    pub lower_control_limit: Option<f64>,
+    pub range_center_line: Option<f64>,
+    pub range_upper_control_limit: Option<f64>,
+    pub range_lower_control_limit: Option<f64>,
+    pub cp: Option<f64>,
+    pub cpk: Option<f64>,
+    /// Measurements outside the specification
+    pub out_of_spec_count: usize,
+    /// No run rule is broken
+    pub in_control: bool,
+    pub points: Vec<SpcChartPoint>,
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::LineStatus;
+use crate::schema::*;
+
+/// Days ahead planned downtime is listed when the page does not say
+pub const DEFAULT_MAINTENANCE_DAYS: i32 = 14;
+
+// Status page models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = status_pages)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StatusPage {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub title: String,
+    pub description: Option<String>,
+    pub machine_ids: Vec<Uuid>,
+    pub group_ids: Vec<Uuid>,
+    pub show_maintenance: bool,
+    pub maintenance_days: i32,
+    pub token_prefix: Option<String>,
+    #[serde(skip_serializing)]
+    pub token_hash: Option<String>,
+    pub token_issued_at: Option<DateTime<Utc>>,
+    pub updated_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = status_pages)]
+pub struct NewStatusPage {
+    pub tenant_id: Uuid,
+    pub enabled: bool,
+    pub title: String,
+    pub description: Option<String>,
+    pub machine_ids: Vec<Uuid>,
+    pub group_ids: Vec<Uuid>,
+    pub show_maintenance: bool,
+    pub maintenance_days: i32,
+    pub updated_by_id: Option<Uuid>,
+}
+
+// Enums
+/// Availability of a machine as customers see it on the status page.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MachineAvailability {
+    /// Up, whether busy or idle
+    #[serde(rename = "operational")]
+    Operational,
+    /// In maintenance, or down during planned downtime
+    #[serde(rename = "maintenance")]
+    Maintenance,
+    /// Down, faulted or not heard from
+    #[serde(rename = "unavailable")]
+    Unavailable,
+}
+
+// Request/Response DTOs
+
+/// The whole status page configuration; what is left out takes its default.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateStatusPageRequest {
+    /// Whether the page is served
+    pub enabled: bool,
+
+    #[validate(length(min = 1, max = 100))]
+    pub title: String,
+
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+
+    /// Machines shown, in order
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub machine_ids: Vec<Uuid>,
+
+    /// Machine groups shown as lines, in order
+    #[serde(default)]
+    #[validate(length(max = 50))]
+    pub group_ids: Vec<Uuid>,
+
+    /// Whether planned downtime is listed (default true)
+    pub show_maintenance: Option<bool>,
+
+    /// How many days ahead planned downtime is listed (default 14)
+    #[validate(range(min = 1, max = 90))]
+    pub maintenance_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusPageResponse {
+    pub id: Uuid,
+    pub enabled: bool,
+    pub title: String,
+    pub description: Option<String>,
+    pub machine_ids: Vec<Uuid>,
+    pub group_ids: Vec<Uuid>,
+    pub show_maintenance: bool,
+    pub maintenance_days: i32,
+    /// Start of the current token; `None` until one is issued, and the page cannot be reached
+    /// without one
+    pub token_prefix: Option<String>,
+    pub token_issued_at: Option<DateTime<Utc>>,
+    pub updated_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<StatusPage> for StatusPageResponse {
+    fn from(page: StatusPage) -> Self {
+        Self {
+            id: page.id,
+            enabled: page.enabled,
+            title: page.title,
+            description: page.description,
+            machine_ids: page.machine_ids,
+            group_ids: page.group_ids,
+            show_maintenance: page.show_maintenance,
+            maintenance_days: page.maintenance_days,
+            token_prefix: page.token_prefix,
+            token_issued_at: page.token_issued_at,
+            updated_by_id: page.updated_by_id,
+            created_at: page.created_at.unwrap_or_else(Utc::now),
+            updated_at: page.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// A newly issued token, which is not shown again. The page is served at
+/// `/api/v1/public-status/{token}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusPageTokenResponse {
+    pub status_page: StatusPageResponse,
+    pub token: String,
+}
+
+/// The status page as the public sees it. Only what the tenant chose to show is in it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicStatusPage {
+    pub title: String,
+    pub description: Option<String>,
+    pub machines: Vec<PublicMachineStatus>,
+    pub lines: Vec<PublicLineStatus>,
+    /// Planned downtime in progress or coming up, soonest first; empty when not shown
+    pub maintenance: Vec<PublicMaintenanceWindow>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicMachineStatus {
+    pub name: String,
+    pub status: MachineAvailability,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicLineStatus {
+    pub name: String,
+    pub status: LineStatus,
+}
+
+/// Planned downtime of shown machines or lines, or of the whole site.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PublicMaintenanceWindow {
+    /// The shown machine the downtime is for
+    pub machine: Option<String>,
+    /// Shown lines the machine is part of
+    pub lines: Vec<String>,
+    /// Whether the downtime covers every machine
+    pub site_wide: bool,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub in_progress: bool,
+}
@@ -0,0 +1,542 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::quantity::{validate_movement_quantity, validate_reserved_quantity, Quantity};
+use crate::models::ItemContext;
+use crate::schema::*;
+
+// Stock Movement Models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = stock_movements)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StockMovement {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: String,
+    pub movement_type: String,
+    pub quantity: Quantity,
+    pub quantity_after: Quantity,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub uom_id: Option<Uuid>,
+    pub uom_quantity: Option<Quantity>,
+    pub owner_id: Option<Uuid>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = stock_movements)]
+pub struct NewStockMovement {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: String,
+    pub movement_type: String,
+    pub quantity: Quantity,
+    pub quantity_after: Quantity,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub uom_id: Option<Uuid>,
+    pub uom_quantity: Option<Quantity>,
+    pub owner_id: Option<Uuid>,
+}
+
+// Stock Reservation Models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = stock_reservations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct StockReservation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: String,
+    pub quantity: Quantity,
+    pub reference_type: String,
+    pub reference_id: Uuid,
+    pub status: String,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = stock_reservations)]
+pub struct NewStockReservation {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: String,
+    pub quantity: Quantity,
+    pub reference_type: String,
+    pub reference_id: Uuid,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+}
+
+// Enums
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StockMovementType {
+    #[serde(rename = "receipt")]
+    Receipt,
+    #[serde(rename = "issue")]
+    Issue,
+    #[serde(rename = "adjustment")]
+    Adjustment,
+    #[serde(rename = "return")]
+    Return,
+}
+
+impl StockMovementType {
+    /// Signed quantity change for a movement. Receipts, returns and issues take a
+    /// positive magnitude; adjustments carry their own sign.
+    pub fn signed_quantity(&self, quantity: Quantity) -> Quantity {
+        match self {
+            StockMovementType::Receipt | StockMovementType::Return => quantity.abs(),
+            StockMovementType::Issue => -quantity.abs(),
+            StockMovementType::Adjustment => quantity,
+        }
+    }
+}
+
+impl std::fmt::Display for StockMovementType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StockMovementType::Receipt => write!(f, "receipt"),
+            StockMovementType::Issue => write!(f, "issue"),
+            StockMovementType::Adjustment => write!(f, "adjustment"),
+            StockMovementType::Return => write!(f, "return"),
+        }
+    }
+}
+
+impl From<StockMovementType> for String {
+    fn from(movement_type: StockMovementType) -> Self {
+        movement_type.to_string()
+    }
+}
+
+impl TryFrom<String> for StockMovementType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "receipt" => Ok(StockMovementType::Receipt),
+            "issue" => Ok(StockMovementType::Issue),
+            "adjustment" => Ok(StockMovementType::Adjustment),
+            "return" => Ok(StockMovementType::Return),
+            _ => Err(format!("Invalid stock movement type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StockReferenceType {
+    #[serde(rename = "job")]
+    Job,
+    #[serde(rename = "order")]
+    Order,
+    #[serde(rename = "manual")]
+    Manual,
+    #[serde(rename = "return")]
+    Return,
+}
+
+impl std::fmt::Display for StockReferenceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StockReferenceType::Job => write!(f, "job"),
+            StockReferenceType::Order => write!(f, "order"),
+            StockReferenceType::Manual => write!(f, "manual"),
+            StockReferenceType::Return => write!(f, "return"),
+        }
+    }
+}
+
+impl From<StockReferenceType> for String {
+    fn from(reference_type: StockReferenceType) -> Self {
+        reference_type.to_string()
+    }
+}
+
+impl TryFrom<String> for StockReferenceType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "job" => Ok(StockReferenceType::Job),
+            "order" => Ok(StockReferenceType::Order),
+            "manual" => Ok(StockReferenceType::Manual),
+            "return" => Ok(StockReferenceType::Return),
+            _ => Err(format!("Invalid stock reference type: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReservationStatus {
+    #[serde(rename = "active")]
+    Active,
+    #[serde(rename = "released")]
+    Released,
+    /// Fully drawn down by issues against the reserving order or job
+    #[serde(rename = "consumed")]
+    Consumed,
+}
+
+impl std::fmt::Display for ReservationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReservationStatus::Active => write!(f, "active"),
+            ReservationStatus::Released => write!(f, "released"),
+            ReservationStatus::Consumed => write!(f, "consumed"),
+        }
+    }
+}
+
+impl From<ReservationStatus> for String {
+    fn from(status: ReservationStatus) -> Self {
+        status.to_string()
+    }
+}
+
+impl TryFrom<String> for ReservationStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "active" => Ok(ReservationStatus::Active),
+            "released" => Ok(ReservationStatus::Released),
+            "consumed" => Ok(ReservationStatus::Consumed),
+            _ => Err(format!("Invalid reservation status: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ForecastMethod {
+    #[serde(rename = "moving_average")]
+    MovingAverage,
+    #[serde(rename = "exponential_smoothing")]
+    ExponentialSmoothing,
+}
+
+impl std::fmt::Display for ForecastMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForecastMethod::MovingAverage => write!(f, "moving_average"),
+            ForecastMethod::ExponentialSmoothing => write!(f, "exponential_smoothing"),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RecordStockMovementRequest {
+    pub context: ItemContext,
+    pub movement_type: StockMovementType,
+
+    #[validate(custom = "validate_movement_quantity")]
+    pub quantity: Quantity,
+
+    pub reference_type: Option<StockReferenceType>,
+    pub reference_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub occurred_at: Option<DateTime<Utc>>,
+
+    /// Lets an issue dip into stock reserved for other orders or jobs instead of failing
+    #[serde(default)]
+    pub allow_reserved: bool,
+
+    /// Unit the quantity is given in, converted to the item's base unit; the base unit when
+    /// left out
+    pub uom_id: Option<Uuid>,
+
+    /// Vendor or customer whose consignment stock moves. Left out, the movement is on our own
+    /// stock, except that issues go on to consignment stock once our own runs out
+    pub owner_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockMovementResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: ItemContext,
+    pub movement_type: StockMovementType,
+    pub quantity: Quantity,
+    pub quantity_after: Quantity,
+    pub reference_type: Option<StockReferenceType>,
+    pub reference_id: Option<Uuid>,
+    pub person_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    /// Unit and signed quantity the movement was entered in, when not the base unit
+    pub uom_id: Option<Uuid>,
+    pub uom_quantity: Option<Quantity>,
+    /// Partner whose consignment stock the movement was entered against
+    pub owner_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateStockReservationRequest {
+    pub context: ItemContext,
+
+    #[validate(custom = "validate_reserved_quantity")]
+    pub quantity: Quantity,
+
+    pub reference_type: StockReferenceType,
+    pub reference_id: Uuid,
+
+    #[validate(length(max = 1000))]
+    pub notes: Option<String>,
+}
+
+impl CreateStockReservationRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match self.reference_type {
+            StockReferenceType::Order | StockReferenceType::Job => Ok(()),
+            _ => Err("Reservations must reference an order or a job".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ListStockReservationsQuery {
+    pub item_id: Option<Uuid>,
+    pub context: Option<ItemContext>,
+    pub status: Option<ReservationStatus>,
+    pub reference_type: Option<StockReferenceType>,
+    pub reference_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 500))]
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockReservationResponse {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: ItemContext,
+    pub quantity: Quantity,
+    pub reference_type: StockReferenceType,
+    pub reference_id: Uuid,
+    pub status: ReservationStatus,
+    pub notes: Option<String>,
+    pub created_by_id: Option<Uuid>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<StockReservation> for StockReservationResponse {
+    fn from(reservation: StockReservation) -> Self {
+        Self {
+            id: reservation.id,
+            item_id: reservation.item_id,
+            inventory_item_id: reservation.inventory_item_id,
+            context: ItemContext::try_from(reservation.context).unwrap_or(ItemContext::Store),
+            quantity: reservation.quantity,
+            reference_type: StockReferenceType::try_from(reservation.reference_type)
+                .unwrap_or(StockReferenceType::Order),
+            reference_id: reservation.reference_id,
+            status: ReservationStatus::try_from(reservation.status)
+                .unwrap_or(ReservationStatus::Active),
+            notes: reservation.notes,
+            created_by_id: reservation.created_by_id,
+            released_at: reservation.released_at,
+            created_at: reservation.created_at.unwrap_or_else(Utc::now),
+            updated_at: reservation.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+/// Stock of one inventory record split into what is reserved and what is still free to promise.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockAvailabilityResponse {
+    pub item_id: Uuid,
+    pub inventory_item_id: Uuid,
+    pub context: ItemContext,
+    pub on_hand: Quantity,
+    pub reserved: Quantity,
+    /// `on_hand - reserved`; negative when stock was issued past its reservations
+    pub available: Quantity,
+    pub reservations: Vec<StockReservationResponse>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct DemandForecastQuery {
+    pub context: Option<ItemContext>,
+    pub method: Option<ForecastMethod>,
+
+    #[validate(range(min = 1, max = 60))]
+    pub months: Option<u32>,
+
+    #[validate(range(min = 1, max = 60))]
+    pub window: Option<u32>,
+
+    #[validate(range(min = 0.01, max = 1.0))]
+    pub alpha: Option<f64>,
+
+    #[validate(range(min = 1, max = 24))]
+    pub horizon: Option<u32>,
+
+    #[validate(range(min = 0.5, max = 0.9999))]
+    pub service_level: Option<f64>,
+
+    #[validate(range(max = 3650))]
+    pub lead_time_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MonthlyConsumptionRow {
+    #[diesel(sql_type = diesel::sql_types::Timestamptz)]
+    pub month_start: DateTime<Utc>,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub quantity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyQuantity {
+    /// Calendar month in `YYYY-MM` form
+    pub month: String,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DemandForecastResponse {
+    pub item_id: Uuid,
+    pub context: Option<ItemContext>,
+    pub method: ForecastMethod,
+    pub window: u32,
+    pub alpha: f64,
+    pub service_level: f64,
+    pub history: Vec<MonthlyQuantity>,
+    pub moving_average: f64,
+    pub exponential_smoothing: f64,
+    pub forecast: Vec<MonthlyQuantity>,
+    pub demand_std_dev: f64,
+    pub lead_time_days: u32,
+    pub on_hand: Quantity,
+    pub safety_stock: i64,
+    pub reorder_point: i64,
+    pub suggested_reorder_quantity: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ItemAnalyticsQuery {
+    pub context: Option<ItemContext>,
+
+    /// Complete calendar months of consumption history; defaults to 12
+    #[validate(range(min = 1, max = 60))]
+    pub months: Option<u32>,
+
+    /// Days of recent consumption the burn rate is taken over; defaults to 90
+    #[validate(range(min = 1, max = 365))]
+    pub burn_rate_days: Option<u32>,
+
+    /// Stock with no movement for this many days is dead stock; defaults to 180
+    #[validate(range(min = 1, max = 3650))]
+    pub dead_stock_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MonthlyMovementRow {
+    #[diesel(sql_type = diesel::sql_types::Timestamptz)]
+    pub month_start: DateTime<Utc>,
+    /// Issues net of returns
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub consumed: f64,
+    /// Change in stock from every movement
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub net_change: f64,
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct MovementActivityRow {
+    #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Timestamptz>)]
+    pub last_movement_at: Option<DateTime<Utc>>,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub recent_consumed: f64,
+}
+
+/// Consumption and turnover of an item, derived from its stock movement ledger.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemAnalyticsResponse {
+    pub item_id: Uuid,
+    pub context: Option<ItemContext>,
+    /// Issues net of returns in each complete month, oldest first
+    pub consumption: Vec<MonthlyQuantity>,
+    pub total_consumed: f64,
+    pub on_hand: Quantity,
+    /// Mean stock over the months, from the opening balance and each month-end balance
+    pub average_inventory: f64,
+    /// Times the average inventory was consumed over the months; empty without stock
+    pub turnover_ratio: Option<f64>,
+    pub burn_rate_days: u32,
+    /// Mean daily consumption over the last `burn_rate_days`
+    pub daily_burn_rate: f64,
+    /// How long stock on hand lasts at the burn rate; empty when nothing is being consumed
+    pub days_of_stock: Option<f64>,
+    pub last_movement_at: Option<DateTime<Utc>>,
+    pub dead_stock_days: u32,
+    /// Stock on hand with no movement in the last `dead_stock_days`
+    pub is_dead_stock: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AvailableToPromiseQuery {
+    #[validate(range(min = 1, max = 1000000))]
+    pub quantity: i32,
+
+    /// Requested delivery date; defaults to now
+    pub date: Option<DateTime<Utc>>,
+
+    /// Restricts on-hand stock and reservations to one context; open orders always count
+    pub context: Option<ItemContext>,
+}
+
+/// Supply and demand expected on one date and the balance they leave.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AtpPeriod {
+    pub date: DateTime<Utc>,
+    pub supply: i32,
+    pub demand: i32,
+    pub projected: i32,
+    pub available_to_promise: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AvailableToPromiseResponse {
+    pub item_id: Uuid,
+    pub quantity: i32,
+    pub date: DateTime<Utc>,
+    /// Promising starts from the whole units of `on_hand - reserved`
+    pub on_hand: Quantity,
+    pub reserved: Quantity,
+    /// Outstanding quantity on open purchase orders
+    pub open_supply: i32,
+    /// Unreserved, unshipped quantity on open customer and distributor orders
+    pub open_demand: i32,
+    pub available_on_date: i32,
+    pub can_promise: bool,
+    /// Earliest date the full quantity can be promised, if any expected receipt covers it
+    pub promise_date: Option<DateTime<Utc>>,
+    pub schedule: Vec<AtpPeriod>,
+}
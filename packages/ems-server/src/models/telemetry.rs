@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Machine telemetry models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = machine_telemetry)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct MachineTelemetry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub channel: String,
+    pub value: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = machine_telemetry)]
+pub struct NewMachineTelemetry {
+    pub tenant_id: Uuid,
+    pub machine_id: Uuid,
+    pub channel: String,
+    pub value: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+// Enums
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TelemetryChannel {
+    #[serde(rename = "energy_kwh")]
+    EnergyKwh,
+    #[serde(rename = "air_pressure")]
+    AirPressure,
+    #[serde(rename = "coolant_level")]
+    CoolantLevel,
+    #[serde(rename = "temperature")]
+    Temperature,
+}
+
+impl TelemetryChannel {
+    pub const ALL: [TelemetryChannel; 4] = [
+        TelemetryChannel::EnergyKwh,
+        TelemetryChannel::AirPressure,
+        TelemetryChannel::CoolantLevel,
+        TelemetryChannel::Temperature,
+    ];
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            TelemetryChannel::EnergyKwh => "kWh",
+            TelemetryChannel::AirPressure => "bar",
+            TelemetryChannel::CoolantLevel => "%",
+            TelemetryChannel::Temperature => "°C",
+        }
+    }
+}
+
+impl std::fmt::Display for TelemetryChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TelemetryChannel::EnergyKwh => write!(f, "energy_kwh"),
+            TelemetryChannel::AirPressure => write!(f, "air_pressure"),
+            TelemetryChannel::CoolantLevel => write!(f, "coolant_level"),
+            TelemetryChannel::Temperature => write!(f, "temperature"),
+        }
+    }
+}
+
+impl From<TelemetryChannel> for String {
+    fn from(channel: TelemetryChannel) -> Self {
+        channel.to_string()
+    }
+}
+
+impl TryFrom<String> for TelemetryChannel {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "energy_kwh" => Ok(TelemetryChannel::EnergyKwh),
+            "air_pressure" => Ok(TelemetryChannel::AirPressure),
+            "coolant_level" => Ok(TelemetryChannel::CoolantLevel),
+            "temperature" => Ok(TelemetryChannel::Temperature),
+            _ => Err(format!("Invalid telemetry channel: {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, QueryableByName)]
+pub struct TelemetryBucketRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub channel: String,
+    #[diesel(sql_type = diesel::sql_types::Timestamptz)]
+    pub bucket_start: DateTime<Utc>,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub avg_value: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub min_value: f64,
+    #[diesel(sql_type = diesel::sql_types::Double)]
+    pub max_value: f64,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub samples: i64,
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TelemetryQuery {
+    /// Omit to return every channel
+    pub channel: Option<TelemetryChannel>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    /// Requested resolution; widened when the window would return too many points
+    #[validate(range(min = 1, max = 2678400))]
+    pub bucket_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryPoint {
+    pub bucket_start: DateTime<Utc>,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+    pub samples: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetrySeries {
+    pub channel: TelemetryChannel,
+    pub unit: String,
+    pub points: Vec<TelemetryPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MachineTelemetryResponse {
+    pub machine_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket_seconds: i64,
+    pub series: Vec<TelemetrySeries>,
+}
@@ -6,6 +6,11 @@ use uuid::Uuid;
 use validator::Validate;
 
 use crate::schema::tenants;
+use crate::utils::access_log::check_access_log_settings;
+use crate::utils::archive::check_archive_settings;
+use crate::utils::branding::{check_branding_settings, settings_branding, BrandingSettings};
+use crate::utils::i18n::{check_settings_locale, settings_locale, Locale};
+use crate::utils::machine_alert::check_anomaly_settings;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = tenants)]
@@ -14,11 +19,35 @@ pub struct Tenant {
     pub id: Uuid,
     pub name: String,
     pub subdomain: String,
+    /// Dedicated database for the tenant; not serialized since the URL carries credentials
+    #[serde(skip_serializing)]
     pub database_url: Option<String>,
+    /// Secrets under keys like `client_secret` or `api_key` are stored and returned encrypted;
+    /// see [`crate::utils::encryption::is_secret_setting`]
     pub settings: Option<serde_json::Value>,
     pub is_active: Option<bool>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    /// Set while a platform operator has the tenant suspended
+    pub suspended_at: Option<DateTime<Utc>>,
+    pub suspended_reason: Option<String>,
+    /// Non-production tenant cloned from `sandbox_of`, deleted at `sandbox_expires_at`
+    pub is_sandbox: bool,
+    pub sandbox_of: Option<Uuid>,
+    pub sandbox_expires_at: Option<DateTime<Utc>>,
+}
+
+impl Tenant {
+    /// Locale for the tenant's documents and its default for API messages, set under
+    /// `locale` in the settings.
+    pub fn locale(&self) -> Locale {
+        settings_locale(self.settings.as_ref()).unwrap_or_default()
+    }
+
+    /// Branding of the tenant's customer documents, set under `branding` in the settings.
+    pub fn branding(&self) -> BrandingSettings {
+        settings_branding(self.settings.as_ref())
+    }
 }
 
 #[derive(Debug, Insertable)]
@@ -42,6 +71,16 @@ pub struct CreateTenantRequest {
     pub settings: Option<serde_json::Value>,
 }
 
+impl CreateTenantRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_settings_locale(self.settings.as_ref())?;
+        check_archive_settings(self.settings.as_ref())?;
+        check_access_log_settings(self.settings.as_ref())?;
+        check_anomaly_settings(self.settings.as_ref())?;
+        check_branding_settings(self.settings.as_ref())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateTenantRequest {
     #[validate(length(min = 1, max = 100))]
@@ -50,6 +89,90 @@ pub struct UpdateTenantRequest {
     pub settings: Option<serde_json::Value>,
 
     pub is_active: Option<bool>,
+
+    /// PostgreSQL URL of a dedicated database; an empty string clears it
+    #[validate(length(max = 500))]
+    pub database_url: Option<String>,
+}
+
+impl UpdateTenantRequest {
+    pub fn check(&self) -> Result<(), String> {
+        match &self.database_url {
+            Some(url)
+                if !url.is_empty()
+                    && !url.starts_with("postgres://")
+                    && !url.starts_with("postgresql://") =>
+            {
+                Err("Tenant database URL must be a PostgreSQL URL".to_string())
+            }
+            _ => {
+                check_settings_locale(self.settings.as_ref())?;
+                check_archive_settings(self.settings.as_ref())?;
+                check_access_log_settings(self.settings.as_ref())?;
+                check_anomaly_settings(self.settings.as_ref())?;
+                check_branding_settings(self.settings.as_ref())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CloneSandboxRequest {
+    /// Defaults to the tenant's name marked as a sandbox
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    /// Defaults to the tenant's subdomain with a random suffix
+    #[validate(length(min = 1, max = 50), regex(path = "SUBDOMAIN_REGEX"))]
+    pub subdomain: Option<String>,
+
+    /// Also copy machines and inventory, not only configuration
+    #[serde(default)]
+    pub include_data: bool,
+
+    /// Defaults to SANDBOX_DEFAULT_TTL_DAYS; capped at SANDBOX_MAX_TTL_DAYS
+    #[validate(range(min = 1))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CloneSandboxResponse {
+    #[serde(flatten)]
+    pub tenant: Tenant,
+    /// Rows copied into the sandbox, by table
+    pub copied: std::collections::BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrandingPreviewFormat {
+    /// An order confirmation PDF
+    #[default]
+    Pdf,
+    /// The email an order confirmation is sent with
+    Email,
+}
+
+/// Renders a sample order confirmation with branding that has not been saved, or with the
+/// saved branding when `branding` is left out.
+#[derive(Debug, Deserialize)]
+pub struct BrandingPreviewRequest {
+    pub branding: Option<BrandingSettings>,
+    #[serde(default)]
+    pub format: BrandingPreviewFormat,
+    /// Defaults to the tenant's locale
+    pub locale: Option<Locale>,
+}
+
+/// A sample branded email. The HTML shows the logo from a `data:` URL, where sent emails
+/// attach it.
+#[derive(Debug, Serialize)]
+pub struct BrandingEmailPreview {
+    pub subject: String,
+    pub reply_to: Option<String>,
+    pub text: String,
+    /// Left out for unbranded emails, which are sent as plain text
+    pub html: Option<String>,
 }
 
 #[derive(Debug, Clone)]
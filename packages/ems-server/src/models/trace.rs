@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::Quantity;
+
+/// Record a trace starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceEntity {
+    /// A produced lot, by batch record ID or batch number, or a component lot number
+    #[serde(rename = "lot")]
+    Lot,
+    /// A component serial number recorded as consumed
+    #[serde(rename = "serial")]
+    Serial,
+    /// A job, by ID or job number
+    #[serde(rename = "job")]
+    Job,
+    /// An order, by ID or order number
+    #[serde(rename = "order")]
+    Order,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TraceQuery {
+    pub entity: TraceEntity,
+
+    #[validate(length(min = 1, max = 100))]
+    pub id: String,
+
+    /// Component a lot or serial number belongs to, when the number is used by several
+    pub item_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TraceNodeKind {
+    #[serde(rename = "order")]
+    Order,
+    #[serde(rename = "job")]
+    Job,
+    #[serde(rename = "machine")]
+    Machine,
+    #[serde(rename = "lot")]
+    Lot,
+    #[serde(rename = "serial")]
+    Serial,
+    #[serde(rename = "component")]
+    Component,
+    #[serde(rename = "vendor")]
+    Vendor,
+}
+
+impl std::fmt::Display for TraceNodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceNodeKind::Order => write!(f, "order"),
+            TraceNodeKind::Job => write!(f, "job"),
+            TraceNodeKind::Machine => write!(f, "machine"),
+            TraceNodeKind::Lot => write!(f, "lot"),
+            TraceNodeKind::Serial => write!(f, "serial"),
+            TraceNodeKind::Component => write!(f, "component"),
+            TraceNodeKind::Vendor => write!(f, "vendor"),
+        }
+    }
+}
+
+/// How two nodes are linked, read from the edge's source to its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TraceRelation {
+    /// Order to a job whose lot shipped on it
+    #[serde(rename = "fulfilled_by")]
+    FulfilledBy,
+    /// Job to a machine it was assigned to
+    #[serde(rename = "ran_on")]
+    RanOn,
+    /// Job to the lot it completed
+    #[serde(rename = "produced")]
+    Produced,
+    /// Lot to a machine recorded in its batch record
+    #[serde(rename = "built_on")]
+    BuiltOn,
+    /// Lot to a component, component lot or serial it consumed
+    #[serde(rename = "consumed")]
+    Consumed,
+    /// Component lot or serial to its component
+    #[serde(rename = "of_item")]
+    OfItem,
+    /// Component to a vendor stocking it
+    #[serde(rename = "supplied_by")]
+    SuppliedBy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceNode {
+    /// `<kind>:<key>`, unique in the graph
+    pub id: String,
+    pub kind: TraceNodeKind,
+    pub label: String,
+    /// Order, job, machine, batch record, item or vendor the node stands for; component lots
+    /// and serials have no record of their own
+    pub record_id: Option<Uuid>,
+    /// Item the node is made of or is, for lots, serials and components
+    pub item_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceEdge {
+    pub from: String,
+    pub to: String,
+    pub relation: TraceRelation,
+    /// Quantity consumed, on edges from a lot to a component
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<Quantity>,
+}
+
+/// Everything linked to the traced record, upstream to the orders it shipped on and
+/// downstream to the components and vendors it was built from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceGraph {
+    /// Nodes the trace started from; a component lot or serial number used by several items
+    /// starts from each
+    pub roots: Vec<String>,
+    pub nodes: Vec<TraceNode>,
+    pub edges: Vec<TraceEdge>,
+}
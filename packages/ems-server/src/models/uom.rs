@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Unit of measure models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = units_of_measure)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UnitOfMeasure {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub dimension: String,
+    pub factor: f64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = units_of_measure)]
+pub struct NewUnitOfMeasure {
+    pub tenant_id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub dimension: String,
+    pub factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = item_units)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ItemUnit {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub base_uom_id: Uuid,
+    pub purchase_uom_id: Option<Uuid>,
+    pub purchase_factor: Option<f64>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = item_units)]
+pub struct NewItemUnit {
+    pub tenant_id: Uuid,
+    pub item_id: Uuid,
+    pub base_uom_id: Uuid,
+    pub purchase_uom_id: Option<Uuid>,
+    pub purchase_factor: Option<f64>,
+}
+
+// Enums
+/// What a unit measures. Units of one dimension convert through their factors; converting
+/// between dimensions needs a per-item factor, such as the meters of wire on a spool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UomDimension {
+    #[serde(rename = "count")]
+    Count,
+    #[serde(rename = "length")]
+    Length,
+    #[serde(rename = "mass")]
+    Mass,
+    #[serde(rename = "volume")]
+    Volume,
+}
+
+impl std::fmt::Display for UomDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UomDimension::Count => write!(f, "count"),
+            UomDimension::Length => write!(f, "length"),
+            UomDimension::Mass => write!(f, "mass"),
+            UomDimension::Volume => write!(f, "volume"),
+        }
+    }
+}
+
+impl From<UomDimension> for String {
+    fn from(dimension: UomDimension) -> Self {
+        dimension.to_string()
+    }
+}
+
+impl TryFrom<String> for UomDimension {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "count" => Ok(UomDimension::Count),
+            "length" => Ok(UomDimension::Length),
+            "mass" => Ok(UomDimension::Mass),
+            "volume" => Ok(UomDimension::Volume),
+            _ => Err(format!("Invalid unit of measure dimension: {}", value)),
+        }
+    }
+}
+
+// Request/Response DTOs
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateUnitOfMeasureRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub code: String,
+
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub dimension: UomDimension,
+
+    /// Size of the unit in the reference unit of its dimension (each, meter, kilogram or
+    /// liter), e.g. 0.001 for millimeters
+    pub factor: f64,
+}
+
+impl CreateUnitOfMeasureRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_factor("factor", Some(self.factor))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateUnitOfMeasureRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+
+    /// Changes how quantities entered from now on convert; quantities already recorded keep
+    /// their base unit amounts
+    pub factor: Option<f64>,
+}
+
+impl UpdateUnitOfMeasureRequest {
+    pub fn check(&self) -> Result<(), String> {
+        check_factor("factor", self.factor)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SetItemUnitsRequest {
+    /// Unit stock of the item is counted in
+    pub base_uom_id: Uuid,
+    /// Unit the item is bought in; purchase order lines default to it
+    pub purchase_uom_id: Option<Uuid>,
+    /// Base units in one purchase unit. Required when the two measure different things (a
+    /// spool of wire to meters); otherwise it overrides the units' own factors.
+    pub purchase_factor: Option<f64>,
+}
+
+impl SetItemUnitsRequest {
+    pub fn check(&self) -> Result<(), String> {
+        if self.purchase_factor.is_some() && self.purchase_uom_id.is_none() {
+            return Err("purchase_factor requires purchase_uom_id".to_string());
+        }
+        check_factor("purchase_factor", self.purchase_factor)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConvertQuantityQuery {
+    pub quantity: f64,
+    pub from_uom_id: Uuid,
+    /// Defaults to the item's base unit
+    pub to_uom_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitOfMeasureResponse {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    pub dimension: UomDimension,
+    pub factor: f64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<UnitOfMeasure> for UnitOfMeasureResponse {
+    fn from(unit: UnitOfMeasure) -> Self {
+        Self {
+            id: unit.id,
+            code: unit.code,
+            name: unit.name,
+            dimension: UomDimension::try_from(unit.dimension).unwrap_or(UomDimension::Count),
+            factor: unit.factor,
+            created_at: unit.created_at.unwrap_or_else(Utc::now),
+            updated_at: unit.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemUnitsResponse {
+    pub item_id: Uuid,
+    pub base_uom: UnitOfMeasureResponse,
+    pub purchase_uom: Option<UnitOfMeasureResponse>,
+    /// Base units in one purchase unit, as set or derived from the units' factors
+    pub purchase_factor: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversionResponse {
+    pub item_id: Uuid,
+    pub quantity: f64,
+    pub from_uom: UnitOfMeasureResponse,
+    pub converted_quantity: f64,
+    pub to_uom: UnitOfMeasureResponse,
+}
+
+fn check_factor(field: &str, factor: Option<f64>) -> Result<(), String> {
+    match factor {
+        Some(factor) if !(factor.is_finite() && factor > 0.0) => {
+            Err(format!("{} must be greater than zero", field))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::schema::*;
+
+// Validation rule models
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = validation_rules)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ValidationRule {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub entity: String,
+    pub condition: Option<String>,
+    pub requirement: String,
+    pub message: Option<String>,
+    pub enabled: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = validation_rules)]
+pub struct NewValidationRule {
+    pub tenant_id: Uuid,
+    pub name: String,
+    pub entity: String,
+    pub condition: Option<String>,
+    pub requirement: String,
+    pub message: Option<String>,
+    pub enabled: bool,
+    pub created_by_id: Option<Uuid>,
+}
+
+// Enums
+/// The kind of record a validation rule checks.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RuleEntity {
+    #[serde(rename = "order")]
+    Order,
+    #[serde(rename = "item")]
+    Item,
+}
+
+impl RuleEntity {
+    /// Fields of the record rule expressions can refer to. Anything under `metadata` can be
+    /// referred to as well, such as `metadata.project_code`.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            RuleEntity::Order => &[
+                "order_number",
+                "order_type",
+                "external_entity_type",
+                "total_amount",
+                "status",
+                "notes",
+                "line_count",
+                "metadata",
+            ],
+            RuleEntity::Item => &[
+                "internal_part_number",
+                "mfr_part_number",
+                "manufacturer",
+                "datasheet",
+                "lifecycle",
+                "description",
+                "category",
+                "metadata",
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for RuleEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleEntity::Order => write!(f, "order"),
+            RuleEntity::Item => write!(f, "item"),
+        }
+    }
+}
+
+impl From<RuleEntity> for String {
+    fn from(entity: RuleEntity) -> Self {
+        entity.to_string()
+    }
+}
+
+impl TryFrom<String> for RuleEntity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "order" => Ok(RuleEntity::Order),
+            "item" => Ok(RuleEntity::Item),
+            _ => Err(format!("Invalid rule entity: {}", value)),
+        }
+    }
+}
+
+/// A rule a record broke.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RuleViolation {
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub message: String,
+}
+
+/// A record was rejected for breaking one or more of the tenant's validation rules.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{}", violation_messages(.violations))]
+pub struct RuleViolations {
+    pub violations: Vec<RuleViolation>,
+}
+
+impl RuleViolations {
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<RuleViolations>().is_some()
+    }
+}
+
+fn violation_messages(violations: &[RuleViolation]) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// Request/Response DTOs
+
+/// A business rule, written in the rule expression language: field names such as
+/// `total_amount` or `metadata.project_code`, literals, the comparisons `==`, `!=`, `<`, `<=`,
+/// `>` and `>=`, `in [..]`, `present(field)`, `and`, `or`, `not` and parentheses.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateValidationRuleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    pub entity: RuleEntity,
+
+    /// When the rule applies, e.g. `total_amount > 10000`; always when left out
+    #[validate(length(min = 1, max = 500))]
+    pub condition: Option<String>,
+
+    /// What a record the rule applies to must satisfy, e.g. `present(metadata.project_code)`
+    #[validate(length(min = 1, max = 500))]
+    pub requirement: String,
+
+    /// Message records breaking the rule are rejected with; made from the name when left out
+    #[validate(length(min = 1, max = 500))]
+    pub message: Option<String>,
+
+    /// Whether the rule is enforced (default true)
+    pub enabled: Option<bool>,
+}
+
+/// The whole rule; it keeps the kind of record it applies to.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateValidationRuleRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(length(min = 1, max = 500))]
+    pub condition: Option<String>,
+
+    #[validate(length(min = 1, max = 500))]
+    pub requirement: String,
+
+    #[validate(length(min = 1, max = 500))]
+    pub message: Option<String>,
+
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ValidationRuleQuery {
+    pub entity: Option<RuleEntity>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationRuleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub entity: RuleEntity,
+    pub condition: Option<String>,
+    pub requirement: String,
+    pub message: Option<String>,
+    pub enabled: bool,
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ValidationRule> for ValidationRuleResponse {
+    fn from(rule: ValidationRule) -> Self {
+        Self {
+            id: rule.id,
+            name: rule.name,
+            entity: RuleEntity::try_from(rule.entity).unwrap_or(RuleEntity::Order),
+            condition: rule.condition,
+            requirement: rule.requirement,
+            message: rule.message,
+            enabled: rule.enabled,
+            created_by_id: rule.created_by_id,
+            created_at: rule.created_at.unwrap_or_else(Utc::now),
+            updated_at: rule.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+
+/// How many rows a list or record is made of and when the newest of them last changed. Response
+/// ETags are derived from it, so a handler can answer 304 Not Modified without loading the rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataVersion {
+    pub rows: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DataVersion {
+    /// Version of `rows` rows, the newest of which changed at the latest of `updated_at`.
+    pub fn new(rows: i64, updated_at: &[Option<DateTime<Utc>>]) -> Self {
+        Self {
+            rows,
+            updated_at: updated_at.iter().flatten().max().copied(),
+        }
+    }
+
+    /// Version of data shown together, such as an order and its lines.
+    pub fn and(self, other: DataVersion) -> Self {
+        Self {
+            rows: self.rows + other.rows,
+            updated_at: self.updated_at.max(other.updated_at),
+        }
+    }
+}
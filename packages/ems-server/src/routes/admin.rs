@@ -0,0 +1,376 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        AdminTenantResponse, CompactMachineHistoryRequest, CreateFeatureFlagRequest,
+        DecommissionReport, DecommissionStaleMachinesRequest, DiagnosticsResponse,
+        FeatureFlagResponse, FlagEvaluation, IntegrityCheckRequest, IntegrityReport,
+        ListAdminTenantsQuery, MachineHistoryCompactionReport, PlatformHealthResponse,
+        QueryMetricsResponse, ResetTenantAdminRequest, ResetTenantAdminResponse,
+        SetFlagOverrideRequest, SuspendTenantRequest, Tenant, TokenBlacklistPurgeReport,
+        UpdateFeatureFlagRequest,
+    },
+    services::{AdminService, FeatureFlagService},
+    utils::{
+        query_metrics::{slow_query_threshold, QueryMetrics},
+        AppError,
+    },
+    AppState,
+};
+
+/// Platform operator API. Mounted behind the auth and platform admin middleware, so every
+/// handler here acts across tenants.
+pub fn routes() -> Router<AppState> {
+    let router = Router::new()
+        // Tenant administration API routes
+        .route("/tenants", get(list_tenants))
+        .route("/tenants/:id", get(get_tenant))
+        .route("/tenants/:id/suspend", post(suspend_tenant))
+        .route("/tenants/:id/reactivate", post(reactivate_tenant))
+        .route("/tenants/:id/reset-admin", post(reset_tenant_admin))
+        // Platform health API routes
+        .route("/health", get(get_platform_health))
+        .route("/health/queries", get(get_query_metrics))
+        .route("/diagnostics", get(get_diagnostics))
+        .route("/integrity", get(get_integrity).post(run_integrity_check))
+        // Maintenance API routes
+        .route(
+            "/maintenance/token-blacklist/purge",
+            post(purge_token_blacklist),
+        )
+        .route(
+            "/maintenance/stale-machines/decommission",
+            post(decommission_stale_machines),
+        )
+        .route(
+            "/maintenance/machine-history/compact",
+            post(compact_machine_history),
+        )
+        // Feature flag API routes
+        .route("/flags", get(list_flags).post(create_flag))
+        .route(
+            "/flags/:key",
+            get(get_flag).put(update_flag).delete(delete_flag),
+        )
+        .route(
+            "/flags/:key/tenants/:tenant_id",
+            get(evaluate_flag)
+                .put(set_flag_override)
+                .delete(remove_flag_override),
+        );
+
+    // Seed API route, only in builds with the fixtures feature
+    #[cfg(feature = "fixtures")]
+    let router = router.route("/seed", post(seed_tenant));
+
+    router
+}
+
+// Maps tenant administration errors to status codes
+fn admin_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Tenant administration API implementations
+
+async fn list_tenants(
+    State(state): State<AppState>,
+    ValidatedQuery(params): ValidatedQuery<ListAdminTenantsQuery>,
+) -> Result<Json<Vec<AdminTenantResponse>>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.list_tenants(params).await {
+        Ok(tenants) => Ok(Json(tenants)),
+        Err(e) => {
+            tracing::error!("Failed to list tenants: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_tenant(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminTenantResponse>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.get_tenant(id).await {
+        Ok(Some(tenant)) => Ok(Json(tenant)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn suspend_tenant(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SuspendTenantRequest>,
+) -> Result<Json<Tenant>, StatusCode> {
+    let admin_service = AdminService::new(state.database).with_cache(state.tenant_cache);
+
+    match admin_service
+        .suspend_tenant(tenant_context.tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(tenant)) => Ok(Json(tenant)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn reactivate_tenant(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Tenant>, StatusCode> {
+    let admin_service = AdminService::new(state.database).with_cache(state.tenant_cache);
+
+    match admin_service.reactivate_tenant(id).await {
+        Ok(Some(tenant)) => Ok(Json(tenant)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn reset_tenant_admin(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ResetTenantAdminRequest>,
+) -> Result<Json<ResetTenantAdminResponse>, StatusCode> {
+    let admin_service =
+        AdminService::new(state.database).with_access_level_cache(state.access_level_cache);
+
+    match admin_service.reset_tenant_admin(id, payload).await {
+        Ok(Some(reset)) => Ok(Json(reset)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+// Platform health API implementations
+
+async fn get_platform_health(
+    State(state): State<AppState>,
+) -> Result<Json<PlatformHealthResponse>, StatusCode> {
+    let supabase = state.supabase.breaker_status();
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.platform_health(supabase).await {
+        Ok(health) => Ok(Json(health)),
+        Err(e) => {
+            tracing::error!("Failed to read platform health: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Where database time goes, per endpoint, to find what is saturating Postgres
+async fn get_query_metrics() -> Json<QueryMetricsResponse> {
+    Json(QueryMetricsResponse {
+        slow_query_ms: slow_query_threshold().as_millis() as u64,
+        endpoints: QueryMetrics::global().snapshot(),
+    })
+}
+
+// Pool, queue, worker, webhook and cache state, so support can triage without shell access
+async fn get_diagnostics(State(state): State<AppState>) -> Json<DiagnosticsResponse> {
+    let supabase = state.supabase.breaker_status();
+    let admin_service = AdminService::new(state.database);
+
+    Json(admin_service.diagnostics(supabase).await)
+}
+
+// Dangling references across all databases, without changing anything
+async fn get_integrity(State(state): State<AppState>) -> Result<Json<IntegrityReport>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service
+        .check_integrity(IntegrityCheckRequest::default())
+        .await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+// Runs selected checks, repairing what they find when asked, e.g. after a manual database fix
+async fn run_integrity_check(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<IntegrityCheckRequest>,
+) -> Result<Json<IntegrityReport>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.check_integrity(payload).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+// Maintenance API implementations
+
+async fn purge_token_blacklist(
+    State(state): State<AppState>,
+) -> Result<Json<TokenBlacklistPurgeReport>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.purge_token_blacklist().await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn decommission_stale_machines(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<DecommissionStaleMachinesRequest>,
+) -> Result<Json<DecommissionReport>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.decommission_stale_machines(payload).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn compact_machine_history(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CompactMachineHistoryRequest>,
+) -> Result<Json<MachineHistoryCompactionReport>, StatusCode> {
+    let admin_service = AdminService::new(state.database);
+
+    match admin_service.compact_machine_history(payload).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+// Seed API implementations
+
+// Creates a tenant filled with deterministic fixture data, e.g. for demos and load tests
+#[cfg(feature = "fixtures")]
+async fn seed_tenant(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<crate::fixtures::SeedRequest>,
+) -> Result<(StatusCode, Json<crate::fixtures::SeedResponse>), StatusCode> {
+    let mut builder = crate::fixtures::FixtureBuilder::tenant()
+        .with_machines(payload.machines)
+        .with_items(payload.items);
+    if let Some(name) = &payload.name {
+        builder = builder.named(name);
+    }
+
+    match builder.build(&state.database).await {
+        Ok(fixture) => Ok((StatusCode::CREATED, Json(fixture.into()))),
+        Err(e) => {
+            tracing::error!("Failed to seed tenant: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Feature flag API implementations
+
+async fn list_flags(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<FeatureFlagResponse>>, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database);
+
+    match flag_service.list_flags().await {
+        Ok(flags) => Ok(Json(flags)),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn create_flag(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateFeatureFlagRequest>,
+) -> Result<(StatusCode, Json<FeatureFlagResponse>), StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database).with_flags(state.flags);
+
+    match flag_service.create_flag(payload).await {
+        Ok(flag) => Ok((StatusCode::CREATED, Json(flag))),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn get_flag(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<Json<FeatureFlagResponse>, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database);
+
+    match flag_service.get_flag(&key).await {
+        Ok(Some(flag)) => Ok(Json(flag)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn update_flag(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateFeatureFlagRequest>,
+) -> Result<Json<FeatureFlagResponse>, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database).with_flags(state.flags);
+
+    match flag_service.update_flag(&key, payload).await {
+        Ok(Some(flag)) => Ok(Json(flag)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn delete_flag(
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database).with_flags(state.flags);
+
+    match flag_service.delete_flag(&key).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn evaluate_flag(
+    State(state): State<AppState>,
+    Path((key, tenant_id)): Path<(String, Uuid)>,
+) -> Json<FlagEvaluation> {
+    Json(state.flags.evaluate(&key, tenant_id).await)
+}
+
+async fn set_flag_override(
+    State(state): State<AppState>,
+    Path((key, tenant_id)): Path<(String, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<SetFlagOverrideRequest>,
+) -> Result<Json<FeatureFlagResponse>, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database).with_flags(state.flags);
+
+    match flag_service.set_override(&key, tenant_id, payload).await {
+        Ok(Some(flag)) => Ok(Json(flag)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
+
+async fn remove_flag_override(
+    State(state): State<AppState>,
+    Path((key, tenant_id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let flag_service = FeatureFlagService::new(state.database).with_flags(state.flags);
+
+    match flag_service.remove_override(&key, tenant_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(admin_error(e)),
+    }
+}
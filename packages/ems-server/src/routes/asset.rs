@@ -1,21 +1,30 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Json, Response},
+    routing::{get, post, put},
     Extension, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::etag::{with_etag, Revalidate},
     middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
     models::{
-        AssetResponse, AssetSummary, AssetTypeResponse, Claims, CreateAssetIdResponse,
-        CreateAssetRequest, CreateAssetTypeRequest, UpdateAssetRequest, UpdateAssetTypeRequest,
+        AssetBlobResponse, AssetDownloadResponse, AssetResponse, AssetSignatureResponse,
+        AssetSummary, AssetTypeResponse, AssetUploadResponse, AssetVersionHistoryResponse, Claims,
+        CreateAssetIdResponse, CreateAssetRequest, CreateAssetTypeRequest,
+        CreateAssetUploadRequest, CreateAssetVersionRequest, FirmwareUpdateRequest,
+        FirmwareUpdateResponse, SignAssetRequest, UpdateAssetRequest, UpdateAssetTypeRequest,
+    },
+    services::{AssetError, AssetService, AssetUploadService, DirectUploadsUnsupported},
+    utils::{
+        asset_upload::{parse_sha256, UPLOAD_CHUNK_BYTES},
+        AppError,
     },
-    services::AssetService,
     AppState,
 };
 
@@ -44,7 +53,41 @@ pub fn routes() -> Router<AppState> {
             "/:id",
             get(get_asset).put(update_asset).delete(delete_asset),
         )
+        .route("/:id/download", get(download_asset))
+        .route("/:id/firmware-update", post(get_firmware_update))
+        // Version chain routes
+        .route(
+            "/:id/versions",
+            get(list_asset_versions).post(create_asset_version),
+        )
+        .route("/:id/rollback", post(rollback_asset_version))
+        // Electronic signature routes
+        .route(
+            "/:id/signatures",
+            get(list_asset_signatures).post(sign_asset),
+        )
+        // Resumable upload routes
+        .route("/:id/uploads", post(create_asset_upload))
+        .route(
+            "/:id/uploads/:upload_id",
+            get(get_asset_upload).delete(abort_asset_upload),
+        )
+        .route(
+            "/:id/uploads/:upload_id/chunks/:index",
+            put(upload_asset_chunk).layer(DefaultBodyLimit::max(UPLOAD_CHUNK_BYTES)),
+        )
+        .route(
+            "/:id/uploads/:upload_id/complete",
+            post(complete_asset_upload),
+        )
+        // Direct upload routes (the file goes straight to storage)
+        .route("/:id/direct-uploads", post(create_direct_asset_upload))
+        .route(
+            "/:id/uploads/:upload_id/confirm",
+            post(confirm_asset_upload),
+        )
         // Utility routes
+        .route("/by-checksum/:checksum", get(get_asset_blob))
         .route("/by-item/:item_id", get(get_assets_by_item))
         .route("/by-type/:asset_type_id", get(get_assets_by_type))
 }
@@ -59,17 +102,22 @@ fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
     Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
 }
 
+// Maps resumable upload and download errors to status codes
+fn upload_error(e: anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<AssetError>() {
+        _ if e.is::<DirectUploadsUnsupported>() => StatusCode::NOT_IMPLEMENTED,
+        Some(AssetError::Mismatch(_)) => StatusCode::UNPROCESSABLE_ENTITY,
+        Some(AssetError::Expired) => StatusCode::GONE,
+        _ => AppError::from_service(e).status(),
+    }
+}
+
 // Asset Type API implementations
 
 async fn create_asset_type(
     State(state): State<AppState>,
-    Json(payload): Json<CreateAssetTypeRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateAssetTypeRequest>,
 ) -> Result<Json<AssetTypeResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let asset_service = AssetService::new(state.database);
 
     match asset_service.create_asset_type(payload).await {
@@ -123,13 +171,8 @@ async fn list_asset_types(
 async fn update_asset_type(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateAssetTypeRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateAssetTypeRequest>,
 ) -> Result<Json<AssetTypeResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let asset_service = AssetService::new(state.database);
 
     match asset_service.update_asset_type(id, payload).await {
@@ -165,13 +208,8 @@ async fn create_asset(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Extension(claims): Extension<Claims>,
-    Json(payload): Json<CreateAssetRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateAssetRequest>,
 ) -> Result<Json<CreateAssetIdResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let created_by_id = extract_user_id(&claims)?;
     let asset_service = AssetService::new(state.database);
@@ -189,12 +227,22 @@ async fn get_asset(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<AssetResponse>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let asset_service = AssetService::new(state.database);
 
+    let etag = match asset_service.asset_version(tenant_id, id).await {
+        Ok(Some(version)) => revalidate.etag(tenant_id, &version),
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
     match asset_service.get_asset_by_id(tenant_id, id).await {
-        Ok(Some(asset)) => Ok(Json(asset)),
+        Ok(Some(asset)) => Ok(with_etag(&etag, Json(asset))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -204,10 +252,25 @@ async fn list_assets(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListAssetsQuery>,
-) -> Result<Json<Vec<AssetSummary>>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let asset_service = AssetService::new(state.database);
 
+    let version = asset_service
+        .list_version(
+            tenant_id,
+            params.asset_type_id,
+            params.item_id,
+            params.is_active,
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
     match asset_service
         .list_assets(
             tenant_id,
@@ -219,7 +282,7 @@ async fn list_assets(
         )
         .await
     {
-        Ok(assets) => Ok(Json(assets)),
+        Ok(assets) => Ok(with_etag(&etag, Json(assets))),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -228,19 +291,14 @@ async fn update_asset(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateAssetRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateAssetRequest>,
 ) -> Result<Json<AssetResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let asset_service = AssetService::new(state.database);
 
     match asset_service.update_asset(tenant_id, id, payload).await {
         Ok(asset) => Ok(Json(asset)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(AppError::from_service(e).status()),
     }
 }
 
@@ -254,12 +312,302 @@ async fn delete_asset(
 
     match asset_service.delete_asset(tenant_id, id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn download_asset(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AssetDownloadResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service.download_asset(tenant_id, id).await {
+        Ok(Some(download)) => Ok(Json(download)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+// Devices report the image they run and get a delta to the current version, or the whole image
+async fn get_firmware_update(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<FirmwareUpdateRequest>,
+) -> Result<Json<FirmwareUpdateResponse>, StatusCode> {
+    let checksum = parse_sha256(&payload.current_checksum).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service
+        .firmware_update(tenant_id, id, &checksum)
+        .await
+    {
+        Ok(Some(update)) => Ok(Json(update)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+// Asset version chain endpoints
+
+async fn create_asset_version(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateAssetVersionRequest>,
+) -> Result<Json<CreateAssetIdResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let created_by_id = extract_user_id(&claims)?;
+    let asset_service = AssetService::new(state.database);
+
+    match asset_service
+        .create_asset_version(tenant_id, created_by_id, id, payload)
+        .await
+    {
+        Ok(Some(response)) => Ok(Json(response)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn list_asset_versions(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AssetVersionHistoryResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let asset_service = AssetService::new(state.database);
+
+    match asset_service.list_asset_versions(tenant_id, id).await {
+        Ok(Some(history)) => Ok(Json(history)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
+async fn rollback_asset_version(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AssetResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let asset_service = AssetService::new(state.database);
+
+    match asset_service.rollback_asset_version(tenant_id, id).await {
+        Ok(Some(asset)) => Ok(Json(asset)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Electronic signature endpoints
+
+async fn sign_asset(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SignAssetRequest>,
+) -> Result<Json<AssetSignatureResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let signed_by_id = extract_user_id(&claims)?;
+    let asset_service = AssetService::new(state.database);
+
+    match asset_service
+        .sign_asset(tenant_id, id, signed_by_id, payload)
+        .await
+    {
+        Ok(Some(signature)) => Ok(Json(signature)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn list_asset_signatures(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<AssetSignatureResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let asset_service = AssetService::new(state.database);
+
+    match asset_service.list_asset_signatures(tenant_id, id).await {
+        Ok(Some(signatures)) => Ok(Json(signatures)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Resumable upload endpoints
+
+async fn create_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateAssetUploadRequest>,
+) -> Result<(StatusCode, Json<AssetUploadResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let created_by_id = extract_user_id(&claims)?;
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service
+        .create_upload(tenant_id, id, created_by_id, payload)
+        .await
+    {
+        Ok(Some(upload)) => Ok((StatusCode::CREATED, Json(upload))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+async fn get_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, upload_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<AssetUploadResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service.get_upload(tenant_id, id, upload_id).await {
+        Ok(Some(upload)) => Ok(Json(upload)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+async fn upload_asset_chunk(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, upload_id, index)): Path<(Uuid, Uuid, u32)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<AssetUploadResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let checksum = headers
+        .get("X-Chunk-SHA256")
+        .and_then(|value| value.to_str().ok());
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service
+        .upload_chunk(tenant_id, id, upload_id, index, checksum, body.to_vec())
+        .await
+    {
+        Ok(Some(upload)) => Ok(Json(upload)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+async fn complete_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, upload_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, Json<AssetUploadResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Assembly finishes in the background; the upload is polled until it completes
+    match upload_service
+        .complete_upload(tenant_id, id, upload_id)
+        .await
+    {
+        Ok(Some(upload)) => Ok((StatusCode::ACCEPTED, Json(upload))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+// Direct upload endpoints
+
+async fn create_direct_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateAssetUploadRequest>,
+) -> Result<(StatusCode, Json<AssetUploadResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let created_by_id = extract_user_id(&claims)?;
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service
+        .create_direct_upload(tenant_id, id, created_by_id, payload)
+        .await
+    {
+        Ok(Some(upload)) => Ok((StatusCode::CREATED, Json(upload))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+async fn confirm_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, upload_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<AssetUploadResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service
+        .confirm_upload(tenant_id, id, upload_id)
+        .await
+    {
+        Ok(Some(upload)) => Ok(Json(upload)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
+async fn abort_asset_upload(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, upload_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service.abort_upload(tenant_id, id, upload_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
 // Utility endpoints
 
+// Lets devices check whether they already have a file before downloading it
+async fn get_asset_blob(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(checksum): Path<String>,
+) -> Result<Json<AssetBlobResponse>, StatusCode> {
+    let checksum = parse_sha256(&checksum).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let upload_service =
+        AssetUploadService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match upload_service.find_blob(tenant_id, &checksum).await {
+        Ok(Some(blob)) => Ok(Json(blob)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(upload_error(e)),
+    }
+}
+
 async fn get_assets_by_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
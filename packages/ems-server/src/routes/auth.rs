@@ -1,22 +1,25 @@
 use axum::{
     extract::State,
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::post,
-    Router,
+    Form, Router,
 };
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::json;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::validation::ValidatedJson,
     models::{
         AuthResponse, CreateAndJoinTenantRequest, InternalPersonOAuthRegisterRequest,
         JoinTenantRequest, LoginRequest, LogoutRequest, OAuthCallbackRequest, OAuthLoginRequest,
         OAuthUrlResponse, PersonOnlyAuthResponse, PersonOnlyRegisterRequest, RefreshTokenRequest,
-        RefreshTokenResponse, RegisterRequest,
+        RefreshTokenResponse, RegisterRequest, ResendVerificationRequest, TokenError, TokenRequest,
+        VerifyEmailRequest, VerifyEmailResponse,
     },
-    services::AuthService,
-    utils::AuthUtils,
+    services::{AuthError, AuthService, ServiceClientService},
+    utils::{fingerprint::ClientInfo, AppError, AuthUtils},
     AppState,
 };
 
@@ -29,7 +32,12 @@ pub fn routes() -> Router<AppState> {
         .route("/join-tenant", post(join_tenant))
         .route("/create-tenant", post(create_tenant))
         .route("/refresh", post(refresh_token))
+        // Client credentials grant for service clients
+        .route("/token", post(service_token))
         .route("/logout", post(logout))
+        // Email verification (local auth backend)
+        .route("/verify-email", post(verify_email))
+        .route("/resend-verification", post(resend_verification))
         // OAuth routes
         .route("/oauth/url", post(oauth_get_url))
         .route("/oauth/callback", post(oauth_callback))
@@ -38,106 +46,72 @@ pub fn routes() -> Router<AppState> {
 
 async fn login(
     State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Authenticate person
     match auth_service.login(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Login failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Person not found") => Err(StatusCode::NOT_FOUND),
-                s if s.contains("Authentication failed") => Err(StatusCode::UNAUTHORIZED),
-                s if s.contains("Tenant not found") => Err(StatusCode::NOT_FOUND),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn register(
     State(state): State<AppState>,
-    Json(payload): Json<RegisterRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Register person
     match auth_service.register(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Registration failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("subdomain already exists") => Err(StatusCode::CONFLICT),
-                s if s.contains("Email already registered") => Err(StatusCode::CONFLICT),
-                s if s.contains("Registration failed") => Err(StatusCode::BAD_REQUEST),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn person_only_register(
     State(state): State<AppState>,
-    Json(payload): Json<PersonOnlyRegisterRequest>,
+    ValidatedJson(payload): ValidatedJson<PersonOnlyRegisterRequest>,
 ) -> Result<Json<PersonOnlyAuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
 
     // Register person without tenant
     match auth_service.person_only_register(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Person-only registration failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Email already registered") => Err(StatusCode::CONFLICT),
-                s if s.contains("Registration failed") => Err(StatusCode::BAD_REQUEST),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn person_only_login(
     State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> Result<Json<PersonOnlyAuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
 
     // Login person without tenant requirement
     match auth_service.person_only_login(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Person-only login failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Authentication failed") => Err(StatusCode::UNAUTHORIZED),
-                s if s.contains("User not found") => Err(StatusCode::NOT_FOUND),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
@@ -145,13 +119,9 @@ async fn person_only_login(
 async fn join_tenant(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<JoinTenantRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<JoinTenantRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Extract person ID from JWT token
     let person_id = match extract_person_id_from_headers(&headers) {
         Ok(id) => id,
@@ -159,19 +129,15 @@ async fn join_tenant(
     };
 
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Join existing tenant
     match auth_service.join_existing_tenant(person_id, payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Join tenant failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Tenant not found") => Err(StatusCode::NOT_FOUND),
-                s if s.contains("not active") => Err(StatusCode::FORBIDDEN),
-                s if s.contains("already associated") => Err(StatusCode::CONFLICT),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
@@ -179,13 +145,9 @@ async fn join_tenant(
 async fn create_tenant(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<CreateAndJoinTenantRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<CreateAndJoinTenantRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Extract person ID from JWT token
     let person_id = match extract_person_id_from_headers(&headers) {
         Ok(id) => id,
@@ -193,7 +155,8 @@ async fn create_tenant(
     };
 
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Create new tenant and associate person
     match auth_service
@@ -203,53 +166,80 @@ async fn create_tenant(
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("Create tenant failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("subdomain already exists") => Err(StatusCode::CONFLICT),
-                s if s.contains("Failed to create tenant") => Err(StatusCode::BAD_REQUEST),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn refresh_token(
     State(state): State<AppState>,
-    Json(payload): Json<RefreshTokenRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<RefreshTokenRequest>,
 ) -> Result<Json<RefreshTokenResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Refresh token
     match auth_service.refresh_token(payload).await {
         Ok(refresh_response) => Ok(Json(refresh_response)),
         Err(e) => {
             tracing::error!("Token refresh failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Invalid refresh token") => Err(StatusCode::UNAUTHORIZED),
-                s if s.contains("not found or inactive") => Err(StatusCode::UNAUTHORIZED),
-                s if s.contains("Failed to validate token") => Err(StatusCode::UNAUTHORIZED),
-                s if s.contains("Token has been revoked") => Err(StatusCode::UNAUTHORIZED),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
-async fn logout(
+// Token endpoint of the client credentials grant. Responses follow RFC 6749: form encoded
+// requests, client authentication in the body or with HTTP Basic, and `{"error": ...}` bodies.
+async fn service_token(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<LogoutRequest>,
-) -> Result<StatusCode, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
+    Form(mut payload): Form<TokenRequest>,
+) -> Response {
+    if let Some((client_id, client_secret)) = basic_credentials(&headers) {
+        payload.client_id = Some(client_id);
+        payload.client_secret = Some(client_secret);
     }
 
+    let service_client_service = ServiceClientService::new(state.database);
+    let (status, body) = match service_client_service.issue_token(payload).await {
+        Ok(token) => (StatusCode::OK, json!(token)),
+        Err(e) => match e.downcast_ref::<TokenError>() {
+            Some(error) => {
+                let status = match error {
+                    TokenError::InvalidClient => StatusCode::UNAUTHORIZED,
+                    _ => StatusCode::BAD_REQUEST,
+                };
+                (status, json!({ "error": error.to_string() }))
+            }
+            None => {
+                tracing::error!("Service token request failed: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        },
+    };
+
+    (status, [(header::CACHE_CONTROL, "no-store")], Json(body)).into_response()
+}
+
+// Client id and secret sent with HTTP Basic authentication
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    Some((client_id.to_string(), client_secret.to_string()))
+}
+
+async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ValidatedJson(payload): ValidatedJson<LogoutRequest>,
+) -> Result<StatusCode, StatusCode> {
     // Extract JWT token from Authorization header
     let auth_header = headers
         .get("authorization")
@@ -284,7 +274,11 @@ async fn logout(
         }
     }
 
-    let auth_service = AuthService::new(state.database.clone(), state.supabase.clone());
+    let auth_service = AuthService::new(
+        state.database.clone(),
+        state.supabase.clone(),
+        state.auth_backend.clone(),
+    );
 
     // Check if access token is already blacklisted
     match auth_service
@@ -311,101 +305,98 @@ async fn logout(
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => {
             tracing::error!("Logout failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Invalid refresh token") => Err(StatusCode::BAD_REQUEST),
-                s if s.contains("Token does not belong") => Err(StatusCode::FORBIDDEN),
-                s if s.contains("Token has been revoked") => Err(StatusCode::UNAUTHORIZED),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
+            match e.downcast_ref::<AuthError>() {
+                // The token sent to be revoked is not a refresh token
+                Some(AuthError::InvalidRefreshToken) => Err(StatusCode::BAD_REQUEST),
+                _ => Err(AppError::from_service(e).status()),
             }
         }
     }
 }
 
+async fn verify_email(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<VerifyEmailRequest>,
+) -> Result<Json<VerifyEmailResponse>, StatusCode> {
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
+
+    match auth_service.verify_email(payload).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            tracing::error!("Email verification failed: {}", e);
+            Err(AppError::from_service(e).status())
+        }
+    }
+}
+
+async fn resend_verification(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ResendVerificationRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
+
+    match auth_service.resend_verification(payload).await {
+        Ok(_) => Ok(StatusCode::ACCEPTED),
+        Err(e) => {
+            tracing::error!("Resending verification failed: {}", e);
+            Err(AppError::from_service(e).status())
+        }
+    }
+}
+
 // OAuth endpoint implementations
 
 async fn oauth_get_url(
     State(state): State<AppState>,
-    Json(payload): Json<OAuthLoginRequest>,
+    ValidatedJson(payload): ValidatedJson<OAuthLoginRequest>,
 ) -> Result<Json<OAuthUrlResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
 
     // Get OAuth URL
     match auth_service.get_oauth_url(payload).await {
         Ok(oauth_url_response) => Ok(Json(oauth_url_response)),
         Err(e) => {
             tracing::error!("OAuth URL generation failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Tenant not found") => Err(StatusCode::NOT_FOUND),
-                s if s.contains("not active") => Err(StatusCode::FORBIDDEN),
-                s if s.contains("not configured") => Err(StatusCode::SERVICE_UNAVAILABLE),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn oauth_callback(
     State(state): State<AppState>,
-    Json(payload): Json<OAuthCallbackRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<OAuthCallbackRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Handle OAuth callback
     match auth_service.oauth_callback(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("OAuth callback failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("Tenant not found") => Err(StatusCode::NOT_FOUND),
-                s if s.contains("not active") => Err(StatusCode::FORBIDDEN),
-                s if s.contains("Invalid state") => Err(StatusCode::BAD_REQUEST),
-                s if s.contains("Person not found") => Err(StatusCode::NOT_FOUND),
-                s if s.contains("not an internal person") => Err(StatusCode::FORBIDDEN),
-                s if s.contains("Failed to exchange") => Err(StatusCode::BAD_REQUEST),
-                s if s.contains("not configured") => Err(StatusCode::SERVICE_UNAVAILABLE),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
 
 async fn oauth_register_internal(
     State(state): State<AppState>,
-    Json(payload): Json<InternalPersonOAuthRegisterRequest>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<InternalPersonOAuthRegisterRequest>,
 ) -> Result<Json<AuthResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     // Create auth service
-    let auth_service = AuthService::new(state.database, state.supabase);
+    let auth_service =
+        AuthService::new(state.database, state.supabase, state.auth_backend).with_client(client);
 
     // Register internal person via OAuth
     match auth_service.oauth_register_internal_person(payload).await {
         Ok(auth_response) => Ok(Json(auth_response)),
         Err(e) => {
             tracing::error!("OAuth internal person registration failed: {}", e);
-            match e.to_string().as_str() {
-                s if s.contains("subdomain already exists") => Err(StatusCode::CONFLICT),
-                s if s.contains("Email already registered") => Err(StatusCode::CONFLICT),
-                s if s.contains("Invalid state") => Err(StatusCode::BAD_REQUEST),
-                s if s.contains("Failed to exchange") => Err(StatusCode::BAD_REQUEST),
-                s if s.contains("not configured") => Err(StatusCode::SERVICE_UNAVAILABLE),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
+            Err(AppError::from_service(e).status())
         }
     }
 }
@@ -0,0 +1,102 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{BillingPortalRequest, BillingPortalResponse, BillingResponse, CallerContext},
+    services::BillingService,
+    utils::AppError,
+    AppState,
+};
+
+/// Tenant admin billing API, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Billing API routes
+        .route("/", get(get_billing))
+        .route("/portal", post(open_billing_portal))
+}
+
+/// Payment provider webhooks. Stripe signs its deliveries rather than authenticating, so these
+/// are mounted without the auth middleware and outside any tenant.
+pub fn webhook_routes() -> Router<AppState> {
+    Router::new().route("/stripe", post(stripe_webhook))
+}
+
+// Helper function to extract tenant ID from tenant context
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps billing errors to status codes
+fn billing_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Billing failed: {}", error);
+    }
+    error.status()
+}
+
+// Billing API implementations
+
+async fn get_billing(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<BillingResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let billing_service =
+        BillingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match billing_service.get_billing(tenant_id, &caller).await {
+        Ok(billing) => Ok(Json(billing)),
+        Err(e) => Err(billing_error(e)),
+    }
+}
+
+async fn open_billing_portal(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<BillingPortalRequest>,
+) -> Result<Json<BillingPortalResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let billing_service =
+        BillingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match billing_service
+        .open_portal(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(portal) => Ok(Json(portal)),
+        Err(e) => Err(billing_error(e)),
+    }
+}
+
+// Webhook API implementations
+
+async fn stripe_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, StatusCode> {
+    let signature = headers
+        .get("Stripe-Signature")
+        .and_then(|value| value.to_str().ok());
+    let billing_service = BillingService::new(state.database)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .with_cache(state.tenant_cache);
+
+    match billing_service.handle_webhook(&body, signature).await {
+        Ok(()) => Ok(StatusCode::OK),
+        Err(e) => Err(billing_error(e)),
+    }
+}
@@ -0,0 +1,462 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get},
+    Extension, Router,
+};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        AssignOperatorShiftRequest, AttendanceResponse, CalendarExceptionResponse, Claims,
+        CreateAttendanceRequest, CreateCalendarExceptionRequest, CreateShiftPatternRequest,
+        ListAttendanceQuery, ListOperatorShiftsQuery, MachineAvailabilityResponse,
+        MachineCalendarResponse, OperatorAvailabilityResponse, OperatorShiftResponse,
+        SetMachineCalendarRequest, ShiftPatternResponse, UpdateShiftPatternRequest,
+    },
+    services::{AttendanceService, CalendarService},
+    utils::{
+        capacity::{DEFAULT_WINDOW_DAYS, MAX_WINDOW_DAYS},
+        AppError,
+    },
+    AppState,
+};
+
+#[derive(Deserialize)]
+struct ListExceptionsQuery {
+    machine_id: Option<Uuid>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct AvailabilityQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Shift pattern routes
+        .route(
+            "/shift-patterns",
+            get(list_shift_patterns).post(create_shift_pattern),
+        )
+        .route(
+            "/shift-patterns/:id",
+            get(get_shift_pattern)
+                .put(update_shift_pattern)
+                .delete(delete_shift_pattern),
+        )
+        // Machine calendar routes
+        .route(
+            "/machines/:machine_id",
+            get(get_machine_calendar)
+                .put(set_machine_calendar)
+                .delete(delete_machine_calendar),
+        )
+        .route(
+            "/machines/:machine_id/availability",
+            get(get_machine_availability),
+        )
+        // Calendar exception routes (planned downtime, holidays, extra shifts)
+        .route("/exceptions", get(list_exceptions).post(create_exception))
+        .route("/exceptions/:id", delete(delete_exception))
+        // Operator shift and attendance routes
+        .route(
+            "/operators/shifts",
+            get(list_operator_shifts).post(assign_operator_shift),
+        )
+        .route("/operators/shifts/:id", delete(delete_operator_shift))
+        .route(
+            "/operators/attendance",
+            get(list_attendance).post(record_attendance),
+        )
+        .route("/operators/attendance/:id", delete(delete_attendance))
+        .route(
+            "/operators/:person_id/availability",
+            get(get_operator_availability),
+        )
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Shift pattern API implementations
+
+async fn list_shift_patterns(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<ShiftPatternResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service.list_shift_patterns(tenant_id).await {
+        Ok(patterns) => Ok(Json(patterns)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_shift_pattern(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateShiftPatternRequest>,
+) -> Result<Json<ShiftPatternResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .create_shift_pattern(tenant_id, payload)
+        .await
+    {
+        Ok(pattern) => Ok(Json(pattern)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn get_shift_pattern(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShiftPatternResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service.get_shift_pattern(tenant_id, id).await {
+        Ok(Some(pattern)) => Ok(Json(pattern)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_shift_pattern(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateShiftPatternRequest>,
+) -> Result<Json<ShiftPatternResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .update_shift_pattern(tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(pattern)) => Ok(Json(pattern)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_shift_pattern(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service.delete_shift_pattern(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Machine calendar API implementations
+
+async fn get_machine_calendar(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(machine_id): Path<Uuid>,
+) -> Result<Json<MachineCalendarResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .get_machine_calendar(tenant_id, machine_id)
+        .await
+    {
+        Ok(Some(calendar)) => Ok(Json(calendar)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn set_machine_calendar(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(machine_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SetMachineCalendarRequest>,
+) -> Result<Json<MachineCalendarResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .set_machine_calendar(tenant_id, machine_id, payload)
+        .await
+    {
+        Ok(Some(calendar)) => Ok(Json(calendar)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_machine_calendar(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(machine_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .delete_machine_calendar(tenant_id, machine_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Working windows of a machine, for schedulers placing job assignments
+async fn get_machine_availability(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(machine_id): Path<Uuid>,
+    Query(params): Query<AvailabilityQuery>,
+) -> Result<Json<MachineAvailabilityResponse>, StatusCode> {
+    let from = params.from.unwrap_or_else(|| Utc::now().date_naive());
+    let to = params
+        .to
+        .unwrap_or(from + Duration::days(DEFAULT_WINDOW_DAYS - 1));
+    if to < from || (to - from).num_days() >= MAX_WINDOW_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .machine_availability(tenant_id, machine_id, from, to)
+        .await
+    {
+        Ok(Some(availability)) => Ok(Json(availability)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Calendar exception API implementations
+
+async fn list_exceptions(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ListExceptionsQuery>,
+) -> Result<Json<Vec<CalendarExceptionResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service
+        .list_exceptions(tenant_id, params.machine_id, params.from, params.to)
+        .await
+    {
+        Ok(exceptions) => Ok(Json(exceptions)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_exception(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateCalendarExceptionRequest>,
+) -> Result<Json<CalendarExceptionResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service.create_exception(tenant_id, payload).await {
+        Ok(exception) => Ok(Json(exception)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_exception(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calendar_service = CalendarService::new(state.database);
+
+    match calendar_service.delete_exception(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Operator shift and attendance API implementations
+
+async fn list_operator_shifts(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ListOperatorShiftsQuery>,
+) -> Result<Json<Vec<OperatorShiftResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service.list_shifts(tenant_id, params).await {
+        Ok(shifts) => Ok(Json(shifts)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn assign_operator_shift(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<AssignOperatorShiftRequest>,
+) -> Result<Json<OperatorShiftResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service.assign_shift(tenant_id, payload).await {
+        Ok(Some(shift)) => Ok(Json(shift)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_operator_shift(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service.delete_shift(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_attendance(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ListAttendanceQuery>,
+) -> Result<Json<Vec<AttendanceResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service.list_attendance(tenant_id, params).await {
+        Ok(records) => Ok(Json(records)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn record_attendance(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateAttendanceRequest>,
+) -> Result<Json<AttendanceResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let recorded_by_id = extract_user_id(&claims)?;
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service
+        .record_attendance(tenant_id, Some(recorded_by_id), payload)
+        .await
+    {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn delete_attendance(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service.delete_attendance(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Working windows of an operator after shifts and absences
+async fn get_operator_availability(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(person_id): Path<Uuid>,
+    Query(params): Query<AvailabilityQuery>,
+) -> Result<Json<OperatorAvailabilityResponse>, StatusCode> {
+    let from = params.from.unwrap_or_else(|| Utc::now().date_naive());
+    let to = params
+        .to
+        .unwrap_or(from + Duration::days(DEFAULT_WINDOW_DAYS - 1));
+    if to < from || (to - from).num_days() >= MAX_WINDOW_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let attendance_service = AttendanceService::new(state.database);
+
+    match attendance_service
+        .operator_availability(tenant_id, person_id, from, to)
+        .await
+    {
+        Ok(Some(availability)) => Ok(Json(availability)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
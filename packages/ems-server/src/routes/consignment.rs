@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post, put},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        ConsignmentBillingResponse, ConsignmentInvoiceResponse, ConsignmentStockResponse,
+        InvoiceConsignmentBillingsRequest, ListConsignmentBillingsQuery, ListConsignmentStockQuery,
+        UpdateConsignmentStockRequest,
+    },
+    services::ConsignmentService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Consignment stock routes
+        .route("/stock", get(list_stock))
+        .route("/stock/:id", put(update_stock))
+        // Self-billing routes
+        .route("/billings", get(list_billings))
+        .route("/billings/invoice", post(invoice_billings))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Consignment API implementations
+
+async fn list_stock(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListConsignmentStockQuery>,
+) -> Result<Json<Vec<ConsignmentStockResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let consignment_service = ConsignmentService::new(state.database);
+
+    match consignment_service.list_stock(tenant_id, params).await {
+        Ok(stock) => Ok(Json(stock)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_stock(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateConsignmentStockRequest>,
+) -> Result<Json<ConsignmentStockResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let consignment_service = ConsignmentService::new(state.database);
+
+    match consignment_service
+        .update_stock(tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(stock)) => Ok(Json(stock)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_billings(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListConsignmentBillingsQuery>,
+) -> Result<Json<Vec<ConsignmentBillingResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let consignment_service = ConsignmentService::new(state.database);
+
+    match consignment_service.list_billings(tenant_id, params).await {
+        Ok(billings) => Ok(Json(billings)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn invoice_billings(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<InvoiceConsignmentBillingsRequest>,
+) -> Result<Json<ConsignmentInvoiceResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let consignment_service = ConsignmentService::new(state.database);
+
+    match consignment_service
+        .invoice_billings(tenant_id, payload)
+        .await
+    {
+        Ok(invoice) => Ok(Json(invoice)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
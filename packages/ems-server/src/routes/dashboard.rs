@@ -0,0 +1,206 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        Claims, CreateDashboardRequest, DashboardDataResponse, DashboardResponse,
+        UpdateDashboardRequest,
+    },
+    services::{DashboardService, ReportService},
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_dashboards).post(create_dashboard))
+        .route("/default", get(get_default_dashboard))
+        .route(
+            "/:id",
+            get(get_dashboard)
+                .put(update_dashboard)
+                .delete(delete_dashboard),
+        )
+        // Data for every widget, fetched in one call
+        .route("/:id/data", get(get_dashboard_data))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Dashboard API implementations
+
+async fn list_dashboards(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<DashboardResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    match dashboard_service.list_dashboards(tenant_id, user_id).await {
+        Ok(dashboards) => Ok(Json(dashboards)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateDashboardRequest>,
+) -> Result<(StatusCode, Json<DashboardResponse>), StatusCode> {
+    if payload.check(ReportService::definition).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    match dashboard_service
+        .create_dashboard(tenant_id, user_id, payload)
+        .await
+    {
+        Ok(dashboard) => Ok((StatusCode::CREATED, Json(dashboard))),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_default_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<DashboardResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    match dashboard_service
+        .get_default_dashboard(tenant_id, user_id)
+        .await
+    {
+        Ok(Some(dashboard)) => Ok(Json(dashboard.into())),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DashboardResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    match dashboard_service
+        .get_dashboard(tenant_id, user_id, id)
+        .await
+    {
+        Ok(Some(dashboard)) => Ok(Json(dashboard.into())),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateDashboardRequest>,
+) -> Result<Json<DashboardResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    let dashboard = match dashboard_service
+        .get_dashboard(tenant_id, user_id, id)
+        .await
+    {
+        Ok(Some(dashboard)) => dashboard,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    if payload
+        .check(&dashboard, ReportService::definition)
+        .is_err()
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match dashboard_service
+        .update_dashboard(tenant_id, dashboard, payload)
+        .await
+    {
+        Ok(dashboard) => Ok(Json(dashboard)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn delete_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    match dashboard_service
+        .delete_dashboard(tenant_id, user_id, id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_dashboard_data(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DashboardDataResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dashboard_service = DashboardService::new(state.database);
+
+    let dashboard = match dashboard_service
+        .get_dashboard(tenant_id, user_id, id)
+        .await
+    {
+        Ok(Some(dashboard)) => dashboard,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    match dashboard_service
+        .load_widget_data(tenant_id, &dashboard)
+        .await
+    {
+        Ok(data) => Ok(Json(data)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
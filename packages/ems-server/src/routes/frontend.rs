@@ -0,0 +1,151 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// Paths never answered with the frontend when SPA_FALLBACK_EXCLUDE is not set
+pub const DEFAULT_FALLBACK_EXCLUDE: &[&str] = &["/api", "/health"];
+
+/// Directory the frontend build writes content-hashed files to
+pub const HASHED_ASSETS_PREFIX: &str = "/assets/";
+
+/// index.html changes with every deploy, so browsers revalidate it each time
+pub const INDEX_CACHE_CONTROL: &str = "no-cache";
+
+/// Hashed files get a new name whenever they change, so they can be kept for good
+pub const HASHED_ASSET_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Other files (favicon, robots.txt, ...) keep their names across deploys
+pub const STATIC_FILE_CACHE_CONTROL: &str = "public, max-age=3600";
+
+#[derive(Clone, Debug)]
+pub struct FrontendConfig {
+    pub static_dir: PathBuf,
+    /// Path prefixes left to the API; unknown paths under them are 404, not the frontend
+    pub fallback_exclude: Vec<String>,
+}
+
+impl FrontendConfig {
+    pub fn new(static_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            static_dir: static_dir.into(),
+            fallback_exclude: DEFAULT_FALLBACK_EXCLUDE
+                .iter()
+                .map(|prefix| prefix.to_string())
+                .collect(),
+        }
+    }
+
+    /// Reads the comma-separated SPA_FALLBACK_EXCLUDE prefixes.
+    pub fn from_env(static_dir: impl Into<PathBuf>) -> Self {
+        let mut config = Self::new(static_dir);
+        if let Ok(exclude) = env::var("SPA_FALLBACK_EXCLUDE") {
+            config.fallback_exclude = exclude
+                .split(',')
+                .map(|prefix| prefix.trim().trim_end_matches('/').to_string())
+                .filter(|prefix| !prefix.is_empty())
+                .collect();
+        }
+        config
+    }
+
+    /// Whether `path` is one of the excluded prefixes or lies under one.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        self.fallback_exclude.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            path == prefix
+                || path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// Whether a path no file was found for is a client-side route, to be answered with
+/// index.html. Paths with an extension are missing files, unless the browser is navigating
+/// to them (ids may contain dots); hashed assets are never routes.
+pub fn is_client_route(path: &str, accepts_html: bool) -> bool {
+    if path.starts_with(HASHED_ASSETS_PREFIX) {
+        return false;
+    }
+    let last_segment = path.rsplit('/').next().unwrap_or_default();
+    !last_segment.contains('.') || accepts_html
+}
+
+/// Cache-Control for a file served from the static directory at `path`.
+pub fn cache_control_for(path: &str, is_index: bool) -> &'static str {
+    if is_index {
+        INDEX_CACHE_CONTROL
+    } else if path.starts_with(HASHED_ASSETS_PREFIX) {
+        HASHED_ASSET_CACHE_CONTROL
+    } else {
+        STATIC_FILE_CACHE_CONTROL
+    }
+}
+
+/// Serves the built frontend, answering client-side routes such as `/orders/123` with
+/// index.html so deep links survive a refresh.
+pub fn routes(config: FrontendConfig) -> Router {
+    Router::new()
+        .fallback(serve_frontend)
+        .with_state(Arc::new(config))
+}
+
+async fn serve_frontend(State(config): State<Arc<FrontendConfig>>, req: Request) -> Response {
+    let path = req.uri().path().to_string();
+    if (req.method() != Method::GET && req.method() != Method::HEAD) || config.is_excluded(&path) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let accepts_html = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    let mut index_request = Request::new(Body::empty());
+    *index_request.method_mut() = req.method().clone();
+    *index_request.headers_mut() = req.headers().clone();
+
+    let response = match ServeDir::new(&config.static_dir)
+        .append_index_html_on_directories(true)
+        .oneshot(req)
+        .await
+    {
+        Ok(response) => response.map(Body::new),
+        Err(never) => match never {},
+    };
+
+    let (response, is_index) =
+        if response.status() == StatusCode::NOT_FOUND && is_client_route(&path, accepts_html) {
+            let index = Path::new(&config.static_dir).join("index.html");
+            match ServeFile::new(index).oneshot(index_request).await {
+                Ok(response) => (response.map(Body::new), true),
+                Err(never) => match never {},
+            }
+        } else {
+            (response, path.ends_with('/') || path.ends_with(".html"))
+        };
+
+    with_cache_control(response, cache_control_for(&path, is_index))
+}
+
+fn with_cache_control(mut response: Response, cache_control: &'static str) -> Response {
+    let status = response.status();
+    if status.is_success() || status == StatusCode::NOT_MODIFIED {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(cache_control),
+        );
+    }
+    response
+}
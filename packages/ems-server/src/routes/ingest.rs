@@ -0,0 +1,203 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CallerContext, CreateIngestSourceRequest, IngestEventResponse, IngestResponse,
+        IngestSourceResponse, IngestSourceTokenResponse, ListIngestEventsQuery,
+        UpdateIngestSourceRequest,
+    },
+    services::IngestService,
+    utils::errors::AppError,
+    AppState,
+};
+
+/// Ingest source management API, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Ingest source API routes
+        .route("/", get(list_sources).post(create_source))
+        .route(
+            "/:id",
+            get(get_source).put(update_source).delete(delete_source),
+        )
+        .route("/:id/rotate-token", post(rotate_token))
+        .route("/:id/events", get(list_events))
+}
+
+/// Generic ingest endpoint. External systems authenticate with the source token in the path,
+/// which also decides the tenant, so this is mounted without the auth middleware.
+pub fn ingest_routes() -> Router<AppState> {
+    Router::new().route("/:source_token", post(ingest))
+}
+
+// Helper function to extract tenant ID from tenant context
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps ingest source errors to status codes
+fn ingest_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Ingest source request failed: {}", error);
+    }
+    error.status()
+}
+
+// Ingest source API implementations
+
+async fn list_sources(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<Vec<IngestSourceResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service.list_sources(tenant_id, &caller).await {
+        Ok(sources) => Ok(Json(sources)),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn create_source(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateIngestSourceRequest>,
+) -> Result<(StatusCode, Json<IngestSourceTokenResponse>), StatusCode> {
+    // Validate the field schema and template
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service
+        .create_source(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(source) => Ok((StatusCode::CREATED, Json(source))),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn get_source(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IngestSourceResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service.get_source(tenant_id, &caller, id).await {
+        Ok(Some(source)) => Ok(Json(source)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn update_source(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateIngestSourceRequest>,
+) -> Result<Json<IngestSourceResponse>, StatusCode> {
+    // Validate the field schema and template
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service
+        .update_source(tenant_id, &caller, id, payload)
+        .await
+    {
+        Ok(Some(source)) => Ok(Json(source)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn delete_source(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service.delete_source(tenant_id, &caller, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn rotate_token(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IngestSourceTokenResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service.rotate_token(tenant_id, &caller, id).await {
+        Ok(Some(source)) => Ok(Json(source)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+async fn list_events(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ListIngestEventsQuery>,
+) -> Result<Json<Vec<IngestEventResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let ingest_service = IngestService::new(state.database);
+
+    match ingest_service
+        .list_events(tenant_id, &caller, id, params)
+        .await
+    {
+        Ok(Some(events)) => Ok(Json(events)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ingest_error(e)),
+    }
+}
+
+// Ingest API implementations
+
+// Rejections carry their reason, so the pushing system can tell what to fix
+async fn ingest(
+    State(state): State<AppState>,
+    Path(source_token): Path<String>,
+    Json(payload): Json<Value>,
+) -> Result<Json<IngestResponse>, AppError> {
+    let ingest_service = IngestService::new(state.database).with_cache(state.tenant_cache);
+
+    match ingest_service.receive(&source_token, payload).await {
+        Ok(outcome) => Ok(Json(outcome)),
+        Err(e) => Err(AppError::from_service(e)),
+    }
+}
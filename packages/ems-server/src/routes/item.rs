@@ -1,22 +1,50 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, LAST_MODIFIED},
+        HeaderMap, HeaderName, StatusCode,
+    },
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
     Extension, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::etag::{with_etag, Revalidate},
+    middleware::limits::MAX_IMPORT_BYTES,
     middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
     models::{
-        BomItemResponse, CreateBomItemRequest, CreateItemIdResponse, CreateItemRequest,
-        FinishedGoodsItemResponse, ItemContext, ItemLifecycle, ItemResponse, ItemStatus,
-        StoreItemResponse, UpdateBomItemRequest, UpdateItemRequest, VendorItemResponse,
+        AvailableToPromiseQuery, AvailableToPromiseResponse, BomExportQuery, BomItemResponse,
+        Claims, ConversionResponse, ConvertQuantityQuery, CreateBomItemRequest,
+        CreateItemIdResponse, CreateItemRequest, CreateStockReservationRequest,
+        CreateUnitOfMeasureRequest, DemandForecastQuery, DemandForecastResponse, DuplicateMatch,
+        DuplicatesQuery, FinishedGoodsItemResponse, GetDatasheetQuery, ImportBomRequest,
+        ImportBomResponse, ItemAnalyticsQuery, ItemAnalyticsResponse, ItemContext,
+        ItemDatasheetResponse, ItemImageResponse, ItemLifecycle, ItemPriceHistoryResponse,
+        ItemResponse, ItemStatus, ItemUnitsResponse, LifecycleAlertResponse,
+        LifecycleCheckResponse, ListDatasheetsQuery, ListLifecycleAlertsQuery,
+        ListStockReservationsQuery, MergeRequest, MergeResponse, PriceHistoryQuery,
+        PriceListImportQuery, PriceListImportResponse, PrintJobResponse, PrintLabelQuery,
+        RecordStockMovementRequest, RefreshDatasheetsRequest, RefreshDatasheetsResponse,
+        SetItemUnitsRequest, StockAvailabilityResponse, StockMovementResponse,
+        StockReservationResponse, StoreItemResponse, UnitOfMeasureResponse, UpdateBomItemRequest,
+        UpdateItemRequest, UpdateUnitOfMeasureRequest, UploadItemImageQuery, VendorItemResponse,
+    },
+    services::{
+        BomImportService, DuplicateService, ItemDatasheetService, ItemError, ItemImageError,
+        ItemImageService, ItemService, LifecycleService, PricingService, PrintService,
+        StockService, UomService,
+    },
+    utils::{
+        errors::AppError,
+        item_image::MAX_IMAGE_BYTES,
+        parametric::parse_parametric_search,
+        streaming::{stream_json_array, STREAM_PAGE_SIZE},
     },
-    services::ItemService,
     AppState,
 };
 
@@ -35,6 +63,7 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         // General Item API
         .route("/", get(list_all_items).post(create_item))
+        .route("/parametric", get(parametric_search))
         .route(
             "/:id",
             get(get_item_details).put(update_item).delete(delete_item),
@@ -51,6 +80,10 @@ pub fn routes() -> Router<AppState> {
             get(get_store_item_details).put(update_store_item),
         )
         .route("/vendor", get(list_vendor_items))
+        .route(
+            "/vendor/price-list",
+            post(import_vendor_price_list).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
         .route(
             "/vendor/:id",
             get(get_vendor_item_details).put(update_vendor_item),
@@ -64,6 +97,66 @@ pub fn routes() -> Router<AppState> {
                 .delete(delete_bom_item),
         )
         .route("/:id/bom", get(get_item_bom))
+        .route(
+            "/:id/bom/import",
+            post(import_item_bom).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
+        .route("/:id/bom/export", get(export_item_bom))
+        // Unit of measure API routes
+        .route("/uoms", get(list_units).post(create_unit))
+        .route(
+            "/uoms/:id",
+            get(get_unit).put(update_unit).delete(delete_unit),
+        )
+        .route("/:id/units", get(get_item_units).put(set_item_units))
+        .route("/:id/units/convert", get(convert_item_quantity))
+        // Stock movement and forecasting API routes
+        .route(
+            "/:id/movements",
+            get(list_item_movements).post(record_item_movement),
+        )
+        .route("/:id/forecast", get(get_item_forecast))
+        .route("/:id/analytics", get(get_item_analytics))
+        // Stock reservation API routes
+        .route(
+            "/:id/reservations",
+            get(get_item_availability).post(reserve_item_stock),
+        )
+        .route("/reservations", get(list_reservations))
+        .route("/reservations/:id/release", post(release_reservation))
+        .route("/:id/atp", get(get_item_atp))
+        // Item image API routes
+        .route(
+            "/:id/images",
+            get(list_item_images)
+                .post(upload_item_image)
+                .layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES)),
+        )
+        .route("/:id/images/:image_id", delete(delete_item_image))
+        .route(
+            "/:id/images/:image_id/primary",
+            post(set_primary_item_image),
+        )
+        // Item datasheet API routes
+        .route("/:id/datasheet", get(get_item_datasheet))
+        .route("/datasheets", get(list_item_datasheets))
+        .route("/datasheets/refresh", post(refresh_item_datasheets))
+        // Label printing API routes
+        .route("/:id/print-label", post(print_item_label))
+        // Vendor pricing API routes
+        .route("/:id/price-history", get(get_item_price_history))
+        // Lifecycle watch API routes
+        .route("/lifecycle-alerts", get(list_lifecycle_alerts))
+        .route("/lifecycle-alerts/:id", get(get_lifecycle_alert))
+        .route("/lifecycle-alerts/:id/accept", post(accept_lifecycle_alert))
+        .route(
+            "/lifecycle-alerts/:id/dismiss",
+            post(dismiss_lifecycle_alert),
+        )
+        .route("/:id/lifecycle-check", post(check_item_lifecycle))
+        // Duplicate detection API routes
+        .route("/duplicates", get(list_duplicate_items))
+        .route("/merge", post(merge_items))
 }
 
 // Helper function to extract tenant ID from request extensions
@@ -71,49 +164,146 @@ fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
     tenant_context.tenant_id
 }
 
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Maps unit of measure errors, which stock, BOM and unit endpoints share, to status codes
+fn uom_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Maps stock movement errors to status codes
+fn movement_error(e: ItemError) -> StatusCode {
+    match e {
+        ItemError::InsufficientStock(_) => StatusCode::CONFLICT,
+        ItemError::InvalidUnit(_) | ItemError::InvalidMovement(_) | ItemError::OwnerNotFound(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// Maps BOM import errors to status codes
+fn bom_import_error(e: ItemError) -> StatusCode {
+    match e {
+        ItemError::NotFound => StatusCode::NOT_FOUND,
+        ItemError::InvalidBomImport(_) => StatusCode::BAD_REQUEST,
+        ItemError::Unnumbered(_) => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// Maps item image errors to status codes
+fn image_error(e: anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<ItemImageError>() {
+        Some(ItemImageError::TooLarge(_)) => StatusCode::PAYLOAD_TOO_LARGE,
+        _ => AppError::from_service(e).status(),
+    }
+}
+
+// Maps item datasheet errors to status codes
+fn datasheet_error(e: ItemError) -> StatusCode {
+    match e {
+        ItemError::StorageNotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+        ItemError::NotFound | ItemError::NoDatasheet => StatusCode::NOT_FOUND,
+        ItemError::InvalidDatasheetUrl(_) => StatusCode::BAD_REQUEST,
+        ItemError::DatasheetTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        ItemError::DatasheetInfected(_) | ItemError::DatasheetScanFailed(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ItemError::DatasheetFetch(_) | ItemError::Storage(_) => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 // General Item API implementations
 
 async fn list_all_items(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<Vec<ItemResponse>>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
-    let item_service = ItemService::new(state.database);
+    let database = state.database;
 
-    match item_service
-        .list_items(
+    let version = ItemService::new(database.clone())
+        .list_version(
             tenant_id,
-            params.context,
-            params.category,
-            params.lifecycle,
-            params.limit,
-            params.offset,
+            params.context.clone(),
+            params.category.clone(),
+            params.lifecycle.clone(),
         )
         .await
-    {
-        Ok(items) => Ok(Json(items)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
     }
+
+    // Large catalogues are sent page by page rather than loaded whole
+    let body = stream_json_array(
+        tenant_id,
+        STREAM_PAGE_SIZE,
+        params.limit,
+        params.offset,
+        move |limit, offset| {
+            let item_service = ItemService::new(database.clone());
+            let (context, category, lifecycle) = (
+                params.context.clone(),
+                params.category.clone(),
+                params.lifecycle.clone(),
+            );
+            async move {
+                item_service
+                    .list_items(
+                        tenant_id,
+                        context,
+                        category,
+                        lifecycle,
+                        Some(limit),
+                        Some(offset),
+                    )
+                    .await
+                    .map_err(anyhow::Error::from)
+            }
+        },
+    )
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(with_etag(
+        &etag,
+        ([(CONTENT_TYPE, "application/json")], body),
+    ))
 }
 
 async fn create_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreateItemRequest>,
-) -> Result<Json<CreateItemIdResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    ValidatedJson(payload): ValidatedJson<CreateItemRequest>,
+) -> Result<Json<CreateItemIdResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let item_service = ItemService::new(state.database);
 
+    let response = item_service.create_item(tenant_id, payload).await?;
+    Ok(Json(response))
+}
+
+// Filters such as `resistance[gte]=1k` repeat and carry brackets, so the query is read as pairs
+async fn parametric_search(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(pairs): Query<Vec<(String, String)>>,
+) -> Result<Json<Vec<ItemResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
+    let search = parse_parametric_search(&pairs).map_err(AppError::Validation)?;
     let item_service = ItemService::new(state.database);
 
-    match item_service.create_item(tenant_id, payload).await {
-        Ok(response) => Ok(Json(response)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let items = item_service.parametric_search(tenant_id, search).await?;
+    Ok(Json(items))
 }
 
 async fn get_item_details(
@@ -121,15 +311,24 @@ async fn get_item_details(
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<ItemResponse>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
     let context = params.context.unwrap_or(ItemContext::Store);
 
-    match item_service.get_item_by_id(tenant_id, id, context).await {
-        Ok(Some(item)) => Ok(Json(item)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    let version = item_service
+        .item_version(tenant_id, id, context.clone())
+        .await?
+        .ok_or(ItemError::NotFound)?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
+    match item_service.get_item_by_id(tenant_id, id, context).await? {
+        Some(item) => Ok(with_etag(&etag, Json(item))),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -138,24 +337,16 @@ async fn update_item(
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
     Query(params): Query<ListQuery>,
-    Json(payload): Json<UpdateItemRequest>,
-) -> Result<Json<ItemResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateItemRequest>,
+) -> Result<Json<ItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
     let context = params.context.unwrap_or(ItemContext::Store);
 
-    match item_service
+    let item = item_service
         .update_item(tenant_id, id, context, payload)
-        .await
-    {
-        Ok(item) => Ok(Json(item)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(item))
 }
 
 async fn delete_item(
@@ -163,15 +354,13 @@ async fn delete_item(
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
     Query(params): Query<ListQuery>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
     let context = params.context.unwrap_or(ItemContext::Store);
 
-    match item_service.delete_item(tenant_id, id, context).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    item_service.delete_item(tenant_id, id, context).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Context-specific implementations
@@ -180,34 +369,30 @@ async fn list_finished_goods_items(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<Vec<FinishedGoodsItemResponse>>, StatusCode> {
+) -> Result<Json<Vec<FinishedGoodsItemResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    let items = item_service
         .list_finished_goods_items(tenant_id, params.limit, params.offset)
-        .await
-    {
-        Ok(items) => Ok(Json(items)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(items))
 }
 
 async fn get_finished_goods_item_details(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<FinishedGoodsItemResponse>, StatusCode> {
+) -> Result<Json<FinishedGoodsItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
     match item_service
         .get_finished_goods_item_by_id(tenant_id, id)
-        .await
+        .await?
     {
-        Ok(Some(item)) => Ok(Json(item)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -215,32 +400,22 @@ async fn update_finished_goods_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateItemRequest>,
-) -> Result<Json<FinishedGoodsItemResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateItemRequest>,
+) -> Result<Json<FinishedGoodsItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    item_service
         .update_item(tenant_id, id, ItemContext::FinishedGoods, payload)
-        .await
+        .await?;
+
+    // Return the updated finished goods item
+    match item_service
+        .get_finished_goods_item_by_id(tenant_id, id)
+        .await?
     {
-        Ok(_) => {
-            // Return the updated finished goods item
-            match item_service
-                .get_finished_goods_item_by_id(tenant_id, id)
-                .await
-            {
-                Ok(Some(item)) => Ok(Json(item)),
-                Ok(None) => Err(StatusCode::NOT_FOUND),
-                Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
-        }
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -248,31 +423,27 @@ async fn list_store_items(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<Vec<StoreItemResponse>>, StatusCode> {
+) -> Result<Json<Vec<StoreItemResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    let items = item_service
         .list_store_items(tenant_id, params.limit, params.offset)
-        .await
-    {
-        Ok(items) => Ok(Json(items)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(items))
 }
 
 async fn get_store_item_details(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<StoreItemResponse>, StatusCode> {
+) -> Result<Json<StoreItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service.get_store_item_by_id(tenant_id, id).await {
-        Ok(Some(item)) => Ok(Json(item)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    match item_service.get_store_item_by_id(tenant_id, id).await? {
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -280,29 +451,19 @@ async fn update_store_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateItemRequest>,
-) -> Result<Json<StoreItemResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateItemRequest>,
+) -> Result<Json<StoreItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    item_service
         .update_item(tenant_id, id, ItemContext::Store, payload)
-        .await
-    {
-        Ok(_) => {
-            // Return the updated store item
-            match item_service.get_store_item_by_id(tenant_id, id).await {
-                Ok(Some(item)) => Ok(Json(item)),
-                Ok(None) => Err(StatusCode::NOT_FOUND),
-                Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
-        }
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        .await?;
+
+    // Return the updated store item
+    match item_service.get_store_item_by_id(tenant_id, id).await? {
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -310,31 +471,27 @@ async fn list_vendor_items(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<Vec<VendorItemResponse>>, StatusCode> {
+) -> Result<Json<Vec<VendorItemResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    let items = item_service
         .list_vendor_items(tenant_id, params.limit, params.offset)
-        .await
-    {
-        Ok(items) => Ok(Json(items)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(items))
 }
 
 async fn get_vendor_item_details(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<VendorItemResponse>, StatusCode> {
+) -> Result<Json<VendorItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service.get_vendor_item_by_id(tenant_id, id).await {
-        Ok(Some(item)) => Ok(Json(item)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    match item_service.get_vendor_item_by_id(tenant_id, id).await? {
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -342,29 +499,19 @@ async fn update_vendor_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateItemRequest>,
-) -> Result<Json<VendorItemResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateItemRequest>,
+) -> Result<Json<VendorItemResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service
+    item_service
         .update_item(tenant_id, id, ItemContext::Vendor, payload)
-        .await
-    {
-        Ok(_) => {
-            // Return the updated vendor item
-            match item_service.get_vendor_item_by_id(tenant_id, id).await {
-                Ok(Some(item)) => Ok(Json(item)),
-                Ok(None) => Err(StatusCode::NOT_FOUND),
-                Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-            }
-        }
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        .await?;
+
+    // Return the updated vendor item
+    match item_service.get_vendor_item_by_id(tenant_id, id).await? {
+        Some(item) => Ok(Json(item)),
+        None => Err(ItemError::NotFound.into()),
     }
 }
 
@@ -383,20 +530,13 @@ async fn list_bom_items(
 async fn create_bom_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreateBomItemRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateBomItemRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let item_service = ItemService::new(state.database);
 
-    match item_service.create_bom_item(tenant_id, payload).await {
-        Ok(bom_id) => Ok(Json(serde_json::json!({"id": bom_id}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let bom_id = item_service.create_bom_item(tenant_id, payload).await?;
+    Ok(Json(serde_json::json!({"id": bom_id})))
 }
 
 async fn get_bom_item_details(
@@ -413,49 +553,783 @@ async fn update_bom_item(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateBomItemRequest>,
-) -> Result<StatusCode, StatusCode> {
+    ValidatedJson(payload): ValidatedJson<UpdateBomItemRequest>,
+) -> Result<StatusCode, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let item_service = ItemService::new(state.database);
+
+    item_service.update_bom_item(tenant_id, id, payload).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn delete_bom_item(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let item_service = ItemService::new(state.database);
+
+    item_service.delete_bom_item(tenant_id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_item_bom(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(parent_item_id): Path<Uuid>,
+) -> Result<Json<Vec<BomItemResponse>>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let item_service = ItemService::new(state.database);
+
+    let bom_items = item_service
+        .get_bom_by_parent_item(tenant_id, parent_item_id)
+        .await?;
+    Ok(Json(bom_items))
+}
+
+async fn import_item_bom(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(parent_item_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ImportBomRequest>,
+) -> Result<Json<ImportBomResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let bom_import_service = BomImportService::new(state.database);
+
+    match bom_import_service
+        .import_bom(tenant_id, parent_item_id, payload)
+        .await
+    {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(bom_import_error(e)),
+    }
+}
+
+async fn export_item_bom(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(parent_item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<BomExportQuery>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let bom_import_service = BomImportService::new(state.database);
+
+    match bom_import_service
+        .export_bom(tenant_id, parent_item_id, params.format.unwrap_or_default())
+        .await
+    {
+        Ok(Some((file_name, csv))) => Ok((
+            [
+                (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", file_name),
+                ),
+            ],
+            csv,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(bom_import_error(e)),
+    }
+}
+
+// Unit of measure implementations
+
+async fn list_units(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<UnitOfMeasureResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.list_units(tenant_id).await {
+        Ok(units) => Ok(Json(units)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_unit(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateUnitOfMeasureRequest>,
+) -> Result<Json<UnitOfMeasureResponse>, StatusCode> {
     // Validate the request
-    if let Err(_) = payload.validate() {
+    if payload.check().is_err() {
         return Err(StatusCode::BAD_REQUEST);
     }
 
     let tenant_id = extract_tenant_id(&tenant_context);
-    let item_service = ItemService::new(state.database);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.create_unit(tenant_id, payload).await {
+        Ok(unit) => Ok(Json(unit)),
+        Err(e) => Err(uom_error(e)),
+    }
+}
 
-    match item_service.update_bom_item(tenant_id, id, payload).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
+async fn get_unit(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UnitOfMeasureResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.get_unit(tenant_id, id).await {
+        Ok(Some(unit)) => Ok(Json(unit)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
-async fn delete_bom_item(
+async fn update_unit(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateUnitOfMeasureRequest>,
+) -> Result<Json<UnitOfMeasureResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.update_unit(tenant_id, id, payload).await {
+        Ok(Some(unit)) => Ok(Json(unit)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn delete_unit(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
-    let item_service = ItemService::new(state.database);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.delete_unit(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(uom_error(e)),
+    }
+}
+
+async fn get_item_units(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+) -> Result<Json<ItemUnitsResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let uom_service = UomService::new(state.database);
 
-    match item_service.delete_bom_item(tenant_id, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
+    match uom_service.get_item_units(tenant_id, item_id).await {
+        Ok(Some(units)) => Ok(Json(units)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
 
-async fn get_item_bom(
+async fn set_item_units(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Path(parent_item_id): Path<Uuid>,
-) -> Result<Json<Vec<BomItemResponse>>, StatusCode> {
+    Path(item_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SetItemUnitsRequest>,
+) -> Result<Json<ItemUnitsResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     let tenant_id = extract_tenant_id(&tenant_context);
-    let item_service = ItemService::new(state.database);
+    let uom_service = UomService::new(state.database);
 
-    match item_service
-        .get_bom_by_parent_item(tenant_id, parent_item_id)
+    match uom_service
+        .set_item_units(tenant_id, item_id, payload)
         .await
     {
-        Ok(bom_items) => Ok(Json(bom_items)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Ok(Some(units)) => Ok(Json(units)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(uom_error(e)),
+    }
+}
+
+async fn convert_item_quantity(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ConvertQuantityQuery>,
+) -> Result<Json<ConversionResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let uom_service = UomService::new(state.database);
+
+    match uom_service.convert(tenant_id, item_id, params).await {
+        Ok(Some(conversion)) => Ok(Json(conversion)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(uom_error(e)),
+    }
+}
+
+// Stock movement and forecasting implementations
+
+async fn list_item_movements(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    Query(params): Query<ListQuery>,
+) -> Result<Json<Vec<StockMovementResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .list_movements(
+            tenant_id,
+            item_id,
+            params.context,
+            params.limit,
+            params.offset,
+        )
+        .await
+    {
+        Ok(movements) => Ok(Json(movements)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn record_item_movement(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(item_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<RecordStockMovementRequest>,
+) -> Result<Json<StockMovementResponse>, StatusCode> {
+    // Validate the request
+    if payload.quantity.is_zero() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .record_movement(tenant_id, item_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(movement)) => Ok(Json(movement)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(movement_error(e)),
+    }
+}
+
+async fn get_item_forecast(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<DemandForecastQuery>,
+) -> Result<Json<DemandForecastResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .forecast_demand(tenant_id, item_id, params)
+        .await
+    {
+        Ok(Some(forecast)) => Ok(Json(forecast)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_item_analytics(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ItemAnalyticsQuery>,
+) -> Result<Json<ItemAnalyticsResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .item_analytics(tenant_id, item_id, params)
+        .await
+    {
+        Ok(Some(analytics)) => Ok(Json(analytics)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Stock reservation implementations
+
+async fn get_item_availability(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    Query(params): Query<ListQuery>,
+) -> Result<Json<Vec<StockAvailabilityResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .get_availability(tenant_id, item_id, params.context)
+        .await
+    {
+        Ok(availability) if availability.is_empty() => Err(StatusCode::NOT_FOUND),
+        Ok(availability) => Ok(Json(availability)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn reserve_item_stock(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(item_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateStockReservationRequest>,
+) -> Result<Json<StockReservationResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .reserve_stock(tenant_id, item_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(reservation)) => Ok(Json(reservation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn list_reservations(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListStockReservationsQuery>,
+) -> Result<Json<Vec<StockReservationResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service.list_reservations(tenant_id, params).await {
+        Ok(reservations) => Ok(Json(reservations)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn release_reservation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StockReservationResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service.release_reservation(tenant_id, id).await {
+        Ok(Some(reservation)) => Ok(Json(reservation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn get_item_atp(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<AvailableToPromiseQuery>,
+) -> Result<Json<AvailableToPromiseResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let stock_service = StockService::new(state.database);
+
+    match stock_service
+        .available_to_promise(tenant_id, item_id, params)
+        .await
+    {
+        Ok(Some(atp)) => Ok(Json(atp)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Label printing implementations
+
+async fn print_item_label(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<PrintLabelQuery>,
+) -> Result<Json<PrintJobResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let print_service = PrintService::new(state.database);
+
+    match print_service
+        .print_item_label(tenant_id, item_id, Some(person_id), params)
+        .await
+    {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Item image API implementations
+
+async fn upload_item_image(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<UploadItemImageQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ItemImageResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(StatusCode::UNSUPPORTED_MEDIA_TYPE)?;
+    let image_service =
+        ItemImageService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match image_service
+        .upload_image(
+            tenant_id,
+            item_id,
+            Some(person_id),
+            content_type,
+            body.to_vec(),
+            params,
+        )
+        .await
+    {
+        Ok(Some(image)) => Ok((StatusCode::CREATED, Json(image))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(image_error(e)),
+    }
+}
+
+async fn list_item_images(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+) -> Result<Json<Vec<ItemImageResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let image_service =
+        ItemImageService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match image_service.list_images(tenant_id, item_id).await {
+        Ok(Some(images)) => Ok(Json(images)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(image_error(e)),
+    }
+}
+
+async fn set_primary_item_image(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((item_id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ItemImageResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let image_service =
+        ItemImageService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match image_service
+        .set_primary_image(tenant_id, item_id, image_id)
+        .await
+    {
+        Ok(Some(image)) => Ok(Json(image)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(image_error(e)),
+    }
+}
+
+async fn delete_item_image(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((item_id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let image_service =
+        ItemImageService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match image_service
+        .delete_image(tenant_id, item_id, image_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(image_error(e)),
+    }
+}
+
+// Item datasheet API implementations
+
+async fn get_item_datasheet(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<GetDatasheetQuery>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let datasheet_service =
+        ItemDatasheetService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match datasheet_service
+        .get_datasheet(
+            tenant_id,
+            item_id,
+            person_id,
+            params.refresh.unwrap_or(false),
+        )
+        .await
+    {
+        Ok(Some(file)) => Ok((
+            [
+                (CONTENT_TYPE, file.content_type),
+                (
+                    CONTENT_DISPOSITION,
+                    format!("inline; filename=\"{}\"", file.file_name),
+                ),
+                (
+                    LAST_MODIFIED,
+                    file.fetched_at
+                        .format("%a, %d %b %Y %H:%M:%S GMT")
+                        .to_string(),
+                ),
+                (
+                    HeaderName::from_static("x-datasheet-stale"),
+                    file.stale.to_string(),
+                ),
+            ],
+            file.content,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(datasheet_error(e)),
+    }
+}
+
+async fn list_item_datasheets(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListDatasheetsQuery>,
+) -> Result<Json<Vec<ItemDatasheetResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let datasheet_service =
+        ItemDatasheetService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match datasheet_service.list_datasheets(tenant_id, params).await {
+        Ok(datasheets) => Ok(Json(datasheets)),
+        Err(e) => Err(datasheet_error(e)),
+    }
+}
+
+async fn refresh_item_datasheets(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<RefreshDatasheetsRequest>,
+) -> Result<Json<RefreshDatasheetsResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let datasheet_service =
+        ItemDatasheetService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match datasheet_service
+        .refresh_stale(tenant_id, person_id, payload.limit)
+        .await
+    {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => Err(datasheet_error(e)),
+    }
+}
+
+// Vendor pricing API implementations
+
+async fn import_vendor_price_list(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<PriceListImportQuery>,
+    body: String,
+) -> Result<Json<PriceListImportResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let pricing_service = PricingService::new(state.database);
+
+    match pricing_service
+        .import_price_list(
+            tenant_id,
+            params.vendor_id,
+            Some(person_id),
+            &body,
+            params.dry_run.unwrap_or(false),
+        )
+        .await
+    {
+        Ok(summary) => Ok(Json(summary)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn get_item_price_history(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<PriceHistoryQuery>,
+) -> Result<Json<Vec<ItemPriceHistoryResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let pricing_service = PricingService::new(state.database);
+
+    match pricing_service
+        .list_price_history(tenant_id, item_id, params)
+        .await
+    {
+        Ok(history) => Ok(Json(history)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Lifecycle watch API implementations
+
+async fn list_lifecycle_alerts(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListLifecycleAlertsQuery>,
+) -> Result<Json<Vec<LifecycleAlertResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let lifecycle_service =
+        LifecycleService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match lifecycle_service.list_alerts(tenant_id, params).await {
+        Ok(alerts) => Ok(Json(alerts)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_lifecycle_alert(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LifecycleAlertResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let lifecycle_service =
+        LifecycleService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match lifecycle_service.get_alert(tenant_id, id).await {
+        Ok(Some(alert)) => Ok(Json(alert)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn accept_lifecycle_alert(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LifecycleAlertResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let lifecycle_service =
+        LifecycleService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match lifecycle_service
+        .accept_alert(tenant_id, id, Some(person_id))
+        .await
+    {
+        Ok(Some(alert)) => Ok(Json(alert)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn dismiss_lifecycle_alert(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LifecycleAlertResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let lifecycle_service =
+        LifecycleService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match lifecycle_service
+        .dismiss_alert(tenant_id, id, Some(person_id))
+        .await
+    {
+        Ok(Some(alert)) => Ok(Json(alert)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn check_item_lifecycle(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(item_id): Path<Uuid>,
+) -> Result<Json<LifecycleCheckResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let lifecycle_service =
+        LifecycleService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match lifecycle_service.check_item_now(tenant_id, item_id).await {
+        Ok(Some(check)) => Ok(Json(check)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Duplicate detection API implementations
+
+async fn list_duplicate_items(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<DuplicatesQuery>,
+) -> Result<Json<Vec<DuplicateMatch>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let duplicate_service = DuplicateService::new(state.database);
+
+    match duplicate_service
+        .find_item_duplicates(tenant_id, params)
+        .await
+    {
+        Ok(matches) => Ok(Json(matches)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn merge_items(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<MergeRequest>,
+) -> Result<Json<MergeResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let duplicate_service = DuplicateService::new(state.database);
+
+    match duplicate_service.merge_items(tenant_id, payload).await {
+        Ok(Some(merged)) => Ok(Json(merged)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
     }
 }
@@ -1,21 +1,32 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
     Extension, Router,
 };
+use chrono::{Duration, Utc};
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
     middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
     models::{
-        CreateJobIdResponse, CreateJobRequest, JobPriority, JobResponse, JobStatus, JobType,
-        ManufacturingJobResponse, QaJobResponse, ServiceJobResponse, UpdateJobRequest,
+        BatchRecordResponse, Claims, CompleteJobOperationRequest, CompleteJobRequest,
+        ConsumeSubstituteRequest, CreateJobDependencyRequest, CreateJobIdResponse,
+        CreateJobRequest, GanttQuery, GanttResponse, JobDependenciesResponse,
+        JobDependencyResponse, JobOperationResponse, JobPriority, JobResponse, JobRoutingResponse,
+        JobStatus, JobSubstitutionResponse, JobType, KitCheckQuery, KitCheckResponse,
+        ListBatchRecordsQuery, ManufacturingJobResponse, QaJobResponse, ServiceJobResponse,
+        SetJobRoutingRequest, StartJobOperationRequest, UpdateJobRequest,
+        DEFAULT_GANTT_WINDOW_DAYS, MAX_GANTT_WINDOW_DAYS,
     },
-    services::JobService,
+    services::{
+        BatchRecordService, JobDependencyService, JobOperationService, JobService, KittingService,
+        UomError,
+    },
+    utils::AppError,
     AppState,
 };
 
@@ -26,6 +37,9 @@ struct ListQuery {
     status: Option<JobStatus>,
     #[allow(dead_code)]
     priority: Option<JobPriority>,
+    /// Also list archived jobs
+    #[serde(default)]
+    include_archived: bool,
     limit: Option<u32>,
     offset: Option<u32>,
 }
@@ -38,6 +52,39 @@ pub fn routes() -> Router<AppState> {
             "/:id",
             get(get_job_details).put(update_job).delete(delete_job),
         )
+        .route("/:id/complete", post(complete_job))
+        .route("/:id/archive", post(archive_job))
+        .route("/:id/unarchive", post(unarchive_job))
+        // Job traveler API routes
+        .route("/:id/operations", get(get_job_routing).put(set_job_routing))
+        .route(
+            "/:id/operations/:operation_id/start",
+            post(start_job_operation),
+        )
+        .route(
+            "/:id/operations/:operation_id/complete",
+            post(complete_job_operation),
+        )
+        // Kitting API routes
+        .route("/:id/kit", get(get_kit_check))
+        .route(
+            "/:id/substitutions",
+            get(list_job_substitutions).post(consume_substitute),
+        )
+        // Job dependency and Gantt API routes
+        .route("/gantt", get(get_gantt))
+        .route(
+            "/:id/dependencies",
+            get(list_job_dependencies).post(add_job_dependency),
+        )
+        .route(
+            "/:id/dependencies/:predecessor_id",
+            delete(remove_job_dependency),
+        )
+        // Batch record API routes
+        .route("/batch-records", get(list_batch_records))
+        .route("/:id/batch-record", get(get_batch_record))
+        .route("/:id/batch-record/pdf", get(download_batch_record_pdf))
         // Specialized Job API routes
         .route("/manufacturing", get(list_manufacturing_jobs))
         .route(
@@ -58,6 +105,11 @@ fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
     tenant_context.tenant_id
 }
 
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
 // General Job API implementations
 
 async fn list_all_jobs(
@@ -73,6 +125,7 @@ async fn list_all_jobs(
             tenant_id,
             params.job_type,
             params.status,
+            params.include_archived,
             params.limit,
             params.offset,
         )
@@ -86,19 +139,14 @@ async fn list_all_jobs(
 async fn create_job(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreateJobRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateJobRequest>,
 ) -> Result<Json<CreateJobIdResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let job_service = JobService::new(state.database);
 
     match job_service.create_job(tenant_id, payload).await {
         Ok(response) => Ok(Json(response)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(AppError::from_service(e).status()),
     }
 }
 
@@ -121,13 +169,8 @@ async fn update_job(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateJobRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateJobRequest>,
 ) -> Result<Json<JobResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let job_service = JobService::new(state.database);
 
@@ -151,6 +194,333 @@ async fn delete_job(
     }
 }
 
+async fn complete_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CompleteJobRequest>,
+) -> Result<Json<JobResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let job_service = JobService::new(state.database);
+
+    match job_service
+        .complete_job(tenant_id, id, user_id, payload)
+        .await
+    {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn archive_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobResponse>, StatusCode> {
+    set_job_archived(state, tenant_context, id, true).await
+}
+
+async fn unarchive_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobResponse>, StatusCode> {
+    set_job_archived(state, tenant_context, id, false).await
+}
+
+async fn set_job_archived(
+    state: AppState,
+    tenant_context: TenantContext,
+    id: Uuid,
+    archived: bool,
+) -> Result<Json<JobResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let job_service = JobService::new(state.database);
+
+    match job_service.set_archived(tenant_id, id, archived).await {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Job traveler implementations
+
+async fn get_job_routing(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobRoutingResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let operation_service = JobOperationService::new(state.database);
+
+    match operation_service.get_routing(tenant_id, id).await {
+        Ok(Some(routing)) => Ok(Json(routing)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn set_job_routing(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SetJobRoutingRequest>,
+) -> Result<Json<JobRoutingResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let operation_service = JobOperationService::new(state.database);
+
+    match operation_service.set_routing(tenant_id, id, payload).await {
+        Ok(Some(routing)) => Ok(Json(routing)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn start_job_operation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path((id, operation_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<StartJobOperationRequest>,
+) -> Result<Json<JobOperationResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let operation_service = JobOperationService::new(state.database);
+
+    match operation_service
+        .start_operation(tenant_id, id, operation_id, user_id, payload)
+        .await
+    {
+        Ok(Some(operation)) => Ok(Json(operation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn complete_job_operation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path((id, operation_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<CompleteJobOperationRequest>,
+) -> Result<Json<JobOperationResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let operation_service = JobOperationService::new(state.database);
+
+    match operation_service
+        .complete_operation(tenant_id, id, operation_id, user_id, payload)
+        .await
+    {
+        Ok(Some(operation)) => Ok(Json(operation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Kitting API implementations
+
+async fn get_kit_check(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<KitCheckQuery>,
+) -> Result<Json<KitCheckResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kitting_service = KittingService::new(state.database);
+
+    match kitting_service.kit_check(tenant_id, id, params).await {
+        Ok(Some(kit)) => Ok(Json(kit)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => match e.downcast_ref::<UomError>() {
+            Some(UomError::Invalid(_)) => Err(StatusCode::UNPROCESSABLE_ENTITY),
+            _ => Err(AppError::from_service(e).status()),
+        },
+    }
+}
+
+async fn list_job_substitutions(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<JobSubstitutionResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kitting_service = KittingService::new(state.database);
+
+    match kitting_service.list_substitutions(tenant_id, id).await {
+        Ok(Some(substitutions)) => Ok(Json(substitutions)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn consume_substitute(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ConsumeSubstituteRequest>,
+) -> Result<(StatusCode, Json<JobSubstitutionResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let kitting_service = KittingService::new(state.database);
+
+    match kitting_service
+        .consume_substitute(tenant_id, id, user_id, payload)
+        .await
+    {
+        Ok(Some(substitution)) => Ok((StatusCode::CREATED, Json(substitution))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Job dependency and Gantt implementations
+
+async fn list_job_dependencies(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobDependenciesResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let dependency_service = JobDependencyService::new(state.database);
+
+    match dependency_service.list_dependencies(tenant_id, id).await {
+        Ok(Some(dependencies)) => Ok(Json(dependencies)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn add_job_dependency(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateJobDependencyRequest>,
+) -> Result<(StatusCode, Json<JobDependencyResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let dependency_service = JobDependencyService::new(state.database);
+
+    match dependency_service
+        .add_dependency(tenant_id, id, Some(user_id), payload)
+        .await
+    {
+        Ok(Some(dependency)) => Ok((StatusCode::CREATED, Json(dependency))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn remove_job_dependency(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, predecessor_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let dependency_service = JobDependencyService::new(state.database);
+
+    match dependency_service
+        .remove_dependency(tenant_id, id, predecessor_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_gantt(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<GanttQuery>,
+) -> Result<Json<GanttResponse>, StatusCode> {
+    let from = params.from.unwrap_or_else(Utc::now);
+    let to = params
+        .to
+        .unwrap_or(from + Duration::days(DEFAULT_GANTT_WINDOW_DAYS));
+    if to <= from || (to - from).num_days() > MAX_GANTT_WINDOW_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let dependency_service = JobDependencyService::new(state.database);
+
+    match dependency_service.gantt(tenant_id, from, to).await {
+        Ok(gantt) => Ok(Json(gantt)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Batch record implementations
+
+async fn list_batch_records(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListBatchRecordsQuery>,
+) -> Result<Json<Vec<BatchRecordResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let batch_record_service = BatchRecordService::new(state.database);
+
+    match batch_record_service
+        .list_batch_records(tenant_id, params)
+        .await
+    {
+        Ok(records) => Ok(Json(records)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_batch_record(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BatchRecordResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let batch_record_service = BatchRecordService::new(state.database);
+
+    match batch_record_service.get_batch_record(tenant_id, id).await {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn download_batch_record_pdf(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let batch_record_service = BatchRecordService::new(state.database);
+
+    match batch_record_service.batch_record_pdf(tenant_id, id).await {
+        Ok(Some((batch_number, pdf))) => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"batch-{}.pdf\"", batch_number),
+                ),
+            ],
+            pdf,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 // Type-specific implementations
 async fn list_manufacturing_jobs(
     State(state): State<AppState>,
@@ -161,7 +531,12 @@ async fn list_manufacturing_jobs(
     let job_service = JobService::new(state.database);
 
     match job_service
-        .list_manufacturing_jobs(tenant_id, params.limit, params.offset)
+        .list_manufacturing_jobs(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(jobs) => Ok(Json(jobs)),
@@ -188,13 +563,8 @@ async fn update_manufacturing_job(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateJobRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateJobRequest>,
 ) -> Result<Json<ManufacturingJobResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let job_service = JobService::new(state.database);
 
@@ -220,7 +590,12 @@ async fn list_qa_jobs(
     let job_service = JobService::new(state.database);
 
     match job_service
-        .list_qa_jobs(tenant_id, params.limit, params.offset)
+        .list_qa_jobs(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(jobs) => Ok(Json(jobs)),
@@ -247,13 +622,8 @@ async fn update_qa_job(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateJobRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateJobRequest>,
 ) -> Result<Json<QaJobResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let job_service = JobService::new(state.database);
 
@@ -279,7 +649,12 @@ async fn list_service_jobs(
     let job_service = JobService::new(state.database);
 
     match job_service
-        .list_service_jobs(tenant_id, params.limit, params.offset)
+        .list_service_jobs(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(jobs) => Ok(Json(jobs)),
@@ -306,13 +681,8 @@ async fn update_service_job(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateJobRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateJobRequest>,
 ) -> Result<Json<ServiceJobResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let job_service = JobService::new(state.database);
 
@@ -0,0 +1,295 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post, put},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        CallerContext, CreateKioskTerminalRequest, JobOperationResponse, KioskClockRequest,
+        KioskCompleteOperationRequest, KioskContext, KioskDowntimeRequest, KioskOperatorResponse,
+        KioskTerminalKeyResponse, KioskTerminalResponse, MachineDowntimeReportResponse,
+        OperatorBadgeResponse, OperatorClockEventResponse, SetOperatorBadgeRequest,
+    },
+    services::KioskService,
+    utils::AppError,
+    AppState,
+};
+
+/// Kiosk API, mounted behind the auth middleware. Terminals and badges are managed by users;
+/// the kiosk endpoints are called by terminals with their kiosk key.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Terminal and badge management routes
+        .route("/terminals", get(list_terminals).post(provision_terminal))
+        .route("/terminals/:id/revoke", post(revoke_terminal))
+        .route("/badges", get(list_badges))
+        .route("/badges/:person_id", put(set_badge).delete(remove_badge))
+        .route("/badges/:person_id/unlock", post(unlock_badge))
+        // Kiosk routes
+        .route("/terminal", get(get_terminal))
+        .route("/operators", get(list_operators))
+        .route("/clock", post(clock))
+        .route("/operations/complete", post(complete_operation))
+        .route("/downtime", post(report_downtime))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Kiosk endpoints are only for terminals, which the auth middleware marks with a kiosk context
+fn extract_terminal_id(kiosk: Option<Extension<KioskContext>>) -> Result<Uuid, StatusCode> {
+    kiosk
+        .map(|Extension(kiosk)| kiosk.terminal_id)
+        .ok_or(StatusCode::FORBIDDEN)
+}
+
+// Maps kiosk errors to status codes
+fn kiosk_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Kiosk request failed: {}", error);
+    }
+    error.status()
+}
+
+// Terminal and badge management API implementations
+
+async fn provision_terminal(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateKioskTerminalRequest>,
+) -> Result<(StatusCode, Json<KioskTerminalKeyResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .provision_terminal(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(terminal) => Ok((StatusCode::CREATED, Json(terminal))),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn list_terminals(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<Vec<KioskTerminalResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.list_terminals(tenant_id, &caller).await {
+        Ok(terminals) => Ok(Json(terminals)),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn revoke_terminal(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<KioskTerminalResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.revoke_terminal(tenant_id, &caller, id).await {
+        Ok(Some(terminal)) => Ok(Json(terminal)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn list_badges(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<Vec<OperatorBadgeResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.list_badges(tenant_id, &caller).await {
+        Ok(badges) => Ok(Json(badges)),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn set_badge(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(person_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SetOperatorBadgeRequest>,
+) -> Result<Json<OperatorBadgeResponse>, StatusCode> {
+    // Validate the PIN
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .set_badge(tenant_id, &caller, person_id, payload)
+        .await
+    {
+        Ok(Some(badge)) => Ok(Json(badge)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn remove_badge(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(person_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .remove_badge(tenant_id, &caller, person_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn unlock_badge(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(person_id): Path<Uuid>,
+) -> Result<Json<OperatorBadgeResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .unlock_badge(tenant_id, &caller, person_id)
+        .await
+    {
+        Ok(Some(badge)) => Ok(Json(badge)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+// Kiosk API implementations
+
+async fn get_terminal(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    kiosk: Option<Extension<KioskContext>>,
+) -> Result<Json<KioskTerminalResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let terminal_id = extract_terminal_id(kiosk)?;
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.get_terminal(tenant_id, terminal_id).await {
+        Ok(Some(terminal)) => Ok(Json(terminal)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn list_operators(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    kiosk: Option<Extension<KioskContext>>,
+) -> Result<Json<Vec<KioskOperatorResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    extract_terminal_id(kiosk)?;
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.list_operators(tenant_id).await {
+        Ok(operators) => Ok(Json(operators)),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn clock(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    kiosk: Option<Extension<KioskContext>>,
+    ValidatedJson(payload): ValidatedJson<KioskClockRequest>,
+) -> Result<(StatusCode, Json<OperatorClockEventResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let terminal_id = extract_terminal_id(kiosk)?;
+
+    // Validate how the operator badges in
+    if payload.operator.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service.clock(tenant_id, terminal_id, payload).await {
+        Ok(event) => Ok((StatusCode::CREATED, Json(event))),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn complete_operation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    kiosk: Option<Extension<KioskContext>>,
+    ValidatedJson(payload): ValidatedJson<KioskCompleteOperationRequest>,
+) -> Result<Json<JobOperationResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let terminal_id = extract_terminal_id(kiosk)?;
+
+    // Validate how the operator badges in
+    if payload.operator.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .complete_operation(tenant_id, terminal_id, payload)
+        .await
+    {
+        Ok(Some(operation)) => Ok(Json(operation)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
+
+async fn report_downtime(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    kiosk: Option<Extension<KioskContext>>,
+    ValidatedJson(payload): ValidatedJson<KioskDowntimeRequest>,
+) -> Result<(StatusCode, Json<MachineDowntimeReportResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let terminal_id = extract_terminal_id(kiosk)?;
+
+    // Validate how the operator badges in and the status reported
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let kiosk_service = KioskService::new(state.database);
+
+    match kiosk_service
+        .report_downtime(tenant_id, terminal_id, payload)
+        .await
+    {
+        Ok(Some(report)) => Ok((StatusCode::CREATED, Json(report))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(kiosk_error(e)),
+    }
+}
@@ -1,25 +1,45 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{Json, Response},
     routing::{delete, get, post, put},
     Extension, Router,
 };
+use chrono::{Duration, Utc};
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::etag::{with_etag, Revalidate},
     middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
     models::{
-        CreateMachineAssetRelationshipRequest, CreateMachineItemRelationshipRequest,
-        CreateMachineJobAssignmentRequest, CreateMachineOperatorAssignmentRequest,
-        CreateMachineRequest, HeartbeatRequest, ItemRelationshipType, JobAssignmentStatus,
-        MachineAssetRelationshipResponse, MachineCreateIdResponse, MachineItemRelationshipResponse,
-        MachineJobAssignmentResponse, MachineOperatorAssignmentResponse, MachineProtocol,
-        MachineResponse, MachineStatus, UpdateMachineJobAssignmentRequest, UpdateMachineRequest,
+        CallerContext, CapacityPlanResponse, CapacityQuery, CreateMachineAlertRuleRequest,
+        CreateMachineAssetRelationshipRequest, CreateMachineCommandRequest,
+        CreateMachineItemRelationshipRequest, CreateMachineJobAssignmentRequest,
+        CreateMachineOperatorAssignmentRequest, CreateMachineRequest, DecommissionMachineRequest,
+        FleetSummaryQuery, HeartbeatBatchRequest, HeartbeatBatchResponse, HeartbeatRequest,
+        HeartbeatResponse, ItemRelationshipType, JobAssignmentStatus, ListMachineAlertsQuery,
+        ListMachineCommandsQuery, ListTelemetryAnomaliesQuery, MachineAlertResponse,
+        MachineAlertRuleResponse, MachineAlertRuleTestResponse, MachineAssetRelationshipResponse,
+        MachineCommandResponse, MachineConfigResponse, MachineCreateIdResponse,
+        MachineCredentialResponse, MachineDecommissionResponse, MachineDocsResponse,
+        MachineFeatureCollection, MachineFleetSummary, MachineItemRelationshipResponse,
+        MachineJobAssignmentResponse, MachineKeyContext, MachineMapQuery,
+        MachineOperatorAssignmentCreateResponse, MachineOperatorAssignmentResponse,
+        MachineProtocol, MachineProvisioningBundle, MachineResponse, MachineStatus,
+        MachineTelemetryResponse, PutMachineConfigRequest, TelemetryAnomalyResponse,
+        TelemetryQuery, TestMachineAlertRuleRequest, UpdateMachineAlertRuleRequest,
+        UpdateMachineJobAssignmentRequest, UpdateMachineRequest, DEFAULT_FLEET_SUMMARY_LIMIT,
+        DEFAULT_STALE_HEARTBEAT_MINUTES,
     },
-    services::MachineService,
+    services::{
+        MachineAlertError, MachineAlertService, MachineCommandService, MachineCredentialService,
+        MachineError, MachineService, TelemetryService,
+    },
+    utils::capacity::{DEFAULT_HOURS_PER_DAY, DEFAULT_WINDOW_DAYS, MAX_WINDOW_DAYS},
+    utils::telemetry,
+    utils::AppError,
     AppState,
 };
 
@@ -27,6 +47,7 @@ use crate::{
 struct ListMachinesQuery {
     status: Option<MachineStatus>,
     protocol: Option<MachineProtocol>,
+    site: Option<String>,
     limit: Option<u32>,
     offset: Option<u32>,
 }
@@ -41,17 +62,68 @@ struct ListByJobQuery {
     status: Option<JobAssignmentStatus>,
 }
 
+#[derive(Deserialize)]
+struct ListAlertRulesQuery {
+    machine_id: Option<Uuid>,
+}
+
 pub fn routes() -> Router<AppState> {
     Router::new()
         // Main machine routes
         .route("/", get(list_machines).post(create_machine))
+        // Capacity planning
+        .route("/capacity", get(get_capacity_plan))
+        // Map view
+        .route("/geojson", get(get_machine_map))
+        // Fleet overview
+        .route("/summary", get(get_fleet_summary))
+        // Heartbeats gateways collected from the machines behind them
+        .route("/heartbeats/batch", post(update_heartbeats))
+        // Alert rules evaluated on each heartbeat, and the alerts they raise
+        .route(
+            "/alert-rules",
+            get(list_alert_rules).post(create_alert_rule),
+        )
+        .route(
+            "/alert-rules/:rule_id",
+            get(get_alert_rule)
+                .put(update_alert_rule)
+                .delete(delete_alert_rule),
+        )
+        .route("/alert-rules/:rule_id/test", post(test_alert_rule))
+        .route("/alerts", get(list_machine_alerts))
         .route(
             "/:id",
             get(get_machine_details)
                 .put(update_machine)
                 .delete(delete_machine),
         )
+        .route("/:id/decommission", post(decommission_machine))
         .route("/:id/heartbeat", post(update_heartbeat))
+        .route("/:id/telemetry", get(get_machine_telemetry))
+        // Telemetry readings the alert worker found anomalous
+        .route("/:id/anomalies", get(list_machine_anomalies))
+        // Documentation bundle for technicians at the machine
+        .route("/:id/docs", get(get_machine_docs))
+        // Commands and desired configuration handed out in heartbeat responses
+        .route(
+            "/:id/commands",
+            get(list_machine_commands).post(create_machine_command),
+        )
+        .route(
+            "/:id/commands/:command_id/cancel",
+            post(cancel_machine_command),
+        )
+        .route(
+            "/:id/config",
+            get(get_machine_config).put(put_machine_config),
+        )
+        // One-time provisioning bundles with the credentials machines connect with
+        .route(
+            "/:id/provision",
+            get(list_machine_credentials).post(provision_machine),
+        )
+        .route("/:id/provision/revoke", post(revoke_machine_credential))
         // Machine-Item relationship routes
         .route("/:id/items", get(list_machine_item_relationships))
         .route("/:id/items", post(create_machine_item_relationship))
@@ -90,113 +162,374 @@ fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
     tenant_context.tenant_id
 }
 
+// A rule naming a machine the tenant does not have is a bad request, not a missing rule
+fn alert_rule_error(e: MachineAlertError) -> AppError {
+    match e {
+        MachineAlertError::MachineNotFound => AppError::Validation(e.to_string()),
+        e => e.into(),
+    }
+}
+
+fn alert_rule_not_found() -> AppError {
+    AppError::NotFound("Alert rule not found".to_string())
+}
+
 // Main machine API implementations
 
 async fn list_machines(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListMachinesQuery>,
-) -> Result<Json<Vec<MachineResponse>>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let version = machine_service
+        .list_version(
+            tenant_id,
+            params.status.clone(),
+            params.protocol.clone(),
+            params.site.clone(),
+        )
+        .await?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
+    let machines = machine_service
         .list_machines(
             tenant_id,
             params.status,
             params.protocol,
+            params.site,
             params.limit,
             params.offset,
         )
-        .await
-    {
-        Ok(machines) => Ok(Json(machines)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(with_etag(&etag, Json(machines)))
 }
 
 async fn create_machine(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreateMachineRequest>,
-) -> Result<Json<MachineCreateIdResponse>, StatusCode> {
+    ValidatedJson(payload): ValidatedJson<CreateMachineRequest>,
+) -> Result<Json<MachineCreateIdResponse>, AppError> {
     // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    payload.check().map_err(AppError::Validation)?;
 
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service.create_machine(tenant_id, payload).await {
-        Ok(response) => Ok(Json(response)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let response = machine_service.create_machine(tenant_id, payload).await?;
+    Ok(Json(response))
 }
 
 async fn get_machine_details(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<MachineResponse>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service.get_machine_by_id(tenant_id, id).await {
-        Ok(Some(machine)) => Ok(Json(machine)),
-        Ok(None) => Err(StatusCode::NOT_FOUND),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    let version = machine_service
+        .machine_version(tenant_id, id)
+        .await?
+        .ok_or(MachineError::NotFound)?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
+    match machine_service.get_machine_by_id(tenant_id, id).await? {
+        Some(machine) => Ok(with_etag(&etag, Json(machine))),
+        None => Err(MachineError::NotFound.into()),
     }
 }
 
+async fn get_machine_docs(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MachineDocsResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let machine_service = MachineService::new(state.database);
+
+    let docs = machine_service.get_machine_docs(tenant_id, id).await?;
+    Ok(Json(docs))
+}
+
 async fn update_machine(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateMachineRequest>,
-) -> Result<Json<MachineResponse>, StatusCode> {
+    ValidatedJson(payload): ValidatedJson<UpdateMachineRequest>,
+) -> Result<Json<MachineResponse>, AppError> {
     // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    payload.check().map_err(AppError::Validation)?;
 
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service.update_machine(tenant_id, id, payload).await {
-        Ok(machine) => Ok(Json(machine)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let machine = machine_service
+        .update_machine(tenant_id, id, payload)
+        .await?;
+    Ok(Json(machine))
 }
 
 async fn delete_machine(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
-    let machine_service = MachineService::new(state.database);
+    let machine_service = MachineService::new(state.database).with_buffer(state.heartbeat_buffer);
 
-    match machine_service.delete_machine(tenant_id, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    machine_service
+        .delete_machine(tenant_id, &caller, id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-async fn update_heartbeat(
+async fn decommission_machine(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<HeartbeatRequest>,
-) -> Result<StatusCode, StatusCode> {
+    ValidatedJson(payload): ValidatedJson<DecommissionMachineRequest>,
+) -> Result<Json<MachineDecommissionResponse>, AppError> {
+    // Validate the request
+    payload.check().map_err(AppError::Validation)?;
+
     let tenant_id = extract_tenant_id(&tenant_context);
-    let machine_service = MachineService::new(state.database);
+    let machine_service = MachineService::new(state.database).with_buffer(state.heartbeat_buffer);
+
+    let decommissioned = machine_service
+        .decommission_machine(tenant_id, &caller, id, payload)
+        .await?;
+    Ok(Json(decommissioned))
+}
 
-    match machine_service
+// Records a heartbeat and answers with the machine's open commands, its configuration when out
+// of date and the interval to report in next
+async fn update_heartbeat(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(mut payload): ValidatedJson<HeartbeatRequest>,
+) -> Result<Json<HeartbeatResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let machine_service =
+        MachineService::new(state.database.clone()).with_buffer(state.heartbeat_buffer.clone());
+    let command_service =
+        MachineCommandService::new(state.database).with_buffer(state.heartbeat_buffer);
+
+    let config_version = payload.config_version;
+    let command_results = payload.command_results.take().unwrap_or_default();
+    machine_service
         .update_heartbeat(tenant_id, id, payload)
+        .await?;
+
+    let response = command_service
+        .heartbeat_response(tenant_id, id, config_version, command_results)
+        .await?;
+    Ok(Json(response))
+}
+
+// Gateways send heartbeats for all machines behind them in one request; with a machine key,
+// each entry must be for the gateway or a machine behind it
+async fn update_heartbeats(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    machine_key: Option<Extension<MachineKeyContext>>,
+    ValidatedJson(payload): ValidatedJson<HeartbeatBatchRequest>,
+) -> Result<Json<HeartbeatBatchResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let gateway_id = machine_key.map(|Extension(key)| key.machine_id);
+    let machine_service = MachineService::new(state.database).with_buffer(state.heartbeat_buffer);
+
+    let response = machine_service
+        .update_heartbeats(tenant_id, gateway_id, payload)
+        .await?;
+    Ok(Json(response))
+}
+
+// Machine command API implementations
+
+async fn list_machine_commands(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ListMachineCommandsQuery>,
+) -> Result<Json<Vec<MachineCommandResponse>>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let command_service = MachineCommandService::new(state.database);
+
+    let commands = command_service.list_commands(tenant_id, id, params).await?;
+    Ok(Json(commands))
+}
+
+async fn create_machine_command(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateMachineCommandRequest>,
+) -> Result<(StatusCode, Json<MachineCommandResponse>), AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let command_service = MachineCommandService::new(state.database);
+
+    let command = command_service
+        .queue_command(tenant_id, &caller, id, payload)
+        .await?;
+    Ok((StatusCode::CREATED, Json(command)))
+}
+
+async fn cancel_machine_command(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((id, command_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<MachineCommandResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let command_service = MachineCommandService::new(state.database);
+
+    let command = command_service
+        .cancel_command(tenant_id, &caller, id, command_id)
+        .await?;
+    Ok(Json(command))
+}
+
+async fn get_machine_config(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MachineConfigResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let command_service = MachineCommandService::new(state.database);
+
+    command_service
+        .get_config(tenant_id, id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("Machine config not found".to_string()))
+}
+
+async fn put_machine_config(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<PutMachineConfigRequest>,
+) -> Result<Json<MachineConfigResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let command_service = MachineCommandService::new(state.database);
+
+    let config = command_service
+        .put_config(tenant_id, &caller, id, payload)
+        .await?;
+    Ok(Json(config))
+}
+
+// Provisioning bundles point machines at MACHINE_API_URL, or else at the host they were
+// requested through
+fn machine_api_url(headers: &HeaderMap) -> Result<String, AppError> {
+    if let Ok(url) = std::env::var("MACHINE_API_URL") {
+        if !url.is_empty() {
+            return Ok(url);
+        }
+    }
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::Validation("Host header required".to_string()))?;
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("http");
+    Ok(format!("{}://{}", scheme, host))
+}
+
+async fn provision_machine(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<MachineProvisioningBundle>), AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let api_url = machine_api_url(&headers)?;
+    let credential_service = MachineCredentialService::new(state.database);
+
+    let bundle = credential_service
+        .provision(tenant_id, &caller, id, &api_url)
+        .await?;
+    Ok((StatusCode::CREATED, Json(bundle)))
+}
+
+async fn list_machine_credentials(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<MachineCredentialResponse>>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let credential_service = MachineCredentialService::new(state.database);
+
+    let credentials = credential_service
+        .list_credentials(tenant_id, &caller, id)
+        .await?;
+    Ok(Json(credentials))
+}
+
+async fn revoke_machine_credential(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MachineCredentialResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let credential_service = MachineCredentialService::new(state.database);
+
+    let credential = credential_service.revoke(tenant_id, &caller, id).await?;
+    Ok(Json(credential))
+}
+
+// Downsampled telemetry readings extracted from heartbeats, for charting
+async fn get_machine_telemetry(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<TelemetryQuery>,
+) -> Result<Json<MachineTelemetryResponse>, StatusCode> {
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params
+        .from
+        .unwrap_or(to - Duration::hours(telemetry::DEFAULT_WINDOW_HOURS));
+    if to <= from || (to - from).num_days() >= telemetry::MAX_WINDOW_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let telemetry_service = TelemetryService::new(state.database);
+
+    match telemetry_service
+        .get_machine_telemetry(
+            tenant_id,
+            id,
+            params.channel,
+            from,
+            to,
+            params.bucket_seconds,
+        )
         .await
     {
-        Ok(_) => Ok(StatusCode::OK),
+        Ok(Some(telemetry)) => Ok(Json(telemetry)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -207,57 +540,43 @@ async fn list_machine_item_relationships(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-) -> Result<Json<Vec<MachineItemRelationshipResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineItemRelationshipResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let relationships = machine_service
         .list_machine_item_relationships(tenant_id, machine_id)
-        .await
-    {
-        Ok(relationships) => Ok(Json(relationships)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(relationships))
 }
 
 async fn create_machine_item_relationship(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-    Json(payload): Json<CreateMachineItemRelationshipRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateMachineItemRelationshipRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let relationship_id = machine_service
         .create_machine_item_relationship(tenant_id, machine_id, payload)
-        .await
-    {
-        Ok(relationship_id) => Ok(Json(serde_json::json!({"id": relationship_id}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(serde_json::json!({"id": relationship_id})))
 }
 
 async fn delete_machine_item_relationship(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(relationship_id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    machine_service
         .delete_machine_item_relationship(tenant_id, relationship_id)
-        .await
-    {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Machine-Asset relationship implementations
@@ -266,57 +585,43 @@ async fn list_machine_asset_relationships(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-) -> Result<Json<Vec<MachineAssetRelationshipResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineAssetRelationshipResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let relationships = machine_service
         .list_machine_asset_relationships(tenant_id, machine_id)
-        .await
-    {
-        Ok(relationships) => Ok(Json(relationships)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(relationships))
 }
 
 async fn create_machine_asset_relationship(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-    Json(payload): Json<CreateMachineAssetRelationshipRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateMachineAssetRelationshipRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let relationship_id = machine_service
         .create_machine_asset_relationship(tenant_id, machine_id, payload)
-        .await
-    {
-        Ok(relationship_id) => Ok(Json(serde_json::json!({"id": relationship_id}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(serde_json::json!({"id": relationship_id})))
 }
 
 async fn delete_machine_asset_relationship(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(relationship_id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    machine_service
         .delete_machine_asset_relationship(tenant_id, relationship_id)
-        .await
-    {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Machine-Operator assignment implementations
@@ -325,57 +630,43 @@ async fn list_machine_operator_assignments(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-) -> Result<Json<Vec<MachineOperatorAssignmentResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineOperatorAssignmentResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let assignments = machine_service
         .list_machine_operator_assignments(tenant_id, machine_id)
-        .await
-    {
-        Ok(assignments) => Ok(Json(assignments)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(assignments))
 }
 
 async fn create_machine_operator_assignment(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-    Json(payload): Json<CreateMachineOperatorAssignmentRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateMachineOperatorAssignmentRequest>,
+) -> Result<Json<MachineOperatorAssignmentCreateResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let assignment = machine_service
         .create_machine_operator_assignment(tenant_id, machine_id, payload)
-        .await
-    {
-        Ok(assignment_id) => Ok(Json(serde_json::json!({"id": assignment_id}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(assignment))
 }
 
 async fn delete_machine_operator_assignment(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(assignment_id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    machine_service
         .delete_machine_operator_assignment(tenant_id, assignment_id)
-        .await
-    {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Machine-Job assignment implementations
@@ -384,80 +675,58 @@ async fn list_machine_job_assignments(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-) -> Result<Json<Vec<MachineJobAssignmentResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineJobAssignmentResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let assignments = machine_service
         .list_machine_job_assignments(tenant_id, machine_id)
-        .await
-    {
-        Ok(assignments) => Ok(Json(assignments)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(assignments))
 }
 
 async fn create_machine_job_assignment(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(machine_id): Path<Uuid>,
-    Json(payload): Json<CreateMachineJobAssignmentRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateMachineJobAssignmentRequest>,
+) -> Result<Json<serde_json::Value>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let assignment_id = machine_service
         .create_machine_job_assignment(tenant_id, machine_id, payload)
-        .await
-    {
-        Ok(assignment_id) => Ok(Json(serde_json::json!({"id": assignment_id}))),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(serde_json::json!({"id": assignment_id})))
 }
 
 async fn update_machine_job_assignment(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(assignment_id): Path<Uuid>,
-    Json(payload): Json<UpdateMachineJobAssignmentRequest>,
-) -> Result<Json<MachineJobAssignmentResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateMachineJobAssignmentRequest>,
+) -> Result<Json<MachineJobAssignmentResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let assignment = machine_service
         .update_machine_job_assignment(tenant_id, assignment_id, payload)
-        .await
-    {
-        Ok(assignment) => Ok(Json(assignment)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(assignment))
 }
 
 async fn delete_machine_job_assignment(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(assignment_id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    machine_service
         .delete_machine_job_assignment(tenant_id, assignment_id)
-        .await
-    {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Utility route implementations
@@ -467,17 +736,14 @@ async fn get_machines_by_item(
     Extension(tenant_context): Extension<TenantContext>,
     Path(item_id): Path<Uuid>,
     Query(params): Query<ListByItemQuery>,
-) -> Result<Json<Vec<MachineResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let machines = machine_service
         .get_machines_by_item_id(tenant_id, item_id, params.relationship_type)
-        .await
-    {
-        Ok(machines) => Ok(Json(machines)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+        .await?;
+    Ok(Json(machines))
 }
 
 async fn get_machines_by_job(
@@ -485,15 +751,225 @@ async fn get_machines_by_job(
     Extension(tenant_context): Extension<TenantContext>,
     Path(job_id): Path<Uuid>,
     Query(params): Query<ListByJobQuery>,
-) -> Result<Json<Vec<MachineResponse>>, StatusCode> {
+) -> Result<Json<Vec<MachineResponse>>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let machine_service = MachineService::new(state.database);
 
-    match machine_service
+    let machines = machine_service
         .get_machines_by_job_id(tenant_id, job_id, params.status)
+        .await?;
+    Ok(Json(machines))
+}
+
+// Capacity planning implementations
+
+async fn get_capacity_plan(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<CapacityQuery>,
+) -> Result<Json<CapacityPlanResponse>, AppError> {
+    let from = params.from.unwrap_or_else(|| Utc::now().date_naive());
+    let to = params
+        .to
+        .unwrap_or(from + Duration::days(DEFAULT_WINDOW_DAYS - 1));
+    if to < from || (to - from).num_days() >= MAX_WINDOW_DAYS {
+        return Err(AppError::Validation(
+            "Invalid capacity planning window".to_string(),
+        ));
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let machine_service = MachineService::new(state.database);
+
+    let plan = machine_service
+        .get_capacity_plan(
+            tenant_id,
+            from,
+            to,
+            params.hours_per_day.unwrap_or(DEFAULT_HOURS_PER_DAY),
+            params.include_weekends.unwrap_or(false),
+        )
+        .await?;
+    Ok(Json(plan))
+}
+
+// Fleet summary implementations
+
+async fn get_fleet_summary(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<FleetSummaryQuery>,
+) -> Result<Json<MachineFleetSummary>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let machine_service = MachineService::new(state.database);
+
+    let summary = machine_service
+        .get_fleet_summary(
+            tenant_id,
+            params.limit.unwrap_or(DEFAULT_FLEET_SUMMARY_LIMIT),
+            params
+                .stale_after_minutes
+                .unwrap_or(DEFAULT_STALE_HEARTBEAT_MINUTES),
+        )
+        .await?;
+    Ok(Json(summary))
+}
+
+// Map view implementations
+
+async fn get_machine_map(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<MachineMapQuery>,
+) -> Result<Json<MachineFeatureCollection>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let machine_service = MachineService::new(state.database);
+
+    let collection = machine_service
+        .get_machine_map(tenant_id, params.status, params.site)
+        .await?;
+    Ok(Json(collection))
+}
+
+// Alert rule implementations
+
+async fn list_alert_rules(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ListAlertRulesQuery>,
+) -> Result<Json<Vec<MachineAlertRuleResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    match alert_service.list_rules(tenant_id, params.machine_id).await {
+        Ok(rules) => Ok(Json(rules)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateMachineAlertRuleRequest>,
+) -> Result<(StatusCode, Json<MachineAlertRuleResponse>), AppError> {
+    // Validate the request
+    payload.check().map_err(AppError::Validation)?;
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    let rule = alert_service
+        .create_rule(tenant_id, &caller, payload)
+        .await
+        .map_err(alert_rule_error)?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+async fn get_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<MachineAlertRuleResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    match alert_service.get_rule(tenant_id, rule_id).await {
+        Ok(Some(rule)) => Ok(Json(rule.into())),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(rule_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateMachineAlertRuleRequest>,
+) -> Result<Json<MachineAlertRuleResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    // The conditions and actions are checked as they will be once the update applies
+    let rule = alert_service
+        .get_rule(tenant_id, rule_id)
+        .await
+        .map_err(AppError::from_service)?
+        .ok_or_else(alert_rule_not_found)?;
+    payload.check(&rule).map_err(AppError::Validation)?;
+
+    alert_service
+        .update_rule(tenant_id, &caller, rule_id, payload)
+        .await
+        .map_err(alert_rule_error)?
+        .map(Json)
+        .ok_or_else(alert_rule_not_found)
+}
+
+async fn delete_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    if alert_service
+        .delete_rule(tenant_id, &caller, rule_id)
         .await
+        .map_err(alert_rule_error)?
     {
-        Ok(machines) => Ok(Json(machines)),
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(alert_rule_not_found())
+    }
+}
+
+// Evaluates a rule against a machine's latest heartbeat without raising an alert
+async fn test_alert_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(rule_id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<TestMachineAlertRuleRequest>,
+) -> Result<Json<MachineAlertRuleTestResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    alert_service
+        .test_rule(tenant_id, rule_id, payload)
+        .await?
+        .map(Json)
+        .ok_or_else(alert_rule_not_found)
+}
+
+async fn list_machine_alerts(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListMachineAlertsQuery>,
+) -> Result<Json<Vec<MachineAlertResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    match alert_service.list_alerts(tenant_id, params).await {
+        Ok(alerts) => Ok(Json(alerts)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_machine_anomalies(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ListTelemetryAnomaliesQuery>,
+) -> Result<Json<Vec<TelemetryAnomalyResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let alert_service = MachineAlertService::new(state.database);
+
+    match alert_service.list_anomalies(tenant_id, id, params).await {
+        Ok(Some(anomalies)) => Ok(Json(anomalies)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -0,0 +1,179 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CreateMachineGroupRequest, MachineGroupHeartbeatQuery, MachineGroupHeartbeatResponse,
+        MachineGroupResponse, MachineGroupUtilizationQuery, MachineGroupUtilizationResponse,
+        UpdateMachineGroupRequest, DEFAULT_STALE_HEARTBEAT_MINUTES,
+        DEFAULT_UTILIZATION_WINDOW_DAYS, MAX_UTILIZATION_WINDOW_DAYS,
+    },
+    services::MachineGroupService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_machine_groups).post(create_machine_group))
+        .route(
+            "/:id",
+            get(get_machine_group)
+                .put(update_machine_group)
+                .delete(delete_machine_group),
+        )
+        // Line-level views over the member machines
+        .route("/:id/heartbeats", get(get_machine_group_heartbeats))
+        .route("/:id/utilization", get(get_machine_group_utilization))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Machine group API implementations
+
+async fn list_machine_groups(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<MachineGroupResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service.list_groups(tenant_id).await {
+        Ok(groups) => Ok(Json(groups)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_machine_group(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateMachineGroupRequest>,
+) -> Result<(StatusCode, Json<MachineGroupResponse>), StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service.create_group(tenant_id, payload).await {
+        Ok(group) => Ok((StatusCode::CREATED, Json(group))),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn get_machine_group(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MachineGroupResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service.get_group(tenant_id, id).await {
+        Ok(Some(group)) => Ok(Json(group)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_machine_group(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateMachineGroupRequest>,
+) -> Result<Json<MachineGroupResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service.update_group(tenant_id, id, payload).await {
+        Ok(Some(group)) => Ok(Json(group)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_machine_group(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service.delete_group(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_machine_group_heartbeats(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<MachineGroupHeartbeatQuery>,
+) -> Result<Json<MachineGroupHeartbeatResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service
+        .get_group_heartbeats(
+            tenant_id,
+            id,
+            params
+                .stale_after_minutes
+                .unwrap_or(DEFAULT_STALE_HEARTBEAT_MINUTES),
+        )
+        .await
+    {
+        Ok(Some(heartbeats)) => Ok(Json(heartbeats)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_machine_group_utilization(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<MachineGroupUtilizationQuery>,
+) -> Result<Json<MachineGroupUtilizationResponse>, StatusCode> {
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params
+        .from
+        .unwrap_or(to - Duration::days(DEFAULT_UTILIZATION_WINDOW_DAYS));
+    if to <= from || (to - from).num_days() > MAX_UTILIZATION_WINDOW_DAYS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let group_service = MachineGroupService::new(state.database);
+
+    match group_service
+        .get_group_utilization(tenant_id, id, from, to)
+        .await
+    {
+        Ok(Some(utilization)) => Ok(Json(utilization)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -1,8 +1,35 @@
+pub mod admin;
 pub mod asset;
 pub mod auth;
+pub mod billing;
+pub mod calendar;
+pub mod consignment;
+pub mod dashboard;
+pub mod frontend;
+pub mod ingest;
 pub mod item;
 pub mod job;
+pub mod kiosk;
 pub mod machine;
+pub mod machine_group;
+pub mod notification;
+pub mod numbering;
 pub mod order;
+pub mod order_return;
 pub mod person;
+pub mod printer;
+pub mod quality;
+pub mod quote;
+pub mod recalculation;
+pub mod report;
+pub mod rfq;
+pub mod search;
+pub mod service_client;
+pub mod shipment;
+pub mod skill;
+pub mod status_page;
+pub mod storage;
 pub mod tenants;
+pub mod trace;
+pub mod validation_rule;
+pub mod watch;
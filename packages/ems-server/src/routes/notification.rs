@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedQuery,
+    models::{
+        CallerContext, ListNotificationsQuery, MarkNotificationsReadResponse,
+        NotificationCountResponse, NotificationResponse,
+    },
+    services::NotificationService,
+    AppState,
+};
+
+/// The caller's notification center, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Notification API routes
+        .route("/", get(list_notifications))
+        .route("/count", get(unread_count))
+        .route("/read-all", post(mark_all_read))
+        .route("/:id/read", post(mark_read))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Notification API implementations
+
+async fn list_notifications(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedQuery(params): ValidatedQuery<ListNotificationsQuery>,
+) -> Result<Json<Vec<NotificationResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let notification_service = NotificationService::new(state.database);
+
+    match notification_service
+        .list_notifications(tenant_id, caller.person_id, params)
+        .await
+    {
+        Ok(notifications) => Ok(Json(notifications)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn unread_count(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<NotificationCountResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let notification_service = NotificationService::new(state.database);
+
+    match notification_service
+        .unread_count(tenant_id, caller.person_id)
+        .await
+    {
+        Ok(unread) => Ok(Json(NotificationCountResponse { unread })),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn mark_read(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<NotificationResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let notification_service = NotificationService::new(state.database);
+
+    match notification_service
+        .mark_read(tenant_id, caller.person_id, id)
+        .await
+    {
+        Ok(Some(notification)) => Ok(Json(notification)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn mark_all_read(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<MarkNotificationsReadResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let notification_service = NotificationService::new(state.database);
+
+    match notification_service
+        .mark_all_read(tenant_id, caller.person_id)
+        .await
+    {
+        Ok(marked) => Ok(Json(MarkNotificationsReadResponse { marked })),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        CallerContext, NextNumberResponse, NumberingEntity, NumberingSequenceResponse,
+        PutNumberingSequenceRequest,
+    },
+    services::NumberingService,
+    utils::AppError,
+    AppState,
+};
+
+/// Numbering sequence API. Orders, jobs, items, quotes and returns created without a number
+/// draw one from their sequence.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Numbering sequence API routes
+        .route("/", get(list_sequences))
+        .route(
+            "/:entity",
+            get(get_sequence).put(put_sequence).delete(delete_sequence),
+        )
+        .route("/:entity/next", post(next_number))
+}
+
+// Helper function to extract tenant ID from tenant context
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps numbering errors to status codes
+fn numbering_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Numbering request failed: {}", error);
+    }
+    error.status()
+}
+
+// Numbering sequence API implementations
+
+async fn list_sequences(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<NumberingSequenceResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let numbering_service = NumberingService::new(state.database);
+
+    match numbering_service.list_sequences(tenant_id).await {
+        Ok(sequences) => Ok(Json(sequences)),
+        Err(e) => Err(numbering_error(e)),
+    }
+}
+
+async fn get_sequence(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(entity): Path<NumberingEntity>,
+) -> Result<Json<NumberingSequenceResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let numbering_service = NumberingService::new(state.database);
+
+    match numbering_service.get_sequence(tenant_id, entity).await {
+        Ok(Some(sequence)) => Ok(Json(sequence)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(numbering_error(e)),
+    }
+}
+
+async fn put_sequence(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(entity): Path<NumberingEntity>,
+    ValidatedJson(payload): ValidatedJson<PutNumberingSequenceRequest>,
+) -> Result<Json<NumberingSequenceResponse>, StatusCode> {
+    // Validate the prefix template
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let numbering_service = NumberingService::new(state.database);
+
+    match numbering_service
+        .put_sequence(tenant_id, &caller, entity, payload)
+        .await
+    {
+        Ok(sequence) => Ok(Json(sequence)),
+        Err(e) => Err(numbering_error(e)),
+    }
+}
+
+async fn delete_sequence(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(entity): Path<NumberingEntity>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let numbering_service = NumberingService::new(state.database);
+
+    match numbering_service
+        .delete_sequence(tenant_id, &caller, entity)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(numbering_error(e)),
+    }
+}
+
+async fn next_number(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(entity): Path<NumberingEntity>,
+) -> Result<Json<NextNumberResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let numbering_service = NumberingService::new(state.database);
+
+    match numbering_service.next_number(tenant_id, entity).await {
+        Ok(Some(number)) => Ok(Json(number)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(numbering_error(e)),
+    }
+}
@@ -1,21 +1,25 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
     Extension, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::etag::{with_etag, Revalidate},
     middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
     models::{
-        CreateOrderIdResponse, CreateOrderRequest, CustomerOrderResponse, DistributorOrderResponse,
-        OrderResponse, OrderStatus, OrderType, PurchaseOrderResponse, UpdateOrderRequest,
+        Claims, CreateOrderIdResponse, CreateOrderRequest, CustomerOrderResponse,
+        DistributorOrderResponse, OrderAnalyticsQuery, OrderAnalyticsResponse, OrderItemResponse,
+        OrderResponse, OrderStatus, OrderType, PurchaseOrderResponse, SendOrderConfirmationRequest,
+        UpdateOrderRequest,
     },
-    services::OrderService,
+    services::{BrandingService, DocumentPackService, EmailService, OrderService, TenantService},
+    utils::{branding::DocumentBranding, errors::AppError, i18n::Locale, zip::safe_file_name},
     AppState,
 };
 
@@ -24,14 +28,24 @@ struct ListQuery {
     #[serde(rename = "type")]
     order_type: Option<OrderType>,
     status: Option<OrderStatus>,
+    /// Also list archived orders
+    #[serde(default)]
+    include_archived: bool,
     limit: Option<u32>,
     offset: Option<u32>,
 }
 
+#[derive(Deserialize)]
+struct DocumentQuery {
+    /// Language of the document; defaults to the tenant's locale
+    locale: Option<Locale>,
+}
+
 pub fn routes() -> Router<AppState> {
     Router::new()
         // General Order API
         .route("/", get(list_all_orders).post(create_order))
+        .route("/analytics", get(get_order_analytics))
         .route(
             "/:id",
             get(get_order_details)
@@ -39,6 +53,15 @@ pub fn routes() -> Router<AppState> {
                 .delete(delete_order),
         )
         .route("/:id/history", get(get_order_history))
+        .route("/:id/archive", post(archive_order))
+        .route("/:id/unarchive", post(unarchive_order))
+        .route("/:id/confirmation", get(download_order_confirmation))
+        .route("/:id/confirmation/send", post(send_order_confirmation))
+        .route("/:id/document-pack", get(download_document_pack))
+        .route(
+            "/:id/items/:line_id/backorder",
+            post(backorder_order_line).delete(clear_order_line_backorder),
+        )
         // Type-specific Order API routes
         .route("/purchase", get(list_purchase_orders))
         .route("/purchase/:id", get(get_purchase_order_details))
@@ -53,27 +76,69 @@ fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
     tenant_context.tenant_id
 }
 
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Helper function to look up the locale customer documents default to
+async fn tenant_locale(state: &AppState, tenant_id: Uuid) -> Result<Locale, StatusCode> {
+    TenantService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .get_locale(tenant_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Helper function to look up the branding customer documents are rendered with
+async fn tenant_branding(
+    state: &AppState,
+    tenant_id: Uuid,
+) -> Result<DocumentBranding, StatusCode> {
+    BrandingService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .document_branding(tenant_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 // General Order API implementations
 
 async fn list_all_orders(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListQuery>,
-) -> Result<Json<Vec<OrderResponse>>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let order_service = OrderService::new(state.database);
 
+    let version = order_service
+        .list_version(
+            tenant_id,
+            params.order_type.clone(),
+            params.status.clone(),
+            params.include_archived,
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let etag = revalidate.etag(tenant_id, &version);
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
     match order_service
         .list_orders(
             tenant_id,
             params.order_type,
             params.status,
+            params.include_archived,
             params.limit,
             params.offset,
         )
         .await
     {
-        Ok(orders) => Ok(Json(orders)),
+        Ok(orders) => Ok(with_etag(&etag, Json(orders))),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -81,19 +146,14 @@ async fn list_all_orders(
 async fn create_order(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreateOrderRequest>,
-) -> Result<Json<CreateOrderIdResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<CreateOrderRequest>,
+) -> Result<Json<CreateOrderIdResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let order_service = OrderService::new(state.database);
 
     match order_service.create_order(tenant_id, payload).await {
         Ok(response) => Ok(Json(response)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(AppError::from_service(e)),
     }
 }
 
@@ -101,12 +161,22 @@ async fn get_order_details(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-) -> Result<Json<OrderResponse>, StatusCode> {
+    revalidate: Revalidate,
+) -> Result<Response, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let order_service = OrderService::new(state.database);
 
+    let etag = match order_service.order_version(tenant_id, id).await {
+        Ok(Some(version)) => revalidate.etag(tenant_id, &version),
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+    if let Some(not_modified) = revalidate.not_modified(&etag) {
+        return Ok(not_modified);
+    }
+
     match order_service.get_order_by_id(tenant_id, id).await {
-        Ok(Some(order)) => Ok(Json(order)),
+        Ok(Some(order)) => Ok(with_etag(&etag, Json(order))),
         Ok(None) => Err(StatusCode::NOT_FOUND),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -116,19 +186,14 @@ async fn update_order(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateOrderRequest>,
-) -> Result<Json<OrderResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdateOrderRequest>,
+) -> Result<Json<OrderResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let order_service = OrderService::new(state.database);
 
     match order_service.update_order(tenant_id, id, payload).await {
         Ok(order) => Ok(Json(order)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(AppError::from_service(e)),
     }
 }
 
@@ -146,6 +211,38 @@ async fn delete_order(
     }
 }
 
+async fn archive_order(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    set_order_archived(state, tenant_context, id, true).await
+}
+
+async fn unarchive_order(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    set_order_archived(state, tenant_context, id, false).await
+}
+
+async fn set_order_archived(
+    state: AppState,
+    tenant_context: TenantContext,
+    id: Uuid,
+    archived: bool,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let order_service = OrderService::new(state.database);
+
+    match order_service.set_archived(tenant_id, id, archived).await {
+        Ok(Some(order)) => Ok(Json(order)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
 async fn get_order_history(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
@@ -160,6 +257,166 @@ async fn get_order_history(
     }
 }
 
+async fn get_order_analytics(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<OrderAnalyticsQuery>,
+) -> Result<Json<OrderAnalyticsResponse>, StatusCode> {
+    if params.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let order_service = OrderService::new(state.database);
+
+    match order_service.order_analytics(tenant_id, params).await {
+        Ok(analytics) => Ok(Json(analytics)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Order confirmation implementations
+
+async fn download_order_confirmation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DocumentQuery>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let locale = match params.locale {
+        Some(locale) => locale,
+        None => tenant_locale(&state, tenant_id).await?,
+    };
+    let branding = tenant_branding(&state, tenant_id).await?;
+    let order_service = OrderService::new(state.database).with_branding(branding);
+
+    match order_service.confirmation_pdf(tenant_id, id, locale).await {
+        Ok(Some((order_number, pdf))) => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}.pdf\"", order_number),
+                ),
+            ],
+            pdf,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Zip of the confirmation, lot batch records, inspection reports and firmware release notes
+async fn download_document_pack(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DocumentQuery>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let locale = match params.locale {
+        Some(locale) => locale,
+        None => tenant_locale(&state, tenant_id).await?,
+    };
+    let branding = tenant_branding(&state, tenant_id).await?;
+    let document_pack_service = DocumentPackService::new(state.database).with_branding(branding);
+
+    match document_pack_service
+        .order_document_pack(tenant_id, id, locale)
+        .await
+    {
+        Ok(Some((order_number, archive))) => Ok((
+            [
+                (header::CONTENT_TYPE, "application/zip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!(
+                        "attachment; filename=\"{}-documents.zip\"",
+                        safe_file_name(&order_number)
+                    ),
+                ),
+            ],
+            archive,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn send_order_confirmation(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SendOrderConfirmationRequest>,
+) -> Result<Json<OrderResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let locale = tenant_locale(&state, tenant_id).await?;
+    let branding = tenant_branding(&state, tenant_id).await?;
+    let order_service = OrderService::new(state.database).with_branding(branding);
+    let email = EmailService::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match order_service
+        .send_confirmation(tenant_id, id, user_id, payload, email.as_ref(), locale)
+        .await
+    {
+        Ok(Some(order)) => Ok(Json(order)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+// Backorder implementations
+
+async fn backorder_order_line(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path((id, line_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<OrderItemResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let order_service = OrderService::new(state.database);
+
+    match order_service
+        .backorder_line(tenant_id, id, line_id, user_id)
+        .await
+    {
+        Ok(Some(line)) => Ok(Json(line)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn clear_order_line_backorder(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path((id, line_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<OrderItemResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let order_service = OrderService::new(state.database);
+
+    match order_service
+        .clear_backorder(tenant_id, id, line_id, user_id)
+        .await
+    {
+        Ok(Some(line)) => Ok(Json(line)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 // Type-specific implementations
 
 async fn list_purchase_orders(
@@ -171,7 +428,12 @@ async fn list_purchase_orders(
     let order_service = OrderService::new(state.database);
 
     match order_service
-        .list_purchase_orders(tenant_id, params.limit, params.offset)
+        .list_purchase_orders(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(orders) => Ok(Json(orders)),
@@ -203,7 +465,12 @@ async fn list_customer_orders(
     let order_service = OrderService::new(state.database);
 
     match order_service
-        .list_customer_orders(tenant_id, params.limit, params.offset)
+        .list_customer_orders(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(orders) => Ok(Json(orders)),
@@ -235,7 +502,12 @@ async fn list_distributor_orders(
     let order_service = OrderService::new(state.database);
 
     match order_service
-        .list_distributor_orders(tenant_id, params.limit, params.offset)
+        .list_distributor_orders(
+            tenant_id,
+            params.include_archived,
+            params.limit,
+            params.offset,
+        )
         .await
     {
         Ok(orders) => Ok(Json(orders)),
@@ -0,0 +1,183 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        Claims, CreateCreditNoteRequest, CreateOrderReturnRequest, DispositionReturnRequest,
+        ListOrderReturnsQuery, OrderReturnResponse, ReceiveReturnRequest,
+    },
+    services::ReturnService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Return routes
+        .route("/", get(list_returns).post(create_return))
+        .route("/:id", get(get_return))
+        .route("/:id/receive", post(receive_return))
+        .route("/:id/cancel", post(cancel_return))
+        // Disposition and credit routes
+        .route("/:id/dispositions", post(disposition_return))
+        .route("/:id/credit-notes", post(add_credit_note))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Maps return errors shared by several endpoints to status codes
+fn return_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Return API implementations
+
+async fn list_returns(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListOrderReturnsQuery>,
+) -> Result<Json<Vec<OrderReturnResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let return_service = ReturnService::new(state.database);
+
+    match return_service.list_returns(tenant_id, params).await {
+        Ok(returns) => Ok(Json(returns)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_return(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateOrderReturnRequest>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let return_service = ReturnService::new(state.database);
+
+    match return_service
+        .create_return(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(return_error(e)),
+    }
+}
+
+async fn get_return(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let return_service = ReturnService::new(state.database);
+
+    match return_service.get_return(tenant_id, id).await {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn receive_return(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ReceiveReturnRequest>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let return_service = ReturnService::new(state.database);
+
+    match return_service.receive_return(tenant_id, id, payload).await {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(return_error(e)),
+    }
+}
+
+async fn cancel_return(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let return_service = ReturnService::new(state.database);
+
+    match return_service.cancel_return(tenant_id, id).await {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(return_error(e)),
+    }
+}
+
+// Disposition and credit API implementations
+
+async fn disposition_return(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<DispositionReturnRequest>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let return_service = ReturnService::new(state.database);
+
+    match return_service
+        .disposition_return(tenant_id, Some(person_id), id, payload)
+        .await
+    {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(return_error(e)),
+    }
+}
+
+async fn add_credit_note(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateCreditNoteRequest>,
+) -> Result<Json<OrderReturnResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let return_service = ReturnService::new(state.database);
+
+    match return_service.add_credit_note(tenant_id, id, payload).await {
+        Ok(Some(order_return)) => Ok(Json(order_return)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(return_error(e)),
+    }
+}
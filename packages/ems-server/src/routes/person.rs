@@ -1,22 +1,26 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
     response::Json,
-    routing::get,
+    routing::{get, post},
     Extension, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
+    middleware::limits::MAX_IMPORT_BYTES,
     middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
     models::{
-        CreatePersonIdResponse, CreatePersonRequest, CustomerPersonResponse,
-        DistributorPersonResponse, InternalPersonResponse, PersonResponse, PersonRole,
-        UpdatePersonRequest, VendorPersonResponse,
+        CallerContext, CreatePersonIdResponse, CreatePersonRequest, CustomerPersonResponse,
+        DistributorPersonResponse, DuplicateMatch, DuplicatesQuery, ImportPersonsRequest,
+        ImportPersonsResponse, InternalPersonResponse, MergeRequest, MergeResponse, PersonResponse,
+        PersonRole, UpdatePersonRequest, VendorPersonResponse, VendorScorecardQuery,
+        VendorScorecardResponse,
     },
-    services::PersonService,
+    services::{DuplicateService, EmailService, PersonService, ScorecardService},
+    utils::AppError,
     AppState,
 };
 
@@ -38,6 +42,10 @@ pub fn routes() -> Router<AppState> {
                 .put(update_person)
                 .delete(delete_person),
         )
+        .route(
+            "/import",
+            post(import_persons).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
         // Specialized Person API routes
         .route("/internal", get(list_internal_persons))
         .route(
@@ -54,11 +62,15 @@ pub fn routes() -> Router<AppState> {
             "/vendor/:id",
             get(get_vendor_person_details).put(update_vendor_person),
         )
+        .route("/vendor/:id/scorecard", get(get_vendor_scorecard))
         .route("/distributor", get(list_distributor_persons))
         .route(
             "/distributor/:id",
             get(get_distributor_person_details).put(update_distributor_person),
         )
+        // Duplicate detection API routes
+        .route("/duplicates", get(list_duplicate_persons))
+        .route("/merge", post(merge_persons))
 }
 
 // Helper function to extract tenant ID from request extensions
@@ -88,13 +100,8 @@ async fn list_all_persons(
 async fn create_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
-    Json(payload): Json<CreatePersonRequest>,
+    ValidatedJson(payload): ValidatedJson<CreatePersonRequest>,
 ) -> Result<Json<CreatePersonIdResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
@@ -122,34 +129,51 @@ async fn get_person_details(
 async fn update_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePersonRequest>,
-) -> Result<Json<PersonResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    ValidatedJson(payload): ValidatedJson<UpdatePersonRequest>,
+) -> Result<Json<PersonResponse>, AppError> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
-    match person_service.update_person(tenant_id, id, payload).await {
-        Ok(person) => Ok(Json(person)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let person = person_service
+        .update_person(tenant_id, &caller, id, payload)
+        .await
+        .map_err(AppError::from_service)?;
+    Ok(Json(person))
 }
 
 async fn delete_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_service = PersonService::new(state.database).with_cache(state.access_level_cache);
+
+    person_service
+        .delete_person(tenant_id, &caller, id)
+        .await
+        .map_err(AppError::from_service)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn import_persons(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<ImportPersonsRequest>,
+) -> Result<Json<ImportPersonsResponse>, StatusCode> {
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
+    let email = EmailService::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    match person_service.delete_person(tenant_id, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    match person_service
+        .import_persons(tenant_id, payload, email.as_ref())
+        .await
+    {
+        Ok(summary) => Ok(Json(summary)),
+        Err(e) => Err(AppError::from_service(e).status()),
     }
 }
 
@@ -193,13 +217,8 @@ async fn update_internal_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePersonRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdatePersonRequest>,
 ) -> Result<Json<InternalPersonResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
@@ -261,13 +280,8 @@ async fn update_customer_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePersonRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdatePersonRequest>,
 ) -> Result<Json<CustomerPersonResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
@@ -326,13 +340,8 @@ async fn update_vendor_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePersonRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdatePersonRequest>,
 ) -> Result<Json<VendorPersonResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
@@ -352,6 +361,29 @@ async fn update_vendor_person(
     }
 }
 
+async fn get_vendor_scorecard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<VendorScorecardQuery>,
+) -> Result<Json<VendorScorecardResponse>, StatusCode> {
+    if params.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let scorecard_service = ScorecardService::new(state.database);
+
+    match scorecard_service
+        .vendor_scorecard(tenant_id, id, params)
+        .await
+    {
+        Ok(Some(scorecard)) => Ok(Json(scorecard)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 async fn list_distributor_persons(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
@@ -391,13 +423,8 @@ async fn update_distributor_person(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdatePersonRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdatePersonRequest>,
 ) -> Result<Json<DistributorPersonResponse>, StatusCode> {
-    // Validate the request
-    if let Err(_) = payload.validate() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let tenant_id = extract_tenant_id(&tenant_context);
     let person_service = PersonService::new(state.database);
 
@@ -419,3 +446,46 @@ async fn update_distributor_person(
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
+
+// Duplicate detection API implementations
+
+async fn list_duplicate_persons(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<DuplicatesQuery>,
+) -> Result<Json<Vec<DuplicateMatch>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let duplicate_service = DuplicateService::new(state.database);
+
+    match duplicate_service
+        .find_person_duplicates(tenant_id, params)
+        .await
+    {
+        Ok(matches) => Ok(Json(matches)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn merge_persons(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<MergeRequest>,
+) -> Result<Json<MergeResponse>, AppError> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(AppError::Validation("Invalid merge request".to_string()));
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let duplicate_service = DuplicateService::new(state.database);
+
+    match duplicate_service
+        .merge_persons(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(Some(merged)) => Ok(Json(merged)),
+        Ok(None) => Err(AppError::NotFound("Person not found".to_string())),
+        Err(e) => Err(AppError::from_service(e)),
+    }
+}
@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CallerContext, CreatePrinterRequest, ListPrintJobsQuery, Permission, PrintJobResponse,
+        PrinterResponse, UpdatePrinterRequest,
+    },
+    services::{PrintError, PrintService},
+    utils::AppError,
+    AppState,
+};
+
+#[derive(Deserialize)]
+struct ListPrintersQuery {
+    location: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Printer configuration routes
+        .route("/", get(list_printers).post(create_printer))
+        .route(
+            "/:id",
+            get(get_printer).put(update_printer).delete(delete_printer),
+        )
+        // Print queue routes
+        .route("/jobs", get(list_print_jobs))
+        .route("/jobs/:id", get(get_print_job))
+        .route("/jobs/:id/retry", post(retry_print_job))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Printer API implementations
+
+async fn list_printers(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ListPrintersQuery>,
+) -> Result<Json<Vec<PrinterResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service
+        .list_printers(tenant_id, params.location)
+        .await
+    {
+        Ok(printers) => Ok(Json(printers)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_printer(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreatePrinterRequest>,
+) -> Result<Json<PrinterResponse>, AppError> {
+    // Print jobs are sent to whatever host and port the printer names
+    caller
+        .require(Permission::ManagePrinters)
+        .map_err(AppError::from_service)?;
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    let printer = print_service
+        .create_printer(tenant_id, payload)
+        .await
+        .map_err(AppError::from_service)?;
+    Ok(Json(printer))
+}
+
+async fn get_printer(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PrinterResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service.get_printer(tenant_id, id).await {
+        Ok(Some(printer)) => Ok(Json(printer)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_printer(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdatePrinterRequest>,
+) -> Result<Json<PrinterResponse>, AppError> {
+    caller
+        .require(Permission::ManagePrinters)
+        .map_err(AppError::from_service)?;
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    print_service
+        .update_printer(tenant_id, id, payload)
+        .await
+        .map_err(AppError::from_service)?
+        .map(Json)
+        .ok_or_else(|| PrintError::PrinterNotFound.into())
+}
+
+async fn delete_printer(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service.delete_printer(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Print queue API implementations
+
+async fn list_print_jobs(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListPrintJobsQuery>,
+) -> Result<Json<Vec<PrintJobResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service.list_jobs(tenant_id, params).await {
+        Ok(jobs) => Ok(Json(jobs)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_print_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PrintJobResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service.get_job(tenant_id, id).await {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn retry_print_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PrintJobResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let print_service = PrintService::new(state.database);
+
+    match print_service.retry_job(tenant_id, id).await {
+        Ok(Some(job)) => Ok(Json(job)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
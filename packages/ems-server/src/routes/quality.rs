@@ -0,0 +1,585 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post, put},
+    Extension, Router,
+};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        AttachCalibrationCertificateRequest, CalibrationRecordResponse, Claims,
+        CreateCapaActionRequest, CreateInstrumentRequest, CreateItemCharacteristicRequest,
+        CreateNcrRequest, EquipmentCalibrationResponse, InstrumentResponse,
+        ItemCharacteristicResponse, ListCalibrationsQuery, ListEquipmentCalibrationQuery,
+        ListInstrumentsQuery, ListItemCharacteristicsQuery, ListMeasurementsQuery, ListNcrsQuery,
+        ListOverdueCapaQuery, NcrResponse, OverdueCapaResponse, QualityDashboardResponse,
+        RecordCalibrationRequest, RecordMeasurementsRequest, SpcMeasurementResponse, SpcQuery,
+        SpcStatisticsResponse, UpdateCapaActionRequest, UpdateInstrumentRequest,
+        UpdateItemCharacteristicRequest, UpdateNcrRequest,
+    },
+    services::{CalibrationService, QualityError, QualityService, SpcService},
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // NCR routes
+        .route("/ncrs", get(list_ncrs).post(create_ncr))
+        .route("/ncrs/:id", get(get_ncr).put(update_ncr))
+        .route("/ncrs/:id/close", post(close_ncr))
+        .route("/ncrs/:id/cancel", post(cancel_ncr))
+        // CAPA routes
+        .route("/ncrs/:id/actions", post(add_action))
+        .route("/ncrs/:id/actions/:action_id", put(update_action))
+        .route("/capas/overdue", get(list_overdue_capas))
+        .route("/dashboard", get(get_dashboard))
+        // SPC routes
+        .route(
+            "/characteristics",
+            get(list_characteristics).post(create_characteristic),
+        )
+        .route(
+            "/characteristics/:id",
+            get(get_characteristic)
+                .put(update_characteristic)
+                .delete(delete_characteristic),
+        )
+        .route(
+            "/characteristics/:id/measurements",
+            get(list_measurements).post(record_measurements),
+        )
+        .route("/characteristics/:id/spc", get(get_spc_statistics))
+        // Calibration routes
+        .route(
+            "/instruments",
+            get(list_instruments).post(create_instrument),
+        )
+        .route(
+            "/instruments/:id",
+            get(get_instrument)
+                .put(update_instrument)
+                .delete(delete_instrument),
+        )
+        .route(
+            "/calibrations",
+            get(list_calibrations).post(record_calibration),
+        )
+        .route("/calibrations/equipment", get(list_equipment_calibration))
+        .route(
+            "/calibrations/:id/certificate",
+            put(attach_calibration_certificate),
+        )
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Maps NCR, CAPA, SPC and calibration errors shared by several endpoints to status codes
+fn quality_error(e: anyhow::Error) -> StatusCode {
+    // A reference the database refuses is the caller's to fix
+    if let Some(DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) =
+        e.downcast_ref::<DieselError>()
+    {
+        return StatusCode::BAD_REQUEST;
+    }
+    AppError::from_service(e).status()
+}
+
+// The record an endpoint acts on exists, so a reference it names that does not is a bad request
+fn reference_error(e: anyhow::Error) -> StatusCode {
+    match e.downcast_ref::<QualityError>() {
+        Some(QualityError::ReferenceNotFound(..)) => StatusCode::BAD_REQUEST,
+        _ => AppError::from_service(e).status(),
+    }
+}
+
+// NCR API implementations
+
+async fn list_ncrs(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListNcrsQuery>,
+) -> Result<Json<Vec<NcrResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.list_ncrs(tenant_id, params).await {
+        Ok(ncrs) => Ok(Json(ncrs)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_ncr(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateNcrRequest>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service
+        .create_ncr(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(ncr) => Ok(Json(ncr)),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn get_ncr(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.get_ncr(tenant_id, id).await {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_ncr(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateNcrRequest>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.update_ncr(tenant_id, id, payload).await {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn close_ncr(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service
+        .close_ncr(tenant_id, id, Some(person_id))
+        .await
+    {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn cancel_ncr(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.cancel_ncr(tenant_id, id).await {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+// CAPA API implementations
+
+async fn add_action(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CreateCapaActionRequest>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.add_action(tenant_id, id, payload).await {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn update_action(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path((id, action_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<UpdateCapaActionRequest>,
+) -> Result<Json<NcrResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service
+        .update_action(tenant_id, id, action_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(ncr)) => Ok(Json(ncr)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn list_overdue_capas(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListOverdueCapaQuery>,
+) -> Result<Json<Vec<OverdueCapaResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.list_overdue_capas(tenant_id, params).await {
+        Ok(overdue) => Ok(Json(overdue)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_dashboard(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<QualityDashboardResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quality_service = QualityService::new(state.database);
+
+    match quality_service.dashboard(tenant_id).await {
+        Ok(dashboard) => Ok(Json(dashboard)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// SPC API implementations
+
+async fn list_characteristics(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListItemCharacteristicsQuery>,
+) -> Result<Json<Vec<ItemCharacteristicResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.list_characteristics(tenant_id, params).await {
+        Ok(characteristics) => Ok(Json(characteristics)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_characteristic(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateItemCharacteristicRequest>,
+) -> Result<(StatusCode, Json<ItemCharacteristicResponse>), StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.create_characteristic(tenant_id, payload).await {
+        Ok(characteristic) => Ok((StatusCode::CREATED, Json(characteristic))),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn get_characteristic(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ItemCharacteristicResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.get_characteristic(tenant_id, id).await {
+        Ok(Some(characteristic)) => Ok(Json(characteristic)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_characteristic(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateItemCharacteristicRequest>,
+) -> Result<Json<ItemCharacteristicResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service
+        .update_characteristic(tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(characteristic)) => Ok(Json(characteristic)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn delete_characteristic(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.delete_characteristic(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_measurements(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<ListMeasurementsQuery>,
+) -> Result<Json<Vec<SpcMeasurementResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.list_measurements(tenant_id, id, params).await {
+        Ok(Some(measurements)) => Ok(Json(measurements)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn record_measurements(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<RecordMeasurementsRequest>,
+) -> Result<(StatusCode, Json<Vec<SpcMeasurementResponse>>), StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service
+        .record_measurements(tenant_id, id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(measurements)) => Ok((StatusCode::CREATED, Json(measurements))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(reference_error(e)),
+    }
+}
+
+async fn get_spc_statistics(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(params): ValidatedQuery<SpcQuery>,
+) -> Result<Json<SpcStatisticsResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let spc_service = SpcService::new(state.database);
+
+    match spc_service.get_statistics(tenant_id, id, params).await {
+        Ok(Some(statistics)) => Ok(Json(statistics)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Calibration API implementations
+
+async fn list_instruments(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListInstrumentsQuery>,
+) -> Result<Json<Vec<InstrumentResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .list_instruments(tenant_id, params)
+        .await
+    {
+        Ok(instruments) => Ok(Json(instruments)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_instrument(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateInstrumentRequest>,
+) -> Result<(StatusCode, Json<InstrumentResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .create_instrument(tenant_id, payload)
+        .await
+    {
+        Ok(instrument) => Ok((StatusCode::CREATED, Json(instrument))),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn get_instrument(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<InstrumentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service.get_instrument(tenant_id, id).await {
+        Ok(Some(instrument)) => Ok(Json(instrument)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_instrument(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateInstrumentRequest>,
+) -> Result<Json<InstrumentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .update_instrument(tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(instrument)) => Ok(Json(instrument)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quality_error(e)),
+    }
+}
+
+async fn delete_instrument(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service.delete_instrument(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_calibrations(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListCalibrationsQuery>,
+) -> Result<Json<Vec<CalibrationRecordResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .list_calibrations(tenant_id, params)
+        .await
+    {
+        Ok(records) => Ok(Json(records)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn record_calibration(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<RecordCalibrationRequest>,
+) -> Result<(StatusCode, Json<CalibrationRecordResponse>), StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .record_calibration(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(record) => Ok((StatusCode::CREATED, Json(record))),
+        Err(e) => Err(reference_error(e)),
+    }
+}
+
+async fn list_equipment_calibration(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListEquipmentCalibrationQuery>,
+) -> Result<Json<Vec<EquipmentCalibrationResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service.list_equipment(tenant_id, params).await {
+        Ok(equipment) => Ok(Json(equipment)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn attach_calibration_certificate(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<AttachCalibrationCertificateRequest>,
+) -> Result<Json<CalibrationRecordResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let calibration_service = CalibrationService::new(state.database);
+
+    match calibration_service
+        .attach_certificate(tenant_id, id, payload)
+        .await
+    {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(reference_error(e)),
+    }
+}
@@ -0,0 +1,265 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        Claims, ConvertQuoteRequest, CreateQuoteRequest, ListQuotesQuery, QuoteResponse,
+        RejectQuoteRequest, SendQuoteRequest, UpdateQuoteRequest,
+    },
+    services::{BrandingService, EmailService, QuoteService, TenantService},
+    utils::{branding::DocumentBranding, i18n::Locale, AppError},
+    AppState,
+};
+
+#[derive(Deserialize)]
+struct DocumentQuery {
+    /// Language of the document; defaults to the tenant's locale
+    locale: Option<Locale>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Quote routes
+        .route("/", get(list_quotes).post(create_quote))
+        .route("/:id", get(get_quote).put(update_quote))
+        .route("/:id/pdf", get(download_quote_pdf))
+        // Customer response routes
+        .route("/:id/send", post(send_quote))
+        .route("/:id/accept", post(accept_quote))
+        .route("/:id/reject", post(reject_quote))
+        .route("/:id/convert", post(convert_quote))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Helper function to look up the locale customer documents default to
+async fn tenant_locale(state: &AppState, tenant_id: Uuid) -> Result<Locale, StatusCode> {
+    TenantService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .get_locale(tenant_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Helper function to look up the branding customer documents are rendered with
+async fn tenant_branding(
+    state: &AppState,
+    tenant_id: Uuid,
+) -> Result<DocumentBranding, StatusCode> {
+    BrandingService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .document_branding(tenant_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Maps quote errors shared by several endpoints to status codes
+fn quote_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Quote API implementations
+
+async fn list_quotes(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListQuotesQuery>,
+) -> Result<Json<Vec<QuoteResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service.list_quotes(tenant_id, params).await {
+        Ok(quotes) => Ok(Json(quotes)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateQuoteRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service
+        .create_quote(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
+
+async fn get_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service.get_quote(tenant_id, id).await {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateQuoteRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service.update_quote(tenant_id, id, payload).await {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
+
+async fn download_quote_pdf(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DocumentQuery>,
+) -> Result<Response, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let locale = match params.locale {
+        Some(locale) => locale,
+        None => tenant_locale(&state, tenant_id).await?,
+    };
+    let branding = tenant_branding(&state, tenant_id).await?;
+    let quote_service = QuoteService::new(state.database).with_branding(branding);
+
+    match quote_service.quote_pdf(tenant_id, id, locale).await {
+        Ok(Some((quote_number, pdf))) => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}.pdf\"", quote_number),
+                ),
+            ],
+            pdf,
+        )
+            .into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Customer response API implementations
+
+async fn send_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SendQuoteRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let locale = tenant_locale(&state, tenant_id).await?;
+    let branding = tenant_branding(&state, tenant_id).await?;
+    let quote_service = QuoteService::new(state.database).with_branding(branding);
+    let email = EmailService::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match quote_service
+        .send_quote(tenant_id, id, payload, email.as_ref(), locale)
+        .await
+    {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
+
+async fn accept_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service.accept_quote(tenant_id, id).await {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
+
+async fn reject_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<RejectQuoteRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service.reject_quote(tenant_id, id, payload).await {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
+
+async fn convert_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<ConvertQuoteRequest>,
+) -> Result<Json<QuoteResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let quote_service = QuoteService::new(state.database);
+
+    match quote_service
+        .convert_quote(tenant_id, person_id, id, payload)
+        .await
+    {
+        Ok(Some(quote)) => Ok(Json(quote)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(quote_error(e)),
+    }
+}
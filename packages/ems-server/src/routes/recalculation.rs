@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CallerContext, CreateRecalculationRequest, ListRecalculationsQuery,
+        RecalculationTaskResponse,
+    },
+    services::RecalculationService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Recalculation task API routes
+        .route("/", get(list_tasks).post(enqueue_task))
+        .route("/:id", get(get_task))
+        .route("/:id/cancel", post(cancel_task))
+}
+
+// Helper function to extract tenant ID from tenant context
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps recalculation errors to status codes
+fn recalculation_error(e: anyhow::Error) -> StatusCode {
+    // A task of the same kind already queued or running is a unique violation, so a 409
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Recalculation request failed: {}", error);
+    }
+    error.status()
+}
+
+// Recalculation task API implementations
+
+async fn list_tasks(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedQuery(params): ValidatedQuery<ListRecalculationsQuery>,
+) -> Result<Json<Vec<RecalculationTaskResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let recalculation_service = RecalculationService::new(state.database);
+
+    match recalculation_service.list(tenant_id, &caller, params).await {
+        Ok(tasks) => Ok(Json(tasks)),
+        Err(e) => Err(recalculation_error(e)),
+    }
+}
+
+// Tasks run in the background; the response is the queued task to poll for progress
+async fn enqueue_task(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateRecalculationRequest>,
+) -> Result<(StatusCode, Json<RecalculationTaskResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let recalculation_service = RecalculationService::new(state.database);
+
+    match recalculation_service
+        .enqueue(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(task) => Ok((StatusCode::ACCEPTED, Json(task))),
+        Err(e) => Err(recalculation_error(e)),
+    }
+}
+
+async fn get_task(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RecalculationTaskResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let recalculation_service = RecalculationService::new(state.database);
+
+    match recalculation_service.get(tenant_id, &caller, id).await {
+        Ok(Some(task)) => Ok(Json(task)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(recalculation_error(e)),
+    }
+}
+
+async fn cancel_task(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RecalculationTaskResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let recalculation_service = RecalculationService::new(state.database);
+
+    match recalculation_service.cancel(tenant_id, &caller, id).await {
+        Ok(Some(task)) => Ok(Json(task)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(recalculation_error(e)),
+    }
+}
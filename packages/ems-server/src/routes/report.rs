@@ -0,0 +1,252 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        Claims, CreateReportScheduleIdResponse, CreateReportScheduleRequest, ReportDefinition,
+        ReportFormat, ReportKind, ReportParameters, ReportScheduleResponse,
+        UpdateReportScheduleRequest,
+    },
+    services::{csv_header, csv_rows, EmailService, ReportScheduleService, ReportService},
+    utils::streaming::{stream_lines, STREAM_PAGE_SIZE},
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_reports))
+        // Scheduled report delivery
+        .route("/schedules", get(list_schedules).post(create_schedule))
+        .route(
+            "/schedules/:id",
+            get(get_schedule)
+                .put(update_schedule)
+                .delete(delete_schedule),
+        )
+        .route("/schedules/:id/run", post(run_schedule))
+        .route("/:key", get(run_report))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+async fn list_reports(
+    Extension(_tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<ReportDefinition>>, StatusCode> {
+    Ok(Json(ReportService::list_reports()))
+}
+
+async fn run_report(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(key): Path<String>,
+    Query(mut params): Query<HashMap<String, String>>,
+) -> Result<Response, StatusCode> {
+    let definition = ReportService::find_report(&key).ok_or(StatusCode::NOT_FOUND)?;
+
+    let format = match params.remove("format") {
+        Some(format) => ReportFormat::try_from(format).map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => ReportFormat::Json,
+    };
+
+    let parameters =
+        ReportParameters::parse(&definition, &params).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let kind = definition.key;
+
+    match format {
+        ReportFormat::Json => {
+            let report_service = ReportService::new(state.database);
+            let result = report_service
+                .run_report(tenant_id, kind, parameters)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Ok(Json(result).into_response())
+        }
+        ReportFormat::Csv => {
+            let generated_at = Utc::now();
+            let filename = format!("{}_{}.csv", kind, generated_at.format("%Y%m%d%H%M%S"));
+
+            // Exports are sent a page at a time rather than loaded whole
+            let database = state.database;
+            let columns = definition.columns;
+            let body = stream_lines(
+                tenant_id,
+                STREAM_PAGE_SIZE,
+                csv_header(&columns),
+                move |limit, offset| {
+                    let report_service = ReportService::new(database.clone());
+                    let (parameters, columns) = (parameters.clone(), columns.clone());
+                    async move {
+                        let rows = report_service
+                            .report_page(tenant_id, kind, &parameters, generated_at, limit, offset)
+                            .await?;
+                        Ok(csv_rows(&columns, &rows))
+                    }
+                },
+            )
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                body,
+            )
+                .into_response())
+        }
+    }
+}
+
+// Report schedule API implementations
+
+async fn list_schedules(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<ReportScheduleResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    match schedule_service.list_schedules(tenant_id).await {
+        Ok(schedules) => Ok(Json(schedules)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_schedule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateReportScheduleRequest>,
+) -> Result<Json<CreateReportScheduleIdResponse>, StatusCode> {
+    let definition = ReportService::definition(payload.report);
+    if payload.check(&definition).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let user_id = extract_user_id(&claims)?;
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    match schedule_service
+        .create_schedule(tenant_id, user_id, payload)
+        .await
+    {
+        Ok(response) => Ok(Json(response)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn get_schedule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ReportScheduleResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    match schedule_service.get_schedule(tenant_id, id).await {
+        Ok(Some(schedule)) => Ok(Json(schedule.into())),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_schedule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateReportScheduleRequest>,
+) -> Result<Json<ReportScheduleResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    let schedule = match schedule_service.get_schedule(tenant_id, id).await {
+        Ok(Some(schedule)) => schedule,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let kind = ReportKind::try_from(schedule.report_key.clone())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if payload.check(&ReportService::definition(kind)).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    match schedule_service
+        .update_schedule(tenant_id, schedule, payload)
+        .await
+    {
+        Ok(schedule) => Ok(Json(schedule)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn delete_schedule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    match schedule_service.delete_schedule(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Delivers a schedule immediately, regardless of its next run time
+async fn run_schedule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ReportScheduleResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let schedule_service = ReportScheduleService::new(state.database);
+
+    let schedule = match schedule_service.get_schedule(tenant_id, id).await {
+        Ok(Some(schedule)) => schedule,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    let email = EmailService::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if schedule_service
+        .deliver_schedule(&schedule, email.as_ref())
+        .await
+        .is_err()
+    {
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    match schedule_service.get_schedule(tenant_id, id).await {
+        Ok(Some(schedule)) => Ok(Json(schedule.into())),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,298 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post, put},
+    Extension, Router,
+};
+use std::env;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        AwardRfqRequest, Claims, CreateRfqRequest, DeclineRfqRequest, ListRfqsQuery,
+        RfqComparisonResponse, RfqInvitationResponse, RfqResponse, SendRfqRequest,
+        SubmitRfqQuoteRequest, UpdateRfqRequest,
+    },
+    services::{EmailService, RfqService, TenantService},
+    utils::{errors::AppError, i18n::Locale},
+    AppState,
+};
+
+/// RFQ management API, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // RFQ routes
+        .route("/", get(list_rfqs).post(create_rfq))
+        .route("/:id", get(get_rfq).put(update_rfq))
+        .route("/:id/cancel", post(cancel_rfq))
+        // Vendor quote routes
+        .route("/:id/send", post(send_rfq))
+        .route("/:id/vendors/:vendor_id/quote", put(record_quote))
+        .route("/:id/compare", get(compare_rfq))
+        .route("/:id/award", post(award_rfq))
+}
+
+/// Vendor response endpoints. Vendors authenticate with the token from their emailed link,
+/// which also decides the tenant, so this is mounted without the auth middleware.
+pub fn response_routes() -> Router<AppState> {
+    Router::new()
+        .route("/:token", get(get_invitation).post(submit_quote))
+        .route("/:token/decline", post(decline_rfq))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Helper function to look up the locale vendor emails default to
+async fn tenant_locale(state: &AppState, tenant_id: Uuid) -> Result<Locale, StatusCode> {
+    TenantService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .get_locale(tenant_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// Maps RFQ errors shared by several endpoints to status codes
+fn rfq_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// RFQ API implementations
+
+async fn list_rfqs(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListRfqsQuery>,
+) -> Result<Json<Vec<RfqResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service.list_rfqs(tenant_id, params).await {
+        Ok(rfqs) => Ok(Json(rfqs)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateRfqRequest>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service
+        .create_rfq(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(rfq) => Ok(Json(rfq)),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+async fn get_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service.get_rfq(tenant_id, id).await {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateRfqRequest>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service.update_rfq(tenant_id, id, payload).await {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+async fn cancel_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service.cancel_rfq(tenant_id, id).await {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+// Vendor quote API implementations
+
+async fn send_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<SendRfqRequest>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let locale = tenant_locale(&state, tenant_id).await?;
+    let rfq_service = RfqService::new(state.database);
+    let email = EmailService::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Page vendors respond on, the token appended, e.g. https://ems.example.com/rfq-response?token=
+    let response_url = env::var("RFQ_RESPONSE_URL")
+        .ok()
+        .filter(|url| !url.is_empty());
+
+    match rfq_service
+        .send_rfq(
+            tenant_id,
+            id,
+            payload,
+            email.as_ref(),
+            response_url.as_deref(),
+            locale,
+        )
+        .await
+    {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+async fn record_quote(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((id, vendor_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<SubmitRfqQuoteRequest>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service
+        .record_quote(tenant_id, id, vendor_id, payload)
+        .await
+    {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+async fn compare_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RfqComparisonResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service.compare_rfq(tenant_id, id).await {
+        Ok(Some(comparison)) => Ok(Json(comparison)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn award_rfq(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<AwardRfqRequest>,
+) -> Result<Json<RfqResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let rfq_service = RfqService::new(state.database);
+
+    match rfq_service
+        .award_rfq(tenant_id, person_id, id, payload)
+        .await
+    {
+        Ok(Some(rfq)) => Ok(Json(rfq)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(rfq_error(e)),
+    }
+}
+
+// Vendor response API implementations
+
+async fn get_invitation(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<RfqInvitationResponse>, AppError> {
+    let rfq_service = RfqService::new(state.database).with_cache(state.tenant_cache);
+
+    match rfq_service.invitation(&token).await {
+        Ok(Some(invitation)) => Ok(Json(invitation)),
+        Ok(None) => Err(AppError::NotFound("RFQ not found".to_string())),
+        Err(e) => Err(AppError::from_service(e)),
+    }
+}
+
+async fn submit_quote(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    ValidatedJson(payload): ValidatedJson<SubmitRfqQuoteRequest>,
+) -> Result<Json<RfqInvitationResponse>, AppError> {
+    payload.check().map_err(AppError::Validation)?;
+
+    let rfq_service = RfqService::new(state.database).with_cache(state.tenant_cache);
+
+    match rfq_service.submit_quote(&token, payload).await {
+        Ok(Some(invitation)) => Ok(Json(invitation)),
+        Ok(None) => Err(AppError::NotFound("RFQ not found".to_string())),
+        Err(e) => Err(AppError::from_service(e)),
+    }
+}
+
+async fn decline_rfq(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    ValidatedJson(payload): ValidatedJson<DeclineRfqRequest>,
+) -> Result<Json<RfqInvitationResponse>, AppError> {
+    let rfq_service = RfqService::new(state.database).with_cache(state.tenant_cache);
+
+    match rfq_service.decline(&token, payload).await {
+        Ok(Some(invitation)) => Ok(Json(invitation)),
+        Ok(None) => Err(AppError::NotFound("RFQ not found".to_string())),
+        Err(e) => Err(AppError::from_service(e)),
+    }
+}
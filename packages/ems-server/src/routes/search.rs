@@ -0,0 +1,38 @@
+use axum::{extract::State, http::StatusCode, response::Json, routing::get, Extension, Router};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedQuery,
+    models::{SuggestQuery, Suggestion},
+    services::SearchService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Typeahead routes
+        .route("/suggest", get(suggest))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Search API implementations
+
+async fn suggest(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<SuggestQuery>,
+) -> Result<Json<Vec<Suggestion>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let search_service = SearchService::new(state.database);
+
+    match search_service.suggest(tenant_id, params).await {
+        Ok(suggestions) => Ok(Json(suggestions)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
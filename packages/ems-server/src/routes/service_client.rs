@@ -0,0 +1,171 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        CallerContext, CreateServiceClientRequest, ServiceClientResponse,
+        ServiceClientSecretResponse, UpdateServiceClientRequest,
+    },
+    services::ServiceClientService,
+    utils::AppError,
+    AppState,
+};
+
+/// Service client management API, mounted behind the auth middleware. Clients get their tokens
+/// from `/api/v1/auth/token`.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Service client API routes
+        .route("/", get(list_clients).post(create_client))
+        .route(
+            "/:id",
+            get(get_client).put(update_client).delete(delete_client),
+        )
+        .route("/:id/rotate-secret", post(rotate_secret))
+}
+
+// Helper function to extract tenant ID from tenant context
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps service client errors to status codes
+fn service_client_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Service client request failed: {}", error);
+    }
+    error.status()
+}
+
+// Service client API implementations
+
+async fn list_clients(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<Vec<ServiceClientResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .list_clients(tenant_id, &caller)
+        .await
+    {
+        Ok(clients) => Ok(Json(clients)),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
+
+async fn create_client(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateServiceClientRequest>,
+) -> Result<(StatusCode, Json<ServiceClientSecretResponse>), StatusCode> {
+    // Validate the scopes
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .create_client(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(client) => Ok((StatusCode::CREATED, Json(client))),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
+
+async fn get_client(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ServiceClientResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .get_client(tenant_id, &caller, id)
+        .await
+    {
+        Ok(Some(client)) => Ok(Json(client)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
+
+async fn update_client(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateServiceClientRequest>,
+) -> Result<Json<ServiceClientResponse>, StatusCode> {
+    // Validate the scopes
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .update_client(tenant_id, &caller, id, payload)
+        .await
+    {
+        Ok(Some(client)) => Ok(Json(client)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
+
+async fn delete_client(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .delete_client(tenant_id, &caller, id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
+
+async fn rotate_secret(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ServiceClientSecretResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let service_client_service = ServiceClientService::new(state.database);
+
+    match service_client_service
+        .rotate_secret(tenant_id, &caller, id)
+        .await
+    {
+        Ok(Some(client)) => Ok(Json(client)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(service_client_error(e)),
+    }
+}
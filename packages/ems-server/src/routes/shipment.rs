@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        Claims, CreateShipmentRequest, ListShipmentsQuery, RecordTrackingRequest, ShipmentResponse,
+    },
+    services::ShippingService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Shipment routes
+        .route("/", get(list_shipments).post(create_shipment))
+        .route("/:id", get(get_shipment))
+        .route("/:id/label", post(request_label))
+        .route("/:id/cancel", post(cancel_shipment))
+        // Carrier tracking routes
+        .route("/:id/tracking", post(record_tracking))
+        .route("/:id/tracking/refresh", post(refresh_tracking))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Helper function to extract user ID from JWT claims
+fn extract_user_id(claims: &Claims) -> Result<Uuid, StatusCode> {
+    Uuid::parse_str(&claims.sub).map_err(|_| StatusCode::UNAUTHORIZED)
+}
+
+// Maps shipping errors shared by several endpoints to status codes
+fn shipping_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Shipment API implementations
+
+async fn list_shipments(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ListShipmentsQuery>,
+) -> Result<Json<Vec<ShipmentResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service.list_shipments(tenant_id, params).await {
+        Ok(shipments) => Ok(Json(shipments)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_shipment(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(payload): ValidatedJson<CreateShipmentRequest>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let person_id = extract_user_id(&claims)?;
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service
+        .create_shipment(tenant_id, Some(person_id), payload)
+        .await
+    {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(shipping_error(e)),
+    }
+}
+
+async fn get_shipment(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service.get_shipment(tenant_id, id).await {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn request_label(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service.request_label(tenant_id, id).await {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(shipping_error(e)),
+    }
+}
+
+async fn cancel_shipment(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service.cancel_shipment(tenant_id, id).await {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(shipping_error(e)),
+    }
+}
+
+// Carrier tracking API implementations
+
+async fn record_tracking(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<RecordTrackingRequest>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service
+        .record_tracking(tenant_id, id, payload.events)
+        .await
+    {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(shipping_error(e)),
+    }
+}
+
+async fn refresh_tracking(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ShipmentResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let shipping_service =
+        ShippingService::new(state.database).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match shipping_service.refresh_tracking(tenant_id, id).await {
+        Ok(Some(shipment)) => Ok(Json(shipment)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(shipping_error(e)),
+    }
+}
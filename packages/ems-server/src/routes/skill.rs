@@ -0,0 +1,299 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, put},
+    Extension, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{
+        CreateSkillRequest, FlaggedOperatorAssignment, MachineSkillRequirementResponse,
+        PersonSkillResponse, SetMachineSkillRequirementRequest, SetPersonSkillRequest,
+        SkillCheckResponse, SkillMatrixResponse, SkillResponse, UpdateSkillRequest,
+    },
+    services::SkillService,
+    utils::AppError,
+    AppState,
+};
+
+#[derive(Deserialize)]
+struct CheckOperatorQuery {
+    machine_id: Uuid,
+    person_id: Uuid,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Skill catalog routes
+        .route("/", get(list_skills).post(create_skill))
+        .route(
+            "/:id",
+            get(get_skill).put(update_skill).delete(delete_skill),
+        )
+        // Skill matrix and operator validation routes
+        .route("/matrix", get(get_skill_matrix))
+        .route("/check", get(check_operator))
+        .route("/flagged-assignments", get(list_flagged_assignments))
+        // Internal person certification routes
+        .route("/persons/:person_id", get(list_person_skills))
+        .route(
+            "/persons/:person_id/:skill_id",
+            put(set_person_skill).delete(delete_person_skill),
+        )
+        // Machine skill requirement routes
+        .route(
+            "/machines/:machine_id",
+            get(list_machine_skill_requirements),
+        )
+        .route(
+            "/machines/:machine_id/:skill_id",
+            put(set_machine_skill_requirement).delete(delete_machine_skill_requirement),
+        )
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Skill catalog API implementations
+
+async fn list_skills(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<SkillResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.list_skills(tenant_id).await {
+        Ok(skills) => Ok(Json(skills)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn create_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedJson(payload): ValidatedJson<CreateSkillRequest>,
+) -> Result<Json<SkillResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.create_skill(tenant_id, payload).await {
+        Ok(skill) => Ok(Json(skill)),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn get_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SkillResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.get_skill(tenant_id, id).await {
+        Ok(Some(skill)) => Ok(Json(skill)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn update_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateSkillRequest>,
+) -> Result<Json<SkillResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.update_skill(tenant_id, id, payload).await {
+        Ok(Some(skill)) => Ok(Json(skill)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.delete_skill(tenant_id, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Skill matrix and operator validation API implementations
+
+async fn get_skill_matrix(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<SkillMatrixResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.skill_matrix(tenant_id).await {
+        Ok(matrix) => Ok(Json(matrix)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Checks an operator against a machine's requirements without creating an assignment
+async fn check_operator(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<CheckOperatorQuery>,
+) -> Result<Json<SkillCheckResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .check_operator(tenant_id, params.machine_id, params.person_id)
+        .await
+    {
+        Ok(check) => Ok(Json(check)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn list_flagged_assignments(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<FlaggedOperatorAssignment>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.flagged_operator_assignments(tenant_id).await {
+        Ok(assignments) => Ok(Json(assignments)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Internal person certification API implementations
+
+async fn list_person_skills(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(person_id): Path<Uuid>,
+) -> Result<Json<Vec<PersonSkillResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service.list_person_skills(tenant_id, person_id).await {
+        Ok(Some(certifications)) => Ok(Json(certifications)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn set_person_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((person_id, skill_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<SetPersonSkillRequest>,
+) -> Result<Json<PersonSkillResponse>, StatusCode> {
+    // Validate the request
+    if payload.check().is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .set_person_skill(tenant_id, person_id, skill_id, payload)
+        .await
+    {
+        Ok(Some(certification)) => Ok(Json(certification)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_person_skill(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((person_id, skill_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .delete_person_skill(tenant_id, person_id, skill_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// Machine skill requirement API implementations
+
+async fn list_machine_skill_requirements(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(machine_id): Path<Uuid>,
+) -> Result<Json<Vec<MachineSkillRequirementResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .list_machine_skill_requirements(tenant_id, machine_id)
+        .await
+    {
+        Ok(Some(requirements)) => Ok(Json(requirements)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+async fn set_machine_skill_requirement(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((machine_id, skill_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<SetMachineSkillRequirementRequest>,
+) -> Result<Json<MachineSkillRequirementResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .set_machine_skill_requirement(tenant_id, machine_id, skill_id, payload)
+        .await
+    {
+        Ok(Some(requirement)) => Ok(Json(requirement)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(AppError::from_service(e).status()),
+    }
+}
+
+async fn delete_machine_skill_requirement(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((machine_id, skill_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let skill_service = SkillService::new(state.database);
+
+    match skill_service
+        .delete_machine_skill_requirement(tenant_id, machine_id, skill_id)
+        .await
+    {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedJson,
+    models::{CallerContext, StatusPageResponse, StatusPageTokenResponse, UpdateStatusPageRequest},
+    services::StatusPageService,
+    utils::AppError,
+    AppState,
+};
+
+// How long the public page may be cached; it is polled by customers' dashboards
+const PUBLIC_CACHE_CONTROL: &str = "public, max-age=30";
+
+/// Status page configuration, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(get_status_page).put(update_status_page))
+        .route("/token", post(issue_token))
+}
+
+/// The public status page, mounted without auth; the token in the URL decides the tenant.
+pub fn public_routes() -> Router<AppState> {
+    Router::new().route("/:token", get(get_public_status_page))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Maps status page errors to status codes
+fn status_page_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Status page request failed: {}", error);
+    }
+    error.status()
+}
+
+// Status page configuration API implementations
+
+async fn get_status_page(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<Json<StatusPageResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let status_page_service = StatusPageService::new(state.database);
+
+    match status_page_service
+        .get_status_page(tenant_id, &caller)
+        .await
+    {
+        Ok(Some(page)) => Ok(Json(page)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_page_error(e)),
+    }
+}
+
+async fn update_status_page(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<UpdateStatusPageRequest>,
+) -> Result<Json<StatusPageResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let status_page_service = StatusPageService::new(state.database);
+
+    match status_page_service
+        .update_status_page(tenant_id, &caller, payload)
+        .await
+    {
+        Ok(page) => Ok(Json(page)),
+        Err(e) => Err(status_page_error(e)),
+    }
+}
+
+async fn issue_token(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+) -> Result<(StatusCode, Json<StatusPageTokenResponse>), StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let status_page_service = StatusPageService::new(state.database);
+
+    match status_page_service.issue_token(tenant_id, &caller).await {
+        Ok(Some(token)) => Ok((StatusCode::CREATED, Json(token))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_page_error(e)),
+    }
+}
+
+// Public status page API implementation
+
+async fn get_public_status_page(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, StatusCode> {
+    let status_page_service = StatusPageService::new(state.database).with_cache(state.tenant_cache);
+
+    // Unknown tokens and disabled pages look the same, so tokens cannot be probed
+    match status_page_service.public_page(&token).await {
+        Ok(Some(page)) => {
+            Ok(([(header::CACHE_CONTROL, PUBLIC_CACHE_CONTROL)], Json(page)).into_response())
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(status_page_error(e)),
+    }
+}
@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, Query, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use std::env;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+use crate::{services::LocalStorage, utils::AppError, AppState};
+
+/// Downloads through the signed URLs of the local filesystem storage backend. Mounted without
+/// tenant or auth: the URL's signature grants access to the one object until it expires.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/:bucket/*path", get(download))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedUrlQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+// Maps signed URL errors to status codes
+fn storage_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+async fn download(
+    Path((bucket, path)): Path<(String, String)>,
+    Query(query): Query<SignedUrlQuery>,
+    req: Request,
+) -> Result<Response, StatusCode> {
+    // Only the local backend issues these URLs
+    if !env::var("STORAGE_BACKEND")
+        .unwrap_or_default()
+        .trim()
+        .eq_ignore_ascii_case("local")
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let storage = LocalStorage::from_env(bucket).map_err(storage_error)?;
+    let file = storage
+        .signed_file_path(&path, query.expires, &query.signature)
+        .map_err(storage_error)?;
+
+    // Serves the file in pieces, with its content type guessed from the extension
+    match ServeFile::new(file).oneshot(req).await {
+        Ok(response) => Ok(response.into_response()),
+        Err(never) => match never {},
+    }
+}
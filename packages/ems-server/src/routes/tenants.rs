@@ -1,17 +1,25 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::get,
-    Router,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Extension, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
 
 use crate::{
-    models::{CreateTenantRequest, Tenant, UpdateTenantRequest},
-    services::tenant::TenantService,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        ApiAccessLog, AuthAuditEvent, BrandingPreviewRequest, CallerContext, Claims,
+        CloneSandboxRequest, CloneSandboxResponse, CreateTenantRequest, ListAccessLogsQuery,
+        ListAuthAuditQuery, Permission, RlsAuditResponse, Tenant, UpdateTenantRequest,
+    },
+    services::{
+        tenant::TenantService, AccessLogService, AuthService, BrandingError, BrandingPreview,
+        BrandingService, RlsService, SandboxService,
+    },
+    utils::{branding::BrandingSettings, AppError},
     AppState,
 };
 
@@ -25,22 +33,59 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_tenants).post(create_tenant))
         .route("/accessible", get(list_accessible_tenants))
+        .route("/rls-audit", get(get_rls_audit))
         .route(
             "/:id",
             get(get_tenant).put(update_tenant).delete(delete_tenant),
         )
+        .route("/:id/clone-sandbox", post(clone_sandbox))
+        .route("/:id/sandboxes", get(list_sandboxes))
+        .route("/:id/auth-audit", get(list_auth_audit))
+        .route("/:id/access-logs", get(list_access_logs))
+        .route("/:id/branding", get(get_branding).put(update_branding))
+        .route("/:id/branding/preview", post(preview_branding))
+}
+
+// Sandboxes are managed from the tenant the caller is signed in to
+fn check_own_tenant(claims: &Claims, id: Uuid) -> Result<(), StatusCode> {
+    if claims.tenant_id == id.to_string() {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+// Maps sandbox errors to status codes
+fn sandbox_error(e: anyhow::Error) -> StatusCode {
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Sandbox request failed: {}", error);
+    }
+    error.status()
+}
+
+// Maps branding errors to status codes
+fn branding_error(e: anyhow::Error) -> StatusCode {
+    if let Some(BrandingError::Invalid(_)) = e.downcast_ref::<BrandingError>() {
+        return StatusCode::UNPROCESSABLE_ENTITY;
+    }
+    let error = AppError::from_service(e);
+    if error.status() == StatusCode::INTERNAL_SERVER_ERROR {
+        tracing::error!("Branding request failed: {}", error);
+    }
+    error.status()
 }
 
 async fn create_tenant(
     State(state): State<AppState>,
-    Json(payload): Json<CreateTenantRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateTenantRequest>,
 ) -> Result<Json<Tenant>, StatusCode> {
     // Validate the request
-    if let Err(_) = payload.validate() {
+    if payload.check().is_err() {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     match tenant_service.create_tenant(payload).await {
         Ok(tenant) => Ok(Json(tenant)),
@@ -52,7 +97,7 @@ async fn get_tenant(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<Tenant>, StatusCode> {
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     match tenant_service.get_tenant_by_id(id).await {
         Ok(Some(tenant)) => Ok(Json(tenant)),
@@ -64,18 +109,18 @@ async fn get_tenant(
 async fn update_tenant(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    Json(payload): Json<UpdateTenantRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateTenantRequest>,
 ) -> Result<Json<Tenant>, StatusCode> {
     // Validate the request
-    if let Err(_) = payload.validate() {
+    if payload.check().is_err() {
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     match tenant_service.update_tenant(id, payload).await {
         Ok(tenant) => Ok(Json(tenant)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(AppError::from_service(e).status()),
     }
 }
 
@@ -83,7 +128,7 @@ async fn delete_tenant(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     match tenant_service.delete_tenant(id).await {
         Ok(_) => Ok(StatusCode::NO_CONTENT),
@@ -95,7 +140,7 @@ async fn list_tenants(
     State(state): State<AppState>,
     Query(params): Query<ListQuery>,
 ) -> Result<Json<Vec<Tenant>>, StatusCode> {
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     match tenant_service
         .list_tenants(params.limit, params.offset)
@@ -113,7 +158,7 @@ async fn list_accessible_tenants(
     State(state): State<AppState>,
     Query(params): Query<ListQuery>,
 ) -> Result<Json<Vec<Tenant>>, StatusCode> {
-    let tenant_service = TenantService::new(state.database);
+    let tenant_service = TenantService::new(state.database).with_cache(state.tenant_cache);
 
     // This endpoint returns all active tenants
     // In the future, this could be filtered based on user permissions
@@ -135,3 +180,172 @@ async fn list_accessible_tenants(
         }
     }
 }
+
+// Row level security status of every table in the database the request is routed to
+async fn get_rls_audit(
+    State(state): State<AppState>,
+) -> Result<Json<RlsAuditResponse>, StatusCode> {
+    let rls_service = RlsService::new(state.database);
+
+    match rls_service.audit().await {
+        Ok(audit) => Ok(Json(audit)),
+        Err(e) => {
+            tracing::error!("Failed to audit row level security: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Copies the tenant into a non-production sandbox that is deleted once it expires
+async fn clone_sandbox(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<CloneSandboxRequest>,
+) -> Result<(StatusCode, Json<CloneSandboxResponse>), StatusCode> {
+    check_own_tenant(&claims, id)?;
+    let sandbox_service = SandboxService::new(state.database).with_cache(state.tenant_cache);
+
+    match sandbox_service.clone_sandbox(id, &caller, payload).await {
+        Ok(Some(sandbox)) => Ok((StatusCode::CREATED, Json(sandbox))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(sandbox_error(e)),
+    }
+}
+
+async fn list_sandboxes(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Tenant>>, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    let sandbox_service = SandboxService::new(state.database).with_cache(state.tenant_cache);
+
+    match sandbox_service.list_sandboxes(id, &caller).await {
+        Ok(sandboxes) => Ok(Json(sandboxes)),
+        Err(e) => Err(sandbox_error(e)),
+    }
+}
+
+// Sign-in audit trail of the caller's tenant, e.g. refresh tokens used from another client
+async fn list_auth_audit(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<ListAuthAuditQuery>,
+) -> Result<Json<Vec<AuthAuditEvent>>, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    caller
+        .require(Permission::ViewAuditLog)
+        .map_err(|_| StatusCode::FORBIDDEN)?;
+    let auth_service = AuthService::new(state.database, state.supabase, state.auth_backend);
+
+    match auth_service.list_audit_events(id, query).await {
+        Ok(events) => Ok(Json(events)),
+        Err(e) => {
+            tracing::error!("Failed to list auth audit events: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// API requests made in the caller's tenant: who called which endpoint, from where, and how it
+// was answered
+async fn list_access_logs(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedQuery(query): ValidatedQuery<ListAccessLogsQuery>,
+) -> Result<Json<Vec<ApiAccessLog>>, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    caller
+        .require(Permission::ViewAuditLog)
+        .map_err(|_| StatusCode::FORBIDDEN)?;
+    let access_log_service = AccessLogService::new(state.database);
+
+    match access_log_service.list(id, query).await {
+        Ok(logs) => Ok(Json(logs)),
+        Err(e) => {
+            tracing::error!("Failed to list access logs: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Colors, logo, footer and reply-to address of the caller's tenant's customer documents
+async fn get_branding(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BrandingSettings>, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    let branding_service = BrandingService::new(state.database).with_cache(state.tenant_cache);
+
+    match branding_service.get_branding(id).await {
+        Ok(Some(branding)) => Ok(Json(branding)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(branding_error(e)),
+    }
+}
+
+async fn update_branding(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<BrandingSettings>,
+) -> Result<Json<BrandingSettings>, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    caller
+        .require(Permission::ManageBranding)
+        .map_err(|_| StatusCode::FORBIDDEN)?;
+    let branding_service = BrandingService::new(state.database).with_cache(state.tenant_cache);
+
+    match branding_service.update_branding(id, payload).await {
+        Ok(Some(branding)) => Ok(Json(branding)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(branding_error(e)),
+    }
+}
+
+// Sample order confirmation, as a PDF or the email it is sent with, rendered with branding that
+// has not been saved yet
+async fn preview_branding(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<BrandingPreviewRequest>,
+) -> Result<Response, StatusCode> {
+    check_own_tenant(&claims, id)?;
+    caller
+        .require(Permission::ManageBranding)
+        .map_err(|_| StatusCode::FORBIDDEN)?;
+    let locale = TenantService::new(state.database.clone())
+        .with_cache(state.tenant_cache.clone())
+        .get_locale(id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let branding_service = BrandingService::new(state.database).with_cache(state.tenant_cache);
+
+    match branding_service.preview(id, payload, locale).await {
+        Ok(Some(BrandingPreview::Pdf(pdf))) => Ok((
+            [
+                (header::CONTENT_TYPE, "application/pdf"),
+                (
+                    header::CONTENT_DISPOSITION,
+                    "inline; filename=\"branding-preview.pdf\"",
+                ),
+            ],
+            pdf,
+        )
+            .into_response()),
+        Ok(Some(BrandingPreview::Email(email))) => Ok(Json(email).into_response()),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(branding_error(e)),
+    }
+}
@@ -0,0 +1,38 @@
+use axum::{extract::State, http::StatusCode, response::Json, routing::get, Extension, Router};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::ValidatedQuery,
+    models::{TraceGraph, TraceQuery},
+    services::TraceService,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Traceability graph routes
+        .route("/", get(trace))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+// Trace API implementations
+
+async fn trace(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<TraceQuery>,
+) -> Result<Json<TraceGraph>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let trace_service = TraceService::new(state.database);
+
+    match trace_service.trace(tenant_id, params).await {
+        Ok(Some(graph)) => Ok(Json(graph)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CallerContext, CreateValidationRuleRequest, UpdateValidationRuleRequest,
+        ValidationRuleQuery, ValidationRuleResponse,
+    },
+    services::ValidationRuleService,
+    utils::errors::AppError,
+    AppState,
+};
+
+/// Tenant business rules, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_rules).post(create_rule))
+        .route("/:id", get(get_rule).put(update_rule).delete(delete_rule))
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+fn rule_not_found() -> AppError {
+    AppError::NotFound("Validation rule not found".to_string())
+}
+
+// Validation rule API implementations
+
+async fn list_rules(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    ValidatedQuery(params): ValidatedQuery<ValidationRuleQuery>,
+) -> Result<Json<Vec<ValidationRuleResponse>>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let validation_rule_service = ValidationRuleService::new(state.database);
+
+    let rules = validation_rule_service
+        .list_rules(tenant_id, params.entity)
+        .await
+        .map_err(AppError::from_service)?;
+    Ok(Json(rules))
+}
+
+async fn get_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ValidationRuleResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let validation_rule_service = ValidationRuleService::new(state.database);
+
+    validation_rule_service
+        .get_rule(tenant_id, id)
+        .await
+        .map_err(AppError::from_service)?
+        .map(Json)
+        .ok_or_else(rule_not_found)
+}
+
+async fn create_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedJson(payload): ValidatedJson<CreateValidationRuleRequest>,
+) -> Result<(StatusCode, Json<ValidationRuleResponse>), AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let validation_rule_service = ValidationRuleService::new(state.database);
+
+    let rule = validation_rule_service
+        .create_rule(tenant_id, &caller, payload)
+        .await
+        .map_err(AppError::from_service)?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+async fn update_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateValidationRuleRequest>,
+) -> Result<Json<ValidationRuleResponse>, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let validation_rule_service = ValidationRuleService::new(state.database);
+
+    validation_rule_service
+        .update_rule(tenant_id, &caller, id, payload)
+        .await
+        .map_err(AppError::from_service)?
+        .map(Json)
+        .ok_or_else(rule_not_found)
+}
+
+async fn delete_rule(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let validation_rule_service = ValidationRuleService::new(state.database);
+
+    let deleted = validation_rule_service
+        .delete_rule(tenant_id, &caller, id)
+        .await
+        .map_err(AppError::from_service)?;
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(rule_not_found())
+    }
+}
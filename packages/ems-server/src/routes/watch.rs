@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Extension, Router,
+};
+use uuid::Uuid;
+
+use crate::{
+    middleware::tenant::TenantContext,
+    middleware::validation::{ValidatedJson, ValidatedQuery},
+    models::{
+        CallerContext, CommentResponse, CreateCommentRequest, ListWatchesQuery, WatchEntityType,
+        WatchResponse, WatchedEntityResponse,
+    },
+    services::WatchService,
+    utils::AppError,
+    AppState,
+};
+
+/// Watching and commenting on machines, orders and items, mounted behind the auth middleware.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Watch API routes
+        .route("/", get(list_watches))
+        .route(
+            "/:entity_type/:entity_id",
+            get(get_watch_status).put(watch).delete(unwatch),
+        )
+        .route("/:entity_type/:entity_id/toggle", post(toggle_watch))
+        // Comment API routes
+        .route(
+            "/:entity_type/:entity_id/comments",
+            get(list_comments).post(add_comment),
+        )
+}
+
+// Helper function to extract tenant ID from request extensions
+fn extract_tenant_id(tenant_context: &TenantContext) -> Uuid {
+    tenant_context.tenant_id
+}
+
+fn watch_error(e: anyhow::Error) -> StatusCode {
+    AppError::from_service(e).status()
+}
+
+// Watch API implementations
+
+async fn list_watches(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    ValidatedQuery(params): ValidatedQuery<ListWatchesQuery>,
+) -> Result<Json<Vec<WatchedEntityResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .list_watches(tenant_id, caller.person_id, params)
+        .await
+    {
+        Ok(watches) => Ok(Json(watches)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+async fn get_watch_status(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+) -> Result<Json<WatchResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .get_watch_status(tenant_id, caller.person_id, entity_type, entity_id)
+        .await
+    {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+async fn watch(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+) -> Result<Json<WatchResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .set_watching(tenant_id, caller.person_id, entity_type, entity_id, true)
+        .await
+    {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+async fn unwatch(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+) -> Result<Json<WatchResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .set_watching(tenant_id, caller.person_id, entity_type, entity_id, false)
+        .await
+    {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+async fn toggle_watch(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+) -> Result<Json<WatchResponse>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .toggle_watching(tenant_id, caller.person_id, entity_type, entity_id)
+        .await
+    {
+        Ok(status) => Ok(Json(status)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+// Comment API implementations
+
+async fn list_comments(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+) -> Result<Json<Vec<CommentResponse>>, StatusCode> {
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .list_comments(tenant_id, entity_type, entity_id)
+        .await
+    {
+        Ok(comments) => Ok(Json(comments)),
+        Err(e) => Err(watch_error(e)),
+    }
+}
+
+async fn add_comment(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Extension(caller): Extension<CallerContext>,
+    Path((entity_type, entity_id)): Path<(WatchEntityType, Uuid)>,
+    ValidatedJson(payload): ValidatedJson<CreateCommentRequest>,
+) -> Result<(StatusCode, Json<CommentResponse>), StatusCode> {
+    if payload.body.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let tenant_id = extract_tenant_id(&tenant_context);
+    let watch_service = WatchService::new(state.database);
+
+    match watch_service
+        .add_comment(tenant_id, caller.person_id, entity_type, entity_id, payload)
+        .await
+    {
+        Ok(comment) => Ok((StatusCode::CREATED, Json(comment))),
+        Err(e) => Err(watch_error(e)),
+    }
+}
@@ -1,5 +1,61 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_access_logs (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 10]
+        method -> Varchar,
+        path -> Text,
+        endpoint -> Text,
+        status -> Int4,
+        latency_ms -> Int4,
+        #[max_length = 20]
+        actor_type -> Nullable<Varchar>,
+        actor_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        ip -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    asset_blobs (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 64]
+        checksum -> Varchar,
+        #[max_length = 500]
+        storage_path -> Varchar,
+        size_bytes -> Int8,
+        #[max_length = 50]
+        content_type -> Nullable<Varchar>,
+        ref_count -> Int4,
+        #[max_length = 20]
+        scan_status -> Nullable<Varchar>,
+        scanned_at -> Nullable<Timestamptz>,
+        scan -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    asset_signatures (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        asset_id -> Uuid,
+        signed_by_id -> Uuid,
+        #[max_length = 20]
+        meaning -> Varchar,
+        comment -> Nullable<Text>,
+        #[max_length = 64]
+        content_checksum -> Nullable<Varchar>,
+        signed_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     asset_types (id) {
         id -> Uuid,
@@ -11,6 +67,47 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    asset_upload_chunks (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        upload_id -> Uuid,
+        chunk_index -> Int4,
+        size_bytes -> Int4,
+        #[max_length = 64]
+        checksum -> Varchar,
+        received_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    asset_uploads (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        asset_id -> Uuid,
+        #[max_length = 255]
+        file_name -> Varchar,
+        #[max_length = 50]
+        content_type -> Varchar,
+        size_bytes -> Int8,
+        chunk_size -> Int4,
+        #[max_length = 64]
+        checksum -> Nullable<Varchar>,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 500]
+        storage_path -> Varchar,
+        error -> Nullable<Text>,
+        created_by_id -> Uuid,
+        expires_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        deduplicated -> Bool,
+        direct -> Bool,
+    }
+}
+
 diesel::table! {
     assets (id) {
         id -> Uuid,
@@ -34,6 +131,162 @@ diesel::table! {
         created_by_id -> Uuid,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        lineage_id -> Uuid,
+        previous_version_id -> Nullable<Uuid>,
+        is_current -> Bool,
+        #[max_length = 20]
+        release_status -> Varchar,
+        released_at -> Nullable<Timestamptz>,
+        retain_until -> Nullable<Timestamptz>,
+        #[max_length = 20]
+        scan_status -> Nullable<Varchar>,
+        scanned_at -> Nullable<Timestamptz>,
+        blob_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    auth_audit_events (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Nullable<Uuid>,
+        #[max_length = 40]
+        event -> Varchar,
+        #[max_length = 20]
+        outcome -> Varchar,
+        #[max_length = 64]
+        ip_prefix -> Nullable<Varchar>,
+        user_agent -> Nullable<Text>,
+        details -> Nullable<Jsonb>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    batch_records (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        job_id -> Uuid,
+        #[max_length = 50]
+        batch_number -> Varchar,
+        item_id -> Nullable<Uuid>,
+        quantity -> Int4,
+        completed_at -> Timestamptz,
+        completed_by_id -> Nullable<Uuid>,
+        record -> Jsonb,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    billing_webhook_events (id) {
+        id -> Uuid,
+        #[max_length = 255]
+        stripe_event_id -> Varchar,
+        #[max_length = 100]
+        event_type -> Varchar,
+        tenant_id -> Nullable<Uuid>,
+        received_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    calendar_exceptions (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Nullable<Uuid>,
+        #[max_length = 20]
+        exception_type -> Varchar,
+        starts_at -> Timestamptz,
+        ends_at -> Timestamptz,
+        reason -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    calibration_records (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Nullable<Uuid>,
+        instrument_id -> Nullable<Uuid>,
+        calibrated_at -> Timestamptz,
+        due_at -> Timestamptz,
+        #[max_length = 20]
+        result -> Varchar,
+        #[max_length = 200]
+        performed_by -> Nullable<Varchar>,
+        #[max_length = 100]
+        certificate_number -> Nullable<Varchar>,
+        certificate_asset_id -> Nullable<Uuid>,
+        notes -> Nullable<Text>,
+        recorded_by_id -> Nullable<Uuid>,
+        overdue_flagged_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    capa_actions (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        ncr_id -> Uuid,
+        #[max_length = 20]
+        action_type -> Varchar,
+        description -> Text,
+        #[max_length = 20]
+        status -> Varchar,
+        owner_id -> Nullable<Uuid>,
+        due_date -> Nullable<Timestamptz>,
+        outcome -> Nullable<Text>,
+        completed_at -> Nullable<Timestamptz>,
+        completed_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    consignment_billings (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        consignment_stock_id -> Uuid,
+        stock_movement_id -> Uuid,
+        item_id -> Uuid,
+        owner_id -> Uuid,
+        quantity -> Numeric,
+        unit_price -> Float8,
+        amount -> Float8,
+        #[max_length = 3]
+        currency -> Nullable<Varchar>,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 100]
+        invoice_reference -> Nullable<Varchar>,
+        invoiced_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    consignment_stock (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        inventory_item_id -> Uuid,
+        owner_id -> Uuid,
+        #[max_length = 20]
+        owner_type -> Varchar,
+        quantity -> Numeric,
+        unit_price -> Nullable<Float8>,
+        #[max_length = 3]
+        currency -> Nullable<Varchar>,
+        notes -> Nullable<Text>,
+        last_received_date -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -53,6 +306,47 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dashboards (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        owner_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        is_default -> Bool,
+        layout -> Jsonb,
+        widgets -> Jsonb,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    data_keys (id) {
+        id -> Uuid,
+        tenant_id -> Nullable<Uuid>,
+        version -> Int4,
+        wrapped_key -> Text,
+        #[max_length = 100]
+        master_key_id -> Varchar,
+        retired_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    email_verification_tokens (id) {
+        id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 255]
+        token_hash -> Varchar,
+        expires_at -> Timestamptz,
+        used_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     distributor_person (id) {
         id -> Uuid,
@@ -71,6 +365,60 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    entity_comments (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 20]
+        entity_type -> Varchar,
+        entity_id -> Uuid,
+        author_id -> Nullable<Uuid>,
+        body -> Text,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    feature_flag_overrides (id) {
+        id -> Uuid,
+        flag_id -> Uuid,
+        tenant_id -> Uuid,
+        enabled -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    feature_flags (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        key -> Varchar,
+        description -> Nullable<Text>,
+        enabled -> Bool,
+        rollout_percentage -> Int4,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    firmware_deltas (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 64]
+        source_checksum -> Varchar,
+        #[max_length = 64]
+        target_checksum -> Varchar,
+        #[max_length = 500]
+        storage_path -> Nullable<Varchar>,
+        size_bytes -> Int8,
+        #[max_length = 64]
+        checksum -> Varchar,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     firmware_specific (id) {
         id -> Uuid,
@@ -90,6 +438,59 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    ingest_events (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        source_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        error -> Nullable<Text>,
+        payload -> Jsonb,
+        entity_id -> Nullable<Uuid>,
+        received_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    ingest_sources (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 64]
+        token_hash -> Varchar,
+        #[max_length = 50]
+        target -> Varchar,
+        field_schema -> Jsonb,
+        template -> Jsonb,
+        is_active -> Bool,
+        last_received_at -> Nullable<Timestamptz>,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    instruments (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 100]
+        serial_number -> Varchar,
+        #[max_length = 50]
+        instrument_type -> Nullable<Varchar>,
+        #[max_length = 200]
+        location -> Nullable<Varchar>,
+        calibration_interval_days -> Nullable<Int4>,
+        is_active -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     internal_person (id) {
         id -> Uuid,
@@ -114,7 +515,7 @@ diesel::table! {
         tenant_id -> Uuid,
         #[max_length = 20]
         context -> Varchar,
-        quantity -> Nullable<Int4>,
+        quantity -> Nullable<Numeric>,
         #[max_length = 100]
         location -> Nullable<Varchar>,
         pricing -> Nullable<Jsonb>,
@@ -139,39 +540,185 @@ diesel::table! {
         tenant_id -> Uuid,
         parent_item_id -> Uuid,
         component_item_id -> Uuid,
-        quantity -> Nullable<Int4>,
+        quantity -> Nullable<Numeric>,
         notes -> Nullable<Text>,
         is_optional -> Nullable<Bool>,
         substitutes -> Nullable<Array<Nullable<Uuid>>>,
         assembly_order -> Nullable<Int4>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        uom_id -> Nullable<Uuid>,
+        designators -> Nullable<Array<Nullable<Text>>>,
     }
 }
 
 diesel::table! {
-    items (id) {
+    item_characteristics (id) {
         id -> Uuid,
-        #[max_length = 50]
-        internal_part_number -> Varchar,
-        #[max_length = 50]
-        mfr_part_number -> Nullable<Varchar>,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
         #[max_length = 100]
-        manufacturer -> Varchar,
-        #[max_length = 500]
-        datasheet -> Nullable<Varchar>,
+        name -> Varchar,
         #[max_length = 20]
-        lifecycle -> Nullable<Varchar>,
-        description -> Nullable<Text>,
-        #[max_length = 50]
-        category -> Nullable<Varchar>,
-        metadata -> Nullable<Jsonb>,
+        unit -> Nullable<Varchar>,
+        nominal -> Float8,
+        lower_tolerance -> Nullable<Float8>,
+        upper_tolerance -> Nullable<Float8>,
+        subgroup_size -> Int4,
+        machine_id -> Nullable<Uuid>,
+        #[max_length = 200]
+        payload_pointer -> Nullable<Varchar>,
+        is_active -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    item_datasheets (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        #[max_length = 500]
+        source_url -> Varchar,
+        asset_id -> Nullable<Uuid>,
+        #[max_length = 100]
+        content_type -> Nullable<Varchar>,
+        size_bytes -> Nullable<Int8>,
+        #[max_length = 64]
+        checksum -> Nullable<Varchar>,
+        fetched_at -> Nullable<Timestamptz>,
+        last_attempt_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    item_images (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        storage_path -> Text,
+        thumbnail_path -> Text,
+        #[max_length = 50]
+        content_type -> Varchar,
+        width -> Int4,
+        height -> Int4,
+        size_bytes -> Int8,
+        caption -> Nullable<Text>,
+        is_primary -> Bool,
+        uploaded_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    item_lifecycle_alerts (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        #[max_length = 20]
+        current_lifecycle -> Nullable<Varchar>,
+        #[max_length = 20]
+        suggested_lifecycle -> Varchar,
+        #[max_length = 100]
+        reported_status -> Varchar,
+        #[max_length = 50]
+        provider -> Varchar,
+        substitutes -> Array<Nullable<Uuid>>,
+        #[max_length = 20]
+        status -> Varchar,
+        resolved_by_id -> Nullable<Uuid>,
+        resolved_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    item_lifecycle_checks (item_id) {
+        item_id -> Uuid,
+        #[max_length = 50]
+        provider -> Varchar,
+        #[max_length = 100]
+        reported_status -> Nullable<Varchar>,
+        #[max_length = 20]
+        suggested_lifecycle -> Nullable<Varchar>,
+        last_error -> Nullable<Text>,
+        checked_at -> Timestamptz,
+        next_check_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    item_price_history (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        inventory_item_id -> Uuid,
+        vendor_id -> Nullable<Uuid>,
+        unit_price -> Float8,
+        price_breaks -> Jsonb,
+        #[max_length = 3]
+        currency -> Nullable<Varchar>,
+        lead_time -> Nullable<Int4>,
+        #[max_length = 20]
+        source -> Varchar,
+        recorded_by_id -> Nullable<Uuid>,
+        recorded_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    item_units (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        base_uom_id -> Uuid,
+        purchase_uom_id -> Nullable<Uuid>,
+        purchase_factor -> Nullable<Float8>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    items (id) {
+        id -> Uuid,
+        #[max_length = 50]
+        internal_part_number -> Varchar,
+        #[max_length = 50]
+        mfr_part_number -> Nullable<Varchar>,
+        #[max_length = 100]
+        manufacturer -> Varchar,
+        #[max_length = 500]
+        datasheet -> Nullable<Varchar>,
+        #[max_length = 20]
+        lifecycle -> Nullable<Varchar>,
+        description -> Nullable<Text>,
+        #[max_length = 50]
+        category -> Nullable<Varchar>,
+        metadata -> Nullable<Jsonb>,
         linked_resources -> Nullable<Jsonb>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
+diesel::table! {
+    job_dependencies (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        predecessor_job_id -> Uuid,
+        successor_job_id -> Uuid,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     job_history (id) {
         id -> Uuid,
@@ -190,242 +737,1202 @@ diesel::table! {
 }
 
 diesel::table! {
-    jobs (id) {
+    job_operations (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        job_id -> Uuid,
+        sequence -> Int4,
+        #[max_length = 100]
+        name -> Varchar,
+        machine_group_id -> Nullable<Uuid>,
+        standard_minutes -> Float8,
+        instructions -> Nullable<Text>,
+        required_asset_ids -> Array<Nullable<Uuid>>,
+        #[max_length = 20]
+        status -> Varchar,
+        started_at -> Nullable<Timestamptz>,
+        started_by_id -> Nullable<Uuid>,
+        completed_at -> Nullable<Timestamptz>,
+        completed_by_id -> Nullable<Uuid>,
+        actual_minutes -> Nullable<Float8>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    job_substitutions (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        job_id -> Uuid,
+        bom_id -> Nullable<Uuid>,
+        component_item_id -> Uuid,
+        substitute_item_id -> Uuid,
+        quantity -> Numeric,
+        stock_movement_id -> Nullable<Uuid>,
+        person_id -> Nullable<Uuid>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    jobs (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        job_number -> Varchar,
+        item_id -> Nullable<Uuid>,
+        quantity -> Int4,
+        assigned_person_id -> Nullable<Uuid>,
+        supervisor_id -> Nullable<Uuid>,
+        customer_id -> Nullable<Uuid>,
+        #[max_length = 20]
+        job_type -> Varchar,
+        #[max_length = 20]
+        priority -> Nullable<Varchar>,
+        start_date -> Nullable<Timestamptz>,
+        end_date -> Nullable<Timestamptz>,
+        due_date -> Nullable<Timestamptz>,
+        #[max_length = 20]
+        status -> Varchar,
+        comments -> Nullable<Text>,
+        materials_consumed -> Nullable<Jsonb>,
+        labor_hours -> Nullable<Float8>,
+        metadata -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        archived -> Bool,
+        archived_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    kiosk_terminals (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 100]
+        location -> Nullable<Varchar>,
+        actions -> Array<Text>,
+        #[max_length = 16]
+        key_prefix -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        created_by_id -> Nullable<Uuid>,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        revoked_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_alert_rules (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        machine_id -> Nullable<Uuid>,
+        conditions -> Jsonb,
+        actions -> Jsonb,
+        is_enabled -> Bool,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_alerts (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        rule_id -> Uuid,
+        machine_id -> Uuid,
+        triggered_at -> Timestamptz,
+        resolved_at -> Nullable<Timestamptz>,
+        observations -> Jsonb,
+        action_results -> Jsonb,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_asset_relationships (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        asset_id -> Uuid,
+        #[max_length = 20]
+        relationship_type -> Varchar,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        #[max_length = 10]
+        pin_mode -> Varchar,
+    }
+}
+
+diesel::table! {
+    machine_calendars (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        shift_pattern_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        timezone -> Varchar,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_commands (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 50]
+        command -> Varchar,
+        parameters -> Nullable<Jsonb>,
+        #[max_length = 20]
+        status -> Varchar,
+        expires_at -> Nullable<Timestamptz>,
+        delivered_at -> Nullable<Timestamptz>,
+        delivery_count -> Int4,
+        completed_at -> Nullable<Timestamptz>,
+        result -> Nullable<Jsonb>,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_configs (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        config -> Jsonb,
+        version -> Int4,
+        reporting_interval_seconds -> Nullable<Int4>,
+        updated_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_credentials (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 16]
+        key_prefix -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        #[max_length = 100]
+        broker_username -> Nullable<Varchar>,
+        #[max_length = 64]
+        broker_password_hash -> Nullable<Varchar>,
+        created_by_id -> Nullable<Uuid>,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        revoked_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_downtime_reports (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        reported_by_id -> Nullable<Uuid>,
+        #[max_length = 20]
+        previous_status -> Nullable<Varchar>,
+        #[max_length = 20]
+        new_status -> Varchar,
+        reason -> Text,
+        kiosk_terminal_id -> Nullable<Uuid>,
+        reported_at -> Timestamptz,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_group_members (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        group_id -> Uuid,
+        machine_id -> Uuid,
+        position -> Int4,
+        is_critical -> Bool,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_groups (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_heartbeat_events (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        payload -> Nullable<Jsonb>,
+        metadata -> Nullable<Jsonb>,
+        received_at -> Timestamptz,
+        processed_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_item_relationships (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        item_id -> Uuid,
+        #[max_length = 20]
+        relationship_type -> Varchar,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_job_assignments (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        job_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        start_time -> Nullable<Timestamptz>,
+        end_time -> Nullable<Timestamptz>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        target_quantity -> Nullable<Int4>,
+        produced_quantity -> Int4,
+        scrap_quantity -> Int4,
+        last_produced_total -> Nullable<Int8>,
+        last_scrap_total -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    machine_operator_assignments (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 20]
+        assignment_type -> Varchar,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_status_daily (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        day -> Date,
+        #[max_length = 20]
+        status -> Varchar,
+        transitions -> Int4,
+        seconds -> Float8,
+    }
+}
+
+diesel::table! {
+    machine_status_history (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 20]
+        previous_status -> Nullable<Varchar>,
+        #[max_length = 20]
+        new_status -> Varchar,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    machine_skill_requirements (id) {
+        id -> Uuid,
+        machine_id -> Uuid,
+        skill_id -> Uuid,
+        #[max_length = 10]
+        enforcement -> Varchar,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    machine_telemetry (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 20]
+        channel -> Varchar,
+        value -> Float8,
+        recorded_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    machine_telemetry_anomalies (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 20]
+        channel -> Varchar,
+        value -> Float8,
+        baseline_mean -> Float8,
+        baseline_std_dev -> Float8,
+        z_score -> Float8,
+        sensitivity -> Float8,
+        baseline_minutes -> Int4,
+        recorded_at -> Timestamptz,
+        detected_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    machine_telemetry_daily (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        machine_id -> Uuid,
+        #[max_length = 20]
+        channel -> Varchar,
+        day -> Date,
+        samples -> Int8,
+        min_value -> Float8,
+        max_value -> Float8,
+        avg_value -> Float8,
+    }
+}
+
+diesel::table! {
+    machines (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 45]
+        ip -> Varchar,
+        port -> Int4,
+        #[max_length = 20]
+        protocol -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 20]
+        action -> Nullable<Varchar>,
+        payload -> Nullable<Jsonb>,
+        last_heartbeat -> Nullable<Timestamptz>,
+        metadata -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        latitude -> Nullable<Float8>,
+        longitude -> Nullable<Float8>,
+        #[max_length = 100]
+        site -> Nullable<Varchar>,
+        decommissioned_at -> Nullable<Timestamptz>,
+        decommission_reason -> Nullable<Text>,
+        decommissioned_by -> Nullable<Uuid>,
+        final_meter_readings -> Nullable<Jsonb>,
+        gateway_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    manufacturing_job (id) {
+        id -> Uuid,
+        job_id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        work_order_number -> Nullable<Varchar>,
+        #[max_length = 100]
+        production_line -> Nullable<Varchar>,
+        #[max_length = 50]
+        machine_id -> Nullable<Varchar>,
+        setup_time_hours -> Nullable<Float8>,
+        cycle_time_minutes -> Nullable<Float8>,
+        quality_check_required -> Nullable<Bool>,
+        batch_size -> Nullable<Int4>,
+        tool_requirements -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    non_conformances (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        ncr_number -> Varchar,
+        #[max_length = 200]
+        title -> Varchar,
+        description -> Nullable<Text>,
+        #[max_length = 20]
+        source_type -> Varchar,
+        job_id -> Nullable<Uuid>,
+        machine_id -> Nullable<Uuid>,
+        item_id -> Nullable<Uuid>,
+        #[max_length = 100]
+        lot_number -> Nullable<Varchar>,
+        quantity_affected -> Nullable<Int4>,
+        #[max_length = 20]
+        severity -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        owner_id -> Nullable<Uuid>,
+        due_date -> Nullable<Timestamptz>,
+        raised_by_id -> Nullable<Uuid>,
+        closed_at -> Nullable<Timestamptz>,
+        closed_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        vendor_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    notifications (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 20]
+        entity_type -> Varchar,
+        entity_id -> Uuid,
+        #[max_length = 20]
+        event_type -> Varchar,
+        title -> Text,
+        detail -> Jsonb,
+        read_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    numbering_sequences (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 30]
+        entity -> Varchar,
+        #[max_length = 30]
+        prefix -> Varchar,
+        padding -> Int4,
+        yearly_reset -> Bool,
+        next_value -> Int8,
+        period_year -> Nullable<Int4>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    operator_attendance (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 20]
+        attendance_type -> Varchar,
+        starts_at -> Timestamptz,
+        ends_at -> Timestamptz,
+        reason -> Nullable<Text>,
+        recorded_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    operator_badges (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 64]
+        badge_id -> Nullable<Varchar>,
+        #[max_length = 255]
+        pin_hash -> Nullable<Varchar>,
+        failed_pin_attempts -> Int4,
+        locked_until -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    operator_clock_events (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 20]
+        event_type -> Varchar,
+        occurred_at -> Timestamptz,
+        kiosk_terminal_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    operator_shifts (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        shift_pattern_id -> Uuid,
+        #[max_length = 64]
+        timezone -> Varchar,
+        effective_from -> Date,
+        effective_to -> Nullable<Date>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    order_history (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Nullable<Uuid>,
+        #[max_length = 50]
+        action -> Varchar,
+        #[max_length = 20]
+        previous_status -> Nullable<Varchar>,
+        #[max_length = 20]
+        new_status -> Nullable<Varchar>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    order_items (id) {
+        id -> Uuid,
+        order_id -> Uuid,
+        item_id -> Nullable<Uuid>,
+        #[max_length = 200]
+        item_name -> Varchar,
+        item_description -> Nullable<Text>,
+        quantity -> Int4,
+        unit_price -> Float8,
+        extended_price -> Float8,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        expected_date -> Nullable<Timestamptz>,
+        backordered -> Bool,
+        uom_id -> Nullable<Uuid>,
+        base_quantity -> Int4,
+    }
+}
+
+diesel::table! {
+    order_return_credit_notes (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        return_id -> Uuid,
+        #[max_length = 50]
+        credit_note_number -> Varchar,
+        amount -> Float8,
+        issued_at -> Timestamptz,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    order_return_dispositions (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        return_line_id -> Uuid,
+        #[max_length = 20]
+        disposition -> Varchar,
+        quantity -> Int4,
+        stock_movement_id -> Nullable<Uuid>,
+        person_id -> Nullable<Uuid>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    order_return_lines (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        return_id -> Uuid,
+        order_item_id -> Uuid,
+        item_id -> Nullable<Uuid>,
+        quantity -> Int4,
+        #[max_length = 20]
+        reason -> Varchar,
+        reason_notes -> Nullable<Text>,
+        received_quantity -> Int4,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    order_returns (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        order_id -> Uuid,
+        #[max_length = 50]
+        rma_number -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 100]
+        quarantine_location -> Varchar,
+        notes -> Nullable<Text>,
+        created_by_id -> Nullable<Uuid>,
+        received_at -> Nullable<Timestamptz>,
+        closed_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    orders (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        order_number -> Varchar,
+        #[max_length = 20]
+        order_type -> Varchar,
+        external_entity_id -> Uuid,
+        #[max_length = 20]
+        external_entity_type -> Varchar,
+        order_date -> Timestamptz,
+        total_amount -> Float8,
+        #[max_length = 20]
+        status -> Varchar,
+        created_by_id -> Uuid,
+        notes -> Nullable<Text>,
+        metadata -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        archived -> Bool,
+        archived_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    person (id) {
+        id -> Uuid,
+        supabase_uid -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 100]
+        email -> Varchar,
+        phone -> Nullable<Text>,
+        global_access -> Nullable<Array<Nullable<Varchar>>>,
+        is_active -> Nullable<Bool>,
+        last_login -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        #[max_length = 255]
+        password_hash -> Nullable<Varchar>,
+        email_verified_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    person_credentials (person_id) {
+        person_id -> Uuid,
+        #[max_length = 255]
+        password_hash -> Varchar,
+        verified_at -> Timestamptz,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    person_skills (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        skill_id -> Uuid,
+        certified_at -> Nullable<Timestamptz>,
+        expires_at -> Nullable<Timestamptz>,
+        #[max_length = 100]
+        certificate_number -> Nullable<Varchar>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    print_jobs (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        printer_id -> Uuid,
+        item_id -> Nullable<Uuid>,
+        copies -> Int4,
+        zpl -> Text,
+        #[max_length = 20]
+        status -> Varchar,
+        attempts -> Int4,
+        max_attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        requested_by_id -> Nullable<Uuid>,
+        printed_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    printers (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 100]
+        location -> Nullable<Varchar>,
+        #[max_length = 255]
+        host -> Varchar,
+        port -> Int4,
+        dpi -> Int4,
+        label_width_mm -> Int4,
+        label_height_mm -> Int4,
+        is_default -> Bool,
+        is_active -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    qa_job (id) {
+        id -> Uuid,
+        job_id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        inspection_type -> Nullable<Varchar>,
+        #[max_length = 50]
+        test_procedure_id -> Nullable<Varchar>,
+        acceptance_criteria -> Nullable<Text>,
+        sampling_size -> Nullable<Int4>,
+        test_equipment -> Nullable<Jsonb>,
+        calibration_required -> Nullable<Bool>,
+        environmental_conditions -> Nullable<Jsonb>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        inspected_job_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    quote_items (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        quote_id -> Uuid,
+        line_number -> Int4,
+        item_id -> Nullable<Uuid>,
+        #[max_length = 200]
+        item_name -> Varchar,
+        item_description -> Nullable<Text>,
+        quantity -> Int4,
+        unit_price -> Float8,
+        extended_price -> Float8,
+        unit_cost -> Nullable<Float8>,
+        margin_percent -> Nullable<Float8>,
+        #[max_length = 20]
+        pricing_source -> Varchar,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    quotes (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        quote_number -> Varchar,
+        customer_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        valid_until -> Nullable<Timestamptz>,
+        total_amount -> Float8,
+        notes -> Nullable<Text>,
+        sent_at -> Nullable<Timestamptz>,
+        responded_at -> Nullable<Timestamptz>,
+        rejection_reason -> Nullable<Text>,
+        order_id -> Nullable<Uuid>,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    recalculation_tasks (id) {
         id -> Uuid,
         tenant_id -> Uuid,
-        #[max_length = 50]
-        job_number -> Varchar,
-        item_id -> Nullable<Uuid>,
-        quantity -> Int4,
-        assigned_person_id -> Nullable<Uuid>,
-        supervisor_id -> Nullable<Uuid>,
-        customer_id -> Nullable<Uuid>,
-        #[max_length = 20]
-        job_type -> Varchar,
-        #[max_length = 20]
-        priority -> Nullable<Varchar>,
-        start_date -> Nullable<Timestamptz>,
-        end_date -> Nullable<Timestamptz>,
-        due_date -> Nullable<Timestamptz>,
+        #[max_length = 30]
+        kind -> Varchar,
         #[max_length = 20]
         status -> Varchar,
-        comments -> Nullable<Text>,
-        materials_consumed -> Nullable<Jsonb>,
-        labor_hours -> Nullable<Float8>,
-        metadata -> Nullable<Jsonb>,
+        total -> Nullable<Int4>,
+        processed -> Int4,
+        updated_records -> Int4,
+        cancel_requested -> Bool,
+        error -> Nullable<Text>,
+        requested_by_id -> Nullable<Uuid>,
+        started_at -> Nullable<Timestamptz>,
+        finished_at -> Nullable<Timestamptz>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    machine_asset_relationships (id) {
+    report_schedules (id) {
         id -> Uuid,
-        machine_id -> Uuid,
-        asset_id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 50]
+        report_key -> Varchar,
+        #[max_length = 100]
+        cron_expression -> Varchar,
+        #[max_length = 64]
+        timezone -> Varchar,
+        parameters -> Jsonb,
+        recipients -> Array<Nullable<Text>>,
+        #[max_length = 10]
+        format -> Varchar,
+        is_active -> Bool,
+        created_by_id -> Nullable<Uuid>,
+        next_run_at -> Nullable<Timestamptz>,
+        last_run_at -> Nullable<Timestamptz>,
         #[max_length = 20]
-        relationship_type -> Varchar,
-        notes -> Nullable<Text>,
+        last_status -> Nullable<Varchar>,
+        last_error -> Nullable<Text>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    machine_item_relationships (id) {
+    rfq_items (id) {
         id -> Uuid,
-        machine_id -> Uuid,
+        tenant_id -> Uuid,
+        rfq_id -> Uuid,
+        line_number -> Int4,
         item_id -> Uuid,
-        #[max_length = 20]
-        relationship_type -> Varchar,
+        #[max_length = 200]
+        item_name -> Varchar,
+        item_description -> Nullable<Text>,
+        quantity -> Int4,
         notes -> Nullable<Text>,
         created_at -> Nullable<Timestamptz>,
-        updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    machine_job_assignments (id) {
+    rfq_quotes (id) {
         id -> Uuid,
-        machine_id -> Uuid,
-        job_id -> Uuid,
+        tenant_id -> Uuid,
+        rfq_vendor_id -> Uuid,
+        rfq_item_id -> Uuid,
+        unit_price -> Float8,
+        lead_time -> Nullable<Int4>,
+        notes -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    rfq_vendors (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        rfq_id -> Uuid,
+        vendor_id -> Uuid,
         #[max_length = 20]
         status -> Varchar,
-        start_time -> Nullable<Timestamptz>,
-        end_time -> Nullable<Timestamptz>,
+        #[max_length = 64]
+        token_hash -> Nullable<Varchar>,
+        #[max_length = 3]
+        currency -> Nullable<Varchar>,
         notes -> Nullable<Text>,
+        sent_at -> Nullable<Timestamptz>,
+        responded_at -> Nullable<Timestamptz>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    machine_operator_assignments (id) {
+    rfqs (id) {
         id -> Uuid,
-        machine_id -> Uuid,
-        person_id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 50]
+        rfq_number -> Varchar,
         #[max_length = 20]
-        assignment_type -> Varchar,
+        status -> Varchar,
+        respond_by -> Nullable<Timestamptz>,
         notes -> Nullable<Text>,
+        sent_at -> Nullable<Timestamptz>,
+        awarded_vendor_id -> Nullable<Uuid>,
+        awarded_at -> Nullable<Timestamptz>,
+        order_id -> Nullable<Uuid>,
+        created_by_id -> Nullable<Uuid>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    machines (id) {
+    service_clients (id) {
         id -> Uuid,
         tenant_id -> Uuid,
         #[max_length = 100]
         name -> Varchar,
-        #[max_length = 45]
-        ip -> Varchar,
-        port -> Int4,
-        #[max_length = 20]
-        protocol -> Varchar,
-        #[max_length = 20]
-        status -> Varchar,
-        #[max_length = 20]
-        action -> Nullable<Varchar>,
-        payload -> Nullable<Jsonb>,
-        last_heartbeat -> Nullable<Timestamptz>,
-        metadata -> Nullable<Jsonb>,
+        #[max_length = 64]
+        client_id -> Varchar,
+        #[max_length = 255]
+        secret_hash -> Varchar,
+        scopes -> Array<Text>,
+        owner_id -> Uuid,
+        is_active -> Bool,
+        last_used_at -> Nullable<Timestamptz>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    manufacturing_job (id) {
+    service_job (id) {
         id -> Uuid,
         job_id -> Uuid,
         tenant_id -> Uuid,
         #[max_length = 50]
-        work_order_number -> Nullable<Varchar>,
+        service_type -> Nullable<Varchar>,
+        #[max_length = 200]
+        location -> Nullable<Varchar>,
         #[max_length = 100]
-        production_line -> Nullable<Varchar>,
+        equipment_serial_number -> Nullable<Varchar>,
         #[max_length = 50]
-        machine_id -> Nullable<Varchar>,
-        setup_time_hours -> Nullable<Float8>,
-        cycle_time_minutes -> Nullable<Float8>,
-        quality_check_required -> Nullable<Bool>,
-        batch_size -> Nullable<Int4>,
-        tool_requirements -> Nullable<Jsonb>,
+        maintenance_type -> Nullable<Varchar>,
+        parts_required -> Nullable<Jsonb>,
+        safety_requirements -> Nullable<Jsonb>,
+        travel_time_hours -> Nullable<Float8>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    order_history (id) {
+    shift_patterns (id) {
         id -> Uuid,
-        order_id -> Uuid,
         tenant_id -> Uuid,
-        person_id -> Nullable<Uuid>,
-        #[max_length = 50]
-        action -> Varchar,
-        #[max_length = 20]
-        previous_status -> Nullable<Varchar>,
-        #[max_length = 20]
-        new_status -> Nullable<Varchar>,
-        notes -> Nullable<Text>,
+        #[max_length = 100]
+        name -> Varchar,
+        description -> Nullable<Text>,
+        shifts -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    order_items (id) {
+    shipment_packages (id) {
         id -> Uuid,
-        order_id -> Uuid,
-        item_id -> Nullable<Uuid>,
+        tenant_id -> Uuid,
+        shipment_id -> Uuid,
+        weight_kg -> Float8,
+        length_cm -> Nullable<Float8>,
+        width_cm -> Nullable<Float8>,
+        height_cm -> Nullable<Float8>,
+        #[max_length = 100]
+        reference -> Nullable<Varchar>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    shipment_tracking_events (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        shipment_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        description -> Nullable<Text>,
         #[max_length = 200]
-        item_name -> Varchar,
-        item_description -> Nullable<Text>,
-        quantity -> Int4,
-        unit_price -> Float8,
-        extended_price -> Float8,
-        notes -> Nullable<Text>,
+        location -> Nullable<Varchar>,
+        occurred_at -> Timestamptz,
         created_at -> Nullable<Timestamptz>,
-        updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    orders (id) {
+    shipments (id) {
         id -> Uuid,
         tenant_id -> Uuid,
+        order_id -> Uuid,
         #[max_length = 50]
-        order_number -> Varchar,
-        #[max_length = 20]
-        order_type -> Varchar,
-        external_entity_id -> Uuid,
-        #[max_length = 20]
-        external_entity_type -> Varchar,
-        order_date -> Timestamptz,
-        total_amount -> Float8,
+        carrier -> Varchar,
+        #[max_length = 50]
+        service_level -> Nullable<Varchar>,
         #[max_length = 20]
         status -> Varchar,
-        created_by_id -> Uuid,
+        ship_to -> Jsonb,
+        #[max_length = 100]
+        tracking_number -> Nullable<Varchar>,
+        #[max_length = 100]
+        carrier_shipment_id -> Nullable<Varchar>,
+        label_url -> Nullable<Text>,
+        shipped_at -> Nullable<Timestamptz>,
+        delivered_at -> Nullable<Timestamptz>,
         notes -> Nullable<Text>,
-        metadata -> Nullable<Jsonb>,
+        created_by_id -> Nullable<Uuid>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    person (id) {
+    skills (id) {
         id -> Uuid,
-        supabase_uid -> Uuid,
+        tenant_id -> Uuid,
         #[max_length = 100]
         name -> Varchar,
-        #[max_length = 100]
-        email -> Varchar,
+        description -> Nullable<Text>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    spc_measurements (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        characteristic_id -> Uuid,
+        value -> Float8,
         #[max_length = 20]
-        phone -> Nullable<Varchar>,
-        global_access -> Nullable<Array<Nullable<Varchar>>>,
-        is_active -> Nullable<Bool>,
-        last_login -> Nullable<Timestamptz>,
+        source -> Varchar,
+        machine_id -> Nullable<Uuid>,
+        job_id -> Nullable<Uuid>,
+        recorded_by_id -> Nullable<Uuid>,
+        measured_at -> Timestamptz,
+        created_at -> Nullable<Timestamptz>,
+        instrument_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    status_pages (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        enabled -> Bool,
+        #[max_length = 100]
+        title -> Varchar,
+        description -> Nullable<Text>,
+        machine_ids -> Array<Uuid>,
+        group_ids -> Array<Uuid>,
+        show_maintenance -> Bool,
+        maintenance_days -> Int4,
+        #[max_length = 16]
+        token_prefix -> Nullable<Varchar>,
+        #[max_length = 64]
+        token_hash -> Nullable<Varchar>,
+        token_issued_at -> Nullable<Timestamptz>,
+        updated_by_id -> Nullable<Uuid>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    qa_job (id) {
+    stock_movements (id) {
         id -> Uuid,
-        job_id -> Uuid,
         tenant_id -> Uuid,
-        #[max_length = 50]
-        inspection_type -> Nullable<Varchar>,
-        #[max_length = 50]
-        test_procedure_id -> Nullable<Varchar>,
-        acceptance_criteria -> Nullable<Text>,
-        sampling_size -> Nullable<Int4>,
-        test_equipment -> Nullable<Jsonb>,
-        calibration_required -> Nullable<Bool>,
-        environmental_conditions -> Nullable<Jsonb>,
+        item_id -> Uuid,
+        inventory_item_id -> Uuid,
+        #[max_length = 20]
+        context -> Varchar,
+        #[max_length = 20]
+        movement_type -> Varchar,
+        quantity -> Numeric,
+        quantity_after -> Numeric,
+        #[max_length = 20]
+        reference_type -> Nullable<Varchar>,
+        reference_id -> Nullable<Uuid>,
+        person_id -> Nullable<Uuid>,
+        notes -> Nullable<Text>,
+        occurred_at -> Timestamptz,
+        created_at -> Nullable<Timestamptz>,
+        uom_id -> Nullable<Uuid>,
+        uom_quantity -> Nullable<Numeric>,
+        owner_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    stock_reservations (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        item_id -> Uuid,
+        inventory_item_id -> Uuid,
+        #[max_length = 20]
+        context -> Varchar,
+        quantity -> Numeric,
+        #[max_length = 20]
+        reference_type -> Varchar,
+        reference_id -> Uuid,
+        #[max_length = 20]
+        status -> Varchar,
+        notes -> Nullable<Text>,
+        created_by_id -> Nullable<Uuid>,
+        released_at -> Nullable<Timestamptz>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
 }
 
 diesel::table! {
-    service_job (id) {
+    tenant_billing (id) {
         id -> Uuid,
-        job_id -> Uuid,
         tenant_id -> Uuid,
-        #[max_length = 50]
-        service_type -> Nullable<Varchar>,
-        #[max_length = 200]
-        location -> Nullable<Varchar>,
         #[max_length = 100]
-        equipment_serial_number -> Nullable<Varchar>,
+        plan -> Varchar,
+        #[max_length = 255]
+        stripe_customer_id -> Nullable<Varchar>,
+        #[max_length = 255]
+        stripe_subscription_id -> Nullable<Varchar>,
         #[max_length = 50]
-        maintenance_type -> Nullable<Varchar>,
-        parts_required -> Nullable<Jsonb>,
-        safety_requirements -> Nullable<Jsonb>,
-        travel_time_hours -> Nullable<Float8>,
+        subscription_status -> Nullable<Varchar>,
+        current_period_end -> Nullable<Timestamptz>,
+        cancel_at_period_end -> Bool,
+        billing_suspended -> Bool,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
@@ -458,6 +1965,11 @@ diesel::table! {
         is_active -> Nullable<Bool>,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        suspended_at -> Nullable<Timestamptz>,
+        suspended_reason -> Nullable<Text>,
+        is_sandbox -> Bool,
+        sandbox_of -> Nullable<Uuid>,
+        sandbox_expires_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -476,6 +1988,41 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    units_of_measure (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 20]
+        code -> Varchar,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 20]
+        dimension -> Varchar,
+        factor -> Float8,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    validation_rules (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 20]
+        entity -> Varchar,
+        condition -> Nullable<Text>,
+        requirement -> Text,
+        #[max_length = 500]
+        message -> Nullable<Varchar>,
+        enabled -> Bool,
+        created_by_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     vendor_person (id) {
         id -> Uuid,
@@ -492,77 +2039,388 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    watches (id) {
+        id -> Uuid,
+        tenant_id -> Uuid,
+        person_id -> Uuid,
+        #[max_length = 20]
+        entity_type -> Varchar,
+        entity_id -> Uuid,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::joinable!(api_access_logs -> tenants (tenant_id));
+diesel::joinable!(asset_blobs -> tenants (tenant_id));
+diesel::joinable!(asset_signatures -> assets (asset_id));
+diesel::joinable!(asset_signatures -> person (signed_by_id));
+diesel::joinable!(asset_signatures -> tenants (tenant_id));
+diesel::joinable!(asset_upload_chunks -> asset_uploads (upload_id));
+diesel::joinable!(asset_upload_chunks -> tenants (tenant_id));
+diesel::joinable!(asset_uploads -> assets (asset_id));
+diesel::joinable!(asset_uploads -> person (created_by_id));
+diesel::joinable!(asset_uploads -> tenants (tenant_id));
+diesel::joinable!(assets -> asset_blobs (blob_id));
 diesel::joinable!(assets -> asset_types (asset_type_id));
 diesel::joinable!(assets -> items (item_id));
 diesel::joinable!(assets -> person (created_by_id));
 diesel::joinable!(assets -> tenants (tenant_id));
+diesel::joinable!(auth_audit_events -> person (person_id));
+diesel::joinable!(auth_audit_events -> tenants (tenant_id));
+diesel::joinable!(batch_records -> items (item_id));
+diesel::joinable!(batch_records -> jobs (job_id));
+diesel::joinable!(batch_records -> person (completed_by_id));
+diesel::joinable!(batch_records -> tenants (tenant_id));
+diesel::joinable!(billing_webhook_events -> tenants (tenant_id));
+diesel::joinable!(calendar_exceptions -> machines (machine_id));
+diesel::joinable!(calendar_exceptions -> tenants (tenant_id));
+diesel::joinable!(calibration_records -> assets (certificate_asset_id));
+diesel::joinable!(calibration_records -> instruments (instrument_id));
+diesel::joinable!(calibration_records -> machines (machine_id));
+diesel::joinable!(calibration_records -> person (recorded_by_id));
+diesel::joinable!(calibration_records -> tenants (tenant_id));
+diesel::joinable!(capa_actions -> non_conformances (ncr_id));
+diesel::joinable!(capa_actions -> person (owner_id));
+diesel::joinable!(capa_actions -> tenants (tenant_id));
+diesel::joinable!(consignment_billings -> consignment_stock (consignment_stock_id));
+diesel::joinable!(consignment_billings -> items (item_id));
+diesel::joinable!(consignment_billings -> person (owner_id));
+diesel::joinable!(consignment_billings -> stock_movements (stock_movement_id));
+diesel::joinable!(consignment_billings -> tenants (tenant_id));
+diesel::joinable!(consignment_stock -> inventory_items (inventory_item_id));
+diesel::joinable!(consignment_stock -> items (item_id));
+diesel::joinable!(consignment_stock -> person (owner_id));
+diesel::joinable!(consignment_stock -> tenants (tenant_id));
 diesel::joinable!(customer_person -> tenants (tenant_id));
+diesel::joinable!(dashboards -> person (owner_id));
+diesel::joinable!(dashboards -> tenants (tenant_id));
+diesel::joinable!(data_keys -> tenants (tenant_id));
 diesel::joinable!(distributor_person -> person (person_id));
 diesel::joinable!(distributor_person -> tenants (tenant_id));
+diesel::joinable!(email_verification_tokens -> person (person_id));
+diesel::joinable!(entity_comments -> person (author_id));
+diesel::joinable!(entity_comments -> tenants (tenant_id));
+diesel::joinable!(feature_flag_overrides -> feature_flags (flag_id));
+diesel::joinable!(feature_flag_overrides -> tenants (tenant_id));
+diesel::joinable!(firmware_deltas -> tenants (tenant_id));
 diesel::joinable!(firmware_specific -> assets (asset_id));
+diesel::joinable!(ingest_events -> ingest_sources (source_id));
+diesel::joinable!(ingest_events -> tenants (tenant_id));
+diesel::joinable!(ingest_sources -> person (created_by_id));
+diesel::joinable!(ingest_sources -> tenants (tenant_id));
+diesel::joinable!(instruments -> tenants (tenant_id));
 diesel::joinable!(internal_person -> person (person_id));
 diesel::joinable!(internal_person -> tenants (tenant_id));
 diesel::joinable!(inventory_items -> items (item_id));
 diesel::joinable!(inventory_items -> person (vendor_id));
 diesel::joinable!(inventory_items -> tenants (tenant_id));
 diesel::joinable!(item_bom -> tenants (tenant_id));
+diesel::joinable!(item_bom -> units_of_measure (uom_id));
+diesel::joinable!(item_characteristics -> items (item_id));
+diesel::joinable!(item_characteristics -> machines (machine_id));
+diesel::joinable!(item_characteristics -> tenants (tenant_id));
+diesel::joinable!(item_datasheets -> assets (asset_id));
+diesel::joinable!(item_datasheets -> items (item_id));
+diesel::joinable!(item_datasheets -> tenants (tenant_id));
+diesel::joinable!(item_images -> items (item_id));
+diesel::joinable!(item_images -> person (uploaded_by_id));
+diesel::joinable!(item_images -> tenants (tenant_id));
+diesel::joinable!(item_lifecycle_alerts -> items (item_id));
+diesel::joinable!(item_lifecycle_alerts -> person (resolved_by_id));
+diesel::joinable!(item_lifecycle_alerts -> tenants (tenant_id));
+diesel::joinable!(item_lifecycle_checks -> items (item_id));
+diesel::joinable!(item_price_history -> inventory_items (inventory_item_id));
+diesel::joinable!(item_price_history -> items (item_id));
+diesel::joinable!(item_price_history -> tenants (tenant_id));
+diesel::joinable!(item_units -> items (item_id));
+diesel::joinable!(item_units -> tenants (tenant_id));
+diesel::joinable!(job_dependencies -> person (created_by_id));
+diesel::joinable!(job_dependencies -> tenants (tenant_id));
 diesel::joinable!(job_history -> jobs (job_id));
 diesel::joinable!(job_history -> person (person_id));
 diesel::joinable!(job_history -> tenants (tenant_id));
+diesel::joinable!(job_operations -> jobs (job_id));
+diesel::joinable!(job_operations -> machine_groups (machine_group_id));
+diesel::joinable!(job_operations -> tenants (tenant_id));
+diesel::joinable!(job_substitutions -> item_bom (bom_id));
+diesel::joinable!(job_substitutions -> jobs (job_id));
+diesel::joinable!(job_substitutions -> person (person_id));
+diesel::joinable!(job_substitutions -> stock_movements (stock_movement_id));
+diesel::joinable!(job_substitutions -> tenants (tenant_id));
 diesel::joinable!(jobs -> tenants (tenant_id));
+diesel::joinable!(kiosk_terminals -> person (created_by_id));
+diesel::joinable!(kiosk_terminals -> tenants (tenant_id));
+diesel::joinable!(machine_alert_rules -> machines (machine_id));
+diesel::joinable!(machine_alert_rules -> person (created_by_id));
+diesel::joinable!(machine_alert_rules -> tenants (tenant_id));
+diesel::joinable!(machine_alerts -> machine_alert_rules (rule_id));
+diesel::joinable!(machine_alerts -> machines (machine_id));
+diesel::joinable!(machine_alerts -> tenants (tenant_id));
 diesel::joinable!(machine_asset_relationships -> assets (asset_id));
 diesel::joinable!(machine_asset_relationships -> machines (machine_id));
+diesel::joinable!(machine_calendars -> machines (machine_id));
+diesel::joinable!(machine_calendars -> shift_patterns (shift_pattern_id));
+diesel::joinable!(machine_calendars -> tenants (tenant_id));
+diesel::joinable!(machine_commands -> machines (machine_id));
+diesel::joinable!(machine_commands -> person (created_by_id));
+diesel::joinable!(machine_commands -> tenants (tenant_id));
+diesel::joinable!(machine_configs -> machines (machine_id));
+diesel::joinable!(machine_configs -> person (updated_by_id));
+diesel::joinable!(machine_configs -> tenants (tenant_id));
+diesel::joinable!(machine_credentials -> person (created_by_id));
+diesel::joinable!(machine_credentials -> tenants (tenant_id));
+diesel::joinable!(machine_downtime_reports -> machines (machine_id));
+diesel::joinable!(machine_downtime_reports -> person (reported_by_id));
+diesel::joinable!(machine_downtime_reports -> tenants (tenant_id));
+diesel::joinable!(machine_group_members -> machine_groups (group_id));
+diesel::joinable!(machine_group_members -> machines (machine_id));
+diesel::joinable!(machine_group_members -> tenants (tenant_id));
+diesel::joinable!(machine_groups -> tenants (tenant_id));
+diesel::joinable!(machine_heartbeat_events -> machines (machine_id));
+diesel::joinable!(machine_heartbeat_events -> tenants (tenant_id));
 diesel::joinable!(machine_item_relationships -> items (item_id));
 diesel::joinable!(machine_item_relationships -> machines (machine_id));
 diesel::joinable!(machine_job_assignments -> jobs (job_id));
 diesel::joinable!(machine_job_assignments -> machines (machine_id));
 diesel::joinable!(machine_operator_assignments -> machines (machine_id));
 diesel::joinable!(machine_operator_assignments -> person (person_id));
+diesel::joinable!(machine_skill_requirements -> machines (machine_id));
+diesel::joinable!(machine_skill_requirements -> skills (skill_id));
+diesel::joinable!(machine_status_daily -> machines (machine_id));
+diesel::joinable!(machine_status_daily -> tenants (tenant_id));
+diesel::joinable!(machine_status_history -> machines (machine_id));
+diesel::joinable!(machine_status_history -> tenants (tenant_id));
+diesel::joinable!(machine_telemetry -> machines (machine_id));
+diesel::joinable!(machine_telemetry -> tenants (tenant_id));
+diesel::joinable!(machine_telemetry_anomalies -> machines (machine_id));
+diesel::joinable!(machine_telemetry_anomalies -> tenants (tenant_id));
+diesel::joinable!(machine_telemetry_daily -> machines (machine_id));
+diesel::joinable!(machine_telemetry_daily -> tenants (tenant_id));
+diesel::joinable!(machines -> person (decommissioned_by));
 diesel::joinable!(machines -> tenants (tenant_id));
 diesel::joinable!(manufacturing_job -> jobs (job_id));
 diesel::joinable!(manufacturing_job -> tenants (tenant_id));
+diesel::joinable!(non_conformances -> items (item_id));
+diesel::joinable!(non_conformances -> jobs (job_id));
+diesel::joinable!(non_conformances -> machines (machine_id));
+diesel::joinable!(non_conformances -> person (owner_id));
+diesel::joinable!(non_conformances -> tenants (tenant_id));
+diesel::joinable!(notifications -> person (person_id));
+diesel::joinable!(notifications -> tenants (tenant_id));
+diesel::joinable!(numbering_sequences -> tenants (tenant_id));
+diesel::joinable!(operator_attendance -> tenants (tenant_id));
+diesel::joinable!(operator_badges -> person (person_id));
+diesel::joinable!(operator_badges -> tenants (tenant_id));
+diesel::joinable!(operator_clock_events -> person (person_id));
+diesel::joinable!(operator_clock_events -> tenants (tenant_id));
+diesel::joinable!(operator_shifts -> person (person_id));
+diesel::joinable!(operator_shifts -> shift_patterns (shift_pattern_id));
+diesel::joinable!(operator_shifts -> tenants (tenant_id));
 diesel::joinable!(order_history -> orders (order_id));
 diesel::joinable!(order_history -> person (person_id));
 diesel::joinable!(order_history -> tenants (tenant_id));
 diesel::joinable!(order_items -> orders (order_id));
+diesel::joinable!(order_items -> units_of_measure (uom_id));
+diesel::joinable!(order_return_credit_notes -> order_returns (return_id));
+diesel::joinable!(order_return_credit_notes -> tenants (tenant_id));
+diesel::joinable!(order_return_dispositions -> order_return_lines (return_line_id));
+diesel::joinable!(order_return_dispositions -> person (person_id));
+diesel::joinable!(order_return_dispositions -> stock_movements (stock_movement_id));
+diesel::joinable!(order_return_dispositions -> tenants (tenant_id));
+diesel::joinable!(order_return_lines -> items (item_id));
+diesel::joinable!(order_return_lines -> order_items (order_item_id));
+diesel::joinable!(order_return_lines -> order_returns (return_id));
+diesel::joinable!(order_return_lines -> tenants (tenant_id));
+diesel::joinable!(order_returns -> orders (order_id));
+diesel::joinable!(order_returns -> person (created_by_id));
+diesel::joinable!(order_returns -> tenants (tenant_id));
 diesel::joinable!(orders -> tenants (tenant_id));
+diesel::joinable!(person_credentials -> person (person_id));
+diesel::joinable!(person_skills -> person (person_id));
+diesel::joinable!(person_skills -> skills (skill_id));
+diesel::joinable!(person_skills -> tenants (tenant_id));
+diesel::joinable!(print_jobs -> items (item_id));
+diesel::joinable!(print_jobs -> person (requested_by_id));
+diesel::joinable!(print_jobs -> printers (printer_id));
+diesel::joinable!(print_jobs -> tenants (tenant_id));
+diesel::joinable!(printers -> tenants (tenant_id));
 diesel::joinable!(qa_job -> jobs (job_id));
 diesel::joinable!(qa_job -> tenants (tenant_id));
+diesel::joinable!(quote_items -> items (item_id));
+diesel::joinable!(quote_items -> quotes (quote_id));
+diesel::joinable!(quote_items -> tenants (tenant_id));
+diesel::joinable!(quotes -> orders (order_id));
+diesel::joinable!(quotes -> tenants (tenant_id));
+diesel::joinable!(recalculation_tasks -> person (requested_by_id));
+diesel::joinable!(recalculation_tasks -> tenants (tenant_id));
+diesel::joinable!(report_schedules -> person (created_by_id));
+diesel::joinable!(report_schedules -> tenants (tenant_id));
+diesel::joinable!(rfq_items -> items (item_id));
+diesel::joinable!(rfq_items -> rfqs (rfq_id));
+diesel::joinable!(rfq_items -> tenants (tenant_id));
+diesel::joinable!(rfq_quotes -> rfq_items (rfq_item_id));
+diesel::joinable!(rfq_quotes -> rfq_vendors (rfq_vendor_id));
+diesel::joinable!(rfq_quotes -> tenants (tenant_id));
+diesel::joinable!(rfq_vendors -> person (vendor_id));
+diesel::joinable!(rfq_vendors -> rfqs (rfq_id));
+diesel::joinable!(rfq_vendors -> tenants (tenant_id));
+diesel::joinable!(rfqs -> orders (order_id));
+diesel::joinable!(rfqs -> tenants (tenant_id));
+diesel::joinable!(service_clients -> person (owner_id));
+diesel::joinable!(service_clients -> tenants (tenant_id));
 diesel::joinable!(service_job -> jobs (job_id));
 diesel::joinable!(service_job -> tenants (tenant_id));
+diesel::joinable!(shift_patterns -> tenants (tenant_id));
+diesel::joinable!(shipment_packages -> shipments (shipment_id));
+diesel::joinable!(shipment_packages -> tenants (tenant_id));
+diesel::joinable!(shipment_tracking_events -> shipments (shipment_id));
+diesel::joinable!(shipment_tracking_events -> tenants (tenant_id));
+diesel::joinable!(shipments -> orders (order_id));
+diesel::joinable!(shipments -> person (created_by_id));
+diesel::joinable!(shipments -> tenants (tenant_id));
+diesel::joinable!(skills -> tenants (tenant_id));
+diesel::joinable!(spc_measurements -> instruments (instrument_id));
+diesel::joinable!(spc_measurements -> item_characteristics (characteristic_id));
+diesel::joinable!(spc_measurements -> jobs (job_id));
+diesel::joinable!(spc_measurements -> tenants (tenant_id));
+diesel::joinable!(status_pages -> person (updated_by_id));
+diesel::joinable!(status_pages -> tenants (tenant_id));
+diesel::joinable!(stock_movements -> inventory_items (inventory_item_id));
+diesel::joinable!(stock_movements -> items (item_id));
+diesel::joinable!(stock_movements -> person (person_id));
+diesel::joinable!(stock_movements -> tenants (tenant_id));
+diesel::joinable!(stock_movements -> units_of_measure (uom_id));
+diesel::joinable!(stock_reservations -> inventory_items (inventory_item_id));
+diesel::joinable!(stock_reservations -> items (item_id));
+diesel::joinable!(stock_reservations -> person (created_by_id));
+diesel::joinable!(stock_reservations -> tenants (tenant_id));
+diesel::joinable!(tenant_billing -> tenants (tenant_id));
 diesel::joinable!(tenant_person -> person (person_id));
 diesel::joinable!(tenant_person -> tenants (tenant_id));
 diesel::joinable!(token_blacklist -> person (person_id));
 diesel::joinable!(token_blacklist -> tenants (tenant_id));
+diesel::joinable!(units_of_measure -> tenants (tenant_id));
+diesel::joinable!(validation_rules -> person (created_by_id));
+diesel::joinable!(validation_rules -> tenants (tenant_id));
 diesel::joinable!(vendor_person -> person (person_id));
 diesel::joinable!(vendor_person -> tenants (tenant_id));
+diesel::joinable!(watches -> person (person_id));
+diesel::joinable!(watches -> tenants (tenant_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_access_logs,
+    asset_blobs,
+    asset_signatures,
     asset_types,
+    asset_upload_chunks,
+    asset_uploads,
     assets,
+    auth_audit_events,
+    batch_records,
+    billing_webhook_events,
+    calendar_exceptions,
+    calibration_records,
+    capa_actions,
+    consignment_billings,
+    consignment_stock,
     customer_person,
+    dashboards,
+    data_keys,
     distributor_person,
+    email_verification_tokens,
+    entity_comments,
+    feature_flag_overrides,
+    feature_flags,
+    firmware_deltas,
     firmware_specific,
+    ingest_events,
+    ingest_sources,
+    instruments,
     internal_person,
     inventory_items,
     item_bom,
+    item_characteristics,
+    item_datasheets,
+    item_images,
+    item_lifecycle_alerts,
+    item_lifecycle_checks,
+    item_price_history,
+    item_units,
     items,
+    job_dependencies,
     job_history,
+    job_operations,
+    job_substitutions,
     jobs,
+    kiosk_terminals,
+    machine_alert_rules,
+    machine_alerts,
     machine_asset_relationships,
+    machine_calendars,
+    machine_commands,
+    machine_configs,
+    machine_credentials,
+    machine_downtime_reports,
+    machine_group_members,
+    machine_groups,
+    machine_heartbeat_events,
     machine_item_relationships,
     machine_job_assignments,
     machine_operator_assignments,
+    machine_skill_requirements,
+    machine_status_daily,
+    machine_status_history,
+    machine_telemetry,
+    machine_telemetry_anomalies,
+    machine_telemetry_daily,
     machines,
     manufacturing_job,
+    non_conformances,
+    notifications,
+    numbering_sequences,
+    operator_attendance,
+    operator_badges,
+    operator_clock_events,
+    operator_shifts,
     order_history,
     order_items,
+    order_return_credit_notes,
+    order_return_dispositions,
+    order_return_lines,
+    order_returns,
     orders,
     person,
+    person_credentials,
+    person_skills,
+    print_jobs,
+    printers,
     qa_job,
+    quote_items,
+    quotes,
+    recalculation_tasks,
+    report_schedules,
+    rfq_items,
+    rfq_quotes,
+    rfq_vendors,
+    rfqs,
+    service_clients,
     service_job,
+    shift_patterns,
+    shipment_packages,
+    shipment_tracking_events,
+    shipments,
+    skills,
+    spc_measurements,
+    status_pages,
+    stock_movements,
+    stock_reservations,
+    tenant_billing,
     tenant_person,
     tenants,
     token_blacklist,
+    units_of_measure,
+    validation_rules,
     vendor_person,
+    watches,
 );
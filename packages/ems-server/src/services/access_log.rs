@@ -0,0 +1,158 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use std::collections::BTreeMap;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::models::{ApiAccessLog, ListAccessLogsQuery, NewApiAccessLog};
+use crate::schema::api_access_logs;
+use crate::services::DatabaseService;
+use crate::utils::search::escape_like;
+
+// Entries waiting to be written before new ones are dropped
+const ACCESS_LOG_QUEUE_SIZE: usize = 10_000;
+// Maximum number of entries written in one batch
+const ACCESS_LOG_BATCH_SIZE: usize = 500;
+
+/// API access log of every tenant. Entries live in the shared database, so a tenant's log stays
+/// in one place whichever database serves its data.
+pub struct AccessLogService {
+    shared: DatabaseService,
+}
+
+impl AccessLogService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+        }
+    }
+
+    /// Writes the entries, tenant by tenant, returning how many were written.
+    pub async fn record(&self, entries: Vec<NewApiAccessLog>) -> Result<usize> {
+        let mut by_tenant: BTreeMap<Uuid, Vec<NewApiAccessLog>> = BTreeMap::new();
+        for entry in entries {
+            by_tenant.entry(entry.tenant_id).or_default().push(entry);
+        }
+
+        let mut conn = self.shared.get_connection().await?;
+        let mut written = 0;
+        for (tenant_id, entries) in by_tenant {
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            written += diesel::insert_into(api_access_logs::table)
+                .values(&entries)
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(written)
+    }
+
+    /// The tenant's access log, newest first
+    pub async fn list(
+        &self,
+        tenant_id: Uuid,
+        query: ListAccessLogsQuery,
+    ) -> Result<Vec<ApiAccessLog>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut logs = api_access_logs::table
+            .filter(api_access_logs::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(actor_type) = query.actor_type {
+            logs = logs.filter(api_access_logs::actor_type.eq(actor_type.to_string()));
+        }
+        if let Some(actor_id) = query.actor_id {
+            logs = logs.filter(api_access_logs::actor_id.eq(actor_id));
+        }
+        if let Some(method) = &query.method {
+            logs = logs.filter(api_access_logs::method.eq(method.to_uppercase()));
+        }
+        if let Some(path) = &query.path {
+            logs = logs.filter(api_access_logs::path.like(format!("%{}%", escape_like(path))));
+        }
+        if let Some(status) = query.status {
+            logs = logs.filter(api_access_logs::status.eq(status));
+        }
+        if query.errors_only.unwrap_or(false) {
+            logs = logs.filter(api_access_logs::status.ge(400));
+        }
+        if let Some(ip) = &query.ip {
+            logs = logs.filter(api_access_logs::ip.eq(ip));
+        }
+        if let Some(from) = query.from {
+            logs = logs.filter(api_access_logs::created_at.ge(from));
+        }
+        if let Some(to) = query.to {
+            logs = logs.filter(api_access_logs::created_at.lt(to));
+        }
+
+        Ok(logs
+            .order(api_access_logs::created_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(ApiAccessLog::as_select())
+            .load::<ApiAccessLog>(&mut conn)
+            .await?)
+    }
+
+    /// Deletes the tenant's entries made before `before`, returning how many were deleted.
+    pub async fn prune(&self, tenant_id: Uuid, before: DateTime<Utc>) -> Result<usize> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(diesel::delete(
+            api_access_logs::table
+                .filter(api_access_logs::tenant_id.eq(tenant_id))
+                .filter(api_access_logs::created_at.lt(before)),
+        )
+        .execute(&mut conn)
+        .await?)
+    }
+}
+
+/// Queues access log entries and writes them in batches from a background task, so logging
+/// never holds up a request.
+#[derive(Clone)]
+pub struct AccessLogWriter {
+    sender: mpsc::Sender<NewApiAccessLog>,
+}
+
+impl AccessLogWriter {
+    /// Starts the task writing queued entries.
+    pub fn spawn(database: DatabaseService) -> Self {
+        let (sender, mut receiver) = mpsc::channel(ACCESS_LOG_QUEUE_SIZE);
+        let service = AccessLogService::new(database);
+
+        tokio::spawn(async move {
+            let mut batch = Vec::with_capacity(ACCESS_LOG_BATCH_SIZE);
+            while receiver.recv_many(&mut batch, ACCESS_LOG_BATCH_SIZE).await > 0 {
+                let count = batch.len();
+                if let Err(e) = service.record(std::mem::take(&mut batch)).await {
+                    tracing::error!("Failed to write {} access log entries: {}", count, e);
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    /// Queues an entry. While the database falls behind and the queue is full, entries are
+    /// dropped rather than slowing requests down.
+    pub fn record(&self, entry: NewApiAccessLog) {
+        if self.sender.try_send(entry).is_err() {
+            tracing::warn!("Access log queue full; entry dropped");
+        }
+    }
+}
@@ -0,0 +1,1058 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Timestamptz, Uuid as SqlUuid};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::BTreeMap;
+use std::future::Future;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    has_platform_admin_scope, AccessLevel, AdminTenantResponse, CompactMachineHistoryRequest,
+    DecommissionReport, DecommissionStaleMachinesRequest, DiagnosticsResponse,
+    IntegrityCheckRequest, IntegrityCheckResult, IntegrityReport, ListAdminTenantsQuery,
+    MachineHistoryCompaction, MachineHistoryCompactionReport, MachineStatus,
+    PlatformDatabaseHealth, PlatformHealthResponse, PlatformTenantCounts, QueueDepths,
+    ResetTenantAdminRequest, ResetTenantAdminResponse, StaleMachine, SuspendTenantRequest, Tenant,
+    TenantStatus, TenantUsage, TenantWebhookDeliveries, TokenBlacklistPurgeReport,
+    WebhookDeliveryStats,
+};
+use crate::schema::*;
+use crate::services::{AccessLevelCache, DatabaseService, MachineService, TenantCache};
+use crate::utils::circuit_breaker::CircuitBreakerStatus;
+use crate::utils::diagnostics::Diagnostics;
+use crate::utils::integrity::{select_checks, IntegrityCheck, INTEGRITY_SAMPLE_SIZE};
+use crate::utils::AppError;
+
+/// Errors from the platform admin services that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("Tenant cannot be suspended: {0}")]
+    NotSuspendable(String),
+
+    #[error("Tenant cannot be reactivated: it is not suspended")]
+    NotSuspended,
+
+    #[error("Invalid tenant admin: {0}")]
+    InvalidAdmin(String),
+
+    #[error("{0}")]
+    InvalidIntegrityCheck(String),
+
+    #[error("Feature flag already exists: {0}")]
+    FlagExists(String),
+
+    #[error("Invalid feature flag override: {0}")]
+    InvalidFlagOverride(String),
+}
+
+impl From<AdminError> for AppError {
+    fn from(error: AdminError) -> Self {
+        match error {
+            AdminError::NotSuspendable(_)
+            | AdminError::NotSuspended
+            | AdminError::FlagExists(_) => AppError::Conflict(error.to_string()),
+            AdminError::InvalidAdmin(_)
+            | AdminError::InvalidIntegrityCheck(_)
+            | AdminError::InvalidFlagOverride(_) => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+const DEFAULT_TENANT_PAGE_SIZE: i64 = 50;
+// Hours of machine alert webhook deliveries counted by the diagnostics
+const WEBHOOK_WINDOW_HOURS: i64 = 24;
+
+#[derive(QueryableByName)]
+struct DatabaseSize {
+    #[diesel(sql_type = BigInt)]
+    size_bytes: i64,
+}
+
+#[derive(QueryableByName)]
+struct DanglingCount {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct CompactedCounts {
+    #[diesel(sql_type = BigInt)]
+    row_count: i64,
+    #[diesel(sql_type = BigInt)]
+    day_count: i64,
+}
+
+#[derive(QueryableByName)]
+struct DanglingRow {
+    #[diesel(sql_type = SqlUuid)]
+    id: Uuid,
+}
+
+/// Platform operations across tenants, for people whose global access includes the platform
+/// admin scope.
+pub struct AdminService {
+    database: DatabaseService,
+    // Tenant records and people are always read from the shared database
+    shared: DatabaseService,
+    cache: Option<TenantCache>,
+    access_levels: Option<AccessLevelCache>,
+}
+
+impl AdminService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+            database,
+            cache: None,
+            access_levels: None,
+        }
+    }
+
+    /// Drops suspended and reactivated tenants from the cache so the change applies at once.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Drops the access levels an admin reset changes from the cache so they apply at once.
+    pub fn with_access_level_cache(mut self, access_levels: AccessLevelCache) -> Self {
+        self.access_levels = Some(access_levels);
+        self
+    }
+
+    pub async fn is_platform_admin(&self, person_id: Uuid) -> Result<bool> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let global_access = person::table
+            .filter(person::id.eq(person_id))
+            .filter(person::is_active.eq(true))
+            .select(person::global_access)
+            .first::<Option<Vec<Option<String>>>>(&mut conn)
+            .await
+            .optional()?
+            .flatten()
+            .unwrap_or_default();
+
+        Ok(has_platform_admin_scope(&global_access))
+    }
+
+    // Tenant operations
+
+    pub async fn list_tenants(
+        &self,
+        query: ListAdminTenantsQuery,
+    ) -> Result<Vec<AdminTenantResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let mut tenant_query = tenants::table.into_boxed();
+        match query.status {
+            Some(TenantStatus::Active) => {
+                tenant_query = tenant_query.filter(tenants::is_active.eq(true));
+            }
+            Some(TenantStatus::Suspended) => {
+                tenant_query = tenant_query.filter(
+                    tenants::is_active
+                        .eq(false)
+                        .or(tenants::is_active.is_null()),
+                );
+            }
+            None => {}
+        }
+        if let Some(search) = &query.search {
+            let pattern = format!("%{}%", search);
+            tenant_query = tenant_query.filter(
+                tenants::name
+                    .ilike(pattern.clone())
+                    .or(tenants::subdomain.ilike(pattern)),
+            );
+        }
+
+        let tenants = tenant_query
+            .order((tenants::name.asc(), tenants::id.asc()))
+            .limit(query.limit.map_or(DEFAULT_TENANT_PAGE_SIZE, i64::from))
+            .offset(query.offset.map_or(0, i64::from))
+            .select(Tenant::as_select())
+            .load::<Tenant>(&mut conn)
+            .await?;
+        drop(conn);
+
+        let mut responses = Vec::with_capacity(tenants.len());
+        for tenant in tenants {
+            responses.push(self.with_usage(tenant).await);
+        }
+        Ok(responses)
+    }
+
+    pub async fn get_tenant(&self, tenant_id: Uuid) -> Result<Option<AdminTenantResponse>> {
+        match self.find_tenant(tenant_id).await? {
+            Some(tenant) => Ok(Some(self.with_usage(tenant).await)),
+            None => Ok(None),
+        }
+    }
+
+    /// Suspends a tenant: its requests are refused until it is reactivated. Operators cannot
+    /// suspend the tenant they are signed in through, which would lock them out.
+    pub async fn suspend_tenant(
+        &self,
+        caller_tenant_id: Uuid,
+        tenant_id: Uuid,
+        request: SuspendTenantRequest,
+    ) -> Result<Option<Tenant>> {
+        let Some(tenant) = self.find_tenant(tenant_id).await? else {
+            return Ok(None);
+        };
+        if tenant_id == caller_tenant_id {
+            return Err(AdminError::NotSuspendable(
+                "it is the tenant you are signed in through".to_string(),
+            )
+            .into());
+        }
+        if tenant.suspended_at.is_some() || !tenant.is_active.unwrap_or(false) {
+            return Err(AdminError::NotSuspendable("it is already suspended".to_string()).into());
+        }
+
+        let mut conn = self.shared.get_connection().await?;
+        let tenant = diesel::update(tenants::table.find(tenant_id))
+            .set((
+                tenants::is_active.eq(Some(false)),
+                tenants::suspended_at.eq(Some(Utc::now())),
+                tenants::suspended_reason.eq(Some(request.reason)),
+            ))
+            .returning(Tenant::as_returning())
+            .get_result::<Tenant>(&mut conn)
+            .await?;
+
+        self.forget(tenant_id);
+        Ok(Some(tenant))
+    }
+
+    pub async fn reactivate_tenant(&self, tenant_id: Uuid) -> Result<Option<Tenant>> {
+        let Some(tenant) = self.find_tenant(tenant_id).await? else {
+            return Ok(None);
+        };
+        if tenant.is_active.unwrap_or(false) {
+            return Err(AdminError::NotSuspended.into());
+        }
+
+        let mut conn = self.shared.get_connection().await?;
+        let tenant = diesel::update(tenants::table.find(tenant_id))
+            .set((
+                tenants::is_active.eq(Some(true)),
+                tenants::suspended_at.eq(None::<chrono::DateTime<Utc>>),
+                tenants::suspended_reason.eq(None::<String>),
+            ))
+            .returning(Tenant::as_returning())
+            .get_result::<Tenant>(&mut conn)
+            .await?;
+
+        self.forget(tenant_id);
+        Ok(Some(tenant))
+    }
+
+    /// Makes a member of the tenant its admin, e.g. when the only admin has left. The person is
+    /// reactivated if they were deactivated. `Ok(None)` when the tenant does not exist.
+    pub async fn reset_tenant_admin(
+        &self,
+        tenant_id: Uuid,
+        request: ResetTenantAdminRequest,
+    ) -> Result<Option<ResetTenantAdminResponse>> {
+        if self.find_tenant(tenant_id).await?.is_none() {
+            return Ok(None);
+        }
+
+        let demote_others = request.demote_others.unwrap_or(true);
+        let email = request.email;
+        let result = DatabaseService::scope_tenant(tenant_id, async {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            conn.transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    Self::promote_admin(conn, tenant_id, &email, demote_others).await
+                })
+            })
+            .await
+        })
+        .await?;
+
+        if !result.demoted_person_ids.is_empty() {
+            tracing::info!(
+                "Tenant {} admin reset to {}; demoted {:?}",
+                tenant_id,
+                result.person_id,
+                result.demoted_person_ids
+            );
+        }
+        if let Some(access_levels) = &self.access_levels {
+            for person_id in std::iter::once(&result.person_id).chain(&result.demoted_person_ids) {
+                access_levels.invalidate(tenant_id, *person_id);
+            }
+        }
+        Ok(Some(result))
+    }
+
+    // Platform health operations
+
+    pub async fn platform_health(
+        &self,
+        supabase: CircuitBreakerStatus,
+    ) -> Result<PlatformHealthResponse> {
+        let pool = self.shared.pool.state();
+        let connection = self.shared.get_connection().await;
+        let reachable = connection.is_ok();
+
+        let (tenants, persons, size_bytes, dedicated_databases) = match connection {
+            Ok(mut conn) => {
+                let counts = tenants::table
+                    .select((tenants::is_active, tenants::database_url.is_not_null()))
+                    .load::<(Option<bool>, bool)>(&mut conn)
+                    .await?;
+                let persons: i64 = person::table.count().get_result(&mut conn).await?;
+                let size = diesel::sql_query(
+                    "SELECT pg_database_size(current_database())::bigint AS size_bytes",
+                )
+                .get_result::<DatabaseSize>(&mut conn)
+                .await
+                .ok()
+                .map(|size| size.size_bytes);
+
+                let active = counts
+                    .iter()
+                    .filter(|(is_active, _)| is_active.unwrap_or(false))
+                    .count() as i64;
+                let tenants = PlatformTenantCounts {
+                    total: counts.len() as i64,
+                    active,
+                    suspended: counts.len() as i64 - active,
+                };
+                let dedicated = counts.iter().filter(|(_, dedicated)| *dedicated).count() as i64;
+                (tenants, persons, size, dedicated)
+            }
+            Err(_) => (
+                PlatformTenantCounts {
+                    total: 0,
+                    active: 0,
+                    suspended: 0,
+                },
+                0,
+                None,
+                0,
+            ),
+        };
+
+        Ok(PlatformHealthResponse {
+            tenants,
+            persons,
+            database: PlatformDatabaseHealth {
+                reachable,
+                size_bytes,
+                pool_connections: pool.connections,
+                pool_idle_connections: pool.idle_connections,
+                dedicated_databases,
+                dedicated_databases_connected: self.database.tenant_database_ids().len(),
+            },
+            supabase,
+            checked_at: Utc::now(),
+        })
+    }
+
+    /// Pool, queue, worker, webhook, Supabase and cache state for support to triage a running
+    /// server. Parts that cannot be read are left out rather than failing the whole report.
+    pub async fn diagnostics(&self, supabase: CircuitBreakerStatus) -> DiagnosticsResponse {
+        let since = Utc::now() - Duration::hours(WEBHOOK_WINDOW_HOURS);
+
+        // Shared database first, then each dedicated tenant database
+        let mut queues = match Self::queue_depths(&self.shared).await {
+            Ok(queues) => Some(queues),
+            Err(e) => {
+                tracing::error!("Failed to read queue depths: {}", e);
+                None
+            }
+        };
+        let mut deliveries = match Self::webhook_deliveries(&self.shared, since).await {
+            Ok(deliveries) => Some(deliveries),
+            Err(e) => {
+                tracing::error!("Failed to read webhook deliveries: {}", e);
+                None
+            }
+        };
+        for tenant_id in self.database.tenant_database_ids() {
+            let outcome = DatabaseService::scope_tenant(tenant_id, async {
+                let queues = Self::queue_depths(&self.database).await?;
+                let deliveries = Self::webhook_deliveries(&self.database, since).await?;
+                Ok::<_, anyhow::Error>((queues, deliveries))
+            })
+            .await;
+            match outcome {
+                Ok((tenant_queues, tenant_deliveries)) => {
+                    queues = queues.map(|queues| queues.merge(tenant_queues));
+                    if let Some(deliveries) = deliveries.as_mut() {
+                        deliveries.extend(tenant_deliveries);
+                    }
+                }
+                Err(e) => tracing::error!("Tenant {} database: {}", tenant_id, e),
+            }
+        }
+
+        let webhooks = deliveries.map(|deliveries| {
+            let delivered = deliveries.iter().map(|tenant| tenant.delivered).sum();
+            let failed = deliveries.iter().map(|tenant| tenant.failed).sum();
+            let mut failing_tenants: Vec<_> = deliveries
+                .into_iter()
+                .filter(|tenant| tenant.failed > 0)
+                .collect();
+            failing_tenants.sort_by_key(|tenant| std::cmp::Reverse(tenant.failed));
+            WebhookDeliveryStats {
+                window_hours: WEBHOOK_WINDOW_HOURS,
+                delivered,
+                failed,
+                failing_tenants,
+            }
+        });
+
+        DiagnosticsResponse {
+            database_pools: self.database.pool_stats(),
+            queues,
+            workers: Diagnostics::global().worker_runs(),
+            webhooks,
+            supabase,
+            tenant_caches: Diagnostics::global().tenant_cache_stats(),
+            checked_at: Utc::now(),
+        }
+    }
+
+    // Integrity check operations
+
+    /// Looks for references to rows that no longer exist, in the shared database and every
+    /// dedicated tenant database, and deletes or clears them when `repair` is set. A dedicated
+    /// database that cannot be checked is reported rather than failing the whole run.
+    pub async fn check_integrity(&self, request: IntegrityCheckRequest) -> Result<IntegrityReport> {
+        let checks =
+            select_checks(request.checks.as_deref()).map_err(AdminError::InvalidIntegrityCheck)?;
+        let repair = request.repair.unwrap_or(false);
+
+        let mut findings = Self::run_integrity_checks(&self.shared, &checks, repair, None).await?;
+        let mut failed_databases = Vec::new();
+        for tenant_id in self.database.tenant_database_ids() {
+            let outcome = DatabaseService::scope_tenant(
+                tenant_id,
+                Self::run_integrity_checks(&self.database, &checks, repair, Some(tenant_id)),
+            )
+            .await;
+            match outcome {
+                Ok(tenant_findings) => findings.extend(tenant_findings),
+                Err(e) => {
+                    tracing::error!("Tenant {} database: {}", tenant_id, e);
+                    failed_databases.push(tenant_id);
+                }
+            }
+        }
+
+        Ok(IntegrityReport {
+            checks_run: checks.len(),
+            dangling: findings.iter().map(|finding| finding.dangling).sum(),
+            repaired: findings.iter().filter_map(|finding| finding.repaired).sum(),
+            findings,
+            failed_databases,
+            checked_at: Utc::now(),
+        })
+    }
+
+    // Maintenance operations
+
+    /// Deletes blacklisted tokens that have expired, in the shared database and every dedicated
+    /// tenant database.
+    pub async fn purge_token_blacklist(&self) -> Result<TokenBlacklistPurgeReport> {
+        let (purged, failed_databases) = self
+            .for_each_database(None, |database| async move {
+                let mut conn = database.get_connection().await?;
+
+                // Maintenance spans tenants, so clear any tenant context left on the connection
+                conn.batch_execute("RESET app.current_tenant_id").await?;
+
+                let purged = diesel::delete(
+                    token_blacklist::table.filter(token_blacklist::expires_at.lt(Utc::now())),
+                )
+                .execute(&mut conn)
+                .await?;
+                Ok(purged as i64)
+            })
+            .await?;
+
+        Ok(TokenBlacklistPurgeReport {
+            purged: purged.into_iter().sum(),
+            failed_databases,
+            purged_at: Utc::now(),
+        })
+    }
+
+    /// Marks machines that stopped sending heartbeats before the cutoff decommissioned, which
+    /// drops them from machine lists. Status history and watcher notifications follow from the
+    /// status change as usual.
+    pub async fn decommission_stale_machines(
+        &self,
+        request: DecommissionStaleMachinesRequest,
+    ) -> Result<DecommissionReport> {
+        let cutoff = request.cutoff(Utc::now());
+        let dry_run = request.dry_run.unwrap_or(false);
+        let tenant_id = request.tenant_id;
+
+        let (found, failed_databases) = self
+            .for_each_database(tenant_id, |database| async move {
+                Self::decommission_machines(&database, cutoff, tenant_id, dry_run).await
+            })
+            .await?;
+        let machines: Vec<StaleMachine> = found.into_iter().flatten().collect();
+
+        Ok(DecommissionReport {
+            cutoff,
+            decommissioned: if dry_run { 0 } else { machines.len() as i64 },
+            machines,
+            dry_run,
+            failed_databases,
+        })
+    }
+
+    /// Folds machine telemetry and status history from before the cutoff into one row per
+    /// machine and day, deleting the raw rows. Each machine keeps one status history row at the
+    /// cutoff with the status it was in then.
+    pub async fn compact_machine_history(
+        &self,
+        request: CompactMachineHistoryRequest,
+    ) -> Result<MachineHistoryCompactionReport> {
+        let cutoff = request.cutoff(Utc::now());
+        let tenant_id = request.tenant_id;
+
+        let (compactions, failed_databases) = self
+            .for_each_database(tenant_id, |database| async move {
+                Self::compact_history(&database, cutoff, tenant_id).await
+            })
+            .await?;
+        let compacted = compactions.into_iter().fold(
+            MachineHistoryCompaction::default(),
+            |total, compaction| MachineHistoryCompaction {
+                telemetry_rows: total.telemetry_rows + compaction.telemetry_rows,
+                telemetry_days: total.telemetry_days + compaction.telemetry_days,
+                status_rows: total.status_rows + compaction.status_rows,
+                status_days: total.status_days + compaction.status_days,
+            },
+        );
+
+        Ok(MachineHistoryCompactionReport {
+            cutoff,
+            compacted,
+            failed_databases,
+            compacted_at: Utc::now(),
+        })
+    }
+
+    // Private helper methods
+
+    // Runs the checks against the database the current task is scoped to, returning those that
+    // found dangling references
+    async fn run_integrity_checks(
+        database: &DatabaseService,
+        checks: &[IntegrityCheck],
+        repair: bool,
+        database_tenant_id: Option<Uuid>,
+    ) -> Result<Vec<IntegrityCheckResult>> {
+        let mut conn = database.get_connection().await?;
+
+        // References span tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let mut findings = Vec::new();
+        for check in checks {
+            let dangling = diesel::sql_query(check.count_sql())
+                .get_result::<DanglingCount>(&mut conn)
+                .await?
+                .count;
+            if dangling == 0 {
+                continue;
+            }
+            let sample_ids = diesel::sql_query(check.sample_sql(INTEGRITY_SAMPLE_SIZE))
+                .load::<DanglingRow>(&mut conn)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect();
+            let repaired = if repair {
+                let repaired = diesel::sql_query(check.repair_sql())
+                    .execute(&mut conn)
+                    .await?;
+                tracing::warn!(
+                    "Integrity repair {} ({}) of {}: {} row(s)",
+                    check.name(),
+                    check.repair,
+                    database_tenant_id.map_or("shared database".to_string(), |tenant_id| {
+                        format!("tenant {} database", tenant_id)
+                    }),
+                    repaired
+                );
+                Some(repaired as i64)
+            } else {
+                None
+            };
+
+            findings.push(IntegrityCheckResult {
+                check: check.name(),
+                description: check.description.to_string(),
+                references: check.references.to_string(),
+                repair: check.repair,
+                database_tenant_id,
+                dangling,
+                sample_ids,
+                repaired,
+            });
+        }
+        Ok(findings)
+    }
+
+    // Runs `run` against the tenant's database when one is given, otherwise against the shared
+    // database and then every dedicated tenant database. A dedicated database that fails is
+    // reported rather than failing the whole run.
+    async fn for_each_database<T, F, Fut>(
+        &self,
+        tenant_id: Option<Uuid>,
+        run: F,
+    ) -> Result<(Vec<T>, Vec<Uuid>)>
+    where
+        F: Fn(DatabaseService) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if let Some(tenant_id) = tenant_id {
+            let outcome =
+                DatabaseService::scope_tenant(tenant_id, run(self.database.clone())).await?;
+            return Ok((vec![outcome], Vec::new()));
+        }
+
+        let mut outcomes = vec![run(self.shared.clone()).await?];
+        let mut failed_databases = Vec::new();
+        for tenant_id in self.database.tenant_database_ids() {
+            match DatabaseService::scope_tenant(tenant_id, run(self.database.clone())).await {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(e) => {
+                    tracing::error!("Tenant {} database: {}", tenant_id, e);
+                    failed_databases.push(tenant_id);
+                }
+            }
+        }
+        Ok((outcomes, failed_databases))
+    }
+
+    // Finds, and unless on a dry run decommissions, the stale machines in the database the
+    // current task is scoped to
+    async fn decommission_machines(
+        database: &DatabaseService,
+        cutoff: DateTime<Utc>,
+        tenant_id: Option<Uuid>,
+        dry_run: bool,
+    ) -> Result<Vec<StaleMachine>> {
+        let mut conn = database.get_connection().await?;
+
+        // Maintenance spans tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let decommissioned = MachineStatus::Decommissioned.to_string();
+        let mut stale = machines::table
+            .filter(machines::status.ne(&decommissioned))
+            .filter(
+                machines::last_heartbeat
+                    .lt(cutoff)
+                    .or(machines::last_heartbeat
+                        .is_null()
+                        .and(machines::created_at.lt(cutoff))),
+            )
+            .into_boxed();
+        if let Some(tenant_id) = tenant_id {
+            stale = stale.filter(machines::tenant_id.eq(tenant_id));
+        }
+
+        let machines: Vec<StaleMachine> = stale
+            .order((machines::tenant_id.asc(), machines::name.asc()))
+            .select((
+                machines::id,
+                machines::tenant_id,
+                machines::name,
+                machines::status,
+                machines::last_heartbeat,
+                machines::created_at,
+            ))
+            .load::<(
+                Uuid,
+                Uuid,
+                String,
+                String,
+                Option<DateTime<Utc>>,
+                Option<DateTime<Utc>>,
+            )>(&mut conn)
+            .await?
+            .into_iter()
+            .map(
+                |(id, tenant_id, name, status, last_heartbeat, created_at)| StaleMachine {
+                    id,
+                    tenant_id,
+                    name,
+                    status: MachineStatus::try_from(status).unwrap_or(MachineStatus::Offline),
+                    last_heartbeat,
+                    created_at,
+                },
+            )
+            .collect();
+
+        drop(conn);
+
+        if !dry_run && !machines.is_empty() {
+            // Each tenant's machines are retired under its own tenant context
+            let mut by_tenant: BTreeMap<Uuid, Vec<Uuid>> = BTreeMap::new();
+            for machine in &machines {
+                by_tenant
+                    .entry(machine.tenant_id)
+                    .or_default()
+                    .push(machine.id);
+            }
+            let reason = format!("No heartbeat since {}", cutoff.to_rfc3339());
+            for (machine_tenant_id, ids) in by_tenant {
+                let reason = &reason;
+                database
+                    .with_tenant_tx::<_, anyhow::Error, _>(machine_tenant_id, |conn| {
+                        Box::pin(async move {
+                            for id in ids {
+                                MachineService::retire_machine(
+                                    conn,
+                                    id,
+                                    Utc::now(),
+                                    reason,
+                                    None,
+                                    BTreeMap::new(),
+                                )
+                                .await?;
+                            }
+                            Ok(())
+                        })
+                    })
+                    .await?;
+            }
+            tracing::warn!(
+                "Decommissioned {} machine(s) without a heartbeat since {}",
+                machines.len(),
+                cutoff
+            );
+        }
+
+        Ok(machines)
+    }
+
+    // Compacts telemetry and status history before the cutoff in the database the current task
+    // is scoped to. Each compaction is a single statement, so it applies fully or not at all.
+    async fn compact_history(
+        database: &DatabaseService,
+        cutoff: DateTime<Utc>,
+        tenant_id: Option<Uuid>,
+    ) -> Result<MachineHistoryCompaction> {
+        let mut conn = database.get_connection().await?;
+
+        // Maintenance spans tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        // Running averages merge weighted by sample count, so compacting a day twice is exact
+        let telemetry = diesel::sql_query(
+            r#"
+            WITH compacted AS (
+                DELETE FROM machine_telemetry
+                WHERE recorded_at < $1
+                  AND ($2::uuid IS NULL OR tenant_id = $2)
+                RETURNING tenant_id, machine_id, channel, value, recorded_at
+            ),
+            written AS (
+                INSERT INTO machine_telemetry_daily AS d
+                    (tenant_id, machine_id, channel, day, samples, min_value, max_value, avg_value)
+                SELECT tenant_id, machine_id, channel, (recorded_at AT TIME ZONE 'UTC')::date,
+                       COUNT(*), MIN(value), MAX(value), AVG(value)
+                FROM compacted
+                GROUP BY tenant_id, machine_id, channel, (recorded_at AT TIME ZONE 'UTC')::date
+                ON CONFLICT (machine_id, channel, day) DO UPDATE SET
+                    samples = d.samples + EXCLUDED.samples,
+                    min_value = LEAST(d.min_value, EXCLUDED.min_value),
+                    max_value = GREATEST(d.max_value, EXCLUDED.max_value),
+                    avg_value = (d.avg_value * d.samples + EXCLUDED.avg_value * EXCLUDED.samples)
+                                / (d.samples + EXCLUDED.samples)
+                RETURNING 1
+            )
+            SELECT (SELECT COUNT(*) FROM compacted) AS row_count,
+                   (SELECT COUNT(*) FROM written) AS day_count
+            "#,
+        )
+        .bind::<Timestamptz, _>(cutoff)
+        .bind::<Nullable<SqlUuid>, _>(tenant_id)
+        .get_result::<CompactedCounts>(&mut conn)
+        .await?;
+
+        // Each status lasts until the next transition, or the cutoff for the last one, and is
+        // split across the UTC days it spans. The row kept at the cutoff repeats the status as
+        // its previous status, which marks it as not being a transition of its own.
+        let status = diesel::sql_query(
+            r#"
+            WITH history AS (
+                SELECT id, tenant_id, machine_id, previous_status, new_status,
+                       changed_at AT TIME ZONE 'UTC' AS started,
+                       COALESCE(
+                           LEAD(changed_at) OVER (PARTITION BY machine_id ORDER BY changed_at, id),
+                           $1
+                       ) AT TIME ZONE 'UTC' AS ended,
+                       ROW_NUMBER() OVER (
+                           PARTITION BY machine_id ORDER BY changed_at DESC, id DESC
+                       ) AS latest
+                FROM machine_status_history
+                WHERE changed_at < $1
+                  AND ($2::uuid IS NULL OR tenant_id = $2)
+            ),
+            written AS (
+                INSERT INTO machine_status_daily AS d
+                    (tenant_id, machine_id, day, status, transitions, seconds)
+                SELECT h.tenant_id, h.machine_id, day::date, h.new_status,
+                       COUNT(*) FILTER (
+                           WHERE h.started >= day
+                             AND h.previous_status IS DISTINCT FROM h.new_status
+                       ),
+                       SUM(EXTRACT(EPOCH FROM
+                           LEAST(h.ended, day + INTERVAL '1 day') - GREATEST(h.started, day)))
+                FROM history h
+                CROSS JOIN LATERAL generate_series(
+                    date_trunc('day', h.started),
+                    GREATEST(h.ended - INTERVAL '1 microsecond', h.started),
+                    INTERVAL '1 day'
+                ) AS day
+                GROUP BY h.tenant_id, h.machine_id, day, h.new_status
+                ON CONFLICT (machine_id, day, status) DO UPDATE SET
+                    transitions = d.transitions + EXCLUDED.transitions,
+                    seconds = d.seconds + EXCLUDED.seconds
+                RETURNING 1
+            ),
+            removed AS (
+                DELETE FROM machine_status_history
+                WHERE id IN (SELECT id FROM history)
+                RETURNING id
+            ),
+            kept AS (
+                INSERT INTO machine_status_history
+                    (machine_id, tenant_id, previous_status, new_status, changed_at)
+                SELECT h.machine_id, h.tenant_id, h.new_status, h.new_status, $1
+                FROM history h
+                WHERE h.latest = 1
+                  AND NOT EXISTS (
+                      SELECT 1 FROM machine_status_history x
+                      WHERE x.machine_id = h.machine_id AND x.changed_at = $1
+                  )
+                RETURNING 1
+            )
+            SELECT (SELECT COUNT(*) FROM removed) AS row_count,
+                   (SELECT COUNT(*) FROM written) AS day_count
+            "#,
+        )
+        .bind::<Timestamptz, _>(cutoff)
+        .bind::<Nullable<SqlUuid>, _>(tenant_id)
+        .get_result::<CompactedCounts>(&mut conn)
+        .await?;
+
+        Ok(MachineHistoryCompaction {
+            telemetry_rows: telemetry.row_count,
+            telemetry_days: telemetry.day_count,
+            status_rows: status.row_count,
+            status_days: status.day_count,
+        })
+    }
+
+    // Depths of the background work queues in the database the current task is scoped to
+    async fn queue_depths(database: &DatabaseService) -> Result<QueueDepths> {
+        let mut conn = database.get_connection().await?;
+
+        // Queues span tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        Ok(diesel::sql_query(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM report_schedules
+                  WHERE is_active AND next_run_at <= NOW()) AS report_schedules_due,
+                (SELECT COUNT(*) FROM print_jobs
+                  WHERE status = 'queued') AS print_jobs_queued,
+                (SELECT COUNT(*) FROM machine_heartbeat_events
+                  WHERE processed_at IS NULL) AS heartbeat_events_pending,
+                (SELECT MIN(received_at) FROM machine_heartbeat_events
+                  WHERE processed_at IS NULL) AS oldest_pending_heartbeat_at,
+                (SELECT COUNT(*) FROM recalculation_tasks
+                  WHERE status IN ('queued', 'running')) AS recalculations_queued
+            "#,
+        )
+        .get_result::<QueueDepths>(&mut conn)
+        .await?)
+    }
+
+    // Machine alert webhook deliveries per tenant since `since`, in the database the current
+    // task is scoped to
+    async fn webhook_deliveries(
+        database: &DatabaseService,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<TenantWebhookDeliveries>> {
+        let mut conn = database.get_connection().await?;
+
+        // Deliveries span tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        Ok(diesel::sql_query(
+            r#"
+            SELECT a.tenant_id,
+                   COUNT(*) FILTER (WHERE (r->>'succeeded')::boolean) AS delivered,
+                   COUNT(*) FILTER (WHERE NOT (r->>'succeeded')::boolean) AS failed,
+                   MAX(a.triggered_at) FILTER (WHERE NOT (r->>'succeeded')::boolean)
+                       AS last_failure_at,
+                   (ARRAY_AGG(r->>'detail' ORDER BY a.triggered_at DESC)
+                       FILTER (WHERE NOT (r->>'succeeded')::boolean))[1] AS last_error
+            FROM machine_alerts a
+            CROSS JOIN LATERAL jsonb_array_elements(a.action_results) r
+            WHERE r->>'action' = 'webhook'
+              AND a.triggered_at >= $1
+            GROUP BY a.tenant_id
+            "#,
+        )
+        .bind::<Timestamptz, _>(since)
+        .load::<TenantWebhookDeliveries>(&mut conn)
+        .await?)
+    }
+
+    async fn find_tenant(&self, tenant_id: Uuid) -> Result<Option<Tenant>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        Ok(tenants::table
+            .filter(tenants::id.eq(tenant_id))
+            .select(Tenant::as_select())
+            .first::<Tenant>(&mut conn)
+            .await
+            .optional()?)
+    }
+
+    fn forget(&self, tenant_id: Uuid) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(tenant_id);
+        }
+    }
+
+    // Usage is read from wherever the tenant's data lives; an unreachable dedicated database
+    // leaves it out rather than failing the whole listing
+    async fn with_usage(&self, tenant: Tenant) -> AdminTenantResponse {
+        let usage = DatabaseService::scope_tenant(tenant.id, self.tenant_usage(tenant.id))
+            .await
+            .map_err(|e| tracing::warn!("Usage of tenant {} not read: {}", tenant.id, e))
+            .ok();
+
+        AdminTenantResponse {
+            status: if tenant.is_active.unwrap_or(false) {
+                TenantStatus::Active
+            } else {
+                TenantStatus::Suspended
+            },
+            dedicated_database: tenant.database_url.is_some(),
+            usage,
+            tenant,
+        }
+    }
+
+    async fn tenant_usage(&self, tenant_id: Uuid) -> Result<TenantUsage> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let query = r#"
+            SELECT
+                (SELECT COUNT(*) FROM tenant_person WHERE tenant_id = $1) AS persons,
+                (SELECT COUNT(*) FROM inventory_items WHERE tenant_id = $1) AS items,
+                (SELECT COUNT(*) FROM orders WHERE tenant_id = $1) AS orders,
+                (SELECT COUNT(*) FROM jobs WHERE tenant_id = $1) AS jobs,
+                GREATEST(
+                    (SELECT MAX(created_at) FROM orders WHERE tenant_id = $1),
+                    (SELECT MAX(created_at) FROM jobs WHERE tenant_id = $1),
+                    (SELECT MAX(created_at) FROM stock_movements WHERE tenant_id = $1)
+                ) AS last_activity_at
+        "#;
+
+        Ok(diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .get_result::<TenantUsage>(&mut conn)
+            .await?)
+    }
+
+    async fn promote_admin(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        email: &str,
+        demote_others: bool,
+    ) -> Result<ResetTenantAdminResponse> {
+        let member = person::table
+            .inner_join(tenant_person::table.on(tenant_person::person_id.eq(person::id)))
+            .filter(person::email.eq(email))
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .select((person::id, tenant_person::id))
+            .first::<(Uuid, Uuid)>(conn)
+            .await
+            .optional()?;
+        let Some((person_id, membership_id)) = member else {
+            return Err(AdminError::InvalidAdmin(format!(
+                "{} is not a member of the tenant",
+                email
+            ))
+            .into());
+        };
+
+        let mut demoted_person_ids = Vec::new();
+        if demote_others {
+            let admins = tenant_person::table
+                .filter(tenant_person::tenant_id.eq(tenant_id))
+                .filter(tenant_person::id.ne(membership_id))
+                .select((
+                    tenant_person::id,
+                    tenant_person::person_id,
+                    tenant_person::access_level,
+                ))
+                .load::<(Uuid, Uuid, Option<Vec<Option<String>>>)>(conn)
+                .await?;
+
+            for (id, other_person_id, levels) in admins {
+                if AccessLevel::highest(&levels.unwrap_or_default()) != AccessLevel::Admin {
+                    continue;
+                }
+                diesel::update(tenant_person::table.find(id))
+                    .set(
+                        tenant_person::access_level
+                            .eq(Some(vec![Some(AccessLevel::Standard.to_string())])),
+                    )
+                    .execute(conn)
+                    .await?;
+                demoted_person_ids.push(other_person_id);
+            }
+        }
+
+        diesel::update(tenant_person::table.find(membership_id))
+            .set(tenant_person::access_level.eq(Some(vec![Some(AccessLevel::Admin.to_string())])))
+            .execute(conn)
+            .await?;
+        diesel::update(person::table.find(person_id))
+            .set(person::is_active.eq(Some(true)))
+            .execute(conn)
+            .await?;
+
+        Ok(ResetTenantAdminResponse {
+            tenant_id,
+            person_id,
+            email: email.to_string(),
+            demoted_person_ids,
+        })
+    }
+}
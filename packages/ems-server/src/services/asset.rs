@@ -1,17 +1,111 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{Eq, InnerJoinOn, IntoBoxed};
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use diesel::sql_types::Uuid as SqlUuid;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    Asset, AssetResponse, AssetSummary, AssetType, AssetTypeResponse, CreateAssetIdResponse,
-    CreateAssetRequest, CreateAssetTypeRequest, FirmwareSpecific, FirmwareSpecificResponse,
-    NewAsset, NewAssetType, NewFirmwareSpecific, Person, PersonSummary, UpdateAssetRequest,
+    Asset, AssetReleaseStatus, AssetResponse, AssetScanStatus, AssetSignature,
+    AssetSignatureResponse, AssetSummary, AssetType, AssetTypeEnum, AssetTypeResponse,
+    AssetVersionHistoryResponse, AssetVersionSummary, CreateAssetIdResponse, CreateAssetRequest,
+    CreateAssetTypeRequest, CreateAssetVersionRequest, DataVersion, FirmwareSpecific,
+    FirmwareSpecificResponse, NewAsset, NewAssetSignature, NewAssetType, NewFirmwareSpecific,
+    Person, PersonSummary, SignAssetRequest, SignatureMeaning, UpdateAssetRequest,
     UpdateAssetTypeRequest,
 };
 use crate::schema::*;
 use crate::services::DatabaseService;
+use crate::utils::AppError;
+
+/// Errors from the asset and asset upload services that are not failures of the services
+/// themselves
+#[derive(Error, Debug)]
+pub enum AssetError {
+    #[error("Asset storage is not configured")]
+    StorageNotConfigured,
+
+    #[error("Invalid asset upload: {0}")]
+    InvalidUpload(String),
+
+    #[error("{0}")]
+    InvalidChunk(String),
+
+    #[error("Invalid firmware update: {0}")]
+    InvalidFirmwareUpdate(String),
+
+    #[error("Released document cannot be modified; upload a new version")]
+    Released,
+
+    #[error("Asset not released: {0}")]
+    NotReleased(Uuid),
+
+    #[error("Version already exists: {0}")]
+    VersionExists(String),
+
+    #[error("Retention period active until {0}")]
+    Retained(DateTime<Utc>),
+
+    #[error("Asset is quarantined: scan {0}")]
+    Quarantined(String),
+
+    #[error("Upload is not accepting chunks: {0}")]
+    NotAcceptingChunks(String),
+
+    #[error("Upload cannot be completed: {0}")]
+    NotCompletable(String),
+
+    #[error("Upload cannot be confirmed: {0}")]
+    NotConfirmable(String),
+
+    #[error("Upload cannot be aborted: {0}")]
+    NotAbortable(String),
+
+    #[error("Upload incomplete: {0}")]
+    Incomplete(String),
+
+    #[error("Firmware cannot be updated: {0}")]
+    FirmwareNotUpdatable(String),
+
+    #[error("{0}")]
+    Mismatch(String),
+
+    #[error("Upload expired; start a new upload")]
+    Expired,
+}
+
+impl From<AssetError> for AppError {
+    fn from(error: AssetError) -> Self {
+        match error {
+            AssetError::StorageNotConfigured => AppError::Unavailable(error.to_string()),
+            AssetError::InvalidUpload(_)
+            | AssetError::InvalidChunk(_)
+            | AssetError::InvalidFirmwareUpdate(_)
+            | AssetError::Mismatch(_) => AppError::Validation(error.to_string()),
+            AssetError::Released
+            | AssetError::NotReleased(_)
+            | AssetError::VersionExists(_)
+            | AssetError::Retained(_)
+            | AssetError::Quarantined(_)
+            | AssetError::NotAcceptingChunks(_)
+            | AssetError::NotCompletable(_)
+            | AssetError::NotConfirmable(_)
+            | AssetError::NotAbortable(_)
+            | AssetError::Incomplete(_)
+            | AssetError::FirmwareNotUpdatable(_)
+            | AssetError::Expired => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+type ListedAssets = IntoBoxed<
+    'static,
+    InnerJoinOn<assets::table, asset_types::table, Eq<asset_types::id, assets::asset_type_id>>,
+    Pg,
+>;
 
 pub struct AssetService {
     database: DatabaseService,
@@ -83,35 +177,40 @@ impl AssetService {
     ) -> Result<AssetType> {
         let mut conn = self.database.get_connection().await?;
 
-        // Update fields individually to avoid Diesel type issues
-        if let Some(name) = &request.name {
-            diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
-                .set(asset_types::name.eq(name))
-                .execute(&mut conn)
-                .await?;
-        }
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            Box::pin(async move {
+                // Update fields individually to avoid Diesel type issues
+                if let Some(name) = &request.name {
+                    diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
+                        .set(asset_types::name.eq(name))
+                        .execute(conn)
+                        .await?;
+                }
 
-        if let Some(description) = &request.description {
-            diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
-                .set(asset_types::description.eq(description))
-                .execute(&mut conn)
-                .await?;
-        }
+                if let Some(description) = &request.description {
+                    diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
+                        .set(asset_types::description.eq(description))
+                        .execute(conn)
+                        .await?;
+                }
 
-        // Always update the updated_at timestamp
-        diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
-            .set(asset_types::updated_at.eq(Utc::now()))
-            .execute(&mut conn)
-            .await?;
+                // Always update the updated_at timestamp
+                diesel::update(asset_types::table.filter(asset_types::id.eq(asset_type_id)))
+                    .set(asset_types::updated_at.eq(Utc::now()))
+                    .execute(conn)
+                    .await?;
 
-        // Return the updated asset type
-        let asset_type = asset_types::table
-            .filter(asset_types::id.eq(asset_type_id))
-            .select(AssetType::as_select())
-            .first::<AssetType>(&mut conn)
-            .await?;
+                // Return the updated asset type
+                let asset_type = asset_types::table
+                    .filter(asset_types::id.eq(asset_type_id))
+                    .select(AssetType::as_select())
+                    .first::<AssetType>(conn)
+                    .await?;
 
-        Ok(asset_type)
+                Ok(asset_type)
+            })
+        })
+        .await
     }
 
     pub async fn delete_asset_type(&self, asset_type_id: Uuid) -> Result<()> {
@@ -141,6 +240,14 @@ impl AssetService {
         let asset_id = conn
             .transaction::<_, diesel::result::Error, _>(|conn| {
                 Box::pin(async move {
+                    // Controlled documents wait for an approval signature
+                    let release_status =
+                        if Self::is_controlled_type(conn, request.asset_type_id).await? {
+                            Some(AssetReleaseStatus::Draft.to_string())
+                        } else {
+                            None
+                        };
+
                     // Create asset record
                     let new_asset = NewAsset {
                         tenant_id,
@@ -156,6 +263,11 @@ impl AssetService {
                         is_active: request.is_active.or(Some(true)),
                         metadata: request.metadata,
                         created_by_id,
+                        lineage_id: None,
+                        previous_version_id: None,
+                        is_current: None,
+                        release_status,
+                        retain_until: request.retain_until,
                     };
 
                     let asset: Asset = diesel::insert_into(assets::table)
@@ -186,8 +298,7 @@ impl AssetService {
                     Ok(asset.id)
                 })
             })
-            .await
-            .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+            .await?;
 
         Ok(CreateAssetIdResponse { id: asset_id })
     }
@@ -268,6 +379,17 @@ impl AssetService {
                     name: person.name,
                     email: person.email,
                 },
+                lineage_id: asset.lineage_id,
+                previous_version_id: asset.previous_version_id,
+                is_current: asset.is_current,
+                release_status: AssetReleaseStatus::try_from(asset.release_status)
+                    .unwrap_or(AssetReleaseStatus::Released),
+                released_at: asset.released_at,
+                retain_until: asset.retain_until,
+                scan_status: asset
+                    .scan_status
+                    .and_then(|status| AssetScanStatus::try_from(status).ok()),
+                scanned_at: asset.scanned_at,
                 created_at: asset.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: asset.updated_at.unwrap_or_else(|| Utc::now()),
                 firmware_details,
@@ -292,23 +414,7 @@ impl AssetService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        let mut query = assets::table
-            .inner_join(asset_types::table.on(asset_types::id.eq(assets::asset_type_id)))
-            .filter(assets::tenant_id.eq(tenant_id))
-            .into_boxed();
-
-        // Apply filters
-        if let Some(type_id) = asset_type_id {
-            query = query.filter(assets::asset_type_id.eq(type_id));
-        }
-
-        if let Some(item_filter) = item_id {
-            query = query.filter(assets::item_id.eq(item_filter));
-        }
-
-        if let Some(active_filter) = is_active {
-            query = query.filter(assets::is_active.eq(active_filter));
-        }
+        let mut query = Self::listed_assets(tenant_id, asset_type_id, item_id, is_active);
 
         // Apply pagination
         if let Some(limit_val) = limit {
@@ -332,6 +438,10 @@ impl AssetService {
                 asset_type: asset_type.name,
                 file_type: asset.file_type,
                 is_active: asset.is_active.unwrap_or(true),
+                lineage_id: asset.lineage_id,
+                is_current: asset.is_current,
+                release_status: AssetReleaseStatus::try_from(asset.release_status)
+                    .unwrap_or(AssetReleaseStatus::Released),
                 created_at: asset.created_at.unwrap_or_else(|| Utc::now()),
             })
             .collect();
@@ -339,6 +449,112 @@ impl AssetService {
         Ok(summaries)
     }
 
+    /// Version of the assets [`Self::list_assets`] lists with these filters, across all pages.
+    pub async fn list_version(
+        &self,
+        tenant_id: Uuid,
+        asset_type_id: Option<Uuid>,
+        item_id: Option<Uuid>,
+        is_active: Option<bool>,
+    ) -> Result<DataVersion> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let (rows, assets_updated_at, types_updated_at) =
+            Self::listed_assets(tenant_id, asset_type_id, item_id, is_active)
+                .select((
+                    diesel::dsl::count_star(),
+                    diesel::dsl::max(assets::updated_at),
+                    diesel::dsl::max(asset_types::updated_at),
+                ))
+                .first::<(i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(&mut conn)
+                .await?;
+
+        Ok(DataVersion::new(
+            rows,
+            &[assets_updated_at, types_updated_at],
+        ))
+    }
+
+    /// Version of the asset [`Self::get_asset_by_id`] returns, including its type, creator and
+    /// firmware details, or `None` when there is none.
+    pub async fn asset_version(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<DataVersion>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some((asset_updated_at, type_updated_at, person_updated_at)) = assets::table
+            .inner_join(asset_types::table.on(asset_types::id.eq(assets::asset_type_id)))
+            .inner_join(person::table.on(person::id.eq(assets::created_by_id)))
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .select((
+                assets::updated_at,
+                asset_types::updated_at,
+                person::updated_at,
+            ))
+            .first::<(
+                Option<DateTime<Utc>>,
+                Option<DateTime<Utc>>,
+                Option<DateTime<Utc>>,
+            )>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let (firmware, firmware_updated_at) = firmware_specific::table
+            .filter(firmware_specific::asset_id.eq(asset_id))
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(firmware_specific::updated_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)
+            .await?;
+
+        Ok(Some(
+            DataVersion::new(1, &[asset_updated_at, type_updated_at, person_updated_at])
+                .and(DataVersion::new(firmware, &[firmware_updated_at])),
+        ))
+    }
+
+    /// The tenant's assets with their types, narrowed by the list filters.
+    fn listed_assets(
+        tenant_id: Uuid,
+        asset_type_id: Option<Uuid>,
+        item_id: Option<Uuid>,
+        is_active: Option<bool>,
+    ) -> ListedAssets {
+        let mut query = assets::table
+            .inner_join(asset_types::table.on(asset_types::id.eq(assets::asset_type_id)))
+            .into_boxed()
+            .filter(assets::tenant_id.eq(tenant_id));
+
+        // Apply filters
+        if let Some(type_id) = asset_type_id {
+            query = query.filter(assets::asset_type_id.eq(type_id));
+        }
+
+        if let Some(item_filter) = item_id {
+            query = query.filter(assets::item_id.eq(item_filter));
+        }
+
+        if let Some(active_filter) = is_active {
+            query = query.filter(assets::is_active.eq(active_filter));
+        }
+
+        query
+    }
+
     pub async fn update_asset(
         &self,
         tenant_id: Uuid,
@@ -351,6 +567,29 @@ impl AssetService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        // Released controlled documents change content only through a new version
+        let changes_content = request.version.is_some()
+            || request.file_path.is_some()
+            || request.file_size.is_some()
+            || request.file_type.is_some()
+            || request.checksum.is_some();
+        if changes_content {
+            let existing = assets::table
+                .filter(assets::id.eq(asset_id))
+                .filter(assets::tenant_id.eq(tenant_id))
+                .select(Asset::as_select())
+                .first::<Asset>(&mut conn)
+                .await
+                .optional()?;
+            if let Some(existing) = existing {
+                if existing.release_status == AssetReleaseStatus::Released.to_string()
+                    && Self::is_controlled_type(&mut conn, existing.asset_type_id).await?
+                {
+                    return Err(AssetError::Released.into());
+                }
+            }
+        }
+
         conn.transaction::<_, diesel::result::Error, _>(|conn| {
             Box::pin(async move {
                 // Update asset fields individually
@@ -417,6 +656,13 @@ impl AssetService {
                         .await?;
                 }
 
+                if let Some(retain_until) = request.retain_until {
+                    diesel::update(assets::table.filter(assets::id.eq(asset_id)))
+                        .set(assets::retain_until.eq(retain_until))
+                        .execute(conn)
+                        .await?;
+                }
+
                 // Always update the updated_at timestamp
                 diesel::update(assets::table.filter(assets::id.eq(asset_id)))
                     .set(assets::updated_at.eq(Utc::now()))
@@ -507,8 +753,7 @@ impl AssetService {
                 Ok(())
             })
         })
-        .await
-        .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+        .await?;
 
         // Return the updated asset
         self.get_asset_by_id(tenant_id, asset_id)
@@ -523,8 +768,33 @@ impl AssetService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        let retain_until = assets::table
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .select(assets::retain_until)
+            .first::<Option<chrono::DateTime<Utc>>>(&mut conn)
+            .await
+            .optional()?
+            .flatten();
+        if let Some(retain_until) = retain_until.filter(|r| *r > Utc::now()) {
+            return Err(AssetError::Retained(retain_until).into());
+        }
+
         conn.transaction::<_, diesel::result::Error, _>(|conn| {
             Box::pin(async move {
+                let asset = assets::table
+                    .filter(assets::id.eq(asset_id))
+                    .filter(assets::tenant_id.eq(tenant_id))
+                    .for_update()
+                    .select(Asset::as_select())
+                    .first::<Asset>(conn)
+                    .await
+                    .optional()?;
+
+                if let Some(asset) = &asset {
+                    Self::unlink_version(conn, asset).await?;
+                }
+
                 // Delete firmware-specific records first (if any)
                 diesel::delete(
                     firmware_specific::table.filter(firmware_specific::asset_id.eq(asset_id)),
@@ -541,15 +811,502 @@ impl AssetService {
                 .execute(conn)
                 .await?;
 
+                // Hand the current flag back to the version this one superseded
+                if let Some(asset) = asset.filter(|a| a.is_current) {
+                    if let Some(replacement_id) = Self::replacement_version(conn, &asset).await? {
+                        diesel::update(assets::table.filter(assets::id.eq(replacement_id)))
+                            .set(assets::is_current.eq(true))
+                            .execute(conn)
+                            .await?;
+                    }
+                }
+
                 Ok(())
             })
         })
-        .await
-        .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+        .await?;
 
         Ok(())
     }
 
+    // Asset version chains
+
+    /// Uploads a new version of the logical asset `asset_id` belongs to. The new version supersedes
+    /// the current one and links to it as its predecessor; name, item and asset type are inherited.
+    /// A controlled document revision is created as a draft and supersedes only once approved.
+    /// Returns `None` when the asset does not exist.
+    pub async fn create_asset_version(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Uuid,
+        asset_id: Uuid,
+        request: CreateAssetVersionRequest,
+    ) -> Result<Option<CreateAssetIdResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let version_id = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let asset = assets::table
+                        .filter(assets::id.eq(asset_id))
+                        .filter(assets::tenant_id.eq(tenant_id))
+                        .select(Asset::as_select())
+                        .first::<Asset>(conn)
+                        .await
+                        .optional()?;
+
+                    let asset = match asset {
+                        Some(asset) => asset,
+                        None => return Ok(None),
+                    };
+
+                    // Lock the current version so concurrent uploads serialize
+                    let predecessor = assets::table
+                        .filter(assets::lineage_id.eq(asset.lineage_id))
+                        .filter(assets::is_current.eq(true))
+                        .for_update()
+                        .select(Asset::as_select())
+                        .first::<Asset>(conn)
+                        .await
+                        .optional()?
+                        .unwrap_or(asset);
+
+                    let existing: i64 = assets::table
+                        .filter(assets::lineage_id.eq(predecessor.lineage_id))
+                        .filter(assets::version.eq(&request.version))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if existing > 0 {
+                        return Err(AssetError::VersionExists(request.version.clone()).into());
+                    }
+
+                    // A controlled document revision stays a draft beside the current version
+                    // until it is approved
+                    let controlled =
+                        Self::is_controlled_type(conn, predecessor.asset_type_id).await?;
+
+                    if !controlled {
+                        diesel::update(
+                            assets::table
+                                .filter(assets::lineage_id.eq(predecessor.lineage_id))
+                                .filter(assets::is_current.eq(true)),
+                        )
+                        .set(assets::is_current.eq(false))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    let new_asset = NewAsset {
+                        tenant_id,
+                        item_id: predecessor.item_id,
+                        asset_type_id: predecessor.asset_type_id,
+                        name: predecessor.name.clone(),
+                        version: Some(request.version),
+                        description: request.description.or(predecessor.description),
+                        file_path: request.file_path,
+                        file_size: request.file_size,
+                        file_type: request.file_type,
+                        checksum: request.checksum,
+                        is_active: Some(true),
+                        metadata: request.metadata.or(predecessor.metadata),
+                        created_by_id,
+                        lineage_id: Some(predecessor.lineage_id),
+                        previous_version_id: Some(predecessor.id),
+                        is_current: Some(!controlled),
+                        release_status: controlled.then(|| AssetReleaseStatus::Draft.to_string()),
+                        retain_until: request.retain_until.or(predecessor.retain_until),
+                    };
+
+                    let version: Asset = diesel::insert_into(assets::table)
+                        .values(&new_asset)
+                        .returning(Asset::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let new_firmware_specific = match request.firmware_details {
+                        Some(firmware_details) => Some(NewFirmwareSpecific {
+                            asset_id: version.id,
+                            hardware_version: firmware_details.hardware_version,
+                            min_hardware_version: firmware_details.min_hardware_version,
+                            max_hardware_version: firmware_details.max_hardware_version,
+                            release_notes: firmware_details.release_notes,
+                            is_beta: firmware_details.is_beta,
+                            is_critical: firmware_details.is_critical,
+                            requires_manual_update: firmware_details.requires_manual_update,
+                        }),
+                        // Carry over the hardware compatibility of the superseded version
+                        None => firmware_specific::table
+                            .filter(firmware_specific::asset_id.eq(predecessor.id))
+                            .select(FirmwareSpecific::as_select())
+                            .first::<FirmwareSpecific>(conn)
+                            .await
+                            .optional()?
+                            .map(|fs| NewFirmwareSpecific {
+                                asset_id: version.id,
+                                hardware_version: fs.hardware_version,
+                                min_hardware_version: fs.min_hardware_version,
+                                max_hardware_version: fs.max_hardware_version,
+                                release_notes: None,
+                                is_beta: fs.is_beta,
+                                is_critical: fs.is_critical,
+                                requires_manual_update: fs.requires_manual_update,
+                            }),
+                    };
+
+                    if let Some(new_firmware_specific) = new_firmware_specific {
+                        diesel::insert_into(firmware_specific::table)
+                            .values(&new_firmware_specific)
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(Some(version.id))
+                })
+            })
+            .await?;
+
+        Ok(version_id.map(|id| CreateAssetIdResponse { id }))
+    }
+
+    /// Every version of the logical asset `asset_id` belongs to, newest first.
+    pub async fn list_asset_versions(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<AssetVersionHistoryResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let lineage_id = assets::table
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .select(assets::lineage_id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?;
+
+        let lineage_id = match lineage_id {
+            Some(lineage_id) => lineage_id,
+            None => return Ok(None),
+        };
+
+        let versions = assets::table
+            .filter(assets::lineage_id.eq(lineage_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .order(assets::created_at.desc())
+            .select(Asset::as_select())
+            .load::<Asset>(&mut conn)
+            .await?;
+
+        let current_version_id = versions.iter().find(|v| v.is_current).map(|v| v.id);
+
+        Ok(Some(AssetVersionHistoryResponse {
+            lineage_id,
+            current_version_id,
+            versions: versions
+                .into_iter()
+                .map(AssetVersionSummary::from)
+                .collect(),
+        }))
+    }
+
+    /// Makes `asset_id` the current version of its lineage again. Relationships pinned to latest
+    /// follow it; relationships pinned to a specific version are untouched. Only released
+    /// versions can be rolled back to. Returns `None` when the asset does not exist.
+    pub async fn rollback_asset_version(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<AssetResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let found = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let asset = assets::table
+                        .filter(assets::id.eq(asset_id))
+                        .filter(assets::tenant_id.eq(tenant_id))
+                        .select(Asset::as_select())
+                        .first::<Asset>(conn)
+                        .await
+                        .optional()?;
+
+                    let asset = match asset {
+                        Some(asset) => asset,
+                        None => return Ok(false),
+                    };
+
+                    if asset.release_status != AssetReleaseStatus::Released.to_string() {
+                        return Err(AssetError::NotReleased(asset.id).into());
+                    }
+
+                    if !asset.is_current {
+                        // Lock the lineage so a concurrent upload or rollback waits
+                        assets::table
+                            .filter(assets::lineage_id.eq(asset.lineage_id))
+                            .for_update()
+                            .select(assets::id)
+                            .load::<Uuid>(conn)
+                            .await?;
+
+                        diesel::update(
+                            assets::table
+                                .filter(assets::lineage_id.eq(asset.lineage_id))
+                                .filter(assets::is_current.eq(true)),
+                        )
+                        .set(assets::is_current.eq(false))
+                        .execute(conn)
+                        .await?;
+
+                        diesel::update(assets::table.filter(assets::id.eq(asset.id)))
+                            .set((
+                                assets::is_current.eq(true),
+                                assets::updated_at.eq(Utc::now()),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(true)
+                })
+            })
+            .await?;
+
+        if !found {
+            return Ok(None);
+        }
+
+        self.get_asset_by_id(tenant_id, asset_id).await
+    }
+
+    // Electronic signatures
+
+    /// Records `signed_by_id`'s signature on the asset. An approval signature releases a draft and,
+    /// for a revision, makes it the current version of its lineage.
+    /// Returns `None` when the asset does not exist.
+    pub async fn sign_asset(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        signed_by_id: Uuid,
+        request: SignAssetRequest,
+    ) -> Result<Option<AssetSignatureResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let signature = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    // Lock the asset so concurrent approvals serialize
+                    let asset = assets::table
+                        .filter(assets::id.eq(asset_id))
+                        .filter(assets::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Asset::as_select())
+                        .first::<Asset>(conn)
+                        .await
+                        .optional()?;
+
+                    let asset = match asset {
+                        Some(asset) => asset,
+                        None => return Ok(None),
+                    };
+
+                    let new_signature = NewAssetSignature {
+                        tenant_id,
+                        asset_id,
+                        signed_by_id,
+                        meaning: request.meaning.to_string(),
+                        comment: request.comment,
+                        content_checksum: asset.checksum.clone(),
+                    };
+
+                    let signature: AssetSignature = diesel::insert_into(asset_signatures::table)
+                        .values(&new_signature)
+                        .returning(AssetSignature::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    if request.meaning == SignatureMeaning::Approved
+                        && asset.release_status == AssetReleaseStatus::Draft.to_string()
+                    {
+                        diesel::update(
+                            assets::table
+                                .filter(assets::lineage_id.eq(asset.lineage_id))
+                                .filter(assets::is_current.eq(true))
+                                .filter(assets::id.ne(asset.id)),
+                        )
+                        .set(assets::is_current.eq(false))
+                        .execute(conn)
+                        .await?;
+
+                        diesel::update(assets::table.filter(assets::id.eq(asset.id)))
+                            .set((
+                                assets::release_status.eq(AssetReleaseStatus::Released.to_string()),
+                                assets::released_at.eq(Some(signature.signed_at)),
+                                assets::is_current.eq(true),
+                                assets::updated_at.eq(Utc::now()),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let signer = person::table
+                        .filter(person::id.eq(signed_by_id))
+                        .select(Person::as_select())
+                        .first::<Person>(conn)
+                        .await?;
+
+                    Ok(Some(AssetSignatureResponse::new(signature, signer)))
+                })
+            })
+            .await?;
+
+        Ok(signature)
+    }
+
+    /// Signatures on the asset, oldest first. Returns `None` when the asset does not exist.
+    pub async fn list_asset_signatures(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<Vec<AssetSignatureResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let exists: i64 = assets::table
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if exists == 0 {
+            return Ok(None);
+        }
+
+        let signatures = asset_signatures::table
+            .inner_join(person::table.on(person::id.eq(asset_signatures::signed_by_id)))
+            .filter(asset_signatures::asset_id.eq(asset_id))
+            .order(asset_signatures::signed_at.asc())
+            .select((AssetSignature::as_select(), Person::as_select()))
+            .load::<(AssetSignature, Person)>(&mut conn)
+            .await?;
+
+        Ok(Some(
+            signatures
+                .into_iter()
+                .map(|(signature, signer)| AssetSignatureResponse::new(signature, signer))
+                .collect(),
+        ))
+    }
+
+    pub(crate) async fn is_controlled_type(
+        conn: &mut AsyncPgConnection,
+        asset_type_id: Uuid,
+    ) -> Result<bool, diesel::result::Error> {
+        let name = asset_types::table
+            .filter(asset_types::id.eq(asset_type_id))
+            .select(asset_types::name)
+            .first::<String>(conn)
+            .await
+            .optional()?;
+
+        Ok(name
+            .and_then(|name| AssetTypeEnum::try_from(name).ok())
+            .is_some_and(|asset_type| asset_type.is_controlled()))
+    }
+
+    /// Removes a version from its chain before it is deleted: successors link to its predecessor,
+    /// and machine relationships pinned to latest through it move to the version that will be
+    /// current afterwards, so the cascade on delete does not drop them.
+    async fn unlink_version(
+        conn: &mut AsyncPgConnection,
+        asset: &Asset,
+    ) -> Result<(), diesel::result::Error> {
+        diesel::update(assets::table.filter(assets::previous_version_id.eq(asset.id)))
+            .set(assets::previous_version_id.eq(asset.previous_version_id))
+            .execute(conn)
+            .await?;
+
+        let next_current = if asset.is_current {
+            Self::replacement_version(conn, asset).await?
+        } else {
+            assets::table
+                .filter(assets::lineage_id.eq(asset.lineage_id))
+                .filter(assets::is_current.eq(true))
+                .select(assets::id)
+                .first::<Uuid>(conn)
+                .await
+                .optional()?
+        };
+
+        if let Some(next_current) = next_current {
+            // Skip relationships the machine already holds for that version
+            diesel::sql_query(
+                "UPDATE machine_asset_relationships r SET asset_id = $1 \
+                 WHERE r.asset_id = $2 AND r.pin_mode = 'latest' AND NOT EXISTS ( \
+                     SELECT 1 FROM machine_asset_relationships o \
+                     WHERE o.machine_id = r.machine_id AND o.asset_id = $1 \
+                       AND o.relationship_type = r.relationship_type)",
+            )
+            .bind::<SqlUuid, _>(next_current)
+            .bind::<SqlUuid, _>(asset.id)
+            .execute(conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Version that becomes current when the current version `asset` is deleted: its predecessor,
+    /// or the newest remaining version when the chain was broken.
+    async fn replacement_version(
+        conn: &mut AsyncPgConnection,
+        asset: &Asset,
+    ) -> Result<Option<Uuid>, diesel::result::Error> {
+        let predecessor = match asset.previous_version_id {
+            Some(previous_id) => assets::table
+                .filter(assets::id.eq(previous_id))
+                .select(assets::id)
+                .first::<Uuid>(conn)
+                .await
+                .optional()?,
+            None => None,
+        };
+
+        if predecessor.is_some() {
+            return Ok(predecessor);
+        }
+
+        assets::table
+            .filter(assets::lineage_id.eq(asset.lineage_id))
+            .filter(assets::id.ne(asset.id))
+            .filter(assets::release_status.eq(AssetReleaseStatus::Released.to_string()))
+            .order(assets::created_at.desc())
+            .select(assets::id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()
+    }
+
     // Get assets by item ID
     pub async fn get_assets_by_item_id(
         &self,
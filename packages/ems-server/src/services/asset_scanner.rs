@@ -0,0 +1,226 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::stream::{BoxStream, StreamExt};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::{env, sync::Arc, time::Duration};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+// A scan must finish within this time, however large the file
+const SCAN_TIMEOUT: Duration = Duration::from_secs(600);
+
+const DEFAULT_CLAMAV_ADDRESS: &str = "127.0.0.1:3310";
+
+// clamd takes a stream as length-prefixed pieces; it refuses pieces above its own limits
+const CLAMAV_PIECE_BYTES: usize = 1024 * 1024;
+
+/// A file handed to a content scanner.
+pub struct ScanFile {
+    pub file_name: String,
+    pub size_bytes: u64,
+    /// SHA-256 (hex) of the whole file
+    pub sha256: String,
+    /// Short-lived URL the file can be downloaded from
+    pub url: String,
+    /// The file's content, in order. Read lazily, so scanners that download from `url` never
+    /// pull it.
+    pub content: BoxStream<'static, Result<Vec<u8>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { signature: String },
+}
+
+/// Antivirus or content scanning of uploaded asset files.
+#[async_trait]
+pub trait ContentScanner: Send + Sync {
+    /// Name recorded with each scan result
+    fn name(&self) -> &'static str;
+
+    async fn scan(&self, file: ScanFile) -> Result<ScanVerdict>;
+}
+
+/// The scanner ASSET_SCANNER selects: `clamav` (a clamd daemon at CLAMAV_ADDRESS, a host:port
+/// or a Unix socket path) or `http` (the scanning API at ASSET_SCAN_URL). `Ok(None)` when
+/// ASSET_SCANNER is not set or `none`.
+pub fn content_scanner_from_env() -> Result<Option<Arc<dyn ContentScanner>>> {
+    let scanner = env::var("ASSET_SCANNER")
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match scanner.as_str() {
+        "" | "none" => Ok(None),
+        "clamav" => {
+            let address = env::var("CLAMAV_ADDRESS")
+                .ok()
+                .filter(|a| !a.is_empty())
+                .unwrap_or_else(|| DEFAULT_CLAMAV_ADDRESS.to_string());
+            Ok(Some(Arc::new(ClamAvScanner::new(address))))
+        }
+        "http" => {
+            let url = env::var("ASSET_SCAN_URL").unwrap_or_default();
+            if url.is_empty() {
+                return Err(anyhow!(
+                    "ASSET_SCAN_URL must be set when ASSET_SCANNER is http"
+                ));
+            }
+            let api_key = env::var("ASSET_SCAN_API_KEY")
+                .ok()
+                .filter(|k| !k.is_empty());
+            Ok(Some(Arc::new(HttpContentScanner::new(url, api_key)?)))
+        }
+        other => Err(anyhow!("Unknown ASSET_SCANNER: {}", other)),
+    }
+}
+
+/// Reads a clamd reply such as `stream: OK` or `stream: Eicar-Signature FOUND`.
+pub fn parse_clamav_reply(reply: &str) -> Result<ScanVerdict> {
+    let reply = reply.trim_end_matches('\0').trim();
+    let result = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected {
+            signature: signature.trim().to_string(),
+        })
+    } else {
+        Err(anyhow!(
+            "Content scanner request failed: clamd replied {}",
+            reply
+        ))
+    }
+}
+
+/// ClamAV daemon, sent each file over its INSTREAM command.
+pub struct ClamAvScanner {
+    address: String,
+}
+
+impl ClamAvScanner {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+
+    async fn instream<S>(
+        mut socket: S,
+        mut content: BoxStream<'static, Result<Vec<u8>>>,
+    ) -> Result<String>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        socket.write_all(b"zINSTREAM\0").await?;
+        while let Some(chunk) = content.next().await {
+            for piece in chunk?.chunks(CLAMAV_PIECE_BYTES) {
+                socket
+                    .write_all(&(piece.len() as u32).to_be_bytes())
+                    .await?;
+                socket.write_all(piece).await?;
+            }
+        }
+        socket.write_all(&0u32.to_be_bytes()).await?;
+        socket.flush().await?;
+
+        // clamd closes the connection after its reply
+        let mut reply = Vec::new();
+        socket.read_to_end(&mut reply).await?;
+        Ok(String::from_utf8_lossy(&reply).to_string())
+    }
+
+    async fn send(&self, content: BoxStream<'static, Result<Vec<u8>>>) -> Result<String> {
+        #[cfg(unix)]
+        if self.address.starts_with('/') {
+            let socket = tokio::net::UnixStream::connect(&self.address).await?;
+            return Self::instream(socket, content).await;
+        }
+        let socket = tokio::net::TcpStream::connect(&self.address).await?;
+        Self::instream(socket, content).await
+    }
+}
+
+#[async_trait]
+impl ContentScanner for ClamAvScanner {
+    fn name(&self) -> &'static str {
+        "clamav"
+    }
+
+    async fn scan(&self, file: ScanFile) -> Result<ScanVerdict> {
+        let reply = tokio::time::timeout(SCAN_TIMEOUT, self.send(file.content))
+            .await
+            .map_err(|_| anyhow!("Content scanner request failed: timed out"))?
+            .map_err(|e| anyhow!("Content scanner request failed: {}", e))?;
+        parse_clamav_reply(&reply)
+    }
+}
+
+#[derive(Deserialize)]
+struct HttpScanResponse {
+    clean: bool,
+    signature: Option<String>,
+}
+
+/// External scanning API. It is sent the file's signed URL, name, size and SHA-256 as JSON and
+/// answers `{"clean": bool, "signature": "..."}`, so the file itself never passes through this
+/// server again.
+pub struct HttpContentScanner {
+    client: Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl HttpContentScanner {
+    pub fn new(url: String, api_key: Option<String>) -> Result<Self> {
+        let client = Client::builder().timeout(SCAN_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            url,
+            api_key,
+        })
+    }
+}
+
+#[async_trait]
+impl ContentScanner for HttpContentScanner {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn scan(&self, file: ScanFile) -> Result<ScanVerdict> {
+        let mut request = self.client.post(&self.url).json(&json!({
+            "url": file.url,
+            "file_name": file.file_name,
+            "size_bytes": file.size_bytes,
+            "sha256": file.sha256,
+        }));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow!("Content scanner request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Content scanner request failed: scan returned {}",
+                response.status()
+            ));
+        }
+        let result = response
+            .json::<HttpScanResponse>()
+            .await
+            .map_err(|e| anyhow!("Content scanner request failed: invalid response: {}", e))?;
+
+        if result.clean {
+            Ok(ScanVerdict::Clean)
+        } else {
+            Ok(ScanVerdict::Infected {
+                signature: result.signature.unwrap_or_else(|| "unknown".to_string()),
+            })
+        }
+    }
+}
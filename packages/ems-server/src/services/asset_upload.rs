@@ -0,0 +1,1451 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use futures::stream::{self, StreamExt};
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use crate::models::{
+    Asset, AssetBlob, AssetBlobReference, AssetBlobResponse, AssetDownloadResponse,
+    AssetReleaseStatus, AssetScanStatus, AssetUpload, AssetUploadChunk, AssetUploadResponse,
+    AssetUploadStatus, CreateAssetUploadRequest, DirectUploadTarget, FirmwareDelta,
+    FirmwareDeltaSummary, FirmwareUpdateKind, FirmwareUpdateResponse, NewAssetBlob, NewAssetUpload,
+    NewAssetUploadChunk, NewFirmwareDelta,
+};
+use crate::schema::*;
+use crate::services::{
+    content_scanner_from_env, storage_backend_from_env, AssetError, AssetService, ContentScanner,
+    DatabaseService, ObjectInfo, ScanFile, ScanVerdict, StorageBackend,
+};
+use crate::utils::asset_upload::{
+    chunk_count, chunk_path, sha256_hex, upload_storage_path, verify_chunk, MAX_UPLOAD_BYTES,
+    UPLOAD_CHUNK_BYTES, UPLOAD_TTL_HOURS,
+};
+use crate::utils::firmware_delta::{delta_storage_path, generate_delta, MAX_DELTA_INPUT_BYTES};
+
+// Signed download URLs stay valid this long
+const DOWNLOAD_URL_TTL: Duration = Duration::from_secs(300);
+
+// Scanners fetching the file themselves get this long to do it
+const SCAN_URL_TTL: Duration = Duration::from_secs(3600);
+
+/// Resumable uploads of asset files: an upload is started with the file's size, its chunks are
+/// sent in any order (and resent after a failure), and completing it assembles the chunks into
+/// the asset's file in the background. When a content scanner is configured the new file is
+/// quarantined until the scanner passes it.
+///
+/// Files are stored once per tenant as blobs keyed by their SHA-256. An upload of a file the
+/// tenant already holds references the stored blob instead, and blobs no asset references any
+/// more are removed together with their file.
+///
+/// Large files can instead go straight to storage through a presigned URL; confirming such a
+/// direct upload checks the stored file's size and SHA-256 before the asset references it.
+///
+/// Devices updating firmware download a delta from the image they run to the current version
+/// when that image is an earlier version of the asset.
+#[derive(Clone)]
+pub struct AssetUploadService {
+    database: DatabaseService,
+    storage: Option<Arc<dyn StorageBackend>>,
+    scanner: Option<Arc<dyn ContentScanner>>,
+}
+
+impl AssetUploadService {
+    /// Uses the storage backend and the content scanner configured through environment
+    /// variables, with the bucket ASSET_BUCKET (default `assets`).
+    pub fn new(database: DatabaseService) -> Result<Self> {
+        let storage = storage_backend_from_env("ASSET_BUCKET", "assets")?;
+        Ok(Self::with_storage(
+            database,
+            storage,
+            content_scanner_from_env()?,
+        ))
+    }
+
+    pub fn with_storage(
+        database: DatabaseService,
+        storage: Option<Arc<dyn StorageBackend>>,
+        scanner: Option<Arc<dyn ContentScanner>>,
+    ) -> Self {
+        Self {
+            database,
+            storage,
+            scanner,
+        }
+    }
+
+    // Asset upload operations
+
+    /// Starts an upload of a new file for an asset. `Ok(None)` when the asset does not exist.
+    pub async fn create_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        created_by_id: Uuid,
+        request: CreateAssetUploadRequest,
+    ) -> Result<Option<AssetUploadResponse>> {
+        self.start_upload(tenant_id, asset_id, created_by_id, request, false)
+            .await
+    }
+
+    /// Starts an upload whose file the client PUTs straight to storage through the presigned
+    /// URL in the response, keeping large files off the server; confirming the upload then
+    /// checks the stored file. Needs the file's SHA-256 and a storage backend that issues
+    /// presigned uploads. `Ok(None)` when the asset does not exist.
+    pub async fn create_direct_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        created_by_id: Uuid,
+        request: CreateAssetUploadRequest,
+    ) -> Result<Option<AssetUploadResponse>> {
+        self.start_upload(tenant_id, asset_id, created_by_id, request, true)
+            .await
+    }
+
+    async fn start_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        created_by_id: Uuid,
+        request: CreateAssetUploadRequest,
+        direct: bool,
+    ) -> Result<Option<AssetUploadResponse>> {
+        let storage = self.storage()?;
+        if request.size_bytes > MAX_UPLOAD_BYTES {
+            return Err(AssetError::InvalidUpload(format!(
+                "files are limited to {} bytes",
+                MAX_UPLOAD_BYTES
+            ))
+            .into());
+        }
+        if direct && request.checksum.is_none() {
+            return Err(AssetError::InvalidUpload(
+                "direct uploads need the file's checksum".to_string(),
+            )
+            .into());
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        Self::ensure_modifiable(&mut conn, &asset).await?;
+        drop(conn);
+
+        let checksum = request.checksum.map(|c| c.to_ascii_lowercase());
+        let upload_id = Uuid::new_v4();
+        let new_upload = NewAssetUpload {
+            id: upload_id,
+            tenant_id,
+            asset_id,
+            storage_path: upload_storage_path(tenant_id, asset_id, upload_id, &request.file_name),
+            file_name: request.file_name,
+            content_type: request.content_type.trim().to_ascii_lowercase(),
+            size_bytes: request.size_bytes,
+            chunk_size: UPLOAD_CHUNK_BYTES as i32,
+            checksum: checksum.clone(),
+            created_by_id,
+            expires_at: Utc::now() + ChronoDuration::hours(UPLOAD_TTL_HOURS),
+            direct,
+        };
+
+        // Issued before anything is recorded, so backends without presigned uploads refuse early
+        let target = if direct {
+            Some(
+                Self::upload_target(
+                    storage.as_ref(),
+                    &new_upload.storage_path,
+                    &new_upload.content_type,
+                    new_upload.size_bytes,
+                    checksum.as_deref().unwrap_or_default(),
+                    new_upload.expires_at,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+
+        // A file the tenant already holds is not sent again; the upload completes at once
+        let upload = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let blob = match &checksum {
+                        Some(checksum) => asset_blobs::table
+                            .filter(asset_blobs::tenant_id.eq(tenant_id))
+                            .filter(asset_blobs::checksum.eq(checksum))
+                            .filter(asset_blobs::size_bytes.eq(new_upload.size_bytes))
+                            .for_update()
+                            .select(AssetBlob::as_select())
+                            .first::<AssetBlob>(conn)
+                            .await
+                            .optional()?,
+                        None => None,
+                    };
+
+                    let upload: AssetUpload = diesel::insert_into(asset_uploads::table)
+                        .values(&new_upload)
+                        .returning(AssetUpload::as_returning())
+                        .get_result(conn)
+                        .await?;
+                    let Some(blob) = blob else {
+                        return Ok(upload);
+                    };
+
+                    Self::link_blob(conn, asset_id, &blob).await?;
+                    Ok(diesel::update(asset_uploads::table.find(upload.id))
+                        .set((
+                            asset_uploads::status.eq(AssetUploadStatus::Completed.to_string()),
+                            asset_uploads::storage_path.eq(&blob.storage_path),
+                            asset_uploads::deduplicated.eq(true),
+                            asset_uploads::completed_at.eq(Utc::now()),
+                        ))
+                        .returning(AssetUpload::as_returning())
+                        .get_result(conn)
+                        .await?)
+                })
+            })
+            .await?;
+
+        if upload.deduplicated {
+            self.remove_unreferenced_blobs(tenant_id).await;
+            return Ok(Some(Self::to_response(upload, &[])));
+        }
+        let mut response = Self::to_response(upload, &[]);
+        response.upload_target = target;
+        Ok(Some(response))
+    }
+
+    /// The file the tenant stores with SHA-256 `checksum` and the assets referencing it, so a
+    /// device can tell it already has a file before downloading it. `Ok(None)` when the tenant
+    /// holds no such file.
+    pub async fn find_blob(
+        &self,
+        tenant_id: Uuid,
+        checksum: &str,
+    ) -> Result<Option<AssetBlobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let blob = asset_blobs::table
+            .filter(asset_blobs::tenant_id.eq(tenant_id))
+            .filter(asset_blobs::checksum.eq(checksum))
+            .select(AssetBlob::as_select())
+            .first::<AssetBlob>(&mut conn)
+            .await
+            .optional()?;
+        let Some(blob) = blob else {
+            return Ok(None);
+        };
+
+        let assets = assets::table
+            .filter(assets::tenant_id.eq(tenant_id))
+            .filter(assets::blob_id.eq(blob.id))
+            .order(assets::created_at.asc())
+            .select((assets::id, assets::item_id, assets::name, assets::version))
+            .load::<AssetBlobReference>(&mut conn)
+            .await?;
+
+        Ok(Some(AssetBlobResponse {
+            checksum: blob.checksum,
+            size_bytes: blob.size_bytes,
+            content_type: blob.content_type,
+            ref_count: blob.ref_count,
+            scan_status: blob
+                .scan_status
+                .map(|status| AssetScanStatus::try_from(status).unwrap_or(AssetScanStatus::Failed)),
+            assets,
+            created_at: blob.created_at.unwrap_or_else(Utc::now),
+        }))
+    }
+
+    /// An upload with the chunks still missing. `Ok(None)` when it does not exist.
+    pub async fn get_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+    ) -> Result<Option<AssetUploadResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(upload) = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?
+        else {
+            return Ok(None);
+        };
+        let chunks = Self::received_chunks(&mut conn, upload.id).await?;
+        drop(conn);
+
+        // A direct upload still waiting for its file gets a fresh URL to send it to
+        let target = match &self.storage {
+            Some(storage) if Self::awaits_file(&upload) => Some(
+                Self::upload_target(
+                    storage.as_ref(),
+                    &upload.storage_path,
+                    &upload.content_type,
+                    upload.size_bytes,
+                    upload.checksum.as_deref().unwrap_or_default(),
+                    upload.expires_at,
+                )
+                .await?,
+            ),
+            _ => None,
+        };
+        let mut response = Self::to_response(upload, &chunks);
+        response.upload_target = target;
+        Ok(Some(response))
+    }
+
+    /// Stores chunk `index` of an upload after checking its size and SHA-256. Sending a chunk
+    /// again replaces it. `Ok(None)` when the upload does not exist.
+    pub async fn upload_chunk(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+        index: u32,
+        checksum: Option<&str>,
+        content: Vec<u8>,
+    ) -> Result<Option<AssetUploadResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(upload) = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?
+        else {
+            return Ok(None);
+        };
+        if upload.direct {
+            return Err(AssetError::NotAcceptingChunks(
+                "its file goes straight to storage".to_string(),
+            )
+            .into());
+        }
+        if upload.status != AssetUploadStatus::Uploading.to_string() {
+            return Err(AssetError::NotAcceptingChunks(format!("it is {}", upload.status)).into());
+        }
+        if upload.expires_at < Utc::now() {
+            return Err(AssetError::Expired.into());
+        }
+
+        let checksum = verify_chunk(
+            upload.size_bytes,
+            upload.chunk_size,
+            index,
+            &content,
+            checksum,
+        )
+        .map_err(AssetError::InvalidChunk)?;
+        let size_bytes = content.len() as i32;
+        storage
+            .put(
+                &chunk_path(tenant_id, upload.id, index),
+                content,
+                "application/octet-stream",
+            )
+            .await?;
+
+        diesel::insert_into(asset_upload_chunks::table)
+            .values(&NewAssetUploadChunk {
+                tenant_id,
+                upload_id: upload.id,
+                chunk_index: index as i32,
+                size_bytes,
+                checksum,
+            })
+            .on_conflict((
+                asset_upload_chunks::upload_id,
+                asset_upload_chunks::chunk_index,
+            ))
+            .do_update()
+            .set((
+                asset_upload_chunks::size_bytes.eq(excluded(asset_upload_chunks::size_bytes)),
+                asset_upload_chunks::checksum.eq(excluded(asset_upload_chunks::checksum)),
+                asset_upload_chunks::received_at.eq(Utc::now()),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        let chunks = Self::received_chunks(&mut conn, upload.id).await?;
+        Ok(Some(Self::to_response(upload, &chunks)))
+    }
+
+    /// Starts assembling an upload whose chunks have all arrived; the upload reports
+    /// `assembling` until the file is in place. Completing an upload that is already assembling
+    /// or completed changes nothing, and completing a failed one retries the assembly.
+    /// `Ok(None)` when the upload does not exist.
+    pub async fn complete_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+    ) -> Result<Option<AssetUploadResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(upload) = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?
+        else {
+            return Ok(None);
+        };
+        if upload.direct {
+            return Err(AssetError::NotCompletable(
+                "its file goes straight to storage; confirm it instead".to_string(),
+            )
+            .into());
+        }
+        let chunks = Self::received_chunks(&mut conn, upload.id).await?;
+
+        let status = AssetUploadStatus::try_from(upload.status.clone()).map_err(|e| anyhow!(e))?;
+        match status {
+            AssetUploadStatus::Assembling | AssetUploadStatus::Completed => {
+                return Ok(Some(Self::to_response(upload, &chunks)));
+            }
+            AssetUploadStatus::Aborted => {
+                return Err(AssetError::NotCompletable("it is aborted".to_string()).into());
+            }
+            AssetUploadStatus::Uploading | AssetUploadStatus::Failed => {}
+        }
+
+        let missing = Self::missing_chunks(&upload, &chunks).len();
+        if missing > 0 {
+            return Err(AssetError::Incomplete(format!("{} chunks missing", missing)).into());
+        }
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        Self::ensure_modifiable(&mut conn, &asset).await?;
+
+        // Only one request moves the upload on, so the file is assembled once
+        let claimed = diesel::update(
+            asset_uploads::table
+                .filter(asset_uploads::id.eq(upload.id))
+                .filter(asset_uploads::status.eq_any([
+                    AssetUploadStatus::Uploading.to_string(),
+                    AssetUploadStatus::Failed.to_string(),
+                ])),
+        )
+        .set((
+            asset_uploads::status.eq(AssetUploadStatus::Assembling.to_string()),
+            asset_uploads::error.eq(None::<String>),
+        ))
+        .returning(AssetUpload::as_returning())
+        .get_result::<AssetUpload>(&mut conn)
+        .await
+        .optional()?;
+
+        let Some(claimed) = claimed else {
+            // Another request got there first
+            let current = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?;
+            return Ok(current.map(|upload| Self::to_response(upload, &chunks)));
+        };
+
+        // Large files take longer to assemble than a request may last. The spawned task does
+        // not inherit the request's tenant scope, so it is scoped again to reach the tenant's
+        // dedicated database.
+        let service = self.clone();
+        let job = claimed.clone();
+        tokio::spawn(DatabaseService::scope_tenant(tenant_id, async move {
+            if let Err(e) = service.assemble(storage, &job).await {
+                tracing::warn!("Asset upload {} failed to assemble: {}", job.id, e);
+                if let Err(e) = service.mark_failed(&job, &e.to_string()).await {
+                    tracing::error!("Asset upload {} not marked failed: {}", job.id, e);
+                }
+            }
+        }));
+
+        Ok(Some(Self::to_response(claimed, &chunks)))
+    }
+
+    /// Checks the file a direct upload sent to storage against the size and SHA-256 the upload
+    /// was started with, and points the asset at it; a content scan then runs in the
+    /// background. A file that does not match is removed and the upload marked failed, so it
+    /// can be sent and confirmed again. Confirming an upload that is already completed changes
+    /// nothing. `Ok(None)` when the upload does not exist.
+    pub async fn confirm_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+    ) -> Result<Option<AssetUploadResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(upload) = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?
+        else {
+            return Ok(None);
+        };
+        if !upload.direct {
+            return Err(AssetError::NotConfirmable(
+                "it is sent in chunks; complete it instead".to_string(),
+            )
+            .into());
+        }
+
+        let status = AssetUploadStatus::try_from(upload.status.clone()).map_err(|e| anyhow!(e))?;
+        match status {
+            AssetUploadStatus::Assembling | AssetUploadStatus::Completed => {
+                return Ok(Some(Self::to_response(upload, &[])));
+            }
+            AssetUploadStatus::Aborted => {
+                return Err(AssetError::NotConfirmable("it is aborted".to_string()).into());
+            }
+            AssetUploadStatus::Uploading | AssetUploadStatus::Failed => {}
+        }
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        Self::ensure_modifiable(&mut conn, &asset).await?;
+
+        // The stored object is only looked at, so a file not sent yet leaves the upload open
+        let Some(object) = storage.stat(&upload.storage_path).await? else {
+            return Err(AssetError::Incomplete(
+                "the file has not been sent to storage".to_string(),
+            )
+            .into());
+        };
+
+        // Only one request moves the upload on, so the file is taken over once
+        let claimed = diesel::update(
+            asset_uploads::table
+                .filter(asset_uploads::id.eq(upload.id))
+                .filter(asset_uploads::status.eq_any([
+                    AssetUploadStatus::Uploading.to_string(),
+                    AssetUploadStatus::Failed.to_string(),
+                ])),
+        )
+        .set((
+            asset_uploads::status.eq(AssetUploadStatus::Assembling.to_string()),
+            asset_uploads::error.eq(None::<String>),
+        ))
+        .returning(AssetUpload::as_returning())
+        .get_result::<AssetUpload>(&mut conn)
+        .await
+        .optional()?;
+        drop(conn);
+
+        let Some(claimed) = claimed else {
+            // Another request got there first
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let current = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?;
+            return Ok(current.map(|upload| Self::to_response(upload, &[])));
+        };
+
+        let finalized = async {
+            let checksum = Self::verify_direct_file(storage.as_ref(), &claimed, object).await?;
+            let deduplicated = self
+                .finalize(storage.as_ref(), &claimed, checksum.clone())
+                .await?;
+            Ok::<_, anyhow::Error>((checksum, deduplicated))
+        }
+        .await;
+        let (checksum, deduplicated) = match finalized {
+            Ok(finalized) => finalized,
+            Err(e) => {
+                if let Err(e) = self.mark_failed(&claimed, &e.to_string()).await {
+                    tracing::error!("Asset upload {} not marked failed: {}", claimed.id, e);
+                }
+                return Err(e);
+            }
+        };
+
+        // Scanning reads the whole file, so it does not hold up the response; like assembly, it
+        // is scoped to the tenant again
+        if !deduplicated {
+            if let Some(scanner) = self.scanner.clone() {
+                let service = self.clone();
+                let job = claimed.clone();
+                tokio::spawn(DatabaseService::scope_tenant(tenant_id, async move {
+                    let verdict = Self::scan_file(scanner.as_ref(), storage, &job, &checksum).await;
+                    if let Err(e) = service.record_scan(scanner.name(), &job, verdict).await {
+                        tracing::error!("Asset {} scan result not recorded: {}", job.asset_id, e);
+                    }
+                }));
+            }
+        }
+
+        // The file the asset had before may no longer be referenced
+        self.remove_unreferenced_blobs(tenant_id).await;
+        self.get_upload(tenant_id, asset_id, upload_id).await
+    }
+
+    /// Abandons an upload and removes the chunks received so far. Returns false when the upload
+    /// does not exist.
+    pub async fn abort_upload(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+    ) -> Result<bool> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(upload) = Self::find_upload(&mut conn, tenant_id, asset_id, upload_id).await?
+        else {
+            return Ok(false);
+        };
+        if upload.status == AssetUploadStatus::Assembling.to_string()
+            || upload.status == AssetUploadStatus::Completed.to_string()
+        {
+            return Err(AssetError::NotAbortable(format!("it is {}", upload.status)).into());
+        }
+
+        let chunks = Self::received_chunks(&mut conn, upload.id).await?;
+        diesel::update(asset_uploads::table.find(upload.id))
+            .set(asset_uploads::status.eq(AssetUploadStatus::Aborted.to_string()))
+            .execute(&mut conn)
+            .await?;
+
+        Self::remove_chunks(storage.as_ref(), &upload, &chunks).await;
+        if upload.direct {
+            if let Err(e) = storage.delete(&upload.storage_path).await {
+                tracing::warn!(
+                    "Direct upload file {} not deleted: {}",
+                    upload.storage_path,
+                    e
+                );
+            }
+        }
+        Ok(true)
+    }
+
+    /// A signed URL of an asset's file. Quarantined files, still being scanned or failed by the
+    /// scanner, are refused. `Ok(None)` when the asset does not exist or has no file.
+    pub async fn download_asset(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<AssetDownloadResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        let Some(file_path) = asset.file_path.clone() else {
+            return Ok(None);
+        };
+        Self::ensure_not_quarantined(&asset)?;
+
+        let expires_at = Utc::now() + ChronoDuration::seconds(DOWNLOAD_URL_TTL.as_secs() as i64);
+        let url = storage.signed_url(&file_path, DOWNLOAD_URL_TTL).await?;
+        Ok(Some(AssetDownloadResponse { url, expires_at }))
+    }
+
+    /// An asset's file read whole, for files the server renders into documents. Quarantined
+    /// files and files over `max_bytes` are refused. `Ok(None)` when the asset does not exist
+    /// or has no file.
+    pub async fn read_asset_file(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        max_bytes: u64,
+    ) -> Result<Option<Vec<u8>>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        drop(conn);
+        let Some(file_path) = asset.file_path.clone() else {
+            return Ok(None);
+        };
+        Self::ensure_not_quarantined(&asset)?;
+        if asset.file_size.is_some_and(|size| size as u64 > max_bytes) {
+            return Err(anyhow!(
+                "File too large: the asset's file has {} bytes, at most {} allowed",
+                asset.file_size.unwrap_or_default(),
+                max_bytes
+            ));
+        }
+
+        storage.get(&file_path).await.map(Some)
+    }
+
+    // Firmware update operations
+
+    /// What a device running the image with SHA-256 `current_checksum` downloads to move to the
+    /// current version of firmware asset `asset_id`, given any of its versions. When the device
+    /// runs an earlier version it gets a delta from that image, generated on the first request
+    /// for the pair and kept for later ones; images of unknown versions, too large to diff or
+    /// whose delta is no smaller are sent whole. `Ok(None)` when the asset does not exist or its
+    /// current version has no file.
+    pub async fn firmware_update(
+        &self,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        current_checksum: &str,
+    ) -> Result<Option<FirmwareUpdateResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(asset) = Self::find_asset(&mut conn, tenant_id, asset_id).await? else {
+            return Ok(None);
+        };
+        let type_name = asset_types::table
+            .filter(asset_types::id.eq(asset.asset_type_id))
+            .select(asset_types::name)
+            .first::<String>(&mut conn)
+            .await?;
+        if type_name != "firmware" {
+            return Err(
+                AssetError::InvalidFirmwareUpdate("asset is not firmware".to_string()).into(),
+            );
+        }
+
+        let target = assets::table
+            .filter(assets::tenant_id.eq(tenant_id))
+            .filter(assets::lineage_id.eq(asset.lineage_id))
+            .filter(assets::is_current.eq(true))
+            .select(Asset::as_select())
+            .first::<Asset>(&mut conn)
+            .await
+            .optional()?
+            .unwrap_or(asset);
+        Self::ensure_not_quarantined(&target)?;
+        let Some(target_path) = target.file_path.clone() else {
+            return Ok(None);
+        };
+        let Some(target_checksum) = target.checksum.as_ref().map(|c| c.to_ascii_lowercase()) else {
+            return Err(AssetError::FirmwareNotUpdatable(
+                "the current version has no checksum".to_string(),
+            )
+            .into());
+        };
+        let target_size = target.file_size.unwrap_or(0);
+
+        let mut response = FirmwareUpdateResponse {
+            kind: FirmwareUpdateKind::UpToDate,
+            asset_id: target.id,
+            version: target.version,
+            checksum: target_checksum.clone(),
+            size_bytes: target_size,
+            url: None,
+            expires_at: None,
+            delta: None,
+        };
+        if target_checksum == current_checksum {
+            return Ok(Some(response));
+        }
+
+        let source = assets::table
+            .filter(assets::tenant_id.eq(tenant_id))
+            .filter(assets::lineage_id.eq(target.lineage_id))
+            .filter(assets::checksum.eq(current_checksum))
+            .filter(assets::file_path.is_not_null())
+            .select(Asset::as_select())
+            .first::<Asset>(&mut conn)
+            .await
+            .optional()?;
+        let delta = match source {
+            Some(source)
+                if target_size as usize <= MAX_DELTA_INPUT_BYTES
+                    && source.file_size.unwrap_or(0) as usize <= MAX_DELTA_INPUT_BYTES =>
+            {
+                Some(
+                    Self::firmware_delta(
+                        &mut conn,
+                        storage.clone(),
+                        &source,
+                        &target_path,
+                        &target_checksum,
+                    )
+                    .await?,
+                )
+            }
+            _ => None,
+        };
+
+        let expires_at = Utc::now() + ChronoDuration::seconds(DOWNLOAD_URL_TTL.as_secs() as i64);
+        response.expires_at = Some(expires_at);
+        match delta.and_then(|delta| delta.storage_path.clone().map(|path| (delta, path))) {
+            Some((delta, path)) => {
+                response.kind = FirmwareUpdateKind::Delta;
+                response.url = Some(storage.signed_url(&path, DOWNLOAD_URL_TTL).await?);
+                response.delta = Some(FirmwareDeltaSummary {
+                    source_checksum: delta.source_checksum,
+                    size_bytes: delta.size_bytes,
+                    checksum: delta.checksum,
+                });
+            }
+            None => {
+                response.kind = FirmwareUpdateKind::Full;
+                response.url = Some(storage.signed_url(&target_path, DOWNLOAD_URL_TTL).await?);
+            }
+        }
+        Ok(Some(response))
+    }
+
+    // Private helper methods
+
+    fn storage(&self) -> Result<Arc<dyn StorageBackend>> {
+        self.storage
+            .clone()
+            .ok_or(AssetError::StorageNotConfigured.into())
+    }
+
+    /// Writes the chunks as the upload's file, points the asset at it and scans it.
+    async fn assemble(&self, storage: Arc<dyn StorageBackend>, upload: &AssetUpload) -> Result<()> {
+        let parts: Vec<String> = (0..chunk_count(upload.size_bytes, upload.chunk_size))
+            .map(|index| chunk_path(upload.tenant_id, upload.id, index))
+            .collect();
+        let checksum = storage
+            .assemble(
+                &parts,
+                &upload.storage_path,
+                &upload.content_type,
+                upload.size_bytes as u64,
+            )
+            .await?;
+        if let Some(expected) = &upload.checksum {
+            if !expected.eq_ignore_ascii_case(&checksum) {
+                storage.delete(&upload.storage_path).await.ok();
+                return Err(AssetError::Mismatch(format!(
+                    "Checksum mismatch: the assembled file's SHA-256 is {}",
+                    checksum
+                ))
+                .into());
+            }
+        }
+
+        let deduplicated = self
+            .finalize(storage.as_ref(), upload, checksum.clone())
+            .await?;
+        if !deduplicated {
+            if let Some(scanner) = &self.scanner {
+                // The chunks are still in storage, so the scan reads those rather than the whole
+                // file
+                let verdict =
+                    Self::scan_file(scanner.as_ref(), storage.clone(), upload, &checksum).await;
+                if let Err(e) = self.record_scan(scanner.name(), upload, verdict).await {
+                    tracing::error!("Asset {} scan result not recorded: {}", upload.asset_id, e);
+                }
+            }
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            upload.tenant_id
+        ))
+        .await?;
+
+        let chunks = Self::received_chunks(&mut conn, upload.id).await?;
+        Self::remove_chunks(storage.as_ref(), upload, &chunks).await;
+        drop(conn);
+
+        // The file the asset had before may no longer be referenced
+        self.remove_unreferenced_blobs(upload.tenant_id).await;
+        Ok(())
+    }
+
+    /// Records the upload's file, with SHA-256 `checksum`, as a blob the asset references and
+    /// completes the upload. Returns whether the tenant already held the file, in which case the
+    /// stored copy is kept and the upload's file removed.
+    async fn finalize(
+        &self,
+        storage: &dyn StorageBackend,
+        upload: &AssetUpload,
+        checksum: String,
+    ) -> Result<bool> {
+        // A new file starts quarantined; one the tenant already holds keeps its scan
+        let new_blob = NewAssetBlob {
+            tenant_id: upload.tenant_id,
+            checksum: checksum.clone(),
+            storage_path: upload.storage_path.clone(),
+            size_bytes: upload.size_bytes,
+            content_type: Some(upload.content_type.clone()),
+            scan_status: self
+                .scanner
+                .as_ref()
+                .map(|_| AssetScanStatus::Pending.to_string()),
+        };
+        let job = upload.clone();
+        let deduplicated = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(upload.tenant_id, |conn| {
+                Box::pin(async move {
+                    // Locks the blob, so it cannot be removed before the asset references it
+                    let blob = diesel::insert_into(asset_blobs::table)
+                        .values(&new_blob)
+                        .on_conflict((asset_blobs::tenant_id, asset_blobs::checksum))
+                        .do_update()
+                        .set(asset_blobs::updated_at.eq(Utc::now()))
+                        .returning(AssetBlob::as_returning())
+                        .get_result::<AssetBlob>(conn)
+                        .await?;
+                    let deduplicated = blob.storage_path != job.storage_path;
+                    Self::link_blob(conn, job.asset_id, &blob).await?;
+
+                    diesel::update(asset_uploads::table.find(job.id))
+                        .set((
+                            asset_uploads::status.eq(AssetUploadStatus::Completed.to_string()),
+                            asset_uploads::checksum.eq(&blob.checksum),
+                            asset_uploads::storage_path.eq(&blob.storage_path),
+                            asset_uploads::deduplicated.eq(deduplicated),
+                            asset_uploads::completed_at.eq(Utc::now()),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    Ok(deduplicated)
+                })
+            })
+            .await?;
+
+        if deduplicated {
+            // The stored copy is used, and its scan already applies
+            if let Err(e) = storage.delete(&upload.storage_path).await {
+                tracing::warn!(
+                    "Duplicate asset file {} not deleted: {}",
+                    upload.storage_path,
+                    e
+                );
+            }
+        }
+        Ok(deduplicated)
+    }
+
+    /// Checks a direct upload's stored file against its size and SHA-256, returning the
+    /// SHA-256. A file that does not match is removed.
+    async fn verify_direct_file(
+        storage: &dyn StorageBackend,
+        upload: &AssetUpload,
+        object: ObjectInfo,
+    ) -> Result<String> {
+        let expected = upload.checksum.clone().unwrap_or_default();
+        let mismatch = if object.size_bytes != upload.size_bytes as u64 {
+            Some(format!(
+                "Size mismatch: the stored file is {} bytes, not {}",
+                object.size_bytes, upload.size_bytes
+            ))
+        } else {
+            // Stores that keep no checksum have the file read back once to hash it
+            let checksum = match object.sha256 {
+                Some(checksum) => checksum,
+                None => sha256_hex(&storage.get(&upload.storage_path).await?),
+            };
+            (!expected.eq_ignore_ascii_case(&checksum)).then(|| {
+                format!(
+                    "Checksum mismatch: the stored file's SHA-256 is {}",
+                    checksum
+                )
+            })
+        };
+
+        if let Some(mismatch) = mismatch {
+            storage.delete(&upload.storage_path).await.ok();
+            return Err(AssetError::Mismatch(mismatch).into());
+        }
+        Ok(expected.to_ascii_lowercase())
+    }
+
+    /// Presigned URL the file of a direct upload is sent to, valid until the upload expires.
+    async fn upload_target(
+        storage: &dyn StorageBackend,
+        path: &str,
+        content_type: &str,
+        size_bytes: i64,
+        checksum: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<DirectUploadTarget> {
+        let expires_in = (expires_at - Utc::now()).num_seconds().max(1) as u64;
+        let presigned = storage
+            .presigned_upload(
+                path,
+                content_type,
+                size_bytes as u64,
+                checksum,
+                Duration::from_secs(expires_in),
+            )
+            .await?;
+        Ok(DirectUploadTarget {
+            method: "PUT".to_string(),
+            url: presigned.url,
+            headers: presigned.headers.into_iter().collect(),
+            expires_at,
+        })
+    }
+
+    // A direct upload takes its file until it is confirmed or expires
+    fn awaits_file(upload: &AssetUpload) -> bool {
+        upload.direct
+            && upload.expires_at > Utc::now()
+            && (upload.status == AssetUploadStatus::Uploading.to_string()
+                || upload.status == AssetUploadStatus::Failed.to_string())
+    }
+
+    async fn scan_file(
+        scanner: &dyn ContentScanner,
+        storage: Arc<dyn StorageBackend>,
+        upload: &AssetUpload,
+        checksum: &str,
+    ) -> Result<ScanVerdict> {
+        let url = storage
+            .signed_url(&upload.storage_path, SCAN_URL_TTL)
+            .await?;
+        // A direct upload has no chunks, so its file is read whole
+        let parts: Vec<String> = if upload.direct {
+            vec![upload.storage_path.clone()]
+        } else {
+            (0..chunk_count(upload.size_bytes, upload.chunk_size))
+                .map(|index| chunk_path(upload.tenant_id, upload.id, index))
+                .collect()
+        };
+        let content = stream::iter(parts)
+            .then(move |part| {
+                let storage = storage.clone();
+                async move { storage.get(&part).await }
+            })
+            .boxed();
+
+        scanner
+            .scan(ScanFile {
+                file_name: upload.file_name.clone(),
+                size_bytes: upload.size_bytes as u64,
+                sha256: checksum.to_string(),
+                url,
+                content,
+            })
+            .await
+    }
+
+    /// Releases or keeps quarantining the file and records the result on its blob and under
+    /// `scan` in the metadata of the assets referencing it. Nothing changes for an asset that
+    /// has moved on to another file meanwhile.
+    async fn record_scan(
+        &self,
+        scanner: &str,
+        upload: &AssetUpload,
+        verdict: Result<ScanVerdict>,
+    ) -> Result<()> {
+        let scanned_at = Utc::now();
+        let (status, signature, error) = match verdict {
+            Ok(ScanVerdict::Clean) => (AssetScanStatus::Clean, None, None),
+            Ok(ScanVerdict::Infected { signature }) => {
+                tracing::warn!(
+                    "Asset {} quarantined: {} found {}",
+                    upload.asset_id,
+                    scanner,
+                    signature
+                );
+                (AssetScanStatus::Infected, Some(signature), None)
+            }
+            Err(e) => {
+                tracing::warn!("Asset {} could not be scanned: {}", upload.asset_id, e);
+                (AssetScanStatus::Failed, None, Some(e.to_string()))
+            }
+        };
+        let result = serde_json::json!({
+            "status": status,
+            "scanner": scanner,
+            "signature": signature,
+            "error": error,
+            "file_path": upload.storage_path,
+            "scanned_at": scanned_at,
+        });
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            upload.tenant_id
+        ))
+        .await?;
+
+        let job = upload.clone();
+        conn.transaction::<_, anyhow::Error, _>(|conn| {
+            Box::pin(async move {
+                diesel::update(
+                    asset_blobs::table
+                        .filter(asset_blobs::tenant_id.eq(job.tenant_id))
+                        .filter(asset_blobs::storage_path.eq(&job.storage_path)),
+                )
+                .set((
+                    asset_blobs::scan_status.eq(status.to_string()),
+                    asset_blobs::scanned_at.eq(scanned_at),
+                    asset_blobs::scan.eq(&result),
+                ))
+                .execute(conn)
+                .await?;
+
+                // Assets that uploaded the same file meanwhile share the result
+                let current = assets::table
+                    .filter(assets::tenant_id.eq(job.tenant_id))
+                    .filter(assets::file_path.eq(&job.storage_path))
+                    .select((assets::id, assets::metadata))
+                    .for_update()
+                    .load::<(Uuid, Option<serde_json::Value>)>(conn)
+                    .await?;
+
+                for (asset_id, metadata) in current {
+                    let mut metadata = match metadata {
+                        Some(serde_json::Value::Object(fields)) => fields,
+                        _ => serde_json::Map::new(),
+                    };
+                    metadata.insert("scan".to_string(), result.clone());
+
+                    diesel::update(assets::table.find(asset_id))
+                        .set((
+                            assets::scan_status.eq(status.to_string()),
+                            assets::scanned_at.eq(scanned_at),
+                            assets::metadata.eq(serde_json::Value::Object(metadata)),
+                        ))
+                        .execute(conn)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn mark_failed(&self, upload: &AssetUpload, error: &str) -> Result<()> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            upload.tenant_id
+        ))
+        .await?;
+
+        diesel::update(asset_uploads::table.find(upload.id))
+            .set((
+                asset_uploads::status.eq(AssetUploadStatus::Failed.to_string()),
+                asset_uploads::error.eq(error),
+            ))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Points an asset at a stored file, taking over the file's scan. The asset's previous blob
+    /// loses a reference through the `count_asset_blob_references` trigger.
+    async fn link_blob(
+        conn: &mut AsyncPgConnection,
+        asset_id: Uuid,
+        blob: &AssetBlob,
+    ) -> Result<()> {
+        let metadata = assets::table
+            .find(asset_id)
+            .select(assets::metadata)
+            .for_update()
+            .first::<Option<serde_json::Value>>(conn)
+            .await?;
+        let metadata = match (metadata, &blob.scan) {
+            (Some(serde_json::Value::Object(mut fields)), scan) => {
+                fields.remove("scan");
+                if let Some(scan) = scan {
+                    fields.insert("scan".to_string(), scan.clone());
+                }
+                Some(serde_json::Value::Object(fields))
+            }
+            (None, Some(scan)) => Some(serde_json::json!({ "scan": scan })),
+            (metadata, _) => metadata,
+        };
+
+        diesel::update(assets::table.find(asset_id))
+            .set((
+                assets::blob_id.eq(blob.id),
+                assets::file_path.eq(&blob.storage_path),
+                assets::file_size.eq(blob.size_bytes),
+                assets::file_type.eq(&blob.content_type),
+                assets::checksum.eq(&blob.checksum),
+                assets::metadata.eq(metadata),
+                assets::scan_status.eq(&blob.scan_status),
+                assets::scanned_at.eq(blob.scanned_at),
+                assets::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    // Files left behind only waste space, so failures are logged rather than returned; a blob
+    // whose file could not be deleted is forgotten all the same
+    async fn remove_unreferenced_blobs(&self, tenant_id: Uuid) {
+        let Ok(storage) = self.storage() else {
+            return;
+        };
+        let removed: Result<Vec<String>> = async {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let blobs = diesel::delete(
+                asset_blobs::table
+                    .filter(asset_blobs::tenant_id.eq(tenant_id))
+                    .filter(asset_blobs::ref_count.eq(0)),
+            )
+            .returning((asset_blobs::storage_path, asset_blobs::checksum))
+            .get_results::<(String, String)>(&mut conn)
+            .await?;
+            let (mut paths, checksums): (Vec<String>, Vec<String>) = blobs.into_iter().unzip();
+
+            // Deltas from or to a removed file are of no use any more
+            let deltas = diesel::delete(
+                firmware_deltas::table
+                    .filter(firmware_deltas::tenant_id.eq(tenant_id))
+                    .filter(
+                        firmware_deltas::source_checksum
+                            .eq_any(&checksums)
+                            .or(firmware_deltas::target_checksum.eq_any(&checksums)),
+                    ),
+            )
+            .returning(firmware_deltas::storage_path)
+            .get_results::<Option<String>>(&mut conn)
+            .await?;
+            paths.extend(deltas.into_iter().flatten());
+            Ok(paths)
+        }
+        .await;
+
+        match removed {
+            Ok(paths) => {
+                for path in paths {
+                    if let Err(e) = storage.delete(&path).await {
+                        tracing::warn!("Unreferenced asset file {} not deleted: {}", path, e);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Unreferenced asset files not removed: {}", e),
+        }
+    }
+
+    /// The cached delta from `source` to the image at `target_path`, generating it on a miss.
+    /// Deltas no smaller than the image are recorded without a file.
+    async fn firmware_delta(
+        conn: &mut AsyncPgConnection,
+        storage: Arc<dyn StorageBackend>,
+        source: &Asset,
+        target_path: &str,
+        target_checksum: &str,
+    ) -> Result<FirmwareDelta> {
+        let tenant_id = source.tenant_id;
+        let source_checksum = source.checksum.clone().unwrap_or_default();
+        let cached = firmware_deltas::table
+            .filter(firmware_deltas::tenant_id.eq(tenant_id))
+            .filter(firmware_deltas::source_checksum.eq(&source_checksum))
+            .filter(firmware_deltas::target_checksum.eq(target_checksum))
+            .select(FirmwareDelta::as_select())
+            .first::<FirmwareDelta>(conn)
+            .await
+            .optional()?;
+        if let Some(delta) = cached {
+            return Ok(delta);
+        }
+
+        let source_image = storage
+            .get(source.file_path.as_deref().unwrap_or_default())
+            .await?;
+        let target_image = storage.get(target_path).await?;
+        if sha256_hex(&source_image) != source_checksum
+            || sha256_hex(&target_image) != target_checksum
+        {
+            return Err(anyhow!("Firmware image does not match its checksum"));
+        }
+        let target_size = target_image.len();
+        let delta =
+            tokio::task::spawn_blocking(move || generate_delta(&source_image, &target_image))
+                .await?;
+
+        let storage_path = if delta.len() < target_size {
+            let path = delta_storage_path(tenant_id, &source_checksum, target_checksum);
+            storage
+                .put(&path, delta.clone(), "application/octet-stream")
+                .await?;
+            Some(path)
+        } else {
+            None
+        };
+        let new_delta = NewFirmwareDelta {
+            tenant_id,
+            source_checksum: source_checksum.clone(),
+            target_checksum: target_checksum.to_string(),
+            storage_path,
+            size_bytes: delta.len() as i64,
+            checksum: sha256_hex(&delta),
+        };
+
+        // Devices asking at once generate the same delta; the first one recorded is kept
+        diesel::insert_into(firmware_deltas::table)
+            .values(&new_delta)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+        Ok(firmware_deltas::table
+            .filter(firmware_deltas::tenant_id.eq(tenant_id))
+            .filter(firmware_deltas::source_checksum.eq(&source_checksum))
+            .filter(firmware_deltas::target_checksum.eq(target_checksum))
+            .select(FirmwareDelta::as_select())
+            .first::<FirmwareDelta>(conn)
+            .await?)
+    }
+
+    // Quarantined files, still being scanned or failed by the scanner, are not handed out
+    fn ensure_not_quarantined(asset: &Asset) -> Result<()> {
+        if let Some(status) = asset
+            .scan_status
+            .clone()
+            .map(|status| AssetScanStatus::try_from(status).unwrap_or(AssetScanStatus::Failed))
+        {
+            if status.is_quarantined() {
+                return Err(AssetError::Quarantined(status.to_string()).into());
+            }
+        }
+        Ok(())
+    }
+
+    async fn find_asset(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<Option<Asset>> {
+        Ok(assets::table
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .select(Asset::as_select())
+            .first::<Asset>(conn)
+            .await
+            .optional()?)
+    }
+
+    // Released controlled documents change content only through a new version
+    async fn ensure_modifiable(conn: &mut AsyncPgConnection, asset: &Asset) -> Result<()> {
+        if asset.release_status == AssetReleaseStatus::Released.to_string()
+            && AssetService::is_controlled_type(conn, asset.asset_type_id).await?
+        {
+            return Err(AssetError::Released.into());
+        }
+        Ok(())
+    }
+
+    async fn find_upload(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+        upload_id: Uuid,
+    ) -> Result<Option<AssetUpload>> {
+        Ok(asset_uploads::table
+            .filter(asset_uploads::id.eq(upload_id))
+            .filter(asset_uploads::tenant_id.eq(tenant_id))
+            .filter(asset_uploads::asset_id.eq(asset_id))
+            .select(AssetUpload::as_select())
+            .first::<AssetUpload>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn received_chunks(
+        conn: &mut AsyncPgConnection,
+        upload_id: Uuid,
+    ) -> Result<Vec<AssetUploadChunk>> {
+        Ok(asset_upload_chunks::table
+            .filter(asset_upload_chunks::upload_id.eq(upload_id))
+            .order(asset_upload_chunks::chunk_index.asc())
+            .select(AssetUploadChunk::as_select())
+            .load::<AssetUploadChunk>(conn)
+            .await?)
+    }
+
+    fn missing_chunks(upload: &AssetUpload, chunks: &[AssetUploadChunk]) -> Vec<u32> {
+        (0..chunk_count(upload.size_bytes, upload.chunk_size))
+            .filter(|index| !chunks.iter().any(|c| c.chunk_index == *index as i32))
+            .collect()
+    }
+
+    // Chunks left behind only waste space, so failures are logged rather than returned
+    async fn remove_chunks(
+        storage: &dyn StorageBackend,
+        upload: &AssetUpload,
+        chunks: &[AssetUploadChunk],
+    ) {
+        for chunk in chunks {
+            let path = chunk_path(upload.tenant_id, upload.id, chunk.chunk_index as u32);
+            if let Err(e) = storage.delete(&path).await {
+                tracing::warn!("Asset upload chunk {} not deleted: {}", path, e);
+            }
+        }
+    }
+
+    fn to_response(upload: AssetUpload, chunks: &[AssetUploadChunk]) -> AssetUploadResponse {
+        // Deduplicated and direct uploads complete without chunks
+        let missing_chunks = if upload.deduplicated || upload.direct {
+            Vec::new()
+        } else {
+            Self::missing_chunks(&upload, chunks)
+        };
+        AssetUploadResponse {
+            id: upload.id,
+            asset_id: upload.asset_id,
+            chunk_count: chunk_count(upload.size_bytes, upload.chunk_size),
+            received_bytes: chunks.iter().map(|c| c.size_bytes as i64).sum(),
+            missing_chunks,
+            file_name: upload.file_name,
+            content_type: upload.content_type,
+            size_bytes: upload.size_bytes,
+            checksum: upload.checksum,
+            status: AssetUploadStatus::try_from(upload.status).unwrap_or(AssetUploadStatus::Failed),
+            chunk_size: upload.chunk_size,
+            error: upload.error,
+            deduplicated: upload.deduplicated,
+            direct: upload.direct,
+            upload_target: None,
+            expires_at: upload.expires_at,
+            completed_at: upload.completed_at,
+            created_at: upload.created_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
@@ -0,0 +1,602 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    parse_timezone, AssignOperatorShiftRequest, AttendanceResponse, AttendanceType,
+    AvailabilityWindow, CreateAttendanceRequest, ListAttendanceQuery, ListOperatorShiftsQuery,
+    NewOperatorAttendance, NewOperatorShift, OperatorAssignmentType, OperatorAttendance,
+    OperatorAvailabilityResponse, OperatorShift, OperatorShiftResponse, ShiftDefinition,
+    ShiftPattern,
+};
+use crate::schema::*;
+use crate::services::{CalendarError, DatabaseService};
+use crate::utils::capacity::{
+    clip_intervals, day_start, intersect_intervals, interval_hours, local_to_utc, merge_intervals,
+    shift_intervals, subtract_intervals, Interval,
+};
+
+/// Working time of an operator over a planning window, resolved from their shift assignments
+/// and attendance.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorAvailability {
+    /// Shift patterns, the timezone each is evaluated in and the period it applies to
+    pub shifts: Vec<(Vec<ShiftDefinition>, Tz, Interval)>,
+    /// Absences, sick leave, vacation and training
+    pub absent: Vec<Interval>,
+    /// Time the operator is available outside their shifts
+    pub extra: Vec<Interval>,
+}
+
+impl OperatorAvailability {
+    /// Available intervals within the window. Outside the periods covered by a shift
+    /// assignment the operator is treated as available around the clock, less absences.
+    pub fn windows(&self, window: Interval) -> Vec<Interval> {
+        let periods: Vec<Interval> = self.shifts.iter().map(|(_, _, period)| *period).collect();
+        let mut working = subtract_intervals(&[window], &periods);
+        for (shifts, tz, period) in &self.shifts {
+            for period in clip_intervals(&[*period], window) {
+                working.extend(shift_intervals(shifts, *tz, period));
+            }
+        }
+        working.extend(clip_intervals(&self.extra, window));
+        subtract_intervals(&merge_intervals(working), &self.absent)
+    }
+}
+
+/// Time within a window when none of a machine's primary operators is there.
+#[derive(Debug, Clone, Default)]
+pub struct OperatorCoverageGaps {
+    /// Time every primary operator is recorded as absent
+    pub absent: Vec<Interval>,
+    /// Time every primary operator is absent or off shift
+    pub unavailable: Vec<Interval>,
+}
+
+pub struct AttendanceService {
+    database: DatabaseService,
+}
+
+impl AttendanceService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Operator shift operations
+
+    /// Assigns an operator to a shift pattern from `effective_from`. Returns `None` when the
+    /// person or the pattern does not belong to the tenant.
+    pub async fn assign_shift(
+        &self,
+        tenant_id: Uuid,
+        request: AssignOperatorShiftRequest,
+    ) -> Result<Option<OperatorShiftResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let pattern_exists = shift_patterns::table
+            .filter(shift_patterns::id.eq(request.shift_pattern_id))
+            .filter(shift_patterns::tenant_id.eq(tenant_id))
+            .select(shift_patterns::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some();
+        if !pattern_exists || !Self::is_member(&mut conn, tenant_id, request.person_id).await? {
+            return Ok(None);
+        }
+
+        // An operator follows one pattern at a time
+        let overlapping = operator_shifts::table
+            .filter(operator_shifts::tenant_id.eq(tenant_id))
+            .filter(operator_shifts::person_id.eq(request.person_id))
+            .filter(
+                operator_shifts::effective_to
+                    .is_null()
+                    .or(operator_shifts::effective_to.ge(request.effective_from)),
+            )
+            .into_boxed();
+        let overlapping = match request.effective_to {
+            Some(to) => overlapping.filter(operator_shifts::effective_from.le(to)),
+            None => overlapping,
+        };
+        if let Some(existing) = overlapping
+            .select(OperatorShift::as_select())
+            .first::<OperatorShift>(&mut conn)
+            .await
+            .optional()?
+        {
+            return Err(CalendarError::ShiftOverlap(format!(
+                "assignment {} starting {}",
+                existing.id, existing.effective_from
+            ))
+            .into());
+        }
+
+        let new_shift = NewOperatorShift {
+            tenant_id,
+            person_id: request.person_id,
+            shift_pattern_id: request.shift_pattern_id,
+            timezone: request.timezone.unwrap_or_else(|| "UTC".to_string()),
+            effective_from: request.effective_from,
+            effective_to: request.effective_to,
+            notes: request.notes,
+        };
+
+        let shift: OperatorShift = diesel::insert_into(operator_shifts::table)
+            .values(&new_shift)
+            .returning(OperatorShift::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Some(shift.into()))
+    }
+
+    pub async fn list_shifts(
+        &self,
+        tenant_id: Uuid,
+        query: ListOperatorShiftsQuery,
+    ) -> Result<Vec<OperatorShiftResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut shifts = operator_shifts::table
+            .filter(operator_shifts::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(person_id) = query.person_id {
+            shifts = shifts.filter(operator_shifts::person_id.eq(person_id));
+        }
+        if let Some(shift_pattern_id) = query.shift_pattern_id {
+            shifts = shifts.filter(operator_shifts::shift_pattern_id.eq(shift_pattern_id));
+        }
+        if let Some(on) = query.on {
+            shifts = shifts
+                .filter(operator_shifts::effective_from.le(on))
+                .filter(
+                    operator_shifts::effective_to
+                        .is_null()
+                        .or(operator_shifts::effective_to.ge(on)),
+                );
+        }
+
+        let shifts = shifts
+            .order((
+                operator_shifts::person_id.asc(),
+                operator_shifts::effective_from.asc(),
+            ))
+            .select(OperatorShift::as_select())
+            .load::<OperatorShift>(&mut conn)
+            .await?;
+
+        Ok(shifts
+            .into_iter()
+            .map(OperatorShiftResponse::from)
+            .collect())
+    }
+
+    pub async fn delete_shift(&self, tenant_id: Uuid, shift_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            operator_shifts::table
+                .filter(operator_shifts::id.eq(shift_id))
+                .filter(operator_shifts::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Attendance operations
+
+    /// Records an absence or extra availability. Returns `None` when the person does not
+    /// belong to the tenant.
+    pub async fn record_attendance(
+        &self,
+        tenant_id: Uuid,
+        recorded_by_id: Option<Uuid>,
+        request: CreateAttendanceRequest,
+    ) -> Result<Option<AttendanceResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::is_member(&mut conn, tenant_id, request.person_id).await? {
+            return Ok(None);
+        }
+
+        let new_attendance = NewOperatorAttendance {
+            tenant_id,
+            person_id: request.person_id,
+            attendance_type: request.attendance_type.to_string(),
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            reason: request.reason,
+            recorded_by_id,
+        };
+
+        let attendance: OperatorAttendance = diesel::insert_into(operator_attendance::table)
+            .values(&new_attendance)
+            .returning(OperatorAttendance::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Some(attendance.into()))
+    }
+
+    pub async fn list_attendance(
+        &self,
+        tenant_id: Uuid,
+        query: ListAttendanceQuery,
+    ) -> Result<Vec<AttendanceResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut records = operator_attendance::table
+            .filter(operator_attendance::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(person_id) = query.person_id {
+            records = records.filter(operator_attendance::person_id.eq(person_id));
+        }
+        if let Some(attendance_type) = query.attendance_type {
+            records = records
+                .filter(operator_attendance::attendance_type.eq(attendance_type.to_string()));
+        }
+        if let Some(from) = query.from {
+            records = records.filter(operator_attendance::ends_at.gt(from));
+        }
+        if let Some(to) = query.to {
+            records = records.filter(operator_attendance::starts_at.lt(to));
+        }
+
+        let records = records
+            .order(operator_attendance::starts_at.asc())
+            .select(OperatorAttendance::as_select())
+            .load::<OperatorAttendance>(&mut conn)
+            .await?;
+
+        Ok(records.into_iter().map(AttendanceResponse::from).collect())
+    }
+
+    pub async fn delete_attendance(&self, tenant_id: Uuid, attendance_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            operator_attendance::table
+                .filter(operator_attendance::id.eq(attendance_id))
+                .filter(operator_attendance::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Availability
+
+    /// Resolves shift assignments and attendance overlapping the window for the given
+    /// operators. Every requested operator gets an entry.
+    pub async fn availability_for_operators(
+        &self,
+        tenant_id: Uuid,
+        person_ids: &[Uuid],
+        window: Interval,
+    ) -> Result<HashMap<Uuid, OperatorAvailability>> {
+        let mut availability: HashMap<Uuid, OperatorAvailability> = person_ids
+            .iter()
+            .map(|id| (*id, OperatorAvailability::default()))
+            .collect();
+        if person_ids.is_empty() {
+            return Ok(availability);
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Dates are local to each assignment's timezone, so allow a day either side
+        let first_day = window.0.date_naive() - Duration::days(1);
+        let last_day = window.1.date_naive() + Duration::days(1);
+        let shifts = operator_shifts::table
+            .filter(operator_shifts::tenant_id.eq(tenant_id))
+            .filter(operator_shifts::person_id.eq_any(person_ids))
+            .filter(operator_shifts::effective_from.le(last_day))
+            .filter(
+                operator_shifts::effective_to
+                    .is_null()
+                    .or(operator_shifts::effective_to.ge(first_day)),
+            )
+            .select(OperatorShift::as_select())
+            .load::<OperatorShift>(&mut conn)
+            .await?;
+
+        let pattern_ids: Vec<Uuid> = shifts.iter().map(|shift| shift.shift_pattern_id).collect();
+        let patterns: HashMap<Uuid, Vec<ShiftDefinition>> = shift_patterns::table
+            .filter(shift_patterns::tenant_id.eq(tenant_id))
+            .filter(shift_patterns::id.eq_any(&pattern_ids))
+            .select(ShiftPattern::as_select())
+            .load::<ShiftPattern>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|pattern| (pattern.id, pattern.shift_definitions()))
+            .collect();
+
+        let records = operator_attendance::table
+            .filter(operator_attendance::tenant_id.eq(tenant_id))
+            .filter(operator_attendance::person_id.eq_any(person_ids))
+            .filter(operator_attendance::starts_at.lt(window.1))
+            .filter(operator_attendance::ends_at.gt(window.0))
+            .select(OperatorAttendance::as_select())
+            .load::<OperatorAttendance>(&mut conn)
+            .await?;
+
+        for shift in shifts {
+            let Some(definitions) = patterns.get(&shift.shift_pattern_id) else {
+                continue;
+            };
+            let tz = parse_timezone(&shift.timezone).unwrap_or(Tz::UTC);
+            let local_midnight =
+                |date: NaiveDate| local_to_utc(tz, date.and_time(Default::default()));
+            let period = (
+                local_midnight(shift.effective_from),
+                shift
+                    .effective_to
+                    .map(|to| local_midnight(to + Duration::days(1)))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            );
+            if let Some(entry) = availability.get_mut(&shift.person_id) {
+                entry.shifts.push((definitions.clone(), tz, period));
+            }
+        }
+
+        for record in records {
+            let absence = AttendanceType::try_from(record.attendance_type)
+                .map(|t| t.is_absence())
+                .unwrap_or(true);
+            if let Some(entry) = availability.get_mut(&record.person_id) {
+                let interval = (record.starts_at, record.ends_at);
+                if absence {
+                    entry.absent.push(interval);
+                } else {
+                    entry.extra.push(interval);
+                }
+            }
+        }
+
+        Ok(availability)
+    }
+
+    /// Working windows of an operator over the inclusive `from..=to` date range (UTC days).
+    /// Returns `None` when the person does not belong to the tenant.
+    pub async fn operator_availability(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Option<OperatorAvailabilityResponse>> {
+        {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            if !Self::is_member(&mut conn, tenant_id, person_id).await? {
+                return Ok(None);
+            }
+        }
+
+        let window = (day_start(from), day_start(to + Duration::days(1)));
+        let availability = self
+            .availability_for_operators(tenant_id, &[person_id], window)
+            .await?
+            .remove(&person_id)
+            .unwrap_or_default();
+
+        let windows: Vec<AvailabilityWindow> = availability
+            .windows(window)
+            .into_iter()
+            .map(|interval| AvailabilityWindow {
+                start: interval.0,
+                end: interval.1,
+                hours: interval_hours(&interval),
+            })
+            .collect();
+
+        let absences = self
+            .list_attendance(
+                tenant_id,
+                ListAttendanceQuery {
+                    person_id: Some(person_id),
+                    attendance_type: None,
+                    from: Some(window.0),
+                    to: Some(window.1),
+                },
+            )
+            .await?
+            .into_iter()
+            .filter(|record| record.attendance_type.is_absence())
+            .collect();
+
+        Ok(Some(OperatorAvailabilityResponse {
+            person_id,
+            from,
+            to,
+            has_shift: availability
+                .shifts
+                .iter()
+                .any(|(_, _, period)| period.0 < window.1 && period.1 > window.0),
+            available_hours: windows.iter().map(|w| w.hours).sum(),
+            windows,
+            absences,
+        }))
+    }
+
+    /// Gaps in primary operator coverage for each machine that has primary operators.
+    /// Machines without one are left out.
+    pub async fn primary_operator_gaps(
+        &self,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+        window: Interval,
+    ) -> Result<HashMap<Uuid, OperatorCoverageGaps>> {
+        let operators = self.primary_operators(tenant_id, machine_ids).await?;
+        let person_ids: Vec<Uuid> = operators.values().flatten().map(|(id, _)| *id).collect();
+        let availability = self
+            .availability_for_operators(tenant_id, &person_ids, window)
+            .await?;
+
+        Ok(operators
+            .into_iter()
+            .map(|(machine_id, operators)| {
+                let mut gaps = OperatorCoverageGaps {
+                    absent: vec![window],
+                    unavailable: vec![window],
+                };
+                for (person_id, _) in operators {
+                    let operator = availability.get(&person_id).cloned().unwrap_or_default();
+                    let absent = merge_intervals(clip_intervals(&operator.absent, window));
+                    let unavailable = subtract_intervals(&[window], &operator.windows(window));
+                    gaps.absent = intersect_intervals(&gaps.absent, &absent);
+                    gaps.unavailable = intersect_intervals(&gaps.unavailable, &unavailable);
+                }
+                (machine_id, gaps)
+            })
+            .collect())
+    }
+
+    /// A recorded absence that leaves the machine without any primary operator during the
+    /// period, with the absent operator's name. Time off shift is not counted, since a job may
+    /// run across several shifts.
+    pub async fn find_primary_operator_absence(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Option<(String, OperatorAttendance)>> {
+        let gaps = self
+            .primary_operator_gaps(tenant_id, &[machine_id], (start, end))
+            .await?
+            .remove(&machine_id)
+            .unwrap_or_default();
+        let [(gap_start, gap_end), ..] = gaps.absent[..] else {
+            return Ok(None);
+        };
+
+        let operators = self
+            .primary_operators(tenant_id, &[machine_id])
+            .await?
+            .remove(&machine_id)
+            .unwrap_or_default();
+        let names: HashMap<Uuid, String> = operators.into_iter().collect();
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let absence = operator_attendance::table
+            .filter(operator_attendance::tenant_id.eq(tenant_id))
+            .filter(
+                operator_attendance::person_id.eq_any(names.keys().copied().collect::<Vec<_>>()),
+            )
+            .filter(operator_attendance::attendance_type.ne(AttendanceType::Available.to_string()))
+            .filter(operator_attendance::starts_at.lt(gap_end))
+            .filter(operator_attendance::ends_at.gt(gap_start))
+            .order(operator_attendance::starts_at.asc())
+            .select(OperatorAttendance::as_select())
+            .first::<OperatorAttendance>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(absence.map(|absence| {
+            let name = names.get(&absence.person_id).cloned().unwrap_or_default();
+            (name, absence)
+        }))
+    }
+
+    // Primary operators (id and name) of each machine
+    async fn primary_operators(
+        &self,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<(Uuid, String)>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let rows = machine_operator_assignments::table
+            .inner_join(machines::table)
+            .inner_join(person::table)
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machine_operator_assignments::machine_id.eq_any(machine_ids))
+            .filter(
+                machine_operator_assignments::assignment_type
+                    .eq(OperatorAssignmentType::Primary.to_string()),
+            )
+            .select((
+                machine_operator_assignments::machine_id,
+                person::id,
+                person::name,
+            ))
+            .load::<(Uuid, Uuid, String)>(&mut conn)
+            .await?;
+
+        let mut operators: HashMap<Uuid, Vec<(Uuid, String)>> = HashMap::new();
+        for (machine_id, person_id, name) in rows {
+            operators
+                .entry(machine_id)
+                .or_default()
+                .push((person_id, name));
+        }
+        Ok(operators)
+    }
+
+    async fn is_member(
+        conn: &mut diesel_async::AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<bool> {
+        Ok(tenant_person::table
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .filter(tenant_person::person_id.eq(person_id))
+            .select(tenant_person::person_id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()?
+            .is_some())
+    }
+}
@@ -1,37 +1,170 @@
 use anyhow::Result;
 use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::sync::Arc;
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    AuthPersonWithoutTenant, AuthResponse, CreateAndJoinTenantRequest,
-    InternalPersonOAuthRegisterRequest, JoinTenantRequest, LoginRequest, LogoutRequest,
+    AuthAuditEvent, AuthBackendKind, AuthPersonWithoutTenant, AuthResponse, Claims,
+    CreateAndJoinTenantRequest, FingerprintMode, InternalPersonOAuthRegisterRequest,
+    JoinTenantRequest, ListAuthAuditQuery, LoginRequest, LogoutRequest, NewAuthAuditEvent,
     NewInternalPerson, NewPerson, NewTenant, NewTenantPerson, NewTokenBlacklist,
     OAuthCallbackRequest, OAuthLoginRequest, OAuthUrlResponse, Person, PersonOnlyAuthResponse,
     PersonOnlyRegisterRequest, PersonRole, RefreshTokenRequest, RefreshTokenResponse,
-    RegisterRequest, Tenant, TenantPerson, TokenBlacklist,
+    RegisterRequest, ResendVerificationRequest, Tenant, TenantPerson, TokenBlacklist,
+    VerifyEmailRequest, VerifyEmailResponse, REFRESH_FINGERPRINT_MISMATCH,
 };
-use crate::schema::{internal_person, person, tenant_person, tenants, token_blacklist};
-use crate::services::{DatabaseService, SupabaseService, TenantService};
+use crate::schema::{
+    auth_audit_events, internal_person, person, tenant_person, tenants, token_blacklist,
+};
+use crate::services::{AuthBackend, DatabaseService, SupabaseService, TenantService};
 use crate::utils::auth::AuthUtils;
+use crate::utils::fingerprint::{ClientInfo, FingerprintMatch};
+use crate::utils::AppError;
+
+/// Sign-in, registration and token errors that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("Authentication failed: {0}")]
+    AuthenticationFailed(String),
+
+    #[error("Email not verified")]
+    EmailNotVerified,
+
+    #[error("User not found in system. Please register first.")]
+    NotRegistered,
+
+    #[error(
+        "User not found. Please register first or contact your administrator to add you to this tenant."
+    )]
+    NotMember,
+
+    #[error("User is not an internal person for this tenant")]
+    NotInternalPerson,
+
+    #[error("Tenant not found")]
+    TenantNotFound,
+
+    #[error("Tenant is not active")]
+    TenantNotActive,
+
+    #[error("Tenant subdomain already exists")]
+    SubdomainTaken,
+
+    #[error("Email already registered")]
+    EmailTaken,
+
+    #[error("Person is already associated with this tenant")]
+    AlreadyMember,
+
+    #[error("Failed to create tenant and associate person: {0}")]
+    TenantNotCreated(String),
+
+    #[error("Registration failed: {0}")]
+    RegistrationFailed(String),
+
+    #[error("Invalid state parameter")]
+    InvalidState,
+
+    #[error("{0} OAuth is not configured")]
+    OAuthNotConfigured(String),
+
+    #[error("Failed to exchange code for token: {0}")]
+    CodeExchangeFailed(String),
+
+    #[error("Failed to validate token: {0}")]
+    InvalidToken(String),
+
+    #[error("Invalid refresh token")]
+    InvalidRefreshToken,
+
+    #[error("Token has been revoked")]
+    TokenRevoked,
+
+    #[error("Refresh token used from another client")]
+    TokenReused,
+
+    #[error("Token does not belong to the requesting person")]
+    TokenNotOwned,
+
+    #[error("Invalid or expired verification token")]
+    InvalidVerificationToken,
+
+    #[error("Email verification is not handled by the {0} auth backend")]
+    VerificationNotHandled(AuthBackendKind),
+}
+
+impl From<AuthError> for AppError {
+    fn from(error: AuthError) -> Self {
+        match error {
+            AuthError::AuthenticationFailed(_)
+            | AuthError::InvalidToken(_)
+            | AuthError::InvalidRefreshToken
+            | AuthError::TokenRevoked
+            | AuthError::TokenReused => AppError::Authentication(error.to_string()),
+            AuthError::EmailNotVerified
+            | AuthError::NotInternalPerson
+            | AuthError::TenantNotActive
+            | AuthError::TokenNotOwned => AppError::Authorization(error.to_string()),
+            AuthError::NotRegistered
+            | AuthError::NotMember
+            | AuthError::TenantNotFound
+            | AuthError::VerificationNotHandled(_) => AppError::NotFound(error.to_string()),
+            AuthError::SubdomainTaken | AuthError::EmailTaken | AuthError::AlreadyMember => {
+                AppError::Conflict(error.to_string())
+            }
+            AuthError::TenantNotCreated(_)
+            | AuthError::RegistrationFailed(_)
+            | AuthError::InvalidState
+            | AuthError::CodeExchangeFailed(_)
+            | AuthError::InvalidVerificationToken => AppError::Validation(error.to_string()),
+            AuthError::OAuthNotConfigured(_) => AppError::Unavailable(error.to_string()),
+        }
+    }
+}
 
 pub struct AuthService {
     database: DatabaseService,
     tenant_service: TenantService,
     supabase_service: SupabaseService,
+    backend: Arc<dyn AuthBackend>,
+    client: Option<ClientInfo>,
 }
 
 impl AuthService {
-    pub fn new(database: DatabaseService, supabase_service: SupabaseService) -> Self {
+    pub fn new(
+        database: DatabaseService,
+        supabase_service: SupabaseService,
+        backend: Arc<dyn AuthBackend>,
+    ) -> Self {
+        // Sign-in data lives in the shared database even for tenants with their own
+        let database = database.shared();
         let tenant_service = TenantService::new(database.clone());
         Self {
             database,
             tenant_service,
             supabase_service,
+            backend,
+            client: None,
         }
     }
 
+    /// Client the request came from; refresh tokens issued to it are bound to its fingerprint
+    pub fn with_client(mut self, client: ClientInfo) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    fn issue_refresh_token(&self, person_id: Uuid, tenant_id: Uuid) -> Result<String> {
+        let fingerprint = match FingerprintMode::from_env()? {
+            FingerprintMode::Off => None,
+            _ => self.client.as_ref().map(AuthUtils::client_fingerprint),
+        };
+        AuthUtils::generate_refresh_token(person_id, tenant_id, fingerprint)
+    }
+
     /// Check if a token is blacklisted
     pub async fn is_token_blacklisted(&self, token: &str, tenant_id: Uuid) -> Result<bool> {
         let mut conn = self.database.get_connection().await?;
@@ -60,10 +193,10 @@ impl AuthService {
             .tenant_service
             .get_tenant_by_subdomain(&request.tenant_subdomain)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Tenant not found"))?;
+            .ok_or(AuthError::TenantNotFound)?;
 
         if !tenant.is_active.unwrap_or(false) {
-            return Err(anyhow::anyhow!("Tenant is not active"));
+            return Err(AuthError::TenantNotActive.into());
         }
 
         // Generate a state parameter that includes tenant info
@@ -84,16 +217,16 @@ impl AuthService {
             .tenant_service
             .get_tenant_by_subdomain(&request.tenant_subdomain)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Tenant not found"))?;
+            .ok_or(AuthError::TenantNotFound)?;
 
         if !tenant.is_active.unwrap_or(false) {
-            return Err(anyhow::anyhow!("Tenant is not active"));
+            return Err(AuthError::TenantNotActive.into());
         }
 
         // Validate state parameter
         let expected_state_prefix = format!("{}:", tenant.subdomain);
         if !request.state.starts_with(&expected_state_prefix) {
-            return Err(anyhow::anyhow!("Invalid state parameter"));
+            return Err(AuthError::InvalidState.into());
         }
 
         // Exchange code for user info
@@ -111,11 +244,7 @@ impl AuthService {
             .await
             .optional()?;
 
-        let person = person_result.ok_or_else(|| {
-            anyhow::anyhow!(
-                "User not found. Please register first or contact your administrator to add you to this tenant."
-            )
-        })?;
+        let person = person_result.ok_or(AuthError::NotMember)?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant.id))
@@ -132,8 +261,7 @@ impl AuthService {
             .await
             .optional()?;
 
-        let (tenant_person, tenant) = tenant_person_result
-            .ok_or_else(|| anyhow::anyhow!("User is not an internal person for this tenant"))?;
+        let (tenant_person, tenant) = tenant_person_result.ok_or(AuthError::NotInternalPerson)?;
 
         // Parse role
         let role = PersonRole::try_from(tenant_person.role)
@@ -141,7 +269,7 @@ impl AuthService {
 
         // Generate JWT tokens
         let access_token = AuthUtils::generate_access_token(person.id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person.id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person.id, tenant.id)?;
 
         // Update last_login timestamp
         diesel::update(person::table.filter(person::id.eq(person.id)))
@@ -175,13 +303,13 @@ impl AuthService {
             .await?
             .is_some()
         {
-            return Err(anyhow::anyhow!("Tenant subdomain already exists"));
+            return Err(AuthError::SubdomainTaken.into());
         }
 
         // Validate state parameter
         let expected_state_prefix = format!("{}:", request.tenant_subdomain);
         if !request.state.starts_with(&expected_state_prefix) {
-            return Err(anyhow::anyhow!("Invalid state parameter"));
+            return Err(AuthError::InvalidState.into());
         }
 
         // Exchange code for user info
@@ -198,7 +326,7 @@ impl AuthService {
             .optional()?;
 
         if existing_person.is_some() {
-            return Err(anyhow::anyhow!("Email already registered"));
+            return Err(AuthError::EmailTaken.into());
         }
 
         let result = conn
@@ -284,7 +412,7 @@ impl AuthService {
         // Generate JWT tokens
         let role = PersonRole::Internal;
         let access_token = AuthUtils::generate_access_token(person.id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person.id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person.id, tenant.id)?;
 
         // Create response
         let auth_user = AuthUtils::create_auth_user(person.id, person.email, person.name, role);
@@ -299,31 +427,13 @@ impl AuthService {
     }
 
     pub async fn login(&self, request: LoginRequest) -> Result<AuthResponse> {
-        // 1. Authenticate with Supabase using direct HTTP requests
-        let _supabase_session = self
-            .supabase_service
-            .authenticate_user(&request.email, &request.password)
-            .await
-            .map_err(|e| {
-                tracing::error!(
-                    "Supabase authentication failed for {}: {}",
-                    request.email,
-                    e
-                );
-                anyhow::anyhow!("Authentication failed: Invalid email or password")
-            })?;
-
         let mut conn = self.database.get_connection().await?;
 
-        // 2. Find user by email in our database
-        let person_result = person::table
-            .filter(person::email.eq(&request.email))
-            .first::<Person>(&mut conn)
-            .await
-            .optional()?;
-
-        let person = person_result
-            .ok_or_else(|| anyhow::anyhow!("User not found in system. Please register first."))?;
+        // 1-2. Authenticate with the auth backend and find user by email in our database
+        let person = self
+            .backend
+            .authenticate(&mut conn, &request.email, &request.password)
+            .await?;
 
         // 3. Find tenant relationship
         let tenant_person_result = if let Some(tenant_subdomain) = &request.tenant_subdomain {
@@ -332,7 +442,7 @@ impl AuthService {
                 .tenant_service
                 .get_tenant_by_subdomain(tenant_subdomain)
                 .await?
-                .ok_or_else(|| anyhow::anyhow!("Tenant not found"))?;
+                .ok_or(AuthError::TenantNotFound)?;
 
             // Set tenant context for RLS
             conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant.id))
@@ -368,7 +478,7 @@ impl AuthService {
 
         // 5. Generate JWT tokens
         let access_token = AuthUtils::generate_access_token(person.id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person.id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person.id, tenant.id)?;
 
         // 6. Update last_login timestamp
         diesel::update(person::table.filter(person::id.eq(person.id)))
@@ -398,7 +508,7 @@ impl AuthService {
             .await?
             .is_some()
         {
-            return Err(anyhow::anyhow!("Tenant subdomain already exists"));
+            return Err(AuthError::SubdomainTaken.into());
         }
 
         // 2. Check if email is already registered
@@ -409,12 +519,12 @@ impl AuthService {
             .optional()?;
 
         if existing_person.is_some() {
-            return Err(anyhow::anyhow!("Email already registered"));
+            return Err(AuthError::EmailTaken.into());
         }
 
         let result = conn
             .transaction::<_, diesel::result::Error, _>(|conn| {
-                let supabase_service = self.supabase_service.clone();
+                let backend = self.backend.clone();
                 let email = request.email.clone();
                 let password = request.password.clone();
                 let first_name = request.first_name.clone();
@@ -423,18 +533,18 @@ impl AuthService {
                 let tenant_name = request.tenant_name.clone();
 
                 Box::pin(async move {
-                    // 3. Register with Supabase using direct HTTP requests
+                    // 3. Register the sign-in identity with the auth backend
                     let user_metadata = serde_json::json!({
                         "first_name": first_name,
                         "last_name": last_name,
                         "tenant_subdomain": tenant_subdomain
                     });
 
-                    let supabase_user = supabase_service
-                        .create_user(&email, &password, Some(user_metadata))
+                    let supabase_uid = backend
+                        .create_identity(&email, &password, user_metadata)
                         .await
                         .map_err(|e| {
-                            tracing::error!("Supabase user creation failed: {}", e);
+                            tracing::error!("{} user creation failed: {}", backend.kind(), e);
                             diesel::result::Error::RollbackTransaction
                         })?;
 
@@ -458,12 +568,7 @@ impl AuthService {
                         .await
                         .map_err(|_| diesel::result::Error::RollbackTransaction)?;
 
-                    // 5. Parse Supabase UUID
-                    let supabase_uid =
-                        Uuid::parse_str(supabase_user["id"].as_str().unwrap_or_default())
-                            .map_err(|_| diesel::result::Error::RollbackTransaction)?;
-
-                    // 6. Create user account with TenantAdmin role
+                    // 5-6. Create user account with TenantAdmin role
                     let full_name = format!("{} {}", first_name, last_name);
                     let new_person = NewPerson {
                         supabase_uid,
@@ -480,6 +585,14 @@ impl AuthService {
                         .get_result(conn)
                         .await?;
 
+                    backend
+                        .person_created(conn, &person, &password)
+                        .await
+                        .map_err(|e| {
+                            tracing::error!("{} credential setup failed: {}", backend.kind(), e);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+
                     // 7. Create tenant_person relationship with internal role
                     let new_tenant_person = NewTenantPerson {
                         person_id: person.id,
@@ -505,7 +618,7 @@ impl AuthService {
         // 8. Generate JWT tokens
         let role = PersonRole::Internal;
         let access_token = AuthUtils::generate_access_token(person.id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person.id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person.id, tenant.id)?;
 
         // 9. Create response
         let auth_user = AuthUtils::create_auth_user(person.id, person.email, person.name, role);
@@ -534,23 +647,20 @@ impl AuthService {
             .optional()?;
 
         if existing_person.is_some() {
-            return Err(anyhow::anyhow!("Email already registered"));
+            return Err(AuthError::EmailTaken.into());
         }
 
-        // 2. Register with Supabase using direct HTTP requests
+        // 2-3. Register the sign-in identity with the auth backend
         let user_metadata = serde_json::json!({
             "first_name": request.first_name,
             "last_name": request.last_name
         });
 
-        let supabase_user = self
-            .supabase_service
-            .create_user(&request.email, &request.password, Some(user_metadata))
+        let supabase_uid = self
+            .backend
+            .create_identity(&request.email, &request.password, user_metadata)
             .await?;
 
-        // 3. Parse Supabase UUID
-        let supabase_uid = Uuid::parse_str(supabase_user["id"].as_str().unwrap_or_default())?;
-
         // 4. Create person account without tenant association
         let full_name = format!("{} {}", request.first_name, request.last_name);
         let new_person = NewPerson {
@@ -567,6 +677,9 @@ impl AuthService {
             .returning(Person::as_returning())
             .get_result(&mut conn)
             .await?;
+        self.backend
+            .person_created(&mut conn, &person, &request.password)
+            .await?;
 
         // 5. Generate temporary JWT tokens without tenant (empty tenant_id for now)
         let access_token = AuthUtils::generate_temporary_access_token(person.id)?;
@@ -589,31 +702,13 @@ impl AuthService {
 
     /// Login without tenant - for users who haven't joined a tenant yet
     pub async fn person_only_login(&self, request: LoginRequest) -> Result<PersonOnlyAuthResponse> {
-        // 1. Authenticate with Supabase
-        let _supabase_session = self
-            .supabase_service
-            .authenticate_user(&request.email, &request.password)
-            .await
-            .map_err(|e| {
-                tracing::error!(
-                    "Supabase authentication failed for {}: {}",
-                    request.email,
-                    e
-                );
-                anyhow::anyhow!("Authentication failed: Invalid email or password")
-            })?;
-
         let mut conn = self.database.get_connection().await?;
 
-        // 2. Find user by email in our database
-        let person_result = person::table
-            .filter(person::email.eq(&request.email))
-            .first::<Person>(&mut conn)
-            .await
-            .optional()?;
-
-        let person = person_result
-            .ok_or_else(|| anyhow::anyhow!("User not found in system. Please register first."))?;
+        // 1-2. Authenticate with the auth backend and find user by email in our database
+        let person = self
+            .backend
+            .authenticate(&mut conn, &request.email, &request.password)
+            .await?;
 
         // 3. Update last_login timestamp
         diesel::update(person::table.filter(person::id.eq(person.id)))
@@ -662,10 +757,10 @@ impl AuthService {
             .tenant_service
             .get_tenant_by_subdomain(&request.tenant_subdomain)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Tenant not found"))?;
+            .ok_or(AuthError::TenantNotFound)?;
 
         if !tenant.is_active.unwrap_or(false) {
-            return Err(anyhow::anyhow!("Tenant is not active"));
+            return Err(AuthError::TenantNotActive.into());
         }
 
         // 2. Get the person
@@ -683,9 +778,7 @@ impl AuthService {
             .optional()?;
 
         if existing_association.is_some() {
-            return Err(anyhow::anyhow!(
-                "Person is already associated with this tenant"
-            ));
+            return Err(AuthError::AlreadyMember.into());
         }
 
         // Set tenant context for RLS
@@ -709,7 +802,7 @@ impl AuthService {
         // 5. Generate JWT tokens with tenant context
         let role = PersonRole::Internal;
         let access_token = AuthUtils::generate_access_token(person.id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person.id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person.id, tenant.id)?;
 
         // 6. Create response
         let auth_user = AuthUtils::create_auth_user(person.id, person.email, person.name, role);
@@ -738,7 +831,7 @@ impl AuthService {
             .await?
             .is_some()
         {
-            return Err(anyhow::anyhow!("Tenant subdomain already exists"));
+            return Err(AuthError::SubdomainTaken.into());
         }
 
         // 2. Get the person
@@ -796,16 +889,14 @@ impl AuthService {
                 })
             })
             .await
-            .map_err(|e| {
-                anyhow::anyhow!("Failed to create tenant and associate person: {:?}", e)
-            })?;
+            .map_err(|e| AuthError::TenantNotCreated(format!("{:?}", e)))?;
 
         let tenant = result;
 
         // 5. Generate JWT tokens with tenant context
         let role = PersonRole::Internal;
         let access_token = AuthUtils::generate_access_token(person_id, tenant.id, &role)?;
-        let refresh_token = AuthUtils::generate_refresh_token(person_id, tenant.id)?;
+        let refresh_token = self.issue_refresh_token(person_id, tenant.id)?;
 
         // 6. Create response
         let auth_user = AuthUtils::create_auth_user(person_id, person_email, person_name, role);
@@ -839,13 +930,17 @@ impl AuthService {
             .is_token_blacklisted(&request.refresh_token, tenant_id)
             .await?
         {
-            return Err(anyhow::anyhow!("Token has been revoked"));
+            return Err(AuthError::TokenRevoked.into());
         }
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        // Compare the client with the one the token was issued to
+        self.check_fingerprint(&mut conn, &claims, person_id, tenant_id)
+            .await?;
+
         // Verify the person-tenant relationship still exists and get role
         let tenant_person = tenant_person::table
             .filter(tenant_person::tenant_id.eq(tenant_id))
@@ -860,7 +955,7 @@ impl AuthService {
 
         // Generate new tokens
         let new_access_token = AuthUtils::generate_access_token(person_id, tenant_id, &role)?;
-        let new_refresh_token = AuthUtils::generate_refresh_token(person_id, tenant_id)?;
+        let new_refresh_token = self.issue_refresh_token(person_id, tenant_id)?;
 
         Ok(RefreshTokenResponse {
             access_token: new_access_token,
@@ -868,89 +963,196 @@ impl AuthService {
         })
     }
 
-    pub async fn logout(
+    /// Records a refresh from a client other than the one the token was issued to, and refuses
+    /// it when the fingerprint mode says so. Tokens without a fingerprint, or requests whose
+    /// client is not known, pass.
+    async fn check_fingerprint(
         &self,
-        request: LogoutRequest,
-        access_token: &str,
+        conn: &mut AsyncPgConnection,
+        claims: &Claims,
         person_id: Uuid,
         tenant_id: Uuid,
     ) -> Result<()> {
+        let mode = FingerprintMode::from_env()?;
+        let (Some(issued), Some(client)) = (claims.fp.as_ref(), self.client.as_ref()) else {
+            return Ok(());
+        };
+        if mode == FingerprintMode::Off {
+            return Ok(());
+        }
+
+        let current = AuthUtils::client_fingerprint(client);
+        let result = FingerprintMatch::compare(issued, &current);
+        if !result.is_anomaly() {
+            return Ok(());
+        }
+        let rejected = mode.rejects(result);
+
+        tracing::warn!(
+            person_id = %person_id,
+            tenant_id = %tenant_id,
+            changed = ?FingerprintMatch::changed_parts(issued, &current),
+            rejected,
+            "Refresh token used from another client"
+        );
+        diesel::insert_into(auth_audit_events::table)
+            .values(&NewAuthAuditEvent {
+                tenant_id,
+                person_id: Some(person_id),
+                event: REFRESH_FINGERPRINT_MISMATCH.to_string(),
+                outcome: if rejected { "rejected" } else { "allowed" }.to_string(),
+                ip_prefix: client.network_prefix(),
+                user_agent: client.user_agent.clone(),
+                details: Some(serde_json::json!({
+                    "changed": FingerprintMatch::changed_parts(issued, &current),
+                    "mode": mode,
+                    "token_issued_at": chrono::DateTime::from_timestamp(claims.iat as i64, 0),
+                })),
+            })
+            .execute(conn)
+            .await?;
+
+        if rejected {
+            return Err(AuthError::TokenReused.into());
+        }
+        Ok(())
+    }
+
+    /// The tenant's sign-in audit trail, newest first
+    pub async fn list_audit_events(
+        &self,
+        tenant_id: Uuid,
+        query: ListAuthAuditQuery,
+    ) -> Result<Vec<AuthAuditEvent>> {
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        // 1. Validate refresh token to ensure it's valid before blacklisting
-        let token_data = AuthUtils::validate_token(&request.refresh_token)?;
-        let claims = token_data.claims;
-
-        // Check if it's actually a refresh token
-        if claims.role != "refresh" {
-            return Err(anyhow::anyhow!("Invalid refresh token"));
+        let mut events = auth_audit_events::table
+            .filter(auth_audit_events::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(event) = &query.event {
+            events = events.filter(auth_audit_events::event.eq(event));
         }
-
-        // Check if token is already blacklisted
-        if self
-            .is_token_blacklisted(&request.refresh_token, tenant_id)
-            .await?
-        {
-            return Err(anyhow::anyhow!("Token has been revoked"));
+        if let Some(person_id) = query.person_id {
+            events = events.filter(auth_audit_events::person_id.eq(person_id));
         }
 
-        // Ensure the token belongs to the requesting person
-        let token_person_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| anyhow::anyhow!("Invalid person ID in token"))?;
-        let token_tenant_id = Uuid::parse_str(&claims.tenant_id)
-            .map_err(|_| anyhow::anyhow!("Invalid tenant ID in token"))?;
+        Ok(events
+            .order(auth_audit_events::created_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(AuthAuditEvent::as_select())
+            .load::<AuthAuditEvent>(&mut conn)
+            .await?)
+    }
 
-        if token_person_id != person_id || token_tenant_id != tenant_id {
-            return Err(anyhow::anyhow!(
-                "Token does not belong to the requesting person"
-            ));
-        }
+    pub async fn logout(
+        &self,
+        request: LogoutRequest,
+        access_token: &str,
+        person_id: Uuid,
+        tenant_id: Uuid,
+    ) -> Result<()> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // 1. Validate refresh token to ensure it's valid before blacklisting
+                    let token_data = AuthUtils::validate_token(&request.refresh_token)?;
+                    let claims = token_data.claims;
+
+                    // Check if it's actually a refresh token
+                    if claims.role != "refresh" {
+                        return Err(AuthError::InvalidRefreshToken.into());
+                    }
+
+                    // Check if token is already blacklisted
+                    if self
+                        .is_token_blacklisted(&request.refresh_token, tenant_id)
+                        .await?
+                    {
+                        return Err(AuthError::TokenRevoked.into());
+                    }
+
+                    // Ensure the token belongs to the requesting person
+                    let token_person_id = Uuid::parse_str(&claims.sub)
+                        .map_err(|_| anyhow::anyhow!("Invalid person ID in token"))?;
+                    let token_tenant_id = Uuid::parse_str(&claims.tenant_id)
+                        .map_err(|_| anyhow::anyhow!("Invalid tenant ID in token"))?;
+
+                    if token_person_id != person_id || token_tenant_id != tenant_id {
+                        return Err(AuthError::TokenNotOwned.into());
+                    }
+
+                    // 2. Validate and blacklist access token
+                    let access_token_data = AuthUtils::validate_token(access_token)?;
+                    let access_claims = access_token_data.claims;
+
+                    let access_token_hash = AuthUtils::hash_token(access_token);
+                    let access_expires_at =
+                        chrono::DateTime::from_timestamp(access_claims.exp as i64, 0)
+                            .ok_or_else(|| anyhow::anyhow!("Invalid access token expiration time"))?
+                            .with_timezone(&Utc);
+
+                    let access_blacklist_entry = NewTokenBlacklist {
+                        token_hash: access_token_hash,
+                        token_type: "access".to_string(),
+                        person_id,
+                        tenant_id,
+                        expires_at: access_expires_at,
+                    };
 
-        // 2. Validate and blacklist access token
-        let access_token_data = AuthUtils::validate_token(access_token)?;
-        let access_claims = access_token_data.claims;
-
-        let access_token_hash = AuthUtils::hash_token(access_token);
-        let access_expires_at = chrono::DateTime::from_timestamp(access_claims.exp as i64, 0)
-            .ok_or_else(|| anyhow::anyhow!("Invalid access token expiration time"))?
-            .with_timezone(&Utc);
-
-        let access_blacklist_entry = NewTokenBlacklist {
-            token_hash: access_token_hash,
-            token_type: "access".to_string(),
-            person_id,
-            tenant_id,
-            expires_at: access_expires_at,
-        };
+                    diesel::insert_into(token_blacklist::table)
+                        .values(&access_blacklist_entry)
+                        .execute(conn)
+                        .await?;
 
-        diesel::insert_into(token_blacklist::table)
-            .values(&access_blacklist_entry)
-            .execute(&mut conn)
-            .await?;
+                    // 3. Add refresh token to blacklist
+                    let token_hash = AuthUtils::hash_token(&request.refresh_token);
+                    let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid token expiration time"))?
+                        .with_timezone(&Utc);
 
-        // 3. Add refresh token to blacklist
-        let token_hash = AuthUtils::hash_token(&request.refresh_token);
-        let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64, 0)
-            .ok_or_else(|| anyhow::anyhow!("Invalid token expiration time"))?
-            .with_timezone(&Utc);
-
-        let new_blacklist_entry = NewTokenBlacklist {
-            token_hash,
-            token_type: "refresh".to_string(),
-            person_id,
-            tenant_id,
-            expires_at,
-        };
+                    let new_blacklist_entry = NewTokenBlacklist {
+                        token_hash,
+                        token_type: "refresh".to_string(),
+                        person_id,
+                        tenant_id,
+                        expires_at,
+                    };
 
-        diesel::insert_into(token_blacklist::table)
-            .values(&new_blacklist_entry)
-            .execute(&mut conn)
-            .await?;
+                    diesel::insert_into(token_blacklist::table)
+                        .values(&new_blacklist_entry)
+                        .execute(conn)
+                        .await?;
 
-        Ok(())
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    /// Confirms an email address with a token the local auth backend sent to it
+    pub async fn verify_email(&self, request: VerifyEmailRequest) -> Result<VerifyEmailResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        let person = self.backend.verify_email(&mut conn, &request.token).await?;
+
+        Ok(VerifyEmailResponse {
+            person_id: person.id,
+            email: person.email,
+            email_verified_at: person.email_verified_at.unwrap_or_else(Utc::now),
+        })
+    }
+
+    /// Sends a fresh verification link to an address that has not been confirmed yet
+    pub async fn resend_verification(&self, request: ResendVerificationRequest) -> Result<()> {
+        let mut conn = self.database.get_connection().await?;
+
+        self.backend
+            .resend_verification(&mut conn, &request.email)
+            .await
     }
 }
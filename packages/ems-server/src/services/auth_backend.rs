@@ -0,0 +1,432 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{AuthBackendKind, NewEmailVerificationToken, NewPersonCredential, Person};
+use crate::schema::{email_verification_tokens, person, person_credentials};
+use crate::services::{AuthError, EmailService, SupabaseService};
+use crate::utils::auth::AuthUtils;
+use crate::utils::circuit_breaker::ServiceUnavailable;
+
+// Hours an email verification link stays valid
+const VERIFICATION_TOKEN_HOURS: i64 = 48;
+
+/// Checks passwords and owns sign-in identities. People, tenants, roles and the JWTs handed out
+/// stay in EMS whichever backend is configured.
+#[async_trait]
+pub trait AuthBackend: Send + Sync {
+    fn kind(&self) -> AuthBackendKind;
+
+    /// Checks the password and returns the person it belongs to. Rejected credentials are
+    /// [`AuthError::AuthenticationFailed`] errors.
+    async fn authenticate(
+        &self,
+        conn: &mut AsyncPgConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Person>;
+
+    /// Creates the sign-in identity for a new account, returning the uid stored on its person.
+    async fn create_identity(
+        &self,
+        email: &str,
+        password: &str,
+        metadata: serde_json::Value,
+    ) -> Result<Uuid>;
+
+    /// Runs on the registration's connection once the new person exists.
+    async fn person_created(
+        &self,
+        conn: &mut AsyncPgConnection,
+        person: &Person,
+        password: &str,
+    ) -> Result<()>;
+
+    /// Confirms an email address from the token sent to it.
+    async fn verify_email(&self, _conn: &mut AsyncPgConnection, _token: &str) -> Result<Person> {
+        Err(AuthError::VerificationNotHandled(self.kind()).into())
+    }
+
+    /// Sends a fresh verification link to an unverified address.
+    async fn resend_verification(&self, _conn: &mut AsyncPgConnection, _email: &str) -> Result<()> {
+        Err(AuthError::VerificationNotHandled(self.kind()).into())
+    }
+}
+
+/// Builds the backend named by AUTH_BACKEND (supabase or local; default supabase).
+pub fn auth_backend_from_env(supabase: SupabaseService) -> Result<Arc<dyn AuthBackend>> {
+    Ok(match auth_backend_kind_from_env()? {
+        AuthBackendKind::Supabase => Arc::new(SupabaseAuthBackend::new(supabase)),
+        AuthBackendKind::Local => Arc::new(LocalAuthBackend::from_env()?),
+    })
+}
+
+pub fn auth_backend_kind_from_env() -> Result<AuthBackendKind> {
+    match env::var("AUTH_BACKEND") {
+        Ok(kind) => AuthBackendKind::try_from(kind.to_lowercase()).map_err(|e| anyhow!(e)),
+        Err(_) => Ok(AuthBackendKind::Supabase),
+    }
+}
+
+fn invalid_credentials() -> anyhow::Error {
+    AuthError::AuthenticationFailed("Invalid email or password".to_string()).into()
+}
+
+async fn find_person_by_email(conn: &mut AsyncPgConnection, email: &str) -> Result<Option<Person>> {
+    Ok(person::table
+        .filter(person::email.eq(email))
+        .first::<Person>(conn)
+        .await
+        .optional()?)
+}
+
+// Supabase
+
+pub struct SupabaseAuthBackend {
+    supabase: SupabaseService,
+}
+
+impl SupabaseAuthBackend {
+    pub fn new(supabase: SupabaseService) -> Self {
+        Self { supabase }
+    }
+
+    // Caches a hash of a password Supabase just accepted, when degraded sign-in is enabled.
+    // Failures are logged and never fail the sign-in itself.
+    async fn remember_credential(
+        &self,
+        conn: &mut AsyncPgConnection,
+        person_id: Uuid,
+        password: &str,
+    ) {
+        if self.supabase.degraded_login_max_age.is_none() {
+            return;
+        }
+
+        let password = password.to_string();
+        let hashed = tokio::task::spawn_blocking(move || AuthUtils::hash_password(&password))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|hash| hash);
+        let password_hash = match hashed {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!("Credential for person {} not cached: {}", person_id, e);
+                return;
+            }
+        };
+
+        let credential = NewPersonCredential {
+            person_id,
+            password_hash,
+            verified_at: Utc::now(),
+        };
+        let result = diesel::insert_into(person_credentials::table)
+            .values(&credential)
+            .on_conflict(person_credentials::person_id)
+            .do_update()
+            .set((
+                person_credentials::password_hash.eq(excluded(person_credentials::password_hash)),
+                person_credentials::verified_at.eq(excluded(person_credentials::verified_at)),
+            ))
+            .execute(conn)
+            .await;
+        if let Err(e) = result {
+            tracing::warn!("Credential for person {} not cached: {}", person_id, e);
+        }
+    }
+}
+
+#[async_trait]
+impl AuthBackend for SupabaseAuthBackend {
+    fn kind(&self) -> AuthBackendKind {
+        AuthBackendKind::Supabase
+    }
+
+    /// While Supabase is unavailable and degraded sign-in is enabled, a password Supabase
+    /// accepted recently enough is checked against the locally cached hash instead.
+    async fn authenticate(
+        &self,
+        conn: &mut AsyncPgConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Person> {
+        let outage = match self.supabase.authenticate_user(email, password).await {
+            Ok(_supabase_session) => None,
+            Err(e) if ServiceUnavailable::is(&e) => Some(e),
+            Err(e) => {
+                tracing::error!("Supabase authentication failed for {}: {}", email, e);
+                return Err(invalid_credentials());
+            }
+        };
+
+        let person = find_person_by_email(conn, email).await?;
+
+        let Some(outage) = outage else {
+            let person = person.ok_or(AuthError::NotRegistered)?;
+            self.remember_credential(conn, person.id, password).await;
+            return Ok(person);
+        };
+
+        // Nobody can sign in without a local account, so an outage changes nothing for them
+        let Some(person) = person else {
+            return Err(invalid_credentials());
+        };
+        let Some(max_age) = self.supabase.degraded_login_max_age else {
+            return Err(outage);
+        };
+        // Without a recent cached credential the password cannot be checked at all
+        let Some(password_hash) = person_credentials::table
+            .filter(person_credentials::person_id.eq(person.id))
+            .filter(person_credentials::verified_at.gt(Utc::now() - max_age))
+            .select(person_credentials::password_hash)
+            .first::<String>(conn)
+            .await
+            .optional()?
+        else {
+            return Err(outage);
+        };
+
+        let password = password.to_string();
+        let matches = tokio::task::spawn_blocking(move || {
+            AuthUtils::verify_password(&password, &password_hash)
+        })
+        .await??;
+        if !matches {
+            return Err(invalid_credentials());
+        }
+
+        tracing::warn!(
+            "Signed in {} with a cached credential while Supabase is unavailable: {}",
+            email,
+            outage
+        );
+        Ok(person)
+    }
+
+    async fn create_identity(
+        &self,
+        email: &str,
+        password: &str,
+        metadata: serde_json::Value,
+    ) -> Result<Uuid> {
+        let supabase_user = self
+            .supabase
+            .create_user(email, password, Some(metadata))
+            .await?;
+        Ok(Uuid::parse_str(
+            supabase_user["id"].as_str().unwrap_or_default(),
+        )?)
+    }
+
+    async fn person_created(
+        &self,
+        conn: &mut AsyncPgConnection,
+        person: &Person,
+        password: &str,
+    ) -> Result<()> {
+        self.remember_credential(conn, person.id, password).await;
+        Ok(())
+    }
+}
+
+// Local
+
+/// Runs sign-in entirely inside EMS: argon2 hashes on the person and emailed verification links.
+pub struct LocalAuthBackend {
+    email: Option<EmailService>,
+    require_verified_email: bool,
+    // Link the token is appended to, e.g. https://ems.example.com/verify-email?token=
+    verify_url: Option<String>,
+}
+
+impl LocalAuthBackend {
+    pub fn new(
+        email: Option<EmailService>,
+        require_verified_email: bool,
+        verify_url: Option<String>,
+    ) -> Self {
+        Self {
+            email,
+            require_verified_email,
+            verify_url,
+        }
+    }
+
+    /// Reads LOCAL_AUTH_REQUIRE_VERIFIED_EMAIL (default false) and LOCAL_AUTH_VERIFY_URL, and
+    /// sends verification links through the SMTP_* settings when configured.
+    pub fn from_env() -> Result<Self> {
+        let email = EmailService::from_env()?;
+        let require_verified_email = env::var("LOCAL_AUTH_REQUIRE_VERIFIED_EMAIL")
+            .map(|v| v == "true")
+            .unwrap_or(false);
+        if email.is_none() {
+            if require_verified_email {
+                return Err(anyhow!(
+                    "LOCAL_AUTH_REQUIRE_VERIFIED_EMAIL needs SMTP_HOST to send verification links"
+                ));
+            }
+            tracing::warn!("SMTP_HOST not set; email verification links will not be sent");
+        }
+        let verify_url = env::var("LOCAL_AUTH_VERIFY_URL")
+            .ok()
+            .filter(|url| !url.is_empty());
+
+        Ok(Self::new(email, require_verified_email, verify_url))
+    }
+
+    /// Stores a new single-use token for the person and returns it.
+    pub async fn issue_verification_token(
+        conn: &mut AsyncPgConnection,
+        person_id: Uuid,
+    ) -> Result<String> {
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let new_token = NewEmailVerificationToken {
+            person_id,
+            token_hash: AuthUtils::hash_token(&token),
+            expires_at: Utc::now() + Duration::hours(VERIFICATION_TOKEN_HOURS),
+        };
+        diesel::insert_into(email_verification_tokens::table)
+            .values(&new_token)
+            .execute(conn)
+            .await?;
+        Ok(token)
+    }
+
+    // Issues a token and emails it. Delivery problems are logged; the person can ask again.
+    async fn send_verification(&self, conn: &mut AsyncPgConnection, person: &Person) -> Result<()> {
+        let token = Self::issue_verification_token(conn, person.id).await?;
+        let Some(email) = &self.email else {
+            return Ok(());
+        };
+
+        let link = match &self.verify_url {
+            Some(url) => format!("{}{}", url, token),
+            None => format!("Verification code: {}", token),
+        };
+        let body = format!(
+            "Hello {},\n\nConfirm your email address for EMS:\n\n{}\n\nThe link expires in {} hours.",
+            person.name, link, VERIFICATION_TOKEN_HOURS
+        );
+        if let Err(e) = email
+            .send(
+                std::slice::from_ref(&person.email),
+                "Confirm your email address",
+                &body,
+                Vec::new(),
+            )
+            .await
+        {
+            tracing::warn!("Verification email to {} not sent: {}", person.email, e);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuthBackend for LocalAuthBackend {
+    fn kind(&self) -> AuthBackendKind {
+        AuthBackendKind::Local
+    }
+
+    async fn authenticate(
+        &self,
+        conn: &mut AsyncPgConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<Person> {
+        let person = find_person_by_email(conn, email).await?;
+        let Some((person, password_hash)) =
+            person.and_then(|p| p.password_hash.clone().map(|hash| (p, hash)))
+        else {
+            return Err(invalid_credentials());
+        };
+
+        let password = password.to_string();
+        let matches = tokio::task::spawn_blocking(move || {
+            AuthUtils::verify_local_password(&password, &password_hash)
+        })
+        .await??;
+        if !matches {
+            return Err(invalid_credentials());
+        }
+
+        if self.require_verified_email && person.email_verified_at.is_none() {
+            return Err(AuthError::EmailNotVerified.into());
+        }
+        Ok(person)
+    }
+
+    // People are identified by their EMS id alone, so any fresh uid will do
+    async fn create_identity(
+        &self,
+        _email: &str,
+        _password: &str,
+        _metadata: serde_json::Value,
+    ) -> Result<Uuid> {
+        Ok(Uuid::new_v4())
+    }
+
+    async fn person_created(
+        &self,
+        conn: &mut AsyncPgConnection,
+        person: &Person,
+        password: &str,
+    ) -> Result<()> {
+        let password = password.to_string();
+        let password_hash =
+            tokio::task::spawn_blocking(move || AuthUtils::hash_local_password(&password))
+                .await??;
+
+        diesel::update(person::table.find(person.id))
+            .set(person::password_hash.eq(Some(password_hash)))
+            .execute(conn)
+            .await?;
+
+        self.send_verification(conn, person).await
+    }
+
+    async fn verify_email(&self, conn: &mut AsyncPgConnection, token: &str) -> Result<Person> {
+        let now = Utc::now();
+
+        // Redeeming the token and checking it is unused and unexpired is one statement
+        let person_id = diesel::update(
+            email_verification_tokens::table
+                .filter(email_verification_tokens::token_hash.eq(AuthUtils::hash_token(token)))
+                .filter(email_verification_tokens::used_at.is_null())
+                .filter(email_verification_tokens::expires_at.gt(now)),
+        )
+        .set(email_verification_tokens::used_at.eq(Some(now)))
+        .returning(email_verification_tokens::person_id)
+        .get_result::<Uuid>(conn)
+        .await
+        .optional()?
+        .ok_or(AuthError::InvalidVerificationToken)?;
+
+        let person = diesel::update(person::table.find(person_id))
+            .set(person::email_verified_at.eq(Some(now)))
+            .returning(Person::as_returning())
+            .get_result(conn)
+            .await?;
+        Ok(person)
+    }
+
+    // Unknown and already verified addresses succeed silently so the endpoint does not reveal
+    // which emails have accounts
+    async fn resend_verification(&self, conn: &mut AsyncPgConnection, email: &str) -> Result<()> {
+        match find_person_by_email(conn, email).await? {
+            Some(person)
+                if person.email_verified_at.is_none() && person.password_hash.is_some() =>
+            {
+                self.send_verification(conn, &person).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::{
+    AssetPinMode, AssetRelationshipType, BatchFirmware, BatchInspection, BatchMachine,
+    BatchOperator, BatchProduct, BatchRecord, BatchRecordContent, BatchRecordResponse, Job,
+    ListBatchRecordsQuery, MachineAssetRelationship, MachineJobAssignment,
+    MachineOperatorAssignment, ManufacturingJob, NewBatchRecord, QaJob, Quantity, ShipmentStatus,
+    StockReferenceType,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, KittingService};
+use crate::utils::batch_record::{consumed_materials, render_batch_record_pdf};
+
+pub struct BatchRecordService {
+    database: DatabaseService,
+}
+
+impl BatchRecordService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Snapshots everything that went into a completed job and stores it as the job's batch
+    /// record. Runs inside the completing transaction, after the job row has been updated, so
+    /// firmware and assignments are captured as they stood at completion.
+    pub async fn assemble(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        job: &Job,
+        completed_by_id: Option<Uuid>,
+    ) -> Result<BatchRecord> {
+        let completed_at = job
+            .end_date
+            .ok_or_else(|| anyhow!("Job has no completion date: {}", job.id))?;
+
+        let manufacturing = manufacturing_job::table
+            .filter(manufacturing_job::job_id.eq(job.id))
+            .select(ManufacturingJob::as_select())
+            .first::<ManufacturingJob>(conn)
+            .await
+            .optional()?;
+        let product_item = match job.item_id {
+            Some(item_id) => items::table
+                .find(item_id)
+                .select((items::internal_part_number, items::description))
+                .first::<(String, Option<String>)>(conn)
+                .await
+                .optional()?,
+            None => None,
+        };
+        let (part_number, description) = product_item.unzip();
+        let product = BatchProduct {
+            item_id: job.item_id,
+            part_number,
+            description: description.flatten(),
+            work_order_number: manufacturing
+                .as_ref()
+                .and_then(|m| m.work_order_number.clone()),
+            production_line: manufacturing.and_then(|m| m.production_line),
+            started_at: job.start_date,
+        };
+
+        // Materials: net issues against the job, with lots and serials from the job itself
+        let issued: Vec<(Uuid, Quantity)> = stock_movements::table
+            .filter(stock_movements::tenant_id.eq(tenant_id))
+            .filter(stock_movements::reference_type.eq(StockReferenceType::Job.to_string()))
+            .filter(stock_movements::reference_id.eq(job.id))
+            .group_by(stock_movements::item_id)
+            .select((
+                stock_movements::item_id,
+                diesel::dsl::sum(stock_movements::quantity),
+            ))
+            .order(stock_movements::item_id)
+            .load::<(Uuid, Option<Quantity>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(item_id, quantity)| (item_id, -quantity.unwrap_or_default()))
+            .filter(|(_, quantity)| *quantity > Quantity::ZERO)
+            .collect();
+        let mut materials = consumed_materials(&issued, job.materials_consumed.as_ref());
+        let material_ids: Vec<Uuid> = materials.iter().map(|m| m.item_id).collect();
+        let part_numbers: HashMap<Uuid, String> = items::table
+            .filter(items::id.eq_any(&material_ids))
+            .select((items::id, items::internal_part_number))
+            .load::<(Uuid, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        // Substitutes consumed in place of BOM components
+        let substitute_for: HashMap<Uuid, Uuid> =
+            KittingService::load_substitutions(conn, tenant_id, job.id)
+                .await?
+                .into_iter()
+                .map(|s| (s.substitute_item_id, s.component_item_id))
+                .collect();
+        for material in &mut materials {
+            material.part_number = part_numbers.get(&material.item_id).cloned();
+            material.substitute_for = substitute_for.get(&material.item_id).copied();
+        }
+
+        // Machines and the firmware they were running
+        let assignments: Vec<(MachineJobAssignment, String)> = machine_job_assignments::table
+            .inner_join(machines::table)
+            .filter(machine_job_assignments::job_id.eq(job.id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .order(machine_job_assignments::start_time.asc())
+            .select((MachineJobAssignment::as_select(), machines::name))
+            .load(conn)
+            .await?;
+        let machine_ids: Vec<Uuid> = assignments.iter().map(|(a, _)| a.machine_id).collect();
+        let firmware = Self::machine_firmware(conn, &machine_ids).await?;
+        let machines = assignments
+            .into_iter()
+            .map(|(assignment, name)| BatchMachine {
+                machine_id: assignment.machine_id,
+                name,
+                started_at: assignment.start_time,
+                ended_at: assignment.end_time,
+                firmware: firmware
+                    .get(&assignment.machine_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        // Operators: the job's own people, then everyone assigned to the machines used
+        let mut roles: Vec<(Uuid, String, Option<Uuid>)> = Vec::new();
+        if let Some(person_id) = job.assigned_person_id {
+            roles.push((person_id, "assigned".to_string(), None));
+        }
+        if let Some(person_id) = job.supervisor_id {
+            roles.push((person_id, "supervisor".to_string(), None));
+        }
+        let machine_operators = machine_operator_assignments::table
+            .filter(machine_operator_assignments::machine_id.eq_any(&machine_ids))
+            .order(machine_operator_assignments::created_at.asc())
+            .select(MachineOperatorAssignment::as_select())
+            .load::<MachineOperatorAssignment>(conn)
+            .await?;
+        for assignment in machine_operators {
+            roles.push((
+                assignment.person_id,
+                assignment.assignment_type,
+                Some(assignment.machine_id),
+            ));
+        }
+        let person_ids: HashSet<Uuid> = roles.iter().map(|(id, _, _)| *id).collect();
+        let names: HashMap<Uuid, String> = person::table
+            .filter(person::id.eq_any(person_ids))
+            .select((person::id, person::name))
+            .load::<(Uuid, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        let operators = roles
+            .into_iter()
+            .map(|(person_id, role, machine_id)| BatchOperator {
+                person_id,
+                name: names.get(&person_id).cloned().unwrap_or_default(),
+                role,
+                machine_id,
+            })
+            .collect();
+
+        let inspections = qa_job::table
+            .inner_join(jobs::table.on(jobs::id.eq(qa_job::job_id)))
+            .filter(qa_job::inspected_job_id.eq(job.id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .order(jobs::created_at.asc())
+            .select((QaJob::as_select(), Job::as_select()))
+            .load::<(QaJob, Job)>(conn)
+            .await?
+            .into_iter()
+            .map(|(qa, inspection)| BatchInspection {
+                job_id: inspection.id,
+                job_number: inspection.job_number,
+                status: inspection.status,
+                inspection_type: qa.inspection_type,
+                test_procedure_id: qa.test_procedure_id,
+                acceptance_criteria: qa.acceptance_criteria,
+                sampling_size: qa.sampling_size,
+                inspector_id: inspection.assigned_person_id,
+                completed_at: inspection.end_date,
+            })
+            .collect();
+
+        let content = BatchRecordContent {
+            product,
+            materials,
+            machines,
+            operators,
+            inspections,
+        };
+
+        let record = diesel::insert_into(batch_records::table)
+            .values(&NewBatchRecord {
+                tenant_id,
+                job_id: job.id,
+                batch_number: job.job_number.clone(),
+                item_id: job.item_id,
+                quantity: job.quantity,
+                completed_at,
+                completed_by_id,
+                record: serde_json::to_value(&content)?,
+            })
+            .returning(BatchRecord::as_returning())
+            .get_result(conn)
+            .await?;
+
+        Ok(record)
+    }
+
+    pub async fn get_batch_record(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<BatchRecordResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let record = batch_records::table
+            .filter(batch_records::job_id.eq(job_id))
+            .filter(batch_records::tenant_id.eq(tenant_id))
+            .select(BatchRecord::as_select())
+            .first::<BatchRecord>(&mut conn)
+            .await
+            .optional()?;
+
+        record
+            .map(|record| BatchRecordResponse::try_from(record).map_err(Into::into))
+            .transpose()
+    }
+
+    pub async fn list_batch_records(
+        &self,
+        tenant_id: Uuid,
+        query: ListBatchRecordsQuery,
+    ) -> Result<Vec<BatchRecordResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut records_query = batch_records::table
+            .filter(batch_records::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(item_id) = query.item_id {
+            records_query = records_query.filter(batch_records::item_id.eq(item_id));
+        }
+        if let Some(from) = query.completed_from {
+            records_query = records_query.filter(batch_records::completed_at.ge(from));
+        }
+        if let Some(to) = query.completed_to {
+            records_query = records_query.filter(batch_records::completed_at.lt(to));
+        }
+
+        let records = records_query
+            .order(batch_records::completed_at.desc())
+            .limit(query.limit.unwrap_or(100) as i64)
+            .offset(query.offset.unwrap_or(0) as i64)
+            .select(BatchRecord::as_select())
+            .load::<BatchRecord>(&mut conn)
+            .await?;
+
+        records
+            .into_iter()
+            .map(|record| BatchRecordResponse::try_from(record).map_err(Into::into))
+            .collect()
+    }
+
+    pub async fn batch_record_pdf(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<(String, Vec<u8>)>> {
+        Ok(self
+            .get_batch_record(tenant_id, job_id)
+            .await?
+            .map(|record| {
+                (
+                    record.batch_number.clone(),
+                    render_batch_record_pdf(&record),
+                )
+            }))
+    }
+
+    /// Lots shipped on an order: the batch records of the customer's jobs for ordered items
+    /// completed before the order's last shipment left. Orders carry no link to the jobs that
+    /// filled them, so this matches on customer, item and ship date.
+    pub async fn shipped_lots(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        customer_id: Uuid,
+        item_ids: &[Uuid],
+    ) -> Result<Vec<BatchRecord>> {
+        let last_shipped_at = shipments::table
+            .filter(shipments::tenant_id.eq(tenant_id))
+            .filter(shipments::order_id.eq(order_id))
+            .filter(shipments::status.ne(ShipmentStatus::Cancelled.to_string()))
+            .select(diesel::dsl::max(shipments::shipped_at))
+            .first::<Option<DateTime<Utc>>>(conn)
+            .await?;
+        let Some(shipped_at) = last_shipped_at else {
+            return Ok(Vec::new());
+        };
+
+        Ok(batch_records::table
+            .inner_join(jobs::table.on(jobs::id.eq(batch_records::job_id)))
+            .filter(batch_records::tenant_id.eq(tenant_id))
+            .filter(jobs::customer_id.eq(customer_id))
+            .filter(batch_records::item_id.eq_any(item_ids))
+            .filter(batch_records::completed_at.le(shipped_at))
+            .order(batch_records::completed_at.asc())
+            .select(BatchRecord::as_select())
+            .load::<BatchRecord>(conn)
+            .await?)
+    }
+
+    /// Orders a lot may have shipped on, matched as in [`Self::shipped_lots`]: the customer's
+    /// orders for the lot's item with a shipment that left after the lot was completed.
+    pub async fn orders_shipping_lot(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        customer_id: Uuid,
+        item_id: Uuid,
+        completed_at: DateTime<Utc>,
+    ) -> Result<Vec<(Uuid, String)>> {
+        Ok(orders::table
+            .filter(orders::tenant_id.eq(tenant_id))
+            .filter(orders::external_entity_id.eq(customer_id))
+            .filter(diesel::dsl::exists(
+                order_items::table
+                    .filter(order_items::order_id.eq(orders::id))
+                    .filter(order_items::item_id.eq(item_id)),
+            ))
+            .filter(diesel::dsl::exists(
+                shipments::table
+                    .filter(shipments::order_id.eq(orders::id))
+                    .filter(shipments::status.ne(ShipmentStatus::Cancelled.to_string()))
+                    .filter(shipments::shipped_at.ge(completed_at)),
+            ))
+            .order(orders::order_date.asc())
+            .select((orders::id, orders::order_number))
+            .load::<(Uuid, String)>(conn)
+            .await?)
+    }
+
+    // Private helper methods
+
+    /// Firmware assets linked to each machine, following `latest` pins to the current version
+    /// of their lineage.
+    async fn machine_firmware(
+        conn: &mut AsyncPgConnection,
+        machine_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<BatchFirmware>>> {
+        let relationships = machine_asset_relationships::table
+            .inner_join(assets::table.on(assets::id.eq(machine_asset_relationships::asset_id)))
+            .filter(machine_asset_relationships::machine_id.eq_any(machine_ids))
+            .filter(
+                machine_asset_relationships::relationship_type
+                    .eq(AssetRelationshipType::Firmware.to_string()),
+            )
+            .select((
+                MachineAssetRelationship::as_select(),
+                assets::lineage_id,
+                assets::name,
+                assets::version,
+                assets::checksum,
+            ))
+            .load::<(
+                MachineAssetRelationship,
+                Uuid,
+                String,
+                Option<String>,
+                Option<String>,
+            )>(conn)
+            .await?;
+
+        let latest_lineages: Vec<Uuid> = relationships
+            .iter()
+            .filter(|(rel, ..)| rel.pin_mode == AssetPinMode::Latest.to_string())
+            .map(|(_, lineage_id, ..)| *lineage_id)
+            .collect();
+        let current: HashMap<Uuid, BatchFirmware> = assets::table
+            .filter(assets::lineage_id.eq_any(&latest_lineages))
+            .filter(assets::is_current.eq(true))
+            .select((
+                assets::lineage_id,
+                assets::id,
+                assets::name,
+                assets::version,
+                assets::checksum,
+            ))
+            .load::<(Uuid, Uuid, String, Option<String>, Option<String>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(lineage_id, asset_id, name, version, checksum)| {
+                (
+                    lineage_id,
+                    BatchFirmware {
+                        asset_id,
+                        name,
+                        version,
+                        checksum,
+                    },
+                )
+            })
+            .collect();
+
+        let mut firmware: HashMap<Uuid, Vec<BatchFirmware>> = HashMap::new();
+        for (rel, lineage_id, name, version, checksum) in relationships {
+            let pinned = BatchFirmware {
+                asset_id: rel.asset_id,
+                name,
+                version,
+                checksum,
+            };
+            let resolved = match AssetPinMode::try_from(rel.pin_mode) {
+                Ok(AssetPinMode::Latest) => current.get(&lineage_id).cloned().unwrap_or(pinned),
+                _ => pinned,
+            };
+            firmware.entry(rel.machine_id).or_default().push(resolved);
+        }
+
+        Ok(firmware)
+    }
+}
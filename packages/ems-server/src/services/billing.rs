@@ -0,0 +1,433 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::{env, sync::Arc};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    BillingPortalRequest, BillingPortalResponse, BillingResponse, CallerContext,
+    NewBillingWebhookEvent, NewTenantBilling, Permission, StripeEvent, StripeInvoice,
+    StripeSubscription, Tenant, TenantBilling, DEFAULT_PLAN,
+};
+use crate::schema::*;
+use crate::services::{BillingProvider, DatabaseService, StripeBilling, TenantCache};
+use crate::utils::billing::{
+    subscription_grants_access, subscription_period_end, subscription_plan, verify_stripe_signature,
+};
+use crate::utils::AppError;
+
+/// Errors from billing that are not failures of the service itself
+#[derive(Error, Debug)]
+pub enum BillingError {
+    #[error("Billing provider not configured")]
+    ProviderNotConfigured,
+
+    #[error("Billing webhooks not configured")]
+    WebhooksNotConfigured,
+
+    #[error("Invalid billing request: {0}")]
+    InvalidRequest(String),
+
+    #[error("{0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid billing event: {0}")]
+    InvalidEvent(String),
+
+    #[error("Billing request failed: {0}")]
+    RequestFailed(String),
+}
+
+impl From<BillingError> for AppError {
+    fn from(error: BillingError) -> Self {
+        match error {
+            BillingError::ProviderNotConfigured | BillingError::WebhooksNotConfigured => {
+                AppError::Unavailable(error.to_string())
+            }
+            BillingError::InvalidRequest(_)
+            | BillingError::InvalidSignature(_)
+            | BillingError::InvalidEvent(_) => AppError::Validation(error.to_string()),
+            BillingError::RequestFailed(_) => AppError::Upstream(error.to_string()),
+        }
+    }
+}
+
+/// Tenant plans and their Stripe subscriptions. Billing records live in the shared database
+/// next to the tenants they belong to.
+pub struct BillingService {
+    shared: DatabaseService,
+    provider: Option<Arc<dyn BillingProvider>>,
+    webhook_secret: Option<String>,
+    portal_return_url: Option<String>,
+    cache: Option<TenantCache>,
+}
+
+impl BillingService {
+    /// Uses the Stripe account configured through STRIPE_SECRET_KEY, verifies webhooks with
+    /// STRIPE_WEBHOOK_SECRET and sends portal visitors back to BILLING_PORTAL_RETURN_URL.
+    pub fn new(database: DatabaseService) -> Result<Self> {
+        let provider =
+            StripeBilling::from_env()?.map(|stripe| Arc::new(stripe) as Arc<dyn BillingProvider>);
+        let non_empty = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+
+        Ok(Self::with_provider(
+            database,
+            provider,
+            non_empty("STRIPE_WEBHOOK_SECRET"),
+            non_empty("BILLING_PORTAL_RETURN_URL"),
+        ))
+    }
+
+    pub fn with_provider(
+        database: DatabaseService,
+        provider: Option<Arc<dyn BillingProvider>>,
+        webhook_secret: Option<String>,
+        portal_return_url: Option<String>,
+    ) -> Self {
+        Self {
+            shared: database.shared(),
+            provider,
+            webhook_secret,
+            portal_return_url,
+            cache: None,
+        }
+    }
+
+    /// Drops tenants suspended or reactivated by a webhook from the cache so the change
+    /// applies at once.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Billing operations
+
+    pub async fn get_billing(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<BillingResponse> {
+        caller.require(Permission::ManageBilling)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let billing = Self::find_billing(&mut conn, tenant_id).await?;
+        Ok(match billing {
+            Some(billing) => BillingResponse {
+                tenant_id,
+                plan: billing.plan,
+                subscription_status: billing.subscription_status,
+                current_period_end: billing.current_period_end,
+                cancel_at_period_end: billing.cancel_at_period_end,
+                customer_linked: billing.stripe_customer_id.is_some(),
+                suspended: billing.billing_suspended,
+            },
+            None => BillingResponse {
+                tenant_id,
+                plan: DEFAULT_PLAN.to_string(),
+                subscription_status: None,
+                current_period_end: None,
+                cancel_at_period_end: false,
+                customer_linked: false,
+                suspended: false,
+            },
+        })
+    }
+
+    /// Opens a Stripe customer portal session for the tenant, creating its Stripe customer
+    /// the first time. The admin subscribes, changes plan or updates payment details there.
+    pub async fn open_portal(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: BillingPortalRequest,
+    ) -> Result<BillingPortalResponse> {
+        caller.require(Permission::ManageBilling)?;
+        let provider = self.provider()?;
+        let return_url = request
+            .return_url
+            .or_else(|| self.portal_return_url.clone())
+            .ok_or_else(|| {
+                BillingError::InvalidRequest(
+                    "return_url is required as BILLING_PORTAL_RETURN_URL is not set".to_string(),
+                )
+            })?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let customer_id = match Self::find_billing(&mut conn, tenant_id)
+            .await?
+            .and_then(|billing| billing.stripe_customer_id)
+        {
+            Some(customer_id) => customer_id,
+            None => {
+                let tenant = tenants::table
+                    .find(tenant_id)
+                    .select(Tenant::as_select())
+                    .first::<Tenant>(&mut conn)
+                    .await?;
+                let email = person::table
+                    .find(caller.person_id)
+                    .select(person::email)
+                    .first::<String>(&mut conn)
+                    .await
+                    .optional()?;
+
+                let customer_id = provider.create_customer(&tenant, email.as_deref()).await?;
+                diesel::insert_into(tenant_billing::table)
+                    .values(&NewTenantBilling {
+                        tenant_id,
+                        stripe_customer_id: Some(customer_id.clone()),
+                    })
+                    .on_conflict(tenant_billing::tenant_id)
+                    .do_update()
+                    .set(tenant_billing::stripe_customer_id.eq(Some(customer_id.clone())))
+                    .execute(&mut conn)
+                    .await?;
+                customer_id
+            }
+        };
+
+        let url = provider
+            .create_portal_session(&customer_id, &return_url)
+            .await?;
+        Ok(BillingPortalResponse { url })
+    }
+
+    // Webhook operations
+
+    /// Applies a Stripe webhook. `signature` is the request's Stripe-Signature header, checked
+    /// against the raw body before anything is read from it. Events Stripe delivers again are
+    /// skipped, as are events for customers that no tenant is billed as.
+    pub async fn handle_webhook(&self, payload: &[u8], signature: Option<&str>) -> Result<()> {
+        let secret = self
+            .webhook_secret
+            .as_deref()
+            .ok_or(BillingError::WebhooksNotConfigured)?;
+        let signature = signature.ok_or_else(|| {
+            BillingError::InvalidSignature(
+                "Invalid signature: the Stripe-Signature header is missing".to_string(),
+            )
+        })?;
+        verify_stripe_signature(payload, signature, secret, Utc::now().timestamp())
+            .map_err(BillingError::InvalidSignature)?;
+
+        let event: StripeEvent = serde_json::from_slice(payload)
+            .map_err(|e| BillingError::InvalidEvent(e.to_string()))?;
+
+        // Events cover any tenant, so they are applied without a tenant context
+        let mut conn = self.shared.get_connection().await?;
+        let changed_tenant = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let recorded = diesel::insert_into(billing_webhook_events::table)
+                        .values(&NewBillingWebhookEvent {
+                            stripe_event_id: event.id.clone(),
+                            event_type: event.event_type.clone(),
+                            tenant_id: None,
+                        })
+                        .on_conflict(billing_webhook_events::stripe_event_id)
+                        .do_nothing()
+                        .execute(conn)
+                        .await?;
+                    if recorded == 0 {
+                        tracing::debug!("Billing event {} already applied", event.id);
+                        return Ok(None);
+                    }
+
+                    let tenant_id = Self::apply_event(conn, &event).await?;
+                    if tenant_id.is_some() {
+                        diesel::update(
+                            billing_webhook_events::table
+                                .filter(billing_webhook_events::stripe_event_id.eq(&event.id)),
+                        )
+                        .set(billing_webhook_events::tenant_id.eq(tenant_id))
+                        .execute(conn)
+                        .await?;
+                    }
+                    Ok(tenant_id)
+                })
+            })
+            .await?;
+
+        if let Some(tenant_id) = changed_tenant {
+            self.forget(tenant_id);
+        }
+        Ok(())
+    }
+
+    // Private helper methods
+
+    fn provider(&self) -> Result<&Arc<dyn BillingProvider>> {
+        self.provider
+            .as_ref()
+            .ok_or(BillingError::ProviderNotConfigured.into())
+    }
+
+    fn forget(&self, tenant_id: Uuid) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(tenant_id);
+        }
+    }
+
+    async fn find_billing(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+    ) -> Result<Option<TenantBilling>> {
+        Ok(tenant_billing::table
+            .filter(tenant_billing::tenant_id.eq(tenant_id))
+            .select(TenantBilling::as_select())
+            .first::<TenantBilling>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn billing_for_customer(
+        conn: &mut AsyncPgConnection,
+        customer_id: &str,
+    ) -> Result<Option<TenantBilling>> {
+        let billing = tenant_billing::table
+            .filter(tenant_billing::stripe_customer_id.eq(customer_id))
+            .select(TenantBilling::as_select())
+            .first::<TenantBilling>(conn)
+            .await
+            .optional()?;
+        if billing.is_none() {
+            tracing::warn!("Billing event for unknown Stripe customer {}", customer_id);
+        }
+        Ok(billing)
+    }
+
+    // Applies one event and returns the tenant it concerned
+    async fn apply_event(
+        conn: &mut AsyncPgConnection,
+        event: &StripeEvent,
+    ) -> Result<Option<Uuid>> {
+        let object = event.data.object.clone();
+        match event.event_type.as_str() {
+            "customer.subscription.created"
+            | "customer.subscription.updated"
+            | "customer.subscription.deleted" => {
+                let subscription: StripeSubscription = serde_json::from_value(object)
+                    .map_err(|e| BillingError::InvalidEvent(e.to_string()))?;
+                Self::apply_subscription(conn, &subscription).await
+            }
+            "invoice.paid" | "invoice.payment_failed" => {
+                let invoice: StripeInvoice = serde_json::from_value(object)
+                    .map_err(|e| BillingError::InvalidEvent(e.to_string()))?;
+                let Some(billing) = Self::billing_for_customer(conn, &invoice.customer).await?
+                else {
+                    return Ok(None);
+                };
+
+                if event.event_type == "invoice.paid" {
+                    Self::set_access(conn, &billing, None).await?;
+                } else if invoice.next_payment_attempt.is_none() {
+                    // Stripe has stopped retrying the invoice
+                    Self::set_access(conn, &billing, Some("Billing: payment failed")).await?;
+                } else {
+                    tracing::info!(
+                        "Payment of invoice {} failed for tenant {}; Stripe will retry",
+                        invoice.id,
+                        billing.tenant_id
+                    );
+                }
+                Ok(Some(billing.tenant_id))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn apply_subscription(
+        conn: &mut AsyncPgConnection,
+        subscription: &StripeSubscription,
+    ) -> Result<Option<Uuid>> {
+        let Some(billing) = Self::billing_for_customer(conn, &subscription.customer).await? else {
+            return Ok(None);
+        };
+
+        // A canceled subscription leaves the tenant on the default plan
+        let plan = if subscription.status == "canceled" {
+            DEFAULT_PLAN.to_string()
+        } else {
+            subscription_plan(subscription)
+        };
+        let billing = diesel::update(tenant_billing::table.find(billing.id))
+            .set((
+                tenant_billing::plan.eq(plan),
+                tenant_billing::stripe_subscription_id.eq(Some(&subscription.id)),
+                tenant_billing::subscription_status.eq(Some(&subscription.status)),
+                tenant_billing::current_period_end.eq(subscription_period_end(subscription)),
+                tenant_billing::cancel_at_period_end.eq(subscription.cancel_at_period_end),
+            ))
+            .returning(TenantBilling::as_returning())
+            .get_result::<TenantBilling>(conn)
+            .await?;
+
+        match subscription_grants_access(&subscription.status) {
+            Some(true) => Self::set_access(conn, &billing, None).await?,
+            Some(false) => {
+                let reason = format!("Billing: subscription {}", subscription.status);
+                Self::set_access(conn, &billing, Some(&reason)).await?;
+            }
+            None => {}
+        }
+        Ok(Some(billing.tenant_id))
+    }
+
+    // Suspends an active tenant for non-payment with `suspend_reason`, or with `None`
+    // reactivates a tenant billing suspended. Suspensions made by platform operators are left
+    // alone either way.
+    async fn set_access(
+        conn: &mut AsyncPgConnection,
+        billing: &TenantBilling,
+        suspend_reason: Option<&str>,
+    ) -> Result<()> {
+        let is_active = tenants::table
+            .find(billing.tenant_id)
+            .select(tenants::is_active)
+            .first::<Option<bool>>(conn)
+            .await?
+            .unwrap_or(false);
+
+        if suspend_reason.is_none() && billing.billing_suspended {
+            diesel::update(tenants::table.find(billing.tenant_id))
+                .set((
+                    tenants::is_active.eq(Some(true)),
+                    tenants::suspended_at.eq(None::<chrono::DateTime<Utc>>),
+                    tenants::suspended_reason.eq(None::<String>),
+                ))
+                .execute(conn)
+                .await?;
+            tracing::info!("Tenant {} reactivated after payment", billing.tenant_id);
+        } else if let (Some(reason), true) = (suspend_reason, is_active) {
+            diesel::update(tenants::table.find(billing.tenant_id))
+                .set((
+                    tenants::is_active.eq(Some(false)),
+                    tenants::suspended_at.eq(Some(Utc::now())),
+                    tenants::suspended_reason.eq(Some(reason)),
+                ))
+                .execute(conn)
+                .await?;
+            tracing::info!("Tenant {} suspended: {}", billing.tenant_id, reason);
+        } else {
+            return Ok(());
+        }
+
+        diesel::update(tenant_billing::table.find(billing.id))
+            .set(tenant_billing::billing_suspended.eq(suspend_reason.is_some()))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::{env, time::Duration};
+
+use crate::models::Tenant;
+use crate::services::BillingError;
+
+// Stripe API calls must finish within this time
+const BILLING_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEFAULT_STRIPE_API_URL: &str = "https://api.stripe.com";
+
+/// Payment provider holding tenants' customers and subscriptions.
+#[async_trait]
+pub trait BillingProvider: Send + Sync {
+    /// Creates the customer a tenant is billed as and returns its id.
+    async fn create_customer(&self, tenant: &Tenant, email: Option<&str>) -> Result<String>;
+
+    /// URL of a short-lived customer portal session, where the customer manages their
+    /// subscription and payment methods before being sent back to `return_url`.
+    async fn create_portal_session(&self, customer_id: &str, return_url: &str) -> Result<String>;
+}
+
+#[derive(Deserialize)]
+struct StripeObject {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct PortalSessionResponse {
+    url: String,
+}
+
+/// Stripe, reached through its REST API. Subscriptions themselves are created and changed in
+/// Stripe (through the portal or the Stripe dashboard) and reported back through webhooks.
+pub struct StripeBilling {
+    client: Client,
+    api_url: String,
+    secret_key: String,
+}
+
+impl StripeBilling {
+    pub fn new(api_url: &str, secret_key: String) -> Result<Self> {
+        let client = Client::builder().timeout(BILLING_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            secret_key,
+        })
+    }
+
+    /// Configures Stripe from STRIPE_SECRET_KEY and STRIPE_API_URL (default
+    /// `https://api.stripe.com`). Returns `Ok(None)` when STRIPE_SECRET_KEY is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        match env::var("STRIPE_SECRET_KEY") {
+            Ok(key) if !key.is_empty() => {
+                let api_url = env::var("STRIPE_API_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty())
+                    .unwrap_or_else(|| DEFAULT_STRIPE_API_URL.to_string());
+                Ok(Some(Self::new(&api_url, key)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn post<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        form: &[(&str, &str)],
+        idempotency_key: Option<&str>,
+    ) -> Result<T> {
+        let mut request = self
+            .client
+            .post(format!("{}/v1/{}", self.api_url, path))
+            .bearer_auth(&self.secret_key)
+            .form(form);
+        if let Some(key) = idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| BillingError::RequestFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(BillingError::RequestFailed(format!(
+                "{} returned {}",
+                path,
+                response.status()
+            ))
+            .into());
+        }
+
+        response.json::<T>().await.map_err(|e| {
+            BillingError::RequestFailed(format!("invalid {} response: {}", path, e)).into()
+        })
+    }
+}
+
+#[async_trait]
+impl BillingProvider for StripeBilling {
+    async fn create_customer(&self, tenant: &Tenant, email: Option<&str>) -> Result<String> {
+        let tenant_id = tenant.id.to_string();
+        let mut form = vec![
+            ("name", tenant.name.as_str()),
+            ("metadata[tenant_id]", tenant_id.as_str()),
+            ("metadata[subdomain]", tenant.subdomain.as_str()),
+        ];
+        if let Some(email) = email {
+            form.push(("email", email));
+        }
+
+        // Two admins opening the portal at once get the same customer rather than one each
+        let idempotency_key = format!("customer-{}", tenant_id);
+        let customer: StripeObject = self
+            .post("customers", &form, Some(&idempotency_key))
+            .await?;
+        Ok(customer.id)
+    }
+
+    async fn create_portal_session(&self, customer_id: &str, return_url: &str) -> Result<String> {
+        let session: PortalSessionResponse = self
+            .post(
+                "billing_portal/sessions",
+                &[("customer", customer_id), ("return_url", return_url)],
+                None,
+            )
+            .await?;
+        Ok(session.url)
+    }
+}
@@ -0,0 +1,528 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use uuid::Uuid;
+
+use crate::models::{
+    BomExportFormat, BomExportLine, BomExportPart, BomImportLineResult, BomImportRow,
+    BomImportStatus, ImportBomRequest, ImportBomResponse, Item, ItemBom, ItemContext,
+    NewInventoryItem, NewItem, NewItemBom, NumberingEntity,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, ItemError, NumberingService};
+use crate::utils::bom_import::{parse_bom_import, render_bom_csv};
+
+type Result<T, E = ItemError> = std::result::Result<T, E>;
+
+/// Imports and exports item BOMs in the CSV conventions of EDA tools. Components are matched to
+/// the tenant's items by internal part number, else by MPN and manufacturer; components that
+/// match none are only created once the caller confirms with `create_missing`.
+pub struct BomImportService {
+    database: DatabaseService,
+}
+
+impl BomImportService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Imports a BOM file into the BOM of `parent_item_id`. Every line is matched first and
+    /// nothing is saved unless all of them pass; lines for components already on the BOM are
+    /// updated, and with `replace` lines the file no longer lists are removed, in one transaction.
+    pub async fn import_bom(
+        &self,
+        tenant_id: Uuid,
+        parent_item_id: Uuid,
+        request: ImportBomRequest,
+    ) -> Result<ImportBomResponse> {
+        let (rows, failed) = parse_bom_import(&request.csv).map_err(ItemError::InvalidBomImport)?;
+
+        let dry_run = request.dry_run.unwrap_or(false);
+        let create_missing = request.create_missing.unwrap_or(false);
+        let replace = request.replace.unwrap_or(false);
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let tenant_items = Self::load_tenant_items(&mut conn, tenant_id).await?;
+        if !tenant_items.iter().any(|item| item.id == parent_item_id) {
+            return Err(ItemError::NotFound);
+        }
+        let matcher = ItemMatcher::new(&tenant_items);
+
+        // Internal part numbers are unique across tenants, so new ones must be free everywhere
+        let new_numbers: Vec<String> = rows
+            .iter()
+            .filter_map(|row| row.internal_part_number.clone())
+            .collect();
+        let taken: HashSet<String> = items::table
+            .filter(items::internal_part_number.eq_any(&new_numbers))
+            .select(items::internal_part_number)
+            .load::<String>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|number| normalize(&number))
+            .collect();
+        let has_item_sequence = numbering_sequences::table
+            .filter(numbering_sequences::tenant_id.eq(tenant_id))
+            .filter(numbering_sequences::entity.eq(NumberingEntity::Item.to_string()))
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await?
+            > 0;
+
+        let existing: Vec<Uuid> = item_bom::table
+            .filter(item_bom::tenant_id.eq(tenant_id))
+            .filter(item_bom::parent_item_id.eq(parent_item_id))
+            .select(item_bom::component_item_id)
+            .load::<Uuid>(&mut conn)
+            .await?;
+        drop(conn);
+
+        let mut results = Vec::new();
+        let mut components: HashMap<Uuid, usize> = HashMap::new();
+        for row in &rows {
+            let mut errors = Vec::new();
+            let component = match matcher.component(row) {
+                Ok(component) => component,
+                Err(e) => {
+                    errors.push(e);
+                    None
+                }
+            };
+
+            match component {
+                Some(id) if id == parent_item_id => {
+                    errors.push("An assembly cannot contain itself".to_string())
+                }
+                Some(id) => {
+                    if let Some(line) = components.insert(id, row.line) {
+                        errors.push(format!("Same component as line {}", line));
+                    }
+                }
+                None if errors.is_empty() && create_missing => {
+                    if row.manufacturer.is_none() {
+                        errors.push("Missing manufacturer for new component".to_string());
+                    }
+                    match &row.internal_part_number {
+                        Some(number) if taken.contains(&normalize(number)) => errors.push(format!(
+                            "Internal part number {} is used by another item",
+                            number
+                        )),
+                        None if !has_item_sequence => errors.push(
+                            "Missing internal part number for new component and no item \
+                             numbering sequence is set up"
+                                .to_string(),
+                        ),
+                        _ => {}
+                    }
+                }
+                None => {}
+            }
+
+            let mut substitutes = Vec::new();
+            let mut unknown_substitutes = Vec::new();
+            for part_number in &row.substitutes {
+                match matcher.substitute(part_number) {
+                    Some(id) if Some(id) != component && id != parent_item_id => {
+                        substitutes.push(id)
+                    }
+                    Some(_) => {}
+                    None => unknown_substitutes.push(part_number.clone()),
+                }
+            }
+
+            let status = match (component, errors.is_empty()) {
+                (_, false) => BomImportStatus::Failed,
+                (Some(_), true) => BomImportStatus::Matched,
+                (None, true) if create_missing => BomImportStatus::New,
+                (None, true) => BomImportStatus::Unknown,
+            };
+            results.push((
+                row,
+                substitutes,
+                BomImportLineResult {
+                    line: row.line,
+                    part_number: row.part_number(),
+                    designators: row.designators.clone(),
+                    quantity: Some(row.quantity),
+                    status,
+                    component_item_id: component,
+                    unknown_substitutes,
+                    errors,
+                },
+            ));
+        }
+
+        let count = |status: BomImportStatus| {
+            results
+                .iter()
+                .filter(|(_, _, result)| result.status == status)
+                .count()
+        };
+        let (matched, new, unknown) = (
+            count(BomImportStatus::Matched),
+            count(BomImportStatus::New),
+            count(BomImportStatus::Unknown),
+        );
+        let failed_count = failed.len() + count(BomImportStatus::Failed);
+        let removed: Vec<Uuid> = if replace {
+            existing
+                .into_iter()
+                .filter(|id| !components.contains_key(id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let imported = failed_count == 0 && unknown == 0 && !dry_run;
+        if imported {
+            let removed = &removed;
+            results = self
+                .database
+                .with_tenant_tx::<_, ItemError, _>(tenant_id, |conn| {
+                    Box::pin(async move {
+                        for (row, substitutes, result) in results.iter_mut() {
+                            let component_item_id = match result.component_item_id {
+                                Some(id) => id,
+                                None => Self::create_component(conn, tenant_id, row).await?,
+                            };
+                            result.component_item_id = Some(component_item_id);
+                            Self::upsert_line(
+                                conn,
+                                tenant_id,
+                                parent_item_id,
+                                component_item_id,
+                                row,
+                                substitutes,
+                            )
+                            .await?;
+                        }
+
+                        if !removed.is_empty() {
+                            diesel::delete(
+                                item_bom::table
+                                    .filter(item_bom::tenant_id.eq(tenant_id))
+                                    .filter(item_bom::parent_item_id.eq(parent_item_id))
+                                    .filter(item_bom::component_item_id.eq_any(removed)),
+                            )
+                            .execute(conn)
+                            .await?;
+                        }
+
+                        Ok(results)
+                    })
+                })
+                .await?;
+        }
+
+        let mut lines: Vec<BomImportLineResult> = results
+            .into_iter()
+            .map(|(_, _, result)| result)
+            .chain(failed)
+            .collect();
+        lines.sort_by_key(|result| result.line);
+
+        Ok(ImportBomResponse {
+            dry_run,
+            imported,
+            total_lines: lines.len(),
+            matched,
+            new,
+            unknown,
+            failed: failed_count,
+            removed: removed.len(),
+            lines,
+        })
+    }
+
+    /// The BOM of `parent_item_id` as CSV in `format`, with the file name it is served under, or
+    /// `None` when the item is not the tenant's.
+    pub async fn export_bom(
+        &self,
+        tenant_id: Uuid,
+        parent_item_id: Uuid,
+        format: BomExportFormat,
+    ) -> Result<Option<(String, String)>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let parent = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(items::id.eq(parent_item_id))
+            .select(Item::as_select())
+            .first::<Item>(&mut conn)
+            .await
+            .optional()?;
+        let Some(parent) = parent else {
+            return Ok(None);
+        };
+
+        let bom_lines = item_bom::table
+            .filter(item_bom::tenant_id.eq(tenant_id))
+            .filter(item_bom::parent_item_id.eq(parent_item_id))
+            .order((item_bom::assembly_order.asc(), item_bom::created_at.asc()))
+            .select(ItemBom::as_select())
+            .load::<ItemBom>(&mut conn)
+            .await?;
+
+        let item_ids: Vec<Uuid> = bom_lines
+            .iter()
+            .flat_map(|line| {
+                std::iter::once(line.component_item_id)
+                    .chain(line.substitutes.iter().flatten().flatten().copied())
+            })
+            .collect();
+        let parts: HashMap<Uuid, Item> = items::table
+            .filter(items::id.eq_any(&item_ids))
+            .select(Item::as_select())
+            .load::<Item>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item))
+            .collect();
+
+        let export_part = |item: &Item| BomExportPart {
+            internal_part_number: item.internal_part_number.clone(),
+            mfr_part_number: item.mfr_part_number.clone(),
+            manufacturer: item.manufacturer.clone(),
+        };
+        let lines: Vec<BomExportLine> = bom_lines
+            .into_iter()
+            .filter_map(|line| {
+                let component = parts.get(&line.component_item_id)?;
+                Some(BomExportLine {
+                    component: export_part(component),
+                    description: component.description.clone(),
+                    quantity: line.quantity.unwrap_or_default(),
+                    designators: line
+                        .designators
+                        .unwrap_or_default()
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                    substitutes: line
+                        .substitutes
+                        .unwrap_or_default()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|id| parts.get(&id).map(export_part))
+                        .collect(),
+                    is_optional: line.is_optional.unwrap_or(false),
+                    notes: line.notes,
+                })
+            })
+            .collect();
+
+        let file_name: String = parent
+            .internal_part_number
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(Some((
+            format!("{}-bom-{}.csv", file_name, format),
+            render_bom_csv(&lines, format),
+        )))
+    }
+
+    // Items the tenant holds inventory records of, once each
+    async fn load_tenant_items(conn: &mut AsyncPgConnection, tenant_id: Uuid) -> Result<Vec<Item>> {
+        let records = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .select(Item::as_select())
+            .load::<Item>(conn)
+            .await?;
+
+        let mut seen = HashSet::new();
+        Ok(records
+            .into_iter()
+            .filter(|item| seen.insert(item.id))
+            .collect())
+    }
+
+    // Creates an item for a component no item matched, held in the tenant's store
+    async fn create_component(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        row: &BomImportRow,
+    ) -> Result<Uuid> {
+        let internal_part_number = NumberingService::number_or_draw(
+            conn,
+            tenant_id,
+            NumberingEntity::Item,
+            row.internal_part_number.clone(),
+        )
+        .await
+        .map_err(ItemError::from_numbering)?;
+
+        let item_id = diesel::insert_into(items::table)
+            .values(&NewItem {
+                internal_part_number,
+                mfr_part_number: row.mfr_part_number.clone(),
+                manufacturer: row.manufacturer.clone().unwrap_or_default(),
+                datasheet: None,
+                lifecycle: None,
+                description: row.description.clone(),
+                category: None,
+                metadata: None,
+                linked_resources: None,
+            })
+            .returning(items::id)
+            .get_result::<Uuid>(conn)
+            .await?;
+
+        diesel::insert_into(inventory_items::table)
+            .values(&NewInventoryItem {
+                item_id,
+                tenant_id,
+                context: ItemContext::Store.to_string(),
+                quantity: None,
+                location: None,
+                pricing: None,
+                lead_time: None,
+                min_stock_level: None,
+                max_stock_level: None,
+                reorder_point: None,
+                vendor_id: None,
+                last_received_date: None,
+                status: None,
+                notes: None,
+                metadata: None,
+            })
+            .execute(conn)
+            .await?;
+
+        Ok(item_id)
+    }
+
+    // Adds the line, or updates the component's existing line. Quantities in a BOM file are in
+    // the component's base unit, so a unit set on the existing line is cleared.
+    async fn upsert_line(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        parent_item_id: Uuid,
+        component_item_id: Uuid,
+        row: &BomImportRow,
+        substitutes: &[Uuid],
+    ) -> Result<()> {
+        diesel::insert_into(item_bom::table)
+            .values(&NewItemBom {
+                tenant_id,
+                parent_item_id,
+                component_item_id,
+                quantity: Some(row.quantity),
+                notes: row.notes.clone(),
+                is_optional: Some(row.is_optional),
+                substitutes: Some(substitutes.iter().copied().map(Some).collect()),
+                assembly_order: None,
+                uom_id: None,
+                designators: Some(row.designators.iter().cloned().map(Some).collect()),
+            })
+            .on_conflict((
+                item_bom::tenant_id,
+                item_bom::parent_item_id,
+                item_bom::component_item_id,
+            ))
+            .do_update()
+            .set((
+                item_bom::quantity.eq(excluded(item_bom::quantity)),
+                item_bom::notes.eq(excluded(item_bom::notes)),
+                item_bom::is_optional.eq(excluded(item_bom::is_optional)),
+                item_bom::substitutes.eq(excluded(item_bom::substitutes)),
+                item_bom::uom_id.eq(excluded(item_bom::uom_id)),
+                item_bom::designators.eq(excluded(item_bom::designators)),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Tenant items indexed by normalized internal part number and MPN
+struct ItemMatcher<'a> {
+    by_number: HashMap<String, &'a Item>,
+    by_mpn: HashMap<String, Vec<&'a Item>>,
+}
+
+impl<'a> ItemMatcher<'a> {
+    fn new(items: &'a [Item]) -> Self {
+        let mut by_number = HashMap::new();
+        let mut by_mpn: HashMap<String, Vec<&Item>> = HashMap::new();
+        for item in items {
+            by_number.insert(normalize(&item.internal_part_number), item);
+            if let Some(mpn) = item.mfr_part_number.as_deref() {
+                by_mpn.entry(normalize(mpn)).or_default().push(item);
+            }
+        }
+        Self { by_number, by_mpn }
+    }
+
+    // The item a line's component is, by internal part number, else by MPN narrowed to the
+    // manufacturer when one is given. An MPN several items share is refused.
+    fn component(&self, row: &BomImportRow) -> Result<Option<Uuid>, String> {
+        if let Some(item) = row
+            .internal_part_number
+            .as_deref()
+            .and_then(|number| self.by_number.get(&normalize(number)))
+        {
+            return Ok(Some(item.id));
+        }
+        let Some(mpn) = row.mfr_part_number.as_deref() else {
+            return Ok(None);
+        };
+
+        let candidates: Vec<&&Item> = self
+            .by_mpn
+            .get(&normalize(mpn))
+            .into_iter()
+            .flatten()
+            .filter(|item| {
+                row.manufacturer
+                    .as_deref()
+                    .is_none_or(|m| normalize(m) == normalize(&item.manufacturer))
+            })
+            .collect();
+        match candidates[..] {
+            [] => Ok(None),
+            [item] => Ok(Some(item.id)),
+            _ => Err(format!(
+                "MPN {} matches {} items; add the manufacturer or internal part number",
+                mpn,
+                candidates.len()
+            )),
+        }
+    }
+
+    // The item an alternate is, by internal part number or a unique MPN
+    fn substitute(&self, part_number: &str) -> Option<Uuid> {
+        let key = normalize(part_number);
+        if let Some(item) = self.by_number.get(&key) {
+            return Some(item.id);
+        }
+        match self.by_mpn.get(&key).map(Vec::as_slice) {
+            Some([item]) => Some(item.id),
+            _ => None,
+        }
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
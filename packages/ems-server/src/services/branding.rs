@@ -0,0 +1,191 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{BrandingEmailPreview, BrandingPreviewFormat, BrandingPreviewRequest};
+use crate::services::{AssetUploadService, DatabaseService, TenantCache, TenantService};
+use crate::utils::branding::{
+    prepare_logo, preview_order, BrandingSettings, DocumentBranding, BRANDING_SETTING,
+    MAX_LOGO_BYTES, PREVIEW_CUSTOMER,
+};
+use crate::utils::i18n::Locale;
+use crate::utils::order_confirmation::{order_confirmation_email, render_order_confirmation_pdf};
+use crate::utils::pdf::PdfImage;
+use crate::utils::AppError;
+
+/// Branding a tenant asked for that cannot be used, as opposed to a failure
+#[derive(Error, Debug)]
+pub enum BrandingError {
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<BrandingError> for AppError {
+    fn from(error: BrandingError) -> Self {
+        match error {
+            BrandingError::Invalid(_) => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+/// A branding preview, in the format asked for.
+#[derive(Debug)]
+pub enum BrandingPreview {
+    Pdf(Vec<u8>),
+    Email(BrandingEmailPreview),
+}
+
+/// Branding of a tenant's customer documents: order confirmations and quotes, and the emails
+/// they are sent with. Kept under `branding` in the tenant's settings; the logo is an asset
+/// whose file is read whenever documents are rendered.
+pub struct BrandingService {
+    database: DatabaseService,
+    cache: Option<TenantCache>,
+}
+
+impl BrandingService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database,
+            cache: None,
+        }
+    }
+
+    /// Reads tenants through the cache and keeps it current when the branding changes.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    fn tenants(&self) -> TenantService {
+        let service = TenantService::new(self.database.clone());
+        match &self.cache {
+            Some(cache) => service.with_cache(cache.clone()),
+            None => service,
+        }
+    }
+
+    /// The tenant's branding settings. `Ok(None)` when the tenant does not exist.
+    pub async fn get_branding(&self, tenant_id: Uuid) -> Result<Option<BrandingSettings>> {
+        Ok(self
+            .tenants()
+            .get_tenant_by_id(tenant_id)
+            .await?
+            .map(|tenant| tenant.branding()))
+    }
+
+    /// Replaces the tenant's branding, leaving its other settings as they are. The logo asset
+    /// must hold a readable image. `Ok(None)` when the tenant does not exist.
+    pub async fn update_branding(
+        &self,
+        tenant_id: Uuid,
+        branding: BrandingSettings,
+    ) -> Result<Option<BrandingSettings>> {
+        branding.check().map_err(BrandingError::Invalid)?;
+        if let Some(asset_id) = branding.logo_asset_id {
+            self.load_logo(tenant_id, asset_id).await?;
+        }
+
+        Ok(self
+            .tenants()
+            .put_setting(
+                tenant_id,
+                BRANDING_SETTING,
+                serde_json::to_value(&branding)?,
+            )
+            .await?
+            .map(|tenant| tenant.branding()))
+    }
+
+    /// The tenant's branding as its documents are rendered with. A logo that cannot be read is
+    /// left out rather than holding up the document; unknown tenants are unbranded.
+    pub async fn document_branding(&self, tenant_id: Uuid) -> Result<DocumentBranding> {
+        let settings = self.get_branding(tenant_id).await?.unwrap_or_default();
+        let logo = match settings.logo_asset_id {
+            Some(asset_id) => match self.load_logo(tenant_id, asset_id).await {
+                Ok(logo) => Some(logo),
+                Err(e) => {
+                    tracing::warn!(
+                        "Tenant {} documents rendered without logo: {}",
+                        tenant_id,
+                        e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        Ok(DocumentBranding::new(&settings, logo))
+    }
+
+    /// Renders a sample order confirmation, or the email it is sent with, with the branding in
+    /// the request, so it can be checked before it is saved. Problems with the branding or its
+    /// logo are reported rather than left out. `default_locale` applies when the request names
+    /// no locale. `Ok(None)` when the tenant does not exist.
+    pub async fn preview(
+        &self,
+        tenant_id: Uuid,
+        request: BrandingPreviewRequest,
+        default_locale: Locale,
+    ) -> Result<Option<BrandingPreview>> {
+        let settings = match request.branding {
+            Some(settings) => settings,
+            None => match self.get_branding(tenant_id).await? {
+                Some(settings) => settings,
+                None => return Ok(None),
+            },
+        };
+        settings.check().map_err(BrandingError::Invalid)?;
+        let logo = match settings.logo_asset_id {
+            Some(asset_id) => Some(self.load_logo(tenant_id, asset_id).await?),
+            None => None,
+        };
+        let branding = DocumentBranding::new(&settings, logo);
+
+        let locale = request.locale.unwrap_or(default_locale);
+        let order = preview_order();
+        let preview = match request.format {
+            BrandingPreviewFormat::Pdf => BrandingPreview::Pdf(render_order_confirmation_pdf(
+                &order,
+                PREVIEW_CUSTOMER,
+                locale,
+                &branding,
+            )),
+            BrandingPreviewFormat::Email => {
+                let (subject, body) =
+                    order_confirmation_email(&order, PREVIEW_CUSTOMER, None, locale);
+                // Sent emails attach the logo; the preview inlines it so it shows on its own
+                let logo_src = branding
+                    .logo
+                    .as_ref()
+                    .map(|logo| format!("data:image/jpeg;base64,{}", STANDARD.encode(&logo.jpeg)));
+                BrandingPreview::Email(BrandingEmailPreview {
+                    subject,
+                    reply_to: branding.reply_to.clone(),
+                    text: branding.email_text(&body),
+                    html: branding
+                        .is_branded()
+                        .then(|| branding.email_html(&body, logo_src.as_deref())),
+                })
+            }
+        };
+        Ok(Some(preview))
+    }
+
+    // The logo asset's file prepared for documents
+    async fn load_logo(&self, tenant_id: Uuid, asset_id: Uuid) -> Result<PdfImage> {
+        let content = AssetUploadService::new(self.database.clone())?
+            .read_asset_file(tenant_id, asset_id, MAX_LOGO_BYTES)
+            .await
+            .map_err(|e| {
+                BrandingError::Invalid(format!("Invalid branding: the logo cannot be read: {}", e))
+            })?
+            .ok_or_else(|| {
+                BrandingError::Invalid(
+                    "Invalid branding: the logo asset does not exist or has no file".to_string(),
+                )
+            })?;
+        Ok(prepare_logo(&content).map_err(BrandingError::Invalid)?)
+    }
+}
@@ -0,0 +1,665 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    parse_timezone, AvailabilityWindow, CalendarException, CalendarExceptionResponse,
+    CalendarExceptionType, CreateCalendarExceptionRequest, CreateShiftPatternRequest,
+    MachineAvailabilityResponse, MachineCalendar, MachineCalendarResponse, NewCalendarException,
+    NewMachineCalendar, NewShiftPattern, SetMachineCalendarRequest, ShiftDefinition, ShiftPattern,
+    ShiftPatternResponse, UpdateShiftPatternRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::capacity::{
+    clip_intervals, daily_interval_hours, day_start, default_available_hours, interval_hours,
+    merge_intervals, shift_intervals, subtract_intervals, Interval,
+};
+use crate::utils::AppError;
+
+/// Errors from the calendar and attendance services that are the caller's to fix
+#[derive(Error, Debug)]
+pub enum CalendarError {
+    #[error("Shift pattern not found")]
+    ShiftPatternNotFound,
+
+    #[error("Machine not found")]
+    MachineNotFound,
+
+    #[error("Shift assignment overlaps {0}")]
+    ShiftOverlap(String),
+}
+
+impl From<CalendarError> for AppError {
+    fn from(error: CalendarError) -> Self {
+        match error {
+            CalendarError::ShiftPatternNotFound | CalendarError::MachineNotFound => {
+                AppError::NotFound(error.to_string())
+            }
+            CalendarError::ShiftOverlap(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+/// Working time for a machine over a planning window, resolved from its calendar.
+#[derive(Debug, Clone, Default)]
+pub struct MachineAvailability {
+    /// Shift pattern and the timezone it is evaluated in; `None` when the machine has no pattern
+    pub pattern: Option<(Vec<ShiftDefinition>, Tz)>,
+    /// Planned downtime and holidays
+    pub blocked: Vec<Interval>,
+    /// Extra shifts worked on top of the pattern
+    pub extra: Vec<Interval>,
+}
+
+impl MachineAvailability {
+    /// Available intervals within the window. Without a shift pattern the machine is treated
+    /// as available around the clock, less downtime and holidays.
+    pub fn windows(&self, window: Interval) -> Vec<Interval> {
+        let mut working = match &self.pattern {
+            Some((shifts, tz)) => shift_intervals(shifts, *tz, window),
+            None => vec![window],
+        };
+        working.extend(clip_intervals(&self.extra, window));
+        subtract_intervals(&merge_intervals(working), &self.blocked)
+    }
+
+    /// Available hours per day (UTC) for capacity planning. Machines without a shift pattern
+    /// get `hours_per_day` on working days, less downtime and holidays, plus extra shifts.
+    pub fn daily_hours(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        hours_per_day: f64,
+        include_weekends: bool,
+    ) -> BTreeMap<NaiveDate, f64> {
+        let window = (day_start(from), day_start(to + Duration::days(1)));
+        if self.pattern.is_some() {
+            return daily_interval_hours(&self.windows(window), from, to);
+        }
+
+        let blocked = daily_interval_hours(
+            &merge_intervals(clip_intervals(&self.blocked, window)),
+            from,
+            to,
+        );
+        let extra = daily_interval_hours(
+            &merge_intervals(clip_intervals(&self.extra, window)),
+            from,
+            to,
+        );
+
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| {
+                let base = default_available_hours(date, hours_per_day, include_weekends);
+                let lost = blocked.get(&date).copied().unwrap_or(0.0);
+                let added = extra.get(&date).copied().unwrap_or(0.0);
+                (date, ((base - lost).max(0.0) + added).min(24.0))
+            })
+            .collect()
+    }
+}
+
+pub struct CalendarService {
+    database: DatabaseService,
+}
+
+impl CalendarService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Shift pattern CRUD operations
+
+    pub async fn create_shift_pattern(
+        &self,
+        tenant_id: Uuid,
+        request: CreateShiftPatternRequest,
+    ) -> Result<ShiftPatternResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let new_pattern = NewShiftPattern {
+            tenant_id,
+            name: request.name,
+            description: request.description,
+            shifts: serde_json::to_value(&request.shifts)?,
+        };
+
+        let pattern: ShiftPattern = diesel::insert_into(shift_patterns::table)
+            .values(&new_pattern)
+            .returning(ShiftPattern::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(pattern.into())
+    }
+
+    pub async fn list_shift_patterns(&self, tenant_id: Uuid) -> Result<Vec<ShiftPatternResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let patterns = shift_patterns::table
+            .filter(shift_patterns::tenant_id.eq(tenant_id))
+            .order(shift_patterns::name.asc())
+            .select(ShiftPattern::as_select())
+            .load::<ShiftPattern>(&mut conn)
+            .await?;
+
+        Ok(patterns.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_shift_pattern(
+        &self,
+        tenant_id: Uuid,
+        pattern_id: Uuid,
+    ) -> Result<Option<ShiftPatternResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let pattern = shift_patterns::table
+            .filter(shift_patterns::id.eq(pattern_id))
+            .filter(shift_patterns::tenant_id.eq(tenant_id))
+            .select(ShiftPattern::as_select())
+            .first::<ShiftPattern>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(pattern.map(Into::into))
+    }
+
+    pub async fn update_shift_pattern(
+        &self,
+        tenant_id: Uuid,
+        pattern_id: Uuid,
+        request: UpdateShiftPatternRequest,
+    ) -> Result<Option<ShiftPatternResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let shifts = match &request.shifts {
+            Some(shifts) => Some(serde_json::to_value(shifts)?),
+            None => None,
+        };
+
+        let updated = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = shift_patterns::table
+                        .filter(shift_patterns::id.eq(pattern_id))
+                        .filter(shift_patterns::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(shift_patterns::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(description) = &request.description {
+                        diesel::update(target)
+                            .set(shift_patterns::description.eq(description))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(shifts) = &shifts {
+                        diesel::update(target)
+                            .set(shift_patterns::shifts.eq(shifts))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    target
+                        .select(ShiftPattern::as_select())
+                        .first::<ShiftPattern>(conn)
+                        .await
+                        .optional()
+                })
+            })
+            .await?;
+
+        Ok(updated.map(Into::into))
+    }
+
+    pub async fn delete_shift_pattern(&self, tenant_id: Uuid, pattern_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            shift_patterns::table
+                .filter(shift_patterns::id.eq(pattern_id))
+                .filter(shift_patterns::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Machine calendar operations
+
+    pub async fn get_machine_calendar(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<Option<MachineCalendarResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let calendar = machine_calendars::table
+            .filter(machine_calendars::machine_id.eq(machine_id))
+            .filter(machine_calendars::tenant_id.eq(tenant_id))
+            .select(MachineCalendar::as_select())
+            .first::<MachineCalendar>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(calendar.map(Into::into))
+    }
+
+    /// Creates or replaces the calendar of a machine. Returns `None` when the machine does not exist.
+    pub async fn set_machine_calendar(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: SetMachineCalendarRequest,
+    ) -> Result<Option<MachineCalendarResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machine_exists = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some();
+        if !machine_exists {
+            return Ok(None);
+        }
+
+        if let Some(pattern_id) = request.shift_pattern_id {
+            let pattern_exists = shift_patterns::table
+                .filter(shift_patterns::id.eq(pattern_id))
+                .filter(shift_patterns::tenant_id.eq(tenant_id))
+                .select(shift_patterns::id)
+                .first::<Uuid>(&mut conn)
+                .await
+                .optional()?
+                .is_some();
+            if !pattern_exists {
+                return Err(CalendarError::ShiftPatternNotFound.into());
+            }
+        }
+
+        let new_calendar = NewMachineCalendar {
+            tenant_id,
+            machine_id,
+            shift_pattern_id: request.shift_pattern_id,
+            timezone: request.timezone.unwrap_or_else(|| "UTC".to_string()),
+            notes: request.notes,
+        };
+
+        let calendar: MachineCalendar = diesel::insert_into(machine_calendars::table)
+            .values(&new_calendar)
+            .on_conflict(machine_calendars::machine_id)
+            .do_update()
+            .set((
+                machine_calendars::shift_pattern_id
+                    .eq(excluded(machine_calendars::shift_pattern_id)),
+                machine_calendars::timezone.eq(excluded(machine_calendars::timezone)),
+                machine_calendars::notes.eq(excluded(machine_calendars::notes)),
+            ))
+            .returning(MachineCalendar::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Some(calendar.into()))
+    }
+
+    pub async fn delete_machine_calendar(&self, tenant_id: Uuid, machine_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            machine_calendars::table
+                .filter(machine_calendars::machine_id.eq(machine_id))
+                .filter(machine_calendars::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Calendar exception operations
+
+    pub async fn create_exception(
+        &self,
+        tenant_id: Uuid,
+        request: CreateCalendarExceptionRequest,
+    ) -> Result<CalendarExceptionResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if let Some(machine_id) = request.machine_id {
+            let machine_exists = machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id))
+                .select(machines::id)
+                .first::<Uuid>(&mut conn)
+                .await
+                .optional()?
+                .is_some();
+            if !machine_exists {
+                return Err(CalendarError::MachineNotFound.into());
+            }
+        }
+
+        let new_exception = NewCalendarException {
+            tenant_id,
+            machine_id: request.machine_id,
+            exception_type: request.exception_type.to_string(),
+            starts_at: request.starts_at,
+            ends_at: request.ends_at,
+            reason: request.reason,
+        };
+
+        let exception: CalendarException = diesel::insert_into(calendar_exceptions::table)
+            .values(&new_exception)
+            .returning(CalendarException::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(exception.into())
+    }
+
+    /// Lists exceptions overlapping the optional period. Filtering by machine also
+    /// returns the tenant-wide exceptions that apply to it.
+    pub async fn list_exceptions(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Option<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<CalendarExceptionResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = calendar_exceptions::table
+            .filter(calendar_exceptions::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(machine_id) = machine_id {
+            query = query.filter(
+                calendar_exceptions::machine_id
+                    .eq(machine_id)
+                    .or(calendar_exceptions::machine_id.is_null()),
+            );
+        }
+        if let Some(from) = from {
+            query = query.filter(calendar_exceptions::ends_at.gt(from));
+        }
+        if let Some(to) = to {
+            query = query.filter(calendar_exceptions::starts_at.lt(to));
+        }
+
+        let exceptions = query
+            .order(calendar_exceptions::starts_at.asc())
+            .select(CalendarException::as_select())
+            .load::<CalendarException>(&mut conn)
+            .await?;
+
+        Ok(exceptions.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn delete_exception(&self, tenant_id: Uuid, exception_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            calendar_exceptions::table
+                .filter(calendar_exceptions::id.eq(exception_id))
+                .filter(calendar_exceptions::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Availability
+
+    /// Resolves calendars, shift patterns and exceptions overlapping the window for the
+    /// given machines. Every requested machine gets an entry.
+    pub async fn availability_for_machines(
+        &self,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+        window: Interval,
+    ) -> Result<HashMap<Uuid, MachineAvailability>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let calendars = machine_calendars::table
+            .filter(machine_calendars::tenant_id.eq(tenant_id))
+            .filter(machine_calendars::machine_id.eq_any(machine_ids))
+            .select(MachineCalendar::as_select())
+            .load::<MachineCalendar>(&mut conn)
+            .await?;
+
+        let pattern_ids: Vec<Uuid> = calendars
+            .iter()
+            .filter_map(|calendar| calendar.shift_pattern_id)
+            .collect();
+        let patterns: HashMap<Uuid, Vec<ShiftDefinition>> = shift_patterns::table
+            .filter(shift_patterns::tenant_id.eq(tenant_id))
+            .filter(shift_patterns::id.eq_any(&pattern_ids))
+            .select(ShiftPattern::as_select())
+            .load::<ShiftPattern>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|pattern| (pattern.id, pattern.shift_definitions()))
+            .collect();
+
+        let exceptions = calendar_exceptions::table
+            .filter(calendar_exceptions::tenant_id.eq(tenant_id))
+            .filter(
+                calendar_exceptions::machine_id
+                    .eq_any(machine_ids)
+                    .or(calendar_exceptions::machine_id.is_null()),
+            )
+            .filter(calendar_exceptions::starts_at.lt(window.1))
+            .filter(calendar_exceptions::ends_at.gt(window.0))
+            .select(CalendarException::as_select())
+            .load::<CalendarException>(&mut conn)
+            .await?;
+
+        let mut availability: HashMap<Uuid, MachineAvailability> = machine_ids
+            .iter()
+            .map(|id| (*id, MachineAvailability::default()))
+            .collect();
+
+        for calendar in calendars {
+            let pattern = calendar
+                .shift_pattern_id
+                .and_then(|id| patterns.get(&id).cloned());
+            let tz = parse_timezone(&calendar.timezone).unwrap_or(Tz::UTC);
+            if let Some(entry) = availability.get_mut(&calendar.machine_id) {
+                entry.pattern = pattern.map(|shifts| (shifts, tz));
+            }
+        }
+
+        for exception in exceptions {
+            let blocking = CalendarExceptionType::try_from(exception.exception_type)
+                .map(|t| t.is_blocking())
+                .unwrap_or(true);
+            let interval = (exception.starts_at, exception.ends_at);
+
+            let targets: Vec<Uuid> = match exception.machine_id {
+                Some(machine_id) => vec![machine_id],
+                None => machine_ids.to_vec(),
+            };
+            for machine_id in targets {
+                if let Some(entry) = availability.get_mut(&machine_id) {
+                    if blocking {
+                        entry.blocked.push(interval);
+                    } else {
+                        entry.extra.push(interval);
+                    }
+                }
+            }
+        }
+
+        Ok(availability)
+    }
+
+    /// Working windows of a machine over the inclusive `from..=to` date range (UTC days).
+    /// Returns `None` when the machine does not exist.
+    pub async fn machine_availability(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Option<MachineAvailabilityResponse>> {
+        let calendar = {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let machine_exists = machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id))
+                .select(machines::id)
+                .first::<Uuid>(&mut conn)
+                .await
+                .optional()?
+                .is_some();
+            if !machine_exists {
+                return Ok(None);
+            }
+
+            machine_calendars::table
+                .filter(machine_calendars::machine_id.eq(machine_id))
+                .filter(machine_calendars::tenant_id.eq(tenant_id))
+                .select(MachineCalendar::as_select())
+                .first::<MachineCalendar>(&mut conn)
+                .await
+                .optional()?
+        };
+
+        let window = (day_start(from), day_start(to + Duration::days(1)));
+        let availability = self
+            .availability_for_machines(tenant_id, &[machine_id], window)
+            .await?
+            .remove(&machine_id)
+            .unwrap_or_default();
+
+        let windows: Vec<AvailabilityWindow> = availability
+            .windows(window)
+            .into_iter()
+            .map(|interval| AvailabilityWindow {
+                start: interval.0,
+                end: interval.1,
+                hours: interval_hours(&interval),
+            })
+            .collect();
+
+        Ok(Some(MachineAvailabilityResponse {
+            machine_id,
+            from,
+            to,
+            has_calendar: availability.pattern.is_some(),
+            timezone: calendar
+                .map(|calendar| calendar.timezone)
+                .unwrap_or_else(|| "UTC".to_string()),
+            available_hours: windows.iter().map(|w| w.hours).sum(),
+            windows,
+        }))
+    }
+
+    /// First planned downtime or holiday that overlaps the period on the given machine, if any.
+    /// Used to keep job assignments out of time the machine is not available.
+    pub async fn find_blocking_exception(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Option<CalendarException>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let exception = calendar_exceptions::table
+            .filter(calendar_exceptions::tenant_id.eq(tenant_id))
+            .filter(
+                calendar_exceptions::machine_id
+                    .eq(machine_id)
+                    .or(calendar_exceptions::machine_id.is_null()),
+            )
+            .filter(
+                calendar_exceptions::exception_type
+                    .ne(CalendarExceptionType::ExtraShift.to_string()),
+            )
+            .filter(calendar_exceptions::starts_at.lt(end))
+            .filter(calendar_exceptions::ends_at.gt(start))
+            .order(calendar_exceptions::starts_at.asc())
+            .select(CalendarException::as_select())
+            .first::<CalendarException>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(exception)
+    }
+}
@@ -0,0 +1,635 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::BigInt;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    AttachCalibrationCertificateRequest, CalibrationRecord, CalibrationRecordResponse,
+    CalibrationResult, CalibrationStatus, CreateInstrumentRequest, EquipmentCalibrationResponse,
+    Instrument, InstrumentResponse, ListCalibrationsQuery, ListEquipmentCalibrationQuery,
+    ListInstrumentsQuery, NewCalibrationRecord, NewInstrument, RecordCalibrationRequest,
+    UpdateInstrumentRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, QualityError};
+use crate::utils::calibration::{calibration_status, due_date};
+
+pub struct CalibrationService {
+    database: DatabaseService,
+}
+
+impl CalibrationService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Instrument methods
+
+    pub async fn create_instrument(
+        &self,
+        tenant_id: Uuid,
+        request: CreateInstrumentRequest,
+    ) -> Result<InstrumentResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let instrument: Instrument = diesel::insert_into(instruments::table)
+            .values(&NewInstrument {
+                tenant_id,
+                name: request.name,
+                serial_number: request.serial_number,
+                instrument_type: request.instrument_type,
+                location: request.location,
+                calibration_interval_days: request.calibration_interval_days,
+            })
+            .returning(Instrument::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Self::instrument_response(instrument, None))
+    }
+
+    pub async fn list_instruments(
+        &self,
+        tenant_id: Uuid,
+        query: ListInstrumentsQuery,
+    ) -> Result<Vec<InstrumentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut statement = instruments::table
+            .filter(instruments::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(instrument_type) = &query.instrument_type {
+            statement = statement.filter(instruments::instrument_type.eq(instrument_type));
+        }
+        if let Some(is_active) = query.is_active {
+            statement = statement.filter(instruments::is_active.eq(is_active));
+        }
+
+        let instruments = statement
+            .order(instruments::name.asc())
+            .select(Instrument::as_select())
+            .load::<Instrument>(&mut conn)
+            .await?;
+
+        let instrument_ids: Vec<Uuid> = instruments.iter().map(|i| i.id).collect();
+        let mut latest = Self::latest_instrument_calibrations(&mut conn, &instrument_ids).await?;
+
+        Ok(instruments
+            .into_iter()
+            .map(|instrument| {
+                let record = latest.remove(&instrument.id);
+                Self::instrument_response(instrument, record)
+            })
+            .filter(|response| {
+                query
+                    .status
+                    .is_none_or(|status| response.calibration_status == status)
+            })
+            .collect())
+    }
+
+    pub async fn get_instrument(
+        &self,
+        tenant_id: Uuid,
+        instrument_id: Uuid,
+    ) -> Result<Option<InstrumentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(instrument) = Self::find_instrument(&mut conn, tenant_id, instrument_id).await?
+        else {
+            return Ok(None);
+        };
+        let latest = Self::latest_calibration(&mut conn, None, Some(instrument_id)).await?;
+
+        Ok(Some(Self::instrument_response(instrument, latest)))
+    }
+
+    pub async fn update_instrument(
+        &self,
+        tenant_id: Uuid,
+        instrument_id: Uuid,
+        request: UpdateInstrumentRequest,
+    ) -> Result<Option<InstrumentResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let target = instruments::table
+                        .filter(instruments::id.eq(instrument_id))
+                        .filter(instruments::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(instruments::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(serial_number) = &request.serial_number {
+                        diesel::update(target)
+                            .set(instruments::serial_number.eq(serial_number))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(instrument_type) = &request.instrument_type {
+                        diesel::update(target)
+                            .set(instruments::instrument_type.eq(instrument_type))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(location) = &request.location {
+                        diesel::update(target)
+                            .set(instruments::location.eq(location))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(calibration_interval_days) = request.calibration_interval_days {
+                        diesel::update(target)
+                            .set(
+                                instruments::calibration_interval_days
+                                    .eq(calibration_interval_days),
+                            )
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(instruments::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let Some(instrument) =
+                        Self::find_instrument(conn, tenant_id, instrument_id).await?
+                    else {
+                        return Ok(None);
+                    };
+                    let latest = Self::latest_calibration(conn, None, Some(instrument_id)).await?;
+
+                    Ok(Some(Self::instrument_response(instrument, latest)))
+                })
+            })
+            .await
+    }
+
+    pub async fn delete_instrument(&self, tenant_id: Uuid, instrument_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            instruments::table
+                .filter(instruments::id.eq(instrument_id))
+                .filter(instruments::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Calibration record methods
+
+    /// Records a calibration of a machine or instrument. The due date is taken from the
+    /// request, or from the calibration interval of the request or the instrument.
+    pub async fn record_calibration(
+        &self,
+        tenant_id: Uuid,
+        recorded_by_id: Option<Uuid>,
+        request: RecordCalibrationRequest,
+    ) -> Result<CalibrationRecordResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut interval_days = request.interval_days;
+        if let Some(machine_id) = request.machine_id {
+            let found: i64 = machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Machine", machine_id).into());
+            }
+        }
+        if let Some(instrument_id) = request.instrument_id {
+            let instrument = Self::find_instrument(&mut conn, tenant_id, instrument_id)
+                .await?
+                .ok_or(QualityError::ReferenceNotFound("Instrument", instrument_id))?;
+            interval_days = interval_days.or(instrument.calibration_interval_days);
+        }
+        if let Some(asset_id) = request.certificate_asset_id {
+            Self::ensure_asset(&mut conn, tenant_id, asset_id).await?;
+        }
+
+        let calibrated_at = request.calibrated_at.unwrap_or_else(Utc::now);
+        let due_at = match (request.due_at, interval_days) {
+            (Some(due_at), _) => due_at,
+            (None, Some(interval_days)) => due_date(calibrated_at, interval_days),
+            (None, None) => return Err(QualityError::DueDateRequired.into()),
+        };
+        if due_at <= calibrated_at {
+            return Err(QualityError::DueDateBeforeCalibration.into());
+        }
+
+        let record: CalibrationRecord = diesel::insert_into(calibration_records::table)
+            .values(&NewCalibrationRecord {
+                tenant_id,
+                machine_id: request.machine_id,
+                instrument_id: request.instrument_id,
+                calibrated_at,
+                due_at,
+                result: request.result.to_string(),
+                performed_by: request.performed_by,
+                certificate_number: request.certificate_number,
+                certificate_asset_id: request.certificate_asset_id,
+                notes: request.notes,
+                recorded_by_id,
+            })
+            .returning(CalibrationRecord::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(record.into())
+    }
+
+    /// Calibrations, latest first.
+    pub async fn list_calibrations(
+        &self,
+        tenant_id: Uuid,
+        query: ListCalibrationsQuery,
+    ) -> Result<Vec<CalibrationRecordResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut statement = calibration_records::table
+            .filter(calibration_records::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(machine_id) = query.machine_id {
+            statement = statement.filter(calibration_records::machine_id.eq(machine_id));
+        }
+        if let Some(instrument_id) = query.instrument_id {
+            statement = statement.filter(calibration_records::instrument_id.eq(instrument_id));
+        }
+        if let Some(result) = query.result {
+            statement = statement.filter(calibration_records::result.eq(result.to_string()));
+        }
+
+        let records = statement
+            .order((
+                calibration_records::calibrated_at.desc(),
+                calibration_records::created_at.desc(),
+            ))
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(CalibrationRecord::as_select())
+            .load::<CalibrationRecord>(&mut conn)
+            .await?;
+
+        Ok(records.into_iter().map(Into::into).collect())
+    }
+
+    /// Links the calibration certificate stored as an asset to a calibration. Returns `None`
+    /// when the calibration does not exist.
+    pub async fn attach_certificate(
+        &self,
+        tenant_id: Uuid,
+        calibration_id: Uuid,
+        request: AttachCalibrationCertificateRequest,
+    ) -> Result<Option<CalibrationRecordResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::ensure_asset(conn, tenant_id, request.certificate_asset_id).await?;
+
+                    let target = calibration_records::table
+                        .filter(calibration_records::id.eq(calibration_id))
+                        .filter(calibration_records::tenant_id.eq(tenant_id));
+
+                    if let Some(certificate_number) = &request.certificate_number {
+                        diesel::update(target)
+                            .set(calibration_records::certificate_number.eq(certificate_number))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let record = diesel::update(target)
+                        .set(
+                            calibration_records::certificate_asset_id
+                                .eq(request.certificate_asset_id),
+                        )
+                        .returning(CalibrationRecord::as_returning())
+                        .get_result::<CalibrationRecord>(conn)
+                        .await
+                        .optional()?;
+
+                    Ok(record.map(Into::into))
+                })
+            })
+            .await
+    }
+
+    /// Calibration status of every machine with calibration records and every active
+    /// instrument, ordered by name.
+    pub async fn list_equipment(
+        &self,
+        tenant_id: Uuid,
+        query: ListEquipmentCalibrationQuery,
+    ) -> Result<Vec<EquipmentCalibrationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machine_records = calibration_records::table
+            .filter(calibration_records::tenant_id.eq(tenant_id))
+            .filter(calibration_records::machine_id.is_not_null())
+            .distinct_on(calibration_records::machine_id)
+            .order((
+                calibration_records::machine_id,
+                calibration_records::calibrated_at.desc(),
+                calibration_records::created_at.desc(),
+            ))
+            .select(CalibrationRecord::as_select())
+            .load::<CalibrationRecord>(&mut conn)
+            .await?;
+
+        let machine_ids: Vec<Uuid> = machine_records
+            .iter()
+            .filter_map(|record| record.machine_id)
+            .collect();
+        let machine_names: HashMap<Uuid, String> = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(&machine_ids))
+            .select((machines::id, machines::name))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let instruments = instruments::table
+            .filter(instruments::tenant_id.eq(tenant_id))
+            .filter(instruments::is_active.eq(true))
+            .select(Instrument::as_select())
+            .load::<Instrument>(&mut conn)
+            .await?;
+        let instrument_ids: Vec<Uuid> = instruments.iter().map(|i| i.id).collect();
+        let mut instrument_records =
+            Self::latest_instrument_calibrations(&mut conn, &instrument_ids).await?;
+
+        let now = Utc::now();
+        let machines = machine_records.into_iter().filter_map(|record| {
+            let machine_id = record.machine_id?;
+            Some(EquipmentCalibrationResponse {
+                machine_id: Some(machine_id),
+                instrument_id: None,
+                name: machine_names.get(&machine_id)?.clone(),
+                calibration_status: Self::status_of(Some(&record), now),
+                latest_calibration: Some(record.into()),
+            })
+        });
+        let instruments = instruments.into_iter().map(|instrument| {
+            let record = instrument_records.remove(&instrument.id);
+            EquipmentCalibrationResponse {
+                machine_id: None,
+                instrument_id: Some(instrument.id),
+                name: instrument.name,
+                calibration_status: Self::status_of(record.as_ref(), now),
+                latest_calibration: record.map(Into::into),
+            }
+        });
+
+        let mut equipment: Vec<EquipmentCalibrationResponse> = machines
+            .chain(instruments)
+            .filter(|equipment| {
+                query
+                    .status
+                    .is_none_or(|status| equipment.calibration_status == status)
+            })
+            .collect();
+        equipment.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(equipment)
+    }
+
+    /// Flags latest calibrations that are past due and not yet flagged, across tenants, and
+    /// returns them. Records are locked while flagged so concurrent checks skip them.
+    pub async fn flag_overdue(&self, limit: i64) -> Result<Vec<CalibrationRecord>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // The calibration check works across tenants, so clear any tenant context left on the
+        // connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let records = diesel::sql_query(
+            r#"
+            UPDATE calibration_records
+            SET overdue_flagged_at = NOW()
+            WHERE id IN (
+                SELECT record.id
+                FROM calibration_records record
+                WHERE record.overdue_flagged_at IS NULL
+                  AND record.due_at <= NOW()
+                  AND NOT EXISTS (
+                      SELECT 1
+                      FROM calibration_records later
+                      WHERE later.tenant_id = record.tenant_id
+                        AND (later.machine_id = record.machine_id
+                             OR later.instrument_id = record.instrument_id)
+                        AND (later.calibrated_at, later.created_at)
+                            > (record.calibrated_at, record.created_at)
+                  )
+                ORDER BY record.due_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind::<BigInt, _>(limit)
+        .load::<CalibrationRecord>(&mut conn)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Refuses measurements taken with a machine or instrument whose latest calibration failed
+    /// or is past due. Equipment never calibrated is not under calibration control and passes.
+    pub async fn ensure_in_calibration(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Option<Uuid>,
+        instrument_id: Option<Uuid>,
+    ) -> Result<()> {
+        if let Some(machine_id) = machine_id {
+            if !Self::machine_in_calibration(conn, machine_id).await? {
+                return Err(QualityError::MachineOutOfCalibration(machine_id).into());
+            }
+        }
+
+        if let Some(instrument_id) = instrument_id {
+            let instrument = Self::find_instrument(conn, tenant_id, instrument_id)
+                .await?
+                .ok_or(QualityError::ReferenceNotFound("Instrument", instrument_id))?;
+            if !instrument.is_active {
+                return Err(QualityError::InstrumentInactive(instrument_id).into());
+            }
+            let latest = Self::latest_calibration(conn, None, Some(instrument_id)).await?;
+            if Self::status_of(latest.as_ref(), Utc::now()).is_out_of_calibration() {
+                return Err(QualityError::InstrumentOutOfCalibration(instrument_id).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the machine's latest calibration, if any, passed and is not past due.
+    pub async fn machine_in_calibration(
+        conn: &mut AsyncPgConnection,
+        machine_id: Uuid,
+    ) -> Result<bool> {
+        let latest = Self::latest_calibration(conn, Some(machine_id), None).await?;
+        Ok(!Self::status_of(latest.as_ref(), Utc::now()).is_out_of_calibration())
+    }
+
+    // Helper methods
+
+    async fn find_instrument(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        instrument_id: Uuid,
+    ) -> Result<Option<Instrument>> {
+        Ok(instruments::table
+            .filter(instruments::id.eq(instrument_id))
+            .filter(instruments::tenant_id.eq(tenant_id))
+            .select(Instrument::as_select())
+            .first::<Instrument>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn ensure_asset(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        asset_id: Uuid,
+    ) -> Result<()> {
+        let found: i64 = assets::table
+            .filter(assets::id.eq(asset_id))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .count()
+            .get_result(conn)
+            .await?;
+        if found == 0 {
+            return Err(QualityError::ReferenceNotFound("Asset", asset_id).into());
+        }
+        Ok(())
+    }
+
+    /// Latest calibration of the machine or the instrument.
+    async fn latest_calibration(
+        conn: &mut AsyncPgConnection,
+        machine_id: Option<Uuid>,
+        instrument_id: Option<Uuid>,
+    ) -> Result<Option<CalibrationRecord>> {
+        let mut statement = calibration_records::table.into_boxed();
+        if let Some(machine_id) = machine_id {
+            statement = statement.filter(calibration_records::machine_id.eq(machine_id));
+        }
+        if let Some(instrument_id) = instrument_id {
+            statement = statement.filter(calibration_records::instrument_id.eq(instrument_id));
+        }
+
+        Ok(statement
+            .order((
+                calibration_records::calibrated_at.desc(),
+                calibration_records::created_at.desc(),
+            ))
+            .select(CalibrationRecord::as_select())
+            .first::<CalibrationRecord>(conn)
+            .await
+            .optional()?)
+    }
+
+    /// Latest calibration of each of the instruments, keyed by instrument.
+    async fn latest_instrument_calibrations(
+        conn: &mut AsyncPgConnection,
+        instrument_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, CalibrationRecord>> {
+        let records = calibration_records::table
+            .filter(calibration_records::instrument_id.eq_any(instrument_ids))
+            .distinct_on(calibration_records::instrument_id)
+            .order((
+                calibration_records::instrument_id,
+                calibration_records::calibrated_at.desc(),
+                calibration_records::created_at.desc(),
+            ))
+            .select(CalibrationRecord::as_select())
+            .load::<CalibrationRecord>(conn)
+            .await?;
+
+        Ok(records
+            .into_iter()
+            .filter_map(|record| Some((record.instrument_id?, record)))
+            .collect())
+    }
+
+    fn status_of(latest: Option<&CalibrationRecord>, now: DateTime<Utc>) -> CalibrationStatus {
+        calibration_status(
+            latest.map(|record| {
+                (
+                    CalibrationResult::try_from(record.result.clone())
+                        .unwrap_or(CalibrationResult::Fail),
+                    record.due_at,
+                )
+            }),
+            now,
+        )
+    }
+
+    fn instrument_response(
+        instrument: Instrument,
+        latest: Option<CalibrationRecord>,
+    ) -> InstrumentResponse {
+        InstrumentResponse {
+            calibration_status: Self::status_of(latest.as_ref(), Utc::now()),
+            latest_calibration: latest.map(Into::into),
+            id: instrument.id,
+            name: instrument.name,
+            serial_number: instrument.serial_number,
+            instrument_type: instrument.instrument_type,
+            location: instrument.location,
+            calibration_interval_days: instrument.calibration_interval_days,
+            is_active: instrument.is_active,
+            created_at: instrument.created_at.unwrap_or_else(Utc::now),
+            updated_at: instrument.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
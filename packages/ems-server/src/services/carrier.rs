@@ -0,0 +1,148 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::{env, sync::Arc, time::Duration};
+
+use crate::models::{CarrierLabel, CarrierLabelRequest, TrackingUpdate};
+use crate::services::ShippingError;
+
+// Carrier API calls must finish within this time
+const CARRIER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A shipping carrier that issues labels and tracking numbers and reports tracking.
+#[async_trait]
+pub trait CarrierProvider: Send + Sync {
+    /// Name stored on shipments handled by this carrier.
+    fn name(&self) -> &str;
+
+    async fn create_label(&self, request: &CarrierLabelRequest) -> Result<CarrierLabel>;
+
+    async fn track(&self, tracking_number: &str) -> Result<Vec<TrackingUpdate>>;
+}
+
+#[derive(Deserialize)]
+struct TrackingResponse {
+    events: Vec<TrackingUpdate>,
+}
+
+/// Generic carrier reached through an HTTP bridge, for carriers without a dedicated provider.
+///
+/// Labels are requested with `POST {base_url}/labels` (a [`CarrierLabelRequest`] answered by a
+/// [`CarrierLabel`]) and tracking is read from `GET {base_url}/tracking/{tracking_number}`
+/// (`{"events": [...]}`). The bridge can also push tracking to the shipment tracking endpoint.
+pub struct WebhookCarrier {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl WebhookCarrier {
+    pub const NAME: &'static str = "webhook";
+
+    pub fn new(base_url: &str, token: Option<String>) -> Result<Self> {
+        let client = Client::builder().timeout(CARRIER_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// Configures the carrier from SHIPPING_WEBHOOK_URL and SHIPPING_WEBHOOK_TOKEN.
+    /// Returns `Ok(None)` when SHIPPING_WEBHOOK_URL is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        match env::var("SHIPPING_WEBHOOK_URL") {
+            Ok(url) if !url.is_empty() => Ok(Some(Self::new(
+                &url,
+                env::var("SHIPPING_WEBHOOK_TOKEN").ok(),
+            )?)),
+            _ => Ok(None),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl CarrierProvider for WebhookCarrier {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn create_label(&self, request: &CarrierLabelRequest) -> Result<CarrierLabel> {
+        let response = self
+            .authorize(self.client.post(format!("{}/labels", self.base_url)))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| ShippingError::CarrierFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ShippingError::CarrierFailed(format!(
+                "label request returned {}",
+                response.status()
+            ))
+            .into());
+        }
+
+        response.json::<CarrierLabel>().await.map_err(|e| {
+            ShippingError::CarrierFailed(format!("invalid label response: {}", e)).into()
+        })
+    }
+
+    async fn track(&self, tracking_number: &str) -> Result<Vec<TrackingUpdate>> {
+        let response = self
+            .authorize(
+                self.client
+                    .get(format!("{}/tracking/{}", self.base_url, tracking_number)),
+            )
+            .send()
+            .await
+            .map_err(|e| ShippingError::CarrierFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ShippingError::CarrierFailed(format!(
+                "tracking request returned {}",
+                response.status()
+            ))
+            .into());
+        }
+
+        let tracking = response.json::<TrackingResponse>().await.map_err(|e| {
+            ShippingError::CarrierFailed(format!("invalid tracking response: {}", e))
+        })?;
+
+        Ok(tracking.events)
+    }
+}
+
+/// Carrier providers available to shipments, looked up by name.
+#[derive(Clone, Default)]
+pub struct CarrierRegistry {
+    providers: Vec<Arc<dyn CarrierProvider>>,
+}
+
+impl CarrierRegistry {
+    pub fn new(providers: Vec<Arc<dyn CarrierProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Registers every carrier configured through environment variables.
+    pub fn from_env() -> Result<Self> {
+        let mut providers: Vec<Arc<dyn CarrierProvider>> = Vec::new();
+        if let Some(webhook) = WebhookCarrier::from_env()? {
+            providers.push(Arc::new(webhook));
+        }
+        Ok(Self::new(providers))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn CarrierProvider>> {
+        self.providers.iter().find(|p| p.name() == name).cloned()
+    }
+}
@@ -0,0 +1,419 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    ConsignmentBilling, ConsignmentBillingResponse, ConsignmentBillingStatus,
+    ConsignmentInvoiceResponse, ConsignmentOwnerType, ConsignmentStock, ConsignmentStockResponse,
+    InventoryItem, InvoiceConsignmentBillingsRequest, ListConsignmentBillingsQuery,
+    ListConsignmentStockQuery, NewConsignmentBilling, NewConsignmentStock, Quantity, StockMovement,
+    StockMovementType, UpdateConsignmentStockRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, ItemError};
+use crate::utils::consignment::{billing_amount, draw_consignment, invoice_totals, unit_cost};
+use crate::utils::AppError;
+
+/// Errors from [`ConsignmentService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum ConsignmentError {
+    #[error("Billing cannot be invoiced: {0}")]
+    NotInvoiceable(String),
+}
+
+impl From<ConsignmentError> for AppError {
+    fn from(error: ConsignmentError) -> Self {
+        match error {
+            ConsignmentError::NotInvoiceable(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+pub struct ConsignmentService {
+    database: DatabaseService,
+}
+
+impl ConsignmentService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Consignment stock ledger
+
+    /// Moves partners' consignment stock along with a movement just applied to `inventory`,
+    /// inside the caller's transaction. A movement with an owner moves that partner's stock,
+    /// opening a balance on the first receipt. Without one only our own stock moves, except
+    /// that issues take consignment stock once our own runs out. Consignment stock issued is
+    /// billed to its owner.
+    pub async fn apply_movement(
+        conn: &mut AsyncPgConnection,
+        inventory: &InventoryItem,
+        movement: &StockMovement,
+        on_hand: Quantity,
+    ) -> Result<(), ItemError> {
+        let delta = movement.quantity;
+        if movement.owner_id.is_none() && delta > Quantity::ZERO {
+            return Ok(());
+        }
+        let is_issue = movement.movement_type == StockMovementType::Issue.to_string();
+
+        // Lock the balances so concurrent movements serialize; oldest stock is drawn first
+        let balances = consignment_stock::table
+            .filter(consignment_stock::inventory_item_id.eq(inventory.id))
+            .order(consignment_stock::created_at.asc())
+            .for_update()
+            .select(ConsignmentStock::as_select())
+            .load::<ConsignmentStock>(conn)
+            .await?;
+
+        let Some(owner_id) = movement.owner_id else {
+            let consigned: Quantity = balances.iter().map(|b| b.quantity).sum();
+            let owned = (on_hand - consigned).max(Quantity::ZERO);
+            if -delta <= owned {
+                return Ok(());
+            }
+            if !is_issue {
+                return Err(ItemError::InsufficientStock(format!(
+                    "{} of {} on hand is consignment stock, movement of {}",
+                    consigned, on_hand, delta
+                )));
+            }
+            for (balance, quantity) in draw_consignment(owned, &balances, -delta) {
+                Self::move_balance(conn, balance, -quantity).await?;
+                Self::bill(conn, inventory, movement, balance, quantity).await?;
+            }
+            return Ok(());
+        };
+
+        let balance = match balances.into_iter().find(|b| b.owner_id == owner_id) {
+            Some(balance) => balance,
+            None if delta > Quantity::ZERO => Self::open_balance(conn, inventory, owner_id).await?,
+            None => {
+                return Err(ItemError::InsufficientStock(format!(
+                    "no consignment stock of {}",
+                    owner_id
+                )))
+            }
+        };
+        if balance.quantity + delta < Quantity::ZERO {
+            return Err(ItemError::InsufficientStock(format!(
+                "{} of consignment stock of {}, movement of {}",
+                balance.quantity, owner_id, delta
+            )));
+        }
+
+        Self::move_balance(conn, &balance, delta).await?;
+        if movement.movement_type == StockMovementType::Receipt.to_string() {
+            diesel::update(consignment_stock::table.find(balance.id))
+                .set(consignment_stock::last_received_date.eq(Some(Utc::now())))
+                .execute(conn)
+                .await?;
+        }
+        if is_issue {
+            Self::bill(conn, inventory, movement, &balance, -delta).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_stock(
+        &self,
+        tenant_id: Uuid,
+        query: ListConsignmentStockQuery,
+    ) -> Result<Vec<ConsignmentStockResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut stock_query = consignment_stock::table
+            .inner_join(person::table)
+            .filter(consignment_stock::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(owner_id) = query.owner_id {
+            stock_query = stock_query.filter(consignment_stock::owner_id.eq(owner_id));
+        }
+        if let Some(owner_type) = query.owner_type {
+            stock_query =
+                stock_query.filter(consignment_stock::owner_type.eq(owner_type.to_string()));
+        }
+        if let Some(item_id) = query.item_id {
+            stock_query = stock_query.filter(consignment_stock::item_id.eq(item_id));
+        }
+        if !query.include_empty {
+            stock_query = stock_query.filter(consignment_stock::quantity.gt(Quantity::ZERO));
+        }
+
+        let stock = stock_query
+            .order((person::name.asc(), consignment_stock::created_at.asc()))
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select((ConsignmentStock::as_select(), person::name))
+            .load::<(ConsignmentStock, String)>(&mut conn)
+            .await?;
+
+        Ok(stock
+            .into_iter()
+            .map(|(stock, owner_name)| ConsignmentStockResponse::new(stock, owner_name))
+            .collect())
+    }
+
+    /// Sets the agreed price and notes of a consignment balance. Returns `None` when there is no
+    /// such balance.
+    pub async fn update_stock(
+        &self,
+        tenant_id: Uuid,
+        id: Uuid,
+        request: UpdateConsignmentStockRequest,
+    ) -> Result<Option<ConsignmentStockResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = diesel::update(
+            consignment_stock::table
+                .filter(consignment_stock::id.eq(id))
+                .filter(consignment_stock::tenant_id.eq(tenant_id)),
+        )
+        .set((
+            consignment_stock::unit_price.eq(request.unit_price),
+            consignment_stock::currency.eq(request.currency.map(|c| c.to_uppercase())),
+            consignment_stock::notes.eq(request.notes),
+        ))
+        .returning(ConsignmentStock::as_returning())
+        .get_result::<ConsignmentStock>(&mut conn)
+        .await
+        .optional()?;
+
+        let Some(stock) = updated else {
+            return Ok(None);
+        };
+        let owner_name = person::table
+            .find(stock.owner_id)
+            .select(person::name)
+            .first::<String>(&mut conn)
+            .await?;
+
+        Ok(Some(ConsignmentStockResponse::new(stock, owner_name)))
+    }
+
+    // Self-billing
+
+    pub async fn list_billings(
+        &self,
+        tenant_id: Uuid,
+        query: ListConsignmentBillingsQuery,
+    ) -> Result<Vec<ConsignmentBillingResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut billing_query = consignment_billings::table
+            .filter(consignment_billings::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(owner_id) = query.owner_id {
+            billing_query = billing_query.filter(consignment_billings::owner_id.eq(owner_id));
+        }
+        if let Some(item_id) = query.item_id {
+            billing_query = billing_query.filter(consignment_billings::item_id.eq(item_id));
+        }
+        if let Some(status) = query.status {
+            billing_query =
+                billing_query.filter(consignment_billings::status.eq(status.to_string()));
+        }
+        if let Some(from) = query.from {
+            billing_query = billing_query.filter(consignment_billings::created_at.ge(from));
+        }
+        if let Some(to) = query.to {
+            billing_query = billing_query.filter(consignment_billings::created_at.lt(to));
+        }
+
+        let billings = billing_query
+            .order(consignment_billings::created_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(ConsignmentBilling::as_select())
+            .load::<ConsignmentBilling>(&mut conn)
+            .await?;
+
+        Ok(billings.into_iter().map(Into::into).collect())
+    }
+
+    /// Marks a partner's pending billings as settled on a self-billing invoice. Every listed
+    /// billing must be a pending one of the owner.
+    pub async fn invoice_billings(
+        &self,
+        tenant_id: Uuid,
+        request: InvoiceConsignmentBillingsRequest,
+    ) -> Result<ConsignmentInvoiceResponse> {
+        let owner_id = request.owner_id;
+        let invoice_reference = request.invoice_reference.clone();
+        let billings = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let pending = consignment_billings::table
+                        .filter(consignment_billings::tenant_id.eq(tenant_id))
+                        .filter(consignment_billings::owner_id.eq(owner_id))
+                        .filter(
+                            consignment_billings::status
+                                .eq(ConsignmentBillingStatus::Pending.to_string()),
+                        )
+                        .for_update()
+                        .select(consignment_billings::id)
+                        .load::<Uuid>(conn)
+                        .await?;
+
+                    let ids = match &request.billing_ids {
+                        Some(billing_ids) => {
+                            if let Some(missing) =
+                                billing_ids.iter().find(|id| !pending.contains(id))
+                            {
+                                return Err(ConsignmentError::NotInvoiceable(format!(
+                                    "{} is not pending for the owner",
+                                    missing
+                                ))
+                                .into());
+                            }
+                            billing_ids.clone()
+                        }
+                        None => pending,
+                    };
+                    if ids.is_empty() {
+                        return Err(ConsignmentError::NotInvoiceable(
+                            "nothing is pending".to_string(),
+                        )
+                        .into());
+                    }
+
+                    let billings = diesel::update(
+                        consignment_billings::table.filter(consignment_billings::id.eq_any(&ids)),
+                    )
+                    .set((
+                        consignment_billings::status
+                            .eq(ConsignmentBillingStatus::Invoiced.to_string()),
+                        consignment_billings::invoice_reference.eq(Some(request.invoice_reference)),
+                        consignment_billings::invoiced_at.eq(Some(Utc::now())),
+                    ))
+                    .returning(ConsignmentBilling::as_returning())
+                    .get_results::<ConsignmentBilling>(conn)
+                    .await?;
+
+                    Ok(billings)
+                })
+            })
+            .await?;
+
+        Ok(ConsignmentInvoiceResponse {
+            owner_id,
+            invoice_reference,
+            totals: invoice_totals(&billings),
+            billings: billings.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    // Helper functions
+
+    // Opens a partner's balance on an inventory record; the partner must be a vendor or customer
+    async fn open_balance(
+        conn: &mut AsyncPgConnection,
+        inventory: &InventoryItem,
+        owner_id: Uuid,
+    ) -> Result<ConsignmentStock, ItemError> {
+        let owner_type = Self::owner_type(conn, inventory.tenant_id, owner_id)
+            .await?
+            .ok_or(ItemError::OwnerNotFound(owner_id))?;
+
+        let balance = diesel::insert_into(consignment_stock::table)
+            .values(&NewConsignmentStock {
+                tenant_id: inventory.tenant_id,
+                item_id: inventory.item_id,
+                inventory_item_id: inventory.id,
+                owner_id,
+                owner_type: owner_type.to_string(),
+            })
+            .returning(ConsignmentStock::as_returning())
+            .get_result::<ConsignmentStock>(conn)
+            .await?;
+
+        Ok(balance)
+    }
+
+    // Whether a person is one of the tenant's vendors or customers; vendors win when both
+    async fn owner_type(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<Option<ConsignmentOwnerType>> {
+        let vendors: i64 = vendor_person::table
+            .filter(vendor_person::tenant_id.eq(tenant_id))
+            .filter(vendor_person::person_id.eq(person_id))
+            .count()
+            .get_result(conn)
+            .await?;
+        if vendors > 0 {
+            return Ok(Some(ConsignmentOwnerType::Vendor));
+        }
+
+        let customers: i64 = customer_person::table
+            .filter(customer_person::tenant_id.eq(tenant_id))
+            .filter(customer_person::person_id.eq(person_id))
+            .count()
+            .get_result(conn)
+            .await?;
+
+        Ok((customers > 0).then_some(ConsignmentOwnerType::Customer))
+    }
+
+    async fn move_balance(
+        conn: &mut AsyncPgConnection,
+        balance: &ConsignmentStock,
+        delta: Quantity,
+    ) -> Result<()> {
+        diesel::update(consignment_stock::table.find(balance.id))
+            .set(consignment_stock::quantity.eq(balance.quantity + delta))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    // Records what consuming `quantity` of a partner's stock owes them, at the agreed price or
+    // else the inventory record's unit cost
+    async fn bill(
+        conn: &mut AsyncPgConnection,
+        inventory: &InventoryItem,
+        movement: &StockMovement,
+        balance: &ConsignmentStock,
+        quantity: Quantity,
+    ) -> Result<()> {
+        let unit_price = balance
+            .unit_price
+            .unwrap_or_else(|| unit_cost(inventory.pricing.as_ref()));
+
+        diesel::insert_into(consignment_billings::table)
+            .values(&NewConsignmentBilling {
+                tenant_id: balance.tenant_id,
+                consignment_stock_id: balance.id,
+                stock_movement_id: movement.id,
+                item_id: balance.item_id,
+                owner_id: balance.owner_id,
+                quantity,
+                unit_price,
+                amount: billing_amount(quantity, unit_price),
+                currency: balance.currency.clone(),
+            })
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use futures::future::join_all;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateDashboardRequest, Dashboard, DashboardDataResponse, DashboardResponse, DashboardWidget,
+    ListOverdueCapaQuery, NewDashboard, ReportParameters, UpdateDashboardRequest, WidgetData,
+    WidgetSource, DEFAULT_WIDGET_LIMIT,
+};
+use crate::schema::*;
+use crate::services::{
+    DatabaseService, JobService, MachineService, OrderService, QualityService, ReportService,
+};
+
+pub struct DashboardService {
+    database: DatabaseService,
+}
+
+impl DashboardService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Dashboard CRUD operations
+
+    /// The owner's dashboards, default first.
+    pub async fn list_dashboards(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<Vec<DashboardResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let dashboards = dashboards::table
+            .filter(dashboards::tenant_id.eq(tenant_id))
+            .filter(dashboards::owner_id.eq(owner_id))
+            .order((dashboards::is_default.desc(), dashboards::name.asc()))
+            .select(Dashboard::as_select())
+            .load::<Dashboard>(&mut conn)
+            .await?;
+
+        Ok(dashboards
+            .into_iter()
+            .map(DashboardResponse::from)
+            .collect())
+    }
+
+    pub async fn get_dashboard(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+        dashboard_id: Uuid,
+    ) -> Result<Option<Dashboard>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let dashboard = dashboards::table
+            .filter(dashboards::id.eq(dashboard_id))
+            .filter(dashboards::tenant_id.eq(tenant_id))
+            .filter(dashboards::owner_id.eq(owner_id))
+            .select(Dashboard::as_select())
+            .first::<Dashboard>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(dashboard)
+    }
+
+    pub async fn get_default_dashboard(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<Option<Dashboard>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let dashboard = dashboards::table
+            .filter(dashboards::tenant_id.eq(tenant_id))
+            .filter(dashboards::owner_id.eq(owner_id))
+            .filter(dashboards::is_default.eq(true))
+            .select(Dashboard::as_select())
+            .first::<Dashboard>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(dashboard)
+    }
+
+    pub async fn create_dashboard(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+        request: CreateDashboardRequest,
+    ) -> Result<DashboardResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let layout = serde_json::to_value(request.layout.unwrap_or_default())?;
+        let widgets = serde_json::to_value(&request.widgets)?;
+
+        let dashboard = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let owned: i64 = dashboards::table
+                        .filter(dashboards::tenant_id.eq(tenant_id))
+                        .filter(dashboards::owner_id.eq(owner_id))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    let is_default = request.is_default.unwrap_or(owned == 0);
+                    if is_default {
+                        Self::clear_default(conn, tenant_id, owner_id).await?;
+                    }
+
+                    let new_dashboard = NewDashboard {
+                        tenant_id,
+                        owner_id,
+                        name: request.name,
+                        is_default,
+                        layout,
+                        widgets,
+                    };
+
+                    let dashboard = diesel::insert_into(dashboards::table)
+                        .values(&new_dashboard)
+                        .returning(Dashboard::as_returning())
+                        .get_result::<Dashboard>(conn)
+                        .await?;
+                    Ok(dashboard)
+                })
+            })
+            .await?;
+
+        Ok(dashboard.into())
+    }
+
+    pub async fn update_dashboard(
+        &self,
+        tenant_id: Uuid,
+        dashboard: Dashboard,
+        request: UpdateDashboardRequest,
+    ) -> Result<DashboardResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let layout = match &request.layout {
+            Some(layout) => Some(serde_json::to_value(layout)?),
+            None => None,
+        };
+        let widgets = match &request.widgets {
+            Some(widgets) => Some(serde_json::to_value(widgets)?),
+            None => None,
+        };
+
+        let updated = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = dashboards::table
+                        .filter(dashboards::id.eq(dashboard.id))
+                        .filter(dashboards::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(dashboards::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(layout) = &layout {
+                        diesel::update(target)
+                            .set(dashboards::layout.eq(layout))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(widgets) = &widgets {
+                        diesel::update(target)
+                            .set(dashboards::widgets.eq(widgets))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_default) = request.is_default {
+                        if is_default && !dashboard.is_default {
+                            Self::clear_default(conn, tenant_id, dashboard.owner_id).await?;
+                        }
+                        diesel::update(target)
+                            .set(dashboards::is_default.eq(is_default))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let updated = diesel::update(target)
+                        .set(dashboards::updated_at.eq(Utc::now()))
+                        .returning(Dashboard::as_returning())
+                        .get_result::<Dashboard>(conn)
+                        .await?;
+                    Ok(updated)
+                })
+            })
+            .await?;
+
+        Ok(updated.into())
+    }
+
+    pub async fn delete_dashboard(
+        &self,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+        dashboard_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            dashboards::table
+                .filter(dashboards::id.eq(dashboard_id))
+                .filter(dashboards::tenant_id.eq(tenant_id))
+                .filter(dashboards::owner_id.eq(owner_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Widget data operations
+
+    /// Loads the data of every widget on the dashboard at once. A widget that fails is
+    /// reported in its own entry rather than failing the whole dashboard.
+    pub async fn load_widget_data(
+        &self,
+        tenant_id: Uuid,
+        dashboard: &Dashboard,
+    ) -> Result<DashboardDataResponse> {
+        let widgets = dashboard.widgets();
+        let results = join_all(
+            widgets
+                .iter()
+                .map(|widget| self.widget_data(tenant_id, widget)),
+        )
+        .await;
+
+        let widgets = widgets
+            .iter()
+            .zip(results)
+            .map(|(widget, result)| match result {
+                Ok(data) => WidgetData {
+                    id: widget.id.clone(),
+                    kind: widget.source.kind().to_string(),
+                    data: Some(data),
+                    error: None,
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        "Widget {} of dashboard {} not loaded: {}",
+                        widget.id,
+                        dashboard.id,
+                        e
+                    );
+                    WidgetData {
+                        id: widget.id.clone(),
+                        kind: widget.source.kind().to_string(),
+                        data: None,
+                        error: Some("Widget data could not be loaded".to_string()),
+                    }
+                }
+            })
+            .collect();
+
+        Ok(DashboardDataResponse {
+            dashboard_id: dashboard.id,
+            generated_at: Utc::now(),
+            widgets,
+        })
+    }
+
+    // Private helper methods
+
+    async fn widget_data(
+        &self,
+        tenant_id: Uuid,
+        widget: &DashboardWidget,
+    ) -> Result<serde_json::Value> {
+        let limit = widget.source.limit().unwrap_or(DEFAULT_WIDGET_LIMIT);
+        let database = self.database.clone();
+
+        let data = match &widget.source {
+            WidgetSource::Machines(params) => serde_json::to_value(
+                MachineService::new(database)
+                    .list_machines(
+                        tenant_id,
+                        params.status.clone(),
+                        None,
+                        params.site.clone(),
+                        Some(limit),
+                        None,
+                    )
+                    .await?,
+            )?,
+            WidgetSource::Orders(params) => serde_json::to_value(
+                OrderService::new(database)
+                    .list_orders(
+                        tenant_id,
+                        params.order_type.clone(),
+                        params.status.clone(),
+                        false,
+                        Some(limit),
+                        None,
+                    )
+                    .await?,
+            )?,
+            WidgetSource::Jobs(params) => serde_json::to_value(
+                JobService::new(database)
+                    .list_jobs(
+                        tenant_id,
+                        params.job_type.clone(),
+                        params.status.clone(),
+                        false,
+                        Some(limit),
+                        None,
+                    )
+                    .await?,
+            )?,
+            WidgetSource::OverdueCapas(params) => serde_json::to_value(
+                QualityService::new(database)
+                    .list_overdue_capas(
+                        tenant_id,
+                        ListOverdueCapaQuery {
+                            owner_id: params.owner_id,
+                            limit: Some(limit as i64),
+                        },
+                    )
+                    .await?,
+            )?,
+            WidgetSource::Report(params) => {
+                let parameters = ReportParameters::parse(
+                    &ReportService::definition(params.report),
+                    &params.parameters,
+                )
+                .map_err(|e| anyhow!(e))?;
+                let mut result = ReportService::new(database)
+                    .run_report(tenant_id, params.report, parameters)
+                    .await?;
+                result.rows.truncate(limit as usize);
+                serde_json::to_value(result)?
+            }
+        };
+
+        Ok(data)
+    }
+
+    // At most one default dashboard per owner
+    async fn clear_default(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        owner_id: Uuid,
+    ) -> Result<()> {
+        diesel::update(
+            dashboards::table
+                .filter(dashboards::tenant_id.eq(tenant_id))
+                .filter(dashboards::owner_id.eq(owner_id))
+                .filter(dashboards::is_default.eq(true)),
+        )
+        .set(dashboards::is_default.eq(false))
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,34 +1,96 @@
 use anyhow::Result;
+use diesel::prelude::*;
 use diesel_async::pooled_connection::bb8::Pool as AsyncPool;
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
-use diesel_async::{AsyncConnection, AsyncPgConnection, SimpleAsyncConnection};
+use diesel_async::scoped_futures::ScopedBoxFuture;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
 use std::env;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+use crate::models::DatabasePoolStats;
+use crate::schema::tenants;
+use crate::utils::query_metrics::QueryInstrumentation;
 
 pub type DbPool = AsyncPool<AsyncPgConnection>;
 pub type DbConnection<'a> =
     bb8::PooledConnection<'a, AsyncDieselConnectionManager<AsyncPgConnection>>;
 
+// Connections kept for the shared database
+const SHARED_POOL_SIZE: u32 = 10;
+// Connections kept per dedicated tenant database
+const TENANT_POOL_SIZE: u32 = 5;
+
+/// A tenant's dedicated database could not be reached, as opposed to a query failing on it.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Tenant database unreachable: {0}")]
+pub struct TenantDatabaseUnreachable(pub String);
+
+tokio::task_local! {
+    // Tenant whose dedicated database connections are routed to in the current task
+    static TENANT_SCOPE: Uuid;
+}
+
 #[derive(Clone)]
 pub struct DatabaseService {
     pub pool: DbPool,
+    // Dedicated tenant pools and the URL each was built from
+    tenant_pools: Arc<RwLock<HashMap<Uuid, (String, DbPool)>>>,
+    dedicated_databases: bool,
+    shared_only: bool,
 }
 
 impl DatabaseService {
+    /// Connects to DATABASE_URL. With TENANT_DEDICATED_DATABASES=true, tenants that have a
+    /// `database_url` get their own pool and connections made while serving them go there.
+    /// Every connection times its queries; see [`QueryInstrumentation`].
     pub async fn new() -> Result<Self> {
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        QueryInstrumentation::install()?;
 
         let config = AsyncPgConnection::establish(&database_url).await?;
         drop(config); // Test connection and drop it
 
         let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(&database_url);
-        let pool = AsyncPool::builder().max_size(10).build(manager).await?;
+        let pool = AsyncPool::builder()
+            .max_size(SHARED_POOL_SIZE)
+            .build(manager)
+            .await?;
+
+        let dedicated_databases = env::var("TENANT_DEDICATED_DATABASES")
+            .map(|v| v == "true")
+            .unwrap_or(false);
 
-        Ok(Self { pool })
+        let service = Self {
+            pool,
+            tenant_pools: Arc::new(RwLock::new(HashMap::new())),
+            dedicated_databases,
+            shared_only: false,
+        };
+        if dedicated_databases {
+            service.load_tenant_databases().await?;
+        }
+
+        Ok(service)
     }
 
+    /// Gets a connection from the current tenant's dedicated database when it has one, otherwise
+    /// from the shared database.
     pub async fn get_connection(&self) -> Result<DbConnection<'_>> {
-        self.pool
-            .get()
+        match self.scoped_tenant_pool() {
+            // The tenant pool is a temporary handle, so the connection owns its pool reference
+            Some(pool) => pool
+                .get_owned()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to get tenant connection: {}", e)),
+            None => Self::pooled(&self.pool).await,
+        }
+    }
+
+    async fn pooled(pool: &DbPool) -> Result<DbConnection<'_>> {
+        pool.get()
             .await
             .map_err(|e| anyhow::anyhow!("Failed to get connection: {}", e))
     }
@@ -45,4 +107,183 @@ impl DatabaseService {
 
         f(conn)
     }
+
+    /// Runs `f` in one transaction with the tenant context set for RLS, so a write that takes
+    /// several statements either lands whole or not at all. Reads through other services open
+    /// their own connections and do not see the transaction's uncommitted rows.
+    pub async fn with_tenant_tx<'a, T, E, F>(
+        &self,
+        tenant_id: Uuid,
+        f: F,
+    ) -> std::result::Result<T, E>
+    where
+        F: for<'r> FnOnce(
+                &'r mut AsyncPgConnection,
+            ) -> ScopedBoxFuture<'a, 'r, std::result::Result<T, E>>
+            + Send
+            + 'a,
+        T: Send + 'a,
+        E: From<diesel::result::Error> + From<anyhow::Error> + Send + 'a,
+    {
+        let mut conn = self.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        (*conn).transaction(f).await
+    }
+
+    // Tenant database routing
+
+    /// A handle that always uses the shared database, for tenant records, sign-in and other
+    /// data that is never moved to a tenant's own database.
+    pub fn shared(&self) -> Self {
+        Self {
+            shared_only: true,
+            ..self.clone()
+        }
+    }
+
+    pub fn dedicated_databases_enabled(&self) -> bool {
+        self.dedicated_databases
+    }
+
+    /// Runs `future` with connections routed to the tenant's dedicated database, if one is
+    /// registered. Tasks spawned from inside the future are not covered.
+    pub async fn scope_tenant<F: Future>(tenant_id: Uuid, future: F) -> F::Output {
+        TENANT_SCOPE.scope(tenant_id, future).await
+    }
+
+    /// The tenant the current task is scoped to, if any.
+    pub fn current_tenant_scope() -> Option<Uuid> {
+        TENANT_SCOPE.try_with(|tenant_id| *tenant_id).ok()
+    }
+
+    /// Opens (or reopens, when the URL changed) the pool for a tenant's dedicated database.
+    /// Use a URL with `options=-csearch_path%3D<schema>` to isolate a tenant in its own schema.
+    pub async fn register_tenant_database(
+        &self,
+        tenant_id: Uuid,
+        database_url: &str,
+    ) -> Result<()> {
+        let registered = self
+            .tenant_pools
+            .read()
+            .map_err(|_| anyhow::anyhow!("Tenant pool registry poisoned"))?
+            .get(&tenant_id)
+            .is_some_and(|(url, _)| url == database_url);
+        if registered {
+            return Ok(());
+        }
+
+        let conn = AsyncPgConnection::establish(database_url)
+            .await
+            .map_err(|e| TenantDatabaseUnreachable(e.to_string()))?;
+        drop(conn);
+
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
+        let pool = AsyncPool::builder()
+            .max_size(TENANT_POOL_SIZE)
+            .build(manager)
+            .await?;
+
+        self.tenant_pools
+            .write()
+            .map_err(|_| anyhow::anyhow!("Tenant pool registry poisoned"))?
+            .insert(tenant_id, (database_url.to_string(), pool));
+        Ok(())
+    }
+
+    /// Drops a tenant's dedicated pool, e.g. after its `database_url` changed or was cleared.
+    pub fn forget_tenant_database(&self, tenant_id: Uuid) {
+        if let Ok(mut pools) = self.tenant_pools.write() {
+            pools.remove(&tenant_id);
+        }
+    }
+
+    /// Tenants that currently have a dedicated database pool.
+    pub fn tenant_database_ids(&self) -> Vec<Uuid> {
+        self.tenant_pools
+            .read()
+            .map(|pools| pools.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Connections held by the shared pool and by every dedicated tenant pool.
+    pub fn pool_stats(&self) -> Vec<DatabasePoolStats> {
+        let shared = self.pool.state();
+        let mut stats = vec![DatabasePoolStats {
+            tenant_id: None,
+            max_size: SHARED_POOL_SIZE,
+            connections: shared.connections,
+            idle_connections: shared.idle_connections,
+        }];
+        if let Ok(pools) = self.tenant_pools.read() {
+            let mut tenants: Vec<_> = pools
+                .iter()
+                .map(|(tenant_id, (_, pool))| {
+                    let state = pool.state();
+                    DatabasePoolStats {
+                        tenant_id: Some(*tenant_id),
+                        max_size: TENANT_POOL_SIZE,
+                        connections: state.connections,
+                        idle_connections: state.idle_connections,
+                    }
+                })
+                .collect();
+            tenants.sort_by_key(|pool| pool.tenant_id);
+            stats.extend(tenants);
+        }
+        stats
+    }
+
+    /// Runs `run` against the shared database and then once scoped to each dedicated tenant
+    /// database, summing the results. Failures in a tenant database are logged and skipped.
+    pub async fn for_each_database<F, Fut>(&self, mut run: F) -> Result<usize>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<usize>>,
+    {
+        let mut total = run().await?;
+        for tenant_id in self.tenant_database_ids() {
+            match Self::scope_tenant(tenant_id, run()).await {
+                Ok(count) => total += count,
+                Err(e) => tracing::error!("Tenant {} database: {}", tenant_id, e),
+            }
+        }
+        Ok(total)
+    }
+
+    // Registers every tenant that has a database_url; unreachable ones are retried on request
+    async fn load_tenant_databases(&self) -> Result<()> {
+        let mut conn = Self::pooled(&self.pool).await?;
+        let tenants = tenants::table
+            .filter(tenants::database_url.is_not_null())
+            .select((tenants::id, tenants::database_url.assume_not_null()))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?;
+
+        for (tenant_id, database_url) in tenants {
+            if let Err(e) = self
+                .register_tenant_database(tenant_id, &database_url)
+                .await
+            {
+                tracing::warn!("Tenant {} database not registered: {}", tenant_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn scoped_tenant_pool(&self) -> Option<DbPool> {
+        if !self.dedicated_databases || self.shared_only {
+            return None;
+        }
+        let tenant_id = Self::current_tenant_scope()?;
+        self.tenant_pools
+            .read()
+            .ok()?
+            .get(&tenant_id)
+            .map(|(_, pool)| pool.clone())
+    }
 }
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use uuid::Uuid;
+
+use crate::models::{
+    BatchRecordResponse, DocumentPackEntry, DocumentPackKind, DocumentPackManifest,
+};
+use crate::schema::*;
+use crate::services::{BatchRecordService, DatabaseService, OrderService};
+use crate::utils::batch_record::{render_batch_record_pdf, render_inspection_report_pdf};
+use crate::utils::branding::DocumentBranding;
+use crate::utils::document_pack::{release_notes_path, release_notes_text};
+use crate::utils::i18n::Locale;
+use crate::utils::zip::{safe_file_name, ZipArchive};
+
+pub struct DocumentPackService {
+    database: DatabaseService,
+    branding: DocumentBranding,
+}
+
+impl DocumentPackService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database,
+            branding: DocumentBranding::default(),
+        }
+    }
+
+    /// Renders the order confirmation with the tenant's branding.
+    pub fn with_branding(mut self, branding: DocumentBranding) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    /// Zips the documents shipping staff send with an order: its confirmation, the batch record
+    /// and inspection report of every lot shipped on it, and the release notes of the firmware
+    /// in those lots and of the current firmware of the ordered items, with a manifest. Shipped
+    /// lots are found with [`BatchRecordService::shipped_lots`]. Returns the order number and
+    /// the archive.
+    pub async fn order_document_pack(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        locale: Locale,
+    ) -> Result<Option<(String, Vec<u8>)>> {
+        let order_service =
+            OrderService::new(self.database.clone()).with_branding(self.branding.clone());
+        let Some((order_number, confirmation)) = order_service
+            .confirmation_pdf(tenant_id, order_id, locale)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let Some(order) = order_service.get_order_by_id(tenant_id, order_id).await? else {
+            return Ok(None);
+        };
+        let item_ids: Vec<Uuid> = order.items.iter().filter_map(|line| line.item_id).collect();
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let lots = BatchRecordService::shipped_lots(
+            &mut conn,
+            tenant_id,
+            order_id,
+            order.external_entity_id,
+            &item_ids,
+        )
+        .await?
+        .into_iter()
+        .map(BatchRecordResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        // Firmware the lots were built with, and the current firmware of the ordered items
+        let lot_firmware: Vec<Uuid> = lots
+            .iter()
+            .flat_map(|lot| &lot.content.machines)
+            .flat_map(|machine| &machine.firmware)
+            .map(|firmware| firmware.asset_id)
+            .collect();
+        let firmware = firmware_specific::table
+            .inner_join(assets::table.on(assets::id.eq(firmware_specific::asset_id)))
+            .filter(assets::tenant_id.eq(tenant_id))
+            .filter(
+                assets::id.eq_any(&lot_firmware).or(assets::item_id
+                    .eq_any(&item_ids)
+                    .and(assets::is_current.eq(true))),
+            )
+            .filter(firmware_specific::release_notes.is_not_null())
+            .order((assets::name.asc(), assets::created_at.asc()))
+            .select((
+                assets::id,
+                assets::name,
+                assets::version,
+                assets::released_at,
+                firmware_specific::release_notes,
+            ))
+            .load::<(
+                Uuid,
+                String,
+                Option<String>,
+                Option<DateTime<Utc>>,
+                Option<String>,
+            )>(&mut conn)
+            .await?;
+
+        let now = Utc::now();
+        let mut archive = ZipArchive::new();
+        let mut documents = Vec::new();
+        let mut add = |path: &str, contents: &[u8], kind, source_id| {
+            let path = archive.add_file(path, now, contents);
+            documents.push(DocumentPackEntry {
+                path,
+                kind,
+                source_id,
+            });
+        };
+
+        add(
+            &format!("{}.pdf", safe_file_name(&order_number)),
+            &confirmation,
+            DocumentPackKind::OrderConfirmation,
+            order_id,
+        );
+        for lot in &lots {
+            let name = safe_file_name(&lot.batch_number);
+            add(
+                &format!("certificates/{}.pdf", name),
+                &render_batch_record_pdf(lot),
+                DocumentPackKind::LotCertificate,
+                lot.id,
+            );
+            if !lot.content.inspections.is_empty() {
+                add(
+                    &format!("inspections/{}.pdf", name),
+                    &render_inspection_report_pdf(lot),
+                    DocumentPackKind::InspectionReport,
+                    lot.id,
+                );
+            }
+        }
+        for (asset_id, name, version, released_at, notes) in firmware {
+            let Some(notes) = notes else {
+                continue;
+            };
+            add(
+                &release_notes_path(&name, version.as_deref()),
+                release_notes_text(&name, version.as_deref(), released_at, &notes).as_bytes(),
+                DocumentPackKind::FirmwareReleaseNotes,
+                asset_id,
+            );
+        }
+
+        let manifest = DocumentPackManifest {
+            order_id,
+            order_number: order_number.clone(),
+            generated_at: now,
+            documents,
+        };
+        archive.add_file("manifest.json", now, &serde_json::to_vec_pretty(&manifest)?);
+
+        Ok(Some((order_number, archive.finish())))
+    }
+}
@@ -0,0 +1,550 @@
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::sql_types::Uuid as SqlUuid;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, DuplicateMatch, DuplicatesQuery, Item, MergeRequest, MergeResponse, Permission,
+    Person, ReferenceCountRow,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, EncryptionService};
+use crate::utils::duplicate::{find_item_duplicates, find_person_duplicates, DEFAULT_MIN_SCORE};
+use crate::utils::AppError;
+
+/// Errors from [`DuplicateService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum DuplicateError {
+    #[error("Duplicate is shared with another tenant")]
+    SharedWithAnotherTenant,
+}
+
+impl From<DuplicateError> for AppError {
+    fn from(error: DuplicateError) -> Self {
+        match error {
+            DuplicateError::SharedWithAnotherTenant => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+const DEFAULT_DUPLICATES_LIMIT: usize = 100;
+
+/// A column referencing a person or an item.
+struct Reference {
+    table: &'static str,
+    column: &'static str,
+    /// Columns the reference is unique together with. A row of the duplicate that would collide
+    /// with a row of the survivor is dropped instead of moved. `Some(&[])` means the column is
+    /// unique on its own.
+    unique_with: Option<&'static [&'static str]>,
+    /// SQL expression giving the tenant of row `t`, or `None` for global tables.
+    tenant: Option<&'static str>,
+}
+
+const ROW_TENANT: Option<&str> = Some("t.tenant_id");
+const MACHINE_TENANT: Option<&str> =
+    Some("(SELECT m.tenant_id FROM machines m WHERE m.id = t.machine_id)");
+const ORDER_TENANT: Option<&str> =
+    Some("(SELECT o.tenant_id FROM orders o WHERE o.id = t.order_id)");
+
+const fn reference(
+    table: &'static str,
+    column: &'static str,
+    unique_with: Option<&'static [&'static str]>,
+    tenant: Option<&'static str>,
+) -> Reference {
+    Reference {
+        table,
+        column,
+        unique_with,
+        tenant,
+    }
+}
+
+const PERSON_REFERENCES: &[Reference] = &[
+    reference(
+        "tenant_person",
+        "person_id",
+        Some(&["tenant_id", "role"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "internal_person",
+        "person_id",
+        Some(&["tenant_id"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "customer_person",
+        "person_id",
+        Some(&["tenant_id"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "vendor_person",
+        "person_id",
+        Some(&["tenant_id"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "distributor_person",
+        "person_id",
+        Some(&["tenant_id"]),
+        ROW_TENANT,
+    ),
+    reference("customer_person", "account_manager_id", None, ROW_TENANT),
+    reference("jobs", "assigned_person_id", None, ROW_TENANT),
+    reference("jobs", "supervisor_id", None, ROW_TENANT),
+    reference("jobs", "customer_id", None, ROW_TENANT),
+    reference("job_history", "person_id", None, ROW_TENANT),
+    reference("orders", "external_entity_id", None, ROW_TENANT),
+    reference("orders", "created_by_id", None, ROW_TENANT),
+    reference("order_history", "person_id", None, ROW_TENANT),
+    reference("inventory_items", "vendor_id", None, ROW_TENANT),
+    reference("assets", "created_by_id", None, ROW_TENANT),
+    reference(
+        "asset_signatures",
+        "signed_by_id",
+        Some(&["asset_id", "meaning"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "machine_operator_assignments",
+        "person_id",
+        Some(&["machine_id", "assignment_type"]),
+        MACHINE_TENANT,
+    ),
+    reference(
+        "person_skills",
+        "person_id",
+        Some(&["skill_id"]),
+        ROW_TENANT,
+    ),
+    reference("report_schedules", "created_by_id", None, ROW_TENANT),
+    reference("stock_movements", "person_id", None, ROW_TENANT),
+    reference("print_jobs", "requested_by_id", None, ROW_TENANT),
+    reference("shipments", "created_by_id", None, ROW_TENANT),
+    reference("item_price_history", "vendor_id", None, ROW_TENANT),
+    reference("item_price_history", "recorded_by_id", None, ROW_TENANT),
+    reference("item_lifecycle_alerts", "resolved_by_id", None, ROW_TENANT),
+    reference("operator_shifts", "person_id", None, ROW_TENANT),
+    reference("operator_attendance", "person_id", None, ROW_TENANT),
+    reference("operator_attendance", "recorded_by_id", None, ROW_TENANT),
+    reference("token_blacklist", "person_id", None, ROW_TENANT),
+];
+
+const ITEM_REFERENCES: &[Reference] = &[
+    reference(
+        "inventory_items",
+        "item_id",
+        Some(&["tenant_id", "context"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "item_bom",
+        "parent_item_id",
+        Some(&["tenant_id", "component_item_id"]),
+        ROW_TENANT,
+    ),
+    reference(
+        "item_bom",
+        "component_item_id",
+        Some(&["tenant_id", "parent_item_id"]),
+        ROW_TENANT,
+    ),
+    reference("assets", "item_id", None, ROW_TENANT),
+    reference("jobs", "item_id", None, ROW_TENANT),
+    reference(
+        "machine_item_relationships",
+        "item_id",
+        Some(&["machine_id", "relationship_type"]),
+        MACHINE_TENANT,
+    ),
+    reference("order_items", "item_id", None, ORDER_TENANT),
+    reference("stock_movements", "item_id", None, ROW_TENANT),
+    reference("print_jobs", "item_id", None, ROW_TENANT),
+    reference("item_price_history", "item_id", None, ROW_TENANT),
+    reference(
+        "item_lifecycle_alerts",
+        "item_id",
+        Some(&["tenant_id", "suggested_lifecycle", "status"]),
+        ROW_TENANT,
+    ),
+    reference("item_lifecycle_checks", "item_id", Some(&[]), None),
+];
+
+// Duplicate inventory records of the same tenant and context as a survivor record
+const INVENTORY_PAIRS_SQL: &str = "SELECT d.id AS duplicate_id, s.id AS survivor_id, \
+     COALESCE(d.quantity, 0) AS quantity \
+     FROM inventory_items d JOIN inventory_items s \
+       ON s.tenant_id = d.tenant_id AND s.context = d.context AND s.item_id = $2 \
+     WHERE d.item_id = $1";
+
+pub struct DuplicateService {
+    database: DatabaseService,
+}
+
+impl DuplicateService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Person duplicate operations
+
+    pub async fn find_person_duplicates(
+        &self,
+        tenant_id: Uuid,
+        query: DuplicatesQuery,
+    ) -> Result<Vec<DuplicateMatch>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let persons = person::table
+            .filter(
+                person::id.eq_any(
+                    tenant_person::table
+                        .filter(tenant_person::tenant_id.eq(tenant_id))
+                        .select(tenant_person::person_id),
+                ),
+            )
+            .order(person::created_at.asc())
+            .select(Person::as_select())
+            .load::<Person>(&mut conn)
+            .await?;
+        // Phone numbers are compared in plain
+        let cipher = EncryptionService::new(self.database.clone())
+            .cipher(None)
+            .await?;
+        let persons = persons
+            .into_iter()
+            .map(|person| person.open_phone(&cipher))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut matches =
+            find_person_duplicates(&persons, query.min_score.unwrap_or(DEFAULT_MIN_SCORE));
+        matches.truncate(query.limit.unwrap_or(DEFAULT_DUPLICATES_LIMIT));
+
+        Ok(matches)
+    }
+
+    /// Moves every reference to the duplicate person onto the survivor and deletes the duplicate.
+    /// Both must belong to the tenant; a duplicate also used by another tenant is not merged.
+    pub async fn merge_persons(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: MergeRequest,
+    ) -> Result<Option<MergeResponse>> {
+        caller.require(Permission::PurgePerson)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let members = tenant_person::table
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .filter(tenant_person::person_id.eq_any([request.survivor_id, request.duplicate_id]))
+            .select(tenant_person::person_id)
+            .distinct()
+            .load::<Uuid>(&mut conn)
+            .await?;
+        if members.len() < 2 {
+            return Ok(None);
+        }
+
+        let (survivor_id, duplicate_id) = (request.survivor_id, request.duplicate_id);
+        let response = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    Self::ensure_not_shared(conn, PERSON_REFERENCES, tenant_id, duplicate_id)
+                        .await?;
+
+                    let duplicate = person::table
+                        .find(duplicate_id)
+                        .select(Person::as_select())
+                        .first::<Person>(conn)
+                        .await?;
+
+                    let mut response = MergeResponse {
+                        survivor_id,
+                        merged_id: duplicate_id,
+                        moved: BTreeMap::new(),
+                        dropped: BTreeMap::new(),
+                    };
+                    for reference in PERSON_REFERENCES {
+                        Self::move_reference(
+                            conn,
+                            reference,
+                            survivor_id,
+                            duplicate_id,
+                            &mut response,
+                        )
+                        .await?;
+                    }
+
+                    // Keep the duplicate's phone when the survivor has none
+                    diesel::update(
+                        person::table
+                            .find(survivor_id)
+                            .filter(person::phone.is_null()),
+                    )
+                    .set(person::phone.eq(duplicate.phone))
+                    .execute(conn)
+                    .await?;
+
+                    diesel::delete(person::table.find(duplicate_id))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(response)
+                })
+            })
+            .await?;
+
+        Ok(Some(response))
+    }
+
+    // Item duplicate operations
+
+    pub async fn find_item_duplicates(
+        &self,
+        tenant_id: Uuid,
+        query: DuplicatesQuery,
+    ) -> Result<Vec<DuplicateMatch>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let items = items::table
+            .filter(
+                items::id.eq_any(
+                    inventory_items::table
+                        .filter(inventory_items::tenant_id.eq(tenant_id))
+                        .select(inventory_items::item_id),
+                ),
+            )
+            .order(items::created_at.asc())
+            .select(Item::as_select())
+            .load::<Item>(&mut conn)
+            .await?;
+
+        let mut matches =
+            find_item_duplicates(&items, query.min_score.unwrap_or(DEFAULT_MIN_SCORE));
+        matches.truncate(query.limit.unwrap_or(DEFAULT_DUPLICATES_LIMIT));
+
+        Ok(matches)
+    }
+
+    /// Moves every reference to the duplicate item onto the survivor and deletes the duplicate.
+    /// Inventory records of the same context are combined by adding the duplicate's quantity and
+    /// history to the survivor's record. A duplicate also used by another tenant is not merged.
+    pub async fn merge_items(
+        &self,
+        tenant_id: Uuid,
+        request: MergeRequest,
+    ) -> Result<Option<MergeResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let found = items::table
+            .filter(items::id.eq_any([request.survivor_id, request.duplicate_id]))
+            .select(Item::as_select())
+            .load::<Item>(&mut conn)
+            .await?;
+        let (Some(survivor), Some(duplicate)) = (
+            found.iter().find(|i| i.id == request.survivor_id).cloned(),
+            found.iter().find(|i| i.id == request.duplicate_id).cloned(),
+        ) else {
+            return Ok(None);
+        };
+
+        let response = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    Self::ensure_not_shared(conn, ITEM_REFERENCES, tenant_id, duplicate.id).await?;
+
+                    let mut response = MergeResponse {
+                        survivor_id: survivor.id,
+                        merged_id: duplicate.id,
+                        moved: BTreeMap::new(),
+                        dropped: BTreeMap::new(),
+                    };
+
+                    // BOM lines between the two items would reference the survivor twice
+                    let dropped = diesel::delete(
+                        item_bom::table.filter(
+                            item_bom::parent_item_id
+                                .eq(duplicate.id)
+                                .and(item_bom::component_item_id.eq(survivor.id))
+                                .or(item_bom::parent_item_id
+                                    .eq(survivor.id)
+                                    .and(item_bom::component_item_id.eq(duplicate.id))),
+                        ),
+                    )
+                    .execute(conn)
+                    .await?;
+                    count(&mut response.dropped, "item_bom.component_item_id", dropped);
+
+                    let moved = diesel::sql_query(
+                        "UPDATE item_bom t SET substitutes = ARRAY( \
+                             SELECT DISTINCT s FROM unnest(array_replace(t.substitutes, $1, $2)) s \
+                             WHERE s <> t.component_item_id) \
+                         WHERE $1 = ANY(t.substitutes)",
+                    )
+                    .bind::<SqlUuid, _>(duplicate.id)
+                    .bind::<SqlUuid, _>(survivor.id)
+                    .execute(conn)
+                    .await?;
+                    count(&mut response.moved, "item_bom.substitutes", moved);
+
+                    // Combine inventory records the survivor already has for the same context
+                    for table in ["stock_movements", "item_price_history"] {
+                        let moved = diesel::sql_query(format!(
+                            "UPDATE {table} t SET inventory_item_id = p.survivor_id \
+                             FROM ({INVENTORY_PAIRS_SQL}) p WHERE t.inventory_item_id = p.duplicate_id"
+                        ))
+                        .bind::<SqlUuid, _>(duplicate.id)
+                        .bind::<SqlUuid, _>(survivor.id)
+                        .execute(conn)
+                        .await?;
+                        count(
+                            &mut response.moved,
+                            &format!("{}.inventory_item_id", table),
+                            moved,
+                        );
+                    }
+                    diesel::sql_query(format!(
+                        "UPDATE inventory_items t SET quantity = COALESCE(t.quantity, 0) + p.quantity \
+                         FROM ({INVENTORY_PAIRS_SQL}) p WHERE t.id = p.survivor_id"
+                    ))
+                    .bind::<SqlUuid, _>(duplicate.id)
+                    .bind::<SqlUuid, _>(survivor.id)
+                    .execute(conn)
+                    .await?;
+
+                    for reference in ITEM_REFERENCES {
+                        Self::move_reference(conn, reference, survivor.id, duplicate.id, &mut response)
+                            .await?;
+                    }
+
+                    // Keep the duplicate's part data where the survivor has none
+                    diesel::update(items::table.find(survivor.id))
+                        .set((
+                            items::mfr_part_number
+                                .eq(survivor.mfr_part_number.or(duplicate.mfr_part_number)),
+                            items::datasheet.eq(survivor.datasheet.or(duplicate.datasheet)),
+                            items::description.eq(survivor.description.or(duplicate.description)),
+                            items::category.eq(survivor.category.or(duplicate.category)),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    diesel::delete(items::table.find(duplicate.id))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(response)
+                })
+            })
+            .await?;
+
+        Ok(Some(response))
+    }
+
+    // Refuses to merge a duplicate that other tenants still reference, since merging removes it
+    // for everyone
+    async fn ensure_not_shared(
+        conn: &mut AsyncPgConnection,
+        references: &[Reference],
+        tenant_id: Uuid,
+        duplicate_id: Uuid,
+    ) -> Result<()> {
+        let checks: Vec<String> = references
+            .iter()
+            .filter_map(|r| {
+                r.tenant.map(|tenant| {
+                    format!(
+                        "SELECT 1 FROM {} t WHERE t.{} = $1 AND {} <> $2",
+                        r.table, r.column, tenant
+                    )
+                })
+            })
+            .collect();
+
+        let shared = diesel::sql_query(format!(
+            "SELECT COUNT(*)::int8 AS count FROM ({}) shared",
+            checks.join(" UNION ALL ")
+        ))
+        .bind::<SqlUuid, _>(duplicate_id)
+        .bind::<SqlUuid, _>(tenant_id)
+        .get_result::<ReferenceCountRow>(conn)
+        .await?;
+
+        if shared.count > 0 {
+            return Err(DuplicateError::SharedWithAnotherTenant.into());
+        }
+        Ok(())
+    }
+
+    // Points one reference column at the survivor, dropping rows that would collide with a row
+    // the survivor already has
+    async fn move_reference(
+        conn: &mut AsyncPgConnection,
+        reference: &Reference,
+        survivor_id: Uuid,
+        duplicate_id: Uuid,
+        response: &mut MergeResponse,
+    ) -> Result<()> {
+        let key = format!("{}.{}", reference.table, reference.column);
+
+        if let Some(unique_with) = reference.unique_with {
+            let same_key: String = unique_with
+                .iter()
+                .map(|column| format!(" AND s.{column} = t.{column}"))
+                .collect();
+            let dropped = diesel::sql_query(format!(
+                "DELETE FROM {table} t WHERE t.{column} = $1 AND EXISTS ( \
+                     SELECT 1 FROM {table} s WHERE s.{column} = $2{same_key})",
+                table = reference.table,
+                column = reference.column,
+            ))
+            .bind::<SqlUuid, _>(duplicate_id)
+            .bind::<SqlUuid, _>(survivor_id)
+            .execute(conn)
+            .await?;
+            count(&mut response.dropped, &key, dropped);
+        }
+
+        let moved = diesel::sql_query(format!(
+            "UPDATE {table} SET {column} = $2 WHERE {column} = $1",
+            table = reference.table,
+            column = reference.column,
+        ))
+        .bind::<SqlUuid, _>(duplicate_id)
+        .bind::<SqlUuid, _>(survivor_id)
+        .execute(conn)
+        .await?;
+        count(&mut response.moved, &key, moved);
+
+        Ok(())
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, key: &str, rows: usize) {
+    if rows > 0 {
+        *counts.entry(key.to_string()).or_default() += rows;
+    }
+}
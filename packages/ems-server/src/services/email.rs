@@ -0,0 +1,135 @@
+use anyhow::{anyhow, Result};
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::env;
+
+use crate::utils::branding::{DocumentBranding, LOGO_CONTENT_ID};
+
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct EmailService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailService {
+    /// Builds the SMTP transport from SMTP_* environment variables.
+    /// Returns `Ok(None)` when SMTP_HOST is not configured so email delivery can be disabled.
+    pub fn from_env() -> Result<Option<Self>> {
+        let host = match env::var("SMTP_HOST") {
+            Ok(host) if !host.is_empty() => host,
+            _ => return Ok(None),
+        };
+
+        let from: Mailbox = env::var("SMTP_FROM")
+            .map_err(|_| anyhow!("SMTP_FROM must be set when SMTP_HOST is configured"))?
+            .parse()
+            .map_err(|e| anyhow!("Invalid SMTP_FROM address: {}", e))?;
+
+        // SMTP_TLS: "starttls" (default), "tls" for implicit TLS, or "none" for local relays
+        let tls_mode = env::var("SMTP_TLS").unwrap_or_else(|_| "starttls".to_string());
+        let mut builder = match tls_mode.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)?,
+        };
+
+        if let Ok(port) = env::var("SMTP_PORT") {
+            builder = builder.port(
+                port.parse()
+                    .map_err(|_| anyhow!("Invalid SMTP_PORT: {}", port))?,
+            );
+        }
+
+        if let (Ok(username), Ok(password)) = (env::var("SMTP_USERNAME"), env::var("SMTP_PASSWORD"))
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Some(Self {
+            transport: builder.build(),
+            from,
+        }))
+    }
+
+    pub async fn send(
+        &self,
+        recipients: &[String],
+        subject: &str,
+        body: &str,
+        attachments: Vec<EmailAttachment>,
+    ) -> Result<()> {
+        self.send_branded(
+            recipients,
+            subject,
+            body,
+            attachments,
+            &DocumentBranding::default(),
+        )
+        .await
+    }
+
+    /// Like [`EmailService::send`], with the tenant's branding: replies go to its reply-to
+    /// address, and a branded email carries an HTML version with its colors, logo and footer
+    /// alongside the plain text.
+    pub async fn send_branded(
+        &self,
+        recipients: &[String],
+        subject: &str,
+        body: &str,
+        attachments: Vec<EmailAttachment>,
+        branding: &DocumentBranding,
+    ) -> Result<()> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject);
+        for recipient in recipients {
+            builder = builder.to(recipient
+                .parse()
+                .map_err(|e| anyhow!("Invalid recipient {}: {}", recipient, e))?);
+        }
+        if let Some(reply_to) = &branding.reply_to {
+            builder = builder.reply_to(
+                reply_to
+                    .parse()
+                    .map_err(|e| anyhow!("Invalid reply-to address {}: {}", reply_to, e))?,
+            );
+        }
+
+        let text = SinglePart::plain(branding.email_text(body));
+        let mut multipart = if branding.is_branded() {
+            // The logo is attached inline and referenced from the HTML by its content ID
+            let logo_src = format!("cid:{}", LOGO_CONTENT_ID);
+            let html = SinglePart::html(branding.email_html(body, Some(&logo_src)));
+            let html = match &branding.logo {
+                Some(logo) => MultiPart::related().singlepart(html).singlepart(
+                    Attachment::new_inline(LOGO_CONTENT_ID.to_string())
+                        .body(logo.jpeg.clone(), ContentType::parse("image/jpeg")?),
+                ),
+                None => MultiPart::related().singlepart(html),
+            };
+            MultiPart::mixed().multipart(MultiPart::alternative().singlepart(text).multipart(html))
+        } else {
+            MultiPart::mixed().singlepart(text)
+        };
+        for attachment in attachments {
+            let content_type = ContentType::parse(&attachment.content_type)
+                .map_err(|e| anyhow!("Invalid attachment content type: {}", e))?;
+            multipart = multipart.singlepart(
+                Attachment::new(attachment.filename).body(attachment.content, content_type),
+            );
+        }
+
+        let message = builder.multipart(multipart)?;
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,464 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::models::{DataKeyRecord, KeyRotation, NewDataKeyRecord};
+use crate::schema::{data_keys, person, tenants};
+use crate::services::DatabaseService;
+use crate::utils::encryption::{decrypt, encrypt, DataKey, FieldCipher};
+
+// Master key id recorded with data keys when ENCRYPTION_MASTER_KEY_ID is not set
+const DEFAULT_MASTER_KEY_ID: &str = "local";
+// Loaded data keys are reused this long, so a key rotated on another instance is picked up
+const CIPHER_CACHE_TTL: Duration = Duration::from_secs(60);
+// Rows re-encrypted per query during a rotation
+const ROTATION_BATCH_SIZE: i64 = 500;
+
+/// Holds the master key data keys are wrapped with. [`LocalKeyWrapper`] keeps master keys in
+/// the server config; a KMS implements the same calls with the master key never leaving it.
+#[async_trait]
+pub trait KeyWrapper: Send + Sync {
+    /// Master key new data keys are wrapped with.
+    fn key_id(&self) -> &str;
+
+    async fn wrap(&self, key: &DataKey) -> Result<Vec<u8>>;
+
+    /// Unwraps a data key wrapped with the master key `key_id`.
+    async fn unwrap(&self, key_id: &str, wrapped: &[u8]) -> Result<DataKey>;
+}
+
+/// Master keys from config. ENCRYPTION_MASTER_KEY (base64 of 32 bytes) wraps new data keys;
+/// keys listed in ENCRYPTION_PREVIOUS_MASTER_KEYS (`id:base64`, comma separated) still unwrap
+/// data keys wrapped before the master key was rotated.
+pub struct LocalKeyWrapper {
+    key_id: String,
+    keys: HashMap<String, DataKey>,
+}
+
+impl LocalKeyWrapper {
+    pub fn new(key_id: &str, key: DataKey) -> Self {
+        Self {
+            key_id: key_id.to_string(),
+            keys: HashMap::from([(key_id.to_string(), key)]),
+        }
+    }
+
+    pub fn with_previous(mut self, key_id: &str, key: DataKey) -> Self {
+        self.keys.entry(key_id.to_string()).or_insert(key);
+        self
+    }
+
+    /// `None` when ENCRYPTION_MASTER_KEY is not set, which leaves encryption off.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(master_key) = env::var("ENCRYPTION_MASTER_KEY")
+            .ok()
+            .filter(|key| !key.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let key_id = env::var("ENCRYPTION_MASTER_KEY_ID")
+            .ok()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| DEFAULT_MASTER_KEY_ID.to_string());
+        let key = DataKey::from_base64(&master_key)
+            .map_err(|e| anyhow!("ENCRYPTION_MASTER_KEY: {}", e))?;
+
+        let mut wrapper = Self::new(&key_id, key);
+        let previous = env::var("ENCRYPTION_PREVIOUS_MASTER_KEYS").unwrap_or_default();
+        for entry in previous.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, key) = entry.split_once(':').ok_or_else(|| {
+                anyhow!("ENCRYPTION_PREVIOUS_MASTER_KEYS entries must be id:base64-key")
+            })?;
+            let key = DataKey::from_base64(key)
+                .map_err(|e| anyhow!("ENCRYPTION_PREVIOUS_MASTER_KEYS: {}", e))?;
+            wrapper = wrapper.with_previous(id.trim(), key);
+        }
+        Ok(Some(wrapper))
+    }
+}
+
+#[async_trait]
+impl KeyWrapper for LocalKeyWrapper {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    async fn wrap(&self, key: &DataKey) -> Result<Vec<u8>> {
+        encrypt(
+            &self.keys[&self.key_id],
+            self.key_id.as_bytes(),
+            key.as_bytes(),
+        )
+    }
+
+    async fn unwrap(&self, key_id: &str, wrapped: &[u8]) -> Result<DataKey> {
+        let master = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("Master key {} is not configured", key_id))?;
+        DataKey::from_bytes(&decrypt(master, key_id.as_bytes(), wrapped)?)
+    }
+}
+
+type SharedWrapper = std::result::Result<Option<Arc<dyn KeyWrapper>>, String>;
+type CipherCache = Arc<RwLock<HashMap<Option<Uuid>, (Instant, FieldCipher)>>>;
+
+// Key wrapper from the environment, read once; an invalid config is kept as its error
+static DEFAULT_WRAPPER: OnceLock<SharedWrapper> = OnceLock::new();
+static DEFAULT_CACHE: OnceLock<CipherCache> = OnceLock::new();
+
+fn default_wrapper() -> SharedWrapper {
+    DEFAULT_WRAPPER
+        .get_or_init(|| match LocalKeyWrapper::from_env() {
+            Ok(wrapper) => Ok(wrapper.map(|wrapper| Arc::new(wrapper) as Arc<dyn KeyWrapper>)),
+            Err(e) => Err(e.to_string()),
+        })
+        .clone()
+}
+
+/// Application-level encryption of sensitive columns: tenant setting secrets with the tenant's
+/// data key, and person phone numbers with the platform key since persons are shared between
+/// tenants. Data keys are created on first use, stored wrapped in the shared database and
+/// cached in memory. Without a master key encryption is off and values are stored as given.
+#[derive(Clone)]
+pub struct EncryptionService {
+    database: DatabaseService,
+    wrapper: SharedWrapper,
+    cache: CipherCache,
+}
+
+impl EncryptionService {
+    /// Uses the master key from the environment and the process-wide data key cache.
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database: database.shared(),
+            wrapper: default_wrapper(),
+            cache: DEFAULT_CACHE.get_or_init(Default::default).clone(),
+        }
+    }
+
+    /// With its own key wrapper and cache, e.g. for tests; `None` leaves encryption off.
+    pub fn with_wrapper(database: DatabaseService, wrapper: Option<Arc<dyn KeyWrapper>>) -> Self {
+        Self {
+            database: database.shared(),
+            wrapper: Ok(wrapper),
+            cache: Default::default(),
+        }
+    }
+
+    /// Checks the master key config, so a bad key stops the server at startup rather than
+    /// failing its first write. Returns whether encryption is on.
+    pub fn check_config() -> Result<bool> {
+        default_wrapper()
+            .map(|wrapper| wrapper.is_some())
+            .map_err(|e| anyhow!(e))
+    }
+
+    fn wrapper(&self) -> Result<Option<Arc<dyn KeyWrapper>>> {
+        self.wrapper.clone().map_err(|e| anyhow!(e))
+    }
+
+    fn required_wrapper(&self) -> Result<Arc<dyn KeyWrapper>> {
+        self.wrapper()?
+            .ok_or_else(|| anyhow!("ENCRYPTION_MASTER_KEY is not set"))
+    }
+
+    /// Cipher for a tenant's values, or with `None` for the platform's. Creates the scope's
+    /// first data key when it has none.
+    pub async fn cipher(&self, tenant_id: Option<Uuid>) -> Result<FieldCipher> {
+        let Some(wrapper) = self.wrapper()? else {
+            return Ok(FieldCipher::disabled(tenant_id));
+        };
+        if let Some(cipher) = self.cached(tenant_id) {
+            return Ok(cipher);
+        }
+
+        let cipher = self.load(wrapper.as_ref(), tenant_id).await?;
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(tenant_id, (Instant::now(), cipher.clone()));
+        }
+        Ok(cipher)
+    }
+
+    /// Replaces the scope's data key with a new version and encrypts the scope's values again
+    /// with it: setting secrets for a tenant, person phone numbers for the platform. Values
+    /// stored before encryption was turned on are encrypted on the way. Retired keys are kept,
+    /// so values written by instances still holding the old key stay readable.
+    pub async fn rotate(&self, tenant_id: Option<Uuid>) -> Result<KeyRotation> {
+        let wrapper = self.required_wrapper()?;
+        let wrapped_key = STANDARD.encode(wrapper.wrap(&DataKey::generate()?).await?);
+        let master_key_id = wrapper.key_id().to_string();
+
+        // Platform keys are not tenant data, so they are written without a tenant context
+        match tenant_id {
+            Some(scope) => {
+                self.database
+                    .with_tenant_tx::<_, anyhow::Error, _>(scope, |conn| {
+                        Box::pin(Self::store_key(conn, tenant_id, wrapped_key, master_key_id))
+                    })
+                    .await?
+            }
+            None => {
+                let mut conn = self.database.get_connection().await?;
+                conn.transaction::<_, anyhow::Error, _>(|conn| {
+                    Box::pin(Self::store_key(conn, tenant_id, wrapped_key, master_key_id))
+                })
+                .await?
+            }
+        }
+
+        self.forget(tenant_id);
+        let cipher = self.cipher(tenant_id).await?;
+        let reencrypted = match tenant_id {
+            Some(tenant_id) => self.reseal_settings(&cipher, tenant_id).await?,
+            None => self.reseal_phones(&cipher).await?,
+        };
+
+        Ok(KeyRotation {
+            tenant_id,
+            version: cipher.active_version().unwrap_or_default(),
+            reencrypted,
+        })
+    }
+
+    /// Wraps every data key still wrapped with an older master key with the current one. Run
+    /// it after moving the old master key to ENCRYPTION_PREVIOUS_MASTER_KEYS; once it finds
+    /// nothing left to rewrap the old master key can be dropped.
+    pub async fn rewrap(&self) -> Result<usize> {
+        let wrapper = self.required_wrapper()?;
+        let mut conn = self.database.get_connection().await?;
+
+        let stale = data_keys::table
+            .filter(data_keys::master_key_id.ne(wrapper.key_id()))
+            .select(DataKeyRecord::as_select())
+            .load::<DataKeyRecord>(&mut conn)
+            .await?;
+        for record in &stale {
+            let wrapped = STANDARD.decode(&record.wrapped_key)?;
+            let key = wrapper.unwrap(&record.master_key_id, &wrapped).await?;
+            diesel::update(data_keys::table.find(record.id))
+                .set((
+                    data_keys::wrapped_key.eq(STANDARD.encode(wrapper.wrap(&key).await?)),
+                    data_keys::master_key_id.eq(wrapper.key_id()),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        if let Ok(mut cache) = self.cache.write() {
+            cache.clear();
+        }
+        Ok(stale.len())
+    }
+
+    /// Every key scope: the platform first, then each tenant.
+    pub async fn scopes(&self) -> Result<Vec<Option<Uuid>>> {
+        let mut conn = self.database.get_connection().await?;
+        let tenant_ids = tenants::table
+            .select(tenants::id)
+            .order(tenants::created_at.asc())
+            .load::<Uuid>(&mut conn)
+            .await?;
+        Ok(std::iter::once(None)
+            .chain(tenant_ids.into_iter().map(Some))
+            .collect())
+    }
+
+    // Private helper methods
+
+    fn cached(&self, tenant_id: Option<Uuid>) -> Option<FieldCipher> {
+        let cache = self.cache.read().ok()?;
+        cache
+            .get(&tenant_id)
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < CIPHER_CACHE_TTL)
+            .map(|(_, cipher)| cipher.clone())
+    }
+
+    fn forget(&self, tenant_id: Option<Uuid>) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.remove(&tenant_id);
+        }
+    }
+
+    // Retires the scope's active key and stores `wrapped_key` as its next version
+    async fn store_key(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Option<Uuid>,
+        wrapped_key: String,
+        master_key_id: String,
+    ) -> Result<()> {
+        let version = Self::records(conn, tenant_id)
+            .await?
+            .iter()
+            .map(|record| record.version)
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        match tenant_id {
+            Some(tenant_id) => {
+                diesel::update(
+                    data_keys::table
+                        .filter(data_keys::tenant_id.eq(tenant_id))
+                        .filter(data_keys::retired_at.is_null()),
+                )
+                .set(data_keys::retired_at.eq(Utc::now()))
+                .execute(conn)
+                .await?
+            }
+            None => {
+                diesel::update(
+                    data_keys::table
+                        .filter(data_keys::tenant_id.is_null())
+                        .filter(data_keys::retired_at.is_null()),
+                )
+                .set(data_keys::retired_at.eq(Utc::now()))
+                .execute(conn)
+                .await?
+            }
+        };
+
+        diesel::insert_into(data_keys::table)
+            .values(&NewDataKeyRecord {
+                tenant_id,
+                version,
+                wrapped_key,
+                master_key_id,
+            })
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    async fn connection(
+        &self,
+        tenant_id: Option<Uuid>,
+    ) -> Result<crate::services::DbConnection<'_>> {
+        let mut conn = self.database.get_connection().await?;
+        if let Some(tenant_id) = tenant_id {
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+        }
+        Ok(conn)
+    }
+
+    async fn records(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Option<Uuid>,
+    ) -> Result<Vec<DataKeyRecord>> {
+        let query = data_keys::table
+            .select(DataKeyRecord::as_select())
+            .order(data_keys::version.asc())
+            .into_boxed();
+        let query = match tenant_id {
+            Some(tenant_id) => query.filter(data_keys::tenant_id.eq(tenant_id)),
+            None => query.filter(data_keys::tenant_id.is_null()),
+        };
+        Ok(query.load::<DataKeyRecord>(conn).await?)
+    }
+
+    async fn load(&self, wrapper: &dyn KeyWrapper, tenant_id: Option<Uuid>) -> Result<FieldCipher> {
+        let mut conn = self.connection(tenant_id).await?;
+        let mut records = Self::records(&mut conn, tenant_id).await?;
+
+        if records.iter().all(|record| record.retired_at.is_some()) {
+            // Instances creating the first key at once meet on the unique index; the others
+            // read the key that won
+            let version = records.iter().map(|r| r.version).max().unwrap_or(0) + 1;
+            diesel::insert_into(data_keys::table)
+                .values(&NewDataKeyRecord {
+                    tenant_id,
+                    version,
+                    wrapped_key: STANDARD.encode(wrapper.wrap(&DataKey::generate()?).await?),
+                    master_key_id: wrapper.key_id().to_string(),
+                })
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await?;
+            records = Self::records(&mut conn, tenant_id).await?;
+        }
+
+        let mut keys = HashMap::with_capacity(records.len());
+        let mut active_version = None;
+        for record in records {
+            let wrapped = STANDARD.decode(&record.wrapped_key)?;
+            keys.insert(
+                record.version,
+                wrapper.unwrap(&record.master_key_id, &wrapped).await?,
+            );
+            if record.retired_at.is_none() {
+                active_version = Some(record.version);
+            }
+        }
+        let active_version =
+            active_version.ok_or_else(|| anyhow!("No active data key could be created"))?;
+        Ok(FieldCipher::new(tenant_id, active_version, keys))
+    }
+
+    async fn reseal_settings(&self, cipher: &FieldCipher, tenant_id: Uuid) -> Result<usize> {
+        let mut conn = self.connection(Some(tenant_id)).await?;
+        let settings = tenants::table
+            .find(tenant_id)
+            .select(tenants::settings)
+            .first::<Option<serde_json::Value>>(&mut conn)
+            .await
+            .optional()?
+            .flatten();
+        let Some(settings) = settings else {
+            return Ok(0);
+        };
+
+        let resealed = cipher.reseal_settings(&settings)?;
+        if resealed == settings {
+            return Ok(0);
+        }
+        diesel::update(tenants::table.find(tenant_id))
+            .set(tenants::settings.eq(resealed))
+            .execute(&mut conn)
+            .await?;
+        Ok(1)
+    }
+
+    async fn reseal_phones(&self, cipher: &FieldCipher) -> Result<usize> {
+        let mut conn = self.database.get_connection().await?;
+        let mut after = Uuid::nil();
+        let mut reencrypted = 0;
+
+        loop {
+            let rows = person::table
+                .filter(person::phone.is_not_null())
+                .filter(person::id.gt(after))
+                .order(person::id.asc())
+                .limit(ROTATION_BATCH_SIZE)
+                .select((person::id, person::phone.assume_not_null()))
+                .load::<(Uuid, String)>(&mut conn)
+                .await?;
+            let Some((last, _)) = rows.last() else {
+                break;
+            };
+            after = *last;
+
+            for (person_id, phone) in rows {
+                let resealed = cipher.reseal(&phone)?;
+                if resealed != phone {
+                    diesel::update(person::table.find(person_id))
+                        .set(person::phone.eq(resealed))
+                        .execute(&mut conn)
+                        .await?;
+                    reencrypted += 1;
+                }
+            }
+        }
+        Ok(reencrypted)
+    }
+}
@@ -0,0 +1,393 @@
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::models::{
+    CreateFeatureFlagRequest, FeatureFlag, FeatureFlagOverride, FeatureFlagResponse,
+    FlagEvaluation, FlagOverrideResponse, NewFeatureFlag, NewFeatureFlagOverride,
+    SetFlagOverrideRequest, UpdateFeatureFlagRequest,
+};
+use crate::schema::*;
+use crate::services::{AdminError, DatabaseService};
+use crate::utils::feature_flag::evaluate_flag;
+
+const DEFAULT_FLAG_CACHE_TTL_SECONDS: u64 = 30;
+
+// Every flag and override, loaded together; there are few enough to hold them all
+#[derive(Default)]
+struct FlagSnapshot {
+    flags: HashMap<String, FeatureFlag>,
+    overrides: HashMap<(Uuid, Uuid), bool>,
+}
+
+type CachedSnapshot = Option<(Instant, Arc<FlagSnapshot>)>;
+
+/// Feature flag checks, shared through [`crate::AppState`]:
+/// `state.flags.enabled("mqtt_bridge", tenant_id).await`.
+///
+/// Flags are read from the shared database and cached for FEATURE_FLAG_CACHE_TTL_SECONDS.
+/// Changes made through [`FeatureFlagService`] apply at once on the instance that made them and
+/// on other instances once the TTL passes. When flags cannot be read the last ones loaded are
+/// kept, and before any have loaded every flag is off.
+#[derive(Clone)]
+pub struct FeatureFlags {
+    database: DatabaseService,
+    ttl: Duration,
+    snapshot: Arc<RwLock<CachedSnapshot>>,
+    // Lets one request reload the flags while the others wait for it
+    reload: Arc<tokio::sync::Mutex<()>>,
+    // Bumped by each invalidation, so a reload that started before one is not cached
+    generation: Arc<AtomicU64>,
+}
+
+impl FeatureFlags {
+    /// A TTL of zero reads the flags on every check.
+    pub fn new(database: DatabaseService, ttl: Duration) -> Self {
+        Self {
+            database: database.shared(),
+            ttl,
+            snapshot: Arc::new(RwLock::new(None)),
+            reload: Arc::new(tokio::sync::Mutex::new(())),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Reads FEATURE_FLAG_CACHE_TTL_SECONDS (default 30).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let ttl = env::var("FEATURE_FLAG_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_FLAG_CACHE_TTL_SECONDS);
+        Self::new(database, Duration::from_secs(ttl))
+    }
+
+    pub async fn enabled(&self, key: &str, tenant_id: Uuid) -> bool {
+        self.evaluate(key, tenant_id).await.enabled
+    }
+
+    pub async fn evaluate(&self, key: &str, tenant_id: Uuid) -> FlagEvaluation {
+        let snapshot = self.snapshot().await;
+        let flag = snapshot.flags.get(key);
+        let tenant_override =
+            flag.and_then(|flag| snapshot.overrides.get(&(flag.id, tenant_id)).copied());
+        let (enabled, decision) = evaluate_flag(flag, tenant_override, tenant_id);
+
+        FlagEvaluation {
+            key: key.to_string(),
+            tenant_id,
+            enabled,
+            decision,
+        }
+    }
+
+    /// Makes the next check read the flags again.
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *self.snapshot.write().unwrap() = None;
+    }
+
+    fn cached(&self) -> Option<Arc<FlagSnapshot>> {
+        self.snapshot
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < self.ttl)
+            .map(|(_, snapshot)| snapshot.clone())
+    }
+
+    async fn snapshot(&self) -> Arc<FlagSnapshot> {
+        if let Some(snapshot) = self.cached() {
+            return snapshot;
+        }
+
+        let _reload = self.reload.lock().await;
+        // Another request may have reloaded the flags while this one waited
+        if let Some(snapshot) = self.cached() {
+            return snapshot;
+        }
+
+        let generation = AtomicU64::load(&self.generation, Ordering::SeqCst);
+        let snapshot = match self.load().await {
+            Ok(snapshot) => Arc::new(snapshot),
+            Err(e) => {
+                tracing::warn!("Feature flags not reloaded: {}", e);
+                self.snapshot
+                    .read()
+                    .unwrap()
+                    .as_ref()
+                    .map(|(_, snapshot)| snapshot.clone())
+                    .unwrap_or_default()
+            }
+        };
+        // A failed reload is retried once the TTL passes rather than on every check
+        let mut cached = self.snapshot.write().unwrap();
+        if AtomicU64::load(&self.generation, Ordering::SeqCst) == generation {
+            *cached = Some((Instant::now(), snapshot.clone()));
+        }
+        snapshot
+    }
+
+    async fn load(&self) -> Result<FlagSnapshot> {
+        let mut conn = self.database.get_connection().await?;
+
+        let flags = feature_flags::table
+            .select(FeatureFlag::as_select())
+            .load::<FeatureFlag>(&mut conn)
+            .await?;
+        // Overrides of every tenant, so no tenant context is set
+        let overrides = feature_flag_overrides::table
+            .select((
+                feature_flag_overrides::flag_id,
+                feature_flag_overrides::tenant_id,
+                feature_flag_overrides::enabled,
+            ))
+            .load::<(Uuid, Uuid, bool)>(&mut conn)
+            .await?;
+
+        Ok(FlagSnapshot {
+            flags: flags
+                .into_iter()
+                .map(|flag| (flag.key.clone(), flag))
+                .collect(),
+            overrides: overrides
+                .into_iter()
+                .map(|(flag_id, tenant_id, enabled)| ((flag_id, tenant_id), enabled))
+                .collect(),
+        })
+    }
+}
+
+/// Management of feature flags and their per-tenant overrides, for platform operators.
+pub struct FeatureFlagService {
+    // Flags are platform-wide, so they are always kept in the shared database
+    shared: DatabaseService,
+    flags: Option<FeatureFlags>,
+}
+
+impl FeatureFlagService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+            flags: None,
+        }
+    }
+
+    /// Reloads these flags after each change so it applies at once.
+    pub fn with_flags(mut self, flags: FeatureFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    // Feature flag operations
+
+    pub async fn list_flags(&self) -> Result<Vec<FeatureFlagResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let flags = feature_flags::table
+            .order(feature_flags::key.asc())
+            .select(FeatureFlag::as_select())
+            .load::<FeatureFlag>(&mut conn)
+            .await?;
+        let overrides = feature_flag_overrides::table
+            .order(feature_flag_overrides::created_at.asc())
+            .select(FeatureFlagOverride::as_select())
+            .load::<FeatureFlagOverride>(&mut conn)
+            .await?;
+
+        let mut by_flag: HashMap<Uuid, Vec<FlagOverrideResponse>> = HashMap::new();
+        for tenant_override in overrides {
+            by_flag
+                .entry(tenant_override.flag_id)
+                .or_default()
+                .push(tenant_override.into());
+        }
+
+        Ok(flags
+            .into_iter()
+            .map(|flag| FeatureFlagResponse {
+                overrides: by_flag.remove(&flag.id).unwrap_or_default(),
+                flag,
+            })
+            .collect())
+    }
+
+    pub async fn get_flag(&self, key: &str) -> Result<Option<FeatureFlagResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        match Self::find_flag(&mut conn, key).await? {
+            Some(flag) => Ok(Some(Self::with_overrides(&mut conn, flag).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create_flag(
+        &self,
+        request: CreateFeatureFlagRequest,
+    ) -> Result<FeatureFlagResponse> {
+        let mut conn = self.shared.get_connection().await?;
+
+        if Self::find_flag(&mut conn, &request.key).await?.is_some() {
+            return Err(AdminError::FlagExists(request.key.clone()).into());
+        }
+
+        let flag = diesel::insert_into(feature_flags::table)
+            .values(&NewFeatureFlag {
+                key: request.key,
+                description: request.description,
+                enabled: request.enabled.unwrap_or(true),
+                rollout_percentage: request.rollout_percentage.unwrap_or(0),
+            })
+            .returning(FeatureFlag::as_returning())
+            .get_result::<FeatureFlag>(&mut conn)
+            .await?;
+
+        self.reload();
+        Ok(FeatureFlagResponse {
+            flag,
+            overrides: Vec::new(),
+        })
+    }
+
+    pub async fn update_flag(
+        &self,
+        key: &str,
+        request: UpdateFeatureFlagRequest,
+    ) -> Result<Option<FeatureFlagResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let Some(flag) = Self::find_flag(&mut conn, key).await? else {
+            return Ok(None);
+        };
+
+        let flag = diesel::update(feature_flags::table.find(flag.id))
+            .set((
+                feature_flags::description.eq(request.description.or(flag.description)),
+                feature_flags::enabled.eq(request.enabled.unwrap_or(flag.enabled)),
+                feature_flags::rollout_percentage.eq(request
+                    .rollout_percentage
+                    .unwrap_or(flag.rollout_percentage)),
+            ))
+            .returning(FeatureFlag::as_returning())
+            .get_result::<FeatureFlag>(&mut conn)
+            .await?;
+
+        self.reload();
+        Ok(Some(Self::with_overrides(&mut conn, flag).await?))
+    }
+
+    /// Deletes a flag with its overrides; code checking it sees it as off from then on.
+    /// Returns `false` when there is no such flag.
+    pub async fn delete_flag(&self, key: &str) -> Result<bool> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let deleted = diesel::delete(feature_flags::table.filter(feature_flags::key.eq(key)))
+            .execute(&mut conn)
+            .await?;
+
+        self.reload();
+        Ok(deleted > 0)
+    }
+
+    /// Turns a flag on or off for one tenant, whatever its rollout percentage says.
+    /// `Ok(None)` when there is no such flag.
+    pub async fn set_override(
+        &self,
+        key: &str,
+        tenant_id: Uuid,
+        request: SetFlagOverrideRequest,
+    ) -> Result<Option<FeatureFlagResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let Some(flag) = Self::find_flag(&mut conn, key).await? else {
+            return Ok(None);
+        };
+        let tenant_exists = diesel::select(diesel::dsl::exists(tenants::table.find(tenant_id)))
+            .get_result::<bool>(&mut conn)
+            .await?;
+        if !tenant_exists {
+            return Err(AdminError::InvalidFlagOverride(format!(
+                "tenant {} does not exist",
+                tenant_id
+            ))
+            .into());
+        }
+
+        diesel::insert_into(feature_flag_overrides::table)
+            .values(&NewFeatureFlagOverride {
+                flag_id: flag.id,
+                tenant_id,
+                enabled: request.enabled,
+            })
+            .on_conflict((
+                feature_flag_overrides::flag_id,
+                feature_flag_overrides::tenant_id,
+            ))
+            .do_update()
+            .set(feature_flag_overrides::enabled.eq(request.enabled))
+            .execute(&mut conn)
+            .await?;
+
+        self.reload();
+        Ok(Some(Self::with_overrides(&mut conn, flag).await?))
+    }
+
+    /// Puts a tenant back on the flag's rollout percentage. Returns `false` when the flag or
+    /// the override does not exist.
+    pub async fn remove_override(&self, key: &str, tenant_id: Uuid) -> Result<bool> {
+        let mut conn = self.shared.get_connection().await?;
+
+        let Some(flag) = Self::find_flag(&mut conn, key).await? else {
+            return Ok(false);
+        };
+        let deleted = diesel::delete(
+            feature_flag_overrides::table
+                .filter(feature_flag_overrides::flag_id.eq(flag.id))
+                .filter(feature_flag_overrides::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        self.reload();
+        Ok(deleted > 0)
+    }
+
+    // Private helper methods
+
+    fn reload(&self) {
+        if let Some(flags) = &self.flags {
+            flags.invalidate();
+        }
+    }
+
+    async fn find_flag(conn: &mut AsyncPgConnection, key: &str) -> Result<Option<FeatureFlag>> {
+        Ok(feature_flags::table
+            .filter(feature_flags::key.eq(key))
+            .select(FeatureFlag::as_select())
+            .first::<FeatureFlag>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn with_overrides(
+        conn: &mut AsyncPgConnection,
+        flag: FeatureFlag,
+    ) -> Result<FeatureFlagResponse> {
+        let overrides = feature_flag_overrides::table
+            .filter(feature_flag_overrides::flag_id.eq(flag.id))
+            .order(feature_flag_overrides::created_at.asc())
+            .select(FeatureFlagOverride::as_select())
+            .load::<FeatureFlagOverride>(conn)
+            .await?;
+
+        Ok(FeatureFlagResponse {
+            flag,
+            overrides: overrides.into_iter().map(Into::into).collect(),
+        })
+    }
+}
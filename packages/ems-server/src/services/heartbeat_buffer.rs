@@ -0,0 +1,339 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Array, Jsonb, Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::models::{
+    HeartbeatRequest, MachineStatus, NewMachineHeartbeatEvent, NewMachineTelemetry,
+};
+use crate::schema::{machine_telemetry, machines};
+use crate::services::{DatabaseService, MachineAlertService, ProductionService, SpcService};
+use crate::utils::diagnostics::track_run;
+use crate::utils::heartbeat_buffer::{BufferedMachineState, HeartbeatBatch, HeartbeatQueue};
+
+// Entries each tenant may have waiting before heartbeats are written directly again
+const DEFAULT_TENANT_CAPACITY: usize = 20_000;
+// Milliseconds between flushes
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 1_000;
+// Telemetry readings written in one insert
+const TELEMETRY_INSERT_CHUNK: usize = 1_000;
+// How long a machine found in service is taken to still be, before it is looked up again
+const IN_SERVICE_TTL: Duration = Duration::from_secs(60);
+// Machines remembered as in service before expired entries are dropped
+const MAX_IN_SERVICE_MACHINES: usize = 100_000;
+
+/// Buffers the machine state, telemetry readings and SPC, production and alert processing of
+/// heartbeats in per-tenant queues and writes them in batches from a background task, so
+/// machines reporting every second do not each cost a transaction. A tenant's queue filling up
+/// lengthens the reporting interval in its heartbeat responses; once it is full, heartbeats are
+/// written directly again. Machines found in service are remembered for a minute, so their
+/// heartbeats are queued without looking them up.
+#[derive(Clone)]
+pub struct HeartbeatBuffer {
+    queues: Arc<Mutex<HashMap<Uuid, HeartbeatQueue>>>,
+    in_service: Arc<Mutex<HashMap<(Uuid, Uuid), Instant>>>,
+    // Woken when a queue is three quarters full, so it is flushed before the next tick
+    flush_now: Arc<Notify>,
+    capacity: usize,
+}
+
+impl HeartbeatBuffer {
+    /// Starts the task flushing the buffer, configured from HEARTBEAT_BUFFER_CAPACITY (entries
+    /// per tenant, default 20000) and HEARTBEAT_FLUSH_INTERVAL_MS (default 1000).
+    pub fn spawn(database: DatabaseService) -> Self {
+        let capacity = env::var("HEARTBEAT_BUFFER_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_TENANT_CAPACITY);
+        let flush_interval_ms = env::var("HEARTBEAT_FLUSH_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|ms| *ms > 0)
+            .unwrap_or(DEFAULT_FLUSH_INTERVAL_MS);
+
+        let buffer = Self {
+            queues: Arc::new(Mutex::new(HashMap::new())),
+            in_service: Arc::new(Mutex::new(HashMap::new())),
+            flush_now: Arc::new(Notify::new()),
+            capacity,
+        };
+
+        let worker = buffer.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(flush_interval_ms));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = worker.flush_now.notified() => {}
+                }
+                if let Err(e) = track_run("heartbeat_buffer", worker.flush(&database)).await {
+                    tracing::error!("Heartbeat buffer flush failed: {}", e);
+                }
+            }
+        });
+
+        buffer
+    }
+
+    /// Queues the heartbeat's machine state for the next flush. Returns false, leaving the write
+    /// to the caller, when the tenant's queue is full.
+    pub fn push_state(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: &HeartbeatRequest,
+        received_at: DateTime<Utc>,
+    ) -> bool {
+        let state = BufferedMachineState::new(machine_id, request, received_at);
+        self.with_queue(tenant_id, |queue| queue.push_state(state))
+    }
+
+    /// Queues the heartbeat to be fed to SPC, production counts and alert rules with the next
+    /// flush. Returns false, leaving that to the caller, when the tenant's queue is full.
+    pub fn push_heartbeat(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: &HeartbeatRequest,
+        received_at: DateTime<Utc>,
+    ) -> bool {
+        let heartbeat = BufferedMachineState::new(machine_id, request, received_at);
+        self.with_queue(tenant_id, |queue| queue.push_heartbeat(heartbeat))
+    }
+
+    /// Queues telemetry readings for the next flush, handing them back when they do not fit in
+    /// the tenant's queue.
+    pub fn push_readings(
+        &self,
+        tenant_id: Uuid,
+        readings: Vec<NewMachineTelemetry>,
+    ) -> Result<(), Vec<NewMachineTelemetry>> {
+        self.with_queue(tenant_id, |queue| queue.push_readings(readings))
+    }
+
+    /// How many times longer the tenant's machines should wait between heartbeats while its
+    /// queue fills up.
+    pub fn load_factor(&self, tenant_id: Uuid) -> i32 {
+        let queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        queues
+            .get(&tenant_id)
+            .map_or(1, HeartbeatQueue::backoff_factor)
+    }
+
+    /// Whether the machine was found in service within the last minute.
+    pub fn known_in_service(&self, tenant_id: Uuid, machine_id: Uuid) -> bool {
+        let in_service = self.in_service.lock().unwrap_or_else(|e| e.into_inner());
+        in_service
+            .get(&(tenant_id, machine_id))
+            .is_some_and(|checked_at| checked_at.elapsed() < IN_SERVICE_TTL)
+    }
+
+    pub fn mark_in_service(&self, tenant_id: Uuid, machine_id: Uuid) {
+        let mut in_service = self.in_service.lock().unwrap_or_else(|e| e.into_inner());
+        if in_service.len() >= MAX_IN_SERVICE_MACHINES {
+            in_service.retain(|_, checked_at| checked_at.elapsed() < IN_SERVICE_TTL);
+        }
+        in_service.insert((tenant_id, machine_id), Instant::now());
+    }
+
+    /// Forgets a machine deleted or taken out of service, so its next heartbeat looks it up.
+    pub fn forget_machine(&self, tenant_id: Uuid, machine_id: Uuid) {
+        let mut in_service = self.in_service.lock().unwrap_or_else(|e| e.into_inner());
+        in_service.remove(&(tenant_id, machine_id));
+    }
+
+    // Runs `push` on the tenant's queue, waking the flush task once the queue is filling up
+    fn with_queue<T>(&self, tenant_id: Uuid, push: impl FnOnce(&mut HeartbeatQueue) -> T) -> T {
+        let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = queues
+            .entry(tenant_id)
+            .or_insert_with(|| HeartbeatQueue::new(self.capacity));
+        let pushed = push(queue);
+        if queue.backoff_factor() > 1 {
+            self.flush_now.notify_one();
+        }
+        pushed
+    }
+
+    // Writes every tenant's queued heartbeats, returning how many entries were written.
+    // A tenant whose write fails loses that batch; its machines report again shortly.
+    async fn flush(&self, database: &DatabaseService) -> Result<usize> {
+        let pending: Vec<(Uuid, HeartbeatBatch)> = {
+            let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+            queues
+                .iter_mut()
+                .filter(|(_, queue)| !queue.is_empty())
+                .map(|(tenant_id, queue)| (*tenant_id, queue.take()))
+                .collect()
+        };
+
+        let mut written = 0;
+        for (tenant_id, batch) in pending {
+            let count = batch.states.len() + batch.heartbeats.len() + batch.readings.len();
+            match DatabaseService::scope_tenant(tenant_id, Self::write(database, tenant_id, batch))
+                .await
+            {
+                Ok(()) => written += count,
+                Err(e) => tracing::error!(
+                    "Failed to write {} buffered heartbeat entries for tenant {}: {}",
+                    count,
+                    tenant_id,
+                    e
+                ),
+            }
+        }
+
+        Ok(written)
+    }
+
+    // Writes one tenant's machine states and readings in one transaction, then feeds its
+    // heartbeats to SPC, production counts and alert rules in another
+    async fn write(
+        database: &DatabaseService,
+        tenant_id: Uuid,
+        batch: HeartbeatBatch,
+    ) -> Result<()> {
+        let HeartbeatBatch {
+            states,
+            heartbeats,
+            readings,
+        } = batch;
+
+        database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    if !states.is_empty() {
+                        let mut ids = Vec::with_capacity(states.len());
+                        let mut statuses = Vec::with_capacity(states.len());
+                        let mut actions = Vec::with_capacity(states.len());
+                        let mut payloads: Vec<Option<Value>> = Vec::with_capacity(states.len());
+                        let mut metadata: Vec<Option<Value>> = Vec::with_capacity(states.len());
+                        let mut last_heartbeats = Vec::with_capacity(states.len());
+                        for state in states {
+                            ids.push(state.machine_id);
+                            statuses.push(state.status);
+                            actions.push(state.action);
+                            payloads.push(state.payload);
+                            metadata.push(state.metadata);
+                            last_heartbeats.push(state.last_heartbeat);
+                        }
+
+                        // Decommissioned machines are frozen, and a state older than the stored
+                        // one is skipped
+                        diesel::sql_query(
+                            r#"
+                            UPDATE machines AS m
+                            SET status = u.status,
+                                action = u.action,
+                                payload = u.payload,
+                                metadata = u.metadata,
+                                last_heartbeat = u.last_heartbeat
+                            FROM unnest($2, $3, $4, $5, $6, $7)
+                                AS u(id, status, action, payload, metadata, last_heartbeat)
+                            WHERE m.id = u.id
+                              AND m.tenant_id = $1
+                              AND m.status <> 'decommissioned'
+                              AND (m.last_heartbeat IS NULL OR m.last_heartbeat <= u.last_heartbeat)
+                            "#,
+                        )
+                        .bind::<SqlUuid, _>(tenant_id)
+                        .bind::<Array<SqlUuid>, _>(ids)
+                        .bind::<Array<Text>, _>(statuses)
+                        .bind::<Array<Nullable<Text>>, _>(actions)
+                        .bind::<Array<Nullable<Jsonb>>, _>(payloads)
+                        .bind::<Array<Nullable<Jsonb>>, _>(metadata)
+                        .bind::<Array<Timestamptz>, _>(last_heartbeats)
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    for chunk in readings.chunks(TELEMETRY_INSERT_CHUNK) {
+                        diesel::insert_into(machine_telemetry::table)
+                            .values(chunk)
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(())
+                })
+            })
+            .await?;
+
+        if heartbeats.is_empty() {
+            return Ok(());
+        }
+        database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move { Self::process(conn, tenant_id, heartbeats).await })
+            })
+            .await
+    }
+
+    // Heartbeats of machines deleted or taken out of service since they were queued are dropped
+    async fn process(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        mut heartbeats: Vec<BufferedMachineState>,
+    ) -> Result<()> {
+        let machine_ids: HashSet<Uuid> = heartbeats.iter().map(|h| h.machine_id).collect();
+        let in_service: HashSet<Uuid> = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(&machine_ids))
+            .filter(machines::status.ne(MachineStatus::Decommissioned.to_string()))
+            .select(machines::id)
+            .load::<Uuid>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        heartbeats.retain(|heartbeat| in_service.contains(&heartbeat.machine_id));
+        heartbeats.sort_by_key(|heartbeat| heartbeat.last_heartbeat);
+
+        // Characteristics measured by the machines read their values from the payloads
+        let measured: Vec<(Uuid, &Value, DateTime<Utc>)> = heartbeats
+            .iter()
+            .filter_map(|h| Some((h.machine_id, h.payload.as_ref()?, h.last_heartbeat)))
+            .collect();
+        SpcService::apply_heartbeat_measurements(conn, tenant_id, &measured).await?;
+
+        // Parts counts are deltas on the previous report, so they are applied in order
+        for heartbeat in &heartbeats {
+            if let Some(payload) = &heartbeat.payload {
+                ProductionService::apply_heartbeat_counts(
+                    conn,
+                    tenant_id,
+                    heartbeat.machine_id,
+                    payload,
+                    heartbeat.metadata.as_ref(),
+                    heartbeat.last_heartbeat,
+                )
+                .await?;
+            }
+        }
+
+        let events = heartbeats
+            .into_iter()
+            .map(|heartbeat| NewMachineHeartbeatEvent {
+                tenant_id,
+                machine_id: heartbeat.machine_id,
+                status: heartbeat.status,
+                payload: heartbeat.payload,
+                metadata: heartbeat.metadata,
+                received_at: heartbeat.last_heartbeat,
+            })
+            .collect();
+        MachineAlertService::queue_heartbeats(conn, tenant_id, events).await?;
+
+        Ok(())
+    }
+}
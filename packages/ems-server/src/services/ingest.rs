@@ -0,0 +1,526 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{
+    CallerContext, CreateIngestSourceRequest, IngestEvent, IngestEventResponse, IngestEventStatus,
+    IngestMachineHeartbeat, IngestOrderUpdate, IngestResponse, IngestSource, IngestSourceResponse,
+    IngestSourceTokenResponse, IngestTarget, ListIngestEventsQuery, NewIngestEvent,
+    NewIngestSource, Permission, UpdateIngestSourceRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, MachineService, OrderService, TenantCache, TenantService};
+use crate::utils::auth::AuthUtils;
+use crate::utils::ingest::{check_fields, parse_field_schema, render_template};
+use crate::utils::AppError;
+
+/// Errors from [`IngestService`] that are the pushing system's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum IngestError {
+    #[error("Ingest source not found")]
+    SourceNotFound,
+
+    #[error("Tenant {0} is not active")]
+    TenantNotActive(Uuid),
+
+    #[error("Invalid ingest payload: {0}")]
+    InvalidPayload(String),
+}
+
+impl From<IngestError> for AppError {
+    fn from(error: IngestError) -> Self {
+        match error {
+            IngestError::SourceNotFound => AppError::NotFound(error.to_string()),
+            IngestError::TenantNotActive(_) => AppError::Authorization(error.to_string()),
+            IngestError::InvalidPayload(_) => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+/// External systems pushing events to the generic ingest endpoint. Sources and their event log
+/// live in the shared database, since a source token is resolved before the tenant is known;
+/// the events themselves are applied in the tenant's database.
+pub struct IngestService {
+    database: DatabaseService,
+    shared: DatabaseService,
+    tenants: TenantService,
+}
+
+impl IngestService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+            tenants: TenantService::new(database.clone()),
+            database,
+        }
+    }
+
+    /// Serves tenant lookups for incoming events from the cache.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.tenants = self.tenants.with_cache(cache);
+        self
+    }
+
+    // Source methods
+
+    /// Creates a source and returns it with its token, which is not shown again.
+    pub async fn create_source(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateIngestSourceRequest,
+    ) -> Result<IngestSourceTokenResponse> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let token = Self::generate_token();
+        let source: IngestSource = diesel::insert_into(ingest_sources::table)
+            .values(&NewIngestSource {
+                tenant_id,
+                name: request.name,
+                token_hash: AuthUtils::hash_token(&token),
+                target: request.target.to_string(),
+                field_schema: request
+                    .field_schema
+                    .unwrap_or_else(|| Value::Object(Default::default())),
+                template: request.template,
+                created_by_id: Some(caller.person_id),
+            })
+            .returning(IngestSource::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(IngestSourceTokenResponse {
+            source: source.into(),
+            token,
+        })
+    }
+
+    pub async fn list_sources(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Vec<IngestSourceResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let sources = ingest_sources::table
+            .filter(ingest_sources::tenant_id.eq(tenant_id))
+            .order(ingest_sources::name.asc())
+            .select(IngestSource::as_select())
+            .load::<IngestSource>(&mut conn)
+            .await?;
+
+        Ok(sources.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_source(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        source_id: Uuid,
+    ) -> Result<Option<IngestSourceResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_source(&mut conn, tenant_id, source_id)
+            .await?
+            .map(Into::into))
+    }
+
+    pub async fn update_source(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        source_id: Uuid,
+        request: UpdateIngestSourceRequest,
+    ) -> Result<Option<IngestSourceResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        self.shared
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let target = ingest_sources::table
+                        .filter(ingest_sources::id.eq(source_id))
+                        .filter(ingest_sources::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(ingest_sources::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(field_schema) = &request.field_schema {
+                        diesel::update(target)
+                            .set(ingest_sources::field_schema.eq(field_schema))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(template) = &request.template {
+                        diesel::update(target)
+                            .set(ingest_sources::template.eq(template))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(ingest_sources::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(Self::find_source(conn, tenant_id, source_id)
+                        .await?
+                        .map(Into::into))
+                })
+            })
+            .await
+    }
+
+    /// Replaces the source's token; the old token stops working at once.
+    pub async fn rotate_token(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        source_id: Uuid,
+    ) -> Result<Option<IngestSourceTokenResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let token = Self::generate_token();
+        let source = diesel::update(
+            ingest_sources::table
+                .filter(ingest_sources::id.eq(source_id))
+                .filter(ingest_sources::tenant_id.eq(tenant_id)),
+        )
+        .set(ingest_sources::token_hash.eq(AuthUtils::hash_token(&token)))
+        .returning(IngestSource::as_returning())
+        .get_result::<IngestSource>(&mut conn)
+        .await
+        .optional()?;
+
+        Ok(source.map(|source| IngestSourceTokenResponse {
+            source: source.into(),
+            token,
+        }))
+    }
+
+    pub async fn delete_source(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        source_id: Uuid,
+    ) -> Result<bool> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            ingest_sources::table
+                .filter(ingest_sources::id.eq(source_id))
+                .filter(ingest_sources::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Events received from the source, latest first. Returns `None` when the source does not
+    /// exist.
+    pub async fn list_events(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        source_id: Uuid,
+        query: ListIngestEventsQuery,
+    ) -> Result<Option<Vec<IngestEventResponse>>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_source(&mut conn, tenant_id, source_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut statement = ingest_events::table
+            .filter(ingest_events::tenant_id.eq(tenant_id))
+            .filter(ingest_events::source_id.eq(source_id))
+            .into_boxed();
+        if let Some(status) = query.status {
+            statement = statement.filter(ingest_events::status.eq(status.to_string()));
+        }
+
+        let events = statement
+            .order(ingest_events::received_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(IngestEvent::as_select())
+            .load::<IngestEvent>(&mut conn)
+            .await?;
+
+        Ok(Some(events.into_iter().map(Into::into).collect()))
+    }
+
+    // Ingest methods
+
+    /// Applies an event pushed by the source with the token: the payload is checked against
+    /// the source's field schema, rendered through its template and passed to the target
+    /// service. Every event is logged with its outcome, rejected ones included.
+    pub async fn receive(&self, token: &str, payload: Value) -> Result<IngestResponse> {
+        let source = self
+            .source_for_token(token)
+            .await?
+            .ok_or(IngestError::SourceNotFound)?;
+
+        // Events for suspended tenants are refused without being logged
+        let tenant = self
+            .tenants
+            .get_tenant_by_id(source.tenant_id)
+            .await?
+            .filter(|tenant| tenant.is_active.unwrap_or(false))
+            .ok_or(IngestError::TenantNotActive(source.tenant_id))?;
+        if let Some(database_url) = &tenant.database_url {
+            if self.database.dedicated_databases_enabled() {
+                self.database
+                    .register_tenant_database(tenant.id, database_url)
+                    .await?;
+            }
+        }
+
+        let outcome = DatabaseService::scope_tenant(tenant.id, self.apply(&source, &payload)).await;
+        let (status, error, entity_id) = match &outcome {
+            Ok(entity_id) => (IngestEventStatus::Processed, None, Some(*entity_id)),
+            Err(e) if matches!(e.downcast_ref(), Some(IngestError::InvalidPayload(_))) => {
+                (IngestEventStatus::Rejected, Some(e.to_string()), None)
+            }
+            Err(e) => (IngestEventStatus::Failed, Some(e.to_string()), None),
+        };
+        let event_id = self
+            .log_event(&source, status, error, payload, entity_id)
+            .await?;
+
+        Ok(IngestResponse {
+            event_id,
+            target: IngestTarget::try_from(source.target)
+                .map_err(|e| anyhow!("Ingest source misconfigured: {}", e))?,
+            entity_id: outcome?,
+        })
+    }
+
+    // Helper methods
+
+    fn generate_token() -> String {
+        format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+    }
+
+    async fn find_source(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        source_id: Uuid,
+    ) -> Result<Option<IngestSource>> {
+        Ok(ingest_sources::table
+            .filter(ingest_sources::id.eq(source_id))
+            .filter(ingest_sources::tenant_id.eq(tenant_id))
+            .select(IngestSource::as_select())
+            .first::<IngestSource>(conn)
+            .await
+            .optional()?)
+    }
+
+    // The active source the token belongs to, in any tenant
+    async fn source_for_token(&self, token: &str) -> Result<Option<IngestSource>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // The token decides the tenant, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        Ok(ingest_sources::table
+            .filter(ingest_sources::token_hash.eq(AuthUtils::hash_token(token)))
+            .filter(ingest_sources::is_active.eq(true))
+            .select(IngestSource::as_select())
+            .first::<IngestSource>(&mut conn)
+            .await
+            .optional()?)
+    }
+
+    // Checks, renders and routes the payload, returning the order or machine it was applied to
+    async fn apply(&self, source: &IngestSource, payload: &Value) -> Result<Uuid> {
+        let rules = parse_field_schema(&source.field_schema)
+            .map_err(|e| anyhow!("Ingest source misconfigured: {}", e))?;
+        let problems = check_fields(payload, &rules);
+        if !problems.is_empty() {
+            return Err(IngestError::InvalidPayload(problems.join(", ")).into());
+        }
+        let rendered = render_template(&source.template, payload);
+
+        match IngestTarget::try_from(source.target.clone())
+            .map_err(|e| anyhow!("Ingest source misconfigured: {}", e))?
+        {
+            IngestTarget::OrderUpdate => {
+                let request: IngestOrderUpdate = serde_json::from_value(rendered)
+                    .map_err(|e| IngestError::InvalidPayload(e.to_string()))?;
+                request
+                    .validate()
+                    .map_err(|e| IngestError::InvalidPayload(e.to_string()))?;
+
+                let order_id = self
+                    .resolve_order(
+                        source.tenant_id,
+                        request.order_id,
+                        request.order_number.as_deref(),
+                    )
+                    .await?;
+                OrderService::new(self.database.clone())
+                    .update_order(source.tenant_id, order_id, request.update)
+                    .await?;
+                Ok(order_id)
+            }
+            IngestTarget::MachineHeartbeat => {
+                let request: IngestMachineHeartbeat = serde_json::from_value(rendered)
+                    .map_err(|e| IngestError::InvalidPayload(e.to_string()))?;
+                request
+                    .validate()
+                    .map_err(|e| IngestError::InvalidPayload(e.to_string()))?;
+
+                let mut conn = self.database.get_connection().await?;
+
+                // Set tenant context for RLS
+                conn.batch_execute(&format!(
+                    "SET app.current_tenant_id = '{}'",
+                    source.tenant_id
+                ))
+                .await?;
+
+                let found: i64 = machines::table
+                    .filter(machines::id.eq(request.machine_id))
+                    .filter(machines::tenant_id.eq(source.tenant_id))
+                    .count()
+                    .get_result(&mut conn)
+                    .await?;
+                drop(conn);
+                if found == 0 {
+                    return Err(IngestError::InvalidPayload(format!(
+                        "machine {} not found",
+                        request.machine_id
+                    ))
+                    .into());
+                }
+
+                MachineService::new(self.database.clone())
+                    .update_heartbeat(source.tenant_id, request.machine_id, request.heartbeat)
+                    .await?;
+                Ok(request.machine_id)
+            }
+        }
+    }
+
+    // The order an update is for, by id or by order number
+    async fn resolve_order(
+        &self,
+        tenant_id: Uuid,
+        order_id: Option<Uuid>,
+        order_number: Option<&str>,
+    ) -> Result<Uuid> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut statement = orders::table
+            .filter(orders::tenant_id.eq(tenant_id))
+            .into_boxed();
+        statement = match (order_id, order_number) {
+            (Some(order_id), _) => statement.filter(orders::id.eq(order_id)),
+            (None, Some(order_number)) => statement.filter(orders::order_number.eq(order_number)),
+            (None, None) => {
+                return Err(IngestError::InvalidPayload(
+                    "order_id or order_number is required".to_string(),
+                )
+                .into())
+            }
+        };
+
+        statement
+            .select(orders::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| IngestError::InvalidPayload("order not found".to_string()).into())
+    }
+
+    async fn log_event(
+        &self,
+        source: &IngestSource,
+        status: IngestEventStatus,
+        error: Option<String>,
+        payload: Value,
+        entity_id: Option<Uuid>,
+    ) -> Result<Uuid> {
+        self.shared
+            .with_tenant_tx::<_, anyhow::Error, _>(source.tenant_id, |conn| {
+                Box::pin(async move {
+                    let event_id = diesel::insert_into(ingest_events::table)
+                        .values(&NewIngestEvent {
+                            tenant_id: source.tenant_id,
+                            source_id: source.id,
+                            status: status.to_string(),
+                            error,
+                            payload,
+                            entity_id,
+                        })
+                        .returning(ingest_events::id)
+                        .get_result::<Uuid>(conn)
+                        .await?;
+
+                    diesel::update(ingest_sources::table.filter(ingest_sources::id.eq(source.id)))
+                        .set(ingest_sources::last_received_at.eq(Utc::now()))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(event_id)
+                })
+            })
+            .await
+    }
+}
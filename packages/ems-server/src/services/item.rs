@@ -1,17 +1,164 @@
-use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{sql, Eq, InnerJoinOn, IntoBoxed};
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use diesel::sql_types::{Bool, Jsonb, Text};
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    BomItemResponse, CreateBomItemRequest, CreateItemIdResponse, CreateItemRequest,
+    BomItemResponse, CreateBomItemRequest, CreateItemIdResponse, CreateItemRequest, DataVersion,
     FinishedGoodsItemResponse, InventoryItem, Item, ItemBom, ItemContext, ItemLifecycle,
-    ItemResponse, ItemStatus, ItemSummary, NewInventoryItem, NewItem, NewItemBom,
-    StoreItemResponse, UpdateBomItemRequest, UpdateItemRequest, VendorItemResponse,
+    ItemResponse, ItemStatus, ItemSummary, NewInventoryItem, NewItem, NewItemBom, NumberingEntity,
+    Quantity, RuleEntity, RuleViolations, StoreItemResponse, UpdateBomItemRequest,
+    UpdateItemRequest, VendorItemResponse,
 };
 use crate::schema::*;
-use crate::services::DatabaseService;
+use crate::services::{
+    DatabaseService, NumberingError, NumberingService, StorageRequestFailed, UomError, UomService,
+    ValidationRuleService,
+};
+use crate::utils::parametric::{AttributeOp, ParametricSearch};
+use crate::utils::AppError;
+
+/// Errors from [`ItemService`], so handlers can tell a missing item from a rejected unit
+#[derive(Error, Debug)]
+pub enum ItemError {
+    #[error("Item not found")]
+    NotFound,
+
+    #[error("{0}")]
+    InvalidUnit(String),
+
+    #[error("{0}")]
+    Unnumbered(String),
+
+    #[error("Invalid BOM import: {0}")]
+    InvalidBomImport(String),
+
+    #[error("Invalid movement: {0}")]
+    InvalidMovement(String),
+
+    #[error("Insufficient stock: {0}")]
+    InsufficientStock(String),
+
+    #[error("{0} cannot reserve stock: status is {1}")]
+    ReferenceClosed(&'static str, String),
+
+    #[error("Reservations must reference an order or a job")]
+    InvalidReference,
+
+    #[error("Reservation cannot be released: status is {0}")]
+    NotReleasable(String),
+
+    #[error("Consignment owner not found: {0}")]
+    OwnerNotFound(Uuid),
+
+    #[error("Asset storage not configured")]
+    StorageNotConfigured,
+
+    #[error("Item has no datasheet")]
+    NoDatasheet,
+
+    #[error("{0}")]
+    InvalidDatasheetUrl(String),
+
+    #[error("Datasheet too large: at most {0} bytes allowed")]
+    DatasheetTooLarge(usize),
+
+    #[error("Datasheet failed content scan: {0}")]
+    DatasheetInfected(String),
+
+    #[error("Datasheet content scan failed: {0}")]
+    DatasheetScanFailed(String),
+
+    #[error("Datasheet fetch failed: {0}")]
+    DatasheetFetch(String),
+
+    #[error(transparent)]
+    Storage(StorageRequestFailed),
+
+    #[error(transparent)]
+    RuleViolations(RuleViolations),
+
+    #[error(transparent)]
+    Database(#[from] diesel::result::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ItemError {
+    // Units that do not fit the item are the caller's to fix
+    pub(crate) fn from_uom(error: anyhow::Error) -> Self {
+        match error.downcast::<UomError>() {
+            Ok(error @ UomError::Invalid(_)) => ItemError::InvalidUnit(error.to_string()),
+            Ok(error) => ItemError::Other(error.into()),
+            Err(error) => ItemError::Other(error),
+        }
+    }
+
+    // Items left without a part number need a sequence to draw one from
+    pub(crate) fn from_numbering(error: anyhow::Error) -> Self {
+        match error.downcast::<NumberingError>() {
+            Ok(error) => ItemError::Unnumbered(error.to_string()),
+            Err(error) => ItemError::Other(error),
+        }
+    }
+
+    // Storage backends report failed calls as [`StorageRequestFailed`]
+    pub(crate) fn from_storage(error: anyhow::Error) -> Self {
+        match error.downcast::<StorageRequestFailed>() {
+            Ok(failed) => ItemError::Storage(failed),
+            Err(error) => ItemError::Other(error),
+        }
+    }
+
+    // Broken business rules carry the messages the tenant wrote for them
+    fn from_rules(error: anyhow::Error) -> Self {
+        match error.downcast::<RuleViolations>() {
+            Ok(violations) => ItemError::RuleViolations(violations),
+            Err(error) => ItemError::Other(error),
+        }
+    }
+}
+
+impl From<ItemError> for AppError {
+    fn from(error: ItemError) -> Self {
+        match error {
+            ItemError::NotFound => AppError::NotFound(error.to_string()),
+            ItemError::InvalidUnit(message) => AppError::Validation(message),
+            ItemError::Unnumbered(message) => AppError::Validation(message),
+            ItemError::InvalidBomImport(_)
+            | ItemError::InvalidMovement(_)
+            | ItemError::OwnerNotFound(_)
+            | ItemError::InvalidDatasheetUrl(_)
+            | ItemError::DatasheetTooLarge(_)
+            | ItemError::DatasheetInfected(_)
+            | ItemError::InvalidReference => AppError::Validation(error.to_string()),
+            ItemError::InsufficientStock(_)
+            | ItemError::ReferenceClosed(..)
+            | ItemError::NotReleasable(_) => AppError::Conflict(error.to_string()),
+            ItemError::NoDatasheet => AppError::NotFound(error.to_string()),
+            ItemError::StorageNotConfigured
+            | ItemError::DatasheetScanFailed(_)
+            | ItemError::DatasheetFetch(_)
+            | ItemError::Storage(_) => AppError::Internal(error.to_string()),
+            ItemError::RuleViolations(violations) => AppError::Validation(violations.to_string()),
+            ItemError::Database(error) => AppError::from_database(error),
+            ItemError::Other(error) => AppError::from_service(error),
+        }
+    }
+}
+
+type Result<T, E = ItemError> = std::result::Result<T, E>;
+
+type ListedItems = IntoBoxed<
+    'static,
+    InnerJoinOn<items::table, inventory_items::table, Eq<inventory_items::item_id, items::id>>,
+    Pg,
+>;
 
 pub struct ItemService {
     database: DatabaseService,
@@ -29,18 +176,39 @@ impl ItemService {
         tenant_id: Uuid,
         request: CreateItemRequest,
     ) -> Result<CreateItemIdResponse> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
+        // Check the tenant's business rules before anything is written
+        let record = serde_json::json!({
+            "internal_part_number": request.internal_part_number,
+            "mfr_part_number": request.mfr_part_number,
+            "manufacturer": request.manufacturer,
+            "datasheet": request.datasheet,
+            "lifecycle": request.lifecycle.as_ref().map(|l| l.to_string()),
+            "description": request.description,
+            "category": request.category,
+            "metadata": request.metadata,
+        });
+        ValidationRuleService::new(self.database.clone())
+            .enforce(tenant_id, RuleEntity::Item, &record)
+            .await
+            .map_err(ItemError::from_rules)?;
 
-        let item_id = conn
-            .transaction::<_, diesel::result::Error, _>(|conn| {
+        let item_id = self
+            .database
+            .with_tenant_tx::<_, ItemError, _>(tenant_id, |conn| {
                 Box::pin(async move {
+                    // Number the item from its sequence unless given
+                    let internal_part_number = NumberingService::number_or_draw(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::Item,
+                        request.internal_part_number.clone(),
+                    )
+                    .await
+                    .map_err(ItemError::from_numbering)?;
+
                     // Create item record
                     let new_item = NewItem {
-                        internal_part_number: request.internal_part_number.clone(),
+                        internal_part_number,
                         mfr_part_number: request.mfr_part_number,
                         manufacturer: request.manufacturer,
                         datasheet: request.datasheet,
@@ -84,8 +252,7 @@ impl ItemService {
                     Ok(item.id)
                 })
             })
-            .await
-            .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+            .await?;
 
         Ok(CreateItemIdResponse { id: item_id })
     }
@@ -130,7 +297,7 @@ impl ItemService {
                 created_at: item.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: item.updated_at.unwrap_or_else(|| Utc::now()),
                 context: ItemContext::try_from(inventory.context).unwrap_or(ItemContext::Store),
-                quantity: inventory.quantity.unwrap_or(0),
+                quantity: inventory.quantity.unwrap_or_default(),
                 location: inventory.location,
                 pricing: inventory.pricing,
                 lead_time: inventory.lead_time,
@@ -159,127 +326,236 @@ impl ItemService {
         item_id: Uuid,
         context: ItemContext,
     ) -> Result<()> {
+        self.database
+            .with_tenant_tx::<_, ItemError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Delete inventory item first (due to foreign key constraints)
+                    let deleted = diesel::delete(
+                        inventory_items::table
+                            .filter(inventory_items::item_id.eq(item_id))
+                            .filter(inventory_items::tenant_id.eq(tenant_id))
+                            .filter(inventory_items::context.eq(context.to_string())),
+                    )
+                    .execute(conn)
+                    .await?;
+                    if deleted == 0 {
+                        return Err(ItemError::NotFound);
+                    }
+
+                    // Check if there are other inventory items for this item
+                    let inventory_count: i64 = inventory_items::table
+                        .filter(inventory_items::item_id.eq(item_id))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+
+                    // If no other inventory items exist, delete the base item
+                    if inventory_count == 0 {
+                        diesel::delete(items::table.filter(items::id.eq(item_id)))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    pub async fn list_items(
+        &self,
+        tenant_id: Uuid,
+        context: Option<ItemContext>,
+        category: Option<String>,
+        lifecycle: Option<ItemLifecycle>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<ItemResponse>> {
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        // Delete inventory item first (due to foreign key constraints)
-        diesel::delete(
-            inventory_items::table
-                .filter(inventory_items::item_id.eq(item_id))
-                .filter(inventory_items::tenant_id.eq(tenant_id))
-                .filter(inventory_items::context.eq(context.to_string())),
-        )
-        .execute(&mut conn)
-        .await?;
-
-        // Check if there are other inventory items for this item
-        let inventory_count: i64 = inventory_items::table
-            .filter(inventory_items::item_id.eq(item_id))
-            .count()
-            .get_result(&mut conn)
-            .await?;
+        let mut query = Self::listed_items(tenant_id, context, category, lifecycle);
 
-        // If no other inventory items exist, delete the base item
-        if inventory_count == 0 {
-            diesel::delete(items::table.filter(items::id.eq(item_id)))
-                .execute(&mut conn)
-                .await?;
+        // Apply pagination, in a stable order so consecutive pages line up
+        if let Some(limit_val) = limit {
+            query = query.limit(limit_val as i64);
+        }
+        if let Some(offset_val) = offset {
+            query = query.offset(offset_val as i64);
         }
 
-        Ok(())
+        let results = query
+            .order(inventory_items::id.asc())
+            .select((Item::as_select(), InventoryItem::as_select()))
+            .load::<(Item, InventoryItem)>(&mut conn)
+            .await?;
+
+        Ok(results
+            .into_iter()
+            .map(|(item, inventory)| Self::to_item_response(item, inventory))
+            .collect())
     }
 
-    pub async fn list_items(
+    /// Version of the items [`Self::list_items`] lists with these filters, across all pages.
+    pub async fn list_version(
         &self,
         tenant_id: Uuid,
         context: Option<ItemContext>,
         category: Option<String>,
         lifecycle: Option<ItemLifecycle>,
-        limit: Option<u32>,
-        offset: Option<u32>,
-    ) -> Result<Vec<ItemResponse>> {
+    ) -> Result<DataVersion> {
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        let mut query = items::table
+        let (rows, items_updated_at, inventory_updated_at) =
+            Self::listed_items(tenant_id, context, category, lifecycle)
+                .select((
+                    diesel::dsl::count_star(),
+                    diesel::dsl::max(items::updated_at),
+                    diesel::dsl::max(inventory_items::updated_at),
+                ))
+                .first::<(i64, Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(&mut conn)
+                .await?;
+
+        Ok(DataVersion::new(
+            rows,
+            &[items_updated_at, inventory_updated_at],
+        ))
+    }
+
+    /// Version of the item [`Self::get_item_by_id`] returns, or `None` when there is none.
+    pub async fn item_version(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        context: ItemContext,
+    ) -> Result<Option<DataVersion>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated_at = items::table
             .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(items::id.eq(item_id))
             .filter(inventory_items::tenant_id.eq(tenant_id))
-            .into_boxed();
+            .filter(inventory_items::context.eq(context.to_string()))
+            .select((items::updated_at, inventory_items::updated_at))
+            .first::<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(updated_at.map(|(item, inventory)| DataVersion::new(1, &[item, inventory])))
+    }
+
+    /// The tenant's items with their inventory records, narrowed by the list filters.
+    fn listed_items(
+        tenant_id: Uuid,
+        context: Option<ItemContext>,
+        category: Option<String>,
+        lifecycle: Option<ItemLifecycle>,
+    ) -> ListedItems {
+        let mut query = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .into_boxed()
+            .filter(inventory_items::tenant_id.eq(tenant_id));
 
         // Filter by context if specified
-        if let Some(context_filter) = &context {
+        if let Some(context_filter) = context {
             query = query.filter(inventory_items::context.eq(context_filter.to_string()));
         }
 
         // Filter by category if specified
-        if let Some(category_filter) = &category {
+        if let Some(category_filter) = category {
             query = query.filter(items::category.eq(category_filter));
         }
 
         // Filter by lifecycle if specified
-        if let Some(lifecycle_filter) = &lifecycle {
+        if let Some(lifecycle_filter) = lifecycle {
             query = query.filter(items::lifecycle.eq(lifecycle_filter.to_string()));
         }
 
-        // Apply pagination
-        if let Some(limit_val) = limit {
-            query = query.limit(limit_val as i64);
+        query
+    }
+
+    /// Items of a category whose attributes match every filter, optionally sorted by an
+    /// attribute. Equality filters are answered from the attribute index; a number given with an
+    /// SI prefix also matches the attribute stored in base units.
+    pub async fn parametric_search(
+        &self,
+        tenant_id: Uuid,
+        search: ParametricSearch,
+    ) -> Result<Vec<ItemResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(category) = &search.category {
+            query = query.filter(items::category.eq(category));
         }
-        if let Some(offset_val) = offset {
-            query = query.offset(offset_val as i64);
+
+        for filter in search.filters {
+            query = match (filter.op, filter.number) {
+                (AttributeOp::Eq, number) => {
+                    let text = serde_json::json!({ &filter.attribute: filter.value });
+                    let number = number
+                        .map(|number| serde_json::json!({ &filter.attribute: number }))
+                        .unwrap_or_else(|| text.clone());
+                    query.filter(
+                        sql::<Bool>("((items.metadata -> 'attributes') @> ")
+                            .bind::<Jsonb, _>(text)
+                            .sql(" OR (items.metadata -> 'attributes') @> ")
+                            .bind::<Jsonb, _>(number)
+                            .sql(")"),
+                    )
+                }
+                (op, Some(number)) => {
+                    let path = format!("$.{} ? (@ {} {})", filter.attribute, op.symbol(), number);
+                    query.filter(
+                        sql::<Bool>("(items.metadata -> 'attributes') @? CAST(")
+                            .bind::<Text, _>(path)
+                            .sql(" AS jsonpath)"),
+                    )
+                }
+                (_, None) => query,
+            };
+        }
+
+        if let Some((attribute, descending)) = search.sort {
+            let value =
+                sql::<Jsonb>("(items.metadata -> 'attributes' -> ").bind::<Text, _>(attribute);
+            query = if descending {
+                query.order(value.sql(")").desc().nulls_last())
+            } else {
+                query.order(value.sql(")").asc().nulls_last())
+            };
         }
 
         let results = query
+            .then_order_by(inventory_items::id.asc())
+            .limit(search.limit)
+            .offset(search.offset)
             .select((Item::as_select(), InventoryItem::as_select()))
             .load::<(Item, InventoryItem)>(&mut conn)
             .await?;
 
-        let mut item_responses = Vec::new();
-        for (item, inventory) in results {
-            item_responses.push(ItemResponse {
-                id: item.id,
-                internal_part_number: item.internal_part_number,
-                mfr_part_number: item.mfr_part_number,
-                manufacturer: item.manufacturer,
-                datasheet: item.datasheet,
-                lifecycle: ItemLifecycle::try_from(
-                    item.lifecycle.unwrap_or_else(|| "production".to_string()),
-                )
-                .unwrap_or(ItemLifecycle::Production),
-                description: item.description,
-                category: item.category,
-                metadata: item.metadata,
-                linked_resources: item.linked_resources,
-                created_at: item.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: item.updated_at.unwrap_or_else(|| Utc::now()),
-                context: ItemContext::try_from(inventory.context).unwrap_or(ItemContext::Store),
-                quantity: inventory.quantity.unwrap_or(0),
-                location: inventory.location,
-                pricing: inventory.pricing,
-                lead_time: inventory.lead_time,
-                min_stock_level: inventory.min_stock_level.unwrap_or(0),
-                max_stock_level: inventory.max_stock_level,
-                reorder_point: inventory.reorder_point,
-                vendor_id: inventory.vendor_id,
-                last_received_date: inventory.last_received_date,
-                status: ItemStatus::try_from(
-                    inventory.status.unwrap_or_else(|| "active".to_string()),
-                )
-                .unwrap_or(ItemStatus::Active),
-                notes: inventory.notes,
-                inventory_metadata: inventory.metadata,
-                inventory_created_at: inventory.created_at.unwrap_or_else(|| Utc::now()),
-                inventory_updated_at: inventory.updated_at.unwrap_or_else(|| Utc::now()),
-            });
-        }
-
-        Ok(item_responses)
+        Ok(results
+            .into_iter()
+            .map(|(item, inventory)| Self::to_item_response(item, inventory))
+            .collect())
     }
 
     pub async fn update_item(
@@ -287,13 +563,32 @@ impl ItemService {
         tenant_id: Uuid,
         item_id: Uuid,
         context: ItemContext,
-        _request: UpdateItemRequest,
+        request: UpdateItemRequest,
     ) -> Result<ItemResponse> {
+        let item = self
+            .get_item_by_id(tenant_id, item_id, context)
+            .await?
+            .ok_or(ItemError::NotFound)?;
+
+        // Check the tenant's business rules against the item as it would be once updated
+        let record = serde_json::json!({
+            "internal_part_number": item.internal_part_number,
+            "mfr_part_number": request.mfr_part_number.as_ref().or(item.mfr_part_number.as_ref()),
+            "manufacturer": request.manufacturer.as_ref().unwrap_or(&item.manufacturer),
+            "datasheet": request.datasheet.as_ref().or(item.datasheet.as_ref()),
+            "lifecycle": request.lifecycle.as_ref().unwrap_or(&item.lifecycle).to_string(),
+            "description": request.description.as_ref().or(item.description.as_ref()),
+            "category": request.category.as_ref().or(item.category.as_ref()),
+            "metadata": request.metadata.as_ref().or(item.metadata.as_ref()),
+        });
+        ValidationRuleService::new(self.database.clone())
+            .enforce(tenant_id, RuleEntity::Item, &record)
+            .await
+            .map_err(ItemError::from_rules)?;
+
         // For now, just return the existing item
         // In a real implementation, this would update the item fields
-        self.get_item_by_id(tenant_id, item_id, context)
-            .await?
-            .ok_or_else(|| anyhow::anyhow!("Item not found"))
+        Ok(item)
     }
 
     // Context-specific implementations
@@ -525,6 +820,13 @@ impl ItemService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        // The component must be convertible from the BOM unit
+        if let Some(uom_id) = request.uom_id {
+            UomService::base_factor(&mut conn, tenant_id, request.component_item_id, uom_id)
+                .await
+                .map_err(ItemError::from_uom)?;
+        }
+
         let new_bom_item = NewItemBom {
             tenant_id,
             parent_item_id: request.parent_item_id,
@@ -536,6 +838,10 @@ impl ItemService {
                 .substitutes
                 .map(|s| s.into_iter().map(Some).collect()),
             assembly_order: request.assembly_order,
+            uom_id: request.uom_id,
+            designators: request
+                .designators
+                .map(|d| d.into_iter().map(Some).collect()),
         };
 
         let bom_item: ItemBom = diesel::insert_into(item_bom::table)
@@ -583,6 +889,14 @@ impl ItemService {
                 .first::<Item>(&mut conn)
                 .await?;
 
+            let quantity = bom.quantity.unwrap_or(Quantity::from(1));
+            let factor = match bom.uom_id {
+                Some(uom_id) => {
+                    UomService::base_factor(&mut conn, tenant_id, component_item.id, uom_id).await?
+                }
+                None => 1.0,
+            };
+
             bom_responses.push(BomItemResponse {
                 id: bom.id,
                 parent_item: ItemSummary {
@@ -599,7 +913,9 @@ impl ItemService {
                     manufacturer: component_item.manufacturer,
                     description: component_item.description,
                 },
-                quantity: bom.quantity.unwrap_or(1),
+                quantity,
+                uom_id: bom.uom_id,
+                base_quantity: quantity.scaled(factor).unwrap_or(quantity),
                 notes: bom.notes,
                 is_optional: bom.is_optional.unwrap_or(false),
                 substitutes: bom
@@ -608,6 +924,12 @@ impl ItemService {
                     .into_iter()
                     .filter_map(|id| id)
                     .collect(),
+                designators: bom
+                    .designators
+                    .unwrap_or_default()
+                    .into_iter()
+                    .flatten()
+                    .collect(),
                 assembly_order: bom.assembly_order,
                 created_at: bom.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: bom.updated_at.unwrap_or_else(|| Utc::now()),
@@ -658,4 +980,40 @@ impl ItemService {
 
         Ok(())
     }
+
+    fn to_item_response(item: Item, inventory: InventoryItem) -> ItemResponse {
+        ItemResponse {
+            id: item.id,
+            internal_part_number: item.internal_part_number,
+            mfr_part_number: item.mfr_part_number,
+            manufacturer: item.manufacturer,
+            datasheet: item.datasheet,
+            lifecycle: ItemLifecycle::try_from(
+                item.lifecycle.unwrap_or_else(|| "production".to_string()),
+            )
+            .unwrap_or(ItemLifecycle::Production),
+            description: item.description,
+            category: item.category,
+            metadata: item.metadata,
+            linked_resources: item.linked_resources,
+            created_at: item.created_at.unwrap_or_else(|| Utc::now()),
+            updated_at: item.updated_at.unwrap_or_else(|| Utc::now()),
+            context: ItemContext::try_from(inventory.context).unwrap_or(ItemContext::Store),
+            quantity: inventory.quantity.unwrap_or_default(),
+            location: inventory.location,
+            pricing: inventory.pricing,
+            lead_time: inventory.lead_time,
+            min_stock_level: inventory.min_stock_level.unwrap_or(0),
+            max_stock_level: inventory.max_stock_level,
+            reorder_point: inventory.reorder_point,
+            vendor_id: inventory.vendor_id,
+            last_received_date: inventory.last_received_date,
+            status: ItemStatus::try_from(inventory.status.unwrap_or_else(|| "active".to_string()))
+                .unwrap_or(ItemStatus::Active),
+            notes: inventory.notes,
+            inventory_metadata: inventory.metadata,
+            inventory_created_at: inventory.created_at.unwrap_or_else(|| Utc::now()),
+            inventory_updated_at: inventory.updated_at.unwrap_or_else(|| Utc::now()),
+        }
+    }
 }
@@ -0,0 +1,735 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use futures::{stream, StreamExt};
+use reqwest::{
+    header::{CONTENT_TYPE, LOCATION, USER_AGENT},
+    redirect::Policy,
+    Client,
+};
+use std::{sync::Arc, time::Duration};
+use url::Url;
+use uuid::Uuid;
+
+use crate::models::{
+    Asset, AssetScanStatus, AssetTypeEnum, DatasheetFile, DatasheetRefreshFailure, Item,
+    ItemDatasheet, ItemDatasheetResponse, ListDatasheetsQuery, NewAsset, NewItemDatasheet,
+    RefreshDatasheetsResponse,
+};
+use crate::schema::*;
+use crate::services::{
+    content_scanner_from_env, storage_backend_from_env, ContentScanner, DatabaseService, ItemError,
+    ScanFile, ScanVerdict, StorageBackend,
+};
+use crate::utils::asset_upload::sha256_hex;
+use crate::utils::datasheet::{
+    check_datasheet_url, datasheet_content_type, datasheet_file_name, datasheet_max_age,
+    datasheet_path, is_stale, DATASHEET_MAX_BYTES, DATASHEET_MAX_REDIRECTS,
+};
+use crate::utils::public_url::{resolve_public_host, PublicUrlError};
+
+type Result<T, E = ItemError> = std::result::Result<T, E>;
+
+// A datasheet server gets this long to send the whole file
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+// Scanners that download the snapshot get a URL valid this long
+const SCAN_URL_TTL: Duration = Duration::from_secs(900);
+const DEFAULT_LIST_LIMIT: i64 = 100;
+const DEFAULT_REFRESH_LIMIT: i64 = 20;
+
+/// Serves item datasheets through the server. The item's datasheet URL is fetched once and the
+/// file kept as a `datasheet` asset of the item; later requests are served from that snapshot
+/// until it is older than DATASHEET_MAX_AGE_DAYS, when it is fetched again. A snapshot that
+/// cannot be refreshed is still served, marked stale.
+pub struct ItemDatasheetService {
+    database: DatabaseService,
+    storage: Option<Arc<dyn StorageBackend>>,
+    scanner: Option<Arc<dyn ContentScanner>>,
+    max_age: ChronoDuration,
+}
+
+impl ItemDatasheetService {
+    /// Uses the storage backend and the content scanner configured through environment
+    /// variables, with the asset bucket ASSET_BUCKET (default `assets`), and
+    /// DATASHEET_MAX_AGE_DAYS (default 30).
+    pub fn new(database: DatabaseService) -> anyhow::Result<Self> {
+        let storage = storage_backend_from_env("ASSET_BUCKET", "assets")?;
+        Ok(Self::with_storage(
+            database,
+            storage,
+            content_scanner_from_env()?,
+            datasheet_max_age(),
+        ))
+    }
+
+    pub fn with_storage(
+        database: DatabaseService,
+        storage: Option<Arc<dyn StorageBackend>>,
+        scanner: Option<Arc<dyn ContentScanner>>,
+        max_age: ChronoDuration,
+    ) -> Self {
+        Self {
+            database,
+            storage,
+            scanner,
+            max_age,
+        }
+    }
+
+    // Item datasheet operations
+
+    /// The item's datasheet, from its snapshot while that is fresh and fetched otherwise (or
+    /// always with `refresh`). `Ok(None)` when the item does not exist.
+    pub async fn get_datasheet(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        person_id: Uuid,
+        refresh: bool,
+    ) -> Result<Option<DatasheetFile>> {
+        let storage = self.storage()?;
+        let (item, snapshot) = {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let Some(item) = Self::find_item(&mut conn, item_id).await? else {
+                return Ok(None);
+            };
+            let snapshot = Self::find_snapshot(&mut conn, tenant_id, item_id).await?;
+            (item, snapshot)
+        };
+        let source_url = Self::source_url(&item)?;
+
+        // A snapshot of an earlier URL is never served; the item now links elsewhere
+        let current = snapshot
+            .filter(|snapshot| snapshot.source_url == source_url && snapshot.fetched_at.is_some());
+        if let Some(snapshot) = &current {
+            if !refresh && !is_stale(snapshot.fetched_at, Utc::now(), self.max_age) {
+                if let Some(file) = self
+                    .read_snapshot(storage.as_ref(), tenant_id, &item, snapshot, false)
+                    .await?
+                {
+                    return Ok(Some(file));
+                }
+            }
+        }
+
+        match self
+            .refresh_item(storage.as_ref(), tenant_id, &item, &source_url, person_id)
+            .await
+        {
+            Ok((_, file)) => Ok(Some(file)),
+            Err(e) => {
+                let Some(snapshot) = &current else {
+                    return Err(e);
+                };
+                tracing::warn!("Datasheet of item {} not refreshed: {}", item.id, e);
+                match self
+                    .read_snapshot(storage.as_ref(), tenant_id, &item, snapshot, true)
+                    .await?
+                {
+                    Some(file) => Ok(Some(file)),
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Datasheet snapshots of the tenant, oldest fetch first.
+    pub async fn list_datasheets(
+        &self,
+        tenant_id: Uuid,
+        query: ListDatasheetsQuery,
+    ) -> Result<Vec<ItemDatasheetResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let now = Utc::now();
+        let mut sql_query = item_datasheets::table
+            .filter(item_datasheets::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if query.stale.unwrap_or(false) {
+            sql_query = sql_query.filter(
+                item_datasheets::fetched_at
+                    .is_null()
+                    .or(item_datasheets::fetched_at.lt(now - self.max_age)),
+            );
+        }
+
+        let snapshots = sql_query
+            .order(item_datasheets::fetched_at.asc().nulls_first())
+            .limit(query.limit.unwrap_or(DEFAULT_LIST_LIMIT))
+            .select(ItemDatasheet::as_select())
+            .load::<ItemDatasheet>(&mut conn)
+            .await?;
+
+        Ok(snapshots
+            .into_iter()
+            .map(|snapshot| self.to_response(snapshot, now))
+            .collect())
+    }
+
+    /// Fetches stale snapshots again, oldest first, up to `limit`. Items whose datasheet link
+    /// was removed are skipped.
+    pub async fn refresh_stale(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        limit: Option<i64>,
+    ) -> Result<RefreshDatasheetsResponse> {
+        let storage = self.storage()?;
+        let items: Vec<Item> = {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            item_datasheets::table
+                .inner_join(items::table)
+                .filter(item_datasheets::tenant_id.eq(tenant_id))
+                .filter(
+                    item_datasheets::fetched_at
+                        .is_null()
+                        .or(item_datasheets::fetched_at.lt(Utc::now() - self.max_age)),
+                )
+                .filter(items::datasheet.is_not_null())
+                .order(item_datasheets::fetched_at.asc().nulls_first())
+                .limit(limit.unwrap_or(DEFAULT_REFRESH_LIMIT))
+                .select(Item::as_select())
+                .load::<Item>(&mut conn)
+                .await?
+        };
+
+        let now = Utc::now();
+        let mut refreshed = Vec::new();
+        let mut failures = Vec::new();
+        for item in items {
+            let result = match Self::source_url(&item) {
+                Ok(source_url) => {
+                    self.refresh_item(storage.as_ref(), tenant_id, &item, &source_url, person_id)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok((snapshot, _)) => refreshed.push(self.to_response(snapshot, now)),
+                Err(e) => failures.push(DatasheetRefreshFailure {
+                    item_id: item.id,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        Ok(RefreshDatasheetsResponse {
+            refreshed,
+            failures,
+        })
+    }
+
+    // Private helper methods
+
+    fn storage(&self) -> Result<Arc<dyn StorageBackend>> {
+        self.storage.clone().ok_or(ItemError::StorageNotConfigured)
+    }
+
+    fn source_url(item: &Item) -> Result<String> {
+        item.datasheet
+            .as_deref()
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .ok_or(ItemError::NoDatasheet)
+    }
+
+    async fn find_item(conn: &mut AsyncPgConnection, item_id: Uuid) -> Result<Option<Item>> {
+        Ok(items::table
+            .find(item_id)
+            .select(Item::as_select())
+            .first::<Item>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn find_snapshot(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<ItemDatasheet>> {
+        Ok(item_datasheets::table
+            .filter(item_datasheets::tenant_id.eq(tenant_id))
+            .filter(item_datasheets::item_id.eq(item_id))
+            .select(ItemDatasheet::as_select())
+            .first::<ItemDatasheet>(conn)
+            .await
+            .optional()?)
+    }
+
+    /// The snapshot's file, `None` when its asset or file is gone or quarantined.
+    async fn read_snapshot(
+        &self,
+        storage: &dyn StorageBackend,
+        tenant_id: Uuid,
+        item: &Item,
+        snapshot: &ItemDatasheet,
+        stale: bool,
+    ) -> Result<Option<DatasheetFile>> {
+        let (Some(asset_id), Some(fetched_at)) = (snapshot.asset_id, snapshot.fetched_at) else {
+            return Ok(None);
+        };
+        let asset = {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            assets::table
+                .filter(assets::id.eq(asset_id))
+                .filter(assets::tenant_id.eq(tenant_id))
+                .select(Asset::as_select())
+                .first::<Asset>(&mut conn)
+                .await
+                .optional()?
+        };
+        let Some(asset) = asset else {
+            return Ok(None);
+        };
+        let quarantined = asset
+            .scan_status
+            .and_then(|status| AssetScanStatus::try_from(status).ok())
+            .is_some_and(|status| status.is_quarantined());
+        let Some(file_path) = asset.file_path.filter(|_| !quarantined) else {
+            return Ok(None);
+        };
+
+        let content_type = snapshot
+            .content_type
+            .clone()
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+        Ok(Some(DatasheetFile {
+            content: storage
+                .get(&file_path)
+                .await
+                .map_err(ItemError::from_storage)?,
+            file_name: datasheet_file_name(&item.internal_part_number, &content_type),
+            content_type,
+            fetched_at,
+            stale,
+        }))
+    }
+
+    /// Fetches the item's datasheet and makes it the snapshot. A failed fetch is recorded on the
+    /// snapshot, keeping the file it already had.
+    async fn refresh_item(
+        &self,
+        storage: &dyn StorageBackend,
+        tenant_id: Uuid,
+        item: &Item,
+        source_url: &str,
+        person_id: Uuid,
+    ) -> Result<(ItemDatasheet, DatasheetFile)> {
+        let attempted_at = Utc::now();
+        match self
+            .store_snapshot(
+                storage,
+                tenant_id,
+                item,
+                source_url,
+                person_id,
+                attempted_at,
+            )
+            .await
+        {
+            Ok(stored) => Ok(stored),
+            Err(e) => {
+                if let Err(record_error) = self
+                    .record_failure(tenant_id, item.id, source_url, attempted_at, &e)
+                    .await
+                {
+                    tracing::error!(
+                        "Datasheet failure of item {} not recorded: {}",
+                        item.id,
+                        record_error
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+
+    async fn store_snapshot(
+        &self,
+        storage: &dyn StorageBackend,
+        tenant_id: Uuid,
+        item: &Item,
+        source_url: &str,
+        person_id: Uuid,
+        attempted_at: DateTime<Utc>,
+    ) -> Result<(ItemDatasheet, DatasheetFile)> {
+        let (content, content_type) = Self::fetch(source_url).await?;
+        let checksum = sha256_hex(&content);
+        let file_path = datasheet_path(tenant_id, item.id, &checksum, &content_type);
+        let file_name = datasheet_file_name(&item.internal_part_number, &content_type);
+        let size_bytes = content.len() as i64;
+
+        storage
+            .put(&file_path, content.clone(), &content_type)
+            .await
+            .map_err(ItemError::from_storage)?;
+        let scan_status = match &self.scanner {
+            Some(scanner) => {
+                let verdict = Self::scan(
+                    scanner.as_ref(),
+                    storage,
+                    &file_path,
+                    &file_name,
+                    &checksum,
+                    content.clone(),
+                )
+                .await;
+                match verdict {
+                    Ok(ScanVerdict::Clean) => Some(AssetScanStatus::Clean.to_string()),
+                    Ok(ScanVerdict::Infected { signature }) => {
+                        storage.delete(&file_path).await.ok();
+                        return Err(ItemError::DatasheetInfected(signature));
+                    }
+                    Err(e) => {
+                        storage.delete(&file_path).await.ok();
+                        return Err(ItemError::DatasheetScanFailed(e.to_string()));
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let item_id = item.id;
+        let source_url = source_url.to_string();
+        let asset_name: String = format!("Datasheet {}", item.internal_part_number)
+            .chars()
+            .take(100)
+            .collect();
+        let new_path = file_path.clone();
+        let new_content_type = content_type.clone();
+        let new_checksum = checksum.clone();
+        let stored = self
+            .database
+            .with_tenant_tx::<_, ItemError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let asset_type_id: Uuid = asset_types::table
+                        .filter(asset_types::name.eq(AssetTypeEnum::Datasheet.to_string()))
+                        .select(asset_types::id)
+                        .first(conn)
+                        .await
+                        .optional()?
+                        .ok_or_else(|| anyhow!("Datasheet asset type not found"))?;
+
+                    let snapshot = Self::find_snapshot(conn, tenant_id, item_id).await?;
+                    let existing = match snapshot.as_ref().and_then(|s| s.asset_id) {
+                        Some(asset_id) => assets::table
+                            .filter(assets::id.eq(asset_id))
+                            .filter(assets::tenant_id.eq(tenant_id))
+                            .select(Asset::as_select())
+                            .first::<Asset>(conn)
+                            .await
+                            .optional()?,
+                        None => None,
+                    };
+                    let replaced_path = existing
+                        .as_ref()
+                        .and_then(|asset| asset.file_path.clone())
+                        .filter(|path| *path != new_path);
+
+                    let asset_id = match existing {
+                        Some(asset) => {
+                            diesel::update(assets::table.find(asset.id))
+                                .set((
+                                    assets::file_path.eq(Some(&new_path)),
+                                    assets::file_size.eq(Some(size_bytes)),
+                                    assets::file_type.eq(Some(&new_content_type)),
+                                    assets::checksum.eq(Some(&new_checksum)),
+                                    assets::scan_status.eq(scan_status.clone()),
+                                    assets::scanned_at
+                                        .eq(scan_status.as_ref().map(|_| attempted_at)),
+                                    assets::updated_at.eq(Utc::now()),
+                                ))
+                                .execute(conn)
+                                .await?;
+                            asset.id
+                        }
+                        None => {
+                            let new_asset = NewAsset {
+                                tenant_id,
+                                item_id,
+                                asset_type_id,
+                                name: asset_name,
+                                version: None,
+                                description: Some(format!("Fetched from {}", source_url)),
+                                file_path: Some(new_path.clone()),
+                                file_size: Some(size_bytes),
+                                file_type: Some(new_content_type.clone()),
+                                checksum: Some(new_checksum.clone()),
+                                is_active: Some(true),
+                                metadata: Some(serde_json::json!({ "source_url": source_url })),
+                                created_by_id: person_id,
+                                lineage_id: None,
+                                previous_version_id: None,
+                                is_current: None,
+                                release_status: None,
+                                retain_until: None,
+                            };
+                            let asset_id: Uuid = diesel::insert_into(assets::table)
+                                .values(&new_asset)
+                                .returning(assets::id)
+                                .get_result(conn)
+                                .await?;
+                            if scan_status.is_some() {
+                                diesel::update(assets::table.find(asset_id))
+                                    .set((
+                                        assets::scan_status.eq(scan_status.clone()),
+                                        assets::scanned_at.eq(attempted_at),
+                                    ))
+                                    .execute(conn)
+                                    .await?;
+                            }
+                            asset_id
+                        }
+                    };
+
+                    let new_snapshot = NewItemDatasheet {
+                        tenant_id,
+                        item_id,
+                        source_url,
+                        asset_id: Some(asset_id),
+                        content_type: Some(new_content_type),
+                        size_bytes: Some(size_bytes),
+                        checksum: Some(new_checksum),
+                        fetched_at: Some(attempted_at),
+                        last_attempt_at: Some(attempted_at),
+                        last_error: None,
+                    };
+                    let snapshot: ItemDatasheet = diesel::insert_into(item_datasheets::table)
+                        .values(&new_snapshot)
+                        .on_conflict(item_datasheets::item_id)
+                        .do_update()
+                        .set((
+                            item_datasheets::source_url.eq(&new_snapshot.source_url),
+                            item_datasheets::asset_id.eq(new_snapshot.asset_id),
+                            item_datasheets::content_type.eq(&new_snapshot.content_type),
+                            item_datasheets::size_bytes.eq(new_snapshot.size_bytes),
+                            item_datasheets::checksum.eq(&new_snapshot.checksum),
+                            item_datasheets::fetched_at.eq(new_snapshot.fetched_at),
+                            item_datasheets::last_attempt_at.eq(new_snapshot.last_attempt_at),
+                            item_datasheets::last_error.eq(None::<String>),
+                        ))
+                        .returning(ItemDatasheet::as_returning())
+                        .get_result(conn)
+                        .await?;
+                    Ok((snapshot, replaced_path))
+                })
+            })
+            .await;
+
+        let (snapshot, replaced_path) = match stored {
+            Ok(stored) => stored,
+            Err(e) => {
+                storage.delete(&file_path).await.ok();
+                return Err(e);
+            }
+        };
+
+        // The previous version's file is no longer referenced; left behind it only wastes space
+        if let Some(path) = replaced_path {
+            if let Err(e) = storage.delete(&path).await {
+                tracing::warn!("Datasheet file {} not deleted: {}", path, e);
+            }
+        }
+
+        Ok((
+            snapshot,
+            DatasheetFile {
+                content,
+                content_type,
+                file_name,
+                fetched_at: attempted_at,
+                stale: false,
+            },
+        ))
+    }
+
+    async fn record_failure(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        source_url: &str,
+        attempted_at: DateTime<Utc>,
+        error: &ItemError,
+    ) -> Result<()> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let new_snapshot = NewItemDatasheet {
+            tenant_id,
+            item_id,
+            source_url: source_url.to_string(),
+            asset_id: None,
+            content_type: None,
+            size_bytes: None,
+            checksum: None,
+            fetched_at: None,
+            last_attempt_at: Some(attempted_at),
+            last_error: Some(error.to_string()),
+        };
+        diesel::insert_into(item_datasheets::table)
+            .values(&new_snapshot)
+            .on_conflict(item_datasheets::item_id)
+            .do_update()
+            .set((
+                item_datasheets::last_attempt_at.eq(new_snapshot.last_attempt_at),
+                item_datasheets::last_error.eq(&new_snapshot.last_error),
+            ))
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Downloads a datasheet, returning its content and media type. Every host on the way,
+    /// redirects included, must resolve to public addresses only, and the connection is made to
+    /// the address checked so a second lookup cannot lead elsewhere.
+    async fn fetch(source_url: &str) -> Result<(Vec<u8>, String)> {
+        let mut url = check_datasheet_url(source_url).map_err(ItemError::InvalidDatasheetUrl)?;
+
+        for _ in 0..=DATASHEET_MAX_REDIRECTS {
+            let client = Self::pinned_client(&url).await?;
+            let mut response = client
+                .get(url.clone())
+                .header(
+                    USER_AGENT,
+                    concat!("ems-server/", env!("CARGO_PKG_VERSION")),
+                )
+                .send()
+                .await
+                .map_err(|e| ItemError::DatasheetFetch(e.to_string()))?;
+
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| {
+                        ItemError::DatasheetFetch("redirect without a location".to_string())
+                    })?;
+                let next = url.join(location).map_err(|e| {
+                    ItemError::InvalidDatasheetUrl(format!("Invalid datasheet URL: {}", e))
+                })?;
+                url = check_datasheet_url(next.as_str()).map_err(ItemError::InvalidDatasheetUrl)?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(ItemError::DatasheetFetch(format!(
+                    "{} returned {}",
+                    url, status
+                )));
+            }
+            if let Some(length) = response.content_length() {
+                if length > DATASHEET_MAX_BYTES as u64 {
+                    return Err(ItemError::DatasheetTooLarge(DATASHEET_MAX_BYTES));
+                }
+            }
+
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let mut content = Vec::new();
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| ItemError::DatasheetFetch(e.to_string()))?
+            {
+                if content.len() + chunk.len() > DATASHEET_MAX_BYTES {
+                    return Err(ItemError::DatasheetTooLarge(DATASHEET_MAX_BYTES));
+                }
+                content.extend_from_slice(&chunk);
+            }
+            if content.is_empty() {
+                return Err(ItemError::DatasheetFetch(format!(
+                    "{} sent no content",
+                    url
+                )));
+            }
+
+            let content_type = datasheet_content_type(content_type.as_deref(), &content);
+            return Ok((content, content_type));
+        }
+
+        Err(ItemError::DatasheetFetch(format!(
+            "more than {} redirects",
+            DATASHEET_MAX_REDIRECTS
+        )))
+    }
+
+    // Redirects are followed by hand, so each hop's host is checked before it is connected to
+    async fn pinned_client(url: &Url) -> Result<Client> {
+        let builder = Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .redirect(Policy::none());
+        match resolve_public_host(url, "datasheet").await {
+            Ok(Some((domain, address))) => Ok(builder
+                .resolve(&domain, address)
+                .build()
+                .map_err(anyhow::Error::from)?),
+            // Address hosts were checked with the URL
+            Ok(None) => Ok(builder.build().map_err(anyhow::Error::from)?),
+            Err(PublicUrlError::Unresolved(message)) => Err(ItemError::DatasheetFetch(message)),
+            Err(PublicUrlError::NotPublic(message)) => Err(ItemError::InvalidDatasheetUrl(message)),
+        }
+    }
+
+    async fn scan(
+        scanner: &dyn ContentScanner,
+        storage: &dyn StorageBackend,
+        file_path: &str,
+        file_name: &str,
+        checksum: &str,
+        content: Vec<u8>,
+    ) -> anyhow::Result<ScanVerdict> {
+        let url = storage.signed_url(file_path, SCAN_URL_TTL).await?;
+        scanner
+            .scan(ScanFile {
+                file_name: file_name.to_string(),
+                size_bytes: content.len() as u64,
+                sha256: checksum.to_string(),
+                url,
+                content: stream::once(async move { Ok(content) }).boxed(),
+            })
+            .await
+    }
+
+    fn to_response(&self, snapshot: ItemDatasheet, now: DateTime<Utc>) -> ItemDatasheetResponse {
+        ItemDatasheetResponse {
+            item_id: snapshot.item_id,
+            is_stale: is_stale(snapshot.fetched_at, now, self.max_age),
+            source_url: snapshot.source_url,
+            asset_id: snapshot.asset_id,
+            content_type: snapshot.content_type,
+            size_bytes: snapshot.size_bytes,
+            checksum: snapshot.checksum,
+            fetched_at: snapshot.fetched_at,
+            last_attempt_at: snapshot.last_attempt_at,
+            last_error: snapshot.last_error,
+        }
+    }
+}
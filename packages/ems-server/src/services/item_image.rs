@@ -0,0 +1,411 @@
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::{env, sync::Arc, time::Duration};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{ItemImage, ItemImageResponse, NewItemImage, UploadItemImageQuery};
+use crate::schema::*;
+use crate::services::{storage_backend_from_env, DatabaseService, StorageBackend};
+use crate::utils::item_image::{
+    image_extension, image_paths, process_image, MAX_IMAGE_BYTES, THUMBNAIL_CONTENT_TYPE,
+};
+use crate::utils::AppError;
+
+/// Errors from [`ItemImageService`] that are not failures of the service itself
+#[derive(Error, Debug)]
+pub enum ItemImageError {
+    #[error("Image storage not configured")]
+    StorageNotConfigured,
+
+    #[error("Image too large: at most {0} bytes allowed")]
+    TooLarge(usize),
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<ItemImageError> for AppError {
+    fn from(error: ItemImageError) -> Self {
+        match error {
+            ItemImageError::StorageNotConfigured => AppError::Unavailable(error.to_string()),
+            ItemImageError::TooLarge(_) | ItemImageError::Invalid(_) => {
+                AppError::Validation(error.to_string())
+            }
+        }
+    }
+}
+
+// Signed image URLs stay valid this many seconds unless ITEM_IMAGE_URL_TTL_SECONDS is set
+const DEFAULT_URL_TTL_SECONDS: u64 = 3600;
+
+pub struct ItemImageService {
+    database: DatabaseService,
+    storage: Option<Arc<dyn StorageBackend>>,
+    url_ttl: Duration,
+}
+
+impl ItemImageService {
+    /// Uses the storage backend configured through environment variables, with the bucket
+    /// ITEM_IMAGE_BUCKET (default `item-images`), and ITEM_IMAGE_URL_TTL_SECONDS (default 3600).
+    pub fn new(database: DatabaseService) -> Result<Self> {
+        let storage = storage_backend_from_env("ITEM_IMAGE_BUCKET", "item-images")?;
+        let ttl_seconds = env::var("ITEM_IMAGE_URL_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(DEFAULT_URL_TTL_SECONDS);
+
+        Ok(Self::with_storage(
+            database,
+            storage,
+            Duration::from_secs(ttl_seconds),
+        ))
+    }
+
+    pub fn with_storage(
+        database: DatabaseService,
+        storage: Option<Arc<dyn StorageBackend>>,
+        url_ttl: Duration,
+    ) -> Self {
+        Self {
+            database,
+            storage,
+            url_ttl,
+        }
+    }
+
+    // Item image operations
+
+    /// Stores an uploaded image and its thumbnail. `Ok(None)` when the item does not exist.
+    pub async fn upload_image(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        uploaded_by_id: Option<Uuid>,
+        content_type: &str,
+        content: Vec<u8>,
+        query: UploadItemImageQuery,
+    ) -> Result<Option<ItemImageResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::item_exists(&mut conn, item_id).await? {
+            return Ok(None);
+        }
+
+        if content.len() > MAX_IMAGE_BYTES {
+            return Err(ItemImageError::TooLarge(MAX_IMAGE_BYTES).into());
+        }
+
+        // Decoding and resizing is CPU bound, so it stays off the request threads
+        let (processed, content) = {
+            let content_type = content_type.to_string();
+            tokio::task::spawn_blocking(move || {
+                process_image(&content, &content_type).map(|processed| (processed, content))
+            })
+            .await?
+            .map_err(ItemImageError::Invalid)?
+        };
+        let extension = image_extension(content_type).unwrap_or("bin");
+        let image_id = Uuid::new_v4();
+        let (storage_path, thumbnail_path) = image_paths(tenant_id, item_id, image_id, extension);
+
+        let size_bytes = content.len() as i64;
+        storage.put(&storage_path, content, content_type).await?;
+        if let Err(e) = storage
+            .put(&thumbnail_path, processed.thumbnail, THUMBNAIL_CONTENT_TYPE)
+            .await
+        {
+            Self::remove_objects(storage.as_ref(), &[&storage_path]).await;
+            return Err(e);
+        }
+
+        let new_image = NewItemImage {
+            id: image_id,
+            tenant_id,
+            item_id,
+            storage_path: storage_path.clone(),
+            thumbnail_path: thumbnail_path.clone(),
+            content_type: content_type.trim().to_ascii_lowercase(),
+            width: processed.width as i32,
+            height: processed.height as i32,
+            size_bytes,
+            caption: query.caption,
+            is_primary: false,
+            uploaded_by_id,
+        };
+        let make_primary = query.primary.unwrap_or(false);
+
+        let inserted = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let mut image: ItemImage = diesel::insert_into(item_images::table)
+                        .values(&new_image)
+                        .returning(ItemImage::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let has_primary = Self::primary_image(conn, tenant_id, item_id)
+                        .await?
+                        .is_some();
+                    if make_primary || !has_primary {
+                        image = Self::make_primary(conn, tenant_id, &image).await?;
+                    }
+                    Ok(image)
+                })
+            })
+            .await;
+
+        let image = match inserted {
+            Ok(image) => image,
+            Err(e) => {
+                Self::remove_objects(storage.as_ref(), &[&storage_path, &thumbnail_path]).await;
+                return Err(e);
+            }
+        };
+
+        Ok(Some(self.to_response(storage.as_ref(), image).await?))
+    }
+
+    /// Images of an item, primary first and then oldest first. `Ok(None)` when the item does not
+    /// exist.
+    pub async fn list_images(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<Vec<ItemImageResponse>>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::item_exists(&mut conn, item_id).await? {
+            return Ok(None);
+        }
+
+        let images = item_images::table
+            .filter(item_images::tenant_id.eq(tenant_id))
+            .filter(item_images::item_id.eq(item_id))
+            .order((
+                item_images::is_primary.desc(),
+                item_images::created_at.asc(),
+            ))
+            .select(ItemImage::as_select())
+            .load::<ItemImage>(&mut conn)
+            .await?;
+
+        let mut responses = Vec::with_capacity(images.len());
+        for image in images {
+            responses.push(self.to_response(storage.as_ref(), image).await?);
+        }
+        Ok(Some(responses))
+    }
+
+    /// Makes an image its item's primary image. `Ok(None)` when the image does not exist.
+    pub async fn set_primary_image(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        image_id: Uuid,
+    ) -> Result<Option<ItemImageResponse>> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(image) = Self::find_image(conn, tenant_id, item_id, image_id).await?
+                    else {
+                        return Ok(None);
+                    };
+                    Ok(Some(Self::make_primary(conn, tenant_id, &image).await?))
+                })
+            })
+            .await?;
+
+        match updated {
+            Some(image) => Ok(Some(self.to_response(storage.as_ref(), image).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Deletes an image and its stored files. When it was the primary image the oldest remaining
+    /// image takes its place. Returns false when the image does not exist.
+    pub async fn delete_image(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        image_id: Uuid,
+    ) -> Result<bool> {
+        let storage = self.storage()?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(image) = Self::find_image(conn, tenant_id, item_id, image_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    diesel::delete(item_images::table.find(image.id))
+                        .execute(conn)
+                        .await?;
+
+                    if image.is_primary {
+                        let next = item_images::table
+                            .filter(item_images::tenant_id.eq(tenant_id))
+                            .filter(item_images::item_id.eq(item_id))
+                            .order(item_images::created_at.asc())
+                            .select(ItemImage::as_select())
+                            .first::<ItemImage>(conn)
+                            .await
+                            .optional()?;
+                        if let Some(next) = next {
+                            Self::make_primary(conn, tenant_id, &next).await?;
+                        }
+                    }
+                    Ok(Some(image))
+                })
+            })
+            .await?;
+
+        let Some(image) = deleted else {
+            return Ok(false);
+        };
+        Self::remove_objects(
+            storage.as_ref(),
+            &[&image.storage_path, &image.thumbnail_path],
+        )
+        .await;
+        Ok(true)
+    }
+
+    // Private helper methods
+
+    fn storage(&self) -> Result<Arc<dyn StorageBackend>> {
+        self.storage
+            .clone()
+            .ok_or(ItemImageError::StorageNotConfigured.into())
+    }
+
+    async fn item_exists(conn: &mut AsyncPgConnection, item_id: Uuid) -> Result<bool> {
+        let found: i64 = items::table
+            .filter(items::id.eq(item_id))
+            .count()
+            .get_result(conn)
+            .await?;
+        Ok(found > 0)
+    }
+
+    async fn find_image(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        image_id: Uuid,
+    ) -> Result<Option<ItemImage>> {
+        Ok(item_images::table
+            .filter(item_images::id.eq(image_id))
+            .filter(item_images::tenant_id.eq(tenant_id))
+            .filter(item_images::item_id.eq(item_id))
+            .select(ItemImage::as_select())
+            .first::<ItemImage>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn primary_image(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<ItemImage>> {
+        Ok(item_images::table
+            .filter(item_images::tenant_id.eq(tenant_id))
+            .filter(item_images::item_id.eq(item_id))
+            .filter(item_images::is_primary.eq(true))
+            .select(ItemImage::as_select())
+            .first::<ItemImage>(conn)
+            .await
+            .optional()?)
+    }
+
+    // The current primary is cleared first; the unique index allows one primary per item
+    async fn make_primary(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        image: &ItemImage,
+    ) -> Result<ItemImage> {
+        diesel::update(
+            item_images::table
+                .filter(item_images::tenant_id.eq(tenant_id))
+                .filter(item_images::item_id.eq(image.item_id))
+                .filter(item_images::is_primary.eq(true))
+                .filter(item_images::id.ne(image.id)),
+        )
+        .set(item_images::is_primary.eq(false))
+        .execute(conn)
+        .await?;
+
+        Ok(diesel::update(item_images::table.find(image.id))
+            .set(item_images::is_primary.eq(true))
+            .returning(ItemImage::as_returning())
+            .get_result(conn)
+            .await?)
+    }
+
+    // Files left behind only waste space, so failures are logged rather than returned
+    async fn remove_objects(storage: &dyn StorageBackend, paths: &[&str]) {
+        for path in paths {
+            if let Err(e) = storage.delete(path).await {
+                tracing::warn!("Item image file {} not deleted: {}", path, e);
+            }
+        }
+    }
+
+    async fn to_response(
+        &self,
+        storage: &dyn StorageBackend,
+        image: ItemImage,
+    ) -> Result<ItemImageResponse> {
+        let urls_expire_at = Utc::now() + ChronoDuration::seconds(self.url_ttl.as_secs() as i64);
+        let url = storage
+            .signed_url(&image.storage_path, self.url_ttl)
+            .await?;
+        let thumbnail_url = storage
+            .signed_url(&image.thumbnail_path, self.url_ttl)
+            .await?;
+
+        Ok(ItemImageResponse {
+            id: image.id,
+            item_id: image.item_id,
+            content_type: image.content_type,
+            width: image.width,
+            height: image.height,
+            size_bytes: image.size_bytes,
+            caption: image.caption,
+            is_primary: image.is_primary,
+            uploaded_by_id: image.uploaded_by_id,
+            url,
+            thumbnail_url,
+            urls_expire_at,
+            created_at: image.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+}
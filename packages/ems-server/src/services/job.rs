@@ -1,17 +1,93 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    CreateJobIdResponse, CreateJobRequest, Job, JobPriority, JobResponse, JobStatus, JobType,
-    ManufacturingJob, ManufacturingJobData, ManufacturingJobResponse, NewJob, NewManufacturingJob,
-    NewQaJob, NewServiceJob, QaJob, QaJobData, QaJobResponse, ServiceJob, ServiceJobData,
-    ServiceJobResponse, UpdateJobRequest,
+    CompleteJobRequest, CreateJobIdResponse, CreateJobRequest, Job, JobPriority, JobResponse,
+    JobStatus, JobType, ManufacturingJob, ManufacturingJobData, ManufacturingJobResponse, NewJob,
+    NewJobHistory, NewManufacturingJob, NewQaJob, NewServiceJob, NumberingEntity, QaJob, QaJobData,
+    QaJobResponse, ServiceJob, ServiceJobData, ServiceJobResponse, StockReferenceType,
+    UpdateJobRequest,
 };
 use crate::schema::*;
-use crate::services::DatabaseService;
+use crate::services::{
+    BatchRecordService, DatabaseService, JobOperationService, NumberingService, StockService,
+};
+use crate::utils::AppError;
+
+/// Errors from the job services, so handlers can tell a refused step from a failure
+#[derive(Error, Debug)]
+pub enum JobError {
+    #[error("Job not found")]
+    NotFound,
+
+    #[error("Job cannot be archived: {0}")]
+    NotArchivable(String),
+
+    #[error("Job cannot be completed: {0}")]
+    NotCompletable(String),
+
+    #[error("Predecessor job not found")]
+    PredecessorNotFound,
+
+    #[error("The job already depends on that job")]
+    DuplicateDependency,
+
+    #[error("The dependency would make a cycle: that job already waits for this one")]
+    DependencyCycle,
+
+    #[error("Machine group not found")]
+    MachineGroupNotFound,
+
+    #[error("Asset not found")]
+    AssetNotFound,
+
+    #[error("Routing cannot be changed once an operation has started")]
+    RoutingStarted,
+
+    #[error("Operation cannot be started: {0}")]
+    NotStartable(String),
+
+    #[error("Operation cannot be started before the previous operation is completed")]
+    PreviousOperationOpen,
+
+    #[error("Operation cannot be completed: {0}")]
+    OperationNotCompletable(String),
+
+    #[error("Operation cannot be completed before it was started")]
+    CompletedBeforeStarted,
+
+    #[error("Substitute cannot be consumed: {0}")]
+    NotConsumable(String),
+
+    #[error("Invalid substitution: {0}")]
+    InvalidSubstitution(String),
+}
+
+impl From<JobError> for AppError {
+    fn from(error: JobError) -> Self {
+        match error {
+            JobError::NotFound => AppError::NotFound(error.to_string()),
+            JobError::PredecessorNotFound
+            | JobError::MachineGroupNotFound
+            | JobError::AssetNotFound
+            | JobError::InvalidSubstitution(_) => AppError::Validation(error.to_string()),
+            JobError::NotArchivable(_)
+            | JobError::NotCompletable(_)
+            | JobError::DuplicateDependency
+            | JobError::DependencyCycle
+            | JobError::RoutingStarted
+            | JobError::NotStartable(_)
+            | JobError::PreviousOperationOpen
+            | JobError::OperationNotCompletable(_)
+            | JobError::CompletedBeforeStarted
+            | JobError::NotConsumable(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
 
 pub struct JobService {
     database: DatabaseService,
@@ -29,19 +105,23 @@ impl JobService {
         tenant_id: Uuid,
         request: CreateJobRequest,
     ) -> Result<CreateJobIdResponse> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        let job_id = conn
-            .transaction::<_, diesel::result::Error, _>(|conn| {
+        let job_id = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
                 Box::pin(async move {
+                    // Number the job from its sequence unless given
+                    let job_number = NumberingService::number_or_draw(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::Job,
+                        request.job_number.clone(),
+                    )
+                    .await?;
+
                     // Create job record
                     let new_job = NewJob {
                         tenant_id,
-                        job_number: request.job_number.clone(),
+                        job_number,
                         item_id: request.item_id,
                         quantity: request.quantity,
                         assigned_person_id: request.assigned_person_id,
@@ -96,6 +176,7 @@ impl JobService {
                                 test_equipment: request.test_equipment,
                                 calibration_required: request.calibration_required,
                                 environmental_conditions: request.environmental_conditions,
+                                inspected_job_id: request.inspected_job_id,
                             };
                             diesel::insert_into(qa_job::table)
                                 .values(&new_qa)
@@ -124,8 +205,7 @@ impl JobService {
                     Ok(job.id)
                 })
             })
-            .await
-            .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+            .await?;
 
         Ok(CreateJobIdResponse { id: job_id })
     }
@@ -199,6 +279,7 @@ impl JobService {
                         test_equipment: q.test_equipment,
                         calibration_required: q.calibration_required,
                         environmental_conditions: q.environmental_conditions,
+                        inspected_job_id: q.inspected_job_id,
                     });
 
                     (None, qa, None)
@@ -228,6 +309,8 @@ impl JobService {
                 }
             };
 
+            let progress_percent = JobOperationService::progress_for(&mut conn, job_id).await?;
+
             Ok(Some(JobResponse {
                 id: job.id,
                 job_number: job.job_number,
@@ -253,6 +336,8 @@ impl JobService {
                 metadata: job.metadata,
                 created_at: job.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: job.updated_at.unwrap_or_else(|| Utc::now()),
+                archived: job.archived,
+                progress_percent,
                 manufacturing,
                 qa,
                 service,
@@ -281,11 +366,93 @@ impl JobService {
         Ok(())
     }
 
+    /// Archives or unarchives a job. Only closed jobs can be archived; a job unarchived by hand
+    /// is not archived automatically again. Returns `None` when the job does not exist.
+    pub async fn set_archived(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        archived: bool,
+    ) -> Result<Option<JobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(job) = jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select(Job::as_select())
+            .first::<Job>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        if archived && !JobStatus::try_from(job.status.clone()).is_ok_and(|s| s.is_closed()) {
+            return Err(JobError::NotArchivable(format!("status is {}", job.status)).into());
+        }
+
+        diesel::update(jobs::table.find(job_id))
+            .set((
+                jobs::archived.eq(archived),
+                jobs::archived_at.eq(if archived {
+                    Some(Utc::now())
+                } else {
+                    job.archived_at
+                }),
+            ))
+            .execute(&mut conn)
+            .await?;
+        drop(conn);
+
+        self.get_job_by_id(tenant_id, job_id).await
+    }
+
+    /// Archives the tenant's jobs closed before `closed_before`, leaving out jobs that were ever
+    /// archived before. A job counts as closed at its end date, or its last update without one.
+    pub async fn archive_closed(
+        &self,
+        tenant_id: Uuid,
+        closed_before: DateTime<Utc>,
+    ) -> Result<usize> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let closed: Vec<String> = JobStatus::CLOSED.iter().map(|s| s.to_string()).collect();
+        let archived = diesel::update(
+            jobs::table
+                .filter(jobs::tenant_id.eq(tenant_id))
+                .filter(jobs::archived.eq(false))
+                .filter(jobs::archived_at.is_null())
+                .filter(jobs::status.eq_any(closed))
+                .filter(
+                    jobs::end_date.lt(closed_before).or(jobs::end_date
+                        .is_null()
+                        .and(jobs::updated_at.lt(closed_before))),
+                ),
+        )
+        .set((
+            jobs::archived.eq(true),
+            jobs::archived_at.eq(Some(Utc::now())),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+        Ok(archived)
+    }
+
     pub async fn list_jobs(
         &self,
         tenant_id: Uuid,
         job_type: Option<JobType>,
         status: Option<JobStatus>,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<JobResponse>> {
@@ -309,6 +476,11 @@ impl JobService {
             query = query.filter(jobs::status.eq(status_filter.to_string()));
         }
 
+        // Leave out archived jobs unless asked for
+        if !include_archived {
+            query = query.filter(jobs::archived.eq(false));
+        }
+
         // Apply pagination
         if let Some(limit_val) = limit {
             query = query.limit(limit_val as i64);
@@ -343,7 +515,93 @@ impl JobService {
         // In a real implementation, this would update the job fields
         self.get_job_by_id(tenant_id, job_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Job not found"))
+            .ok_or(JobError::NotFound.into())
+    }
+
+    /// Completes an open job. Manufacturing jobs get their batch record assembled in the same
+    /// transaction, and any stock still reserved for the job is released. Returns `None` when
+    /// the job does not exist.
+    pub async fn complete_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        person_id: Uuid,
+        request: CompleteJobRequest,
+    ) -> Result<Option<JobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let completed = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(job) = jobs::table
+                        .filter(jobs::id.eq(job_id))
+                        .filter(jobs::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Job::as_select())
+                        .first::<Job>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(false);
+                    };
+
+                    if !matches!(
+                        JobStatus::try_from(job.status.clone()),
+                        Ok(JobStatus::Pending | JobStatus::InProgress | JobStatus::OnHold)
+                    ) {
+                        return Err(
+                            JobError::NotCompletable(format!("status is {}", job.status)).into(),
+                        );
+                    }
+
+                    let previous_status = job.status.clone();
+                    let job: Job = diesel::update(jobs::table.find(job_id))
+                        .set((
+                            jobs::status.eq(JobStatus::Completed.to_string()),
+                            jobs::end_date.eq(Some(request.end_date.unwrap_or_else(Utc::now))),
+                            jobs::labor_hours.eq(request.labor_hours.or(job.labor_hours)),
+                            jobs::materials_consumed
+                                .eq(request.materials_consumed.or(job.materials_consumed)),
+                            jobs::comments.eq(request.comments.or(job.comments)),
+                        ))
+                        .returning(Job::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    diesel::insert_into(job_history::table)
+                        .values(&NewJobHistory {
+                            job_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "complete".to_string(),
+                            previous_status: Some(previous_status),
+                            new_status: Some(JobStatus::Completed.to_string()),
+                            notes: None,
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    StockService::release_reservations_for(conn, StockReferenceType::Job, job_id)
+                        .await?;
+
+                    if job.job_type == JobType::Manufacturing.to_string() {
+                        BatchRecordService::assemble(conn, tenant_id, &job, Some(person_id))
+                            .await?;
+                    }
+
+                    Ok(true)
+                })
+            })
+            .await?;
+
+        if !completed {
+            return Ok(None);
+        }
+        self.get_job_by_id(tenant_id, job_id).await
     }
 
     // Type-specific implementations (simplified for brevity)
@@ -351,6 +609,7 @@ impl JobService {
     pub async fn list_manufacturing_jobs(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<ManufacturingJobResponse>> {
@@ -365,6 +624,11 @@ impl JobService {
             .filter(manufacturing_job::tenant_id.eq(tenant_id))
             .into_boxed();
 
+        // Leave out archived jobs unless asked for
+        if !include_archived {
+            query = query.filter(jobs::archived.eq(false));
+        }
+
         // Apply pagination
         if let Some(limit_val) = limit {
             query = query.limit(limit_val as i64);
@@ -421,13 +685,16 @@ impl JobService {
         job_id: Uuid,
     ) -> Result<Option<ManufacturingJobResponse>> {
         // Simplified implementation
-        let jobs = self.list_manufacturing_jobs(tenant_id, None, None).await?;
+        let jobs = self
+            .list_manufacturing_jobs(tenant_id, true, None, None)
+            .await?;
         Ok(jobs.into_iter().find(|j| j.id == job_id))
     }
 
     pub async fn list_qa_jobs(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<QaJobResponse>> {
@@ -442,6 +709,11 @@ impl JobService {
             .filter(qa_job::tenant_id.eq(tenant_id))
             .into_boxed();
 
+        // Leave out archived jobs unless asked for
+        if !include_archived {
+            query = query.filter(jobs::archived.eq(false));
+        }
+
         // Apply pagination
         if let Some(limit_val) = limit {
             query = query.limit(limit_val as i64);
@@ -479,6 +751,7 @@ impl JobService {
                 test_equipment: qa.test_equipment,
                 calibration_required: qa.calibration_required,
                 environmental_conditions: qa.environmental_conditions,
+                inspected_job_id: qa.inspected_job_id,
                 created_at: job.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: job.updated_at.unwrap_or_else(|| Utc::now()),
             })
@@ -493,13 +766,14 @@ impl JobService {
         job_id: Uuid,
     ) -> Result<Option<QaJobResponse>> {
         // Simplified implementation
-        let jobs = self.list_qa_jobs(tenant_id, None, None).await?;
+        let jobs = self.list_qa_jobs(tenant_id, true, None, None).await?;
         Ok(jobs.into_iter().find(|j| j.id == job_id))
     }
 
     pub async fn list_service_jobs(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<ServiceJobResponse>> {
@@ -514,6 +788,11 @@ impl JobService {
             .filter(service_job::tenant_id.eq(tenant_id))
             .into_boxed();
 
+        // Leave out archived jobs unless asked for
+        if !include_archived {
+            query = query.filter(jobs::archived.eq(false));
+        }
+
         // Apply pagination
         if let Some(limit_val) = limit {
             query = query.limit(limit_val as i64);
@@ -567,7 +846,7 @@ impl JobService {
         job_id: Uuid,
     ) -> Result<Option<ServiceJobResponse>> {
         // Simplified implementation
-        let jobs = self.list_service_jobs(tenant_id, None, None).await?;
+        let jobs = self.list_service_jobs(tenant_id, true, None, None).await?;
         Ok(jobs.into_iter().find(|j| j.id == job_id))
     }
 }
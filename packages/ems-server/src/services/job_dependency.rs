@@ -0,0 +1,394 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateJobDependencyRequest, GanttAssignment, GanttDependency, GanttJob, GanttResponse, Job,
+    JobAssignmentStatus, JobDependenciesResponse, JobDependency, JobDependencyResponse,
+    JobPriority, JobStatus, JobType, NewJobDependency,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, JobError};
+use crate::utils::job_dependency::{critical_path, would_create_cycle, ScheduledJob};
+
+// (assignment, job, machine, machine name, status, start, end)
+type AssignmentRow = (
+    Uuid,
+    Uuid,
+    Uuid,
+    String,
+    String,
+    DateTime<Utc>,
+    DateTime<Utc>,
+);
+
+pub struct JobDependencyService {
+    database: DatabaseService,
+}
+
+impl JobDependencyService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Job dependency operations
+
+    /// The jobs the job waits for and the jobs waiting for it. Returns `None` when the job does
+    /// not exist.
+    pub async fn list_dependencies(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<JobDependenciesResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_job_number(&mut conn, tenant_id, job_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let dependencies = job_dependencies::table
+            .filter(job_dependencies::tenant_id.eq(tenant_id))
+            .filter(
+                job_dependencies::predecessor_job_id
+                    .eq(job_id)
+                    .or(job_dependencies::successor_job_id.eq(job_id)),
+            )
+            .order(job_dependencies::created_at.asc())
+            .select(JobDependency::as_select())
+            .load::<JobDependency>(&mut conn)
+            .await?;
+        let (successors, predecessors) = Self::with_job_numbers(&mut conn, tenant_id, dependencies)
+            .await?
+            .into_iter()
+            .partition(|dependency| dependency.predecessor_job_id == job_id);
+
+        Ok(Some(JobDependenciesResponse {
+            job_id,
+            predecessors,
+            successors,
+        }))
+    }
+
+    /// Makes the job wait for the predecessor to finish before it starts. The predecessor must
+    /// belong to the tenant, and the dependency must not close a cycle. Returns `None` when the
+    /// job does not exist.
+    pub async fn add_dependency(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateJobDependencyRequest,
+    ) -> Result<Option<JobDependencyResponse>> {
+        let predecessor_id = request.predecessor_job_id;
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    if Self::find_job_number(conn, tenant_id, job_id)
+                        .await?
+                        .is_none()
+                    {
+                        return Ok(None);
+                    }
+                    if Self::find_job_number(conn, tenant_id, predecessor_id)
+                        .await?
+                        .is_none()
+                    {
+                        return Err(JobError::PredecessorNotFound.into());
+                    }
+
+                    // Additions wait for each other so that two of them cannot close a cycle
+                    // between them
+                    conn.batch_execute(
+                        "LOCK TABLE public.job_dependencies IN SHARE ROW EXCLUSIVE MODE",
+                    )
+                    .await?;
+
+                    let edges: Vec<(Uuid, Uuid)> = job_dependencies::table
+                        .filter(job_dependencies::tenant_id.eq(tenant_id))
+                        .select((
+                            job_dependencies::predecessor_job_id,
+                            job_dependencies::successor_job_id,
+                        ))
+                        .load(conn)
+                        .await?;
+                    if edges.contains(&(predecessor_id, job_id)) {
+                        return Err(JobError::DuplicateDependency.into());
+                    }
+                    if would_create_cycle(&edges, predecessor_id, job_id) {
+                        return Err(JobError::DependencyCycle.into());
+                    }
+
+                    let dependency = diesel::insert_into(job_dependencies::table)
+                        .values(&NewJobDependency {
+                            tenant_id,
+                            predecessor_job_id: predecessor_id,
+                            successor_job_id: job_id,
+                            created_by_id,
+                        })
+                        .returning(JobDependency::as_returning())
+                        .get_result::<JobDependency>(conn)
+                        .await?;
+                    Ok(Self::with_job_numbers(conn, tenant_id, vec![dependency])
+                        .await?
+                        .pop())
+                })
+            })
+            .await
+    }
+
+    /// Stops the job waiting for the predecessor. Returns whether there was such a dependency.
+    pub async fn remove_dependency(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        predecessor_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            job_dependencies::table
+                .filter(job_dependencies::tenant_id.eq(tenant_id))
+                .filter(job_dependencies::predecessor_job_id.eq(predecessor_id))
+                .filter(job_dependencies::successor_job_id.eq(job_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Gantt chart
+
+    /// Open and completed jobs scheduled within `[from, to)`, by their dates or their machine
+    /// assignments, with their assignments, the dependencies between them and the critical path.
+    /// Archived and cancelled jobs, and jobs without a schedule, are left out.
+    pub async fn gantt(
+        &self,
+        tenant_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<GanttResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Failed assignments no longer occupy the machine
+        let assigned_job_ids: Vec<Uuid> = machine_job_assignments::table
+            .inner_join(machines::table.on(machines::id.eq(machine_job_assignments::machine_id)))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machine_job_assignments::status.ne(JobAssignmentStatus::Failed.to_string()))
+            .filter(machine_job_assignments::start_time.lt(to))
+            .filter(machine_job_assignments::end_time.gt(from))
+            .select(machine_job_assignments::job_id)
+            .distinct()
+            .load(&mut conn)
+            .await?;
+
+        let jobs = jobs::table
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .filter(jobs::archived.eq(false))
+            .filter(jobs::status.ne(JobStatus::Cancelled.to_string()))
+            .filter(
+                jobs::start_date
+                    .lt(to)
+                    .and(jobs::end_date.gt(from))
+                    .or(jobs::id.eq_any(&assigned_job_ids)),
+            )
+            .select(Job::as_select())
+            .load::<Job>(&mut conn)
+            .await?;
+        let job_ids: Vec<Uuid> = jobs.iter().map(|job| job.id).collect();
+
+        let assignment_rows: Vec<AssignmentRow> = machine_job_assignments::table
+            .inner_join(machines::table.on(machines::id.eq(machine_job_assignments::machine_id)))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machine_job_assignments::job_id.eq_any(&job_ids))
+            .filter(machine_job_assignments::status.ne(JobAssignmentStatus::Failed.to_string()))
+            .filter(machine_job_assignments::start_time.is_not_null())
+            .filter(machine_job_assignments::end_time.is_not_null())
+            .order(machine_job_assignments::start_time.asc())
+            .select((
+                machine_job_assignments::id,
+                machine_job_assignments::job_id,
+                machine_job_assignments::machine_id,
+                machines::name,
+                machine_job_assignments::status,
+                machine_job_assignments::start_time.assume_not_null(),
+                machine_job_assignments::end_time.assume_not_null(),
+            ))
+            .load(&mut conn)
+            .await?;
+        let mut assignments_by_job: HashMap<Uuid, Vec<GanttAssignment>> = HashMap::new();
+        for (id, job_id, machine_id, machine_name, status, start_time, end_time) in assignment_rows
+        {
+            assignments_by_job
+                .entry(job_id)
+                .or_default()
+                .push(GanttAssignment {
+                    id,
+                    machine_id,
+                    machine_name,
+                    status: JobAssignmentStatus::try_from(status)
+                        .unwrap_or(JobAssignmentStatus::Pending),
+                    start_time,
+                    end_time,
+                });
+        }
+
+        let dependencies = job_dependencies::table
+            .filter(job_dependencies::tenant_id.eq(tenant_id))
+            .filter(job_dependencies::predecessor_job_id.eq_any(&job_ids))
+            .filter(job_dependencies::successor_job_id.eq_any(&job_ids))
+            .order(job_dependencies::created_at.asc())
+            .select(JobDependency::as_select())
+            .load::<JobDependency>(&mut conn)
+            .await?;
+
+        // A job spans its dates, falling back on its machine assignments for either end
+        let mut gantt_jobs: Vec<GanttJob> = jobs
+            .into_iter()
+            .filter_map(|job| {
+                let assignments = assignments_by_job.remove(&job.id).unwrap_or_default();
+                let start = job
+                    .start_date
+                    .or_else(|| assignments.iter().map(|a| a.start_time).min())?;
+                let end = job
+                    .end_date
+                    .or_else(|| assignments.iter().map(|a| a.end_time).max())?
+                    .max(start);
+                Some(GanttJob {
+                    id: job.id,
+                    job_number: job.job_number,
+                    job_type: JobType::try_from(job.job_type).unwrap_or(JobType::Manufacturing),
+                    status: JobStatus::try_from(job.status).unwrap_or(JobStatus::Pending),
+                    priority: JobPriority::try_from(
+                        job.priority.unwrap_or_else(|| "normal".to_string()),
+                    )
+                    .unwrap_or(JobPriority::Normal),
+                    item_id: job.item_id,
+                    start,
+                    end,
+                    due_date: job.due_date,
+                    assignments,
+                    slack_minutes: 0,
+                    is_critical: false,
+                    is_late: job.due_date.is_some_and(|due| end > due),
+                })
+            })
+            .collect();
+        gantt_jobs.sort_by(|a, b| (a.start, &a.job_number).cmp(&(b.start, &b.job_number)));
+
+        let scheduled: Vec<ScheduledJob> = gantt_jobs
+            .iter()
+            .map(|job| ScheduledJob {
+                id: job.id,
+                start: job.start,
+                end: job.end,
+            })
+            .collect();
+        let edges: Vec<(Uuid, Uuid)> = dependencies
+            .iter()
+            .map(|d| (d.predecessor_job_id, d.successor_job_id))
+            .collect();
+        let critical = critical_path(&scheduled, &edges);
+
+        for job in &mut gantt_jobs {
+            if let Some(timing) = critical.timings.get(&job.id) {
+                job.slack_minutes = timing.slack_minutes();
+                job.is_critical = timing.is_critical();
+            }
+        }
+        let ends: HashMap<Uuid, DateTime<Utc>> =
+            gantt_jobs.iter().map(|job| (job.id, job.end)).collect();
+        let starts: HashMap<Uuid, DateTime<Utc>> =
+            gantt_jobs.iter().map(|job| (job.id, job.start)).collect();
+        let dependencies = dependencies
+            .into_iter()
+            .filter(|d| {
+                starts.contains_key(&d.predecessor_job_id)
+                    && starts.contains_key(&d.successor_job_id)
+            })
+            .map(|d| GanttDependency {
+                id: d.id,
+                predecessor_job_id: d.predecessor_job_id,
+                successor_job_id: d.successor_job_id,
+                is_critical: critical.is_critical_link(d.predecessor_job_id, d.successor_job_id),
+                is_violated: starts[&d.successor_job_id] < ends[&d.predecessor_job_id],
+            })
+            .collect();
+
+        Ok(GanttResponse {
+            from,
+            to,
+            jobs: gantt_jobs,
+            dependencies,
+            critical_path: critical.path,
+            finish: critical.finish,
+        })
+    }
+
+    // Private helper methods
+
+    async fn find_job_number(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<String>> {
+        Ok(jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select(jobs::job_number)
+            .first::<String>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn with_job_numbers(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        dependencies: Vec<JobDependency>,
+    ) -> Result<Vec<JobDependencyResponse>> {
+        let job_ids: Vec<Uuid> = dependencies
+            .iter()
+            .flat_map(|d| [d.predecessor_job_id, d.successor_job_id])
+            .collect();
+        let numbers: HashMap<Uuid, String> = jobs::table
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .filter(jobs::id.eq_any(&job_ids))
+            .select((jobs::id, jobs::job_number))
+            .load::<(Uuid, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        let number = |id: &Uuid| numbers.get(id).cloned().unwrap_or_default();
+
+        Ok(dependencies
+            .into_iter()
+            .map(|d| JobDependencyResponse {
+                id: d.id,
+                predecessor_job_number: number(&d.predecessor_job_id),
+                predecessor_job_id: d.predecessor_job_id,
+                successor_job_number: number(&d.successor_job_id),
+                successor_job_id: d.successor_job_id,
+                created_by_id: d.created_by_id,
+                created_at: d.created_at.unwrap_or_else(Utc::now),
+            })
+            .collect())
+    }
+}
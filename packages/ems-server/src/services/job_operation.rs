@@ -0,0 +1,514 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::{
+    CompleteJobOperationRequest, Job, JobOperation, JobOperationAttachment, JobOperationResponse,
+    JobOperationStatus, JobRoutingResponse, JobStatus, NewJobHistory, NewJobOperation,
+    SetJobRoutingRequest, StartJobOperationRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, JobError};
+use crate::utils::job_operation::{elapsed_minutes, routing_progress};
+
+pub struct JobOperationService {
+    database: DatabaseService,
+}
+
+impl JobOperationService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// The job traveler. Returns `None` when the job does not exist.
+    pub async fn get_routing(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<JobRoutingResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let exists = jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select(jobs::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            Self::load_routing(&mut conn, tenant_id, job_id).await?,
+        ))
+    }
+
+    /// Replaces the routing of a job. The routing is fixed once an operation has started.
+    pub async fn set_routing(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        request: SetJobRoutingRequest,
+    ) -> Result<Option<JobRoutingResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let replaced = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let exists = jobs::table
+                        .filter(jobs::id.eq(job_id))
+                        .filter(jobs::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(jobs::id)
+                        .first::<Uuid>(conn)
+                        .await
+                        .optional()?;
+                    if exists.is_none() {
+                        return Ok(false);
+                    }
+
+                    let started: i64 = job_operations::table
+                        .filter(job_operations::job_id.eq(job_id))
+                        .filter(job_operations::status.ne(JobOperationStatus::Pending.to_string()))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if started > 0 {
+                        return Err(JobError::RoutingStarted.into());
+                    }
+
+                    let mut group_ids: Vec<Uuid> = request
+                        .operations
+                        .iter()
+                        .filter_map(|operation| operation.machine_group_id)
+                        .collect();
+                    group_ids.sort();
+                    group_ids.dedup();
+                    let found: i64 = machine_groups::table
+                        .filter(machine_groups::tenant_id.eq(tenant_id))
+                        .filter(machine_groups::id.eq_any(&group_ids))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if found != group_ids.len() as i64 {
+                        return Err(JobError::MachineGroupNotFound.into());
+                    }
+
+                    let mut asset_ids: Vec<Uuid> = request
+                        .operations
+                        .iter()
+                        .flat_map(|operation| operation.required_asset_ids.iter().copied())
+                        .collect();
+                    asset_ids.sort();
+                    asset_ids.dedup();
+                    let found: i64 = assets::table
+                        .filter(assets::tenant_id.eq(tenant_id))
+                        .filter(assets::id.eq_any(&asset_ids))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if found != asset_ids.len() as i64 {
+                        return Err(JobError::AssetNotFound.into());
+                    }
+
+                    diesel::delete(job_operations::table.filter(job_operations::job_id.eq(job_id)))
+                        .execute(conn)
+                        .await?;
+
+                    let new_operations: Vec<NewJobOperation> = request
+                        .operations
+                        .into_iter()
+                        .enumerate()
+                        .map(|(sequence, operation)| {
+                            let mut seen = HashSet::new();
+                            let mut required_asset_ids = operation.required_asset_ids;
+                            required_asset_ids.retain(|asset_id| seen.insert(*asset_id));
+                            NewJobOperation {
+                                tenant_id,
+                                job_id,
+                                sequence: sequence as i32,
+                                name: operation.name.trim().to_string(),
+                                machine_group_id: operation.machine_group_id,
+                                standard_minutes: operation.standard_minutes,
+                                instructions: operation.instructions,
+                                required_asset_ids: required_asset_ids
+                                    .into_iter()
+                                    .map(Some)
+                                    .collect(),
+                            }
+                        })
+                        .collect();
+                    if !new_operations.is_empty() {
+                        diesel::insert_into(job_operations::table)
+                            .values(&new_operations)
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(true)
+                })
+            })
+            .await?;
+
+        if !replaced {
+            return Ok(None);
+        }
+        Ok(Some(
+            Self::load_routing(&mut conn, tenant_id, job_id).await?,
+        ))
+    }
+
+    /// Starts an operation once the one before it is completed. Starting the first operation of
+    /// a pending job puts the job in progress. Returns `None` when the job or the operation does
+    /// not exist.
+    pub async fn start_operation(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        operation_id: Uuid,
+        person_id: Uuid,
+        request: StartJobOperationRequest,
+    ) -> Result<Option<JobOperationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let started = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some((job, operation)) =
+                        Self::lock_operation(conn, tenant_id, job_id, operation_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let job_status =
+                        JobStatus::try_from(job.status.clone()).unwrap_or(JobStatus::Pending);
+                    if !matches!(job_status, JobStatus::Pending | JobStatus::InProgress) {
+                        return Err(JobError::NotStartable(format!(
+                            "job status is {}",
+                            job.status
+                        ))
+                        .into());
+                    }
+                    if operation.status != JobOperationStatus::Pending.to_string() {
+                        return Err(JobError::NotStartable(format!(
+                            "status is {}",
+                            operation.status
+                        ))
+                        .into());
+                    }
+
+                    let previous = job_operations::table
+                        .filter(job_operations::job_id.eq(job_id))
+                        .filter(job_operations::sequence.lt(operation.sequence))
+                        .order(job_operations::sequence.desc())
+                        .select(job_operations::status)
+                        .first::<String>(conn)
+                        .await
+                        .optional()?;
+                    if previous
+                        .is_some_and(|status| status != JobOperationStatus::Completed.to_string())
+                    {
+                        return Err(JobError::PreviousOperationOpen.into());
+                    }
+
+                    let started_at = request.started_at.unwrap_or_else(Utc::now);
+                    let operation: JobOperation =
+                        diesel::update(job_operations::table.find(operation_id))
+                            .set((
+                                job_operations::status
+                                    .eq(JobOperationStatus::InProgress.to_string()),
+                                job_operations::started_at.eq(Some(started_at)),
+                                job_operations::started_by_id.eq(Some(person_id)),
+                                job_operations::notes.eq(request.notes.or(operation.notes)),
+                            ))
+                            .returning(JobOperation::as_returning())
+                            .get_result(conn)
+                            .await?;
+
+                    if job_status == JobStatus::Pending {
+                        diesel::update(jobs::table.find(job_id))
+                            .set((
+                                jobs::status.eq(JobStatus::InProgress.to_string()),
+                                jobs::start_date.eq(Some(job.start_date.unwrap_or(started_at))),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    diesel::insert_into(job_history::table)
+                        .values(&NewJobHistory {
+                            job_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "start_operation".to_string(),
+                            previous_status: Some(job.status),
+                            new_status: Some(JobStatus::InProgress.to_string()),
+                            notes: Some(operation.name.clone()),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(operation))
+                })
+            })
+            .await?;
+
+        match started {
+            Some(operation) => Ok(Some(
+                Self::to_responses(&mut conn, tenant_id, vec![operation])
+                    .await?
+                    .remove(0),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Completes a started operation with its actual time. Returns `None` when the job or the
+    /// operation does not exist.
+    pub async fn complete_operation(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        operation_id: Uuid,
+        person_id: Uuid,
+        request: CompleteJobOperationRequest,
+    ) -> Result<Option<JobOperationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let completed = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some((job, operation)) =
+                        Self::lock_operation(conn, tenant_id, job_id, operation_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    if operation.status != JobOperationStatus::InProgress.to_string() {
+                        return Err(JobError::OperationNotCompletable(format!(
+                            "status is {}",
+                            operation.status
+                        ))
+                        .into());
+                    }
+
+                    let completed_at = request.completed_at.unwrap_or_else(Utc::now);
+                    let started_at = operation.started_at.unwrap_or(completed_at);
+                    if completed_at < started_at {
+                        return Err(JobError::CompletedBeforeStarted.into());
+                    }
+                    let actual_minutes = request
+                        .actual_minutes
+                        .unwrap_or_else(|| elapsed_minutes(started_at, completed_at));
+
+                    let operation: JobOperation =
+                        diesel::update(job_operations::table.find(operation_id))
+                            .set((
+                                job_operations::status
+                                    .eq(JobOperationStatus::Completed.to_string()),
+                                job_operations::completed_at.eq(Some(completed_at)),
+                                job_operations::completed_by_id.eq(Some(person_id)),
+                                job_operations::actual_minutes.eq(Some(actual_minutes)),
+                                job_operations::notes.eq(request.notes.or(operation.notes)),
+                            ))
+                            .returning(JobOperation::as_returning())
+                            .get_result(conn)
+                            .await?;
+
+                    diesel::insert_into(job_history::table)
+                        .values(&NewJobHistory {
+                            job_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "complete_operation".to_string(),
+                            previous_status: Some(job.status.clone()),
+                            new_status: Some(job.status),
+                            notes: Some(operation.name.clone()),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(operation))
+                })
+            })
+            .await?;
+
+        match completed {
+            Some(operation) => Ok(Some(
+                Self::to_responses(&mut conn, tenant_id, vec![operation])
+                    .await?
+                    .remove(0),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Routing progress of a job; `None` when it has no routing. Runs on the caller's
+    /// connection, with the tenant context already set.
+    pub async fn progress_for(conn: &mut AsyncPgConnection, job_id: Uuid) -> Result<Option<f64>> {
+        let operations: Vec<(f64, String)> = job_operations::table
+            .filter(job_operations::job_id.eq(job_id))
+            .select((job_operations::standard_minutes, job_operations::status))
+            .load(conn)
+            .await?;
+
+        Ok(routing_progress(
+            &operations
+                .into_iter()
+                .map(|(minutes, status)| {
+                    (minutes, status == JobOperationStatus::Completed.to_string())
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    // Helper methods
+
+    async fn lock_operation(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        operation_id: Uuid,
+    ) -> Result<Option<(Job, JobOperation)>> {
+        let Some(job) = jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(Job::as_select())
+            .first::<Job>(conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let operation = job_operations::table
+            .filter(job_operations::id.eq(operation_id))
+            .filter(job_operations::job_id.eq(job_id))
+            .for_update()
+            .select(JobOperation::as_select())
+            .first::<JobOperation>(conn)
+            .await
+            .optional()?;
+
+        Ok(operation.map(|operation| (job, operation)))
+    }
+
+    async fn load_routing(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<JobRoutingResponse> {
+        let operations = job_operations::table
+            .filter(job_operations::job_id.eq(job_id))
+            .filter(job_operations::tenant_id.eq(tenant_id))
+            .order(job_operations::sequence.asc())
+            .select(JobOperation::as_select())
+            .load::<JobOperation>(conn)
+            .await?;
+
+        let progress_percent = routing_progress(
+            &operations
+                .iter()
+                .map(|operation| {
+                    (
+                        operation.standard_minutes,
+                        operation.status == JobOperationStatus::Completed.to_string(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        let operations = Self::to_responses(conn, tenant_id, operations).await?;
+
+        let completed: Vec<&JobOperationResponse> = operations
+            .iter()
+            .filter(|operation| operation.status == JobOperationStatus::Completed)
+            .collect();
+        Ok(JobRoutingResponse {
+            job_id,
+            completed_count: completed.len(),
+            progress_percent,
+            standard_minutes: operations.iter().map(|o| o.standard_minutes).sum(),
+            actual_minutes: completed.iter().filter_map(|o| o.actual_minutes).sum(),
+            operations,
+        })
+    }
+
+    /// Resolves the required documents of each operation.
+    async fn to_responses(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        operations: Vec<JobOperation>,
+    ) -> Result<Vec<JobOperationResponse>> {
+        let asset_ids: Vec<Uuid> = operations
+            .iter()
+            .flat_map(|operation| operation.required_asset_ids.iter().flatten().copied())
+            .collect();
+        let attachments: HashMap<Uuid, JobOperationAttachment> = assets::table
+            .filter(assets::tenant_id.eq(tenant_id))
+            .filter(assets::id.eq_any(&asset_ids))
+            .select((assets::id, assets::name, assets::version, assets::file_type))
+            .load::<(Uuid, String, Option<String>, Option<String>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(asset_id, name, version, file_type)| {
+                (
+                    asset_id,
+                    JobOperationAttachment {
+                        asset_id,
+                        name,
+                        version,
+                        file_type,
+                    },
+                )
+            })
+            .collect();
+
+        Ok(operations
+            .into_iter()
+            .map(|operation| JobOperationResponse {
+                required_attachments: operation
+                    .required_asset_ids
+                    .iter()
+                    .flatten()
+                    .filter_map(|asset_id| attachments.get(asset_id).cloned())
+                    .collect(),
+                status: JobOperationStatus::try_from(operation.status)
+                    .unwrap_or(JobOperationStatus::Pending),
+                id: operation.id,
+                job_id: operation.job_id,
+                sequence: operation.sequence,
+                name: operation.name,
+                machine_group_id: operation.machine_group_id,
+                standard_minutes: operation.standard_minutes,
+                instructions: operation.instructions,
+                started_at: operation.started_at,
+                started_by_id: operation.started_by_id,
+                completed_at: operation.completed_at,
+                completed_by_id: operation.completed_by_id,
+                actual_minutes: operation.actual_minutes,
+                notes: operation.notes,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,820 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::{json, Value};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, ClockEventType, CompleteJobOperationRequest, CreateKioskTerminalRequest,
+    JobOperationResponse, KioskBadgeIn, KioskClockRequest, KioskCompleteOperationRequest,
+    KioskDowntimeRequest, KioskOperatorResponse, KioskTerminal, KioskTerminalKeyResponse,
+    KioskTerminalResponse, MachineDowntimeReport, MachineDowntimeReportResponse, MachineStatus,
+    NewAuthAuditEvent, NewKioskTerminal, NewMachineDowntimeReport, NewOperatorBadge,
+    NewOperatorClockEvent, OperatorBadge, OperatorBadgeResponse, OperatorClockEvent,
+    OperatorClockEventResponse, Permission, SetOperatorBadgeRequest, KIOSK_BADGE_IN,
+    OPERATOR_CREDENTIALS_CHANGED,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, JobOperationService};
+use crate::utils::auth::AuthUtils;
+use crate::utils::kiosk::{clock_event_allowed, generate_kiosk_key, pin_lockout};
+use crate::utils::machine_credential::machine_key_prefix;
+use crate::utils::AppError;
+
+/// Errors from [`KioskService`] that are the operator's or the caller's to fix
+#[derive(Error, Debug)]
+pub enum KioskError {
+    #[error("Operator not recognised")]
+    NotRecognised,
+
+    #[error("Operator locked out until {0}")]
+    LockedOut(DateTime<Utc>),
+
+    #[error("Operator cannot clock in: already clocked in")]
+    AlreadyClockedIn,
+
+    #[error("Operator cannot clock out: not clocked in")]
+    NotClockedIn,
+
+    #[error("Downtime cannot be reported: machine is decommissioned")]
+    Decommissioned,
+}
+
+impl From<KioskError> for AppError {
+    fn from(error: KioskError) -> Self {
+        match error {
+            KioskError::NotRecognised => AppError::Authentication(error.to_string()),
+            KioskError::LockedOut(_) => AppError::Authorization(error.to_string()),
+            KioskError::AlreadyClockedIn
+            | KioskError::NotClockedIn
+            | KioskError::Decommissioned => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// How stale last_used_at may get before a request records its use again
+const LAST_USED_GRANULARITY_SECONDS: i64 = 60;
+
+/// Shop-floor kiosk terminals and the operators badging in on them. Terminals live in the
+/// shared database, since the auth middleware resolves a kiosk key before any tenant database
+/// is used; badges, clock events and downtime reports live in the tenant's database.
+pub struct KioskService {
+    database: DatabaseService,
+    shared: DatabaseService,
+}
+
+impl KioskService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+            database,
+        }
+    }
+
+    // Terminal methods
+
+    /// Provisions a terminal and returns it with its API key, which is not shown again.
+    pub async fn provision_terminal(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateKioskTerminalRequest,
+    ) -> Result<KioskTerminalKeyResponse> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let api_key = generate_kiosk_key();
+        let mut actions: Vec<String> = request.actions.iter().map(|a| a.to_string()).collect();
+        actions.sort();
+        actions.dedup();
+
+        let terminal = diesel::insert_into(kiosk_terminals::table)
+            .values(&NewKioskTerminal {
+                tenant_id,
+                name: request.name,
+                location: request.location,
+                actions,
+                key_prefix: machine_key_prefix(&api_key),
+                key_hash: AuthUtils::hash_token(&api_key),
+                created_by_id: Some(caller.person_id),
+            })
+            .returning(KioskTerminal::as_returning())
+            .get_result::<KioskTerminal>(&mut conn)
+            .await?;
+
+        Ok(KioskTerminalKeyResponse {
+            terminal: terminal.into(),
+            api_key,
+        })
+    }
+
+    /// The tenant's terminals, newest first, without their keys.
+    pub async fn list_terminals(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Vec<KioskTerminalResponse>> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let terminals = kiosk_terminals::table
+            .filter(kiosk_terminals::tenant_id.eq(tenant_id))
+            .order(kiosk_terminals::created_at.desc())
+            .select(KioskTerminal::as_select())
+            .load::<KioskTerminal>(&mut conn)
+            .await?;
+
+        Ok(terminals.into_iter().map(Into::into).collect())
+    }
+
+    /// Stops the terminal's key working. Returns `None` when the terminal does not exist.
+    pub async fn revoke_terminal(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        terminal_id: Uuid,
+    ) -> Result<Option<KioskTerminalResponse>> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Revoking again keeps the original revocation
+        diesel::update(
+            kiosk_terminals::table
+                .filter(kiosk_terminals::id.eq(terminal_id))
+                .filter(kiosk_terminals::tenant_id.eq(tenant_id))
+                .filter(kiosk_terminals::revoked_at.is_null()),
+        )
+        .set((
+            kiosk_terminals::revoked_at.eq(Some(Utc::now())),
+            kiosk_terminals::revoked_by_id.eq(Some(caller.person_id)),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+        let terminal = kiosk_terminals::table
+            .filter(kiosk_terminals::id.eq(terminal_id))
+            .filter(kiosk_terminals::tenant_id.eq(tenant_id))
+            .select(KioskTerminal::as_select())
+            .first::<KioskTerminal>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(terminal.map(Into::into))
+    }
+
+    /// The terminal a kiosk is calling from, so it knows which actions to offer.
+    pub async fn get_terminal(
+        &self,
+        tenant_id: Uuid,
+        terminal_id: Uuid,
+    ) -> Result<Option<KioskTerminalResponse>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let terminal = kiosk_terminals::table
+            .filter(kiosk_terminals::id.eq(terminal_id))
+            .filter(kiosk_terminals::tenant_id.eq(tenant_id))
+            .select(KioskTerminal::as_select())
+            .first::<KioskTerminal>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(terminal.map(Into::into))
+    }
+
+    /// The active terminal the kiosk key belongs to, in any tenant, recording its use.
+    pub async fn authenticate(&self, api_key: &str) -> Result<Option<KioskTerminal>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // The key decides the tenant, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let Some(terminal) = kiosk_terminals::table
+            .filter(kiosk_terminals::key_hash.eq(AuthUtils::hash_token(api_key)))
+            .filter(kiosk_terminals::revoked_at.is_null())
+            .select(KioskTerminal::as_select())
+            .first::<KioskTerminal>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        // Busy terminals make many requests a minute, so use is only recorded once a minute
+        let now = Utc::now();
+        if terminal.last_used_at.is_none_or(|last_used| {
+            now - last_used > Duration::seconds(LAST_USED_GRANULARITY_SECONDS)
+        }) {
+            // Set tenant context for RLS
+            conn.batch_execute(&format!(
+                "SET app.current_tenant_id = '{}'",
+                terminal.tenant_id
+            ))
+            .await?;
+
+            diesel::update(kiosk_terminals::table.find(terminal.id))
+                .set(kiosk_terminals::last_used_at.eq(Some(now)))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(Some(terminal))
+    }
+
+    // Operator badge methods
+
+    /// Every operator's badge and PIN status, by name, for managing kiosk credentials.
+    pub async fn list_badges(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Vec<OperatorBadgeResponse>> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let badges = operator_badges::table
+            .inner_join(person::table)
+            .filter(operator_badges::tenant_id.eq(tenant_id))
+            .order(person::name.asc())
+            .select((OperatorBadge::as_select(), person::name))
+            .load::<(OperatorBadge, String)>(&mut conn)
+            .await?;
+
+        Ok(badges
+            .into_iter()
+            .map(|(badge, name)| OperatorBadgeResponse::new(badge, name))
+            .collect())
+    }
+
+    /// Replaces how the person identifies on kiosks and clears any PIN lockout. Returns `None`
+    /// when the person is not in the tenant.
+    pub async fn set_badge(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        person_id: Uuid,
+        request: SetOperatorBadgeRequest,
+    ) -> Result<Option<OperatorBadgeResponse>> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(name) = Self::member_name(&mut conn, tenant_id, person_id).await? else {
+            return Ok(None);
+        };
+        drop(conn);
+
+        let pin_hash = request
+            .pin
+            .as_deref()
+            .map(AuthUtils::hash_local_password)
+            .transpose()?;
+        let details = json!({
+            "action": "set",
+            "changed_by_id": caller.person_id,
+            "badge": request.badge_id.is_some(),
+            "pin": pin_hash.is_some(),
+        });
+
+        let badge = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let badge = diesel::insert_into(operator_badges::table)
+                        .values(&NewOperatorBadge {
+                            tenant_id,
+                            person_id,
+                            badge_id: request.badge_id,
+                            pin_hash,
+                        })
+                        .on_conflict((operator_badges::tenant_id, operator_badges::person_id))
+                        .do_update()
+                        .set((
+                            operator_badges::badge_id.eq(excluded(operator_badges::badge_id)),
+                            operator_badges::pin_hash.eq(excluded(operator_badges::pin_hash)),
+                            operator_badges::failed_pin_attempts.eq(0),
+                            operator_badges::locked_until.eq(None::<DateTime<Utc>>),
+                        ))
+                        .returning(OperatorBadge::as_returning())
+                        .get_result::<OperatorBadge>(conn)
+                        .await?;
+
+                    Self::audit(
+                        conn,
+                        tenant_id,
+                        Some(person_id),
+                        OPERATOR_CREDENTIALS_CHANGED,
+                        "allowed",
+                        details,
+                    )
+                    .await?;
+
+                    Ok(badge)
+                })
+            })
+            .await?;
+
+        Ok(Some(OperatorBadgeResponse::new(badge, name)))
+    }
+
+    /// Removes the person's badge and PIN, so they can no longer badge in on kiosks. Returns
+    /// whether they had any.
+    pub async fn remove_badge(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        person_id: Uuid,
+    ) -> Result<bool> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let changed_by_id = caller.person_id;
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let deleted = diesel::delete(
+                        operator_badges::table
+                            .filter(operator_badges::tenant_id.eq(tenant_id))
+                            .filter(operator_badges::person_id.eq(person_id)),
+                    )
+                    .execute(conn)
+                    .await?;
+                    if deleted == 0 {
+                        return Ok(false);
+                    }
+
+                    Self::audit(
+                        conn,
+                        tenant_id,
+                        Some(person_id),
+                        OPERATOR_CREDENTIALS_CHANGED,
+                        "allowed",
+                        json!({ "action": "removed", "changed_by_id": changed_by_id }),
+                    )
+                    .await?;
+
+                    Ok(true)
+                })
+            })
+            .await
+    }
+
+    /// Lifts a PIN lockout before it runs out. Returns `None` when the person has no badge.
+    pub async fn unlock_badge(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        person_id: Uuid,
+    ) -> Result<Option<OperatorBadgeResponse>> {
+        caller.require(Permission::ManageKiosks)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(name) = Self::member_name(&mut conn, tenant_id, person_id).await? else {
+            return Ok(None);
+        };
+
+        let changed_by_id = caller.person_id;
+        drop(conn);
+        let badge = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(badge) = diesel::update(
+                        operator_badges::table
+                            .filter(operator_badges::tenant_id.eq(tenant_id))
+                            .filter(operator_badges::person_id.eq(person_id)),
+                    )
+                    .set((
+                        operator_badges::failed_pin_attempts.eq(0),
+                        operator_badges::locked_until.eq(None::<DateTime<Utc>>),
+                    ))
+                    .returning(OperatorBadge::as_returning())
+                    .get_result::<OperatorBadge>(conn)
+                    .await
+                    .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    Self::audit(
+                        conn,
+                        tenant_id,
+                        Some(person_id),
+                        OPERATOR_CREDENTIALS_CHANGED,
+                        "allowed",
+                        json!({ "action": "unlocked", "changed_by_id": changed_by_id }),
+                    )
+                    .await?;
+
+                    Ok(Some(badge))
+                })
+            })
+            .await?;
+
+        Ok(badge.map(|badge| OperatorBadgeResponse::new(badge, name)))
+    }
+
+    /// Operators with a PIN, by name, for picking oneself on the kiosk before entering it.
+    pub async fn list_operators(&self, tenant_id: Uuid) -> Result<Vec<KioskOperatorResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let operators = operator_badges::table
+            .inner_join(person::table)
+            .filter(operator_badges::tenant_id.eq(tenant_id))
+            .filter(operator_badges::pin_hash.is_not_null())
+            .filter(person::is_active.eq(true))
+            .order(person::name.asc())
+            .select((person::id, person::name))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?;
+
+        Ok(operators
+            .into_iter()
+            .map(|(person_id, name)| KioskOperatorResponse { person_id, name })
+            .collect())
+    }
+
+    // Kiosk actions
+
+    /// Clocks the operator in or out on the terminal.
+    pub async fn clock(
+        &self,
+        tenant_id: Uuid,
+        terminal_id: Uuid,
+        request: KioskClockRequest,
+    ) -> Result<OperatorClockEventResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let person_id =
+            Self::identify(&mut conn, tenant_id, terminal_id, &request.operator).await?;
+        let event_type = request.event_type;
+
+        drop(conn);
+        let event = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Locking the badge keeps two terminals from clocking the operator at once
+                    operator_badges::table
+                        .filter(operator_badges::tenant_id.eq(tenant_id))
+                        .filter(operator_badges::person_id.eq(person_id))
+                        .select(operator_badges::id)
+                        .for_update()
+                        .first::<Uuid>(conn)
+                        .await?;
+
+                    let last = operator_clock_events::table
+                        .filter(operator_clock_events::tenant_id.eq(tenant_id))
+                        .filter(operator_clock_events::person_id.eq(person_id))
+                        .order(operator_clock_events::occurred_at.desc())
+                        .select(operator_clock_events::event_type)
+                        .first::<String>(conn)
+                        .await
+                        .optional()?
+                        .and_then(|last| ClockEventType::try_from(last).ok());
+                    if !clock_event_allowed(last, event_type) {
+                        return Err(match event_type {
+                            ClockEventType::ClockIn => KioskError::AlreadyClockedIn,
+                            ClockEventType::ClockOut => KioskError::NotClockedIn,
+                        }
+                        .into());
+                    }
+
+                    Ok(diesel::insert_into(operator_clock_events::table)
+                        .values(&NewOperatorClockEvent {
+                            tenant_id,
+                            person_id,
+                            event_type: event_type.to_string(),
+                            occurred_at: Utc::now(),
+                            kiosk_terminal_id: Some(terminal_id),
+                        })
+                        .returning(OperatorClockEvent::as_returning())
+                        .get_result::<OperatorClockEvent>(conn)
+                        .await?)
+                })
+            })
+            .await?;
+
+        Ok(event.into())
+    }
+
+    /// Completes a started job operation as the operator. Returns `None` when the job or the
+    /// operation does not exist.
+    pub async fn complete_operation(
+        &self,
+        tenant_id: Uuid,
+        terminal_id: Uuid,
+        request: KioskCompleteOperationRequest,
+    ) -> Result<Option<JobOperationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let person_id =
+            Self::identify(&mut conn, tenant_id, terminal_id, &request.operator).await?;
+        drop(conn);
+
+        JobOperationService::new(self.database.clone())
+            .complete_operation(
+                tenant_id,
+                request.job_id,
+                request.operation_id,
+                person_id,
+                CompleteJobOperationRequest {
+                    completed_at: None,
+                    actual_minutes: request.actual_minutes,
+                    notes: request.notes,
+                },
+            )
+            .await
+    }
+
+    /// Takes the machine down as the operator reports, recording why. Returns `None` when the
+    /// machine does not exist.
+    pub async fn report_downtime(
+        &self,
+        tenant_id: Uuid,
+        terminal_id: Uuid,
+        request: KioskDowntimeRequest,
+    ) -> Result<Option<MachineDowntimeReportResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let person_id =
+            Self::identify(&mut conn, tenant_id, terminal_id, &request.operator).await?;
+        let machine_id = request.machine_id;
+        let new_status = request.status.unwrap_or(MachineStatus::Error).to_string();
+        let reason = request.reason.trim().to_string();
+
+        drop(conn);
+        let report = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(status) = machines::table
+                        .filter(machines::id.eq(machine_id))
+                        .filter(machines::tenant_id.eq(tenant_id))
+                        .select(machines::status)
+                        .for_update()
+                        .first::<String>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+                    if status == MachineStatus::Decommissioned.to_string() {
+                        return Err(KioskError::Decommissioned.into());
+                    }
+
+                    // The status history trigger logs the change for downtime reporting
+                    let now = Utc::now();
+                    diesel::update(machines::table.find(machine_id))
+                        .set((
+                            machines::status.eq(&new_status),
+                            machines::updated_at.eq(Some(now)),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(
+                        diesel::insert_into(machine_downtime_reports::table)
+                            .values(&NewMachineDowntimeReport {
+                                tenant_id,
+                                machine_id,
+                                reported_by_id: Some(person_id),
+                                previous_status: Some(status),
+                                new_status,
+                                reason,
+                                kiosk_terminal_id: Some(terminal_id),
+                                reported_at: now,
+                            })
+                            .returning(MachineDowntimeReport::as_returning())
+                            .get_result::<MachineDowntimeReport>(conn)
+                            .await?,
+                    ))
+                })
+            })
+            .await?;
+
+        Ok(report.map(Into::into))
+    }
+
+    // The active operator badging in on the terminal. A badge identifies its holder on its
+    // own; a PIN is checked against the picked person's, locking PIN entry after too many wrong
+    // ones. Refusals go to the sign-in audit trail. Runs outside any transaction so failed
+    // attempts are kept.
+    async fn identify(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        terminal_id: Uuid,
+        operator: &KioskBadgeIn,
+    ) -> Result<Uuid> {
+        let query = operator_badges::table
+            .inner_join(person::table)
+            .filter(operator_badges::tenant_id.eq(tenant_id))
+            .filter(person::is_active.eq(true))
+            .select(OperatorBadge::as_select())
+            .into_boxed();
+        let query = match (&operator.badge_id, operator.person_id) {
+            (Some(badge_id), _) => query.filter(operator_badges::badge_id.eq(badge_id.clone())),
+            (None, Some(person_id)) => query.filter(operator_badges::person_id.eq(person_id)),
+            (None, None) => return Err(KioskError::NotRecognised.into()),
+        };
+        let Some(badge) = query.first::<OperatorBadge>(conn).await.optional()? else {
+            Self::reject(
+                conn,
+                tenant_id,
+                operator.person_id,
+                terminal_id,
+                operator,
+                "unknown",
+            )
+            .await?;
+            return Err(KioskError::NotRecognised.into());
+        };
+
+        let now = Utc::now();
+        if operator.badge_id.is_none() {
+            if let Some(locked_until) = badge.locked_until.filter(|until| *until > now) {
+                Self::reject(
+                    conn,
+                    tenant_id,
+                    Some(badge.person_id),
+                    terminal_id,
+                    operator,
+                    "locked",
+                )
+                .await?;
+                return Err(KioskError::LockedOut(locked_until).into());
+            }
+
+            let pin_matches = match (&badge.pin_hash, &operator.pin) {
+                (Some(hash), Some(pin)) => AuthUtils::verify_local_password(pin, hash)?,
+                _ => false,
+            };
+            if !pin_matches {
+                let failed_attempts = diesel::update(operator_badges::table.find(badge.id))
+                    .set(
+                        operator_badges::failed_pin_attempts
+                            .eq(operator_badges::failed_pin_attempts + 1),
+                    )
+                    .returning(operator_badges::failed_pin_attempts)
+                    .get_result::<i32>(conn)
+                    .await?;
+                let locked_until = pin_lockout(failed_attempts, now);
+                if let Some(locked_until) = locked_until {
+                    diesel::update(operator_badges::table.find(badge.id))
+                        .set((
+                            operator_badges::failed_pin_attempts.eq(0),
+                            operator_badges::locked_until.eq(Some(locked_until)),
+                        ))
+                        .execute(conn)
+                        .await?;
+                }
+                let reason = if locked_until.is_some() {
+                    "wrong_pin_locked"
+                } else {
+                    "wrong_pin"
+                };
+                Self::reject(
+                    conn,
+                    tenant_id,
+                    Some(badge.person_id),
+                    terminal_id,
+                    operator,
+                    reason,
+                )
+                .await?;
+                return Err(KioskError::NotRecognised.into());
+            }
+        }
+
+        // A successful PIN starts the count of wrong ones over
+        let reset_pin = operator.badge_id.is_none()
+            && (badge.failed_pin_attempts > 0 || badge.locked_until.is_some());
+        diesel::update(operator_badges::table.find(badge.id))
+            .set((
+                operator_badges::last_used_at.eq(Some(now)),
+                operator_badges::failed_pin_attempts.eq(if reset_pin {
+                    0
+                } else {
+                    badge.failed_pin_attempts
+                }),
+                operator_badges::locked_until.eq(if reset_pin { None } else { badge.locked_until }),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(badge.person_id)
+    }
+
+    // Records a refused badge-in on the terminal in the sign-in audit trail
+    async fn reject(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Option<Uuid>,
+        terminal_id: Uuid,
+        operator: &KioskBadgeIn,
+        reason: &str,
+    ) -> Result<()> {
+        Self::audit(
+            conn,
+            tenant_id,
+            person_id,
+            KIOSK_BADGE_IN,
+            "rejected",
+            json!({
+                "method": operator.method(),
+                "reason": reason,
+                "kiosk_terminal_id": terminal_id,
+            }),
+        )
+        .await
+    }
+
+    async fn audit(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Option<Uuid>,
+        event: &str,
+        outcome: &str,
+        details: Value,
+    ) -> Result<()> {
+        diesel::insert_into(auth_audit_events::table)
+            .values(&NewAuthAuditEvent {
+                tenant_id,
+                person_id,
+                event: event.to_string(),
+                outcome: outcome.to_string(),
+                ip_prefix: None,
+                user_agent: None,
+                details: Some(details),
+            })
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    // Name of the person when they are in the tenant
+    async fn member_name(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<Option<String>> {
+        Ok(tenant_person::table
+            .inner_join(person::table)
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .filter(tenant_person::person_id.eq(person_id))
+            .select(person::name)
+            .first::<String>(conn)
+            .await
+            .optional()?)
+    }
+}
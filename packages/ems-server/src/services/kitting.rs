@@ -0,0 +1,411 @@
+use anyhow::{anyhow, Result};
+use diesel::dsl::not;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    ConsumeSubstituteRequest, ItemBom, ItemContext, Job, JobStatus, JobSubstitution,
+    JobSubstitutionResponse, KitCheckQuery, KitCheckResponse, KitComponent, NewJobHistory,
+    NewJobSubstitution, Quantity, RecordStockMovementRequest, ReservationStatus, StockMovementType,
+    StockReferenceType,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, JobError, StockService, UomService};
+use crate::utils::kitting::{kit_shortfall, propose_substitutes, substitutes_cover};
+
+/// Kitting of jobs from stock: which BOM components are short, the approved substitutes that
+/// could stand in for them, and the substitutes actually consumed.
+pub struct KittingService {
+    database: DatabaseService,
+}
+
+impl KittingService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Checks each BOM line of the job's item against the free stock of its component, and
+    /// proposes the line's substitutes with stock for any shortfall. Returns `None` when the
+    /// job does not exist.
+    pub async fn kit_check(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        query: KitCheckQuery,
+    ) -> Result<Option<KitCheckResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(job) = jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select(Job::as_select())
+            .first::<Job>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let context = query.context.unwrap_or(ItemContext::Store);
+
+        let lines = match job.item_id {
+            Some(item_id) => {
+                item_bom::table
+                    .filter(item_bom::tenant_id.eq(tenant_id))
+                    .filter(item_bom::parent_item_id.eq(item_id))
+                    .order((item_bom::assembly_order.asc(), item_bom::created_at.asc()))
+                    .select(ItemBom::as_select())
+                    .load::<ItemBom>(&mut conn)
+                    .await?
+            }
+            None => Vec::new(),
+        };
+
+        // Net quantities issued to the job, and substitutes consumed per BOM line
+        let issued: HashMap<Uuid, Quantity> = stock_movements::table
+            .filter(stock_movements::tenant_id.eq(tenant_id))
+            .filter(stock_movements::reference_type.eq(StockReferenceType::Job.to_string()))
+            .filter(stock_movements::reference_id.eq(job_id))
+            .group_by(stock_movements::item_id)
+            .select((
+                stock_movements::item_id,
+                diesel::dsl::sum(stock_movements::quantity),
+            ))
+            .load::<(Uuid, Option<Quantity>)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(item_id, quantity)| (item_id, -quantity.unwrap_or_default()))
+            .collect();
+        let mut substituted: HashMap<Uuid, Quantity> = HashMap::new();
+        for substitution in Self::load_substitutions(&mut conn, tenant_id, job_id).await? {
+            if let Some(bom_id) = substitution.bom_id {
+                *substituted.entry(bom_id).or_default() += substitution.quantity;
+            }
+        }
+
+        let mut kittable = true;
+        let mut components = Vec::with_capacity(lines.len());
+        for line in lines {
+            let per_unit = line.quantity.unwrap_or(Quantity::from(1));
+            let factor = match line.uom_id {
+                Some(uom_id) => {
+                    UomService::base_factor(&mut conn, tenant_id, line.component_item_id, uom_id)
+                        .await?
+                }
+                None => 1.0,
+            };
+            let per_unit = per_unit.scaled(factor).unwrap_or(per_unit);
+            let required = per_unit
+                .scaled(job.quantity as f64)
+                .ok_or_else(|| anyhow!("Kit quantity out of range for BOM line {}", line.id))?;
+
+            let issued = issued
+                .get(&line.component_item_id)
+                .copied()
+                .unwrap_or_default()
+                .max(Quantity::ZERO);
+            let substituted = substituted.get(&line.id).copied().unwrap_or_default();
+            // Stock this job already reserved counts as its own
+            let available = Self::free_stock(
+                &mut conn,
+                tenant_id,
+                line.component_item_id,
+                &context,
+                job_id,
+            )
+            .await?;
+            let (outstanding, shortfall) = kit_shortfall(required, issued, substituted, available);
+
+            let mut substitutes = Vec::new();
+            if shortfall > Quantity::ZERO {
+                let mut candidates = Vec::new();
+                for substitute_id in Self::line_substitutes(&line) {
+                    let free =
+                        Self::free_stock(&mut conn, tenant_id, substitute_id, &context, job_id)
+                            .await?;
+                    candidates.push((substitute_id, free));
+                }
+                substitutes = propose_substitutes(shortfall, &candidates);
+                if !line.is_optional.unwrap_or(false) && !substitutes_cover(shortfall, &substitutes)
+                {
+                    kittable = false;
+                }
+            }
+
+            components.push(KitComponent {
+                bom_id: line.id,
+                item_id: line.component_item_id,
+                part_number: None,
+                is_optional: line.is_optional.unwrap_or(false),
+                required,
+                issued,
+                substituted,
+                outstanding,
+                available,
+                shortfall,
+                substitutes,
+            });
+        }
+
+        // Part numbers of the components and their proposed substitutes
+        let item_ids: Vec<Uuid> = components
+            .iter()
+            .flat_map(|c| std::iter::once(c.item_id).chain(c.substitutes.iter().map(|s| s.item_id)))
+            .collect();
+        let part_numbers: HashMap<Uuid, String> = items::table
+            .filter(items::id.eq_any(&item_ids))
+            .select((items::id, items::internal_part_number))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+        for component in &mut components {
+            component.part_number = part_numbers.get(&component.item_id).cloned();
+            for substitute in &mut component.substitutes {
+                substitute.part_number = part_numbers.get(&substitute.item_id).cloned();
+            }
+        }
+
+        Ok(Some(KitCheckResponse {
+            job_id,
+            item_id: job.item_id,
+            quantity: job.quantity,
+            context,
+            kittable,
+            components,
+        }))
+    }
+
+    /// Issues an approved substitute of one of the job's BOM lines to the job and records it
+    /// against the component it replaces. Returns `None` when the job does not exist.
+    pub async fn consume_substitute(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+        person_id: Uuid,
+        request: ConsumeSubstituteRequest,
+    ) -> Result<Option<JobSubstitutionResponse>> {
+        let substitution = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(job) = jobs::table
+                        .filter(jobs::id.eq(job_id))
+                        .filter(jobs::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Job::as_select())
+                        .first::<Job>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    if !matches!(
+                        JobStatus::try_from(job.status.clone()),
+                        Ok(JobStatus::Pending | JobStatus::InProgress | JobStatus::OnHold)
+                    ) {
+                        return Err(JobError::NotConsumable(format!(
+                            "job status is {}",
+                            job.status
+                        ))
+                        .into());
+                    }
+
+                    let line = match job.item_id {
+                        Some(item_id) => item_bom::table
+                            .filter(item_bom::id.eq(request.bom_id))
+                            .filter(item_bom::tenant_id.eq(tenant_id))
+                            .filter(item_bom::parent_item_id.eq(item_id))
+                            .select(ItemBom::as_select())
+                            .first::<ItemBom>(conn)
+                            .await
+                            .optional()?,
+                        None => None,
+                    }
+                    .ok_or_else(|| {
+                        JobError::InvalidSubstitution(
+                            "BOM line is not on the job's item".to_string(),
+                        )
+                    })?;
+                    if !Self::line_substitutes(&line).contains(&request.substitute_item_id) {
+                        return Err(JobError::InvalidSubstitution(
+                            "item is not an approved substitute for the BOM line".to_string(),
+                        )
+                        .into());
+                    }
+
+                    let context = request.context.unwrap_or(ItemContext::Store);
+                    let movement = StockService::apply_movement(
+                        conn,
+                        tenant_id,
+                        request.substitute_item_id,
+                        Some(person_id),
+                        &RecordStockMovementRequest {
+                            context: context.clone(),
+                            movement_type: StockMovementType::Issue,
+                            quantity: request.quantity,
+                            reference_type: Some(StockReferenceType::Job),
+                            reference_id: Some(job_id),
+                            notes: request.notes.clone(),
+                            occurred_at: None,
+                            allow_reserved: false,
+                            uom_id: None,
+                            owner_id: None,
+                        },
+                    )
+                    .await?
+                    .ok_or_else(|| {
+                        JobError::InvalidSubstitution(format!(
+                            "no {} inventory of the substitute",
+                            context
+                        ))
+                    })?;
+
+                    let substitution: JobSubstitution =
+                        diesel::insert_into(job_substitutions::table)
+                            .values(&NewJobSubstitution {
+                                tenant_id,
+                                job_id,
+                                bom_id: Some(line.id),
+                                component_item_id: line.component_item_id,
+                                substitute_item_id: request.substitute_item_id,
+                                quantity: request.quantity,
+                                stock_movement_id: Some(movement.id),
+                                person_id: Some(person_id),
+                                notes: request.notes,
+                            })
+                            .returning(JobSubstitution::as_returning())
+                            .get_result(conn)
+                            .await?;
+
+                    diesel::insert_into(job_history::table)
+                        .values(&NewJobHistory {
+                            job_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "substitute".to_string(),
+                            previous_status: None,
+                            new_status: None,
+                            notes: Some(format!(
+                                "{} of item {} consumed in place of item {}",
+                                substitution.quantity,
+                                substitution.substitute_item_id,
+                                substitution.component_item_id
+                            )),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(substitution))
+                })
+            })
+            .await?;
+
+        Ok(substitution.map(Into::into))
+    }
+
+    /// Substitutes consumed by the job, oldest first. Returns `None` when the job does not
+    /// exist.
+    pub async fn list_substitutions(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<Vec<JobSubstitutionResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let exists = jobs::table
+            .filter(jobs::id.eq(job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select(jobs::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some();
+        if !exists {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            Self::load_substitutions(&mut conn, tenant_id, job_id)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        ))
+    }
+
+    /// Substitutes consumed by the job, oldest first, for callers holding a connection.
+    pub async fn load_substitutions(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Vec<JobSubstitution>> {
+        Ok(job_substitutions::table
+            .filter(job_substitutions::tenant_id.eq(tenant_id))
+            .filter(job_substitutions::job_id.eq(job_id))
+            .order(job_substitutions::created_at.asc())
+            .select(JobSubstitution::as_select())
+            .load::<JobSubstitution>(conn)
+            .await?)
+    }
+
+    // Helper methods
+
+    // Approved substitutes listed on the BOM line, without the component itself
+    fn line_substitutes(line: &ItemBom) -> Vec<Uuid> {
+        let mut substitutes: Vec<Uuid> = line
+            .substitutes
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .filter(|id| *id != line.component_item_id)
+            .collect();
+        substitutes.dedup();
+        substitutes
+    }
+
+    // On-hand stock of the item less what is reserved for other orders and jobs
+    async fn free_stock(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        context: &ItemContext,
+        job_id: Uuid,
+    ) -> Result<Quantity> {
+        let Some((inventory_id, on_hand)) = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .filter(inventory_items::context.eq(context.to_string()))
+            .select((inventory_items::id, inventory_items::quantity))
+            .first::<(Uuid, Option<Quantity>)>(conn)
+            .await
+            .optional()?
+        else {
+            return Ok(Quantity::ZERO);
+        };
+
+        let reserved_for_others = stock_reservations::table
+            .filter(stock_reservations::inventory_item_id.eq(inventory_id))
+            .filter(stock_reservations::status.eq(ReservationStatus::Active.to_string()))
+            .filter(not(stock_reservations::reference_type
+                .eq(StockReferenceType::Job.to_string())
+                .and(stock_reservations::reference_id.eq(job_id))))
+            .select(diesel::dsl::sum(stock_reservations::quantity))
+            .first::<Option<Quantity>>(conn)
+            .await?
+            .unwrap_or_default();
+
+        Ok((on_hand.unwrap_or_default() - reserved_for_others).max(Quantity::ZERO))
+    }
+}
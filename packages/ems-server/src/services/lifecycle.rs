@@ -0,0 +1,453 @@
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::{env, sync::Arc};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    Item, ItemLifecycle, ItemLifecycleAlert, ItemLifecycleCheck, LifecycleAlertResponse,
+    LifecycleAlertStatus, LifecycleCheckResponse, ListLifecycleAlertsQuery, NewItemLifecycleAlert,
+    NewItemLifecycleCheck,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, HttpPartDataProvider, PartDataProvider};
+use crate::utils::lifecycle::{needs_lifecycle_change, suggested_lifecycle};
+use crate::utils::AppError;
+
+/// Errors from [`LifecycleService`] that are not failures of the lookup itself
+#[derive(Error, Debug)]
+pub enum LifecycleError {
+    #[error("Part data provider not configured")]
+    NotConfigured,
+
+    #[error("Item has no manufacturer part number")]
+    NoPartNumber,
+
+    #[error("Lifecycle alert is not open")]
+    AlertNotOpen,
+}
+
+impl From<LifecycleError> for AppError {
+    fn from(error: LifecycleError) -> Self {
+        match error {
+            LifecycleError::NotConfigured => AppError::Unavailable(error.to_string()),
+            LifecycleError::NoPartNumber => AppError::Validation(error.to_string()),
+            LifecycleError::AlertNotOpen => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// A part is looked up again this many days after a successful check
+const DEFAULT_RECHECK_DAYS: i64 = 7;
+// A failed lookup is retried after this many minutes
+const ERROR_RETRY_MINUTES: i64 = 60;
+
+pub struct LifecycleService {
+    database: DatabaseService,
+    provider: Option<Arc<dyn PartDataProvider>>,
+    recheck_interval: ChronoDuration,
+}
+
+impl LifecycleService {
+    /// Uses the part data provider configured through environment variables and
+    /// LIFECYCLE_RECHECK_DAYS (default 7).
+    pub fn new(database: DatabaseService) -> Result<Self> {
+        let provider = HttpPartDataProvider::from_env()?
+            .map(|provider| Arc::new(provider) as Arc<dyn PartDataProvider>);
+        let recheck_days = env::var("LIFECYCLE_RECHECK_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|d| *d > 0)
+            .unwrap_or(DEFAULT_RECHECK_DAYS);
+
+        Ok(Self::with_provider(
+            database,
+            provider,
+            ChronoDuration::days(recheck_days),
+        ))
+    }
+
+    pub fn with_provider(
+        database: DatabaseService,
+        provider: Option<Arc<dyn PartDataProvider>>,
+        recheck_interval: ChronoDuration,
+    ) -> Self {
+        Self {
+            database,
+            provider,
+            recheck_interval,
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    // Lifecycle check operations
+
+    /// Items with a manufacturer part number that were never checked or are due for a recheck.
+    pub async fn due_items(&self, limit: i64) -> Result<Vec<Item>> {
+        let mut conn = self.database.get_connection().await?;
+
+        let due = items::table
+            .left_join(
+                item_lifecycle_checks::table.on(item_lifecycle_checks::item_id.eq(items::id)),
+            )
+            .filter(items::mfr_part_number.is_not_null())
+            .filter(
+                item_lifecycle_checks::next_check_at
+                    .is_null()
+                    .or(item_lifecycle_checks::next_check_at.le(Utc::now())),
+            )
+            .order(item_lifecycle_checks::checked_at.asc().nulls_first())
+            .limit(limit)
+            .select(Item::as_select())
+            .load::<Item>(&mut conn)
+            .await?;
+
+        Ok(due)
+    }
+
+    /// Looks an item up with the part data provider and records the result. When the reported
+    /// status means the item should move to NRND or obsolete, an alert is raised in every tenant
+    /// that stocks or builds with the item. Lookup failures are recorded on the check and retried.
+    pub async fn check_item(&self, item: &Item) -> Result<ItemLifecycleCheck> {
+        let provider = self.provider.clone().ok_or(LifecycleError::NotConfigured)?;
+        let mfr_part_number = item
+            .mfr_part_number
+            .as_deref()
+            .ok_or(LifecycleError::NoPartNumber)?;
+
+        let lookup = provider
+            .lifecycle(mfr_part_number, Some(&item.manufacturer))
+            .await;
+
+        let now = Utc::now();
+        let check = match &lookup {
+            Ok(part) => {
+                let reported_status = part.as_ref().map(|p| p.lifecycle_status.clone());
+                NewItemLifecycleCheck {
+                    item_id: item.id,
+                    provider: provider.name().to_string(),
+                    suggested_lifecycle: reported_status
+                        .as_deref()
+                        .and_then(suggested_lifecycle)
+                        .map(|l| l.to_string()),
+                    reported_status,
+                    last_error: None,
+                    checked_at: now,
+                    next_check_at: now + self.recheck_interval,
+                }
+            }
+            Err(e) => NewItemLifecycleCheck {
+                item_id: item.id,
+                provider: provider.name().to_string(),
+                reported_status: None,
+                suggested_lifecycle: None,
+                last_error: Some(e.to_string()),
+                checked_at: now,
+                next_check_at: now + ChronoDuration::minutes(ERROR_RETRY_MINUTES),
+            },
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Lifecycle checks apply to every tenant using the item, so clear any tenant context
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let check = diesel::insert_into(item_lifecycle_checks::table)
+            .values(&check)
+            .on_conflict(item_lifecycle_checks::item_id)
+            .do_update()
+            .set(&check)
+            .returning(ItemLifecycleCheck::as_returning())
+            .get_result::<ItemLifecycleCheck>(&mut conn)
+            .await?;
+
+        let current = item
+            .lifecycle
+            .clone()
+            .and_then(|l| ItemLifecycle::try_from(l).ok());
+        let suggested = check
+            .suggested_lifecycle
+            .clone()
+            .and_then(|l| ItemLifecycle::try_from(l).ok());
+
+        if let (Some(suggested), Some(reported_status)) = (suggested, &check.reported_status) {
+            if needs_lifecycle_change(current.as_ref(), &suggested) {
+                Self::raise_alerts(&mut conn, item, suggested, reported_status, provider.name())
+                    .await?;
+            }
+        }
+
+        Ok(check)
+    }
+
+    /// Checks one item right away and returns the result with the tenant's open alert for it.
+    pub async fn check_item_now(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<LifecycleCheckResponse>> {
+        if !self.is_configured() {
+            return Err(LifecycleError::NotConfigured.into());
+        }
+
+        let item = {
+            let mut conn = self.database.get_connection().await?;
+            items::table
+                .find(item_id)
+                .select(Item::as_select())
+                .first::<Item>(&mut conn)
+                .await
+                .optional()?
+        };
+        let Some(item) = item else {
+            return Ok(None);
+        };
+
+        let check = self.check_item(&item).await?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let alert = item_lifecycle_alerts::table
+            .filter(item_lifecycle_alerts::tenant_id.eq(tenant_id))
+            .filter(item_lifecycle_alerts::item_id.eq(item_id))
+            .filter(item_lifecycle_alerts::status.eq(LifecycleAlertStatus::Open.to_string()))
+            .order(item_lifecycle_alerts::created_at.desc())
+            .select(ItemLifecycleAlert::as_select())
+            .first::<ItemLifecycleAlert>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(Some(LifecycleCheckResponse::new(check, alert)))
+    }
+
+    // Lifecycle alert operations
+
+    pub async fn list_alerts(
+        &self,
+        tenant_id: Uuid,
+        query: ListLifecycleAlertsQuery,
+    ) -> Result<Vec<LifecycleAlertResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut alerts_query = item_lifecycle_alerts::table
+            .filter(item_lifecycle_alerts::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(status) = query.status {
+            alerts_query =
+                alerts_query.filter(item_lifecycle_alerts::status.eq(status.to_string()));
+        }
+        if let Some(item_id) = query.item_id {
+            alerts_query = alerts_query.filter(item_lifecycle_alerts::item_id.eq(item_id));
+        }
+
+        let alerts = alerts_query
+            .order(item_lifecycle_alerts::created_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(ItemLifecycleAlert::as_select())
+            .load::<ItemLifecycleAlert>(&mut conn)
+            .await?;
+
+        Ok(alerts.into_iter().map(|alert| alert.into()).collect())
+    }
+
+    pub async fn get_alert(
+        &self,
+        tenant_id: Uuid,
+        alert_id: Uuid,
+    ) -> Result<Option<LifecycleAlertResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let alert = item_lifecycle_alerts::table
+            .filter(item_lifecycle_alerts::id.eq(alert_id))
+            .filter(item_lifecycle_alerts::tenant_id.eq(tenant_id))
+            .select(ItemLifecycleAlert::as_select())
+            .first::<ItemLifecycleAlert>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(alert.map(|alert| alert.into()))
+    }
+
+    /// Accepts an open alert, moving the item to the suggested lifecycle.
+    pub async fn accept_alert(
+        &self,
+        tenant_id: Uuid,
+        alert_id: Uuid,
+        resolved_by_id: Option<Uuid>,
+    ) -> Result<Option<LifecycleAlertResponse>> {
+        self.resolve_alert(
+            tenant_id,
+            alert_id,
+            resolved_by_id,
+            LifecycleAlertStatus::Accepted,
+        )
+        .await
+    }
+
+    /// Dismisses an open alert, leaving the item lifecycle unchanged.
+    pub async fn dismiss_alert(
+        &self,
+        tenant_id: Uuid,
+        alert_id: Uuid,
+        resolved_by_id: Option<Uuid>,
+    ) -> Result<Option<LifecycleAlertResponse>> {
+        self.resolve_alert(
+            tenant_id,
+            alert_id,
+            resolved_by_id,
+            LifecycleAlertStatus::Dismissed,
+        )
+        .await
+    }
+
+    async fn resolve_alert(
+        &self,
+        tenant_id: Uuid,
+        alert_id: Uuid,
+        resolved_by_id: Option<Uuid>,
+        status: LifecycleAlertStatus,
+    ) -> Result<Option<LifecycleAlertResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let alert = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let alert = item_lifecycle_alerts::table
+                        .filter(item_lifecycle_alerts::id.eq(alert_id))
+                        .filter(item_lifecycle_alerts::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(ItemLifecycleAlert::as_select())
+                        .first::<ItemLifecycleAlert>(conn)
+                        .await
+                        .optional()?;
+
+                    let Some(alert) = alert else {
+                        return Ok(None);
+                    };
+                    if alert.status != LifecycleAlertStatus::Open.to_string() {
+                        return Err(LifecycleError::AlertNotOpen.into());
+                    }
+
+                    if status == LifecycleAlertStatus::Accepted {
+                        diesel::update(items::table.find(alert.item_id))
+                            .set((
+                                items::lifecycle.eq(Some(alert.suggested_lifecycle.clone())),
+                                items::updated_at.eq(Utc::now()),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let alert = diesel::update(item_lifecycle_alerts::table.find(alert.id))
+                        .set((
+                            item_lifecycle_alerts::status.eq(status.to_string()),
+                            item_lifecycle_alerts::resolved_by_id.eq(resolved_by_id),
+                            item_lifecycle_alerts::resolved_at.eq(Some(Utc::now())),
+                        ))
+                        .returning(ItemLifecycleAlert::as_returning())
+                        .get_result::<ItemLifecycleAlert>(conn)
+                        .await?;
+
+                    Ok(Some(alert))
+                })
+            })
+            .await?;
+
+        Ok(alert.map(|alert| alert.into()))
+    }
+
+    // Raises an open alert, unless one exists, in each tenant that stocks the item or uses it in
+    // a BOM. Suggested substitutes come from the tenant's BOM lines using the item.
+    async fn raise_alerts(
+        conn: &mut AsyncPgConnection,
+        item: &Item,
+        suggested: ItemLifecycle,
+        reported_status: &str,
+        provider: &str,
+    ) -> Result<()> {
+        let mut tenant_ids = inventory_items::table
+            .filter(inventory_items::item_id.eq(item.id))
+            .select(inventory_items::tenant_id)
+            .distinct()
+            .load::<Uuid>(conn)
+            .await?;
+        let bom_tenant_ids = item_bom::table
+            .filter(item_bom::component_item_id.eq(item.id))
+            .select(item_bom::tenant_id)
+            .distinct()
+            .load::<Uuid>(conn)
+            .await?;
+        tenant_ids.extend(bom_tenant_ids);
+        tenant_ids.sort();
+        tenant_ids.dedup();
+
+        for tenant_id in tenant_ids {
+            let substitutes = item_bom::table
+                .filter(item_bom::tenant_id.eq(tenant_id))
+                .filter(item_bom::component_item_id.eq(item.id))
+                .select(item_bom::substitutes)
+                .load::<Option<Vec<Option<Uuid>>>>(conn)
+                .await?;
+            let mut substitutes: Vec<Uuid> = substitutes
+                .into_iter()
+                .flatten()
+                .flatten()
+                .flatten()
+                .filter(|id| *id != item.id)
+                .collect();
+            substitutes.sort();
+            substitutes.dedup();
+
+            // Substitutes that are themselves at end of life are not worth suggesting
+            let retired = items::table
+                .filter(items::id.eq_any(&substitutes))
+                .filter(items::lifecycle.eq_any([
+                    ItemLifecycle::Nrfnd.to_string(),
+                    ItemLifecycle::Obsolete.to_string(),
+                ]))
+                .select(items::id)
+                .load::<Uuid>(conn)
+                .await?;
+            substitutes.retain(|id| !retired.contains(id));
+
+            diesel::insert_into(item_lifecycle_alerts::table)
+                .values(&NewItemLifecycleAlert {
+                    tenant_id,
+                    item_id: item.id,
+                    current_lifecycle: item.lifecycle.clone(),
+                    suggested_lifecycle: suggested.to_string(),
+                    reported_status: reported_status.to_string(),
+                    provider: provider.to_string(),
+                    substitutes: substitutes.into_iter().map(Some).collect(),
+                })
+                .on_conflict_do_nothing()
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
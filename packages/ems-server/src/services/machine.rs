@@ -1,31 +1,120 @@
-use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::dsl::IntoBoxed;
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::models::{
-    AssetRelationshipType, CreateMachineAssetRelationshipRequest,
+    Asset, AssetPinMode, AssetRelationshipType, AssetReleaseStatus, CalibrationRecord,
+    CallerContext, CapacityPlanResponse, CapacitySlot, CreateMachineAssetRelationshipRequest,
     CreateMachineItemRelationshipRequest, CreateMachineJobAssignmentRequest,
-    CreateMachineOperatorAssignmentRequest, CreateMachineRequest, HeartbeatRequest,
-    ItemRelationshipType, JobAssignmentStatus, Machine, MachineAction, MachineAssetRelationship,
-    MachineAssetRelationshipResponse, MachineCreateIdResponse, MachineItemRelationship,
-    MachineItemRelationshipResponse, MachineJobAssignment, MachineJobAssignmentResponse,
-    MachineOperatorAssignment, MachineOperatorAssignmentResponse, MachineProtocol, MachineResponse,
-    MachineStatus, NewMachine, NewMachineAssetRelationship, NewMachineItemRelationship,
-    NewMachineJobAssignment, NewMachineOperatorAssignment, OperatorAssignmentType,
-    UpdateMachineJobAssignmentRequest, UpdateMachineRequest,
+    CreateMachineOperatorAssignmentRequest, CreateMachineRequest, DataVersion,
+    DecommissionMachineRequest, FleetSummaryRow, HeartbeatBatchOutcome, HeartbeatBatchRequest,
+    HeartbeatBatchResponse, HeartbeatBatchResult, HeartbeatRequest, ItemRelationshipType,
+    JobAssignmentStatus, JobPriority, JobStatus, JobType, Machine, MachineAction,
+    MachineAssetRelationship, MachineAssetRelationshipResponse, MachineCalibrationDocument,
+    MachineCapacity, MachineCreateIdResponse, MachineDecommissionResponse, MachineDocsResponse,
+    MachineDocument, MachineDowntimeReport, MachineFeature, MachineFeatureCollection,
+    MachineFleetSummary, MachineItemRelationship, MachineItemRelationshipResponse,
+    MachineJobAssignment, MachineJobAssignmentResponse, MachineMaintenanceOrder,
+    MachineOperatorAssignment, MachineOperatorAssignmentCreateResponse,
+    MachineOperatorAssignmentResponse, MachineProtocol, MachineResponse, MachineStatus, NewMachine,
+    NewMachineAssetRelationship, NewMachineItemRelationship, NewMachineJobAssignment,
+    NewMachineOperatorAssignment, OperatorAssignmentType, Permission,
+    UpdateMachineJobAssignmentRequest, UpdateMachineRequest, MACHINE_DOCS_HISTORY_LIMIT,
 };
 use crate::schema::*;
-use crate::services::DatabaseService;
+use crate::services::{
+    AttendanceService, CalendarService, DatabaseService, HeartbeatBuffer, MachineAlertService,
+    MachineCommandService, ProductionService, SkillService, SpcService, TelemetryService,
+};
+use crate::utils::capacity::{day_start, hours_by_day, load_percent};
+use crate::utils::AppError;
+
+/// Errors from [`MachineService`], so handlers can tell a missing machine from a refused change
+#[derive(Error, Debug)]
+pub enum MachineError {
+    #[error("Machine not found")]
+    NotFound,
+
+    #[error("Asset not found")]
+    AssetNotFound,
+
+    #[error("Asset not released: {0}")]
+    AssetNotReleased(String),
+
+    #[error("Operator not certified: {0}")]
+    OperatorNotCertified(String),
+
+    #[error("Machine unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Command not found")]
+    CommandNotFound,
+
+    #[error("Command already closed: {0}")]
+    CommandClosed(String),
+
+    #[error("Machine has no active credential")]
+    CredentialNotFound,
+
+    #[error("Machine decommissioned: {0}")]
+    Decommissioned(String),
+
+    #[error("Invalid machine change: {0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] diesel::result::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<MachineError> for AppError {
+    fn from(error: MachineError) -> Self {
+        match error {
+            MachineError::NotFound
+            | MachineError::AssetNotFound
+            | MachineError::CommandNotFound
+            | MachineError::CredentialNotFound => AppError::NotFound(error.to_string()),
+            MachineError::AssetNotReleased(_)
+            | MachineError::OperatorNotCertified(_)
+            | MachineError::Unavailable(_)
+            | MachineError::CommandClosed(_)
+            | MachineError::Decommissioned(_) => AppError::Conflict(error.to_string()),
+            MachineError::Invalid(message) => AppError::Validation(message),
+            MachineError::Database(error) => AppError::from_database(error),
+            MachineError::Other(error) => AppError::from_service(error),
+        }
+    }
+}
+
+type Result<T, E = MachineError> = std::result::Result<T, E>;
 
 pub struct MachineService {
     database: DatabaseService,
+    heartbeat_buffer: Option<HeartbeatBuffer>,
 }
 
 impl MachineService {
     pub fn new(database: DatabaseService) -> Self {
-        Self { database }
+        Self {
+            database,
+            heartbeat_buffer: None,
+        }
+    }
+
+    /// Writes heartbeats' machine state and telemetry, and their SPC, production and alert
+    /// processing, through the heartbeat buffer.
+    pub fn with_buffer(mut self, heartbeat_buffer: Option<HeartbeatBuffer>) -> Self {
+        self.heartbeat_buffer = heartbeat_buffer;
+        self
     }
 
     // Machine CRUD operations
@@ -41,6 +130,10 @@ impl MachineService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        if let Some(gateway_id) = request.gateway_id {
+            Self::ensure_gateway(&mut conn, tenant_id, None, gateway_id).await?;
+        }
+
         let new_machine = NewMachine {
             tenant_id,
             name: request.name,
@@ -52,6 +145,10 @@ impl MachineService {
             payload: request.payload,
             last_heartbeat: None,
             metadata: request.metadata,
+            latitude: request.latitude,
+            longitude: request.longitude,
+            site: request.site,
+            gateway_id: request.gateway_id,
         };
 
         let machine: Machine = diesel::insert_into(machines::table)
@@ -96,6 +193,14 @@ impl MachineService {
                 payload: machine.payload,
                 last_heartbeat: machine.last_heartbeat,
                 metadata: machine.metadata,
+                latitude: machine.latitude,
+                longitude: machine.longitude,
+                site: machine.site,
+                decommissioned_at: machine.decommissioned_at,
+                decommission_reason: machine.decommission_reason,
+                decommissioned_by: machine.decommissioned_by,
+                final_meter_readings: machine.final_meter_readings,
+                gateway_id: machine.gateway_id,
                 created_at: machine.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: machine.updated_at.unwrap_or_else(|| Utc::now()),
             }))
@@ -109,6 +214,7 @@ impl MachineService {
         tenant_id: Uuid,
         status: Option<MachineStatus>,
         protocol: Option<MachineProtocol>,
+        site: Option<String>,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<MachineResponse>> {
@@ -118,17 +224,7 @@ impl MachineService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        let mut query = machines::table
-            .filter(machines::tenant_id.eq(tenant_id))
-            .into_boxed();
-
-        if let Some(status_filter) = &status {
-            query = query.filter(machines::status.eq(status_filter.to_string()));
-        }
-
-        if let Some(protocol_filter) = &protocol {
-            query = query.filter(machines::protocol.eq(protocol_filter.to_string()));
-        }
+        let mut query = Self::listed_machines(tenant_id, status, protocol, site);
 
         if let Some(limit_val) = limit {
             query = query.limit(limit_val as i64);
@@ -157,6 +253,14 @@ impl MachineService {
                 payload: machine.payload,
                 last_heartbeat: machine.last_heartbeat,
                 metadata: machine.metadata,
+                latitude: machine.latitude,
+                longitude: machine.longitude,
+                site: machine.site,
+                decommissioned_at: machine.decommissioned_at,
+                decommission_reason: machine.decommission_reason,
+                decommissioned_by: machine.decommissioned_by,
+                final_meter_readings: machine.final_meter_readings,
+                gateway_id: machine.gateway_id,
                 created_at: machine.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: machine.updated_at.unwrap_or_else(|| Utc::now()),
             });
@@ -165,121 +269,252 @@ impl MachineService {
         Ok(machine_responses)
     }
 
-    pub async fn update_machine(
+    /// Version of the machines [`Self::list_machines`] lists with these filters, across all
+    /// pages.
+    pub async fn list_version(
         &self,
         tenant_id: Uuid,
-        machine_id: Uuid,
-        request: UpdateMachineRequest,
-    ) -> Result<MachineResponse> {
+        status: Option<MachineStatus>,
+        protocol: Option<MachineProtocol>,
+        site: Option<String>,
+    ) -> Result<DataVersion> {
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        // Update fields individually to avoid Diesel type issues
-        if let Some(name) = &request.name {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::name.eq(name))
-            .execute(&mut conn)
+        let (rows, updated_at) = Self::listed_machines(tenant_id, status, protocol, site)
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(machines::updated_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)
             .await?;
-        }
 
-        if let Some(ip) = &request.ip {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::ip.eq(ip))
-            .execute(&mut conn)
-            .await?;
-        }
+        Ok(DataVersion::new(rows, &[updated_at]))
+    }
 
-        if let Some(port) = request.port {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::port.eq(port))
-            .execute(&mut conn)
-            .await?;
-        }
+    /// Version of the machine [`Self::get_machine_by_id`] returns, or `None` when there is none.
+    pub async fn machine_version(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<Option<DataVersion>> {
+        let mut conn = self.database.get_connection().await?;
 
-        if let Some(protocol) = &request.protocol {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::protocol.eq(protocol.to_string()))
-            .execute(&mut conn)
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
-        }
 
-        if let Some(status) = &request.status {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::status.eq(status.to_string()))
-            .execute(&mut conn)
-            .await?;
+        let updated_at = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::updated_at)
+            .first::<Option<DateTime<Utc>>>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(updated_at.map(|updated_at| DataVersion::new(1, &[updated_at])))
+    }
+
+    /// The tenant's machines, narrowed by the list filters.
+    fn listed_machines(
+        tenant_id: Uuid,
+        status: Option<MachineStatus>,
+        protocol: Option<MachineProtocol>,
+        site: Option<String>,
+    ) -> IntoBoxed<'static, machines::table, Pg> {
+        let mut query = machines::table
+            .into_boxed()
+            .filter(machines::tenant_id.eq(tenant_id));
+
+        // Decommissioned machines are only listed when asked for by status
+        match &status {
+            Some(status_filter) => {
+                query = query.filter(machines::status.eq(status_filter.to_string()));
+            }
+            None => {
+                query =
+                    query.filter(machines::status.ne(MachineStatus::Decommissioned.to_string()));
+            }
         }
 
-        if let Some(action) = &request.action {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::action.eq(action.to_string()))
-            .execute(&mut conn)
-            .await?;
+        if let Some(protocol_filter) = &protocol {
+            query = query.filter(machines::protocol.eq(protocol_filter.to_string()));
         }
 
-        if let Some(payload) = &request.payload {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::payload.eq(payload))
-            .execute(&mut conn)
-            .await?;
+        if let Some(site_filter) = site {
+            query = query.filter(machines::site.eq(site_filter));
         }
 
-        if let Some(metadata) = &request.metadata {
-            diesel::update(
-                machines::table
-                    .filter(machines::id.eq(machine_id))
-                    .filter(machines::tenant_id.eq(tenant_id)),
-            )
-            .set(machines::metadata.eq(metadata))
-            .execute(&mut conn)
+        query
+    }
+
+    pub async fn update_machine(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: UpdateMachineRequest,
+    ) -> Result<MachineResponse> {
+        self.database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::ensure_in_service(conn, tenant_id, machine_id).await?;
+
+                    // Update fields individually to avoid Diesel type issues
+                    if let Some(name) = &request.name {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::name.eq(name))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(ip) = &request.ip {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::ip.eq(ip))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(port) = request.port {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::port.eq(port))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(protocol) = &request.protocol {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::protocol.eq(protocol.to_string()))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(status) = &request.status {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::status.eq(status.to_string()))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(action) = &request.action {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::action.eq(action.to_string()))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(payload) = &request.payload {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::payload.eq(payload))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(metadata) = &request.metadata {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::metadata.eq(metadata))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let (Some(latitude), Some(longitude)) = (request.latitude, request.longitude)
+                    {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set((
+                            machines::latitude.eq(latitude),
+                            machines::longitude.eq(longitude),
+                        ))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(site) = &request.site {
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::site.eq(site))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(gateway_id) = request.gateway_id {
+                        Self::ensure_gateway(conn, tenant_id, Some(machine_id), gateway_id).await?;
+                        diesel::update(
+                            machines::table
+                                .filter(machines::id.eq(machine_id))
+                                .filter(machines::tenant_id.eq(tenant_id)),
+                        )
+                        .set(machines::gateway_id.eq(gateway_id))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    Ok(())
+                })
+            })
             .await?;
-        }
 
         // Return the updated machine
         self.get_machine_by_id(tenant_id, machine_id)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Machine not found"))
+            .ok_or(MachineError::NotFound)
     }
 
-    pub async fn delete_machine(&self, tenant_id: Uuid, machine_id: Uuid) -> Result<()> {
+    pub async fn delete_machine(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+    ) -> Result<()> {
+        caller.require(Permission::DeleteMachine)?;
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        diesel::delete(
+        let deleted = diesel::delete(
             machines::table
                 .filter(machines::id.eq(machine_id))
                 .filter(machines::tenant_id.eq(tenant_id)),
@@ -287,9 +522,272 @@ impl MachineService {
         .execute(&mut conn)
         .await?;
 
+        if deleted == 0 {
+            return Err(MachineError::NotFound);
+        }
+        if let Some(buffer) = &self.heartbeat_buffer {
+            buffer.forget_machine(tenant_id, machine_id);
+        }
         Ok(())
     }
 
+    /// Takes a machine out of service for good while keeping its data queryable. Records why,
+    /// when and by whom with its final meter readings, detaches its operators and fails its
+    /// open job assignments; the machine's status is frozen from then on.
+    pub async fn decommission_machine(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+        request: DecommissionMachineRequest,
+    ) -> Result<MachineDecommissionResponse> {
+        caller.require(Permission::DeleteMachine)?;
+        request.check().map_err(MachineError::Invalid)?;
+
+        let decommissioned_by = caller.person_id;
+        let (detached_operators, closed_assignments) = self
+            .database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::ensure_in_service(conn, tenant_id, machine_id).await?;
+                    Self::retire_machine(
+                        conn,
+                        machine_id,
+                        request.decommissioned_at.unwrap_or_else(Utc::now),
+                        request.reason.trim(),
+                        Some(decommissioned_by),
+                        request.final_meter_readings.unwrap_or_default(),
+                    )
+                    .await
+                })
+            })
+            .await?;
+
+        if let Some(buffer) = &self.heartbeat_buffer {
+            buffer.forget_machine(tenant_id, machine_id);
+        }
+        tracing::info!(
+            "Machine {} decommissioned; {} operator(s) detached, {} assignment(s) closed",
+            machine_id,
+            detached_operators,
+            closed_assignments
+        );
+
+        let machine = self
+            .get_machine_by_id(tenant_id, machine_id)
+            .await?
+            .ok_or(MachineError::NotFound)?;
+        Ok(MachineDecommissionResponse {
+            machine,
+            detached_operators,
+            closed_assignments,
+        })
+    }
+
+    /// Marks a machine decommissioned on a connection the caller set up. The latest reading of
+    /// each telemetry channel is kept as a final meter reading unless one was given for it.
+    /// Returns the operator assignments detached and the job assignments closed.
+    pub(crate) async fn retire_machine(
+        conn: &mut AsyncPgConnection,
+        machine_id: Uuid,
+        decommissioned_at: DateTime<Utc>,
+        reason: &str,
+        decommissioned_by: Option<Uuid>,
+        readings: BTreeMap<String, f64>,
+    ) -> Result<(usize, usize)> {
+        let mut final_readings: BTreeMap<String, f64> = machine_telemetry::table
+            .filter(machine_telemetry::machine_id.eq(machine_id))
+            .distinct_on(machine_telemetry::channel)
+            .order((
+                machine_telemetry::channel,
+                machine_telemetry::recorded_at.desc(),
+            ))
+            .select((machine_telemetry::channel, machine_telemetry::value))
+            .load::<(String, f64)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        final_readings.extend(readings);
+
+        let updated = diesel::update(
+            machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::status.ne(MachineStatus::Decommissioned.to_string())),
+        )
+        .set((
+            machines::status.eq(MachineStatus::Decommissioned.to_string()),
+            machines::action.eq(None::<String>),
+            machines::decommissioned_at.eq(decommissioned_at),
+            machines::decommission_reason.eq(reason),
+            machines::decommissioned_by.eq(decommissioned_by),
+            machines::final_meter_readings.eq(serde_json::json!(final_readings)),
+            machines::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)
+        .await?;
+        if updated == 0 {
+            return Err(MachineError::Decommissioned(machine_id.to_string()));
+        }
+
+        let detached_operators = diesel::delete(
+            machine_operator_assignments::table
+                .filter(machine_operator_assignments::machine_id.eq(machine_id)),
+        )
+        .execute(conn)
+        .await?;
+
+        let closed_assignments = diesel::update(
+            machine_job_assignments::table
+                .filter(machine_job_assignments::machine_id.eq(machine_id))
+                .filter(machine_job_assignments::status.eq_any([
+                    JobAssignmentStatus::Pending.to_string(),
+                    JobAssignmentStatus::InProgress.to_string(),
+                ])),
+        )
+        .set((
+            machine_job_assignments::status.eq(JobAssignmentStatus::Failed.to_string()),
+            machine_job_assignments::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)
+        .await?;
+
+        Ok((detached_operators, closed_assignments))
+    }
+
+    /// Machines with coordinates as a GeoJSON feature collection for map views.
+    pub async fn get_machine_map(
+        &self,
+        tenant_id: Uuid,
+        status: Option<MachineStatus>,
+        site: Option<String>,
+    ) -> Result<MachineFeatureCollection> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::latitude.is_not_null())
+            .filter(machines::longitude.is_not_null())
+            .into_boxed();
+
+        // Decommissioned machines are only listed when asked for by status
+        match &status {
+            Some(status_filter) => {
+                query = query.filter(machines::status.eq(status_filter.to_string()));
+            }
+            None => {
+                query =
+                    query.filter(machines::status.ne(MachineStatus::Decommissioned.to_string()));
+            }
+        }
+
+        if let Some(site_filter) = &site {
+            query = query.filter(machines::site.eq(site_filter));
+        }
+
+        let machines = query
+            .order(machines::name.asc())
+            .select(Machine::as_select())
+            .load::<Machine>(&mut conn)
+            .await?;
+
+        Ok(MachineFeatureCollection::new(
+            machines
+                .into_iter()
+                .filter_map(MachineFeature::from_machine)
+                .collect(),
+        ))
+    }
+
+    // Fleet summary
+
+    /// Counts by status, protocol and category, the most recently faulted machines and the
+    /// machines with stale heartbeats, all in one query so the fleet overview needs no full list.
+    pub async fn get_fleet_summary(
+        &self,
+        tenant_id: Uuid,
+        limit: u32,
+        stale_after_minutes: u32,
+    ) -> Result<MachineFleetSummary> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let now = Utc::now();
+        let stale_before = now - Duration::minutes(stale_after_minutes as i64);
+
+        // A machine's latest entry into the error status comes from its status history; only
+        // machines reporting themselves idle or busy can have a stale heartbeat
+        let query = r#"
+            WITH fleet AS (
+                SELECT id, name, status, protocol, site,
+                       COALESCE(NULLIF(metadata->>'category', ''), 'uncategorized') AS category,
+                       last_heartbeat
+                FROM machines
+                WHERE tenant_id = $1
+                  AND status <> 'decommissioned'
+            ),
+            faults AS (
+                SELECT DISTINCT ON (h.machine_id) h.machine_id, h.changed_at
+                FROM machine_status_history h
+                WHERE h.tenant_id = $1
+                  AND h.new_status = 'error'
+                ORDER BY h.machine_id, h.changed_at DESC
+            ),
+            stale AS (
+                SELECT *
+                FROM fleet
+                WHERE status IN ('idle', 'busy')
+                  AND (last_heartbeat IS NULL OR last_heartbeat < $3)
+            )
+            SELECT
+                (SELECT COUNT(*) FROM fleet) AS total,
+                (SELECT COALESCE(jsonb_object_agg(status, n), '{}'::jsonb)
+                 FROM (SELECT status, COUNT(*) AS n FROM fleet GROUP BY status) s) AS by_status,
+                (SELECT COALESCE(jsonb_object_agg(protocol, n), '{}'::jsonb)
+                 FROM (SELECT protocol, COUNT(*) AS n FROM fleet GROUP BY protocol) p) AS by_protocol,
+                (SELECT COALESCE(jsonb_object_agg(category, n), '{}'::jsonb)
+                 FROM (SELECT category, COUNT(*) AS n FROM fleet GROUP BY category) c) AS by_category,
+                (SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                            'id', f.id, 'name', f.name, 'status', f.status, 'site', f.site,
+                            'faulted_at', x.changed_at
+                        ) ORDER BY x.changed_at DESC), '[]'::jsonb)
+                 FROM (SELECT machine_id, changed_at FROM faults ORDER BY changed_at DESC LIMIT $2) x
+                 JOIN fleet f ON f.id = x.machine_id) AS recently_faulted,
+                (SELECT COUNT(*) FROM stale) AS stale_heartbeat_count,
+                (SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                            'id', s.id, 'name', s.name, 'status', s.status, 'site', s.site,
+                            'last_heartbeat', s.last_heartbeat
+                        ) ORDER BY s.last_heartbeat ASC NULLS FIRST, s.name), '[]'::jsonb)
+                 FROM (SELECT * FROM stale ORDER BY last_heartbeat ASC NULLS FIRST, name LIMIT $2) s)
+                    AS stale_heartbeats
+        "#;
+
+        let row = diesel::sql_query(query)
+            .bind::<diesel::sql_types::Uuid, _>(tenant_id)
+            .bind::<diesel::sql_types::BigInt, _>(limit as i64)
+            .bind::<diesel::sql_types::Timestamptz, _>(stale_before)
+            .get_result::<FleetSummaryRow>(&mut conn)
+            .await?;
+
+        Ok(MachineFleetSummary {
+            total: row.total,
+            by_status: from_json(row.by_status)?,
+            by_protocol: from_json(row.by_protocol)?,
+            by_category: from_json(row.by_category)?,
+            recently_faulted: from_json(row.recently_faulted)?,
+            stale_heartbeat_count: row.stale_heartbeat_count,
+            stale_heartbeats: from_json(row.stale_heartbeats)?,
+            stale_after_minutes,
+            generated_at: now,
+        })
+    }
+
     // Heartbeat functionality
 
     pub async fn update_heartbeat(
@@ -298,28 +796,285 @@ impl MachineService {
         machine_id: Uuid,
         request: HeartbeatRequest,
     ) -> Result<()> {
+        if request.status == MachineStatus::Decommissioned {
+            return Err(MachineError::Invalid(
+                "Machines are decommissioned through the decommission endpoint".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+
+        // Buffered heartbeats are written with the next flush; without a buffer, or while the
+        // tenant's queue is full, they are written here
+        if let Some(buffer) = &self.heartbeat_buffer {
+            // Machines found in service are not looked up again on every heartbeat
+            if !buffer.known_in_service(tenant_id, machine_id) {
+                let mut conn = self.database.get_connection().await?;
+
+                // Set tenant context for RLS
+                conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                    .await?;
+
+                match Self::ensure_in_service(&mut conn, tenant_id, machine_id).await {
+                    Ok(()) => buffer.mark_in_service(tenant_id, machine_id),
+                    // Heartbeats from unknown machines are ignored, as when written directly
+                    Err(MachineError::NotFound) => return Ok(()),
+                    Err(e) => return Err(e),
+                }
+            }
+            if buffer.push_state(tenant_id, machine_id, &request, now) {
+                return self
+                    .record_heartbeat(tenant_id, machine_id, &request, now)
+                    .await;
+            }
+        }
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        let updated =
+            Self::apply_heartbeat(&mut conn, tenant_id, machine_id, &request, now).await?;
+        drop(conn);
+
+        if updated {
+            self.record_heartbeat(tenant_id, machine_id, &request, now)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies the heartbeats a gateway collected from the machines behind it. Machine state is
+    /// stored for all of them in one transaction; entries that are invalid, outside the scope of
+    /// the gateway's key or for missing or decommissioned machines are skipped and reported in
+    /// their result. `gateway_id` is the machine whose key sent the batch, `None` for users.
+    pub async fn update_heartbeats(
+        &self,
+        tenant_id: Uuid,
+        gateway_id: Option<Uuid>,
+        request: HeartbeatBatchRequest,
+    ) -> Result<HeartbeatBatchResponse> {
         let now = Utc::now();
 
-        diesel::update(
+        let (mut results, applied) = self
+            .database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // A gateway's key covers the gateway and the machines behind it
+                    let covered: Option<HashSet<Uuid>> = match gateway_id {
+                        Some(gateway_id) => Some(
+                            machines::table
+                                .filter(machines::tenant_id.eq(tenant_id))
+                                .filter(
+                                    machines::id
+                                        .eq(gateway_id)
+                                        .or(machines::gateway_id.eq(gateway_id)),
+                                )
+                                .select(machines::id)
+                                .load::<Uuid>(conn)
+                                .await?
+                                .into_iter()
+                                .collect(),
+                        ),
+                        None => None,
+                    };
+
+                    let mut results = Vec::with_capacity(request.heartbeats.len());
+                    let mut applied = Vec::new();
+                    let mut seen = HashSet::new();
+                    for (index, entry) in request.heartbeats.into_iter().enumerate() {
+                        let machine_id = entry.machine_id;
+                        let rejected = if !seen.insert(machine_id) {
+                            Some((
+                                HeartbeatBatchOutcome::Invalid,
+                                "Machine appears more than once in the batch".to_string(),
+                            ))
+                        } else if let Err(e) = entry.heartbeat.validate() {
+                            Some((HeartbeatBatchOutcome::Invalid, e.to_string()))
+                        } else if entry.heartbeat.status == MachineStatus::Decommissioned {
+                            Some((
+                                HeartbeatBatchOutcome::Invalid,
+                                "Machines are decommissioned through the decommission endpoint"
+                                    .to_string(),
+                            ))
+                        } else if covered
+                            .as_ref()
+                            .is_some_and(|covered| !covered.contains(&machine_id))
+                        {
+                            Some((
+                                HeartbeatBatchOutcome::Forbidden,
+                                "Machine is not behind this gateway".to_string(),
+                            ))
+                        } else {
+                            match Self::apply_heartbeat(
+                                conn,
+                                tenant_id,
+                                machine_id,
+                                &entry.heartbeat,
+                                now,
+                            )
+                            .await
+                            {
+                                Ok(true) => None,
+                                Ok(false) => Some((
+                                    HeartbeatBatchOutcome::NotFound,
+                                    MachineError::NotFound.to_string(),
+                                )),
+                                Err(e @ MachineError::Decommissioned(_)) => {
+                                    Some((HeartbeatBatchOutcome::Decommissioned, e.to_string()))
+                                }
+                                Err(e) => return Err(e),
+                            }
+                        };
+
+                        let (outcome, error) = match rejected {
+                            Some((outcome, error)) => (outcome, Some(error)),
+                            None => {
+                                applied.push((index, entry.heartbeat));
+                                (HeartbeatBatchOutcome::Applied, None)
+                            }
+                        };
+                        results.push(HeartbeatBatchResult {
+                            machine_id,
+                            outcome,
+                            error,
+                            response: None,
+                        });
+                    }
+
+                    Ok((results, applied))
+                })
+            })
+            .await?;
+
+        // Stored heartbeats feed telemetry, SPC, production counts and alerts, and hand the
+        // machines their commands and configuration, as single heartbeats do
+        let command_service = MachineCommandService::new(self.database.clone())
+            .with_buffer(self.heartbeat_buffer.clone());
+        for (index, mut heartbeat) in applied {
+            let machine_id = results[index].machine_id;
+            let config_version = heartbeat.config_version;
+            let command_results = heartbeat.command_results.take().unwrap_or_default();
+
+            let response = match self
+                .record_heartbeat(tenant_id, machine_id, &heartbeat, now)
+                .await
+            {
+                Ok(()) => {
+                    command_service
+                        .heartbeat_response(tenant_id, machine_id, config_version, command_results)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match response {
+                Ok(response) => results[index].response = Some(response),
+                Err(e) => {
+                    tracing::error!("Batched heartbeat for machine {} failed: {}", machine_id, e);
+                    results[index].outcome = HeartbeatBatchOutcome::Failed;
+                    results[index].error = Some(e.to_string());
+                }
+            }
+        }
+
+        Ok(HeartbeatBatchResponse { results })
+    }
+
+    // Stores the heartbeat on the machine; false when the machine does not exist. Decommissioned
+    // machines are frozen, so their heartbeats are refused
+    async fn apply_heartbeat(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: &HeartbeatRequest,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let updated = diesel::update(
             machines::table
                 .filter(machines::id.eq(machine_id))
-                .filter(machines::tenant_id.eq(tenant_id)),
+                .filter(machines::tenant_id.eq(tenant_id))
+                .filter(machines::status.ne(MachineStatus::Decommissioned.to_string())),
         )
         .set((
             machines::status.eq(request.status.to_string()),
-            machines::action.eq(request.action.map(|a| a.to_string())),
-            machines::payload.eq(request.payload),
-            machines::metadata.eq(request.metadata),
+            machines::action.eq(request.action.as_ref().map(|a| a.to_string())),
+            machines::payload.eq(&request.payload),
+            machines::metadata.eq(&request.metadata),
             machines::last_heartbeat.eq(now),
         ))
-        .execute(&mut conn)
+        .execute(conn)
         .await?;
+        if updated == 0 {
+            let decommissioned = diesel::select(diesel::dsl::exists(
+                machines::table
+                    .filter(machines::id.eq(machine_id))
+                    .filter(machines::tenant_id.eq(tenant_id))
+                    .filter(machines::status.eq(MachineStatus::Decommissioned.to_string())),
+            ))
+            .get_result::<bool>(conn)
+            .await?;
+            if decommissioned {
+                return Err(MachineError::Decommissioned(machine_id.to_string()));
+            }
+        }
+
+        Ok(updated > 0)
+    }
+
+    // Feeds a stored heartbeat to telemetry, SPC, production counts and alert rules; with a
+    // buffer, they all get it with the next flush unless the tenant's queue is full
+    async fn record_heartbeat(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: &HeartbeatRequest,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        // Keep declared telemetry channels as a time series; the payload itself is overwritten
+        if let Some(payload) = &request.payload {
+            TelemetryService::new(self.database.clone())
+                .with_buffer(self.heartbeat_buffer.clone())
+                .record_heartbeat_telemetry(
+                    tenant_id,
+                    machine_id,
+                    payload,
+                    request.metadata.as_ref(),
+                    now,
+                )
+                .await?;
+        }
+
+        if let Some(buffer) = &self.heartbeat_buffer {
+            if buffer.push_heartbeat(tenant_id, machine_id, request, now) {
+                return Ok(());
+            }
+        }
+
+        if let Some(payload) = &request.payload {
+            // Characteristics measured by the machine read their values from the same payload
+            SpcService::new(self.database.clone())
+                .record_heartbeat_measurements(tenant_id, machine_id, payload, now)
+                .await?;
+
+            // Parts the machine made count towards its active job assignment
+            ProductionService::new(self.database.clone())
+                .record_heartbeat_counts(
+                    tenant_id,
+                    machine_id,
+                    payload,
+                    request.metadata.as_ref(),
+                    now,
+                )
+                .await?;
+        }
+
+        // Rules watching the machine are evaluated by the alert worker
+        MachineAlertService::new(self.database.clone())
+            .queue_heartbeat(tenant_id, machine_id, request, now)
+            .await?;
 
         Ok(())
     }
@@ -416,27 +1171,54 @@ impl MachineService {
         machine_id: Uuid,
         request: CreateMachineAssetRelationshipRequest,
     ) -> Result<Uuid> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        let new_relationship = NewMachineAssetRelationship {
-            machine_id,
-            asset_id: request.asset_id,
-            relationship_type: request.relationship_type.to_string(),
-            notes: request.notes,
-        };
-
-        let relationship: MachineAssetRelationship =
-            diesel::insert_into(machine_asset_relationships::table)
-                .values(&new_relationship)
-                .returning(MachineAssetRelationship::as_returning())
-                .get_result(&mut conn)
-                .await?;
-
-        Ok(relationship.id)
+        self.database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let pin_mode = request.pin_mode.unwrap_or(AssetPinMode::Specific);
+
+                    // Unreleased controlled documents cannot be linked; a latest pin links the current version
+                    let asset = assets::table
+                        .filter(assets::id.eq(request.asset_id))
+                        .filter(assets::tenant_id.eq(tenant_id))
+                        .select(Asset::as_select())
+                        .first::<Asset>(conn)
+                        .await
+                        .optional()?
+                        .ok_or(MachineError::AssetNotFound)?;
+                    let linked_release_status = match pin_mode {
+                        AssetPinMode::Latest => assets::table
+                            .filter(assets::lineage_id.eq(asset.lineage_id))
+                            .filter(assets::is_current.eq(true))
+                            .select(assets::release_status)
+                            .first::<String>(conn)
+                            .await
+                            .optional()?
+                            .unwrap_or(asset.release_status),
+                        AssetPinMode::Specific => asset.release_status,
+                    };
+                    if linked_release_status != AssetReleaseStatus::Released.to_string() {
+                        return Err(MachineError::AssetNotReleased(asset.name));
+                    }
+
+                    let new_relationship = NewMachineAssetRelationship {
+                        machine_id,
+                        asset_id: request.asset_id,
+                        relationship_type: request.relationship_type.to_string(),
+                        notes: request.notes,
+                        pin_mode: pin_mode.to_string(),
+                    };
+
+                    let relationship: MachineAssetRelationship =
+                        diesel::insert_into(machine_asset_relationships::table)
+                            .values(&new_relationship)
+                            .returning(MachineAssetRelationship::as_returning())
+                            .get_result(conn)
+                            .await?;
+
+                    Ok(relationship.id)
+                })
+            })
+            .await
     }
 
     pub async fn list_machine_asset_relationships(
@@ -451,26 +1233,216 @@ impl MachineService {
             .await?;
 
         let relationships = machine_asset_relationships::table
+            .inner_join(assets::table.on(assets::id.eq(machine_asset_relationships::asset_id)))
             .filter(machine_asset_relationships::machine_id.eq(machine_id))
-            .select(MachineAssetRelationship::as_select())
-            .load::<MachineAssetRelationship>(&mut conn)
+            .select((
+                MachineAssetRelationship::as_select(),
+                assets::lineage_id,
+                assets::version,
+            ))
+            .load::<(MachineAssetRelationship, Uuid, Option<String>)>(&mut conn)
             .await?;
 
+        // Relationships pinned to latest follow the current version of their lineage
+        let latest_lineages: Vec<Uuid> = relationships
+            .iter()
+            .filter(|(rel, _, _)| rel.pin_mode == AssetPinMode::Latest.to_string())
+            .map(|(_, lineage_id, _)| *lineage_id)
+            .collect();
+
+        let current_versions: HashMap<Uuid, (Uuid, Option<String>)> = if latest_lineages.is_empty()
+        {
+            HashMap::new()
+        } else {
+            assets::table
+                .filter(assets::lineage_id.eq_any(&latest_lineages))
+                .filter(assets::is_current.eq(true))
+                .select((assets::lineage_id, assets::id, assets::version))
+                .load::<(Uuid, Uuid, Option<String>)>(&mut conn)
+                .await?
+                .into_iter()
+                .map(|(lineage_id, id, version)| (lineage_id, (id, version)))
+                .collect()
+        };
+
         Ok(relationships
             .into_iter()
-            .map(|rel| MachineAssetRelationshipResponse {
-                id: rel.id,
-                machine_id: rel.machine_id,
-                asset_id: rel.asset_id,
-                relationship_type: AssetRelationshipType::try_from(rel.relationship_type)
-                    .unwrap_or(AssetRelationshipType::Firmware),
-                notes: rel.notes,
-                created_at: rel.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: rel.updated_at.unwrap_or_else(|| Utc::now()),
+            .map(|(rel, lineage_id, version)| {
+                let pin_mode =
+                    AssetPinMode::try_from(rel.pin_mode).unwrap_or(AssetPinMode::Specific);
+                let (resolved_asset_id, resolved_version) = match pin_mode {
+                    AssetPinMode::Latest => current_versions
+                        .get(&lineage_id)
+                        .cloned()
+                        .unwrap_or((rel.asset_id, version)),
+                    AssetPinMode::Specific => (rel.asset_id, version),
+                };
+                MachineAssetRelationshipResponse {
+                    id: rel.id,
+                    machine_id: rel.machine_id,
+                    asset_id: rel.asset_id,
+                    relationship_type: AssetRelationshipType::try_from(rel.relationship_type)
+                        .unwrap_or(AssetRelationshipType::Firmware),
+                    notes: rel.notes,
+                    pin_mode,
+                    resolved_asset_id,
+                    resolved_version,
+                    created_at: rel.created_at.unwrap_or_else(|| Utc::now()),
+                    updated_at: rel.updated_at.unwrap_or_else(|| Utc::now()),
+                }
             })
             .collect())
     }
 
+    /// Everything a technician needs at the machine in one response: linked manuals, firmware
+    /// with its release notes, configuration and calibration data, recent calibrations with
+    /// their certificates, recent downtime reports and open maintenance orders.
+    pub async fn get_machine_docs(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<MachineDocsResponse> {
+        let machine = self
+            .get_machine_by_id(tenant_id, machine_id)
+            .await?
+            .ok_or(MachineError::NotFound)?;
+        let relationships = self
+            .list_machine_asset_relationships(tenant_id, machine_id)
+            .await?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Linked documents are shown in the version their relationship resolves to
+        let asset_ids: Vec<Uuid> = relationships
+            .iter()
+            .map(|rel| rel.resolved_asset_id)
+            .collect();
+        let mut linked_assets: HashMap<Uuid, (Asset, Option<String>)> = assets::table
+            .left_join(firmware_specific::table)
+            .filter(assets::id.eq_any(&asset_ids))
+            .select((
+                Asset::as_select(),
+                firmware_specific::release_notes.nullable(),
+            ))
+            .load::<(Asset, Option<String>)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(asset, release_notes)| (asset.id, (asset, release_notes)))
+            .collect();
+        let documents = relationships
+            .into_iter()
+            .filter_map(|rel| {
+                let (asset, release_notes) = linked_assets.remove(&rel.resolved_asset_id)?;
+                Some(MachineDocument {
+                    relationship_id: rel.id,
+                    relationship_type: rel.relationship_type,
+                    pin_mode: rel.pin_mode,
+                    notes: rel.notes,
+                    asset: asset.into(),
+                    release_notes,
+                })
+            })
+            .collect();
+
+        let calibrations = calibration_records::table
+            .left_join(assets::table)
+            .filter(calibration_records::machine_id.eq(machine_id))
+            .order((
+                calibration_records::calibrated_at.desc(),
+                calibration_records::created_at.desc(),
+            ))
+            .limit(MACHINE_DOCS_HISTORY_LIMIT)
+            .select((CalibrationRecord::as_select(), Option::<Asset>::as_select()))
+            .load::<(CalibrationRecord, Option<Asset>)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(record, certificate)| MachineCalibrationDocument {
+                calibration: record.into(),
+                certificate: certificate.map(Into::into),
+            })
+            .collect();
+
+        let recent_downtime = machine_downtime_reports::table
+            .filter(machine_downtime_reports::machine_id.eq(machine_id))
+            .order(machine_downtime_reports::reported_at.desc())
+            .limit(MACHINE_DOCS_HISTORY_LIMIT)
+            .select(MachineDowntimeReport::as_select())
+            .load::<MachineDowntimeReport>(&mut conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        // Maintenance orders are the open service jobs assigned to the machine
+        let closed: Vec<String> = JobStatus::CLOSED.iter().map(|s| s.to_string()).collect();
+        let open_maintenance_orders = machine_job_assignments::table
+            .inner_join(jobs::table)
+            .left_join(service_job::table.on(service_job::job_id.eq(jobs::id)))
+            .filter(machine_job_assignments::machine_id.eq(machine_id))
+            .filter(jobs::job_type.eq(JobType::Service.to_string()))
+            .filter(jobs::status.ne_all(closed))
+            .filter(jobs::archived.eq(false))
+            .order((jobs::due_date.asc().nulls_last(), jobs::job_number.asc()))
+            .select((
+                MachineJobAssignment::as_select(),
+                jobs::job_number,
+                jobs::status,
+                jobs::priority,
+                jobs::due_date,
+                service_job::service_type.nullable(),
+                service_job::maintenance_type.nullable(),
+            ))
+            .load::<(
+                MachineJobAssignment,
+                String,
+                String,
+                Option<String>,
+                Option<DateTime<Utc>>,
+                Option<String>,
+                Option<String>,
+            )>(&mut conn)
+            .await?
+            .into_iter()
+            .map(
+                |(
+                    assignment,
+                    job_number,
+                    status,
+                    priority,
+                    due_date,
+                    service_type,
+                    maintenance_type,
+                )| {
+                    MachineMaintenanceOrder {
+                        job_id: assignment.job_id,
+                        job_number,
+                        status: JobStatus::try_from(status).unwrap_or(JobStatus::Pending),
+                        priority: priority.and_then(|p| JobPriority::try_from(p).ok()),
+                        due_date,
+                        service_type,
+                        maintenance_type,
+                        assignment_id: assignment.id,
+                        assignment_status: JobAssignmentStatus::try_from(assignment.status)
+                            .unwrap_or(JobAssignmentStatus::Pending),
+                        start_time: assignment.start_time,
+                    }
+                },
+            )
+            .collect();
+
+        Ok(MachineDocsResponse {
+            machine,
+            documents,
+            calibrations,
+            recent_downtime,
+            open_maintenance_orders,
+        })
+    }
+
     pub async fn delete_machine_asset_relationship(
         &self,
         tenant_id: Uuid,
@@ -499,7 +1471,30 @@ impl MachineService {
         tenant_id: Uuid,
         machine_id: Uuid,
         request: CreateMachineOperatorAssignmentRequest,
-    ) -> Result<Uuid> {
+    ) -> Result<MachineOperatorAssignmentCreateResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::ensure_in_service(&mut conn, tenant_id, machine_id).await?;
+        drop(conn);
+
+        // Validate the operator against the machine's skill requirements
+        let skill_check = SkillService::new(self.database.clone())
+            .check_operator(tenant_id, machine_id, request.person_id)
+            .await?;
+        if !skill_check.allowed {
+            let missing: Vec<String> = skill_check
+                .gaps
+                .iter()
+                .filter(|gap| gap.is_blocking())
+                .map(|gap| format!("{} ({})", gap.skill_name, gap.reason))
+                .collect();
+            return Err(MachineError::OperatorNotCertified(missing.join(", ")));
+        }
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
@@ -520,7 +1515,10 @@ impl MachineService {
                 .get_result(&mut conn)
                 .await?;
 
-        Ok(assignment.id)
+        Ok(MachineOperatorAssignmentCreateResponse {
+            id: assignment.id,
+            skill_warnings: skill_check.gaps,
+        })
     }
 
     pub async fn list_machine_operator_assignments(
@@ -539,10 +1537,22 @@ impl MachineService {
             .select(MachineOperatorAssignment::as_select())
             .load::<MachineOperatorAssignment>(&mut conn)
             .await?;
+        drop(conn);
+
+        let pairs: Vec<(Uuid, Uuid)> = assignments
+            .iter()
+            .map(|assignment| (assignment.machine_id, assignment.person_id))
+            .collect();
+        let mut skill_gaps = SkillService::new(self.database.clone())
+            .operator_gaps(tenant_id, &pairs)
+            .await?;
 
         Ok(assignments
             .into_iter()
             .map(|assignment| MachineOperatorAssignmentResponse {
+                skill_gaps: skill_gaps
+                    .remove(&(assignment.machine_id, assignment.person_id))
+                    .unwrap_or_default(),
                 id: assignment.id,
                 machine_id: assignment.machine_id,
                 person_id: assignment.person_id,
@@ -590,6 +1600,13 @@ impl MachineService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
+        Self::ensure_in_service(&mut conn, tenant_id, machine_id).await?;
+
+        if let (Some(start), Some(end)) = (request.start_time, request.end_time) {
+            self.ensure_machine_available(tenant_id, machine_id, start, end)
+                .await?;
+        }
+
         let new_assignment = NewMachineJobAssignment {
             machine_id,
             job_id: request.job_id,
@@ -600,6 +1617,7 @@ impl MachineService {
             start_time: request.start_time,
             end_time: request.end_time,
             notes: request.notes,
+            target_quantity: request.target_quantity,
         };
 
         let assignment: MachineJobAssignment = diesel::insert_into(machine_job_assignments::table)
@@ -639,6 +1657,9 @@ impl MachineService {
                 start_time: assignment.start_time,
                 end_time: assignment.end_time,
                 notes: assignment.notes,
+                target_quantity: assignment.target_quantity,
+                produced_quantity: assignment.produced_quantity,
+                scrap_quantity: assignment.scrap_quantity,
                 created_at: assignment.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: assignment.updated_at.unwrap_or_else(|| Utc::now()),
             })
@@ -651,72 +1672,225 @@ impl MachineService {
         assignment_id: Uuid,
         request: UpdateMachineJobAssignmentRequest,
     ) -> Result<MachineJobAssignmentResponse> {
-        let mut conn = self.database.get_connection().await?;
+        self.database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Rescheduling must not move the assignment into downtime or a holiday
+                    if request.start_time.is_some() || request.end_time.is_some() {
+                        let current = machine_job_assignments::table
+                            .filter(machine_job_assignments::id.eq(assignment_id))
+                            .select(MachineJobAssignment::as_select())
+                            .first::<MachineJobAssignment>(conn)
+                            .await?;
+                        Self::ensure_in_service(conn, tenant_id, current.machine_id).await?;
+
+                        if let (Some(start), Some(end)) = (
+                            request.start_time.or(current.start_time),
+                            request.end_time.or(current.end_time),
+                        ) {
+                            self.ensure_machine_available(
+                                tenant_id,
+                                current.machine_id,
+                                start,
+                                end,
+                            )
+                            .await?;
+                        }
+                    }
+
+                    // Update fields individually
+                    if let Some(status) = &request.status {
+                        diesel::update(
+                            machine_job_assignments::table
+                                .filter(machine_job_assignments::id.eq(assignment_id)),
+                        )
+                        .set(machine_job_assignments::status.eq(status.to_string()))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(start_time) = request.start_time {
+                        diesel::update(
+                            machine_job_assignments::table
+                                .filter(machine_job_assignments::id.eq(assignment_id)),
+                        )
+                        .set(machine_job_assignments::start_time.eq(start_time))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(end_time) = request.end_time {
+                        diesel::update(
+                            machine_job_assignments::table
+                                .filter(machine_job_assignments::id.eq(assignment_id)),
+                        )
+                        .set(machine_job_assignments::end_time.eq(end_time))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(notes) = &request.notes {
+                        diesel::update(
+                            machine_job_assignments::table
+                                .filter(machine_job_assignments::id.eq(assignment_id)),
+                        )
+                        .set(machine_job_assignments::notes.eq(notes))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    if let Some(target_quantity) = request.target_quantity {
+                        diesel::update(
+                            machine_job_assignments::table
+                                .filter(machine_job_assignments::id.eq(assignment_id)),
+                        )
+                        .set(machine_job_assignments::target_quantity.eq(target_quantity))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    // Return the updated assignment
+                    let assignment = machine_job_assignments::table
+                        .filter(machine_job_assignments::id.eq(assignment_id))
+                        .select(MachineJobAssignment::as_select())
+                        .first::<MachineJobAssignment>(conn)
+                        .await?;
+
+                    Ok(MachineJobAssignmentResponse {
+                        id: assignment.id,
+                        machine_id: assignment.machine_id,
+                        job_id: assignment.job_id,
+                        status: JobAssignmentStatus::try_from(assignment.status)
+                            .unwrap_or(JobAssignmentStatus::Pending),
+                        start_time: assignment.start_time,
+                        end_time: assignment.end_time,
+                        notes: assignment.notes,
+                        target_quantity: assignment.target_quantity,
+                        produced_quantity: assignment.produced_quantity,
+                        scrap_quantity: assignment.scrap_quantity,
+                        created_at: assignment.created_at.unwrap_or_else(|| Utc::now()),
+                        updated_at: assignment.updated_at.unwrap_or_else(|| Utc::now()),
+                    })
+                })
+            })
+            .await
+    }
 
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+    // Rejects a period that overlaps planned downtime or a holiday on the machine's calendar,
+    // or during which all of the machine's primary operators are absent
+    async fn ensure_machine_available(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<()> {
+        let blocking = CalendarService::new(self.database.clone())
+            .find_blocking_exception(tenant_id, machine_id, start, end)
             .await?;
 
-        // Update fields individually
-        if let Some(status) = &request.status {
-            diesel::update(
-                machine_job_assignments::table
-                    .filter(machine_job_assignments::id.eq(assignment_id)),
-            )
-            .set(machine_job_assignments::status.eq(status.to_string()))
-            .execute(&mut conn)
-            .await?;
+        if let Some(exception) = blocking {
+            return Err(MachineError::Unavailable(format!(
+                "{} from {} to {}",
+                exception.exception_type,
+                exception.starts_at.to_rfc3339(),
+                exception.ends_at.to_rfc3339()
+            )));
         }
 
-        if let Some(start_time) = request.start_time {
-            diesel::update(
-                machine_job_assignments::table
-                    .filter(machine_job_assignments::id.eq(assignment_id)),
-            )
-            .set(machine_job_assignments::start_time.eq(start_time))
-            .execute(&mut conn)
+        let absence = AttendanceService::new(self.database.clone())
+            .find_primary_operator_absence(tenant_id, machine_id, start, end)
             .await?;
+
+        match absence {
+            Some((operator, absence)) => Err(MachineError::Unavailable(format!(
+                "primary operator {} is {} from {} to {}",
+                operator,
+                absence.attendance_type,
+                absence.starts_at.to_rfc3339(),
+                absence.ends_at.to_rfc3339()
+            ))),
+            None => Ok(()),
         }
+    }
 
-        if let Some(end_time) = request.end_time {
-            diesel::update(
-                machine_job_assignments::table
-                    .filter(machine_job_assignments::id.eq(assignment_id)),
-            )
-            .set(machine_job_assignments::end_time.eq(end_time))
-            .execute(&mut conn)
-            .await?;
+    // Fails unless the machine exists in the tenant and has not been decommissioned
+    async fn ensure_in_service(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<()> {
+        let status = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::status)
+            .first::<String>(conn)
+            .await
+            .optional()?;
+
+        match status.map(MachineStatus::try_from) {
+            None => Err(MachineError::NotFound),
+            Some(Ok(MachineStatus::Decommissioned)) => {
+                Err(MachineError::Decommissioned(machine_id.to_string()))
+            }
+            Some(_) => Ok(()),
         }
+    }
 
-        if let Some(notes) = &request.notes {
-            diesel::update(
-                machine_job_assignments::table
-                    .filter(machine_job_assignments::id.eq(assignment_id)),
-            )
-            .set(machine_job_assignments::notes.eq(notes))
-            .execute(&mut conn)
-            .await?;
+    // Gateways are in-service machines that do not report through a gateway themselves, so a
+    // gateway's key covers exactly the machines directly behind it
+    async fn ensure_gateway(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Option<Uuid>,
+        gateway_id: Uuid,
+    ) -> Result<()> {
+        if machine_id == Some(gateway_id) {
+            return Err(MachineError::Invalid(
+                "A machine cannot be its own gateway".to_string(),
+            ));
         }
 
-        // Return the updated assignment
-        let assignment = machine_job_assignments::table
-            .filter(machine_job_assignments::id.eq(assignment_id))
-            .select(MachineJobAssignment::as_select())
-            .first::<MachineJobAssignment>(&mut conn)
+        let gateway = machines::table
+            .filter(machines::id.eq(gateway_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select((machines::status, machines::gateway_id))
+            .first::<(String, Option<Uuid>)>(conn)
+            .await
+            .optional()?;
+        match gateway {
+            None => {
+                return Err(MachineError::Invalid(
+                    "Gateway machine not found".to_string(),
+                ))
+            }
+            Some((status, _)) if status == MachineStatus::Decommissioned.to_string() => {
+                return Err(MachineError::Decommissioned(gateway_id.to_string()))
+            }
+            Some((_, Some(_))) => {
+                return Err(MachineError::Invalid(
+                    "Gateway machine reports through a gateway itself".to_string(),
+                ))
+            }
+            Some(_) => {}
+        }
+
+        if let Some(machine_id) = machine_id {
+            let is_gateway = diesel::select(diesel::dsl::exists(
+                machines::table
+                    .filter(machines::tenant_id.eq(tenant_id))
+                    .filter(machines::gateway_id.eq(machine_id)),
+            ))
+            .get_result::<bool>(conn)
             .await?;
+            if is_gateway {
+                return Err(MachineError::Invalid(
+                    "A gateway cannot report through another gateway".to_string(),
+                ));
+            }
+        }
 
-        Ok(MachineJobAssignmentResponse {
-            id: assignment.id,
-            machine_id: assignment.machine_id,
-            job_id: assignment.job_id,
-            status: JobAssignmentStatus::try_from(assignment.status)
-                .unwrap_or(JobAssignmentStatus::Pending),
-            start_time: assignment.start_time,
-            end_time: assignment.end_time,
-            notes: assignment.notes,
-            created_at: assignment.created_at.unwrap_or_else(|| Utc::now()),
-            updated_at: assignment.updated_at.unwrap_or_else(|| Utc::now()),
-        })
+        Ok(())
     }
 
     pub async fn delete_machine_job_assignment(
@@ -787,6 +1961,14 @@ impl MachineService {
                 payload: machine.payload,
                 last_heartbeat: machine.last_heartbeat,
                 metadata: machine.metadata,
+                latitude: machine.latitude,
+                longitude: machine.longitude,
+                site: machine.site,
+                decommissioned_at: machine.decommissioned_at,
+                decommission_reason: machine.decommission_reason,
+                decommissioned_by: machine.decommissioned_by,
+                final_meter_readings: machine.final_meter_readings,
+                gateway_id: machine.gateway_id,
                 created_at: machine.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: machine.updated_at.unwrap_or_else(|| Utc::now()),
             })
@@ -838,9 +2020,170 @@ impl MachineService {
                 payload: machine.payload,
                 last_heartbeat: machine.last_heartbeat,
                 metadata: machine.metadata,
+                latitude: machine.latitude,
+                longitude: machine.longitude,
+                site: machine.site,
+                decommissioned_at: machine.decommissioned_at,
+                decommission_reason: machine.decommission_reason,
+                decommissioned_by: machine.decommissioned_by,
+                final_meter_readings: machine.final_meter_readings,
+                gateway_id: machine.gateway_id,
                 created_at: machine.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: machine.updated_at.unwrap_or_else(|| Utc::now()),
             })
             .collect())
     }
+
+    // Capacity planning
+
+    /// Aggregates scheduled job assignments per machine over the inclusive `from..=to` window
+    /// and compares them with each machine's available hours from its calendar, flagging
+    /// overbooked days. Machines without a shift pattern fall back to `hours_per_day` on
+    /// working days. Time when none of a machine's primary operators can work it (off shift
+    /// or absent; only absences for machines without a pattern) is not available. Machines
+    /// are returned busiest first.
+    pub async fn get_capacity_plan(
+        &self,
+        tenant_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+        hours_per_day: f64,
+        include_weekends: bool,
+    ) -> Result<CapacityPlanResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machines = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::status.ne(MachineStatus::Decommissioned.to_string()))
+            .order(machines::name.asc())
+            .select(Machine::as_select())
+            .load::<Machine>(&mut conn)
+            .await?;
+
+        let window_start = day_start(from);
+        let window_end = day_start(to + Duration::days(1));
+
+        // Failed assignments no longer occupy the machine; unscheduled ones have no time to count
+        let assignments = machine_job_assignments::table
+            .inner_join(machines::table.on(machines::id.eq(machine_job_assignments::machine_id)))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machine_job_assignments::status.ne(JobAssignmentStatus::Failed.to_string()))
+            .filter(machine_job_assignments::start_time.lt(window_end))
+            .filter(machine_job_assignments::end_time.gt(window_start))
+            .select(MachineJobAssignment::as_select())
+            .load::<MachineJobAssignment>(&mut conn)
+            .await?;
+
+        let mut scheduled: HashMap<Uuid, (usize, BTreeMap<NaiveDate, f64>)> = HashMap::new();
+        for assignment in assignments {
+            let (start, end) = match (assignment.start_time, assignment.end_time) {
+                (Some(start), Some(end)) => (start, end),
+                _ => continue,
+            };
+
+            let entry = scheduled.entry(assignment.machine_id).or_default();
+            entry.0 += 1;
+            for (date, hours) in hours_by_day(start, end, from, to) {
+                *entry.1.entry(date).or_insert(0.0) += hours;
+            }
+        }
+
+        let machine_ids: Vec<Uuid> = machines.iter().map(|machine| machine.id).collect();
+        let mut availability = CalendarService::new(self.database.clone())
+            .availability_for_machines(tenant_id, &machine_ids, (window_start, window_end))
+            .await?;
+        let operator_gaps = AttendanceService::new(self.database.clone())
+            .primary_operator_gaps(tenant_id, &machine_ids, (window_start, window_end))
+            .await?;
+        for (machine_id, gaps) in operator_gaps {
+            if let Some(machine) = availability.get_mut(&machine_id) {
+                let gaps = if machine.pattern.is_some() {
+                    gaps.unavailable
+                } else {
+                    gaps.absent
+                };
+                machine.blocked.extend(gaps);
+            }
+        }
+
+        let days: Vec<NaiveDate> = from.iter_days().take_while(|date| *date <= to).collect();
+
+        let mut capacities: Vec<MachineCapacity> = machines
+            .into_iter()
+            .map(|machine| {
+                let (assignment_count, daily) = scheduled.remove(&machine.id).unwrap_or_default();
+                let available_by_day = availability
+                    .remove(&machine.id)
+                    .unwrap_or_default()
+                    .daily_hours(from, to, hours_per_day, include_weekends);
+
+                let mut available_hours = 0.0;
+                let mut scheduled_hours = 0.0;
+                let mut overbooked_slots = Vec::new();
+
+                for date in &days {
+                    let available = available_by_day.get(date).copied().unwrap_or(0.0);
+                    let booked = daily.get(date).copied().unwrap_or(0.0);
+                    available_hours += available;
+                    scheduled_hours += booked;
+
+                    if booked > available {
+                        overbooked_slots.push(CapacitySlot {
+                            date: *date,
+                            available_hours: available,
+                            scheduled_hours: booked,
+                            overbooked_hours: booked - available,
+                        });
+                    }
+                }
+
+                MachineCapacity {
+                    machine_id: machine.id,
+                    machine_name: machine.name,
+                    status: MachineStatus::try_from(machine.status)
+                        .unwrap_or(MachineStatus::Offline),
+                    assignment_count,
+                    available_hours,
+                    scheduled_hours,
+                    load_percent: load_percent(scheduled_hours, available_hours),
+                    overbooked_slots,
+                }
+            })
+            .collect();
+
+        // Bottlenecks first: highest load (booked time with no availability ranks highest),
+        // then most overbooked days
+        let load_key = |capacity: &MachineCapacity| match capacity.load_percent {
+            Some(load) => load,
+            None if capacity.scheduled_hours > 0.0 => f64::INFINITY,
+            None => 0.0,
+        };
+        capacities.sort_by(|a, b| {
+            load_key(b)
+                .partial_cmp(&load_key(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.overbooked_slots.len().cmp(&a.overbooked_slots.len()))
+        });
+
+        let available_hours: f64 = capacities.iter().map(|c| c.available_hours).sum();
+        let scheduled_hours: f64 = capacities.iter().map(|c| c.scheduled_hours).sum();
+
+        Ok(CapacityPlanResponse {
+            from,
+            to,
+            available_hours,
+            scheduled_hours,
+            load_percent: load_percent(scheduled_hours, available_hours),
+            machines: capacities,
+        })
+    }
+}
+
+// Summary columns are built as JSON by the query; a mismatch is an internal error
+fn from_json<T: DeserializeOwned>(value: serde_json::Value) -> Result<T> {
+    serde_json::from_value(value).map_err(|e| MachineError::Other(e.into()))
 }
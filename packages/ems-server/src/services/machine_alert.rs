@@ -0,0 +1,909 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use reqwest::{redirect::Policy, Client};
+use serde_json::json;
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    ActionResult, AlertAction, AlertCondition, CallerContext, CreateJobRequest,
+    CreateMachineAlertRuleRequest, HeartbeatRequest, JobPriority, ListMachineAlertsQuery,
+    ListTelemetryAnomaliesQuery, MachineAlert, MachineAlertResponse, MachineAlertRule,
+    MachineAlertRuleResponse, MachineAlertRuleTestResponse, MachineHeartbeatEvent, MachineStatus,
+    MachineTelemetryAnomaly, NewMachineAlert, NewMachineAlertRule, NewMachineHeartbeatEvent,
+    NewMachineTelemetryAnomaly, Permission, TelemetryAnomalyResponse, TestMachineAlertRuleRequest,
+    UpdateMachineAlertRuleRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, EmailService, JobService};
+use crate::utils::machine_alert::{
+    anomaly_score, anomaly_sensitivity, evaluate_conditions, longest_window_minutes,
+    HeartbeatSnapshot,
+};
+use crate::utils::public_url::{check_public_url, resolve_public_host};
+use crate::utils::AppError;
+
+// Posting an alert to a webhook must finish within this time
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+// Queued heartbeats written in one insert
+const EVENT_INSERT_CHUNK: usize = 1_000;
+// Maintenance type of jobs opened by a rule that does not name one
+const DEFAULT_MAINTENANCE_TYPE: &str = "corrective";
+
+/// Errors from alert rule changes and tests, so handlers can tell a missing machine from a failure
+#[derive(Error, Debug)]
+pub enum MachineAlertError {
+    #[error("Machine not found")]
+    MachineNotFound,
+
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error(transparent)]
+    Database(#[from] diesel::result::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl From<MachineAlertError> for AppError {
+    fn from(error: MachineAlertError) -> Self {
+        match error {
+            MachineAlertError::MachineNotFound => AppError::NotFound(error.to_string()),
+            MachineAlertError::Invalid(message) => AppError::Validation(message),
+            MachineAlertError::Database(error) => AppError::from_database(error),
+            MachineAlertError::Other(error) => AppError::from_service(error),
+        }
+    }
+}
+
+pub struct MachineAlertService {
+    database: DatabaseService,
+}
+
+impl MachineAlertService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Alert rule CRUD operations
+
+    pub async fn create_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateMachineAlertRuleRequest,
+    ) -> Result<MachineAlertRuleResponse, MachineAlertError> {
+        caller.require(Permission::ManageAlertRules)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if let Some(machine_id) = request.machine_id {
+            Self::require_machine(&mut conn, tenant_id, machine_id).await?;
+        }
+
+        let new_rule = NewMachineAlertRule {
+            tenant_id,
+            name: request.name,
+            machine_id: request.machine_id,
+            conditions: serde_json::to_value(&request.conditions).map_err(anyhow::Error::from)?,
+            actions: serde_json::to_value(&request.actions).map_err(anyhow::Error::from)?,
+            is_enabled: request.is_enabled.unwrap_or(true),
+            created_by_id: Some(caller.person_id),
+        };
+
+        let rule: MachineAlertRule = diesel::insert_into(machine_alert_rules::table)
+            .values(&new_rule)
+            .returning(MachineAlertRule::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(rule.into())
+    }
+
+    pub async fn list_rules(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Option<Uuid>,
+    ) -> Result<Vec<MachineAlertRuleResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = machine_alert_rules::table
+            .filter(machine_alert_rules::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        // Rules watching every machine apply to the requested one too
+        if let Some(machine_id) = machine_id {
+            query = query.filter(
+                machine_alert_rules::machine_id
+                    .eq(machine_id)
+                    .or(machine_alert_rules::machine_id.is_null()),
+            );
+        }
+
+        let rules = query
+            .order(machine_alert_rules::name.asc())
+            .select(MachineAlertRule::as_select())
+            .load::<MachineAlertRule>(&mut conn)
+            .await?;
+
+        Ok(rules.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_rule(
+        &self,
+        tenant_id: Uuid,
+        rule_id: Uuid,
+    ) -> Result<Option<MachineAlertRule>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let rule = machine_alert_rules::table
+            .filter(machine_alert_rules::id.eq(rule_id))
+            .filter(machine_alert_rules::tenant_id.eq(tenant_id))
+            .select(MachineAlertRule::as_select())
+            .first::<MachineAlertRule>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(rule)
+    }
+
+    pub async fn update_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        rule_id: Uuid,
+        request: UpdateMachineAlertRuleRequest,
+    ) -> Result<Option<MachineAlertRuleResponse>, MachineAlertError> {
+        caller.require(Permission::ManageAlertRules)?;
+
+        let conditions = request
+            .conditions
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(anyhow::Error::from)?;
+        let actions = request
+            .actions
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(anyhow::Error::from)?;
+
+        let updated = self
+            .database
+            .with_tenant_tx::<_, MachineAlertError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    if let Some(machine_id) = request.machine_id {
+                        Self::require_machine(conn, tenant_id, machine_id).await?;
+                    }
+
+                    let target = machine_alert_rules::table
+                        .filter(machine_alert_rules::id.eq(rule_id))
+                        .filter(machine_alert_rules::tenant_id.eq(tenant_id));
+
+                    let exists = target
+                        .select(machine_alert_rules::id)
+                        .first::<Uuid>(conn)
+                        .await
+                        .optional()?;
+                    if exists.is_none() {
+                        return Ok(None);
+                    }
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(machine_alert_rules::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(machine_id) = request.machine_id {
+                        diesel::update(target)
+                            .set(machine_alert_rules::machine_id.eq(machine_id))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(conditions) = &conditions {
+                        diesel::update(target)
+                            .set(machine_alert_rules::conditions.eq(conditions))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(actions) = &actions {
+                        diesel::update(target)
+                            .set(machine_alert_rules::actions.eq(actions))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_enabled) = request.is_enabled {
+                        diesel::update(target)
+                            .set(machine_alert_rules::is_enabled.eq(is_enabled))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(target
+                        .select(MachineAlertRule::as_select())
+                        .first::<MachineAlertRule>(conn)
+                        .await
+                        .optional()?)
+                })
+            })
+            .await?;
+
+        Ok(updated.map(Into::into))
+    }
+
+    /// Deletes a rule together with the alerts it raised.
+    pub async fn delete_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        rule_id: Uuid,
+    ) -> Result<bool, MachineAlertError> {
+        caller.require(Permission::ManageAlertRules)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            machine_alert_rules::table
+                .filter(machine_alert_rules::id.eq(rule_id))
+                .filter(machine_alert_rules::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Evaluates a rule against the machine's latest heartbeat as of now, without recording an
+    /// alert or running any action. `None` when the rule does not exist.
+    pub async fn test_rule(
+        &self,
+        tenant_id: Uuid,
+        rule_id: Uuid,
+        request: TestMachineAlertRuleRequest,
+    ) -> Result<Option<MachineAlertRuleTestResponse>, MachineAlertError> {
+        let Some(rule) = self.get_rule(tenant_id, rule_id).await? else {
+            return Ok(None);
+        };
+
+        let machine_id = match (rule.machine_id, request.machine_id) {
+            (Some(watched), Some(requested)) if watched != requested => {
+                return Err(MachineAlertError::Invalid(
+                    "Rule does not watch this machine".to_string(),
+                ));
+            }
+            (Some(machine_id), _) | (None, Some(machine_id)) => machine_id,
+            (None, None) => {
+                return Err(MachineAlertError::Invalid(
+                    "machine_id is required".to_string(),
+                ))
+            }
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let status = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::status)
+            .first::<String>(&mut conn)
+            .await
+            .optional()?
+            .ok_or(MachineAlertError::MachineNotFound)?;
+        let status = MachineStatus::try_from(status).map_err(|e| anyhow!(e))?;
+
+        let conditions = rule.conditions();
+        let now = Utc::now();
+        let snapshot =
+            Self::load_snapshot(&mut conn, tenant_id, machine_id, status, now, &conditions).await?;
+        let (would_fire, observations) = evaluate_conditions(&conditions, &snapshot);
+
+        Ok(Some(MachineAlertRuleTestResponse {
+            rule_id: rule.id,
+            machine_id,
+            would_fire,
+            observations,
+            actions: rule
+                .actions()
+                .iter()
+                .map(|action| action.kind().to_string())
+                .collect(),
+            evaluated_at: now,
+        }))
+    }
+
+    // Alert operations
+
+    pub async fn list_alerts(
+        &self,
+        tenant_id: Uuid,
+        query: ListMachineAlertsQuery,
+    ) -> Result<Vec<MachineAlertResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut alerts_query = machine_alerts::table
+            .filter(machine_alerts::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(machine_id) = query.machine_id {
+            alerts_query = alerts_query.filter(machine_alerts::machine_id.eq(machine_id));
+        }
+        if let Some(rule_id) = query.rule_id {
+            alerts_query = alerts_query.filter(machine_alerts::rule_id.eq(rule_id));
+        }
+        match query.open {
+            Some(true) => alerts_query = alerts_query.filter(machine_alerts::resolved_at.is_null()),
+            Some(false) => {
+                alerts_query = alerts_query.filter(machine_alerts::resolved_at.is_not_null())
+            }
+            None => {}
+        }
+
+        let alerts = alerts_query
+            .order(machine_alerts::triggered_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .select(MachineAlert::as_select())
+            .load::<MachineAlert>(&mut conn)
+            .await?;
+
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+
+    /// Anomalies the alert worker found in the machine's telemetry, newest first. `None` when
+    /// the machine does not exist.
+    pub async fn list_anomalies(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        query: ListTelemetryAnomaliesQuery,
+    ) -> Result<Option<Vec<TelemetryAnomalyResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::require_machine(&mut conn, tenant_id, machine_id)
+            .await
+            .is_err()
+        {
+            return Ok(None);
+        }
+
+        let mut anomalies_query = machine_telemetry_anomalies::table
+            .filter(machine_telemetry_anomalies::tenant_id.eq(tenant_id))
+            .filter(machine_telemetry_anomalies::machine_id.eq(machine_id))
+            .into_boxed();
+
+        if let Some(channel) = query.channel {
+            anomalies_query = anomalies_query
+                .filter(machine_telemetry_anomalies::channel.eq(channel.to_string()));
+        }
+        if let Some(since) = query.since {
+            anomalies_query =
+                anomalies_query.filter(machine_telemetry_anomalies::recorded_at.ge(since));
+        }
+
+        let anomalies = anomalies_query
+            .order(machine_telemetry_anomalies::recorded_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .select(MachineTelemetryAnomaly::as_select())
+            .load::<MachineTelemetryAnomaly>(&mut conn)
+            .await?;
+
+        Ok(Some(anomalies.into_iter().map(Into::into).collect()))
+    }
+
+    // Heartbeat queue operations
+
+    /// Queues a heartbeat for the alert worker when an enabled rule watches the machine.
+    /// Returns whether it was queued.
+    pub async fn queue_heartbeat(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        request: &HeartbeatRequest,
+        received_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let event = NewMachineHeartbeatEvent {
+            tenant_id,
+            machine_id,
+            status: request.status.to_string(),
+            payload: request.payload.clone(),
+            metadata: request.metadata.clone(),
+            received_at,
+        };
+        Ok(Self::queue_heartbeats(&mut conn, tenant_id, vec![event]).await? > 0)
+    }
+
+    /// Queues the heartbeats of machines an enabled rule watches, looking the rules up and
+    /// inserting once for the whole batch, on a connection the caller holds with the tenant
+    /// context set. Returns how many were queued.
+    pub async fn queue_heartbeats(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        mut events: Vec<NewMachineHeartbeatEvent>,
+    ) -> Result<usize> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+        let machine_ids: HashSet<Uuid> = events.iter().map(|event| event.machine_id).collect();
+
+        // A rule without a machine watches every machine of the tenant
+        let watching: Vec<Option<Uuid>> = machine_alert_rules::table
+            .filter(machine_alert_rules::tenant_id.eq(tenant_id))
+            .filter(machine_alert_rules::is_enabled.eq(true))
+            .filter(
+                machine_alert_rules::machine_id
+                    .eq_any(&machine_ids)
+                    .or(machine_alert_rules::machine_id.is_null()),
+            )
+            .select(machine_alert_rules::machine_id)
+            .distinct()
+            .load(conn)
+            .await?;
+        if !watching.contains(&None) {
+            let watched: HashSet<Uuid> = watching.into_iter().flatten().collect();
+            events.retain(|event| watched.contains(&event.machine_id));
+        }
+
+        let mut queued = 0;
+        for chunk in events.chunks(EVENT_INSERT_CHUNK) {
+            queued += diesel::insert_into(machine_heartbeat_events::table)
+                .values(chunk)
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(queued)
+    }
+
+    /// Claims queued heartbeats across all tenants, oldest first, marking them processed so
+    /// concurrent workers skip them.
+    pub async fn claim_pending_events(&self, limit: i64) -> Result<Vec<MachineHeartbeatEvent>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // The alert worker works across tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let now = Utc::now();
+        let mut events = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let pending = machine_heartbeat_events::table
+                        .filter(machine_heartbeat_events::processed_at.is_null())
+                        .order(machine_heartbeat_events::received_at.asc())
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .select(machine_heartbeat_events::id)
+                        .load::<Uuid>(conn)
+                        .await?;
+
+                    diesel::update(
+                        machine_heartbeat_events::table
+                            .filter(machine_heartbeat_events::id.eq_any(&pending)),
+                    )
+                    .set(machine_heartbeat_events::processed_at.eq(now))
+                    .returning(MachineHeartbeatEvent::as_returning())
+                    .get_results::<MachineHeartbeatEvent>(conn)
+                    .await
+                })
+            })
+            .await?;
+
+        // UPDATE ... RETURNING does not keep the claim order
+        events.sort_by_key(|event| event.received_at);
+        Ok(events)
+    }
+
+    /// Evaluates the machine's enabled rules against a claimed heartbeat. A rule whose conditions
+    /// all hold raises an alert and runs its actions, unless an alert of the rule is already open
+    /// for the machine; an open alert is resolved once its conditions no longer hold. Action
+    /// failures are recorded on the alert; only bookkeeping errors are returned. Returns the
+    /// number of alerts raised.
+    pub async fn process_event(
+        &self,
+        event: &MachineHeartbeatEvent,
+        email: Option<&EmailService>,
+    ) -> Result<usize> {
+        let Ok(status) = MachineStatus::try_from(event.status.clone()) else {
+            return Ok(0);
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            event.tenant_id
+        ))
+        .await?;
+
+        let rules = machine_alert_rules::table
+            .filter(machine_alert_rules::tenant_id.eq(event.tenant_id))
+            .filter(machine_alert_rules::is_enabled.eq(true))
+            .filter(
+                machine_alert_rules::machine_id
+                    .eq(event.machine_id)
+                    .or(machine_alert_rules::machine_id.is_null()),
+            )
+            .order(machine_alert_rules::name.asc())
+            .select(MachineAlertRule::as_select())
+            .load::<MachineAlertRule>(&mut conn)
+            .await?;
+        if rules.is_empty() {
+            return Ok(0);
+        }
+
+        let Some(machine_name) = machines::table
+            .filter(machines::id.eq(event.machine_id))
+            .filter(machines::tenant_id.eq(event.tenant_id))
+            .select(machines::name)
+            .first::<String>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(0);
+        };
+
+        // One snapshot serves every rule, so it covers the longest window any of them needs
+        let all_conditions: Vec<AlertCondition> =
+            rules.iter().flat_map(|rule| rule.conditions()).collect();
+        let snapshot = Self::load_snapshot(
+            &mut conn,
+            event.tenant_id,
+            event.machine_id,
+            status,
+            event.received_at,
+            &all_conditions,
+        )
+        .await?;
+        Self::record_anomalies(&mut conn, event, &snapshot, &all_conditions).await?;
+
+        let mut raised = 0;
+        for rule in &rules {
+            let (fired, observations) = evaluate_conditions(&rule.conditions(), &snapshot);
+
+            let open = machine_alerts::table
+                .filter(machine_alerts::rule_id.eq(rule.id))
+                .filter(machine_alerts::machine_id.eq(event.machine_id))
+                .filter(machine_alerts::resolved_at.is_null())
+                .select(MachineAlert::as_select())
+                .first::<MachineAlert>(&mut conn)
+                .await
+                .optional()?;
+
+            match (fired, open) {
+                (true, None) => {
+                    let alert: MachineAlert = diesel::insert_into(machine_alerts::table)
+                        .values(&NewMachineAlert {
+                            tenant_id: event.tenant_id,
+                            rule_id: rule.id,
+                            machine_id: event.machine_id,
+                            triggered_at: event.received_at,
+                            observations: serde_json::to_value(&observations)?,
+                        })
+                        .returning(MachineAlert::as_returning())
+                        .get_result(&mut conn)
+                        .await?;
+
+                    let mut results = Vec::new();
+                    for action in rule.actions() {
+                        results.push(
+                            self.run_action(&action, rule, &alert, &machine_name, event, email)
+                                .await,
+                        );
+                    }
+
+                    diesel::update(machine_alerts::table.filter(machine_alerts::id.eq(alert.id)))
+                        .set(machine_alerts::action_results.eq(serde_json::to_value(&results)?))
+                        .execute(&mut conn)
+                        .await?;
+                    raised += 1;
+                }
+                (false, Some(open)) => {
+                    diesel::update(machine_alerts::table.filter(machine_alerts::id.eq(open.id)))
+                        .set(machine_alerts::resolved_at.eq(event.received_at))
+                        .execute(&mut conn)
+                        .await?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(raised)
+    }
+
+    // Private helper methods
+
+    async fn require_machine(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<(), MachineAlertError> {
+        machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()?
+            .map(|_| ())
+            .ok_or(MachineAlertError::MachineNotFound)
+    }
+
+    /// The machine's state at `at` as the conditions need it: how long it has been in `status`,
+    /// the readings of each telemetry channel the conditions look at and the tenant's anomaly
+    /// sensitivity.
+    async fn load_snapshot(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        status: MachineStatus,
+        at: DateTime<Utc>,
+        conditions: &[AlertCondition],
+    ) -> Result<HeartbeatSnapshot> {
+        // The status history trigger logs every transition; when the latest one is not the
+        // reported status, the machine only just entered it
+        let last_change = machine_status_history::table
+            .filter(machine_status_history::machine_id.eq(machine_id))
+            .filter(machine_status_history::changed_at.le(at))
+            .order(machine_status_history::changed_at.desc())
+            .select((
+                machine_status_history::new_status,
+                machine_status_history::changed_at,
+            ))
+            .first::<(String, DateTime<Utc>)>(conn)
+            .await
+            .optional()?;
+        let status_since = match last_change {
+            Some((new_status, changed_at)) if new_status == status.to_string() => changed_at,
+            _ => at,
+        };
+
+        let window_start = at - ChronoDuration::minutes(longest_window_minutes(conditions) as i64);
+        let mut readings = HashMap::new();
+        for condition in conditions {
+            let Some(channel) = condition.channel() else {
+                continue;
+            };
+            if readings.contains_key(&channel) {
+                continue;
+            }
+
+            let channel_readings = machine_telemetry::table
+                .filter(machine_telemetry::machine_id.eq(machine_id))
+                .filter(machine_telemetry::channel.eq(channel.to_string()));
+
+            let before = channel_readings
+                .clone()
+                .filter(machine_telemetry::recorded_at.le(window_start))
+                .order(machine_telemetry::recorded_at.desc())
+                .select((machine_telemetry::recorded_at, machine_telemetry::value))
+                .first::<(DateTime<Utc>, f64)>(conn)
+                .await
+                .optional()?;
+            let within = channel_readings
+                .filter(machine_telemetry::recorded_at.gt(window_start))
+                .filter(machine_telemetry::recorded_at.le(at))
+                .order(machine_telemetry::recorded_at.asc())
+                .select((machine_telemetry::recorded_at, machine_telemetry::value))
+                .load::<(DateTime<Utc>, f64)>(conn)
+                .await?;
+
+            readings.insert(channel, before.into_iter().chain(within).collect());
+        }
+
+        let settings = tenants::table
+            .find(tenant_id)
+            .select(tenants::settings)
+            .first::<Option<serde_json::Value>>(conn)
+            .await
+            .optional()?
+            .flatten();
+
+        Ok(HeartbeatSnapshot {
+            status,
+            status_since,
+            at,
+            readings,
+            anomaly_sensitivity: anomaly_sensitivity(settings.as_ref()),
+        })
+    }
+
+    /// Records the anomalous latest readings of the channels the anomaly conditions watch,
+    /// whether or not their rules fire. A reading is recorded once, against the first baseline
+    /// that finds it anomalous.
+    async fn record_anomalies(
+        conn: &mut AsyncPgConnection,
+        event: &MachineHeartbeatEvent,
+        snapshot: &HeartbeatSnapshot,
+        conditions: &[AlertCondition],
+    ) -> Result<()> {
+        for condition in conditions {
+            let AlertCondition::Anomaly { channel, .. } = condition else {
+                continue;
+            };
+            let Some(score) = snapshot
+                .readings
+                .get(channel)
+                .and_then(|readings| anomaly_score(readings, condition.window_minutes()))
+                .filter(|score| score.is_anomaly(snapshot.anomaly_sensitivity))
+            else {
+                continue;
+            };
+
+            diesel::insert_into(machine_telemetry_anomalies::table)
+                .values(&NewMachineTelemetryAnomaly {
+                    tenant_id: event.tenant_id,
+                    machine_id: event.machine_id,
+                    channel: channel.to_string(),
+                    value: score.value,
+                    baseline_mean: score.mean,
+                    baseline_std_dev: score.std_dev,
+                    z_score: score.z_score,
+                    sensitivity: snapshot.anomaly_sensitivity,
+                    baseline_minutes: condition.window_minutes() as i32,
+                    recorded_at: score.recorded_at,
+                })
+                .on_conflict((
+                    machine_telemetry_anomalies::machine_id,
+                    machine_telemetry_anomalies::channel,
+                    machine_telemetry_anomalies::recorded_at,
+                ))
+                .do_nothing()
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run_action(
+        &self,
+        action: &AlertAction,
+        rule: &MachineAlertRule,
+        alert: &MachineAlert,
+        machine_name: &str,
+        event: &MachineHeartbeatEvent,
+        email: Option<&EmailService>,
+    ) -> ActionResult {
+        let summary = format!(
+            "Alert rule \"{}\" fired for machine {} at {}",
+            rule.name,
+            machine_name,
+            alert.triggered_at.to_rfc3339()
+        );
+
+        let outcome = match action {
+            AlertAction::Notify { recipients } => match email {
+                Some(email) => {
+                    let subject = format!("Machine alert: {} on {}", rule.name, machine_name);
+                    email
+                        .send(recipients, &subject, &summary, Vec::new())
+                        .await
+                        .map(|_| None)
+                }
+                None => Err(anyhow!("Email delivery is not configured")),
+            },
+            AlertAction::Webhook { url } => {
+                let body = json!({
+                    "alert_id": alert.id,
+                    "rule_id": rule.id,
+                    "rule_name": rule.name,
+                    "machine_id": alert.machine_id,
+                    "machine_name": machine_name,
+                    "status": event.status,
+                    "triggered_at": alert.triggered_at,
+                    "observations": alert.observations,
+                    "payload": event.payload,
+                });
+                post_webhook(url, &body).await.map(|_| None)
+            }
+            AlertAction::CreateMaintenanceOrder {
+                priority,
+                maintenance_type,
+            } => {
+                let request = CreateJobRequest::maintenance(
+                    format!("MNT-{}", &Uuid::new_v4().simple().to_string()[..8]),
+                    priority.clone().unwrap_or(JobPriority::High),
+                    maintenance_type
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_MAINTENANCE_TYPE.to_string()),
+                    summary,
+                    json!({
+                        "machine_alert_id": alert.id,
+                        "machine_alert_rule_id": rule.id,
+                        "machine_id": alert.machine_id,
+                    }),
+                );
+                JobService::new(self.database.clone())
+                    .create_job(alert.tenant_id, request)
+                    .await
+                    .map(|job| Some(job.id.to_string()))
+            }
+        };
+
+        match outcome {
+            Ok(detail) => ActionResult {
+                action: action.kind().to_string(),
+                succeeded: true,
+                detail,
+            },
+            Err(e) => {
+                tracing::warn!(
+                    "Machine alert {} action {} failed: {}",
+                    alert.id,
+                    action.kind(),
+                    e
+                );
+                ActionResult {
+                    action: action.kind().to_string(),
+                    succeeded: false,
+                    detail: Some(e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+// The hook's host is checked again on every send, as its DNS may have changed since the rule was
+// saved, and the connection is pinned to the checked address. Redirects are not followed.
+async fn post_webhook(url: &str, body: &serde_json::Value) -> Result<()> {
+    let url = check_public_url(url, "webhook").map_err(|e| anyhow!(e))?;
+    let builder = Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .redirect(Policy::none());
+    let client = match resolve_public_host(&url, "webhook").await? {
+        Some((domain, address)) => builder.resolve(&domain, address).build()?,
+        None => builder.build()?,
+    };
+    let response = client.post(url).json(body).send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Webhook responded with {}", response.status()));
+    }
+    Ok(())
+}
@@ -0,0 +1,409 @@
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, CreateMachineCommandRequest, HeartbeatCommand, HeartbeatConfig,
+    HeartbeatResponse, ListMachineCommandsQuery, MachineCommand, MachineCommandResponse,
+    MachineCommandResult, MachineCommandStatus, MachineConfig, MachineConfigResponse,
+    NewMachineCommand, NewMachineConfig, Permission, PutMachineConfigRequest,
+    MAX_COMMANDS_PER_HEARTBEAT,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, HeartbeatBuffer, MachineError};
+use crate::utils::machine_command::{
+    backoff_factor, default_reporting_interval, reporting_interval,
+};
+
+type Result<T, E = MachineError> = std::result::Result<T, E>;
+
+// Commands listed when a query gives no limit
+const DEFAULT_COMMAND_LIMIT: i64 = 100;
+
+const OPEN_STATUSES: [&str; 2] = ["pending", "delivered"];
+
+/// Commands and desired configuration the server hands machines in their heartbeat responses.
+/// Only heartbeats sent to the heartbeat endpoint get a response; heartbeats relayed through
+/// ingest sources are recorded without one.
+pub struct MachineCommandService {
+    database: DatabaseService,
+    heartbeat_buffer: Option<HeartbeatBuffer>,
+}
+
+impl MachineCommandService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database,
+            heartbeat_buffer: None,
+        }
+    }
+
+    /// Also backs machines off while the tenant's queue in the heartbeat buffer fills up.
+    pub fn with_buffer(mut self, heartbeat_buffer: Option<HeartbeatBuffer>) -> Self {
+        self.heartbeat_buffer = heartbeat_buffer;
+        self
+    }
+
+    // Command operations
+
+    pub async fn queue_command(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+        request: CreateMachineCommandRequest,
+    ) -> Result<MachineCommandResponse> {
+        caller.require(Permission::CommandMachines)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::require_machine(&mut conn, tenant_id, machine_id).await?;
+
+        let command: MachineCommand = diesel::insert_into(machine_commands::table)
+            .values(&NewMachineCommand {
+                tenant_id,
+                machine_id,
+                command: request.command,
+                parameters: request.parameters,
+                status: MachineCommandStatus::Pending.to_string(),
+                expires_at: request
+                    .expires_in_seconds
+                    .map(|seconds| Utc::now() + Duration::seconds(seconds)),
+                created_by_id: Some(caller.person_id),
+            })
+            .returning(MachineCommand::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(command.into())
+    }
+
+    /// A machine's commands, newest first.
+    pub async fn list_commands(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        query: ListMachineCommandsQuery,
+    ) -> Result<Vec<MachineCommandResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::require_machine(&mut conn, tenant_id, machine_id).await?;
+
+        let mut statement = machine_commands::table
+            .filter(machine_commands::tenant_id.eq(tenant_id))
+            .filter(machine_commands::machine_id.eq(machine_id))
+            .into_boxed();
+        if let Some(status) = query.status {
+            statement = statement.filter(machine_commands::status.eq(status.to_string()));
+        }
+
+        let commands = statement
+            .order(machine_commands::created_at.desc())
+            .limit(query.limit.unwrap_or(DEFAULT_COMMAND_LIMIT))
+            .select(MachineCommand::as_select())
+            .load::<MachineCommand>(&mut conn)
+            .await?;
+
+        Ok(commands.into_iter().map(Into::into).collect())
+    }
+
+    /// Cancels a command the machine has not reported a result for yet. A machine that already
+    /// received it may still run it.
+    pub async fn cancel_command(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+        command_id: Uuid,
+    ) -> Result<MachineCommandResponse> {
+        caller.require(Permission::CommandMachines)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let now = Utc::now();
+        let cancelled = diesel::update(
+            machine_commands::table
+                .filter(machine_commands::id.eq(command_id))
+                .filter(machine_commands::tenant_id.eq(tenant_id))
+                .filter(machine_commands::machine_id.eq(machine_id))
+                .filter(machine_commands::status.eq_any(OPEN_STATUSES)),
+        )
+        .set((
+            machine_commands::status.eq(MachineCommandStatus::Cancelled.to_string()),
+            machine_commands::completed_at.eq(Some(now)),
+        ))
+        .returning(MachineCommand::as_returning())
+        .get_result::<MachineCommand>(&mut conn)
+        .await
+        .optional()?;
+        if let Some(command) = cancelled {
+            return Ok(command.into());
+        }
+
+        let status = machine_commands::table
+            .filter(machine_commands::id.eq(command_id))
+            .filter(machine_commands::tenant_id.eq(tenant_id))
+            .filter(machine_commands::machine_id.eq(machine_id))
+            .select(machine_commands::status)
+            .first::<String>(&mut conn)
+            .await
+            .optional()?;
+        match status {
+            Some(status) => Err(MachineError::CommandClosed(status)),
+            None => Err(MachineError::CommandNotFound),
+        }
+    }
+
+    // Config operations
+
+    pub async fn get_config(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<Option<MachineConfigResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::require_machine(&mut conn, tenant_id, machine_id).await?;
+
+        let config = machine_configs::table
+            .filter(machine_configs::tenant_id.eq(tenant_id))
+            .filter(machine_configs::machine_id.eq(machine_id))
+            .select(MachineConfig::as_select())
+            .first::<MachineConfig>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(config.map(Into::into))
+    }
+
+    /// Replaces a machine's desired configuration. The version is raised when the config
+    /// changes, so the machine is sent it with its next heartbeat.
+    pub async fn put_config(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+        request: PutMachineConfigRequest,
+    ) -> Result<MachineConfigResponse> {
+        caller.require(Permission::CommandMachines)?;
+
+        let person_id = caller.person_id;
+        let config = self
+            .database
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::require_machine(conn, tenant_id, machine_id).await?;
+
+                    let existing = machine_configs::table
+                        .filter(machine_configs::tenant_id.eq(tenant_id))
+                        .filter(machine_configs::machine_id.eq(machine_id))
+                        .for_update()
+                        .select(MachineConfig::as_select())
+                        .first::<MachineConfig>(conn)
+                        .await
+                        .optional()?;
+
+                    let config = match existing {
+                        Some(existing) => {
+                            let version = if existing.config == request.config {
+                                existing.version
+                            } else {
+                                existing.version + 1
+                            };
+                            diesel::update(machine_configs::table.find(existing.id))
+                                .set((
+                                    machine_configs::config.eq(&request.config),
+                                    machine_configs::version.eq(version),
+                                    machine_configs::reporting_interval_seconds
+                                        .eq(request.reporting_interval_seconds),
+                                    machine_configs::updated_by_id.eq(Some(person_id)),
+                                ))
+                                .returning(MachineConfig::as_returning())
+                                .get_result(conn)
+                                .await?
+                        }
+                        None => {
+                            diesel::insert_into(machine_configs::table)
+                                .values(&NewMachineConfig {
+                                    tenant_id,
+                                    machine_id,
+                                    config: request.config,
+                                    version: 1,
+                                    reporting_interval_seconds: request.reporting_interval_seconds,
+                                    updated_by_id: Some(person_id),
+                                })
+                                .returning(MachineConfig::as_returning())
+                                .get_result(conn)
+                                .await?
+                        }
+                    };
+                    Ok(config)
+                })
+            })
+            .await?;
+
+        Ok(config.into())
+    }
+
+    // Heartbeat exchange
+
+    /// Answers a machine's heartbeat: records the command results it reported, expires commands
+    /// past their deadline and returns its open commands, its configuration when the version
+    /// it runs is not the current one, and the interval to report in next.
+    pub async fn heartbeat_response(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        config_version: Option<i32>,
+        command_results: Vec<MachineCommandResult>,
+    ) -> Result<HeartbeatResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::require_machine(&mut conn, tenant_id, machine_id).await?;
+
+        let now = Utc::now();
+        let open_commands = || {
+            machine_commands::table
+                .filter(machine_commands::tenant_id.eq(tenant_id))
+                .filter(machine_commands::machine_id.eq(machine_id))
+                .filter(machine_commands::status.eq_any(OPEN_STATUSES))
+        };
+
+        // Results for commands that are unknown or already closed are ignored
+        for reported in command_results {
+            let status = if reported.succeeded {
+                MachineCommandStatus::Succeeded
+            } else {
+                MachineCommandStatus::Failed
+            };
+            diesel::update(open_commands().filter(machine_commands::id.eq(reported.id)))
+                .set((
+                    machine_commands::status.eq(status.to_string()),
+                    machine_commands::result.eq(reported.result),
+                    machine_commands::completed_at.eq(Some(now)),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        diesel::update(open_commands().filter(machine_commands::expires_at.lt(now)))
+            .set((
+                machine_commands::status.eq(MachineCommandStatus::Expired.to_string()),
+                machine_commands::completed_at.eq(Some(now)),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        let commands = open_commands()
+            .order(machine_commands::created_at.asc())
+            .limit(MAX_COMMANDS_PER_HEARTBEAT)
+            .select(MachineCommand::as_select())
+            .load::<MachineCommand>(&mut conn)
+            .await?;
+        if !commands.is_empty() {
+            let ids: Vec<Uuid> = commands.iter().map(|command| command.id).collect();
+            diesel::update(
+                machine_commands::table
+                    .filter(machine_commands::id.eq_any(&ids))
+                    .filter(machine_commands::delivered_at.is_null()),
+            )
+            .set((
+                machine_commands::status.eq(MachineCommandStatus::Delivered.to_string()),
+                machine_commands::delivered_at.eq(Some(now)),
+            ))
+            .execute(&mut conn)
+            .await?;
+            diesel::update(machine_commands::table.filter(machine_commands::id.eq_any(&ids)))
+                .set(machine_commands::delivery_count.eq(machine_commands::delivery_count + 1))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        let config = machine_configs::table
+            .filter(machine_configs::tenant_id.eq(tenant_id))
+            .filter(machine_configs::machine_id.eq(machine_id))
+            .select(MachineConfig::as_select())
+            .first::<MachineConfig>(&mut conn)
+            .await
+            .optional()?;
+        drop(conn);
+
+        let base = config
+            .as_ref()
+            .and_then(|config| config.reporting_interval_seconds)
+            .unwrap_or_else(default_reporting_interval);
+
+        Ok(HeartbeatResponse {
+            commands: commands.into_iter().map(HeartbeatCommand::from).collect(),
+            config: config
+                .filter(|config| config_version != Some(config.version))
+                .map(|config| HeartbeatConfig {
+                    version: config.version,
+                    config: config.config,
+                }),
+            reporting_interval_seconds: reporting_interval(base, self.load_factor(tenant_id)),
+        })
+    }
+
+    // Backoff for the pool serving the tenant: its dedicated database's, else the shared one;
+    // or for the tenant's heartbeat buffer queue when that is fuller
+    fn load_factor(&self, tenant_id: Uuid) -> i32 {
+        let pools = self.database.pool_stats();
+        let pool_factor = pools
+            .iter()
+            .find(|pool| pool.tenant_id == Some(tenant_id))
+            .or_else(|| pools.iter().find(|pool| pool.tenant_id.is_none()))
+            .map(|pool| {
+                backoff_factor(
+                    pool.connections.saturating_sub(pool.idle_connections),
+                    pool.max_size,
+                )
+            })
+            .unwrap_or(1);
+        let buffer_factor = self
+            .heartbeat_buffer
+            .as_ref()
+            .map_or(1, |buffer| buffer.load_factor(tenant_id));
+        pool_factor.max(buffer_factor)
+    }
+
+    async fn require_machine(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<()> {
+        let exists: bool = diesel::select(diesel::dsl::exists(
+            machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id)),
+        ))
+        .get_result(conn)
+        .await?;
+        if exists {
+            Ok(())
+        } else {
+            Err(MachineError::NotFound)
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::env;
+
+use anyhow::anyhow;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use uuid::Uuid;
+
+use crate::models::{
+    BrokerCredentials, CallerContext, MachineCredential, MachineCredentialResponse,
+    MachineProtocol, MachineProvisioningBundle, NewMachineCredential, Permission,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, MachineError};
+use crate::utils::auth::AuthUtils;
+use crate::utils::machine_credential::{
+    broker_topic, broker_username, generate_broker_password, generate_machine_key, heartbeat_url,
+    machine_key_prefix,
+};
+
+type Result<T, E = MachineError> = std::result::Result<T, E>;
+
+// How stale last_used_at may get before a request records its use again
+const LAST_USED_GRANULARITY_SECONDS: i64 = 60;
+
+/// Credentials machines connect with, issued in one-time provisioning bundles. Credentials live
+/// in the shared database, since the auth middleware resolves an API key before any tenant
+/// database is used; the machines themselves are looked up in the tenant's database.
+pub struct MachineCredentialService {
+    database: DatabaseService,
+    shared: DatabaseService,
+}
+
+impl MachineCredentialService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+            database,
+        }
+    }
+
+    /// Issues a new credential for the machine and returns it as a provisioning bundle with the
+    /// endpoints under `api_url`. The machine's previous credential is revoked, so provisioning
+    /// again also rotates a leaked key. Broker credentials are included for MQTT machines when
+    /// MQTT_BROKER_URL is set, the CA certificate when MACHINE_CA_CERT_FILE is.
+    pub async fn provision(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+        api_url: &str,
+    ) -> Result<MachineProvisioningBundle> {
+        caller.require(Permission::ProvisionMachines)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let protocol = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::protocol)
+            .first::<String>(&mut conn)
+            .await
+            .optional()?
+            .ok_or(MachineError::NotFound)?;
+        drop(conn);
+
+        let broker_url = env::var("MQTT_BROKER_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .filter(|_| MachineProtocol::try_from(protocol) == Ok(MachineProtocol::Mqtt));
+        let ca_certificate = match env::var("MACHINE_CA_CERT_FILE") {
+            Ok(path) if !path.is_empty() => Some(
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| anyhow!("Machine CA certificate unreadable: {}", e))?,
+            ),
+            _ => None,
+        };
+
+        let api_key = generate_machine_key();
+        let broker = broker_url.map(|url| BrokerCredentials {
+            url,
+            username: broker_username(machine_id),
+            password: generate_broker_password(),
+            topic: broker_topic(tenant_id, machine_id),
+        });
+        let new_credential = NewMachineCredential {
+            tenant_id,
+            machine_id,
+            key_prefix: machine_key_prefix(&api_key),
+            key_hash: AuthUtils::hash_token(&api_key),
+            broker_username: broker.as_ref().map(|b| b.username.clone()),
+            broker_password_hash: broker.as_ref().map(|b| AuthUtils::hash_token(&b.password)),
+            created_by_id: Some(caller.person_id),
+        };
+
+        let person_id = caller.person_id;
+        let credential = self
+            .shared
+            .with_tenant_tx::<_, MachineError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    diesel::update(
+                        machine_credentials::table
+                            .filter(machine_credentials::tenant_id.eq(tenant_id))
+                            .filter(machine_credentials::machine_id.eq(machine_id))
+                            .filter(machine_credentials::revoked_at.is_null()),
+                    )
+                    .set((
+                        machine_credentials::revoked_at.eq(Some(Utc::now())),
+                        machine_credentials::revoked_by_id.eq(Some(person_id)),
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                    Ok(diesel::insert_into(machine_credentials::table)
+                        .values(&new_credential)
+                        .returning(MachineCredential::as_returning())
+                        .get_result::<MachineCredential>(conn)
+                        .await?)
+                })
+            })
+            .await?;
+
+        Ok(MachineProvisioningBundle {
+            credential: credential.into(),
+            tenant_id,
+            machine_id,
+            heartbeat_url: heartbeat_url(api_url, machine_id),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key,
+            broker,
+            ca_certificate,
+        })
+    }
+
+    /// The machine's credentials, newest first, without their secrets.
+    pub async fn list_credentials(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+    ) -> Result<Vec<MachineCredentialResponse>> {
+        caller.require(Permission::ProvisionMachines)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let credentials = machine_credentials::table
+            .filter(machine_credentials::tenant_id.eq(tenant_id))
+            .filter(machine_credentials::machine_id.eq(machine_id))
+            .order(machine_credentials::created_at.desc())
+            .select(MachineCredential::as_select())
+            .load::<MachineCredential>(&mut conn)
+            .await?;
+
+        Ok(credentials.into_iter().map(Into::into).collect())
+    }
+
+    /// Revokes the machine's active credential; its API key and broker password stop working at
+    /// once.
+    pub async fn revoke(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        machine_id: Uuid,
+    ) -> Result<MachineCredentialResponse> {
+        caller.require(Permission::ProvisionMachines)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let credential = diesel::update(
+            machine_credentials::table
+                .filter(machine_credentials::tenant_id.eq(tenant_id))
+                .filter(machine_credentials::machine_id.eq(machine_id))
+                .filter(machine_credentials::revoked_at.is_null()),
+        )
+        .set((
+            machine_credentials::revoked_at.eq(Some(Utc::now())),
+            machine_credentials::revoked_by_id.eq(Some(caller.person_id)),
+        ))
+        .returning(MachineCredential::as_returning())
+        .get_result::<MachineCredential>(&mut conn)
+        .await
+        .optional()?
+        .ok_or(MachineError::CredentialNotFound)?;
+
+        Ok(credential.into())
+    }
+
+    /// The active credential the API key belongs to, in any tenant, recording its use.
+    pub async fn authenticate(&self, api_key: &str) -> anyhow::Result<Option<MachineCredential>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // The key decides the tenant, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let Some(credential) = machine_credentials::table
+            .filter(machine_credentials::key_hash.eq(AuthUtils::hash_token(api_key)))
+            .filter(machine_credentials::revoked_at.is_null())
+            .select(MachineCredential::as_select())
+            .first::<MachineCredential>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        // Machines report every few seconds, so use is only recorded once a minute
+        let now = Utc::now();
+        if credential.last_used_at.is_none_or(|last_used| {
+            now - last_used > Duration::seconds(LAST_USED_GRANULARITY_SECONDS)
+        }) {
+            // Set tenant context for RLS
+            conn.batch_execute(&format!(
+                "SET app.current_tenant_id = '{}'",
+                credential.tenant_id
+            ))
+            .await?;
+
+            diesel::update(machine_credentials::table.find(credential.id))
+                .set(machine_credentials::last_used_at.eq(Some(now)))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(Some(credential))
+    }
+}
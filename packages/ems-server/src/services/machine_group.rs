@@ -0,0 +1,507 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateMachineGroupRequest, MachineGroup, MachineGroupHeartbeatResponse, MachineGroupMember,
+    MachineGroupMemberHeartbeat, MachineGroupMemberRequest, MachineGroupMemberRow,
+    MachineGroupMemberUtilization, MachineGroupResponse, MachineGroupUtilizationResponse,
+    MachineStatus, NewMachineGroup, NewMachineGroupMember, UpdateMachineGroupRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::machine_group::{hours_where, line_status, status_periods, union_hours};
+use crate::utils::AppError;
+
+/// Errors from [`MachineGroupService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum MachineGroupError {
+    #[error("Machine not found")]
+    MachineNotFound,
+}
+
+impl From<MachineGroupError> for AppError {
+    fn from(error: MachineGroupError) -> Self {
+        match error {
+            MachineGroupError::MachineNotFound => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+pub struct MachineGroupService {
+    database: DatabaseService,
+}
+
+impl MachineGroupService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Machine group CRUD operations
+
+    pub async fn create_group(
+        &self,
+        tenant_id: Uuid,
+        request: CreateMachineGroupRequest,
+    ) -> Result<MachineGroupResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let group = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let group: MachineGroup = diesel::insert_into(machine_groups::table)
+                        .values(&NewMachineGroup {
+                            tenant_id,
+                            name: request.name,
+                            description: request.description,
+                        })
+                        .returning(MachineGroup::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    Self::replace_members(conn, tenant_id, group.id, &request.members).await?;
+
+                    Ok(group)
+                })
+            })
+            .await?;
+
+        Self::with_members(&mut conn, vec![group])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("Machine group not found"))
+    }
+
+    pub async fn list_groups(&self, tenant_id: Uuid) -> Result<Vec<MachineGroupResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let groups = machine_groups::table
+            .filter(machine_groups::tenant_id.eq(tenant_id))
+            .order(machine_groups::name.asc())
+            .select(MachineGroup::as_select())
+            .load::<MachineGroup>(&mut conn)
+            .await?;
+
+        Self::with_members(&mut conn, groups).await
+    }
+
+    pub async fn get_group(
+        &self,
+        tenant_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<Option<MachineGroupResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(group) = Self::find_group(&mut conn, tenant_id, group_id).await? else {
+            return Ok(None);
+        };
+
+        Ok(Self::with_members(&mut conn, vec![group]).await?.pop())
+    }
+
+    pub async fn update_group(
+        &self,
+        tenant_id: Uuid,
+        group_id: Uuid,
+        request: UpdateMachineGroupRequest,
+    ) -> Result<Option<MachineGroupResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = machine_groups::table
+                        .filter(machine_groups::id.eq(group_id))
+                        .filter(machine_groups::tenant_id.eq(tenant_id));
+
+                    let exists = target
+                        .select(machine_groups::id)
+                        .first::<Uuid>(conn)
+                        .await
+                        .optional()?;
+                    if exists.is_none() {
+                        return Ok(None);
+                    }
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(machine_groups::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(description) = &request.description {
+                        diesel::update(target)
+                            .set(machine_groups::description.eq(description))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(members) = &request.members {
+                        Self::replace_members(conn, tenant_id, group_id, members).await?;
+                    }
+
+                    Ok(target
+                        .select(MachineGroup::as_select())
+                        .first::<MachineGroup>(conn)
+                        .await
+                        .optional()?)
+                })
+            })
+            .await?;
+
+        match updated {
+            Some(group) => Ok(Self::with_members(&mut conn, vec![group]).await?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn delete_group(&self, tenant_id: Uuid, group_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            machine_groups::table
+                .filter(machine_groups::id.eq(group_id))
+                .filter(machine_groups::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Group views
+
+    /// Heartbeats of every member, with members reporting themselves idle or busy but silent
+    /// for longer than `stale_after_minutes` marked stale and counted as offline for the line.
+    pub async fn get_group_heartbeats(
+        &self,
+        tenant_id: Uuid,
+        group_id: Uuid,
+        stale_after_minutes: u32,
+    ) -> Result<Option<MachineGroupHeartbeatResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_group(&mut conn, tenant_id, group_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let stale_before = now - Duration::minutes(stale_after_minutes as i64);
+        let members: Vec<MachineGroupMemberHeartbeat> = Self::load_members(&mut conn, &[group_id])
+            .await?
+            .into_iter()
+            .map(|row| {
+                let status = row.status();
+                let stale = matches!(status, MachineStatus::Idle | MachineStatus::Busy)
+                    && row.last_heartbeat.is_none_or(|at| at < stale_before);
+                MachineGroupMemberHeartbeat {
+                    machine_id: row.member.machine_id,
+                    name: row.name,
+                    position: row.member.position,
+                    is_critical: row.member.is_critical,
+                    status,
+                    last_heartbeat: row.last_heartbeat,
+                    minutes_since_heartbeat: row.last_heartbeat.map(|at| (now - at).num_minutes()),
+                    stale,
+                }
+            })
+            .collect();
+
+        let effective: Vec<(MachineStatus, bool)> = members
+            .iter()
+            .map(|member| {
+                let status = if member.stale {
+                    MachineStatus::Offline
+                } else {
+                    member.status.clone()
+                };
+                (status, member.is_critical)
+            })
+            .collect();
+
+        Ok(Some(MachineGroupHeartbeatResponse {
+            group_id,
+            status: line_status(&effective),
+            stale_count: members.iter().filter(|member| member.stale).count(),
+            members,
+            stale_after_minutes,
+            generated_at: now,
+        }))
+    }
+
+    /// Hours each member spent busy, idle and down over `[from, to)` from the status history,
+    /// and how long the line was down because a critical member was.
+    pub async fn get_group_utilization(
+        &self,
+        tenant_id: Uuid,
+        group_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Option<MachineGroupUtilizationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_group(&mut conn, tenant_id, group_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let window_hours = (to - from).num_seconds() as f64 / 3600.0;
+        let mut members = Vec::new();
+        let mut critical_down = Vec::new();
+
+        for row in Self::load_members(&mut conn, &[group_id]).await? {
+            let machine_id = row.member.machine_id;
+
+            // The status in effect at the start of the window, then every change within it
+            let before = machine_status_history::table
+                .filter(machine_status_history::machine_id.eq(machine_id))
+                .filter(machine_status_history::changed_at.le(from))
+                .order(machine_status_history::changed_at.desc())
+                .select((
+                    machine_status_history::changed_at,
+                    machine_status_history::new_status,
+                ))
+                .first::<(DateTime<Utc>, String)>(&mut conn)
+                .await
+                .optional()?;
+            let within = machine_status_history::table
+                .filter(machine_status_history::machine_id.eq(machine_id))
+                .filter(machine_status_history::changed_at.gt(from))
+                .filter(machine_status_history::changed_at.lt(to))
+                .order(machine_status_history::changed_at.asc())
+                .select((
+                    machine_status_history::changed_at,
+                    machine_status_history::new_status,
+                ))
+                .load::<(DateTime<Utc>, String)>(&mut conn)
+                .await?;
+
+            let transitions: Vec<(DateTime<Utc>, MachineStatus)> = before
+                .into_iter()
+                .chain(within)
+                .filter_map(|(changed_at, status)| {
+                    MachineStatus::try_from(status)
+                        .ok()
+                        .map(|status| (changed_at, status))
+                })
+                .collect();
+            let periods = status_periods(&transitions, from, to);
+
+            if row.member.is_critical {
+                critical_down.extend(
+                    periods
+                        .iter()
+                        .filter(|(_, status)| status.is_down())
+                        .map(|(interval, _)| *interval),
+                );
+            }
+
+            let busy_hours = hours_where(&periods, |status| *status == MachineStatus::Busy);
+            members.push(MachineGroupMemberUtilization {
+                machine_id,
+                name: row.name,
+                position: row.member.position,
+                is_critical: row.member.is_critical,
+                busy_hours,
+                idle_hours: hours_where(&periods, |status| *status == MachineStatus::Idle),
+                down_hours: hours_where(&periods, MachineStatus::is_down),
+                utilization_percent: percent(busy_hours, window_hours),
+            });
+        }
+
+        let line_down_hours = union_hours(critical_down);
+        let line_utilization_percent = if members.is_empty() {
+            0.0
+        } else {
+            members.iter().map(|m| m.utilization_percent).sum::<f64>() / members.len() as f64
+        };
+
+        Ok(Some(MachineGroupUtilizationResponse {
+            group_id,
+            from,
+            to,
+            members,
+            line_down_hours,
+            line_availability_percent: 100.0 - percent(line_down_hours, window_hours),
+            line_utilization_percent,
+        }))
+    }
+
+    // Private helper methods
+
+    async fn find_group(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<Option<MachineGroup>> {
+        let group = machine_groups::table
+            .filter(machine_groups::id.eq(group_id))
+            .filter(machine_groups::tenant_id.eq(tenant_id))
+            .select(MachineGroup::as_select())
+            .first::<MachineGroup>(conn)
+            .await
+            .optional()?;
+
+        Ok(group)
+    }
+
+    /// Members of the groups in line order, with the state each machine last reported.
+    async fn load_members(
+        conn: &mut AsyncPgConnection,
+        group_ids: &[Uuid],
+    ) -> Result<Vec<MachineGroupMemberRow>> {
+        let rows = machine_group_members::table
+            .inner_join(machines::table)
+            .filter(machine_group_members::group_id.eq_any(group_ids))
+            .order((
+                machine_group_members::group_id,
+                machine_group_members::position.asc(),
+            ))
+            .select((
+                MachineGroupMember::as_select(),
+                machines::name,
+                machines::status,
+                machines::last_heartbeat,
+            ))
+            .load::<(MachineGroupMember, String, String, Option<DateTime<Utc>>)>(conn)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(member, name, status, last_heartbeat)| MachineGroupMemberRow {
+                    member,
+                    name,
+                    status,
+                    last_heartbeat,
+                },
+            )
+            .collect())
+    }
+
+    async fn with_members(
+        conn: &mut AsyncPgConnection,
+        groups: Vec<MachineGroup>,
+    ) -> Result<Vec<MachineGroupResponse>> {
+        let group_ids: Vec<Uuid> = groups.iter().map(|group| group.id).collect();
+        let mut members_by_group: HashMap<Uuid, Vec<MachineGroupMemberRow>> = HashMap::new();
+        for row in Self::load_members(conn, &group_ids).await? {
+            members_by_group
+                .entry(row.member.group_id)
+                .or_default()
+                .push(row);
+        }
+
+        Ok(groups
+            .into_iter()
+            .map(|group| {
+                let rows = members_by_group.remove(&group.id).unwrap_or_default();
+                let states: Vec<(MachineStatus, bool)> = rows
+                    .iter()
+                    .map(|row| (row.status(), row.member.is_critical))
+                    .collect();
+                MachineGroupResponse {
+                    id: group.id,
+                    name: group.name,
+                    description: group.description,
+                    status: line_status(&states),
+                    members: rows.into_iter().map(Into::into).collect(),
+                    created_at: group.created_at.unwrap_or_else(Utc::now),
+                    updated_at: group.updated_at.unwrap_or_else(Utc::now),
+                }
+            })
+            .collect())
+    }
+
+    /// Replaces the group's members with the given machines, in order. Every machine must belong
+    /// to the tenant.
+    async fn replace_members(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        group_id: Uuid,
+        members: &[MachineGroupMemberRequest],
+    ) -> Result<()> {
+        let machine_ids: Vec<Uuid> = members.iter().map(|member| member.machine_id).collect();
+        let found: i64 = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(&machine_ids))
+            .count()
+            .get_result(conn)
+            .await?;
+        if found != machine_ids.len() as i64 {
+            return Err(MachineGroupError::MachineNotFound.into());
+        }
+
+        diesel::delete(
+            machine_group_members::table.filter(machine_group_members::group_id.eq(group_id)),
+        )
+        .execute(conn)
+        .await?;
+
+        let new_members: Vec<NewMachineGroupMember> = members
+            .iter()
+            .enumerate()
+            .map(|(position, member)| NewMachineGroupMember {
+                tenant_id,
+                group_id,
+                machine_id: member.machine_id,
+                position: position as i32,
+                is_critical: member.is_critical.unwrap_or(true),
+            })
+            .collect();
+        if !new_members.is_empty() {
+            diesel::insert_into(machine_group_members::table)
+                .values(&new_members)
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn percent(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        (100.0 * part / whole).clamp(0.0, 100.0)
+    } else {
+        0.0
+    }
+}
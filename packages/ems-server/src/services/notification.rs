@@ -0,0 +1,130 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use uuid::Uuid;
+
+use crate::models::{ListNotificationsQuery, Notification, NotificationResponse};
+use crate::schema::notifications;
+use crate::services::DatabaseService;
+
+/// The notification center: each person's notifications about the records they watch.
+pub struct NotificationService {
+    database: DatabaseService,
+}
+
+impl NotificationService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// The person's notifications, newest first
+    pub async fn list_notifications(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        query: ListNotificationsQuery,
+    ) -> Result<Vec<NotificationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut listed = notifications::table
+            .filter(notifications::tenant_id.eq(tenant_id))
+            .filter(notifications::person_id.eq(person_id))
+            .into_boxed();
+        if query.unread.unwrap_or(false) {
+            listed = listed.filter(notifications::read_at.is_null());
+        }
+        if let Some(entity_type) = query.entity_type {
+            listed = listed.filter(notifications::entity_type.eq(entity_type.to_string()));
+        }
+        if let Some(event_type) = query.event_type {
+            listed = listed.filter(notifications::event_type.eq(event_type.to_string()));
+        }
+
+        listed
+            .order(notifications::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(Notification::as_select())
+            .load::<Notification>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|notification| {
+                NotificationResponse::try_from(notification).map_err(|e| anyhow!(e))
+            })
+            .collect()
+    }
+
+    pub async fn unread_count(&self, tenant_id: Uuid, person_id: Uuid) -> Result<i64> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(notifications::table
+            .filter(notifications::tenant_id.eq(tenant_id))
+            .filter(notifications::person_id.eq(person_id))
+            .filter(notifications::read_at.is_null())
+            .count()
+            .get_result(&mut conn)
+            .await?)
+    }
+
+    /// Marks one of the person's notifications read. Returns `None` when it is not theirs.
+    pub async fn mark_read(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        notification_id: Uuid,
+    ) -> Result<Option<NotificationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let notification = diesel::update(
+            notifications::table
+                .filter(notifications::id.eq(notification_id))
+                .filter(notifications::tenant_id.eq(tenant_id))
+                .filter(notifications::person_id.eq(person_id)),
+        )
+        .set(notifications::read_at.eq(diesel::dsl::sql::<
+            diesel::sql_types::Nullable<diesel::sql_types::Timestamptz>,
+        >("COALESCE(read_at, NOW())")))
+        .returning(Notification::as_returning())
+        .get_result::<Notification>(&mut conn)
+        .await
+        .optional()?;
+
+        notification
+            .map(|notification| {
+                NotificationResponse::try_from(notification).map_err(|e| anyhow!(e))
+            })
+            .transpose()
+    }
+
+    /// Marks all of the person's unread notifications read, returning how many there were.
+    pub async fn mark_all_read(&self, tenant_id: Uuid, person_id: Uuid) -> Result<usize> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(diesel::update(
+            notifications::table
+                .filter(notifications::tenant_id.eq(tenant_id))
+                .filter(notifications::person_id.eq(person_id))
+                .filter(notifications::read_at.is_null()),
+        )
+        .set(notifications::read_at.eq(Utc::now()))
+        .execute(&mut conn)
+        .await?)
+    }
+}
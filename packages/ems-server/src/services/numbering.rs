@@ -0,0 +1,388 @@
+use anyhow::Result;
+use chrono::{Datelike, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, NewNumberingSequence, NextNumberResponse, NumberingEntity, NumberingSequence,
+    NumberingSequenceResponse, Permission, PutNumberingSequenceRequest, UpdateNumberingSequence,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::numbering::{format_number, sequence_start};
+use crate::utils::AppError;
+
+// Padding of sequences created without one
+const DEFAULT_PADDING: i32 = 4;
+
+// Numbers already in use skipped before drawing gives up
+const MAX_SKIPPED_NUMBERS: i64 = 1000;
+
+/// Per-tenant sequences generating document numbers. Numbers are drawn with
+/// [`NumberingService::draw`] inside the transaction creating the document, so the sequence
+/// stays locked until the document is committed and a rollback hands the number back.
+/// Errors from drawing document numbers
+#[derive(Error, Debug)]
+pub enum NumberingError {
+    #[error("Numbering sequence not configured: {0}")]
+    NotConfigured(String),
+
+    #[error("Numbering sequence exhausted: {0}")]
+    Exhausted(String),
+}
+
+impl From<NumberingError> for AppError {
+    fn from(error: NumberingError) -> Self {
+        match error {
+            NumberingError::NotConfigured(_) => AppError::Validation(error.to_string()),
+            NumberingError::Exhausted(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+pub struct NumberingService {
+    database: DatabaseService,
+}
+
+impl NumberingService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Sequence methods
+
+    pub async fn list_sequences(&self, tenant_id: Uuid) -> Result<Vec<NumberingSequenceResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let sequences = numbering_sequences::table
+            .filter(numbering_sequences::tenant_id.eq(tenant_id))
+            .order(numbering_sequences::entity.asc())
+            .select(NumberingSequence::as_select())
+            .load::<NumberingSequence>(&mut conn)
+            .await?;
+
+        Ok(sequences.into_iter().map(Self::to_response).collect())
+    }
+
+    pub async fn get_sequence(
+        &self,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+    ) -> Result<Option<NumberingSequenceResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let sequence = numbering_sequences::table
+            .filter(numbering_sequences::tenant_id.eq(tenant_id))
+            .filter(numbering_sequences::entity.eq(entity.to_string()))
+            .select(NumberingSequence::as_select())
+            .first::<NumberingSequence>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(sequence.map(Self::to_response))
+    }
+
+    /// Creates the sequence of an entity, or changes the fields given of an existing one.
+    pub async fn put_sequence(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        entity: NumberingEntity,
+        request: PutNumberingSequenceRequest,
+    ) -> Result<NumberingSequenceResponse> {
+        caller.require(Permission::ConfigureNumbering)?;
+
+        let sequence = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let existing = Self::lock_sequence(conn, tenant_id, entity).await?;
+                    let sequence = match existing {
+                        Some(existing) => {
+                            diesel::update(numbering_sequences::table.find(existing.id))
+                                .set(&UpdateNumberingSequence {
+                                    prefix: request.prefix,
+                                    padding: request.padding,
+                                    yearly_reset: request.yearly_reset,
+                                    next_value: request.next_value,
+                                    updated_at: Some(Utc::now()),
+                                })
+                                .returning(NumberingSequence::as_returning())
+                                .get_result(conn)
+                                .await?
+                        }
+                        None => {
+                            diesel::insert_into(numbering_sequences::table)
+                                .values(&NewNumberingSequence {
+                                    tenant_id,
+                                    entity: entity.to_string(),
+                                    prefix: request.prefix.unwrap_or_default(),
+                                    padding: request.padding.unwrap_or(DEFAULT_PADDING),
+                                    yearly_reset: request.yearly_reset.unwrap_or(false),
+                                    next_value: request.next_value.unwrap_or(1),
+                                })
+                                .returning(NumberingSequence::as_returning())
+                                .get_result(conn)
+                                .await?
+                        }
+                    };
+                    Ok(sequence)
+                })
+            })
+            .await?;
+
+        Ok(Self::to_response(sequence))
+    }
+
+    /// Removes the sequence of an entity; its documents need numbers given again.
+    pub async fn delete_sequence(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        entity: NumberingEntity,
+    ) -> Result<bool> {
+        caller.require(Permission::ConfigureNumbering)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            numbering_sequences::table
+                .filter(numbering_sequences::tenant_id.eq(tenant_id))
+                .filter(numbering_sequences::entity.eq(entity.to_string())),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Draws the next number of an entity for a document numbered outside the API. The number
+    /// is used up whether or not a document ever carries it.
+    pub async fn next_number(
+        &self,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+    ) -> Result<Option<NextNumberResponse>> {
+        let drawn = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move { Self::draw(conn, tenant_id, entity).await })
+            })
+            .await?;
+
+        Ok(drawn.map(|(number, value)| NextNumberResponse {
+            entity: entity.to_string(),
+            number,
+            value,
+        }))
+    }
+
+    // Generation helpers
+
+    /// Draws the next free number of an entity and advances its sequence, or `None` when the
+    /// tenant has no sequence for it. Numbers already in use, for instance given by hand, are
+    /// skipped. Must run inside the transaction creating the document, with the tenant context
+    /// set.
+    pub async fn draw(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+    ) -> Result<Option<(String, i64)>> {
+        let Some(sequence) = Self::lock_sequence(conn, tenant_id, entity).await? else {
+            return Ok(None);
+        };
+
+        let year = Utc::now().year();
+        let start = sequence_start(
+            sequence.next_value,
+            sequence.period_year,
+            sequence.yearly_reset,
+            year,
+        );
+        let mut value = start;
+        let number = loop {
+            let number = format_number(&sequence.prefix, sequence.padding, year, value);
+            if !Self::number_in_use(conn, tenant_id, entity, &number).await? {
+                break number;
+            }
+            if value - start >= MAX_SKIPPED_NUMBERS {
+                return Err(NumberingError::Exhausted(format!(
+                    "{} numbers from {} are in use",
+                    entity,
+                    format_number(&sequence.prefix, sequence.padding, year, start)
+                ))
+                .into());
+            }
+            value += 1;
+        };
+
+        diesel::update(numbering_sequences::table.find(sequence.id))
+            .set((
+                numbering_sequences::next_value.eq(value + 1),
+                numbering_sequences::period_year.eq(Some(year)),
+                numbering_sequences::updated_at.eq(Some(Utc::now())),
+            ))
+            .execute(conn)
+            .await?;
+
+        Ok(Some((number, value)))
+    }
+
+    /// The number given for a document, or the next one drawn from the entity's sequence when
+    /// none was given.
+    pub async fn number_or_draw(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+        given: Option<String>,
+    ) -> Result<String> {
+        if let Some(number) = given {
+            return Ok(number);
+        }
+        match Self::draw(conn, tenant_id, entity).await? {
+            Some((number, _)) => Ok(number),
+            None => Err(NumberingError::NotConfigured(format!(
+                "give a {} number or set up its sequence",
+                entity
+            ))
+            .into()),
+        }
+    }
+
+    /// The number given for a document, else the next one drawn from the entity's sequence, else
+    /// `fallback` when the tenant has no sequence for the entity.
+    pub async fn number_or_else(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+        given: Option<String>,
+        fallback: impl FnOnce() -> String,
+    ) -> Result<String> {
+        if let Some(number) = given {
+            return Ok(number);
+        }
+        match Self::draw(conn, tenant_id, entity).await? {
+            Some((number, _)) => Ok(number),
+            None => Ok(fallback()),
+        }
+    }
+
+    async fn lock_sequence(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+    ) -> Result<Option<NumberingSequence>> {
+        let sequence = numbering_sequences::table
+            .filter(numbering_sequences::tenant_id.eq(tenant_id))
+            .filter(numbering_sequences::entity.eq(entity.to_string()))
+            .for_update()
+            .select(NumberingSequence::as_select())
+            .first::<NumberingSequence>(conn)
+            .await
+            .optional()?;
+        Ok(sequence)
+    }
+
+    // Whether a document of the entity already carries the number. Item part numbers are unique
+    // across tenants, so they are checked against every item.
+    async fn number_in_use(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: NumberingEntity,
+        number: &str,
+    ) -> Result<bool> {
+        let in_use = match entity {
+            NumberingEntity::PurchaseOrder
+            | NumberingEntity::CustomerOrder
+            | NumberingEntity::DistributorOrder => {
+                diesel::select(diesel::dsl::exists(
+                    orders::table
+                        .filter(orders::tenant_id.eq(tenant_id))
+                        .filter(orders::order_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+            NumberingEntity::Job => {
+                diesel::select(diesel::dsl::exists(
+                    jobs::table
+                        .filter(jobs::tenant_id.eq(tenant_id))
+                        .filter(jobs::job_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+            NumberingEntity::Item => {
+                diesel::select(diesel::dsl::exists(
+                    items::table.filter(items::internal_part_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+            NumberingEntity::Quote => {
+                diesel::select(diesel::dsl::exists(
+                    quotes::table
+                        .filter(quotes::tenant_id.eq(tenant_id))
+                        .filter(quotes::quote_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+            NumberingEntity::Return => {
+                diesel::select(diesel::dsl::exists(
+                    order_returns::table
+                        .filter(order_returns::tenant_id.eq(tenant_id))
+                        .filter(order_returns::rma_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+            NumberingEntity::Rfq => {
+                diesel::select(diesel::dsl::exists(
+                    rfqs::table
+                        .filter(rfqs::tenant_id.eq(tenant_id))
+                        .filter(rfqs::rfq_number.eq(number)),
+                ))
+                .get_result(conn)
+                .await?
+            }
+        };
+        Ok(in_use)
+    }
+
+    fn to_response(sequence: NumberingSequence) -> NumberingSequenceResponse {
+        let year = Utc::now().year();
+        let next = sequence_start(
+            sequence.next_value,
+            sequence.period_year,
+            sequence.yearly_reset,
+            year,
+        );
+        NumberingSequenceResponse {
+            id: sequence.id,
+            preview: format_number(&sequence.prefix, sequence.padding, year, next),
+            entity: sequence.entity,
+            prefix: sequence.prefix,
+            padding: sequence.padding,
+            yearly_reset: sequence.yearly_reset,
+            next_value: sequence.next_value,
+            period_year: sequence.period_year,
+            created_at: sequence.created_at.unwrap_or_else(Utc::now),
+            updated_at: sequence.updated_at.unwrap_or_else(Utc::now),
+        }
+    }
+}
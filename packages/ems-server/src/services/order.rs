@@ -1,25 +1,124 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
+use diesel::dsl::IntoBoxed;
+use diesel::pg::Pg;
 use diesel::prelude::*;
-use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    CreateOrderIdResponse, CreateOrderRequest, CustomerOrderResponse, DistributorOrderResponse,
-    ExternalEntityType, NewOrder, NewOrderHistory, NewOrderItem, Order, OrderHistory, OrderItem,
-    OrderItemResponse, OrderResponse, OrderStatus, OrderType, PurchaseOrderResponse,
+    AnalyticsGranularity, AvailableToPromiseQuery, CreateOrderIdResponse, CreateOrderRequest,
+    CustomerOrderResponse, DataVersion, DistributorOrderResponse, ExternalEntityType, NewOrder,
+    NewOrderHistory, NewOrderItem, NumberingEntity, Order, OrderAnalyticsBucket,
+    OrderAnalyticsGroup, OrderAnalyticsQuery, OrderAnalyticsResponse, OrderAnalyticsRow,
+    OrderHistory, OrderItem, OrderItemResponse, OrderResponse, OrderStatus, OrderType,
+    PurchaseOrderResponse, RuleEntity, SendOrderConfirmationRequest, StockReferenceType,
     UpdateOrderRequest,
 };
 use crate::schema::*;
-use crate::services::DatabaseService;
+use crate::services::{
+    DatabaseService, EmailAttachment, EmailService, NumberingService, StockService, UomError,
+    UomService, ValidationRuleService,
+};
+use crate::utils::branding::DocumentBranding;
+use crate::utils::i18n::Locale;
+use crate::utils::order_confirmation::{order_confirmation_email, render_order_confirmation_pdf};
+use crate::utils::AppError;
+
+/// Errors from [`OrderService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum OrderError {
+    #[error("Order not found")]
+    NotFound,
+
+    #[error("Order cannot be archived: {0}")]
+    NotArchivable(String),
+
+    #[error("Order line cannot be backordered: {0}")]
+    NotBackorderable(String),
+
+    #[error("Order cannot be confirmed: {0}")]
+    NotConfirmable(String),
+
+    #[error("Email delivery is not configured (SMTP_HOST is not set)")]
+    EmailNotConfigured,
+}
+
+impl From<OrderError> for AppError {
+    fn from(error: OrderError) -> Self {
+        match error {
+            OrderError::NotFound => AppError::NotFound(error.to_string()),
+            OrderError::NotArchivable(_)
+            | OrderError::NotBackorderable(_)
+            | OrderError::NotConfirmable(_) => AppError::Conflict(error.to_string()),
+            OrderError::EmailNotConfigured => AppError::Unavailable(error.to_string()),
+        }
+    }
+}
+
+// Length of the analytics window when the query gives no start
+const DEFAULT_ANALYTICS_DAYS: i64 = 90;
+
+// Placed orders of one type in the window, with the period each falls in, when it was first
+// fulfilled and the latest date its lines were expected. Orders moved straight to a fulfilled
+// status on creation have no history entry, so their last update stands in.
+const PLACED_ORDERS_SQL: &str = r#"
+    WITH placed AS (
+        SELECT o.id, o.external_entity_id, o.total_amount,
+               date_trunc($1, o.order_date AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS period_start,
+               COALESCE(f.fulfilled_at,
+                        CASE WHEN o.status IN ('fulfilled', 'paid') THEN o.updated_at END) AS fulfilled_at,
+               o.order_date,
+               d.due_date
+        FROM orders o
+        LEFT JOIN LATERAL (
+            SELECT MIN(h.created_at) AS fulfilled_at
+            FROM order_history h
+            WHERE h.order_id = o.id AND h.new_status = 'fulfilled'
+        ) f ON TRUE
+        LEFT JOIN LATERAL (
+            SELECT MAX(i.expected_date) AS due_date
+            FROM order_items i
+            WHERE i.order_id = o.id
+        ) d ON TRUE
+        WHERE o.tenant_id = $2
+          AND o.order_type = $3
+          AND o.order_date >= $4
+          AND o.order_date < $5
+          AND o.status NOT IN ('draft', 'cancelled')
+    )
+"#;
+
+// Measures of each bucket over the placed orders `p`, given the revenue expression
+fn analytics_measures(revenue: &str) -> String {
+    format!(
+        r#"COUNT(p.id) AS order_count,
+           COALESCE(SUM({revenue}), 0)::float8 AS revenue,
+           COUNT(p.fulfilled_at) AS fulfilled_count,
+           (AVG(EXTRACT(EPOCH FROM (p.fulfilled_at - p.order_date))) / 86400.0)::float8 AS avg_lead_time_days,
+           COUNT(p.id) FILTER (WHERE p.fulfilled_at IS NOT NULL AND p.due_date IS NOT NULL) AS due_count,
+           COUNT(p.id) FILTER (WHERE p.fulfilled_at <= p.due_date) AS on_time_count"#
+    )
+}
 
 pub struct OrderService {
     database: DatabaseService,
+    branding: DocumentBranding,
 }
 
 impl OrderService {
     pub fn new(database: DatabaseService) -> Self {
-        Self { database }
+        Self {
+            database,
+            branding: DocumentBranding::default(),
+        }
+    }
+
+    /// Renders and emails confirmations with the tenant's branding.
+    pub fn with_branding(mut self, branding: DocumentBranding) -> Self {
+        self.branding = branding;
+        self
     }
 
     // General Order API methods
@@ -29,6 +128,21 @@ impl OrderService {
         tenant_id: Uuid,
         request: CreateOrderRequest,
     ) -> Result<CreateOrderIdResponse> {
+        // Check the tenant's business rules before anything is written
+        let record = serde_json::json!({
+            "order_number": request.order_number,
+            "order_type": request.order_type.to_string(),
+            "external_entity_type": request.external_entity_type.to_string(),
+            "total_amount": request.total_amount,
+            "status": request.status.clone().unwrap_or(OrderStatus::Draft).to_string(),
+            "notes": request.notes,
+            "line_count": request.items.len(),
+            "metadata": request.metadata,
+        });
+        ValidationRuleService::new(self.database.clone())
+            .enforce(tenant_id, RuleEntity::Order, &record)
+            .await?;
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
@@ -36,12 +150,21 @@ impl OrderService {
             .await?;
 
         let order_id = conn
-            .transaction::<_, diesel::result::Error, _>(|conn| {
+            .transaction::<_, anyhow::Error, _>(|conn| {
                 Box::pin(async move {
+                    // Number the order from its type's sequence unless given
+                    let order_number = NumberingService::number_or_draw(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::from(&request.order_type),
+                        request.order_number.clone(),
+                    )
+                    .await?;
+
                     // Create order record
                     let new_order = NewOrder {
                         tenant_id,
-                        order_number: request.order_number.clone(),
+                        order_number,
                         order_type: request.order_type.to_string(),
                         external_entity_id: request.external_entity_id,
                         external_entity_type: request.external_entity_type.to_string(),
@@ -63,9 +186,31 @@ impl OrderService {
                         .get_result(conn)
                         .await?;
 
-                    // Create order items
+                    // Create order items, converting each quantity to the item's base unit
+                    let purchase = matches!(request.order_type, OrderType::PurchaseOrder);
                     for item_request in &request.items {
                         let extended_price = item_request.quantity as f64 * item_request.unit_price;
+                        let (uom_id, base_quantity) = match item_request.item_id {
+                            Some(item_id) => {
+                                UomService::line_quantity(
+                                    conn,
+                                    tenant_id,
+                                    item_id,
+                                    item_request.uom_id,
+                                    item_request.quantity,
+                                    purchase,
+                                )
+                                .await?
+                            }
+                            None if item_request.uom_id.is_some() => {
+                                return Err(UomError::Invalid(format!(
+                                    "line {} has no item",
+                                    item_request.item_name
+                                ))
+                                .into());
+                            }
+                            None => (None, item_request.quantity),
+                        };
 
                         let new_item = NewOrderItem {
                             order_id: order.id,
@@ -76,6 +221,9 @@ impl OrderService {
                             unit_price: item_request.unit_price,
                             extended_price: extended_price,
                             notes: item_request.notes.clone(),
+                            expected_date: item_request.expected_date,
+                            uom_id,
+                            base_quantity,
                         };
 
                         diesel::insert_into(order_items::table)
@@ -103,8 +251,7 @@ impl OrderService {
                     Ok(order.id)
                 })
             })
-            .await
-            .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+            .await?;
 
         Ok(CreateOrderIdResponse { id: order_id })
     }
@@ -137,21 +284,8 @@ impl OrderService {
                 .load::<OrderItem>(&mut conn)
                 .await?;
 
-            let item_responses: Vec<OrderItemResponse> = items
-                .into_iter()
-                .map(|item| OrderItemResponse {
-                    id: item.id,
-                    item_id: item.item_id,
-                    item_name: item.item_name,
-                    item_description: item.item_description,
-                    quantity: item.quantity,
-                    unit_price: item.unit_price,
-                    extended_price: item.extended_price,
-                    notes: item.notes,
-                    created_at: item.created_at.unwrap_or_else(|| Utc::now()),
-                    updated_at: item.updated_at.unwrap_or_else(|| Utc::now()),
-                })
-                .collect();
+            let item_responses: Vec<OrderItemResponse> =
+                items.into_iter().map(OrderItemResponse::from).collect();
 
             Ok(Some(OrderResponse {
                 id: order.id,
@@ -170,6 +304,7 @@ impl OrderService {
                 metadata: order.metadata,
                 created_at: order.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: order.updated_at.unwrap_or_else(|| Utc::now()),
+                archived: order.archived,
                 items: item_responses,
             }))
         } else {
@@ -183,63 +318,105 @@ impl OrderService {
         order_id: Uuid,
         request: UpdateOrderRequest,
     ) -> Result<OrderResponse> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        conn.transaction::<_, diesel::result::Error, _>(|conn| {
-            Box::pin(async move {
-                // Update order fields individually
-                if let Some(order_number) = &request.order_number {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::order_number.eq(order_number))
-                        .execute(conn)
-                        .await?;
-                }
-                if let Some(external_entity_id) = request.external_entity_id {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::external_entity_id.eq(external_entity_id))
-                        .execute(conn)
-                        .await?;
-                }
-                if let Some(order_date) = request.order_date {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::order_date.eq(order_date))
-                        .execute(conn)
-                        .await?;
-                }
-                if let Some(total_amount) = request.total_amount {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::total_amount.eq(total_amount))
-                        .execute(conn)
-                        .await?;
-                }
-                if let Some(status) = &request.status {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::status.eq(status.to_string()))
-                        .execute(conn)
-                        .await?;
-                }
-                if let Some(notes) = &request.notes {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::notes.eq(notes))
-                        .execute(conn)
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Lock the order so the record checked against the tenant's business rules
+                    // is the one written
+                    let order = orders::table
+                        .filter(orders::id.eq(order_id))
+                        .filter(orders::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Order::as_select())
+                        .first::<Order>(conn)
+                        .await
+                        .optional()?
+                        .ok_or(OrderError::NotFound)?;
+                    let line_count: i64 = order_items::table
+                        .filter(order_items::order_id.eq(order_id))
+                        .count()
+                        .get_result(conn)
                         .await?;
-                }
-                if let Some(metadata) = &request.metadata {
-                    diesel::update(orders::table.filter(orders::id.eq(order_id)))
-                        .set(orders::metadata.eq(metadata))
-                        .execute(conn)
+
+                    // Check the rules against the order as it will be once updated
+                    let record = serde_json::json!({
+                        "order_number": request
+                            .order_number
+                            .as_ref()
+                            .unwrap_or(&order.order_number),
+                        "order_type": order.order_type,
+                        "external_entity_type": order.external_entity_type,
+                        "total_amount": request.total_amount.unwrap_or(order.total_amount),
+                        "status": request
+                            .status
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or(order.status),
+                        "notes": request.notes.as_ref().or(order.notes.as_ref()),
+                        "line_count": line_count,
+                        "metadata": request.metadata.as_ref().or(order.metadata.as_ref()),
+                    });
+                    ValidationRuleService::enforce_in(conn, tenant_id, RuleEntity::Order, &record)
                         .await?;
-                }
 
-                Ok(())
+                    // Update order fields individually
+                    if let Some(order_number) = &request.order_number {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::order_number.eq(order_number))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(external_entity_id) = request.external_entity_id {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::external_entity_id.eq(external_entity_id))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(order_date) = request.order_date {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::order_date.eq(order_date))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(total_amount) = request.total_amount {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::total_amount.eq(total_amount))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(status) = &request.status {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::status.eq(status.to_string()))
+                            .execute(conn)
+                            .await?;
+
+                        // A closed order no longer needs the stock set aside for it
+                        if matches!(status, OrderStatus::Cancelled | OrderStatus::Fulfilled) {
+                            StockService::release_reservations_for(
+                                conn,
+                                StockReferenceType::Order,
+                                order_id,
+                            )
+                            .await?;
+                        }
+                    }
+                    if let Some(notes) = &request.notes {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::notes.eq(notes))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(metadata) = &request.metadata {
+                        diesel::update(orders::table.filter(orders::id.eq(order_id)))
+                            .set(orders::metadata.eq(metadata))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(())
+                })
             })
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Update transaction failed: {}", e))?;
+            .await?;
 
         // Return updated order
         self.get_order_by_id(tenant_id, order_id)
@@ -265,11 +442,89 @@ impl OrderService {
         Ok(())
     }
 
+    /// Archives or unarchives an order. Only closed orders can be archived; an order unarchived
+    /// by hand is not archived automatically again. Returns `None` when the order does not exist.
+    pub async fn set_archived(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        archived: bool,
+    ) -> Result<Option<OrderResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(order) = orders::table
+            .filter(orders::id.eq(order_id))
+            .filter(orders::tenant_id.eq(tenant_id))
+            .select(Order::as_select())
+            .first::<Order>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        if archived && !OrderStatus::try_from(order.status.clone()).is_ok_and(|s| s.is_closed()) {
+            return Err(OrderError::NotArchivable(format!("status is {}", order.status)).into());
+        }
+
+        diesel::update(orders::table.find(order_id))
+            .set((
+                orders::archived.eq(archived),
+                orders::archived_at.eq(if archived {
+                    Some(Utc::now())
+                } else {
+                    order.archived_at
+                }),
+            ))
+            .execute(&mut conn)
+            .await?;
+        drop(conn);
+
+        self.get_order_by_id(tenant_id, order_id).await
+    }
+
+    /// Archives the tenant's orders closed before `closed_before`, leaving out orders that were
+    /// ever archived before. An order counts as closed at its last update.
+    pub async fn archive_closed(
+        &self,
+        tenant_id: Uuid,
+        closed_before: DateTime<Utc>,
+    ) -> Result<usize> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let closed: Vec<String> = OrderStatus::CLOSED.iter().map(|s| s.to_string()).collect();
+        let archived = diesel::update(
+            orders::table
+                .filter(orders::tenant_id.eq(tenant_id))
+                .filter(orders::archived.eq(false))
+                .filter(orders::archived_at.is_null())
+                .filter(orders::status.eq_any(closed))
+                .filter(orders::updated_at.lt(closed_before)),
+        )
+        .set((
+            orders::archived.eq(true),
+            orders::archived_at.eq(Some(Utc::now())),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+        Ok(archived)
+    }
+
     pub async fn list_orders(
         &self,
         tenant_id: Uuid,
         order_type: Option<OrderType>,
         status: Option<OrderStatus>,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<OrderResponse>> {
@@ -279,19 +534,7 @@ impl OrderService {
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        let mut query = orders::table
-            .filter(orders::tenant_id.eq(tenant_id))
-            .into_boxed();
-
-        // Filter by order type if specified
-        if let Some(order_type) = &order_type {
-            query = query.filter(orders::order_type.eq(order_type.to_string()));
-        }
-
-        // Filter by status if specified
-        if let Some(status) = &status {
-            query = query.filter(orders::status.eq(status.to_string()));
-        }
+        let mut query = Self::listed_orders(tenant_id, order_type, status, include_archived);
 
         // Apply pagination
         if let Some(limit_val) = limit {
@@ -315,21 +558,8 @@ impl OrderService {
                 .load::<OrderItem>(&mut conn)
                 .await?;
 
-            let item_responses: Vec<OrderItemResponse> = items
-                .into_iter()
-                .map(|item| OrderItemResponse {
-                    id: item.id,
-                    item_id: item.item_id,
-                    item_name: item.item_name,
-                    item_description: item.item_description,
-                    quantity: item.quantity,
-                    unit_price: item.unit_price,
-                    extended_price: item.extended_price,
-                    notes: item.notes,
-                    created_at: item.created_at.unwrap_or_else(|| Utc::now()),
-                    updated_at: item.updated_at.unwrap_or_else(|| Utc::now()),
-                })
-                .collect();
+            let item_responses: Vec<OrderItemResponse> =
+                items.into_iter().map(OrderItemResponse::from).collect();
 
             order_responses.push(OrderResponse {
                 id: order.id,
@@ -348,6 +578,7 @@ impl OrderService {
                 metadata: order.metadata,
                 created_at: order.created_at.unwrap_or_else(|| Utc::now()),
                 updated_at: order.updated_at.unwrap_or_else(|| Utc::now()),
+                archived: order.archived,
                 items: item_responses,
             });
         }
@@ -355,11 +586,124 @@ impl OrderService {
         Ok(order_responses)
     }
 
+    /// Version of the orders [`Self::list_orders`] lists with these filters, across all pages,
+    /// including their lines.
+    pub async fn list_version(
+        &self,
+        tenant_id: Uuid,
+        order_type: Option<OrderType>,
+        status: Option<OrderStatus>,
+        include_archived: bool,
+    ) -> Result<DataVersion> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let (orders, orders_updated_at) = Self::listed_orders(
+            tenant_id,
+            order_type.clone(),
+            status.clone(),
+            include_archived,
+        )
+        .select((
+            diesel::dsl::count_star(),
+            diesel::dsl::max(orders::updated_at),
+        ))
+        .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)
+        .await?;
+        let (lines, lines_updated_at) = order_items::table
+            .filter(
+                order_items::order_id.eq_any(
+                    Self::listed_orders(tenant_id, order_type, status, include_archived)
+                        .select(orders::id),
+                ),
+            )
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(order_items::updated_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)
+            .await?;
+
+        Ok(DataVersion::new(orders, &[orders_updated_at])
+            .and(DataVersion::new(lines, &[lines_updated_at])))
+    }
+
+    /// Version of the order [`Self::get_order_by_id`] returns, including its lines, or `None`
+    /// when there is none.
+    pub async fn order_version(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+    ) -> Result<Option<DataVersion>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(order_updated_at) = orders::table
+            .filter(orders::id.eq(order_id))
+            .filter(orders::tenant_id.eq(tenant_id))
+            .select(orders::updated_at)
+            .first::<Option<DateTime<Utc>>>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let (lines, lines_updated_at) = order_items::table
+            .filter(order_items::order_id.eq(order_id))
+            .select((
+                diesel::dsl::count_star(),
+                diesel::dsl::max(order_items::updated_at),
+            ))
+            .first::<(i64, Option<DateTime<Utc>>)>(&mut conn)
+            .await?;
+
+        Ok(Some(
+            DataVersion::new(1, &[order_updated_at])
+                .and(DataVersion::new(lines, &[lines_updated_at])),
+        ))
+    }
+
+    /// The tenant's orders, narrowed by the list filters.
+    fn listed_orders(
+        tenant_id: Uuid,
+        order_type: Option<OrderType>,
+        status: Option<OrderStatus>,
+        include_archived: bool,
+    ) -> IntoBoxed<'static, orders::table, Pg> {
+        let mut query = orders::table
+            .into_boxed()
+            .filter(orders::tenant_id.eq(tenant_id));
+
+        // Filter by order type if specified
+        if let Some(order_type) = order_type {
+            query = query.filter(orders::order_type.eq(order_type.to_string()));
+        }
+
+        // Filter by status if specified
+        if let Some(status) = status {
+            query = query.filter(orders::status.eq(status.to_string()));
+        }
+
+        // Leave out archived orders unless asked for
+        if !include_archived {
+            query = query.filter(orders::archived.eq(false));
+        }
+
+        query
+    }
+
     // Type-specific order methods
 
     pub async fn list_purchase_orders(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<PurchaseOrderResponse>> {
@@ -368,6 +712,7 @@ impl OrderService {
                 tenant_id,
                 Some(OrderType::PurchaseOrder),
                 None,
+                include_archived,
                 limit,
                 offset,
             )
@@ -425,6 +770,7 @@ impl OrderService {
     pub async fn list_customer_orders(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<CustomerOrderResponse>> {
@@ -433,6 +779,7 @@ impl OrderService {
                 tenant_id,
                 Some(OrderType::CustomerOrder),
                 None,
+                include_archived,
                 limit,
                 offset,
             )
@@ -490,6 +837,7 @@ impl OrderService {
     pub async fn list_distributor_orders(
         &self,
         tenant_id: Uuid,
+        include_archived: bool,
         limit: Option<u32>,
         offset: Option<u32>,
     ) -> Result<Vec<DistributorOrderResponse>> {
@@ -498,6 +846,7 @@ impl OrderService {
                 tenant_id,
                 Some(OrderType::DistributorOrder),
                 None,
+                include_archived,
                 limit,
                 offset,
             )
@@ -573,4 +922,420 @@ impl OrderService {
 
         Ok(history)
     }
+
+    // Order analytics
+
+    /// Order counts, revenue, lead time and on-time delivery of placed orders, bucketed by the
+    /// period they were placed in (weeks start on Monday, all periods in UTC) and optionally
+    /// split by customer or item category.
+    pub async fn order_analytics(
+        &self,
+        tenant_id: Uuid,
+        query: OrderAnalyticsQuery,
+    ) -> Result<OrderAnalyticsResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let granularity = query.granularity.unwrap_or(AnalyticsGranularity::Week);
+        let order_type = query.order_type.unwrap_or(OrderType::CustomerOrder);
+        let to = query.to.unwrap_or_else(Utc::now);
+        let from = query
+            .from
+            .unwrap_or(to - Duration::days(DEFAULT_ANALYTICS_DAYS));
+        let step = match granularity {
+            AnalyticsGranularity::Day => "1 day",
+            AnalyticsGranularity::Week => "1 week",
+            AnalyticsGranularity::Month => "1 month",
+            AnalyticsGranularity::Quarter => "3 months",
+        };
+
+        let sql = match query.group_by {
+            // Every period in the window, so charts show the gaps
+            None => format!(
+                r#"{PLACED_ORDERS_SQL},
+                periods AS (
+                    SELECT s AT TIME ZONE 'UTC' AS period_start
+                    FROM generate_series(
+                        date_trunc($1, $4 AT TIME ZONE 'UTC'),
+                        ($5 AT TIME ZONE 'UTC') - INTERVAL '1 microsecond',
+                        INTERVAL '{step}'
+                    ) s
+                )
+                SELECT periods.period_start, NULL::text AS group_key, NULL::text AS group_label,
+                       {}
+                FROM periods
+                LEFT JOIN placed p ON p.period_start = periods.period_start
+                GROUP BY periods.period_start
+                ORDER BY periods.period_start"#,
+                analytics_measures("p.total_amount")
+            ),
+            Some(OrderAnalyticsGroup::Customer) => format!(
+                r#"{PLACED_ORDERS_SQL}
+                SELECT p.period_start, p.external_entity_id::text AS group_key,
+                       pe.name::text AS group_label,
+                       {}
+                FROM placed p
+                LEFT JOIN person pe ON pe.id = p.external_entity_id
+                GROUP BY p.period_start, p.external_entity_id, pe.name
+                ORDER BY p.period_start, pe.name, p.external_entity_id"#,
+                analytics_measures("p.total_amount")
+            ),
+            Some(OrderAnalyticsGroup::ItemCategory) => format!(
+                r#"{PLACED_ORDERS_SQL}
+                SELECT p.period_start, c.category AS group_key, c.category AS group_label,
+                       {}
+                FROM placed p
+                JOIN LATERAL (
+                    SELECT COALESCE(it.category, 'uncategorized')::text AS category,
+                           SUM(oi.extended_price) AS revenue
+                    FROM order_items oi
+                    LEFT JOIN items it ON it.id = oi.item_id
+                    WHERE oi.order_id = p.id
+                    GROUP BY 1
+                ) c ON TRUE
+                GROUP BY p.period_start, c.category
+                ORDER BY p.period_start, c.category"#,
+                analytics_measures("c.revenue")
+            ),
+        };
+
+        let rows = diesel::sql_query(sql)
+            .bind::<diesel::sql_types::Text, _>(granularity.to_string())
+            .bind::<diesel::sql_types::Uuid, _>(tenant_id)
+            .bind::<diesel::sql_types::Text, _>(order_type.to_string())
+            .bind::<diesel::sql_types::Timestamptz, _>(from)
+            .bind::<diesel::sql_types::Timestamptz, _>(to)
+            .load::<OrderAnalyticsRow>(&mut conn)
+            .await?;
+
+        Ok(OrderAnalyticsResponse {
+            granularity,
+            from,
+            to,
+            order_type,
+            group_by: query.group_by,
+            buckets: rows.into_iter().map(OrderAnalyticsBucket::from).collect(),
+        })
+    }
+
+    // Backorders
+
+    /// Flags a sales order line as backordered, setting its expected date to the earliest
+    /// date available-to-promise can cover it (left empty when no open receipt does).
+    /// Returns `None` when the order or line does not exist.
+    pub async fn backorder_line(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        line_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<Option<OrderItemResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let line = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some((order, line)) =
+                        Self::lock_line(conn, tenant_id, order_id, line_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    if order.order_type == OrderType::PurchaseOrder.to_string() {
+                        return Err(OrderError::NotBackorderable(
+                            "purchase orders are supply".to_string(),
+                        )
+                        .into());
+                    }
+                    if !matches!(
+                        OrderStatus::try_from(order.status.clone()),
+                        Ok(OrderStatus::Submitted
+                            | OrderStatus::Approved
+                            | OrderStatus::PartiallyFulfilled)
+                    ) {
+                        return Err(OrderError::NotBackorderable(format!(
+                            "status is {}",
+                            order.status
+                        ))
+                        .into());
+                    }
+                    let Some(item_id) = line.item_id else {
+                        return Err(
+                            OrderError::NotBackorderable("no item linked".to_string()).into()
+                        );
+                    };
+
+                    // The line is promised against everything except its own demand
+                    let query = AvailableToPromiseQuery {
+                        quantity: line.base_quantity,
+                        date: None,
+                        context: None,
+                    };
+                    let atp = StockService::compute_available_to_promise(
+                        conn,
+                        tenant_id,
+                        item_id,
+                        &query,
+                        Some(line.id),
+                    )
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Item not found: {}", item_id))?;
+                    if atp.can_promise {
+                        return Err(OrderError::NotBackorderable(format!(
+                            "{} available to promise now",
+                            atp.available_on_date
+                        ))
+                        .into());
+                    }
+
+                    let line: OrderItem = diesel::update(order_items::table.find(line.id))
+                        .set((
+                            order_items::backordered.eq(true),
+                            order_items::expected_date.eq(atp.promise_date),
+                        ))
+                        .returning(OrderItem::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let notes = match atp.promise_date {
+                        Some(date) => format!(
+                            "{} backordered, expected {}",
+                            line.item_name,
+                            date.format("%Y-%m-%d")
+                        ),
+                        None => format!("{} backordered, no expected date", line.item_name),
+                    };
+                    diesel::insert_into(order_history::table)
+                        .values(&NewOrderHistory {
+                            order_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "backorder".to_string(),
+                            previous_status: None,
+                            new_status: None,
+                            notes: Some(notes),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(line))
+                })
+            })
+            .await?;
+
+        Ok(line.map(OrderItemResponse::from))
+    }
+
+    /// Clears the backorder flag once the line can ship; the expected date is kept.
+    pub async fn clear_backorder(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        line_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<Option<OrderItemResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let line = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some((_, line)) =
+                        Self::lock_line(conn, tenant_id, order_id, line_id).await?
+                    else {
+                        return Ok(None);
+                    };
+                    if !line.backordered {
+                        return Ok(Some(line));
+                    }
+
+                    let line: OrderItem = diesel::update(order_items::table.find(line.id))
+                        .set(order_items::backordered.eq(false))
+                        .returning(OrderItem::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    diesel::insert_into(order_history::table)
+                        .values(&NewOrderHistory {
+                            order_id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "backorder_cleared".to_string(),
+                            previous_status: None,
+                            new_status: None,
+                            notes: Some(format!("{} no longer backordered", line.item_name)),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(line))
+                })
+            })
+            .await?;
+
+        Ok(line.map(OrderItemResponse::from))
+    }
+
+    // Order confirmations
+
+    /// Renders the customer's confirmation of a sales order in `locale`, returning the order
+    /// number alongside for the file name.
+    pub async fn confirmation_pdf(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        locale: Locale,
+    ) -> Result<Option<(String, Vec<u8>)>> {
+        let Some(order) = self.get_order_by_id(tenant_id, order_id).await? else {
+            return Ok(None);
+        };
+        Self::require_confirmable(&order)?;
+        let (customer_name, _) = self.customer(tenant_id, &order).await?;
+
+        let pdf = render_order_confirmation_pdf(&order, &customer_name, locale, &self.branding);
+        Ok(Some((order.order_number, pdf)))
+    }
+
+    /// Emails the confirmation PDF to the customer, or to the given recipients, and records
+    /// it in the order history. The email and PDF are written in the request's locale, or
+    /// `default_locale` when it names none.
+    pub async fn send_confirmation(
+        &self,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        person_id: Uuid,
+        request: SendOrderConfirmationRequest,
+        email: Option<&EmailService>,
+        default_locale: Locale,
+    ) -> Result<Option<OrderResponse>> {
+        let Some(email) = email else {
+            return Err(OrderError::EmailNotConfigured.into());
+        };
+        let Some(order) = self.get_order_by_id(tenant_id, order_id).await? else {
+            return Ok(None);
+        };
+        Self::require_confirmable(&order)?;
+        let (customer_name, customer_email) = self.customer(tenant_id, &order).await?;
+
+        let locale = request.locale.unwrap_or(default_locale);
+        let recipients = match request.recipients.filter(|r| !r.is_empty()) {
+            Some(recipients) => recipients,
+            None => vec![customer_email],
+        };
+        let attachment = EmailAttachment {
+            filename: format!("{}.pdf", order.order_number),
+            content_type: "application/pdf".to_string(),
+            content: render_order_confirmation_pdf(&order, &customer_name, locale, &self.branding),
+        };
+        let (subject, body) =
+            order_confirmation_email(&order, &customer_name, request.message.as_deref(), locale);
+
+        email
+            .send_branded(
+                &recipients,
+                &subject,
+                &body,
+                vec![attachment],
+                &self.branding,
+            )
+            .await?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        diesel::insert_into(order_history::table)
+            .values(&NewOrderHistory {
+                order_id,
+                tenant_id,
+                person_id: Some(person_id),
+                action: "confirmation_sent".to_string(),
+                previous_status: None,
+                new_status: None,
+                notes: Some(format!(
+                    "Confirmation sent to {} ({})",
+                    recipients.join(", "),
+                    locale
+                )),
+            })
+            .execute(&mut conn)
+            .await?;
+
+        Ok(Some(order))
+    }
+
+    // Private helper methods
+
+    // Only placed sales orders are confirmed to the customer
+    fn require_confirmable(order: &OrderResponse) -> Result<()> {
+        if order.order_type == OrderType::PurchaseOrder {
+            return Err(OrderError::NotConfirmable(
+                "purchase orders are not sent to customers".to_string(),
+            )
+            .into());
+        }
+        if matches!(order.status, OrderStatus::Draft | OrderStatus::Cancelled) {
+            return Err(OrderError::NotConfirmable(format!("status is {}", order.status)).into());
+        }
+        Ok(())
+    }
+
+    // Name and email of the customer or distributor an order was placed by
+    async fn customer(&self, tenant_id: Uuid, order: &OrderResponse) -> Result<(String, String)> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        person::table
+            .filter(person::id.eq(order.external_entity_id))
+            .select((person::name, person::email))
+            .first::<(String, String)>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| anyhow::anyhow!("Customer not found: {}", order.external_entity_id))
+    }
+
+    async fn lock_line(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        order_id: Uuid,
+        line_id: Uuid,
+    ) -> Result<Option<(Order, OrderItem)>> {
+        let Some(order) = orders::table
+            .filter(orders::id.eq(order_id))
+            .filter(orders::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(Order::as_select())
+            .first::<Order>(conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let line = order_items::table
+            .filter(order_items::id.eq(line_id))
+            .filter(order_items::order_id.eq(order_id))
+            .for_update()
+            .select(OrderItem::as_select())
+            .first::<OrderItem>(conn)
+            .await
+            .optional()?;
+
+        Ok(line.map(|line| (order, line)))
+    }
 }
@@ -0,0 +1,772 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateCreditNoteRequest, CreateOrderReturnRequest, DispositionReturnRequest, ItemContext,
+    ListOrderReturnsQuery, NewOrderReturn, NewOrderReturnCreditNote, NewOrderReturnDisposition,
+    NewOrderReturnLine, NumberingEntity, Order, OrderItem, OrderReturn, OrderReturnCreditNote,
+    OrderReturnDisposition, OrderReturnLine, OrderReturnLineResponse, OrderReturnResponse,
+    ReceiveReturnRequest, RecordStockMovementRequest, ReturnDisposition, ReturnStatus,
+    ShipmentStatus, StockMovementType, StockReferenceType,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, NumberingService, StockService};
+use crate::utils::returns::{return_status, returnable_quantity, ReturnLineProgress};
+use crate::utils::AppError;
+
+/// Errors from [`OrderReturnService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum OrderReturnError {
+    #[error("Order has not been shipped: {0}")]
+    NotShipped(String),
+
+    #[error("Order item not on order: {0}")]
+    ItemNotOnOrder(Uuid),
+
+    #[error("Return line not on return: {0}")]
+    LineNotOnReturn(Uuid),
+
+    #[error("{0}")]
+    QuantityExceeded(String),
+
+    #[error("Return cannot be received: {0}")]
+    NotReceivable(String),
+
+    #[error("Return cannot be dispositioned: {0}")]
+    NotDispositionable(String),
+
+    #[error("Return line cannot be restocked: {0}")]
+    NotRestockable(String),
+
+    #[error("Return cannot be credited: {0}")]
+    NotCreditable(String),
+
+    #[error("Return cannot be cancelled: {0}")]
+    NotCancellable(String),
+}
+
+impl From<OrderReturnError> for AppError {
+    fn from(error: OrderReturnError) -> Self {
+        match error {
+            OrderReturnError::ItemNotOnOrder(_) | OrderReturnError::LineNotOnReturn(_) => {
+                AppError::Validation(error.to_string())
+            }
+            OrderReturnError::NotShipped(_)
+            | OrderReturnError::QuantityExceeded(_)
+            | OrderReturnError::NotReceivable(_)
+            | OrderReturnError::NotDispositionable(_)
+            | OrderReturnError::NotRestockable(_)
+            | OrderReturnError::NotCreditable(_)
+            | OrderReturnError::NotCancellable(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// Location received stock is held at when the return does not name one
+const DEFAULT_QUARANTINE_LOCATION: &str = "QUARANTINE";
+
+pub struct ReturnService {
+    database: DatabaseService,
+}
+
+impl ReturnService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Return operations
+
+    /// Opens a return against a shipped order. Each line may return at most the ordered quantity
+    /// less what other returns for the order already cover.
+    pub async fn create_return(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateOrderReturnRequest,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let created = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    // Locking the order serializes returns raised against it
+                    let Some(order) = orders::table
+                        .filter(orders::id.eq(request.order_id))
+                        .filter(orders::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Order::as_select())
+                        .first::<Order>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let shipped: i64 =
+                        shipments::table
+                            .filter(shipments::order_id.eq(order.id))
+                            .filter(shipments::shipped_at.is_not_null().or(
+                                shipments::status.eq_any([
+                                    ShipmentStatus::InTransit.to_string(),
+                                    ShipmentStatus::Delivered.to_string(),
+                                ]),
+                            ))
+                            .count()
+                            .get_result(conn)
+                            .await?;
+                    if shipped == 0 {
+                        return Err(OrderReturnError::NotShipped(order.order_number.clone()).into());
+                    }
+
+                    let order_item_ids: Vec<Uuid> =
+                        request.lines.iter().map(|l| l.order_item_id).collect();
+                    let order_items: HashMap<Uuid, OrderItem> = order_items::table
+                        .filter(order_items::order_id.eq(order.id))
+                        .filter(order_items::id.eq_any(&order_item_ids))
+                        .select(OrderItem::as_select())
+                        .load::<OrderItem>(conn)
+                        .await?
+                        .into_iter()
+                        .map(|item| (item.id, item))
+                        .collect();
+
+                    let mut already_returned: HashMap<Uuid, i32> = HashMap::new();
+                    for (order_item_id, quantity) in order_return_lines::table
+                        .inner_join(order_returns::table)
+                        .filter(order_return_lines::order_item_id.eq_any(&order_item_ids))
+                        .filter(order_returns::status.ne(ReturnStatus::Cancelled.to_string()))
+                        .select((
+                            order_return_lines::order_item_id,
+                            order_return_lines::quantity,
+                        ))
+                        .load::<(Uuid, i32)>(conn)
+                        .await?
+                    {
+                        *already_returned.entry(order_item_id).or_default() += quantity;
+                    }
+
+                    for line in &request.lines {
+                        let Some(order_item) = order_items.get(&line.order_item_id) else {
+                            return Err(OrderReturnError::ItemNotOnOrder(line.order_item_id).into());
+                        };
+                        let returnable = returnable_quantity(
+                            order_item.quantity,
+                            already_returned.get(&order_item.id).copied().unwrap_or(0),
+                        );
+                        if line.quantity > returnable {
+                            return Err(OrderReturnError::QuantityExceeded(format!(
+                                "Return quantity exceeds ordered quantity for {}: {} returnable",
+                                order_item.item_name, returnable
+                            ))
+                            .into());
+                        }
+                    }
+
+                    // Number the return from its sequence unless given, falling back to a
+                    // random number when the tenant has none
+                    let rma_number = NumberingService::number_or_else(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::Return,
+                        request.rma_number,
+                        || {
+                            format!("RMA-{}", &Uuid::new_v4().simple().to_string()[..8])
+                                .to_uppercase()
+                        },
+                    )
+                    .await?;
+
+                    let new_return = NewOrderReturn {
+                        tenant_id,
+                        order_id: order.id,
+                        rma_number,
+                        quarantine_location: request
+                            .quarantine_location
+                            .unwrap_or_else(|| DEFAULT_QUARANTINE_LOCATION.to_string()),
+                        notes: request.notes,
+                        created_by_id,
+                    };
+
+                    let order_return: OrderReturn = diesel::insert_into(order_returns::table)
+                        .values(&new_return)
+                        .returning(OrderReturn::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let new_lines: Vec<NewOrderReturnLine> = request
+                        .lines
+                        .into_iter()
+                        .map(|line| NewOrderReturnLine {
+                            tenant_id,
+                            return_id: order_return.id,
+                            order_item_id: line.order_item_id,
+                            item_id: order_items[&line.order_item_id].item_id,
+                            quantity: line.quantity,
+                            reason: line.reason.to_string(),
+                            reason_notes: line.reason_notes,
+                        })
+                        .collect();
+
+                    diesel::insert_into(order_return_lines::table)
+                        .values(&new_lines)
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(order_return))
+                })
+            })
+            .await?;
+
+        match created {
+            Some(order_return) => Self::load_response(&mut conn, order_return).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn list_returns(
+        &self,
+        tenant_id: Uuid,
+        query: ListOrderReturnsQuery,
+    ) -> Result<Vec<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut returns_query = order_returns::table
+            .filter(order_returns::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(order_id) = query.order_id {
+            returns_query = returns_query.filter(order_returns::order_id.eq(order_id));
+        }
+
+        if let Some(status) = query.status {
+            returns_query = returns_query.filter(order_returns::status.eq(status.to_string()));
+        }
+
+        let returns = returns_query
+            .order(order_returns::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(OrderReturn::as_select())
+            .load::<OrderReturn>(&mut conn)
+            .await?;
+
+        Self::load_responses(&mut conn, returns).await
+    }
+
+    pub async fn get_return(
+        &self,
+        tenant_id: Uuid,
+        return_id: Uuid,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(order_return) = order_returns::table
+            .filter(order_returns::id.eq(return_id))
+            .filter(order_returns::tenant_id.eq(tenant_id))
+            .select(OrderReturn::as_select())
+            .first::<OrderReturn>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        Self::load_response(&mut conn, order_return).await.map(Some)
+    }
+
+    /// Books returned stock into the return's quarantine location. Received units stay out of
+    /// inventory until they are dispositioned.
+    pub async fn receive_return(
+        &self,
+        tenant_id: Uuid,
+        return_id: Uuid,
+        request: ReceiveReturnRequest,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let received = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(order_return) = Self::lock_return(conn, tenant_id, return_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let receivable = matches!(
+                        ReturnStatus::try_from(order_return.status.clone()),
+                        Ok(ReturnStatus::Requested) | Ok(ReturnStatus::PartiallyReceived)
+                    );
+                    if !receivable {
+                        return Err(OrderReturnError::NotReceivable(
+                            order_return.status.to_string(),
+                        )
+                        .into());
+                    }
+
+                    let lines = Self::find_lines(conn, order_return.id).await?;
+                    for receipt in &request.lines {
+                        let Some(line) = lines.iter().find(|l| l.id == receipt.line_id) else {
+                            return Err(OrderReturnError::LineNotOnReturn(receipt.line_id).into());
+                        };
+                        if line.received_quantity + receipt.quantity > line.quantity {
+                            return Err(OrderReturnError::QuantityExceeded(format!(
+                                "Received quantity exceeds returned quantity: {} outstanding",
+                                line.quantity - line.received_quantity
+                            ))
+                            .into());
+                        }
+
+                        diesel::update(order_return_lines::table.find(line.id))
+                            .set(
+                                order_return_lines::received_quantity
+                                    .eq(order_return_lines::received_quantity + receipt.quantity),
+                            )
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let quarantine_location = request
+                        .quarantine_location
+                        .unwrap_or(order_return.quarantine_location);
+                    diesel::update(order_returns::table.find(order_return.id))
+                        .set((
+                            order_returns::quarantine_location.eq(quarantine_location),
+                            order_returns::received_at
+                                .eq(order_return.received_at.unwrap_or_else(Utc::now)),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    Self::update_status(conn, order_return.id).await.map(Some)
+                })
+            })
+            .await?;
+
+        match received {
+            Some(order_return) => Self::load_response(&mut conn, order_return).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Decides what happens to quarantined units of a line. Restocking posts a return movement to
+    /// the item's inventory; scrapped and repaired units leave quarantine without touching stock.
+    pub async fn disposition_return(
+        &self,
+        tenant_id: Uuid,
+        person_id: Option<Uuid>,
+        return_id: Uuid,
+        request: DispositionReturnRequest,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let dispositioned = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(order_return) = Self::lock_return(conn, tenant_id, return_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let open = matches!(
+                        ReturnStatus::try_from(order_return.status.clone()),
+                        Ok(ReturnStatus::PartiallyReceived) | Ok(ReturnStatus::Received)
+                    );
+                    if !open {
+                        return Err(OrderReturnError::NotDispositionable(
+                            order_return.status.to_string(),
+                        )
+                        .into());
+                    }
+
+                    let Some(line) = Self::find_lines(conn, order_return.id)
+                        .await?
+                        .into_iter()
+                        .find(|l| l.id == request.line_id)
+                    else {
+                        return Err(OrderReturnError::LineNotOnReturn(request.line_id).into());
+                    };
+
+                    let dispositioned: i32 = order_return_dispositions::table
+                        .filter(order_return_dispositions::return_line_id.eq(line.id))
+                        .select(order_return_dispositions::quantity)
+                        .load::<i32>(conn)
+                        .await?
+                        .into_iter()
+                        .sum();
+                    let quarantined = line.received_quantity - dispositioned;
+                    if request.quantity > quarantined {
+                        return Err(OrderReturnError::QuantityExceeded(format!(
+                            "Disposition exceeds quarantined quantity: {} in quarantine",
+                            quarantined
+                        ))
+                        .into());
+                    }
+
+                    let stock_movement_id = match request.disposition {
+                        ReturnDisposition::Restock => {
+                            let Some(item_id) = line.item_id else {
+                                return Err(OrderReturnError::NotRestockable(
+                                    "no inventory item".to_string(),
+                                )
+                                .into());
+                            };
+                            let context = request.context.unwrap_or(ItemContext::FinishedGoods);
+                            // Returned quantities are in the unit the order line was in
+                            let uom_id = order_items::table
+                                .find(line.order_item_id)
+                                .select(order_items::uom_id)
+                                .first::<Option<Uuid>>(conn)
+                                .await?;
+                            let movement = StockService::apply_movement(
+                                conn,
+                                tenant_id,
+                                item_id,
+                                person_id,
+                                &RecordStockMovementRequest {
+                                    context: context.clone(),
+                                    movement_type: StockMovementType::Return,
+                                    quantity: request.quantity.into(),
+                                    reference_type: Some(StockReferenceType::Return),
+                                    reference_id: Some(order_return.id),
+                                    notes: Some(format!(
+                                        "Restocked from {}",
+                                        order_return.rma_number
+                                    )),
+                                    occurred_at: None,
+                                    allow_reserved: false,
+                                    uom_id,
+                                    owner_id: None,
+                                },
+                            )
+                            .await?;
+                            match movement {
+                                Some(movement) => Some(movement.id),
+                                None => {
+                                    return Err(OrderReturnError::NotRestockable(format!(
+                                        "no {} inventory",
+                                        context
+                                    ))
+                                    .into())
+                                }
+                            }
+                        }
+                        ReturnDisposition::Scrap | ReturnDisposition::Repair => None,
+                    };
+
+                    diesel::insert_into(order_return_dispositions::table)
+                        .values(&NewOrderReturnDisposition {
+                            tenant_id,
+                            return_line_id: line.id,
+                            disposition: request.disposition.to_string(),
+                            quantity: request.quantity,
+                            stock_movement_id,
+                            person_id,
+                            notes: request.notes,
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Self::update_status(conn, order_return.id).await.map(Some)
+                })
+            })
+            .await?;
+
+        match dispositioned {
+            Some(order_return) => Self::load_response(&mut conn, order_return).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Links a credit note issued for the return. Credit notes together may not exceed the value
+    /// of the returned lines at their order prices.
+    pub async fn add_credit_note(
+        &self,
+        tenant_id: Uuid,
+        return_id: Uuid,
+        request: CreateCreditNoteRequest,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let credited = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(order_return) = Self::lock_return(conn, tenant_id, return_id).await?
+                    else {
+                        return Ok(None);
+                    };
+
+                    if order_return.status == ReturnStatus::Cancelled.to_string() {
+                        return Err(OrderReturnError::NotCreditable(
+                            order_return.status.to_string(),
+                        )
+                        .into());
+                    }
+
+                    let return_value: f64 = order_return_lines::table
+                        .inner_join(order_items::table)
+                        .filter(order_return_lines::return_id.eq(order_return.id))
+                        .select((order_return_lines::quantity, order_items::unit_price))
+                        .load::<(i32, f64)>(conn)
+                        .await?
+                        .into_iter()
+                        .map(|(quantity, unit_price)| quantity as f64 * unit_price)
+                        .sum();
+                    let credited: f64 = order_return_credit_notes::table
+                        .filter(order_return_credit_notes::return_id.eq(order_return.id))
+                        .select(order_return_credit_notes::amount)
+                        .load::<f64>(conn)
+                        .await?
+                        .into_iter()
+                        .sum();
+
+                    // Allow for rounding in amounts entered to the cent
+                    if credited + request.amount > return_value + 0.005 {
+                        return Err(OrderReturnError::QuantityExceeded(format!(
+                            "Credit exceeds return value: {:.2} remaining",
+                            (return_value - credited).max(0.0)
+                        ))
+                        .into());
+                    }
+
+                    diesel::insert_into(order_return_credit_notes::table)
+                        .values(&NewOrderReturnCreditNote {
+                            tenant_id,
+                            return_id: order_return.id,
+                            credit_note_number: request.credit_note_number,
+                            amount: request.amount,
+                            issued_at: request.issued_at.unwrap_or_else(Utc::now),
+                            notes: request.notes,
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(order_return))
+                })
+            })
+            .await?;
+
+        match credited {
+            Some(order_return) => Self::load_response(&mut conn, order_return).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Cancels a return before any stock has come back.
+    pub async fn cancel_return(
+        &self,
+        tenant_id: Uuid,
+        return_id: Uuid,
+    ) -> Result<Option<OrderReturnResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(order_return) = order_returns::table
+            .filter(order_returns::id.eq(return_id))
+            .filter(order_returns::tenant_id.eq(tenant_id))
+            .select(OrderReturn::as_select())
+            .first::<OrderReturn>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        if order_return.status != ReturnStatus::Requested.to_string() {
+            return Err(OrderReturnError::NotCancellable(order_return.status.clone()).into());
+        }
+
+        let cancelled = diesel::update(order_returns::table.find(order_return.id))
+            .filter(order_returns::status.eq(ReturnStatus::Requested.to_string()))
+            .set(order_returns::status.eq(ReturnStatus::Cancelled.to_string()))
+            .returning(OrderReturn::as_returning())
+            .get_result::<OrderReturn>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| OrderReturnError::NotCancellable("no longer requested".to_string()))?;
+
+        Self::load_response(&mut conn, cancelled).await.map(Some)
+    }
+
+    // Private helper methods
+
+    async fn lock_return(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        return_id: Uuid,
+    ) -> Result<Option<OrderReturn>> {
+        Ok(order_returns::table
+            .filter(order_returns::id.eq(return_id))
+            .filter(order_returns::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(OrderReturn::as_select())
+            .first::<OrderReturn>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn find_lines(
+        conn: &mut AsyncPgConnection,
+        return_id: Uuid,
+    ) -> Result<Vec<OrderReturnLine>> {
+        Ok(order_return_lines::table
+            .filter(order_return_lines::return_id.eq(return_id))
+            .order(order_return_lines::created_at.asc())
+            .select(OrderReturnLine::as_select())
+            .load::<OrderReturnLine>(conn)
+            .await?)
+    }
+
+    /// Moves the return to the status its lines' progress implies, stamping `closed_at` when
+    /// the last quarantined unit is dispositioned.
+    async fn update_status(conn: &mut AsyncPgConnection, return_id: Uuid) -> Result<OrderReturn> {
+        let lines = Self::find_lines(conn, return_id).await?;
+        let line_ids: Vec<Uuid> = lines.iter().map(|l| l.id).collect();
+
+        let mut dispositioned: HashMap<Uuid, i32> = HashMap::new();
+        for (line_id, quantity) in order_return_dispositions::table
+            .filter(order_return_dispositions::return_line_id.eq_any(&line_ids))
+            .select((
+                order_return_dispositions::return_line_id,
+                order_return_dispositions::quantity,
+            ))
+            .load::<(Uuid, i32)>(conn)
+            .await?
+        {
+            *dispositioned.entry(line_id).or_default() += quantity;
+        }
+
+        let progress: Vec<ReturnLineProgress> = lines
+            .iter()
+            .map(|line| ReturnLineProgress {
+                quantity: line.quantity,
+                received: line.received_quantity,
+                dispositioned: dispositioned.get(&line.id).copied().unwrap_or(0),
+            })
+            .collect();
+        let status = return_status(&progress);
+        let closed_at = (status == ReturnStatus::Closed).then(Utc::now);
+
+        Ok(diesel::update(order_returns::table.find(return_id))
+            .set((
+                order_returns::status.eq(status.to_string()),
+                order_returns::closed_at.eq(closed_at),
+            ))
+            .returning(OrderReturn::as_returning())
+            .get_result::<OrderReturn>(conn)
+            .await?)
+    }
+
+    async fn load_response(
+        conn: &mut AsyncPgConnection,
+        order_return: OrderReturn,
+    ) -> Result<OrderReturnResponse> {
+        let mut responses = Self::load_responses(conn, vec![order_return]).await?;
+        responses
+            .pop()
+            .ok_or_else(|| anyhow!("Return disappeared while loading"))
+    }
+
+    async fn load_responses(
+        conn: &mut AsyncPgConnection,
+        returns: Vec<OrderReturn>,
+    ) -> Result<Vec<OrderReturnResponse>> {
+        let return_ids: Vec<Uuid> = returns.iter().map(|r| r.id).collect();
+
+        let lines = order_return_lines::table
+            .inner_join(order_items::table)
+            .filter(order_return_lines::return_id.eq_any(&return_ids))
+            .order(order_return_lines::created_at.asc())
+            .select((
+                OrderReturnLine::as_select(),
+                order_items::item_name,
+                order_items::unit_price,
+            ))
+            .load::<(OrderReturnLine, String, f64)>(conn)
+            .await?;
+
+        let line_ids: Vec<Uuid> = lines.iter().map(|(line, _, _)| line.id).collect();
+        let mut dispositions: HashMap<Uuid, Vec<OrderReturnDisposition>> = HashMap::new();
+        for disposition in order_return_dispositions::table
+            .filter(order_return_dispositions::return_line_id.eq_any(&line_ids))
+            .order(order_return_dispositions::created_at.asc())
+            .select(OrderReturnDisposition::as_select())
+            .load::<OrderReturnDisposition>(conn)
+            .await?
+        {
+            dispositions
+                .entry(disposition.return_line_id)
+                .or_default()
+                .push(disposition);
+        }
+
+        let mut line_responses: HashMap<Uuid, Vec<OrderReturnLineResponse>> = HashMap::new();
+        for (line, item_name, unit_price) in lines {
+            let return_id = line.return_id;
+            let line_dispositions = dispositions.remove(&line.id).unwrap_or_default();
+            line_responses
+                .entry(return_id)
+                .or_default()
+                .push(OrderReturnLineResponse::new(
+                    line,
+                    item_name,
+                    unit_price,
+                    line_dispositions,
+                ));
+        }
+
+        let mut credit_notes: HashMap<Uuid, Vec<OrderReturnCreditNote>> = HashMap::new();
+        for credit_note in order_return_credit_notes::table
+            .filter(order_return_credit_notes::return_id.eq_any(&return_ids))
+            .order(order_return_credit_notes::issued_at.asc())
+            .select(OrderReturnCreditNote::as_select())
+            .load::<OrderReturnCreditNote>(conn)
+            .await?
+        {
+            credit_notes
+                .entry(credit_note.return_id)
+                .or_default()
+                .push(credit_note);
+        }
+
+        Ok(returns
+            .into_iter()
+            .map(|order_return| {
+                let lines = line_responses.remove(&order_return.id).unwrap_or_default();
+                let notes = credit_notes.remove(&order_return.id).unwrap_or_default();
+                OrderReturnResponse::new(order_return, lines, notes)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::{env, time::Duration};
+
+use crate::models::PartLifecycle;
+
+// Part data API calls must finish within this time
+const PART_DATA_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An external source of part data that reports the lifecycle status of manufacturer parts.
+#[async_trait]
+pub trait PartDataProvider: Send + Sync {
+    /// Name stored with lifecycle checks made through this provider.
+    fn name(&self) -> &str;
+
+    /// Looks up a manufacturer part number, returning `None` when the provider does not know it.
+    async fn lifecycle(
+        &self,
+        mfr_part_number: &str,
+        manufacturer: Option<&str>,
+    ) -> Result<Option<PartLifecycle>>;
+}
+
+#[derive(Deserialize)]
+struct PartSearchResult {
+    mpn: Option<String>,
+    manufacturer: Option<String>,
+    lifecycle_status: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PartSearchResponse {
+    results: Vec<PartSearchResult>,
+}
+
+/// Part data provider reached over HTTP, for Octopart-compatible search APIs or a bridge to one.
+///
+/// Parts are looked up with `GET {base_url}/parts?mpn={mpn}&manufacturer={manufacturer}`,
+/// answered by `{"results": [{"mpn": ..., "manufacturer": ..., "lifecycle_status": ...}]}`.
+/// The result for the requested manufacturer is preferred when several parts share the MPN.
+pub struct HttpPartDataProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl HttpPartDataProvider {
+    pub const NAME: &'static str = "http";
+
+    pub fn new(base_url: &str, api_key: Option<String>) -> Result<Self> {
+        let client = Client::builder().timeout(PART_DATA_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        })
+    }
+
+    /// Configures the provider from PART_DATA_API_URL and PART_DATA_API_KEY.
+    /// Returns `Ok(None)` when PART_DATA_API_URL is not set.
+    pub fn from_env() -> Result<Option<Self>> {
+        match env::var("PART_DATA_API_URL") {
+            Ok(url) if !url.is_empty() => {
+                Ok(Some(Self::new(&url, env::var("PART_DATA_API_KEY").ok())?))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[async_trait]
+impl PartDataProvider for HttpPartDataProvider {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    async fn lifecycle(
+        &self,
+        mfr_part_number: &str,
+        manufacturer: Option<&str>,
+    ) -> Result<Option<PartLifecycle>> {
+        let mut request = self
+            .client
+            .get(format!("{}/parts", self.base_url))
+            .query(&[("mpn", mfr_part_number)]);
+        if let Some(manufacturer) = manufacturer {
+            request = request.query(&[("manufacturer", manufacturer)]);
+        }
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| anyhow!("Part data request failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Part data request failed: lookup returned {}",
+                response.status()
+            ));
+        }
+
+        let search = response
+            .json::<PartSearchResponse>()
+            .await
+            .map_err(|e| anyhow!("Part data request failed: invalid response: {}", e))?;
+
+        let same =
+            |a: Option<&str>, b: &str| a.is_some_and(|a| a.trim().eq_ignore_ascii_case(b.trim()));
+        let mut results: Vec<PartSearchResult> = search
+            .results
+            .into_iter()
+            .filter(|r| r.lifecycle_status.is_some())
+            .filter(|r| r.mpn.is_none() || same(r.mpn.as_deref(), mfr_part_number))
+            .collect();
+
+        let position = manufacturer
+            .and_then(|m| {
+                results
+                    .iter()
+                    .position(|r| same(r.manufacturer.as_deref(), m))
+            })
+            .unwrap_or(0);
+
+        if results.is_empty() {
+            return Ok(None);
+        }
+        let result = results.swap_remove(position);
+
+        Ok(Some(PartLifecycle {
+            lifecycle_status: result.lifecycle_status.unwrap_or_default(),
+            manufacturer: result.manufacturer,
+        }))
+    }
+}
@@ -1,27 +1,130 @@
 use anyhow::Result;
 use chrono::Utc;
 use diesel::prelude::*;
-use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::{
-    CreatePersonIdResponse, CreatePersonRequest, CustomerPerson, CustomerPersonData,
-    CustomerPersonResponse, DistributorPerson, DistributorPersonData, DistributorPersonResponse,
-    InternalPerson, InternalPersonData, InternalPersonResponse, NewCustomerPerson,
-    NewDistributorPerson, NewInternalPerson, NewPerson, NewTenantPerson, NewVendorPerson, Person,
-    PersonResponse, PersonRole, TenantPerson, UpdatePersonRequest, VendorPerson, VendorPersonData,
+    AccessLevel, CallerContext, CreatePersonIdResponse, CreatePersonRequest, CustomerPerson,
+    CustomerPersonData, CustomerPersonResponse, DistributorPerson, DistributorPersonData,
+    DistributorPersonResponse, ImportPersonsRequest, ImportPersonsResponse, InternalPerson,
+    InternalPersonData, InternalPersonResponse, NewCustomerPerson, NewDistributorPerson,
+    NewInternalPerson, NewMachineOperatorAssignment, NewPerson, NewTenantPerson, NewVendorPerson,
+    Permission, Person, PersonImportRow, PersonImportRowResult, PersonImportStatus, PersonResponse,
+    PersonRole, TenantPerson, UpdatePersonRequest, VendorPerson, VendorPersonData,
     VendorPersonResponse,
 };
 use crate::schema::*;
-use crate::services::DatabaseService;
+use crate::services::{DatabaseService, EmailService, EncryptionService, SkillService};
+use crate::utils::encryption::FieldCipher;
+use crate::utils::person_import::parse_person_import;
+use crate::utils::AppError;
+
+/// Errors from [`PersonService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum PersonError {
+    #[error("Invalid person import: {0}")]
+    InvalidImport(String),
+
+    #[error("Email delivery is not configured (SMTP_HOST is not set)")]
+    EmailNotConfigured,
+}
+
+impl From<PersonError> for AppError {
+    fn from(error: PersonError) -> Self {
+        match error {
+            PersonError::InvalidImport(_) => AppError::Validation(error.to_string()),
+            PersonError::EmailNotConfigured => AppError::Unavailable(error.to_string()),
+        }
+    }
+}
+
+// Seconds a looked-up access level is reused before it is read again
+const DEFAULT_ACCESS_LEVEL_CACHE_TTL_SECONDS: u64 = 60;
+const MAX_CACHED_ACCESS_LEVELS: usize = 100_000;
+
+/// Recently looked-up access levels by tenant and person, shared by every request so
+/// authenticating a request does not query the database each time. Changes made through
+/// [`PersonService`] and the admin reset invalidate their entry, other changes show up once the
+/// TTL passes.
+#[derive(Clone)]
+pub struct AccessLevelCache {
+    ttl: Duration,
+    entries: Arc<RwLock<AccessLevelEntries>>,
+}
+
+// When each (tenant, person) access level was looked up, and what it was
+type AccessLevelEntries = HashMap<(Uuid, Uuid), (Instant, AccessLevel)>;
+
+impl AccessLevelCache {
+    /// A TTL of zero disables caching.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Reads ACCESS_LEVEL_CACHE_TTL_SECONDS (default 60; 0 disables the cache).
+    pub fn from_env() -> Self {
+        let ttl = env::var("ACCESS_LEVEL_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_ACCESS_LEVEL_CACHE_TTL_SECONDS);
+        Self::new(Duration::from_secs(ttl))
+    }
+
+    pub fn get(&self, tenant_id: Uuid, person_id: Uuid) -> Option<AccessLevel> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(&(tenant_id, person_id))
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, access_level)| *access_level)
+    }
+
+    pub fn insert(&self, tenant_id: Uuid, person_id: Uuid, access_level: AccessLevel) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.write().unwrap();
+        // Drop expired entries once the cache grows large so former callers do not pile up
+        if entries.len() >= MAX_CACHED_ACCESS_LEVELS {
+            let ttl = self.ttl;
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+        }
+        entries.insert((tenant_id, person_id), (Instant::now(), access_level));
+    }
+
+    pub fn invalidate(&self, tenant_id: Uuid, person_id: Uuid) {
+        self.entries
+            .write()
+            .unwrap()
+            .remove(&(tenant_id, person_id));
+    }
+}
 
 pub struct PersonService {
     database: DatabaseService,
+    cache: Option<AccessLevelCache>,
 }
 
 impl PersonService {
     pub fn new(database: DatabaseService) -> Self {
-        Self { database }
+        Self {
+            database,
+            cache: None,
+        }
+    }
+
+    /// Reads and invalidates access levels through the shared cache.
+    pub fn with_cache(mut self, cache: AccessLevelCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
     // General Person API methods
@@ -31,6 +134,7 @@ impl PersonService {
         tenant_id: Uuid,
         request: CreatePersonRequest,
     ) -> Result<CreatePersonIdResponse> {
+        let phone = self.phone_cipher().await?.seal_opt(request.phone.clone())?;
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
@@ -45,7 +149,7 @@ impl PersonService {
                         supabase_uid: Uuid::new_v4(), // Generate temporary UID
                         name: request.name.clone(),
                         email: request.email.clone(),
-                        phone: request.phone.clone(),
+                        phone,
                         global_access: request
                             .global_access
                             .map(|ga| ga.into_iter().map(Some).collect()),
@@ -138,8 +242,7 @@ impl PersonService {
                     Ok(person.id)
                 })
             })
-            .await
-            .map_err(|e| anyhow::anyhow!("Transaction failed: {}", e))?;
+            .await?;
 
         Ok(CreatePersonIdResponse { id: person_id })
     }
@@ -166,6 +269,7 @@ impl PersonService {
             .optional()?;
 
         if let Some((person, tenant_person)) = result {
+            let person = person.open_phone(&self.phone_cipher().await?)?;
             let role = PersonRole::try_from(tenant_person.role)
                 .map_err(|e| anyhow::anyhow!("Invalid role: {}", e))?;
 
@@ -278,9 +382,15 @@ impl PersonService {
     pub async fn update_person(
         &self,
         tenant_id: Uuid,
+        caller: &CallerContext,
         person_id: Uuid,
         request: UpdatePersonRequest,
     ) -> Result<PersonResponse> {
+        if request.role.is_some() || request.global_access.is_some() {
+            caller.require(Permission::ChangeRoles)?;
+        }
+        let phone = self.phone_cipher().await?.seal_opt(request.phone.clone())?;
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
@@ -296,7 +406,7 @@ impl PersonService {
                         .execute(conn)
                         .await?;
                 }
-                if let Some(phone) = &request.phone {
+                if let Some(phone) = &phone {
                     diesel::update(person::table.filter(person::id.eq(person_id)))
                         .set(person::phone.eq(phone))
                         .execute(conn)
@@ -504,7 +614,14 @@ impl PersonService {
             .ok_or_else(|| anyhow::anyhow!("Person not found after update"))
     }
 
-    pub async fn delete_person(&self, tenant_id: Uuid, person_id: Uuid) -> Result<()> {
+    pub async fn delete_person(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        person_id: Uuid,
+    ) -> Result<()> {
+        caller.require(Permission::PurgePerson)?;
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
@@ -520,9 +637,45 @@ impl PersonService {
         .execute(&mut conn)
         .await?;
 
+        if let Some(cache) = &self.cache {
+            cache.invalidate(tenant_id, person_id);
+        }
         Ok(())
     }
 
+    /// The person's access level in the tenant; standard when they are not a member.
+    pub async fn get_access_level(&self, tenant_id: Uuid, person_id: Uuid) -> Result<AccessLevel> {
+        if let Some(access_level) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(tenant_id, person_id))
+        {
+            return Ok(access_level);
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let levels = tenant_person::table
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .filter(tenant_person::person_id.eq(person_id))
+            .select(tenant_person::access_level)
+            .first::<Option<Vec<Option<String>>>>(&mut conn)
+            .await
+            .optional()?
+            .flatten()
+            .unwrap_or_default();
+
+        let access_level = AccessLevel::highest(&levels);
+        if let Some(cache) = &self.cache {
+            cache.insert(tenant_id, person_id, access_level);
+        }
+        Ok(access_level)
+    }
+
     pub async fn list_persons(
         &self,
         tenant_id: Uuid,
@@ -559,8 +712,10 @@ impl PersonService {
             .load::<(Person, TenantPerson)>(&mut conn)
             .await?;
 
+        let cipher = self.phone_cipher().await?;
         let mut persons = Vec::new();
         for (person, tenant_person) in results {
+            let person = person.open_phone(&cipher)?;
             let role = PersonRole::try_from(tenant_person.role)
                 .map_err(|e| anyhow::anyhow!("Invalid role: {}", e))?;
 
@@ -702,21 +857,25 @@ impl PersonService {
             .load::<(InternalPerson, Person)>(&mut conn)
             .await?;
 
+        let cipher = self.phone_cipher().await?;
         let internal_persons = results
             .into_iter()
-            .map(|(internal, person)| InternalPersonResponse {
-                id: person.id,
-                name: person.name,
-                email: person.email,
-                phone: person.phone,
-                department: internal.department,
-                position: internal.position,
-                employee_id: internal.employee_id,
-                hire_date: internal.hire_date,
-                created_at: person.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+            .map(|(internal, person)| {
+                let person = person.open_phone(&cipher)?;
+                Ok(InternalPersonResponse {
+                    id: person.id,
+                    name: person.name,
+                    email: person.email,
+                    phone: person.phone,
+                    department: internal.department,
+                    position: internal.position,
+                    employee_id: internal.employee_id,
+                    hire_date: internal.hire_date,
+                    created_at: person.created_at.unwrap_or_else(|| Utc::now()),
+                    updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         Ok(internal_persons)
     }
@@ -742,6 +901,7 @@ impl PersonService {
             .optional()?;
 
         if let Some((internal, person)) = result {
+            let person = person.open_phone(&self.phone_cipher().await?)?;
             Ok(Some(InternalPersonResponse {
                 id: person.id,
                 name: person.name,
@@ -765,60 +925,60 @@ impl PersonService {
         person_id: Uuid,
         request: UpdatePersonRequest,
     ) -> Result<()> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        // Check if there are any internal-specific fields to update
-        let has_internal_updates = request.department.is_some()
-            || request.position.is_some()
-            || request.employee_id.is_some()
-            || request.hire_date.is_some();
-
-        if has_internal_updates {
-            // Build the changeset using conditional values
-            let changeset = (
-                request
-                    .department
-                    .map(|d| internal_person::department.eq(d)),
-                request.position.map(|p| internal_person::position.eq(p)),
-                request
-                    .employee_id
-                    .map(|e| internal_person::employee_id.eq(e)),
-                request.hire_date.map(|h| internal_person::hire_date.eq(h)),
-            );
-
-            // Apply only the Some values
-            let target = internal_person::table
-                .filter(internal_person::person_id.eq(person_id))
-                .filter(internal_person::tenant_id.eq(tenant_id));
-
-            if let Some(dept) = changeset.0 {
-                diesel::update(target.clone())
-                    .set(dept)
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(pos) = changeset.1 {
-                diesel::update(target.clone())
-                    .set(pos)
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(emp_id) = changeset.2 {
-                diesel::update(target.clone())
-                    .set(emp_id)
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(hire) = changeset.3 {
-                diesel::update(target).set(hire).execute(&mut conn).await?;
-            }
-        }
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Check if there are any internal-specific fields to update
+                    let has_internal_updates = request.department.is_some()
+                        || request.position.is_some()
+                        || request.employee_id.is_some()
+                        || request.hire_date.is_some();
+
+                    if has_internal_updates {
+                        // Build the changeset using conditional values
+                        let changeset = (
+                            request
+                                .department
+                                .map(|d| internal_person::department.eq(d)),
+                            request.position.map(|p| internal_person::position.eq(p)),
+                            request
+                                .employee_id
+                                .map(|e| internal_person::employee_id.eq(e)),
+                            request.hire_date.map(|h| internal_person::hire_date.eq(h)),
+                        );
+
+                        // Apply only the Some values
+                        let target = internal_person::table
+                            .filter(internal_person::person_id.eq(person_id))
+                            .filter(internal_person::tenant_id.eq(tenant_id));
+
+                        if let Some(dept) = changeset.0 {
+                            diesel::update(target.clone())
+                                .set(dept)
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(pos) = changeset.1 {
+                            diesel::update(target.clone())
+                                .set(pos)
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(emp_id) = changeset.2 {
+                            diesel::update(target.clone())
+                                .set(emp_id)
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(hire) = changeset.3 {
+                            diesel::update(target).set(hire).execute(conn).await?;
+                        }
+                    }
 
-        Ok(())
+                    Ok(())
+                })
+            })
+            .await
     }
 
     // Customer Person API methods
@@ -853,21 +1013,25 @@ impl PersonService {
             .load::<(CustomerPerson, Person)>(&mut conn)
             .await?;
 
+        let cipher = self.phone_cipher().await?;
         let customer_persons = results
             .into_iter()
-            .map(|(customer, person)| CustomerPersonResponse {
-                id: person.id,
-                name: person.name,
-                email: person.email,
-                phone: person.phone,
-                company: customer.company,
-                industry: customer.industry,
-                customer_since: customer.customer_since,
-                account_manager_id: customer.account_manager_id,
-                created_at: person.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+            .map(|(customer, person)| {
+                let person = person.open_phone(&cipher)?;
+                Ok(CustomerPersonResponse {
+                    id: person.id,
+                    name: person.name,
+                    email: person.email,
+                    phone: person.phone,
+                    company: customer.company,
+                    industry: customer.industry,
+                    customer_since: customer.customer_since,
+                    account_manager_id: customer.account_manager_id,
+                    created_at: person.created_at.unwrap_or_else(|| Utc::now()),
+                    updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         Ok(customer_persons)
     }
@@ -893,6 +1057,7 @@ impl PersonService {
             .optional()?;
 
         if let Some((customer, person)) = result {
+            let person = person.open_phone(&self.phone_cipher().await?)?;
             Ok(Some(CustomerPersonResponse {
                 id: person.id,
                 name: person.name,
@@ -916,50 +1081,50 @@ impl PersonService {
         person_id: Uuid,
         request: UpdatePersonRequest,
     ) -> Result<()> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        // Check if there are any customer-specific fields to update
-        let has_customer_updates = request.company.is_some()
-            || request.industry.is_some()
-            || request.customer_since.is_some()
-            || request.account_manager_id.is_some();
-
-        if has_customer_updates {
-            let target = customer_person::table
-                .filter(customer_person::person_id.eq(person_id))
-                .filter(customer_person::tenant_id.eq(tenant_id));
-
-            if let Some(company) = request.company {
-                diesel::update(target.clone())
-                    .set(customer_person::company.eq(company))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(industry) = request.industry {
-                diesel::update(target.clone())
-                    .set(customer_person::industry.eq(industry))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(customer_since) = request.customer_since {
-                diesel::update(target.clone())
-                    .set(customer_person::customer_since.eq(customer_since))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(account_manager_id) = request.account_manager_id {
-                diesel::update(target)
-                    .set(customer_person::account_manager_id.eq(account_manager_id))
-                    .execute(&mut conn)
-                    .await?;
-            }
-        }
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Check if there are any customer-specific fields to update
+                    let has_customer_updates = request.company.is_some()
+                        || request.industry.is_some()
+                        || request.customer_since.is_some()
+                        || request.account_manager_id.is_some();
+
+                    if has_customer_updates {
+                        let target = customer_person::table
+                            .filter(customer_person::person_id.eq(person_id))
+                            .filter(customer_person::tenant_id.eq(tenant_id));
+
+                        if let Some(company) = request.company {
+                            diesel::update(target.clone())
+                                .set(customer_person::company.eq(company))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(industry) = request.industry {
+                            diesel::update(target.clone())
+                                .set(customer_person::industry.eq(industry))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(customer_since) = request.customer_since {
+                            diesel::update(target.clone())
+                                .set(customer_person::customer_since.eq(customer_since))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(account_manager_id) = request.account_manager_id {
+                            diesel::update(target)
+                                .set(customer_person::account_manager_id.eq(account_manager_id))
+                                .execute(conn)
+                                .await?;
+                        }
+                    }
 
-        Ok(())
+                    Ok(())
+                })
+            })
+            .await
     }
 
     // Vendor Person API methods
@@ -994,21 +1159,25 @@ impl PersonService {
             .load::<(VendorPerson, Person)>(&mut conn)
             .await?;
 
+        let cipher = self.phone_cipher().await?;
         let vendor_persons = results
             .into_iter()
-            .map(|(vendor, person)| VendorPersonResponse {
-                id: person.id,
-                name: person.name,
-                email: person.email,
-                phone: person.phone,
-                company: vendor.company,
-                service_type: vendor.service_type,
-                contract_start: vendor.contract_start,
-                contract_end: vendor.contract_end,
-                created_at: person.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+            .map(|(vendor, person)| {
+                let person = person.open_phone(&cipher)?;
+                Ok(VendorPersonResponse {
+                    id: person.id,
+                    name: person.name,
+                    email: person.email,
+                    phone: person.phone,
+                    company: vendor.company,
+                    service_type: vendor.service_type,
+                    contract_start: vendor.contract_start,
+                    contract_end: vendor.contract_end,
+                    created_at: person.created_at.unwrap_or_else(|| Utc::now()),
+                    updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         Ok(vendor_persons)
     }
@@ -1034,6 +1203,7 @@ impl PersonService {
             .optional()?;
 
         if let Some((vendor, person)) = result {
+            let person = person.open_phone(&self.phone_cipher().await?)?;
             Ok(Some(VendorPersonResponse {
                 id: person.id,
                 name: person.name,
@@ -1057,50 +1227,50 @@ impl PersonService {
         person_id: Uuid,
         request: UpdatePersonRequest,
     ) -> Result<()> {
-        let mut conn = self.database.get_connection().await?;
-
-        // Set tenant context for RLS
-        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
-            .await?;
-
-        // Check if there are any vendor-specific fields to update
-        let has_vendor_updates = request.company.is_some()
-            || request.service_type.is_some()
-            || request.contract_start.is_some()
-            || request.contract_end.is_some();
-
-        if has_vendor_updates {
-            let target = vendor_person::table
-                .filter(vendor_person::person_id.eq(person_id))
-                .filter(vendor_person::tenant_id.eq(tenant_id));
-
-            if let Some(company) = request.company {
-                diesel::update(target.clone())
-                    .set(vendor_person::company.eq(company))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(service_type) = request.service_type {
-                diesel::update(target.clone())
-                    .set(vendor_person::service_type.eq(service_type))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(contract_start) = request.contract_start {
-                diesel::update(target.clone())
-                    .set(vendor_person::contract_start.eq(contract_start))
-                    .execute(&mut conn)
-                    .await?;
-            }
-            if let Some(contract_end) = request.contract_end {
-                diesel::update(target)
-                    .set(vendor_person::contract_end.eq(contract_end))
-                    .execute(&mut conn)
-                    .await?;
-            }
-        }
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Check if there are any vendor-specific fields to update
+                    let has_vendor_updates = request.company.is_some()
+                        || request.service_type.is_some()
+                        || request.contract_start.is_some()
+                        || request.contract_end.is_some();
+
+                    if has_vendor_updates {
+                        let target = vendor_person::table
+                            .filter(vendor_person::person_id.eq(person_id))
+                            .filter(vendor_person::tenant_id.eq(tenant_id));
+
+                        if let Some(company) = request.company {
+                            diesel::update(target.clone())
+                                .set(vendor_person::company.eq(company))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(service_type) = request.service_type {
+                            diesel::update(target.clone())
+                                .set(vendor_person::service_type.eq(service_type))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(contract_start) = request.contract_start {
+                            diesel::update(target.clone())
+                                .set(vendor_person::contract_start.eq(contract_start))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(contract_end) = request.contract_end {
+                            diesel::update(target)
+                                .set(vendor_person::contract_end.eq(contract_end))
+                                .execute(conn)
+                                .await?;
+                        }
+                    }
 
-        Ok(())
+                    Ok(())
+                })
+            })
+            .await
     }
 
     // Distributor Person API methods
@@ -1135,21 +1305,25 @@ impl PersonService {
             .load::<(DistributorPerson, Person)>(&mut conn)
             .await?;
 
+        let cipher = self.phone_cipher().await?;
         let distributor_persons = results
             .into_iter()
-            .map(|(distributor, person)| DistributorPersonResponse {
-                id: person.id,
-                name: person.name,
-                email: person.email,
-                phone: person.phone,
-                company: distributor.company,
-                territory: distributor.territory,
-                distribution_tier: distributor.distribution_tier,
-                commission_rate: distributor.commission_rate,
-                created_at: person.created_at.unwrap_or_else(|| Utc::now()),
-                updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+            .map(|(distributor, person)| {
+                let person = person.open_phone(&cipher)?;
+                Ok(DistributorPersonResponse {
+                    id: person.id,
+                    name: person.name,
+                    email: person.email,
+                    phone: person.phone,
+                    company: distributor.company,
+                    territory: distributor.territory,
+                    distribution_tier: distributor.distribution_tier,
+                    commission_rate: distributor.commission_rate,
+                    created_at: person.created_at.unwrap_or_else(|| Utc::now()),
+                    updated_at: person.updated_at.unwrap_or_else(|| Utc::now()),
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         Ok(distributor_persons)
     }
@@ -1175,6 +1349,7 @@ impl PersonService {
             .optional()?;
 
         if let Some((distributor, person)) = result {
+            let person = person.open_phone(&self.phone_cipher().await?)?;
             Ok(Some(DistributorPersonResponse {
                 id: person.id,
                 name: person.name,
@@ -1198,49 +1373,413 @@ impl PersonService {
         person_id: Uuid,
         request: UpdatePersonRequest,
     ) -> Result<()> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Check if there are any distributor-specific fields to update
+                    let has_distributor_updates = request.company.is_some()
+                        || request.territory.is_some()
+                        || request.distribution_tier.is_some()
+                        || request.commission_rate.is_some();
+
+                    if has_distributor_updates {
+                        let target = distributor_person::table
+                            .filter(distributor_person::person_id.eq(person_id))
+                            .filter(distributor_person::tenant_id.eq(tenant_id));
+
+                        if let Some(company) = request.company {
+                            diesel::update(target.clone())
+                                .set(distributor_person::company.eq(company))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(territory) = request.territory {
+                            diesel::update(target.clone())
+                                .set(distributor_person::territory.eq(territory))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(distribution_tier) = request.distribution_tier {
+                            diesel::update(target.clone())
+                                .set(distributor_person::distribution_tier.eq(distribution_tier))
+                                .execute(conn)
+                                .await?;
+                        }
+                        if let Some(commission_rate) = request.commission_rate {
+                            diesel::update(target)
+                                .set(distributor_person::commission_rate.eq(commission_rate))
+                                .execute(conn)
+                                .await?;
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .await
+    }
+
+    // Bulk import
+
+    /// Imports persons from CSV with optional machine operator assignments. Every row and
+    /// assignment is checked first and nothing is saved unless all of them pass; the import
+    /// then runs in one transaction. Invitations are emailed after it commits.
+    pub async fn import_persons(
+        &self,
+        tenant_id: Uuid,
+        request: ImportPersonsRequest,
+        email: Option<&EmailService>,
+    ) -> Result<ImportPersonsResponse> {
+        let (rows, mut failed) =
+            parse_person_import(&request.csv).map_err(PersonError::InvalidImport)?;
+
+        let dry_run = request.dry_run.unwrap_or(false);
+        let send_invites = request.send_invites.unwrap_or(false);
+        if send_invites && email.is_none() {
+            return Err(PersonError::EmailNotConfigured.into());
+        }
+
+        let assignments = request.assignments.unwrap_or_default();
+        let row_emails: HashSet<&str> = rows.iter().map(|r| r.email.as_str()).collect();
+        let failed_emails: HashSet<String> =
+            failed.iter().filter_map(|r| r.email.clone()).collect();
+        if let Some(unknown) = assignments.iter().find(|a| {
+            let email = a.email.to_lowercase();
+            !row_emails.contains(email.as_str()) && !failed_emails.contains(&email)
+        }) {
+            return Err(PersonError::InvalidImport(format!(
+                "assignment for {} has no matching row",
+                unknown.email
+            ))
+            .into());
+        }
+
         let mut conn = self.database.get_connection().await?;
 
         // Set tenant context for RLS
         conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
             .await?;
 
-        // Check if there are any distributor-specific fields to update
-        let has_distributor_updates = request.company.is_some()
-            || request.territory.is_some()
-            || request.distribution_tier.is_some()
-            || request.commission_rate.is_some();
-
-        if has_distributor_updates {
-            let target = distributor_person::table
-                .filter(distributor_person::person_id.eq(person_id))
-                .filter(distributor_person::tenant_id.eq(tenant_id));
-
-            if let Some(company) = request.company {
-                diesel::update(target.clone())
-                    .set(distributor_person::company.eq(company))
-                    .execute(&mut conn)
+        let emails: Vec<String> = rows.iter().map(|r| r.email.clone()).collect();
+        let existing: HashMap<String, Uuid> = person::table
+            .filter(person::email.eq_any(&emails))
+            .select((person::email, person::id))
+            .load::<(String, Uuid)>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+        let members: HashSet<Uuid> = tenant_person::table
+            .filter(tenant_person::tenant_id.eq(tenant_id))
+            .filter(tenant_person::person_id.eq_any(existing.values().copied().collect::<Vec<_>>()))
+            .select(tenant_person::person_id)
+            .load::<Uuid>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let machine_ids: Vec<Uuid> = assignments.iter().map(|a| a.machine_id).collect();
+        let machine_names: HashMap<Uuid, String> = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(&machine_ids))
+            .select((machines::id, machines::name))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+        drop(conn);
+
+        // New persons have no certifications, so they are checked as an unknown person
+        let person_key =
+            |row: &PersonImportRow| existing.get(&row.email).copied().unwrap_or_else(Uuid::nil);
+        let pairs: Vec<(Uuid, Uuid)> = rows
+            .iter()
+            .flat_map(|row| {
+                assignments
+                    .iter()
+                    .filter(|a| a.email.eq_ignore_ascii_case(&row.email))
+                    .map(|a| (a.machine_id, person_key(row)))
+            })
+            .collect();
+        let gaps = SkillService::new(self.database.clone())
+            .operator_gaps(tenant_id, &pairs)
+            .await?;
+
+        let mut results = Vec::new();
+        for row in &rows {
+            let mut errors = Vec::new();
+            let mut machines_assigned = 0;
+            for assignment in assignments
+                .iter()
+                .filter(|a| a.email.eq_ignore_ascii_case(&row.email))
+            {
+                let Some(machine_name) = machine_names.get(&assignment.machine_id) else {
+                    errors.push(format!("Machine not found: {}", assignment.machine_id));
+                    continue;
+                };
+                let blocking: Vec<String> = gaps
+                    .get(&(assignment.machine_id, person_key(row)))
+                    .map(Vec::as_slice)
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|gap| gap.is_blocking())
+                    .map(|gap| format!("{} ({})", gap.skill_name, gap.reason))
+                    .collect();
+                if !blocking.is_empty() {
+                    errors.push(format!(
+                        "Operator not certified for {}: {}",
+                        machine_name,
+                        blocking.join(", ")
+                    ));
+                    continue;
+                }
+                machines_assigned += 1;
+            }
+
+            if !errors.is_empty() {
+                failed.push(PersonImportRowResult::failed(
+                    row.line,
+                    Some(row.email.clone()),
+                    errors,
+                ));
+                continue;
+            }
+
+            let person_id = existing.get(&row.email).copied();
+            let status = match person_id {
+                None => PersonImportStatus::Created,
+                Some(id) if members.contains(&id) => PersonImportStatus::Existing,
+                Some(_) => PersonImportStatus::Linked,
+            };
+            results.push(PersonImportRowResult {
+                line: row.line,
+                email: Some(row.email.clone()),
+                status,
+                person_id,
+                machines_assigned,
+                invited: false,
+                invite_error: None,
+                errors: Vec::new(),
+            });
+        }
+
+        let imported = failed.is_empty() && !dry_run;
+        if imported {
+            let cipher = self.phone_cipher().await?;
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let (rows, assignments, cipher) = (&rows, &assignments, &cipher);
+            results = conn
+                .transaction::<_, anyhow::Error, _>(|conn| {
+                    Box::pin(async move {
+                        for (row, result) in rows.iter().zip(results.iter_mut()) {
+                            let person_id =
+                                Self::import_row(conn, tenant_id, cipher, row, result).await?;
+                            result.person_id = Some(person_id);
+
+                            result.machines_assigned = 0;
+                            for assignment in assignments
+                                .iter()
+                                .filter(|a| a.email.eq_ignore_ascii_case(&row.email))
+                            {
+                                result.machines_assigned +=
+                                    diesel::insert_into(machine_operator_assignments::table)
+                                        .values(&NewMachineOperatorAssignment {
+                                            machine_id: assignment.machine_id,
+                                            person_id,
+                                            assignment_type: assignment.assignment_type.to_string(),
+                                            notes: assignment.notes.clone(),
+                                        })
+                                        .on_conflict_do_nothing()
+                                        .execute(conn)
+                                        .await?;
+                            }
+                        }
+                        Ok(results)
+                    })
+                })
+                .await?;
+
+            if let (true, Some(email)) = (send_invites, email) {
+                self.send_invites(tenant_id, rows, &mut results, email)
+                    .await?;
+            }
+        }
+
+        results.extend(failed);
+        results.sort_by_key(|r| r.line);
+
+        let count =
+            |status: PersonImportStatus| results.iter().filter(|r| r.status == status).count();
+        Ok(ImportPersonsResponse {
+            dry_run,
+            imported,
+            total_rows: results.len(),
+            created: count(PersonImportStatus::Created),
+            linked: count(PersonImportStatus::Linked),
+            existing: count(PersonImportStatus::Existing),
+            failed: count(PersonImportStatus::Failed),
+            invited: results.iter().filter(|r| r.invited).count(),
+            rows: results,
+        })
+    }
+
+    // Phone numbers are encrypted with the platform key, since persons are shared by tenants
+    async fn phone_cipher(&self) -> Result<FieldCipher> {
+        EncryptionService::new(self.database.clone())
+            .cipher(None)
+            .await
+    }
+
+    // Creates or links the person of one import row, returning its id
+    async fn import_row(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        cipher: &FieldCipher,
+        row: &PersonImportRow,
+        result: &PersonImportRowResult,
+    ) -> Result<Uuid> {
+        let person_id = match (result.status, result.person_id) {
+            (PersonImportStatus::Existing, Some(person_id)) => return Ok(person_id),
+            (PersonImportStatus::Linked, Some(person_id)) => person_id,
+            _ => {
+                let new_person = NewPerson {
+                    supabase_uid: Uuid::new_v4(), // Generate temporary UID
+                    name: row.name.clone(),
+                    email: row.email.clone(),
+                    phone: cipher.seal_opt(row.phone.clone())?,
+                    global_access: None,
+                    is_active: Some(true),
+                };
+                diesel::insert_into(person::table)
+                    .values(&new_person)
+                    .returning(person::id)
+                    .get_result::<Uuid>(conn)
+                    .await?
+            }
+        };
+
+        diesel::insert_into(tenant_person::table)
+            .values(&NewTenantPerson {
+                person_id,
+                tenant_id,
+                role: row.role.to_string(),
+                access_level: Some(vec![Some("standard".to_string())]),
+                is_primary: Some(result.status == PersonImportStatus::Created),
+            })
+            .execute(conn)
+            .await?;
+
+        match row.role {
+            PersonRole::Pending => {}
+            PersonRole::Internal => {
+                diesel::insert_into(internal_person::table)
+                    .values(&NewInternalPerson {
+                        person_id,
+                        tenant_id,
+                        department: row.department.clone(),
+                        position: row.position.clone(),
+                        employee_id: row.employee_id.clone(),
+                        hire_date: None,
+                    })
+                    .execute(conn)
                     .await?;
             }
-            if let Some(territory) = request.territory {
-                diesel::update(target.clone())
-                    .set(distributor_person::territory.eq(territory))
-                    .execute(&mut conn)
+            PersonRole::Customer => {
+                diesel::insert_into(customer_person::table)
+                    .values(&NewCustomerPerson {
+                        person_id,
+                        tenant_id,
+                        company: None,
+                        industry: None,
+                        customer_since: None,
+                        account_manager_id: None,
+                    })
+                    .execute(conn)
                     .await?;
             }
-            if let Some(distribution_tier) = request.distribution_tier {
-                diesel::update(target.clone())
-                    .set(distributor_person::distribution_tier.eq(distribution_tier))
-                    .execute(&mut conn)
+            PersonRole::Vendor => {
+                diesel::insert_into(vendor_person::table)
+                    .values(&NewVendorPerson {
+                        person_id,
+                        tenant_id,
+                        company: None,
+                        service_type: None,
+                        contract_start: None,
+                        contract_end: None,
+                    })
+                    .execute(conn)
                     .await?;
             }
-            if let Some(commission_rate) = request.commission_rate {
-                diesel::update(target)
-                    .set(distributor_person::commission_rate.eq(commission_rate))
-                    .execute(&mut conn)
+            PersonRole::Distributor => {
+                diesel::insert_into(distributor_person::table)
+                    .values(&NewDistributorPerson {
+                        person_id,
+                        tenant_id,
+                        company: None,
+                        territory: None,
+                        distribution_tier: None,
+                        commission_rate: None,
+                    })
+                    .execute(conn)
                     .await?;
             }
         }
 
+        Ok(person_id)
+    }
+
+    // Emails created and linked persons; a failed invitation is reported on its row only
+    async fn send_invites(
+        &self,
+        tenant_id: Uuid,
+        rows: &[PersonImportRow],
+        results: &mut [PersonImportRowResult],
+        email: &EmailService,
+    ) -> Result<()> {
+        let mut conn = self.database.get_connection().await?;
+        let tenant_name = tenants::table
+            .find(tenant_id)
+            .select(tenants::name)
+            .first::<String>(&mut conn)
+            .await?;
+        drop(conn);
+
+        // INVITE_URL is where invited persons sign in, e.g. the frontend login page
+        let sign_in = match env::var("INVITE_URL") {
+            Ok(url) if !url.is_empty() => format!(" at {}", url),
+            _ => String::new(),
+        };
+
+        for (row, result) in rows.iter().zip(results.iter_mut()) {
+            if result.status == PersonImportStatus::Existing {
+                continue;
+            }
+
+            let body = format!(
+                "Hello {},\n\nYou have been added to {} on EMS. Sign in with {}{} to get started.\n",
+                row.name, tenant_name, row.email, sign_in
+            );
+            match email
+                .send(
+                    std::slice::from_ref(&row.email),
+                    &format!("You're invited to {}", tenant_name),
+                    &body,
+                    Vec::new(),
+                )
+                .await
+            {
+                Ok(()) => result.invited = true,
+                Err(e) => {
+                    tracing::warn!("Invitation to {} failed: {}", row.email, e);
+                    result.invite_error = Some(e.to_string());
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,316 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    InventoryItem, Item, ItemContext, ItemPriceHistory, ItemPriceHistoryResponse,
+    NewItemPriceHistory, PriceHistoryQuery, PriceListImportResponse, PriceListRow,
+    PriceListRowIssue,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::price_list::{parse_price_list, price_breaks};
+use crate::utils::AppError;
+
+/// Errors from [`PricingService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum PricingError {
+    #[error("Invalid price list: {0}")]
+    InvalidPriceList(String),
+}
+
+impl From<PricingError> for AppError {
+    fn from(error: PricingError) -> Self {
+        match error {
+            PricingError::InvalidPriceList(_) => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+const DEFAULT_HISTORY_LIMIT: i64 = 100;
+
+pub struct PricingService {
+    database: DatabaseService,
+}
+
+impl PricingService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Price list operations
+
+    /// Imports a vendor price list, updating the pricing and lead time of every vendor inventory
+    /// record whose item matches a row by manufacturer part number. Fails with "Invalid price
+    /// list" when the file itself cannot be read; row problems are reported as issues.
+    pub async fn import_price_list(
+        &self,
+        tenant_id: Uuid,
+        vendor_id: Option<Uuid>,
+        recorded_by_id: Option<Uuid>,
+        csv: &str,
+        dry_run: bool,
+    ) -> Result<PriceListImportResponse> {
+        let (rows, mut issues) = parse_price_list(csv).map_err(PricingError::InvalidPriceList)?;
+        let total_rows = rows.len() + issues.len();
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let (matched, updated, unchanged, row_issues) = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let candidates = Self::load_vendor_records(conn, tenant_id, vendor_id).await?;
+
+                    let mut seen = HashSet::new();
+                    let mut issues = Vec::new();
+                    let (mut matched, mut updated, mut unchanged) = (0, 0, 0);
+
+                    for row in rows {
+                        let key = normalize(&row.mfr_part_number);
+                        if !seen.insert(key.clone()) {
+                            issues.push(row_issue(&row, "Duplicate MPN in price list"));
+                            continue;
+                        }
+
+                        let records: Vec<&(Item, InventoryItem)> = candidates
+                            .get(&key)
+                            .into_iter()
+                            .flatten()
+                            .filter(|(item, _)| {
+                                row.manufacturer
+                                    .as_deref()
+                                    .is_none_or(|m| normalize(m) == normalize(&item.manufacturer))
+                            })
+                            .collect();
+
+                        if records.is_empty() {
+                            issues.push(row_issue(&row, "No vendor item with this MPN"));
+                            continue;
+                        }
+                        matched += 1;
+
+                        let mut changed = false;
+                        for (_, inventory) in records {
+                            if !price_changed(inventory, &row) {
+                                continue;
+                            }
+                            changed = true;
+
+                            if !dry_run {
+                                Self::apply_row(
+                                    conn,
+                                    tenant_id,
+                                    vendor_id,
+                                    recorded_by_id,
+                                    inventory,
+                                    &row,
+                                    "price_list",
+                                )
+                                .await?;
+                            }
+                        }
+
+                        if changed {
+                            updated += 1;
+                        } else {
+                            unchanged += 1;
+                        }
+                    }
+
+                    Ok((matched, updated, unchanged, issues))
+                })
+            })
+            .await?;
+
+        issues.extend(row_issues);
+        issues.sort_by_key(|issue| issue.line);
+
+        Ok(PriceListImportResponse {
+            dry_run,
+            total_rows,
+            matched,
+            updated,
+            unchanged,
+            issues,
+        })
+    }
+
+    pub async fn list_price_history(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: PriceHistoryQuery,
+    ) -> Result<Vec<ItemPriceHistoryResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut history_query = item_price_history::table
+            .filter(item_price_history::tenant_id.eq(tenant_id))
+            .filter(item_price_history::item_id.eq(item_id))
+            .into_boxed();
+
+        if let Some(vendor_id) = query.vendor_id {
+            history_query = history_query.filter(item_price_history::vendor_id.eq(vendor_id));
+        }
+        if let Some(from) = query.from {
+            history_query = history_query.filter(item_price_history::recorded_at.ge(from));
+        }
+        if let Some(to) = query.to {
+            history_query = history_query.filter(item_price_history::recorded_at.lt(to));
+        }
+
+        let history = history_query
+            .order(item_price_history::recorded_at.asc())
+            .limit(query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+            .select(ItemPriceHistory::as_select())
+            .load::<ItemPriceHistory>(&mut conn)
+            .await?;
+
+        Ok(history.into_iter().map(|entry| entry.into()).collect())
+    }
+
+    // Vendor records with a manufacturer part number, keyed by the normalized part number. With a
+    // vendor, only records of that vendor or without a vendor are candidates.
+    async fn load_vendor_records(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        vendor_id: Option<Uuid>,
+    ) -> Result<HashMap<String, Vec<(Item, InventoryItem)>>> {
+        let mut records_query = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::context.eq(String::from(ItemContext::Vendor)))
+            .filter(items::mfr_part_number.is_not_null())
+            .into_boxed();
+
+        if let Some(vendor_id) = vendor_id {
+            records_query = records_query.filter(
+                inventory_items::vendor_id
+                    .eq(vendor_id)
+                    .or(inventory_items::vendor_id.is_null()),
+            );
+        }
+
+        let records = records_query
+            .select((Item::as_select(), InventoryItem::as_select()))
+            .load::<(Item, InventoryItem)>(conn)
+            .await?;
+
+        let mut by_part_number: HashMap<String, Vec<(Item, InventoryItem)>> = HashMap::new();
+        for (item, inventory) in records {
+            if let Some(part_number) = item.mfr_part_number.as_deref() {
+                by_part_number
+                    .entry(normalize(part_number))
+                    .or_default()
+                    .push((item, inventory));
+            }
+        }
+
+        Ok(by_part_number)
+    }
+
+    /// Writes a price list row to a vendor record and records the change in the price history
+    /// under `source`, e.g. `price_list` or `rfq`.
+    pub(crate) async fn apply_row(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        vendor_id: Option<Uuid>,
+        recorded_by_id: Option<Uuid>,
+        inventory: &InventoryItem,
+        row: &PriceListRow,
+        source: &str,
+    ) -> Result<()> {
+        let price_breaks = serde_json::to_value(&row.price_breaks)?;
+        let unit_price = row.price_breaks[0].unit_price;
+        let currency = row
+            .currency
+            .clone()
+            .or_else(|| pricing_str(inventory, "currency"));
+        let lead_time = row.lead_time.or(inventory.lead_time);
+
+        let mut pricing = match inventory.pricing.clone() {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        pricing.insert("unit_price".to_string(), unit_price.into());
+        pricing.insert("price_breaks".to_string(), price_breaks.clone());
+        if let Some(currency) = &currency {
+            pricing.insert("currency".to_string(), currency.clone().into());
+        }
+        pricing.insert(
+            "price_list_updated_at".to_string(),
+            Utc::now().to_rfc3339().into(),
+        );
+
+        diesel::update(inventory_items::table.find(inventory.id))
+            .set((
+                inventory_items::pricing.eq(serde_json::Value::Object(pricing)),
+                inventory_items::lead_time.eq(lead_time),
+                inventory_items::vendor_id.eq(vendor_id.or(inventory.vendor_id)),
+                inventory_items::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)
+            .await?;
+
+        diesel::insert_into(item_price_history::table)
+            .values(&NewItemPriceHistory {
+                tenant_id,
+                item_id: inventory.item_id,
+                inventory_item_id: inventory.id,
+                vendor_id: vendor_id.or(inventory.vendor_id),
+                unit_price,
+                price_breaks,
+                currency,
+                lead_time,
+                source: source.to_string(),
+                recorded_by_id,
+            })
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+fn row_issue(row: &PriceListRow, reason: &str) -> PriceListRowIssue {
+    PriceListRowIssue {
+        line: row.line,
+        mfr_part_number: Some(row.mfr_part_number.clone()),
+        reason: reason.to_string(),
+    }
+}
+
+fn pricing_str(inventory: &InventoryItem, key: &str) -> Option<String> {
+    inventory
+        .pricing
+        .as_ref()?
+        .get(key)?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+fn price_changed(inventory: &InventoryItem, row: &PriceListRow) -> bool {
+    price_breaks(inventory.pricing.as_ref()) != row.price_breaks
+        || row
+            .lead_time
+            .is_some_and(|days| inventory.lead_time != Some(days))
+        || row
+            .currency
+            .as_ref()
+            .is_some_and(|currency| pricing_str(inventory, "currency").as_ref() != Some(currency))
+}
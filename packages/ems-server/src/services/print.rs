@@ -0,0 +1,688 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::net::SocketAddr;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use uuid::Uuid;
+
+use crate::models::{
+    CreatePrinterRequest, InventoryItem, Item, ListPrintJobsQuery, NewPrintJob, NewPrinter,
+    PrintJob, PrintJobResponse, PrintJobStatus, PrintLabelQuery, Printer, PrinterResponse,
+    UpdatePrinterRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::label::{
+    check_printer_port, item_label_zpl, printer_url, retry_delay, ItemLabel, LabelFormat,
+};
+use crate::utils::public_url::resolve_public_host;
+use crate::utils::AppError;
+
+/// Errors from [`PrintService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum PrintError {
+    #[error("Printer not found")]
+    PrinterNotFound,
+
+    #[error("Print job has not failed")]
+    NotFailed,
+}
+
+impl From<PrintError> for AppError {
+    fn from(error: PrintError) -> Self {
+        match error {
+            PrintError::PrinterNotFound => AppError::NotFound(error.to_string()),
+            PrintError::NotFailed => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// Connecting to and writing a label to the printer must finish within this time
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+// A job left in printing this long (e.g. the server stopped mid-send) is claimed again
+const STALE_PRINTING_MINUTES: i64 = 5;
+
+pub struct PrintService {
+    database: DatabaseService,
+}
+
+impl PrintService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Printer CRUD operations
+
+    pub async fn create_printer(
+        &self,
+        tenant_id: Uuid,
+        request: CreatePrinterRequest,
+    ) -> Result<PrinterResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let new_printer = NewPrinter {
+            tenant_id,
+            name: request.name,
+            location: request.location,
+            host: request.host,
+            port: request.port,
+            dpi: request.dpi,
+            label_width_mm: request.label_width_mm,
+            label_height_mm: request.label_height_mm,
+            is_default: request.is_default,
+            is_active: request.is_active,
+        };
+
+        let printer = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    if new_printer.is_default == Some(true) {
+                        Self::clear_default(conn, tenant_id).await?;
+                    }
+
+                    diesel::insert_into(printers::table)
+                        .values(&new_printer)
+                        .returning(Printer::as_returning())
+                        .get_result::<Printer>(conn)
+                        .await
+                })
+            })
+            .await?;
+
+        Ok(printer.into())
+    }
+
+    pub async fn list_printers(
+        &self,
+        tenant_id: Uuid,
+        location: Option<String>,
+    ) -> Result<Vec<PrinterResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = printers::table
+            .filter(printers::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(location) = location {
+            query = query.filter(printers::location.eq(location));
+        }
+
+        let printers = query
+            .order(printers::name.asc())
+            .select(Printer::as_select())
+            .load::<Printer>(&mut conn)
+            .await?;
+
+        Ok(printers.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_printer(
+        &self,
+        tenant_id: Uuid,
+        printer_id: Uuid,
+    ) -> Result<Option<PrinterResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let printer = printers::table
+            .filter(printers::id.eq(printer_id))
+            .filter(printers::tenant_id.eq(tenant_id))
+            .select(Printer::as_select())
+            .first::<Printer>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(printer.map(Into::into))
+    }
+
+    pub async fn update_printer(
+        &self,
+        tenant_id: Uuid,
+        printer_id: Uuid,
+        request: UpdatePrinterRequest,
+    ) -> Result<Option<PrinterResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = printers::table
+                        .filter(printers::id.eq(printer_id))
+                        .filter(printers::tenant_id.eq(tenant_id));
+
+                    let exists = target
+                        .select(printers::id)
+                        .first::<Uuid>(conn)
+                        .await
+                        .optional()?;
+                    if exists.is_none() {
+                        return Ok(None);
+                    }
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(printers::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(location) = &request.location {
+                        diesel::update(target)
+                            .set(printers::location.eq(location))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(host) = &request.host {
+                        diesel::update(target)
+                            .set(printers::host.eq(host))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(port) = request.port {
+                        diesel::update(target)
+                            .set(printers::port.eq(port))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(dpi) = request.dpi {
+                        diesel::update(target)
+                            .set(printers::dpi.eq(dpi))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(label_width_mm) = request.label_width_mm {
+                        diesel::update(target)
+                            .set(printers::label_width_mm.eq(label_width_mm))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(label_height_mm) = request.label_height_mm {
+                        diesel::update(target)
+                            .set(printers::label_height_mm.eq(label_height_mm))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_default) = request.is_default {
+                        if is_default {
+                            Self::clear_default(conn, tenant_id).await?;
+                        }
+                        diesel::update(target)
+                            .set(printers::is_default.eq(is_default))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(printers::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    target
+                        .select(Printer::as_select())
+                        .first::<Printer>(conn)
+                        .await
+                        .optional()
+                })
+            })
+            .await?;
+
+        Ok(updated.map(Into::into))
+    }
+
+    pub async fn delete_printer(&self, tenant_id: Uuid, printer_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            printers::table
+                .filter(printers::id.eq(printer_id))
+                .filter(printers::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Label printing operations
+
+    /// Renders the item's label for the chosen printer, queues it and sends it straight away.
+    /// A failed send stays queued and is retried by the print worker.
+    pub async fn print_item_label(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        requested_by_id: Option<Uuid>,
+        query: PrintLabelQuery,
+    ) -> Result<Option<PrintJobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut item_query = items::table
+            .inner_join(inventory_items::table.on(inventory_items::item_id.eq(items::id)))
+            .filter(items::id.eq(item_id))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(context) = &query.context {
+            item_query = item_query.filter(inventory_items::context.eq(context.to_string()));
+        }
+
+        let Some((item, inventory)) = item_query
+            .order(inventory_items::created_at.asc())
+            .select((Item::as_select(), InventoryItem::as_select()))
+            .first::<(Item, InventoryItem)>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let printer = Self::resolve_printer(&mut conn, tenant_id, &query).await?;
+
+        let label = ItemLabel {
+            item_id: item.id.to_string(),
+            part_number: item.internal_part_number,
+            description: item.description,
+            manufacturer: item.manufacturer,
+            mfr_part_number: item.mfr_part_number,
+            location: inventory.location,
+        };
+        let format = LabelFormat {
+            width_mm: printer.label_width_mm,
+            height_mm: printer.label_height_mm,
+            dpi: printer.dpi,
+        };
+        let copies = query.copies.unwrap_or(1);
+
+        let new_job = NewPrintJob {
+            tenant_id,
+            printer_id: printer.id,
+            item_id: Some(item.id),
+            copies,
+            zpl: item_label_zpl(&label, format, copies),
+            requested_by_id,
+        };
+
+        let job: PrintJob = diesel::insert_into(print_jobs::table)
+            .values(&new_job)
+            .returning(PrintJob::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        self.dispatch_job(&mut conn, job).await.map(Some)
+    }
+
+    pub async fn list_jobs(
+        &self,
+        tenant_id: Uuid,
+        query: ListPrintJobsQuery,
+    ) -> Result<Vec<PrintJobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut jobs_query = print_jobs::table
+            .filter(print_jobs::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(status) = query.status {
+            jobs_query = jobs_query.filter(print_jobs::status.eq(status.to_string()));
+        }
+
+        if let Some(printer_id) = query.printer_id {
+            jobs_query = jobs_query.filter(print_jobs::printer_id.eq(printer_id));
+        }
+
+        if let Some(item_id) = query.item_id {
+            jobs_query = jobs_query.filter(print_jobs::item_id.eq(item_id));
+        }
+
+        let jobs = jobs_query
+            .order(print_jobs::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(PrintJob::as_select())
+            .load::<PrintJob>(&mut conn)
+            .await?;
+
+        Ok(jobs.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_job(&self, tenant_id: Uuid, job_id: Uuid) -> Result<Option<PrintJobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let job = print_jobs::table
+            .filter(print_jobs::id.eq(job_id))
+            .filter(print_jobs::tenant_id.eq(tenant_id))
+            .select(PrintJob::as_select())
+            .first::<PrintJob>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(job.map(Into::into))
+    }
+
+    /// Requeues a failed job with a fresh set of attempts and sends it straight away.
+    pub async fn retry_job(
+        &self,
+        tenant_id: Uuid,
+        job_id: Uuid,
+    ) -> Result<Option<PrintJobResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let target = print_jobs::table
+            .filter(print_jobs::id.eq(job_id))
+            .filter(print_jobs::tenant_id.eq(tenant_id));
+
+        let Some(job) = target
+            .select(PrintJob::as_select())
+            .first::<PrintJob>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        if job.status != PrintJobStatus::Failed.to_string() {
+            return Err(PrintError::NotFailed.into());
+        }
+
+        let job = diesel::update(target)
+            .set((
+                print_jobs::status.eq(PrintJobStatus::Queued.to_string()),
+                print_jobs::attempts.eq(0),
+                print_jobs::next_attempt_at.eq(Utc::now()),
+            ))
+            .returning(PrintJob::as_returning())
+            .get_result::<PrintJob>(&mut conn)
+            .await?;
+
+        self.dispatch_job(&mut conn, job).await.map(Some)
+    }
+
+    // Print queue operations
+
+    /// Claims queued jobs that are due for another attempt across all tenants, marking them as
+    /// printing so concurrent workers skip them.
+    pub async fn claim_due_jobs(&self, limit: i64) -> Result<Vec<PrintJob>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // The print worker works across tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let now = Utc::now();
+        let stale_before = now - ChronoDuration::minutes(STALE_PRINTING_MINUTES);
+        let jobs = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let due = print_jobs::table
+                        .filter(
+                            print_jobs::status
+                                .eq(PrintJobStatus::Queued.to_string())
+                                .and(print_jobs::next_attempt_at.le(now))
+                                .or(print_jobs::status
+                                    .eq(PrintJobStatus::Printing.to_string())
+                                    .and(print_jobs::updated_at.lt(stale_before))),
+                        )
+                        .order(print_jobs::next_attempt_at.asc())
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .select(PrintJob::as_select())
+                        .load::<PrintJob>(conn)
+                        .await?;
+
+                    let mut claimed = Vec::with_capacity(due.len());
+                    for job in due {
+                        let job =
+                            diesel::update(print_jobs::table.filter(print_jobs::id.eq(job.id)))
+                                .set((
+                                    print_jobs::status.eq(PrintJobStatus::Printing.to_string()),
+                                    print_jobs::attempts.eq(print_jobs::attempts + 1),
+                                ))
+                                .returning(PrintJob::as_returning())
+                                .get_result::<PrintJob>(conn)
+                                .await?;
+                        claimed.push(job);
+                    }
+
+                    Ok(claimed)
+                })
+            })
+            .await?;
+
+        Ok(jobs)
+    }
+
+    /// Sends a claimed job to its printer and records the outcome. A failed send is requeued
+    /// with backoff until the job runs out of attempts; only bookkeeping errors are returned.
+    pub async fn deliver_job(&self, job: &PrintJob) -> Result<PrintJobStatus> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", job.tenant_id))
+            .await?;
+
+        Self::send_and_record(&mut conn, job)
+            .await
+            .map(|job| PrintJobStatus::try_from(job.status).unwrap_or(PrintJobStatus::Queued))
+    }
+
+    // Private helper methods
+
+    async fn clear_default(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+    ) -> Result<usize, diesel::result::Error> {
+        diesel::update(
+            printers::table
+                .filter(printers::tenant_id.eq(tenant_id))
+                .filter(printers::is_default.eq(true)),
+        )
+        .set(printers::is_default.eq(false))
+        .execute(conn)
+        .await
+    }
+
+    /// Picks the requested printer, else the default printer for the requested location, else
+    /// the tenant default printer. Only active printers are used.
+    async fn resolve_printer(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        query: &PrintLabelQuery,
+    ) -> Result<Printer> {
+        let active = printers::table
+            .filter(printers::tenant_id.eq(tenant_id))
+            .filter(printers::is_active.eq(true));
+
+        let printer = match (query.printer, &query.location) {
+            (Some(printer_id), _) => active
+                .filter(printers::id.eq(printer_id))
+                .select(Printer::as_select())
+                .first::<Printer>(conn)
+                .await
+                .optional()?,
+            (None, Some(location)) => active
+                .filter(printers::location.eq(location))
+                .order((printers::is_default.desc(), printers::name.asc()))
+                .select(Printer::as_select())
+                .first::<Printer>(conn)
+                .await
+                .optional()?,
+            (None, None) => active
+                .filter(printers::is_default.eq(true))
+                .select(Printer::as_select())
+                .first::<Printer>(conn)
+                .await
+                .optional()?,
+        };
+
+        printer.ok_or(PrintError::PrinterNotFound.into())
+    }
+
+    /// Claims a freshly queued job for this request and sends it, unless the print worker got to
+    /// it first.
+    async fn dispatch_job(
+        &self,
+        conn: &mut AsyncPgConnection,
+        job: PrintJob,
+    ) -> Result<PrintJobResponse> {
+        let claimed = diesel::update(
+            print_jobs::table
+                .filter(print_jobs::id.eq(job.id))
+                .filter(print_jobs::status.eq(PrintJobStatus::Queued.to_string())),
+        )
+        .set((
+            print_jobs::status.eq(PrintJobStatus::Printing.to_string()),
+            print_jobs::attempts.eq(print_jobs::attempts + 1),
+        ))
+        .returning(PrintJob::as_returning())
+        .get_result::<PrintJob>(conn)
+        .await
+        .optional()?;
+
+        match claimed {
+            Some(claimed) => Self::send_and_record(conn, &claimed).await.map(Into::into),
+            None => Ok(job.into()),
+        }
+    }
+
+    async fn send_and_record(conn: &mut AsyncPgConnection, job: &PrintJob) -> Result<PrintJob> {
+        let printer = printers::table
+            .filter(printers::id.eq(job.printer_id))
+            .filter(printers::tenant_id.eq(job.tenant_id))
+            .select(Printer::as_select())
+            .first::<Printer>(conn)
+            .await
+            .optional()?;
+
+        let outcome = match printer {
+            Some(printer) if printer.is_active => {
+                send_to_printer(&printer.host, printer.port, &job.zpl).await
+            }
+            Some(_) => Err(anyhow!("Printer is inactive")),
+            None => Err(anyhow!("Printer not found")),
+        };
+
+        let target = print_jobs::table
+            .filter(print_jobs::id.eq(job.id))
+            .filter(print_jobs::tenant_id.eq(job.tenant_id));
+        let now = Utc::now();
+
+        let updated = match outcome {
+            Ok(()) => {
+                diesel::update(target)
+                    .set((
+                        print_jobs::status.eq(PrintJobStatus::Printed.to_string()),
+                        print_jobs::printed_at.eq(now),
+                        print_jobs::last_error.eq(None::<String>),
+                    ))
+                    .returning(PrintJob::as_returning())
+                    .get_result::<PrintJob>(conn)
+                    .await?
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Print job {} attempt {} failed: {}",
+                    job.id,
+                    job.attempts,
+                    e
+                );
+
+                let (status, next_attempt_at) = if job.attempts >= job.max_attempts {
+                    (PrintJobStatus::Failed, now)
+                } else {
+                    let delay = ChronoDuration::from_std(retry_delay(job.attempts))?;
+                    (PrintJobStatus::Queued, now + delay)
+                };
+
+                diesel::update(target)
+                    .set((
+                        print_jobs::status.eq(status.to_string()),
+                        print_jobs::next_attempt_at.eq(next_attempt_at),
+                        print_jobs::last_error.eq(e.to_string()),
+                    ))
+                    .returning(PrintJob::as_returning())
+                    .get_result::<PrintJob>(conn)
+                    .await?
+            }
+        };
+
+        Ok(updated)
+    }
+}
+
+/// Sends raw ZPL to a printer's TCP print port (9100 on Zebra printers). Only allowed ports and
+/// hosts off the server's own network are connected to, checked again here since printers saved
+/// before the checks, or resolving elsewhere since, would otherwise be trusted.
+pub async fn send_to_printer(host: &str, port: i32, zpl: &str) -> Result<()> {
+    let port = u16::try_from(port).map_err(|_| anyhow!("Invalid printer port: {}", port))?;
+    check_printer_port(port).map_err(|e| anyhow!(e))?;
+    let url = printer_url(host, port).map_err(|e| anyhow!(e))?;
+
+    // The resolved address is connected to, so a second lookup cannot lead elsewhere
+    let address = match resolve_public_host(&url, "printer").await? {
+        Some((_, address)) => SocketAddr::new(address.ip(), port),
+        // Address hosts were checked with the URL
+        None => url
+            .socket_addrs(|| Some(port))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Invalid printer host: {}", host))?,
+    };
+
+    tokio::time::timeout(SEND_TIMEOUT, async {
+        let mut stream = TcpStream::connect(address).await?;
+        stream.write_all(zpl.as_bytes()).await?;
+        stream.flush().await?;
+        stream.shutdown().await?;
+        Ok::<_, std::io::Error>(())
+    })
+    .await
+    .map_err(|_| anyhow!("Timed out sending to printer {}:{}", host, port))?
+    .map_err(|e| anyhow!("Failed to send to printer {}:{}: {}", host, port, e))
+}
@@ -0,0 +1,204 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::{
+    ItemContext, JobAssignmentStatus, MachineJobAssignment, RecordStockMovementRequest,
+    StockMovementType, StockReferenceType,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, StockService};
+use crate::utils::production::{
+    counter_delta, declared_counters, read_counters, target_met, CounterReading, ProductionCounters,
+};
+
+pub struct ProductionService {
+    database: DatabaseService,
+}
+
+impl ProductionService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Adds the good and scrap parts a heartbeat reports to the machine's active job
+    /// assignment: the one in progress, or else the earliest pending one, which is started.
+    /// Good parts are received into finished-goods stock of the job's item, and the assignment
+    /// completes once its target quantity, or the job quantity, is made. Returns the updated
+    /// assignment, or `None` when the machine declares no counters, reports no parts or has no
+    /// active assignment.
+    pub async fn record_heartbeat_counts(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        payload: &Value,
+        metadata: Option<&Value>,
+        reported_at: DateTime<Utc>,
+    ) -> Result<Option<MachineJobAssignment>> {
+        if heartbeat_counts(payload, metadata).is_none() {
+            return Ok(None);
+        }
+
+        let (payload, metadata) = (payload.clone(), metadata.cloned());
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::apply_heartbeat_counts(
+                        conn,
+                        tenant_id,
+                        machine_id,
+                        &payload,
+                        metadata.as_ref(),
+                        reported_at,
+                    )
+                    .await
+                })
+            })
+            .await
+    }
+
+    /// [`Self::record_heartbeat_counts`] on a connection the caller holds in a transaction with
+    /// the tenant context set, for heartbeats written in batches by the heartbeat buffer.
+    pub async fn apply_heartbeat_counts(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        payload: &Value,
+        metadata: Option<&Value>,
+        reported_at: DateTime<Utc>,
+    ) -> Result<Option<MachineJobAssignment>> {
+        let Some((counters, reading)) = heartbeat_counts(payload, metadata) else {
+            return Ok(None);
+        };
+
+        let active = machine_job_assignments::table
+            .filter(machine_job_assignments::machine_id.eq(machine_id))
+            .filter(machine_job_assignments::status.eq_any([
+                JobAssignmentStatus::InProgress.to_string(),
+                JobAssignmentStatus::Pending.to_string(),
+            ]))
+            .order((
+                machine_job_assignments::start_time.asc().nulls_last(),
+                machine_job_assignments::created_at.asc(),
+            ))
+            .select(MachineJobAssignment::as_select())
+            .for_update()
+            .load::<MachineJobAssignment>(conn)
+            .await?;
+        let in_progress = JobAssignmentStatus::InProgress.to_string();
+        let Some(assignment) = active
+            .iter()
+            .find(|assignment| assignment.status == in_progress)
+            .or_else(|| <[_]>::first(&active))
+        else {
+            return Ok(None);
+        };
+
+        let delta = |count: Option<i64>, last_total: Option<i64>| {
+            count.map_or(0, |count| counter_delta(counters.mode, count, last_total))
+        };
+        let produced = delta(reading.produced, assignment.last_produced_total);
+        let scrap = delta(reading.scrap, assignment.last_scrap_total);
+        let produced_quantity = clamp_count(assignment.produced_quantity, produced);
+        let scrap_quantity = clamp_count(assignment.scrap_quantity, scrap);
+        let produced = produced_quantity - assignment.produced_quantity;
+
+        let (job_number, item_id, job_quantity): (String, Option<Uuid>, i32) = jobs::table
+            .filter(jobs::id.eq(assignment.job_id))
+            .filter(jobs::tenant_id.eq(tenant_id))
+            .select((jobs::job_number, jobs::item_id, jobs::quantity))
+            .first(conn)
+            .await?;
+
+        let status = if target_met(
+            produced_quantity,
+            assignment.target_quantity.or(Some(job_quantity)),
+        ) {
+            JobAssignmentStatus::Completed
+        } else if produced > 0 || scrap > 0 || assignment.status == in_progress {
+            JobAssignmentStatus::InProgress
+        } else {
+            JobAssignmentStatus::Pending
+        };
+        let started = status != JobAssignmentStatus::Pending;
+        let completed = status == JobAssignmentStatus::Completed;
+
+        let updated = diesel::update(
+            machine_job_assignments::table.filter(machine_job_assignments::id.eq(assignment.id)),
+        )
+        .set((
+            machine_job_assignments::status.eq(status.to_string()),
+            machine_job_assignments::produced_quantity.eq(produced_quantity),
+            machine_job_assignments::scrap_quantity.eq(scrap_quantity),
+            machine_job_assignments::last_produced_total
+                .eq(reading.produced.or(assignment.last_produced_total)),
+            machine_job_assignments::last_scrap_total
+                .eq(reading.scrap.or(assignment.last_scrap_total)),
+            machine_job_assignments::start_time
+                .eq(assignment.start_time.or(started.then_some(reported_at))),
+            machine_job_assignments::end_time.eq(if completed {
+                Some(reported_at)
+            } else {
+                assignment.end_time
+            }),
+            machine_job_assignments::updated_at.eq(reported_at),
+        ))
+        .returning(MachineJobAssignment::as_returning())
+        .get_result::<MachineJobAssignment>(conn)
+        .await?;
+
+        // Good parts go into finished-goods stock as they are made
+        if let (true, Some(item_id)) = (produced > 0, item_id) {
+            let movement = StockService::apply_movement(
+                conn,
+                tenant_id,
+                item_id,
+                None,
+                &RecordStockMovementRequest {
+                    context: ItemContext::FinishedGoods,
+                    movement_type: StockMovementType::Receipt,
+                    quantity: produced.into(),
+                    reference_type: Some(StockReferenceType::Job),
+                    reference_id: Some(assignment.job_id),
+                    notes: Some(format!("Reported by machine on job {}", job_number)),
+                    occurred_at: Some(reported_at),
+                    allow_reserved: false,
+                    uom_id: None,
+                    owner_id: None,
+                },
+            )
+            .await?;
+            if movement.is_none() {
+                tracing::warn!(
+                    "Job {} produced {} parts but its item has no finished-goods inventory",
+                    job_number,
+                    produced
+                );
+            }
+        }
+
+        Ok(Some(updated))
+    }
+}
+
+// The machine's declared counters and what the heartbeat reports on them; `None` when it
+// declares none or reports no parts
+fn heartbeat_counts(
+    payload: &Value,
+    metadata: Option<&Value>,
+) -> Option<(ProductionCounters, CounterReading)> {
+    let counters = declared_counters(metadata)?;
+    let reading = read_counters(payload, &counters);
+    if reading.produced.is_none() && reading.scrap.is_none() {
+        return None;
+    }
+    Some((counters, reading))
+}
+
+// A count plus the parts just reported, kept within the column's range
+fn clamp_count(count: i32, delta: i64) -> i32 {
+    i32::try_from(i64::from(count) + delta.max(0)).unwrap_or(i32::MAX)
+}
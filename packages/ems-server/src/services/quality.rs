@@ -0,0 +1,746 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CapaAction, CapaActionResponse, CapaActionType, CapaStatus, CreateCapaActionRequest,
+    CreateNcrRequest, ListNcrsQuery, ListOverdueCapaQuery, NcrResponse, NcrSeverity,
+    NcrSeverityCounts, NcrStatus, NewCapaAction, NewNonConformance, NonConformance,
+    OverdueCapaResponse, QualityDashboardResponse, UpdateCapaActionRequest, UpdateNcrRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::quality::{close_blocker, days_overdue};
+use crate::utils::AppError;
+
+/// Errors from the quality services (NCRs, CAPA, SPC and calibration) that are the caller's
+/// to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum QualityError {
+    #[error("{0} not found: {1}")]
+    ReferenceNotFound(&'static str, Uuid),
+
+    #[error("Machine {0} is not a QA machine")]
+    NotQaMachine(Uuid),
+
+    #[error("NCR cannot be closed: {0}")]
+    NcrNotClosable(String),
+
+    #[error("NCR cannot be changed: status is {0}")]
+    NcrNotChangeable(String),
+
+    #[error("CAPA action cannot be updated: status is {0}")]
+    CapaNotUpdatable(String),
+
+    #[error("CAPA action cannot be completed: a root cause needs an outcome")]
+    CapaNotCompletable,
+
+    #[error("Machine {0} is out of calibration")]
+    MachineOutOfCalibration(Uuid),
+
+    #[error("Instrument {0} is inactive and cannot be used")]
+    InstrumentInactive(Uuid),
+
+    #[error("Instrument {0} is out of calibration")]
+    InstrumentOutOfCalibration(Uuid),
+
+    #[error("A due date or calibration interval is required for the calibration")]
+    DueDateRequired,
+
+    #[error("The due date must be after the calibration")]
+    DueDateBeforeCalibration,
+}
+
+impl From<QualityError> for AppError {
+    fn from(error: QualityError) -> Self {
+        match error {
+            QualityError::ReferenceNotFound(..) => AppError::NotFound(error.to_string()),
+            QualityError::NotQaMachine(_)
+            | QualityError::DueDateRequired
+            | QualityError::DueDateBeforeCalibration => AppError::Validation(error.to_string()),
+            _ => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// Overdue actions listed on the dashboard
+const DASHBOARD_OVERDUE_LIMIT: i64 = 20;
+
+pub struct QualityService {
+    database: DatabaseService,
+}
+
+impl QualityService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // NCR operations
+
+    /// Raises a non-conformance against the job, machine or item lot named by its source type,
+    /// together with any actions already decided on.
+    pub async fn create_ncr(
+        &self,
+        tenant_id: Uuid,
+        raised_by_id: Option<Uuid>,
+        request: CreateNcrRequest,
+    ) -> Result<NcrResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if let Some(job_id) = request.job_id {
+            let found: i64 = jobs::table
+                .filter(jobs::id.eq(job_id))
+                .filter(jobs::tenant_id.eq(tenant_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Job", job_id).into());
+            }
+        }
+        if let Some(machine_id) = request.machine_id {
+            let found: i64 = machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Machine", machine_id).into());
+            }
+        }
+        if let Some(item_id) = request.item_id {
+            let found: i64 = items::table
+                .filter(items::id.eq(item_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Item", item_id).into());
+            }
+        }
+        if let Some(vendor_id) = request.vendor_id {
+            Self::ensure_vendor(&mut conn, tenant_id, vendor_id).await?;
+        }
+
+        let ncr = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let new_ncr = NewNonConformance {
+                        tenant_id,
+                        ncr_number: request.ncr_number.unwrap_or_else(|| {
+                            format!("NCR-{}", &Uuid::new_v4().simple().to_string()[..8])
+                                .to_uppercase()
+                        }),
+                        title: request.title,
+                        description: request.description,
+                        source_type: request.source_type.to_string(),
+                        job_id: request.job_id,
+                        machine_id: request.machine_id,
+                        item_id: request.item_id,
+                        lot_number: request.lot_number,
+                        quantity_affected: request.quantity_affected,
+                        severity: request.severity.to_string(),
+                        owner_id: request.owner_id,
+                        due_date: request.due_date,
+                        raised_by_id,
+                        vendor_id: request.vendor_id,
+                    };
+
+                    let ncr: NonConformance = diesel::insert_into(non_conformances::table)
+                        .values(&new_ncr)
+                        .returning(NonConformance::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    for action in request.actions {
+                        Self::insert_action(conn, &ncr, action).await?;
+                    }
+
+                    Ok(ncr)
+                })
+            })
+            .await?;
+
+        Self::load_response(&mut conn, ncr).await
+    }
+
+    pub async fn list_ncrs(
+        &self,
+        tenant_id: Uuid,
+        query: ListNcrsQuery,
+    ) -> Result<Vec<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut ncrs_query = non_conformances::table
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(status) = query.status {
+            ncrs_query = ncrs_query.filter(non_conformances::status.eq(status.to_string()));
+        }
+        if let Some(severity) = query.severity {
+            ncrs_query = ncrs_query.filter(non_conformances::severity.eq(severity.to_string()));
+        }
+        if let Some(source_type) = query.source_type {
+            ncrs_query =
+                ncrs_query.filter(non_conformances::source_type.eq(source_type.to_string()));
+        }
+        if let Some(job_id) = query.job_id {
+            ncrs_query = ncrs_query.filter(non_conformances::job_id.eq(job_id));
+        }
+        if let Some(machine_id) = query.machine_id {
+            ncrs_query = ncrs_query.filter(non_conformances::machine_id.eq(machine_id));
+        }
+        if let Some(item_id) = query.item_id {
+            ncrs_query = ncrs_query.filter(non_conformances::item_id.eq(item_id));
+        }
+        if let Some(owner_id) = query.owner_id {
+            ncrs_query = ncrs_query.filter(non_conformances::owner_id.eq(owner_id));
+        }
+        if let Some(vendor_id) = query.vendor_id {
+            ncrs_query = ncrs_query.filter(non_conformances::vendor_id.eq(vendor_id));
+        }
+
+        let ncrs = ncrs_query
+            .order(non_conformances::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(NonConformance::as_select())
+            .load::<NonConformance>(&mut conn)
+            .await?;
+
+        Self::load_responses(&mut conn, ncrs).await
+    }
+
+    pub async fn get_ncr(&self, tenant_id: Uuid, ncr_id: Uuid) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(ncr) = non_conformances::table
+            .filter(non_conformances::id.eq(ncr_id))
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .select(NonConformance::as_select())
+            .first::<NonConformance>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        Self::load_response(&mut conn, ncr).await.map(Some)
+    }
+
+    pub async fn update_ncr(
+        &self,
+        tenant_id: Uuid,
+        ncr_id: Uuid,
+        request: UpdateNcrRequest,
+    ) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(ncr) = Self::lock_open_ncr(conn, tenant_id, ncr_id).await? else {
+                        return Ok(None);
+                    };
+                    if let Some(vendor_id) = request.vendor_id {
+                        Self::ensure_vendor(conn, tenant_id, vendor_id).await?;
+                    }
+
+                    let ncr = diesel::update(non_conformances::table.find(ncr.id))
+                        .set((
+                            non_conformances::title.eq(request.title.unwrap_or(ncr.title)),
+                            non_conformances::description
+                                .eq(request.description.or(ncr.description)),
+                            non_conformances::quantity_affected
+                                .eq(request.quantity_affected.or(ncr.quantity_affected)),
+                            non_conformances::severity.eq(request
+                                .severity
+                                .map(|s| s.to_string())
+                                .unwrap_or(ncr.severity)),
+                            non_conformances::owner_id.eq(request.owner_id.or(ncr.owner_id)),
+                            non_conformances::due_date.eq(request.due_date.or(ncr.due_date)),
+                            non_conformances::vendor_id.eq(request.vendor_id.or(ncr.vendor_id)),
+                        ))
+                        .returning(NonConformance::as_returning())
+                        .get_result::<NonConformance>(conn)
+                        .await?;
+
+                    Ok(Some(ncr))
+                })
+            })
+            .await?;
+
+        match updated {
+            Some(ncr) => Self::load_response(&mut conn, ncr).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Closes an NCR once its actions are finished; see `close_blocker` for what is required.
+    pub async fn close_ncr(
+        &self,
+        tenant_id: Uuid,
+        ncr_id: Uuid,
+        person_id: Option<Uuid>,
+    ) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let closed = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(ncr) = Self::lock_open_ncr(conn, tenant_id, ncr_id).await? else {
+                        return Ok(None);
+                    };
+
+                    let actions: Vec<(CapaActionType, CapaStatus)> =
+                        Self::find_actions(conn, &[ncr.id])
+                            .await?
+                            .into_iter()
+                            .filter_map(|action| {
+                                Some((
+                                    CapaActionType::try_from(action.action_type).ok()?,
+                                    CapaStatus::try_from(action.status).ok()?,
+                                ))
+                            })
+                            .collect();
+                    let severity =
+                        NcrSeverity::try_from(ncr.severity.clone()).unwrap_or(NcrSeverity::Minor);
+                    if let Some(blocker) = close_blocker(severity, &actions) {
+                        return Err(QualityError::NcrNotClosable(blocker.to_string()).into());
+                    }
+
+                    let ncr = diesel::update(non_conformances::table.find(ncr.id))
+                        .set((
+                            non_conformances::status.eq(NcrStatus::Closed.to_string()),
+                            non_conformances::closed_at.eq(Some(Utc::now())),
+                            non_conformances::closed_by_id.eq(person_id),
+                        ))
+                        .returning(NonConformance::as_returning())
+                        .get_result::<NonConformance>(conn)
+                        .await?;
+
+                    Ok(Some(ncr))
+                })
+            })
+            .await?;
+
+        match closed {
+            Some(ncr) => Self::load_response(&mut conn, ncr).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Withdraws an NCR raised in error. Its unfinished actions are cancelled with it.
+    pub async fn cancel_ncr(&self, tenant_id: Uuid, ncr_id: Uuid) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let cancelled = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(ncr) = Self::lock_open_ncr(conn, tenant_id, ncr_id).await? else {
+                        return Ok(None);
+                    };
+
+                    diesel::update(
+                        capa_actions::table
+                            .filter(capa_actions::ncr_id.eq(ncr.id))
+                            .filter(capa_actions::status.eq_any([
+                                CapaStatus::Open.to_string(),
+                                CapaStatus::InProgress.to_string(),
+                            ])),
+                    )
+                    .set(capa_actions::status.eq(CapaStatus::Cancelled.to_string()))
+                    .execute(conn)
+                    .await?;
+
+                    let ncr = diesel::update(non_conformances::table.find(ncr.id))
+                        .set(non_conformances::status.eq(NcrStatus::Cancelled.to_string()))
+                        .returning(NonConformance::as_returning())
+                        .get_result::<NonConformance>(conn)
+                        .await?;
+
+                    Ok(Some(ncr))
+                })
+            })
+            .await?;
+
+        match cancelled {
+            Some(ncr) => Self::load_response(&mut conn, ncr).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // CAPA action operations
+
+    pub async fn add_action(
+        &self,
+        tenant_id: Uuid,
+        ncr_id: Uuid,
+        request: CreateCapaActionRequest,
+    ) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let ncr = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(ncr) = Self::lock_open_ncr(conn, tenant_id, ncr_id).await? else {
+                        return Ok(None);
+                    };
+
+                    Self::insert_action(conn, &ncr, request).await?;
+
+                    Ok(Some(ncr))
+                })
+            })
+            .await?;
+
+        match ncr {
+            Some(ncr) => Self::load_response(&mut conn, ncr).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Progresses a CAPA action. Completing one stamps who completed it and when; completed and
+    /// cancelled actions are final.
+    pub async fn update_action(
+        &self,
+        tenant_id: Uuid,
+        ncr_id: Uuid,
+        action_id: Uuid,
+        person_id: Option<Uuid>,
+        request: UpdateCapaActionRequest,
+    ) -> Result<Option<NcrResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let ncr = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(ncr) = Self::lock_open_ncr(conn, tenant_id, ncr_id).await? else {
+                        return Ok(None);
+                    };
+                    let Some(action) = capa_actions::table
+                        .filter(capa_actions::id.eq(action_id))
+                        .filter(capa_actions::ncr_id.eq(ncr.id))
+                        .for_update()
+                        .select(CapaAction::as_select())
+                        .first::<CapaAction>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let current =
+                        CapaStatus::try_from(action.status.clone()).map_err(|e| anyhow!(e))?;
+                    if !current.is_active() {
+                        return Err(QualityError::CapaNotUpdatable(current.to_string()).into());
+                    }
+
+                    let status = request.status.unwrap_or(current);
+                    let outcome = request.outcome.or(action.outcome);
+                    let completing = status == CapaStatus::Completed;
+                    if completing
+                        && action.action_type == CapaActionType::RootCause.to_string()
+                        && outcome.as_deref().is_none_or(str::is_empty)
+                    {
+                        return Err(QualityError::CapaNotCompletable.into());
+                    }
+
+                    diesel::update(capa_actions::table.find(action.id))
+                        .set((
+                            capa_actions::status.eq(status.to_string()),
+                            capa_actions::description
+                                .eq(request.description.unwrap_or(action.description)),
+                            capa_actions::owner_id.eq(request.owner_id.or(action.owner_id)),
+                            capa_actions::due_date.eq(request.due_date.or(action.due_date)),
+                            capa_actions::outcome.eq(outcome),
+                            capa_actions::completed_at.eq(completing.then(Utc::now)),
+                            capa_actions::completed_by_id.eq(person_id.filter(|_| completing)),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(ncr))
+                })
+            })
+            .await?;
+
+        match ncr {
+            Some(ncr) => Self::load_response(&mut conn, ncr).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Unfinished actions on open NCRs that are past their due date, most overdue first.
+    pub async fn list_overdue_capas(
+        &self,
+        tenant_id: Uuid,
+        query: ListOverdueCapaQuery,
+    ) -> Result<Vec<OverdueCapaResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::find_overdue(
+            &mut conn,
+            tenant_id,
+            query.owner_id,
+            query.limit.unwrap_or(50),
+            Utc::now(),
+        )
+        .await
+    }
+
+    /// Quality summary for the dashboard: open NCRs by severity and the overdue CAPA actions.
+    pub async fn dashboard(&self, tenant_id: Uuid) -> Result<QualityDashboardResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let now = Utc::now();
+        let open_ncrs = non_conformances::table
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .filter(non_conformances::status.eq(NcrStatus::Open.to_string()));
+
+        let mut open_ncrs_by_severity = NcrSeverityCounts::default();
+        for (severity, count) in open_ncrs
+            .clone()
+            .group_by(non_conformances::severity)
+            .select((non_conformances::severity, diesel::dsl::count_star()))
+            .load::<(String, i64)>(&mut conn)
+            .await?
+        {
+            match NcrSeverity::try_from(severity) {
+                Ok(NcrSeverity::Minor) => open_ncrs_by_severity.minor += count,
+                Ok(NcrSeverity::Major) => open_ncrs_by_severity.major += count,
+                Ok(NcrSeverity::Critical) => open_ncrs_by_severity.critical += count,
+                Err(_) => {}
+            }
+        }
+        let overdue_ncrs: i64 = open_ncrs
+            .filter(non_conformances::due_date.lt(now))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+
+        let open_actions = capa_actions::table
+            .inner_join(non_conformances::table)
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .filter(non_conformances::status.eq(NcrStatus::Open.to_string()))
+            .filter(capa_actions::status.eq_any([
+                CapaStatus::Open.to_string(),
+                CapaStatus::InProgress.to_string(),
+            ]));
+        let open_capa_actions: i64 = open_actions.clone().count().get_result(&mut conn).await?;
+        let overdue_capa_actions: i64 = open_actions
+            .filter(capa_actions::due_date.lt(now))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+
+        let overdue_capas =
+            Self::find_overdue(&mut conn, tenant_id, None, DASHBOARD_OVERDUE_LIMIT, now).await?;
+
+        Ok(QualityDashboardResponse {
+            open_ncrs: open_ncrs_by_severity.minor
+                + open_ncrs_by_severity.major
+                + open_ncrs_by_severity.critical,
+            open_ncrs_by_severity,
+            overdue_ncrs,
+            open_capa_actions,
+            overdue_capa_actions,
+            overdue_capas,
+        })
+    }
+
+    // Private helper methods
+
+    async fn lock_open_ncr(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        ncr_id: Uuid,
+    ) -> Result<Option<NonConformance>> {
+        let ncr = non_conformances::table
+            .filter(non_conformances::id.eq(ncr_id))
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(NonConformance::as_select())
+            .first::<NonConformance>(conn)
+            .await
+            .optional()?;
+
+        match ncr {
+            Some(ncr) if ncr.status != NcrStatus::Open.to_string() => {
+                Err(QualityError::NcrNotChangeable(ncr.status).into())
+            }
+            ncr => Ok(ncr),
+        }
+    }
+
+    async fn ensure_vendor(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        vendor_id: Uuid,
+    ) -> Result<()> {
+        let found: i64 = vendor_person::table
+            .filter(vendor_person::person_id.eq(vendor_id))
+            .filter(vendor_person::tenant_id.eq(tenant_id))
+            .count()
+            .get_result(conn)
+            .await?;
+        if found == 0 {
+            return Err(QualityError::ReferenceNotFound("Vendor", vendor_id).into());
+        }
+        Ok(())
+    }
+
+    /// Adds an action to the NCR, inheriting its owner and due date where the request has none.
+    async fn insert_action(
+        conn: &mut AsyncPgConnection,
+        ncr: &NonConformance,
+        request: CreateCapaActionRequest,
+    ) -> Result<CapaAction> {
+        Ok(diesel::insert_into(capa_actions::table)
+            .values(&NewCapaAction {
+                tenant_id: ncr.tenant_id,
+                ncr_id: ncr.id,
+                action_type: request.action_type.to_string(),
+                description: request.description,
+                owner_id: request.owner_id.or(ncr.owner_id),
+                due_date: request.due_date.or(ncr.due_date),
+            })
+            .returning(CapaAction::as_returning())
+            .get_result(conn)
+            .await?)
+    }
+
+    async fn find_actions(
+        conn: &mut AsyncPgConnection,
+        ncr_ids: &[Uuid],
+    ) -> Result<Vec<CapaAction>> {
+        Ok(capa_actions::table
+            .filter(capa_actions::ncr_id.eq_any(ncr_ids))
+            .order(capa_actions::created_at.asc())
+            .select(CapaAction::as_select())
+            .load::<CapaAction>(conn)
+            .await?)
+    }
+
+    async fn find_overdue(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        owner_id: Option<Uuid>,
+        limit: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<OverdueCapaResponse>> {
+        let mut overdue_query = capa_actions::table
+            .inner_join(non_conformances::table)
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .filter(non_conformances::status.eq(NcrStatus::Open.to_string()))
+            .filter(capa_actions::status.eq_any([
+                CapaStatus::Open.to_string(),
+                CapaStatus::InProgress.to_string(),
+            ]))
+            .filter(capa_actions::due_date.lt(now))
+            .into_boxed();
+
+        if let Some(owner_id) = owner_id {
+            overdue_query = overdue_query.filter(capa_actions::owner_id.eq(owner_id));
+        }
+
+        let overdue = overdue_query
+            .order(capa_actions::due_date.asc())
+            .limit(limit)
+            .select((CapaAction::as_select(), NonConformance::as_select()))
+            .load::<(CapaAction, NonConformance)>(conn)
+            .await?;
+
+        Ok(overdue
+            .into_iter()
+            .map(|(action, ncr)| {
+                let due = action.due_date.unwrap_or(now);
+                OverdueCapaResponse {
+                    ncr_id: ncr.id,
+                    ncr_number: ncr.ncr_number,
+                    ncr_title: ncr.title,
+                    severity: NcrSeverity::try_from(ncr.severity).unwrap_or(NcrSeverity::Minor),
+                    action: CapaActionResponse::new(action, now),
+                    days_overdue: days_overdue(due, now),
+                }
+            })
+            .collect())
+    }
+
+    async fn load_response(
+        conn: &mut AsyncPgConnection,
+        ncr: NonConformance,
+    ) -> Result<NcrResponse> {
+        let mut responses = Self::load_responses(conn, vec![ncr]).await?;
+        responses
+            .pop()
+            .ok_or_else(|| anyhow!("NCR disappeared while loading"))
+    }
+
+    async fn load_responses(
+        conn: &mut AsyncPgConnection,
+        ncrs: Vec<NonConformance>,
+    ) -> Result<Vec<NcrResponse>> {
+        let ncr_ids: Vec<Uuid> = ncrs.iter().map(|n| n.id).collect();
+
+        let mut actions: HashMap<Uuid, Vec<CapaAction>> = HashMap::new();
+        for action in Self::find_actions(conn, &ncr_ids).await? {
+            actions.entry(action.ncr_id).or_default().push(action);
+        }
+
+        let now = Utc::now();
+        Ok(ncrs
+            .into_iter()
+            .map(|ncr| {
+                let ncr_actions = actions.remove(&ncr.id).unwrap_or_default();
+                NcrResponse::new(ncr, ncr_actions, now)
+            })
+            .collect())
+    }
+}
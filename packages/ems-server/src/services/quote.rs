@@ -0,0 +1,817 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    ConvertQuoteRequest, CreateQuoteItemRequest, CreateQuoteRequest, ExternalEntityType,
+    ItemContext, ListQuotesQuery, NewOrder, NewOrderHistory, NewOrderItem, NewQuote, NewQuoteItem,
+    NumberingEntity, Order, OrderStatus, OrderType, Quantity, Quote, QuoteItem, QuotePricing,
+    QuoteResponse, QuoteStatus, RejectQuoteRequest, SendQuoteRequest, UpdateQuoteRequest,
+};
+use crate::schema::*;
+use crate::services::{
+    DatabaseService, EmailAttachment, EmailService, NumberingService, UomService,
+};
+use crate::utils::branding::DocumentBranding;
+use crate::utils::i18n::Locale;
+use crate::utils::price_list::{price_breaks, unit_price_at};
+use crate::utils::quote::{bom_cost, margin_price, quote_email, render_quote_pdf, round_cents};
+use crate::utils::AppError;
+
+/// Errors from [`QuoteService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum QuoteError {
+    #[error("Item not found: {0}")]
+    ItemNotFound(Uuid),
+
+    #[error("Quote cannot be {0}")]
+    NotAllowed(String),
+
+    #[error("Line {0} cannot be priced: {1}")]
+    Unpriceable(i32, String),
+
+    #[error("Email delivery is not configured (SMTP_HOST is not set)")]
+    EmailNotConfigured,
+}
+
+impl From<QuoteError> for AppError {
+    fn from(error: QuoteError) -> Self {
+        match error {
+            QuoteError::ItemNotFound(_) => AppError::Validation(error.to_string()),
+            QuoteError::NotAllowed(_) | QuoteError::Unpriceable(..) => {
+                AppError::Conflict(error.to_string())
+            }
+            QuoteError::EmailNotConfigured => AppError::Unavailable(error.to_string()),
+        }
+    }
+}
+
+pub struct QuoteService {
+    database: DatabaseService,
+    branding: DocumentBranding,
+}
+
+impl QuoteService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database,
+            branding: DocumentBranding::default(),
+        }
+    }
+
+    /// Renders and emails quotes with the tenant's branding.
+    pub fn with_branding(mut self, branding: DocumentBranding) -> Self {
+        self.branding = branding;
+        self
+    }
+
+    // Quote operations
+
+    /// Creates a draft quote for a person of the tenant, pricing every line as it is added.
+    pub async fn create_quote(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateQuoteRequest,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let created = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let customers: i64 = tenant_person::table
+                        .filter(tenant_person::tenant_id.eq(tenant_id))
+                        .filter(tenant_person::person_id.eq(request.customer_id))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if customers == 0 {
+                        return Ok(None);
+                    }
+
+                    // Number the quote from its sequence unless given, falling back to a
+                    // random number when the tenant has none
+                    let quote_number = NumberingService::number_or_else(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::Quote,
+                        request.quote_number,
+                        || {
+                            format!("Q-{}", &Uuid::new_v4().simple().to_string()[..8])
+                                .to_uppercase()
+                        },
+                    )
+                    .await?;
+
+                    let new_quote = NewQuote {
+                        tenant_id,
+                        quote_number,
+                        customer_id: request.customer_id,
+                        valid_until: request.valid_until,
+                        total_amount: 0.0,
+                        notes: request.notes,
+                        created_by_id,
+                    };
+
+                    let quote: Quote = diesel::insert_into(quotes::table)
+                        .values(&new_quote)
+                        .returning(Quote::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    Self::replace_items(conn, tenant_id, quote.id, &request.items)
+                        .await
+                        .map(Some)
+                })
+            })
+            .await?;
+
+        match created {
+            Some(quote) => Self::load_response(&mut conn, quote).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn list_quotes(
+        &self,
+        tenant_id: Uuid,
+        query: ListQuotesQuery,
+    ) -> Result<Vec<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut quotes_query = quotes::table
+            .filter(quotes::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(customer_id) = query.customer_id {
+            quotes_query = quotes_query.filter(quotes::customer_id.eq(customer_id));
+        }
+
+        if let Some(status) = query.status {
+            quotes_query = quotes_query.filter(quotes::status.eq(status.to_string()));
+        }
+
+        let quotes = quotes_query
+            .order(quotes::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(Quote::as_select())
+            .load::<Quote>(&mut conn)
+            .await?;
+
+        Self::load_responses(&mut conn, quotes).await
+    }
+
+    pub async fn get_quote(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(quote) = quotes::table
+            .filter(quotes::id.eq(quote_id))
+            .filter(quotes::tenant_id.eq(tenant_id))
+            .select(Quote::as_select())
+            .first::<Quote>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        Self::load_response(&mut conn, quote).await.map(Some)
+    }
+
+    /// Edits a draft quote. Replacing the lines prices them again from current item data.
+    pub async fn update_quote(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+        request: UpdateQuoteRequest,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(quote) = Self::lock_quote(conn, tenant_id, quote_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&quote, &[QuoteStatus::Draft], "updated")?;
+
+                    let mut quote = diesel::update(quotes::table.find(quote.id))
+                        .set((
+                            quotes::valid_until.eq(request.valid_until.or(quote.valid_until)),
+                            quotes::notes.eq(request.notes.or(quote.notes)),
+                        ))
+                        .returning(Quote::as_returning())
+                        .get_result::<Quote>(conn)
+                        .await?;
+
+                    if let Some(items) = &request.items {
+                        quote = Self::replace_items(conn, tenant_id, quote.id, items).await?;
+                    }
+
+                    Ok(Some(quote))
+                })
+            })
+            .await?;
+
+        match updated {
+            Some(quote) => Self::load_response(&mut conn, quote).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Renders the quote document in `locale`, returning the quote number alongside for the
+    /// file name.
+    pub async fn quote_pdf(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+        locale: Locale,
+    ) -> Result<Option<(String, Vec<u8>)>> {
+        Ok(self.get_quote(tenant_id, quote_id).await?.map(|quote| {
+            (
+                quote.quote_number.clone(),
+                render_quote_pdf(&quote, locale, &self.branding),
+            )
+        }))
+    }
+
+    /// Emails the quote PDF to the customer, or to the given recipients, and marks a draft as
+    /// sent. A sent quote can be sent again, e.g. after the customer lost it. The email and PDF
+    /// are written in the request's locale, or `default_locale` when it names none.
+    pub async fn send_quote(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+        request: SendQuoteRequest,
+        email: Option<&EmailService>,
+        default_locale: Locale,
+    ) -> Result<Option<QuoteResponse>> {
+        let Some(email) = email else {
+            return Err(QuoteError::EmailNotConfigured.into());
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(quote) = quotes::table
+            .filter(quotes::id.eq(quote_id))
+            .filter(quotes::tenant_id.eq(tenant_id))
+            .select(Quote::as_select())
+            .first::<Quote>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        Self::require_status(&quote, &[QuoteStatus::Draft, QuoteStatus::Sent], "sent")?;
+
+        let recipients = match request.recipients.filter(|r| !r.is_empty()) {
+            Some(recipients) => recipients,
+            None => vec![
+                person::table
+                    .filter(person::id.eq(quote.customer_id))
+                    .select(person::email)
+                    .first::<String>(&mut conn)
+                    .await?,
+            ],
+        };
+
+        let locale = request.locale.unwrap_or(default_locale);
+        let response = Self::load_response(&mut conn, quote).await?;
+        let attachment = EmailAttachment {
+            filename: format!("{}.pdf", response.quote_number),
+            content_type: "application/pdf".to_string(),
+            content: render_quote_pdf(&response, locale, &self.branding),
+        };
+        let (subject, body) = quote_email(&response, request.message.as_deref(), locale);
+
+        email
+            .send_branded(
+                &recipients,
+                &subject,
+                &body,
+                vec![attachment],
+                &self.branding,
+            )
+            .await?;
+
+        let quote = diesel::update(quotes::table.find(response.id))
+            .set((
+                quotes::status.eq(QuoteStatus::Sent.to_string()),
+                quotes::sent_at.eq(Some(Utc::now())),
+            ))
+            .returning(Quote::as_returning())
+            .get_result::<Quote>(&mut conn)
+            .await?;
+
+        Self::load_response(&mut conn, quote).await.map(Some)
+    }
+
+    /// Records the customer accepting a sent quote. Expired quotes cannot be accepted.
+    pub async fn accept_quote(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let accepted = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(quote) = Self::lock_quote(conn, tenant_id, quote_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&quote, &[QuoteStatus::Sent], "accepted")?;
+
+                    let now = Utc::now();
+                    if let Some(valid_until) = quote.valid_until.filter(|until| *until < now) {
+                        return Err(QuoteError::NotAllowed(format!(
+                            "accepted: expired on {}",
+                            valid_until.format("%Y-%m-%d")
+                        ))
+                        .into());
+                    }
+
+                    Ok(Some(
+                        diesel::update(quotes::table.find(quote.id))
+                            .set((
+                                quotes::status.eq(QuoteStatus::Accepted.to_string()),
+                                quotes::responded_at.eq(Some(now)),
+                            ))
+                            .returning(Quote::as_returning())
+                            .get_result::<Quote>(conn)
+                            .await?,
+                    ))
+                })
+            })
+            .await?;
+
+        match accepted {
+            Some(quote) => Self::load_response(&mut conn, quote).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn reject_quote(
+        &self,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+        request: RejectQuoteRequest,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let rejected = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(quote) = Self::lock_quote(conn, tenant_id, quote_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&quote, &[QuoteStatus::Sent], "rejected")?;
+
+                    Ok(Some(
+                        diesel::update(quotes::table.find(quote.id))
+                            .set((
+                                quotes::status.eq(QuoteStatus::Rejected.to_string()),
+                                quotes::responded_at.eq(Some(Utc::now())),
+                                quotes::rejection_reason.eq(request.reason),
+                            ))
+                            .returning(Quote::as_returning())
+                            .get_result::<Quote>(conn)
+                            .await?,
+                    ))
+                })
+            })
+            .await?;
+
+        match rejected {
+            Some(quote) => Self::load_response(&mut conn, quote).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Turns an accepted quote into a draft customer order with the quoted lines and prices.
+    /// The order's metadata and the quote's `order_id` link the two.
+    pub async fn convert_quote(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        quote_id: Uuid,
+        request: ConvertQuoteRequest,
+    ) -> Result<Option<QuoteResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let converted = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(quote) = Self::lock_quote(conn, tenant_id, quote_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&quote, &[QuoteStatus::Accepted], "converted")?;
+
+                    let order_number = NumberingService::number_or_else(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::CustomerOrder,
+                        request.order_number,
+                        || format!("SO-{}", quote.quote_number),
+                    )
+                    .await?;
+
+                    let new_order = NewOrder {
+                        tenant_id,
+                        order_number,
+                        order_type: OrderType::CustomerOrder.to_string(),
+                        external_entity_id: quote.customer_id,
+                        external_entity_type: ExternalEntityType::Customer.to_string(),
+                        order_date: Utc::now(),
+                        total_amount: quote.total_amount,
+                        status: OrderStatus::Draft.to_string(),
+                        created_by_id: person_id,
+                        notes: quote.notes.clone(),
+                        metadata: Some(serde_json::json!({
+                            "quote_id": quote.id,
+                            "quote_number": quote.quote_number,
+                        })),
+                    };
+
+                    let order: Order = diesel::insert_into(orders::table)
+                        .values(&new_order)
+                        .returning(Order::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let new_items: Vec<NewOrderItem> = Self::find_items(conn, quote.id)
+                        .await?
+                        .into_iter()
+                        .map(|item| NewOrderItem {
+                            order_id: order.id,
+                            item_id: item.item_id,
+                            item_name: item.item_name,
+                            item_description: item.item_description,
+                            quantity: item.quantity,
+                            unit_price: item.unit_price,
+                            extended_price: item.extended_price,
+                            notes: item.notes,
+                            expected_date: None,
+                            uom_id: None,
+                            base_quantity: item.quantity,
+                        })
+                        .collect();
+
+                    diesel::insert_into(order_items::table)
+                        .values(&new_items)
+                        .execute(conn)
+                        .await?;
+
+                    diesel::insert_into(order_history::table)
+                        .values(&NewOrderHistory {
+                            order_id: order.id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "create".to_string(),
+                            previous_status: None,
+                            new_status: Some(order.status.clone()),
+                            notes: Some(format!("Order created from quote {}", quote.quote_number)),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(
+                        diesel::update(quotes::table.find(quote.id))
+                            .set((
+                                quotes::status.eq(QuoteStatus::Converted.to_string()),
+                                quotes::order_id.eq(Some(order.id)),
+                            ))
+                            .returning(Quote::as_returning())
+                            .get_result::<Quote>(conn)
+                            .await?,
+                    ))
+                })
+            })
+            .await?;
+
+        match converted {
+            Some(quote) => Self::load_response(&mut conn, quote).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Private helper methods
+
+    async fn lock_quote(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+    ) -> Result<Option<Quote>> {
+        Ok(quotes::table
+            .filter(quotes::id.eq(quote_id))
+            .filter(quotes::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(Quote::as_select())
+            .first::<Quote>(conn)
+            .await
+            .optional()?)
+    }
+
+    fn require_status(quote: &Quote, allowed: &[QuoteStatus], action: &str) -> Result<()> {
+        let status = QuoteStatus::try_from(quote.status.clone()).map_err(|e| anyhow!(e))?;
+        if allowed.contains(&status) {
+            Ok(())
+        } else {
+            Err(QuoteError::NotAllowed(format!("{}: status is {}", action, status)).into())
+        }
+    }
+
+    async fn find_items(conn: &mut AsyncPgConnection, quote_id: Uuid) -> Result<Vec<QuoteItem>> {
+        Ok(quote_items::table
+            .filter(quote_items::quote_id.eq(quote_id))
+            .order(quote_items::line_number.asc())
+            .select(QuoteItem::as_select())
+            .load::<QuoteItem>(conn)
+            .await?)
+    }
+
+    /// Prices `items` and stores them as the quote's lines in place of any existing ones, then
+    /// updates the quote total.
+    async fn replace_items(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        quote_id: Uuid,
+        items: &[CreateQuoteItemRequest],
+    ) -> Result<Quote> {
+        let item_ids: Vec<Uuid> = items.iter().filter_map(|i| i.item_id).collect();
+        let item_details: HashMap<Uuid, (String, Option<String>)> = items::table
+            .filter(items::id.eq_any(&item_ids))
+            .select((items::id, items::internal_part_number, items::description))
+            .load::<(Uuid, String, Option<String>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(id, part_number, description)| (id, (part_number, description)))
+            .collect();
+
+        let mut new_items = Vec::with_capacity(items.len());
+        for (index, request) in items.iter().enumerate() {
+            let line_number = index as i32 + 1;
+            let details = match request.item_id {
+                Some(item_id) => Some(
+                    item_details
+                        .get(&item_id)
+                        .ok_or(QuoteError::ItemNotFound(item_id))?,
+                ),
+                None => None,
+            };
+
+            let pricing = request.pricing();
+            let (unit_price, unit_cost) = match (pricing, request.item_id) {
+                (QuotePricing::Item, Some(item_id)) => {
+                    let price = Self::selling_price(conn, tenant_id, item_id, request.quantity)
+                        .await?
+                        .ok_or_else(|| {
+                            QuoteError::Unpriceable(
+                                line_number,
+                                "item has no finished goods price".to_string(),
+                            )
+                        })?;
+                    (price, None)
+                }
+                (QuotePricing::BomCost, Some(item_id)) => {
+                    let cost = Self::bom_unit_cost(conn, tenant_id, item_id, request.quantity)
+                        .await?
+                        .map_err(|e| QuoteError::Unpriceable(line_number, e.to_string()))?;
+                    let margin = request.margin_percent.unwrap_or(0.0);
+                    (margin_price(cost, margin), Some(round_cents(cost)))
+                }
+                _ => (request.unit_price.unwrap_or(0.0), None),
+            };
+
+            new_items.push(NewQuoteItem {
+                tenant_id,
+                quote_id,
+                line_number,
+                item_id: request.item_id,
+                item_name: request
+                    .item_name
+                    .clone()
+                    .or_else(|| details.map(|(part_number, _)| part_number.clone()))
+                    .unwrap_or_default(),
+                item_description: request
+                    .item_description
+                    .clone()
+                    .or_else(|| details.and_then(|(_, description)| description.clone())),
+                quantity: request.quantity,
+                unit_price,
+                extended_price: round_cents(unit_price * request.quantity as f64),
+                unit_cost,
+                margin_percent: request.margin_percent,
+                pricing_source: pricing.to_string(),
+                notes: request.notes.clone(),
+            });
+        }
+
+        diesel::delete(quote_items::table.filter(quote_items::quote_id.eq(quote_id)))
+            .execute(conn)
+            .await?;
+        diesel::insert_into(quote_items::table)
+            .values(&new_items)
+            .execute(conn)
+            .await?;
+
+        let total = round_cents(new_items.iter().map(|i| i.extended_price).sum());
+        Ok(diesel::update(quotes::table.find(quote_id))
+            .set(quotes::total_amount.eq(total))
+            .returning(Quote::as_returning())
+            .get_result::<Quote>(conn)
+            .await?)
+    }
+
+    /// Finished goods selling price of `item_id` at the price break `quantity` reaches.
+    async fn selling_price(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        quantity: i32,
+    ) -> Result<Option<f64>> {
+        let pricing = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .filter(inventory_items::context.eq(ItemContext::FinishedGoods.to_string()))
+            .filter(inventory_items::pricing.is_not_null())
+            .select(inventory_items::pricing)
+            .load::<Option<serde_json::Value>>(conn)
+            .await?;
+
+        Ok(pricing
+            .iter()
+            .find_map(|p| unit_price_at(&price_breaks(p.as_ref()), quantity)))
+    }
+
+    /// Rolls up the unit cost of `item_id` from its BOM. Required components are costed from
+    /// vendor pricing, falling back to store pricing, at the break the quoted quantity reaches.
+    /// Component quantities are converted to base units, the unit pricing is per.
+    /// The outer result carries database errors and the inner one pricing problems.
+    pub(crate) async fn bom_unit_cost(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        quantity: i32,
+    ) -> Result<std::result::Result<f64, String>> {
+        let mut boms: HashMap<Uuid, Vec<(Uuid, f64)>> = HashMap::new();
+        let mut seen: HashSet<Uuid> = HashSet::from([item_id]);
+        let mut frontier = vec![item_id];
+
+        while !frontier.is_empty() {
+            let rows = item_bom::table
+                .filter(item_bom::tenant_id.eq(tenant_id))
+                .filter(item_bom::parent_item_id.eq_any(&frontier))
+                .filter(item_bom::is_optional.is_distinct_from(true))
+                .select((
+                    item_bom::parent_item_id,
+                    item_bom::component_item_id,
+                    item_bom::quantity,
+                    item_bom::uom_id,
+                ))
+                .load::<(Uuid, Uuid, Option<Quantity>, Option<Uuid>)>(conn)
+                .await?;
+
+            frontier.clear();
+            for (parent_id, component_id, component_quantity, uom_id) in rows {
+                let factor = match uom_id {
+                    Some(uom_id) => {
+                        UomService::base_factor(conn, tenant_id, component_id, uom_id).await?
+                    }
+                    None => 1.0,
+                };
+                boms.entry(parent_id).or_default().push((
+                    component_id,
+                    component_quantity.map_or(1.0, Quantity::to_f64) * factor,
+                ));
+                if seen.insert(component_id) {
+                    frontier.push(component_id);
+                }
+            }
+        }
+
+        let component_ids: Vec<Uuid> = seen
+            .into_iter()
+            .filter(|id| !boms.contains_key(id))
+            .collect();
+        let mut vendor_costs: HashMap<Uuid, f64> = HashMap::new();
+        let mut store_costs: HashMap<Uuid, f64> = HashMap::new();
+        for (component_id, context, pricing) in inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq_any(&component_ids))
+            .filter(inventory_items::context.eq_any([
+                ItemContext::Vendor.to_string(),
+                ItemContext::Store.to_string(),
+            ]))
+            .select((
+                inventory_items::item_id,
+                inventory_items::context,
+                inventory_items::pricing,
+            ))
+            .load::<(Uuid, String, Option<serde_json::Value>)>(conn)
+            .await?
+        {
+            let Some(cost) = unit_price_at(&price_breaks(pricing.as_ref()), quantity) else {
+                continue;
+            };
+            let costs = if context == ItemContext::Vendor.to_string() {
+                &mut vendor_costs
+            } else {
+                &mut store_costs
+            };
+            costs.entry(component_id).or_insert(cost);
+        }
+        for (component_id, cost) in store_costs {
+            vendor_costs.entry(component_id).or_insert(cost);
+        }
+
+        Ok(bom_cost(item_id, &boms, &vendor_costs))
+    }
+
+    async fn load_response(conn: &mut AsyncPgConnection, quote: Quote) -> Result<QuoteResponse> {
+        let mut responses = Self::load_responses(conn, vec![quote]).await?;
+        responses
+            .pop()
+            .ok_or_else(|| anyhow!("Quote disappeared while loading"))
+    }
+
+    async fn load_responses(
+        conn: &mut AsyncPgConnection,
+        quotes: Vec<Quote>,
+    ) -> Result<Vec<QuoteResponse>> {
+        let quote_ids: Vec<Uuid> = quotes.iter().map(|q| q.id).collect();
+        let customer_ids: Vec<Uuid> = quotes.iter().map(|q| q.customer_id).collect();
+
+        let customer_names: HashMap<Uuid, String> = person::table
+            .filter(person::id.eq_any(&customer_ids))
+            .select((person::id, person::name))
+            .load::<(Uuid, String)>(conn)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut items: HashMap<Uuid, Vec<QuoteItem>> = HashMap::new();
+        for item in quote_items::table
+            .filter(quote_items::quote_id.eq_any(&quote_ids))
+            .order(quote_items::line_number.asc())
+            .select(QuoteItem::as_select())
+            .load::<QuoteItem>(conn)
+            .await?
+        {
+            items.entry(item.quote_id).or_default().push(item);
+        }
+
+        Ok(quotes
+            .into_iter()
+            .map(|quote| {
+                let customer_name = customer_names
+                    .get(&quote.customer_id)
+                    .cloned()
+                    .unwrap_or_default();
+                let quote_items = items.remove(&quote.id).unwrap_or_default();
+                QuoteResponse::new(quote, customer_name, quote_items)
+            })
+            .collect())
+    }
+}
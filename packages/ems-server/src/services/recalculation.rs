@@ -0,0 +1,503 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Text, Uuid as SqlUuid};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::json;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, CreateRecalculationRequest, DemandForecastQuery, ListRecalculationsQuery,
+    NewRecalculationTask, OrderStatus, Permission, RecalculationKind, RecalculationTask,
+    RecalculationTaskResponse, RecalculationTaskStatus,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, QuoteService, StockService};
+use crate::utils::quote::round_cents;
+use crate::utils::recalculation::with_unit_cost;
+use crate::utils::AppError;
+
+/// Errors from [`RecalculationService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum RecalculationError {
+    #[error("Recalculation task {0} cannot be cancelled once {1}")]
+    NotCancellable(Uuid, String),
+}
+
+impl From<RecalculationError> for AppError {
+    fn from(error: RecalculationError) -> Self {
+        match error {
+            RecalculationError::NotCancellable(..) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// Records recalculated between progress updates and cancellation checks
+const RECALCULATION_BATCH_SIZE: usize = 50;
+// A task left running this long without progress (e.g. the server stopped) is claimed again
+const STALE_RUNNING_MINUTES: i64 = 10;
+
+/// Tenant-wide recalculations, queued by admins after costing or BOM changes and run by the
+/// recalculation worker in batches.
+pub struct RecalculationService {
+    database: DatabaseService,
+}
+
+impl RecalculationService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Queues a recalculation. Only one task of a kind can be queued or running per tenant;
+    /// a second one fails on the unique index.
+    pub async fn enqueue(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateRecalculationRequest,
+    ) -> Result<RecalculationTaskResponse> {
+        caller.require(Permission::RunRecalculations)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let task = diesel::insert_into(recalculation_tasks::table)
+            .values(&NewRecalculationTask {
+                tenant_id,
+                kind: request.kind.to_string(),
+                requested_by_id: Some(caller.person_id),
+            })
+            .returning(RecalculationTask::as_returning())
+            .get_result::<RecalculationTask>(&mut conn)
+            .await?;
+
+        Ok(task.into())
+    }
+
+    /// The tenant's recalculations, newest first.
+    pub async fn list(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        query: ListRecalculationsQuery,
+    ) -> Result<Vec<RecalculationTaskResponse>> {
+        caller.require(Permission::RunRecalculations)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut tasks_query = recalculation_tasks::table
+            .filter(recalculation_tasks::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(kind) = query.kind {
+            tasks_query = tasks_query.filter(recalculation_tasks::kind.eq(kind.to_string()));
+        }
+        if let Some(status) = query.status {
+            tasks_query = tasks_query.filter(recalculation_tasks::status.eq(status.to_string()));
+        }
+
+        let tasks = tasks_query
+            .order(recalculation_tasks::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(RecalculationTask::as_select())
+            .load::<RecalculationTask>(&mut conn)
+            .await?;
+
+        Ok(tasks.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        task_id: Uuid,
+    ) -> Result<Option<RecalculationTaskResponse>> {
+        caller.require(Permission::RunRecalculations)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let task = recalculation_tasks::table
+            .filter(recalculation_tasks::tenant_id.eq(tenant_id))
+            .filter(recalculation_tasks::id.eq(task_id))
+            .select(RecalculationTask::as_select())
+            .first::<RecalculationTask>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(task.map(Into::into))
+    }
+
+    /// Cancels a task. A queued task is cancelled at once; a running one stops after its
+    /// current batch, keeping the records already recalculated.
+    pub async fn cancel(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        task_id: Uuid,
+    ) -> Result<Option<RecalculationTaskResponse>> {
+        caller.require(Permission::RunRecalculations)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let task = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(task) = recalculation_tasks::table
+                        .filter(recalculation_tasks::tenant_id.eq(tenant_id))
+                        .filter(recalculation_tasks::id.eq(task_id))
+                        .for_update()
+                        .select(RecalculationTask::as_select())
+                        .first::<RecalculationTask>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let target =
+                        recalculation_tasks::table.filter(recalculation_tasks::id.eq(task.id));
+                    let task = match RecalculationTaskStatus::try_from(task.status.clone()) {
+                        Ok(RecalculationTaskStatus::Queued) => {
+                            diesel::update(target)
+                                .set((
+                                    recalculation_tasks::status
+                                        .eq(RecalculationTaskStatus::Cancelled.to_string()),
+                                    recalculation_tasks::cancel_requested.eq(true),
+                                    recalculation_tasks::finished_at.eq(Some(Utc::now())),
+                                ))
+                                .returning(RecalculationTask::as_returning())
+                                .get_result::<RecalculationTask>(conn)
+                                .await?
+                        }
+                        Ok(RecalculationTaskStatus::Running) => {
+                            diesel::update(target)
+                                .set(recalculation_tasks::cancel_requested.eq(true))
+                                .returning(RecalculationTask::as_returning())
+                                .get_result::<RecalculationTask>(conn)
+                                .await?
+                        }
+                        _ => {
+                            return Err(
+                                RecalculationError::NotCancellable(task.id, task.status).into()
+                            )
+                        }
+                    };
+                    Ok(Some(task))
+                })
+            })
+            .await?;
+
+        Ok(task.map(Into::into))
+    }
+
+    // Worker operations
+
+    /// Claims queued tasks across all tenants, and running ones that stopped making progress,
+    /// marking them as running so concurrent workers skip them.
+    pub async fn claim_due(&self, limit: i64) -> Result<Vec<RecalculationTask>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // The recalculation worker works across tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let now = Utc::now();
+        let stale_before = now - ChronoDuration::minutes(STALE_RUNNING_MINUTES);
+        let tasks = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let due = recalculation_tasks::table
+                        .filter(
+                            recalculation_tasks::status
+                                .eq(RecalculationTaskStatus::Queued.to_string())
+                                .or(recalculation_tasks::status
+                                    .eq(RecalculationTaskStatus::Running.to_string())
+                                    .and(recalculation_tasks::updated_at.lt(stale_before))),
+                        )
+                        .order(recalculation_tasks::created_at.asc())
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .select(RecalculationTask::as_select())
+                        .load::<RecalculationTask>(conn)
+                        .await?;
+
+                    let mut claimed = Vec::with_capacity(due.len());
+                    for task in due {
+                        let task = diesel::update(
+                            recalculation_tasks::table.filter(recalculation_tasks::id.eq(task.id)),
+                        )
+                        .set((
+                            recalculation_tasks::status
+                                .eq(RecalculationTaskStatus::Running.to_string()),
+                            recalculation_tasks::started_at.eq(task.started_at.or(Some(now))),
+                        ))
+                        .returning(RecalculationTask::as_returning())
+                        .get_result::<RecalculationTask>(conn)
+                        .await?;
+                        claimed.push(task);
+                    }
+
+                    Ok(claimed)
+                })
+            })
+            .await?;
+
+        Ok(tasks)
+    }
+
+    /// Runs a claimed task to the end, recording progress after every batch. A reclaimed task
+    /// resumes after the records it already went through. Failures are recorded on the task;
+    /// only bookkeeping errors are returned.
+    pub async fn run(&self, task: &RecalculationTask) -> Result<RecalculationTaskStatus> {
+        let kind = RecalculationKind::try_from(task.kind.clone()).map_err(|e| anyhow!(e))?;
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", task.tenant_id))
+            .await?;
+
+        let outcome = self.recalculate(&mut conn, task, kind).await;
+        let (status, error) = match outcome {
+            Ok(status) => (status, None),
+            Err(e) => {
+                tracing::warn!("Recalculation task {} failed: {}", task.id, e);
+                (RecalculationTaskStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        diesel::update(recalculation_tasks::table.filter(recalculation_tasks::id.eq(task.id)))
+            .set((
+                recalculation_tasks::status.eq(status.to_string()),
+                recalculation_tasks::error.eq(error),
+                recalculation_tasks::finished_at.eq(Some(Utc::now())),
+            ))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(status)
+    }
+
+    // Goes through the task's records in batches, stopping early when cancellation is requested
+    async fn recalculate(
+        &self,
+        conn: &mut AsyncPgConnection,
+        task: &RecalculationTask,
+        kind: RecalculationKind,
+    ) -> Result<RecalculationTaskStatus> {
+        // Reclaimed after cancellation was requested
+        if task.cancel_requested {
+            return Ok(RecalculationTaskStatus::Cancelled);
+        }
+
+        let targets = Self::targets(conn, task.tenant_id, kind).await?;
+        let total = targets.len() as i32;
+        let mut processed = task.processed.min(total);
+
+        diesel::update(recalculation_tasks::table.filter(recalculation_tasks::id.eq(task.id)))
+            .set(recalculation_tasks::total.eq(Some(total)))
+            .execute(conn)
+            .await?;
+
+        for batch in targets[processed as usize..].chunks(RECALCULATION_BATCH_SIZE) {
+            processed += batch.len() as i32;
+
+            // A batch commits with its progress, so a reclaimed task resumes after the last
+            // batch that landed rather than part way through one
+            let cancel_requested = conn
+                .transaction::<_, anyhow::Error, _>(|conn| {
+                    Box::pin(async move {
+                        let mut updated = 0;
+                        for target_id in batch {
+                            updated += match kind {
+                                RecalculationKind::InventoryValuation => {
+                                    Self::revalue_item(conn, task.tenant_id, *target_id).await?
+                                }
+                                RecalculationKind::OrderTotals => {
+                                    Self::retotal_order(conn, task.tenant_id, *target_id).await?
+                                }
+                                RecalculationKind::ForecastRefresh => {
+                                    self.refresh_forecast(conn, task.tenant_id, *target_id)
+                                        .await?
+                                }
+                            };
+                        }
+
+                        let cancel_requested = diesel::update(
+                            recalculation_tasks::table.filter(recalculation_tasks::id.eq(task.id)),
+                        )
+                        .set((
+                            recalculation_tasks::processed.eq(processed),
+                            recalculation_tasks::updated_records
+                                .eq(recalculation_tasks::updated_records + updated as i32),
+                        ))
+                        .returning(recalculation_tasks::cancel_requested)
+                        .get_result::<bool>(conn)
+                        .await?;
+                        Ok(cancel_requested)
+                    })
+                })
+                .await?;
+            if cancel_requested {
+                return Ok(RecalculationTaskStatus::Cancelled);
+            }
+        }
+
+        Ok(RecalculationTaskStatus::Completed)
+    }
+
+    // Records the task goes through, in a stable order so a reclaimed task can resume
+    async fn targets(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        kind: RecalculationKind,
+    ) -> Result<Vec<Uuid>> {
+        let targets = match kind {
+            // Items with a BOM, whose cost rolls up from their components
+            RecalculationKind::InventoryValuation => {
+                item_bom::table
+                    .filter(item_bom::tenant_id.eq(tenant_id))
+                    .select(item_bom::parent_item_id)
+                    .distinct()
+                    .order(item_bom::parent_item_id.asc())
+                    .load::<Uuid>(conn)
+                    .await?
+            }
+            // Orders that can still change
+            RecalculationKind::OrderTotals => {
+                let closed: Vec<String> =
+                    OrderStatus::CLOSED.iter().map(|s| s.to_string()).collect();
+                orders::table
+                    .filter(orders::tenant_id.eq(tenant_id))
+                    .filter(orders::status.ne_all(closed))
+                    .select(orders::id)
+                    .order(orders::id.asc())
+                    .load::<Uuid>(conn)
+                    .await?
+            }
+            // Stocked items
+            RecalculationKind::ForecastRefresh => {
+                inventory_items::table
+                    .filter(inventory_items::tenant_id.eq(tenant_id))
+                    .select(inventory_items::item_id)
+                    .distinct()
+                    .order(inventory_items::item_id.asc())
+                    .load::<Uuid>(conn)
+                    .await?
+            }
+        };
+        Ok(targets)
+    }
+
+    // Sets pricing.unit_cost of the item's inventory records to its rolled up BOM cost. Items
+    // whose components cannot be costed are left as they are. Each recalculation returns 1 when
+    // the record's values changed.
+    async fn revalue_item(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<usize> {
+        let cost = match QuoteService::bom_unit_cost(conn, tenant_id, item_id, 1).await? {
+            Ok(cost) => round_cents(cost),
+            Err(_) => return Ok(0),
+        };
+
+        let records = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .select((inventory_items::id, inventory_items::pricing))
+            .load::<(Uuid, Option<serde_json::Value>)>(conn)
+            .await?;
+
+        let mut updated = 0;
+        for (id, pricing) in records {
+            let Some(pricing) = with_unit_cost(pricing.as_ref(), cost) else {
+                continue;
+            };
+            updated += diesel::update(inventory_items::table.filter(inventory_items::id.eq(id)))
+                .set(inventory_items::pricing.eq(Some(pricing)))
+                .execute(conn)
+                .await?;
+        }
+        Ok(usize::from(updated > 0))
+    }
+
+    // Recomputes the order's line totals and, for orders with lines, the order total
+    async fn retotal_order(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        order_id: Uuid,
+    ) -> Result<usize> {
+        let lines = diesel::sql_query(
+            "UPDATE order_items SET extended_price = quantity * unit_price \
+             WHERE order_id = $1 AND extended_price <> quantity * unit_price",
+        )
+        .bind::<SqlUuid, _>(order_id)
+        .execute(conn)
+        .await?;
+
+        let order = diesel::sql_query(
+            "UPDATE orders o SET total_amount = t.total \
+             FROM (SELECT SUM(extended_price) AS total FROM order_items WHERE order_id = $1) t \
+             WHERE o.id = $1 AND o.tenant_id = $2 AND t.total IS NOT NULL \
+             AND o.total_amount <> t.total",
+        )
+        .bind::<SqlUuid, _>(order_id)
+        .bind::<SqlUuid, _>(tenant_id)
+        .execute(conn)
+        .await?;
+
+        Ok(usize::from(lines + order > 0))
+    }
+
+    // Stores the item's demand forecast with its reorder suggestion under metadata.forecast
+    async fn refresh_forecast(
+        &self,
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<usize> {
+        let Some(forecast) = StockService::new(self.database.clone())
+            .forecast_demand(tenant_id, item_id, DemandForecastQuery::default())
+            .await?
+        else {
+            return Ok(0);
+        };
+
+        let summary = json!({
+            "method": forecast.method,
+            "next_month": forecast.forecast.iter().map(|m| m.quantity).next(),
+            "safety_stock": forecast.safety_stock,
+            "reorder_point": forecast.reorder_point,
+            "suggested_reorder_quantity": forecast.suggested_reorder_quantity,
+            "refreshed_at": Utc::now(),
+        });
+        let updated = diesel::sql_query(
+            "UPDATE inventory_items \
+             SET metadata = COALESCE(metadata, '{}'::jsonb) || jsonb_build_object('forecast', $3::jsonb) \
+             WHERE tenant_id = $1 AND item_id = $2",
+        )
+        .bind::<SqlUuid, _>(tenant_id)
+        .bind::<SqlUuid, _>(item_id)
+        .bind::<Text, _>(summary.to_string())
+        .execute(conn)
+        .await?;
+
+        Ok(usize::from(updated > 0))
+    }
+}
@@ -0,0 +1,713 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::sql_types::{Double, Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::{
+    ConsignmentBalanceRow, InventoryValuationRow, JobMarginRow, MachineDowntimeRow,
+    OpenOrderBookRow, ReportDefinition, ReportKind, ReportParameter, ReportParameterType,
+    ReportParameters, ReportResult,
+};
+use crate::services::DatabaseService;
+use crate::utils::csv::csv_escape;
+
+// Order statuses that still count as open in the order book
+const OPEN_ORDER_STATUSES: &str = "'draft', 'submitted', 'approved', 'partially_fulfilled'";
+
+// Machine statuses that count as downtime
+const DOWNTIME_STATUSES: &str = "'offline', 'error', 'maintenance'";
+
+// Default look-back window for date-ranged reports when no `from` is supplied
+const DEFAULT_WINDOW_DAYS: i64 = 30;
+
+pub struct ReportService {
+    database: DatabaseService,
+}
+
+impl ReportService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Report registry
+
+    pub fn list_reports() -> Vec<ReportDefinition> {
+        ReportKind::all()
+            .into_iter()
+            .map(Self::definition)
+            .collect()
+    }
+
+    pub fn find_report(key: &str) -> Option<ReportDefinition> {
+        ReportKind::try_from(key.to_string())
+            .ok()
+            .map(Self::definition)
+    }
+
+    pub fn definition(kind: ReportKind) -> ReportDefinition {
+        match kind {
+            ReportKind::InventoryValuation => ReportDefinition {
+                key: kind,
+                name: "Inventory valuation".to_string(),
+                description: "On-hand quantity and value per inventory record. Consignment stock owned by vendors or customers is counted but not valued. Unit cost is read from pricing.unit_cost, falling back to pricing.unit_price.".to_string(),
+                parameters: vec![
+                    enum_parameter(
+                        "context",
+                        "Restrict to one inventory context",
+                        &["finished_goods", "store", "vendor"],
+                    ),
+                    parameter(
+                        "category",
+                        ReportParameterType::String,
+                        false,
+                        "Restrict to one item category",
+                    ),
+                ],
+                columns: columns(&[
+                    "inventory_item_id",
+                    "item_id",
+                    "internal_part_number",
+                    "manufacturer",
+                    "category",
+                    "context",
+                    "location",
+                    "quantity",
+                    "consigned_quantity",
+                    "unit_cost",
+                    "total_value",
+                ]),
+            },
+            ReportKind::OpenOrderBook => ReportDefinition {
+                key: kind,
+                name: "Open order book".to_string(),
+                description: "Orders that are not yet fulfilled, cancelled or paid, with line totals and age.".to_string(),
+                parameters: vec![
+                    enum_parameter(
+                        "order_type",
+                        "Restrict to one order type",
+                        &["purchase_order", "customer_order", "distributor_order"],
+                    ),
+                    parameter(
+                        "as_of",
+                        ReportParameterType::Date,
+                        false,
+                        "Only include orders placed before this point in time (defaults to now)",
+                    ),
+                ],
+                columns: columns(&[
+                    "order_id",
+                    "order_number",
+                    "order_type",
+                    "status",
+                    "order_date",
+                    "external_entity_id",
+                    "external_entity_type",
+                    "line_count",
+                    "total_quantity",
+                    "total_amount",
+                    "age_days",
+                ]),
+            },
+            ReportKind::MachineDowntime => ReportDefinition {
+                key: kind,
+                name: "Machine downtime".to_string(),
+                description: "Hours each machine spent offline, in error or in maintenance within the window, derived from the machine status history.".to_string(),
+                parameters: vec![
+                    parameter(
+                        "from",
+                        ReportParameterType::Date,
+                        false,
+                        "Start of the window (defaults to 30 days before `to`)",
+                    ),
+                    parameter(
+                        "to",
+                        ReportParameterType::Date,
+                        false,
+                        "End of the window, exclusive (defaults to now)",
+                    ),
+                ],
+                columns: columns(&[
+                    "machine_id",
+                    "machine_name",
+                    "current_status",
+                    "offline_hours",
+                    "error_hours",
+                    "maintenance_hours",
+                    "downtime_hours",
+                    "downtime_events",
+                    "availability_percent",
+                ]),
+            },
+            ReportKind::JobMargin => ReportDefinition {
+                key: kind,
+                name: "Job margin".to_string(),
+                description: "Revenue against labor and material cost for completed jobs. Revenue uses the finished goods unit price; materials_consumed entries ({item_id, quantity}) are costed at the store unit cost.".to_string(),
+                parameters: vec![
+                    parameter(
+                        "from",
+                        ReportParameterType::Date,
+                        false,
+                        "Only include jobs completed on or after this date (defaults to 30 days before `to`)",
+                    ),
+                    parameter(
+                        "to",
+                        ReportParameterType::Date,
+                        false,
+                        "Only include jobs completed before this date (defaults to now)",
+                    ),
+                    parameter(
+                        "labor_rate",
+                        ReportParameterType::Number,
+                        false,
+                        "Hourly labor rate used to cost labor_hours (defaults to 0)",
+                    ),
+                ],
+                columns: columns(&[
+                    "job_id",
+                    "job_number",
+                    "job_type",
+                    "item_id",
+                    "quantity",
+                    "completed_at",
+                    "revenue",
+                    "labor_hours",
+                    "labor_cost",
+                    "material_cost",
+                    "margin",
+                    "margin_percent",
+                ]),
+            },
+            ReportKind::ConsignmentBalances => ReportDefinition {
+                key: kind,
+                name: "Consignment balances".to_string(),
+                description: "Consignment stock held for each vendor and customer, valued at the agreed unit price or else the inventory unit cost, with consumption billed to them but not yet invoiced.".to_string(),
+                parameters: vec![
+                    enum_parameter(
+                        "owner_type",
+                        "Restrict to vendors or customers",
+                        &["vendor", "customer"],
+                    ),
+                    enum_parameter(
+                        "context",
+                        "Restrict to one inventory context",
+                        &["finished_goods", "store", "vendor"],
+                    ),
+                ],
+                columns: columns(&[
+                    "owner_id",
+                    "owner_name",
+                    "owner_type",
+                    "item_id",
+                    "internal_part_number",
+                    "context",
+                    "quantity",
+                    "unit_price",
+                    "total_value",
+                    "pending_quantity",
+                    "pending_amount",
+                ]),
+            },
+        }
+    }
+
+    // Report execution
+
+    pub async fn run_report(
+        &self,
+        tenant_id: Uuid,
+        kind: ReportKind,
+        parameters: ReportParameters,
+    ) -> Result<ReportResult> {
+        let definition = Self::definition(kind);
+        let generated_at = Utc::now();
+
+        let rows = self
+            .report_rows(tenant_id, kind, &parameters, generated_at, None)
+            .await?;
+
+        Ok(ReportResult {
+            report: kind,
+            name: definition.name,
+            generated_at,
+            parameters: parameters.values().clone(),
+            columns: definition.columns,
+            rows,
+        })
+    }
+
+    /// One page of a report's rows, for exports sent a page at a time rather than loaded
+    /// whole. Dates left out default relative to `generated_at`, which must stay the same for
+    /// every page of an export so the pages line up.
+    pub async fn report_page(
+        &self,
+        tenant_id: Uuid,
+        kind: ReportKind,
+        parameters: &ReportParameters,
+        generated_at: DateTime<Utc>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<serde_json::Value>> {
+        self.report_rows(
+            tenant_id,
+            kind,
+            parameters,
+            generated_at,
+            Some((limit, offset)),
+        )
+        .await
+    }
+
+    async fn report_rows(
+        &self,
+        tenant_id: Uuid,
+        kind: ReportKind,
+        parameters: &ReportParameters,
+        now: DateTime<Utc>,
+        page: Option<(u32, u32)>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let page = page_sql(page);
+        match kind {
+            ReportKind::InventoryValuation => to_json_rows(
+                self.inventory_valuation(tenant_id, parameters, &page)
+                    .await?,
+            ),
+            ReportKind::OpenOrderBook => to_json_rows(
+                self.open_order_book(tenant_id, parameters, now, &page)
+                    .await?,
+            ),
+            ReportKind::MachineDowntime => to_json_rows(
+                self.machine_downtime(tenant_id, parameters, now, &page)
+                    .await?,
+            ),
+            ReportKind::JobMargin => {
+                to_json_rows(self.job_margin(tenant_id, parameters, now, &page).await?)
+            }
+            ReportKind::ConsignmentBalances => to_json_rows(
+                self.consignment_balances(tenant_id, parameters, &page)
+                    .await?,
+            ),
+        }
+    }
+
+    async fn inventory_valuation(
+        &self,
+        tenant_id: Uuid,
+        parameters: &ReportParameters,
+        page: &str,
+    ) -> Result<Vec<InventoryValuationRow>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let query = format!(
+            r#"
+            SELECT ii.id AS inventory_item_id,
+                   i.id AS item_id,
+                   i.internal_part_number::text AS internal_part_number,
+                   i.manufacturer::text AS manufacturer,
+                   i.category::text AS category,
+                   ii.context::text AS context,
+                   ii.location::text AS location,
+                   COALESCE(ii.quantity, 0) AS quantity,
+                   cs.quantity AS consigned_quantity,
+                   p.unit_cost,
+                   GREATEST(COALESCE(ii.quantity, 0) - cs.quantity, 0) * p.unit_cost AS total_value
+            FROM inventory_items ii
+            JOIN items i ON i.id = ii.item_id
+            CROSS JOIN LATERAL (SELECT {unit_cost} AS unit_cost) p
+            CROSS JOIN LATERAL (
+                SELECT COALESCE(SUM(c.quantity), 0) AS quantity
+                FROM consignment_stock c
+                WHERE c.inventory_item_id = ii.id
+            ) cs
+            WHERE ii.tenant_id = $1
+              AND ($2::text IS NULL OR ii.context = $2)
+              AND ($3::text IS NULL OR i.category = $3)
+            ORDER BY total_value DESC, i.internal_part_number, ii.id
+            {page}
+            "#,
+            unit_cost = pricing_value_sql("ii", &["unit_cost", "unit_price"]),
+            page = page,
+        );
+
+        let rows = diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .bind::<Nullable<Text>, _>(parameters.get_str("context"))
+            .bind::<Nullable<Text>, _>(parameters.get_str("category"))
+            .load::<InventoryValuationRow>(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn open_order_book(
+        &self,
+        tenant_id: Uuid,
+        parameters: &ReportParameters,
+        now: DateTime<Utc>,
+        page: &str,
+    ) -> Result<Vec<OpenOrderBookRow>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let as_of = parameters.get_date("as_of").unwrap_or(now);
+
+        let query = format!(
+            r#"
+            SELECT o.id AS order_id,
+                   o.order_number::text AS order_number,
+                   o.order_type::text AS order_type,
+                   o.status::text AS status,
+                   o.order_date,
+                   o.external_entity_id,
+                   o.external_entity_type::text AS external_entity_type,
+                   COUNT(oi.id) AS line_count,
+                   COALESCE(SUM(oi.quantity), 0)::int8 AS total_quantity,
+                   o.total_amount,
+                   GREATEST(EXTRACT(DAY FROM ($3::timestamptz - o.order_date)), 0)::int4 AS age_days
+            FROM orders o
+            LEFT JOIN order_items oi ON oi.order_id = o.id
+            WHERE o.tenant_id = $1
+              AND o.status IN ({statuses})
+              AND ($2::text IS NULL OR o.order_type = $2)
+              AND o.order_date <= $3::timestamptz
+            GROUP BY o.id
+            ORDER BY o.order_date, o.order_number, o.id
+            {page}
+            "#,
+            statuses = OPEN_ORDER_STATUSES,
+            page = page,
+        );
+
+        let rows = diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .bind::<Nullable<Text>, _>(parameters.get_str("order_type"))
+            .bind::<Timestamptz, _>(as_of)
+            .load::<OpenOrderBookRow>(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn machine_downtime(
+        &self,
+        tenant_id: Uuid,
+        parameters: &ReportParameters,
+        now: DateTime<Utc>,
+        page: &str,
+    ) -> Result<Vec<MachineDowntimeRow>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let to = parameters.get_date("to").unwrap_or(now);
+        let from = parameters
+            .get_date("from")
+            .unwrap_or(to - Duration::days(DEFAULT_WINDOW_DAYS));
+
+        // Each status history row opens a period that lasts until the next transition
+        // for the same machine; periods are clipped to the requested window.
+        let query = format!(
+            r#"
+            WITH periods AS (
+                SELECT h.machine_id,
+                       h.new_status,
+                       h.changed_at,
+                       LEAD(h.changed_at, 1, $3::timestamptz)
+                           OVER (PARTITION BY h.machine_id ORDER BY h.changed_at) AS ended_at
+                FROM machine_status_history h
+                WHERE h.tenant_id = $1
+                  AND h.changed_at < $3::timestamptz
+            ),
+            clipped AS (
+                SELECT machine_id,
+                       new_status,
+                       changed_at >= $2::timestamptz AS started_in_window,
+                       EXTRACT(EPOCH FROM (LEAST(ended_at, $3::timestamptz)
+                           - GREATEST(changed_at, $2::timestamptz)))::float8 / 3600.0 AS hours
+                FROM periods
+                WHERE ended_at > $2::timestamptz
+            )
+            SELECT m.id AS machine_id,
+                   m.name::text AS machine_name,
+                   m.status::text AS current_status,
+                   COALESCE(SUM(c.hours) FILTER (WHERE c.new_status = 'offline'), 0) AS offline_hours,
+                   COALESCE(SUM(c.hours) FILTER (WHERE c.new_status = 'error'), 0) AS error_hours,
+                   COALESCE(SUM(c.hours) FILTER (WHERE c.new_status = 'maintenance'), 0) AS maintenance_hours,
+                   COALESCE(SUM(c.hours) FILTER (WHERE c.new_status IN ({statuses})), 0) AS downtime_hours,
+                   COUNT(c.machine_id) FILTER (WHERE c.new_status IN ({statuses}) AND c.started_in_window) AS downtime_events,
+                   CASE WHEN $3::timestamptz > $2::timestamptz THEN
+                       GREATEST(0, 100.0 * (1 - COALESCE(SUM(c.hours) FILTER (WHERE c.new_status IN ({statuses})), 0)
+                           / (EXTRACT(EPOCH FROM ($3::timestamptz - $2::timestamptz))::float8 / 3600.0)))
+                   ELSE 100.0 END AS availability_percent
+            FROM machines m
+            LEFT JOIN clipped c ON c.machine_id = m.id
+            WHERE m.tenant_id = $1
+            GROUP BY m.id
+            ORDER BY downtime_hours DESC, m.name, m.id
+            {page}
+            "#,
+            statuses = DOWNTIME_STATUSES,
+            page = page,
+        );
+
+        let rows = diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .bind::<Timestamptz, _>(from)
+            .bind::<Timestamptz, _>(to)
+            .load::<MachineDowntimeRow>(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn job_margin(
+        &self,
+        tenant_id: Uuid,
+        parameters: &ReportParameters,
+        now: DateTime<Utc>,
+        page: &str,
+    ) -> Result<Vec<JobMarginRow>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let to = parameters.get_date("to").unwrap_or(now);
+        let from = parameters
+            .get_date("from")
+            .unwrap_or(to - Duration::days(DEFAULT_WINDOW_DAYS));
+        let labor_rate = parameters.get_f64("labor_rate").unwrap_or(0.0);
+
+        let query = format!(
+            r#"
+            WITH completed AS (
+                SELECT j.*, COALESCE(j.end_date, j.updated_at) AS completed_at
+                FROM jobs j
+                WHERE j.tenant_id = $1
+                  AND j.status = 'completed'
+                  AND COALESCE(j.end_date, j.updated_at) >= $2::timestamptz
+                  AND COALESCE(j.end_date, j.updated_at) < $3::timestamptz
+            ),
+            materials AS (
+                SELECT c.id AS job_id,
+                       SUM({material_quantity} * COALESCE({store_cost}, 0)) AS material_cost
+                FROM completed c
+                CROSS JOIN LATERAL jsonb_array_elements(
+                    CASE WHEN jsonb_typeof(c.materials_consumed) = 'array'
+                         THEN c.materials_consumed ELSE '[]'::jsonb END
+                ) AS m(entry)
+                LEFT JOIN inventory_items si
+                       ON si.tenant_id = $1
+                      AND si.context = 'store'
+                      AND si.item_id::text = m.entry->>'item_id'
+                GROUP BY c.id
+            ),
+            costed AS (
+                SELECT c.id AS job_id,
+                       c.job_number::text AS job_number,
+                       c.job_type::text AS job_type,
+                       c.item_id,
+                       c.quantity,
+                       c.completed_at,
+                       c.quantity * COALESCE({sale_price}, 0) AS revenue,
+                       COALESCE(c.labor_hours, 0) AS labor_hours,
+                       COALESCE(c.labor_hours, 0) * $4 AS labor_cost,
+                       COALESCE(mt.material_cost, 0) AS material_cost
+                FROM completed c
+                LEFT JOIN materials mt ON mt.job_id = c.id
+                LEFT JOIN inventory_items fg
+                       ON fg.tenant_id = $1
+                      AND fg.context = 'finished_goods'
+                      AND fg.item_id = c.item_id
+            )
+            SELECT job_id, job_number, job_type, item_id, quantity, completed_at,
+                   revenue, labor_hours, labor_cost, material_cost,
+                   revenue - labor_cost - material_cost AS margin,
+                   CASE WHEN revenue > 0
+                        THEN 100.0 * (revenue - labor_cost - material_cost) / revenue
+                   END AS margin_percent
+            FROM costed
+            ORDER BY completed_at DESC, job_number, job_id
+            {page}
+            "#,
+            material_quantity = numeric_json_sql("m.entry->>'quantity'", "0"),
+            store_cost = pricing_value_sql("si", &["unit_cost", "unit_price"]),
+            sale_price = pricing_value_sql("fg", &["unit_price"]),
+            page = page,
+        );
+
+        let rows = diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .bind::<Timestamptz, _>(from)
+            .bind::<Timestamptz, _>(to)
+            .bind::<Double, _>(labor_rate)
+            .load::<JobMarginRow>(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+
+    async fn consignment_balances(
+        &self,
+        tenant_id: Uuid,
+        parameters: &ReportParameters,
+        page: &str,
+    ) -> Result<Vec<ConsignmentBalanceRow>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Balances that were used up still show while billings are pending
+        let query = format!(
+            r#"
+            SELECT cs.owner_id,
+                   p.name::text AS owner_name,
+                   cs.owner_type::text AS owner_type,
+                   i.id AS item_id,
+                   i.internal_part_number::text AS internal_part_number,
+                   ii.context::text AS context,
+                   cs.quantity,
+                   pr.unit_price,
+                   cs.quantity * pr.unit_price AS total_value,
+                   COALESCE(b.quantity, 0) AS pending_quantity,
+                   COALESCE(b.amount, 0) AS pending_amount
+            FROM consignment_stock cs
+            JOIN inventory_items ii ON ii.id = cs.inventory_item_id
+            JOIN items i ON i.id = cs.item_id
+            JOIN person p ON p.id = cs.owner_id
+            CROSS JOIN LATERAL (SELECT COALESCE(cs.unit_price, {unit_cost}) AS unit_price) pr
+            LEFT JOIN LATERAL (
+                SELECT SUM(cb.quantity) AS quantity, SUM(cb.amount) AS amount
+                FROM consignment_billings cb
+                WHERE cb.consignment_stock_id = cs.id
+                  AND cb.status = 'pending'
+            ) b ON TRUE
+            WHERE cs.tenant_id = $1
+              AND ($2::text IS NULL OR cs.owner_type = $2)
+              AND ($3::text IS NULL OR ii.context = $3)
+              AND (cs.quantity > 0 OR b.quantity IS NOT NULL)
+            ORDER BY p.name, i.internal_part_number, cs.id
+            {page}
+            "#,
+            unit_cost = pricing_value_sql("ii", &["unit_cost", "unit_price"]),
+            page = page,
+        );
+
+        let rows = diesel::sql_query(query)
+            .bind::<SqlUuid, _>(tenant_id)
+            .bind::<Nullable<Text>, _>(parameters.get_str("owner_type"))
+            .bind::<Nullable<Text>, _>(parameters.get_str("context"))
+            .load::<ConsignmentBalanceRow>(&mut conn)
+            .await?;
+
+        Ok(rows)
+    }
+}
+
+// CSV rendering
+
+pub fn render_csv(result: &ReportResult) -> String {
+    let mut output = csv_header(&result.columns);
+    for row in &result.rows {
+        output.push_str(&csv_row(&result.columns, row));
+    }
+    output
+}
+
+/// Header line of a report's CSV.
+pub fn csv_header(columns: &[String]) -> String {
+    csv_line(columns.iter().map(|c| csv_escape(c)))
+}
+
+/// CSV lines for a page of report rows, for exports sent a page at a time.
+pub fn csv_rows(columns: &[String], rows: &[serde_json::Value]) -> Vec<String> {
+    rows.iter().map(|row| csv_row(columns, row)).collect()
+}
+
+fn csv_row(columns: &[String], row: &serde_json::Value) -> String {
+    csv_line(columns.iter().map(|column| match row.get(column) {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => csv_escape(s),
+        // Numbers are written as they are, so negative values stay numeric
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(other) => csv_escape(&other.to_string()),
+    }))
+}
+
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    let mut line = fields.collect::<Vec<_>>().join(",");
+    line.push_str("\r\n");
+    line
+}
+
+// Helpers
+
+// LIMIT/OFFSET clause for a page of a report, or nothing for the whole report
+fn page_sql(page: Option<(u32, u32)>) -> String {
+    page.map(|(limit, offset)| format!("LIMIT {} OFFSET {}", limit, offset))
+        .unwrap_or_default()
+}
+
+fn parameter(
+    name: &str,
+    param_type: ReportParameterType,
+    required: bool,
+    description: &str,
+) -> ReportParameter {
+    ReportParameter {
+        name: name.to_string(),
+        param_type,
+        required,
+        description: description.to_string(),
+        allowed_values: None,
+    }
+}
+
+fn enum_parameter(name: &str, description: &str, allowed_values: &[&str]) -> ReportParameter {
+    ReportParameter {
+        allowed_values: Some(allowed_values.iter().map(|v| v.to_string()).collect()),
+        ..parameter(name, ReportParameterType::Enum, false, description)
+    }
+}
+
+fn columns(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn to_json_rows<T: Serialize>(rows: Vec<T>) -> Result<Vec<serde_json::Value>> {
+    rows.into_iter()
+        .map(|row| serde_json::to_value(row).map_err(Into::into))
+        .collect()
+}
+
+// SQL expression casting a JSON text value to float8, or `fallback` when it is not numeric
+fn numeric_json_sql(text_expr: &str, fallback: &str) -> String {
+    format!(
+        "(CASE WHEN ({expr}) ~ '^-?[0-9]+(\\.[0-9]+)?$' THEN ({expr})::float8 ELSE {fallback} END)",
+        expr = text_expr,
+        fallback = fallback,
+    )
+}
+
+// SQL expression reading the first numeric key from an inventory row's pricing JSON
+fn pricing_value_sql(alias: &str, keys: &[&str]) -> String {
+    let candidates: Vec<String> = keys
+        .iter()
+        .map(|key| numeric_json_sql(&format!("{}.pricing->>'{}'", alias, key), "NULL"))
+        .chain(std::iter::once("0".to_string()))
+        .collect();
+    format!("COALESCE({})", candidates.join(", "))
+}
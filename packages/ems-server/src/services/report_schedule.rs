@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    next_cron_run, CreateReportScheduleIdResponse, CreateReportScheduleRequest, NewReportSchedule,
+    ReportDeliveryStatus, ReportFormat, ReportKind, ReportParameters, ReportSchedule,
+    ReportScheduleResponse, UpdateReportScheduleRequest,
+};
+use crate::schema::*;
+use crate::services::{render_csv, DatabaseService, EmailAttachment, EmailService, ReportService};
+
+pub struct ReportScheduleService {
+    database: DatabaseService,
+}
+
+impl ReportScheduleService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Report schedule CRUD operations
+
+    pub async fn create_schedule(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Uuid,
+        request: CreateReportScheduleRequest,
+    ) -> Result<CreateReportScheduleIdResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let timezone = request.timezone.unwrap_or_else(|| "UTC".to_string());
+        let next_run_at = next_cron_run(&request.cron_expression, &timezone, Utc::now())
+            .map_err(|e| anyhow!(e))?;
+
+        let new_schedule = NewReportSchedule {
+            tenant_id,
+            name: request.name,
+            report_key: request.report.to_string(),
+            cron_expression: request.cron_expression,
+            timezone,
+            parameters: serde_json::to_value(request.parameters.unwrap_or_default())?,
+            recipients: request.recipients.into_iter().map(Some).collect(),
+            format: request.format.unwrap_or(ReportFormat::Csv).to_string(),
+            is_active: request.is_active.unwrap_or(true),
+            created_by_id: Some(created_by_id),
+            next_run_at,
+        };
+
+        let schedule: ReportSchedule = diesel::insert_into(report_schedules::table)
+            .values(&new_schedule)
+            .returning(ReportSchedule::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(CreateReportScheduleIdResponse { id: schedule.id })
+    }
+
+    pub async fn list_schedules(&self, tenant_id: Uuid) -> Result<Vec<ReportScheduleResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let schedules = report_schedules::table
+            .filter(report_schedules::tenant_id.eq(tenant_id))
+            .order(report_schedules::name.asc())
+            .select(ReportSchedule::as_select())
+            .load::<ReportSchedule>(&mut conn)
+            .await?;
+
+        Ok(schedules.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_schedule(
+        &self,
+        tenant_id: Uuid,
+        schedule_id: Uuid,
+    ) -> Result<Option<ReportSchedule>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let schedule = report_schedules::table
+            .filter(report_schedules::id.eq(schedule_id))
+            .filter(report_schedules::tenant_id.eq(tenant_id))
+            .select(ReportSchedule::as_select())
+            .first::<ReportSchedule>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(schedule)
+    }
+
+    pub async fn update_schedule(
+        &self,
+        tenant_id: Uuid,
+        schedule: ReportSchedule,
+        request: UpdateReportScheduleRequest,
+    ) -> Result<ReportScheduleResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let cron_expression = request
+            .cron_expression
+            .clone()
+            .unwrap_or_else(|| schedule.cron_expression.clone());
+        let timezone = request
+            .timezone
+            .clone()
+            .unwrap_or_else(|| schedule.timezone.clone());
+        let reschedule = request.cron_expression.is_some()
+            || request.timezone.is_some()
+            || (request.is_active == Some(true) && !schedule.is_active);
+        let next_run_at = if reschedule {
+            next_cron_run(&cron_expression, &timezone, Utc::now()).map_err(|e| anyhow!(e))?
+        } else {
+            schedule.next_run_at
+        };
+
+        let parameters = match &request.parameters {
+            Some(parameters) => Some(serde_json::to_value(parameters)?),
+            None => None,
+        };
+
+        let updated = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = report_schedules::table
+                        .filter(report_schedules::id.eq(schedule.id))
+                        .filter(report_schedules::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(report_schedules::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(parameters) = &parameters {
+                        diesel::update(target)
+                            .set(report_schedules::parameters.eq(parameters))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(recipients) = &request.recipients {
+                        let recipients: Vec<Option<String>> =
+                            recipients.iter().cloned().map(Some).collect();
+                        diesel::update(target)
+                            .set(report_schedules::recipients.eq(recipients))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(format) = &request.format {
+                        diesel::update(target)
+                            .set(report_schedules::format.eq(format.to_string()))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(report_schedules::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    diesel::update(target)
+                        .set((
+                            report_schedules::cron_expression.eq(&cron_expression),
+                            report_schedules::timezone.eq(&timezone),
+                            report_schedules::next_run_at.eq(next_run_at),
+                        ))
+                        .returning(ReportSchedule::as_returning())
+                        .get_result::<ReportSchedule>(conn)
+                        .await
+                })
+            })
+            .await?;
+
+        Ok(updated.into())
+    }
+
+    pub async fn delete_schedule(&self, tenant_id: Uuid, schedule_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            report_schedules::table
+                .filter(report_schedules::id.eq(schedule_id))
+                .filter(report_schedules::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Scheduler support
+
+    /// Locks active schedules whose next run is due (across all tenants), advances
+    /// their `next_run_at` to the following cron occurrence and returns them.
+    /// `SKIP LOCKED` keeps concurrent server instances from delivering the same run twice.
+    pub async fn claim_due_schedules(&self, limit: i64) -> Result<Vec<ReportSchedule>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // The scheduler works across tenants, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let now = Utc::now();
+        let schedules = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let due = report_schedules::table
+                        .filter(report_schedules::is_active.eq(true))
+                        .filter(report_schedules::next_run_at.le(now))
+                        .order(report_schedules::next_run_at.asc())
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .select(ReportSchedule::as_select())
+                        .load::<ReportSchedule>(conn)
+                        .await?;
+
+                    for schedule in &due {
+                        // An unparseable schedule is parked (no next run) rather than retried every poll
+                        let next_run_at =
+                            next_cron_run(&schedule.cron_expression, &schedule.timezone, now)
+                                .ok()
+                                .flatten();
+
+                        diesel::update(
+                            report_schedules::table.filter(report_schedules::id.eq(schedule.id)),
+                        )
+                        .set(report_schedules::next_run_at.eq(next_run_at))
+                        .execute(conn)
+                        .await?;
+                    }
+
+                    Ok(due)
+                })
+            })
+            .await?;
+
+        Ok(schedules)
+    }
+
+    /// Renders the schedule's report and emails it to its recipients, recording the outcome.
+    /// Delivery failures are stored on the schedule; only bookkeeping errors are returned.
+    pub async fn deliver_schedule(
+        &self,
+        schedule: &ReportSchedule,
+        email: Option<&EmailService>,
+    ) -> Result<ReportDeliveryStatus> {
+        let outcome = match email {
+            Some(email) => self.render_and_send(schedule, email).await,
+            None => Err(anyhow!(
+                "Email delivery is not configured (SMTP_HOST is not set)"
+            )),
+        };
+
+        let (status, error) = match &outcome {
+            Ok(()) => (ReportDeliveryStatus::Success, None),
+            Err(e) => {
+                tracing::warn!("Report schedule {} delivery failed: {}", schedule.id, e);
+                (ReportDeliveryStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            schedule.tenant_id
+        ))
+        .await?;
+
+        diesel::update(
+            report_schedules::table
+                .filter(report_schedules::id.eq(schedule.id))
+                .filter(report_schedules::tenant_id.eq(schedule.tenant_id)),
+        )
+        .set((
+            report_schedules::last_run_at.eq(Some(Utc::now())),
+            report_schedules::last_status.eq(Some(status.to_string())),
+            report_schedules::last_error.eq(error),
+        ))
+        .execute(&mut conn)
+        .await?;
+
+        Ok(status)
+    }
+
+    async fn render_and_send(&self, schedule: &ReportSchedule, email: &EmailService) -> Result<()> {
+        let kind = ReportKind::try_from(schedule.report_key.clone()).map_err(|e| anyhow!(e))?;
+        let format = ReportFormat::try_from(schedule.format.clone()).unwrap_or(ReportFormat::Csv);
+        let definition = ReportService::definition(kind);
+
+        let raw: HashMap<String, String> =
+            serde_json::from_value(schedule.parameters.clone()).unwrap_or_default();
+        let parameters = ReportParameters::parse(&definition, &raw).map_err(|e| anyhow!(e))?;
+
+        let result = ReportService::new(self.database.clone())
+            .run_report(schedule.tenant_id, kind, parameters)
+            .await?;
+
+        let stamp = result.generated_at.format("%Y%m%d%H%M%S");
+        let attachment = match format {
+            ReportFormat::Csv => EmailAttachment {
+                filename: format!("{}_{}.csv", result.report, stamp),
+                content_type: "text/csv; charset=utf-8".to_string(),
+                content: render_csv(&result).into_bytes(),
+            },
+            ReportFormat::Json => EmailAttachment {
+                filename: format!("{}_{}.json", result.report, stamp),
+                content_type: "application/json".to_string(),
+                content: serde_json::to_vec_pretty(&result)?,
+            },
+        };
+
+        let subject = format!("{} - {}", schedule.name, result.name);
+        let body = format!(
+            "Your scheduled \"{}\" report is attached.\n\nReport: {}\nGenerated at: {}\nRows: {}\n",
+            schedule.name,
+            result.name,
+            result.generated_at.to_rfc3339(),
+            result.rows.len()
+        );
+
+        let recipients: Vec<String> = schedule.recipients.iter().flatten().cloned().collect();
+        email
+            .send(&recipients, &subject, &body, vec![attachment])
+            .await
+    }
+}
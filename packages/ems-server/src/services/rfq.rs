@@ -0,0 +1,1155 @@
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    AwardRfqRequest, CreateRfqItemRequest, CreateRfqRequest, DeclineRfqRequest, ExternalEntityType,
+    InventoryItem, ItemContext, ListRfqsQuery, NewInventoryItem, NewOrder, NewOrderHistory,
+    NewOrderItem, NewRfq, NewRfqItem, NewRfqQuote, NewRfqVendor, NumberingEntity, Order,
+    OrderStatus, OrderType, PriceBreak, PriceListRow, Rfq, RfqComparisonResponse,
+    RfqInvitationResponse, RfqItem, RfqQuote, RfqResponse, RfqStatus, RfqVendor, RfqVendorResponse,
+    RfqVendorStatus, SendRfqRequest, SubmitRfqQuoteRequest, UpdateRfqRequest,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, EmailService, NumberingService, PricingService};
+use crate::services::{TenantCache, TenantService};
+use crate::utils::auth::AuthUtils;
+use crate::utils::i18n::Locale;
+use crate::utils::price_list::{merge_price_break, price_breaks};
+use crate::utils::quote::round_cents;
+use crate::utils::rfq::{compare_quotes, response_token, rfq_email, token_tenant};
+use crate::utils::AppError;
+
+/// Errors from [`RfqService`] that are the caller's or the vendor's to fix, as opposed to
+/// failures
+#[derive(Error, Debug)]
+pub enum RfqError {
+    #[error("Item not found: {0}")]
+    ItemNotFound(Uuid),
+
+    #[error("Vendor not found: {0}")]
+    VendorNotFound(Uuid),
+
+    #[error("Vendor not found on RFQ: {0}")]
+    VendorNotOnRfq(Uuid),
+
+    #[error("Invalid quote: {0}")]
+    InvalidQuote(String),
+
+    #[error("RFQ cannot be {0}")]
+    NotAllowed(String),
+
+    #[error("Vendor cannot be {0}")]
+    VendorNotAllowed(String),
+
+    #[error("Email delivery is not configured (SMTP_HOST is not set)")]
+    EmailNotConfigured,
+}
+
+impl From<RfqError> for AppError {
+    fn from(error: RfqError) -> Self {
+        match error {
+            RfqError::ItemNotFound(_)
+            | RfqError::VendorNotFound(_)
+            | RfqError::VendorNotOnRfq(_)
+            | RfqError::InvalidQuote(_) => AppError::Validation(error.to_string()),
+            RfqError::NotAllowed(_) | RfqError::VendorNotAllowed(_) => {
+                AppError::Conflict(error.to_string())
+            }
+            RfqError::EmailNotConfigured => AppError::Unavailable(error.to_string()),
+        }
+    }
+}
+
+pub struct RfqService {
+    database: DatabaseService,
+    tenants: TenantService,
+}
+
+impl RfqService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            tenants: TenantService::new(database.clone()),
+            database,
+        }
+    }
+
+    /// Serves tenant lookups for vendor responses from the cache.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.tenants = self.tenants.with_cache(cache);
+        self
+    }
+
+    // RFQ operations
+
+    /// Creates a draft RFQ for the given items and vendors. Vendors must be persons with a
+    /// vendor record in the tenant.
+    pub async fn create_rfq(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateRfqRequest,
+    ) -> Result<RfqResponse> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Number the RFQ from its sequence unless given, falling back to a random
+                    // number when the tenant has none
+                    let rfq_number = NumberingService::number_or_else(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::Rfq,
+                        request.rfq_number,
+                        || {
+                            format!("RFQ-{}", &Uuid::new_v4().simple().to_string()[..8])
+                                .to_uppercase()
+                        },
+                    )
+                    .await?;
+
+                    let new_rfq = NewRfq {
+                        tenant_id,
+                        rfq_number,
+                        respond_by: request.respond_by,
+                        notes: request.notes,
+                        created_by_id,
+                    };
+
+                    let rfq: Rfq = diesel::insert_into(rfqs::table)
+                        .values(&new_rfq)
+                        .returning(Rfq::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    Self::replace_items(conn, tenant_id, rfq.id, &request.items).await?;
+                    Self::replace_vendors(conn, tenant_id, rfq.id, &request.vendor_ids).await?;
+                    Self::load_response(conn, rfq).await
+                })
+            })
+            .await
+    }
+
+    pub async fn list_rfqs(
+        &self,
+        tenant_id: Uuid,
+        query: ListRfqsQuery,
+    ) -> Result<Vec<RfqResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut rfqs_query = rfqs::table
+            .filter(rfqs::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(status) = query.status {
+            rfqs_query = rfqs_query.filter(rfqs::status.eq(status.to_string()));
+        }
+
+        if let Some(vendor_id) = query.vendor_id {
+            rfqs_query = rfqs_query.filter(
+                rfqs::id.eq_any(
+                    rfq_vendors::table
+                        .filter(rfq_vendors::vendor_id.eq(vendor_id))
+                        .select(rfq_vendors::rfq_id),
+                ),
+            );
+        }
+
+        let rfqs = rfqs_query
+            .order(rfqs::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(Rfq::as_select())
+            .load::<Rfq>(&mut conn)
+            .await?;
+
+        Self::load_responses(&mut conn, rfqs).await
+    }
+
+    pub async fn get_rfq(&self, tenant_id: Uuid, rfq_id: Uuid) -> Result<Option<RfqResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        match Self::find_rfq(&mut conn, tenant_id, rfq_id).await? {
+            Some(rfq) => Self::load_response(&mut conn, rfq).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Edits a draft RFQ.
+    pub async fn update_rfq(
+        &self,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+        request: UpdateRfqRequest,
+    ) -> Result<Option<RfqResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(rfq) = Self::lock_rfq(conn, tenant_id, rfq_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&rfq, &[RfqStatus::Draft], "updated")?;
+
+                    let rfq = diesel::update(rfqs::table.find(rfq.id))
+                        .set((
+                            rfqs::respond_by.eq(request.respond_by.or(rfq.respond_by)),
+                            rfqs::notes.eq(request.notes.or(rfq.notes)),
+                        ))
+                        .returning(Rfq::as_returning())
+                        .get_result::<Rfq>(conn)
+                        .await?;
+
+                    if let Some(items) = &request.items {
+                        Self::replace_items(conn, tenant_id, rfq.id, items).await?;
+                    }
+                    if let Some(vendor_ids) = &request.vendor_ids {
+                        Self::replace_vendors(conn, tenant_id, rfq.id, vendor_ids).await?;
+                    }
+
+                    Self::load_response(conn, rfq).await.map(Some)
+                })
+            })
+            .await
+    }
+
+    /// Emails every vendor that has not answered yet a link to respond through, and marks a
+    /// draft RFQ as sent. Each send issues a new link, so earlier ones stop working. The link is
+    /// `response_url` followed by the token; without one, the token is sent as a code.
+    pub async fn send_rfq(
+        &self,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+        request: SendRfqRequest,
+        email: Option<&EmailService>,
+        response_url: Option<&str>,
+        default_locale: Locale,
+    ) -> Result<Option<RfqResponse>> {
+        let Some(email) = email else {
+            return Err(RfqError::EmailNotConfigured.into());
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(rfq) = Self::find_rfq(&mut conn, tenant_id, rfq_id).await? else {
+            return Ok(None);
+        };
+        Self::require_status(&rfq, &[RfqStatus::Draft, RfqStatus::Sent], "sent")?;
+        if let Some(respond_by) = rfq.respond_by.filter(|by| *by < Utc::now()) {
+            return Err(RfqError::NotAllowed(format!(
+                "sent: responses were due {}",
+                respond_by.format("%Y-%m-%d")
+            ))
+            .into());
+        }
+
+        let items = Self::find_items(&mut conn, rfq.id).await?;
+        let vendors: Vec<(RfqVendor, String, String)> = rfq_vendors::table
+            .inner_join(person::table)
+            .filter(rfq_vendors::rfq_id.eq(rfq.id))
+            .filter(rfq_vendors::status.eq_any([
+                RfqVendorStatus::Pending.to_string(),
+                RfqVendorStatus::Sent.to_string(),
+            ]))
+            .select((RfqVendor::as_select(), person::name, person::email))
+            .load::<(RfqVendor, String, String)>(&mut conn)
+            .await?;
+
+        let locale = request.locale.unwrap_or(default_locale);
+        for (vendor, vendor_name, vendor_email) in vendors {
+            let token = response_token(tenant_id);
+            let (subject, body) = rfq_email(
+                &rfq,
+                &vendor_name,
+                &items,
+                response_url,
+                &token,
+                request.message.as_deref(),
+                locale,
+            );
+            email
+                .send(
+                    std::slice::from_ref(&vendor_email),
+                    &subject,
+                    &body,
+                    Vec::new(),
+                )
+                .await?;
+
+            diesel::update(rfq_vendors::table.find(vendor.id))
+                .set((
+                    rfq_vendors::status.eq(RfqVendorStatus::Sent.to_string()),
+                    rfq_vendors::token_hash.eq(Some(AuthUtils::hash_token(&token))),
+                    rfq_vendors::sent_at.eq(Some(Utc::now())),
+                ))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        let rfq = diesel::update(rfqs::table.find(rfq.id))
+            .set((
+                rfqs::status.eq(RfqStatus::Sent.to_string()),
+                rfqs::sent_at.eq(Some(Utc::now())),
+            ))
+            .returning(Rfq::as_returning())
+            .get_result::<Rfq>(&mut conn)
+            .await?;
+
+        Self::load_response(&mut conn, rfq).await.map(Some)
+    }
+
+    /// Records a quote the vendor gave some other way, e.g. by phone or email, in place of any
+    /// quote it already has.
+    pub async fn record_quote(
+        &self,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+        vendor_id: Uuid,
+        request: SubmitRfqQuoteRequest,
+    ) -> Result<Option<RfqResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(rfq) = Self::lock_rfq(conn, tenant_id, rfq_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&rfq, &[RfqStatus::Sent], "quoted")?;
+
+                    let vendor = Self::find_vendor(conn, rfq.id, vendor_id).await?;
+                    Self::require_vendor_status(
+                        &vendor,
+                        &[
+                            RfqVendorStatus::Pending,
+                            RfqVendorStatus::Sent,
+                            RfqVendorStatus::Responded,
+                            RfqVendorStatus::Declined,
+                        ],
+                        "quoted",
+                    )?;
+                    Self::save_quote(conn, tenant_id, &vendor, request).await?;
+
+                    Self::load_response(conn, rfq).await.map(Some)
+                })
+            })
+            .await
+    }
+
+    /// The vendors' quotes side by side.
+    pub async fn compare_rfq(
+        &self,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+    ) -> Result<Option<RfqComparisonResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(rfq) = Self::find_rfq(&mut conn, tenant_id, rfq_id).await? else {
+            return Ok(None);
+        };
+
+        let items = Self::find_items(&mut conn, rfq.id).await?;
+        let vendors: Vec<(RfqVendor, String)> = rfq_vendors::table
+            .inner_join(person::table)
+            .filter(rfq_vendors::rfq_id.eq(rfq.id))
+            .order(person::name.asc())
+            .select((RfqVendor::as_select(), person::name))
+            .load::<(RfqVendor, String)>(&mut conn)
+            .await?;
+        let vendor_ids: Vec<Uuid> = vendors.iter().map(|(vendor, _)| vendor.id).collect();
+        let quotes = rfq_quotes::table
+            .filter(rfq_quotes::rfq_vendor_id.eq_any(&vendor_ids))
+            .select(RfqQuote::as_select())
+            .load::<RfqQuote>(&mut conn)
+            .await?;
+
+        Ok(Some(compare_quotes(&rfq, &items, &vendors, &quotes)))
+    }
+
+    /// Awards a sent RFQ to a vendor that quoted every line: creates a draft purchase order at
+    /// the quoted prices, expected after each line's lead time, and, unless told not to, writes
+    /// the prices and lead times to the vendor's item pricing and price history. The other
+    /// vendors lose the RFQ.
+    pub async fn award_rfq(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        rfq_id: Uuid,
+        request: AwardRfqRequest,
+    ) -> Result<Option<RfqResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(rfq) = Self::lock_rfq(conn, tenant_id, rfq_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&rfq, &[RfqStatus::Sent], "awarded")?;
+
+                    let vendor = Self::find_vendor(conn, rfq.id, request.vendor_id).await?;
+                    Self::require_vendor_status(&vendor, &[RfqVendorStatus::Responded], "awarded")?;
+
+                    let quotes: HashMap<Uuid, RfqQuote> = rfq_quotes::table
+                        .filter(rfq_quotes::rfq_vendor_id.eq(vendor.id))
+                        .select(RfqQuote::as_select())
+                        .load::<RfqQuote>(conn)
+                        .await?
+                        .into_iter()
+                        .map(|quote| (quote.rfq_item_id, quote))
+                        .collect();
+                    let items = Self::find_items(conn, rfq.id).await?;
+                    let mut lines = Vec::with_capacity(items.len());
+                    for item in items {
+                        let quote = quotes.get(&item.id).ok_or_else(|| {
+                            RfqError::VendorNotAllowed(format!(
+                                "awarded: line {} is not quoted",
+                                item.line_number
+                            ))
+                        })?;
+                        lines.push((item, quote));
+                    }
+
+                    let order_number = NumberingService::number_or_else(
+                        conn,
+                        tenant_id,
+                        NumberingEntity::PurchaseOrder,
+                        request.order_number,
+                        || format!("PO-{}", rfq.rfq_number),
+                    )
+                    .await?;
+
+                    let now = Utc::now();
+                    let new_order = NewOrder {
+                        tenant_id,
+                        order_number,
+                        order_type: OrderType::PurchaseOrder.to_string(),
+                        external_entity_id: vendor.vendor_id,
+                        external_entity_type: ExternalEntityType::Vendor.to_string(),
+                        order_date: now,
+                        total_amount: round_cents(
+                            lines
+                                .iter()
+                                .map(|(item, quote)| {
+                                    round_cents(quote.unit_price * item.quantity as f64)
+                                })
+                                .sum(),
+                        ),
+                        status: OrderStatus::Draft.to_string(),
+                        created_by_id: person_id,
+                        notes: rfq.notes.clone(),
+                        metadata: Some(serde_json::json!({
+                            "rfq_id": rfq.id,
+                            "rfq_number": rfq.rfq_number,
+                            "currency": vendor.currency,
+                        })),
+                    };
+
+                    let order: Order = diesel::insert_into(orders::table)
+                        .values(&new_order)
+                        .returning(Order::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let new_items: Vec<NewOrderItem> = lines
+                        .iter()
+                        .map(|(item, quote)| NewOrderItem {
+                            order_id: order.id,
+                            item_id: Some(item.item_id),
+                            item_name: item.item_name.clone(),
+                            item_description: item.item_description.clone(),
+                            quantity: item.quantity,
+                            unit_price: quote.unit_price,
+                            extended_price: round_cents(quote.unit_price * item.quantity as f64),
+                            notes: quote.notes.clone().or_else(|| item.notes.clone()),
+                            expected_date: quote
+                                .lead_time
+                                .map(|days| now + Duration::days(days as i64)),
+                            uom_id: None,
+                            base_quantity: item.quantity,
+                        })
+                        .collect();
+
+                    diesel::insert_into(order_items::table)
+                        .values(&new_items)
+                        .execute(conn)
+                        .await?;
+
+                    diesel::insert_into(order_history::table)
+                        .values(&NewOrderHistory {
+                            order_id: order.id,
+                            tenant_id,
+                            person_id: Some(person_id),
+                            action: "create".to_string(),
+                            previous_status: None,
+                            new_status: Some(order.status.clone()),
+                            notes: Some(format!("Order created from RFQ {}", rfq.rfq_number)),
+                        })
+                        .execute(conn)
+                        .await?;
+
+                    if request.update_price_list.unwrap_or(true) {
+                        for (item, quote) in &lines {
+                            Self::update_vendor_price(
+                                conn, tenant_id, person_id, &vendor, item, quote,
+                            )
+                            .await?;
+                        }
+                    }
+
+                    diesel::update(rfq_vendors::table.find(vendor.id))
+                        .set(rfq_vendors::status.eq(RfqVendorStatus::Awarded.to_string()))
+                        .execute(conn)
+                        .await?;
+                    diesel::update(
+                        rfq_vendors::table
+                            .filter(rfq_vendors::rfq_id.eq(rfq.id))
+                            .filter(rfq_vendors::id.ne(vendor.id))
+                            .filter(rfq_vendors::status.eq_any([
+                                RfqVendorStatus::Pending.to_string(),
+                                RfqVendorStatus::Sent.to_string(),
+                                RfqVendorStatus::Responded.to_string(),
+                            ])),
+                    )
+                    .set(rfq_vendors::status.eq(RfqVendorStatus::Lost.to_string()))
+                    .execute(conn)
+                    .await?;
+
+                    let rfq = diesel::update(rfqs::table.find(rfq.id))
+                        .set((
+                            rfqs::status.eq(RfqStatus::Awarded.to_string()),
+                            rfqs::awarded_vendor_id.eq(Some(vendor.vendor_id)),
+                            rfqs::awarded_at.eq(Some(now)),
+                            rfqs::order_id.eq(Some(order.id)),
+                        ))
+                        .returning(Rfq::as_returning())
+                        .get_result::<Rfq>(conn)
+                        .await?;
+                    Self::load_response(conn, rfq).await.map(Some)
+                })
+            })
+            .await
+    }
+
+    /// Cancels a draft or sent RFQ; response links stop accepting quotes.
+    pub async fn cancel_rfq(&self, tenant_id: Uuid, rfq_id: Uuid) -> Result<Option<RfqResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let Some(rfq) = Self::lock_rfq(conn, tenant_id, rfq_id).await? else {
+                        return Ok(None);
+                    };
+                    Self::require_status(&rfq, &[RfqStatus::Draft, RfqStatus::Sent], "cancelled")?;
+
+                    let rfq = diesel::update(rfqs::table.find(rfq.id))
+                        .set(rfqs::status.eq(RfqStatus::Cancelled.to_string()))
+                        .returning(Rfq::as_returning())
+                        .get_result::<Rfq>(conn)
+                        .await?;
+                    Self::load_response(conn, rfq).await.map(Some)
+                })
+            })
+            .await
+    }
+
+    // Vendor response operations (the token in the emailed link decides the tenant)
+
+    /// The RFQ as the vendor with the response token sees it.
+    pub async fn invitation(&self, token: &str) -> Result<Option<RfqInvitationResponse>> {
+        let Some((tenant_id, company)) = self.resolve_tenant(token).await? else {
+            return Ok(None);
+        };
+        DatabaseService::scope_tenant(tenant_id, async {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            match Self::vendor_for_token(&mut conn, tenant_id, token).await? {
+                Some(vendor) => Self::load_invitation(&mut conn, company, vendor)
+                    .await
+                    .map(Some),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// Stores the quote of the vendor with the response token, in place of any earlier one.
+    pub async fn submit_quote(
+        &self,
+        token: &str,
+        request: SubmitRfqQuoteRequest,
+    ) -> Result<Option<RfqInvitationResponse>> {
+        let Some((tenant_id, company)) = self.resolve_tenant(token).await? else {
+            return Ok(None);
+        };
+        DatabaseService::scope_tenant(tenant_id, async {
+            let token_hash = AuthUtils::hash_token(token);
+            self.database
+                .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                    Box::pin(async move {
+                        let Some(vendor) =
+                            Self::lock_vendor_for_token(conn, tenant_id, &token_hash).await?
+                        else {
+                            return Ok(None);
+                        };
+                        Self::require_open(conn, &vendor, "quoted").await?;
+                        let vendor = Self::save_quote(conn, tenant_id, &vendor, request).await?;
+                        Self::load_invitation(conn, company, vendor).await.map(Some)
+                    })
+                })
+                .await
+        })
+        .await
+    }
+
+    /// Records the vendor with the response token declining to quote. Any quote it gave is
+    /// withdrawn.
+    pub async fn decline(
+        &self,
+        token: &str,
+        request: DeclineRfqRequest,
+    ) -> Result<Option<RfqInvitationResponse>> {
+        let Some((tenant_id, company)) = self.resolve_tenant(token).await? else {
+            return Ok(None);
+        };
+        DatabaseService::scope_tenant(tenant_id, async {
+            let token_hash = AuthUtils::hash_token(token);
+            self.database
+                .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                    Box::pin(async move {
+                        let Some(vendor) =
+                            Self::lock_vendor_for_token(conn, tenant_id, &token_hash).await?
+                        else {
+                            return Ok(None);
+                        };
+                        Self::require_open(conn, &vendor, "declined").await?;
+
+                        diesel::delete(
+                            rfq_quotes::table.filter(rfq_quotes::rfq_vendor_id.eq(vendor.id)),
+                        )
+                        .execute(conn)
+                        .await?;
+                        let vendor = diesel::update(rfq_vendors::table.find(vendor.id))
+                            .set((
+                                rfq_vendors::status.eq(RfqVendorStatus::Declined.to_string()),
+                                rfq_vendors::responded_at.eq(Some(Utc::now())),
+                                rfq_vendors::notes.eq(request.reason),
+                            ))
+                            .returning(RfqVendor::as_returning())
+                            .get_result::<RfqVendor>(conn)
+                            .await?;
+                        Self::load_invitation(conn, company, vendor).await.map(Some)
+                    })
+                })
+                .await
+        })
+        .await
+    }
+
+    // Private helper methods
+
+    // The active tenant a response token names, with its name, routing connections to its
+    // database
+    async fn resolve_tenant(&self, token: &str) -> Result<Option<(Uuid, String)>> {
+        let Some(tenant_id) = token_tenant(token) else {
+            return Ok(None);
+        };
+        let Some(tenant) = self
+            .tenants
+            .get_tenant_by_id(tenant_id)
+            .await?
+            .filter(|tenant| tenant.is_active.unwrap_or(false))
+        else {
+            return Ok(None);
+        };
+        if let Some(database_url) = &tenant.database_url {
+            if self.database.dedicated_databases_enabled() {
+                self.database
+                    .register_tenant_database(tenant.id, database_url)
+                    .await?;
+            }
+        }
+        Ok(Some((tenant.id, tenant.name)))
+    }
+
+    async fn vendor_for_token(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        token: &str,
+    ) -> Result<Option<RfqVendor>> {
+        Ok(rfq_vendors::table
+            .filter(rfq_vendors::tenant_id.eq(tenant_id))
+            .filter(rfq_vendors::token_hash.eq(AuthUtils::hash_token(token)))
+            .select(RfqVendor::as_select())
+            .first::<RfqVendor>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn lock_vendor_for_token(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        token_hash: &str,
+    ) -> Result<Option<RfqVendor>> {
+        Ok(rfq_vendors::table
+            .filter(rfq_vendors::tenant_id.eq(tenant_id))
+            .filter(rfq_vendors::token_hash.eq(token_hash))
+            .for_update()
+            .select(RfqVendor::as_select())
+            .first::<RfqVendor>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn find_rfq(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+    ) -> Result<Option<Rfq>> {
+        Ok(rfqs::table
+            .filter(rfqs::id.eq(rfq_id))
+            .filter(rfqs::tenant_id.eq(tenant_id))
+            .select(Rfq::as_select())
+            .first::<Rfq>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn lock_rfq(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+    ) -> Result<Option<Rfq>> {
+        Ok(rfqs::table
+            .filter(rfqs::id.eq(rfq_id))
+            .filter(rfqs::tenant_id.eq(tenant_id))
+            .for_update()
+            .select(Rfq::as_select())
+            .first::<Rfq>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn find_vendor(
+        conn: &mut AsyncPgConnection,
+        rfq_id: Uuid,
+        vendor_id: Uuid,
+    ) -> Result<RfqVendor> {
+        rfq_vendors::table
+            .filter(rfq_vendors::rfq_id.eq(rfq_id))
+            .filter(rfq_vendors::vendor_id.eq(vendor_id))
+            .for_update()
+            .select(RfqVendor::as_select())
+            .first::<RfqVendor>(conn)
+            .await
+            .optional()?
+            .ok_or(RfqError::VendorNotOnRfq(vendor_id).into())
+    }
+
+    async fn find_items(conn: &mut AsyncPgConnection, rfq_id: Uuid) -> Result<Vec<RfqItem>> {
+        Ok(rfq_items::table
+            .filter(rfq_items::rfq_id.eq(rfq_id))
+            .order(rfq_items::line_number.asc())
+            .select(RfqItem::as_select())
+            .load::<RfqItem>(conn)
+            .await?)
+    }
+
+    fn require_status(rfq: &Rfq, allowed: &[RfqStatus], action: &str) -> Result<()> {
+        let status = RfqStatus::try_from(rfq.status.clone()).map_err(|e| anyhow!(e))?;
+        if allowed.contains(&status) {
+            Ok(())
+        } else {
+            Err(RfqError::NotAllowed(format!("{}: status is {}", action, status)).into())
+        }
+    }
+
+    fn require_vendor_status(
+        vendor: &RfqVendor,
+        allowed: &[RfqVendorStatus],
+        action: &str,
+    ) -> Result<()> {
+        let status = RfqVendorStatus::try_from(vendor.status.clone()).map_err(|e| anyhow!(e))?;
+        if allowed.contains(&status) {
+            Ok(())
+        } else {
+            Err(RfqError::VendorNotAllowed(format!("{}: status is {}", action, status)).into())
+        }
+    }
+
+    // Vendors answer through their link while the RFQ is out and before it is due
+    async fn require_open(
+        conn: &mut AsyncPgConnection,
+        vendor: &RfqVendor,
+        action: &str,
+    ) -> Result<()> {
+        let rfq = rfqs::table
+            .find(vendor.rfq_id)
+            .select(Rfq::as_select())
+            .first::<Rfq>(conn)
+            .await?;
+        Self::require_status(&rfq, &[RfqStatus::Sent], action)?;
+        Self::require_vendor_status(
+            vendor,
+            &[RfqVendorStatus::Sent, RfqVendorStatus::Responded],
+            action,
+        )?;
+        if let Some(respond_by) = rfq.respond_by.filter(|by| *by < Utc::now()) {
+            return Err(RfqError::NotAllowed(format!(
+                "{}: responses were due {}",
+                action,
+                respond_by.format("%Y-%m-%d")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Replaces the vendor's quote with `request` and marks it as responded.
+    async fn save_quote(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        vendor: &RfqVendor,
+        request: SubmitRfqQuoteRequest,
+    ) -> Result<RfqVendor> {
+        let item_ids: HashSet<Uuid> = rfq_items::table
+            .filter(rfq_items::rfq_id.eq(vendor.rfq_id))
+            .select(rfq_items::id)
+            .load::<Uuid>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        if let Some(line) = request
+            .lines
+            .iter()
+            .find(|line| !item_ids.contains(&line.rfq_item_id))
+        {
+            return Err(RfqError::InvalidQuote(format!(
+                "line {} is not on the RFQ",
+                line.rfq_item_id
+            ))
+            .into());
+        }
+
+        let new_quotes: Vec<NewRfqQuote> = request
+            .lines
+            .into_iter()
+            .map(|line| NewRfqQuote {
+                tenant_id,
+                rfq_vendor_id: vendor.id,
+                rfq_item_id: line.rfq_item_id,
+                unit_price: line.unit_price,
+                lead_time: line.lead_time,
+                notes: line.notes,
+            })
+            .collect();
+
+        diesel::delete(rfq_quotes::table.filter(rfq_quotes::rfq_vendor_id.eq(vendor.id)))
+            .execute(conn)
+            .await?;
+        diesel::insert_into(rfq_quotes::table)
+            .values(&new_quotes)
+            .execute(conn)
+            .await?;
+
+        Ok(diesel::update(rfq_vendors::table.find(vendor.id))
+            .set((
+                rfq_vendors::status.eq(RfqVendorStatus::Responded.to_string()),
+                rfq_vendors::responded_at.eq(Some(Utc::now())),
+                rfq_vendors::currency.eq(request.currency.map(|c| c.to_uppercase())),
+                rfq_vendors::notes.eq(request.notes),
+            ))
+            .returning(RfqVendor::as_returning())
+            .get_result::<RfqVendor>(conn)
+            .await?)
+    }
+
+    /// Stores `items` as the RFQ's lines in place of any existing ones. Quotes for the old lines
+    /// go with them.
+    async fn replace_items(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+        items: &[CreateRfqItemRequest],
+    ) -> Result<()> {
+        let item_ids: Vec<Uuid> = items.iter().map(|i| i.item_id).collect();
+        let item_details: HashMap<Uuid, (String, Option<String>)> = items::table
+            .filter(items::id.eq_any(&item_ids))
+            .select((items::id, items::internal_part_number, items::description))
+            .load::<(Uuid, String, Option<String>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(id, part_number, description)| (id, (part_number, description)))
+            .collect();
+
+        let mut new_items = Vec::with_capacity(items.len());
+        for (index, request) in items.iter().enumerate() {
+            let (part_number, description) = item_details
+                .get(&request.item_id)
+                .ok_or(RfqError::ItemNotFound(request.item_id))?;
+            new_items.push(NewRfqItem {
+                tenant_id,
+                rfq_id,
+                line_number: index as i32 + 1,
+                item_id: request.item_id,
+                item_name: request
+                    .item_name
+                    .clone()
+                    .unwrap_or_else(|| part_number.clone()),
+                item_description: request
+                    .item_description
+                    .clone()
+                    .or_else(|| description.clone()),
+                quantity: request.quantity,
+                notes: request.notes.clone(),
+            });
+        }
+
+        diesel::delete(rfq_items::table.filter(rfq_items::rfq_id.eq(rfq_id)))
+            .execute(conn)
+            .await?;
+        diesel::insert_into(rfq_items::table)
+            .values(&new_items)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Invites `vendor_ids` in place of the RFQ's current vendors.
+    async fn replace_vendors(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        rfq_id: Uuid,
+        vendor_ids: &[Uuid],
+    ) -> Result<()> {
+        let vendors: HashSet<Uuid> = vendor_person::table
+            .filter(vendor_person::tenant_id.eq(tenant_id))
+            .filter(vendor_person::person_id.eq_any(vendor_ids))
+            .select(vendor_person::person_id)
+            .load::<Uuid>(conn)
+            .await?
+            .into_iter()
+            .collect();
+        if let Some(missing) = vendor_ids.iter().find(|id| !vendors.contains(id)) {
+            return Err(RfqError::VendorNotFound(*missing).into());
+        }
+
+        let new_vendors: Vec<NewRfqVendor> = vendor_ids
+            .iter()
+            .map(|vendor_id| NewRfqVendor {
+                tenant_id,
+                rfq_id,
+                vendor_id: *vendor_id,
+            })
+            .collect();
+
+        diesel::delete(rfq_vendors::table.filter(rfq_vendors::rfq_id.eq(rfq_id)))
+            .execute(conn)
+            .await?;
+        diesel::insert_into(rfq_vendors::table)
+            .values(&new_vendors)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Writes an awarded line's price, as a break at the RFQ quantity, and lead time to the
+    /// item's vendor record, creating the record when the item has none.
+    async fn update_vendor_price(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        vendor: &RfqVendor,
+        item: &RfqItem,
+        quote: &RfqQuote,
+    ) -> Result<()> {
+        let existing = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item.item_id))
+            .filter(inventory_items::context.eq(ItemContext::Vendor.to_string()))
+            .for_update()
+            .select(InventoryItem::as_select())
+            .first::<InventoryItem>(conn)
+            .await
+            .optional()?;
+        let inventory = match existing {
+            Some(inventory) => inventory,
+            None => {
+                diesel::insert_into(inventory_items::table)
+                    .values(&NewInventoryItem {
+                        item_id: item.item_id,
+                        tenant_id,
+                        context: ItemContext::Vendor.to_string(),
+                        quantity: None,
+                        location: None,
+                        pricing: None,
+                        lead_time: None,
+                        min_stock_level: None,
+                        max_stock_level: None,
+                        reorder_point: None,
+                        vendor_id: Some(vendor.vendor_id),
+                        last_received_date: None,
+                        status: None,
+                        notes: None,
+                        metadata: None,
+                    })
+                    .returning(InventoryItem::as_returning())
+                    .get_result::<InventoryItem>(conn)
+                    .await?
+            }
+        };
+
+        // A vendor record bought from someone else keeps its other breaks only when they are
+        // the same vendor's
+        let breaks = if inventory.vendor_id.is_none_or(|id| id == vendor.vendor_id) {
+            price_breaks(inventory.pricing.as_ref())
+        } else {
+            Vec::new()
+        };
+        let row = PriceListRow {
+            line: item.line_number as usize,
+            mfr_part_number: item.item_name.clone(),
+            manufacturer: None,
+            price_breaks: merge_price_break(
+                &breaks,
+                PriceBreak {
+                    quantity: item.quantity,
+                    unit_price: quote.unit_price,
+                },
+            ),
+            lead_time: quote.lead_time,
+            currency: vendor.currency.clone(),
+        };
+
+        PricingService::apply_row(
+            conn,
+            tenant_id,
+            Some(vendor.vendor_id),
+            Some(person_id),
+            &inventory,
+            &row,
+            "rfq",
+        )
+        .await
+    }
+
+    async fn load_invitation(
+        conn: &mut AsyncPgConnection,
+        company: String,
+        vendor: RfqVendor,
+    ) -> Result<RfqInvitationResponse> {
+        let rfq = rfqs::table
+            .find(vendor.rfq_id)
+            .select(Rfq::as_select())
+            .first::<Rfq>(conn)
+            .await?;
+        let vendor_name = person::table
+            .find(vendor.vendor_id)
+            .select(person::name)
+            .first::<String>(conn)
+            .await?;
+        let items = Self::find_items(conn, rfq.id).await?;
+        let quotes = rfq_quotes::table
+            .filter(rfq_quotes::rfq_vendor_id.eq(vendor.id))
+            .select(RfqQuote::as_select())
+            .load::<RfqQuote>(conn)
+            .await?;
+
+        let status = RfqVendorStatus::try_from(vendor.status).unwrap_or(RfqVendorStatus::Pending);
+        let is_open = rfq.status == RfqStatus::Sent.to_string()
+            && matches!(status, RfqVendorStatus::Sent | RfqVendorStatus::Responded)
+            && rfq.respond_by.is_none_or(|by| by >= Utc::now());
+
+        Ok(RfqInvitationResponse {
+            rfq_number: rfq.rfq_number,
+            company,
+            vendor_name,
+            status,
+            respond_by: rfq.respond_by,
+            is_open,
+            notes: rfq.notes,
+            items: items.into_iter().map(Into::into).collect(),
+            currency: vendor.currency,
+            quotes: quotes.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    async fn load_response(conn: &mut AsyncPgConnection, rfq: Rfq) -> Result<RfqResponse> {
+        let mut responses = Self::load_responses(conn, vec![rfq]).await?;
+        responses
+            .pop()
+            .ok_or_else(|| anyhow!("RFQ disappeared while loading"))
+    }
+
+    async fn load_responses(
+        conn: &mut AsyncPgConnection,
+        rfqs: Vec<Rfq>,
+    ) -> Result<Vec<RfqResponse>> {
+        let rfq_ids: Vec<Uuid> = rfqs.iter().map(|r| r.id).collect();
+
+        let mut items: HashMap<Uuid, Vec<RfqItem>> = HashMap::new();
+        for item in rfq_items::table
+            .filter(rfq_items::rfq_id.eq_any(&rfq_ids))
+            .order(rfq_items::line_number.asc())
+            .select(RfqItem::as_select())
+            .load::<RfqItem>(conn)
+            .await?
+        {
+            items.entry(item.rfq_id).or_default().push(item);
+        }
+
+        let vendors = rfq_vendors::table
+            .inner_join(person::table)
+            .filter(rfq_vendors::rfq_id.eq_any(&rfq_ids))
+            .order(person::name.asc())
+            .select((RfqVendor::as_select(), person::name))
+            .load::<(RfqVendor, String)>(conn)
+            .await?;
+
+        let vendor_ids: Vec<Uuid> = vendors.iter().map(|(vendor, _)| vendor.id).collect();
+        let mut quotes: HashMap<Uuid, Vec<RfqQuote>> = HashMap::new();
+        for quote in rfq_quotes::table
+            .filter(rfq_quotes::rfq_vendor_id.eq_any(&vendor_ids))
+            .select(RfqQuote::as_select())
+            .load::<RfqQuote>(conn)
+            .await?
+        {
+            quotes.entry(quote.rfq_vendor_id).or_default().push(quote);
+        }
+
+        let mut vendor_responses: HashMap<Uuid, Vec<RfqVendorResponse>> = HashMap::new();
+        for (vendor, vendor_name) in vendors {
+            let vendor_quotes = quotes.remove(&vendor.id).unwrap_or_default();
+            vendor_responses
+                .entry(vendor.rfq_id)
+                .or_default()
+                .push(RfqVendorResponse::new(vendor, vendor_name, vendor_quotes));
+        }
+
+        Ok(rfqs
+            .into_iter()
+            .map(|rfq| {
+                let rfq_items = items.remove(&rfq.id).unwrap_or_default();
+                let rfq_vendors = vendor_responses.remove(&rfq.id).unwrap_or_default();
+                RfqResponse::new(rfq, rfq_items, rfq_vendors)
+            })
+            .collect())
+    }
+}
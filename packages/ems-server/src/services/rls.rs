@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use diesel_async::RunQueryDsl;
+use std::env;
+
+use crate::models::{RlsAuditResponse, RlsCheckMode, RlsRoleRow, RlsTableRow, RlsTableStatus};
+use crate::services::DatabaseService;
+
+/// Tables deliberately visible to every tenant. Every other table in the public schema is
+/// expected to carry tenant isolation policies.
+pub const SHARED_TABLES: &[&str] = &[
+    "asset_types",
+    "feature_flags",
+    "item_lifecycle_checks",
+    "items",
+];
+
+const RLS_TABLES_SQL: &str = r#"
+    SELECT c.relname::text AS table_name,
+           c.relrowsecurity AS rls_enabled,
+           c.relforcerowsecurity AS rls_forced,
+           COUNT(p.policyname)::int8 AS policy_count,
+           COUNT(p.policyname) FILTER (
+               WHERE concat_ws(' ', p.qual, p.with_check) ~ '(get_current_tenant_id|app\.current_tenant_id)'
+           )::int8 AS tenant_policy_count
+    FROM pg_class c
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    LEFT JOIN pg_policies p ON p.schemaname = n.nspname AND p.tablename = c.relname
+    WHERE n.nspname = 'public'
+      AND c.relkind IN ('r', 'p')
+      AND c.relname NOT LIKE '\_\_%'
+    GROUP BY c.relname, c.relrowsecurity, c.relforcerowsecurity
+    ORDER BY c.relname
+"#;
+
+const RLS_ROLE_SQL: &str = r#"
+    SELECT current_user::text AS role, (rolsuper OR rolbypassrls) AS bypasses_rls
+    FROM pg_roles
+    WHERE rolname = current_user
+"#;
+
+pub struct RlsService {
+    database: DatabaseService,
+}
+
+impl RlsService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Introspects the connected database for row level security on every public table.
+    pub async fn audit(&self) -> Result<RlsAuditResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        let role = diesel::sql_query(RLS_ROLE_SQL)
+            .get_result::<RlsRoleRow>(&mut conn)
+            .await?;
+        let rows = diesel::sql_query(RLS_TABLES_SQL)
+            .load::<RlsTableRow>(&mut conn)
+            .await?;
+
+        let tables: Vec<RlsTableStatus> = rows
+            .into_iter()
+            .map(|row| {
+                let tenant_scoped = !SHARED_TABLES.contains(&row.table_name.as_str());
+                RlsTableStatus::from_row(row, tenant_scoped)
+            })
+            .collect();
+        let failing: Vec<String> = tables
+            .iter()
+            .filter(|table| !table.problems.is_empty())
+            .map(|table| table.table.clone())
+            .collect();
+
+        Ok(RlsAuditResponse {
+            role: role.role,
+            role_bypasses_rls: role.bypasses_rls,
+            passed: failing.is_empty(),
+            failing,
+            tables,
+        })
+    }
+
+    /// Reads RLS_CHECK (off, warn or enforce; default warn).
+    pub fn mode_from_env() -> Result<RlsCheckMode> {
+        match env::var("RLS_CHECK") {
+            Ok(mode) => RlsCheckMode::try_from(mode.to_lowercase()).map_err(|e| anyhow!(e)),
+            Err(_) => Ok(RlsCheckMode::Warn),
+        }
+    }
+
+    /// Audits the shared database and every dedicated tenant database before serving. Problems
+    /// are logged; in enforce mode they stop the server from starting.
+    pub async fn check_on_startup(&self, mode: RlsCheckMode) -> Result<()> {
+        if mode == RlsCheckMode::Off {
+            return Ok(());
+        }
+
+        let mut audits = vec![("shared database".to_string(), self.audit().await?)];
+        for tenant_id in self.database.tenant_database_ids() {
+            let audit = DatabaseService::scope_tenant(tenant_id, self.audit()).await?;
+            audits.push((format!("tenant {} database", tenant_id), audit));
+        }
+
+        let mut failing = 0;
+        for (database, audit) in &audits {
+            if audit.role_bypasses_rls {
+                tracing::warn!(
+                    "Role {} bypasses row level security in the {}; tenant isolation relies on service filters",
+                    audit.role,
+                    database
+                );
+            }
+            for table in audit.tables.iter().filter(|t| !t.problems.is_empty()) {
+                tracing::error!(
+                    "RLS check: {}.{}: {}",
+                    database,
+                    table.table,
+                    table.problems.join("; ")
+                );
+            }
+            failing += audit.failing.len();
+        }
+
+        if failing > 0 && mode == RlsCheckMode::Enforce {
+            return Err(anyhow!(
+                "RLS check failed: {} table(s) missing tenant isolation policies",
+                failing
+            ));
+        }
+        Ok(())
+    }
+}
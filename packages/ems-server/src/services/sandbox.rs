@@ -0,0 +1,294 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_types::Uuid as SqlUuid;
+use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::BTreeMap;
+use std::env;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{CallerContext, CloneSandboxRequest, CloneSandboxResponse, Permission, Tenant};
+use crate::schema::tenants;
+use crate::services::{DatabaseService, TenantCache, TenantService};
+use crate::utils::encryption::strip_secret_settings;
+use crate::utils::sandbox::{sandbox_expiry, sandbox_name, sandbox_subdomain};
+use crate::utils::AppError;
+
+/// Errors from [`SandboxService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum SandboxError {
+    #[error("Invalid sandbox request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Sandbox limit of {0} reached")]
+    LimitReached(i64),
+}
+
+impl From<SandboxError> for AppError {
+    fn from(error: SandboxError) -> Self {
+        match error {
+            SandboxError::InvalidRequest(_) => AppError::Validation(error.to_string()),
+            SandboxError::LimitReached(_) => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+// Sandbox lifetime when the request sets none, in days
+const DEFAULT_SANDBOX_TTL_DAYS: i64 = 14;
+// Longest sandbox lifetime that can be requested, in days
+const DEFAULT_SANDBOX_MAX_TTL_DAYS: i64 = 90;
+// Sandboxes a tenant can have at once
+const DEFAULT_SANDBOX_LIMIT: i64 = 3;
+
+// Configuration copied into every sandbox. Each statement copies the source tenant's ($1) rows
+// to the sandbox ($2).
+const CONFIGURATION_COPIES: &[(&str, &str)] = &[
+    (
+        "tenant_person",
+        "INSERT INTO tenant_person (person_id, tenant_id, role, access_level, is_primary) \
+         SELECT person_id, $2, role, access_level, false FROM tenant_person WHERE tenant_id = $1",
+    ),
+    (
+        "internal_person",
+        "INSERT INTO internal_person (person_id, tenant_id, department, position, employee_id, hire_date) \
+         SELECT person_id, $2, department, position, employee_id, hire_date \
+         FROM internal_person WHERE tenant_id = $1",
+    ),
+    (
+        "feature_flag_overrides",
+        "INSERT INTO feature_flag_overrides (flag_id, tenant_id, enabled) \
+         SELECT flag_id, $2, enabled FROM feature_flag_overrides WHERE tenant_id = $1",
+    ),
+    (
+        "units_of_measure",
+        "INSERT INTO units_of_measure (tenant_id, code, name, dimension, factor) \
+         SELECT $2, code, name, dimension, factor FROM units_of_measure WHERE tenant_id = $1",
+    ),
+    (
+        "skills",
+        "INSERT INTO skills (tenant_id, name, description) \
+         SELECT $2, name, description FROM skills WHERE tenant_id = $1",
+    ),
+    (
+        "shift_patterns",
+        "INSERT INTO shift_patterns (tenant_id, name, description, shifts) \
+         SELECT $2, name, description, shifts FROM shift_patterns WHERE tenant_id = $1",
+    ),
+    // Default dashboards are unique per person across tenants, so copies are not defaults
+    (
+        "dashboards",
+        "INSERT INTO dashboards (tenant_id, owner_id, name, is_default, layout, widgets) \
+         SELECT $2, owner_id, name, false, layout, widgets FROM dashboards WHERE tenant_id = $1",
+    ),
+];
+
+// Data copied when requested. Units are matched to the sandbox's copies by code.
+const DATA_COPIES: &[(&str, &str)] = &[
+    (
+        "machines",
+        "INSERT INTO machines (tenant_id, name, ip, port, protocol, status, metadata, latitude, longitude, site) \
+         SELECT $2, name, ip, port, protocol, 'offline', metadata, latitude, longitude, site \
+         FROM machines WHERE tenant_id = $1",
+    ),
+    (
+        "inventory_items",
+        "INSERT INTO inventory_items (item_id, tenant_id, context, quantity, location, pricing, lead_time, \
+             min_stock_level, max_stock_level, reorder_point, vendor_id, last_received_date, status, notes, metadata) \
+         SELECT item_id, $2, context, quantity, location, pricing, lead_time, min_stock_level, \
+             max_stock_level, reorder_point, vendor_id, last_received_date, status, notes, metadata \
+         FROM inventory_items WHERE tenant_id = $1",
+    ),
+    (
+        "item_units",
+        "INSERT INTO item_units (tenant_id, item_id, base_uom_id, purchase_uom_id, purchase_factor) \
+         SELECT $2, t.item_id, b.id, p.id, t.purchase_factor FROM item_units t \
+         JOIN units_of_measure sb ON sb.id = t.base_uom_id \
+         JOIN units_of_measure b ON b.tenant_id = $2 AND b.code = sb.code \
+         LEFT JOIN units_of_measure sp ON sp.id = t.purchase_uom_id \
+         LEFT JOIN units_of_measure p ON p.tenant_id = $2 AND p.code = sp.code \
+         WHERE t.tenant_id = $1",
+    ),
+    (
+        "item_bom",
+        "INSERT INTO item_bom (tenant_id, parent_item_id, component_item_id, quantity, notes, is_optional, \
+             substitutes, assembly_order, uom_id) \
+         SELECT $2, t.parent_item_id, t.component_item_id, t.quantity, t.notes, t.is_optional, \
+             t.substitutes, t.assembly_order, u.id FROM item_bom t \
+         LEFT JOIN units_of_measure su ON su.id = t.uom_id \
+         LEFT JOIN units_of_measure u ON u.tenant_id = $2 AND u.code = su.code \
+         WHERE t.tenant_id = $1",
+    ),
+];
+
+/// Sandbox tenants: non-production copies of a tenant where users can try out changes without
+/// touching live data. Sandboxes live in the shared database and are deleted once they expire.
+pub struct SandboxService {
+    database: DatabaseService,
+    tenants: TenantService,
+    default_ttl_days: i64,
+    max_ttl_days: i64,
+    limit: i64,
+}
+
+impl SandboxService {
+    /// Reads the expiry policy from SANDBOX_DEFAULT_TTL_DAYS (default 14), SANDBOX_MAX_TTL_DAYS
+    /// (default 90) and SANDBOX_LIMIT_PER_TENANT (default 3).
+    pub fn new(database: DatabaseService) -> Self {
+        let setting = |name: &str, default: i64| {
+            env::var(name)
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .filter(|s| *s > 0)
+                .unwrap_or(default)
+        };
+
+        Self {
+            tenants: TenantService::new(database.clone()),
+            database: database.shared(),
+            default_ttl_days: setting("SANDBOX_DEFAULT_TTL_DAYS", DEFAULT_SANDBOX_TTL_DAYS),
+            max_ttl_days: setting("SANDBOX_MAX_TTL_DAYS", DEFAULT_SANDBOX_MAX_TTL_DAYS),
+            limit: setting("SANDBOX_LIMIT_PER_TENANT", DEFAULT_SANDBOX_LIMIT),
+        }
+    }
+
+    /// Keeps the tenant cache current when sandboxes are deleted.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.tenants = self.tenants.with_cache(cache);
+        self
+    }
+
+    /// Clones the tenant's configuration, and its data when requested, into a new sandbox.
+    /// Returns `None` when the tenant does not exist.
+    pub async fn clone_sandbox(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CloneSandboxRequest,
+    ) -> Result<Option<CloneSandboxResponse>> {
+        caller.require(Permission::ManageSandboxes)?;
+
+        let Some(source) = self.tenants.get_tenant_by_id(tenant_id).await? else {
+            return Ok(None);
+        };
+        if source.is_sandbox {
+            return Err(
+                SandboxError::InvalidRequest("sandboxes cannot be cloned".to_string()).into(),
+            );
+        }
+        if source.database_url.is_some() {
+            return Err(SandboxError::InvalidRequest(
+                "tenants with a dedicated database cannot be cloned".to_string(),
+            )
+            .into());
+        }
+        let expires_at = sandbox_expiry(
+            Utc::now(),
+            request.expires_in_days,
+            self.default_ttl_days,
+            self.max_ttl_days,
+        )
+        .map_err(SandboxError::InvalidRequest)?;
+
+        let name = request.name.unwrap_or_else(|| sandbox_name(&source.name));
+        let subdomain = request.subdomain.unwrap_or_else(|| {
+            sandbox_subdomain(&source.subdomain, &Uuid::new_v4().simple().to_string()[..6])
+        });
+        let limit = self.limit;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Rows of both tenants are copied, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let response = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let existing: i64 = tenants::table
+                        .filter(tenants::sandbox_of.eq(source.id))
+                        .count()
+                        .get_result(conn)
+                        .await?;
+                    if existing >= limit {
+                        return Err(SandboxError::LimitReached(limit).into());
+                    }
+
+                    // Secrets are encrypted for the source tenant, and a sandbox should not
+                    // hold production credentials anyway
+                    let settings = source.settings.as_ref().map(strip_secret_settings);
+                    let tenant: Tenant = diesel::insert_into(tenants::table)
+                        .values((
+                            tenants::name.eq(name),
+                            tenants::subdomain.eq(subdomain),
+                            tenants::settings.eq(settings),
+                            tenants::is_active.eq(Some(true)),
+                            tenants::is_sandbox.eq(true),
+                            tenants::sandbox_of.eq(Some(source.id)),
+                            tenants::sandbox_expires_at.eq(Some(expires_at)),
+                        ))
+                        .returning(Tenant::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let mut copied = BTreeMap::new();
+                    let copies = CONFIGURATION_COPIES.iter().chain(if request.include_data {
+                        DATA_COPIES
+                    } else {
+                        &[]
+                    });
+                    for (table, statement) in copies {
+                        let rows = diesel::sql_query(*statement)
+                            .bind::<SqlUuid, _>(source.id)
+                            .bind::<SqlUuid, _>(tenant.id)
+                            .execute(conn)
+                            .await?;
+                        copied.insert(table.to_string(), rows);
+                    }
+
+                    Ok(CloneSandboxResponse { tenant, copied })
+                })
+            })
+            .await?;
+
+        Ok(Some(response))
+    }
+
+    /// The tenant's sandboxes, newest first.
+    pub async fn list_sandboxes(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Vec<Tenant>> {
+        caller.require(Permission::ManageSandboxes)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        let sandboxes = tenants::table
+            .filter(tenants::sandbox_of.eq(tenant_id))
+            .order(tenants::created_at.desc())
+            .select(Tenant::as_select())
+            .load::<Tenant>(&mut conn)
+            .await?;
+        Ok(sandboxes)
+    }
+
+    /// Deletes up to `limit` expired sandboxes with all their data, returning them.
+    pub async fn delete_expired(&self, limit: i64) -> Result<Vec<Tenant>> {
+        let mut conn = self.database.get_connection().await?;
+
+        let expired = tenants::table
+            .filter(tenants::is_sandbox.eq(true))
+            .filter(tenants::sandbox_expires_at.le(Utc::now()))
+            .order(tenants::sandbox_expires_at.asc())
+            .limit(limit)
+            .select(Tenant::as_select())
+            .load::<Tenant>(&mut conn)
+            .await?;
+        drop(conn);
+
+        for sandbox in &expired {
+            self.tenants.delete_tenant(sandbox.id).await?;
+        }
+        Ok(expired)
+    }
+}
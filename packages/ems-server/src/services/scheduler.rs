@@ -0,0 +1,812 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::{env, time::Duration};
+use tokio::task::JoinHandle;
+
+use crate::models::IntegrityCheckRequest;
+use crate::services::{
+    AccessLogService, AdminService, CalibrationService, DatabaseService, EmailService, JobService,
+    LifecycleService, MachineAlertService, OrderService, PrintService, RecalculationService,
+    ReportScheduleService, SandboxService, TenantService,
+};
+use crate::utils::access_log::access_log_retention_days;
+use crate::utils::archive::archive_after_days;
+use crate::utils::diagnostics::track_run;
+
+// Maximum number of schedules claimed per poll
+const CLAIM_BATCH_SIZE: i64 = 25;
+// Maximum number of print jobs claimed per poll
+const PRINT_CLAIM_BATCH_SIZE: i64 = 25;
+// Maximum number of items looked up per lifecycle batch
+const LIFECYCLE_BATCH_SIZE: i64 = 50;
+// Maximum number of heartbeats claimed per alert poll
+const ALERT_CLAIM_BATCH_SIZE: i64 = 100;
+// Maximum number of overdue calibrations flagged per batch
+const CALIBRATION_FLAG_BATCH_SIZE: i64 = 100;
+// Maximum number of expired sandboxes deleted per batch
+const SANDBOX_CLEANUP_BATCH_SIZE: i64 = 10;
+// Maximum number of recalculation tasks claimed per poll
+const RECALCULATION_CLAIM_BATCH_SIZE: i64 = 5;
+
+/// Background task that periodically delivers due report schedules, in the shared database and
+/// every dedicated tenant database.
+pub struct ReportScheduler {
+    database: DatabaseService,
+    email: Option<EmailService>,
+    poll_interval: Duration,
+}
+
+impl ReportScheduler {
+    pub fn new(
+        database: DatabaseService,
+        email: Option<EmailService>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            database,
+            email,
+            poll_interval,
+        }
+    }
+
+    /// Configures the scheduler from REPORT_SCHEDULER_POLL_SECONDS (default 60) and SMTP_* variables.
+    pub fn from_env(database: DatabaseService) -> Result<Self> {
+        let poll_seconds = env::var("REPORT_SCHEDULER_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(60);
+
+        let email = EmailService::from_env()?;
+        if email.is_none() {
+            tracing::warn!("SMTP_HOST not set; scheduled reports will be recorded as failed");
+        }
+
+        Ok(Self::new(
+            database,
+            email,
+            Duration::from_secs(poll_seconds),
+        ))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "report_scheduler",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Delivered {} scheduled report(s)", count),
+                    Err(e) => tracing::error!("Report scheduler poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Claims and delivers all currently due schedules, returning how many were processed.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = ReportScheduleService::new(self.database.clone());
+        let mut processed = 0;
+
+        loop {
+            let due = service.claim_due_schedules(CLAIM_BATCH_SIZE).await?;
+            if due.is_empty() {
+                break;
+            }
+
+            for schedule in &due {
+                service
+                    .deliver_schedule(schedule, self.email.as_ref())
+                    .await?;
+                processed += 1;
+            }
+        }
+
+        Ok(processed)
+    }
+}
+
+/// Background task that retries queued label print jobs once their backoff has elapsed.
+pub struct PrintQueueWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+}
+
+impl PrintQueueWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration) -> Self {
+        Self {
+            database,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from PRINT_QUEUE_POLL_SECONDS (default 15).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("PRINT_QUEUE_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(15);
+
+        Self::new(database, Duration::from_secs(poll_seconds))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "print_queue",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Processed {} queued print job(s)", count),
+                    Err(e) => tracing::error!("Print queue poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Claims and sends all currently due print jobs, returning how many were processed.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = PrintService::new(self.database.clone());
+        let mut processed = 0;
+
+        loop {
+            let due = service.claim_due_jobs(PRINT_CLAIM_BATCH_SIZE).await?;
+            if due.is_empty() {
+                break;
+            }
+
+            for job in &due {
+                service.deliver_job(job).await?;
+                processed += 1;
+            }
+        }
+
+        Ok(processed)
+    }
+}
+
+/// Background task that checks item manufacturer part numbers against the part data provider and
+/// raises lifecycle alerts for parts going NRND or obsolete.
+pub struct LifecycleWatchWorker {
+    database: DatabaseService,
+    service: LifecycleService,
+    poll_interval: Duration,
+}
+
+impl LifecycleWatchWorker {
+    pub fn new(
+        database: DatabaseService,
+        service: LifecycleService,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            database,
+            service,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from LIFECYCLE_WATCH_POLL_SECONDS (default 3600) and the part data
+    /// provider variables. Returns `Ok(None)` when no provider is configured.
+    pub fn from_env(database: DatabaseService) -> Result<Option<Self>> {
+        let poll_seconds = env::var("LIFECYCLE_WATCH_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3600);
+
+        let service = LifecycleService::new(database.clone())?;
+        if !service.is_configured() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::new(
+            database,
+            service,
+            Duration::from_secs(poll_seconds),
+        )))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "lifecycle_watch",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Checked lifecycle of {} item(s)", count),
+                    Err(e) => tracing::error!("Lifecycle watch poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Checks all items currently due for a lifecycle lookup, returning how many were checked.
+    pub async fn run_once(&self) -> Result<usize> {
+        let mut processed = 0;
+
+        loop {
+            let due = self.service.due_items(LIFECYCLE_BATCH_SIZE).await?;
+            if due.is_empty() {
+                break;
+            }
+
+            for item in &due {
+                self.service.check_item(item).await?;
+                processed += 1;
+            }
+        }
+
+        Ok(processed)
+    }
+}
+
+/// Background task that evaluates machine alert rules against queued heartbeats and runs the
+/// actions of rules that fire.
+pub struct MachineAlertWorker {
+    database: DatabaseService,
+    email: Option<EmailService>,
+    poll_interval: Duration,
+}
+
+impl MachineAlertWorker {
+    pub fn new(
+        database: DatabaseService,
+        email: Option<EmailService>,
+        poll_interval: Duration,
+    ) -> Self {
+        Self {
+            database,
+            email,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from MACHINE_ALERT_POLL_SECONDS (default 5) and SMTP_* variables.
+    pub fn from_env(database: DatabaseService) -> Result<Self> {
+        let poll_seconds = env::var("MACHINE_ALERT_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(5);
+
+        let email = EmailService::from_env()?;
+        if email.is_none() {
+            tracing::warn!(
+                "SMTP_HOST not set; machine alert notifications will be recorded as failed"
+            );
+        }
+
+        Ok(Self::new(
+            database,
+            email,
+            Duration::from_secs(poll_seconds),
+        ))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "machine_alerts",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Raised {} machine alert(s)", count),
+                    Err(e) => tracing::error!("Machine alert poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Evaluates every queued heartbeat, returning how many alerts were raised.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = MachineAlertService::new(self.database.clone());
+        let mut raised = 0;
+
+        loop {
+            let pending = service.claim_pending_events(ALERT_CLAIM_BATCH_SIZE).await?;
+            if pending.is_empty() {
+                break;
+            }
+
+            for event in &pending {
+                raised += service.process_event(event, self.email.as_ref()).await?;
+            }
+        }
+
+        Ok(raised)
+    }
+}
+
+/// Background task that flags calibrations of machines and instruments once they are past due.
+pub struct CalibrationWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+}
+
+impl CalibrationWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration) -> Self {
+        Self {
+            database,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from CALIBRATION_CHECK_POLL_SECONDS (default 3600).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("CALIBRATION_CHECK_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3600);
+
+        Self::new(database, Duration::from_secs(poll_seconds))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "calibration",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Flagged {} overdue calibration(s)", count),
+                    Err(e) => tracing::error!("Calibration check failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Flags every overdue calibration not flagged yet, returning how many were flagged.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = CalibrationService::new(self.database.clone());
+        let mut flagged = 0;
+
+        loop {
+            let overdue = service.flag_overdue(CALIBRATION_FLAG_BATCH_SIZE).await?;
+            if overdue.is_empty() {
+                break;
+            }
+
+            for record in &overdue {
+                match (record.machine_id, record.instrument_id) {
+                    (Some(machine_id), _) => tracing::warn!(
+                        "Calibration of machine {} (tenant {}) was due {}",
+                        machine_id,
+                        record.tenant_id,
+                        record.due_at
+                    ),
+                    (_, Some(instrument_id)) => tracing::warn!(
+                        "Calibration of instrument {} (tenant {}) was due {}",
+                        instrument_id,
+                        record.tenant_id,
+                        record.due_at
+                    ),
+                    (None, None) => {}
+                }
+            }
+            flagged += overdue.len();
+        }
+
+        Ok(flagged)
+    }
+}
+
+/// Background task that periodically deletes expired sandbox tenants. Sandboxes always live in
+/// the shared database.
+pub struct SandboxCleanupWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+}
+
+impl SandboxCleanupWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration) -> Self {
+        Self {
+            database,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from SANDBOX_CLEANUP_POLL_SECONDS (default 3600).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("SANDBOX_CLEANUP_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3600);
+
+        Self::new(database, Duration::from_secs(poll_seconds))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run("sandbox_cleanup", self.run_once()).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Deleted {} expired sandbox(es)", count),
+                    Err(e) => tracing::error!("Sandbox cleanup failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Deletes every expired sandbox, returning how many were deleted.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = SandboxService::new(self.database.clone());
+        let mut deleted = 0;
+
+        loop {
+            let expired = service.delete_expired(SANDBOX_CLEANUP_BATCH_SIZE).await?;
+            if expired.is_empty() {
+                break;
+            }
+
+            for sandbox in &expired {
+                tracing::info!(
+                    "Deleted sandbox {} ({}) of tenant {}",
+                    sandbox.id,
+                    sandbox.subdomain,
+                    sandbox.sandbox_of.unwrap_or_default()
+                );
+            }
+            deleted += expired.len();
+        }
+
+        Ok(deleted)
+    }
+}
+
+/// Background task that periodically archives jobs and orders closed longer than their
+/// tenant's archive age.
+pub struct ArchiveWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+    default_days: i64,
+}
+
+impl ArchiveWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration, default_days: i64) -> Self {
+        Self {
+            database,
+            poll_interval,
+            default_days,
+        }
+    }
+
+    /// Configures the worker from ARCHIVE_POLL_SECONDS (default 3600) and ARCHIVE_AFTER_DAYS,
+    /// the archive age of tenants that set none (default 180; 0 archives only for tenants that
+    /// set one).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("ARCHIVE_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3600);
+        let default_days = env::var("ARCHIVE_AFTER_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|s| *s >= 0)
+            .unwrap_or(180);
+
+        Self::new(database, Duration::from_secs(poll_seconds), default_days)
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run("archive", self.run_once()).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Archived {} job(s) and order(s)", count),
+                    Err(e) => tracing::error!("Archiving failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Archives the closed jobs and orders of every active tenant, returning how many were
+    /// archived. A tenant that fails is logged and skipped.
+    pub async fn run_once(&self) -> Result<usize> {
+        let tenants = TenantService::new(self.database.clone())
+            .list_tenants(None, None)
+            .await?;
+        let now = Utc::now();
+        let mut archived = 0;
+
+        for tenant in tenants.iter().filter(|t| t.is_active.unwrap_or(false)) {
+            let Some(days) = archive_after_days(tenant.settings.as_ref(), self.default_days) else {
+                continue;
+            };
+            let closed_before = now - chrono::Duration::days(days);
+
+            let outcome = DatabaseService::scope_tenant(tenant.id, async {
+                if let Some(database_url) = &tenant.database_url {
+                    if self.database.dedicated_databases_enabled() {
+                        self.database
+                            .register_tenant_database(tenant.id, database_url)
+                            .await?;
+                    }
+                }
+                let jobs = JobService::new(self.database.clone())
+                    .archive_closed(tenant.id, closed_before)
+                    .await?;
+                let orders = OrderService::new(self.database.clone())
+                    .archive_closed(tenant.id, closed_before)
+                    .await?;
+                Ok::<_, anyhow::Error>(jobs + orders)
+            })
+            .await;
+
+            match outcome {
+                Ok(count) => archived += count,
+                Err(e) => tracing::error!("Archiving for tenant {} failed: {}", tenant.id, e),
+            }
+        }
+
+        Ok(archived)
+    }
+}
+
+/// Background task that deletes API access log entries older than their tenant's retention.
+pub struct AccessLogRetentionWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+    default_days: i64,
+}
+
+impl AccessLogRetentionWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration, default_days: i64) -> Self {
+        Self {
+            database,
+            poll_interval,
+            default_days,
+        }
+    }
+
+    /// Configures the worker from ACCESS_LOG_RETENTION_POLL_SECONDS (default 3600) and
+    /// ACCESS_LOG_RETENTION_DAYS, the retention of tenants that set none (default 90; 0 keeps
+    /// their entries indefinitely).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("ACCESS_LOG_RETENTION_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(3600);
+        let default_days = env::var("ACCESS_LOG_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<i64>().ok())
+            .filter(|s| *s >= 0)
+            .unwrap_or(90);
+
+        Self::new(database, Duration::from_secs(poll_seconds), default_days)
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run("access_log_retention", self.run_once()).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Deleted {} expired access log entries", count),
+                    Err(e) => tracing::error!("Access log retention failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Deletes the expired access log entries of every tenant, returning how many were deleted.
+    /// A tenant that fails is logged and skipped.
+    pub async fn run_once(&self) -> Result<usize> {
+        let tenants = TenantService::new(self.database.clone())
+            .list_tenants(None, None)
+            .await?;
+        let service = AccessLogService::new(self.database.clone());
+        let now = Utc::now();
+        let mut deleted = 0;
+
+        for tenant in &tenants {
+            let Some(days) = access_log_retention_days(tenant.settings.as_ref(), self.default_days)
+            else {
+                continue;
+            };
+            match service
+                .prune(tenant.id, now - chrono::Duration::days(days))
+                .await
+            {
+                Ok(count) => deleted += count,
+                Err(e) => tracing::error!(
+                    "Access log retention for tenant {} failed: {}",
+                    tenant.id,
+                    e
+                ),
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+/// Background task that runs queued tenant-wide recalculations, in the shared database and every
+/// dedicated tenant database.
+pub struct RecalculationWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+}
+
+impl RecalculationWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration) -> Self {
+        Self {
+            database,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from RECALCULATION_POLL_SECONDS (default 15).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("RECALCULATION_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(15);
+
+        Self::new(database, Duration::from_secs(poll_seconds))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run(
+                    "recalculation",
+                    self.database.for_each_database(|| self.run_once()),
+                )
+                .await
+                {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Ran {} recalculation task(s)", count),
+                    Err(e) => tracing::error!("Recalculation poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Claims and runs all queued recalculation tasks, returning how many were run.
+    pub async fn run_once(&self) -> Result<usize> {
+        let service = RecalculationService::new(self.database.clone());
+        let mut ran = 0;
+
+        loop {
+            let due = service.claim_due(RECALCULATION_CLAIM_BATCH_SIZE).await?;
+            if due.is_empty() {
+                break;
+            }
+
+            for task in &due {
+                let status = service.run(task).await?;
+                tracing::info!(
+                    "Recalculation {} ({}) of tenant {} {}",
+                    task.id,
+                    task.kind,
+                    task.tenant_id,
+                    status
+                );
+                ran += 1;
+            }
+        }
+
+        Ok(ran)
+    }
+}
+
+/// Background task that periodically runs the reference integrity checks as a sanity check.
+/// It only reports; repairs are left to a platform admin.
+pub struct IntegrityCheckWorker {
+    database: DatabaseService,
+    poll_interval: Duration,
+}
+
+impl IntegrityCheckWorker {
+    pub fn new(database: DatabaseService, poll_interval: Duration) -> Self {
+        Self {
+            database,
+            poll_interval,
+        }
+    }
+
+    /// Configures the worker from INTEGRITY_CHECK_POLL_SECONDS (default 86400).
+    pub fn from_env(database: DatabaseService) -> Self {
+        let poll_seconds = env::var("INTEGRITY_CHECK_POLL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(86400);
+
+        Self::new(database, Duration::from_secs(poll_seconds))
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+                match track_run("integrity_check", self.run_once()).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::warn!("Found {} dangling reference(s)", count),
+                    Err(e) => tracing::error!("Integrity check failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Runs every check without repairing, returning how many dangling references were found.
+    pub async fn run_once(&self) -> Result<usize> {
+        let report = AdminService::new(self.database.clone())
+            .check_integrity(IntegrityCheckRequest::default())
+            .await?;
+
+        for finding in &report.findings {
+            tracing::warn!(
+                "Integrity check {}: {} dangling row(s) in {}",
+                finding.check,
+                finding.dangling,
+                finding
+                    .database_tenant_id
+                    .map_or("the shared database".to_string(), |tenant_id| {
+                        format!("tenant {} database", tenant_id)
+                    })
+            );
+        }
+        Ok(report.dangling as usize)
+    }
+}
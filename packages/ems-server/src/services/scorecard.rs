@@ -0,0 +1,271 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    DeliveryScore, ExternalEntityType, NcrSeverity, NcrStatus, OrderItem, OrderStatus, OrderType,
+    PriceScore, QualityScore, Quantity, StockMovementType, StockReferenceType,
+    VendorScorecardQuery, VendorScorecardResponse,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::scorecard::{delivery_outcome, fill_dates, DeliveryOutcome};
+
+// Length of the scorecard period when the query gives no start
+const DEFAULT_PERIOD_DAYS: i64 = 90;
+
+// Date and quantity of a receipt against a purchase order line
+type Receipt = (DateTime<Utc>, Quantity);
+
+pub struct ScorecardService {
+    database: DatabaseService,
+}
+
+impl ScorecardService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Scores a vendor over a period on delivery against promised dates, the quality of what
+    /// they supplied and the prices paid. Returns `None` when the vendor does not exist.
+    pub async fn vendor_scorecard(
+        &self,
+        tenant_id: Uuid,
+        vendor_id: Uuid,
+        query: VendorScorecardQuery,
+    ) -> Result<Option<VendorScorecardResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some((name, company)) = vendor_person::table
+            .inner_join(person::table.on(vendor_person::person_id.eq(person::id)))
+            .filter(vendor_person::person_id.eq(vendor_id))
+            .filter(vendor_person::tenant_id.eq(tenant_id))
+            .select((person::name, vendor_person::company))
+            .first::<(String, Option<String>)>(&mut conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        let to = query.to.unwrap_or(now);
+        let from = query
+            .from
+            .unwrap_or(to - Duration::days(DEFAULT_PERIOD_DAYS));
+        let in_period = |date: DateTime<Utc>| date >= from && date < to;
+
+        // Every live purchase order with the vendor: lines promised in the period may belong to
+        // orders placed before it
+        let orders: HashMap<Uuid, DateTime<Utc>> = orders::table
+            .filter(orders::tenant_id.eq(tenant_id))
+            .filter(orders::order_type.eq(OrderType::PurchaseOrder.to_string()))
+            .filter(orders::external_entity_type.eq(ExternalEntityType::Vendor.to_string()))
+            .filter(orders::external_entity_id.eq(vendor_id))
+            .filter(orders::status.ne_all([
+                OrderStatus::Draft.to_string(),
+                OrderStatus::Cancelled.to_string(),
+            ]))
+            .select((orders::id, orders::order_date))
+            .load::<(Uuid, DateTime<Utc>)>(&mut conn)
+            .await?
+            .into_iter()
+            .collect();
+        let order_ids: Vec<Uuid> = orders.keys().copied().collect();
+
+        let lines = order_items::table
+            .filter(order_items::order_id.eq_any(&order_ids))
+            .filter(order_items::item_id.is_not_null())
+            .order(order_items::created_at.asc())
+            .select(OrderItem::as_select())
+            .load::<OrderItem>(&mut conn)
+            .await?;
+
+        let receipts = stock_movements::table
+            .filter(stock_movements::tenant_id.eq(tenant_id))
+            .filter(stock_movements::reference_type.eq(StockReferenceType::Order.to_string()))
+            .filter(stock_movements::reference_id.eq_any(&order_ids))
+            .filter(stock_movements::movement_type.eq(StockMovementType::Receipt.to_string()))
+            .filter(stock_movements::quantity.gt(Quantity::ZERO))
+            .select((
+                stock_movements::reference_id,
+                stock_movements::item_id,
+                stock_movements::occurred_at,
+                stock_movements::quantity,
+            ))
+            .load::<(Option<Uuid>, Uuid, DateTime<Utc>, Quantity)>(&mut conn)
+            .await?;
+
+        // Delivery: receipts for an item on an order fill its lines earliest promise first
+        let mut receipts_by_line: HashMap<(Uuid, Uuid), Vec<Receipt>> = HashMap::new();
+        let mut units_received = Quantity::ZERO;
+        for (order_id, item_id, occurred_at, quantity) in receipts {
+            if in_period(occurred_at) {
+                units_received += quantity;
+            }
+            if let Some(order_id) = order_id {
+                receipts_by_line
+                    .entry((order_id, item_id))
+                    .or_default()
+                    .push((occurred_at, quantity));
+            }
+        }
+        let mut lines_by_item: HashMap<(Uuid, Uuid), Vec<&OrderItem>> = HashMap::new();
+        for line in &lines {
+            if let Some(item_id) = line.item_id {
+                lines_by_item
+                    .entry((line.order_id, item_id))
+                    .or_default()
+                    .push(line);
+            }
+        }
+
+        let mut delivery = DeliveryScore::default();
+        let mut days_late = 0;
+        for (key, mut item_lines) in lines_by_item {
+            item_lines.sort_by_key(|line| (line.expected_date.is_none(), line.expected_date));
+            let quantities: Vec<Quantity> = item_lines
+                .iter()
+                .map(|line| line.base_quantity.into())
+                .collect();
+            let filled = fill_dates(
+                &quantities,
+                receipts_by_line
+                    .get(&key)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+            );
+
+            for (line, received) in item_lines.into_iter().zip(filled) {
+                let Some(expected) = line.expected_date else {
+                    if in_period(orders[&line.order_id]) {
+                        delivery.lines_without_promise_date += 1;
+                    }
+                    continue;
+                };
+                if !in_period(expected) {
+                    continue;
+                }
+                match delivery_outcome(expected, received, now) {
+                    Some(DeliveryOutcome::OnTime) => delivery.on_time += 1,
+                    Some(DeliveryOutcome::Late(days)) => {
+                        delivery.late += 1;
+                        days_late += days;
+                    }
+                    Some(DeliveryOutcome::Overdue) => delivery.overdue += 1,
+                    None => {}
+                }
+            }
+        }
+        delivery.lines_scored = delivery.on_time + delivery.late + delivery.overdue;
+        if delivery.lines_scored > 0 {
+            delivery.on_time_rate = Some(delivery.on_time as f64 / delivery.lines_scored as f64);
+        }
+        if delivery.late > 0 {
+            delivery.average_days_late = Some(days_late as f64 / delivery.late as f64);
+        }
+
+        // Price: each line of the period's orders against the price on record at the order date,
+        // preferring the vendor's own price over one recorded without a vendor
+        let priced_lines: Vec<&OrderItem> = lines
+            .iter()
+            .filter(|line| in_period(orders[&line.order_id]))
+            .collect();
+        let priced_items: Vec<Uuid> = priced_lines.iter().filter_map(|l| l.item_id).collect();
+        let history = item_price_history::table
+            .filter(item_price_history::tenant_id.eq(tenant_id))
+            .filter(item_price_history::item_id.eq_any(&priced_items))
+            .filter(
+                item_price_history::vendor_id
+                    .eq(vendor_id)
+                    .or(item_price_history::vendor_id.is_null()),
+            )
+            .filter(item_price_history::recorded_at.lt(to))
+            .order(item_price_history::recorded_at.desc())
+            .select((
+                item_price_history::item_id,
+                item_price_history::vendor_id,
+                item_price_history::unit_price,
+                item_price_history::recorded_at,
+            ))
+            .load::<(Uuid, Option<Uuid>, f64, DateTime<Utc>)>(&mut conn)
+            .await?;
+
+        let mut price = PriceScore::default();
+        for line in priced_lines {
+            let order_date = orders[&line.order_id];
+            let recorded = |own: bool| {
+                history
+                    .iter()
+                    .find(|(item_id, vendor, _, recorded_at)| {
+                        Some(*item_id) == line.item_id
+                            && vendor.is_some() == own
+                            && *recorded_at <= order_date
+                    })
+                    .map(|(_, _, unit_price, _)| *unit_price)
+            };
+            price.lines += 1;
+            price.ordered_value += line.unit_price * line.quantity as f64;
+            if let Some(reference) = recorded(true).or_else(|| recorded(false)) {
+                price.compared_lines += 1;
+                price.reference_value += reference * line.quantity as f64;
+                price.variance += (line.unit_price - reference) * line.quantity as f64;
+            }
+        }
+        if price.reference_value > 0.0 {
+            price.variance_percent = Some(price.variance / price.reference_value * 100.0);
+        }
+
+        // Quality: NCRs raised against the vendor in the period
+        let ncrs = non_conformances::table
+            .filter(non_conformances::tenant_id.eq(tenant_id))
+            .filter(non_conformances::vendor_id.eq(vendor_id))
+            .filter(non_conformances::status.ne(NcrStatus::Cancelled.to_string()))
+            .filter(non_conformances::created_at.ge(from))
+            .filter(non_conformances::created_at.lt(to))
+            .select((
+                non_conformances::severity,
+                non_conformances::quantity_affected,
+            ))
+            .load::<(String, Option<i32>)>(&mut conn)
+            .await?;
+
+        let mut quality = QualityScore {
+            units_received,
+            ..Default::default()
+        };
+        for (severity, quantity_affected) in ncrs {
+            quality.ncrs += 1;
+            quality.quantity_affected += quantity_affected.unwrap_or(0) as i64;
+            match NcrSeverity::try_from(severity) {
+                Ok(NcrSeverity::Minor) => quality.minor += 1,
+                Ok(NcrSeverity::Major) => quality.major += 1,
+                Ok(NcrSeverity::Critical) => quality.critical += 1,
+                Err(_) => {}
+            }
+        }
+        if units_received > Quantity::ZERO {
+            quality.defect_ppm =
+                Some(quality.quantity_affected as f64 / units_received.to_f64() * 1_000_000.0);
+        }
+
+        Ok(Some(VendorScorecardResponse {
+            vendor_id,
+            name,
+            company,
+            from,
+            to,
+            purchase_orders: orders.values().filter(|date| in_period(**date)).count() as i64,
+            delivery,
+            quality,
+            price,
+        }))
+    }
+}
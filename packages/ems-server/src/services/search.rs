@@ -0,0 +1,166 @@
+use anyhow::Result;
+use diesel::sql_types::{BigInt, Text, Uuid as SqlUuid};
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{SuggestQuery, Suggestion, SuggestionRow, SuggestionType};
+use crate::services::DatabaseService;
+use crate::utils::search::{
+    escape_like, merge_suggestions, parse_suggestion_types, DEFAULT_SUGGEST_LIMIT,
+    MIN_SUGGEST_CHARS,
+};
+use crate::utils::AppError;
+
+/// Errors from [`SearchService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum SearchError {
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<SearchError> for AppError {
+    fn from(error: SearchError) -> Self {
+        match error {
+            SearchError::Invalid(message) => AppError::Validation(message),
+        }
+    }
+}
+
+/// Where suggestions of one type come from. Every matched column has a trigram index, so
+/// substring matches stay cheap.
+struct SuggestionSource {
+    from: &'static str,
+    /// Limits rows to the tenant bound as $1
+    tenant_filter: &'static str,
+    id: &'static str,
+    label: &'static str,
+    detail: &'static str,
+    columns: &'static [&'static str],
+}
+
+impl SuggestionSource {
+    fn for_type(suggestion_type: SuggestionType) -> Self {
+        match suggestion_type {
+            // Items are shared between tenants; a tenant sees those it stocks
+            SuggestionType::Item => Self {
+                from: "items i",
+                tenant_filter: "EXISTS (SELECT 1 FROM inventory_items ii \
+                                WHERE ii.item_id = i.id AND ii.tenant_id = $1)",
+                id: "i.id",
+                label: "i.internal_part_number",
+                detail: "i.manufacturer",
+                columns: &[
+                    "i.internal_part_number",
+                    "i.mfr_part_number",
+                    "i.manufacturer",
+                ],
+            },
+            SuggestionType::Person => Self {
+                from: "person p",
+                tenant_filter: "EXISTS (SELECT 1 FROM tenant_person tp \
+                                WHERE tp.person_id = p.id AND tp.tenant_id = $1)",
+                id: "p.id",
+                label: "p.name",
+                detail: "p.email",
+                columns: &["p.name", "p.email"],
+            },
+            SuggestionType::Machine => Self {
+                from: "machines m",
+                tenant_filter: "m.tenant_id = $1",
+                id: "m.id",
+                label: "m.name",
+                detail: "m.status",
+                columns: &["m.name"],
+            },
+            SuggestionType::Order => Self {
+                from: "orders o",
+                tenant_filter: "o.tenant_id = $1",
+                id: "o.id",
+                label: "o.order_number",
+                detail: "o.order_type",
+                columns: &["o.order_number"],
+            },
+        }
+    }
+
+    /// Binds: $1 tenant, $2 typed text, $3 prefix pattern, $4 substring pattern, $5 limit.
+    /// Exact labels rank first, then labels starting with the text, then by trigram similarity.
+    fn query(&self) -> String {
+        let matches = self
+            .columns
+            .iter()
+            .map(|column| format!("{} ILIKE $4", column))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let similarities = self
+            .columns
+            .iter()
+            .map(|column| format!("similarity({}, $2)", column))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "SELECT {id} AS id, {label}::varchar AS label, {detail}::varchar AS detail, \
+             (CASE WHEN lower({label}) = lower($2) THEN 2 WHEN {label} ILIKE $3 THEN 1 ELSE 0 END \
+              + COALESCE(GREATEST({similarities}), 0))::float8 AS score \
+             FROM {from} \
+             WHERE {tenant_filter} AND ({matches}) \
+             ORDER BY score DESC, {label} ASC \
+             LIMIT $5",
+            id = self.id,
+            label = self.label,
+            detail = self.detail,
+            similarities = similarities,
+            from = self.from,
+            tenant_filter = self.tenant_filter,
+            matches = matches,
+        )
+    }
+}
+
+pub struct SearchService {
+    database: DatabaseService,
+}
+
+impl SearchService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Search operations
+
+    /// Lightweight matches for typeahead fields: at most `limit` (default 8) across the requested
+    /// types. Text shorter than two characters suggests nothing.
+    pub async fn suggest(&self, tenant_id: Uuid, query: SuggestQuery) -> Result<Vec<Suggestion>> {
+        let types = parse_suggestion_types(query.types.as_deref()).map_err(SearchError::Invalid)?;
+        let text = query.q.trim();
+        if text.chars().count() < MIN_SUGGEST_CHARS {
+            return Ok(Vec::new());
+        }
+        let limit = query.limit.unwrap_or(DEFAULT_SUGGEST_LIMIT);
+        let escaped = escape_like(text);
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        // Each type contributes its own best matches, which are then ranked together
+        let mut groups = Vec::with_capacity(types.len());
+        for suggestion_type in types {
+            let rows = diesel::sql_query(SuggestionSource::for_type(suggestion_type).query())
+                .bind::<SqlUuid, _>(tenant_id)
+                .bind::<Text, _>(text)
+                .bind::<Text, _>(format!("{}%", escaped))
+                .bind::<Text, _>(format!("%{}%", escaped))
+                .bind::<BigInt, _>(limit as i64)
+                .load::<SuggestionRow>(&mut conn)
+                .await?;
+            groups.push((suggestion_type, rows));
+        }
+
+        Ok(merge_suggestions(groups, limit as usize))
+    }
+}
@@ -0,0 +1,353 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::env;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, CreateServiceClientRequest, NewServiceClient, Permission, ServiceClient,
+    ServiceClientResponse, ServiceClientSecretResponse, TokenError, TokenRequest, TokenResponse,
+    UpdateServiceClientRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::auth::AuthUtils;
+use crate::utils::service_scope::{parse_scopes, scopes_allow};
+
+// Lifetime of machine tokens when SERVICE_TOKEN_TTL_SECONDS is not set
+const DEFAULT_SERVICE_TOKEN_TTL_SECONDS: i64 = 3600;
+
+/// Internal services calling the API with machine tokens from the client credentials grant.
+/// Clients live in the shared database, since the client id is resolved before the tenant is
+/// known.
+pub struct ServiceClientService {
+    shared: DatabaseService,
+}
+
+impl ServiceClientService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            shared: database.shared(),
+        }
+    }
+
+    // Client methods
+
+    /// Registers a client acting for the caller and returns it with its secret, which is not
+    /// shown again.
+    pub async fn create_client(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateServiceClientRequest,
+    ) -> Result<ServiceClientSecretResponse> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let secret = Self::generate_secret();
+        let client: ServiceClient = diesel::insert_into(service_clients::table)
+            .values(&NewServiceClient {
+                tenant_id,
+                name: request.name,
+                client_id: format!("svc_{}", Uuid::new_v4().simple()),
+                secret_hash: AuthUtils::hash_local_password(&secret)?,
+                scopes: parse_scopes(&request.scopes.join(" ")),
+                owner_id: caller.person_id,
+            })
+            .returning(ServiceClient::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(ServiceClientSecretResponse {
+            client: client.into(),
+            client_secret: secret,
+        })
+    }
+
+    pub async fn list_clients(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Vec<ServiceClientResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let clients = service_clients::table
+            .filter(service_clients::tenant_id.eq(tenant_id))
+            .order(service_clients::name.asc())
+            .select(ServiceClient::as_select())
+            .load::<ServiceClient>(&mut conn)
+            .await?;
+
+        Ok(clients.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_client(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        id: Uuid,
+    ) -> Result<Option<ServiceClientResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_client(&mut conn, tenant_id, id)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Updates the client. Tokens already issued keep the scopes they were issued with until
+    /// they expire, but stop working at once when the client is deactivated.
+    pub async fn update_client(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        id: Uuid,
+        request: UpdateServiceClientRequest,
+    ) -> Result<Option<ServiceClientResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        self.shared
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    let target = service_clients::table
+                        .filter(service_clients::id.eq(id))
+                        .filter(service_clients::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(service_clients::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(scopes) = &request.scopes {
+                        diesel::update(target)
+                            .set(service_clients::scopes.eq(parse_scopes(&scopes.join(" "))))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(service_clients::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(Self::find_client(conn, tenant_id, id)
+                        .await?
+                        .map(Into::into))
+                })
+            })
+            .await
+    }
+
+    /// Replaces the client's secret; the old secret stops working at once, tokens already
+    /// issued with it run until they expire.
+    pub async fn rotate_secret(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        id: Uuid,
+    ) -> Result<Option<ServiceClientSecretResponse>> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let secret = Self::generate_secret();
+        let client = diesel::update(
+            service_clients::table
+                .filter(service_clients::id.eq(id))
+                .filter(service_clients::tenant_id.eq(tenant_id)),
+        )
+        .set(service_clients::secret_hash.eq(AuthUtils::hash_local_password(&secret)?))
+        .returning(ServiceClient::as_returning())
+        .get_result::<ServiceClient>(&mut conn)
+        .await
+        .optional()?;
+
+        Ok(client.map(|client| ServiceClientSecretResponse {
+            client: client.into(),
+            client_secret: secret,
+        }))
+    }
+
+    pub async fn delete_client(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        id: Uuid,
+    ) -> Result<bool> {
+        caller.require(Permission::ManageIntegrations)?;
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            service_clients::table
+                .filter(service_clients::id.eq(id))
+                .filter(service_clients::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Token methods
+
+    /// Client credentials grant: checks the client's secret and issues a machine token acting
+    /// for its owner with the requested scopes, or all of the client's scopes when none are
+    /// requested. Refusals are returned as a [`TokenError`].
+    pub async fn issue_token(&self, request: TokenRequest) -> Result<TokenResponse> {
+        if request.grant_type.is_empty() {
+            return Err(TokenError::InvalidRequest.into());
+        }
+        if request.grant_type != "client_credentials" {
+            return Err(TokenError::UnsupportedGrantType.into());
+        }
+        let (Some(client_id), Some(client_secret)) = (&request.client_id, &request.client_secret)
+        else {
+            return Err(TokenError::InvalidRequest.into());
+        };
+
+        let mut conn = self.shared.get_connection().await?;
+
+        // The client id decides the tenant, so clear any tenant context left on the connection
+        conn.batch_execute("RESET app.current_tenant_id").await?;
+
+        let client = service_clients::table
+            .filter(service_clients::client_id.eq(client_id))
+            .filter(service_clients::is_active.eq(true))
+            .select(ServiceClient::as_select())
+            .first::<ServiceClient>(&mut conn)
+            .await
+            .optional()?
+            .ok_or(TokenError::InvalidClient)?;
+        if !AuthUtils::verify_local_password(client_secret, &client.secret_hash)? {
+            return Err(TokenError::InvalidClient.into());
+        }
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!(
+            "SET app.current_tenant_id = '{}'",
+            client.tenant_id
+        ))
+        .await?;
+
+        // The owner must still be an internal person of an active tenant
+        let owner_is_member = tenant_person::table
+            .inner_join(tenants::table.on(tenant_person::tenant_id.eq(tenants::id)))
+            .filter(tenant_person::tenant_id.eq(client.tenant_id))
+            .filter(tenant_person::person_id.eq(client.owner_id))
+            .filter(tenant_person::role.eq("internal"))
+            .filter(tenants::is_active.eq(true))
+            .select(tenant_person::person_id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some();
+        if !owner_is_member {
+            return Err(TokenError::InvalidClient.into());
+        }
+
+        let scopes = match request.scope.as_deref().map(parse_scopes) {
+            Some(requested) if !requested.is_empty() => requested,
+            _ => client.scopes.clone(),
+        };
+        if scopes.is_empty()
+            || !scopes
+                .iter()
+                .all(|scope| scopes_allow(&client.scopes, scope))
+        {
+            return Err(TokenError::InvalidScope.into());
+        }
+
+        diesel::update(service_clients::table.filter(service_clients::id.eq(client.id)))
+            .set(service_clients::last_used_at.eq(Some(Utc::now())))
+            .execute(&mut conn)
+            .await?;
+
+        let expires_in = Self::token_ttl_seconds();
+        let access_token = AuthUtils::generate_service_token(
+            client.id,
+            client.owner_id,
+            client.tenant_id,
+            &scopes,
+            expires_in,
+        )?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            scope: scopes.join(" "),
+        })
+    }
+
+    /// The active client a machine token was issued to, if it still belongs to the tenant.
+    pub async fn active_client(&self, tenant_id: Uuid, id: Uuid) -> Result<Option<ServiceClient>> {
+        let mut conn = self.shared.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_client(&mut conn, tenant_id, id)
+            .await?
+            .filter(|client| client.is_active))
+    }
+
+    /// Lifetime of machine tokens from SERVICE_TOKEN_TTL_SECONDS
+    pub fn token_ttl_seconds() -> i64 {
+        env::var("SERVICE_TOKEN_TTL_SECONDS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .filter(|ttl| *ttl > 0)
+            .unwrap_or(DEFAULT_SERVICE_TOKEN_TTL_SECONDS)
+    }
+
+    // Helper methods
+
+    fn generate_secret() -> String {
+        format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+    }
+
+    async fn find_client(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ServiceClient>> {
+        Ok(service_clients::table
+            .filter(service_clients::id.eq(id))
+            .filter(service_clients::tenant_id.eq(tenant_id))
+            .select(ServiceClient::as_select())
+            .first::<ServiceClient>(conn)
+            .await
+            .optional()?)
+    }
+}
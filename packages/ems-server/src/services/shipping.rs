@@ -0,0 +1,594 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CarrierLabelRequest, CarrierPackage, CreateShipmentRequest, ListShipmentsQuery, NewShipment,
+    NewShipmentPackage, NewShipmentTrackingEvent, Order, OrderStatus, Shipment, ShipmentPackage,
+    ShipmentResponse, ShipmentStatus, ShipmentTrackingEvent, TrackingUpdate,
+};
+use crate::schema::*;
+use crate::services::{CarrierRegistry, DatabaseService, WebhookCarrier};
+use crate::utils::shipping::{fulfillment_status, tracked_status};
+use crate::utils::AppError;
+
+/// Errors from [`ShippingService`] that are not failures of the service itself
+#[derive(Error, Debug)]
+pub enum ShippingError {
+    #[error("Carrier not configured: {0}")]
+    CarrierNotConfigured(String),
+
+    #[error("Carrier request failed: {0}")]
+    CarrierFailed(String),
+
+    #[error("Order cannot be shipped: {0}")]
+    NotShippable(String),
+
+    #[error("Label already requested")]
+    LabelRequested,
+
+    #[error("Shipment has no tracking number")]
+    NoTrackingNumber,
+
+    #[error("Shipment cannot be cancelled: {0}")]
+    NotCancellable(String),
+
+    #[error("Shipment is cancelled")]
+    Cancelled,
+}
+
+impl From<ShippingError> for AppError {
+    fn from(error: ShippingError) -> Self {
+        match error {
+            ShippingError::CarrierNotConfigured(_) => AppError::Validation(error.to_string()),
+            ShippingError::CarrierFailed(_) => AppError::Upstream(error.to_string()),
+            ShippingError::NotShippable(_)
+            | ShippingError::LabelRequested
+            | ShippingError::NoTrackingNumber
+            | ShippingError::NotCancellable(_)
+            | ShippingError::Cancelled => AppError::Conflict(error.to_string()),
+        }
+    }
+}
+
+pub struct ShippingService {
+    database: DatabaseService,
+    carriers: CarrierRegistry,
+}
+
+impl ShippingService {
+    pub fn new(database: DatabaseService) -> Result<Self> {
+        Ok(Self::with_carriers(database, CarrierRegistry::from_env()?))
+    }
+
+    pub fn with_carriers(database: DatabaseService, carriers: CarrierRegistry) -> Self {
+        Self { database, carriers }
+    }
+
+    // Shipment operations
+
+    pub async fn create_shipment(
+        &self,
+        tenant_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateShipmentRequest,
+    ) -> Result<Option<ShipmentResponse>> {
+        let carrier = request
+            .carrier
+            .unwrap_or_else(|| WebhookCarrier::NAME.to_string());
+        if self.carriers.get(&carrier).is_none() {
+            return Err(ShippingError::CarrierNotConfigured(carrier.to_string()).into());
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let created = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(order) = orders::table
+                        .filter(orders::id.eq(request.order_id))
+                        .filter(orders::tenant_id.eq(tenant_id))
+                        .select(Order::as_select())
+                        .first::<Order>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let shippable = !matches!(
+                        OrderStatus::try_from(order.status.clone()),
+                        Ok(OrderStatus::Draft) | Ok(OrderStatus::Cancelled)
+                    );
+                    if !shippable {
+                        return Err(ShippingError::NotShippable(order.status.clone()).into());
+                    }
+
+                    let new_shipment = NewShipment {
+                        tenant_id,
+                        order_id: order.id,
+                        carrier,
+                        service_level: request.service_level,
+                        ship_to: request.ship_to,
+                        notes: request.notes,
+                        created_by_id,
+                    };
+
+                    let shipment: Shipment = diesel::insert_into(shipments::table)
+                        .values(&new_shipment)
+                        .returning(Shipment::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    let new_packages: Vec<NewShipmentPackage> = request
+                        .packages
+                        .into_iter()
+                        .map(|package| NewShipmentPackage {
+                            tenant_id,
+                            shipment_id: shipment.id,
+                            weight_kg: package.weight_kg,
+                            length_cm: package.length_cm,
+                            width_cm: package.width_cm,
+                            height_cm: package.height_cm,
+                            reference: package.reference,
+                        })
+                        .collect();
+
+                    let packages = diesel::insert_into(shipment_packages::table)
+                        .values(&new_packages)
+                        .returning(ShipmentPackage::as_returning())
+                        .get_results::<ShipmentPackage>(conn)
+                        .await?;
+
+                    Ok(Some(ShipmentResponse::new(shipment, packages, Vec::new())))
+                })
+            })
+            .await?;
+
+        Ok(created)
+    }
+
+    pub async fn list_shipments(
+        &self,
+        tenant_id: Uuid,
+        query: ListShipmentsQuery,
+    ) -> Result<Vec<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut shipments_query = shipments::table
+            .filter(shipments::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(order_id) = query.order_id {
+            shipments_query = shipments_query.filter(shipments::order_id.eq(order_id));
+        }
+
+        if let Some(status) = query.status {
+            shipments_query = shipments_query.filter(shipments::status.eq(status.to_string()));
+        }
+
+        let shipments = shipments_query
+            .order(shipments::created_at.desc())
+            .limit(query.limit.unwrap_or(50))
+            .offset(query.offset.unwrap_or(0))
+            .select(Shipment::as_select())
+            .load::<Shipment>(&mut conn)
+            .await?;
+
+        let shipment_ids: Vec<Uuid> = shipments.iter().map(|s| s.id).collect();
+
+        let mut packages: HashMap<Uuid, Vec<ShipmentPackage>> = HashMap::new();
+        for package in shipment_packages::table
+            .filter(shipment_packages::shipment_id.eq_any(&shipment_ids))
+            .order(shipment_packages::created_at.asc())
+            .select(ShipmentPackage::as_select())
+            .load::<ShipmentPackage>(&mut conn)
+            .await?
+        {
+            packages
+                .entry(package.shipment_id)
+                .or_default()
+                .push(package);
+        }
+
+        let mut events: HashMap<Uuid, Vec<ShipmentTrackingEvent>> = HashMap::new();
+        for event in shipment_tracking_events::table
+            .filter(shipment_tracking_events::shipment_id.eq_any(&shipment_ids))
+            .order(shipment_tracking_events::occurred_at.asc())
+            .select(ShipmentTrackingEvent::as_select())
+            .load::<ShipmentTrackingEvent>(&mut conn)
+            .await?
+        {
+            events.entry(event.shipment_id).or_default().push(event);
+        }
+
+        Ok(shipments
+            .into_iter()
+            .map(|shipment| {
+                let shipment_packages = packages.remove(&shipment.id).unwrap_or_default();
+                let shipment_events = events.remove(&shipment.id).unwrap_or_default();
+                ShipmentResponse::new(shipment, shipment_packages, shipment_events)
+            })
+            .collect())
+    }
+
+    pub async fn get_shipment(
+        &self,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(shipment) = Self::find_shipment(&mut conn, tenant_id, shipment_id).await? else {
+            return Ok(None);
+        };
+
+        Self::load_response(&mut conn, shipment).await.map(Some)
+    }
+
+    /// Requests a label and tracking number for a pending shipment from its carrier.
+    pub async fn request_label(
+        &self,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(shipment) = Self::find_shipment(&mut conn, tenant_id, shipment_id).await? else {
+            return Ok(None);
+        };
+
+        if shipment.status != ShipmentStatus::Pending.to_string() {
+            return Err(ShippingError::LabelRequested.into());
+        }
+
+        let carrier = self
+            .carriers
+            .get(&shipment.carrier)
+            .ok_or_else(|| ShippingError::CarrierNotConfigured(shipment.carrier.clone()))?;
+
+        let order_number = orders::table
+            .filter(orders::id.eq(shipment.order_id))
+            .select(orders::order_number)
+            .first::<String>(&mut conn)
+            .await?;
+
+        let packages = shipment_packages::table
+            .filter(shipment_packages::shipment_id.eq(shipment.id))
+            .order(shipment_packages::created_at.asc())
+            .select(ShipmentPackage::as_select())
+            .load::<ShipmentPackage>(&mut conn)
+            .await?;
+
+        let label_request = CarrierLabelRequest {
+            shipment_id: shipment.id,
+            order_number,
+            service_level: shipment.service_level.clone(),
+            ship_to: shipment.ship_to.clone(),
+            packages: packages.iter().map(CarrierPackage::from).collect(),
+        };
+
+        let label = carrier.create_label(&label_request).await?;
+        let now = Utc::now();
+
+        // Only the request that still finds the shipment pending records its label
+        let updated = diesel::update(
+            shipments::table
+                .filter(shipments::id.eq(shipment.id))
+                .filter(shipments::status.eq(ShipmentStatus::Pending.to_string())),
+        )
+        .set((
+            shipments::status.eq(ShipmentStatus::LabelCreated.to_string()),
+            shipments::tracking_number.eq(&label.tracking_number),
+            shipments::carrier_shipment_id.eq(&label.carrier_shipment_id),
+            shipments::label_url.eq(&label.label_url),
+        ))
+        .returning(Shipment::as_returning())
+        .get_result::<Shipment>(&mut conn)
+        .await
+        .optional()?
+        .ok_or(ShippingError::LabelRequested)?;
+
+        diesel::insert_into(shipment_tracking_events::table)
+            .values(&NewShipmentTrackingEvent {
+                tenant_id,
+                shipment_id: shipment.id,
+                status: ShipmentStatus::LabelCreated.to_string(),
+                description: Some(format!("Label created by {}", carrier.name())),
+                location: None,
+                occurred_at: now,
+            })
+            .execute(&mut conn)
+            .await?;
+
+        Self::load_response(&mut conn, updated).await.map(Some)
+    }
+
+    /// Records tracking events pushed by a carrier (or its webhook bridge).
+    pub async fn record_tracking(
+        &self,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+        updates: Vec<TrackingUpdate>,
+    ) -> Result<Option<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::apply_tracking(&mut conn, tenant_id, shipment_id, updates).await
+    }
+
+    /// Pulls the latest tracking of a shipment from its carrier and records it.
+    pub async fn refresh_tracking(
+        &self,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(shipment) = Self::find_shipment(&mut conn, tenant_id, shipment_id).await? else {
+            return Ok(None);
+        };
+
+        let tracking_number = shipment
+            .tracking_number
+            .ok_or(ShippingError::NoTrackingNumber)?;
+
+        let carrier = self
+            .carriers
+            .get(&shipment.carrier)
+            .ok_or_else(|| ShippingError::CarrierNotConfigured(shipment.carrier.clone()))?;
+
+        let updates = carrier.track(&tracking_number).await?;
+
+        Self::apply_tracking(&mut conn, tenant_id, shipment_id, updates).await
+    }
+
+    /// Cancels a shipment that has not been handed to the carrier yet.
+    pub async fn cancel_shipment(
+        &self,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<ShipmentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(shipment) = Self::find_shipment(&mut conn, tenant_id, shipment_id).await? else {
+            return Ok(None);
+        };
+
+        let cancellable = [ShipmentStatus::Pending, ShipmentStatus::LabelCreated]
+            .iter()
+            .any(|status| shipment.status == status.to_string());
+        if !cancellable {
+            return Err(ShippingError::NotCancellable(shipment.status.clone()).into());
+        }
+
+        let cancelled = diesel::update(shipments::table.filter(shipments::id.eq(shipment.id)))
+            .set(shipments::status.eq(ShipmentStatus::Cancelled.to_string()))
+            .returning(Shipment::as_returning())
+            .get_result::<Shipment>(&mut conn)
+            .await?;
+
+        Self::load_response(&mut conn, cancelled).await.map(Some)
+    }
+
+    // Private helper methods
+
+    async fn find_shipment(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+    ) -> Result<Option<Shipment>> {
+        Ok(shipments::table
+            .filter(shipments::id.eq(shipment_id))
+            .filter(shipments::tenant_id.eq(tenant_id))
+            .select(Shipment::as_select())
+            .first::<Shipment>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn load_response(
+        conn: &mut AsyncPgConnection,
+        shipment: Shipment,
+    ) -> Result<ShipmentResponse> {
+        let packages = shipment_packages::table
+            .filter(shipment_packages::shipment_id.eq(shipment.id))
+            .order(shipment_packages::created_at.asc())
+            .select(ShipmentPackage::as_select())
+            .load::<ShipmentPackage>(conn)
+            .await?;
+
+        let events = shipment_tracking_events::table
+            .filter(shipment_tracking_events::shipment_id.eq(shipment.id))
+            .order(shipment_tracking_events::occurred_at.asc())
+            .select(ShipmentTrackingEvent::as_select())
+            .load::<ShipmentTrackingEvent>(conn)
+            .await?;
+
+        Ok(ShipmentResponse::new(shipment, packages, events))
+    }
+
+    /// Stores new tracking events, moves the shipment to the status they report and, once it is
+    /// delivered, marks the order fulfilled or partially fulfilled.
+    async fn apply_tracking(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        shipment_id: Uuid,
+        updates: Vec<TrackingUpdate>,
+    ) -> Result<Option<ShipmentResponse>> {
+        let shipment = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(shipment) = shipments::table
+                        .filter(shipments::id.eq(shipment_id))
+                        .filter(shipments::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(Shipment::as_select())
+                        .first::<Shipment>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let current = ShipmentStatus::try_from(shipment.status.clone())
+                        .unwrap_or(ShipmentStatus::Pending);
+                    if current == ShipmentStatus::Cancelled {
+                        return Err(ShippingError::Cancelled.into());
+                    }
+
+                    let new_events: Vec<NewShipmentTrackingEvent> = updates
+                        .into_iter()
+                        .filter(|update| update.status.is_tracking_status())
+                        .map(|update| NewShipmentTrackingEvent {
+                            tenant_id,
+                            shipment_id,
+                            status: update.status.to_string(),
+                            description: update.description,
+                            location: update.location,
+                            occurred_at: update.occurred_at,
+                        })
+                        .collect();
+
+                    if !new_events.is_empty() {
+                        // Carriers resend their full history, so known events are skipped
+                        diesel::insert_into(shipment_tracking_events::table)
+                            .values(&new_events)
+                            .on_conflict_do_nothing()
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    let history: Vec<(ShipmentStatus, chrono::DateTime<Utc>)> =
+                        shipment_tracking_events::table
+                            .filter(shipment_tracking_events::shipment_id.eq(shipment_id))
+                            .select((
+                                shipment_tracking_events::status,
+                                shipment_tracking_events::occurred_at,
+                            ))
+                            .load::<(String, chrono::DateTime<Utc>)>(conn)
+                            .await?
+                            .into_iter()
+                            .filter_map(|(status, occurred_at)| {
+                                Some((ShipmentStatus::try_from(status).ok()?, occurred_at))
+                            })
+                            .collect();
+
+                    let status = tracked_status(current, &history);
+                    let first_scan = |wanted: ShipmentStatus| {
+                        history
+                            .iter()
+                            .filter(|(status, _)| *status == wanted)
+                            .map(|(_, occurred_at)| *occurred_at)
+                            .min()
+                    };
+                    let shipped_at = shipment.shipped_at.or_else(|| {
+                        first_scan(ShipmentStatus::InTransit)
+                            .or_else(|| first_scan(ShipmentStatus::Delivered))
+                    });
+                    let delivered_at = shipment
+                        .delivered_at
+                        .or_else(|| first_scan(ShipmentStatus::Delivered));
+
+                    let updated =
+                        diesel::update(shipments::table.filter(shipments::id.eq(shipment_id)))
+                            .set((
+                                shipments::status.eq(status.to_string()),
+                                shipments::shipped_at.eq(shipped_at),
+                                shipments::delivered_at.eq(delivered_at),
+                            ))
+                            .returning(Shipment::as_returning())
+                            .get_result::<Shipment>(conn)
+                            .await?;
+
+                    if status == ShipmentStatus::Delivered && current != ShipmentStatus::Delivered {
+                        Self::update_order_fulfillment(conn, &updated).await?;
+                    }
+
+                    Ok(Some(updated))
+                })
+            })
+            .await?;
+
+        match shipment {
+            Some(shipment) => Self::load_response(conn, shipment).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn update_order_fulfillment(
+        conn: &mut AsyncPgConnection,
+        shipment: &Shipment,
+    ) -> Result<()> {
+        let order = orders::table
+            .filter(orders::id.eq(shipment.order_id))
+            .for_update()
+            .select(Order::as_select())
+            .first::<Order>(conn)
+            .await?;
+
+        // Paid and cancelled orders are past fulfillment tracking
+        let previous = OrderStatus::try_from(order.status.clone()).ok();
+        if matches!(
+            previous,
+            Some(OrderStatus::Paid) | Some(OrderStatus::Cancelled)
+        ) {
+            return Ok(());
+        }
+
+        let statuses: Vec<ShipmentStatus> = shipments::table
+            .filter(shipments::order_id.eq(order.id))
+            .select(shipments::status)
+            .load::<String>(conn)
+            .await?
+            .into_iter()
+            .filter_map(|status| ShipmentStatus::try_from(status).ok())
+            .collect();
+
+        let Some(new_status) = fulfillment_status(&statuses) else {
+            return Ok(());
+        };
+        if previous.as_ref() == Some(&new_status) {
+            return Ok(());
+        }
+
+        // The order history trigger logs the status change
+        diesel::update(orders::table.filter(orders::id.eq(order.id)))
+            .set(orders::status.eq(new_status.to_string()))
+            .execute(conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,664 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    skill_gaps, CreateSkillRequest, FlaggedOperatorAssignment, MachineOperatorAssignment,
+    MachineSkillRequirement, MachineSkillRequirementResponse, NewMachineSkillRequirement,
+    NewPersonSkill, NewSkill, OperatorAssignmentType, PersonSkill, PersonSkillResponse,
+    SetMachineSkillRequirementRequest, SetPersonSkillRequest, Skill, SkillCheckResponse,
+    SkillEnforcement, SkillGap, SkillMatrixResponse, SkillMatrixRow, SkillResponse,
+    UpdateSkillRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::AppError;
+
+/// Errors from [`SkillService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum SkillError {
+    #[error("Skill not found")]
+    NotFound,
+}
+
+impl From<SkillError> for AppError {
+    fn from(error: SkillError) -> Self {
+        match error {
+            SkillError::NotFound => AppError::NotFound(error.to_string()),
+        }
+    }
+}
+
+pub struct SkillService {
+    database: DatabaseService,
+}
+
+impl SkillService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Skill CRUD operations
+
+    pub async fn create_skill(
+        &self,
+        tenant_id: Uuid,
+        request: CreateSkillRequest,
+    ) -> Result<SkillResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let new_skill = NewSkill {
+            tenant_id,
+            name: request.name,
+            description: request.description,
+        };
+
+        let skill: Skill = diesel::insert_into(skills::table)
+            .values(&new_skill)
+            .returning(Skill::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(skill.into())
+    }
+
+    pub async fn list_skills(&self, tenant_id: Uuid) -> Result<Vec<SkillResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let skills = skills::table
+            .filter(skills::tenant_id.eq(tenant_id))
+            .order(skills::name.asc())
+            .select(Skill::as_select())
+            .load::<Skill>(&mut conn)
+            .await?;
+
+        Ok(skills.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_skill(
+        &self,
+        tenant_id: Uuid,
+        skill_id: Uuid,
+    ) -> Result<Option<SkillResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let skill = skills::table
+            .filter(skills::id.eq(skill_id))
+            .filter(skills::tenant_id.eq(tenant_id))
+            .select(Skill::as_select())
+            .first::<Skill>(&mut conn)
+            .await
+            .optional()?;
+
+        Ok(skill.map(Into::into))
+    }
+
+    pub async fn update_skill(
+        &self,
+        tenant_id: Uuid,
+        skill_id: Uuid,
+        request: UpdateSkillRequest,
+    ) -> Result<Option<SkillResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let updated = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                Box::pin(async move {
+                    let target = skills::table
+                        .filter(skills::id.eq(skill_id))
+                        .filter(skills::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(skills::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(description) = &request.description {
+                        diesel::update(target)
+                            .set(skills::description.eq(description))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    target
+                        .select(Skill::as_select())
+                        .first::<Skill>(conn)
+                        .await
+                        .optional()
+                })
+            })
+            .await?;
+
+        Ok(updated.map(Into::into))
+    }
+
+    pub async fn delete_skill(&self, tenant_id: Uuid, skill_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            skills::table
+                .filter(skills::id.eq(skill_id))
+                .filter(skills::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Person certification operations
+
+    /// Certifications held by an internal person; `None` if the person is not internal to the tenant.
+    pub async fn list_person_skills(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<Option<Vec<PersonSkillResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::is_internal_person(&mut conn, tenant_id, person_id).await? {
+            return Ok(None);
+        }
+
+        let certifications = person_skills::table
+            .inner_join(skills::table)
+            .filter(person_skills::person_id.eq(person_id))
+            .filter(person_skills::tenant_id.eq(tenant_id))
+            .order(skills::name.asc())
+            .select((PersonSkill::as_select(), skills::name))
+            .load::<(PersonSkill, String)>(&mut conn)
+            .await?;
+
+        Ok(Some(
+            certifications
+                .into_iter()
+                .map(|(certification, skill_name)| {
+                    PersonSkillResponse::new(certification, skill_name)
+                })
+                .collect(),
+        ))
+    }
+
+    /// Records or renews a certification; `None` if the person is not internal to the tenant.
+    pub async fn set_person_skill(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        skill_id: Uuid,
+        request: SetPersonSkillRequest,
+    ) -> Result<Option<PersonSkillResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::is_internal_person(&mut conn, tenant_id, person_id).await? {
+            return Ok(None);
+        }
+
+        let skill_name = Self::skill_name(&mut conn, tenant_id, skill_id).await?;
+
+        let new_certification = NewPersonSkill {
+            tenant_id,
+            person_id,
+            skill_id,
+            certified_at: request.certified_at,
+            expires_at: request.expires_at,
+            certificate_number: request.certificate_number,
+            notes: request.notes,
+        };
+
+        let certification: PersonSkill = diesel::insert_into(person_skills::table)
+            .values(&new_certification)
+            .on_conflict((person_skills::person_id, person_skills::skill_id))
+            .do_update()
+            .set((
+                person_skills::certified_at.eq(excluded(person_skills::certified_at)),
+                person_skills::expires_at.eq(excluded(person_skills::expires_at)),
+                person_skills::certificate_number.eq(excluded(person_skills::certificate_number)),
+                person_skills::notes.eq(excluded(person_skills::notes)),
+            ))
+            .returning(PersonSkill::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Some(PersonSkillResponse::new(certification, skill_name)))
+    }
+
+    pub async fn delete_person_skill(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        skill_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            person_skills::table
+                .filter(person_skills::person_id.eq(person_id))
+                .filter(person_skills::skill_id.eq(skill_id))
+                .filter(person_skills::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Machine skill requirement operations
+
+    /// Skills a machine requires from its operators; `None` if the machine does not exist.
+    pub async fn list_machine_skill_requirements(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<Option<Vec<MachineSkillRequirementResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::machine_exists(&mut conn, tenant_id, machine_id).await? {
+            return Ok(None);
+        }
+
+        let requirements = Self::load_requirements(&mut conn, tenant_id, &[machine_id]).await?;
+
+        Ok(Some(requirements))
+    }
+
+    /// Adds a required skill to a machine or changes its enforcement; `None` if the machine does not exist.
+    pub async fn set_machine_skill_requirement(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        skill_id: Uuid,
+        request: SetMachineSkillRequirementRequest,
+    ) -> Result<Option<MachineSkillRequirementResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::machine_exists(&mut conn, tenant_id, machine_id).await? {
+            return Ok(None);
+        }
+
+        let skill_name = Self::skill_name(&mut conn, tenant_id, skill_id).await?;
+
+        let new_requirement = NewMachineSkillRequirement {
+            machine_id,
+            skill_id,
+            enforcement: request
+                .enforcement
+                .unwrap_or(SkillEnforcement::Block)
+                .to_string(),
+        };
+
+        let requirement: MachineSkillRequirement =
+            diesel::insert_into(machine_skill_requirements::table)
+                .values(&new_requirement)
+                .on_conflict((
+                    machine_skill_requirements::machine_id,
+                    machine_skill_requirements::skill_id,
+                ))
+                .do_update()
+                .set(
+                    machine_skill_requirements::enforcement
+                        .eq(excluded(machine_skill_requirements::enforcement)),
+                )
+                .returning(MachineSkillRequirement::as_returning())
+                .get_result(&mut conn)
+                .await?;
+
+        Ok(Some(MachineSkillRequirementResponse::new(
+            requirement,
+            skill_name,
+        )))
+    }
+
+    pub async fn delete_machine_skill_requirement(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        skill_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if !Self::machine_exists(&mut conn, tenant_id, machine_id).await? {
+            return Ok(false);
+        }
+
+        let deleted = diesel::delete(
+            machine_skill_requirements::table
+                .filter(machine_skill_requirements::machine_id.eq(machine_id))
+                .filter(machine_skill_requirements::skill_id.eq(skill_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Skill matrix and operator validation
+
+    /// Every internal person of the tenant with the certifications they hold.
+    pub async fn skill_matrix(&self, tenant_id: Uuid) -> Result<SkillMatrixResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let skills = skills::table
+            .filter(skills::tenant_id.eq(tenant_id))
+            .order(skills::name.asc())
+            .select(Skill::as_select())
+            .load::<Skill>(&mut conn)
+            .await?;
+
+        let persons = internal_person::table
+            .inner_join(person::table)
+            .filter(internal_person::tenant_id.eq(tenant_id))
+            .order(person::name.asc())
+            .select((
+                person::id,
+                person::name,
+                internal_person::department,
+                internal_person::position,
+            ))
+            .load::<(Uuid, String, Option<String>, Option<String>)>(&mut conn)
+            .await?;
+
+        let certifications = person_skills::table
+            .filter(person_skills::tenant_id.eq(tenant_id))
+            .select(PersonSkill::as_select())
+            .load::<PersonSkill>(&mut conn)
+            .await?;
+
+        let skill_names: HashMap<Uuid, String> = skills
+            .iter()
+            .map(|skill| (skill.id, skill.name.clone()))
+            .collect();
+        let mut by_person: HashMap<Uuid, Vec<PersonSkillResponse>> = HashMap::new();
+        for certification in certifications {
+            let skill_name = skill_names
+                .get(&certification.skill_id)
+                .cloned()
+                .unwrap_or_default();
+            by_person
+                .entry(certification.person_id)
+                .or_default()
+                .push(PersonSkillResponse::new(certification, skill_name));
+        }
+
+        let persons = persons
+            .into_iter()
+            .map(|(person_id, name, department, position)| {
+                let mut certifications = by_person.remove(&person_id).unwrap_or_default();
+                certifications.sort_by(|a, b| a.skill_name.cmp(&b.skill_name));
+                SkillMatrixRow {
+                    person_id,
+                    name,
+                    department,
+                    position,
+                    certifications,
+                }
+            })
+            .collect();
+
+        Ok(SkillMatrixResponse {
+            skills: skills.into_iter().map(Into::into).collect(),
+            persons,
+        })
+    }
+
+    /// Unmet requirements for each (machine, operator) pair, evaluated now.
+    pub async fn operator_gaps(
+        &self,
+        tenant_id: Uuid,
+        pairs: &[(Uuid, Uuid)],
+    ) -> Result<HashMap<(Uuid, Uuid), Vec<SkillGap>>> {
+        if pairs.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machine_ids: Vec<Uuid> = pairs.iter().map(|(machine_id, _)| *machine_id).collect();
+        let person_ids: Vec<Uuid> = pairs.iter().map(|(_, person_id)| *person_id).collect();
+
+        let mut requirements: HashMap<Uuid, Vec<MachineSkillRequirementResponse>> = HashMap::new();
+        for requirement in Self::load_requirements(&mut conn, tenant_id, &machine_ids).await? {
+            requirements
+                .entry(requirement.machine_id)
+                .or_default()
+                .push(requirement);
+        }
+
+        let mut certifications: HashMap<Uuid, Vec<PersonSkill>> = HashMap::new();
+        for certification in person_skills::table
+            .filter(person_skills::tenant_id.eq(tenant_id))
+            .filter(person_skills::person_id.eq_any(&person_ids))
+            .select(PersonSkill::as_select())
+            .load::<PersonSkill>(&mut conn)
+            .await?
+        {
+            certifications
+                .entry(certification.person_id)
+                .or_default()
+                .push(certification);
+        }
+
+        let now = Utc::now();
+        Ok(pairs
+            .iter()
+            .map(|&(machine_id, person_id)| {
+                let gaps = skill_gaps(
+                    requirements
+                        .get(&machine_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                    certifications
+                        .get(&person_id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                    now,
+                );
+                ((machine_id, person_id), gaps)
+            })
+            .collect())
+    }
+
+    /// Checks whether a person may operate a machine under its skill requirements.
+    pub async fn check_operator(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<SkillCheckResponse> {
+        let gaps = self
+            .operator_gaps(tenant_id, &[(machine_id, person_id)])
+            .await?
+            .remove(&(machine_id, person_id))
+            .unwrap_or_default();
+
+        Ok(SkillCheckResponse {
+            machine_id,
+            person_id,
+            allowed: !gaps.iter().any(SkillGap::is_blocking),
+            gaps,
+        })
+    }
+
+    /// Existing operator assignments whose operator is missing a required skill or whose
+    /// certification has expired since the assignment was made.
+    pub async fn flagged_operator_assignments(
+        &self,
+        tenant_id: Uuid,
+    ) -> Result<Vec<FlaggedOperatorAssignment>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let assignments = machine_operator_assignments::table
+            .inner_join(machines::table)
+            .inner_join(person::table)
+            .filter(machines::tenant_id.eq(tenant_id))
+            .order((machines::name.asc(), person::name.asc()))
+            .select((
+                MachineOperatorAssignment::as_select(),
+                machines::name,
+                person::name,
+            ))
+            .load::<(MachineOperatorAssignment, String, String)>(&mut conn)
+            .await?;
+        drop(conn);
+
+        let pairs: Vec<(Uuid, Uuid)> = assignments
+            .iter()
+            .map(|(assignment, _, _)| (assignment.machine_id, assignment.person_id))
+            .collect();
+        let mut gaps = self.operator_gaps(tenant_id, &pairs).await?;
+
+        Ok(assignments
+            .into_iter()
+            .filter_map(|(assignment, machine_name, person_name)| {
+                let gaps = gaps
+                    .get_mut(&(assignment.machine_id, assignment.person_id))
+                    .map(std::mem::take)
+                    .unwrap_or_default();
+                if gaps.is_empty() {
+                    return None;
+                }
+                Some(FlaggedOperatorAssignment {
+                    assignment_id: assignment.id,
+                    machine_id: assignment.machine_id,
+                    machine_name,
+                    person_id: assignment.person_id,
+                    person_name,
+                    assignment_type: OperatorAssignmentType::try_from(assignment.assignment_type)
+                        .unwrap_or(OperatorAssignmentType::Primary),
+                    gaps,
+                })
+            })
+            .collect())
+    }
+
+    // Helpers
+
+    async fn load_requirements(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+    ) -> Result<Vec<MachineSkillRequirementResponse>> {
+        let requirements = machine_skill_requirements::table
+            .inner_join(skills::table)
+            .filter(machine_skill_requirements::machine_id.eq_any(machine_ids))
+            .filter(skills::tenant_id.eq(tenant_id))
+            .order(skills::name.asc())
+            .select((MachineSkillRequirement::as_select(), skills::name))
+            .load::<(MachineSkillRequirement, String)>(conn)
+            .await?;
+
+        Ok(requirements
+            .into_iter()
+            .map(|(requirement, skill_name)| {
+                MachineSkillRequirementResponse::new(requirement, skill_name)
+            })
+            .collect())
+    }
+
+    async fn skill_name(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        skill_id: Uuid,
+    ) -> Result<String> {
+        skills::table
+            .filter(skills::id.eq(skill_id))
+            .filter(skills::tenant_id.eq(tenant_id))
+            .select(skills::name)
+            .first::<String>(conn)
+            .await
+            .optional()?
+            .ok_or(SkillError::NotFound.into())
+    }
+
+    async fn is_internal_person(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+    ) -> Result<bool> {
+        Ok(internal_person::table
+            .filter(internal_person::person_id.eq(person_id))
+            .filter(internal_person::tenant_id.eq(tenant_id))
+            .select(internal_person::id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()?
+            .is_some())
+    }
+
+    async fn machine_exists(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<bool> {
+        Ok(machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()?
+            .is_some())
+    }
+}
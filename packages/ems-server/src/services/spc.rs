@@ -0,0 +1,635 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::{
+    CreateItemCharacteristicRequest, ItemCharacteristic, ItemCharacteristicResponse,
+    ListItemCharacteristicsQuery, ListMeasurementsQuery, MeasurementSource, NewItemCharacteristic,
+    NewSpcMeasurement, RecordMeasurementsRequest, SpcChartPoint, SpcMeasurement,
+    SpcMeasurementResponse, SpcQuery, SpcStatisticsResponse, UpdateItemCharacteristicRequest,
+    DEFAULT_SPC_POINTS,
+};
+use crate::schema::*;
+use crate::services::{CalibrationService, DatabaseService, QualityError};
+use crate::utils::spc::{
+    capability, control_chart, mean, rule_violations, std_dev, subgroup_points,
+};
+use crate::utils::telemetry::numeric_at;
+
+// Heartbeat measurements written in one insert
+const MEASUREMENT_INSERT_CHUNK: usize = 1_000;
+
+pub struct SpcService {
+    database: DatabaseService,
+}
+
+impl SpcService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Item characteristic methods
+
+    pub async fn create_characteristic(
+        &self,
+        tenant_id: Uuid,
+        request: CreateItemCharacteristicRequest,
+    ) -> Result<ItemCharacteristicResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let found: i64 = items::table
+            .filter(items::id.eq(request.item_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if found == 0 {
+            return Err(QualityError::ReferenceNotFound("Item", request.item_id).into());
+        }
+        if let Some(machine_id) = request.machine_id {
+            Self::ensure_qa_machine(&mut conn, tenant_id, machine_id).await?;
+        }
+
+        let characteristic: ItemCharacteristic = diesel::insert_into(item_characteristics::table)
+            .values(&NewItemCharacteristic {
+                tenant_id,
+                item_id: request.item_id,
+                name: request.name,
+                unit: request.unit,
+                nominal: request.nominal,
+                lower_tolerance: request.lower_tolerance,
+                upper_tolerance: request.upper_tolerance,
+                subgroup_size: request.subgroup_size.unwrap_or(1),
+                machine_id: request.machine_id,
+                payload_pointer: request.payload_pointer,
+            })
+            .returning(ItemCharacteristic::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(characteristic.into())
+    }
+
+    pub async fn list_characteristics(
+        &self,
+        tenant_id: Uuid,
+        query: ListItemCharacteristicsQuery,
+    ) -> Result<Vec<ItemCharacteristicResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut statement = item_characteristics::table
+            .filter(item_characteristics::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(item_id) = query.item_id {
+            statement = statement.filter(item_characteristics::item_id.eq(item_id));
+        }
+        if let Some(machine_id) = query.machine_id {
+            statement = statement.filter(item_characteristics::machine_id.eq(machine_id));
+        }
+        if let Some(is_active) = query.is_active {
+            statement = statement.filter(item_characteristics::is_active.eq(is_active));
+        }
+
+        let characteristics = statement
+            .order((
+                item_characteristics::item_id.asc(),
+                item_characteristics::name.asc(),
+            ))
+            .select(ItemCharacteristic::as_select())
+            .load::<ItemCharacteristic>(&mut conn)
+            .await?;
+
+        Ok(characteristics.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_characteristic(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+    ) -> Result<Option<ItemCharacteristicResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(
+            Self::find_characteristic(&mut conn, tenant_id, characteristic_id)
+                .await?
+                .map(Into::into),
+        )
+    }
+
+    pub async fn update_characteristic(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+        request: UpdateItemCharacteristicRequest,
+    ) -> Result<Option<ItemCharacteristicResponse>> {
+        self.database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    if Self::find_characteristic(conn, tenant_id, characteristic_id)
+                        .await?
+                        .is_none()
+                    {
+                        return Ok(None);
+                    }
+                    if let Some(machine_id) = request.machine_id {
+                        Self::ensure_qa_machine(conn, tenant_id, machine_id).await?;
+                    }
+
+                    let target = item_characteristics::table
+                        .filter(item_characteristics::id.eq(characteristic_id))
+                        .filter(item_characteristics::tenant_id.eq(tenant_id));
+
+                    if let Some(name) = &request.name {
+                        diesel::update(target)
+                            .set(item_characteristics::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(unit) = &request.unit {
+                        diesel::update(target)
+                            .set(item_characteristics::unit.eq(unit))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(nominal) = request.nominal {
+                        diesel::update(target)
+                            .set(item_characteristics::nominal.eq(nominal))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(lower_tolerance) = request.lower_tolerance {
+                        diesel::update(target)
+                            .set(item_characteristics::lower_tolerance.eq(lower_tolerance))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(upper_tolerance) = request.upper_tolerance {
+                        diesel::update(target)
+                            .set(item_characteristics::upper_tolerance.eq(upper_tolerance))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let (Some(machine_id), Some(payload_pointer)) =
+                        (request.machine_id, &request.payload_pointer)
+                    {
+                        diesel::update(target)
+                            .set((
+                                item_characteristics::machine_id.eq(machine_id),
+                                item_characteristics::payload_pointer.eq(payload_pointer),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if request.remove_heartbeat_mapping == Some(true) {
+                        diesel::update(target)
+                            .set((
+                                item_characteristics::machine_id.eq(None::<Uuid>),
+                                item_characteristics::payload_pointer.eq(None::<String>),
+                            ))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(target)
+                            .set(item_characteristics::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(
+                        Self::find_characteristic(conn, tenant_id, characteristic_id)
+                            .await?
+                            .map(Into::into),
+                    )
+                })
+            })
+            .await
+    }
+
+    pub async fn delete_characteristic(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+    ) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            item_characteristics::table
+                .filter(item_characteristics::id.eq(characteristic_id))
+                .filter(item_characteristics::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Measurement methods
+
+    /// Records measurements entered by an inspector. Measurements taken on an out of calibration
+    /// machine or instrument are refused. Returns `None` when the characteristic does not exist.
+    pub async fn record_measurements(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+        recorded_by_id: Option<Uuid>,
+        request: RecordMeasurementsRequest,
+    ) -> Result<Option<Vec<SpcMeasurementResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_characteristic(&mut conn, tenant_id, characteristic_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        if let Some(machine_id) = request.machine_id {
+            let found: i64 = machines::table
+                .filter(machines::id.eq(machine_id))
+                .filter(machines::tenant_id.eq(tenant_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Machine", machine_id).into());
+            }
+        }
+        if let Some(job_id) = request.job_id {
+            let found: i64 = jobs::table
+                .filter(jobs::id.eq(job_id))
+                .filter(jobs::tenant_id.eq(tenant_id))
+                .count()
+                .get_result(&mut conn)
+                .await?;
+            if found == 0 {
+                return Err(QualityError::ReferenceNotFound("Job", job_id).into());
+            }
+        }
+        CalibrationService::ensure_in_calibration(
+            &mut conn,
+            tenant_id,
+            request.machine_id,
+            request.instrument_id,
+        )
+        .await?;
+
+        let now = Utc::now();
+        let measurements: Vec<NewSpcMeasurement> = request
+            .measurements
+            .into_iter()
+            .map(|measurement| NewSpcMeasurement {
+                tenant_id,
+                characteristic_id,
+                value: measurement.value,
+                source: MeasurementSource::Manual.to_string(),
+                machine_id: request.machine_id,
+                job_id: request.job_id,
+                recorded_by_id,
+                measured_at: measurement.measured_at.unwrap_or(now),
+                instrument_id: request.instrument_id,
+            })
+            .collect();
+
+        let recorded = diesel::insert_into(spc_measurements::table)
+            .values(&measurements)
+            .returning(SpcMeasurement::as_returning())
+            .get_results::<SpcMeasurement>(&mut conn)
+            .await?;
+
+        Ok(Some(recorded.into_iter().map(Into::into).collect()))
+    }
+
+    /// Records the values of every active characteristic mapped to the machine's heartbeat
+    /// payload, unless the machine is out of calibration. Returns the number of measurements
+    /// recorded.
+    pub async fn record_heartbeat_measurements(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        payload: &Value,
+        measured_at: DateTime<Utc>,
+    ) -> Result<usize> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::apply_heartbeat_measurements(
+            &mut conn,
+            tenant_id,
+            &[(machine_id, payload, measured_at)],
+        )
+        .await
+    }
+
+    /// [`Self::record_heartbeat_measurements`] for a batch of `(machine, payload, measured at)`
+    /// heartbeats, on a connection the caller holds with the tenant context set: mappings are
+    /// loaded and measurements inserted once for the whole batch.
+    pub async fn apply_heartbeat_measurements(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        heartbeats: &[(Uuid, &Value, DateTime<Utc>)],
+    ) -> Result<usize> {
+        let machine_ids: HashSet<Uuid> = heartbeats.iter().map(|(id, _, _)| *id).collect();
+        if machine_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mappings: Vec<(Uuid, Option<Uuid>, Option<String>)> = item_characteristics::table
+            .filter(item_characteristics::tenant_id.eq(tenant_id))
+            .filter(item_characteristics::machine_id.eq_any(&machine_ids))
+            .filter(item_characteristics::is_active.eq(true))
+            .select((
+                item_characteristics::id,
+                item_characteristics::machine_id,
+                item_characteristics::payload_pointer,
+            ))
+            .load(conn)
+            .await?;
+        let mut pointers: HashMap<Uuid, Vec<(Uuid, String)>> = HashMap::new();
+        for (characteristic_id, machine_id, pointer) in mappings {
+            if let (Some(machine_id), Some(pointer)) = (machine_id, pointer) {
+                pointers
+                    .entry(machine_id)
+                    .or_default()
+                    .push((characteristic_id, pointer));
+            }
+        }
+
+        let mut measurements = Vec::new();
+        let mut in_calibration: HashMap<Uuid, bool> = HashMap::new();
+        for (machine_id, payload, measured_at) in heartbeats {
+            let Some(mapped) = pointers.get(machine_id) else {
+                continue;
+            };
+            let values: Vec<NewSpcMeasurement> = mapped
+                .iter()
+                .filter_map(|(characteristic_id, pointer)| {
+                    Some(NewSpcMeasurement {
+                        tenant_id,
+                        characteristic_id: *characteristic_id,
+                        value: numeric_at(payload, pointer)?,
+                        source: MeasurementSource::Heartbeat.to_string(),
+                        machine_id: Some(*machine_id),
+                        job_id: None,
+                        recorded_by_id: None,
+                        measured_at: *measured_at,
+                        instrument_id: None,
+                    })
+                })
+                .collect();
+            if values.is_empty() {
+                continue;
+            }
+
+            let calibrated = match in_calibration.get(machine_id) {
+                Some(calibrated) => *calibrated,
+                None => {
+                    let calibrated =
+                        CalibrationService::machine_in_calibration(conn, *machine_id).await?;
+                    if !calibrated {
+                        tracing::warn!(
+                            "Machine {} is out of calibration; heartbeat measurements not recorded",
+                            machine_id
+                        );
+                    }
+                    in_calibration.insert(*machine_id, calibrated);
+                    calibrated
+                }
+            };
+            if calibrated {
+                measurements.extend(values);
+            }
+        }
+        let mut inserted = 0;
+        for chunk in measurements.chunks(MEASUREMENT_INSERT_CHUNK) {
+            inserted += diesel::insert_into(spc_measurements::table)
+                .values(chunk)
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Measurements of a characteristic, latest first. Returns `None` when the characteristic
+    /// does not exist.
+    pub async fn list_measurements(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+        query: ListMeasurementsQuery,
+    ) -> Result<Option<Vec<SpcMeasurementResponse>>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_characteristic(&mut conn, tenant_id, characteristic_id)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let mut statement = spc_measurements::table
+            .filter(spc_measurements::characteristic_id.eq(characteristic_id))
+            .into_boxed();
+        if let Some(from) = query.from {
+            statement = statement.filter(spc_measurements::measured_at.ge(from));
+        }
+        if let Some(to) = query.to {
+            statement = statement.filter(spc_measurements::measured_at.lt(to));
+        }
+
+        let measurements = statement
+            .order(spc_measurements::measured_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(SpcMeasurement::as_select())
+            .load::<SpcMeasurement>(&mut conn)
+            .await?;
+
+        Ok(Some(measurements.into_iter().map(Into::into).collect()))
+    }
+
+    /// Control chart, run rule violations and capability over the most recent points of a
+    /// characteristic. Returns `None` when the characteristic does not exist.
+    pub async fn get_statistics(
+        &self,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+        query: SpcQuery,
+    ) -> Result<Option<SpcStatisticsResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(characteristic) =
+            Self::find_characteristic(&mut conn, tenant_id, characteristic_id).await?
+        else {
+            return Ok(None);
+        };
+        let subgroup_size = characteristic.subgroup_size.max(1) as usize;
+
+        let mut statement = spc_measurements::table
+            .filter(spc_measurements::characteristic_id.eq(characteristic_id))
+            .into_boxed();
+        if let Some(from) = query.from {
+            statement = statement.filter(spc_measurements::measured_at.ge(from));
+        }
+        if let Some(to) = query.to {
+            statement = statement.filter(spc_measurements::measured_at.lt(to));
+        }
+
+        let mut measurements: Vec<(f64, DateTime<Utc>)> = statement
+            .order(spc_measurements::measured_at.desc())
+            .limit(query.points.unwrap_or(DEFAULT_SPC_POINTS) * subgroup_size as i64)
+            .select((spc_measurements::value, spc_measurements::measured_at))
+            .load(&mut conn)
+            .await?;
+        measurements.reverse();
+        // Leave out measurements that do not fill the first subgroup
+        let measurements = &measurements[measurements.len() % subgroup_size..];
+        let values: Vec<f64> = measurements.iter().map(|(value, _)| *value).collect();
+
+        let lower_spec_limit = characteristic.lower_spec_limit();
+        let upper_spec_limit = characteristic.upper_spec_limit();
+        let chart = control_chart(&values, subgroup_size);
+        let process_mean = (!values.is_empty()).then(|| mean(&values));
+        let (cp, cpk) = match (&chart, process_mean) {
+            (Some(chart), Some(process_mean)) => capability(
+                process_mean,
+                chart.sigma_within,
+                lower_spec_limit,
+                upper_spec_limit,
+            ),
+            _ => (None, None),
+        };
+
+        let violations = chart
+            .as_ref()
+            .map(|chart| rule_violations(&chart.points, chart.center_line, chart.point_sigma()))
+            .unwrap_or_default();
+        let (points, ranges) = subgroup_points(&values, subgroup_size);
+        let points: Vec<SpcChartPoint> = points
+            .into_iter()
+            .zip(ranges)
+            .enumerate()
+            .map(|(index, (value, range))| SpcChartPoint {
+                value,
+                range,
+                measured_at: measurements[(index + 1) * subgroup_size - 1].1,
+                violations: violations
+                    .iter()
+                    .filter(|(point, _)| *point == index)
+                    .map(|(_, rule)| *rule)
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Some(SpcStatisticsResponse {
+            characteristic_id,
+            subgroup_size: characteristic.subgroup_size,
+            measurement_count: values.len(),
+            nominal: characteristic.nominal,
+            lower_spec_limit,
+            upper_spec_limit,
+            mean: process_mean,
+            std_dev: std_dev(&values),
+            sigma_within: chart.as_ref().map(|c| c.sigma_within),
+            center_line: chart.as_ref().map(|c| c.center_line),
+            upper_control_limit: chart.as_ref().map(|c| c.upper_control_limit),
+            lower_control_limit: chart.as_ref().map(|c| c.lower_control_limit),
+            range_center_line: chart.as_ref().map(|c| c.range_center_line),
+            range_upper_control_limit: chart.as_ref().map(|c| c.range_upper_control_limit),
+            range_lower_control_limit: chart.as_ref().map(|c| c.range_lower_control_limit),
+            cp,
+            cpk,
+            out_of_spec_count: values
+                .iter()
+                .filter(|&&value| {
+                    lower_spec_limit.is_some_and(|lower| value < lower)
+                        || upper_spec_limit.is_some_and(|upper| value > upper)
+                })
+                .count(),
+            in_control: violations.is_empty(),
+            points,
+        }))
+    }
+
+    // Helper methods
+
+    async fn find_characteristic(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        characteristic_id: Uuid,
+    ) -> Result<Option<ItemCharacteristic>> {
+        Ok(item_characteristics::table
+            .filter(item_characteristics::id.eq(characteristic_id))
+            .filter(item_characteristics::tenant_id.eq(tenant_id))
+            .select(ItemCharacteristic::as_select())
+            .first::<ItemCharacteristic>(conn)
+            .await
+            .optional()?)
+    }
+
+    /// Heartbeat mappings are limited to QA machines, those with `metadata.category` "qa".
+    async fn ensure_qa_machine(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+    ) -> Result<()> {
+        let metadata = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::metadata)
+            .first::<Option<Value>>(conn)
+            .await
+            .optional()?
+            .ok_or(QualityError::ReferenceNotFound("Machine", machine_id))?;
+
+        let category = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("category"))
+            .and_then(Value::as_str);
+        if category != Some("qa") {
+            return Err(QualityError::NotQaMachine(machine_id).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,413 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CalendarExceptionType, CallerContext, MachineStatus, NewStatusPage, Permission,
+    PublicStatusPage, StatusPage, StatusPageResponse, StatusPageTokenResponse,
+    UpdateStatusPageRequest, DEFAULT_MAINTENANCE_DAYS, DEFAULT_STALE_HEARTBEAT_MINUTES,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, TenantCache, TenantService};
+use crate::utils::auth::AuthUtils;
+use crate::utils::status_page::{
+    public_status_page, status_page_token, status_token_prefix, status_token_tenant, LineState,
+    MachineState, PlannedDowntime,
+};
+use crate::utils::AppError;
+
+/// Errors from [`StatusPageService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum StatusPageError {
+    #[error("Machine not found")]
+    MachineNotFound,
+
+    #[error("Machine group not found")]
+    MachineGroupNotFound,
+}
+
+impl From<StatusPageError> for AppError {
+    fn from(error: StatusPageError) -> Self {
+        match error {
+            StatusPageError::MachineNotFound | StatusPageError::MachineGroupNotFound => {
+                AppError::Validation(error.to_string())
+            }
+        }
+    }
+}
+
+/// A tenant's public status page: which of its machines and lines customers see, and the token
+/// in the page URL, which decides the tenant when the page is served.
+pub struct StatusPageService {
+    database: DatabaseService,
+    tenants: TenantService,
+}
+
+impl StatusPageService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            tenants: TenantService::new(database.clone()),
+            database,
+        }
+    }
+
+    /// Serves tenant lookups for public pages from the cache.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.tenants = self.tenants.with_cache(cache);
+        self
+    }
+
+    // Status page configuration
+
+    /// The tenant's status page configuration. `Ok(None)` until it has been set up.
+    pub async fn get_status_page(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Option<StatusPageResponse>> {
+        caller.require(Permission::ManageStatusPage)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_page(&mut conn, tenant_id).await?.map(Into::into))
+    }
+
+    /// Sets up the tenant's status page, or replaces its configuration, keeping its token. Every
+    /// machine and machine group shown must belong to the tenant.
+    pub async fn update_status_page(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: UpdateStatusPageRequest,
+    ) -> Result<StatusPageResponse> {
+        caller.require(Permission::ManageStatusPage)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machine_ids = dedup(request.machine_ids);
+        let found: i64 = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(&machine_ids))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if found != machine_ids.len() as i64 {
+            return Err(StatusPageError::MachineNotFound.into());
+        }
+
+        let group_ids = dedup(request.group_ids);
+        let found: i64 = machine_groups::table
+            .filter(machine_groups::tenant_id.eq(tenant_id))
+            .filter(machine_groups::id.eq_any(&group_ids))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if found != group_ids.len() as i64 {
+            return Err(StatusPageError::MachineGroupNotFound.into());
+        }
+
+        let show_maintenance = request.show_maintenance.unwrap_or(true);
+        let maintenance_days = request.maintenance_days.unwrap_or(DEFAULT_MAINTENANCE_DAYS);
+        let page = diesel::insert_into(status_pages::table)
+            .values(&NewStatusPage {
+                tenant_id,
+                enabled: request.enabled,
+                title: request.title.clone(),
+                description: request.description.clone(),
+                machine_ids: machine_ids.clone(),
+                group_ids: group_ids.clone(),
+                show_maintenance,
+                maintenance_days,
+                updated_by_id: Some(caller.person_id),
+            })
+            .on_conflict(status_pages::tenant_id)
+            .do_update()
+            .set((
+                status_pages::enabled.eq(request.enabled),
+                status_pages::title.eq(request.title),
+                status_pages::description.eq(request.description),
+                status_pages::machine_ids.eq(machine_ids),
+                status_pages::group_ids.eq(group_ids),
+                status_pages::show_maintenance.eq(show_maintenance),
+                status_pages::maintenance_days.eq(maintenance_days),
+                status_pages::updated_by_id.eq(Some(caller.person_id)),
+            ))
+            .returning(StatusPage::as_returning())
+            .get_result::<StatusPage>(&mut conn)
+            .await?;
+
+        Ok(page.into())
+    }
+
+    /// Issues a new token for the status page URL, which stops the previous one working. The
+    /// token is not shown again. `Ok(None)` when the page has not been set up.
+    pub async fn issue_token(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+    ) -> Result<Option<StatusPageTokenResponse>> {
+        caller.require(Permission::ManageStatusPage)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let token = status_page_token(tenant_id);
+        let page =
+            diesel::update(status_pages::table.filter(status_pages::tenant_id.eq(tenant_id)))
+                .set((
+                    status_pages::token_prefix.eq(Some(status_token_prefix(&token))),
+                    status_pages::token_hash.eq(Some(AuthUtils::hash_token(&token))),
+                    status_pages::token_issued_at.eq(Some(Utc::now())),
+                    status_pages::updated_by_id.eq(Some(caller.person_id)),
+                ))
+                .returning(StatusPage::as_returning())
+                .get_result::<StatusPage>(&mut conn)
+                .await
+                .optional()?;
+
+        Ok(page.map(|page| StatusPageTokenResponse {
+            status_page: page.into(),
+            token,
+        }))
+    }
+
+    // Public status page (the token in the URL decides the tenant)
+
+    /// The enabled status page the token is for, as the public sees it.
+    pub async fn public_page(&self, token: &str) -> Result<Option<PublicStatusPage>> {
+        let Some(tenant_id) = self.resolve_tenant(token).await? else {
+            return Ok(None);
+        };
+        DatabaseService::scope_tenant(tenant_id, async {
+            let mut conn = self.database.get_connection().await?;
+
+            // Set tenant context for RLS
+            conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+                .await?;
+
+            let Some(page) = status_pages::table
+                .filter(status_pages::tenant_id.eq(tenant_id))
+                .filter(status_pages::token_hash.eq(AuthUtils::hash_token(token)))
+                .filter(status_pages::enabled.eq(true))
+                .select(StatusPage::as_select())
+                .first::<StatusPage>(&mut conn)
+                .await
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            let now = Utc::now();
+            let lines = Self::load_lines(&mut conn, tenant_id, &page.group_ids).await?;
+            let mut machine_ids = page.machine_ids.clone();
+            machine_ids.extend(
+                lines
+                    .iter()
+                    .flat_map(|line| line.members.iter().map(|m| m.0)),
+            );
+            let machines = Self::load_machines(&mut conn, tenant_id, &dedup(machine_ids)).await?;
+            let machine_ids: Vec<Uuid> = machines.keys().copied().collect();
+            let downtime = Self::load_planned_downtime(
+                &mut conn,
+                tenant_id,
+                &machine_ids,
+                now,
+                now + Duration::days(page.maintenance_days as i64),
+            )
+            .await?;
+
+            Ok(Some(public_status_page(
+                &page,
+                &machines,
+                &lines,
+                &downtime,
+                now,
+                now - Duration::minutes(DEFAULT_STALE_HEARTBEAT_MINUTES as i64),
+            )))
+        })
+        .await
+    }
+
+    // Private helper methods
+
+    // The active tenant a status page token names, routing connections to its database
+    async fn resolve_tenant(&self, token: &str) -> Result<Option<Uuid>> {
+        let Some(tenant_id) = status_token_tenant(token) else {
+            return Ok(None);
+        };
+        let Some(tenant) = self
+            .tenants
+            .get_tenant_by_id(tenant_id)
+            .await?
+            .filter(|tenant| tenant.is_active.unwrap_or(false))
+        else {
+            return Ok(None);
+        };
+        if let Some(database_url) = &tenant.database_url {
+            if self.database.dedicated_databases_enabled() {
+                self.database
+                    .register_tenant_database(tenant.id, database_url)
+                    .await?;
+            }
+        }
+        Ok(Some(tenant.id))
+    }
+
+    async fn find_page(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+    ) -> Result<Option<StatusPage>> {
+        Ok(status_pages::table
+            .filter(status_pages::tenant_id.eq(tenant_id))
+            .select(StatusPage::as_select())
+            .first::<StatusPage>(conn)
+            .await
+            .optional()?)
+    }
+
+    // The groups that still exist with their members in line order
+    async fn load_lines(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        group_ids: &[Uuid],
+    ) -> Result<Vec<LineState>> {
+        if group_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let groups: Vec<(Uuid, String)> = machine_groups::table
+            .filter(machine_groups::tenant_id.eq(tenant_id))
+            .filter(machine_groups::id.eq_any(group_ids))
+            .select((machine_groups::id, machine_groups::name))
+            .load(conn)
+            .await?;
+        let members: Vec<(Uuid, Uuid, bool)> = machine_group_members::table
+            .filter(machine_group_members::tenant_id.eq(tenant_id))
+            .filter(machine_group_members::group_id.eq_any(group_ids))
+            .order((
+                machine_group_members::group_id,
+                machine_group_members::position,
+            ))
+            .select((
+                machine_group_members::group_id,
+                machine_group_members::machine_id,
+                machine_group_members::is_critical,
+            ))
+            .load(conn)
+            .await?;
+
+        let mut members_by_group: HashMap<Uuid, Vec<(Uuid, bool)>> = HashMap::new();
+        for (group_id, machine_id, is_critical) in members {
+            members_by_group
+                .entry(group_id)
+                .or_default()
+                .push((machine_id, is_critical));
+        }
+        Ok(groups
+            .into_iter()
+            .map(|(id, name)| LineState {
+                id,
+                name,
+                members: members_by_group.remove(&id).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    async fn load_machines(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, MachineState>> {
+        if machine_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<(Uuid, String, String, Option<DateTime<Utc>>)> = machines::table
+            .filter(machines::tenant_id.eq(tenant_id))
+            .filter(machines::id.eq_any(machine_ids))
+            .select((
+                machines::id,
+                machines::name,
+                machines::status,
+                machines::last_heartbeat,
+            ))
+            .load(conn)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, name, status, last_heartbeat)| {
+                let state = MachineState {
+                    name,
+                    status: MachineStatus::try_from(status).unwrap_or(MachineStatus::Offline),
+                    last_heartbeat,
+                };
+                (id, state)
+            })
+            .collect())
+    }
+
+    // Planned downtime of the machines, or of every machine, overlapping [from, to)
+    async fn load_planned_downtime(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        machine_ids: &[Uuid],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PlannedDowntime>> {
+        let rows: Vec<(Option<Uuid>, DateTime<Utc>, DateTime<Utc>)> = calendar_exceptions::table
+            .filter(calendar_exceptions::tenant_id.eq(tenant_id))
+            .filter(
+                calendar_exceptions::exception_type
+                    .eq(CalendarExceptionType::PlannedDowntime.to_string()),
+            )
+            .filter(calendar_exceptions::ends_at.gt(from))
+            .filter(calendar_exceptions::starts_at.lt(to))
+            .filter(
+                calendar_exceptions::machine_id
+                    .is_null()
+                    .or(calendar_exceptions::machine_id.eq_any(machine_ids)),
+            )
+            .order(calendar_exceptions::starts_at)
+            .select((
+                calendar_exceptions::machine_id,
+                calendar_exceptions::starts_at,
+                calendar_exceptions::ends_at,
+            ))
+            .load(conn)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(machine_id, starts_at, ends_at)| PlannedDowntime {
+                machine_id,
+                starts_at,
+                ends_at,
+            })
+            .collect())
+    }
+}
+
+// The ids in their first order, without repeats
+fn dedup(ids: Vec<Uuid>) -> Vec<Uuid> {
+    let mut unique = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    unique
+}
@@ -0,0 +1,1080 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    AvailableToPromiseQuery, AvailableToPromiseResponse, CreateStockReservationRequest,
+    DemandForecastQuery, DemandForecastResponse, ForecastMethod, InventoryItem, ItemAnalyticsQuery,
+    ItemAnalyticsResponse, ItemContext, JobStatus, ListStockReservationsQuery,
+    MonthlyConsumptionRow, MonthlyMovementRow, MonthlyQuantity, MovementActivityRow,
+    NewStockMovement, NewStockReservation, Order, OrderItem, OrderStatus, OrderType, Quantity,
+    RecordStockMovementRequest, ReservationStatus, StockAvailabilityResponse, StockMovement,
+    StockMovementResponse, StockMovementType, StockReferenceType, StockReservation,
+    StockReservationResponse,
+};
+use crate::schema::*;
+use crate::services::{ConsignmentService, DatabaseService, ItemError, UomService};
+use crate::utils::atp::{atp_schedule, available_on, promise_date};
+use crate::utils::forecast::{
+    average_balance, exponential_smoothing, moving_average, safety_stock, standard_deviation,
+    DAYS_PER_MONTH,
+};
+
+// Lead time assumed when neither the request nor the inventory record provides one
+const DEFAULT_LEAD_TIME_DAYS: u32 = 30;
+
+pub struct StockService {
+    database: DatabaseService,
+}
+
+impl StockService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Stock movement ledger
+
+    /// Applies a movement to the item's inventory record for the given context and
+    /// appends it to the ledger. Returns `None` when the tenant holds no such inventory record.
+    pub async fn record_movement(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        person_id: Option<Uuid>,
+        request: RecordStockMovementRequest,
+    ) -> Result<Option<StockMovementResponse>, ItemError> {
+        let delta = request.movement_type.signed_quantity(request.quantity);
+        if delta.is_zero() {
+            return Err(ItemError::InvalidMovement(
+                "quantity must not be zero".to_string(),
+            ));
+        }
+
+        let movement = self
+            .database
+            .with_tenant_tx::<_, ItemError, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    Self::apply_movement(conn, tenant_id, item_id, person_id, &request).await
+                })
+            })
+            .await?;
+
+        Ok(movement.map(movement_to_response))
+    }
+
+    /// Applies a movement inside the caller's transaction, locking the inventory record so
+    /// concurrent movements serialize. Returns `None` when there is no such inventory record.
+    pub async fn apply_movement(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        person_id: Option<Uuid>,
+        request: &RecordStockMovementRequest,
+    ) -> Result<Option<StockMovement>, ItemError> {
+        // Stock is counted in the item's base unit
+        let quantity = match request.uom_id {
+            Some(uom_id) => {
+                UomService::to_base_quantity(conn, tenant_id, item_id, uom_id, request.quantity)
+                    .await
+                    .map_err(ItemError::from_uom)?
+            }
+            None => {
+                UomService::check_base_quantity(conn, tenant_id, item_id, request.quantity)
+                    .await
+                    .map_err(ItemError::from_uom)?;
+                request.quantity
+            }
+        };
+        let delta = request.movement_type.signed_quantity(quantity);
+        if delta.is_zero() {
+            return Err(ItemError::InvalidMovement(
+                "quantity must not be zero".to_string(),
+            ));
+        }
+
+        // Lock the inventory record so concurrent movements serialize
+        let inventory = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .filter(inventory_items::context.eq(request.context.to_string()))
+            .for_update()
+            .select(InventoryItem::as_select())
+            .first::<InventoryItem>(conn)
+            .await
+            .optional()?;
+
+        let inventory = match inventory {
+            Some(inventory) => inventory,
+            None => return Ok(None),
+        };
+
+        let on_hand = inventory.quantity.unwrap_or_default();
+        let quantity_after = on_hand + delta;
+        if quantity_after < Quantity::ZERO {
+            return Err(ItemError::InsufficientStock(format!(
+                "{} on hand, movement of {}",
+                on_hand, delta
+            )));
+        }
+
+        // Stock reserved for the movement's own order or job is drawn down by it; stock
+        // reserved for anyone else must survive the movement
+        let mut own_reservations = Vec::new();
+        if delta < Quantity::ZERO {
+            let reference = request
+                .reference_type
+                .as_ref()
+                .zip(request.reference_id)
+                .map(|(reference_type, id)| (reference_type.to_string(), id));
+            let (own, others): (Vec<StockReservation>, Vec<StockReservation>) =
+                Self::active_reservations(conn, inventory.id)
+                    .await?
+                    .into_iter()
+                    .partition(|r| {
+                        reference.as_ref().is_some_and(|(reference_type, id)| {
+                            r.reference_type == *reference_type && r.reference_id == *id
+                        })
+                    });
+
+            let reserved_for_others: Quantity = others.iter().map(|r| r.quantity).sum();
+            if quantity_after < reserved_for_others {
+                if !request.allow_reserved {
+                    return Err(ItemError::InsufficientStock(format!(
+                        "{} of {} on hand reserved for other orders or jobs, movement of {}",
+                        reserved_for_others, on_hand, delta
+                    )));
+                }
+                tracing::warn!(
+                    "Movement of {} on inventory {} breaks reservations: {} left, {} reserved",
+                    delta,
+                    inventory.id,
+                    quantity_after,
+                    reserved_for_others
+                );
+            }
+            own_reservations = own;
+        }
+
+        diesel::update(inventory_items::table.find(inventory.id))
+            .set(inventory_items::quantity.eq(Some(quantity_after)))
+            .execute(conn)
+            .await?;
+
+        if matches!(request.movement_type, StockMovementType::Receipt) {
+            diesel::update(inventory_items::table.find(inventory.id))
+                .set(inventory_items::last_received_date.eq(Some(Utc::now())))
+                .execute(conn)
+                .await?;
+        }
+
+        let new_movement = NewStockMovement {
+            tenant_id,
+            item_id,
+            inventory_item_id: inventory.id,
+            context: request.context.to_string(),
+            movement_type: request.movement_type.to_string(),
+            quantity: delta,
+            quantity_after,
+            reference_type: request.reference_type.as_ref().map(|r| r.to_string()),
+            reference_id: request.reference_id,
+            person_id,
+            notes: request.notes.clone(),
+            occurred_at: request.occurred_at.unwrap_or_else(Utc::now),
+            uom_id: request.uom_id,
+            uom_quantity: request
+                .uom_id
+                .map(|_| request.movement_type.signed_quantity(request.quantity)),
+            owner_id: request.owner_id,
+        };
+
+        let movement: StockMovement = diesel::insert_into(stock_movements::table)
+            .values(&new_movement)
+            .returning(StockMovement::as_returning())
+            .get_result(conn)
+            .await?;
+
+        // Partners' consignment stock moves with the record; consuming it bills them
+        ConsignmentService::apply_movement(conn, &inventory, &movement, on_hand).await?;
+
+        // Oldest reservations are consumed first
+        let mut remaining = -delta;
+        for reservation in own_reservations {
+            if remaining.is_zero() {
+                break;
+            }
+            let consumed = remaining.min(reservation.quantity);
+            remaining -= consumed;
+
+            let left = reservation.quantity - consumed;
+            let status = if left.is_zero() {
+                ReservationStatus::Consumed
+            } else {
+                ReservationStatus::Active
+            };
+            diesel::update(stock_reservations::table.find(reservation.id))
+                .set((
+                    stock_reservations::quantity.eq(left),
+                    stock_reservations::status.eq(status.to_string()),
+                ))
+                .execute(conn)
+                .await?;
+        }
+
+        Ok(Some(movement))
+    }
+
+    pub async fn list_movements(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        context: Option<ItemContext>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> Result<Vec<StockMovementResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = stock_movements::table
+            .filter(stock_movements::tenant_id.eq(tenant_id))
+            .filter(stock_movements::item_id.eq(item_id))
+            .into_boxed();
+
+        if let Some(context) = &context {
+            query = query.filter(stock_movements::context.eq(context.to_string()));
+        }
+
+        let movements = query
+            .order(stock_movements::occurred_at.desc())
+            .limit(limit.unwrap_or(100) as i64)
+            .offset(offset.unwrap_or(0) as i64)
+            .select(StockMovement::as_select())
+            .load::<StockMovement>(&mut conn)
+            .await?;
+
+        Ok(movements.into_iter().map(movement_to_response).collect())
+    }
+
+    // Stock reservations
+
+    /// Soft-allocates stock to a confirmed order or an open job. Only stock that is on hand and
+    /// not reserved yet can be reserved. Returns `None` when the reference or the inventory
+    /// record does not exist.
+    pub async fn reserve_stock(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        created_by_id: Option<Uuid>,
+        request: CreateStockReservationRequest,
+    ) -> Result<Option<StockReservationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let reservation = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    if !Self::check_reference(
+                        conn,
+                        tenant_id,
+                        &request.reference_type,
+                        request.reference_id,
+                    )
+                    .await?
+                    {
+                        return Ok(None);
+                    }
+
+                    // Locking the inventory record serializes reservations against it
+                    let Some(inventory) = inventory_items::table
+                        .filter(inventory_items::tenant_id.eq(tenant_id))
+                        .filter(inventory_items::item_id.eq(item_id))
+                        .filter(inventory_items::context.eq(request.context.to_string()))
+                        .for_update()
+                        .select(InventoryItem::as_select())
+                        .first::<InventoryItem>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    UomService::check_base_quantity(conn, tenant_id, item_id, request.quantity)
+                        .await?;
+
+                    let on_hand = inventory.quantity.unwrap_or_default();
+                    let reserved: Quantity = Self::active_reservations(conn, inventory.id)
+                        .await?
+                        .iter()
+                        .map(|r| r.quantity)
+                        .sum();
+                    let available = (on_hand - reserved).max(Quantity::ZERO);
+                    if request.quantity > available {
+                        return Err(ItemError::InsufficientStock(format!(
+                            "{} available to reserve ({} on hand, {} reserved)",
+                            available, on_hand, reserved
+                        ))
+                        .into());
+                    }
+
+                    let new_reservation = NewStockReservation {
+                        tenant_id,
+                        item_id,
+                        inventory_item_id: inventory.id,
+                        context: request.context.to_string(),
+                        quantity: request.quantity,
+                        reference_type: request.reference_type.to_string(),
+                        reference_id: request.reference_id,
+                        notes: request.notes,
+                        created_by_id,
+                    };
+
+                    let reservation: StockReservation =
+                        diesel::insert_into(stock_reservations::table)
+                            .values(&new_reservation)
+                            .returning(StockReservation::as_returning())
+                            .get_result(conn)
+                            .await?;
+
+                    Ok(Some(reservation))
+                })
+            })
+            .await?;
+
+        Ok(reservation.map(Into::into))
+    }
+
+    pub async fn list_reservations(
+        &self,
+        tenant_id: Uuid,
+        query: ListStockReservationsQuery,
+    ) -> Result<Vec<StockReservationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut reservations_query = stock_reservations::table
+            .filter(stock_reservations::tenant_id.eq(tenant_id))
+            .into_boxed();
+
+        if let Some(item_id) = query.item_id {
+            reservations_query = reservations_query.filter(stock_reservations::item_id.eq(item_id));
+        }
+        if let Some(context) = &query.context {
+            reservations_query =
+                reservations_query.filter(stock_reservations::context.eq(context.to_string()));
+        }
+        if let Some(status) = query.status {
+            reservations_query =
+                reservations_query.filter(stock_reservations::status.eq(status.to_string()));
+        }
+        if let Some(reference_type) = &query.reference_type {
+            reservations_query = reservations_query
+                .filter(stock_reservations::reference_type.eq(reference_type.to_string()));
+        }
+        if let Some(reference_id) = query.reference_id {
+            reservations_query =
+                reservations_query.filter(stock_reservations::reference_id.eq(reference_id));
+        }
+
+        let reservations = reservations_query
+            .order(stock_reservations::created_at.desc())
+            .limit(query.limit.unwrap_or(100))
+            .offset(query.offset.unwrap_or(0))
+            .select(StockReservation::as_select())
+            .load::<StockReservation>(&mut conn)
+            .await?;
+
+        Ok(reservations.into_iter().map(Into::into).collect())
+    }
+
+    /// On hand, reserved and available stock per inventory record of the item, with the active
+    /// reservations behind the reserved figure.
+    pub async fn get_availability(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        context: Option<ItemContext>,
+    ) -> Result<Vec<StockAvailabilityResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut inventory_query = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .into_boxed();
+
+        if let Some(context) = &context {
+            inventory_query =
+                inventory_query.filter(inventory_items::context.eq(context.to_string()));
+        }
+
+        let inventories = inventory_query
+            .select(InventoryItem::as_select())
+            .load::<InventoryItem>(&mut conn)
+            .await?;
+
+        let mut availability = Vec::with_capacity(inventories.len());
+        for inventory in inventories {
+            let reservations = Self::active_reservations(&mut conn, inventory.id).await?;
+            let on_hand = inventory.quantity.unwrap_or_default();
+            let reserved: Quantity = reservations.iter().map(|r| r.quantity).sum();
+            availability.push(StockAvailabilityResponse {
+                item_id,
+                inventory_item_id: inventory.id,
+                context: ItemContext::try_from(inventory.context).unwrap_or(ItemContext::Store),
+                on_hand,
+                reserved,
+                available: on_hand - reserved,
+                reservations: reservations.into_iter().map(Into::into).collect(),
+            });
+        }
+
+        Ok(availability)
+    }
+
+    pub async fn release_reservation(
+        &self,
+        tenant_id: Uuid,
+        reservation_id: Uuid,
+    ) -> Result<Option<StockReservationResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let released = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    let Some(reservation) = stock_reservations::table
+                        .filter(stock_reservations::id.eq(reservation_id))
+                        .filter(stock_reservations::tenant_id.eq(tenant_id))
+                        .for_update()
+                        .select(StockReservation::as_select())
+                        .first::<StockReservation>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    if reservation.status != ReservationStatus::Active.to_string() {
+                        return Err(ItemError::NotReleasable(reservation.status).into());
+                    }
+
+                    let reservation =
+                        diesel::update(stock_reservations::table.find(reservation.id))
+                            .set((
+                                stock_reservations::status
+                                    .eq(ReservationStatus::Released.to_string()),
+                                stock_reservations::released_at.eq(Some(Utc::now())),
+                            ))
+                            .returning(StockReservation::as_returning())
+                            .get_result::<StockReservation>(conn)
+                            .await?;
+
+                    Ok(Some(reservation))
+                })
+            })
+            .await?;
+
+        Ok(released.map(Into::into))
+    }
+
+    /// Releases every active reservation held by an order or job, e.g. once the order is
+    /// cancelled. Runs inside the caller's transaction.
+    pub async fn release_reservations_for(
+        conn: &mut AsyncPgConnection,
+        reference_type: StockReferenceType,
+        reference_id: Uuid,
+    ) -> QueryResult<usize> {
+        diesel::update(
+            stock_reservations::table
+                .filter(stock_reservations::reference_type.eq(reference_type.to_string()))
+                .filter(stock_reservations::reference_id.eq(reference_id))
+                .filter(stock_reservations::status.eq(ReservationStatus::Active.to_string())),
+        )
+        .set((
+            stock_reservations::status.eq(ReservationStatus::Released.to_string()),
+            stock_reservations::released_at.eq(Some(Utc::now())),
+        ))
+        .execute(conn)
+        .await
+    }
+
+    // Available to promise
+
+    /// Works out how much of the item can be promised on the requested date from on-hand,
+    /// reserved, open purchase order and open sales order quantities. Returns `None` when the
+    /// item does not exist.
+    pub async fn available_to_promise(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: AvailableToPromiseQuery,
+    ) -> Result<Option<AvailableToPromiseResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::compute_available_to_promise(&mut conn, tenant_id, item_id, &query, None).await
+    }
+
+    /// Available-to-promise on the caller's connection. `exclude_line` leaves one order line
+    /// out of demand so that line can be promised against everything else.
+    pub async fn compute_available_to_promise(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: &AvailableToPromiseQuery,
+        exclude_line: Option<Uuid>,
+    ) -> Result<Option<AvailableToPromiseResponse>> {
+        let item_count: i64 = items::table
+            .filter(items::id.eq(item_id))
+            .count()
+            .get_result(conn)
+            .await?;
+        if item_count == 0 {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let date = query.date.unwrap_or(now);
+
+        let inventories = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .select(InventoryItem::as_select())
+            .load::<InventoryItem>(conn)
+            .await?;
+        let lead_time_days = inventories
+            .iter()
+            .filter_map(|i| i.lead_time)
+            .max()
+            .unwrap_or(0);
+
+        // Reservations already cover part of their order's demand in every context
+        let mut on_hand = Quantity::ZERO;
+        let mut reserved = Quantity::ZERO;
+        let mut reserved_by_order: HashMap<Uuid, Quantity> = HashMap::new();
+        for inventory in &inventories {
+            let in_context = query
+                .context
+                .as_ref()
+                .is_none_or(|context| inventory.context == context.to_string());
+            for reservation in Self::active_reservations(conn, inventory.id).await? {
+                if in_context {
+                    reserved += reservation.quantity;
+                }
+                if reservation.reference_type == StockReferenceType::Order.to_string() {
+                    *reserved_by_order
+                        .entry(reservation.reference_id)
+                        .or_default() += reservation.quantity;
+                }
+            }
+            if in_context {
+                on_hand += inventory.quantity.unwrap_or_default();
+            }
+        }
+
+        let open_statuses = [
+            OrderStatus::Submitted,
+            OrderStatus::Approved,
+            OrderStatus::PartiallyFulfilled,
+        ]
+        .map(|status| status.to_string());
+        let lines: Vec<(OrderItem, Order)> = order_items::table
+            .inner_join(orders::table)
+            .filter(orders::tenant_id.eq(tenant_id))
+            .filter(orders::status.eq_any(open_statuses))
+            .filter(order_items::item_id.eq(item_id))
+            .order((
+                orders::order_date.asc(),
+                order_items::expected_date.asc(),
+                order_items::created_at.asc(),
+            ))
+            .select((OrderItem::as_select(), Order::as_select()))
+            .load(conn)
+            .await?;
+
+        // Net stock moved against each order: receipts on purchase orders, issues on sales orders
+        let order_ids: Vec<Uuid> = lines.iter().map(|(_, order)| order.id).collect();
+        let moved: HashMap<Uuid, Quantity> = stock_movements::table
+            .filter(stock_movements::tenant_id.eq(tenant_id))
+            .filter(stock_movements::item_id.eq(item_id))
+            .filter(stock_movements::reference_type.eq(StockReferenceType::Order.to_string()))
+            .filter(stock_movements::reference_id.eq_any(&order_ids))
+            .group_by(stock_movements::reference_id)
+            .select((
+                stock_movements::reference_id,
+                diesel::dsl::sum(stock_movements::quantity),
+            ))
+            .load::<(Option<Uuid>, Option<Quantity>)>(conn)
+            .await?
+            .into_iter()
+            .filter_map(|(id, quantity)| Some((id?, quantity.unwrap_or_default())))
+            .collect();
+
+        // Each order's receipts, issues and reservations close its lines in date order
+        let mut covered: HashMap<Uuid, i32> = HashMap::new();
+        let mut events = Vec::new();
+        let mut open_supply = 0;
+        let mut open_demand = 0;
+        for (line, order) in lines {
+            let is_supply = order.order_type == OrderType::PurchaseOrder.to_string();
+            let remaining = covered.entry(order.id).or_insert_with(|| {
+                let moved = moved.get(&order.id).copied().unwrap_or_default();
+                let covered = if is_supply {
+                    moved.max(Quantity::ZERO)
+                } else {
+                    (-moved).max(Quantity::ZERO)
+                        + reserved_by_order
+                            .get(&order.id)
+                            .copied()
+                            .unwrap_or_default()
+                };
+                covered.whole_units()
+            });
+            let closed = (*remaining).min(line.base_quantity);
+            *remaining -= closed;
+
+            let open = line.base_quantity - closed;
+            if open == 0 || exclude_line == Some(line.id) {
+                continue;
+            }
+            // Late receipts and overdue shipments are still expected, just not in the past
+            if is_supply {
+                let expected = line
+                    .expected_date
+                    .unwrap_or_else(|| order.order_date + Duration::days(lead_time_days as i64));
+                events.push((expected.max(now), open));
+                open_supply += open;
+            } else {
+                let expected = line.expected_date.unwrap_or(order.order_date);
+                events.push((expected.max(now), -open));
+                open_demand += open;
+            }
+        }
+
+        let opening = (on_hand - reserved).whole_units();
+        let schedule = atp_schedule(opening, events);
+        let available_on_date = available_on(opening, &schedule, date);
+
+        Ok(Some(AvailableToPromiseResponse {
+            item_id,
+            quantity: query.quantity,
+            date,
+            on_hand,
+            reserved,
+            open_supply,
+            open_demand,
+            available_on_date,
+            can_promise: available_on_date >= query.quantity,
+            promise_date: promise_date(opening, &schedule, query.quantity, date),
+            schedule,
+        }))
+    }
+
+    // Demand forecasting
+
+    /// Forecasts monthly consumption (issues net of returns) from the stock movement ledger
+    /// and derives safety stock and a suggested reorder quantity. Returns `None` when the
+    /// tenant holds no inventory for the item.
+    pub async fn forecast_demand(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: DemandForecastQuery,
+    ) -> Result<Option<DemandForecastResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut inventory_query = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .into_boxed();
+        if let Some(context) = &query.context {
+            inventory_query =
+                inventory_query.filter(inventory_items::context.eq(context.to_string()));
+        }
+        let inventory = inventory_query
+            .select(InventoryItem::as_select())
+            .load::<InventoryItem>(&mut conn)
+            .await?;
+
+        if inventory.is_empty() {
+            return Ok(None);
+        }
+
+        let months = query.months.unwrap_or(12);
+        let window = query.window.unwrap_or(3).min(months);
+        let alpha = query.alpha.unwrap_or(0.3);
+        let horizon = query.horizon.unwrap_or(3);
+        let service_level = query.service_level.unwrap_or(0.95);
+        let method = query
+            .method
+            .clone()
+            .unwrap_or(ForecastMethod::ExponentialSmoothing);
+
+        // History covers the last `months` complete calendar months
+        let current_month = month_start(Utc::now().date_naive());
+        let history_start = current_month - Months::new(months);
+
+        let rows = diesel::sql_query(
+            r#"
+            SELECT date_trunc('month', occurred_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS month_start,
+                   SUM(-quantity)::float8 AS quantity
+            FROM stock_movements
+            WHERE tenant_id = $1
+              AND item_id = $2
+              AND movement_type IN ('issue', 'return')
+              AND ($3::text IS NULL OR context = $3)
+              AND occurred_at >= $4
+              AND occurred_at < $5
+            GROUP BY 1
+            "#,
+        )
+        .bind::<SqlUuid, _>(tenant_id)
+        .bind::<SqlUuid, _>(item_id)
+        .bind::<Nullable<Text>, _>(query.context.as_ref().map(|c| c.to_string()))
+        .bind::<Timestamptz, _>(to_utc(history_start))
+        .bind::<Timestamptz, _>(to_utc(current_month))
+        .load::<MonthlyConsumptionRow>(&mut conn)
+        .await?;
+
+        let by_month: HashMap<NaiveDate, f64> = rows
+            .into_iter()
+            .map(|row| (row.month_start.date_naive(), row.quantity))
+            .collect();
+
+        let history: Vec<MonthlyQuantity> = (0..months)
+            .map(|offset| {
+                let month = history_start + Months::new(offset);
+                MonthlyQuantity {
+                    month: month.format("%Y-%m").to_string(),
+                    quantity: by_month.get(&month).copied().unwrap_or(0.0).max(0.0),
+                }
+            })
+            .collect();
+        let series: Vec<f64> = history.iter().map(|m| m.quantity).collect();
+
+        let moving_average = moving_average(&series, window as usize);
+        let exponential_smoothing = exponential_smoothing(&series, alpha);
+        let level = match method {
+            ForecastMethod::MovingAverage => moving_average,
+            ForecastMethod::ExponentialSmoothing => exponential_smoothing,
+        };
+
+        let forecast = (0..horizon)
+            .map(|offset| MonthlyQuantity {
+                month: (current_month + Months::new(offset))
+                    .format("%Y-%m")
+                    .to_string(),
+                quantity: level,
+            })
+            .collect();
+
+        let lead_time_days = query
+            .lead_time_days
+            .or_else(|| {
+                inventory
+                    .iter()
+                    .filter_map(|i| i.lead_time)
+                    .max()
+                    .map(|days| days.max(0) as u32)
+            })
+            .unwrap_or(DEFAULT_LEAD_TIME_DAYS);
+
+        let demand_std_dev = standard_deviation(&series);
+        let safety_stock = safety_stock(service_level, demand_std_dev, lead_time_days).ceil();
+        let lead_time_demand = level * lead_time_days as f64 / DAYS_PER_MONTH;
+        let reorder_point = (lead_time_demand + safety_stock).ceil();
+        let on_hand: Quantity = inventory.iter().filter_map(|i| i.quantity).sum();
+
+        // Order up to the reorder point plus one month of forecast demand
+        let suggested_reorder_quantity =
+            ((reorder_point + level - on_hand.to_f64()).ceil() as i64).max(0);
+
+        Ok(Some(DemandForecastResponse {
+            item_id,
+            context: query.context,
+            method,
+            window,
+            alpha,
+            service_level,
+            history,
+            moving_average,
+            exponential_smoothing,
+            forecast,
+            demand_std_dev,
+            lead_time_days,
+            on_hand,
+            safety_stock: safety_stock as i64,
+            reorder_point: reorder_point as i64,
+            suggested_reorder_quantity,
+        }))
+    }
+
+    // Consumption analytics
+
+    /// Monthly consumption, turnover, days of stock left at the recent burn rate and whether
+    /// the stock has gone dead, all from the stock movement ledger. Returns `None` when the
+    /// tenant holds no inventory for the item.
+    pub async fn item_analytics(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: ItemAnalyticsQuery,
+    ) -> Result<Option<ItemAnalyticsResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let context = query.context.as_ref().map(|c| c.to_string());
+        let mut inventory_query = inventory_items::table
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq(item_id))
+            .into_boxed();
+        if let Some(context) = &context {
+            inventory_query = inventory_query.filter(inventory_items::context.eq(context));
+        }
+        let on_hand: Option<Quantity> = match inventory_query
+            .select(inventory_items::quantity)
+            .load::<Option<Quantity>>(&mut conn)
+            .await?
+        {
+            quantities if quantities.is_empty() => None,
+            quantities => Some(quantities.into_iter().flatten().sum()),
+        };
+        let Some(on_hand) = on_hand else {
+            return Ok(None);
+        };
+
+        let months = query.months.unwrap_or(12);
+        let burn_rate_days = query.burn_rate_days.unwrap_or(90);
+        let dead_stock_days = query.dead_stock_days.unwrap_or(180);
+
+        // Months from the start of the history up to today; the current month only moves the
+        // balance back to where the history closes
+        let now = Utc::now();
+        let current_month = month_start(now.date_naive());
+        let history_start = current_month - Months::new(months);
+
+        let rows = diesel::sql_query(
+            r#"
+            SELECT date_trunc('month', occurred_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS month_start,
+                   COALESCE(SUM(-quantity) FILTER (WHERE movement_type IN ('issue', 'return')), 0)::float8 AS consumed,
+                   SUM(quantity)::float8 AS net_change
+            FROM stock_movements
+            WHERE tenant_id = $1
+              AND item_id = $2
+              AND ($3::text IS NULL OR context = $3)
+              AND occurred_at >= $4
+            GROUP BY 1
+            "#,
+        )
+        .bind::<SqlUuid, _>(tenant_id)
+        .bind::<SqlUuid, _>(item_id)
+        .bind::<Nullable<Text>, _>(context.clone())
+        .bind::<Timestamptz, _>(to_utc(history_start))
+        .load::<MonthlyMovementRow>(&mut conn)
+        .await?;
+
+        let activity = diesel::sql_query(
+            r#"
+            SELECT MAX(occurred_at) AS last_movement_at,
+                   COALESCE(SUM(-quantity) FILTER (
+                       WHERE movement_type IN ('issue', 'return') AND occurred_at >= $4
+                   ), 0)::float8 AS recent_consumed
+            FROM stock_movements
+            WHERE tenant_id = $1
+              AND item_id = $2
+              AND ($3::text IS NULL OR context = $3)
+            "#,
+        )
+        .bind::<SqlUuid, _>(tenant_id)
+        .bind::<SqlUuid, _>(item_id)
+        .bind::<Nullable<Text>, _>(context)
+        .bind::<Timestamptz, _>(now - Duration::days(burn_rate_days as i64))
+        .get_result::<MovementActivityRow>(&mut conn)
+        .await?;
+
+        let by_month: HashMap<NaiveDate, MonthlyMovementRow> = rows
+            .into_iter()
+            .map(|row| (row.month_start.date_naive(), row))
+            .collect();
+        let month_rows: Vec<(NaiveDate, Option<&MonthlyMovementRow>)> = (0..months)
+            .map(|offset| {
+                let month = history_start + Months::new(offset);
+                (month, by_month.get(&month))
+            })
+            .collect();
+
+        let consumption: Vec<MonthlyQuantity> = month_rows
+            .iter()
+            .map(|(month, row)| MonthlyQuantity {
+                month: month.format("%Y-%m").to_string(),
+                quantity: row.map(|r| r.consumed).unwrap_or(0.0).max(0.0),
+            })
+            .collect();
+        let total_consumed: f64 = consumption.iter().map(|m| m.quantity).sum();
+
+        let since_close = by_month
+            .get(&current_month)
+            .map(|r| r.net_change)
+            .unwrap_or(0.0);
+        let net_changes: Vec<f64> = month_rows
+            .iter()
+            .map(|(_, row)| row.map(|r| r.net_change).unwrap_or(0.0))
+            .collect();
+        let average_inventory = average_balance(on_hand.to_f64() - since_close, &net_changes);
+        let turnover_ratio = (average_inventory > 0.0).then(|| total_consumed / average_inventory);
+
+        let daily_burn_rate = activity.recent_consumed.max(0.0) / burn_rate_days as f64;
+        let days_of_stock =
+            (daily_burn_rate > 0.0).then(|| on_hand.to_f64().max(0.0) / daily_burn_rate);
+
+        let dead_since = now - Duration::days(dead_stock_days as i64);
+        let is_dead_stock = on_hand > Quantity::ZERO
+            && activity
+                .last_movement_at
+                .is_none_or(|last| last < dead_since);
+
+        Ok(Some(ItemAnalyticsResponse {
+            item_id,
+            context: query.context,
+            consumption,
+            total_consumed,
+            on_hand,
+            average_inventory,
+            turnover_ratio,
+            burn_rate_days,
+            daily_burn_rate,
+            days_of_stock,
+            last_movement_at: activity.last_movement_at,
+            dead_stock_days,
+            is_dead_stock,
+        }))
+    }
+
+    // Private helper methods
+
+    async fn active_reservations(
+        conn: &mut AsyncPgConnection,
+        inventory_item_id: Uuid,
+    ) -> Result<Vec<StockReservation>> {
+        Ok(stock_reservations::table
+            .filter(stock_reservations::inventory_item_id.eq(inventory_item_id))
+            .filter(stock_reservations::status.eq(ReservationStatus::Active.to_string()))
+            .order(stock_reservations::created_at.asc())
+            .select(StockReservation::as_select())
+            .load::<StockReservation>(conn)
+            .await?)
+    }
+
+    /// Whether the order or job exists for the tenant. Orders must be confirmed and jobs still
+    /// open to hold stock.
+    async fn check_reference(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        reference_type: &StockReferenceType,
+        reference_id: Uuid,
+    ) -> Result<bool> {
+        match reference_type {
+            StockReferenceType::Order => {
+                let Some(status) = orders::table
+                    .filter(orders::id.eq(reference_id))
+                    .filter(orders::tenant_id.eq(tenant_id))
+                    .select(orders::status)
+                    .first::<String>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(false);
+                };
+                let confirmed = [
+                    OrderStatus::Submitted,
+                    OrderStatus::Approved,
+                    OrderStatus::PartiallyFulfilled,
+                ];
+                if !confirmed.iter().any(|s| s.to_string() == status) {
+                    return Err(ItemError::ReferenceClosed("Order", status).into());
+                }
+                Ok(true)
+            }
+            StockReferenceType::Job => {
+                let Some(status) = jobs::table
+                    .filter(jobs::id.eq(reference_id))
+                    .filter(jobs::tenant_id.eq(tenant_id))
+                    .select(jobs::status)
+                    .first::<String>(conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(false);
+                };
+                let closed = [JobStatus::Completed, JobStatus::Cancelled];
+                if closed.iter().any(|s| s.to_string() == status) {
+                    return Err(ItemError::ReferenceClosed("Job", status).into());
+                }
+                Ok(true)
+            }
+            _ => Err(ItemError::InvalidReference.into()),
+        }
+    }
+}
+
+fn movement_to_response(movement: StockMovement) -> StockMovementResponse {
+    StockMovementResponse {
+        id: movement.id,
+        item_id: movement.item_id,
+        inventory_item_id: movement.inventory_item_id,
+        context: ItemContext::try_from(movement.context).unwrap_or(ItemContext::Store),
+        movement_type: StockMovementType::try_from(movement.movement_type)
+            .unwrap_or(StockMovementType::Adjustment),
+        quantity: movement.quantity,
+        quantity_after: movement.quantity_after,
+        reference_type: movement
+            .reference_type
+            .and_then(|r| StockReferenceType::try_from(r).ok()),
+        reference_id: movement.reference_id,
+        person_id: movement.person_id,
+        notes: movement.notes,
+        occurred_at: movement.occurred_at,
+        created_at: movement.created_at.unwrap_or_else(Utc::now),
+        uom_id: movement.uom_id,
+        uom_quantity: movement.uom_quantity,
+        owner_id: movement.owner_id,
+    }
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn to_utc(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .map(|datetime| datetime.and_utc())
+        .unwrap_or_else(Utc::now)
+}
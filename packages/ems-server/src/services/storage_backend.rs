@@ -0,0 +1,1021 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use reqwest::{header, Client, Method, RequestBuilder, Response, Url};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::utils::storage::{
+    amz_date, canonical_query, checksum_from_header, checksum_header, local_object_path,
+    local_url_signature, uri_encode, verify_local_url, SigV4Credentials,
+};
+use crate::utils::AppError;
+
+// Storage API calls must finish within this time; chunks are a few MiB at most
+const STORAGE_TIMEOUT: Duration = Duration::from_secs(120);
+
+const DEFAULT_S3_REGION: &str = "us-east-1";
+const DEFAULT_LOCAL_ROOT: &str = "./storage";
+
+/// Assembled files are written in pieces of exactly this size: Supabase's resumable upload
+/// endpoint takes nothing else, and S3 multipart parts must be at least 5 MiB
+pub const RESUMABLE_PIECE_BYTES: usize = 6 * 1024 * 1024;
+
+const TUS_VERSION: &str = "1.0.0";
+
+/// Where a client sends a file straight to storage, bypassing the server
+#[derive(Debug, Clone)]
+pub struct PresignedUpload {
+    pub url: String,
+    /// Headers the upload must carry with exactly these values
+    pub headers: Vec<(String, String)>,
+}
+
+/// Size and content of a stored object as the store reports them
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub size_bytes: u64,
+    /// SHA-256 (hex) the store verified on upload, when it keeps one
+    pub sha256: Option<String>,
+}
+
+/// Object storage holding asset files, item images and the chunks of uploads in progress.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Stores `content` at `path`, replacing anything already there.
+    async fn put(&self, path: &str, content: Vec<u8>, content_type: &str) -> Result<()>;
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>>;
+
+    async fn delete(&self, path: &str) -> Result<()>;
+
+    /// URL the object at `path` can be downloaded from without credentials for `expires_in`.
+    async fn signed_url(&self, path: &str, expires_in: Duration) -> Result<String>;
+
+    /// URL a client can PUT the file for `path` to without credentials for `expires_in`. The
+    /// store itself refuses content of another size or SHA-256 (hex), so only backends able to
+    /// check both issue these.
+    async fn presigned_upload(
+        &self,
+        _path: &str,
+        _content_type: &str,
+        _size_bytes: u64,
+        _sha256: &str,
+        _expires_in: Duration,
+    ) -> Result<PresignedUpload> {
+        Err(DirectUploadsUnsupported.into())
+    }
+
+    /// Size and checksum of the object at `path`, without downloading it. `Ok(None)` when
+    /// there is no such object.
+    async fn stat(&self, _path: &str) -> Result<Option<ObjectInfo>> {
+        Err(DirectUploadsUnsupported.into())
+    }
+
+    /// Writes the objects at `parts`, in order, as one object of `size_bytes` at `path`, and
+    /// returns its SHA-256 (hex). Backends that can write in pieces read the parts one at a
+    /// time, so the file is never held in memory whole; this default gathers it first.
+    async fn assemble(
+        &self,
+        parts: &[String],
+        path: &str,
+        content_type: &str,
+        size_bytes: u64,
+    ) -> Result<String> {
+        let mut content = Vec::with_capacity(size_bytes as usize);
+        for part in parts {
+            content.extend(self.get(part).await?);
+        }
+        if content.len() as u64 != size_bytes {
+            return Err(request_failed(format!(
+                "assembled {} of {} bytes",
+                content.len(),
+                size_bytes
+            )));
+        }
+        let checksum = format!("{:x}", Sha256::digest(&content));
+        self.put(path, content, content_type).await?;
+        Ok(checksum)
+    }
+}
+
+/// The backend STORAGE_BACKEND selects for the bucket named by `bucket_var` (default
+/// `default_bucket`): `supabase` (the default; Supabase Storage at SUPABASE_URL), `s3` (an
+/// S3-compatible service such as MinIO, see [`S3Storage::from_env`]) or `local` (the server's
+/// filesystem, see [`LocalStorage::from_env`]). `Ok(None)` when Supabase is selected but
+/// SUPABASE_URL or SUPABASE_SERVICE_ROLE_KEY is not set.
+pub fn storage_backend_from_env(
+    bucket_var: &str,
+    default_bucket: &str,
+) -> Result<Option<Arc<dyn StorageBackend>>> {
+    let bucket = env::var(bucket_var)
+        .ok()
+        .filter(|b| !b.is_empty())
+        .unwrap_or_else(|| default_bucket.to_string());
+    let backend = env::var("STORAGE_BACKEND")
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match backend.as_str() {
+        "" | "supabase" => Ok(SupabaseStorage::from_env(bucket)?
+            .map(|storage| Arc::new(storage) as Arc<dyn StorageBackend>)),
+        "s3" => Ok(Some(Arc::new(S3Storage::from_env(bucket)?))),
+        "local" => Ok(Some(Arc::new(LocalStorage::from_env(bucket)?))),
+        other => Err(anyhow!("Unknown STORAGE_BACKEND: {}", other)),
+    }
+}
+
+/// A call to the storage service failed, as opposed to the caller asking for something wrong.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Storage request failed: {0}")]
+pub struct StorageRequestFailed(pub String);
+
+impl StorageRequestFailed {
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<StorageRequestFailed>().is_some()
+    }
+}
+
+/// The storage backend cannot take files from clients directly.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Direct uploads are not supported by the storage backend")]
+pub struct DirectUploadsUnsupported;
+
+/// A signed URL to the local backend that cannot be served.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum StorageUrlError {
+    #[error("Storage signing is not configured")]
+    NotConfigured,
+
+    #[error("Invalid storage bucket name: {0}")]
+    InvalidBucket(String),
+
+    #[error("{0}")]
+    Invalid(String),
+}
+
+impl From<StorageUrlError> for AppError {
+    fn from(error: StorageUrlError) -> Self {
+        match error {
+            StorageUrlError::NotConfigured | StorageUrlError::InvalidBucket(_) => {
+                AppError::NotFound(error.to_string())
+            }
+            StorageUrlError::Invalid(message) => AppError::Authorization(message),
+        }
+    }
+}
+
+fn request_failed(e: impl std::fmt::Display) -> anyhow::Error {
+    StorageRequestFailed(e.to_string()).into()
+}
+
+// Supabase
+
+#[derive(Deserialize)]
+struct SignedUrlResponse {
+    #[serde(rename = "signedURL")]
+    signed_url: String,
+}
+
+/// Supabase Storage bucket. The bucket should be private: objects are only reachable through
+/// the signed URLs it issues. Assembled files are written through the resumable (TUS) upload
+/// endpoint, which takes files larger than a single request allows.
+pub struct SupabaseStorage {
+    client: Client,
+    base_url: String,
+    service_key: String,
+    bucket: String,
+}
+
+impl SupabaseStorage {
+    pub fn new(url: &str, service_key: String, bucket: String) -> Result<Self> {
+        let client = Client::builder().timeout(STORAGE_TIMEOUT).build()?;
+        Ok(Self {
+            client,
+            base_url: format!("{}/storage/v1", url.trim_end_matches('/')),
+            service_key,
+            bucket,
+        })
+    }
+
+    /// Configures the bucket from SUPABASE_URL and SUPABASE_SERVICE_ROLE_KEY.
+    /// Returns `Ok(None)` when either is not set.
+    pub fn from_env(bucket: String) -> Result<Option<Self>> {
+        let url = env::var("SUPABASE_URL").unwrap_or_default();
+        let service_key = env::var("SUPABASE_SERVICE_ROLE_KEY").unwrap_or_default();
+        if url.is_empty() || service_key.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::new(&url, service_key, bucket)?))
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request
+            .bearer_auth(&self.service_key)
+            .header("apikey", &self.service_key)
+    }
+
+    fn object_url(&self, kind: &str, path: &str) -> String {
+        format!("{}/{}/{}/{}", self.base_url, kind, self.bucket, path)
+    }
+
+    /// Starts a resumable upload of `size_bytes` to `path`, returning the URL pieces go to.
+    async fn start_resumable(
+        &self,
+        path: &str,
+        content_type: &str,
+        size_bytes: u64,
+    ) -> Result<String> {
+        let metadata = [
+            ("bucketName", self.bucket.as_str()),
+            ("objectName", path),
+            ("contentType", content_type),
+        ]
+        .iter()
+        .map(|(key, value)| format!("{} {}", key, STANDARD.encode(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+
+        let response = self
+            .authorize(
+                self.client
+                    .post(format!("{}/upload/resumable", self.base_url)),
+            )
+            .header("Tus-Resumable", TUS_VERSION)
+            .header("Upload-Length", size_bytes.to_string())
+            .header("Upload-Metadata", metadata)
+            .header("x-upsert", "true")
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        if response.status() != reqwest::StatusCode::CREATED {
+            return Err(request_failed(format!(
+                "starting the upload returned {}",
+                response.status()
+            )));
+        }
+        let location = response
+            .headers()
+            .get(header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| request_failed("no upload location"))?;
+
+        // The location may be relative to the storage host
+        if location.starts_with('/') {
+            let origin = Url::parse(&self.base_url)?;
+            Ok(origin.join(location)?.to_string())
+        } else {
+            Ok(location.to_string())
+        }
+    }
+
+    async fn send_piece(&self, location: &str, offset: u64, piece: Vec<u8>) -> Result<u64> {
+        let length = piece.len() as u64;
+        let response = self
+            .authorize(self.client.patch(location))
+            .header("Tus-Resumable", TUS_VERSION)
+            .header("Upload-Offset", offset.to_string())
+            .header(header::CONTENT_TYPE, "application/offset+octet-stream")
+            .body(piece)
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        if !response.status().is_success() {
+            return Err(request_failed(format!(
+                "upload returned {}",
+                response.status()
+            )));
+        }
+        Ok(offset + length)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for SupabaseStorage {
+    async fn put(&self, path: &str, content: Vec<u8>, content_type: &str) -> Result<()> {
+        let response = self
+            .authorize(self.client.post(self.object_url("object", path)))
+            .header(header::CONTENT_TYPE, content_type)
+            .header("x-upsert", "true")
+            .body(content)
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        if !response.status().is_success() {
+            return Err(request_failed(format!(
+                "upload returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>> {
+        let response = self
+            .authorize(
+                self.client
+                    .get(self.object_url("object/authenticated", path)),
+            )
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        if !response.status().is_success() {
+            return Err(request_failed(format!(
+                "download returned {}",
+                response.status()
+            )));
+        }
+        Ok(response.bytes().await.map_err(request_failed)?.to_vec())
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        let response = self
+            .authorize(self.client.delete(self.object_url("object", path)))
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        // Already gone is as good as deleted
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(request_failed(format!(
+                "delete returned {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn signed_url(&self, path: &str, expires_in: Duration) -> Result<String> {
+        let response = self
+            .authorize(self.client.post(self.object_url("object/sign", path)))
+            .json(&json!({ "expiresIn": expires_in.as_secs().max(1) }))
+            .send()
+            .await
+            .map_err(request_failed)?;
+
+        if !response.status().is_success() {
+            return Err(request_failed(format!(
+                "signing returned {}",
+                response.status()
+            )));
+        }
+
+        let signed = response
+            .json::<SignedUrlResponse>()
+            .await
+            .map_err(|e| request_failed(format!("invalid signing response: {}", e)))?;
+
+        // The signed path is relative to the storage API
+        Ok(format!("{}{}", self.base_url, signed.signed_url))
+    }
+
+    async fn assemble(
+        &self,
+        parts: &[String],
+        path: &str,
+        content_type: &str,
+        size_bytes: u64,
+    ) -> Result<String> {
+        let location = self.start_resumable(path, content_type, size_bytes).await?;
+        let mut hasher = Sha256::new();
+        let mut buffer: Vec<u8> = Vec::with_capacity(RESUMABLE_PIECE_BYTES);
+        let mut offset = 0u64;
+
+        // Parts and pieces differ in size, so bytes are regrouped into pieces as parts arrive
+        for part in parts {
+            let content = self.get(part).await?;
+            hasher.update(&content);
+            buffer.extend_from_slice(&content);
+            while buffer.len() >= RESUMABLE_PIECE_BYTES {
+                let rest = buffer.split_off(RESUMABLE_PIECE_BYTES);
+                let piece = std::mem::replace(&mut buffer, rest);
+                offset = self.send_piece(&location, offset, piece).await?;
+            }
+        }
+        if !buffer.is_empty() {
+            offset = self.send_piece(&location, offset, buffer).await?;
+        }
+
+        if offset != size_bytes {
+            return Err(request_failed(format!(
+                "assembled {} of {} bytes",
+                offset, size_bytes
+            )));
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
+// S3-compatible
+
+/// Bucket of an S3-compatible object store such as MinIO, with requests signed by AWS
+/// Signature Version 4. Signed URLs are presigned GET URLs; assembled files are written as
+/// multipart uploads. Clients can upload files directly through presigned PUT URLs, with the
+/// file's length and SHA-256 signed in so the store refuses anything else.
+pub struct S3Storage {
+    client: Client,
+    endpoint: Url,
+    credentials: SigV4Credentials,
+    bucket: String,
+    path_style: bool,
+}
+
+impl S3Storage {
+    /// `endpoint` is the service's base URL, e.g. `http://minio:9000`. With `path_style` the
+    /// bucket is addressed as the first path segment (as MinIO expects), otherwise as a
+    /// subdomain of the endpoint's host.
+    pub fn new(
+        endpoint: &str,
+        credentials: SigV4Credentials,
+        bucket: String,
+        path_style: bool,
+    ) -> Result<Self> {
+        let client = Client::builder().timeout(STORAGE_TIMEOUT).build()?;
+        let endpoint =
+            Url::parse(endpoint).map_err(|e| anyhow!("Invalid S3 endpoint {}: {}", endpoint, e))?;
+        if endpoint.host_str().is_none() {
+            return Err(anyhow!("Invalid S3 endpoint {}: no host", endpoint));
+        }
+        Ok(Self {
+            client,
+            endpoint,
+            credentials,
+            bucket,
+            path_style,
+        })
+    }
+
+    /// Configures the bucket from S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY (both required),
+    /// S3_REGION (default `us-east-1`), S3_ENDPOINT (default the AWS endpoint of the region)
+    /// and S3_PATH_STYLE (default true; false addresses buckets as subdomains).
+    pub fn from_env(bucket: String) -> Result<Self> {
+        let access_key_id = env::var("S3_ACCESS_KEY_ID").unwrap_or_default();
+        let secret_access_key = env::var("S3_SECRET_ACCESS_KEY").unwrap_or_default();
+        if access_key_id.is_empty() || secret_access_key.is_empty() {
+            return Err(anyhow!(
+                "S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY must be set when STORAGE_BACKEND is s3"
+            ));
+        }
+        let region = env::var("S3_REGION")
+            .ok()
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| DEFAULT_S3_REGION.to_string());
+        let endpoint = env::var("S3_ENDPOINT")
+            .ok()
+            .filter(|e| !e.is_empty())
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+        let path_style =
+            env::var("S3_PATH_STYLE").unwrap_or_else(|_| "true".to_string()) != "false";
+
+        Self::new(
+            &endpoint,
+            SigV4Credentials {
+                access_key_id,
+                secret_access_key,
+                region,
+            },
+            bucket,
+            path_style,
+        )
+    }
+
+    // Host (with any non-default port) and encoded path of the object at `path`
+    fn address(&self, path: &str) -> (String, String) {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let key = uri_encode(path, true);
+        if self.path_style {
+            (host, format!("/{}/{}", self.bucket, key))
+        } else {
+            (format!("{}.{}", self.bucket, host), format!("/{}", key))
+        }
+    }
+
+    // Sends a request signed with the service credentials; `headers` are signed and sent too
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(String, String)],
+        body: Vec<u8>,
+        headers: &[(&str, &str)],
+    ) -> Result<Response> {
+        let (host, object_path) = self.address(path);
+        let now = Utc::now();
+        let payload_hash = format!("{:x}", Sha256::digest(&body));
+        let mut signed = vec![
+            ("host".to_string(), host.clone()),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date(now)),
+        ];
+        signed.extend(
+            headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        let authorization = self.credentials.authorization(
+            method.as_str(),
+            &object_path,
+            query,
+            &signed,
+            &payload_hash,
+            now,
+        );
+
+        let mut url = format!("{}://{}{}", self.endpoint.scheme(), host, object_path);
+        if !query.is_empty() {
+            url = format!("{}?{}", url, canonical_query(query));
+        }
+        let mut request = self
+            .client
+            .request(method, url)
+            .header(header::AUTHORIZATION, authorization)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date(now))
+            .body(body);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.send().await.map_err(request_failed)
+    }
+
+    // Fails with the S3 error code for responses that are not a success
+    async fn check(response: Response, action: &str) -> Result<Response> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(request_failed(format!(
+            "{} returned {}{}",
+            action,
+            status,
+            xml_element(&body, "Code")
+                .map(|code| format!(" ({})", code))
+                .unwrap_or_default()
+        )))
+    }
+
+    async fn upload_part(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: usize,
+        piece: Vec<u8>,
+    ) -> Result<String> {
+        let query = [
+            ("partNumber".to_string(), part_number.to_string()),
+            ("uploadId".to_string(), upload_id.to_string()),
+        ];
+        let response = self.send(Method::PUT, path, &query, piece, &[]).await?;
+        let response = Self::check(response, "upload").await?;
+        response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| request_failed(format!("no ETag for part {}", part_number)))
+    }
+
+    // Writes the parts as pieces of one multipart upload, returning the SHA-256 and bytes sent
+    async fn upload_parts(
+        &self,
+        parts: &[String],
+        path: &str,
+        upload_id: &str,
+    ) -> Result<(String, u64)> {
+        let mut hasher = Sha256::new();
+        let mut buffer: Vec<u8> = Vec::with_capacity(RESUMABLE_PIECE_BYTES);
+        let mut etags = Vec::new();
+        let mut sent = 0u64;
+
+        // Parts and pieces differ in size, so bytes are regrouped into pieces as parts arrive
+        for part in parts {
+            let content = self.get(part).await?;
+            hasher.update(&content);
+            buffer.extend_from_slice(&content);
+            while buffer.len() >= RESUMABLE_PIECE_BYTES {
+                let rest = buffer.split_off(RESUMABLE_PIECE_BYTES);
+                let piece = std::mem::replace(&mut buffer, rest);
+                sent += piece.len() as u64;
+                etags.push(
+                    self.upload_part(path, upload_id, etags.len() + 1, piece)
+                        .await?,
+                );
+            }
+        }
+        if !buffer.is_empty() || etags.is_empty() {
+            sent += buffer.len() as u64;
+            etags.push(
+                self.upload_part(path, upload_id, etags.len() + 1, buffer)
+                    .await?,
+            );
+        }
+
+        let body: String = etags
+            .iter()
+            .enumerate()
+            .map(|(index, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    index + 1,
+                    etag
+                )
+            })
+            .collect();
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            body
+        );
+        let query = [("uploadId".to_string(), upload_id.to_string())];
+        let response = self
+            .send(
+                Method::POST,
+                path,
+                &query,
+                body.into_bytes(),
+                &[("content-type", "application/xml")],
+            )
+            .await?;
+        let response = Self::check(response, "completing the upload").await?;
+
+        // Completion can fail after the status line went out, so the body carries the outcome
+        let body = response.text().await.map_err(request_failed)?;
+        if let Some(code) = xml_element(&body, "Code") {
+            return Err(request_failed(format!(
+                "completing the upload returned {}",
+                code
+            )));
+        }
+        Ok((format!("{:x}", hasher.finalize()), sent))
+    }
+}
+
+// Text of the first `<name>` element in an S3 XML response
+fn xml_element(body: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let start = body.find(&open)? + open.len();
+    let end = body[start..].find(&format!("</{}>", name))? + start;
+    Some(body[start..end].to_string())
+}
+
+#[async_trait]
+impl StorageBackend for S3Storage {
+    async fn put(&self, path: &str, content: Vec<u8>, content_type: &str) -> Result<()> {
+        let response = self
+            .send(
+                Method::PUT,
+                path,
+                &[],
+                content,
+                &[("content-type", content_type)],
+            )
+            .await?;
+        Self::check(response, "upload").await?;
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>> {
+        let response = self.send(Method::GET, path, &[], Vec::new(), &[]).await?;
+        let response = Self::check(response, "download").await?;
+        Ok(response.bytes().await.map_err(request_failed)?.to_vec())
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        // S3 answers 204 whether or not the object was there
+        let response = self
+            .send(Method::DELETE, path, &[], Vec::new(), &[])
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        Self::check(response, "delete").await?;
+        Ok(())
+    }
+
+    async fn signed_url(&self, path: &str, expires_in: Duration) -> Result<String> {
+        let (host, object_path) = self.address(path);
+        let query = self.credentials.presigned_query(
+            "GET",
+            &host,
+            &object_path,
+            expires_in.as_secs(),
+            Utc::now(),
+        );
+        Ok(format!(
+            "{}://{}{}?{}",
+            self.endpoint.scheme(),
+            host,
+            object_path,
+            query
+        ))
+    }
+
+    async fn presigned_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        size_bytes: u64,
+        sha256: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedUpload> {
+        let checksum = checksum_header(sha256)
+            .ok_or_else(|| anyhow!("Invalid checksum: expected a SHA-256 in hex"))?;
+        let (host, object_path) = self.address(path);
+
+        // The store checks the length and checksum it was told against what arrives
+        let headers = vec![
+            ("content-length".to_string(), size_bytes.to_string()),
+            ("content-type".to_string(), content_type.to_string()),
+            ("x-amz-checksum-sha256".to_string(), checksum),
+        ];
+        let mut signed = headers.clone();
+        signed.push(("host".to_string(), host.clone()));
+        let query = self.credentials.presigned_query_with_headers(
+            "PUT",
+            &object_path,
+            &signed,
+            expires_in.as_secs(),
+            Utc::now(),
+        );
+        Ok(PresignedUpload {
+            url: format!(
+                "{}://{}{}?{}",
+                self.endpoint.scheme(),
+                host,
+                object_path,
+                query
+            ),
+            headers,
+        })
+    }
+
+    async fn stat(&self, path: &str) -> Result<Option<ObjectInfo>> {
+        let response = self
+            .send(
+                Method::HEAD,
+                path,
+                &[],
+                Vec::new(),
+                &[("x-amz-checksum-mode", "ENABLED")],
+            )
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = Self::check(response, "inspecting the object").await?;
+        let header_value = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let size_bytes = header_value(header::CONTENT_LENGTH.as_str())
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| request_failed(format!("no size for {}", path)))?;
+        Ok(Some(ObjectInfo {
+            size_bytes,
+            sha256: header_value("x-amz-checksum-sha256").and_then(checksum_from_header),
+        }))
+    }
+
+    async fn assemble(
+        &self,
+        parts: &[String],
+        path: &str,
+        content_type: &str,
+        size_bytes: u64,
+    ) -> Result<String> {
+        let query = [("uploads".to_string(), String::new())];
+        let response = self
+            .send(
+                Method::POST,
+                path,
+                &query,
+                Vec::new(),
+                &[("content-type", content_type)],
+            )
+            .await?;
+        let response = Self::check(response, "starting the upload").await?;
+        let body = response.text().await.map_err(request_failed)?;
+        let upload_id =
+            xml_element(&body, "UploadId").ok_or_else(|| request_failed("no upload id"))?;
+
+        let uploaded = match self.upload_parts(parts, path, &upload_id).await {
+            Ok((_, sent)) if sent != size_bytes => Err(request_failed(format!(
+                "assembled {} of {} bytes",
+                sent, size_bytes
+            ))),
+            uploaded => uploaded,
+        };
+        match uploaded {
+            Ok((checksum, _)) => Ok(checksum),
+            Err(e) => {
+                // Abandoned multipart uploads keep their parts until aborted
+                let query = [("uploadId".to_string(), upload_id)];
+                if let Err(abort) = self
+                    .send(Method::DELETE, path, &query, Vec::new(), &[])
+                    .await
+                {
+                    tracing::warn!("Multipart upload of {} not aborted: {}", path, abort);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+// Local filesystem
+
+/// Directory on the server's filesystem, for deployments without object storage. Signed URLs
+/// point at the server's storage endpoint, which checks their HMAC signature before serving
+/// the file; they need STORAGE_PUBLIC_URL and STORAGE_SIGNING_KEY.
+pub struct LocalStorage {
+    root: PathBuf,
+    bucket: String,
+    public_url: Option<String>,
+    signing_key: Option<String>,
+}
+
+impl LocalStorage {
+    /// Objects live under `root`/`bucket`. Without `public_url` (the server's address as
+    /// clients reach it) and `signing_key`, no signed URLs are issued.
+    pub fn new(
+        root: &Path,
+        bucket: String,
+        public_url: Option<String>,
+        signing_key: Option<String>,
+    ) -> Result<Self> {
+        let root = local_object_path(root, &bucket)
+            .ok_or_else(|| StorageUrlError::InvalidBucket(bucket.clone()))?;
+        Ok(Self {
+            root,
+            bucket,
+            public_url: public_url.map(|url| url.trim_end_matches('/').to_string()),
+            signing_key,
+        })
+    }
+
+    /// Configures the directory from STORAGE_LOCAL_ROOT (default `./storage`), and signed URLs
+    /// from STORAGE_PUBLIC_URL and STORAGE_SIGNING_KEY.
+    pub fn from_env(bucket: String) -> Result<Self> {
+        let root = env::var("STORAGE_LOCAL_ROOT")
+            .ok()
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| DEFAULT_LOCAL_ROOT.to_string());
+        let public_url = env::var("STORAGE_PUBLIC_URL")
+            .ok()
+            .filter(|u| !u.is_empty());
+        let signing_key = env::var("STORAGE_SIGNING_KEY")
+            .ok()
+            .filter(|k| !k.is_empty());
+        Self::new(Path::new(&root), bucket, public_url, signing_key)
+    }
+
+    /// Where the object at `path` is stored.
+    pub fn file_path(&self, path: &str) -> Result<PathBuf> {
+        local_object_path(&self.root, path)
+            .ok_or_else(|| request_failed(format!("invalid object path {}", path)))
+    }
+
+    /// Where the object a signed URL points at is stored, once its signature and expiry
+    /// check out.
+    pub fn signed_file_path(&self, path: &str, expires: i64, signature: &str) -> Result<PathBuf> {
+        let signing_key = self
+            .signing_key
+            .as_deref()
+            .ok_or(StorageUrlError::NotConfigured)?;
+        verify_local_url(
+            signing_key,
+            &self.bucket,
+            path,
+            expires,
+            signature,
+            Utc::now().timestamp(),
+        )
+        .map_err(StorageUrlError::Invalid)?;
+        local_object_path(&self.root, path).ok_or_else(|| {
+            StorageUrlError::Invalid("Invalid storage URL: bad path".to_string()).into()
+        })
+    }
+
+    // Files are written beside their final path and moved into place once complete, so readers
+    // never see half a file
+    async fn create_temporary(&self, target: &Path) -> Result<(PathBuf, tokio::fs::File)> {
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(request_failed)?;
+        }
+        let temporary = target.with_extension(format!("{}.partial", Uuid::new_v4().simple()));
+        let file = tokio::fs::File::create(&temporary)
+            .await
+            .map_err(request_failed)?;
+        Ok((temporary, file))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn put(&self, path: &str, content: Vec<u8>, _content_type: &str) -> Result<()> {
+        let target = self.file_path(path)?;
+        let (temporary, mut file) = self.create_temporary(&target).await?;
+        let written = async {
+            file.write_all(&content).await?;
+            file.sync_all().await?;
+            tokio::fs::rename(&temporary, &target).await
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(request_failed(e));
+        }
+        Ok(())
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>> {
+        tokio::fs::read(self.file_path(path)?)
+            .await
+            .map_err(request_failed)
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.file_path(path)?).await {
+            // Already gone is as good as deleted
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(request_failed(e)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn signed_url(&self, path: &str, expires_in: Duration) -> Result<String> {
+        let (Some(public_url), Some(signing_key)) = (&self.public_url, &self.signing_key) else {
+            return Err(request_failed(
+                "signed URLs need STORAGE_PUBLIC_URL and STORAGE_SIGNING_KEY",
+            ));
+        };
+        self.file_path(path)?;
+        let expires = Utc::now().timestamp() + expires_in.as_secs().max(1) as i64;
+        Ok(format!(
+            "{}/api/v1/storage/{}/{}?expires={}&signature={}",
+            public_url,
+            uri_encode(&self.bucket, false),
+            uri_encode(path, true),
+            expires,
+            local_url_signature(signing_key, &self.bucket, path, expires)
+        ))
+    }
+
+    async fn assemble(
+        &self,
+        parts: &[String],
+        path: &str,
+        _content_type: &str,
+        size_bytes: u64,
+    ) -> Result<String> {
+        let target = self.file_path(path)?;
+        let (temporary, mut file) = self.create_temporary(&target).await?;
+        let mut hasher = Sha256::new();
+        let written = async {
+            let mut written = 0u64;
+            for part in parts {
+                let content = self.get(part).await?;
+                hasher.update(&content);
+                file.write_all(&content).await.map_err(request_failed)?;
+                written += content.len() as u64;
+            }
+            if written != size_bytes {
+                return Err(request_failed(format!(
+                    "assembled {} of {} bytes",
+                    written, size_bytes
+                )));
+            }
+            file.sync_all().await.map_err(request_failed)?;
+            tokio::fs::rename(&temporary, &target)
+                .await
+                .map_err(request_failed)
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(e);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
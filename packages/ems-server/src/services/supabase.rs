@@ -4,11 +4,16 @@ use oauth2::{
     TokenResponse, TokenUrl,
 };
 use postgrest::Postgrest;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use serde::{Deserialize, Serialize};
 use std::env;
 
 use crate::models::{OAuthProvider, OAuthUserInfo};
+use crate::services::AuthError;
+use crate::utils::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStatus};
+
+// Days a cached password stays usable for degraded sign-in
+const DEFAULT_DEGRADED_LOGIN_MAX_AGE_DAYS: i64 = 30;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SupabaseOAuthResponse {
@@ -41,6 +46,60 @@ pub struct OAuthConfig {
     pub scopes: Vec<String>,
 }
 
+/// Where and how the server talks to Supabase. `from_env` reads the server configuration;
+/// tests build one pointing at a recorded server instead.
+#[derive(Debug, Clone)]
+pub struct SupabaseConfig {
+    /// Project URL, e.g. `https://abc.supabase.co`
+    pub url: String,
+    /// Anon key sent with every request
+    pub api_key: String,
+    /// Key for the admin API (creating and looking up users); None when not configured
+    pub service_role_key: Option<String>,
+    pub breaker: CircuitBreakerConfig,
+    /// How long a password Supabase accepted may be checked locally while Supabase is
+    /// unavailable; None when degraded sign-in is disabled
+    pub degraded_login_max_age: Option<chrono::Duration>,
+}
+
+impl SupabaseConfig {
+    /// Default breaker settings, no admin key and degraded sign-in disabled.
+    pub fn new(url: &str, api_key: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            service_role_key: None,
+            breaker: CircuitBreakerConfig::default(),
+            degraded_login_max_age: None,
+        }
+    }
+
+    /// Reads SUPABASE_SERVICE_ROLE_KEY, the SUPABASE_* breaker settings, SUPABASE_DEGRADED_LOGIN
+    /// and SUPABASE_DEGRADED_LOGIN_MAX_AGE_DAYS (default 30).
+    pub fn from_env(url: &str, api_key: &str) -> Self {
+        let degraded_login_max_age = (env::var("SUPABASE_DEGRADED_LOGIN")
+            .map(|v| v == "true")
+            .unwrap_or(false))
+        .then(|| {
+            let days = env::var("SUPABASE_DEGRADED_LOGIN_MAX_AGE_DAYS")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .filter(|d| *d > 0)
+                .unwrap_or(DEFAULT_DEGRADED_LOGIN_MAX_AGE_DAYS);
+            chrono::Duration::days(days)
+        });
+
+        Self {
+            service_role_key: env::var("SUPABASE_SERVICE_ROLE_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+            breaker: CircuitBreakerConfig::from_env("SUPABASE"),
+            degraded_login_max_age,
+            ..Self::new(url, api_key)
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SupabaseService {
     pub client: Postgrest,
@@ -50,12 +109,26 @@ pub struct SupabaseService {
     pub google_oauth: Option<OAuthConfig>,
     pub microsoft_oauth: Option<OAuthConfig>,
     pub apple_oauth: Option<OAuthConfig>,
+    service_role_key: Option<String>,
+    // Shared by every clone, so all requests see the same Supabase health
+    breaker: CircuitBreaker,
+    /// How long a password Supabase accepted may be checked locally while Supabase is
+    /// unavailable; None when degraded sign-in is disabled
+    pub degraded_login_max_age: Option<chrono::Duration>,
 }
 
 impl SupabaseService {
     pub async fn new(url: &str, api_key: &str) -> Result<Self> {
-        let client = Postgrest::new(format!("{}/rest/v1", url)).insert_header("apikey", api_key);
-        let http_client = Client::new();
+        Self::with_config(SupabaseConfig::from_env(url, api_key))
+    }
+
+    pub fn with_config(config: SupabaseConfig) -> Result<Self> {
+        let client = Postgrest::new(format!("{}/rest/v1", config.url))
+            .insert_header("apikey", &config.api_key);
+        let breaker = CircuitBreaker::new("Supabase", config.breaker);
+        let http_client = Client::builder()
+            .timeout(breaker.config().call_timeout)
+            .build()?;
 
         // Initialize OAuth configurations
         let google_oauth = Self::init_google_oauth()?;
@@ -64,15 +137,43 @@ impl SupabaseService {
 
         Ok(Self {
             client,
-            url: url.to_string(),
-            api_key: api_key.to_string(),
+            url: config.url,
+            api_key: config.api_key,
             http_client,
             google_oauth,
             microsoft_oauth,
             apple_oauth,
+            service_role_key: config.service_role_key,
+            breaker,
+            degraded_login_max_age: config.degraded_login_max_age,
         })
     }
 
+    pub fn breaker_status(&self) -> CircuitBreakerStatus {
+        self.breaker.status()
+    }
+
+    // Sends a Supabase request through the circuit breaker. Server errors count as failures
+    // alongside timeouts and connection errors; other responses go back to the caller.
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        self.breaker
+            .call(async move {
+                let response = request.send().await?;
+                if response.status().is_server_error() {
+                    return Err(anyhow::anyhow!("HTTP {}", response.status()));
+                }
+                Ok(response)
+            })
+            .await
+    }
+
+    // Key for the admin API
+    fn service_role_key(&self) -> Result<&str> {
+        self.service_role_key
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("SUPABASE_SERVICE_ROLE_KEY not set"))
+    }
+
     pub fn get_client(&self) -> Postgrest {
         self.client.clone()
     }
@@ -173,8 +274,7 @@ impl SupabaseService {
             OAuthProvider::Apple => self.apple_oauth.as_ref(),
         };
 
-        let config = config
-            .ok_or_else(|| anyhow::anyhow!("{} OAuth is not configured", provider.to_string()))?;
+        let config = config.ok_or_else(|| AuthError::OAuthNotConfigured(provider.to_string()))?;
 
         let client = oauth2::basic::BasicClient::new(
             ClientId::new(config.client_id.clone()),
@@ -220,8 +320,7 @@ impl SupabaseService {
             OAuthProvider::Apple => self.apple_oauth.as_ref(),
         };
 
-        let config = config
-            .ok_or_else(|| anyhow::anyhow!("{} OAuth is not configured", provider.to_string()))?;
+        let config = config.ok_or_else(|| AuthError::OAuthNotConfigured(provider.to_string()))?;
 
         let client = oauth2::basic::BasicClient::new(
             ClientId::new(config.client_id.clone()),
@@ -236,7 +335,7 @@ impl SupabaseService {
             .exchange_code(AuthorizationCode::new(code.to_string()))
             .request_async(oauth2::reqwest::async_http_client)
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to exchange code for token: {:?}", e))?;
+            .map_err(|e| AuthError::CodeExchangeFailed(format!("{:?}", e)))?;
 
         let access_token = token_result.access_token().secret();
 
@@ -334,28 +433,29 @@ impl SupabaseService {
         let auth_url = format!("{}/auth/v1/token?grant_type=id_token", self.url);
 
         let response = self
-            .http_client
-            .post(&auth_url)
-            .header("apikey", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "id_token": access_token,
-                "provider": provider.to_string()
-            }))
-            .send()
+            .send(
+                self.http_client
+                    .post(&auth_url)
+                    .header("apikey", &self.api_key)
+                    .header("Content-Type", "application/json")
+                    .json(&serde_json::json!({
+                        "id_token": access_token,
+                        "provider": provider.to_string()
+                    })),
+            )
             .await?;
 
         if response.status().is_success() {
             let oauth_response: SupabaseOAuthResponse = response.json().await?;
             Ok(oauth_response)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
+            let error = ApiError::read(response).await;
+            if error.is_expired_token() {
+                return Err(AuthError::AuthenticationFailed("Token expired".to_string()).into());
+            }
             Err(anyhow::anyhow!(
                 "Supabase OAuth sign-in failed: {}",
-                error_text
+                error.message
             ))
         }
     }
@@ -368,8 +468,7 @@ impl SupabaseService {
         metadata: Option<serde_json::Value>,
     ) -> Result<serde_json::Value> {
         // Use service role key for admin operations
-        let service_role_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")
-            .map_err(|_| anyhow::anyhow!("SUPABASE_SERVICE_ROLE_KEY not set"))?;
+        let service_role_key = self.service_role_key()?;
 
         let admin_url = format!("{}/auth/v1/admin/users", self.url);
 
@@ -384,24 +483,26 @@ impl SupabaseService {
         }
 
         let response = self
-            .http_client
-            .post(&admin_url)
-            .header("apikey", &service_role_key)
-            .header("Authorization", format!("Bearer {}", service_role_key))
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.http_client
+                    .post(&admin_url)
+                    .header("apikey", service_role_key)
+                    .header("Authorization", format!("Bearer {}", service_role_key))
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
         if response.status().is_success() {
             let user: serde_json::Value = response.json().await?;
             Ok(user)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("User creation failed: {}", error_text))
+            let error = ApiError::read(response).await;
+            // Signed up before, e.g. in another EMS deployment sharing the project
+            if error.is_existing_user() {
+                return Err(AuthError::EmailTaken.into());
+            }
+            Err(anyhow::anyhow!("User creation failed: {}", error.message))
         }
     }
 
@@ -414,45 +515,43 @@ impl SupabaseService {
         let auth_url = format!("{}/auth/v1/token?grant_type=password", self.url);
 
         let response = self
-            .http_client
-            .post(&auth_url)
-            .header("apikey", &self.api_key)
-            .header("Content-Type", "application/json")
-            .json(&serde_json::json!({
-                "email": email,
-                "password": password
-            }))
-            .send()
+            .send(
+                self.http_client
+                    .post(&auth_url)
+                    .header("apikey", &self.api_key)
+                    .header("Content-Type", "application/json")
+                    .json(&serde_json::json!({
+                        "email": email,
+                        "password": password
+                    })),
+            )
             .await?;
 
         if response.status().is_success() {
             let session: serde_json::Value = response.json().await?;
             Ok(session)
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Authentication failed: {}", error_text))
+            let error = ApiError::read(response).await;
+            Err(AuthError::AuthenticationFailed(error.message).into())
         }
     }
 
     /// Verify if user exists in Supabase
     pub async fn verify_user_exists(&self, email: &str) -> Result<bool> {
         // Use service role key for admin operations
-        let service_role_key = std::env::var("SUPABASE_SERVICE_ROLE_KEY")
-            .map_err(|_| anyhow::anyhow!("SUPABASE_SERVICE_ROLE_KEY not set"))?;
+        let service_role_key = self.service_role_key()?;
 
         let admin_url = format!("{}/auth/v1/admin/users", self.url);
 
         let response = self
-            .http_client
-            .get(&admin_url)
-            .header("apikey", &service_role_key)
-            .header("Authorization", format!("Bearer {}", service_role_key))
-            .header("Content-Type", "application/json")
-            .query(&[("email", email)])
-            .send()
+            .send(
+                self.http_client
+                    .get(&admin_url)
+                    .header("apikey", service_role_key)
+                    .header("Authorization", format!("Bearer {}", service_role_key))
+                    .header("Content-Type", "application/json")
+                    .query(&[("email", email)]),
+            )
             .await?;
 
         if response.status().is_success() {
@@ -465,11 +564,58 @@ impl SupabaseService {
                 Ok(false)
             }
         } else {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Failed to verify user: {}", error_text))
+            let error = ApiError::read(response).await;
+            Err(anyhow::anyhow!("Failed to verify user: {}", error.message))
         }
     }
 }
+
+// Error answered by the Supabase API. Auth errors carry their message in `msg` or
+// `error_description` and their code in `error_code` or `error`; REST and storage errors use
+// `message` and `code`.
+struct ApiError {
+    status: reqwest::StatusCode,
+    code: Option<String>,
+    message: String,
+}
+
+impl ApiError {
+    async fn read(response: Response) -> Self {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        let field = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| body[*name].as_str())
+                .map(str::to_string)
+        };
+
+        let message = field(&["msg", "error_description", "message"])
+            .or_else(|| field(&["error"]))
+            .unwrap_or_else(|| {
+                if text.is_empty() {
+                    format!("HTTP {}", status)
+                } else {
+                    text
+                }
+            });
+        Self {
+            status,
+            code: field(&["error_code", "code", "error"]),
+            message,
+        }
+    }
+
+    fn is_existing_user(&self) -> bool {
+        matches!(
+            self.code.as_deref(),
+            Some("email_exists") | Some("user_already_exists")
+        ) || (self.status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
+            && self.message.contains("already been registered"))
+    }
+
+    fn is_expired_token(&self) -> bool {
+        self.code.as_deref() == Some("bad_jwt") || self.message.contains("expired")
+    }
+}
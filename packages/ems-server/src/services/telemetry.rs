@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::{
+    MachineTelemetryResponse, NewMachineTelemetry, TelemetryBucketRow, TelemetryChannel,
+    TelemetryPoint, TelemetrySeries,
+};
+use crate::schema::*;
+use crate::services::{DatabaseService, HeartbeatBuffer};
+use crate::utils::telemetry::{bucket_seconds, extract_readings};
+
+pub struct TelemetryService {
+    database: DatabaseService,
+    heartbeat_buffer: Option<HeartbeatBuffer>,
+}
+
+impl TelemetryService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self {
+            database,
+            heartbeat_buffer: None,
+        }
+    }
+
+    /// Queues heartbeat readings in the heartbeat buffer instead of writing them at once.
+    pub fn with_buffer(mut self, heartbeat_buffer: Option<HeartbeatBuffer>) -> Self {
+        self.heartbeat_buffer = heartbeat_buffer;
+        self
+    }
+
+    /// Stores the declared telemetry channels found in a heartbeat payload, through the heartbeat
+    /// buffer when there is one and the tenant's queue has room.
+    /// Returns the number of readings recorded.
+    pub async fn record_heartbeat_telemetry(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        payload: &Value,
+        metadata: Option<&Value>,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<usize> {
+        let readings: Vec<NewMachineTelemetry> = extract_readings(payload, metadata)
+            .into_iter()
+            .map(|(channel, value)| NewMachineTelemetry {
+                tenant_id,
+                machine_id,
+                channel: channel.to_string(),
+                value,
+                recorded_at,
+            })
+            .collect();
+        if readings.is_empty() {
+            return Ok(0);
+        }
+        let readings = match &self.heartbeat_buffer {
+            Some(buffer) => {
+                let count = readings.len();
+                match buffer.push_readings(tenant_id, readings) {
+                    Ok(()) => return Ok(count),
+                    Err(readings) => readings,
+                }
+            }
+            None => readings,
+        };
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let inserted = diesel::insert_into(machine_telemetry::table)
+            .values(&readings)
+            .execute(&mut conn)
+            .await?;
+
+        Ok(inserted)
+    }
+
+    /// Downsampled telemetry for a machine; `None` if the machine does not exist.
+    pub async fn get_machine_telemetry(
+        &self,
+        tenant_id: Uuid,
+        machine_id: Uuid,
+        channel: Option<TelemetryChannel>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        requested_bucket_seconds: Option<i64>,
+    ) -> Result<Option<MachineTelemetryResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let machine_exists = machines::table
+            .filter(machines::id.eq(machine_id))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .select(machines::id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .is_some();
+        if !machine_exists {
+            return Ok(None);
+        }
+
+        let bucket_seconds = bucket_seconds(from, to, requested_bucket_seconds);
+
+        let rows = diesel::sql_query(
+            r#"
+            SELECT channel,
+                   to_timestamp((floor(extract(epoch FROM recorded_at) / $4) * $4)::float8) AS bucket_start,
+                   avg(value) AS avg_value,
+                   min(value) AS min_value,
+                   max(value) AS max_value,
+                   count(*) AS samples
+            FROM machine_telemetry
+            WHERE tenant_id = $1
+              AND machine_id = $2
+              AND ($3::text IS NULL OR channel = $3)
+              AND recorded_at >= $5
+              AND recorded_at < $6
+            GROUP BY channel, bucket_start
+            ORDER BY channel, bucket_start
+            "#,
+        )
+        .bind::<SqlUuid, _>(tenant_id)
+        .bind::<SqlUuid, _>(machine_id)
+        .bind::<Nullable<Text>, _>(channel.map(|c| c.to_string()))
+        .bind::<BigInt, _>(bucket_seconds)
+        .bind::<Timestamptz, _>(from)
+        .bind::<Timestamptz, _>(to)
+        .load::<TelemetryBucketRow>(&mut conn)
+        .await?;
+
+        let mut series: Vec<TelemetrySeries> = Vec::new();
+        for row in rows {
+            let Ok(row_channel) = TelemetryChannel::try_from(row.channel) else {
+                continue;
+            };
+            let point = TelemetryPoint {
+                bucket_start: row.bucket_start,
+                avg: row.avg_value,
+                min: row.min_value,
+                max: row.max_value,
+                samples: row.samples,
+            };
+            match series.last_mut() {
+                Some(current) if current.channel == row_channel => current.points.push(point),
+                _ => series.push(TelemetrySeries {
+                    channel: row_channel,
+                    unit: row_channel.unit().to_string(),
+                    points: vec![point],
+                }),
+            }
+        }
+
+        Ok(Some(MachineTelemetryResponse {
+            machine_id,
+            from,
+            to,
+            bucket_seconds,
+            series,
+        }))
+    }
+}
@@ -1,29 +1,132 @@
 use anyhow::Result;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 use crate::models::{CreateTenantRequest, NewTenant, Tenant, UpdateTenantRequest};
 use crate::schema::tenants;
-use crate::services::DatabaseService;
+use crate::services::{DatabaseService, EncryptionService};
+use crate::utils::diagnostics::Diagnostics;
+use crate::utils::encryption::{has_secret_settings, strip_secret_settings};
+use crate::utils::i18n::Locale;
+
+// Seconds a looked-up tenant is reused before it is read again
+const DEFAULT_TENANT_CACHE_TTL_SECONDS: u64 = 60;
+const MAX_CACHED_TENANTS: usize = 10_000;
+
+/// Recently resolved tenants, shared by every request so tenant resolution does not query the
+/// database each time. Only tenants that exist are cached; changes made through
+/// [`TenantService`] invalidate their entry, other changes show up once the TTL passes.
+#[derive(Clone)]
+pub struct TenantCache {
+    ttl: Duration,
+    entries: Arc<RwLock<TenantCacheEntries>>,
+}
+
+#[derive(Default)]
+struct TenantCacheEntries {
+    by_id: HashMap<Uuid, (Instant, Tenant)>,
+    by_subdomain: HashMap<String, Uuid>,
+}
+
+impl TenantCache {
+    /// A TTL of zero disables caching.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(RwLock::new(TenantCacheEntries::default())),
+        }
+    }
+
+    /// Reads TENANT_CACHE_TTL_SECONDS (default 60; 0 disables the cache).
+    pub fn from_env() -> Self {
+        let ttl = env::var("TENANT_CACHE_TTL_SECONDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_TENANT_CACHE_TTL_SECONDS);
+        Self::new(Duration::from_secs(ttl))
+    }
+
+    pub fn get(&self, tenant_id: Uuid) -> Option<Tenant> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .by_id
+            .get(&tenant_id)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, tenant)| tenant.clone())
+    }
+
+    pub fn get_by_subdomain(&self, subdomain: &str) -> Option<Tenant> {
+        let tenant_id = *self.entries.read().unwrap().by_subdomain.get(subdomain)?;
+        self.get(tenant_id)
+    }
+
+    pub fn insert(&self, tenant: &Tenant) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.write().unwrap();
+        // Drop expired entries once the cache grows large so unused tenants do not pile up
+        if entries.by_id.len() >= MAX_CACHED_TENANTS {
+            let ttl = self.ttl;
+            let TenantCacheEntries {
+                by_id,
+                by_subdomain,
+            } = &mut *entries;
+            by_id.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+            by_subdomain.retain(|_, id| by_id.contains_key(id));
+        }
+        entries
+            .by_subdomain
+            .insert(tenant.subdomain.clone(), tenant.id);
+        entries
+            .by_id
+            .insert(tenant.id, (Instant::now(), tenant.clone()));
+    }
+
+    pub fn invalidate(&self, tenant_id: Uuid) {
+        let mut entries = self.entries.write().unwrap();
+        if let Some((_, tenant)) = entries.by_id.remove(&tenant_id) {
+            entries.by_subdomain.remove(&tenant.subdomain);
+        }
+    }
+}
 
 pub struct TenantService {
     database: DatabaseService,
+    cache: Option<TenantCache>,
 }
 
 impl TenantService {
     pub fn new(database: DatabaseService) -> Self {
-        Self { database }
+        // Tenant records are never moved to a tenant's dedicated database
+        Self {
+            database: database.shared(),
+            cache: None,
+        }
+    }
+
+    /// Serves lookups from the cache and keeps it current when tenants change.
+    pub fn with_cache(mut self, cache: TenantCache) -> Self {
+        self.cache = Some(cache);
+        self
     }
 
+    /// Secrets in the settings are encrypted with the tenant's data key, which can only be made
+    /// once the tenant exists, so they are added right after the tenant is created.
     pub async fn create_tenant(&self, request: CreateTenantRequest) -> Result<Tenant> {
         let mut conn = self.database.get_connection().await?;
 
+        let secrets = request.settings.clone().filter(has_secret_settings);
         let new_tenant = NewTenant {
             name: request.name,
             subdomain: request.subdomain,
             database_url: None,
-            settings: request.settings,
+            settings: request.settings.as_ref().map(strip_secret_settings),
             is_active: Some(true),
         };
 
@@ -33,10 +136,28 @@ impl TenantService {
             .get_result(&mut conn)
             .await?;
 
+        let Some(settings) = secrets else {
+            return Ok(tenant);
+        };
+        let settings = self.seal_settings(tenant.id, &settings).await?;
+        let tenant = diesel::update(tenants::table.filter(tenants::id.eq(tenant.id)))
+            .set(tenants::settings.eq(settings))
+            .returning(Tenant::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
         Ok(tenant)
     }
 
     pub async fn get_tenant_by_id(&self, tenant_id: Uuid) -> Result<Option<Tenant>> {
+        if let Some(cache) = &self.cache {
+            let cached = cache.get(tenant_id);
+            Diagnostics::global().record_cache_lookup(tenant_id, cached.is_some());
+            if cached.is_some() {
+                return Ok(cached);
+            }
+        }
+
         let mut conn = self.database.get_connection().await?;
 
         let tenant = tenants::table
@@ -46,10 +167,30 @@ impl TenantService {
             .await
             .optional()?;
 
+        self.remember(tenant.as_ref());
         Ok(tenant)
     }
 
+    /// Locale the tenant's documents are written in; English when none is set or the tenant
+    /// does not exist.
+    pub async fn get_locale(&self, tenant_id: Uuid) -> Result<Locale> {
+        Ok(self
+            .get_tenant_by_id(tenant_id)
+            .await?
+            .map(|tenant| tenant.locale())
+            .unwrap_or_default())
+    }
+
     pub async fn get_tenant_by_subdomain(&self, subdomain: &str) -> Result<Option<Tenant>> {
+        if let Some(tenant) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get_by_subdomain(subdomain))
+        {
+            Diagnostics::global().record_cache_lookup(tenant.id, true);
+            return Ok(Some(tenant));
+        }
+
         let mut conn = self.database.get_connection().await?;
 
         let tenant = tenants::table
@@ -59,43 +200,151 @@ impl TenantService {
             .await
             .optional()?;
 
+        // Misses are only attributed to tenants that exist
+        if let (Some(_), Some(tenant)) = (&self.cache, &tenant) {
+            Diagnostics::global().record_cache_lookup(tenant.id, false);
+        }
+        self.remember(tenant.as_ref());
         Ok(tenant)
     }
 
+    fn remember(&self, tenant: Option<&Tenant>) {
+        if let (Some(cache), Some(tenant)) = (&self.cache, tenant) {
+            cache.insert(tenant);
+        }
+    }
+
+    fn forget(&self, tenant_id: Uuid) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(tenant_id);
+        }
+    }
+
+    // Encrypts the secrets in tenant settings; settings without secrets never create a data key
+    async fn seal_settings(
+        &self,
+        tenant_id: Uuid,
+        settings: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if !has_secret_settings(settings) {
+            return Ok(settings.clone());
+        }
+        EncryptionService::new(self.database.clone())
+            .cipher(Some(tenant_id))
+            .await?
+            .seal_settings(settings)
+    }
+
     pub async fn update_tenant(
         &self,
         tenant_id: Uuid,
         request: UpdateTenantRequest,
     ) -> Result<Tenant> {
+        let settings = match &request.settings {
+            Some(settings) => Some(self.seal_settings(tenant_id, settings).await?),
+            None => None,
+        };
         let mut conn = self.database.get_connection().await?;
 
-        // Update fields individually to avoid Diesel type issues
-        if let Some(name) = &request.name {
-            diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
-                .set(tenants::name.eq(name))
-                .execute(&mut conn)
-                .await?;
-        }
-        if let Some(settings) = &request.settings {
-            diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
-                .set(tenants::settings.eq(settings))
-                .execute(&mut conn)
-                .await?;
-        }
-        if let Some(is_active) = request.is_active {
-            diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
-                .set(tenants::is_active.eq(is_active))
-                .execute(&mut conn)
-                .await?;
-        }
+        let tenant = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                Box::pin(async move {
+                    // Update fields individually to avoid Diesel type issues
+                    if let Some(name) = &request.name {
+                        diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
+                            .set(tenants::name.eq(name))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(settings) = &settings {
+                        diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
+                            .set(tenants::settings.eq(settings))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(is_active) = request.is_active {
+                        diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
+                            .set(tenants::is_active.eq(is_active))
+                            .execute(conn)
+                            .await?;
+                    }
+                    if let Some(database_url) = &request.database_url {
+                        // An empty URL moves the tenant back to the shared database
+                        let database_url =
+                            Some(database_url.as_str()).filter(|url| !url.is_empty());
+                        if let (Some(url), true) =
+                            (database_url, self.database.dedicated_databases_enabled())
+                        {
+                            self.database
+                                .register_tenant_database(tenant_id, url)
+                                .await?;
+                        } else {
+                            self.database.forget_tenant_database(tenant_id);
+                        }
+                        diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
+                            .set(tenants::database_url.eq(database_url))
+                            .execute(conn)
+                            .await?;
+                    }
 
-        // Return the updated tenant
-        let tenant = tenants::table
-            .filter(tenants::id.eq(tenant_id))
-            .select(Tenant::as_select())
-            .first::<Tenant>(&mut conn)
+                    // Return the updated tenant
+                    let tenant = tenants::table
+                        .filter(tenants::id.eq(tenant_id))
+                        .select(Tenant::as_select())
+                        .first::<Tenant>(conn)
+                        .await?;
+
+                    Ok(tenant)
+                })
+            })
+            .await?;
+
+        self.forget(tenant_id);
+        Ok(tenant)
+    }
+
+    /// Sets the setting `key` to `value`, leaving the other settings as they are. Meant for
+    /// settings without secrets, which are stored as given. `Ok(None)` when the tenant does not
+    /// exist.
+    pub async fn put_setting(
+        &self,
+        tenant_id: Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<Option<Tenant>> {
+        let tenant = self
+            .database
+            .with_tenant_tx::<_, anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    // Locked so settings changed meanwhile are not written back over
+                    let Some(settings) = tenants::table
+                        .filter(tenants::id.eq(tenant_id))
+                        .select(tenants::settings)
+                        .for_update()
+                        .first::<Option<serde_json::Value>>(conn)
+                        .await
+                        .optional()?
+                    else {
+                        return Ok(None);
+                    };
+
+                    let mut settings = match settings {
+                        Some(serde_json::Value::Object(settings)) => settings,
+                        _ => serde_json::Map::new(),
+                    };
+                    settings.insert(key.to_string(), value);
+                    let tenant = diesel::update(tenants::table.filter(tenants::id.eq(tenant_id)))
+                        .set(tenants::settings.eq(serde_json::Value::Object(settings)))
+                        .returning(Tenant::as_returning())
+                        .get_result::<Tenant>(conn)
+                        .await?;
+
+                    Ok(Some(tenant))
+                })
+            })
             .await?;
 
+        self.forget(tenant_id);
         Ok(tenant)
     }
 
@@ -106,6 +355,7 @@ impl TenantService {
             .execute(&mut conn)
             .await?;
 
+        self.forget(tenant_id);
         Ok(())
     }
 
@@ -0,0 +1,354 @@
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel::PgJsonbExpressionMethods;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::models::{
+    BatchMaterial, BatchRecord, BatchRecordResponse, Job, Order, TraceEntity, TraceGraph,
+    TraceNodeKind, TraceQuery, TraceRelation,
+};
+use crate::schema::*;
+use crate::services::{BatchRecordService, DatabaseService};
+use crate::utils::trace::{node_id, numbered_key, TraceGraphBuilder};
+
+pub struct TraceService {
+    database: DatabaseService,
+}
+
+impl TraceService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Builds the traceability graph of a lot, serial, job or order for recall investigations:
+    /// the orders its lots shipped on, the jobs and machines that made them, and the
+    /// components, component lots, serials and vendors they were built from. Orders are linked
+    /// to lots as in [`BatchRecordService::shipped_lots`]. Returns `None` when nothing matches.
+    pub async fn trace(&self, tenant_id: Uuid, query: TraceQuery) -> Result<Option<TraceGraph>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let record_id = Uuid::parse_str(query.id.trim()).ok();
+        let number = query.id.trim();
+        let mut graph = TraceGraphBuilder::new();
+        let mut roots = Vec::new();
+        let mut jobs: Vec<Job> = Vec::new();
+        // Orders found from the start, with the lots they shipped
+        let mut shipped: Vec<(Order, Vec<Uuid>)> = Vec::new();
+        // Whether the lots themselves were asked for
+        let mut lots_are_roots = false;
+
+        let lots: Vec<BatchRecordResponse> = match query.entity {
+            TraceEntity::Order => {
+                let mut orders = orders::table
+                    .filter(orders::tenant_id.eq(tenant_id))
+                    .into_boxed();
+                orders = match record_id {
+                    Some(id) => orders.filter(orders::id.eq(id)),
+                    None => orders.filter(orders::order_number.eq(number)),
+                };
+                let Some(order) = orders
+                    .select(Order::as_select())
+                    .first::<Order>(&mut conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+                let item_ids: Vec<Uuid> = order_items::table
+                    .filter(order_items::order_id.eq(order.id))
+                    .filter(order_items::item_id.is_not_null())
+                    .select(order_items::item_id.assume_not_null())
+                    .load(&mut conn)
+                    .await?;
+                let lots = BatchRecordService::shipped_lots(
+                    &mut conn,
+                    tenant_id,
+                    order.id,
+                    order.external_entity_id,
+                    &item_ids,
+                )
+                .await?;
+                shipped.push((order, lots.iter().map(|lot| lot.id).collect()));
+                responses(lots)?
+            }
+            TraceEntity::Job => {
+                let mut job_query = jobs::table
+                    .filter(jobs::tenant_id.eq(tenant_id))
+                    .into_boxed();
+                job_query = match record_id {
+                    Some(id) => job_query.filter(jobs::id.eq(id)),
+                    None => job_query.filter(jobs::job_number.eq(number)),
+                };
+                let Some(job) = job_query
+                    .select(Job::as_select())
+                    .first::<Job>(&mut conn)
+                    .await
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+                roots.push(graph.node(
+                    TraceNodeKind::Job,
+                    job.id,
+                    &job.job_number,
+                    Some(job.id),
+                    job.item_id,
+                ));
+                let lots = batch_records::table
+                    .filter(batch_records::tenant_id.eq(tenant_id))
+                    .filter(batch_records::job_id.eq(job.id))
+                    .select(BatchRecord::as_select())
+                    .load::<BatchRecord>(&mut conn)
+                    .await?;
+                jobs.push(job);
+                responses(lots)?
+            }
+            TraceEntity::Lot => {
+                let mut produced = batch_records::table
+                    .filter(batch_records::tenant_id.eq(tenant_id))
+                    .into_boxed();
+                produced = match record_id {
+                    Some(id) => produced.filter(batch_records::id.eq(id)),
+                    None => produced.filter(batch_records::batch_number.eq(number)),
+                };
+                if let Some(item_id) = query.item_id {
+                    produced = produced.filter(batch_records::item_id.eq(item_id));
+                }
+                let produced = produced
+                    .select(BatchRecord::as_select())
+                    .load::<BatchRecord>(&mut conn)
+                    .await?;
+                if produced.is_empty() {
+                    let consumed =
+                        Self::lots_consuming(&mut conn, tenant_id, "lot_numbers", number, &query)
+                            .await?;
+                    roots = Self::numbered_roots(
+                        &mut graph,
+                        TraceNodeKind::Lot,
+                        &consumed,
+                        |material| &material.lot_numbers,
+                        number,
+                        query.item_id,
+                    );
+                    consumed
+                } else {
+                    lots_are_roots = true;
+                    responses(produced)?
+                }
+            }
+            TraceEntity::Serial => {
+                let consumed =
+                    Self::lots_consuming(&mut conn, tenant_id, "serial_numbers", number, &query)
+                        .await?;
+                roots = Self::numbered_roots(
+                    &mut graph,
+                    TraceNodeKind::Serial,
+                    &consumed,
+                    |material| &material.serial_numbers,
+                    number,
+                    query.item_id,
+                );
+                consumed
+            }
+        };
+        if lots.is_empty() && jobs.is_empty() && shipped.is_empty() {
+            return Ok(None);
+        }
+
+        // Jobs that produced the lots
+        let job_ids: Vec<Uuid> = lots.iter().map(|lot| lot.job_id).collect();
+        jobs.extend(
+            jobs::table
+                .filter(jobs::tenant_id.eq(tenant_id))
+                .filter(jobs::id.eq_any(&job_ids))
+                .select(Job::as_select())
+                .load::<Job>(&mut conn)
+                .await?,
+        );
+        let jobs: BTreeMap<Uuid, Job> = jobs.into_iter().map(|job| (job.id, job)).collect();
+        let mut job_nodes = HashMap::new();
+        for job in jobs.values() {
+            let id = graph.node(
+                TraceNodeKind::Job,
+                job.id,
+                &job.job_number,
+                Some(job.id),
+                job.item_id,
+            );
+            job_nodes.insert(job.id, id);
+        }
+
+        let mut lot_jobs = HashMap::new();
+        for lot in &lots {
+            let lot_node = graph.lot(lot);
+            if lots_are_roots {
+                roots.push(lot_node.clone());
+            }
+            if let Some(job_node) = job_nodes.get(&lot.job_id) {
+                graph.edge(job_node, &lot_node, TraceRelation::Produced);
+            }
+            lot_jobs.insert(lot.id, lot.job_id);
+        }
+
+        // Orders the lots shipped on; an order trace only follows its own
+        if query.entity != TraceEntity::Order {
+            for lot in &lots {
+                let (Some(customer_id), Some(item_id), Some(job_node)) = (
+                    jobs.get(&lot.job_id).and_then(|job| job.customer_id),
+                    lot.item_id,
+                    job_nodes.get(&lot.job_id),
+                ) else {
+                    continue;
+                };
+                for (order_id, order_number) in BatchRecordService::orders_shipping_lot(
+                    &mut conn,
+                    tenant_id,
+                    customer_id,
+                    item_id,
+                    lot.completed_at,
+                )
+                .await?
+                {
+                    let order_node = graph.node(
+                        TraceNodeKind::Order,
+                        order_id,
+                        order_number,
+                        Some(order_id),
+                        None,
+                    );
+                    graph.edge(&order_node, job_node, TraceRelation::FulfilledBy);
+                }
+            }
+        }
+        for (order, lot_ids) in shipped {
+            let order_node = graph.node(
+                TraceNodeKind::Order,
+                order.id,
+                &order.order_number,
+                Some(order.id),
+                None,
+            );
+            roots.push(order_node.clone());
+            for lot_id in lot_ids {
+                if let Some(job_node) = lot_jobs.get(&lot_id).and_then(|id| job_nodes.get(id)) {
+                    graph.edge(&order_node, job_node, TraceRelation::FulfilledBy);
+                }
+            }
+        }
+
+        // Machines the jobs were assigned to
+        let assignments: Vec<(Uuid, Uuid, String)> = machine_job_assignments::table
+            .inner_join(machines::table)
+            .filter(machine_job_assignments::job_id.eq_any(jobs.keys()))
+            .filter(machines::tenant_id.eq(tenant_id))
+            .order(machine_job_assignments::start_time.asc())
+            .select((
+                machine_job_assignments::job_id,
+                machines::id,
+                machines::name,
+            ))
+            .load(&mut conn)
+            .await?;
+        for (job_id, machine_id, name) in assignments {
+            let machine_node = graph.machine(machine_id, &name);
+            if let Some(job_node) = job_nodes.get(&job_id) {
+                graph.edge(job_node, &machine_node, TraceRelation::RanOn);
+            }
+        }
+
+        // Vendors stocking the components
+        let vendors: Vec<(Uuid, Uuid, String)> = inventory_items::table
+            .inner_join(person::table.on(person::id.nullable().eq(inventory_items::vendor_id)))
+            .filter(inventory_items::tenant_id.eq(tenant_id))
+            .filter(inventory_items::item_id.eq_any(graph.component_items()))
+            .order(person::name.asc())
+            .select((inventory_items::item_id, person::id, person::name))
+            .load(&mut conn)
+            .await?;
+        for (item_id, vendor_id, name) in vendors {
+            let vendor_node = graph.node(
+                TraceNodeKind::Vendor,
+                vendor_id,
+                name,
+                Some(vendor_id),
+                None,
+            );
+            graph.edge(
+                &node_id(TraceNodeKind::Component, item_id),
+                &vendor_node,
+                TraceRelation::SuppliedBy,
+            );
+        }
+
+        Ok(Some(graph.finish(roots)))
+    }
+
+    // Private helper methods
+
+    /// Lots whose batch record lists `number` under `field` of a consumed material, optionally
+    /// of one item.
+    async fn lots_consuming(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        field: &str,
+        number: &str,
+        query: &TraceQuery,
+    ) -> Result<Vec<BatchRecordResponse>> {
+        let mut material = json!({ field: [number] });
+        if let Some(item_id) = query.item_id {
+            material["item_id"] = json!(item_id);
+        }
+        let records = batch_records::table
+            .filter(batch_records::tenant_id.eq(tenant_id))
+            .filter(batch_records::record.contains(json!({ "materials": [material] })))
+            .order(batch_records::completed_at.asc())
+            .select(BatchRecord::as_select())
+            .load::<BatchRecord>(conn)
+            .await?;
+        responses(records)
+    }
+
+    /// Root nodes for a component lot or serial number: one per item it was consumed as.
+    fn numbered_roots(
+        graph: &mut TraceGraphBuilder,
+        kind: TraceNodeKind,
+        lots: &[BatchRecordResponse],
+        numbers: impl Fn(&BatchMaterial) -> &Vec<String>,
+        number: &str,
+        item_id: Option<Uuid>,
+    ) -> Vec<String> {
+        let mut roots = Vec::new();
+        for material in lots.iter().flat_map(|lot| &lot.content.materials) {
+            if item_id.is_some_and(|item_id| item_id != material.item_id)
+                || !numbers(material).iter().any(|n| n == number)
+            {
+                continue;
+            }
+            let root = graph.node(
+                kind,
+                numbered_key(material.item_id, number),
+                number,
+                None,
+                Some(material.item_id),
+            );
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+}
+
+fn responses(records: Vec<BatchRecord>) -> Result<Vec<BatchRecordResponse>> {
+    Ok(records
+        .into_iter()
+        .map(BatchRecordResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?)
+}
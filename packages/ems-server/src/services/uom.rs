@@ -0,0 +1,535 @@
+use anyhow::Result;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    ConversionResponse, ConvertQuantityQuery, CreateUnitOfMeasureRequest, ItemUnit,
+    ItemUnitsResponse, NewItemUnit, NewUnitOfMeasure, Quantity, SetItemUnitsRequest, UnitOfMeasure,
+    UnitOfMeasureResponse, UomDimension, UpdateUnitOfMeasureRequest,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::uom::{base_units_per, purchase_factor};
+use crate::utils::AppError;
+
+/// Errors from [`UomService`], so callers can tell a unit that does not fit from a failure
+#[derive(Error, Debug)]
+pub enum UomError {
+    #[error("Invalid unit of measure: {0}")]
+    Invalid(String),
+
+    #[error("Unit of measure already exists: {0}")]
+    Exists(String),
+
+    #[error("Unit of measure cannot be deleted: in use")]
+    InUse,
+
+    #[error("Base unit cannot be changed: item has stock movements")]
+    BaseUnitInUse,
+}
+
+impl From<UomError> for AppError {
+    fn from(error: UomError) -> Self {
+        match error {
+            UomError::Invalid(_) => AppError::Validation(error.to_string()),
+            UomError::Exists(_) | UomError::InUse | UomError::BaseUnitInUse => {
+                AppError::Conflict(error.to_string())
+            }
+        }
+    }
+}
+
+pub struct UomService {
+    database: DatabaseService,
+}
+
+impl UomService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Unit of measure operations
+
+    pub async fn list_units(&self, tenant_id: Uuid) -> Result<Vec<UnitOfMeasureResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let units = units_of_measure::table
+            .filter(units_of_measure::tenant_id.eq(tenant_id))
+            .order((
+                units_of_measure::dimension.asc(),
+                units_of_measure::factor.asc(),
+            ))
+            .select(UnitOfMeasure::as_select())
+            .load::<UnitOfMeasure>(&mut conn)
+            .await?;
+
+        Ok(units.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_unit(
+        &self,
+        tenant_id: Uuid,
+        uom_id: Uuid,
+    ) -> Result<Option<UnitOfMeasureResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_unit(&mut conn, tenant_id, uom_id)
+            .await?
+            .map(Into::into))
+    }
+
+    pub async fn create_unit(
+        &self,
+        tenant_id: Uuid,
+        request: CreateUnitOfMeasureRequest,
+    ) -> Result<UnitOfMeasureResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let code = request.code.trim().to_string();
+        let taken: i64 = units_of_measure::table
+            .filter(units_of_measure::tenant_id.eq(tenant_id))
+            .filter(units_of_measure::code.eq(&code))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if taken > 0 {
+            return Err(UomError::Exists(code).into());
+        }
+
+        let unit: UnitOfMeasure = diesel::insert_into(units_of_measure::table)
+            .values(&NewUnitOfMeasure {
+                tenant_id,
+                code,
+                name: request.name,
+                dimension: request.dimension.to_string(),
+                factor: request.factor,
+            })
+            .returning(UnitOfMeasure::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(unit.into())
+    }
+
+    pub async fn update_unit(
+        &self,
+        tenant_id: Uuid,
+        uom_id: Uuid,
+        request: UpdateUnitOfMeasureRequest,
+    ) -> Result<Option<UnitOfMeasureResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(unit) = Self::find_unit(&mut conn, tenant_id, uom_id).await? else {
+            return Ok(None);
+        };
+
+        let unit: UnitOfMeasure = diesel::update(units_of_measure::table.find(unit.id))
+            .set((
+                units_of_measure::name.eq(request.name.unwrap_or(unit.name)),
+                units_of_measure::factor.eq(request.factor.unwrap_or(unit.factor)),
+            ))
+            .returning(UnitOfMeasure::as_returning())
+            .get_result(&mut conn)
+            .await?;
+
+        Ok(Some(unit.into()))
+    }
+
+    /// Deletes a unit nothing has been recorded in. Returns `false` when there is no such unit.
+    pub async fn delete_unit(&self, tenant_id: Uuid, uom_id: Uuid) -> Result<bool> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        if Self::find_unit(&mut conn, tenant_id, uom_id)
+            .await?
+            .is_none()
+        {
+            return Ok(false);
+        }
+
+        let item_units: i64 = item_units::table
+            .filter(
+                item_units::base_uom_id
+                    .eq(uom_id)
+                    .or(item_units::purchase_uom_id.eq(uom_id)),
+            )
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        let movements: i64 = stock_movements::table
+            .filter(stock_movements::uom_id.eq(uom_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        let bom_lines: i64 = item_bom::table
+            .filter(item_bom::uom_id.eq(uom_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        let order_lines: i64 = order_items::table
+            .filter(order_items::uom_id.eq(uom_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if item_units + movements + bom_lines + order_lines > 0 {
+            return Err(UomError::InUse.into());
+        }
+
+        diesel::delete(units_of_measure::table.find(uom_id))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(true)
+    }
+
+    // Item unit operations
+
+    /// Units of an item, `None` when none have been set.
+    pub async fn get_item_units(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<ItemUnitsResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::item_units(&mut conn, tenant_id, item_id).await
+    }
+
+    /// Sets the base and purchase units of an item. The base unit is fixed once stock of the
+    /// item has moved, since on-hand quantities are counted in it. Returns `None` when the item
+    /// does not exist.
+    pub async fn set_item_units(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        request: SetItemUnitsRequest,
+    ) -> Result<Option<ItemUnitsResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let found: i64 = items::table
+            .filter(items::id.eq(item_id))
+            .count()
+            .get_result(&mut conn)
+            .await?;
+        if found == 0 {
+            return Ok(None);
+        }
+
+        let base: UnitOfMeasureResponse =
+            Self::require_unit(&mut conn, tenant_id, request.base_uom_id)
+                .await?
+                .into();
+        if let Some(purchase_uom_id) = request.purchase_uom_id {
+            let purchase: UnitOfMeasureResponse =
+                Self::require_unit(&mut conn, tenant_id, purchase_uom_id)
+                    .await?
+                    .into();
+            if purchase_factor(&base, &purchase, request.purchase_factor).is_none() {
+                return Err(UomError::Invalid(format!(
+                    "{} ({}) cannot be converted to {} ({}) without a purchase_factor",
+                    purchase.code, purchase.dimension, base.code, base.dimension
+                ))
+                .into());
+            }
+        }
+
+        let existing = item_units::table
+            .filter(item_units::tenant_id.eq(tenant_id))
+            .filter(item_units::item_id.eq(item_id))
+            .select(ItemUnit::as_select())
+            .first::<ItemUnit>(&mut conn)
+            .await
+            .optional()?;
+
+        match existing {
+            Some(existing) => {
+                if existing.base_uom_id != base.id {
+                    let movements: i64 = stock_movements::table
+                        .filter(stock_movements::tenant_id.eq(tenant_id))
+                        .filter(stock_movements::item_id.eq(item_id))
+                        .count()
+                        .get_result(&mut conn)
+                        .await?;
+                    if movements > 0 {
+                        return Err(UomError::BaseUnitInUse.into());
+                    }
+                }
+                diesel::update(item_units::table.find(existing.id))
+                    .set((
+                        item_units::base_uom_id.eq(base.id),
+                        item_units::purchase_uom_id.eq(request.purchase_uom_id),
+                        item_units::purchase_factor.eq(request.purchase_factor),
+                    ))
+                    .execute(&mut conn)
+                    .await?;
+            }
+            None => {
+                diesel::insert_into(item_units::table)
+                    .values(&NewItemUnit {
+                        tenant_id,
+                        item_id,
+                        base_uom_id: base.id,
+                        purchase_uom_id: request.purchase_uom_id,
+                        purchase_factor: request.purchase_factor,
+                    })
+                    .execute(&mut conn)
+                    .await?;
+            }
+        }
+
+        Self::item_units(&mut conn, tenant_id, item_id).await
+    }
+
+    /// Converts a quantity of an item between two of the units it can be given in. Returns
+    /// `None` when the item has no units set.
+    pub async fn convert(
+        &self,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        query: ConvertQuantityQuery,
+    ) -> Result<Option<ConversionResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(units) = Self::item_units(&mut conn, tenant_id, item_id).await? else {
+            return Ok(None);
+        };
+
+        let from_uom: UnitOfMeasureResponse =
+            Self::require_unit(&mut conn, tenant_id, query.from_uom_id)
+                .await?
+                .into();
+        let to_uom: UnitOfMeasureResponse = match query.to_uom_id {
+            Some(to_uom_id) => Self::require_unit(&mut conn, tenant_id, to_uom_id)
+                .await?
+                .into(),
+            None => units.base_uom.clone(),
+        };
+
+        let from_factor = Self::factor(&units, &from_uom)?;
+        let to_factor = Self::factor(&units, &to_uom)?;
+
+        Ok(Some(ConversionResponse {
+            item_id,
+            quantity: query.quantity,
+            from_uom,
+            converted_quantity: query.quantity * from_factor / to_factor,
+            to_uom,
+        }))
+    }
+
+    // Conversions used by stock, BOM and order operations
+
+    /// Units of an item inside the caller's connection, `None` when none have been set.
+    pub async fn item_units(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+    ) -> Result<Option<ItemUnitsResponse>> {
+        let Some(item_unit) = item_units::table
+            .filter(item_units::tenant_id.eq(tenant_id))
+            .filter(item_units::item_id.eq(item_id))
+            .select(ItemUnit::as_select())
+            .first::<ItemUnit>(conn)
+            .await
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let base_uom: UnitOfMeasureResponse =
+            Self::require_unit(conn, tenant_id, item_unit.base_uom_id)
+                .await?
+                .into();
+        let purchase_uom: Option<UnitOfMeasureResponse> = match item_unit.purchase_uom_id {
+            Some(purchase_uom_id) => Some(
+                Self::require_unit(conn, tenant_id, purchase_uom_id)
+                    .await?
+                    .into(),
+            ),
+            None => None,
+        };
+        let purchase_factor = purchase_uom
+            .as_ref()
+            .and_then(|purchase| purchase_factor(&base_uom, purchase, item_unit.purchase_factor));
+
+        Ok(Some(ItemUnitsResponse {
+            item_id,
+            base_uom,
+            purchase_uom,
+            purchase_factor,
+        }))
+    }
+
+    /// Base units of an item in one of `uom_id`, for quantities that need not be whole such as
+    /// BOM requirements.
+    pub async fn base_factor(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        uom_id: Uuid,
+    ) -> Result<f64> {
+        let Some(units) = Self::item_units(conn, tenant_id, item_id).await? else {
+            return Err(UomError::Invalid(format!("item {} has no base unit", item_id)).into());
+        };
+        let unit = Self::require_unit(conn, tenant_id, uom_id).await?.into();
+        Self::factor(&units, &unit)
+    }
+
+    /// `quantity` of an item given in `uom_id` in its base unit, the unit stock is counted in.
+    /// Items counted in a unit of the count dimension only take whole base quantities.
+    pub async fn to_base_quantity(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        uom_id: Uuid,
+        quantity: Quantity,
+    ) -> Result<Quantity> {
+        let Some(units) = Self::item_units(conn, tenant_id, item_id).await? else {
+            return Err(UomError::Invalid(format!("item {} has no base unit", item_id)).into());
+        };
+        let unit: UnitOfMeasureResponse = Self::require_unit(conn, tenant_id, uom_id).await?.into();
+        let base = quantity
+            .scaled(Self::factor(&units, &unit)?)
+            .ok_or_else(|| {
+                UomError::Invalid(format!("{} {} is out of range", quantity, unit.code))
+            })?;
+
+        if units.base_uom.dimension == UomDimension::Count && base.whole().is_none() {
+            return Err(UomError::Invalid(format!(
+                "{} {} is {} {}, not a whole number",
+                quantity, unit.code, base, units.base_uom.code
+            ))
+            .into());
+        }
+        Ok(base)
+    }
+
+    /// Checks a base unit quantity suits the item: fractions are refused for items whose base
+    /// unit counts pieces. Items without units take any quantity.
+    pub async fn check_base_quantity(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        quantity: Quantity,
+    ) -> Result<()> {
+        if quantity.whole().is_some() {
+            return Ok(());
+        }
+        match Self::item_units(conn, tenant_id, item_id).await? {
+            Some(units) if units.base_uom.dimension == UomDimension::Count => {
+                Err(UomError::Invalid(format!(
+                    "{} {} is not a whole number",
+                    quantity, units.base_uom.code
+                ))
+                .into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Unit and base quantity of an order line for an item. Without a unit the line is in the
+    /// item's purchase unit on purchase orders, when it has one, and in its base unit otherwise.
+    pub async fn line_quantity(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        item_id: Uuid,
+        uom_id: Option<Uuid>,
+        quantity: i32,
+        purchase: bool,
+    ) -> Result<(Option<Uuid>, i32)> {
+        let uom_id = match uom_id {
+            Some(uom_id) => Some(uom_id),
+            None if purchase => Self::item_units(conn, tenant_id, item_id)
+                .await?
+                .and_then(|units| units.purchase_uom)
+                .map(|unit| unit.id),
+            None => None,
+        };
+
+        match uom_id {
+            Some(uom_id) => {
+                let base_quantity =
+                    Self::to_base_quantity(conn, tenant_id, item_id, uom_id, quantity.into())
+                        .await?;
+                // Order lines are kept in whole base units
+                let whole = base_quantity.whole().ok_or_else(|| {
+                    UomError::Invalid(format!(
+                        "order line of {} is {} base units, not a whole number",
+                        quantity, base_quantity
+                    ))
+                })?;
+                Ok((Some(uom_id), whole))
+            }
+            None => Ok((None, quantity)),
+        }
+    }
+
+    // Private helper methods
+
+    async fn find_unit(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        uom_id: Uuid,
+    ) -> Result<Option<UnitOfMeasure>> {
+        Ok(units_of_measure::table
+            .filter(units_of_measure::id.eq(uom_id))
+            .filter(units_of_measure::tenant_id.eq(tenant_id))
+            .select(UnitOfMeasure::as_select())
+            .first::<UnitOfMeasure>(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn require_unit(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        uom_id: Uuid,
+    ) -> Result<UnitOfMeasure> {
+        Self::find_unit(conn, tenant_id, uom_id)
+            .await?
+            .ok_or_else(|| UomError::Invalid(format!("{} not found", uom_id)).into())
+    }
+
+    fn factor(units: &ItemUnitsResponse, unit: &UnitOfMeasureResponse) -> Result<f64> {
+        base_units_per(units, unit).ok_or_else(|| {
+            UomError::Invalid(format!(
+                "{} cannot be converted to {}",
+                unit.code, units.base_uom.code
+            ))
+            .into()
+        })
+    }
+}
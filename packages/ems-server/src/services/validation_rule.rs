@@ -0,0 +1,300 @@
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use serde_json::Value;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CallerContext, CreateValidationRuleRequest, NewValidationRule, Permission, RuleEntity,
+    RuleViolations, UpdateValidationRuleRequest, ValidationRule, ValidationRuleResponse,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::validation_rule::{parse_rule_expression, rule_violations, CompiledRule};
+use crate::utils::AppError;
+
+/// Errors from [`ValidationRuleService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum ValidationRuleError {
+    #[error("A validation rule named {0} already exists")]
+    NameTaken(String),
+
+    #[error("Invalid condition: {0}")]
+    InvalidCondition(String),
+
+    #[error("Invalid requirement: {0}")]
+    InvalidRequirement(String),
+}
+
+impl From<ValidationRuleError> for AppError {
+    fn from(error: ValidationRuleError) -> Self {
+        match error {
+            ValidationRuleError::NameTaken(_) => AppError::Conflict(error.to_string()),
+            ValidationRuleError::InvalidCondition(_)
+            | ValidationRuleError::InvalidRequirement(_) => AppError::Validation(error.to_string()),
+        }
+    }
+}
+
+/// A tenant's business rules: expressions orders and items are checked against when they are
+/// created or updated, on top of the checks every tenant gets.
+pub struct ValidationRuleService {
+    database: DatabaseService,
+}
+
+impl ValidationRuleService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    // Rule enforcement
+
+    /// Checks the record against the tenant's enabled rules for its entity, failing with
+    /// [`RuleViolations`] listing every rule it breaks.
+    pub async fn enforce(&self, tenant_id: Uuid, entity: RuleEntity, record: &Value) -> Result<()> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::enforce_in(&mut conn, tenant_id, entity, record).await
+    }
+
+    /// [`Self::enforce`] on a connection the caller holds, so a record read and locked in a
+    /// transaction is checked before the same transaction writes it.
+    pub async fn enforce_in(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity: RuleEntity,
+        record: &Value,
+    ) -> Result<()> {
+        let rules: Vec<ValidationRule> = validation_rules::table
+            .filter(validation_rules::tenant_id.eq(tenant_id))
+            .filter(validation_rules::entity.eq(entity.to_string()))
+            .filter(validation_rules::enabled.eq(true))
+            .order(validation_rules::name.asc())
+            .select(ValidationRule::as_select())
+            .load(conn)
+            .await?;
+
+        // Expressions are checked when saved, so a rule failing to compile is skipped rather
+        // than blocking every write
+        let rules: Vec<CompiledRule> = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let id = rule.id;
+                CompiledRule::compile(rule)
+                    .map_err(|e| tracing::warn!("Skipping validation rule {}: {}", id, e))
+                    .ok()
+            })
+            .collect();
+
+        let violations = rule_violations(&rules, record);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(RuleViolations { violations }.into())
+        }
+    }
+
+    // Rule management
+
+    pub async fn list_rules(
+        &self,
+        tenant_id: Uuid,
+        entity: Option<RuleEntity>,
+    ) -> Result<Vec<ValidationRuleResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut query = validation_rules::table
+            .filter(validation_rules::tenant_id.eq(tenant_id))
+            .into_boxed();
+        if let Some(entity) = entity {
+            query = query.filter(validation_rules::entity.eq(entity.to_string()));
+        }
+
+        let rules: Vec<ValidationRule> = query
+            .order((validation_rules::entity.asc(), validation_rules::name.asc()))
+            .select(ValidationRule::as_select())
+            .load(&mut conn)
+            .await?;
+
+        Ok(rules.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn get_rule(
+        &self,
+        tenant_id: Uuid,
+        rule_id: Uuid,
+    ) -> Result<Option<ValidationRuleResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Ok(Self::find_rule(&mut conn, tenant_id, rule_id)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Adds a rule. Its expressions must parse and refer only to fields of its entity.
+    pub async fn create_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        request: CreateValidationRuleRequest,
+    ) -> Result<ValidationRuleResponse> {
+        caller.require(Permission::ManageValidationRules)?;
+        check_expressions(
+            request.entity,
+            request.condition.as_deref(),
+            &request.requirement,
+        )?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::check_name_free(&mut conn, tenant_id, &request.name, None).await?;
+
+        let rule = diesel::insert_into(validation_rules::table)
+            .values(&NewValidationRule {
+                tenant_id,
+                name: request.name,
+                entity: request.entity.to_string(),
+                condition: request.condition,
+                requirement: request.requirement,
+                message: request.message,
+                enabled: request.enabled.unwrap_or(true),
+                created_by_id: Some(caller.person_id),
+            })
+            .returning(ValidationRule::as_returning())
+            .get_result::<ValidationRule>(&mut conn)
+            .await?;
+
+        Ok(rule.into())
+    }
+
+    /// Replaces a rule, keeping the entity it applies to. `Ok(None)` when there is no such rule.
+    pub async fn update_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        rule_id: Uuid,
+        request: UpdateValidationRuleRequest,
+    ) -> Result<Option<ValidationRuleResponse>> {
+        caller.require(Permission::ManageValidationRules)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let Some(rule) = Self::find_rule(&mut conn, tenant_id, rule_id).await? else {
+            return Ok(None);
+        };
+        let entity = RuleEntity::try_from(rule.entity).map_err(|e| anyhow!(e))?;
+        check_expressions(entity, request.condition.as_deref(), &request.requirement)?;
+        Self::check_name_free(&mut conn, tenant_id, &request.name, Some(rule_id)).await?;
+
+        let rule = diesel::update(
+            validation_rules::table
+                .filter(validation_rules::id.eq(rule_id))
+                .filter(validation_rules::tenant_id.eq(tenant_id)),
+        )
+        .set((
+            validation_rules::name.eq(request.name),
+            validation_rules::condition.eq(request.condition),
+            validation_rules::requirement.eq(request.requirement),
+            validation_rules::message.eq(request.message),
+            validation_rules::enabled.eq(request.enabled),
+        ))
+        .returning(ValidationRule::as_returning())
+        .get_result::<ValidationRule>(&mut conn)
+        .await?;
+
+        Ok(Some(rule.into()))
+    }
+
+    /// Removes a rule. `Ok(false)` when there is no such rule.
+    pub async fn delete_rule(
+        &self,
+        tenant_id: Uuid,
+        caller: &CallerContext,
+        rule_id: Uuid,
+    ) -> Result<bool> {
+        caller.require(Permission::ManageValidationRules)?;
+
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let deleted = diesel::delete(
+            validation_rules::table
+                .filter(validation_rules::id.eq(rule_id))
+                .filter(validation_rules::tenant_id.eq(tenant_id)),
+        )
+        .execute(&mut conn)
+        .await?;
+
+        Ok(deleted > 0)
+    }
+
+    // Helper methods
+
+    async fn find_rule(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        rule_id: Uuid,
+    ) -> Result<Option<ValidationRule>> {
+        Ok(validation_rules::table
+            .filter(validation_rules::id.eq(rule_id))
+            .filter(validation_rules::tenant_id.eq(tenant_id))
+            .select(ValidationRule::as_select())
+            .first(conn)
+            .await
+            .optional()?)
+    }
+
+    async fn check_name_free(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        name: &str,
+        except: Option<Uuid>,
+    ) -> Result<()> {
+        let taken: Option<Uuid> = validation_rules::table
+            .filter(validation_rules::tenant_id.eq(tenant_id))
+            .filter(validation_rules::name.eq(name))
+            .select(validation_rules::id)
+            .first(conn)
+            .await
+            .optional()?;
+        match taken {
+            Some(id) if Some(id) != except => {
+                Err(ValidationRuleError::NameTaken(name.to_string()).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Both expressions of a rule must parse and refer only to fields of its entity
+fn check_expressions(entity: RuleEntity, condition: Option<&str>, requirement: &str) -> Result<()> {
+    if let Some(condition) = condition {
+        parse_rule_expression(condition, entity).map_err(ValidationRuleError::InvalidCondition)?;
+    }
+    parse_rule_expression(requirement, entity).map_err(ValidationRuleError::InvalidRequirement)?;
+    Ok(())
+}
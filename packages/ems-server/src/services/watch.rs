@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl, SimpleAsyncConnection};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    CommentResponse, CreateCommentRequest, EntityComment, ListWatchesQuery, NewEntityComment,
+    NewWatch, Watch, WatchEntityType, WatchResponse, WatchedEntityResponse,
+};
+use crate::schema::*;
+use crate::services::DatabaseService;
+use crate::utils::AppError;
+
+/// Errors from [`WatchService`] that are the caller's to fix, as opposed to failures
+#[derive(Error, Debug)]
+pub enum WatchError {
+    #[error("{0} not found")]
+    EntityNotFound(String),
+}
+
+impl From<WatchError> for AppError {
+    fn from(error: WatchError) -> Self {
+        match error {
+            WatchError::EntityNotFound(_) => AppError::NotFound(error.to_string()),
+        }
+    }
+}
+
+/// Watches and comments on machines, orders and items. Watchers are notified of status
+/// changes, low stock and comments by database triggers writing to their notifications.
+pub struct WatchService {
+    database: DatabaseService,
+}
+
+impl WatchService {
+    pub fn new(database: DatabaseService) -> Self {
+        Self { database }
+    }
+
+    /// Starts or stops the person watching a record.
+    pub async fn set_watching(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+        watching: bool,
+    ) -> Result<WatchResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::ensure_entity(&mut conn, tenant_id, entity_type, entity_id).await?;
+
+        if watching {
+            diesel::insert_into(watches::table)
+                .values(&NewWatch {
+                    tenant_id,
+                    person_id,
+                    entity_type: entity_type.to_string(),
+                    entity_id,
+                })
+                .on_conflict_do_nothing()
+                .execute(&mut conn)
+                .await?;
+        } else {
+            diesel::delete(
+                watches::table
+                    .filter(watches::tenant_id.eq(tenant_id))
+                    .filter(watches::person_id.eq(person_id))
+                    .filter(watches::entity_type.eq(entity_type.to_string()))
+                    .filter(watches::entity_id.eq(entity_id)),
+            )
+            .execute(&mut conn)
+            .await?;
+        }
+
+        Self::watch_status(&mut conn, tenant_id, person_id, entity_type, entity_id).await
+    }
+
+    /// Watches a record the person is not watching, and stops watching one they are.
+    pub async fn toggle_watching(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+    ) -> Result<WatchResponse> {
+        let current = self
+            .get_watch_status(tenant_id, person_id, entity_type, entity_id)
+            .await?;
+        self.set_watching(
+            tenant_id,
+            person_id,
+            entity_type,
+            entity_id,
+            !current.watching,
+        )
+        .await
+    }
+
+    pub async fn get_watch_status(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+    ) -> Result<WatchResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::ensure_entity(&mut conn, tenant_id, entity_type, entity_id).await?;
+        Self::watch_status(&mut conn, tenant_id, person_id, entity_type, entity_id).await
+    }
+
+    /// Records the person watches, newest first.
+    pub async fn list_watches(
+        &self,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        query: ListWatchesQuery,
+    ) -> Result<Vec<WatchedEntityResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        let mut watched = watches::table
+            .filter(watches::tenant_id.eq(tenant_id))
+            .filter(watches::person_id.eq(person_id))
+            .into_boxed();
+        if let Some(entity_type) = query.entity_type {
+            watched = watched.filter(watches::entity_type.eq(entity_type.to_string()));
+        }
+
+        watched
+            .order(watches::created_at.desc())
+            .select(Watch::as_select())
+            .load::<Watch>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|watch| {
+                Ok(WatchedEntityResponse {
+                    id: watch.id,
+                    entity_type: WatchEntityType::try_from(watch.entity_type)
+                        .map_err(|e| anyhow!(e))?,
+                    entity_id: watch.entity_id,
+                    created_at: watch.created_at.unwrap_or_else(chrono::Utc::now),
+                })
+            })
+            .collect()
+    }
+
+    /// Comments on a record, oldest first.
+    pub async fn list_comments(
+        &self,
+        tenant_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+    ) -> Result<Vec<CommentResponse>> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::ensure_entity(&mut conn, tenant_id, entity_type, entity_id).await?;
+
+        entity_comments::table
+            .filter(entity_comments::tenant_id.eq(tenant_id))
+            .filter(entity_comments::entity_type.eq(entity_type.to_string()))
+            .filter(entity_comments::entity_id.eq(entity_id))
+            .order(entity_comments::created_at.asc())
+            .select(EntityComment::as_select())
+            .load::<EntityComment>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|comment| CommentResponse::try_from(comment).map_err(|e| anyhow!(e)))
+            .collect()
+    }
+
+    /// Comments on a record; everyone else watching it is notified.
+    pub async fn add_comment(
+        &self,
+        tenant_id: Uuid,
+        author_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+        request: CreateCommentRequest,
+    ) -> Result<CommentResponse> {
+        let mut conn = self.database.get_connection().await?;
+
+        // Set tenant context for RLS
+        conn.batch_execute(&format!("SET app.current_tenant_id = '{}'", tenant_id))
+            .await?;
+
+        Self::ensure_entity(&mut conn, tenant_id, entity_type, entity_id).await?;
+
+        let comment = diesel::insert_into(entity_comments::table)
+            .values(&NewEntityComment {
+                tenant_id,
+                entity_type: entity_type.to_string(),
+                entity_id,
+                author_id: Some(author_id),
+                body: request.body.trim().to_string(),
+            })
+            .returning(EntityComment::as_returning())
+            .get_result::<EntityComment>(&mut conn)
+            .await?;
+
+        CommentResponse::try_from(comment).map_err(|e| anyhow!(e))
+    }
+
+    // Private helper methods
+
+    async fn watch_status(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        person_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+    ) -> Result<WatchResponse> {
+        let watchers: Vec<Uuid> = watches::table
+            .filter(watches::tenant_id.eq(tenant_id))
+            .filter(watches::entity_type.eq(entity_type.to_string()))
+            .filter(watches::entity_id.eq(entity_id))
+            .select(watches::person_id)
+            .load(conn)
+            .await?;
+
+        Ok(WatchResponse {
+            entity_type,
+            entity_id,
+            watching: watchers.contains(&person_id),
+            watcher_count: watchers.len() as i64,
+        })
+    }
+
+    // Fails with "not found" unless the record exists in the tenant; items are shared, so
+    // an item belongs to the tenant once it has an inventory record there
+    async fn ensure_entity(
+        conn: &mut AsyncPgConnection,
+        tenant_id: Uuid,
+        entity_type: WatchEntityType,
+        entity_id: Uuid,
+    ) -> Result<()> {
+        let found = match entity_type {
+            WatchEntityType::Machine => {
+                diesel::select(diesel::dsl::exists(
+                    machines::table
+                        .filter(machines::id.eq(entity_id))
+                        .filter(machines::tenant_id.eq(tenant_id)),
+                ))
+                .get_result::<bool>(conn)
+                .await?
+            }
+            WatchEntityType::Order => {
+                diesel::select(diesel::dsl::exists(
+                    orders::table
+                        .filter(orders::id.eq(entity_id))
+                        .filter(orders::tenant_id.eq(tenant_id)),
+                ))
+                .get_result::<bool>(conn)
+                .await?
+            }
+            WatchEntityType::Item => {
+                diesel::select(diesel::dsl::exists(
+                    inventory_items::table
+                        .filter(inventory_items::item_id.eq(entity_id))
+                        .filter(inventory_items::tenant_id.eq(tenant_id)),
+                ))
+                .get_result::<bool>(conn)
+                .await?
+            }
+        };
+
+        if found {
+            Ok(())
+        } else {
+            Err(WatchError::EntityNotFound(entity_type.to_string()).into())
+        }
+    }
+}
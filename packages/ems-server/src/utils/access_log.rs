@@ -0,0 +1,29 @@
+// Access log helpers: how long a tenant's API access log is kept
+
+use serde_json::Value;
+
+/// Tenant setting giving the days API access log entries are kept; 0 keeps them indefinitely.
+pub const ACCESS_LOG_RETENTION_DAYS_SETTING: &str = "access_log_retention_days";
+
+/// Days the tenant's access log entries are kept: the tenant setting, or `default_days` when it
+/// is not set. `None` when they are kept indefinitely.
+pub fn access_log_retention_days(settings: Option<&Value>, default_days: i64) -> Option<i64> {
+    let days = settings
+        .and_then(|settings| settings.get(ACCESS_LOG_RETENTION_DAYS_SETTING))
+        .and_then(Value::as_i64)
+        .unwrap_or(default_days);
+    (days > 0).then_some(days)
+}
+
+/// Checks the access log retention in tenant settings, which may be left out but must be a whole
+/// number of days when given.
+pub fn check_access_log_settings(settings: Option<&Value>) -> Result<(), String> {
+    match settings.and_then(|settings| settings.get(ACCESS_LOG_RETENTION_DAYS_SETTING)) {
+        None | Some(Value::Null) => Ok(()),
+        Some(days) if days.as_i64().is_some_and(|days| days >= 0) => Ok(()),
+        Some(days) => Err(format!(
+            "{} must be a whole number of days, got {}",
+            ACCESS_LOG_RETENTION_DAYS_SETTING, days
+        )),
+    }
+}
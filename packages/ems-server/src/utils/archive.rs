@@ -0,0 +1,30 @@
+// Archive helpers: how long closed jobs and orders stay in the default lists
+
+use serde_json::Value;
+
+/// Tenant setting giving the days after closing that jobs and orders are archived; 0 turns
+/// automatic archiving off for the tenant.
+pub const ARCHIVE_AFTER_DAYS_SETTING: &str = "archive_after_days";
+
+/// Days after closing that the tenant's jobs and orders are archived: the tenant setting, or
+/// `default_days` when it is not set. `None` when automatic archiving is off.
+pub fn archive_after_days(settings: Option<&Value>, default_days: i64) -> Option<i64> {
+    let days = settings
+        .and_then(|settings| settings.get(ARCHIVE_AFTER_DAYS_SETTING))
+        .and_then(Value::as_i64)
+        .unwrap_or(default_days);
+    (days > 0).then_some(days)
+}
+
+/// Checks the archive age in tenant settings, which may be left out but must be a whole number
+/// of days when given.
+pub fn check_archive_settings(settings: Option<&Value>) -> Result<(), String> {
+    match settings.and_then(|settings| settings.get(ARCHIVE_AFTER_DAYS_SETTING)) {
+        None | Some(Value::Null) => Ok(()),
+        Some(days) if days.as_i64().is_some_and(|days| days >= 0) => Ok(()),
+        Some(days) => Err(format!(
+            "{} must be a whole number of days, got {}",
+            ARCHIVE_AFTER_DAYS_SETTING, days
+        )),
+    }
+}
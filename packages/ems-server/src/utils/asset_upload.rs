@@ -0,0 +1,108 @@
+// Resumable asset upload helpers
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+/// Every chunk of an upload but the last is exactly this many bytes
+pub const UPLOAD_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+
+/// Largest file accepted through a resumable upload
+pub const MAX_UPLOAD_BYTES: i64 = 5 * 1024 * 1024 * 1024;
+
+/// Uploads still unfinished this many hours after they were started take no more chunks
+pub const UPLOAD_TTL_HOURS: i64 = 24;
+
+/// Number of chunks a file of `size_bytes` is sent in.
+pub fn chunk_count(size_bytes: i64, chunk_size: i32) -> u32 {
+    let chunk_size = chunk_size.max(1) as i64;
+    ((size_bytes.max(0) + chunk_size - 1) / chunk_size) as u32
+}
+
+/// Size chunk `index` must have, `None` past the last chunk.
+pub fn expected_chunk_size(size_bytes: i64, chunk_size: i32, index: u32) -> Option<usize> {
+    if index >= chunk_count(size_bytes, chunk_size) {
+        return None;
+    }
+    let start = index as i64 * chunk_size as i64;
+    Some((size_bytes - start).min(chunk_size as i64) as usize)
+}
+
+/// Lower-case hex SHA-256 of `content`.
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Lower-case form of a SHA-256 given in hex, as files are looked up by it.
+pub fn parse_sha256(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid checksum: expected a SHA-256 in hex".to_string());
+    }
+    Ok(value.to_ascii_lowercase())
+}
+
+/// Checks a received chunk against the upload's layout and the SHA-256 (hex) the client sent
+/// with it, returning the chunk's checksum.
+pub fn verify_chunk(
+    size_bytes: i64,
+    chunk_size: i32,
+    index: u32,
+    content: &[u8],
+    checksum: Option<&str>,
+) -> Result<String, String> {
+    let expected = expected_chunk_size(size_bytes, chunk_size, index).ok_or_else(|| {
+        format!(
+            "Invalid chunk: index {} is past the last chunk ({})",
+            index,
+            chunk_count(size_bytes, chunk_size).saturating_sub(1)
+        )
+    })?;
+    if content.len() != expected {
+        return Err(format!(
+            "Invalid chunk: chunk {} must be {} bytes, got {}",
+            index,
+            expected,
+            content.len()
+        ));
+    }
+    let checksum = checksum
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| "Invalid chunk: the X-Chunk-SHA256 header is required".to_string())?;
+    let actual = sha256_hex(content);
+    if !actual.eq_ignore_ascii_case(checksum) {
+        return Err(format!(
+            "Invalid chunk: checksum mismatch for chunk {}",
+            index
+        ));
+    }
+    Ok(actual)
+}
+
+/// Storage path chunk `index` of an upload is kept at until the file is assembled.
+pub fn chunk_path(tenant_id: Uuid, upload_id: Uuid, index: u32) -> String {
+    format!("uploads/{}/{}/{:06}", tenant_id, upload_id, index)
+}
+
+/// Storage path of an upload's assembled file, keeping a readable form of its name.
+pub fn upload_storage_path(
+    tenant_id: Uuid,
+    asset_id: Uuid,
+    upload_id: Uuid,
+    file_name: &str,
+) -> String {
+    let name: String = file_name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(100)
+        .collect();
+    let name = name.trim_start_matches('.');
+    let name = if name.is_empty() { "file" } else { name };
+    format!("{}/{}/{}/{}", tenant_id, asset_id, upload_id, name)
+}
@@ -0,0 +1,71 @@
+// Available-to-promise helpers over time-phased supply and demand
+use chrono::{DateTime, Utc};
+
+use crate::models::AtpPeriod;
+
+/// Groups signed quantities by date (positive for expected receipts, negative for demand) and
+/// projects the balance from `opening`. Each period's `available_to_promise` is the lowest
+/// balance from that period on, so a promise never takes stock a later commitment relies on.
+pub fn atp_schedule(opening: i32, mut events: Vec<(DateTime<Utc>, i32)>) -> Vec<AtpPeriod> {
+    events.sort_by_key(|(date, _)| *date);
+
+    let mut periods: Vec<AtpPeriod> = Vec::new();
+    let mut balance = opening;
+    for (date, quantity) in events {
+        balance += quantity;
+        let period = match periods.last_mut() {
+            Some(last) if last.date == date => last,
+            _ => {
+                periods.push(AtpPeriod {
+                    date,
+                    supply: 0,
+                    demand: 0,
+                    projected: balance,
+                    available_to_promise: balance,
+                });
+                periods.last_mut().unwrap()
+            }
+        };
+        if quantity > 0 {
+            period.supply += quantity;
+        } else {
+            period.demand -= quantity;
+        }
+        period.projected = balance;
+    }
+
+    let mut lowest = i32::MAX;
+    for period in periods.iter_mut().rev() {
+        lowest = lowest.min(period.projected);
+        period.available_to_promise = lowest;
+    }
+
+    periods
+}
+
+/// Quantity that can be promised for delivery on `date`.
+pub fn available_on(opening: i32, periods: &[AtpPeriod], date: DateTime<Utc>) -> i32 {
+    match periods.iter().rposition(|p| p.date <= date) {
+        Some(index) => periods[index].available_to_promise,
+        None => periods
+            .first()
+            .map_or(opening, |p| opening.min(p.available_to_promise)),
+    }
+}
+
+/// Earliest date on or after `requested` by which `quantity` can be promised, or `None` when
+/// even the last expected receipt leaves too little.
+pub fn promise_date(
+    opening: i32,
+    periods: &[AtpPeriod],
+    quantity: i32,
+    requested: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if available_on(opening, periods, requested) >= quantity {
+        return Some(requested);
+    }
+    periods
+        .iter()
+        .find(|p| p.date > requested && p.available_to_promise >= quantity)
+        .map(|p| p.date)
+}
@@ -1,4 +1,8 @@
 use anyhow::Result;
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
@@ -7,7 +11,10 @@ use sha2::{Digest, Sha256};
 use std::env;
 use uuid::Uuid;
 
-use crate::models::{AuthTenant, AuthUser, Claims, PersonRole};
+use crate::models::{AuthTenant, AuthUser, Claims, ClientFingerprint, PersonRole};
+use crate::services::AuthError;
+use crate::utils::fingerprint::ClientInfo;
+use crate::utils::service_scope::SERVICE_ROLE;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SupabaseUser {
@@ -36,6 +43,24 @@ impl AuthUtils {
         verify(password, hash).map_err(|e| anyhow::anyhow!("Failed to verify password: {}", e))
     }
 
+    /// Hash a password with argon2 for the local auth backend
+    pub fn hash_local_password(password: &str) -> Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| anyhow::anyhow!("Failed to hash password: {}", e))
+    }
+
+    /// Verify a password against an argon2 hash
+    pub fn verify_local_password(password: &str, hash: &str) -> Result<bool> {
+        let parsed = PasswordHash::new(hash)
+            .map_err(|e| anyhow::anyhow!("Failed to verify password: {}", e))?;
+        Ok(Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok())
+    }
+
     /// Hash a token for storage in blacklist (using SHA256)
     pub fn hash_token(token: &str) -> String {
         let mut hasher = Sha256::new();
@@ -59,6 +84,9 @@ impl AuthUtils {
             role: role.to_string(),
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            fp: None,
+            azp: None,
+            scope: None,
         };
 
         encode(
@@ -69,8 +97,18 @@ impl AuthUtils {
         .map_err(|e| anyhow::anyhow!("Failed to generate access token: {}", e))
     }
 
-    /// Generate JWT refresh token
-    pub fn generate_refresh_token(user_id: Uuid, tenant_id: Uuid) -> Result<String> {
+    /// Fingerprint of `client` for binding refresh tokens, keyed with the JWT secret
+    pub fn client_fingerprint(client: &ClientInfo) -> ClientFingerprint {
+        let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
+        client.fingerprint(&secret)
+    }
+
+    /// Generate JWT refresh token, bound to the client it is issued to when a fingerprint is given
+    pub fn generate_refresh_token(
+        user_id: Uuid,
+        tenant_id: Uuid,
+        fingerprint: Option<ClientFingerprint>,
+    ) -> Result<String> {
         let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
         let now = Utc::now();
         let exp = now + Duration::days(30); // 30 days expiration
@@ -81,6 +119,9 @@ impl AuthUtils {
             role: "refresh".to_string(),
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            fp: fingerprint,
+            azp: None,
+            scope: None,
         };
 
         encode(
@@ -91,6 +132,37 @@ impl AuthUtils {
         .map_err(|e| anyhow::anyhow!("Failed to generate refresh token: {}", e))
     }
 
+    /// Generate a machine token for a service client, acting for its owner with `scopes`
+    pub fn generate_service_token(
+        client_id: Uuid,
+        owner_id: Uuid,
+        tenant_id: Uuid,
+        scopes: &[String],
+        ttl_seconds: i64,
+    ) -> Result<String> {
+        let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
+        let now = Utc::now();
+        let exp = now + Duration::seconds(ttl_seconds);
+
+        let claims = Claims {
+            sub: owner_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            role: SERVICE_ROLE.to_string(),
+            exp: exp.timestamp() as usize,
+            iat: now.timestamp() as usize,
+            fp: None,
+            azp: Some(client_id.to_string()),
+            scope: Some(scopes.join(" ")),
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_ref()),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to generate service token: {}", e))
+    }
+
     /// Generate temporary JWT access token without tenant context (for user-only registration)
     pub fn generate_temporary_access_token(user_id: Uuid) -> Result<String> {
         let secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
@@ -103,6 +175,9 @@ impl AuthUtils {
             role: "pending".to_string(), // Special role for users without tenants
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            fp: None,
+            azp: None,
+            scope: None,
         };
 
         encode(
@@ -125,6 +200,9 @@ impl AuthUtils {
             role: "pending".to_string(), // Special role for users without tenants
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            fp: None,
+            azp: None,
+            scope: None,
         };
 
         encode(
@@ -144,7 +222,7 @@ impl AuthUtils {
             &DecodingKey::from_secret(secret.as_ref()),
             &Validation::default(),
         )
-        .map_err(|e| anyhow::anyhow!("Failed to validate token: {}", e))
+        .map_err(|e| AuthError::InvalidToken(e.to_string()).into())
     }
 
     /// Verify JWT token (alias for validate_token for compatibility)
@@ -216,7 +294,7 @@ impl AuthUtils {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Authentication failed: {}", error_text))
+            Err(AuthError::AuthenticationFailed(error_text).into())
         }
     }
 
@@ -263,7 +341,7 @@ impl AuthUtils {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Registration failed: {}", error_text))
+            Err(AuthError::RegistrationFailed(error_text).into())
         }
     }
 
@@ -0,0 +1,323 @@
+// Batch record assembly and document helpers
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{BatchMaterial, BatchRecordResponse, Quantity};
+use crate::utils::pdf::{Font, PdfDocument, PAGE_HEIGHT};
+
+// Page margin of the batch record document in points
+const MARGIN: f64 = 50.0;
+
+/// Merges the net quantities issued to the job from the stock ledger with the job's own
+/// `materials_consumed` entries. The ledger quantity wins where both exist; entries contribute
+/// their `lot_number` and `serial_numbers` (or `serial_number`). Entries without a valid
+/// `item_id` are skipped.
+pub fn consumed_materials(
+    issued: &[(Uuid, Quantity)],
+    materials_consumed: Option<&serde_json::Value>,
+) -> Vec<BatchMaterial> {
+    let mut materials: Vec<BatchMaterial> = issued
+        .iter()
+        .map(|(item_id, quantity)| BatchMaterial {
+            item_id: *item_id,
+            part_number: None,
+            quantity: *quantity,
+            lot_numbers: Vec::new(),
+            serial_numbers: Vec::new(),
+            substitute_for: None,
+        })
+        .collect();
+    let from_ledger = materials.len();
+
+    let entries = materials_consumed
+        .and_then(|value| value.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for entry in entries {
+        let Some(item_id) = entry
+            .get("item_id")
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+        else {
+            continue;
+        };
+        let index = match materials.iter().position(|m| m.item_id == item_id) {
+            Some(index) => index,
+            None => {
+                materials.push(BatchMaterial {
+                    item_id,
+                    part_number: None,
+                    quantity: Quantity::ZERO,
+                    lot_numbers: Vec::new(),
+                    serial_numbers: Vec::new(),
+                    substitute_for: None,
+                });
+                materials.len() - 1
+            }
+        };
+        let material = &mut materials[index];
+
+        if index >= from_ledger {
+            material.quantity += entry
+                .get("quantity")
+                .and_then(|v| Quantity::deserialize(v).ok())
+                .unwrap_or_default();
+        }
+        if let Some(lot) = entry.get("lot_number").and_then(|v| v.as_str()) {
+            push_unique(&mut material.lot_numbers, lot);
+        }
+        if let Some(serial) = entry.get("serial_number").and_then(|v| v.as_str()) {
+            push_unique(&mut material.serial_numbers, serial);
+        }
+        for serial in entry
+            .get("serial_numbers")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+        {
+            push_unique(&mut material.serial_numbers, serial);
+        }
+    }
+
+    materials
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !value.is_empty() && !values.iter().any(|v| v == value) {
+        values.push(value.to_string());
+    }
+}
+
+/// Renders the batch record for signing and archiving: one section per part of the record,
+/// continued on further pages as needed.
+pub fn render_batch_record_pdf(record: &BatchRecordResponse) -> Vec<u8> {
+    let mut page = Page::new();
+    let content = &record.content;
+
+    page.write(
+        Font::Bold,
+        18.0,
+        &format!("Batch record {}", record.batch_number),
+    );
+    page.space(8.0);
+    if let Some(part_number) = &content.product.part_number {
+        page.write(Font::Regular, 10.0, &format!("Product: {}", part_number));
+    }
+    if let Some(description) = &content.product.description {
+        page.write(Font::Regular, 10.0, description);
+    }
+    page.write(
+        Font::Regular,
+        10.0,
+        &format!("Quantity: {}", record.quantity),
+    );
+    if let Some(work_order) = &content.product.work_order_number {
+        page.write(Font::Regular, 10.0, &format!("Work order: {}", work_order));
+    }
+    if let Some(line) = &content.product.production_line {
+        page.write(Font::Regular, 10.0, &format!("Production line: {}", line));
+    }
+    page.write(
+        Font::Regular,
+        10.0,
+        &format!(
+            "Started: {}  Completed: {}",
+            timestamp(content.product.started_at),
+            timestamp(Some(record.completed_at))
+        ),
+    );
+
+    page.heading("Materials");
+    for material in &content.materials {
+        let mut line = format!(
+            "{} x {}",
+            material.quantity,
+            material
+                .part_number
+                .clone()
+                .unwrap_or_else(|| material.item_id.to_string())
+        );
+        if !material.lot_numbers.is_empty() {
+            line.push_str(&format!("  lot {}", material.lot_numbers.join(", ")));
+        }
+        if material.substitute_for.is_some() {
+            line.push_str("  (substitute)");
+        }
+        page.write(Font::Regular, 10.0, &line);
+        if !material.serial_numbers.is_empty() {
+            page.write(
+                Font::Regular,
+                8.0,
+                &format!("    S/N {}", material.serial_numbers.join(", ")),
+            );
+        }
+    }
+
+    page.heading("Machines");
+    for machine in &content.machines {
+        page.write(
+            Font::Regular,
+            10.0,
+            &format!(
+                "{}  {} to {}",
+                machine.name,
+                timestamp(machine.started_at),
+                timestamp(machine.ended_at)
+            ),
+        );
+        for firmware in &machine.firmware {
+            page.write(
+                Font::Regular,
+                8.0,
+                &format!(
+                    "    Firmware {} {}{}",
+                    firmware.name,
+                    firmware.version.as_deref().unwrap_or("(unversioned)"),
+                    firmware
+                        .checksum
+                        .as_ref()
+                        .map(|c| format!("  checksum {}", c))
+                        .unwrap_or_default()
+                ),
+            );
+        }
+    }
+
+    page.heading("Operators");
+    for operator in &content.operators {
+        page.write(
+            Font::Regular,
+            10.0,
+            &format!("{} ({})", operator.name, operator.role),
+        );
+    }
+
+    page.heading("Inspections");
+    if content.inspections.is_empty() {
+        page.write(Font::Regular, 10.0, "None recorded");
+    }
+    for inspection in &content.inspections {
+        page.write(
+            Font::Regular,
+            10.0,
+            &format!(
+                "{}  {}  {}  {}",
+                inspection.job_number,
+                inspection
+                    .inspection_type
+                    .as_deref()
+                    .unwrap_or("inspection"),
+                inspection.status,
+                timestamp(inspection.completed_at)
+            ),
+        );
+    }
+
+    page.pdf.render()
+}
+
+/// Renders the inspections of a batch for the customer, without the production details of the
+/// full batch record.
+pub fn render_inspection_report_pdf(record: &BatchRecordResponse) -> Vec<u8> {
+    let mut page = Page::new();
+    let content = &record.content;
+
+    page.write(
+        Font::Bold,
+        18.0,
+        &format!("Inspection report {}", record.batch_number),
+    );
+    page.space(8.0);
+    if let Some(part_number) = &content.product.part_number {
+        page.write(Font::Regular, 10.0, &format!("Product: {}", part_number));
+    }
+    page.write(
+        Font::Regular,
+        10.0,
+        &format!(
+            "Quantity: {}  Completed: {}",
+            record.quantity,
+            timestamp(Some(record.completed_at))
+        ),
+    );
+
+    for inspection in &content.inspections {
+        page.heading(&format!(
+            "{} {}",
+            inspection
+                .inspection_type
+                .as_deref()
+                .unwrap_or("Inspection"),
+            inspection.job_number
+        ));
+        page.write(
+            Font::Regular,
+            10.0,
+            &format!(
+                "Result: {}  Completed: {}",
+                inspection.status,
+                timestamp(inspection.completed_at)
+            ),
+        );
+        if let Some(procedure) = &inspection.test_procedure_id {
+            page.write(Font::Regular, 10.0, &format!("Procedure: {}", procedure));
+        }
+        if let Some(sampling_size) = inspection.sampling_size {
+            page.write(
+                Font::Regular,
+                10.0,
+                &format!("Sample size: {}", sampling_size),
+            );
+        }
+        if let Some(criteria) = &inspection.acceptance_criteria {
+            page.write(
+                Font::Regular,
+                10.0,
+                &format!("Acceptance criteria: {}", criteria),
+            );
+        }
+    }
+
+    page.pdf.render()
+}
+
+fn timestamp(value: Option<DateTime<Utc>>) -> String {
+    value
+        .map(|v| v.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+// Writes lines top to bottom, starting a new page when the current one is full
+struct Page {
+    pdf: PdfDocument,
+    y: f64,
+}
+
+impl Page {
+    fn new() -> Self {
+        Self {
+            pdf: PdfDocument::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+
+    fn write(&mut self, font: Font, size: f64, text: &str) {
+        if self.y - size < MARGIN {
+            self.pdf.add_page();
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.pdf.text(MARGIN, self.y, font, size, text);
+        self.y -= size + 4.0;
+    }
+
+    fn space(&mut self, height: f64) {
+        self.y -= height;
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.space(14.0);
+        self.write(Font::Bold, 12.0, text);
+    }
+}
@@ -0,0 +1,119 @@
+// Billing helpers: Stripe webhook signatures and subscription state
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::models::{StripeSubscription, DEFAULT_PLAN};
+
+/// Signed webhooks older (or newer) than this many seconds are refused, so a captured delivery
+/// cannot be replayed later
+pub const SIGNATURE_TOLERANCE_SECONDS: i64 = 300;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn signature_mac(payload: &[u8], secret: &str, timestamp: i64) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// `Stripe-Signature` header value for `payload` signed at `timestamp`, as Stripe sends it.
+pub fn stripe_signature(payload: &[u8], secret: &str, timestamp: i64) -> String {
+    let signature = signature_mac(payload, secret, timestamp)
+        .finalize()
+        .into_bytes();
+    format!("t={},v1={:x}", timestamp, signature)
+}
+
+/// Checks a `Stripe-Signature` header (`t=<unix time>,v1=<hex HMAC-SHA256>`, possibly with
+/// several `v1` entries while a secret is being rolled) against the raw request body.
+pub fn verify_stripe_signature(
+    payload: &[u8],
+    header: &str,
+    secret: &str,
+    now: i64,
+) -> Result<(), String> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp =
+        timestamp.ok_or_else(|| "Invalid signature: the header has no timestamp".to_string())?;
+    if (now - timestamp).abs() > SIGNATURE_TOLERANCE_SECONDS {
+        return Err("Invalid signature: the timestamp is outside the tolerance".to_string());
+    }
+
+    let matches = signatures
+        .iter()
+        .filter_map(|s| decode_hex(s))
+        .any(|bytes| {
+            signature_mac(payload, secret, timestamp)
+                .verify_slice(&bytes)
+                .is_ok()
+        });
+    if matches {
+        Ok(())
+    } else {
+        Err("Invalid signature: no signature matches the payload".to_string())
+    }
+}
+
+/// Whether a subscription in `status` lets the tenant use the service: `Some(true)` for paid
+/// and trial subscriptions, `Some(false)` once Stripe has given up on payment, and `None` for
+/// states that change nothing, such as `past_due` while payment is still being retried.
+pub fn subscription_grants_access(status: &str) -> Option<bool> {
+    match status {
+        "active" | "trialing" => Some(true),
+        "canceled" | "unpaid" | "incomplete_expired" => Some(false),
+        _ => None,
+    }
+}
+
+/// The plan a subscription is for: the lookup key of its price, or the price id when the price
+/// has no lookup key.
+pub fn subscription_plan(subscription: &StripeSubscription) -> String {
+    subscription
+        .items
+        .data
+        .first()
+        .map(|item| {
+            item.price
+                .lookup_key
+                .clone()
+                .unwrap_or_else(|| item.price.id.clone())
+        })
+        .unwrap_or_else(|| DEFAULT_PLAN.to_string())
+}
+
+pub fn subscription_period_end(subscription: &StripeSubscription) -> Option<DateTime<Utc>> {
+    subscription
+        .current_period_end
+        .or_else(|| {
+            subscription
+                .items
+                .data
+                .iter()
+                .filter_map(|item| item.current_period_end)
+                .max()
+        })
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+}
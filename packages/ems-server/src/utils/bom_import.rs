@@ -0,0 +1,453 @@
+// BOM import parsing and export helpers
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::quantity::validate_bom_quantity;
+use crate::models::{
+    BomExportFormat, BomExportLine, BomExportPart, BomImportLineResult, BomImportRow, Quantity,
+};
+use crate::utils::csv::csv_escape;
+use crate::utils::price_list::parse_csv;
+
+/// Most designators a single range such as R1-R100 expands to
+pub const MAX_DESIGNATOR_RANGE: u32 = 1000;
+
+// Columns recognised in a BOM header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Column {
+    Designator,
+    Quantity,
+    InternalPartNumber,
+    /// The first is the component, later ones alternates
+    MfrPartNumber(u32),
+    Manufacturer(u32),
+    Description,
+    Value,
+    Substitutes,
+    Dnp,
+    Notes,
+}
+
+fn column(header: &str) -> Option<Column> {
+    let name = header.trim().to_lowercase().replace([' ', '-'], "_");
+
+    let numbered = |prefixes: &[&str]| {
+        prefixes
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix)?.parse::<u32>().ok())
+            .filter(|n| *n > 0)
+    };
+
+    match name.as_str() {
+        "designator" | "designators" | "reference" | "references" | "ref" | "refs" | "refdes"
+        | "ref_des" => Some(Column::Designator),
+        "qty" | "quantity" | "count" => Some(Column::Quantity),
+        "ipn" | "internal_part_number" => Some(Column::InternalPartNumber),
+        "mpn" | "mfr_part_number" | "manufacturer_part_number" | "mfr_pn" => {
+            Some(Column::MfrPartNumber(1))
+        }
+        "manufacturer" | "mfr" | "manufacturer_name" => Some(Column::Manufacturer(1)),
+        "description" | "desc" => Some(Column::Description),
+        "value" | "comment" => Some(Column::Value),
+        "substitutes" | "alternates" | "alternate_mpn" | "alt_mpn" | "alt_mpns" => {
+            Some(Column::Substitutes)
+        }
+        "dnp" | "dnf" | "do_not_populate" | "do_not_place" => Some(Column::Dnp),
+        "notes" | "note" => Some(Column::Notes),
+        _ => numbered(&["manufacturer_part_number_", "mfr_part_number_", "mpn_"])
+            .map(Column::MfrPartNumber)
+            .or_else(|| numbered(&["manufacturer_", "mfr_"]).map(Column::Manufacturer)),
+    }
+}
+
+/// Parses a BOM CSV as exported by Altium, KiCad or this server. The header needs a designator
+/// (`designator`, `reference`) or quantity (`qty`) column and a part column, either an MPN
+/// (`mpn`, `manufacturer_part_number_1`) or an internal part number (`ipn`). Optional columns
+/// are `manufacturer`, `description` (or `value`/`comment`), alternates as numbered MPN columns
+/// or a `substitutes` list, `dnp` and `notes`. Quantities default to the designator count.
+/// Rows naming the same part are merged; rows with problems, including designators used twice,
+/// are returned as failed results.
+pub fn parse_bom_import(
+    text: &str,
+) -> Result<(Vec<BomImportRow>, Vec<BomImportLineResult>), String> {
+    let mut records = parse_csv(text)?.into_iter();
+
+    let (_, header) = records.next().ok_or("BOM is empty")?;
+    let columns: Vec<Option<Column>> = header.iter().map(|h| column(h)).collect();
+
+    let has = |wanted: fn(&Column) -> bool| columns.iter().flatten().any(wanted);
+    if !has(|c| matches!(c, Column::Designator | Column::Quantity)) {
+        return Err("BOM has no designator or quantity column".to_string());
+    }
+    if !has(|c| matches!(c, Column::InternalPartNumber | Column::MfrPartNumber(1))) {
+        return Err("BOM has no MPN or internal part number column".to_string());
+    }
+
+    let mut rows: Vec<BomImportRow> = Vec::new();
+    let mut by_part: HashMap<String, usize> = HashMap::new();
+    let mut failed = Vec::new();
+    let mut placed = HashSet::new();
+
+    for (line, record) in records {
+        if record.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+
+        let row = match parse_row(line, &columns, &record) {
+            Ok(row) => row,
+            Err(result) => {
+                failed.push(*result);
+                continue;
+            }
+        };
+
+        let repeated: Vec<&String> = row
+            .designators
+            .iter()
+            .filter(|d| placed.contains(&d.to_uppercase()))
+            .collect();
+        if !repeated.is_empty() {
+            let repeated: Vec<&str> = repeated.iter().map(|d| d.as_str()).collect();
+            failed.push(BomImportLineResult::failed(
+                line,
+                row.part_number(),
+                vec![format!(
+                    "Designator appears more than once in the file: {}",
+                    repeated.join(", ")
+                )],
+            ));
+            continue;
+        }
+        placed.extend(row.designators.iter().map(|d| d.to_uppercase()));
+
+        match by_part.get(&part_key(&row)) {
+            Some(&index) => merge_row(&mut rows[index], row),
+            None => {
+                by_part.insert(part_key(&row), rows.len());
+                rows.push(row);
+            }
+        }
+    }
+
+    Ok((rows, failed))
+}
+
+fn parse_row(
+    line: usize,
+    columns: &[Option<Column>],
+    record: &[String],
+) -> Result<BomImportRow, Box<BomImportLineResult>> {
+    let mut values: BTreeMap<Column, &str> = BTreeMap::new();
+    for (column, value) in columns.iter().zip(record) {
+        if let Some(column) = column {
+            let value = value.trim();
+            if !value.is_empty() {
+                values.insert(*column, value);
+            }
+        }
+    }
+
+    let value = |column: Column| values.get(&column).map(|v| v.to_string());
+    let internal_part_number = value(Column::InternalPartNumber);
+    let mfr_part_number = value(Column::MfrPartNumber(1));
+    let part_number = internal_part_number.clone().or(mfr_part_number.clone());
+    let mut errors = Vec::new();
+
+    if part_number.is_none() {
+        errors.push("Missing MPN or internal part number".to_string());
+    }
+
+    let designators = match values.get(&Column::Designator) {
+        Some(value) => parse_designators(value).unwrap_or_else(|e| {
+            errors.push(e);
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+
+    let quantity = match values.get(&Column::Quantity) {
+        Some(value) => match value.parse::<Quantity>() {
+            Ok(quantity) if validate_bom_quantity(&quantity).is_err() => {
+                errors.push(format!("Invalid quantity: {}", value));
+                None
+            }
+            Ok(quantity) => Some(quantity),
+            Err(e) => {
+                errors.push(e);
+                None
+            }
+        },
+        None if designators.is_empty() => {
+            errors.push("Missing quantity".to_string());
+            None
+        }
+        None => Some(Quantity::from(designators.len() as i64)),
+    };
+    if let Some(quantity) = quantity {
+        if !designators.is_empty() && quantity != Quantity::from(designators.len() as i64) {
+            errors.push(format!(
+                "Quantity {} does not match {} designators",
+                quantity,
+                designators.len()
+            ));
+        }
+    }
+
+    // Same limits as the item columns
+    for (column, label, max) in [
+        (Column::InternalPartNumber, "internal part number", 50),
+        (Column::MfrPartNumber(1), "MPN", 50),
+        (Column::Manufacturer(1), "manufacturer", 100),
+    ] {
+        if values.get(&column).is_some_and(|v| v.chars().count() > max) {
+            errors.push(format!("{} longer than {} characters", label, max));
+        }
+    }
+
+    let mut substitutes: Vec<String> = Vec::new();
+    for (column, value) in &values {
+        match column {
+            Column::MfrPartNumber(n) if *n > 1 => substitutes.push(value.to_string()),
+            Column::Substitutes => substitutes.extend(
+                value
+                    .split([';', '|', ','])
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string),
+            ),
+            _ => {}
+        }
+    }
+    let mut seen = HashSet::new();
+    substitutes.retain(|s| {
+        seen.insert(s.to_lowercase())
+            && mfr_part_number
+                .as_deref()
+                .is_none_or(|mpn| !mpn.eq_ignore_ascii_case(s))
+    });
+
+    let (Some(quantity), true) = (quantity, errors.is_empty()) else {
+        return Err(Box::new(BomImportLineResult::failed(
+            line,
+            part_number,
+            errors,
+        )));
+    };
+
+    Ok(BomImportRow {
+        line,
+        internal_part_number,
+        mfr_part_number,
+        manufacturer: value(Column::Manufacturer(1)),
+        description: value(Column::Description).or_else(|| value(Column::Value)),
+        quantity,
+        designators,
+        substitutes,
+        is_optional: values.get(&Column::Dnp).is_some_and(|v| is_dnp(v)),
+        notes: value(Column::Notes),
+    })
+}
+
+// Parts are the same when their internal part numbers are, or else their MPN and manufacturer
+fn part_key(row: &BomImportRow) -> String {
+    match &row.internal_part_number {
+        Some(ipn) => format!("ipn:{}", ipn.to_lowercase()),
+        None => format!(
+            "mpn:{}:{}",
+            row.mfr_part_number
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase(),
+            row.manufacturer
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase()
+        ),
+    }
+}
+
+// A part split across rows, e.g. by footprint variant, is placed at every row's designators and
+// is only optional when every row is
+fn merge_row(into: &mut BomImportRow, row: BomImportRow) {
+    into.quantity += row.quantity;
+    into.designators.extend(row.designators);
+    for substitute in row.substitutes {
+        if !into
+            .substitutes
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(&substitute))
+        {
+            into.substitutes.push(substitute);
+        }
+    }
+    into.is_optional = into.is_optional && row.is_optional;
+    into.description = into.description.take().or(row.description);
+    into.notes = into.notes.take().or(row.notes);
+}
+
+fn is_dnp(value: &str) -> bool {
+    matches!(
+        value.to_lowercase().as_str(),
+        "1" | "x" | "y" | "yes" | "true" | "dnp" | "dnf" | "not fitted"
+    )
+}
+
+/// Splits a designator list on commas, semicolons and whitespace and expands ranges such as
+/// R1-R4.
+pub fn parse_designators(value: &str) -> Result<Vec<String>, String> {
+    let mut designators = Vec::new();
+    for token in value
+        .split([',', ';', ' ', '\t'])
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        match token
+            .split_once('-')
+            .and_then(|(start, end)| Some((split_designator(start)?, split_designator(end)?)))
+        {
+            Some(((prefix, start), (end_prefix, end))) => {
+                if !prefix.eq_ignore_ascii_case(end_prefix)
+                    || start > end
+                    || end - start >= MAX_DESIGNATOR_RANGE
+                {
+                    return Err(format!("Invalid designator range: {}", token));
+                }
+                designators.extend((start..=end).map(|n| format!("{}{}", prefix, n)));
+            }
+            None => designators.push(token.to_string()),
+        }
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(repeated) = designators.iter().find(|d| !seen.insert(d.to_uppercase())) {
+        return Err(format!("Designator listed twice: {}", repeated));
+    }
+    Ok(designators)
+}
+
+// A designator as its letter prefix and number, e.g. ("R", 12)
+fn split_designator(designator: &str) -> Option<(&str, u32)> {
+    let digits = designator.find(|c: char| c.is_ascii_digit())?;
+    let (prefix, number) = designator.split_at(digits);
+    if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((prefix, number.parse().ok()?))
+}
+
+/// Writes BOM lines as CSV with the columns of `format`. The `csv` format carries every field the
+/// import reads; `altium` and `kicad` follow those tools' BOM column names.
+pub fn render_bom_csv(lines: &[BomExportLine], format: BomExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        BomExportFormat::Csv => {
+            push_record(
+                &mut out,
+                &[
+                    "Designator",
+                    "Quantity",
+                    "Internal Part Number",
+                    "Manufacturer",
+                    "MPN",
+                    "Description",
+                    "Substitutes",
+                    "DNP",
+                    "Notes",
+                ],
+            );
+            for line in lines {
+                let substitutes: Vec<&str> = line.substitutes.iter().map(part_number).collect();
+                push_record(
+                    &mut out,
+                    &[
+                        &line.designators.join(", "),
+                        &line.quantity.to_string(),
+                        &line.component.internal_part_number,
+                        &line.component.manufacturer,
+                        line.component
+                            .mfr_part_number
+                            .as_deref()
+                            .unwrap_or_default(),
+                        line.description.as_deref().unwrap_or_default(),
+                        &substitutes.join("; "),
+                        dnp(line),
+                        line.notes.as_deref().unwrap_or_default(),
+                    ],
+                );
+            }
+        }
+        BomExportFormat::Altium => {
+            let parts = 1 + lines.iter().map(|l| l.substitutes.len()).max().unwrap_or(0);
+            let mut header = vec![
+                "Designator".to_string(),
+                "Quantity".to_string(),
+                "Description".to_string(),
+            ];
+            for n in 1..=parts {
+                header.push(format!("Manufacturer {}", n));
+                header.push(format!("Manufacturer Part Number {}", n));
+            }
+            push_record(&mut out, &header);
+            for line in lines {
+                let mut record = vec![
+                    line.designators.join(", "),
+                    line.quantity.to_string(),
+                    line.description.clone().unwrap_or_default(),
+                ];
+                for part in std::iter::once(&line.component).chain(&line.substitutes) {
+                    record.push(part.manufacturer.clone());
+                    record.push(part_number(part).to_string());
+                }
+                record.resize(header.len(), String::new());
+                push_record(&mut out, &record);
+            }
+        }
+        BomExportFormat::Kicad => {
+            push_record(
+                &mut out,
+                &[
+                    "Reference",
+                    "Qty",
+                    "Description",
+                    "Manufacturer",
+                    "MPN",
+                    "DNP",
+                ],
+            );
+            for line in lines {
+                push_record(
+                    &mut out,
+                    &[
+                        &line.designators.join(","),
+                        &line.quantity.to_string(),
+                        line.description.as_deref().unwrap_or_default(),
+                        &line.component.manufacturer,
+                        part_number(&line.component),
+                        dnp(line),
+                    ],
+                );
+            }
+        }
+    }
+    out
+}
+
+// Alternates are listed by MPN, or by internal part number for parts without one
+fn part_number(part: &BomExportPart) -> &str {
+    part.mfr_part_number
+        .as_deref()
+        .unwrap_or(&part.internal_part_number)
+}
+
+fn dnp(line: &BomExportLine) -> &'static str {
+    if line.is_optional {
+        "DNP"
+    } else {
+        ""
+    }
+}
+
+fn push_record<S: AsRef<str>>(out: &mut String, fields: &[S]) {
+    let fields: Vec<String> = fields.iter().map(|f| csv_escape(f.as_ref())).collect();
+    out.push_str(&fields.join(","));
+    out.push_str("\r\n");
+}
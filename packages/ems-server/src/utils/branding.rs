@@ -0,0 +1,310 @@
+// Tenant branding of customer documents: the colors, logo, footer and reply-to address order
+// confirmations and quotes are rendered and emailed with
+use std::io::Cursor;
+
+use chrono::{TimeZone, Utc};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{ExternalEntityType, OrderItemResponse, OrderResponse, OrderStatus, OrderType};
+use crate::utils::pdf::{Color, Letterhead, PdfImage};
+
+/// Key of the branding in tenant settings
+pub const BRANDING_SETTING: &str = "branding";
+
+/// Longest footer text, in characters
+pub const MAX_FOOTER_CHARS: usize = 300;
+
+/// Largest logo file accepted, in bytes
+pub const MAX_LOGO_BYTES: u64 = 2 * 1024 * 1024;
+
+// Logos are scaled down to fit within this many pixels, about four times the size they print at
+const LOGO_MAX_WIDTH: u32 = 560;
+const LOGO_MAX_HEIGHT: u32 = 144;
+const LOGO_QUALITY: u8 = 90;
+
+/// Content ID the logo is attached under in branded emails
+pub const LOGO_CONTENT_ID: &str = "logo";
+
+/// Branding a tenant sets under `branding` in its settings. Every part is optional; documents
+/// leave out what is not set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct BrandingSettings {
+    /// Asset whose current file, a JPEG, PNG or WebP image, is printed in the top right corner
+    /// of documents and heads branded emails
+    pub logo_asset_id: Option<Uuid>,
+
+    /// `#rrggbb` of the band along the top of documents and the email header
+    pub primary_color: Option<String>,
+
+    /// `#rrggbb` of the rule above the footer in documents and emails
+    pub accent_color: Option<String>,
+
+    /// Printed along the bottom of every page and closing every email, e.g. the company's
+    /// registered address
+    #[validate(length(min = 1, max = 300))]
+    pub footer_text: Option<String>,
+
+    /// Address customer replies to document emails go to
+    #[validate(email)]
+    pub reply_to: Option<String>,
+}
+
+impl BrandingSettings {
+    pub fn check(&self) -> Result<(), String> {
+        self.validate()
+            .map_err(|e| format!("Invalid branding: {}", e))?;
+        for (name, color) in [
+            ("primary_color", &self.primary_color),
+            ("accent_color", &self.accent_color),
+        ] {
+            if let Some(color) = color {
+                if Color::from_hex(color).is_none() {
+                    return Err(format!(
+                        "Invalid branding: {} must be a color written #rrggbb, got {}",
+                        name, color
+                    ));
+                }
+            }
+        }
+        if self
+            .footer_text
+            .as_deref()
+            .is_some_and(|footer| footer.trim().is_empty())
+        {
+            return Err("Invalid branding: footer_text is blank".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// The branding in tenant settings; unbranded when none is set or it cannot be read.
+pub fn settings_branding(settings: Option<&Value>) -> BrandingSettings {
+    settings
+        .and_then(|settings| settings.get(BRANDING_SETTING))
+        .filter(|branding| !branding.is_null())
+        .and_then(|branding| serde_json::from_value(branding.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Checks the branding in tenant settings, which may be left out but must be valid when
+/// given.
+pub fn check_branding_settings(settings: Option<&Value>) -> Result<(), String> {
+    match settings.and_then(|settings| settings.get(BRANDING_SETTING)) {
+        None | Some(Value::Null) => Ok(()),
+        Some(branding) => serde_json::from_value::<BrandingSettings>(branding.clone())
+            .map_err(|e| format!("Invalid branding: {}", e))?
+            .check(),
+    }
+}
+
+/// Branding as documents and emails are rendered with, its logo loaded. The default leaves
+/// documents unbranded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentBranding {
+    pub primary_color: Option<Color>,
+    pub accent_color: Option<Color>,
+    pub footer_text: Option<String>,
+    pub reply_to: Option<String>,
+    pub logo: Option<PdfImage>,
+}
+
+impl DocumentBranding {
+    /// Branding from checked settings and the logo prepared from their logo asset.
+    pub fn new(settings: &BrandingSettings, logo: Option<PdfImage>) -> Self {
+        Self {
+            primary_color: settings.primary_color.as_deref().and_then(Color::from_hex),
+            accent_color: settings.accent_color.as_deref().and_then(Color::from_hex),
+            footer_text: settings
+                .footer_text
+                .clone()
+                .filter(|footer| !footer.trim().is_empty()),
+            reply_to: settings.reply_to.clone(),
+            logo,
+        }
+    }
+
+    /// Whether documents look any different from unbranded ones.
+    pub fn is_branded(&self) -> bool {
+        self.primary_color.is_some()
+            || self.accent_color.is_some()
+            || self.footer_text.is_some()
+            || self.logo.is_some()
+    }
+
+    /// What every page of a branded document is drawn with.
+    pub fn letterhead(&self) -> Letterhead {
+        Letterhead {
+            color: self.primary_color,
+            accent_color: self.accent_color,
+            logo: self.logo.clone(),
+            footer: self.footer_text.clone(),
+        }
+    }
+
+    /// Plain text of an email with `body`, closed by the footer.
+    pub fn email_text(&self, body: &str) -> String {
+        match &self.footer_text {
+            Some(footer) => format!("{}\n--\n{}\n", body.trim_end(), footer),
+            None => body.to_string(),
+        }
+    }
+
+    /// HTML of an email with the plain text `body`: the logo, loaded from `logo_src`, on a band
+    /// of the primary color, the body's paragraphs and the footer below an accent rule.
+    pub fn email_html(&self, body: &str, logo_src: Option<&str>) -> String {
+        let primary = self
+            .primary_color
+            .map(|color| color.hex())
+            .unwrap_or_else(|| "#ffffff".to_string());
+        let accent = self
+            .accent_color
+            .map(|color| color.hex())
+            .unwrap_or_else(|| "#cccccc".to_string());
+
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html><body style=\"margin:0;font-family:Helvetica,Arial,sans-serif;\
+             color:#222222;\">\n",
+        );
+        html.push_str(&format!(
+            "<div style=\"background:{};padding:12px 24px;min-height:8px;\">",
+            primary
+        ));
+        if let (Some(_), Some(src)) = (&self.logo, logo_src) {
+            html.push_str(&format!(
+                "<img src=\"{}\" alt=\"\" style=\"max-height:48px;max-width:200px;\">",
+                escape_html(src)
+            ));
+        }
+        html.push_str("</div>\n<div style=\"padding:24px;\">\n");
+        for paragraph in body.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            html.push_str(&format!(
+                "<p>{}</p>\n",
+                escape_html(paragraph.trim()).replace('\n', "<br>")
+            ));
+        }
+        html.push_str("</div>\n");
+        if let Some(footer) = &self.footer_text {
+            html.push_str(&format!(
+                "<div style=\"border-top:1px solid {};margin:0 24px;padding:12px 0;\
+                 font-size:12px;color:#666666;\">{}</div>\n",
+                accent,
+                escape_html(footer).replace('\n', "<br>")
+            ));
+        }
+        html.push_str("</body></html>\n");
+        html
+    }
+}
+
+/// Prepares a logo image for documents: decoded, scaled down to the size it prints at, with
+/// transparent areas flattened onto white, and encoded as JPEG, which PDFs embed as is.
+pub fn prepare_logo(content: &[u8]) -> Result<PdfImage, String> {
+    if content.is_empty() {
+        return Err("Invalid branding: the logo file is empty".to_string());
+    }
+    if content.len() as u64 > MAX_LOGO_BYTES {
+        return Err(format!(
+            "Invalid branding: the logo file has {} bytes, at most {} allowed",
+            content.len(),
+            MAX_LOGO_BYTES
+        ));
+    }
+    let decoded = image::load_from_memory(content)
+        .map_err(|e| format!("Invalid branding: the logo is not a readable image: {}", e))?;
+
+    let fitted = if decoded.width() > LOGO_MAX_WIDTH || decoded.height() > LOGO_MAX_HEIGHT {
+        decoded.resize(LOGO_MAX_WIDTH, LOGO_MAX_HEIGHT, FilterType::Triangle)
+    } else {
+        decoded
+    };
+    let flattened = flatten(&fitted);
+
+    let mut encoded = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut encoded, LOGO_QUALITY)
+        .encode_image(&flattened)
+        .map_err(|e| format!("Invalid branding: the logo could not be encoded: {}", e))?;
+
+    Ok(PdfImage {
+        width: flattened.width(),
+        height: flattened.height(),
+        jpeg: encoded.into_inner(),
+    })
+}
+
+// The image over a white background
+fn flatten(image: &DynamicImage) -> RgbImage {
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+/// Escapes text for HTML content and attribute values.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The order confirmation branding previews are rendered with, so admins see the branding on
+/// a realistic document without picking an order.
+pub fn preview_order() -> OrderResponse {
+    let date = Utc.with_ymd_and_hms(2026, 1, 15, 9, 0, 0).unwrap();
+    let line = |name: &str, quantity: i32, unit_price: f64| OrderItemResponse {
+        id: Uuid::nil(),
+        item_id: None,
+        item_name: name.to_string(),
+        item_description: None,
+        quantity,
+        unit_price,
+        extended_price: quantity as f64 * unit_price,
+        notes: None,
+        expected_date: Some(date + chrono::Duration::days(14)),
+        backordered: false,
+        uom_id: None,
+        base_quantity: quantity,
+        created_at: date,
+        updated_at: date,
+    };
+    let items = vec![
+        line("Control board", 10, 25.0),
+        line("Enclosure, powder coated", 10, 12.5),
+        line("Wiring harness", 20, 4.75),
+    ];
+    OrderResponse {
+        id: Uuid::nil(),
+        order_number: "SO-0001".to_string(),
+        order_type: OrderType::CustomerOrder,
+        external_entity_id: Uuid::nil(),
+        external_entity_type: ExternalEntityType::Customer,
+        order_date: date,
+        total_amount: items.iter().map(|item| item.extended_price).sum(),
+        status: OrderStatus::Approved,
+        created_by_id: Uuid::nil(),
+        notes: None,
+        metadata: None,
+        created_at: date,
+        updated_at: date,
+        archived: false,
+        items,
+    }
+}
+
+/// Customer name on the preview order
+pub const PREVIEW_CUSTOMER: &str = "Example Customer Ltd.";
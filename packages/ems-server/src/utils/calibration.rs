@@ -0,0 +1,26 @@
+// Calibration helpers: due dates and the calibration status of a machine or instrument
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::{CalibrationResult, CalibrationStatus, CALIBRATION_DUE_SOON_DAYS};
+
+/// Date the next calibration is due after one performed at `calibrated_at`.
+pub fn due_date(calibrated_at: DateTime<Utc>, interval_days: i32) -> DateTime<Utc> {
+    calibrated_at + Duration::days(interval_days as i64)
+}
+
+/// Status at `now` from the result and due date of the latest calibration, if any.
+pub fn calibration_status(
+    latest: Option<(CalibrationResult, DateTime<Utc>)>,
+    now: DateTime<Utc>,
+) -> CalibrationStatus {
+    match latest {
+        None => CalibrationStatus::Uncalibrated,
+        Some((CalibrationResult::Fail, _)) => CalibrationStatus::Failed,
+        Some((_, due_at)) if due_at <= now => CalibrationStatus::Overdue,
+        Some((_, due_at)) if due_at - now <= Duration::days(CALIBRATION_DUE_SOON_DAYS) => {
+            CalibrationStatus::DueSoon
+        }
+        Some(_) => CalibrationStatus::Current,
+    }
+}
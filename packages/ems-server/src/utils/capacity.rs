@@ -0,0 +1,188 @@
+// Capacity planning helpers: working-time intervals from shift patterns and bucketing
+// scheduled time into calendar days (UTC)
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+use crate::models::ShiftDefinition;
+
+/// A half-open `[start, end)` time interval.
+pub type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+// Working hours assumed for a machine on a working day when no calendar is defined
+pub const DEFAULT_HOURS_PER_DAY: f64 = 8.0;
+
+// Default and maximum length of a planning window, in days
+pub const DEFAULT_WINDOW_DAYS: i64 = 14;
+pub const MAX_WINDOW_DAYS: i64 = 366;
+
+/// Hours of overlap between two time intervals (0 when they do not intersect).
+pub fn overlap_hours(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+) -> f64 {
+    let start = start.max(window_start);
+    let end = end.min(window_end);
+    if end <= start {
+        return 0.0;
+    }
+    (end - start).num_seconds() as f64 / 3600.0
+}
+
+/// Start of the given day in UTC.
+pub fn day_start(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .map(|datetime| datetime.and_utc())
+        .unwrap_or_else(Utc::now)
+}
+
+/// Splits an interval into hours per calendar day, limited to the inclusive `from..=to` date range.
+pub fn hours_by_day(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> BTreeMap<NaiveDate, f64> {
+    let mut hours = BTreeMap::new();
+    let mut date = start.date_naive().max(from);
+    let last = end.date_naive().min(to);
+
+    while date <= last {
+        let next = date + Duration::days(1);
+        let overlap = overlap_hours(start, end, day_start(date), day_start(next));
+        if overlap > 0.0 {
+            hours.insert(date, overlap);
+        }
+        date = next;
+    }
+
+    hours
+}
+
+/// Default availability for a day: `hours_per_day` on weekdays, and on weekends only when included.
+pub fn default_available_hours(date: NaiveDate, hours_per_day: f64, include_weekends: bool) -> f64 {
+    match date.weekday() {
+        Weekday::Sat | Weekday::Sun if !include_weekends => 0.0,
+        _ => hours_per_day,
+    }
+}
+
+/// Scheduled hours as a percentage of available hours; `None` when nothing is available.
+pub fn load_percent(scheduled_hours: f64, available_hours: f64) -> Option<f64> {
+    if available_hours <= 0.0 {
+        return None;
+    }
+    Some(scheduled_hours / available_hours * 100.0)
+}
+
+/// Sorts intervals and merges those that overlap or touch, dropping empty ones.
+pub fn merge_intervals(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.retain(|(start, end)| end > start);
+    intervals.sort();
+
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Removes the `blocked` intervals from `intervals`.
+pub fn subtract_intervals(intervals: &[Interval], blocked: &[Interval]) -> Vec<Interval> {
+    let blocked = merge_intervals(blocked.to_vec());
+    let mut remaining = Vec::new();
+
+    for &(start, end) in intervals {
+        let mut cursor = start;
+        for &(block_start, block_end) in &blocked {
+            if block_end <= cursor || block_start >= end {
+                continue;
+            }
+            if block_start > cursor {
+                remaining.push((cursor, block_start));
+            }
+            cursor = cursor.max(block_end);
+        }
+        if cursor < end {
+            remaining.push((cursor, end));
+        }
+    }
+
+    remaining
+}
+
+/// Time covered by both `a` and `b`.
+pub fn intersect_intervals(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    subtract_intervals(a, &subtract_intervals(a, b))
+}
+
+/// Clips intervals to a window, dropping those that fall outside it.
+pub fn clip_intervals(intervals: &[Interval], window: Interval) -> Vec<Interval> {
+    intervals
+        .iter()
+        .map(|&(start, end)| (start.max(window.0), end.min(window.1)))
+        .filter(|(start, end)| end > start)
+        .collect()
+}
+
+/// Resolves a local time to UTC, moving past a DST gap.
+pub fn local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// Expands a weekly shift pattern, evaluated in `tz`, into working intervals within the window.
+pub fn shift_intervals(shifts: &[ShiftDefinition], tz: Tz, window: Interval) -> Vec<Interval> {
+    // Start a day early so overnight shifts from the previous day are included
+    let first = window.0.with_timezone(&tz).date_naive() - Duration::days(1);
+    let last = window.1.with_timezone(&tz).date_naive();
+
+    let mut intervals = Vec::new();
+    for date in first.iter_days().take_while(|date| *date <= last) {
+        let weekday = date.weekday().number_from_monday();
+        for shift in shifts.iter().filter(|s| s.day_of_week == weekday) {
+            let end_date = if shift.end_time <= shift.start_time {
+                date + Duration::days(1)
+            } else {
+                date
+            };
+            let start = local_to_utc(tz, date.and_time(shift.start_time));
+            let end = local_to_utc(tz, end_date.and_time(shift.end_time));
+            intervals.push((start, end));
+        }
+    }
+
+    merge_intervals(clip_intervals(&intervals, window))
+}
+
+/// Total hours of availability per calendar day (UTC) within the inclusive `from..=to` range.
+pub fn daily_interval_hours(
+    intervals: &[Interval],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> BTreeMap<NaiveDate, f64> {
+    let mut hours = BTreeMap::new();
+    for &(start, end) in intervals {
+        for (date, overlap) in hours_by_day(start, end, from, to) {
+            *hours.entry(date).or_insert(0.0) += overlap;
+        }
+    }
+    hours
+}
+
+/// Length of an interval in hours.
+pub fn interval_hours(interval: &Interval) -> f64 {
+    overlap_hours(interval.0, interval.1, interval.0, interval.1)
+}
@@ -0,0 +1,284 @@
+// Circuit breaker for calls to external services
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+// Consecutive failures that open the circuit
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+// How long an open circuit fails fast before letting a trial call through
+pub const DEFAULT_OPEN_SECONDS: u64 = 30;
+// Longest a single call may take before it counts as a failure
+pub const DEFAULT_CALL_TIMEOUT_SECONDS: u64 = 10;
+
+/// Returned when the service is failing, either straight from a call that errored or timed out,
+/// or without calling at all while the circuit is open.
+#[derive(Error, Debug, Clone)]
+#[error("{service} unavailable: {reason}")]
+pub struct ServiceUnavailable {
+    pub service: &'static str,
+    pub reason: String,
+}
+
+impl ServiceUnavailable {
+    /// Whether `error` (or anything it wraps) is a `ServiceUnavailable`.
+    pub fn is(error: &anyhow::Error) -> bool {
+        error.downcast_ref::<ServiceUnavailable>().is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls go through
+    #[serde(rename = "closed")]
+    Closed,
+    /// Calls fail fast
+    #[serde(rename = "open")]
+    Open,
+    /// One trial call decides whether to close or reopen
+    #[serde(rename = "half_open")]
+    HalfOpen,
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub open_duration: Duration,
+    pub call_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            open_duration: Duration::from_secs(DEFAULT_OPEN_SECONDS),
+            call_timeout: Duration::from_secs(DEFAULT_CALL_TIMEOUT_SECONDS),
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Reads `<PREFIX>_BREAKER_FAILURE_THRESHOLD`, `<PREFIX>_BREAKER_OPEN_SECONDS` and
+    /// `<PREFIX>_TIMEOUT_SECONDS`, keeping the default for anything unset or not positive.
+    pub fn from_env(prefix: &str) -> Self {
+        let positive = |name: &str| {
+            env::var(format!("{}_{}", prefix, name))
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .filter(|s| *s > 0)
+        };
+        let defaults = Self::default();
+
+        Self {
+            failure_threshold: positive("BREAKER_FAILURE_THRESHOLD")
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.failure_threshold),
+            open_duration: positive("BREAKER_OPEN_SECONDS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.open_duration),
+            call_timeout: positive("TIMEOUT_SECONDS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.call_timeout),
+        }
+    }
+}
+
+// Point-in-time view of a breaker, as reported by the readiness check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub service: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    /// Seconds until an open circuit lets a trial call through
+    pub retry_in_seconds: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    // Start of the trial call while half open
+    probe_started_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// Counts consecutive failures of an external service and, past the threshold, fails calls
+/// immediately instead of waiting on the service. After the open period one call is let through;
+/// its outcome closes or reopens the circuit. Clones share state.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    service: &'static str,
+    config: CircuitBreakerConfig,
+    inner: Arc<Mutex<BreakerInner>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &'static str, config: CircuitBreakerConfig) -> Self {
+        Self {
+            service,
+            config,
+            inner: Arc::new(Mutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                probe_started_at: None,
+                last_error: None,
+            })),
+        }
+    }
+
+    pub fn config(&self) -> CircuitBreakerConfig {
+        self.config
+    }
+
+    /// Runs `future` under the breaker and the call timeout. Errors and timeouts are recorded as
+    /// failures and returned as `ServiceUnavailable`; while the circuit is open `future` is
+    /// dropped without being polled.
+    pub async fn call<T, E, F>(&self, future: F) -> Result<T>
+    where
+        E: Display,
+        F: Future<Output = std::result::Result<T, E>>,
+    {
+        self.acquire()?;
+
+        let outcome = match tokio::time::timeout(self.config.call_timeout, future).await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!(
+                "timed out after {}s",
+                self.config.call_timeout.as_secs()
+            )),
+        };
+
+        match outcome {
+            Ok(value) => {
+                self.record_success();
+                Ok(value)
+            }
+            Err(reason) => {
+                self.record_failure(&reason);
+                Err(self.unavailable(reason).into())
+            }
+        }
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let inner = self.lock();
+        let remaining = inner.opened_at.map(|opened_at| {
+            self.config
+                .open_duration
+                .saturating_sub(opened_at.elapsed())
+        });
+
+        // An open circuit whose period has passed takes its trial call on the next request
+        let state = match (inner.state, remaining) {
+            (CircuitState::Open, Some(remaining)) if remaining.is_zero() => CircuitState::HalfOpen,
+            (state, _) => state,
+        };
+
+        CircuitBreakerStatus {
+            service: self.service.to_string(),
+            state,
+            consecutive_failures: inner.consecutive_failures,
+            failure_threshold: self.config.failure_threshold,
+            retry_in_seconds: (state == CircuitState::Open)
+                .then(|| remaining.map(|r| r.as_secs() + 1))
+                .flatten(),
+            last_error: inner.last_error.clone(),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.status().state
+    }
+
+    // Lets a call through, or fails fast while open or while another trial call is running
+    fn acquire(&self) -> Result<()> {
+        let mut inner = self.lock();
+        match inner.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open => {
+                let elapsed = inner.opened_at.map(|opened_at| opened_at.elapsed());
+                if elapsed.is_some_and(|elapsed| elapsed < self.config.open_duration) {
+                    return Err(self.unavailable("circuit open".to_string()).into());
+                }
+                inner.state = CircuitState::HalfOpen;
+                inner.probe_started_at = Some(Instant::now());
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                // A trial call that was cancelled never reports back; replace it once it would
+                // have timed out
+                let probing = inner
+                    .probe_started_at
+                    .is_some_and(|started| started.elapsed() < self.config.call_timeout);
+                if probing {
+                    return Err(self.unavailable("circuit open".to_string()).into());
+                }
+                inner.probe_started_at = Some(Instant::now());
+                Ok(())
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.lock();
+        if inner.state != CircuitState::Closed {
+            tracing::info!("{} circuit closed", self.service);
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+        inner.probe_started_at = None;
+    }
+
+    fn record_failure(&self, reason: &str) {
+        let mut inner = self.lock();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        inner.last_error = Some(reason.to_string());
+
+        let trial_failed = inner.state == CircuitState::HalfOpen;
+        if trial_failed || inner.consecutive_failures >= self.config.failure_threshold {
+            if inner.state != CircuitState::Open {
+                tracing::warn!(
+                    "{} circuit opened after {} consecutive failure(s): {}",
+                    self.service,
+                    inner.consecutive_failures,
+                    reason
+                );
+            }
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+            inner.probe_started_at = None;
+        }
+    }
+
+    fn unavailable(&self, reason: String) -> ServiceUnavailable {
+        ServiceUnavailable {
+            service: self.service,
+            reason,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BreakerInner> {
+        // The state stays consistent even if a holder panicked
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,55 @@
+// Consignment stock helpers: which partners' stock an issue draws on and what it bills
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::models::{ConsignmentBilling, ConsignmentStock, Quantity};
+use crate::utils::quote::round_cents;
+
+/// Unit cost of an inventory record's pricing: `unit_cost`, falling back to `unit_price`, else 0.
+pub fn unit_cost(pricing: Option<&Value>) -> f64 {
+    ["unit_cost", "unit_price"]
+        .iter()
+        .find_map(|key| pricing?.get(key)?.as_f64())
+        .unwrap_or(0.0)
+}
+
+/// How much of an issue of `quantity` comes out of each partner's consignment stock once the
+/// `owned` stock is used up, taking the balances in order. Stops short when they run out.
+pub fn draw_consignment(
+    owned: Quantity,
+    balances: &[ConsignmentStock],
+    quantity: Quantity,
+) -> Vec<(&ConsignmentStock, Quantity)> {
+    let mut remaining = quantity - owned.min(quantity).max(Quantity::ZERO);
+    let mut drawn = Vec::new();
+    for balance in balances {
+        if remaining <= Quantity::ZERO {
+            break;
+        }
+        let take = remaining.min(balance.quantity);
+        if take > Quantity::ZERO {
+            drawn.push((balance, take));
+            remaining -= take;
+        }
+    }
+    drawn
+}
+
+/// What consuming `quantity` at `unit_price` owes the partner, rounded to cents.
+pub fn billing_amount(quantity: Quantity, unit_price: f64) -> f64 {
+    round_cents(quantity.to_f64() * unit_price)
+}
+
+/// Billed amounts summed per currency; billings without a currency go under an empty key.
+pub fn invoice_totals(billings: &[ConsignmentBilling]) -> BTreeMap<String, f64> {
+    let mut totals = BTreeMap::new();
+    for billing in billings {
+        *totals
+            .entry(billing.currency.clone().unwrap_or_default())
+            .or_insert(0.0) += billing.amount;
+    }
+    totals
+        .into_iter()
+        .map(|(currency, amount)| (currency, round_cents(amount)))
+        .collect()
+}
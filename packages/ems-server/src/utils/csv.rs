@@ -0,0 +1,17 @@
+// CSV writing helpers shared by exports
+
+/// One CSV field: quoted when it holds a separator, quote or line break, and prefixed with `'`
+/// when a spreadsheet would run it as a formula, so it opens as plain text. Numbers that should
+/// stay numeric, negative ones too, are written as they are instead.
+pub fn csv_escape(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
@@ -0,0 +1,119 @@
+// Item datasheet fetching helpers
+use std::env;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+use url::Url;
+use uuid::Uuid;
+
+pub use crate::utils::public_url::is_public_ip;
+use crate::utils::public_url::{check_public_address, check_public_url};
+
+/// Largest datasheet fetched, in bytes
+pub const DATASHEET_MAX_BYTES: usize = 25 * 1024 * 1024;
+
+/// Redirects followed before a fetch is given up
+pub const DATASHEET_MAX_REDIRECTS: usize = 5;
+
+// Days a snapshot stays fresh when DATASHEET_MAX_AGE_DAYS is not set
+pub const DEFAULT_DATASHEET_MAX_AGE_DAYS: i64 = 30;
+
+static DATASHEET_MAX_AGE: OnceLock<Duration> = OnceLock::new();
+
+/// How long a snapshot is served before it is fetched again, from DATASHEET_MAX_AGE_DAYS, read
+/// once.
+pub fn datasheet_max_age() -> Duration {
+    *DATASHEET_MAX_AGE.get_or_init(|| {
+        let days = env::var("DATASHEET_MAX_AGE_DAYS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_DATASHEET_MAX_AGE_DAYS);
+        Duration::days(days)
+    })
+}
+
+/// A snapshot is stale once it is older than `max_age`, or when it was never fetched.
+pub fn is_stale(fetched_at: Option<DateTime<Utc>>, now: DateTime<Utc>, max_age: Duration) -> bool {
+    match fetched_at {
+        Some(fetched_at) => now - fetched_at > max_age,
+        None => true,
+    }
+}
+
+/// Checks a datasheet URL before it is fetched: only http and https, and never a host on the
+/// server's own network, so item links cannot be used to reach internal services. Host names are
+/// checked again once resolved.
+pub fn check_datasheet_url(url: &str) -> Result<Url, String> {
+    check_public_url(url, "datasheet")
+}
+
+/// Refuses addresses that are not on the public internet.
+pub fn check_address(ip: IpAddr) -> Result<(), String> {
+    check_public_address(ip, "datasheet")
+}
+
+/// The media type a datasheet is stored as: the one the server sent, without parameters, or
+/// PDF when the server sent none or a generic one and the file is a PDF.
+pub fn datasheet_content_type(header: Option<&str>, content: &[u8]) -> String {
+    let sent = header
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| !value.is_empty());
+    match sent {
+        Some(content_type) if content_type != "application/octet-stream" => content_type,
+        _ if content.starts_with(b"%PDF-") => "application/pdf".to_string(),
+        _ => "application/octet-stream".to_string(),
+    }
+}
+
+/// File extension of a stored datasheet of `content_type`.
+pub fn datasheet_extension(content_type: &str) -> &'static str {
+    match content_type {
+        "application/pdf" => "pdf",
+        "text/html" => "html",
+        "text/plain" => "txt",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        _ => "bin",
+    }
+}
+
+/// Name a datasheet is served under, after the item's part number.
+pub fn datasheet_file_name(part_number: &str, content_type: &str) -> String {
+    let name: String = part_number
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = if name.is_empty() {
+        "datasheet".to_string()
+    } else {
+        name
+    };
+    format!("{}.{}", name, datasheet_extension(content_type))
+}
+
+/// Storage path of a datasheet snapshot. The checksum keeps each fetched version apart, so a
+/// refresh never overwrites the file the current asset points at.
+pub fn datasheet_path(
+    tenant_id: Uuid,
+    item_id: Uuid,
+    checksum: &str,
+    content_type: &str,
+) -> String {
+    format!(
+        "datasheets/{}/{}/{}.{}",
+        tenant_id,
+        item_id,
+        checksum,
+        datasheet_extension(content_type)
+    )
+}
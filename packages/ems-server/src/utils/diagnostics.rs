@@ -0,0 +1,153 @@
+// Process-wide diagnostics: background worker runs and tenant cache hit rates
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use uuid::Uuid;
+
+static REGISTRY: OnceLock<Diagnostics> = OnceLock::new();
+
+/// Runs of one background worker since the server started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkerRunStats {
+    pub worker: String,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<f64>,
+    /// Records the last successful run processed
+    pub last_processed: Option<usize>,
+    pub last_error: Option<String>,
+}
+
+/// Tenant cache lookups for one tenant since the server started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TenantCacheStats {
+    pub tenant_id: Uuid,
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of lookups served from the cache; `None` before the first lookup
+    pub hit_rate: Option<f64>,
+}
+
+/// Counters support reads to triage a running server, filled in by the background workers and
+/// the tenant cache.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    workers: Mutex<HashMap<String, WorkerRunStats>>,
+    tenant_cache: Mutex<HashMap<Uuid, TenantCacheStats>>,
+}
+
+impl Diagnostics {
+    /// The process-wide registry.
+    pub fn global() -> &'static Diagnostics {
+        REGISTRY.get_or_init(Diagnostics::default)
+    }
+
+    pub fn record_run(
+        &self,
+        worker: &str,
+        started_at: DateTime<Utc>,
+        duration_ms: f64,
+        outcome: &anyhow::Result<usize>,
+    ) {
+        let Ok(mut workers) = self.workers.lock() else {
+            return;
+        };
+        let stats = workers
+            .entry(worker.to_string())
+            .or_insert_with(|| WorkerRunStats {
+                worker: worker.to_string(),
+                ..Default::default()
+            });
+        stats.runs += 1;
+        stats.last_run_at = Some(started_at);
+        stats.last_duration_ms = Some(duration_ms);
+        match outcome {
+            Ok(processed) => {
+                stats.last_success_at = Some(started_at);
+                stats.last_processed = Some(*processed);
+            }
+            Err(e) => {
+                stats.failures += 1;
+                stats.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Every worker's runs, by worker name.
+    pub fn worker_runs(&self) -> Vec<WorkerRunStats> {
+        let mut stats: Vec<_> = self
+            .workers
+            .lock()
+            .map(|workers| workers.values().cloned().collect())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| a.worker.cmp(&b.worker));
+        stats
+    }
+
+    pub fn record_cache_lookup(&self, tenant_id: Uuid, hit: bool) {
+        let Ok(mut tenants) = self.tenant_cache.lock() else {
+            return;
+        };
+        let stats = tenants
+            .entry(tenant_id)
+            .or_insert_with(|| TenantCacheStats {
+                tenant_id,
+                ..Default::default()
+            });
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        stats.hit_rate = hit_rate(stats.hits, stats.misses);
+    }
+
+    /// Every tenant's cache lookups, the tenants looked up most first.
+    pub fn tenant_cache_stats(&self) -> Vec<TenantCacheStats> {
+        let mut stats: Vec<_> = self
+            .tenant_cache
+            .lock()
+            .map(|tenants| tenants.values().cloned().collect())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| {
+            (b.hits + b.misses)
+                .cmp(&(a.hits + a.misses))
+                .then(a.tenant_id.cmp(&b.tenant_id))
+        });
+        stats
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut workers) = self.workers.lock() {
+            workers.clear();
+        }
+        if let Ok(mut tenants) = self.tenant_cache.lock() {
+            tenants.clear();
+        }
+    }
+}
+
+/// Share of lookups that hit, or `None` when there were none.
+pub fn hit_rate(hits: u64, misses: u64) -> Option<f64> {
+    let lookups = hits + misses;
+    (lookups > 0).then(|| hits as f64 / lookups as f64)
+}
+
+/// Runs one poll of a background worker, recording when it ran, how long it took and how it
+/// ended in [`Diagnostics::global`].
+pub async fn track_run<F>(worker: &str, run: F) -> anyhow::Result<usize>
+where
+    F: Future<Output = anyhow::Result<usize>>,
+{
+    let started_at = Utc::now();
+    let started = Instant::now();
+    let outcome = run.await;
+    let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    Diagnostics::global().record_run(worker, started_at, duration_ms, &outcome);
+    outcome
+}
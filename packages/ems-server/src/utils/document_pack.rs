@@ -0,0 +1,30 @@
+// Order document pack helpers
+use chrono::{DateTime, Utc};
+
+use crate::utils::zip::safe_file_name;
+
+/// Release notes of a firmware version as the text file put in document packs.
+pub fn release_notes_text(
+    name: &str,
+    version: Option<&str>,
+    released_at: Option<DateTime<Utc>>,
+    notes: &str,
+) -> String {
+    let mut text = format!("{} {}\n", name, version.unwrap_or("(unversioned)"));
+    if let Some(released_at) = released_at {
+        text.push_str(&format!("Released {}\n", released_at.format("%Y-%m-%d")));
+    }
+    text.push('\n');
+    text.push_str(notes.trim());
+    text.push('\n');
+    text
+}
+
+/// Archive path of a firmware version's release notes.
+pub fn release_notes_path(name: &str, version: Option<&str>) -> String {
+    let file = match version {
+        Some(version) => format!("{}-{}", name, version),
+        None => name.to_string(),
+    };
+    format!("firmware/{}.txt", safe_file_name(&file))
+}
@@ -0,0 +1,205 @@
+// Duplicate detection helpers for persons and items
+use crate::models::{DuplicateMatch, DuplicateReason, Item, Person};
+
+/// Similarity a fuzzy match needs when no minimum is given.
+pub const DEFAULT_MIN_SCORE: f64 = 0.85;
+
+// Phone numbers shorter than this are too short to compare reliably
+const MIN_PHONE_DIGITS: usize = 7;
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Digits of a phone number without leading zeros (international and trunk prefixes), or `None`
+/// when there are too few to compare.
+pub fn normalize_phone(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let digits = digits.trim_start_matches('0').to_string();
+    (digits.len() >= MIN_PHONE_DIGITS).then_some(digits)
+}
+
+/// Phones match when their digits are equal or one ends with the other, which covers the same
+/// number written with and without a country code.
+pub fn same_phone(a: &str, b: &str) -> bool {
+    match (normalize_phone(a), normalize_phone(b)) {
+        (Some(a), Some(b)) => a.ends_with(&b) || b.ends_with(&a),
+        _ => false,
+    }
+}
+
+/// Lowercase words without punctuation, separated by single spaces.
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Part number with only its letters and digits, so "RC0603-10K" and "rc0603 10k" compare equal.
+pub fn normalize_part_number(part_number: &str) -> String {
+    part_number
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+fn edit_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Similarity (0-1) of two names, ignoring case, punctuation and word order.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_name(a), normalize_name(b));
+    let sorted = |name: &str| {
+        let mut words: Vec<&str> = name.split(' ').collect();
+        words.sort_unstable();
+        words.join(" ")
+    };
+
+    edit_similarity(&a, &b).max(edit_similarity(&sorted(&a), &sorted(&b)))
+}
+
+// Orders a pair so the older record is the suggested survivor
+fn ordered<'a, T>(
+    a: &'a T,
+    b: &'a T,
+    created_at: impl Fn(&T) -> Option<chrono::DateTime<chrono::Utc>>,
+) -> (&'a T, &'a T) {
+    match (created_at(a), created_at(b)) {
+        (Some(x), Some(y)) if y < x => (b, a),
+        (None, Some(_)) => (b, a),
+        _ => (a, b),
+    }
+}
+
+fn sort_matches(matches: &mut [DuplicateMatch]) {
+    matches.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.survivor_label.cmp(&b.survivor_label))
+    });
+}
+
+/// Pairs of persons with the same email or phone, or names at least `min_score` similar.
+pub fn find_person_duplicates(persons: &[Person], min_score: f64) -> Vec<DuplicateMatch> {
+    let mut matches = Vec::new();
+
+    for (i, a) in persons.iter().enumerate() {
+        for b in &persons[i + 1..] {
+            let mut reasons = Vec::new();
+            if normalize_email(&a.email) == normalize_email(&b.email) {
+                reasons.push(DuplicateReason::SameEmail);
+            }
+            if let (Some(pa), Some(pb)) = (&a.phone, &b.phone) {
+                if same_phone(pa, pb) {
+                    reasons.push(DuplicateReason::SamePhone);
+                }
+            }
+            let similarity = name_similarity(&a.name, &b.name);
+            if similarity >= min_score {
+                reasons.push(DuplicateReason::SimilarName);
+            }
+            if reasons.is_empty() {
+                continue;
+            }
+
+            let exact = reasons.iter().any(|r| *r != DuplicateReason::SimilarName);
+            let (survivor, duplicate) = ordered(a, b, |p| p.created_at);
+            matches.push(DuplicateMatch {
+                survivor_id: survivor.id,
+                survivor_label: format!("{} <{}>", survivor.name, survivor.email),
+                duplicate_id: duplicate.id,
+                duplicate_label: format!("{} <{}>", duplicate.name, duplicate.email),
+                reasons,
+                score: if exact { 1.0 } else { similarity },
+            });
+        }
+    }
+
+    sort_matches(&mut matches);
+    matches
+}
+
+/// Pairs of items with the same MPN and manufacturer, the same MPN and a similar manufacturer,
+/// or descriptions at least `min_score` similar from the same manufacturer.
+pub fn find_item_duplicates(items: &[Item], min_score: f64) -> Vec<DuplicateMatch> {
+    let mut matches = Vec::new();
+
+    for (i, a) in items.iter().enumerate() {
+        for b in &items[i + 1..] {
+            let manufacturer_similarity = name_similarity(&a.manufacturer, &b.manufacturer);
+            let same_manufacturer =
+                normalize_name(&a.manufacturer) == normalize_name(&b.manufacturer);
+
+            let mut reasons = Vec::new();
+            let mut score: f64 = 0.0;
+
+            let same_mpn = match (&a.mfr_part_number, &b.mfr_part_number) {
+                (Some(ma), Some(mb)) => {
+                    let (ma, mb) = (normalize_part_number(ma), normalize_part_number(mb));
+                    !ma.is_empty() && ma == mb
+                }
+                _ => false,
+            };
+            if same_mpn && same_manufacturer {
+                reasons.push(DuplicateReason::SameMpnManufacturer);
+                score = 1.0;
+            } else if same_mpn && manufacturer_similarity >= min_score {
+                reasons.push(DuplicateReason::SimilarMpn);
+                score = manufacturer_similarity;
+            }
+
+            if let (Some(da), Some(db)) = (&a.description, &b.description) {
+                let similarity = name_similarity(da, db);
+                if same_manufacturer && similarity >= min_score {
+                    reasons.push(DuplicateReason::SimilarDescription);
+                    score = score.max(similarity);
+                }
+            }
+            if reasons.is_empty() {
+                continue;
+            }
+
+            let (survivor, duplicate) = ordered(a, b, |item| item.created_at);
+            matches.push(DuplicateMatch {
+                survivor_id: survivor.id,
+                survivor_label: survivor.internal_part_number.clone(),
+                duplicate_id: duplicate.id,
+                duplicate_label: duplicate.internal_part_number.clone(),
+                reasons,
+                score,
+            });
+        }
+    }
+
+    sort_matches(&mut matches);
+    matches
+}
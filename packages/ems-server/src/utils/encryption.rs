@@ -0,0 +1,300 @@
+// Field encryption: AES-256-GCM with per-tenant data keys
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+
+/// Marks an encrypted value: `enc:v1:<data key version>:<base64 of nonce and ciphertext>`
+pub const SEALED_PREFIX: &str = "enc:v1:";
+pub const KEY_LEN: usize = 32;
+
+// Associated data for values in the platform scope, i.e. records shared across tenants
+const PLATFORM_AAD: &[u8] = b"platform";
+
+/// A 256-bit AES key. Its `Debug` output never shows the key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DataKey([u8; KEY_LEN]);
+
+impl DataKey {
+    pub fn generate() -> Result<Self> {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| anyhow!("Failed to generate a data key"))?;
+        Ok(Self(key))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let key: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Encryption keys must be {} bytes", KEY_LEN))?;
+        Ok(Self(key))
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|_| anyhow!("Encryption keys must be base64"))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for DataKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DataKey(..)")
+    }
+}
+
+/// Encrypts `plaintext`, returning the random nonce followed by the ciphertext and tag.
+pub fn encrypt(key: &DataKey, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("Failed to generate a nonce"))?;
+
+    let mut in_out = plaintext.to_vec();
+    aead_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow!("Encryption failed"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&in_out);
+    Ok(sealed)
+}
+
+/// Reverses [`encrypt`]. Fails when the key or associated data differ from the ones used to
+/// encrypt, or the ciphertext was changed.
+pub fn decrypt(key: &DataKey, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(anyhow!("Encrypted value is truncated"));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Invalid nonce"))?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = aead_key(key)?
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| anyhow!("Decryption failed"))?;
+    Ok(plaintext.to_vec())
+}
+
+fn aead_key(key: &DataKey) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, key.as_bytes())
+        .map_err(|_| anyhow!("Invalid encryption key"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(SEALED_PREFIX)
+}
+
+/// Version of the data key a sealed value was encrypted with.
+pub fn sealed_version(value: &str) -> Option<i32> {
+    let (version, _) = value.strip_prefix(SEALED_PREFIX)?.split_once(':')?;
+    version.parse().ok()
+}
+
+/// Settings keys holding credentials, which are stored encrypted: `client_secret`, `api_key`,
+/// `password`, `token` and keys ending in `_secret`, `_api_key`, `_password` or `_token`.
+pub fn is_secret_setting(key: &str) -> bool {
+    const SECRET_KEYS: [&str; 4] = ["secret", "api_key", "password", "token"];
+    SECRET_KEYS
+        .iter()
+        .any(|secret| key == *secret || key.ends_with(&format!("_{}", secret)))
+}
+
+/// Whether any string in `settings`, at any depth, is under a secret key.
+pub fn has_secret_settings(settings: &Value) -> bool {
+    match settings {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            (is_secret_setting(key) && value.is_string()) || has_secret_settings(value)
+        }),
+        Value::Array(values) => values.iter().any(has_secret_settings),
+        _ => false,
+    }
+}
+
+/// `settings` without its secrets, e.g. for copying settings to a sandbox tenant.
+pub fn strip_secret_settings(settings: &Value) -> Value {
+    match settings {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, value)| !(is_secret_setting(key) && value.is_string()))
+                .map(|(key, value)| (key.clone(), strip_secret_settings(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.iter().map(strip_secret_settings).collect()),
+        other => other.clone(),
+    }
+}
+
+// Applies `f` to every string under a secret key
+fn map_secret_settings(
+    settings: &Value,
+    f: &mut dyn FnMut(&str) -> Result<String>,
+) -> Result<Value> {
+    Ok(match settings {
+        Value::Object(map) => {
+            let mut mapped = Map::with_capacity(map.len());
+            for (key, value) in map {
+                let value = match value {
+                    Value::String(secret) if is_secret_setting(key) => Value::String(f(secret)?),
+                    other => map_secret_settings(other, f)?,
+                };
+                mapped.insert(key.clone(), value);
+            }
+            Value::Object(mapped)
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| map_secret_settings(value, f))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Data keys of one scope: a tenant, or the platform for records shared between tenants such as
+/// persons. Values are sealed with the active key and bound to the scope, so a value copied to
+/// another tenant's row does not decrypt there. Without keys (no master key configured) values
+/// are stored as given.
+#[derive(Debug, Clone)]
+pub struct FieldCipher {
+    scope: Option<Uuid>,
+    active_version: Option<i32>,
+    keys: HashMap<i32, DataKey>,
+}
+
+impl FieldCipher {
+    pub fn new(scope: Option<Uuid>, active_version: i32, keys: HashMap<i32, DataKey>) -> Self {
+        Self {
+            scope,
+            active_version: Some(active_version),
+            keys,
+        }
+    }
+
+    pub fn disabled(scope: Option<Uuid>) -> Self {
+        Self {
+            scope,
+            active_version: None,
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn scope(&self) -> Option<Uuid> {
+        self.scope
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.active_version.is_some()
+    }
+
+    pub fn active_version(&self) -> Option<i32> {
+        self.active_version
+    }
+
+    pub fn has_version(&self, version: i32) -> bool {
+        self.keys.contains_key(&version)
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        match self.scope {
+            Some(tenant_id) => tenant_id.as_bytes().to_vec(),
+            None => PLATFORM_AAD.to_vec(),
+        }
+    }
+
+    /// Encrypts `plaintext` with the active key. Values that are already sealed are kept, so
+    /// a client sending back what it read does not encrypt twice.
+    pub fn seal(&self, plaintext: &str) -> Result<String> {
+        let Some(version) = self.active_version else {
+            return Ok(plaintext.to_string());
+        };
+        if is_sealed(plaintext) {
+            return Ok(plaintext.to_string());
+        }
+        let key = self
+            .keys
+            .get(&version)
+            .ok_or_else(|| anyhow!("Data key version {} not loaded", version))?;
+        let sealed = encrypt(key, &self.aad(), plaintext.as_bytes())?;
+        Ok(format!(
+            "{}{}:{}",
+            SEALED_PREFIX,
+            version,
+            STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Decrypts a sealed value. Values stored before encryption was enabled pass through.
+    pub fn open(&self, value: &str) -> Result<String> {
+        if !is_sealed(value) {
+            return Ok(value.to_string());
+        }
+        let version = sealed_version(value).ok_or_else(|| anyhow!("Malformed encrypted value"))?;
+        let key = self.keys.get(&version).ok_or_else(|| {
+            anyhow!(
+                "Data key version {} is not available; is ENCRYPTION_MASTER_KEY set?",
+                version
+            )
+        })?;
+        let encoded = value
+            .rsplit_once(':')
+            .map(|(_, encoded)| encoded)
+            .unwrap_or_default();
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|_| anyhow!("Malformed encrypted value"))?;
+        let plaintext = decrypt(key, &self.aad(), &sealed)?;
+        String::from_utf8(plaintext).map_err(|_| anyhow!("Decrypted value is not UTF-8"))
+    }
+
+    /// Opens `value` and seals it again with the active key, e.g. after a rotation.
+    pub fn reseal(&self, value: &str) -> Result<String> {
+        if !self.enabled() {
+            return Ok(value.to_string());
+        }
+        let plaintext = self.open(value)?;
+        let key_version = sealed_version(value);
+        if key_version.is_some() && key_version == self.active_version {
+            return Ok(value.to_string());
+        }
+        self.seal(&plaintext)
+    }
+
+    pub fn seal_opt(&self, value: Option<String>) -> Result<Option<String>> {
+        value.map(|value| self.seal(&value)).transpose()
+    }
+
+    pub fn open_opt(&self, value: Option<String>) -> Result<Option<String>> {
+        value.map(|value| self.open(&value)).transpose()
+    }
+
+    /// Seals the secrets in tenant settings; see [`is_secret_setting`].
+    pub fn seal_settings(&self, settings: &Value) -> Result<Value> {
+        map_secret_settings(settings, &mut |secret| self.seal(secret))
+    }
+
+    /// Tenant settings with their secrets decrypted.
+    pub fn open_settings(&self, settings: &Value) -> Result<Value> {
+        map_secret_settings(settings, &mut |secret| self.open(secret))
+    }
+
+    pub fn reseal_settings(&self, settings: &Value) -> Result<Value> {
+        map_secret_settings(settings, &mut |secret| self.reseal(secret))
+    }
+}
@@ -3,9 +3,23 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use serde_json::json;
 use thiserror::Error;
 
+use crate::models::{AccessDenied, RuleViolations};
+use crate::services::{
+    AdminError, AssetError, AuthError, BillingError, BrandingError, CalendarError,
+    ConsignmentError, DuplicateError, IngestError, ItemError, ItemImageError, JobError, KioskError,
+    LifecycleError, MachineAlertError, MachineError, MachineGroupError, NumberingError, OrderError,
+    OrderReturnError, PersonError, PricingError, PrintError, QualityError, QuoteError,
+    RecalculationError, RfqError, SandboxError, SearchError, ShippingError, SkillError,
+    StatusPageError, StorageRequestFailed, StorageUrlError, TenantDatabaseUnreachable, UomError,
+    ValidationRuleError, WatchError,
+};
+use crate::utils::circuit_breaker::ServiceUnavailable;
+use crate::utils::i18n::{current_locale, Text};
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -28,24 +42,168 @@ pub enum AppError {
 
     #[error("Internal server error: {0}")]
     Internal(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Upstream error: {0}")]
+    Upstream(String),
+}
+
+impl AppError {
+    /// Maps a service error: access denials are 403s with their message, the services' typed
+    /// errors map as their own conversions say, and database errors are classified by
+    /// [`AppError::from_database`]
+    pub fn from_service(error: anyhow::Error) -> Self {
+        downcast::<AccessDenied>(error)
+            .or_else(downcast::<AdminError>)
+            .or_else(downcast::<AssetError>)
+            .or_else(downcast::<AuthError>)
+            .or_else(downcast::<BillingError>)
+            .or_else(downcast::<BrandingError>)
+            .or_else(downcast::<CalendarError>)
+            .or_else(downcast::<ConsignmentError>)
+            .or_else(downcast::<DuplicateError>)
+            .or_else(downcast::<IngestError>)
+            .or_else(downcast::<ItemError>)
+            .or_else(downcast::<ItemImageError>)
+            .or_else(downcast::<JobError>)
+            .or_else(downcast::<KioskError>)
+            .or_else(downcast::<LifecycleError>)
+            .or_else(downcast::<MachineAlertError>)
+            .or_else(downcast::<MachineError>)
+            .or_else(downcast::<MachineGroupError>)
+            .or_else(downcast::<NumberingError>)
+            .or_else(downcast::<OrderError>)
+            .or_else(downcast::<OrderReturnError>)
+            .or_else(downcast::<PersonError>)
+            .or_else(downcast::<PricingError>)
+            .or_else(downcast::<PrintError>)
+            .or_else(downcast::<QualityError>)
+            .or_else(downcast::<QuoteError>)
+            .or_else(downcast::<RecalculationError>)
+            .or_else(downcast::<RfqError>)
+            .or_else(downcast::<RuleViolations>)
+            .or_else(downcast::<SandboxError>)
+            .or_else(downcast::<SearchError>)
+            .or_else(downcast::<ServiceUnavailable>)
+            .or_else(downcast::<ShippingError>)
+            .or_else(downcast::<SkillError>)
+            .or_else(downcast::<StatusPageError>)
+            .or_else(downcast::<StorageRequestFailed>)
+            .or_else(downcast::<StorageUrlError>)
+            .or_else(downcast::<TenantDatabaseUnreachable>)
+            .or_else(downcast::<UomError>)
+            .or_else(downcast::<ValidationRuleError>)
+            .or_else(downcast::<WatchError>)
+            .unwrap_or_else(|error| match error.downcast::<DieselError>() {
+                Ok(error) => AppError::from_database(error),
+                Err(error) => AppError::Database(error),
+            })
+    }
+
+    /// Maps a database error: a missing row is a 404, a violated unique or foreign key a 409,
+    /// any other violated constraint a 400, and a row-level security refusal a 403
+    pub fn from_database(error: DieselError) -> Self {
+        match &error {
+            DieselError::NotFound => AppError::NotFound("Record not found".to_string()),
+            DieselError::DatabaseError(kind, info) => match kind {
+                DatabaseErrorKind::UniqueViolation | DatabaseErrorKind::ForeignKeyViolation => {
+                    AppError::Conflict(info.message().to_string())
+                }
+                DatabaseErrorKind::CheckViolation | DatabaseErrorKind::NotNullViolation => {
+                    AppError::Validation(info.message().to_string())
+                }
+                _ if info.message().contains("row-level security") => {
+                    AppError::Authorization(info.message().to_string())
+                }
+                _ => AppError::Database(error.into()),
+            },
+            _ => AppError::Database(error.into()),
+        }
+    }
+
+    /// Status code the error is answered with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
+            AppError::Authorization(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+// The typed error `E` if `error` carries one, as an `AppError`
+fn downcast<E>(error: anyhow::Error) -> std::result::Result<AppError, anyhow::Error>
+where
+    E: std::fmt::Display + std::fmt::Debug + Send + Sync + 'static,
+    AppError: From<E>,
+{
+    error.downcast::<E>().map(AppError::from)
+}
+
+impl From<AccessDenied> for AppError {
+    fn from(denied: AccessDenied) -> Self {
+        AppError::Authorization(denied.to_string())
+    }
+}
+
+impl From<RuleViolations> for AppError {
+    fn from(violations: RuleViolations) -> Self {
+        AppError::Validation(violations.to_string())
+    }
+}
+
+impl From<ServiceUnavailable> for AppError {
+    fn from(unavailable: ServiceUnavailable) -> Self {
+        AppError::Unavailable(unavailable.to_string())
+    }
+}
+
+impl From<StorageRequestFailed> for AppError {
+    fn from(failed: StorageRequestFailed) -> Self {
+        AppError::Upstream(failed.to_string())
+    }
+}
+
+impl From<TenantDatabaseUnreachable> for AppError {
+    fn from(unreachable: TenantDatabaseUnreachable) -> Self {
+        AppError::Upstream(unreachable.to_string())
+    }
+}
+
+impl From<DieselError> for AppError {
+    fn from(error: DieselError) -> Self {
+        AppError::from_database(error)
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+        // Messages from services are passed through as written; only the generic ones are ours
+        let internal = current_locale().text(Text::InternalServerError);
+        let status = self.status();
+        let error_message = match self {
             AppError::Database(ref err) => {
                 tracing::error!("Database error: {:?}", err);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                internal
             }
-            AppError::Validation(ref message) => (StatusCode::BAD_REQUEST, message.as_str()),
-            AppError::Authentication(ref message) => (StatusCode::UNAUTHORIZED, message.as_str()),
-            AppError::Authorization(ref message) => (StatusCode::FORBIDDEN, message.as_str()),
-            AppError::NotFound(ref message) => (StatusCode::NOT_FOUND, message.as_str()),
-            AppError::Conflict(ref message) => (StatusCode::CONFLICT, message.as_str()),
             AppError::Internal(ref message) => {
                 tracing::error!("Internal error: {}", message);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                internal
             }
+            AppError::Validation(ref message)
+            | AppError::Authentication(ref message)
+            | AppError::Authorization(ref message)
+            | AppError::NotFound(ref message)
+            | AppError::Conflict(ref message)
+            | AppError::Unavailable(ref message)
+            | AppError::Upstream(ref message) => message.as_str(),
         };
 
         let body = Json(json!({
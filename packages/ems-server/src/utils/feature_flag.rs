@@ -0,0 +1,40 @@
+// Feature flag evaluation
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::{FeatureFlag, FlagDecision};
+
+/// Bucket from 0 to 99 a tenant falls in for a flag. Buckets come from hashing the flag key
+/// with the tenant, so each flag rolls out to its own set of tenants, and a tenant inside a
+/// 10% rollout stays inside when it grows to 20%.
+pub fn rollout_bucket(key: &str, tenant_id: Uuid) -> u32 {
+    let digest = Sha256::new()
+        .chain_update(key.as_bytes())
+        .chain_update(b":")
+        .chain_update(tenant_id.as_bytes())
+        .finalize();
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 100
+}
+
+/// Whether `flag` is on for a tenant with the given override, and which rule decided it.
+/// Unknown flags are off, so code checking a flag that was never created keeps the old path.
+pub fn evaluate_flag(
+    flag: Option<&FeatureFlag>,
+    tenant_override: Option<bool>,
+    tenant_id: Uuid,
+) -> (bool, FlagDecision) {
+    let Some(flag) = flag else {
+        return (false, FlagDecision::Unknown);
+    };
+    if !flag.enabled {
+        return (false, FlagDecision::Disabled);
+    }
+    if let Some(enabled) = tenant_override {
+        return (enabled, FlagDecision::Override);
+    }
+    let percentage = flag.rollout_percentage.clamp(0, 100) as u32;
+    (
+        rollout_bucket(&flag.key, tenant_id) < percentage,
+        FlagDecision::Rollout,
+    )
+}
@@ -0,0 +1,183 @@
+// Client fingerprints binding refresh tokens to the client they were issued to
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, HeaderMap},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::convert::Infallible;
+use std::env;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::models::{ClientFingerprint, FingerprintMode};
+
+// Hex characters kept from each keyed hash; enough to tell clients apart, too few to be useful
+// outside the token
+const FINGERPRINT_HEX_CHARS: usize = 16;
+// Longest user agent kept for the audit trail
+const MAX_USER_AGENT_CHARS: usize = 512;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// User agent and address of the client making a request. The address is the last
+/// X-Forwarded-For entry, the one added by the proxy in front of the server, or else the peer
+/// address of the connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<IpAddr>,
+}
+
+impl ClientInfo {
+    pub fn from_parts(headers: &HeaderMap, peer: Option<SocketAddr>) -> Self {
+        let user_agent = headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|agent| !agent.is_empty())
+            .map(|agent| agent.chars().take(MAX_USER_AGENT_CHARS).collect());
+        let forwarded = headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        Self {
+            user_agent,
+            ip: forwarded.or(peer.map(|peer| peer.ip())),
+        }
+    }
+
+    /// Network the client is on: the /24 of an IPv4 address or the /48 of an IPv6 address, so
+    /// a tablet moving between addresses on the same network keeps its fingerprint.
+    pub fn network_prefix(&self) -> Option<String> {
+        let ip = match self.ip? {
+            IpAddr::V6(ip) => ip
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(ip)),
+            ip => ip,
+        };
+        Some(match ip {
+            IpAddr::V4(ip) => {
+                let [a, b, c, _] = ip.octets();
+                format!("{}.{}.{}.0/24", a, b, c)
+            }
+            IpAddr::V6(ip) => {
+                let s = ip.segments();
+                format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2])
+            }
+        })
+    }
+
+    /// Fingerprint stored in refresh tokens. Its parts are keyed with `secret`, so the user
+    /// agent and network cannot be read back from a token, nor a matching one made up.
+    pub fn fingerprint(&self, secret: &str) -> ClientFingerprint {
+        ClientFingerprint {
+            ua: self
+                .user_agent
+                .as_deref()
+                .map(|agent| keyed_hash(secret, "ua", agent)),
+            net: self
+                .network_prefix()
+                .map(|prefix| keyed_hash(secret, "net", &prefix)),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| *peer);
+        Ok(Self::from_parts(&parts.headers, peer))
+    }
+}
+
+fn keyed_hash(secret: &str, part: &str, value: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(part.as_bytes());
+    mac.update(b":");
+    mac.update(value.to_lowercase().as_bytes());
+    let hash = format!("{:x}", mac.finalize().into_bytes());
+    hash[..FINGERPRINT_HEX_CHARS].to_string()
+}
+
+/// How the client refreshing a token compares to the one it was issued to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintMatch {
+    /// Every part known on both sides is unchanged
+    Same,
+    /// Either the user agent or the network changed
+    Partial,
+    /// Both the user agent and the network changed
+    Different,
+    /// Nothing to compare, e.g. a token issued before fingerprints were recorded
+    Unknown,
+}
+
+impl FingerprintMatch {
+    pub fn compare(issued: &ClientFingerprint, current: &ClientFingerprint) -> Self {
+        let compared = [(&issued.ua, &current.ua), (&issued.net, &current.net)]
+            .iter()
+            .filter(|(issued, current)| issued.is_some() && current.is_some())
+            .count();
+        match (compared, Self::changed_parts(issued, current).len()) {
+            (0, _) => FingerprintMatch::Unknown,
+            (_, 0) => FingerprintMatch::Same,
+            (2, 2) => FingerprintMatch::Different,
+            _ => FingerprintMatch::Partial,
+        }
+    }
+
+    /// Names of the parts that changed: `user_agent` and/or `network`
+    pub fn changed_parts(
+        issued: &ClientFingerprint,
+        current: &ClientFingerprint,
+    ) -> Vec<&'static str> {
+        [
+            ("user_agent", &issued.ua, &current.ua),
+            ("network", &issued.net, &current.net),
+        ]
+        .into_iter()
+        .filter(|(_, issued, current)| issued.is_some() && current.is_some() && issued != current)
+        .map(|(part, _, _)| part)
+        .collect()
+    }
+
+    /// Whether the change is recorded in the audit trail
+    pub fn is_anomaly(&self) -> bool {
+        matches!(
+            self,
+            FingerprintMatch::Partial | FingerprintMatch::Different
+        )
+    }
+}
+
+impl FingerprintMode {
+    /// Mode from REFRESH_FINGERPRINT_MODE; audit when not set.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("REFRESH_FINGERPRINT_MODE") {
+            Ok(mode) => FingerprintMode::try_from(mode.trim().to_lowercase())
+                .map_err(|e| anyhow::anyhow!(e)),
+            Err(_) => Ok(FingerprintMode::Audit),
+        }
+    }
+
+    /// Whether a refresh from a client that compares as `result` is refused.
+    pub fn rejects(&self, result: FingerprintMatch) -> bool {
+        match self {
+            FingerprintMode::Off | FingerprintMode::Audit => false,
+            FingerprintMode::Lenient => result == FingerprintMatch::Different,
+            FingerprintMode::Strict => result.is_anomaly(),
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// Binary deltas between firmware images, so devices on slow links download only what changed
+//
+// Matching follows bsdiff: a suffix array of the old image finds long matches, and each match is
+// extended forwards and backwards over bytes that mostly agree. Compiled code moved by a few
+// bytes differs only in addresses, so the bytes added to the old image are mostly zero. Without
+// a compressor, runs of zero bytes are stored as their length instead.
+//
+// Layout, integers little-endian and lengths as LEB128 varints:
+//   "EMSDELT1", target size (u64), SHA-256 of the old and of the new image (32 bytes each)
+//   records until the new image is complete:
+//     add length, copy length, seek (zigzag)
+//     add bytes, added to the old image byte by byte: (zero run, literal length, literals)...
+//     copy bytes, taken as they are
+//   after a record the old image is read `seek` bytes further on
+
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+const MAGIC: &[u8; 8] = b"EMSDELT1";
+
+/// Images larger than this are always sent whole; the suffix array takes 12 bytes per byte
+pub const MAX_DELTA_INPUT_BYTES: usize = 16 * 1024 * 1024;
+
+// Zero runs shorter than this stay among the literals, where they cost less than a new segment
+const MIN_ZERO_RUN: usize = 4;
+
+// A match replaces the current alignment only when it is this many bytes longer
+const MATCH_MARGIN: usize = 8;
+
+/// Where the delta from the image with SHA-256 `source` to the one with `target` is stored.
+pub fn delta_storage_path(tenant_id: Uuid, source: &str, target: &str) -> String {
+    format!("{}/deltas/{}-{}.delta", tenant_id, source, target)
+}
+
+/// Delta turning `source` into `target`, applied with [`apply_delta`].
+pub fn generate_delta(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let suffixes = suffix_array(source);
+    let mut out = Vec::with_capacity(target.len() / 8 + 80);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&(target.len() as u64).to_le_bytes());
+    out.extend_from_slice(&Sha256::digest(source));
+    out.extend_from_slice(&Sha256::digest(target));
+
+    let (mut scan, mut len, mut pos) = (0usize, 0usize, 0usize);
+    let (mut last_scan, mut last_pos, mut last_offset) = (0usize, 0usize, 0isize);
+    while scan < target.len() {
+        // Look for a match clearly better than carrying on with the current alignment
+        let mut old_score = 0isize;
+        scan += len;
+        let mut scored = scan;
+        while scan < target.len() {
+            (pos, len) = longest_match(source, &suffixes, &target[scan..]);
+            while scored < scan + len {
+                if aligned(source, target, scored, last_offset) {
+                    old_score += 1;
+                }
+                scored += 1;
+            }
+            if (len as isize == old_score && len != 0)
+                || len as isize > old_score + MATCH_MARGIN as isize
+            {
+                break;
+            }
+            if aligned(source, target, scan, last_offset) {
+                old_score -= 1;
+            }
+            scan += 1;
+        }
+        if len as isize == old_score && scan != target.len() {
+            continue;
+        }
+
+        // Extend the previous match forwards while at least half the bytes agree
+        let (mut score, mut best, mut forward) = (0isize, 0isize, 0usize);
+        let mut i = 0;
+        while last_scan + i < scan && last_pos + i < source.len() {
+            if source[last_pos + i] == target[last_scan + i] {
+                score += 1;
+            }
+            i += 1;
+            if score * 2 - i as isize > best * 2 - forward as isize {
+                best = score;
+                forward = i;
+            }
+        }
+
+        // and the new match backwards
+        let mut backward = 0usize;
+        if scan < target.len() {
+            let (mut score, mut best) = (0isize, 0isize);
+            let mut i = 1;
+            while scan >= last_scan + i && pos >= i {
+                if source[pos - i] == target[scan - i] {
+                    score += 1;
+                }
+                if score * 2 - i as isize > best * 2 - backward as isize {
+                    best = score;
+                    backward = i;
+                }
+                i += 1;
+            }
+        }
+
+        // Where the extensions overlap, split them where the bytes agree best
+        if last_scan + forward > scan - backward {
+            let overlap = (last_scan + forward) - (scan - backward);
+            let (mut score, mut best, mut split) = (0isize, 0isize, 0usize);
+            for i in 0..overlap {
+                if target[last_scan + forward - overlap + i]
+                    == source[last_pos + forward - overlap + i]
+                {
+                    score += 1;
+                }
+                if target[scan - backward + i] == source[pos - backward + i] {
+                    score -= 1;
+                }
+                if score > best {
+                    best = score;
+                    split = i + 1;
+                }
+            }
+            forward = forward + split - overlap;
+            backward -= split;
+        }
+
+        let added: Vec<u8> = (0..forward)
+            .map(|i| target[last_scan + i].wrapping_sub(source[last_pos + i]))
+            .collect();
+        let copied = &target[last_scan + forward..scan - backward];
+        let seek = (pos - backward) as i64 - (last_pos + forward) as i64;
+        write_varint(&mut out, added.len() as u64);
+        write_varint(&mut out, copied.len() as u64);
+        write_varint(&mut out, ((seek << 1) ^ (seek >> 63)) as u64);
+        write_added(&mut out, &added);
+        out.extend_from_slice(copied);
+
+        last_scan = scan - backward;
+        last_pos = pos - backward;
+        last_offset = pos as isize - scan as isize;
+    }
+    out
+}
+
+/// The image a delta from [`generate_delta`] turns `source` into. Fails when the delta is
+/// malformed or was made from another image, and when the result does not match the checksum
+/// recorded in the delta.
+pub fn apply_delta(source: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = Reader {
+        data: delta,
+        pos: 0,
+    };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err("Invalid delta: unknown format".to_string());
+    }
+    let size = u64::from_le_bytes(reader.take(8)?.try_into().unwrap_or_default()) as usize;
+    let source_checksum = reader.take(32)?;
+    let target_checksum = reader.take(32)?;
+    if Sha256::digest(source).as_slice() != source_checksum {
+        return Err("Invalid delta: made from another image".to_string());
+    }
+
+    let mut target = Vec::with_capacity(size.min(delta.len().saturating_mul(64)));
+    let mut source_pos = 0i64;
+    while target.len() < size {
+        let add = reader.varint()? as usize;
+        let copy = reader.varint()? as usize;
+        let seek = reader.varint()?;
+        let seek = (seek >> 1) as i64 ^ -((seek & 1) as i64);
+        if add > size - target.len() || copy > size - target.len() - add {
+            return Err("Invalid delta: record runs past the end of the image".to_string());
+        }
+        if source_pos < 0 || source_pos as usize + add > source.len() {
+            return Err("Invalid delta: record reads outside the old image".to_string());
+        }
+
+        let start = target.len();
+        target.extend_from_slice(&source[source_pos as usize..source_pos as usize + add]);
+        let mut filled = 0;
+        while filled < add {
+            let zeros = reader.varint()? as usize;
+            let literals = reader.varint()? as usize;
+            if zeros + literals == 0 || zeros + literals > add - filled {
+                return Err("Invalid delta: malformed add bytes".to_string());
+            }
+            filled += zeros;
+            for (byte, diff) in target[start + filled..start + filled + literals]
+                .iter_mut()
+                .zip(reader.take(literals)?)
+            {
+                *byte = byte.wrapping_add(*diff);
+            }
+            filled += literals;
+        }
+        target.extend_from_slice(reader.take(copy)?);
+        source_pos += add as i64 + seek;
+    }
+
+    if reader.pos != delta.len() {
+        return Err("Invalid delta: trailing data".to_string());
+    }
+    if Sha256::digest(&target).as_slice() != target_checksum {
+        return Err("Invalid delta: checksum mismatch".to_string());
+    }
+    Ok(target)
+}
+
+// Start of every suffix of `data` in sorted order, by prefix doubling
+fn suffix_array(data: &[u8]) -> Vec<u32> {
+    let n = data.len();
+    let mut suffixes: Vec<u32> = (0..n as u32).collect();
+    if n < 2 {
+        return suffixes;
+    }
+    let mut rank: Vec<u32> = data.iter().map(|&b| b as u32).collect();
+    let mut next = vec![0u32; n];
+    let mut k = 1;
+    loop {
+        let key = |i: u32| {
+            let i = i as usize;
+            let second = if i + k < n { rank[i + k] as u64 + 1 } else { 0 };
+            ((rank[i] as u64) << 32) | second
+        };
+        suffixes.sort_unstable_by_key(|&i| key(i));
+        next[suffixes[0] as usize] = 0;
+        for w in 1..n {
+            let step = u32::from(key(suffixes[w - 1]) < key(suffixes[w]));
+            next[suffixes[w] as usize] = next[suffixes[w - 1] as usize] + step;
+        }
+        std::mem::swap(&mut rank, &mut next);
+        if rank[suffixes[n - 1] as usize] as usize == n - 1 {
+            break;
+        }
+        k *= 2;
+    }
+    suffixes
+}
+
+// Position in `source` and length of the longest match for the start of `target`
+fn longest_match(source: &[u8], suffixes: &[u32], target: &[u8]) -> (usize, usize) {
+    if suffixes.is_empty() {
+        return (0, 0);
+    }
+    let (mut low, mut high) = (0, suffixes.len() - 1);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let suffix = &source[suffixes[mid] as usize..];
+        let n = suffix.len().min(target.len());
+        if suffix[..n] < target[..n] {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    [low, high]
+        .into_iter()
+        .map(|i| {
+            let start = suffixes[i] as usize;
+            let len = source[start..]
+                .iter()
+                .zip(target)
+                .take_while(|(a, b)| a == b)
+                .count();
+            (start, len)
+        })
+        .max_by_key(|&(_, len)| len)
+        .unwrap_or((0, 0))
+}
+
+// Whether target byte `i` equals the old image byte at the current alignment
+fn aligned(source: &[u8], target: &[u8], i: usize, offset: isize) -> bool {
+    let s = i as isize + offset;
+    s >= 0 && (s as usize) < source.len() && source[s as usize] == target[i]
+}
+
+fn write_added(out: &mut Vec<u8>, added: &[u8]) {
+    let mut i = 0;
+    while i < added.len() {
+        let zeros = added[i..].iter().take_while(|&&b| b == 0).count();
+        let start = i + zeros;
+        let mut end = start;
+        while end < added.len() && !added[end..].iter().take(MIN_ZERO_RUN).all(|&b| b == 0) {
+            end += 1;
+        }
+        write_varint(out, zeros as u64);
+        write_varint(out, (end - start) as u64);
+        out.extend_from_slice(&added[start..end]);
+        i = end;
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.data.len() - self.pos {
+            return Err("Invalid delta: truncated".to_string());
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("Invalid delta: malformed length".to_string())
+    }
+}
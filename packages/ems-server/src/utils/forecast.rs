@@ -0,0 +1,108 @@
+// Demand forecasting helpers operating on a series of per-period quantities (oldest first)
+
+// Days per month used when converting lead times into forecast periods
+pub const DAYS_PER_MONTH: f64 = 30.0;
+
+/// Mean of the last `window` periods (or all periods when fewer are available).
+pub fn moving_average(history: &[f64], window: usize) -> f64 {
+    let window = window.max(1).min(history.len());
+    if window == 0 {
+        return 0.0;
+    }
+    history[history.len() - window..].iter().sum::<f64>() / window as f64
+}
+
+/// Simple exponential smoothing level after the last period, seeded with the first observation.
+pub fn exponential_smoothing(history: &[f64], alpha: f64) -> f64 {
+    let mut values = history.iter();
+    let mut level = match values.next() {
+        Some(first) => *first,
+        None => return 0.0,
+    };
+    for value in values {
+        level = alpha * value + (1.0 - alpha) * level;
+    }
+    level
+}
+
+/// Sample standard deviation of the series (0 for fewer than two periods).
+pub fn standard_deviation(history: &[f64]) -> f64 {
+    if history.len() < 2 {
+        return 0.0;
+    }
+    let mean = history.iter().sum::<f64>() / history.len() as f64;
+    let variance =
+        history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (history.len() - 1) as f64;
+    variance.sqrt()
+}
+
+/// Standard normal quantile for a service level in (0, 1), using Acklam's rational approximation.
+pub fn service_level_z(service_level: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let p = service_level.clamp(1e-9, 1.0 - 1e-9);
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    }
+}
+
+/// Safety stock covering demand variability over the lead time: z * sigma * sqrt(lead time in periods).
+pub fn safety_stock(service_level: f64, monthly_std_dev: f64, lead_time_days: u32) -> f64 {
+    let lead_time_months = lead_time_days as f64 / DAYS_PER_MONTH;
+    (service_level_z(service_level) * monthly_std_dev * lead_time_months.sqrt()).max(0.0)
+}
+
+/// Mean stock level over consecutive periods, given the closing balance and each period's net
+/// change (oldest first). Balances are walked back from the close; the opening balance and every
+/// period-end balance count once.
+pub fn average_balance(closing: f64, net_changes: &[f64]) -> f64 {
+    let mut balance = closing;
+    let mut total = closing;
+    for change in net_changes.iter().rev() {
+        balance -= change;
+        total += balance;
+    }
+    total / (net_changes.len() + 1) as f64
+}
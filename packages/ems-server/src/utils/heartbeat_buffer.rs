@@ -0,0 +1,131 @@
+// Heartbeat write buffering helpers
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{HeartbeatRequest, NewMachineTelemetry};
+use crate::utils::machine_command::backoff_factor;
+
+/// Machine state from a machine's heartbeat: written to the machine when the buffer flushes, and
+/// fed to SPC, production counts and alert rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferedMachineState {
+    pub machine_id: Uuid,
+    pub status: String,
+    pub action: Option<String>,
+    pub payload: Option<Value>,
+    pub metadata: Option<Value>,
+    pub last_heartbeat: DateTime<Utc>,
+}
+
+impl BufferedMachineState {
+    pub fn new(machine_id: Uuid, request: &HeartbeatRequest, received_at: DateTime<Utc>) -> Self {
+        Self {
+            machine_id,
+            status: request.status.to_string(),
+            action: request.action.as_ref().map(|a| a.to_string()),
+            payload: request.payload.clone(),
+            metadata: request.metadata.clone(),
+            last_heartbeat: received_at,
+        }
+    }
+}
+
+/// What a flush writes for one tenant.
+#[derive(Debug, Default)]
+pub struct HeartbeatBatch {
+    /// Each machine's latest state
+    pub states: Vec<BufferedMachineState>,
+    /// Every heartbeat, in the order received, for SPC, production counts and alert rules
+    pub heartbeats: Vec<BufferedMachineState>,
+    pub readings: Vec<NewMachineTelemetry>,
+}
+
+/// One tenant's heartbeat writes waiting for the next flush, bounded to `capacity` entries.
+/// Each machine keeps only its latest state, so a machine reporting again before the flush
+/// takes no more room for it; every heartbeat queued for processing and every telemetry reading
+/// takes one entry.
+#[derive(Debug)]
+pub struct HeartbeatQueue {
+    states: HashMap<Uuid, BufferedMachineState>,
+    heartbeats: Vec<BufferedMachineState>,
+    readings: Vec<NewMachineTelemetry>,
+    capacity: usize,
+}
+
+impl HeartbeatQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            states: HashMap::new(),
+            heartbeats: Vec::new(),
+            readings: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Entries waiting: machine states, heartbeats and telemetry readings.
+    pub fn len(&self) -> usize {
+        self.states.len() + self.heartbeats.len() + self.readings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty() && self.heartbeats.is_empty() && self.readings.is_empty()
+    }
+
+    /// Queues the machine's state unless the queue is full. A state older than the one already
+    /// queued for the machine is dropped, since the flush would overwrite it anyway.
+    pub fn push_state(&mut self, state: BufferedMachineState) -> bool {
+        let full = self.len() >= self.capacity;
+        match self.states.get_mut(&state.machine_id) {
+            Some(queued) => {
+                if queued.last_heartbeat <= state.last_heartbeat {
+                    *queued = state;
+                }
+                true
+            }
+            None if !full => {
+                self.states.insert(state.machine_id, state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Queues a heartbeat for SPC, production counts and alert rules unless the queue is full.
+    pub fn push_heartbeat(&mut self, heartbeat: BufferedMachineState) -> bool {
+        if self.len() >= self.capacity {
+            return false;
+        }
+        self.heartbeats.push(heartbeat);
+        true
+    }
+
+    /// Queues the readings if they all fit, otherwise hands them back.
+    pub fn push_readings(
+        &mut self,
+        readings: Vec<NewMachineTelemetry>,
+    ) -> Result<(), Vec<NewMachineTelemetry>> {
+        if self.len() + readings.len() > self.capacity {
+            return Err(readings);
+        }
+        self.readings.extend(readings);
+        Ok(())
+    }
+
+    /// How many times longer the tenant's machines should wait between heartbeats while the
+    /// queue fills: 1 below three quarters, 2 from there and 4 once it is full.
+    pub fn backoff_factor(&self) -> i32 {
+        let saturate = |n: usize| u32::try_from(n).unwrap_or(u32::MAX);
+        backoff_factor(saturate(self.len()), saturate(self.capacity))
+    }
+
+    /// Empties the queue, returning what to write.
+    pub fn take(&mut self) -> HeartbeatBatch {
+        HeartbeatBatch {
+            states: self.states.drain().map(|(_, state)| state).collect(),
+            heartbeats: std::mem::take(&mut self.heartbeats),
+            readings: std::mem::take(&mut self.readings),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// Locales for server-generated text: API error messages, customer documents and vendor requests
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+
+tokio::task_local! {
+    // Locale negotiated for the request the current task is serving
+    static REQUEST_LOCALE: Locale;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "es")]
+    Es,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Es];
+
+    /// The locale for a BCP 47 language tag, matched on its primary language so that `es-MX`
+    /// and `ES` both give Spanish.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?;
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.to_string().eq_ignore_ascii_case(language))
+    }
+
+    /// Translated `text`, with each `{}` in it replaced by the next of `args`.
+    pub fn format(self, text: Text, args: &[&str]) -> String {
+        let mut args = args.iter();
+        let mut parts = self.text(text).split("{}");
+        let mut formatted = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            formatted.push_str(args.next().copied().unwrap_or_default());
+            formatted.push_str(part);
+        }
+        formatted
+    }
+
+    /// Calendar date as written in the locale.
+    pub fn date(self, date: DateTime<Utc>) -> String {
+        match self {
+            Locale::En => date.format("%Y-%m-%d").to_string(),
+            Locale::Es => date.format("%d/%m/%Y").to_string(),
+        }
+    }
+
+    pub fn text(self, text: Text) -> &'static str {
+        use Text::*;
+
+        match self {
+            Locale::En => match text {
+                InternalServerError => "Internal server error",
+                ValidationFailed => "Validation failed",
+                Length => "length {}",
+                Exactly => "must be exactly {}",
+                Between => "must be between {} and {}",
+                AtLeast => "must be at least {}",
+                AtMost => "must be at most {}",
+                OutOfRange => "is out of range",
+                InvalidEmail => "must be a valid email address",
+                InvalidUrl => "must be a valid URL",
+                InvalidFormat => "has an invalid format",
+                Required => "is required",
+                Invalid => "is invalid ({})",
+                QuoteTitle => "Quote {}",
+                OrderConfirmationTitle => "Order confirmation {}",
+                Customer => "Customer: {}",
+                Date => "Date: {}",
+                ValidUntil => "Valid until: {}",
+                Item => "Item",
+                Quantity => "Qty",
+                UnitPrice => "Unit price",
+                Amount => "Amount",
+                Delivery => "Delivery",
+                Total => "Total",
+                TotalAmount => "Total: {}",
+                Greeting => "Dear {},",
+                QuoteAttached => "Please find quote {} attached.",
+                OrderConfirmationAttached => {
+                    "Thank you for your order. Please find order confirmation {} attached."
+                }
+                RfqTitle => "Request for quote {}",
+                RfqRequested => "Please quote your unit price and lead time for the items below.",
+                RespondBy => "Please respond by: {}",
+                RfqRespondAt => "Submit your quote at: {}",
+                RfqResponseCode => "Response code: {}",
+            },
+            Locale::Es => match text {
+                InternalServerError => "Error interno del servidor",
+                ValidationFailed => "La validación falló",
+                Length => "longitud {}",
+                Exactly => "debe ser exactamente {}",
+                Between => "debe estar entre {} y {}",
+                AtLeast => "debe ser al menos {}",
+                AtMost => "debe ser como máximo {}",
+                OutOfRange => "está fuera de rango",
+                InvalidEmail => "debe ser un correo electrónico válido",
+                InvalidUrl => "debe ser una URL válida",
+                InvalidFormat => "tiene un formato no válido",
+                Required => "es obligatorio",
+                Invalid => "no es válido ({})",
+                QuoteTitle => "Cotización {}",
+                OrderConfirmationTitle => "Confirmación de pedido {}",
+                Customer => "Cliente: {}",
+                Date => "Fecha: {}",
+                ValidUntil => "Válida hasta: {}",
+                Item => "Artículo",
+                Quantity => "Cant.",
+                UnitPrice => "Precio unitario",
+                Amount => "Importe",
+                Delivery => "Entrega",
+                Total => "Total",
+                TotalAmount => "Total: {}",
+                Greeting => "Estimado/a {}:",
+                QuoteAttached => "Adjuntamos la cotización {}.",
+                OrderConfirmationAttached => {
+                    "Gracias por su pedido. Adjuntamos la confirmación de pedido {}."
+                }
+                RfqTitle => "Solicitud de cotización {}",
+                RfqRequested => {
+                    "Le solicitamos su precio unitario y plazo de entrega para los artículos siguientes."
+                }
+                RespondBy => "Responda antes del: {}",
+                RfqRespondAt => "Envíe su cotización en: {}",
+                RfqResponseCode => "Código de respuesta: {}",
+            },
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::Es => write!(f, "es"),
+        }
+    }
+}
+
+impl From<Locale> for String {
+    fn from(locale: Locale) -> Self {
+        locale.to_string()
+    }
+}
+
+impl TryFrom<String> for Locale {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_tag(&value).ok_or_else(|| format!("Unsupported locale: {}", value))
+    }
+}
+
+/// Server-generated strings with a translation in every [`Locale`]. `{}` marks where
+/// [`Locale::format`] puts its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    // API errors
+    InternalServerError,
+    ValidationFailed,
+    // Field validation messages
+    Length,
+    Exactly,
+    Between,
+    AtLeast,
+    AtMost,
+    OutOfRange,
+    InvalidEmail,
+    InvalidUrl,
+    InvalidFormat,
+    Required,
+    Invalid,
+    // Customer documents
+    QuoteTitle,
+    OrderConfirmationTitle,
+    Customer,
+    Date,
+    ValidUntil,
+    Item,
+    Quantity,
+    UnitPrice,
+    Amount,
+    Delivery,
+    Total,
+    TotalAmount,
+    Greeting,
+    QuoteAttached,
+    OrderConfirmationAttached,
+    // Vendor requests
+    RfqTitle,
+    RfqRequested,
+    RespondBy,
+    RfqRespondAt,
+    RfqResponseCode,
+}
+
+/// Picks the supported locale the client prefers most from an Accept-Language header, e.g.
+/// `es-MX,es;q=0.9,en;q=0.8`. Without a header, or when it names nothing supported, the
+/// `fallback` (usually the tenant's locale) is used.
+pub fn negotiate(accept_language: Option<&str>, fallback: Locale) -> Locale {
+    let mut preferences: Vec<(&str, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so tags of equal quality keep the client's order
+    preferences.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    preferences
+        .into_iter()
+        .find_map(|(tag, _)| match tag {
+            "*" => Some(fallback),
+            tag => Locale::from_tag(tag),
+        })
+        .unwrap_or(fallback)
+}
+
+/// The `locale` a tenant's settings name, if it is supported.
+pub fn settings_locale(settings: Option<&serde_json::Value>) -> Option<Locale> {
+    settings?.get("locale")?.as_str().and_then(Locale::from_tag)
+}
+
+/// Checks the `locale` in tenant settings, which may be left out but must be supported when
+/// given.
+pub fn check_settings_locale(settings: Option<&serde_json::Value>) -> Result<(), String> {
+    match settings.and_then(|settings| settings.get("locale")) {
+        None | Some(serde_json::Value::Null) => Ok(()),
+        Some(locale) => match locale.as_str().and_then(Locale::from_tag) {
+            Some(_) => Ok(()),
+            None => Err(format!("Unsupported locale: {}", locale)),
+        },
+    }
+}
+
+/// Runs `future` with [`current_locale`] giving `locale`.
+pub async fn scope_locale<F: Future>(locale: Locale, future: F) -> F::Output {
+    REQUEST_LOCALE.scope(locale, future).await
+}
+
+/// Locale of the request being served; English outside of a request.
+pub fn current_locale() -> Locale {
+    REQUEST_LOCALE
+        .try_with(|locale| *locale)
+        .unwrap_or_default()
+}
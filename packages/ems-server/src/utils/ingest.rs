@@ -0,0 +1,153 @@
+// Ingest helpers: payload field checks and transformation templates
+use serde_json::{Map, Value};
+
+use crate::models::IngestFieldType;
+
+/// A field the payload of an ingest source is checked for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRule {
+    pub pointer: String,
+    pub field_type: IngestFieldType,
+    pub required: bool,
+}
+
+/// Reads a field schema: an object mapping JSON pointers into the payload to the type expected
+/// there, e.g. `{"/order/number": "string", "/order/note": "string?"}`. A trailing `?` makes the
+/// field optional; it is only checked when present.
+pub fn parse_field_schema(schema: &Value) -> Result<Vec<FieldRule>, String> {
+    let fields = schema
+        .as_object()
+        .ok_or_else(|| "field_schema must be an object".to_string())?;
+
+    fields
+        .iter()
+        .map(|(pointer, expected)| {
+            if !pointer.starts_with('/') {
+                return Err(format!("{} is not a JSON pointer", pointer));
+            }
+            let expected = expected
+                .as_str()
+                .ok_or_else(|| format!("Type of {} must be a string", pointer))?;
+            let (name, required) = match expected.strip_suffix('?') {
+                Some(name) => (name, false),
+                None => (expected, true),
+            };
+            let field_type = IngestFieldType::try_from(name.to_string())?;
+            Ok(FieldRule {
+                pointer: pointer.clone(),
+                field_type,
+                required,
+            })
+        })
+        .collect()
+}
+
+/// Problems with the payload under the rules, one per field; empty when the payload matches.
+/// A null value counts as missing.
+pub fn check_fields(payload: &Value, rules: &[FieldRule]) -> Vec<String> {
+    rules
+        .iter()
+        .filter_map(|rule| match payload.pointer(&rule.pointer) {
+            None | Some(Value::Null) if rule.required => {
+                Some(format!("{} is missing", rule.pointer))
+            }
+            None | Some(Value::Null) => None,
+            Some(value) if !rule.field_type.matches(value) => {
+                Some(format!("{} must be {}", rule.pointer, rule.field_type))
+            }
+            Some(_) => None,
+        })
+        .collect()
+}
+
+/// Checks a transformation template: an object whose `{{/pointer}}` placeholders all hold JSON
+/// pointers.
+pub fn check_template(template: &Value) -> Result<(), String> {
+    if !template.is_object() {
+        return Err("template must be an object".to_string());
+    }
+
+    fn check(value: &Value) -> Result<(), String> {
+        match value {
+            Value::String(text) => placeholders(text).into_iter().try_for_each(|(_, pointer)| {
+                if pointer.is_empty() || pointer.starts_with('/') {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Placeholder {{{{{}}}}} is not a JSON pointer",
+                        pointer
+                    ))
+                }
+            }),
+            Value::Array(values) => values.iter().try_for_each(check),
+            Value::Object(fields) => fields.values().try_for_each(check),
+            _ => Ok(()),
+        }
+    }
+    check(template)
+}
+
+/// Fills a template from the payload. A string that is a single `{{/pointer}}` placeholder is
+/// replaced by the value at the pointer, keeping its type (null when it is missing); placeholders
+/// inside longer strings are replaced by the value as text. `{{}}` stands for the whole payload.
+/// Everything else is copied as is.
+pub fn render_template(template: &Value, payload: &Value) -> Value {
+    match template {
+        Value::String(text) => {
+            let found = placeholders(text);
+            match found.as_slice() {
+                [] => template.clone(),
+                [(range, pointer)] if range.start == 0 && range.end == text.len() => {
+                    payload.pointer(pointer).cloned().unwrap_or(Value::Null)
+                }
+                _ => {
+                    let mut rendered = String::with_capacity(text.len());
+                    let mut copied = 0;
+                    for (range, pointer) in found {
+                        rendered.push_str(&text[copied..range.start]);
+                        rendered.push_str(&as_text(payload.pointer(&pointer)));
+                        copied = range.end;
+                    }
+                    rendered.push_str(&text[copied..]);
+                    Value::String(rendered)
+                }
+            }
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| render_template(value, payload))
+                .collect(),
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), render_template(value, payload)))
+                .collect::<Map<String, Value>>(),
+        ),
+        _ => template.clone(),
+    }
+}
+
+// Byte ranges of the `{{...}}` placeholders in the text, with the pointers they hold
+fn placeholders(text: &str) -> Vec<(std::ops::Range<usize>, String)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{{").map(|i| from + i) {
+        let Some(end) = text[start + 2..].find("}}").map(|i| start + 2 + i) else {
+            break;
+        };
+        found.push((start..end + 2, text[start + 2..end].trim().to_string()));
+        from = end + 2;
+    }
+    found
+}
+
+// A value as it reads inside text: strings without quotes, and nothing for a missing value
+fn as_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(other) => other.to_string(),
+    }
+}
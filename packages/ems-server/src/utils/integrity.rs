@@ -0,0 +1,171 @@
+// Cross-entity reference integrity checks
+use crate::models::IntegrityRepair;
+
+/// Rows of each dangling reference listed in a report
+pub const INTEGRITY_SAMPLE_SIZE: i64 = 20;
+
+/// A reference from one table to another that must point at an existing row. Foreign keys keep
+/// most of these intact, but not after manual interventions that bypass them (or on columns that
+/// never had one), so each is checked by query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegrityCheck {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub references: &'static str,
+    pub description: &'static str,
+    /// How rows with a dangling reference are repaired
+    pub repair: IntegrityRepair,
+}
+
+impl IntegrityCheck {
+    /// Name the check is selected by, `table.column`
+    pub fn name(&self) -> String {
+        format!("{}.{}", self.table, self.column)
+    }
+
+    // Rows of the table whose reference is set but points at no row
+    fn dangling_condition(&self) -> String {
+        format!(
+            "t.{column} IS NOT NULL AND NOT EXISTS \
+             (SELECT 1 FROM public.{references} r WHERE r.id = t.{column})",
+            column = self.column,
+            references = self.references
+        )
+    }
+
+    /// Counts the dangling rows, as `count`.
+    pub fn count_sql(&self) -> String {
+        format!(
+            "SELECT COUNT(*) AS count FROM public.{} t WHERE {}",
+            self.table,
+            self.dangling_condition()
+        )
+    }
+
+    /// Ids of the first dangling rows, as `id`.
+    pub fn sample_sql(&self, limit: i64) -> String {
+        format!(
+            "SELECT t.id FROM public.{} t WHERE {} ORDER BY t.id LIMIT {}",
+            self.table,
+            self.dangling_condition(),
+            limit
+        )
+    }
+
+    /// Deletes the dangling rows or clears their reference.
+    pub fn repair_sql(&self) -> String {
+        match self.repair {
+            IntegrityRepair::Delete => format!(
+                "DELETE FROM public.{} t WHERE {}",
+                self.table,
+                self.dangling_condition()
+            ),
+            IntegrityRepair::Clear => format!(
+                "UPDATE public.{} t SET {} = NULL WHERE {}",
+                self.table,
+                self.column,
+                self.dangling_condition()
+            ),
+        }
+    }
+}
+
+/// Every reference checked, in the order reports list them.
+pub const INTEGRITY_CHECKS: &[IntegrityCheck] = &[
+    IntegrityCheck {
+        table: "inventory_items",
+        column: "item_id",
+        references: "items",
+        description: "Inventory rows of deleted items",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "item_bom",
+        column: "parent_item_id",
+        references: "items",
+        description: "BOM rows of deleted assemblies",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "item_bom",
+        column: "component_item_id",
+        references: "items",
+        description: "BOM rows referencing missing components",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "stock_reservations",
+        column: "item_id",
+        references: "items",
+        description: "Stock reservations of deleted items",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "jobs",
+        column: "item_id",
+        references: "items",
+        description: "Jobs building deleted items",
+        repair: IntegrityRepair::Clear,
+    },
+    IntegrityCheck {
+        table: "order_items",
+        column: "item_id",
+        references: "items",
+        description: "Order lines of deleted items",
+        repair: IntegrityRepair::Clear,
+    },
+    IntegrityCheck {
+        table: "jobs",
+        column: "assigned_person_id",
+        references: "person",
+        description: "Jobs assigned to deleted persons",
+        repair: IntegrityRepair::Clear,
+    },
+    IntegrityCheck {
+        table: "machine_job_assignments",
+        column: "machine_id",
+        references: "machines",
+        description: "Job assignments to deleted machines",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "machine_job_assignments",
+        column: "job_id",
+        references: "jobs",
+        description: "Machine assignments of deleted jobs",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "machine_operator_assignments",
+        column: "machine_id",
+        references: "machines",
+        description: "Operator assignments to deleted machines",
+        repair: IntegrityRepair::Delete,
+    },
+    IntegrityCheck {
+        table: "machine_operator_assignments",
+        column: "person_id",
+        references: "person",
+        description: "Machine assignments of deleted persons",
+        repair: IntegrityRepair::Delete,
+    },
+];
+
+/// The checks named, in report order, or every check when `names` is `None`. Unknown names are
+/// refused.
+pub fn select_checks(names: Option<&[String]>) -> Result<Vec<IntegrityCheck>, String> {
+    let Some(names) = names else {
+        return Ok(INTEGRITY_CHECKS.to_vec());
+    };
+    if let Some(unknown) = names
+        .iter()
+        .find(|name| !INTEGRITY_CHECKS.iter().any(|check| check.name() == **name))
+    {
+        return Err(format!("Invalid integrity check: {}", unknown));
+    }
+    Ok(INTEGRITY_CHECKS
+        .iter()
+        .filter(|check| names.contains(&check.name()))
+        .copied()
+        .collect())
+}
@@ -0,0 +1,105 @@
+// Item image processing helpers
+use std::io::Cursor;
+
+use image::{codecs::jpeg::JpegEncoder, ImageFormat};
+use uuid::Uuid;
+
+/// Largest image accepted for upload, in bytes
+pub const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Thumbnails fit within a square of this many pixels, keeping the image's aspect ratio
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Thumbnails are always stored as JPEG
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/jpeg";
+
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// An uploaded image checked and decoded, with its thumbnail.
+#[derive(Debug, Clone)]
+pub struct ProcessedImage {
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Vec<u8>,
+}
+
+/// File extension stored images of `content_type` get, `None` for formats not accepted.
+pub fn image_extension(content_type: &str) -> Option<&'static str> {
+    match content_type.trim().to_ascii_lowercase().as_str() {
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Decodes an uploaded image and renders its thumbnail. The file itself must be in the format
+/// `content_type` claims, so a renamed file cannot be stored under the wrong type.
+pub fn process_image(content: &[u8], content_type: &str) -> Result<ProcessedImage, String> {
+    if content.is_empty() {
+        return Err("Invalid image: the upload is empty".to_string());
+    }
+    if content.len() > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "Image too large: {} bytes, at most {} allowed",
+            content.len(),
+            MAX_IMAGE_BYTES
+        ));
+    }
+
+    let expected = match image_extension(content_type) {
+        Some("jpg") => ImageFormat::Jpeg,
+        Some("png") => ImageFormat::Png,
+        Some("webp") => ImageFormat::WebP,
+        _ => {
+            return Err(format!(
+                "Invalid image: {} is not supported, use JPEG, PNG or WebP",
+                content_type
+            ))
+        }
+    };
+    let format = image::guess_format(content)
+        .map_err(|_| "Invalid image: the file is not a recognizable image".to_string())?;
+    if format != expected {
+        return Err(format!(
+            "Invalid image: the file is {:?} but was sent as {}",
+            format, content_type
+        ));
+    }
+
+    let decoded = image::load_from_memory_with_format(content, format)
+        .map_err(|e| format!("Invalid image: {}", e))?;
+
+    // Small images are kept at their size rather than enlarged; JPEG has no alpha channel, so
+    // transparent areas are flattened
+    let thumbnail = if decoded.width() > THUMBNAIL_SIZE || decoded.height() > THUMBNAIL_SIZE {
+        decoded.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8()
+    } else {
+        decoded.to_rgb8()
+    };
+    let mut encoded = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut encoded, THUMBNAIL_QUALITY)
+        .encode_image(&thumbnail)
+        .map_err(|e| format!("Invalid image: thumbnail could not be rendered: {}", e))?;
+
+    Ok(ProcessedImage {
+        width: decoded.width(),
+        height: decoded.height(),
+        thumbnail: encoded.into_inner(),
+    })
+}
+
+/// Storage paths of an image and its thumbnail. Paths start with the tenant so a bucket can be
+/// listed or cleared per tenant.
+pub fn image_paths(
+    tenant_id: Uuid,
+    item_id: Uuid,
+    image_id: Uuid,
+    extension: &str,
+) -> (String, String) {
+    let prefix = format!("{}/{}/{}", tenant_id, item_id, image_id);
+    (
+        format!("{}.{}", prefix, extension),
+        format!("{}_thumb.jpg", prefix),
+    )
+}
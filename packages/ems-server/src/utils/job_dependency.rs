@@ -0,0 +1,194 @@
+// Job dependency helpers: cycle checks and the critical path through scheduled jobs
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// A scheduled job for critical path computation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScheduledJob {
+    pub id: Uuid,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// Earliest and latest times of a job once dependencies are respected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JobTiming {
+    pub earliest_start: DateTime<Utc>,
+    pub earliest_finish: DateTime<Utc>,
+    pub latest_finish: DateTime<Utc>,
+}
+
+impl JobTiming {
+    /// How long the job could slip without delaying the finish, in whole minutes.
+    pub fn slack_minutes(&self) -> i64 {
+        (self.latest_finish - self.earliest_finish).num_minutes()
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.latest_finish == self.earliest_finish
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CriticalPath {
+    pub timings: HashMap<Uuid, JobTiming>,
+    /// Critical jobs, first to last
+    pub path: Vec<Uuid>,
+    pub finish: Option<DateTime<Utc>>,
+}
+
+impl CriticalPath {
+    /// Whether the dependency is a link of the critical path.
+    pub fn is_critical_link(&self, predecessor: Uuid, successor: Uuid) -> bool {
+        self.path
+            .windows(2)
+            .any(|pair| pair[0] == predecessor && pair[1] == successor)
+    }
+}
+
+/// Whether making `successor` wait for `predecessor` would close a cycle, given the existing
+/// `(predecessor, successor)` dependencies: it would when `predecessor` already waits for
+/// `successor`, directly or through other jobs.
+pub fn would_create_cycle(edges: &[(Uuid, Uuid)], predecessor: Uuid, successor: Uuid) -> bool {
+    if predecessor == successor {
+        return true;
+    }
+    let mut successors: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (from, to) in edges {
+        successors.entry(*from).or_default().push(*to);
+    }
+
+    let mut seen = HashSet::from([successor]);
+    let mut queue = VecDeque::from([successor]);
+    while let Some(job) = queue.pop_front() {
+        for next in successors.get(&job).into_iter().flatten() {
+            if *next == predecessor {
+                return true;
+            }
+            if seen.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    false
+}
+
+/// The critical path through the jobs, with `(predecessor, successor)` finish-to-start
+/// dependencies between them. A job starts at its scheduled start or once its last predecessor
+/// finishes, whichever is later, and takes as long as it is scheduled for. Dependencies on jobs
+/// not given are ignored, as are those in a cycle.
+pub fn critical_path(jobs: &[ScheduledJob], edges: &[(Uuid, Uuid)]) -> CriticalPath {
+    let by_id: HashMap<Uuid, &ScheduledJob> = jobs.iter().map(|job| (job.id, job)).collect();
+    let edges: Vec<(Uuid, Uuid)> = edges
+        .iter()
+        .copied()
+        .filter(|(from, to)| from != to && by_id.contains_key(from) && by_id.contains_key(to))
+        .collect();
+    let mut predecessors: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    let mut successors: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (from, to) in &edges {
+        predecessors.entry(*to).or_default().push(*from);
+        successors.entry(*from).or_default().push(*to);
+    }
+
+    // Jobs in dependency order; jobs in a cycle never become ready and are left out
+    let mut waiting: HashMap<Uuid, usize> = jobs
+        .iter()
+        .map(|job| (job.id, predecessors.get(&job.id).map_or(0, Vec::len)))
+        .collect();
+    let mut ready: VecDeque<Uuid> = jobs
+        .iter()
+        .filter(|job| waiting[&job.id] == 0)
+        .map(|job| job.id)
+        .collect();
+    let mut order = Vec::with_capacity(jobs.len());
+    while let Some(id) = ready.pop_front() {
+        order.push(id);
+        for next in successors.get(&id).into_iter().flatten() {
+            let count = waiting.get_mut(next).expect("successor is a job");
+            *count -= 1;
+            if *count == 0 {
+                ready.push_back(*next);
+            }
+        }
+    }
+    if order.is_empty() {
+        return CriticalPath::default();
+    }
+
+    // Forward pass: earliest start and finish
+    let mut earliest: HashMap<Uuid, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+    for id in &order {
+        let job = by_id[id];
+        let duration = (job.end - job.start).max(chrono::Duration::zero());
+        let start = predecessors
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|p| earliest.get(p).map(|(_, finish)| *finish))
+            .fold(job.start, DateTime::max);
+        earliest.insert(*id, (start, start + duration));
+    }
+    let finish = earliest
+        .values()
+        .map(|(_, finish)| *finish)
+        .max()
+        .expect("at least one job is ordered");
+
+    // Backward pass: latest finish
+    let mut latest_finish: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+    for id in order.iter().rev() {
+        let latest = successors
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|s| {
+                let (start, end) = earliest.get(s)?;
+                Some(*latest_finish.get(s)? - (*end - *start))
+            })
+            .fold(finish, DateTime::min);
+        latest_finish.insert(*id, latest);
+    }
+
+    let timings: HashMap<Uuid, JobTiming> = order
+        .iter()
+        .map(|id| {
+            let (earliest_start, earliest_finish) = earliest[id];
+            let timing = JobTiming {
+                earliest_start,
+                earliest_finish,
+                latest_finish: latest_finish[id],
+            };
+            (*id, timing)
+        })
+        .collect();
+
+    // Walk back from the job finishing last through the predecessors it waited for
+    let mut path = Vec::new();
+    let mut current = order
+        .iter()
+        .copied()
+        .find(|id| timings[id].earliest_finish == finish && timings[id].is_critical());
+    while let Some(id) = current {
+        path.push(id);
+        let start = timings[&id].earliest_start;
+        current = predecessors
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .copied()
+            .find(|p| {
+                timings
+                    .get(p)
+                    .is_some_and(|t| t.is_critical() && t.earliest_finish == start)
+            });
+    }
+    path.reverse();
+
+    CriticalPath {
+        timings,
+        path,
+        finish: Some(finish),
+    }
+}
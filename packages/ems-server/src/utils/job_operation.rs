@@ -0,0 +1,35 @@
+// Job routing helpers: progress over the operations of a routing
+
+use chrono::{DateTime, Utc};
+
+/// Share of the routing completed, from each operation's standard minutes and whether it is
+/// completed. Operations weigh by standard time; when none has one, each counts the same.
+/// Returns `None` for an empty routing.
+pub fn routing_progress(operations: &[(f64, bool)]) -> Option<f64> {
+    if operations.is_empty() {
+        return None;
+    }
+
+    let total: f64 = operations.iter().map(|(minutes, _)| minutes).sum();
+    let percent = if total > 0.0 {
+        let completed: f64 = operations
+            .iter()
+            .filter(|(_, completed)| *completed)
+            .map(|(minutes, _)| minutes)
+            .sum();
+        completed / total * 100.0
+    } else {
+        let completed = operations
+            .iter()
+            .filter(|(_, completed)| *completed)
+            .count();
+        completed as f64 / operations.len() as f64 * 100.0
+    };
+    Some((percent * 10.0).round() / 10.0)
+}
+
+/// Minutes between the start and the completion of an operation, to one decimal.
+pub fn elapsed_minutes(started_at: DateTime<Utc>, completed_at: DateTime<Utc>) -> f64 {
+    let minutes = (completed_at - started_at).num_seconds().max(0) as f64 / 60.0;
+    (minutes * 10.0).round() / 10.0
+}
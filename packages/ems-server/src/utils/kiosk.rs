@@ -0,0 +1,72 @@
+// Kiosk terminal helpers
+use axum::http::Method;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::models::{ClockEventType, KioskAction};
+
+/// Start of every kiosk API key, so the auth middleware can tell them from machine keys and JWTs
+pub const KIOSK_KEY_PREFIX: &str = "emsk_";
+
+/// Wrong PINs in a row after which PIN entry is locked
+pub const MAX_PIN_ATTEMPTS: i32 = 5;
+
+// How long PIN entry stays locked after too many wrong PINs
+const PIN_LOCKOUT_MINUTES: i64 = 15;
+
+/// A new kiosk API key.
+pub fn generate_kiosk_key() -> String {
+    format!(
+        "{}{}{}",
+        KIOSK_KEY_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Whether `pin` is 4 to 12 digits.
+pub fn is_valid_pin(pin: &str) -> bool {
+    (4..=12).contains(&pin.len()) && pin.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The action a kiosk request performs; `None` for the reads every terminal needs to badge
+/// operators in, and for anything else, which [`kiosk_key_allows`] turns away.
+fn kiosk_action(method: &Method, path: &str) -> Option<KioskAction> {
+    if *method != Method::POST {
+        return None;
+    }
+    match path {
+        "/api/v1/kiosk/clock" => Some(KioskAction::Clock),
+        "/api/v1/kiosk/operations/complete" => Some(KioskAction::CompleteOperation),
+        "/api/v1/kiosk/downtime" => Some(KioskAction::ReportDowntime),
+        _ => None,
+    }
+}
+
+/// Whether a kiosk key for a terminal allowing `actions` may make the request. Keys reach the
+/// terminal's own configuration, the operator list and the actions the terminal allows.
+pub fn kiosk_key_allows(method: &Method, path: &str, actions: &[String]) -> bool {
+    let path = path.trim_end_matches('/');
+    match kiosk_action(method, path) {
+        Some(action) => actions.iter().any(|allowed| *allowed == action.to_string()),
+        None => {
+            *method == Method::GET
+                && matches!(path, "/api/v1/kiosk/terminal" | "/api/v1/kiosk/operators")
+        }
+    }
+}
+
+/// When PIN entry unlocks after `failed_attempts` wrong PINs in a row; `None` while the operator
+/// may keep trying.
+pub fn pin_lockout(failed_attempts: i32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    (failed_attempts >= MAX_PIN_ATTEMPTS).then(|| now + Duration::minutes(PIN_LOCKOUT_MINUTES))
+}
+
+/// Whether an operator whose last clock event was `last` may clock `event_type`: in when not
+/// clocked in, out only when clocked in.
+pub fn clock_event_allowed(last: Option<ClockEventType>, event_type: ClockEventType) -> bool {
+    match event_type {
+        ClockEventType::ClockIn => last != Some(ClockEventType::ClockIn),
+        ClockEventType::ClockOut => last == Some(ClockEventType::ClockIn),
+    }
+}
@@ -0,0 +1,56 @@
+// Kitting helpers: component shortfalls and the substitutes proposed to cover them
+use uuid::Uuid;
+
+use crate::models::{Quantity, SubstituteProposal};
+
+/// Quantity of a component still to be issued to the job, and how much of that the free stock
+/// cannot cover. Substitutes count one for one against the component they replace.
+pub fn kit_shortfall(
+    required: Quantity,
+    issued: Quantity,
+    substituted: Quantity,
+    available: Quantity,
+) -> (Quantity, Quantity) {
+    let outstanding = (required - issued - substituted).max(Quantity::ZERO);
+    let shortfall = (outstanding - available.max(Quantity::ZERO)).max(Quantity::ZERO);
+    (outstanding, shortfall)
+}
+
+/// Proposes the substitutes with free stock for a shortfall, those with the most stock first,
+/// and spreads the shortfall over them in that order. Substitutes without stock are left out.
+pub fn propose_substitutes(
+    shortfall: Quantity,
+    candidates: &[(Uuid, Quantity)],
+) -> Vec<SubstituteProposal> {
+    let mut candidates: Vec<(Uuid, Quantity)> = candidates
+        .iter()
+        .filter(|(_, available)| *available > Quantity::ZERO)
+        .copied()
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut remaining = shortfall.max(Quantity::ZERO);
+    candidates
+        .into_iter()
+        .map(|(item_id, available)| {
+            let suggested_quantity = remaining.min(available);
+            remaining -= suggested_quantity;
+            SubstituteProposal {
+                item_id,
+                part_number: None,
+                available,
+                suggested_quantity,
+                covers_shortfall: available >= shortfall,
+            }
+        })
+        .collect()
+}
+
+/// Whether the proposed substitutes together make up the shortfall.
+pub fn substitutes_cover(shortfall: Quantity, proposals: &[SubstituteProposal]) -> bool {
+    proposals
+        .iter()
+        .map(|proposal| proposal.suggested_quantity)
+        .sum::<Quantity>()
+        >= shortfall
+}
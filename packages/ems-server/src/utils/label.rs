@@ -0,0 +1,177 @@
+// ZPL label rendering and printer address helpers
+use std::env;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use url::Url;
+
+use crate::utils::public_url::check_public_url;
+
+// Resolution the label layout is designed for; other resolutions are scaled from it
+const BASE_DPI: i32 = 203;
+// Margin around the label content in dots at the base resolution
+const MARGIN_DOTS: i32 = 20;
+// First retry delay for a failed print job, doubled on every further attempt
+pub const RETRY_BASE_SECONDS: u64 = 30;
+// Longest delay between two attempts of the same print job
+pub const RETRY_MAX_SECONDS: u64 = 3600;
+// Raw print port of Zebra and most other network label printers
+pub const DEFAULT_PRINTER_PORT: u16 = 9100;
+
+static ALLOWED_PRINTER_PORTS: OnceLock<Vec<u16>> = OnceLock::new();
+
+/// Ports printers may be configured on, from the comma-separated PRINTER_ALLOWED_PORTS, read
+/// once; only [`DEFAULT_PRINTER_PORT`] when it is not set.
+pub fn allowed_printer_ports() -> &'static [u16] {
+    ALLOWED_PRINTER_PORTS.get_or_init(|| {
+        let ports: Vec<u16> = env::var("PRINTER_ALLOWED_PORTS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|port| port.trim().parse().ok())
+            .filter(|port| *port > 0)
+            .collect();
+        if ports.is_empty() {
+            vec![DEFAULT_PRINTER_PORT]
+        } else {
+            ports
+        }
+    })
+}
+
+/// Refuses ports other than [`allowed_printer_ports`], so printer settings cannot be used to
+/// talk to other services.
+pub fn check_printer_port(port: u16) -> Result<(), String> {
+    if allowed_printer_ports().contains(&port) {
+        Ok(())
+    } else {
+        Err(format!("Printer port {} is not allowed", port))
+    }
+}
+
+/// The printer's host and port as a URL, checked like any tenant-supplied URL: never a host on
+/// the server's own network. Host names are checked again once resolved.
+pub fn printer_url(host: &str, port: u16) -> Result<Url, String> {
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let url = check_public_url(&format!("http://{}", authority), "printer")?;
+    // Anything else in the host, like a path or credentials, would be dropped by the URL
+    let parsed = url.host_str().unwrap_or_default();
+    if !parsed
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .eq_ignore_ascii_case(host)
+    {
+        return Err(format!("Invalid printer host: {}", host));
+    }
+    Ok(url)
+}
+
+/// Fields printed on an item label.
+#[derive(Debug, Clone)]
+pub struct ItemLabel {
+    pub item_id: String,
+    pub part_number: String,
+    pub description: Option<String>,
+    pub manufacturer: String,
+    pub mfr_part_number: Option<String>,
+    pub location: Option<String>,
+}
+
+/// Label size and print head resolution of the target printer.
+#[derive(Debug, Clone, Copy)]
+pub struct LabelFormat {
+    pub width_mm: i32,
+    pub height_mm: i32,
+    pub dpi: i32,
+}
+
+pub fn mm_to_dots(mm: i32, dpi: i32) -> i32 {
+    (mm as f64 * dpi as f64 / 25.4).round() as i32
+}
+
+/// Escapes a value for a `^FH` field so ZPL control characters print literally.
+pub fn escape_field(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '_' => escaped.push_str("_5F"),
+            '^' => escaped.push_str("_5E"),
+            '~' => escaped.push_str("_7E"),
+            '\r' | '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders an item label: part number and barcode, description, manufacturer details, storage
+/// location and a QR code of the item id.
+pub fn item_label_zpl(label: &ItemLabel, format: LabelFormat, copies: i32) -> String {
+    let scale = |dots: i32| dots * format.dpi / BASE_DPI;
+    let width = mm_to_dots(format.width_mm, format.dpi);
+    let height = mm_to_dots(format.height_mm, format.dpi);
+    let margin = scale(MARGIN_DOTS);
+
+    let mut zpl = String::new();
+    zpl.push_str("^XA\n^CI28\n");
+    zpl.push_str(&format!("^PW{}\n^LL{}\n", width, height));
+
+    let mut field = |y: i32, font: i32, value: &str| {
+        zpl.push_str(&format!(
+            "^FO{},{}^A0N,{},{}^FH^FD{}^FS\n",
+            margin,
+            y,
+            scale(font),
+            scale(font),
+            escape_field(value)
+        ));
+    };
+
+    let mut y = margin;
+    field(y, 36, &label.part_number);
+    y += scale(44);
+    if let Some(description) = &label.description {
+        field(y, 24, description);
+        y += scale(30);
+    }
+    let manufacturer = match &label.mfr_part_number {
+        Some(mfr_part_number) => format!("{} {}", label.manufacturer, mfr_part_number),
+        None => label.manufacturer.clone(),
+    };
+    field(y, 22, &manufacturer);
+    y += scale(28);
+    if let Some(location) = &label.location {
+        field(y, 22, &format!("Loc: {}", location));
+        y += scale(28);
+    }
+
+    zpl.push_str(&format!(
+        "^FO{},{}^BY2^BCN,{},N,N,N^FH^FD{}^FS\n",
+        margin,
+        y + scale(6),
+        scale(60),
+        escape_field(&label.part_number)
+    ));
+
+    let qr_magnification = (format.dpi / 100).clamp(1, 10);
+    zpl.push_str(&format!(
+        "^FO{},{}^BQN,2,{}^FDQA,{}^FS\n",
+        (width - margin - scale(120)).max(margin),
+        margin,
+        qr_magnification,
+        label.item_id
+    ));
+
+    zpl.push_str(&format!("^PQ{}\n^XZ\n", copies.max(1)));
+    zpl
+}
+
+/// Delay before retrying a print job that has failed `attempts` times.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    let seconds = RETRY_BASE_SECONDS.saturating_mul(2u64.pow(exponent));
+    Duration::from_secs(seconds.min(RETRY_MAX_SECONDS))
+}
@@ -0,0 +1,38 @@
+// Part lifecycle watch helpers
+use crate::models::ItemLifecycle;
+
+/// Maps a lifecycle status reported by a part data provider to the item lifecycle it implies.
+/// Returns `None` for active parts and statuses that are not recognised.
+pub fn suggested_lifecycle(reported_status: &str) -> Option<ItemLifecycle> {
+    let status = reported_status
+        .trim()
+        .to_lowercase()
+        .replace(['_', '-'], " ");
+
+    match status.as_str() {
+        "nrnd"
+        | "nrfnd"
+        | "not recommended for new designs"
+        | "last time buy"
+        | "ltb"
+        | "eol announced"
+        | "end of life announced" => Some(ItemLifecycle::Nrfnd),
+        "obsolete" | "eol" | "end of life" | "discontinued" => Some(ItemLifecycle::Obsolete),
+        _ => None,
+    }
+}
+
+// Ordering of lifecycles from active to obsolete
+fn rank(lifecycle: &ItemLifecycle) -> u8 {
+    match lifecycle {
+        ItemLifecycle::Prototype | ItemLifecycle::Production => 0,
+        ItemLifecycle::Nrfnd => 1,
+        ItemLifecycle::Obsolete => 2,
+    }
+}
+
+/// Whether an item should be flagged: only when the suggested lifecycle is further along than its
+/// current one, so items already marked NRND or obsolete are not flagged again.
+pub fn needs_lifecycle_change(current: Option<&ItemLifecycle>, suggested: &ItemLifecycle) -> bool {
+    rank(suggested) > current.map(rank).unwrap_or(0)
+}
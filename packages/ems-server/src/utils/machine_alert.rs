@@ -0,0 +1,225 @@
+// Machine alert rule evaluation helpers
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::models::{AlertCondition, ConditionObservation, MachineStatus, TelemetryChannel};
+
+/// Tenant setting giving how many standard deviations from its baseline mean a reading must be
+/// to count as an anomaly.
+pub const ANOMALY_SENSITIVITY_SETTING: &str = "anomaly_sensitivity";
+/// Sensitivity of tenants that have not set one
+pub const DEFAULT_ANOMALY_SENSITIVITY: f64 = 3.0;
+/// Readings a baseline needs before anything is scored against it
+pub const MIN_BASELINE_READINGS: usize = 10;
+
+/// A machine's state at one heartbeat, as the rule conditions see it.
+#[derive(Debug, Clone)]
+pub struct HeartbeatSnapshot {
+    pub status: MachineStatus,
+    /// When the machine entered its current status
+    pub status_since: DateTime<Utc>,
+    /// When the heartbeat was received
+    pub at: DateTime<Utc>,
+    /// Readings of each channel up to `at`, oldest first. Covers the longest window a condition
+    /// looks at, plus the last reading before it.
+    pub readings: HashMap<TelemetryChannel, Vec<(DateTime<Utc>, f64)>>,
+    /// The tenant's anomaly sensitivity, in standard deviations
+    pub anomaly_sensitivity: f64,
+}
+
+/// Longest window any of the conditions looks at, in minutes.
+pub fn longest_window_minutes(conditions: &[AlertCondition]) -> u32 {
+    conditions
+        .iter()
+        .map(AlertCondition::window_minutes)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The tenant's anomaly sensitivity: the tenant setting, or the default when it is not set.
+pub fn anomaly_sensitivity(settings: Option<&Value>) -> f64 {
+    settings
+        .and_then(|settings| settings.get(ANOMALY_SENSITIVITY_SETTING))
+        .and_then(Value::as_f64)
+        .filter(|sensitivity| *sensitivity > 0.0)
+        .unwrap_or(DEFAULT_ANOMALY_SENSITIVITY)
+}
+
+/// Checks the anomaly sensitivity in tenant settings, which may be left out but must be a
+/// positive number of standard deviations when given.
+pub fn check_anomaly_settings(settings: Option<&Value>) -> Result<(), String> {
+    match settings.and_then(|settings| settings.get(ANOMALY_SENSITIVITY_SETTING)) {
+        None | Some(Value::Null) => Ok(()),
+        Some(sensitivity) if sensitivity.as_f64().is_some_and(|s| s > 0.0 && s <= 10.0) => Ok(()),
+        Some(sensitivity) => Err(format!(
+            "{} must be a number of standard deviations above 0 and at most 10, got {}",
+            ANOMALY_SENSITIVITY_SETTING, sensitivity
+        )),
+    }
+}
+
+/// A channel's latest reading scored against the readings in the baseline window before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyScore {
+    pub recorded_at: DateTime<Utc>,
+    pub value: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Standard deviations from the mean; negative below it
+    pub z_score: f64,
+}
+
+impl AnomalyScore {
+    pub fn is_anomaly(&self, sensitivity: f64) -> bool {
+        self.z_score.abs() >= sensitivity
+    }
+}
+
+/// Scores the latest reading against the mean and standard deviation of the readings in the
+/// `baseline_minutes` before it. `None` without a latest reading, with fewer than
+/// [`MIN_BASELINE_READINGS`] in the baseline, or when the baseline never varies.
+pub fn anomaly_score(
+    readings: &[(DateTime<Utc>, f64)],
+    baseline_minutes: u32,
+) -> Option<AnomalyScore> {
+    let (latest, baseline) = readings.split_last()?;
+    let baseline_start = latest.0 - Duration::minutes(baseline_minutes as i64);
+    let baseline: Vec<f64> = baseline
+        .iter()
+        .filter(|(at, _)| *at >= baseline_start && *at < latest.0)
+        .map(|(_, value)| *value)
+        .collect();
+    if baseline.len() < MIN_BASELINE_READINGS {
+        return None;
+    }
+
+    let count = baseline.len() as f64;
+    let mean = baseline.iter().sum::<f64>() / count;
+    let variance = baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (count - 1.0);
+    let std_dev = variance.sqrt();
+    if !std_dev.is_normal() {
+        return None;
+    }
+
+    Some(AnomalyScore {
+        recorded_at: latest.0,
+        value: latest.1,
+        mean,
+        std_dev,
+        z_score: (latest.1 - mean) / std_dev,
+    })
+}
+
+/// Start of the unbroken run of readings, ending with the latest one, that meet `predicate`.
+/// `None` when the latest reading does not.
+pub fn breached_since(
+    readings: &[(DateTime<Utc>, f64)],
+    predicate: impl Fn(f64) -> bool,
+) -> Option<DateTime<Utc>> {
+    readings
+        .iter()
+        .rev()
+        .take_while(|(_, value)| predicate(*value))
+        .last()
+        .map(|(at, _)| *at)
+}
+
+fn held(since: DateTime<Utc>, at: DateTime<Utc>, for_minutes: Option<u32>) -> bool {
+    for_minutes.is_none_or(|minutes| at - since >= Duration::minutes(minutes as i64))
+}
+
+pub fn evaluate_condition(
+    condition: &AlertCondition,
+    snapshot: &HeartbeatSnapshot,
+) -> ConditionObservation {
+    let (matched, detail) = match condition {
+        AlertCondition::Status {
+            status,
+            for_minutes,
+        } => {
+            let minutes = (snapshot.at - snapshot.status_since).num_minutes().max(0);
+            if snapshot.status != *status {
+                (false, format!("Status is {}", snapshot.status))
+            } else {
+                (
+                    held(snapshot.status_since, snapshot.at, *for_minutes),
+                    format!("Status {} for {} minute(s)", status, minutes),
+                )
+            }
+        }
+        AlertCondition::Telemetry {
+            channel,
+            operator,
+            value,
+            for_minutes,
+        } => {
+            let readings = snapshot
+                .readings
+                .get(channel)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            match readings.last() {
+                None => (false, format!("No {} reading", channel)),
+                Some((_, latest)) => {
+                    match breached_since(readings, |observed| operator.compare(observed, *value)) {
+                        Some(since) => (
+                            held(since, snapshot.at, *for_minutes),
+                            format!(
+                                "{} {} {} {} for {} minute(s)",
+                                channel,
+                                latest,
+                                operator,
+                                value,
+                                (snapshot.at - since).num_minutes().max(0)
+                            ),
+                        ),
+                        None => (
+                            false,
+                            format!("{} is {} {}", channel, latest, channel.unit()),
+                        ),
+                    }
+                }
+            }
+        }
+        AlertCondition::Anomaly { channel, .. } => {
+            let readings = snapshot
+                .readings
+                .get(channel)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            match anomaly_score(readings, condition.window_minutes()) {
+                None => (
+                    false,
+                    format!("Not enough {} readings for a baseline", channel),
+                ),
+                Some(score) => (
+                    score.is_anomaly(snapshot.anomaly_sensitivity),
+                    format!(
+                        "{} {} is {:.1} standard deviations from its baseline mean of {:.2}",
+                        channel, score.value, score.z_score, score.mean
+                    ),
+                ),
+            }
+        }
+    };
+
+    ConditionObservation {
+        condition: condition.clone(),
+        matched,
+        detail,
+    }
+}
+
+/// Evaluates every condition; the rule fires only when all of them match.
+pub fn evaluate_conditions(
+    conditions: &[AlertCondition],
+    snapshot: &HeartbeatSnapshot,
+) -> (bool, Vec<ConditionObservation>) {
+    let observations: Vec<ConditionObservation> = conditions
+        .iter()
+        .map(|condition| evaluate_condition(condition, snapshot))
+        .collect();
+    let fired = !observations.is_empty() && observations.iter().all(|o| o.matched);
+    (fired, observations)
+}
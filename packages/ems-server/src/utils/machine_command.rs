@@ -0,0 +1,43 @@
+// Heartbeat reporting intervals, lengthened while the server is under load
+use std::env;
+use std::sync::OnceLock;
+
+// Seconds between heartbeats when HEARTBEAT_INTERVAL_SECONDS is not set
+pub const DEFAULT_REPORTING_INTERVAL_SECONDS: i32 = 30;
+// Backoff never lengthens an interval past this, one hour
+pub const MAX_REPORTING_INTERVAL_SECONDS: i32 = 3600;
+
+static DEFAULT_REPORTING_INTERVAL: OnceLock<i32> = OnceLock::new();
+
+/// Seconds between heartbeats of machines without their own interval, from
+/// HEARTBEAT_INTERVAL_SECONDS, read once.
+pub fn default_reporting_interval() -> i32 {
+    *DEFAULT_REPORTING_INTERVAL.get_or_init(|| {
+        env::var("HEARTBEAT_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_REPORTING_INTERVAL_SECONDS)
+    })
+}
+
+/// How many times longer machines should wait between heartbeats, given how many of the
+/// database pool's connections are busy: 1 below three quarters, 2 from there and 4 once every
+/// connection is in use.
+pub fn backoff_factor(busy: u32, max_size: u32) -> i32 {
+    if max_size == 0 || busy >= max_size {
+        4
+    } else if busy * 4 >= max_size * 3 {
+        2
+    } else {
+        1
+    }
+}
+
+/// The interval a machine is told to report in: its base interval times the backoff factor,
+/// capped at [`MAX_REPORTING_INTERVAL_SECONDS`] unless the base is already longer.
+pub fn reporting_interval(base: i32, factor: i32) -> i32 {
+    base.saturating_mul(factor)
+        .min(MAX_REPORTING_INTERVAL_SECONDS)
+        .max(base)
+}
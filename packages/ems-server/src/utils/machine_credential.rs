@@ -0,0 +1,58 @@
+// Machine provisioning helpers
+use axum::http::Method;
+use uuid::Uuid;
+
+/// Start of every machine API key, so the auth middleware can tell keys from JWTs
+pub const MACHINE_KEY_PREFIX: &str = "emsm_";
+
+// Characters of a key kept to tell keys apart
+const KEY_PREFIX_LENGTH: usize = 12;
+
+/// A new machine API key.
+pub fn generate_machine_key() -> String {
+    format!(
+        "{}{}{}",
+        MACHINE_KEY_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// A new MQTT broker password.
+pub fn generate_broker_password() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// The start of `key` stored to identify it.
+pub fn machine_key_prefix(key: &str) -> String {
+    key.chars().take(KEY_PREFIX_LENGTH).collect()
+}
+
+/// Broker user a machine connects to MQTT as.
+pub fn broker_username(machine_id: Uuid) -> String {
+    format!("machine-{}", machine_id.simple())
+}
+
+/// Topic filter a machine publishes and subscribes under.
+pub fn broker_topic(tenant_id: Uuid, machine_id: Uuid) -> String {
+    format!("ems/{}/machines/{}/#", tenant_id, machine_id)
+}
+
+/// Heartbeat endpoint of a machine under the API base URL.
+pub fn heartbeat_url(api_url: &str, machine_id: Uuid) -> String {
+    format!(
+        "{}/api/v1/machine/{}/heartbeat",
+        api_url.trim_end_matches('/'),
+        machine_id
+    )
+}
+
+/// Whether a machine API key for `machine_id` may make the request. Keys only reach the
+/// machine's own heartbeat, whose response carries its commands and configuration, and the
+/// heartbeat batch, where each entry is checked against the machines the key covers.
+pub fn machine_key_allows(method: &Method, path: &str, machine_id: Uuid) -> bool {
+    let path = path.trim_end_matches('/');
+    *method == Method::POST
+        && (path == format!("/api/v1/machine/{}/heartbeat", machine_id)
+            || path == "/api/v1/machine/heartbeats/batch")
+}
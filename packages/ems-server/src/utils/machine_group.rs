@@ -0,0 +1,88 @@
+// Machine group helpers: line status from member states and time spent per state from the
+// machine status history
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{LineStatus, MachineStatus};
+use crate::utils::capacity::Interval;
+
+fn hours(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_seconds() as f64 / 3600.0
+}
+
+/// Line status from each member's status and whether the member is critical.
+pub fn line_status(members: &[(MachineStatus, bool)]) -> LineStatus {
+    if members
+        .iter()
+        .any(|(status, critical)| *critical && status.is_down())
+    {
+        LineStatus::Down
+    } else if members.iter().any(|(status, _)| status.is_down()) {
+        LineStatus::Degraded
+    } else if members
+        .iter()
+        .any(|(status, _)| *status == MachineStatus::Busy)
+    {
+        LineStatus::Running
+    } else {
+        LineStatus::Idle
+    }
+}
+
+/// Periods a machine spent in each status within `[from, to)`, from its status transitions in
+/// time order. The transition in effect at `from` may precede it; time before the first
+/// transition is left out.
+pub fn status_periods(
+    transitions: &[(DateTime<Utc>, MachineStatus)],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<(Interval, MachineStatus)> {
+    transitions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (changed_at, status))| {
+            let ended_at = transitions
+                .get(index + 1)
+                .map_or(to, |(next, _)| *next)
+                .min(to);
+            let started_at = (*changed_at).max(from);
+            (started_at < ended_at).then(|| ((started_at, ended_at), status.clone()))
+        })
+        .collect()
+}
+
+/// Hours of the periods whose status meets `predicate`.
+pub fn hours_where(
+    periods: &[(Interval, MachineStatus)],
+    predicate: impl Fn(&MachineStatus) -> bool,
+) -> f64 {
+    periods
+        .iter()
+        .filter(|(_, status)| predicate(status))
+        .map(|((start, end), _)| hours(*start, *end))
+        .sum()
+}
+
+/// Hours covered by at least one of the intervals.
+pub fn union_hours(mut intervals: Vec<Interval>) -> f64 {
+    intervals.sort();
+
+    let mut total = 0.0;
+    let mut current: Option<Interval> = None;
+    for (start, end) in intervals {
+        current = match current {
+            Some((current_start, current_end)) if start <= current_end => {
+                Some((current_start, current_end.max(end)))
+            }
+            Some((current_start, current_end)) => {
+                total += hours(current_start, current_end);
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((start, end)) = current {
+        total += hours(start, end);
+    }
+    total
+}
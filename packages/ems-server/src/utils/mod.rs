@@ -1,5 +1,71 @@
+pub mod access_log;
+pub mod archive;
+pub mod asset_upload;
+pub mod atp;
 pub mod auth;
+pub mod batch_record;
+pub mod billing;
+pub mod bom_import;
+pub mod branding;
+pub mod calibration;
+pub mod capacity;
+pub mod circuit_breaker;
+pub mod consignment;
+pub mod csv;
+pub mod datasheet;
+pub mod diagnostics;
+pub mod document_pack;
+pub mod duplicate;
+pub mod encryption;
 pub mod errors;
+pub mod feature_flag;
+pub mod fingerprint;
+pub mod firmware_delta;
+pub mod forecast;
+pub mod heartbeat_buffer;
+pub mod i18n;
+pub mod ingest;
+pub mod integrity;
+pub mod item_image;
+pub mod job_dependency;
+pub mod job_operation;
+pub mod kiosk;
+pub mod kitting;
+pub mod label;
+pub mod lifecycle;
+pub mod machine_alert;
+pub mod machine_command;
+pub mod machine_credential;
+pub mod machine_group;
+pub mod numbering;
+pub mod parametric;
+pub mod order_confirmation;
+pub mod pdf;
+pub mod query_metrics;
+pub mod person_import;
+pub mod price_list;
+pub mod production;
+pub mod public_url;
+pub mod quality;
+pub mod quote;
+pub mod recalculation;
+pub mod returns;
+pub mod rfq;
+pub mod sandbox;
+pub mod scorecard;
+pub mod search;
+pub mod service_scope;
+pub mod shipping;
+pub mod soak;
+pub mod spc;
+pub mod status_page;
+pub mod storage;
+pub mod streaming;
+pub mod telemetry;
+pub mod trace;
+pub mod uom;
+pub mod validation_rule;
+pub mod zip;
 
 pub use auth::*;
 pub use errors::*;
@@ -0,0 +1,41 @@
+// Numbering helpers: formatting sequence values into document numbers
+
+/// Formats a counter value with the sequence's prefix and padding. `{YYYY}` and `{YY}` in the
+/// prefix are replaced by the year, so `PO-{YYYY}-` with padding 4 gives `PO-2025-0042`.
+pub fn format_number(prefix: &str, padding: i32, year: i32, value: i64) -> String {
+    let prefix = prefix
+        .replace("{YYYY}", &format!("{:04}", year))
+        .replace("{YY}", &format!("{:02}", year.rem_euclid(100)));
+    format!(
+        "{}{:0width$}",
+        prefix,
+        value,
+        width = padding.max(1) as usize
+    )
+}
+
+/// Counter value the next number is drawn from in `year`. Sequences that reset yearly start
+/// again at 1 when the last number was drawn in an earlier year.
+pub fn sequence_start(
+    next_value: i64,
+    period_year: Option<i32>,
+    yearly_reset: bool,
+    year: i32,
+) -> i64 {
+    match period_year {
+        Some(period_year) if yearly_reset && period_year != year => 1,
+        _ => next_value.max(1),
+    }
+}
+
+/// Checks a prefix template only uses the year placeholders it knows about.
+pub fn check_prefix(prefix: &str) -> Result<(), String> {
+    let stripped = prefix.replace("{YYYY}", "").replace("{YY}", "");
+    if stripped.contains('{') || stripped.contains('}') {
+        return Err(format!("Invalid prefix: {}", prefix));
+    }
+    if stripped.chars().any(|c| c.is_control()) {
+        return Err(format!("Invalid prefix: {}", prefix));
+    }
+    Ok(())
+}
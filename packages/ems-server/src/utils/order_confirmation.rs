@@ -0,0 +1,160 @@
+// Order confirmation document helpers
+use crate::models::OrderResponse;
+use crate::utils::branding::DocumentBranding;
+use crate::utils::i18n::{Locale, Text};
+use crate::utils::pdf::{truncate, Font, PdfDocument, PAGE_HEIGHT, PAGE_WIDTH};
+
+// Page margin of the confirmation in points
+const MARGIN: f64 = 50.0;
+// Longest item name printed before it is cut short
+const MAX_NAME_CHARS: usize = 40;
+
+/// Renders the confirmation sent to the customer for a sales order in `locale` and the
+/// tenant's `branding`: header, one row per line with its delivery date when known, and the
+/// total.
+pub fn render_order_confirmation_pdf(
+    order: &OrderResponse,
+    customer_name: &str,
+    locale: Locale,
+    branding: &DocumentBranding,
+) -> Vec<u8> {
+    let right = PAGE_WIDTH - MARGIN;
+    let quantity_right = right - 170.0;
+    let price_right = right - 85.0;
+    let delivery_x = quantity_right - 130.0;
+
+    let mut pdf = PdfDocument::new();
+    if branding.is_branded() {
+        pdf.set_letterhead(branding.letterhead());
+    }
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Bold,
+        18.0,
+        &locale.format(Text::OrderConfirmationTitle, &[&order.order_number]),
+    );
+    y -= 28.0;
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Regular,
+        10.0,
+        &locale.format(Text::Customer, &[customer_name]),
+    );
+    y -= 14.0;
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Regular,
+        10.0,
+        &locale.format(Text::Date, &[&locale.date(order.order_date)]),
+    );
+    y -= 30.0;
+
+    let header = |pdf: &mut PdfDocument, y: f64| {
+        pdf.text(MARGIN, y, Font::Bold, 10.0, "#");
+        pdf.text(MARGIN + 25.0, y, Font::Bold, 10.0, locale.text(Text::Item));
+        pdf.text(delivery_x, y, Font::Bold, 10.0, locale.text(Text::Delivery));
+        pdf.text_right(
+            quantity_right,
+            y,
+            Font::Bold,
+            10.0,
+            locale.text(Text::Quantity),
+        );
+        pdf.text_right(
+            price_right,
+            y,
+            Font::Bold,
+            10.0,
+            locale.text(Text::UnitPrice),
+        );
+        pdf.text_right(right, y, Font::Bold, 10.0, locale.text(Text::Amount));
+        pdf.line(MARGIN, y - 5.0, right, y - 5.0);
+    };
+    header(&mut pdf, y);
+    y -= 20.0;
+
+    for (index, item) in order.items.iter().enumerate() {
+        if y - 12.0 < MARGIN {
+            pdf.add_page();
+            y = PAGE_HEIGHT - MARGIN;
+            header(&mut pdf, y);
+            y -= 20.0;
+        }
+
+        pdf.text(MARGIN, y, Font::Regular, 10.0, &(index + 1).to_string());
+        pdf.text(
+            MARGIN + 25.0,
+            y,
+            Font::Regular,
+            10.0,
+            &truncate(&item.item_name, MAX_NAME_CHARS),
+        );
+        if let Some(expected) = item.expected_date {
+            pdf.text(delivery_x, y, Font::Regular, 10.0, &locale.date(expected));
+        }
+        pdf.text_right(
+            quantity_right,
+            y,
+            Font::Regular,
+            10.0,
+            &item.quantity.to_string(),
+        );
+        pdf.text_right(
+            price_right,
+            y,
+            Font::Regular,
+            10.0,
+            &format!("{:.2}", item.unit_price),
+        );
+        pdf.text_right(
+            right,
+            y,
+            Font::Regular,
+            10.0,
+            &format!("{:.2}", item.extended_price),
+        );
+        y -= 16.0;
+    }
+
+    if y - 40.0 < MARGIN {
+        pdf.add_page();
+        y = PAGE_HEIGHT - MARGIN;
+    }
+    pdf.line(price_right - 60.0, y + 6.0, right, y + 6.0);
+    y -= 8.0;
+    pdf.text_right(price_right, y, Font::Bold, 11.0, locale.text(Text::Total));
+    pdf.text_right(
+        right,
+        y,
+        Font::Bold,
+        11.0,
+        &format!("{:.2}", order.total_amount),
+    );
+
+    pdf.render()
+}
+
+/// Subject and body of the email the confirmation PDF is sent with, in `locale`.
+pub fn order_confirmation_email(
+    order: &OrderResponse,
+    customer_name: &str,
+    message: Option<&str>,
+    locale: Locale,
+) -> (String, String) {
+    let subject = locale.format(Text::OrderConfirmationTitle, &[&order.order_number]);
+    let mut body = format!(
+        "{}\n\n{}\n\n{}\n",
+        locale.format(Text::Greeting, &[customer_name]),
+        locale.format(Text::OrderConfirmationAttached, &[&order.order_number]),
+        locale.format(Text::TotalAmount, &[&format!("{:.2}", order.total_amount)]),
+    );
+    if let Some(message) = message {
+        body.push_str(&format!("\n{}\n", message));
+    }
+    (subject, body)
+}
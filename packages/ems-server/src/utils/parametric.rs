@@ -0,0 +1,216 @@
+// Parametric item search: attribute filters from query strings like
+// `category=resistor&resistance[gte]=1k&package=0402&sort=resistance`
+//
+// Attributes live under `attributes` in the item's metadata, numbers in base units (ohms, farads,
+// volts) so ranges compare across prefixes.
+
+/// Results returned when no limit is given
+pub const DEFAULT_PARAMETRIC_LIMIT: i64 = 50;
+
+/// Most results one search returns
+pub const MAX_PARAMETRIC_LIMIT: i64 = 500;
+
+// Most attribute filters one search takes
+const MAX_FILTERS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeOp {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl AttributeOp {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "eq" => Some(AttributeOp::Eq),
+            "gt" => Some(AttributeOp::Gt),
+            "gte" => Some(AttributeOp::Gte),
+            "lt" => Some(AttributeOp::Lt),
+            "lte" => Some(AttributeOp::Lte),
+            _ => None,
+        }
+    }
+
+    /// The jsonpath comparison operator
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AttributeOp::Eq => "==",
+            AttributeOp::Gt => ">",
+            AttributeOp::Gte => ">=",
+            AttributeOp::Lt => "<",
+            AttributeOp::Lte => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeFilter {
+    pub attribute: String,
+    pub op: AttributeOp,
+    /// The value as written, matched against text attributes
+    pub value: String,
+    /// The value in base units, `None` when it is not a number; ranges need one
+    pub number: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParametricSearch {
+    pub category: Option<String>,
+    pub filters: Vec<AttributeFilter>,
+    /// Attribute to sort by and whether descending
+    pub sort: Option<(String, bool)>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Reads a parametric search from query string pairs. `category`, `sort`, `order` (`asc` or
+/// `desc`), `limit` and `offset` are reserved; any other key filters an attribute, either
+/// `name=value` or `name[op]=value` with op one of eq, gt, gte, lt and lte.
+pub fn parse_parametric_search(pairs: &[(String, String)]) -> Result<ParametricSearch, String> {
+    let mut search = ParametricSearch {
+        category: None,
+        filters: Vec::new(),
+        sort: None,
+        limit: DEFAULT_PARAMETRIC_LIMIT,
+        offset: 0,
+    };
+    let mut descending = false;
+    for (key, value) in pairs {
+        let value = value.trim();
+        match key.as_str() {
+            "category" => search.category = Some(value.to_string()).filter(|c| !c.is_empty()),
+            "sort" => search.sort = Some((attribute_name(value)?, false)),
+            "order" => {
+                descending = match value {
+                    "asc" => false,
+                    "desc" => true,
+                    _ => return Err(format!("Invalid order '{}': use asc or desc", value)),
+                }
+            }
+            "limit" => {
+                search.limit = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|limit| (1..=MAX_PARAMETRIC_LIMIT).contains(limit))
+                    .ok_or_else(|| format!("Invalid limit: use 1 to {}", MAX_PARAMETRIC_LIMIT))?
+            }
+            "offset" => {
+                search.offset = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|offset| *offset >= 0)
+                    .ok_or_else(|| "Invalid offset".to_string())?
+            }
+            _ => search.filters.push(attribute_filter(key, value)?),
+        }
+    }
+    if search.filters.len() > MAX_FILTERS {
+        return Err(format!(
+            "Too many attribute filters: at most {}",
+            MAX_FILTERS
+        ));
+    }
+    if let Some((_, desc)) = &mut search.sort {
+        *desc = descending;
+    }
+    Ok(search)
+}
+
+/// A value in base units: `1k` is 1000, `4.7u` and `4u7` are 0.0000047, `100nF` is 1e-7.
+/// A unit after the prefix is ignored. `None` when the value is not a number.
+pub fn parse_si_value(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(value.len());
+    let (mantissa, rest) = value.split_at(split);
+    let mut chars = rest.chars();
+    let (scale, rest) = match chars.next().and_then(si_scale) {
+        Some(scale) => (scale, chars.as_str()),
+        None => (1.0, rest),
+    };
+
+    // `4k7` writes the prefix where the decimal point goes
+    let fraction: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let unit = &rest[fraction.len()..];
+    let mantissa = if fraction.is_empty() {
+        mantissa.to_string()
+    } else if scale != 1.0 && !mantissa.contains('.') {
+        format!("{}.{}", mantissa, fraction)
+    } else {
+        return None;
+    };
+    if !unit.chars().all(|c| c.is_alphabetic()) {
+        return None;
+    }
+    let number = mantissa.parse::<f64>().ok()? * scale;
+    number.is_finite().then_some(number)
+}
+
+fn si_scale(prefix: char) -> Option<f64> {
+    match prefix {
+        'p' => Some(1e-12),
+        'n' => Some(1e-9),
+        'u' | 'µ' | 'μ' => Some(1e-6),
+        'm' => Some(1e-3),
+        'k' | 'K' => Some(1e3),
+        'M' => Some(1e6),
+        'G' => Some(1e9),
+        _ => None,
+    }
+}
+
+fn attribute_filter(key: &str, value: &str) -> Result<AttributeFilter, String> {
+    let (attribute, op) = match key.split_once('[') {
+        Some((attribute, op)) => {
+            let op = op
+                .strip_suffix(']')
+                .and_then(AttributeOp::parse)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid filter '{}': use eq, gt, gte, lt or lte in brackets",
+                        key
+                    )
+                })?;
+            (attribute, op)
+        }
+        None => (key, AttributeOp::Eq),
+    };
+    let attribute = attribute_name(attribute)?;
+    if value.is_empty() {
+        return Err(format!("Invalid filter '{}': a value is required", key));
+    }
+    let number = parse_si_value(value);
+    if op != AttributeOp::Eq && number.is_none() {
+        return Err(format!(
+            "Invalid filter '{}': '{}' is not a number",
+            key, value
+        ));
+    }
+    Ok(AttributeFilter {
+        attribute,
+        op,
+        value: value.to_string(),
+        number,
+    })
+}
+
+// Attribute names go into JSON paths, so only lower-case words are taken
+fn attribute_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let valid = name.len() <= 63
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid attribute '{}': use lower-case letters, digits and underscores",
+            name
+        ));
+    }
+    Ok(name.to_string())
+}
@@ -0,0 +1,396 @@
+// Minimal PDF writer for server-generated documents
+//
+// Pages hold text in the standard Helvetica faces and straight rules. Every PDF reader ships
+// those fonts, so nothing is embedded and the output stays a few kilobytes. A letterhead can add
+// colors and one JPEG image, embedded as is.
+
+// A4 portrait in points
+pub const PAGE_WIDTH: f64 = 595.0;
+pub const PAGE_HEIGHT: f64 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+// Letterhead layout in points: the band along the top edge, the box the logo is fitted into
+// below it and the baseline of the first footer line
+const BAND_HEIGHT: f64 = 8.0;
+const LOGO_MAX_WIDTH: f64 = 140.0;
+const LOGO_MAX_HEIGHT: f64 = 36.0;
+const LOGO_RIGHT: f64 = PAGE_WIDTH - 50.0;
+const LOGO_TOP: f64 = PAGE_HEIGHT - BAND_HEIGHT - 6.0;
+const FOOTER_X: f64 = 50.0;
+const FOOTER_Y: f64 = 34.0;
+const FOOTER_SIZE: f64 = 8.0;
+/// Footer lines a letterhead prints below the page margin; longer footers are cut short
+pub const MAX_FOOTER_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    /// A color written `#rrggbb`, case-insensitive.
+    pub fn from_hex(value: &str) -> Option<Self> {
+        let hex = value.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        })
+    }
+
+    /// The color as `#rrggbb`.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    // Operands of the rg and RG operators
+    fn components(&self) -> String {
+        format!(
+            "{:.3} {:.3} {:.3}",
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0
+        )
+    }
+}
+
+/// A baseline JPEG placed on pages without decoding it; `width` and `height` are in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfImage {
+    pub width: u32,
+    pub height: u32,
+    pub jpeg: Vec<u8>,
+}
+
+/// Drawn on every page around its content: a band of `color` along the top edge, the logo in
+/// the top right corner and the footer text, with a rule of `accent_color` above it, along the
+/// bottom edge below the page margin. Parts left out are not drawn.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Letterhead {
+    pub color: Option<Color>,
+    pub accent_color: Option<Color>,
+    pub logo: Option<PdfImage>,
+    pub footer: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PdfDocument {
+    pages: Vec<Vec<u8>>,
+    letterhead: Option<Letterhead>,
+}
+
+impl PdfDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draws `letterhead` on every page, including pages already added.
+    pub fn set_letterhead(&mut self, letterhead: Letterhead) {
+        self.letterhead = Some(letterhead);
+    }
+
+    pub fn add_page(&mut self) {
+        self.pages.push(Vec::new());
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Writes `text` on the current page with its baseline starting at `x`, `y` points from
+    /// the bottom-left corner.
+    pub fn text(&mut self, x: f64, y: f64, font: Font, size: f64, text: &str) {
+        let page = self.current_page();
+        page.extend_from_slice(
+            format!(
+                "BT /{} {:.1} Tf {:.2} {:.2} Td (",
+                font.resource(),
+                size,
+                x,
+                y
+            )
+            .as_bytes(),
+        );
+        page.extend_from_slice(&encode_text(text));
+        page.extend_from_slice(b") Tj ET\n");
+    }
+
+    /// Like [`PdfDocument::text`], but ends the text at `right` instead of starting it at `x`.
+    pub fn text_right(&mut self, right: f64, y: f64, font: Font, size: f64, text: &str) {
+        self.text(right - text_width(text, size), y, font, size, text);
+    }
+
+    pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        let page = self.current_page();
+        page.extend_from_slice(
+            format!("0.5 w {:.2} {:.2} m {:.2} {:.2} l S\n", x1, y1, x2, y2).as_bytes(),
+        );
+    }
+
+    /// Serializes the document. A document without pages renders as a single blank page.
+    pub fn render(&self) -> Vec<u8> {
+        let blank = [Vec::new()];
+        let pages: &[Vec<u8>] = if self.pages.is_empty() {
+            &blank
+        } else {
+            &self.pages
+        };
+
+        // Objects 1-4 are the catalog, page tree and fonts, followed by the logo when there is
+        // one; each page adds a page object and its content stream
+        let logo = self.letterhead.as_ref().and_then(|l| l.logo.as_ref());
+        let first_page = if logo.is_some() { 6 } else { 5 };
+        let kids: Vec<String> = (0..pages.len())
+            .map(|i| format!("{} 0 R", first_page + i * 2))
+            .collect();
+        let mut objects: Vec<Vec<u8>> = vec![
+            b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                pages.len()
+            )
+            .into_bytes(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        ];
+        if let Some(logo) = logo {
+            let mut image = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+                 /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+                logo.width,
+                logo.height,
+                logo.jpeg.len()
+            )
+            .into_bytes();
+            image.extend_from_slice(&logo.jpeg);
+            image.extend_from_slice(b"\nendstream");
+            objects.push(image);
+        }
+        let images = if logo.is_some() {
+            " /XObject << /Im1 5 0 R >>"
+        } else {
+            ""
+        };
+        let letterhead = self
+            .letterhead
+            .as_ref()
+            .map(letterhead_content)
+            .unwrap_or_default();
+        for (i, page) in pages.iter().enumerate() {
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >>{} >> /Contents {} 0 R >>",
+                    PAGE_WIDTH,
+                    PAGE_HEIGHT,
+                    images,
+                    first_page + 1 + i * 2
+                )
+                .into_bytes(),
+            );
+            let content = [letterhead.as_slice(), page.as_slice()].concat();
+            let mut stream = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            stream.extend_from_slice(&content);
+            stream.extend_from_slice(b"\nendstream");
+            objects.push(stream);
+        }
+
+        let mut out = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref_offset = out.len();
+        out.extend_from_slice(
+            format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+        );
+        for offset in offsets {
+            out.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend_from_slice(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+                objects.len() + 1,
+                xref_offset
+            )
+            .as_bytes(),
+        );
+        out
+    }
+
+    fn current_page(&mut self) -> &mut Vec<u8> {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        self.pages.last_mut().expect("page was just added")
+    }
+}
+
+// Content stream drawing a letterhead, in its own graphics state so the page content is drawn
+// in the default colors
+fn letterhead_content(letterhead: &Letterhead) -> Vec<u8> {
+    let mut content = b"q\n".to_vec();
+    if let Some(color) = letterhead.color {
+        content.extend_from_slice(
+            format!(
+                "{} rg 0 {:.2} {:.2} {:.2} re f\n",
+                color.components(),
+                PAGE_HEIGHT - BAND_HEIGHT,
+                PAGE_WIDTH,
+                BAND_HEIGHT
+            )
+            .as_bytes(),
+        );
+    }
+    if let Some(logo) = &letterhead.logo {
+        // Fitted into the logo box keeping its aspect ratio, against the box's top right corner
+        let scale = (LOGO_MAX_WIDTH / logo.width.max(1) as f64)
+            .min(LOGO_MAX_HEIGHT / logo.height.max(1) as f64);
+        let (width, height) = (logo.width as f64 * scale, logo.height as f64 * scale);
+        content.extend_from_slice(
+            format!(
+                "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im1 Do Q\n",
+                width,
+                height,
+                LOGO_RIGHT - width,
+                LOGO_TOP - height
+            )
+            .as_bytes(),
+        );
+    }
+    if let Some(color) = letterhead.accent_color {
+        content.extend_from_slice(
+            format!(
+                "{} RG 0.5 w {:.2} {:.2} m {:.2} {:.2} l S\n",
+                color.components(),
+                FOOTER_X,
+                FOOTER_Y + 12.0,
+                PAGE_WIDTH - FOOTER_X,
+                FOOTER_Y + 12.0
+            )
+            .as_bytes(),
+        );
+    }
+    if let Some(footer) = &letterhead.footer {
+        let mut lines = wrap_text(footer, PAGE_WIDTH - 2.0 * FOOTER_X, FOOTER_SIZE);
+        if lines.len() > MAX_FOOTER_LINES {
+            lines.truncate(MAX_FOOTER_LINES);
+            if let Some(last) = lines.last_mut() {
+                last.push_str(" ...");
+            }
+        }
+        for (i, line) in lines.iter().enumerate() {
+            content.extend_from_slice(
+                format!(
+                    "BT /F1 {:.1} Tf {:.2} {:.2} Td (",
+                    FOOTER_SIZE,
+                    FOOTER_X,
+                    FOOTER_Y - i as f64 * (FOOTER_SIZE + 2.0)
+                )
+                .as_bytes(),
+            );
+            content.extend_from_slice(&encode_text(line));
+            content.extend_from_slice(b") Tj ET\n");
+        }
+    }
+    content.extend_from_slice(b"Q\n");
+    content
+}
+
+/// Encodes text for a string literal in a content stream. Latin-1 characters map onto the
+/// WinAnsi encoding the fonts use; anything else prints as `?`.
+pub fn encode_text(text: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                encoded.push(b'\\');
+                encoded.push(c as u8);
+            }
+            '\r' | '\n' | '\t' => encoded.push(b' '),
+            c if (' '..='~').contains(&c) || ('\u{a0}'..='\u{ff}').contains(&c) => {
+                encoded.push(c as u32 as u8)
+            }
+            _ => encoded.push(b'?'),
+        }
+    }
+    encoded
+}
+
+/// `text` cut to at most `max_chars` characters, ending in "..." when shortened.
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Approximate width of `text` in points. Digits and common punctuation use their Helvetica
+/// metrics, which keeps right-aligned amounts flush; letters use an average width.
+pub fn text_width(text: &str, size: f64) -> f64 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => 556,
+            '.' | ',' | ' ' => 278,
+            '-' => 333,
+            'i' | 'j' | 'l' | 'I' => 222,
+            'm' | 'w' | 'M' | 'W' => 833,
+            'A'..='Z' => 667,
+            _ => 556,
+        })
+        .sum();
+    units as f64 * size / 1000.0
+}
+
+/// `text` broken into lines at most `max_width` points wide at `size`, at spaces where it can
+/// be; line breaks in `text` are kept.
+pub fn wrap_text(text: &str, max_width: f64, size: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if line.is_empty() || text_width(&candidate, size) <= max_width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
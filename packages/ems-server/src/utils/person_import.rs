@@ -0,0 +1,145 @@
+// Bulk person import parsing helpers
+use std::collections::{BTreeMap, HashSet};
+
+use crate::models::{PersonImportRow, PersonImportRowResult, PersonRole};
+use crate::utils::price_list::parse_csv;
+
+// Columns recognised in a person import header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Column {
+    Name,
+    Email,
+    Role,
+    Department,
+    Position,
+    EmployeeId,
+    Phone,
+}
+
+fn column(header: &str) -> Option<Column> {
+    match header
+        .trim()
+        .to_lowercase()
+        .replace([' ', '-'], "_")
+        .as_str()
+    {
+        "name" | "full_name" => Some(Column::Name),
+        "email" | "email_address" => Some(Column::Email),
+        "role" | "type" => Some(Column::Role),
+        "department" => Some(Column::Department),
+        "position" | "title" | "job_title" => Some(Column::Position),
+        "employee_id" | "employee_number" => Some(Column::EmployeeId),
+        "phone" | "phone_number" => Some(Column::Phone),
+        _ => None,
+    }
+}
+
+/// Parses a person import CSV. The header needs `name` and `email` columns; optional columns
+/// are `role` (defaults to internal), `department`, `position`, `employee_id` and `phone`.
+/// Rows with problems, including repeated emails, are returned as failed results.
+pub fn parse_person_import(
+    text: &str,
+) -> Result<(Vec<PersonImportRow>, Vec<PersonImportRowResult>), String> {
+    let mut records = parse_csv(text)?.into_iter();
+
+    let (_, header) = records.next().ok_or("Person import is empty")?;
+    let columns: Vec<Option<Column>> = header.iter().map(|h| column(h)).collect();
+    for (required, name) in [(Column::Name, "name"), (Column::Email, "email")] {
+        if !columns.contains(&Some(required)) {
+            return Err(format!("Person import has no {} column", name));
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut failed = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (line, record) in records {
+        if record.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+
+        match parse_row(line, &columns, &record) {
+            Ok(row) if !seen.insert(row.email.clone()) => {
+                failed.push(PersonImportRowResult::failed(
+                    line,
+                    Some(row.email),
+                    vec!["Email appears more than once in the file".to_string()],
+                ))
+            }
+            Ok(row) => rows.push(row),
+            Err(result) => failed.push(result),
+        }
+    }
+
+    Ok((rows, failed))
+}
+
+fn parse_row(
+    line: usize,
+    columns: &[Option<Column>],
+    record: &[String],
+) -> Result<PersonImportRow, PersonImportRowResult> {
+    let mut values: BTreeMap<Column, &str> = BTreeMap::new();
+    for (column, value) in columns.iter().zip(record) {
+        if let Some(column) = column {
+            let value = value.trim();
+            if !value.is_empty() {
+                values.insert(*column, value);
+            }
+        }
+    }
+
+    let value = |column: Column| values.get(&column).map(|v| v.to_string());
+    let email = value(Column::Email).map(|e| e.to_lowercase());
+    let mut errors = Vec::new();
+
+    let name = value(Column::Name).unwrap_or_default();
+    if name.is_empty() {
+        errors.push("Missing name".to_string());
+    }
+    match &email {
+        None => errors.push("Missing email".to_string()),
+        Some(email) if !validator::validate_email(email.as_str()) => {
+            errors.push(format!("Invalid email: {}", email))
+        }
+        _ => {}
+    }
+
+    let role = match values.get(&Column::Role) {
+        Some(role) => PersonRole::try_from(role.to_lowercase()).unwrap_or_else(|e| {
+            errors.push(e);
+            PersonRole::Pending
+        }),
+        None => PersonRole::Internal,
+    };
+
+    // Same limits as the person columns
+    for (column, label, max) in [
+        (Column::Name, "name", 100),
+        (Column::Email, "email", 100),
+        (Column::Department, "department", 50),
+        (Column::Position, "position", 100),
+        (Column::EmployeeId, "employee_id", 20),
+        (Column::Phone, "phone", 20),
+    ] {
+        if values.get(&column).is_some_and(|v| v.chars().count() > max) {
+            errors.push(format!("{} is longer than {} characters", label, max));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(PersonImportRowResult::failed(line, email, errors));
+    }
+
+    Ok(PersonImportRow {
+        line,
+        name,
+        email: email.unwrap_or_default(),
+        phone: value(Column::Phone),
+        role,
+        department: value(Column::Department),
+        position: value(Column::Position),
+        employee_id: value(Column::EmployeeId),
+    })
+}
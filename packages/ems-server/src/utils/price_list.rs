@@ -0,0 +1,276 @@
+// Vendor price list parsing helpers
+use std::collections::BTreeMap;
+
+use crate::models::{PriceBreak, PriceListRow, PriceListRowIssue};
+
+/// Splits CSV text into records, handling quoted fields, escaped quotes and CRLF line endings.
+/// Each record carries the 1-based line it starts on.
+pub fn parse_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            ('\n', true) => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!(
+            "Unterminated quoted field starting on line {}",
+            record_line
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+
+    Ok(records)
+}
+
+// Columns recognised in a price list header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Column {
+    MfrPartNumber,
+    Manufacturer,
+    Price,
+    BreakQuantity(u32),
+    BreakPrice(u32),
+    LeadTime,
+    Currency,
+}
+
+fn column(header: &str) -> Option<Column> {
+    let name = header.trim().to_lowercase().replace([' ', '-'], "_");
+
+    let numbered = |prefixes: &[&str]| {
+        prefixes
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix)?.parse::<u32>().ok())
+    };
+
+    match name.as_str() {
+        "mpn" | "mfr_part_number" | "manufacturer_part_number" => Some(Column::MfrPartNumber),
+        "manufacturer" | "mfr" => Some(Column::Manufacturer),
+        "price" | "unit_price" => Some(Column::Price),
+        "lead_time" | "lead_time_days" => Some(Column::LeadTime),
+        "currency" => Some(Column::Currency),
+        _ => numbered(&["qty_", "quantity_"])
+            .map(Column::BreakQuantity)
+            .or_else(|| numbered(&["price_"]).map(Column::BreakPrice)),
+    }
+}
+
+fn parse_price(value: &str) -> Option<f64> {
+    let value = value.trim().trim_start_matches(['$', '€', '£']).trim();
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|p| p.is_finite() && *p >= 0.0)
+}
+
+/// Parses a vendor price list. The header needs an MPN column (`mpn`, `mfr_part_number`) and
+/// prices, either a single `price` column or numbered `qty_N`/`price_N` break columns; optional
+/// columns are `manufacturer`, `lead_time` (days) and `currency`. Rows with problems are
+/// returned as issues instead of failing the whole file.
+pub fn parse_price_list(text: &str) -> Result<(Vec<PriceListRow>, Vec<PriceListRowIssue>), String> {
+    let mut records = parse_csv(text)?.into_iter();
+
+    let (_, header) = records.next().ok_or("Price list is empty")?;
+    let columns: Vec<Option<Column>> = header.iter().map(|h| column(h)).collect();
+
+    let has = |wanted: fn(&Column) -> bool| columns.iter().flatten().any(wanted);
+    if !has(|c| *c == Column::MfrPartNumber) {
+        return Err("Price list has no MPN column".to_string());
+    }
+    if !has(|c| matches!(c, Column::Price | Column::BreakPrice(_))) {
+        return Err("Price list has no price column".to_string());
+    }
+
+    let mut rows = Vec::new();
+    let mut issues = Vec::new();
+
+    for (line, record) in records {
+        if record.iter().all(|value| value.trim().is_empty()) {
+            continue;
+        }
+
+        match parse_row(line, &columns, &record) {
+            Ok(row) => rows.push(row),
+            Err(issue) => issues.push(issue),
+        }
+    }
+
+    Ok((rows, issues))
+}
+
+fn parse_row(
+    line: usize,
+    columns: &[Option<Column>],
+    record: &[String],
+) -> Result<PriceListRow, PriceListRowIssue> {
+    let mut values: BTreeMap<Column, &str> = BTreeMap::new();
+    for (column, value) in columns.iter().zip(record) {
+        if let Some(column) = column {
+            let value = value.trim();
+            if !value.is_empty() {
+                values.insert(*column, value);
+            }
+        }
+    }
+
+    let mfr_part_number = values.get(&Column::MfrPartNumber).map(|v| v.to_string());
+    let issue = |reason: String| PriceListRowIssue {
+        line,
+        mfr_part_number: mfr_part_number.clone(),
+        reason,
+    };
+
+    let Some(part_number) = mfr_part_number.clone() else {
+        return Err(issue("Missing MPN".to_string()));
+    };
+
+    let mut price_breaks: Vec<PriceBreak> = Vec::new();
+    if let Some(value) = values.get(&Column::Price) {
+        let unit_price =
+            parse_price(value).ok_or_else(|| issue(format!("Invalid price: {}", value)))?;
+        price_breaks.push(PriceBreak {
+            quantity: 1,
+            unit_price,
+        });
+    }
+
+    for (column, value) in &values {
+        let Column::BreakPrice(n) = column else {
+            continue;
+        };
+        let unit_price =
+            parse_price(value).ok_or_else(|| issue(format!("Invalid price_{}: {}", n, value)))?;
+        let quantity = match values.get(&Column::BreakQuantity(*n)) {
+            Some(quantity) => quantity
+                .parse::<i32>()
+                .ok()
+                .filter(|q| *q > 0)
+                .ok_or_else(|| issue(format!("Invalid qty_{}: {}", n, quantity)))?,
+            None => return Err(issue(format!("price_{} has no qty_{}", n, n))),
+        };
+        price_breaks.push(PriceBreak {
+            quantity,
+            unit_price,
+        });
+    }
+
+    if price_breaks.is_empty() {
+        return Err(issue("No price".to_string()));
+    }
+
+    price_breaks.sort_by_key(|b| b.quantity);
+    if price_breaks
+        .windows(2)
+        .any(|pair| pair[0].quantity == pair[1].quantity)
+    {
+        return Err(issue("Duplicate break quantity".to_string()));
+    }
+
+    let lead_time = match values.get(&Column::LeadTime) {
+        Some(value) => Some(
+            value
+                .parse::<i32>()
+                .ok()
+                .filter(|days| *days >= 0)
+                .ok_or_else(|| issue(format!("Invalid lead time: {}", value)))?,
+        ),
+        None => None,
+    };
+
+    let currency = match values.get(&Column::Currency) {
+        Some(value) if value.len() == 3 && value.chars().all(|c| c.is_ascii_alphabetic()) => {
+            Some(value.to_uppercase())
+        }
+        Some(value) => return Err(issue(format!("Invalid currency: {}", value))),
+        None => None,
+    };
+
+    Ok(PriceListRow {
+        line,
+        mfr_part_number: part_number,
+        manufacturer: values.get(&Column::Manufacturer).map(|v| v.to_string()),
+        price_breaks,
+        lead_time,
+        currency,
+    })
+}
+
+/// Price breaks stored in an inventory record's pricing. A plain `unit_price` counts as a break
+/// at quantity 1.
+pub fn price_breaks(pricing: Option<&serde_json::Value>) -> Vec<PriceBreak> {
+    let Some(pricing) = pricing else {
+        return Vec::new();
+    };
+
+    if let Some(breaks) = pricing
+        .get("price_breaks")
+        .and_then(|breaks| serde_json::from_value::<Vec<PriceBreak>>(breaks.clone()).ok())
+    {
+        return breaks;
+    }
+
+    pricing
+        .get("unit_price")
+        .and_then(|price| price.as_f64())
+        .map(|unit_price| {
+            vec![PriceBreak {
+                quantity: 1,
+                unit_price,
+            }]
+        })
+        .unwrap_or_default()
+}
+
+/// Unit price for buying `quantity`: the largest break the quantity reaches, or the smallest
+/// break when it reaches none.
+pub fn unit_price_at(breaks: &[PriceBreak], quantity: i32) -> Option<f64> {
+    breaks
+        .iter()
+        .filter(|b| b.quantity <= quantity)
+        .max_by_key(|b| b.quantity)
+        .or_else(|| breaks.iter().min_by_key(|b| b.quantity))
+        .map(|b| b.unit_price)
+}
+
+/// `breaks` with `quoted` in place of any break at the same quantity, ascending by quantity.
+pub fn merge_price_break(breaks: &[PriceBreak], quoted: PriceBreak) -> Vec<PriceBreak> {
+    let mut merged: Vec<PriceBreak> = breaks
+        .iter()
+        .filter(|b| b.quantity != quoted.quantity)
+        .copied()
+        .chain([quoted])
+        .collect();
+    merged.sort_by_key(|b| b.quantity);
+    merged
+}
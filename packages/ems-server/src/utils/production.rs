@@ -0,0 +1,93 @@
+// Production count helpers: reading good and scrap part counts from heartbeat payloads
+use serde_json::Value;
+
+use crate::utils::telemetry::numeric_at;
+
+/// How a machine reports its part counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterMode {
+    /// Each heartbeat carries the parts made since the previous one
+    Increment,
+    /// Each heartbeat carries a running total the machine keeps, which may be reset
+    Total,
+}
+
+/// Where a machine reports its good and scrap part counts in its heartbeat payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductionCounters {
+    pub produced: String,
+    pub scrap: String,
+    pub mode: CounterMode,
+}
+
+/// Part counts read from one heartbeat, in the machine's counter mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CounterReading {
+    pub produced: Option<i64>,
+    pub scrap: Option<i64>,
+}
+
+/// The part counters a machine declares under `production_counters` in its metadata, or `None`
+/// when it reports no production.
+///
+/// The declaration is either `true`, reading increments from the top-level `produced` and
+/// `scrap` keys, or an object with JSON pointers under `produced` and `scrap` and a `mode` of
+/// `increment` (the default) or `total` (e.g.
+/// `{"produced": "/counters/good", "scrap": "/counters/bad", "mode": "total"}`).
+pub fn declared_counters(metadata: Option<&Value>) -> Option<ProductionCounters> {
+    let pointer = |declaration: &Value, name: &str| {
+        declaration
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("/{}", name))
+    };
+
+    match metadata.and_then(|m| m.get("production_counters"))? {
+        Value::Bool(true) => Some(ProductionCounters {
+            produced: "/produced".to_string(),
+            scrap: "/scrap".to_string(),
+            mode: CounterMode::Increment,
+        }),
+        declaration @ Value::Object(_) => Some(ProductionCounters {
+            produced: pointer(declaration, "produced"),
+            scrap: pointer(declaration, "scrap"),
+            mode: match declaration.get("mode").and_then(Value::as_str) {
+                Some("total") => CounterMode::Total,
+                _ => CounterMode::Increment,
+            },
+        }),
+        _ => None,
+    }
+}
+
+/// The counts in a heartbeat payload; missing, negative or non-numeric counts are skipped.
+pub fn read_counters(payload: &Value, counters: &ProductionCounters) -> CounterReading {
+    let count = |pointer: &str| {
+        numeric_at(payload, pointer)
+            .filter(|value| *value >= 0.0)
+            .map(|value| value.round() as i64)
+    };
+
+    CounterReading {
+        produced: count(&counters.produced),
+        scrap: count(&counters.scrap),
+    }
+}
+
+/// Parts made since the last heartbeat given a count and, for running totals, the total last
+/// seen. The first total seen only sets the baseline, and a total lower than the last one means
+/// the machine reset its counter, so the whole total is new.
+pub fn counter_delta(mode: CounterMode, count: i64, last_total: Option<i64>) -> i64 {
+    match (mode, last_total) {
+        (CounterMode::Increment, _) => count,
+        (CounterMode::Total, None) => 0,
+        (CounterMode::Total, Some(last)) if count < last => count,
+        (CounterMode::Total, Some(last)) => count - last,
+    }
+}
+
+/// Whether the good parts made meet the assignment's target.
+pub fn target_met(produced: i32, target: Option<i32>) -> bool {
+    target.is_some_and(|target| target > 0 && produced >= target)
+}
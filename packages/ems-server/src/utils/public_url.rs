@@ -0,0 +1,118 @@
+// Guards for outbound requests to URLs that tenants supply
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use thiserror::Error;
+use url::{Host, Url};
+
+#[derive(Debug, Error)]
+pub enum PublicUrlError {
+    /// The host has no address, or its lookup failed
+    #[error("{0}")]
+    Unresolved(String),
+
+    /// The host resolves to an address that is not on the public internet
+    #[error("{0}")]
+    NotPublic(String),
+}
+
+/// Checks a URL before anything is sent to it: only http and https, and never a host on the
+/// server's own network, so tenant-supplied links cannot be used to reach internal services.
+/// Host names are checked again once resolved. `what` names the URL in error messages.
+pub fn check_public_url(url: &str, what: &str) -> Result<Url, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid {} URL: {}", what, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "Invalid {} URL: {} is not supported, use http or https",
+            what,
+            parsed.scheme()
+        ));
+    }
+    match parsed.host() {
+        None => return Err(format!("Invalid {} URL: no host", what)),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                return Err(format!("Invalid {} URL: local hosts are not allowed", what));
+            }
+        }
+        Some(Host::Ipv4(ip)) => check_public_address(IpAddr::V4(ip), what)?,
+        Some(Host::Ipv6(ip)) => check_public_address(IpAddr::V6(ip), what)?,
+    }
+    Ok(parsed)
+}
+
+/// Refuses addresses that are not on the public internet.
+pub fn check_public_address(ip: IpAddr, what: &str) -> Result<(), String> {
+    if is_public_ip(ip) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid {} URL: {} is not a public address",
+            what, ip
+        ))
+    }
+}
+
+/// Resolves the host of a checked URL and checks every address it resolves to. The caller
+/// connects to the returned address, so a second lookup cannot lead elsewhere. `None` for
+/// address hosts, which were checked with the URL.
+pub async fn resolve_public_host(
+    url: &Url,
+    what: &str,
+) -> Result<Option<(String, SocketAddr)>, PublicUrlError> {
+    let Some(Host::Domain(domain)) = url.host() else {
+        return Ok(None);
+    };
+    let port = url.port_or_known_default().unwrap_or(443);
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+        .await
+        .map_err(|e| PublicUrlError::Unresolved(format!("{} not resolved: {}", domain, e)))?
+        .collect();
+    let [address, ..] = addresses[..] else {
+        return Err(PublicUrlError::Unresolved(format!(
+            "{} not resolved",
+            domain
+        )));
+    };
+    for address in &addresses {
+        check_public_address(address.ip(), what).map_err(PublicUrlError::NotPublic)?;
+    }
+    Ok(Some((domain.to_string(), address)))
+}
+
+/// Whether `ip` is routable on the public internet, rather than loopback, private, link-local,
+/// shared, documentation, multicast or unspecified.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public_ipv4(mapped),
+            None => is_public_ipv6(ip),
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Shared address space (100.64.0.0/10) and the reserved 0.0.0.0/8 and 240.0.0.0/4
+        || (a == 100 && (64..128).contains(&b))
+        || a == 0
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // Unique local (fc00::/7) and link-local (fe80::/10)
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80)
+}
@@ -0,0 +1,43 @@
+// Non-conformance and CAPA rules
+use chrono::{DateTime, Utc};
+
+use crate::models::{CapaActionType, CapaStatus, NcrSeverity};
+
+/// Why an NCR with these actions cannot be closed yet, if anything. Every action must be
+/// finished, and major or critical NCRs also need a completed root cause and corrective action.
+pub fn close_blocker(
+    severity: NcrSeverity,
+    actions: &[(CapaActionType, CapaStatus)],
+) -> Option<String> {
+    let open = actions
+        .iter()
+        .filter(|(_, status)| status.is_active())
+        .count();
+    if open > 0 {
+        return Some(format!("CAPA actions still open: {}", open));
+    }
+
+    if severity >= NcrSeverity::Major {
+        let completed = |action_type: CapaActionType| {
+            actions
+                .iter()
+                .any(|(t, status)| *t == action_type && *status == CapaStatus::Completed)
+        };
+        if !completed(CapaActionType::RootCause) {
+            return Some(format!("{} NCR needs a completed root cause", severity));
+        }
+        if !completed(CapaActionType::Corrective) {
+            return Some(format!(
+                "{} NCR needs a completed corrective action",
+                severity
+            ));
+        }
+    }
+
+    None
+}
+
+/// Whole days an action due at `due` has been overdue at `now`; zero until a full day has passed.
+pub fn days_overdue(due: DateTime<Utc>, now: DateTime<Utc>) -> i64 {
+    (now - due).num_days().max(0)
+}
@@ -0,0 +1,222 @@
+// Database query timing, slow-query logging and per-endpoint query metrics
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Queries taking at least this long are logged when SLOW_QUERY_MS is not set
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 500;
+// Longest statement text written to a slow-query log line
+pub const MAX_LOGGED_STATEMENT_CHARS: usize = 1000;
+// Label for queries run outside a request, e.g. by background workers
+pub const BACKGROUND_ENDPOINT: &str = "background";
+
+tokio::task_local! {
+    // Endpoint whose queries the current task runs
+    static QUERY_ENDPOINT: String;
+}
+
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+static REGISTRY: OnceLock<QueryMetrics> = OnceLock::new();
+
+/// Query counts and durations for one endpoint since the server started.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EndpointQueryStats {
+    pub endpoint: String,
+    pub queries: u64,
+    pub failed: u64,
+    pub slow: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Per-endpoint query metrics, filled in by [`QueryInstrumentation`] on every connection.
+#[derive(Debug, Default)]
+pub struct QueryMetrics {
+    endpoints: Mutex<HashMap<String, EndpointQueryStats>>,
+}
+
+impl QueryMetrics {
+    /// The process-wide registry.
+    pub fn global() -> &'static QueryMetrics {
+        REGISTRY.get_or_init(QueryMetrics::default)
+    }
+
+    pub fn record(&self, endpoint: &str, duration: Duration, slow: bool, failed: bool) {
+        let Ok(mut endpoints) = self.endpoints.lock() else {
+            return;
+        };
+        let stats = endpoints
+            .entry(endpoint.to_string())
+            .or_insert_with(|| EndpointQueryStats {
+                endpoint: endpoint.to_string(),
+                ..Default::default()
+            });
+        let ms = duration.as_secs_f64() * 1000.0;
+        stats.queries += 1;
+        stats.total_ms += ms;
+        stats.mean_ms = stats.total_ms / stats.queries as f64;
+        stats.max_ms = stats.max_ms.max(ms);
+        stats.slow += slow as u64;
+        stats.failed += failed as u64;
+    }
+
+    /// Every endpoint's stats, the ones spending the most time in the database first.
+    pub fn snapshot(&self) -> Vec<EndpointQueryStats> {
+        let mut stats: Vec<_> = self
+            .endpoints
+            .lock()
+            .map(|endpoints| endpoints.values().cloned().collect())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        stats
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut endpoints) = self.endpoints.lock() {
+            endpoints.clear();
+        }
+    }
+}
+
+/// Slow-query threshold from SLOW_QUERY_MS, read once. Zero logs every query.
+pub fn slow_query_threshold() -> Duration {
+    *SLOW_QUERY_THRESHOLD.get_or_init(|| {
+        let ms = env::var("SLOW_QUERY_MS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_SLOW_QUERY_MS);
+        Duration::from_millis(ms)
+    })
+}
+
+/// Runs `future` with its queries counted against `endpoint`. Tasks spawned from inside the
+/// future, and response bodies streamed after it returns, count as background queries.
+pub async fn scope_endpoint<F: Future>(endpoint: String, future: F) -> F::Output {
+    QUERY_ENDPOINT.scope(endpoint, future).await
+}
+
+/// The endpoint the current task's queries are counted against.
+pub fn current_endpoint() -> String {
+    QUERY_ENDPOINT
+        .try_with(|endpoint| endpoint.clone())
+        .unwrap_or_else(|_| BACKGROUND_ENDPOINT.to_string())
+}
+
+/// Metrics label for a request: the method and path with ids replaced by `:id`, so requests
+/// for different records share a label.
+pub fn endpoint_label(method: &str, path: &str) -> String {
+    let path = path
+        .split('/')
+        .map(|segment| {
+            let is_id = uuid::Uuid::parse_str(segment).is_ok()
+                || (!segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()));
+            if is_id {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    format!("{} {}", method, path)
+}
+
+/// Tenant a `SET app.current_tenant_id = '...'` statement switches the connection to.
+pub fn tenant_from_statement(sql: &str) -> Option<&str> {
+    let rest = sql.trim_start().strip_prefix("SET")?.trim_start();
+    let rest = rest.strip_prefix("LOCAL").unwrap_or(rest).trim_start();
+    let value = rest
+        .strip_prefix("app.current_tenant_id")?
+        .trim_start()
+        .strip_prefix('=')?;
+    let value = value.trim().trim_end_matches(';').trim().trim_matches('\'');
+    (!value.is_empty()).then_some(value)
+}
+
+/// SQL text of a query for the log: bind values are left out, since they can hold secrets,
+/// and long statements are cut short.
+pub fn loggable_statement(query: &str) -> String {
+    let sql = query.split(" -- binds:").next().unwrap_or(query).trim();
+    match sql.char_indices().nth(MAX_LOGGED_STATEMENT_CHARS) {
+        Some((end, _)) => format!("{}...", &sql[..end]),
+        None => sql.to_string(),
+    }
+}
+
+/// Diesel instrumentation timing each query on a connection. Durations go to the current
+/// tracing span as debug events and to [`QueryMetrics::global`]; queries over the slow-query
+/// threshold are logged with their statement, endpoint and tenant.
+pub struct QueryInstrumentation {
+    slow_threshold: Duration,
+    // Start times of queries in flight; pipelined queries finish in the order they started
+    started: VecDeque<Instant>,
+    // Tenant context last set on this connection
+    tenant_id: Option<String>,
+}
+
+impl QueryInstrumentation {
+    pub fn new(slow_threshold: Duration) -> Self {
+        Self {
+            slow_threshold,
+            started: VecDeque::new(),
+            tenant_id: None,
+        }
+    }
+
+    /// Installs the instrumentation on every connection established from now on.
+    pub fn install() -> anyhow::Result<()> {
+        diesel::connection::set_default_instrumentation(|| {
+            Some(Box::new(QueryInstrumentation::new(slow_query_threshold())))
+        })?;
+        Ok(())
+    }
+
+    fn finish(&mut self, query: &str, failed: bool) {
+        let Some(started) = self.started.pop_front() else {
+            return;
+        };
+        let duration = started.elapsed();
+        let endpoint = current_endpoint();
+        let slow = duration >= self.slow_threshold;
+        QueryMetrics::global().record(&endpoint, duration, slow, failed);
+
+        let duration_ms = duration.as_secs_f64() * 1000.0;
+        let tenant_id = self.tenant_id.as_deref().unwrap_or("-");
+        if slow {
+            tracing::warn!(
+                target: "ems_server::db",
+                duration_ms,
+                endpoint = %endpoint,
+                tenant_id,
+                failed,
+                "Slow query: {}",
+                loggable_statement(query)
+            );
+        } else {
+            tracing::debug!(target: "ems_server::db", duration_ms, endpoint = %endpoint, tenant_id, failed, "Query");
+        }
+    }
+}
+
+impl Instrumentation for QueryInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started.push_back(Instant::now()),
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let sql = query.to_string();
+                if error.is_none() {
+                    if let Some(tenant_id) = tenant_from_statement(&sql) {
+                        self.tenant_id = Some(tenant_id.to_string());
+                    }
+                }
+                self.finish(&sql, error.is_some());
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// Quote pricing and document helpers
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::QuoteResponse;
+use crate::utils::branding::DocumentBranding;
+use crate::utils::i18n::{Locale, Text};
+use crate::utils::pdf::{truncate, Font, PdfDocument, PAGE_HEIGHT, PAGE_WIDTH};
+
+// Page margin of the quote document in points
+const MARGIN: f64 = 50.0;
+// Longest item name printed before it is cut short
+const MAX_NAME_CHARS: usize = 48;
+
+pub fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Cost-plus unit price: `cost` marked up by `margin_percent`, rounded to cents.
+pub fn margin_price(cost: f64, margin_percent: f64) -> f64 {
+    round_cents(cost * (1.0 + margin_percent / 100.0))
+}
+
+/// Cost of one unit of `item_id` rolled up through its BOM. `boms` maps each assembly to its
+/// components and their quantity per assembly in base units; `costs` holds the unit cost of bought-in parts.
+/// Sub-assemblies are costed from their own BOM.
+pub fn bom_cost(
+    item_id: Uuid,
+    boms: &HashMap<Uuid, Vec<(Uuid, f64)>>,
+    costs: &HashMap<Uuid, f64>,
+) -> Result<f64, String> {
+    if boms
+        .get(&item_id)
+        .is_none_or(|components| components.is_empty())
+    {
+        return Err(format!("item has no BOM: {}", item_id));
+    }
+    rollup(item_id, boms, costs, &mut HashSet::new())
+}
+
+fn rollup(
+    item_id: Uuid,
+    boms: &HashMap<Uuid, Vec<(Uuid, f64)>>,
+    costs: &HashMap<Uuid, f64>,
+    path: &mut HashSet<Uuid>,
+) -> Result<f64, String> {
+    let Some(components) = boms.get(&item_id).filter(|c| !c.is_empty()) else {
+        return costs
+            .get(&item_id)
+            .copied()
+            .ok_or_else(|| format!("no cost for BOM component: {}", item_id));
+    };
+
+    if !path.insert(item_id) {
+        return Err(format!("BOM contains a cycle at item: {}", item_id));
+    }
+    let mut total = 0.0;
+    for (component_id, quantity) in components {
+        total += rollup(*component_id, boms, costs, path)? * *quantity;
+    }
+    path.remove(&item_id);
+
+    Ok(total)
+}
+
+/// Renders the customer-facing quote in `locale` and the tenant's `branding`: header, one row
+/// per line and the total. Rows continue on further pages as needed.
+pub fn render_quote_pdf(
+    quote: &QuoteResponse,
+    locale: Locale,
+    branding: &DocumentBranding,
+) -> Vec<u8> {
+    let right = PAGE_WIDTH - MARGIN;
+    let quantity_right = right - 170.0;
+    let price_right = right - 85.0;
+
+    let mut pdf = PdfDocument::new();
+    if branding.is_branded() {
+        pdf.set_letterhead(branding.letterhead());
+    }
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Bold,
+        18.0,
+        &locale.format(Text::QuoteTitle, &[&quote.quote_number]),
+    );
+    y -= 28.0;
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Regular,
+        10.0,
+        &locale.format(Text::Customer, &[&quote.customer_name]),
+    );
+    y -= 14.0;
+    pdf.text(
+        MARGIN,
+        y,
+        Font::Regular,
+        10.0,
+        &locale.format(Text::Date, &[&locale.date(quote.created_at)]),
+    );
+    if let Some(valid_until) = quote.valid_until {
+        y -= 14.0;
+        pdf.text(
+            MARGIN,
+            y,
+            Font::Regular,
+            10.0,
+            &locale.format(Text::ValidUntil, &[&locale.date(valid_until)]),
+        );
+    }
+    y -= 30.0;
+
+    let header = |pdf: &mut PdfDocument, y: f64| {
+        pdf.text(MARGIN, y, Font::Bold, 10.0, "#");
+        pdf.text(MARGIN + 25.0, y, Font::Bold, 10.0, locale.text(Text::Item));
+        pdf.text_right(
+            quantity_right,
+            y,
+            Font::Bold,
+            10.0,
+            locale.text(Text::Quantity),
+        );
+        pdf.text_right(
+            price_right,
+            y,
+            Font::Bold,
+            10.0,
+            locale.text(Text::UnitPrice),
+        );
+        pdf.text_right(right, y, Font::Bold, 10.0, locale.text(Text::Amount));
+        pdf.line(MARGIN, y - 5.0, right, y - 5.0);
+    };
+    header(&mut pdf, y);
+    y -= 20.0;
+
+    for item in &quote.items {
+        let rows = if item.item_description.is_some() {
+            2.0
+        } else {
+            1.0
+        };
+        if y - rows * 12.0 < MARGIN {
+            pdf.add_page();
+            y = PAGE_HEIGHT - MARGIN;
+            header(&mut pdf, y);
+            y -= 20.0;
+        }
+
+        pdf.text(
+            MARGIN,
+            y,
+            Font::Regular,
+            10.0,
+            &item.line_number.to_string(),
+        );
+        pdf.text(
+            MARGIN + 25.0,
+            y,
+            Font::Regular,
+            10.0,
+            &truncate(&item.item_name, MAX_NAME_CHARS),
+        );
+        pdf.text_right(
+            quantity_right,
+            y,
+            Font::Regular,
+            10.0,
+            &item.quantity.to_string(),
+        );
+        pdf.text_right(
+            price_right,
+            y,
+            Font::Regular,
+            10.0,
+            &format!("{:.2}", item.unit_price),
+        );
+        pdf.text_right(
+            right,
+            y,
+            Font::Regular,
+            10.0,
+            &format!("{:.2}", item.extended_price),
+        );
+        if let Some(description) = &item.item_description {
+            y -= 12.0;
+            pdf.text(
+                MARGIN + 25.0,
+                y,
+                Font::Regular,
+                8.0,
+                &truncate(description, MAX_NAME_CHARS),
+            );
+        }
+        y -= 16.0;
+    }
+
+    if y - 40.0 < MARGIN {
+        pdf.add_page();
+        y = PAGE_HEIGHT - MARGIN;
+    }
+    pdf.line(price_right - 60.0, y + 6.0, right, y + 6.0);
+    y -= 8.0;
+    pdf.text_right(price_right, y, Font::Bold, 11.0, locale.text(Text::Total));
+    pdf.text_right(
+        right,
+        y,
+        Font::Bold,
+        11.0,
+        &format!("{:.2}", quote.total_amount),
+    );
+
+    if let Some(notes) = &quote.notes {
+        y -= 30.0;
+        for line in notes.lines() {
+            if y < MARGIN {
+                pdf.add_page();
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            pdf.text(MARGIN, y, Font::Regular, 9.0, line);
+            y -= 12.0;
+        }
+    }
+
+    pdf.render()
+}
+
+/// Subject and body of the email the quote PDF is sent with, in `locale`.
+pub fn quote_email(
+    quote: &QuoteResponse,
+    message: Option<&str>,
+    locale: Locale,
+) -> (String, String) {
+    let subject = locale.format(Text::QuoteTitle, &[&quote.quote_number]);
+    let mut body = format!(
+        "{}\n\n{}\n\n{}\n",
+        locale.format(Text::Greeting, &[&quote.customer_name]),
+        locale.format(Text::QuoteAttached, &[&quote.quote_number]),
+        locale.format(Text::TotalAmount, &[&format!("{:.2}", quote.total_amount)]),
+    );
+    if let Some(valid_until) = quote.valid_until {
+        body.push_str(&locale.format(Text::ValidUntil, &[&locale.date(valid_until)]));
+        body.push('\n');
+    }
+    if let Some(message) = message {
+        body.push_str(&format!("\n{}\n", message));
+    }
+    (subject, body)
+}
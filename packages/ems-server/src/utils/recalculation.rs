@@ -0,0 +1,27 @@
+// Recalculation task helpers
+use serde_json::{json, Value};
+
+/// Share of records gone through, 0-100 with one decimal. `None` until the total is known;
+/// a task with nothing to go through is complete.
+pub fn progress_percent(processed: i32, total: Option<i32>) -> Option<f64> {
+    let total = total?;
+    if total <= 0 {
+        return Some(100.0);
+    }
+    let percent = processed.clamp(0, total) as f64 * 100.0 / total as f64;
+    Some((percent * 10.0).round() / 10.0)
+}
+
+/// The pricing with `unit_cost` set, keeping the other keys. Returns `None` when the unit cost
+/// is already the given one, so unchanged records are not written.
+pub fn with_unit_cost(pricing: Option<&Value>, unit_cost: f64) -> Option<Value> {
+    let mut pricing = match pricing {
+        Some(Value::Object(fields)) => fields.clone(),
+        _ => serde_json::Map::new(),
+    };
+    if pricing.get("unit_cost").and_then(Value::as_f64) == Some(unit_cost) {
+        return None;
+    }
+    pricing.insert("unit_cost".to_string(), json!(unit_cost));
+    Some(Value::Object(pricing))
+}
@@ -0,0 +1,31 @@
+// Order return (RMA) progress helpers
+use crate::models::ReturnStatus;
+
+/// Quantities of one return line: requested, received so far and dispositioned so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReturnLineProgress {
+    pub quantity: i32,
+    pub received: i32,
+    pub dispositioned: i32,
+}
+
+/// Status of an open return from the progress of its lines. A return closes once every line is
+/// fully received and nothing is left in quarantine.
+pub fn return_status(lines: &[ReturnLineProgress]) -> ReturnStatus {
+    if lines.iter().all(|line| line.received == 0) {
+        return ReturnStatus::Requested;
+    }
+    if lines.iter().any(|line| line.received < line.quantity) {
+        return ReturnStatus::PartiallyReceived;
+    }
+    if lines.iter().all(|line| line.dispositioned >= line.received) {
+        return ReturnStatus::Closed;
+    }
+    ReturnStatus::Received
+}
+
+/// How many more units of an order item can be returned, given the ordered quantity and the
+/// quantity already on other open or closed returns.
+pub fn returnable_quantity(ordered: i32, already_returned: i32) -> i32 {
+    (ordered - already_returned).max(0)
+}
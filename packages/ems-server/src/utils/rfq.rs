@@ -0,0 +1,170 @@
+// Vendor RFQ helpers: response link tokens, the quote comparison and the request email
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    Rfq, RfqComparisonLine, RfqComparisonResponse, RfqItem, RfqOffer, RfqQuote, RfqVendor,
+    RfqVendorStatus, RfqVendorTotal,
+};
+use crate::utils::i18n::{Locale, Text};
+use crate::utils::quote::round_cents;
+
+/// A new token for a vendor's response link. The tenant comes first so the link can be served
+/// from the tenant's own database; the random part after the dot is what keeps it secret.
+pub fn response_token(tenant_id: Uuid) -> String {
+    format!(
+        "{}.{}{}",
+        tenant_id.simple(),
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// The tenant a response token was issued for, `None` when it is not one.
+pub fn token_tenant(token: &str) -> Option<Uuid> {
+    let (tenant, secret) = token.split_once('.')?;
+    if secret.len() != 64 || !secret.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Uuid::try_parse(tenant).ok()
+}
+
+/// Every line with each vendor's offer, cheapest first, and each vendor's total. Quotes for
+/// lines no longer on the RFQ are ignored.
+pub fn compare_quotes(
+    rfq: &Rfq,
+    items: &[RfqItem],
+    vendors: &[(RfqVendor, String)],
+    quotes: &[RfqQuote],
+) -> RfqComparisonResponse {
+    let vendor_ids: HashMap<Uuid, Uuid> = vendors
+        .iter()
+        .map(|(vendor, _)| (vendor.id, vendor.vendor_id))
+        .collect();
+    let quantities: HashMap<Uuid, i32> = items.iter().map(|i| (i.id, i.quantity)).collect();
+
+    let lines = items
+        .iter()
+        .map(|item| {
+            let mut offers: Vec<RfqOffer> = quotes
+                .iter()
+                .filter(|q| q.rfq_item_id == item.id)
+                .filter_map(|q| {
+                    Some(RfqOffer {
+                        vendor_id: *vendor_ids.get(&q.rfq_vendor_id)?,
+                        unit_price: q.unit_price,
+                        extended_price: round_cents(q.unit_price * item.quantity as f64),
+                        lead_time: q.lead_time,
+                        is_lowest_price: false,
+                        is_shortest_lead_time: false,
+                    })
+                })
+                .collect();
+            offers.sort_by(|a, b| a.unit_price.total_cmp(&b.unit_price));
+
+            let lowest_price = offers.first().map(|o| o.unit_price);
+            let shortest_lead_time = offers.iter().filter_map(|o| o.lead_time).min();
+            for offer in &mut offers {
+                offer.is_lowest_price = Some(offer.unit_price) == lowest_price;
+                offer.is_shortest_lead_time =
+                    offer.lead_time.is_some() && offer.lead_time == shortest_lead_time;
+            }
+
+            RfqComparisonLine {
+                rfq_item_id: item.id,
+                line_number: item.line_number,
+                item_id: item.item_id,
+                item_name: item.item_name.clone(),
+                quantity: item.quantity,
+                offers,
+            }
+        })
+        .collect();
+
+    let mut totals: Vec<RfqVendorTotal> = vendors
+        .iter()
+        .map(|(vendor, vendor_name)| {
+            let vendor_quotes: Vec<&RfqQuote> = quotes
+                .iter()
+                .filter(|q| q.rfq_vendor_id == vendor.id)
+                .filter(|q| quantities.contains_key(&q.rfq_item_id))
+                .collect();
+            let total_amount = vendor_quotes
+                .iter()
+                .map(|q| round_cents(q.unit_price * quantities[&q.rfq_item_id] as f64))
+                .sum::<f64>();
+            RfqVendorTotal {
+                vendor_id: vendor.vendor_id,
+                vendor_name: vendor_name.clone(),
+                status: RfqVendorStatus::try_from(vendor.status.clone())
+                    .unwrap_or(RfqVendorStatus::Pending),
+                currency: vendor.currency.clone(),
+                quoted_lines: vendor_quotes.len(),
+                is_complete: !items.is_empty() && vendor_quotes.len() == items.len(),
+                total_amount: round_cents(total_amount),
+                max_lead_time: vendor_quotes.iter().filter_map(|q| q.lead_time).max(),
+                is_lowest_total: false,
+            }
+        })
+        .collect();
+
+    let lowest_total = totals
+        .iter()
+        .filter(|t| t.is_complete)
+        .map(|t| t.total_amount)
+        .min_by(f64::total_cmp);
+    for total in &mut totals {
+        total.is_lowest_total = total.is_complete && Some(total.total_amount) == lowest_total;
+    }
+    totals.sort_by(|a, b| {
+        b.is_complete
+            .cmp(&a.is_complete)
+            .then(a.total_amount.total_cmp(&b.total_amount))
+    });
+
+    RfqComparisonResponse {
+        rfq_id: rfq.id,
+        rfq_number: rfq.rfq_number.clone(),
+        lines,
+        vendors: totals,
+    }
+}
+
+/// Subject and body of the email asking a vendor to quote: the lines, the deadline and where to
+/// respond. Without a `response_url` the token is given as a code instead of a link.
+pub fn rfq_email(
+    rfq: &Rfq,
+    vendor_name: &str,
+    items: &[RfqItem],
+    response_url: Option<&str>,
+    token: &str,
+    message: Option<&str>,
+    locale: Locale,
+) -> (String, String) {
+    let subject = locale.format(Text::RfqTitle, &[&rfq.rfq_number]);
+    let mut body = format!(
+        "{}\n\n{}\n\n",
+        locale.format(Text::Greeting, &[vendor_name]),
+        locale.text(Text::RfqRequested),
+    );
+    for item in items {
+        body.push_str(&format!(
+            "{}. {} x {}\n",
+            item.line_number, item.item_name, item.quantity
+        ));
+    }
+    body.push('\n');
+    if let Some(respond_by) = rfq.respond_by {
+        body.push_str(&locale.format(Text::RespondBy, &[&locale.date(respond_by)]));
+        body.push('\n');
+    }
+    body.push_str(&match response_url {
+        Some(url) => locale.format(Text::RfqRespondAt, &[&format!("{}{}", url, token)]),
+        None => locale.format(Text::RfqResponseCode, &[token]),
+    });
+    body.push('\n');
+    if let Some(message) = message {
+        body.push_str(&format!("\n{}\n", message));
+    }
+    (subject, body)
+}
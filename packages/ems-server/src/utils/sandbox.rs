@@ -0,0 +1,44 @@
+// Sandbox helpers: names, subdomains and expiry of sandbox tenants
+
+use chrono::{DateTime, Duration, Utc};
+
+// Longest subdomain a tenant can have
+const MAX_SUBDOMAIN_LEN: usize = 50;
+
+/// Name of a sandbox cloned from the tenant named `source`.
+pub fn sandbox_name(source: &str) -> String {
+    let name = format!("{} (sandbox)", source);
+    if name.chars().count() <= 100 {
+        name
+    } else {
+        let kept: String = source.chars().take(100 - " (sandbox)".len()).collect();
+        format!("{} (sandbox)", kept.trim_end())
+    }
+}
+
+/// Subdomain of a sandbox cloned from the tenant at `source`: the source subdomain followed by
+/// `-sandbox-` and the suffix, shortened so the whole fits a tenant subdomain.
+pub fn sandbox_subdomain(source: &str, suffix: &str) -> String {
+    let tail = format!("-sandbox-{}", suffix);
+    let room = MAX_SUBDOMAIN_LEN.saturating_sub(tail.len());
+    let head = source[..source.len().min(room)].trim_end_matches('-');
+    format!("{}{}", head, tail)
+}
+
+/// When a sandbox created at `now` expires: after `requested_days`, or `default_days` when not
+/// requested. Requests beyond `max_days` are refused.
+pub fn sandbox_expiry(
+    now: DateTime<Utc>,
+    requested_days: Option<i64>,
+    default_days: i64,
+    max_days: i64,
+) -> Result<DateTime<Utc>, String> {
+    let days = requested_days.unwrap_or(default_days.min(max_days));
+    if days < 1 || days > max_days {
+        return Err(format!(
+            "Sandbox expiry must be between 1 and {} days",
+            max_days
+        ));
+    }
+    Ok(now + Duration::days(days))
+}
@@ -0,0 +1,61 @@
+// Supplier scorecard helpers
+use chrono::{DateTime, Utc};
+
+use crate::models::Quantity;
+
+/// How a promised purchase order line turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    OnTime,
+    /// Received this many calendar days after the promised date
+    Late(i64),
+    /// Still not (fully) received after the promised date
+    Overdue,
+}
+
+/// Date each line was fully received, allocating receipts to lines first come, first served.
+/// `lines` holds ordered quantities in the order they should be filled (earliest promise
+/// first); `None` marks a line the receipts do not cover yet.
+pub fn fill_dates(
+    lines: &[Quantity],
+    receipts: &[(DateTime<Utc>, Quantity)],
+) -> Vec<Option<DateTime<Utc>>> {
+    let mut receipts = receipts.to_vec();
+    receipts.sort_by_key(|(date, _)| *date);
+    let mut receipts = receipts.into_iter();
+
+    let mut carried: Option<(DateTime<Utc>, Quantity)> = None;
+    lines
+        .iter()
+        .map(|&quantity| {
+            let mut outstanding = quantity;
+            let mut filled = None;
+            while outstanding > Quantity::ZERO {
+                let (date, available) = carried.take().or_else(|| receipts.next())?;
+                let used = available.min(outstanding);
+                outstanding -= used;
+                if available > used {
+                    carried = Some((date, available - used));
+                }
+                filled = Some(date);
+            }
+            filled
+        })
+        .collect()
+}
+
+/// Scores a line promised for `expected`. Arriving any time on the promised calendar day is on
+/// time; lines neither received nor yet due are not scored.
+pub fn delivery_outcome(
+    expected: DateTime<Utc>,
+    received: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Option<DeliveryOutcome> {
+    let days_late = |at: DateTime<Utc>| (at.date_naive() - expected.date_naive()).num_days();
+    match received {
+        Some(at) if days_late(at) <= 0 => Some(DeliveryOutcome::OnTime),
+        Some(at) => Some(DeliveryOutcome::Late(days_late(at))),
+        None if days_late(now) > 0 => Some(DeliveryOutcome::Overdue),
+        None => None,
+    }
+}
@@ -0,0 +1,71 @@
+// Search suggestion helpers
+use crate::models::{Suggestion, SuggestionRow, SuggestionType};
+
+/// Fewer characters than this match too much to be worth suggesting
+pub const MIN_SUGGEST_CHARS: usize = 2;
+
+pub const DEFAULT_SUGGEST_LIMIT: u32 = 8;
+
+/// Types to suggest from a comma-separated list, in the order given and without repeats; every
+/// type when the list is missing or empty.
+pub fn parse_suggestion_types(types: Option<&str>) -> Result<Vec<SuggestionType>, String> {
+    let mut parsed = Vec::new();
+    for name in types.unwrap_or_default().split(',') {
+        let name = name.trim().to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let suggestion_type = SuggestionType::try_from(name.as_str())?;
+        if !parsed.contains(&suggestion_type) {
+            parsed.push(suggestion_type);
+        }
+    }
+    if parsed.is_empty() {
+        parsed.extend(SuggestionType::ALL);
+    }
+    Ok(parsed)
+}
+
+/// Escapes the LIKE wildcards in typed text so it only matches literally.
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Best matches across all types: highest score first, then by label, at most `limit`.
+pub fn merge_suggestions(
+    groups: Vec<(SuggestionType, Vec<SuggestionRow>)>,
+    limit: usize,
+) -> Vec<Suggestion> {
+    let mut ranked: Vec<(f64, Suggestion)> = groups
+        .into_iter()
+        .flat_map(|(suggestion_type, rows)| {
+            rows.into_iter().map(move |row| {
+                (
+                    row.score,
+                    Suggestion {
+                        id: row.id,
+                        label: row.label,
+                        detail: row.detail,
+                        suggestion_type,
+                    },
+                )
+            })
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.0.total_cmp(&a.0)
+            .then_with(|| a.1.label.to_lowercase().cmp(&b.1.label.to_lowercase()))
+    });
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
@@ -0,0 +1,83 @@
+// Scopes of machine tokens issued to service clients
+use axum::http::Method;
+
+/// Role of machine tokens issued to service clients
+pub const SERVICE_ROLE: &str = "service";
+
+/// Scope giving a service client its owner's access level, so it may use endpoints needing
+/// admin access
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// API areas service clients can be granted, by the path segment after `/api/v1/`. Tenant,
+/// billing and platform admin endpoints are left out; they stay with people.
+pub const SERVICE_SCOPE_AREAS: [&str; 20] = [
+    "asset",
+    "calendar",
+    "dashboards",
+    "ingest-sources",
+    "item",
+    "job",
+    "machine",
+    "machine-groups",
+    "numbering-sequences",
+    "order",
+    "person",
+    "printer",
+    "quality",
+    "quote",
+    "recalculations",
+    "report",
+    "return",
+    "search",
+    "shipment",
+    "skill",
+];
+
+/// Whether `scope` is `admin` or `<area>:read` / `<area>:write` for a grantable area.
+pub fn is_valid_scope(scope: &str) -> bool {
+    if scope == ADMIN_SCOPE {
+        return true;
+    }
+    match scope.split_once(':') {
+        Some((area, access)) => {
+            SERVICE_SCOPE_AREAS.contains(&area) && matches!(access, "read" | "write")
+        }
+        None => false,
+    }
+}
+
+/// Splits a space separated `scope` parameter, dropping duplicates.
+pub fn parse_scopes(scope: &str) -> Vec<String> {
+    let mut scopes: Vec<String> = Vec::new();
+    for scope in scope.split_whitespace() {
+        if !scopes.iter().any(|s| s == scope) {
+            scopes.push(scope.to_string());
+        }
+    }
+    scopes
+}
+
+/// Scope a request needs: `<area>:read` for safe methods, `<area>:write` otherwise. `None` when
+/// the endpoint is not open to service clients at all.
+pub fn required_scope(method: &Method, path: &str) -> Option<String> {
+    let area = path.strip_prefix("/api/v1/")?.split('/').next()?;
+    if !SERVICE_SCOPE_AREAS.contains(&area) {
+        return None;
+    }
+    let access = if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        "read"
+    } else {
+        "write"
+    };
+    Some(format!("{}:{}", area, access))
+}
+
+/// Whether `granted` covers `required`; `<area>:write` covers `<area>:read` too.
+pub fn scopes_allow(granted: &[String], required: &str) -> bool {
+    granted.iter().any(|scope| {
+        scope == required
+            || required
+                .strip_suffix(":read")
+                .is_some_and(|area| *scope == format!("{}:write", area))
+    })
+}
@@ -0,0 +1,44 @@
+// Shipment tracking and fulfillment helpers
+use chrono::{DateTime, Utc};
+
+use crate::models::{OrderStatus, ShipmentStatus};
+
+/// Status a shipment is in after its tracking history: the status of the latest event, except
+/// that a delivered shipment stays delivered when late scans arrive out of order.
+pub fn tracked_status(
+    current: ShipmentStatus,
+    events: &[(ShipmentStatus, DateTime<Utc>)],
+) -> ShipmentStatus {
+    if events
+        .iter()
+        .any(|(status, _)| *status == ShipmentStatus::Delivered)
+    {
+        return ShipmentStatus::Delivered;
+    }
+
+    events
+        .iter()
+        .filter(|(status, _)| status.is_tracking_status())
+        .max_by_key(|(_, occurred_at)| *occurred_at)
+        .map(|(status, _)| *status)
+        .unwrap_or(current)
+}
+
+/// Fulfillment status of an order from the statuses of its shipments, ignoring cancelled ones.
+/// Returns `None` while nothing has been delivered.
+pub fn fulfillment_status(shipments: &[ShipmentStatus]) -> Option<OrderStatus> {
+    let active: Vec<_> = shipments
+        .iter()
+        .filter(|status| **status != ShipmentStatus::Cancelled)
+        .collect();
+    let delivered = active
+        .iter()
+        .filter(|status| ***status == ShipmentStatus::Delivered)
+        .count();
+
+    match delivered {
+        0 => None,
+        n if n == active.len() => Some(OrderStatus::Fulfilled),
+        _ => Some(OrderStatus::PartiallyFulfilled),
+    }
+}
@@ -0,0 +1,174 @@
+// Latency recording, percentiles and pass/fail gates for the soak load harness
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Latencies of one kind of request over a soak run. Failed requests count towards the
+/// percentiles as well as the error rate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct OperationReport {
+    pub operation: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    /// Requests per second over the run
+    pub throughput: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Outcome of a soak run, one entry per operation in name order.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SoakReport {
+    pub duration_secs: f64,
+    pub tenants: usize,
+    pub machines: usize,
+    pub operations: Vec<OperationReport>,
+}
+
+impl SoakReport {
+    /// The report as a plain-text table for the console.
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "{} tenants, {} machines, {:.1}s\n{:<16} {:>8} {:>7} {:>9} {:>9} {:>9} {:>9} {:>9}\n",
+            self.tenants,
+            self.machines,
+            self.duration_secs,
+            "operation",
+            "requests",
+            "errors",
+            "req/s",
+            "p50 ms",
+            "p90 ms",
+            "p99 ms",
+            "max ms"
+        );
+        for op in &self.operations {
+            table.push_str(&format!(
+                "{:<16} {:>8} {:>7} {:>9.1} {:>9.1} {:>9.1} {:>9.1} {:>9.1}\n",
+                op.operation,
+                op.requests,
+                op.errors,
+                op.throughput,
+                op.p50_ms,
+                op.p90_ms,
+                op.p99_ms,
+                op.max_ms
+            ));
+        }
+        table
+    }
+}
+
+/// Limits a soak run must stay within to pass, e.g. as a CI perf gate. Limits left out are not
+/// checked.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SoakThresholds {
+    pub max_p99_ms: Option<f64>,
+    /// Share of failed requests, 0 to 1
+    pub max_error_rate: Option<f64>,
+}
+
+impl SoakThresholds {
+    /// Every limit an operation of the report exceeds; empty when the run passed.
+    pub fn violations(&self, report: &SoakReport) -> Vec<String> {
+        let mut violations = Vec::new();
+        for op in &report.operations {
+            if let Some(max) = self.max_p99_ms.filter(|max| op.p99_ms > *max) {
+                violations.push(format!(
+                    "{} p99 {:.1} ms exceeds {:.1} ms",
+                    op.operation, op.p99_ms, max
+                ));
+            }
+            if let Some(max) = self.max_error_rate.filter(|max| op.error_rate > *max) {
+                violations.push(format!(
+                    "{} error rate {:.3} exceeds {:.3}",
+                    op.operation, op.error_rate, max
+                ));
+            }
+        }
+        violations
+    }
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    latencies_ms: Vec<f64>,
+    errors: u64,
+}
+
+/// Latencies of every request a soak run sends, shared by its tasks.
+#[derive(Debug, Default)]
+pub struct LatencyRecorder {
+    operations: Mutex<BTreeMap<String, Samples>>,
+}
+
+impl LatencyRecorder {
+    pub fn record(&self, operation: &str, latency: Duration, ok: bool) {
+        let Ok(mut operations) = self.operations.lock() else {
+            return;
+        };
+        let samples = operations.entry(operation.to_string()).or_default();
+        samples.latencies_ms.push(latency.as_secs_f64() * 1000.0);
+        if !ok {
+            samples.errors += 1;
+        }
+    }
+
+    /// Percentiles of every operation recorded, over a run that took `duration`.
+    pub fn report(&self, duration: Duration, tenants: usize, machines: usize) -> SoakReport {
+        let secs = duration.as_secs_f64();
+        let operations = self
+            .operations
+            .lock()
+            .map(|operations| {
+                operations
+                    .iter()
+                    .map(|(operation, samples)| {
+                        let mut sorted = samples.latencies_ms.clone();
+                        sorted.sort_by(f64::total_cmp);
+                        let requests = sorted.len() as u64;
+                        OperationReport {
+                            operation: operation.clone(),
+                            requests,
+                            errors: samples.errors,
+                            error_rate: if requests > 0 {
+                                samples.errors as f64 / requests as f64
+                            } else {
+                                0.0
+                            },
+                            throughput: if secs > 0.0 {
+                                requests as f64 / secs
+                            } else {
+                                0.0
+                            },
+                            p50_ms: percentile(&sorted, 50.0),
+                            p90_ms: percentile(&sorted, 90.0),
+                            p99_ms: percentile(&sorted, 99.0),
+                            max_ms: sorted.last().copied().unwrap_or(0.0),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        SoakReport {
+            duration_secs: secs,
+            tenants,
+            machines,
+            operations,
+        }
+    }
+}
+
+/// Nearest-rank percentile of ascending samples; 0 when there are none.
+pub fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
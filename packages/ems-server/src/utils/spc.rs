@@ -0,0 +1,184 @@
+// Statistical process control helpers: control limits, process capability and the Western
+// Electric run rules over a series of measurements
+
+use crate::models::SpcRule;
+
+/// Control chart for a series charted in subgroups of equal size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlChart {
+    /// Plotted values: individual measurements, or subgroup means
+    pub points: Vec<f64>,
+    /// Moving ranges of individuals (the first is empty), or subgroup ranges
+    pub ranges: Vec<Option<f64>>,
+    pub center_line: f64,
+    /// Within-subgroup standard deviation estimated from the mean range
+    pub sigma_within: f64,
+    pub upper_control_limit: f64,
+    pub lower_control_limit: f64,
+    pub range_center_line: f64,
+    pub range_upper_control_limit: f64,
+    pub range_lower_control_limit: f64,
+}
+
+impl ControlChart {
+    /// Standard deviation of a plotted point.
+    pub fn point_sigma(&self) -> f64 {
+        (self.upper_control_limit - self.center_line) / 3.0
+    }
+}
+
+// Control chart constants by subgroup size (index 0 is a subgroup of 2). Individuals use the
+// size-2 constants over moving ranges.
+const D2: [f64; 9] = [
+    1.128, 1.693, 2.059, 2.326, 2.534, 2.704, 2.847, 2.970, 3.078,
+];
+const D3: [f64; 9] = [0.0, 0.0, 0.0, 0.0, 0.0, 0.076, 0.136, 0.184, 0.223];
+const D4: [f64; 9] = [
+    3.267, 2.574, 2.282, 2.114, 2.004, 1.924, 1.864, 1.816, 1.777,
+];
+
+pub const MAX_SUBGROUP_SIZE: usize = 10;
+
+/// Arithmetic mean of a non-empty series.
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation; `None` below two values.
+pub fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values);
+    let squares: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    Some((squares / (values.len() - 1) as f64).sqrt())
+}
+
+/// Plotted points and their ranges from measurements in time order: individual values with
+/// their moving ranges (subgroup size 1), or subgroup means with subgroup ranges. Measurements
+/// that do not fill the first subgroup are left out, so the latest subgroup is always complete.
+pub fn subgroup_points(values: &[f64], subgroup_size: usize) -> (Vec<f64>, Vec<Option<f64>>) {
+    let values = &values[values.len() % subgroup_size.max(1)..];
+
+    if subgroup_size <= 1 {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (v, i.checked_sub(1).map(|p| (v - values[p]).abs())))
+            .unzip()
+    } else {
+        values
+            .chunks(subgroup_size)
+            .map(|subgroup| {
+                let max = subgroup.iter().cloned().fold(f64::MIN, f64::max);
+                let min = subgroup.iter().cloned().fold(f64::MAX, f64::min);
+                (mean(subgroup), Some(max - min))
+            })
+            .unzip()
+    }
+}
+
+/// Individuals and moving range chart (subgroup size 1) or X-bar and R chart (2 to 10), from
+/// measurements in time order. `None` with fewer than two plotted points.
+pub fn control_chart(values: &[f64], subgroup_size: usize) -> Option<ControlChart> {
+    if !(1..=MAX_SUBGROUP_SIZE).contains(&subgroup_size) {
+        return None;
+    }
+    let (points, ranges) = subgroup_points(values, subgroup_size);
+    if points.len() < 2 {
+        return None;
+    }
+
+    let constants = subgroup_size.max(2) - 2;
+    let range_center_line = mean(&ranges.iter().flatten().copied().collect::<Vec<_>>());
+    let sigma_within = range_center_line / D2[constants];
+    let center_line = mean(&points);
+    let spread = 3.0 * sigma_within / (subgroup_size as f64).sqrt();
+
+    Some(ControlChart {
+        center_line,
+        sigma_within,
+        upper_control_limit: center_line + spread,
+        lower_control_limit: center_line - spread,
+        range_center_line,
+        range_upper_control_limit: D4[constants] * range_center_line,
+        range_lower_control_limit: D3[constants] * range_center_line,
+        points,
+        ranges,
+    })
+}
+
+/// Process capability `(Cp, Cpk)` from the process mean, within-subgroup sigma and the
+/// specification limits. Cp needs both limits; Cpk uses whichever are set.
+pub fn capability(
+    mean: f64,
+    sigma: f64,
+    lower_spec_limit: Option<f64>,
+    upper_spec_limit: Option<f64>,
+) -> (Option<f64>, Option<f64>) {
+    if sigma <= 0.0 || !sigma.is_finite() {
+        return (None, None);
+    }
+
+    let cp = match (lower_spec_limit, upper_spec_limit) {
+        (Some(lower), Some(upper)) => Some((upper - lower) / (6.0 * sigma)),
+        _ => None,
+    };
+    let cpk = [
+        upper_spec_limit.map(|upper| (upper - mean) / (3.0 * sigma)),
+        lower_spec_limit.map(|lower| (mean - lower) / (3.0 * sigma)),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::min);
+
+    (cp, cpk)
+}
+
+/// Points breaking a run rule, as `(point index, rule)`. A point is flagged when the run it
+/// ends meets the rule.
+pub fn rule_violations(points: &[f64], center_line: f64, sigma: f64) -> Vec<(usize, SpcRule)> {
+    let mut violations = Vec::new();
+    // Points above (1) or below (-1) the center line by more than `k` sigma
+    let side = |value: f64, k: f64| {
+        if value > center_line + k * sigma {
+            1
+        } else if value < center_line - k * sigma {
+            -1
+        } else {
+            0
+        }
+    };
+    let window =
+        |end: usize, length: usize| (end + 1).checked_sub(length).map(|s| &points[s..=end]);
+    let beyond = |run: &[f64], k: f64, needed: usize, last: f64| {
+        let direction = side(last, k);
+        direction != 0 && run.iter().filter(|&&v| side(v, k) == direction).count() >= needed
+    };
+
+    for (i, &point) in points.iter().enumerate() {
+        if sigma > 0.0 && side(point, 3.0) != 0 {
+            violations.push((i, SpcRule::BeyondControlLimits));
+        }
+        if sigma > 0.0 && window(i, 3).is_some_and(|run| beyond(run, 2.0, 2, point)) {
+            violations.push((i, SpcRule::TwoOfThreeBeyondTwoSigma));
+        }
+        if sigma > 0.0 && window(i, 5).is_some_and(|run| beyond(run, 1.0, 4, point)) {
+            violations.push((i, SpcRule::FourOfFiveBeyondOneSigma));
+        }
+        if window(i, 8).is_some_and(|run| {
+            let direction = side(run[0], 0.0);
+            direction != 0 && run.iter().all(|&v| side(v, 0.0) == direction)
+        }) {
+            violations.push((i, SpcRule::EightOnOneSide));
+        }
+        if window(i, 6).is_some_and(|run| {
+            run.windows(2).all(|pair| pair[1] > pair[0])
+                || run.windows(2).all(|pair| pair[1] < pair[0])
+        }) {
+            violations.push((i, SpcRule::SixTrending));
+        }
+    }
+
+    violations
+}
@@ -0,0 +1,207 @@
+// Public status page helpers: page tokens and what the page shows of machines, lines and planned
+// downtime
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{
+    MachineAvailability, MachineStatus, PublicLineStatus, PublicMachineStatus,
+    PublicMaintenanceWindow, PublicStatusPage, StatusPage,
+};
+use crate::utils::machine_group::line_status;
+
+// Characters of the token's secret part stored to tell tokens apart
+const TOKEN_PREFIX_LENGTH: usize = 8;
+
+/// A new token for a tenant's status page URL. The tenant comes first so the page can be served
+/// from the tenant's own database; the random part after the dot is what keeps it secret.
+pub fn status_page_token(tenant_id: Uuid) -> String {
+    format!(
+        "{}.{}{}",
+        tenant_id.simple(),
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// The tenant a status page token was issued for, `None` when it is not one.
+pub fn status_token_tenant(token: &str) -> Option<Uuid> {
+    let (tenant, secret) = token.split_once('.')?;
+    if secret.len() != 64 || !secret.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Uuid::try_parse(tenant).ok()
+}
+
+/// The start of the token's secret part, stored to identify it. The tenant part is the same for
+/// every token of the tenant, so it is left out.
+pub fn status_token_prefix(token: &str) -> String {
+    let secret = token.split_once('.').map_or(token, |(_, secret)| secret);
+    secret.chars().take(TOKEN_PREFIX_LENGTH).collect()
+}
+
+/// A machine as last reported, for the status page.
+#[derive(Debug, Clone)]
+pub struct MachineState {
+    pub name: String,
+    pub status: MachineStatus,
+    pub last_heartbeat: Option<DateTime<Utc>>,
+}
+
+impl MachineState {
+    /// The status the machine counts as having: reporting itself idle or busy without a
+    /// heartbeat since `stale_before` counts as offline.
+    pub fn effective_status(&self, stale_before: DateTime<Utc>) -> MachineStatus {
+        let stale = matches!(self.status, MachineStatus::Idle | MachineStatus::Busy)
+            && self.last_heartbeat.is_none_or(|at| at < stale_before);
+        if stale {
+            MachineStatus::Offline
+        } else {
+            self.status.clone()
+        }
+    }
+}
+
+/// A machine group shown as a line, with its members and whether each is critical.
+#[derive(Debug, Clone)]
+pub struct LineState {
+    pub id: Uuid,
+    pub name: String,
+    pub members: Vec<(Uuid, bool)>,
+}
+
+/// Planned downtime of one machine, or of every machine when `machine_id` is `None`.
+#[derive(Debug, Clone)]
+pub struct PlannedDowntime {
+    pub machine_id: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl PlannedDowntime {
+    fn covers(&self, machine_id: Uuid, at: DateTime<Utc>) -> bool {
+        self.machine_id.is_none_or(|id| id == machine_id)
+            && self.starts_at <= at
+            && at < self.ends_at
+    }
+}
+
+/// How a machine's status is shown to the public. A machine that is down while planned downtime
+/// covers it is shown in maintenance rather than unavailable.
+pub fn machine_availability(
+    status: &MachineStatus,
+    in_planned_downtime: bool,
+) -> MachineAvailability {
+    match status {
+        MachineStatus::Idle | MachineStatus::Busy => MachineAvailability::Operational,
+        MachineStatus::Maintenance => MachineAvailability::Maintenance,
+        _ if in_planned_downtime => MachineAvailability::Maintenance,
+        _ => MachineAvailability::Unavailable,
+    }
+}
+
+/// The status page as served at `now`, from the state of its machines and lines and the planned
+/// downtime loaded for them. Machines that no longer exist or are decommissioned are left out,
+/// as are lines that no longer exist, and members of lines are only listed when shown
+/// themselves.
+pub fn public_status_page(
+    page: &StatusPage,
+    machines: &HashMap<Uuid, MachineState>,
+    lines: &[LineState],
+    downtime: &[PlannedDowntime],
+    now: DateTime<Utc>,
+    stale_before: DateTime<Utc>,
+) -> PublicStatusPage {
+    let in_downtime =
+        |machine_id: Uuid| downtime.iter().any(|window| window.covers(machine_id, now));
+
+    let shown_machines: Vec<PublicMachineStatus> = page
+        .machine_ids
+        .iter()
+        .filter_map(|id| machines.get(id).map(|machine| (*id, machine)))
+        .filter(|(_, machine)| machine.status != MachineStatus::Decommissioned)
+        .map(|(id, machine)| PublicMachineStatus {
+            name: machine.name.clone(),
+            status: machine_availability(&machine.effective_status(stale_before), in_downtime(id)),
+        })
+        .collect();
+
+    let lines_by_id: HashMap<Uuid, &LineState> = lines.iter().map(|line| (line.id, line)).collect();
+    let shown_lines: Vec<&LineState> = page
+        .group_ids
+        .iter()
+        .filter_map(|id| lines_by_id.get(id).copied())
+        .collect();
+    let line_statuses = shown_lines
+        .iter()
+        .map(|line| {
+            let states: Vec<(MachineStatus, bool)> = line
+                .members
+                .iter()
+                .filter_map(|(machine_id, is_critical)| {
+                    machines
+                        .get(machine_id)
+                        .map(|machine| (machine.effective_status(stale_before), *is_critical))
+                })
+                .collect();
+            PublicLineStatus {
+                name: line.name.clone(),
+                status: line_status(&states),
+            }
+        })
+        .collect();
+
+    let mut maintenance: Vec<PublicMaintenanceWindow> = if page.show_maintenance {
+        downtime
+            .iter()
+            .filter(|window| window.ends_at > now)
+            .filter_map(|window| {
+                let Some(machine_id) = window.machine_id else {
+                    return Some(PublicMaintenanceWindow {
+                        machine: None,
+                        lines: Vec::new(),
+                        site_wide: true,
+                        starts_at: window.starts_at,
+                        ends_at: window.ends_at,
+                        in_progress: window.starts_at <= now,
+                    });
+                };
+                let machine = page
+                    .machine_ids
+                    .contains(&machine_id)
+                    .then(|| machines.get(&machine_id))
+                    .flatten()
+                    .map(|machine| machine.name.clone());
+                let lines: Vec<String> = shown_lines
+                    .iter()
+                    .filter(|line| line.members.iter().any(|(id, _)| *id == machine_id))
+                    .map(|line| line.name.clone())
+                    .collect();
+                // Downtime of machines that are not shown, on their own or in a line, is private
+                if machine.is_none() && lines.is_empty() {
+                    return None;
+                }
+                Some(PublicMaintenanceWindow {
+                    machine,
+                    lines,
+                    site_wide: false,
+                    starts_at: window.starts_at,
+                    ends_at: window.ends_at,
+                    in_progress: window.starts_at <= now,
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    maintenance.sort_by_key(|window| (window.starts_at, window.ends_at));
+
+    PublicStatusPage {
+        title: page.title.clone(),
+        description: page.description.clone(),
+        machines: shown_machines,
+        lines: line_statuses,
+        maintenance,
+        generated_at: now,
+    }
+}
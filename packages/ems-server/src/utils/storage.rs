@@ -0,0 +1,280 @@
+// Storage backend helpers: S3 request signing (AWS Signature Version 4) and the signed URLs of
+// the local filesystem backend
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+
+/// Payload hash of presigned URLs, whose body is not known when they are signed
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Longest a presigned S3 URL can stay valid, seven days
+pub const MAX_PRESIGN_SECONDS: u64 = 604_800;
+
+const SIGV4_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Percent-encodes `value` as S3 signing expects: everything but unreserved characters, and
+/// `/` too unless `keep_slashes`.
+pub fn uri_encode(value: &str, keep_slashes: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slashes => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Credentials and region S3 requests are signed with.
+#[derive(Debug, Clone)]
+pub struct SigV4Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub region: String,
+}
+
+impl SigV4Credentials {
+    fn scope(&self, at: DateTime<Utc>) -> String {
+        format!("{}/{}/s3/aws4_request", at.format("%Y%m%d"), self.region)
+    }
+
+    fn signing_key(&self, at: DateTime<Utc>) -> Vec<u8> {
+        let date = hmac(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            &at.format("%Y%m%d").to_string(),
+        );
+        let region = hmac(&date, &self.region);
+        let service = hmac(&region, "s3");
+        hmac(&service, "aws4_request")
+    }
+
+    fn sign(&self, canonical_request: &str, at: DateTime<Utc>) -> String {
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{:x}",
+            SIGV4_ALGORITHM,
+            amz_date(at),
+            self.scope(at),
+            Sha256::digest(canonical_request.as_bytes())
+        );
+        hmac(&self.signing_key(at), &string_to_sign)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// `Authorization` header of a request to `path` (already encoded) with `query` and the
+    /// `headers` to sign, which must include `host`, `x-amz-date` and `x-amz-content-sha256`.
+    pub fn authorization(
+        &self,
+        method: &str,
+        path: &str,
+        query: &[(String, String)],
+        headers: &[(String, String)],
+        payload_hash: &str,
+        at: DateTime<Utc>,
+    ) -> String {
+        let (canonical_request, signed_headers) =
+            canonical_request(method, path, query, headers, payload_hash);
+        format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            SIGV4_ALGORITHM,
+            self.access_key_id,
+            self.scope(at),
+            signed_headers,
+            self.sign(&canonical_request, at)
+        )
+    }
+
+    /// Query string of a URL for `method` on `path` at `host` that is valid for `expires_in`
+    /// seconds from `at` without further credentials.
+    pub fn presigned_query(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        expires_in: u64,
+        at: DateTime<Utc>,
+    ) -> String {
+        let headers = [("host".to_string(), host.to_string())];
+        self.presigned_query_with_headers(method, path, &headers, expires_in, at)
+    }
+
+    /// Like [`Self::presigned_query`], with `headers` (which must include `host`) signed too:
+    /// requests through the URL must send them with exactly these values.
+    pub fn presigned_query_with_headers(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        expires_in: u64,
+        at: DateTime<Utc>,
+    ) -> String {
+        let mut signed_headers: Vec<String> = headers
+            .iter()
+            .map(|(name, _)| name.to_ascii_lowercase())
+            .collect();
+        signed_headers.sort();
+        let mut query = vec![
+            ("X-Amz-Algorithm".to_string(), SIGV4_ALGORITHM.to_string()),
+            (
+                "X-Amz-Credential".to_string(),
+                format!("{}/{}", self.access_key_id, self.scope(at)),
+            ),
+            ("X-Amz-Date".to_string(), amz_date(at)),
+            (
+                "X-Amz-Expires".to_string(),
+                expires_in.clamp(1, MAX_PRESIGN_SECONDS).to_string(),
+            ),
+            ("X-Amz-SignedHeaders".to_string(), signed_headers.join(";")),
+        ];
+        let (canonical_request, _) =
+            canonical_request(method, path, &query, headers, UNSIGNED_PAYLOAD);
+        query.push((
+            "X-Amz-Signature".to_string(),
+            self.sign(&canonical_request, at),
+        ));
+        canonical_query(&query)
+    }
+}
+
+/// Value of the `x-amz-checksum-sha256` header for a SHA-256 given in hex: the digest in
+/// base64. `None` when `sha256` is not a SHA-256 in hex.
+pub fn checksum_header(sha256: &str) -> Option<String> {
+    decode_hex(sha256)
+        .filter(|digest| digest.len() == 32)
+        .map(|digest| STANDARD.encode(digest))
+}
+
+/// Lower-case hex SHA-256 from an `x-amz-checksum-sha256` header. S3 reports the checksum of
+/// multipart objects as a checksum of part checksums, suffixed with the part count, which is
+/// not the file's SHA-256; `None` for those.
+pub fn checksum_from_header(value: &str) -> Option<String> {
+    STANDARD
+        .decode(value.trim())
+        .ok()
+        .filter(|digest| digest.len() == 32)
+        .map(|digest| digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Timestamp in the `x-amz-date` format, e.g. `20130524T000000Z`.
+pub fn amz_date(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Query parameters encoded and sorted as S3 signs them.
+pub fn canonical_query(query: &[(String, String)]) -> String {
+    let mut encoded: Vec<(String, String)> = query
+        .iter()
+        .map(|(key, value)| (uri_encode(key, false), uri_encode(value, false)))
+        .collect();
+    encoded.sort();
+    encoded
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+// The canonical request and its signed header names
+fn canonical_request(
+    method: &str,
+    path: &str,
+    query: &[(String, String)],
+    headers: &[(String, String)],
+    payload_hash: &str,
+) -> (String, String) {
+    let mut headers: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    (
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method,
+            path,
+            canonical_query(query),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        ),
+        signed_headers,
+    )
+}
+
+/// Signature of a local storage URL for the object at `path` in `bucket`, valid until the Unix
+/// time `expires`.
+pub fn local_url_signature(key: &str, bucket: &str, path: &str, expires: i64) -> String {
+    hmac(key.as_bytes(), &format!("{}/{}\n{}", bucket, path, expires))
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Checks a local storage URL's signature and that it has not expired by `now` (Unix time).
+pub fn verify_local_url(
+    key: &str,
+    bucket: &str,
+    path: &str,
+    expires: i64,
+    signature: &str,
+    now: i64,
+) -> Result<(), String> {
+    if expires < now {
+        return Err("Invalid storage URL: the link has expired".to_string());
+    }
+    let signature =
+        decode_hex(signature).ok_or_else(|| "Invalid storage URL: bad signature".to_string())?;
+    let mut mac =
+        HmacSha256::new_from_slice(key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}/{}\n{}", bucket, path, expires).as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| "Invalid storage URL: bad signature".to_string())
+}
+
+/// Where the object at `path` lives under `root`. `None` for paths that are empty, absolute or
+/// would leave `root`.
+pub fn local_object_path(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(root.join(relative))
+}
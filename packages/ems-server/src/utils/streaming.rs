@@ -0,0 +1,158 @@
+// Streamed response bodies for large lists and exports
+use std::future::Future;
+
+use anyhow::Result;
+use axum::body::{Body, Bytes};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::DatabaseService;
+
+/// Rows fetched from the database per page while streaming a list
+pub const STREAM_PAGE_SIZE: u32 = 500;
+
+/// Where a streamed JSON array has got to.
+struct ArrayWriter {
+    page_size: u32,
+    offset: u32,
+    remaining: Option<u32>,
+    written: usize,
+    done: bool,
+}
+
+impl ArrayWriter {
+    /// Rows to ask the next page for
+    fn next_limit(&self) -> u32 {
+        self.remaining
+            .map_or(self.page_size, |remaining| remaining.min(self.page_size))
+    }
+
+    /// Serializes a page fetched with `limit`, opening the array on the first page and closing
+    /// it once a page comes back short or the limit is reached.
+    fn render<T: Serialize>(&mut self, rows: &[T], limit: u32, first: bool) -> Result<Bytes> {
+        let mut chunk = Vec::new();
+        if first {
+            chunk.push(b'[');
+        }
+        for row in rows {
+            if self.written > 0 {
+                chunk.push(b',');
+            }
+            serde_json::to_writer(&mut chunk, row)?;
+            self.written += 1;
+        }
+
+        let fetched = rows.len() as u32;
+        self.offset += fetched;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= fetched.min(*remaining);
+        }
+        if fetched < limit || self.remaining == Some(0) {
+            chunk.push(b']');
+            self.done = true;
+        }
+        Ok(Bytes::from(chunk))
+    }
+}
+
+/// Streams rows as one JSON array, so the whole list never sits in memory at once.
+/// `fetch(limit, offset)` loads a page and is called for successive offsets until it returns
+/// a short page or `limit` rows have been sent; it must order rows stably for the pages to
+/// line up. The first page is loaded before returning so a failing query still becomes an
+/// error status. A later failure can only cut the body short.
+///
+/// Every page is fetched scoped to `tenant_id`: later pages are loaded after the handler has
+/// returned, outside the request's tenant scope, so a tenant with a dedicated database would
+/// otherwise be read from the shared one.
+pub async fn stream_json_array<T, F, Fut>(
+    tenant_id: Uuid,
+    page_size: u32,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    mut fetch: F,
+) -> Result<Body>
+where
+    T: Serialize + Send + 'static,
+    F: FnMut(u32, u32) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<T>>> + Send + 'static,
+{
+    let mut writer = ArrayWriter {
+        page_size: page_size.max(1),
+        offset: offset.unwrap_or(0),
+        remaining: limit,
+        written: 0,
+        done: false,
+    };
+    let limit = writer.next_limit();
+    let rows = if limit == 0 {
+        Vec::new()
+    } else {
+        DatabaseService::scope_tenant(tenant_id, fetch(limit, writer.offset)).await?
+    };
+    let first = writer.render(&rows, limit, true)?;
+    drop(rows);
+
+    let rest = stream::unfold((writer, fetch), move |(mut writer, mut fetch)| async move {
+        if writer.done {
+            return None;
+        }
+        let limit = writer.next_limit();
+        let chunk =
+            match DatabaseService::scope_tenant(tenant_id, fetch(limit, writer.offset)).await {
+                Ok(rows) => writer.render(&rows, limit, false),
+                Err(e) => Err(e),
+            };
+        if let Err(e) = &chunk {
+            tracing::error!("Streaming list aborted: {}", e);
+            writer.done = true;
+        }
+        Some((chunk, (writer, fetch)))
+    });
+
+    Ok(Body::from_stream(
+        stream::once(async move { Ok(first) }).chain(rest),
+    ))
+}
+
+/// Streams text a page of lines at a time: `head` first, then the lines `fetch(limit, offset)`
+/// returns for successive offsets until a page comes back short. Pages are fetched and fail the
+/// same way as [`stream_json_array`]'s.
+pub async fn stream_lines<F, Fut>(
+    tenant_id: Uuid,
+    page_size: u32,
+    head: String,
+    mut fetch: F,
+) -> Result<Body>
+where
+    F: FnMut(u32, u32) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<String>>> + Send + 'static,
+{
+    let page_size = page_size.max(1);
+    let lines = DatabaseService::scope_tenant(tenant_id, fetch(page_size, 0)).await?;
+    let fetched = lines.len() as u32;
+    let first = Bytes::from(head + &lines.concat());
+    drop(lines);
+
+    let state = (fetched, fetched < page_size, fetch);
+    let rest = stream::unfold(state, move |(offset, done, mut fetch)| async move {
+        if done {
+            return None;
+        }
+        match DatabaseService::scope_tenant(tenant_id, fetch(page_size, offset)).await {
+            Ok(lines) => {
+                let fetched = lines.len() as u32;
+                let chunk = Bytes::from(lines.concat());
+                Some((Ok(chunk), (offset + fetched, fetched < page_size, fetch)))
+            }
+            Err(e) => {
+                tracing::error!("Streaming export aborted: {}", e);
+                Some((Err(e), (offset, true, fetch)))
+            }
+        }
+    });
+
+    Ok(Body::from_stream(
+        stream::once(async move { Ok(first) }).chain(rest),
+    ))
+}
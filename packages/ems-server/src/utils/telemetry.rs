@@ -0,0 +1,73 @@
+// Telemetry extraction and downsampling helpers
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::models::TelemetryChannel;
+
+// Window returned when the caller does not specify one
+pub const DEFAULT_WINDOW_HOURS: i64 = 24;
+// Longest window served in one request
+pub const MAX_WINDOW_DAYS: i64 = 366;
+// Upper bound on points per series; the bucket is widened to stay under it
+pub const MAX_POINTS_PER_SERIES: i64 = 500;
+// Finest resolution served, regardless of the requested bucket
+pub const MIN_BUCKET_SECONDS: i64 = 60;
+
+/// Channels a machine reports and where to find them in its heartbeat payload.
+///
+/// Machines declare channels under `telemetry_channels` in their metadata, either as a list of
+/// channel names read from top-level payload keys, or as an object mapping channel names to
+/// JSON pointers (e.g. `{"energy_kwh": "/meters/total_kwh"}`). Without a declaration every
+/// known channel is looked up under its own name.
+pub fn declared_channels(metadata: Option<&Value>) -> Vec<(TelemetryChannel, String)> {
+    let default_pointer = |channel: TelemetryChannel| format!("/{}", channel);
+
+    match metadata.and_then(|m| m.get("telemetry_channels")) {
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|name| name.as_str())
+            .filter_map(|name| TelemetryChannel::try_from(name.to_string()).ok())
+            .map(|channel| (channel, default_pointer(channel)))
+            .collect(),
+        Some(Value::Object(pointers)) => pointers
+            .iter()
+            .filter_map(|(name, pointer)| {
+                let channel = TelemetryChannel::try_from(name.clone()).ok()?;
+                Some((channel, pointer.as_str()?.to_string()))
+            })
+            .collect(),
+        _ => TelemetryChannel::ALL
+            .iter()
+            .map(|&channel| (channel, default_pointer(channel)))
+            .collect(),
+    }
+}
+
+/// Finite number at a JSON pointer in a heartbeat payload; numeric strings are accepted.
+pub fn numeric_at(payload: &Value, pointer: &str) -> Option<f64> {
+    let value = match payload.pointer(pointer)? {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }?;
+    value.is_finite().then_some(value)
+}
+
+/// Numeric readings for the declared channels; missing or non-numeric values are skipped.
+pub fn extract_readings(payload: &Value, metadata: Option<&Value>) -> Vec<(TelemetryChannel, f64)> {
+    declared_channels(metadata)
+        .into_iter()
+        .filter_map(|(channel, pointer)| Some((channel, numeric_at(payload, &pointer)?)))
+        .collect()
+}
+
+/// Bucket width for a window: the requested width, widened so a series never exceeds
+/// `MAX_POINTS_PER_SERIES` points and never finer than `MIN_BUCKET_SECONDS`.
+pub fn bucket_seconds(from: DateTime<Utc>, to: DateTime<Utc>, requested: Option<i64>) -> i64 {
+    let span = (to - from).num_seconds().max(0);
+    let narrowest_allowed = (span + MAX_POINTS_PER_SERIES - 1) / MAX_POINTS_PER_SERIES;
+    requested
+        .unwrap_or(0)
+        .max(narrowest_allowed)
+        .max(MIN_BUCKET_SECONDS)
+}
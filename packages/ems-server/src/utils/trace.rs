@@ -0,0 +1,167 @@
+// Traceability graph helpers: collecting nodes and edges without duplicates
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::models::{
+    BatchRecordResponse, Quantity, TraceEdge, TraceGraph, TraceNode, TraceNodeKind, TraceRelation,
+};
+
+/// ID of the node for a record of `kind` identified by `key`.
+pub fn node_id(kind: TraceNodeKind, key: impl std::fmt::Display) -> String {
+    format!("{}:{}", kind, key)
+}
+
+/// Key of a component lot or serial number, which is only unique within its item.
+pub fn numbered_key(item_id: Uuid, number: &str) -> String {
+    format!("{}/{}", item_id, number)
+}
+
+/// Collects a trace graph, keeping the first of any repeated node or edge.
+#[derive(Debug, Default)]
+pub struct TraceGraphBuilder {
+    nodes: Vec<TraceNode>,
+    edges: Vec<TraceEdge>,
+    node_ids: HashSet<String>,
+    edge_keys: HashSet<(String, String, TraceRelation)>,
+}
+
+impl TraceGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the node for a record and returns its ID.
+    pub fn node(
+        &mut self,
+        kind: TraceNodeKind,
+        key: impl std::fmt::Display,
+        label: impl Into<String>,
+        record_id: Option<Uuid>,
+        item_id: Option<Uuid>,
+    ) -> String {
+        let id = node_id(kind, key);
+        if self.node_ids.insert(id.clone()) {
+            self.nodes.push(TraceNode {
+                id: id.clone(),
+                kind,
+                label: label.into(),
+                record_id,
+                item_id,
+            });
+        }
+        id
+    }
+
+    pub fn edge(&mut self, from: &str, to: &str, relation: TraceRelation) {
+        self.edge_with_quantity(from, to, relation, None);
+    }
+
+    pub fn edge_with_quantity(
+        &mut self,
+        from: &str,
+        to: &str,
+        relation: TraceRelation,
+        quantity: Option<Quantity>,
+    ) {
+        if self
+            .edge_keys
+            .insert((from.to_string(), to.to_string(), relation))
+        {
+            self.edges.push(TraceEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                relation,
+                quantity,
+            });
+        }
+    }
+
+    /// Adds a produced lot with the machines and materials its batch record names, and
+    /// returns the lot's node ID. The job that produced it is linked by the caller.
+    pub fn lot(&mut self, lot: &BatchRecordResponse) -> String {
+        let lot_id = self.node(
+            TraceNodeKind::Lot,
+            lot.id,
+            &lot.batch_number,
+            Some(lot.id),
+            lot.item_id,
+        );
+
+        for machine in &lot.content.machines {
+            let machine_id = self.machine(machine.machine_id, &machine.name);
+            self.edge(&lot_id, &machine_id, TraceRelation::BuiltOn);
+        }
+
+        for material in &lot.content.materials {
+            let component_id = self.component(material.item_id, material.part_number.as_deref());
+            self.edge_with_quantity(
+                &lot_id,
+                &component_id,
+                TraceRelation::Consumed,
+                Some(material.quantity),
+            );
+            let numbered = material
+                .lot_numbers
+                .iter()
+                .map(|number| (TraceNodeKind::Lot, number))
+                .chain(
+                    material
+                        .serial_numbers
+                        .iter()
+                        .map(|number| (TraceNodeKind::Serial, number)),
+                );
+            for (kind, number) in numbered {
+                let id = self.node(
+                    kind,
+                    numbered_key(material.item_id, number),
+                    number,
+                    None,
+                    Some(material.item_id),
+                );
+                self.edge(&lot_id, &id, TraceRelation::Consumed);
+                self.edge(&id, &component_id, TraceRelation::OfItem);
+            }
+        }
+
+        lot_id
+    }
+
+    pub fn machine(&mut self, machine_id: Uuid, name: &str) -> String {
+        self.node(
+            TraceNodeKind::Machine,
+            machine_id,
+            name,
+            Some(machine_id),
+            None,
+        )
+    }
+
+    /// Adds a component, labelled with its part number when known.
+    pub fn component(&mut self, item_id: Uuid, part_number: Option<&str>) -> String {
+        let label = part_number.map_or_else(|| item_id.to_string(), str::to_string);
+        self.node(
+            TraceNodeKind::Component,
+            item_id,
+            label,
+            Some(item_id),
+            Some(item_id),
+        )
+    }
+
+    /// Items of the components in the graph.
+    pub fn component_items(&self) -> Vec<Uuid> {
+        self.nodes
+            .iter()
+            .filter(|node| node.kind == TraceNodeKind::Component)
+            .filter_map(|node| node.item_id)
+            .collect()
+    }
+
+    pub fn finish(self, roots: Vec<String>) -> TraceGraph {
+        TraceGraph {
+            roots,
+            nodes: self.nodes,
+            edges: self.edges,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+// Unit of measure conversion helpers
+use crate::models::{ItemUnitsResponse, UnitOfMeasureResponse};
+
+/// Base units of an item in one `unit`: its purchase factor for the purchase unit, otherwise
+/// the ratio of the unit factors when `unit` measures the same thing as the base unit. `None`
+/// when the item's quantities cannot be given in `unit`.
+pub fn base_units_per(units: &ItemUnitsResponse, unit: &UnitOfMeasureResponse) -> Option<f64> {
+    if unit.id == units.base_uom.id {
+        return Some(1.0);
+    }
+    if let (Some(purchase), Some(factor)) = (&units.purchase_uom, units.purchase_factor) {
+        if purchase.id == unit.id {
+            return Some(factor);
+        }
+    }
+    (unit.dimension == units.base_uom.dimension).then(|| unit.factor / units.base_uom.factor)
+}
+
+/// Base units in one purchase unit, `None` when the units measure different things and no
+/// factor was set.
+pub fn purchase_factor(
+    base: &UnitOfMeasureResponse,
+    purchase: &UnitOfMeasureResponse,
+    purchase_factor: Option<f64>,
+) -> Option<f64> {
+    purchase_factor
+        .or_else(|| (purchase.dimension == base.dimension).then(|| purchase.factor / base.factor))
+}
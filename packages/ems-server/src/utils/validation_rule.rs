@@ -0,0 +1,523 @@
+// Validation rule expressions: a small language tenants write their business rules in, such as
+// `total_amount > 10000` or `category in ["ic", "module"] and not present(datasheet)`
+//
+// Expressions are evaluated against a record as JSON. A field missing from the record is null;
+// comparisons between values of different types are false, and `!=` is their negation.
+use serde_json::Value;
+use std::fmt;
+
+use crate::models::{RuleEntity, RuleViolation, ValidationRule};
+
+/// Longest expression a rule takes, in characters
+pub const MAX_EXPRESSION_LENGTH: usize = 500;
+
+// Deepest nesting of `not` and parentheses an expression takes
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompareOp::Eq => write!(f, "=="),
+            CompareOp::Ne => write!(f, "!="),
+            CompareOp::Lt => write!(f, "<"),
+            CompareOp::Lte => write!(f, "<="),
+            CompareOp::Gt => write!(f, ">"),
+            CompareOp::Gte => write!(f, ">="),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(Value),
+    /// A field of the record, by path: `metadata.project_code` is `["metadata", "project_code"]`
+    Field(Vec<String>),
+    /// Whether the field has a value: not null, not blank text and not an empty list or object
+    Present(Vec<String>),
+    Compare(CompareOp, Box<Expression>, Box<Expression>),
+    In(Box<Expression>, Vec<Value>),
+    Not(Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Whether the record satisfies the expression.
+    pub fn holds(&self, record: &Value) -> bool {
+        truthy(&self.value(record))
+    }
+
+    /// Paths of the fields the expression refers to.
+    pub fn fields(&self) -> Vec<&[String]> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    fn collect_fields<'a>(&'a self, fields: &mut Vec<&'a [String]>) {
+        match self {
+            Expression::Literal(_) => {}
+            Expression::Field(path) | Expression::Present(path) => fields.push(path),
+            Expression::Compare(_, left, right)
+            | Expression::And(left, right)
+            | Expression::Or(left, right) => {
+                left.collect_fields(fields);
+                right.collect_fields(fields);
+            }
+            Expression::In(operand, _) | Expression::Not(operand) => operand.collect_fields(fields),
+        }
+    }
+
+    fn value(&self, record: &Value) -> Value {
+        match self {
+            Expression::Literal(value) => value.clone(),
+            Expression::Field(path) => field(record, path).clone(),
+            Expression::Present(path) => Value::Bool(present(field(record, path))),
+            Expression::Compare(op, left, right) => {
+                Value::Bool(compare(*op, &left.value(record), &right.value(record)))
+            }
+            Expression::In(operand, values) => {
+                let value = operand.value(record);
+                Value::Bool(values.iter().any(|v| equal(&value, v)))
+            }
+            Expression::Not(operand) => Value::Bool(!operand.holds(record)),
+            Expression::And(left, right) => Value::Bool(left.holds(record) && right.holds(record)),
+            Expression::Or(left, right) => Value::Bool(left.holds(record) || right.holds(record)),
+        }
+    }
+}
+
+fn field<'a>(record: &'a Value, path: &[String]) -> &'a Value {
+    path.iter()
+        .try_fold(record, |value, name| value.get(name))
+        .unwrap_or(&Value::Null)
+}
+
+fn present(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(text) => !text.trim().is_empty(),
+        Value::Array(values) => !values.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+        Value::Bool(_) | Value::Number(_) => true,
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        _ => present(value),
+    }
+}
+
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn compare(op: CompareOp, left: &Value, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Lt => ordering.is_some_and(|o| o.is_lt()),
+        CompareOp::Lte => ordering.is_some_and(|o| o.is_le()),
+        CompareOp::Gt => ordering.is_some_and(|o| o.is_gt()),
+        CompareOp::Gte => ordering.is_some_and(|o| o.is_ge()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Name(String),
+    Op(CompareOp),
+    LeftParen,
+    RightParen,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Dot,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::LeftParen => write!(f, "("),
+            Token::RightParen => write!(f, ")"),
+            Token::LeftBracket => write!(f, "["),
+            Token::RightBracket => write!(f, "]"),
+            Token::Comma => write!(f, ","),
+            Token::Dot => write!(f, "."),
+        }
+    }
+}
+
+// Tokens with the character position each starts at, counting from 1
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = match c {
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            ',' => Token::Comma,
+            '.' => Token::Dot,
+            '=' | '!' | '<' | '>' => {
+                let double = chars.get(i + 1) == Some(&'=');
+                let op = match (c, double) {
+                    ('=', true) => CompareOp::Eq,
+                    ('!', true) => CompareOp::Ne,
+                    ('<', true) => CompareOp::Lte,
+                    ('<', false) => CompareOp::Lt,
+                    ('>', true) => CompareOp::Gte,
+                    ('>', false) => CompareOp::Gt,
+                    _ => return Err(format!("Unexpected '{}' at position {}", c, position)),
+                };
+                i += if double { 2 } else { 1 };
+                tokens.push((Token::Op(op), position));
+                continue;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&q| q == c)
+                    .ok_or_else(|| format!("Unclosed text at position {}", position))?;
+                let text: String = chars[i + 1..i + 1 + end].iter().collect();
+                i += end + 2;
+                tokens.push((Token::Text(text), position));
+                continue;
+            }
+            _ if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+            {
+                let len = chars[i + 1..]
+                    .iter()
+                    .take_while(|d| d.is_ascii_digit() || **d == '.')
+                    .count();
+                let number: String = chars[i..i + 1 + len].iter().collect();
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("Invalid number {} at position {}", number, position))?;
+                i += len + 1;
+                tokens.push((Token::Number(number), position));
+                continue;
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|d| d.is_ascii_alphanumeric() || **d == '_')
+                    .count();
+                let name: String = chars[i..i + len].iter().collect();
+                i += len;
+                tokens.push((Token::Name(name), position));
+                continue;
+            }
+            _ => return Err(format!("Unexpected '{}' at position {}", c, position)),
+        };
+        tokens.push((token, position));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    depth: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(token, _)| token)
+    }
+
+    fn peek_name(&self, name: &str) -> bool {
+        matches!(self.peek(), Some(Token::Name(n)) if n == name)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(token, _)| token.clone());
+        self.next += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(_, position)| *position)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("Unexpected {} at position {}", token, self.position()),
+            None => "Unexpected end of expression".to_string(),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        if self.peek() == Some(&expected) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected {} at position {}",
+                expected,
+                self.position()
+            ))
+        }
+    }
+
+    fn nest(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        let mut left = self.and()?;
+        while self.peek_name("or") {
+            self.next += 1;
+            left = Expression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        let mut left = self.not()?;
+        while self.peek_name("and") {
+            self.next += 1;
+            left = Expression::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expression, String> {
+        if self.peek_name("not") {
+            self.next += 1;
+            self.nest()?;
+            let operand = self.not()?;
+            self.depth -= 1;
+            return Ok(Expression::Not(Box::new(operand)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let left = self.operand()?;
+        if let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            self.next += 1;
+            let right = self.operand()?;
+            return Ok(Expression::Compare(op, Box::new(left), Box::new(right)));
+        }
+        if self.peek_name("in") {
+            self.next += 1;
+            self.expect(Token::LeftBracket)?;
+            let mut values = vec![self.literal()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.next += 1;
+                values.push(self.literal()?);
+            }
+            self.expect(Token::RightBracket)?;
+            return Ok(Expression::In(Box::new(left), values));
+        }
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some(Token::LeftParen) => {
+                self.next += 1;
+                self.nest()?;
+                let inner = self.or()?;
+                self.depth -= 1;
+                self.expect(Token::RightParen)?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) if name == "present" => {
+                self.next += 1;
+                self.expect(Token::LeftParen)?;
+                let path = self.path()?;
+                self.expect(Token::RightParen)?;
+                Ok(Expression::Present(path))
+            }
+            Some(Token::Name(name)) if !is_literal_name(name) => {
+                Ok(Expression::Field(self.path()?))
+            }
+            _ => Ok(Expression::Literal(self.literal()?)),
+        }
+    }
+
+    fn path(&mut self) -> Result<Vec<String>, String> {
+        let mut path = Vec::new();
+        loop {
+            match self.peek() {
+                Some(Token::Name(name)) if !is_keyword(name) => {
+                    path.push(name.clone());
+                    self.next += 1;
+                }
+                _ => return Err(format!("Expected a field at position {}", self.position())),
+            }
+            if self.peek() != Some(&Token::Dot) {
+                return Ok(path);
+            }
+            self.next += 1;
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, String> {
+        let value = match self.peek() {
+            Some(Token::Number(number)) => serde_json::Number::from_f64(*number)
+                .map(Value::Number)
+                .ok_or_else(|| self.unexpected())?,
+            Some(Token::Text(text)) => Value::String(text.clone()),
+            Some(Token::Name(name)) if name == "true" => Value::Bool(true),
+            Some(Token::Name(name)) if name == "false" => Value::Bool(false),
+            Some(Token::Name(name)) if name == "null" => Value::Null,
+            _ => return Err(self.unexpected()),
+        };
+        self.advance();
+        Ok(value)
+    }
+}
+
+fn is_literal_name(name: &str) -> bool {
+    matches!(name, "true" | "false" | "null")
+}
+
+fn is_keyword(name: &str) -> bool {
+    is_literal_name(name) || matches!(name, "and" | "or" | "not" | "in" | "present")
+}
+
+/// Parses a rule expression. Errors say what is wrong and where, by character position.
+pub fn parse_expression(text: &str) -> Result<Expression, String> {
+    let length = text.chars().count();
+    if length > MAX_EXPRESSION_LENGTH {
+        return Err(format!(
+            "Expression is longer than {} characters",
+            MAX_EXPRESSION_LENGTH
+        ));
+    }
+    let tokens = tokenize(text)?;
+    if tokens.is_empty() {
+        return Err("Expression is empty".to_string());
+    }
+    let mut parser = Parser {
+        tokens,
+        next: 0,
+        depth: 0,
+        end: length + 1,
+    };
+    let expression = parser.or()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(expression)
+}
+
+/// Parses a rule expression and checks it only refers to fields records of the entity have.
+pub fn parse_rule_expression(text: &str, entity: RuleEntity) -> Result<Expression, String> {
+    let expression = parse_expression(text)?;
+    for path in expression.fields() {
+        let known = entity.fields().contains(&path[0].as_str());
+        if !known || (path.len() > 1 && path[0] != "metadata") {
+            return Err(format!(
+                "Unknown {} field: {}; fields are {}",
+                entity,
+                path.join("."),
+                entity.fields().join(", ")
+            ));
+        }
+    }
+    Ok(expression)
+}
+
+/// A stored rule with its expressions parsed.
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub rule: ValidationRule,
+    pub condition: Option<Expression>,
+    pub requirement: Expression,
+}
+
+impl CompiledRule {
+    pub fn compile(rule: ValidationRule) -> Result<Self, String> {
+        let entity = RuleEntity::try_from(rule.entity.clone())?;
+        let condition = rule
+            .condition
+            .as_deref()
+            .map(|condition| parse_rule_expression(condition, entity))
+            .transpose()?;
+        let requirement = parse_rule_expression(&rule.requirement, entity)?;
+        Ok(Self {
+            rule,
+            condition,
+            requirement,
+        })
+    }
+
+    /// Whether the rule applies to the record and the record breaks it.
+    pub fn is_broken_by(&self, record: &Value) -> bool {
+        let applies = self
+            .condition
+            .as_ref()
+            .is_none_or(|condition| condition.holds(record));
+        applies && !self.requirement.holds(record)
+    }
+
+    /// The rule's message, or one made from its name and requirement.
+    pub fn message(&self) -> String {
+        self.rule
+            .message
+            .clone()
+            .unwrap_or_else(|| format!("{}: requires {}", self.rule.name, self.rule.requirement))
+    }
+}
+
+/// The rules the record breaks, in the order given.
+pub fn rule_violations(rules: &[CompiledRule], record: &Value) -> Vec<RuleViolation> {
+    rules
+        .iter()
+        .filter(|rule| rule.is_broken_by(record))
+        .map(|rule| RuleViolation {
+            rule_id: rule.rule.id,
+            rule_name: rule.rule.name.clone(),
+            message: rule.message(),
+        })
+        .collect()
+}
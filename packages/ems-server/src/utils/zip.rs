@@ -0,0 +1,178 @@
+// Minimal ZIP writer for server-generated archives
+//
+// Files are stored without compression: the archives hold PDFs and short text, which gain
+// little from deflate, and every unzip tool reads stored entries.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::collections::HashSet;
+
+// Bit 11 of the general purpose flags: names are UTF-8
+const UTF8_NAMES: u16 = 0x0800;
+// ZIP 2.0, the version stored entries need
+const VERSION: u16 = 20;
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+    time: u16,
+    date: u16,
+}
+
+#[derive(Debug, Default)]
+pub struct ZipArchive {
+    data: Vec<u8>,
+    entries: Vec<Entry>,
+    names: HashSet<String>,
+}
+
+impl ZipArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a file at `name`, using `/` between folders, and returns the name it was stored
+    /// under: a name already in the archive gets a number before its extension.
+    pub fn add_file(&mut self, name: &str, modified: DateTime<Utc>, contents: &[u8]) -> String {
+        let name = self.unique_name(name);
+        let (time, date) = dos_date_time(modified);
+        let entry = Entry {
+            name: name.clone(),
+            crc: crc32(contents),
+            size: contents.len() as u32,
+            offset: self.data.len() as u32,
+            time,
+            date,
+        };
+
+        let data = &mut self.data;
+        data.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        data.extend_from_slice(&VERSION.to_le_bytes());
+        data.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes()); // stored
+        data.extend_from_slice(&entry.time.to_le_bytes());
+        data.extend_from_slice(&entry.date.to_le_bytes());
+        data.extend_from_slice(&entry.crc.to_le_bytes());
+        data.extend_from_slice(&entry.size.to_le_bytes());
+        data.extend_from_slice(&entry.size.to_le_bytes());
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes()); // no extra field
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(contents);
+
+        self.names.insert(name.clone());
+        self.entries.push(entry);
+        name
+    }
+
+    /// The archive bytes, ending with the central directory.
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        for entry in &self.entries {
+            let data = &mut self.data;
+            data.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            data.extend_from_slice(&VERSION.to_le_bytes()); // made by
+            data.extend_from_slice(&VERSION.to_le_bytes()); // needed
+            data.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes()); // stored
+            data.extend_from_slice(&entry.time.to_le_bytes());
+            data.extend_from_slice(&entry.date.to_le_bytes());
+            data.extend_from_slice(&entry.crc.to_le_bytes());
+            data.extend_from_slice(&entry.size.to_le_bytes());
+            data.extend_from_slice(&entry.size.to_le_bytes());
+            data.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes()); // no extra field
+            data.extend_from_slice(&0u16.to_le_bytes()); // no comment
+            data.extend_from_slice(&0u16.to_le_bytes()); // disk
+            data.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            data.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            data.extend_from_slice(&entry.offset.to_le_bytes());
+            data.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = self.data.len() as u32 - directory_offset;
+
+        let count = self.entries.len() as u16;
+        let data = &mut self.data;
+        data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes()); // disk
+        data.extend_from_slice(&0u16.to_le_bytes()); // disk with the directory
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(&count.to_le_bytes());
+        data.extend_from_slice(&directory_size.to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes()); // no comment
+        self.data
+    }
+
+    fn unique_name(&self, name: &str) -> String {
+        if !self.names.contains(name) {
+            return name.to_string();
+        }
+        let (stem, extension) = match name.rfind('.') {
+            Some(dot) if dot > name.rfind('/').map_or(0, |slash| slash + 1) => name.split_at(dot),
+            _ => (name, ""),
+        };
+        (2..)
+            .map(|n| format!("{} ({}){}", stem, n, extension))
+            .find(|candidate| !self.names.contains(candidate))
+            .expect("some numbered name is free")
+    }
+}
+
+/// CRC-32 (IEEE) checksum of `data`, as ZIP entries carry.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// A file name safe in every archive tool: letters, digits, `-`, `_` and `.`, with anything
+/// else replaced by `_`.
+pub fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        "file".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+// MS-DOS time and date of a modification time; ZIP cannot store dates before 1980
+fn dos_date_time(modified: DateTime<Utc>) -> (u16, u16) {
+    if modified.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let time = (modified.hour() << 11) | (modified.minute() << 5) | (modified.second() / 2);
+    let date = ((modified.year() as u32 - 1980) << 9) | (modified.month() << 5) | modified.day();
+    (time as u16, date as u16)
+}
@@ -0,0 +1,752 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware::from_fn_with_state,
+        Router,
+    };
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        middleware::auth::{auth_middleware, platform_admin_middleware},
+        models::{NewPerson, NewTenantPerson, Person},
+        routes::admin::routes,
+        schema::{person, tenant_person},
+        services::{DatabaseService, TenantService},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        // Mounted the way main.rs mounts it, behind auth and the platform admin check
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes()
+            .layer(from_fn_with_state(state.clone(), platform_admin_middleware))
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+    }
+
+    async fn database() -> DatabaseService {
+        dotenv().ok();
+        DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.")
+    }
+
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    async fn create_tenant(database: &DatabaseService, name: &str, subdomain: &str) -> Uuid {
+        TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({ "name": name, "subdomain": subdomain })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn create_member(
+        database: &DatabaseService,
+        tenant_id: Uuid,
+        email: &str,
+        global_access: Vec<&str>,
+        access_level: &str,
+    ) -> Uuid {
+        let mut conn = database.get_connection().await.unwrap();
+        let member: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: email.to_string(),
+                email: email.to_string(),
+                phone: None,
+                global_access: Some(
+                    global_access
+                        .into_iter()
+                        .map(|scope| Some(scope.to_string()))
+                        .collect(),
+                ),
+                is_active: Some(true),
+            })
+            .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(tenant_person::table)
+            .values(&NewTenantPerson {
+                person_id: member.id,
+                tenant_id,
+                role: "internal".to_string(),
+                access_level: Some(vec![Some(access_level.to_string())]),
+                is_primary: Some(true),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        member.id
+    }
+
+    // Admin API tests
+
+    #[tokio::test]
+    async fn test_list_admin_tenants() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/tenants", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Admin routes require authentication, will fail without JWT token
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_suspend_tenant_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/tenants/{}/suspend", Uuid::new_v4()),
+            Some(json!({ "reason": "Unpaid invoices" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_has_platform_admin_scope() {
+        use ems_server::models::has_platform_admin_scope;
+
+        assert!(has_platform_admin_scope(&[
+            None,
+            Some("platform_admin".to_string())
+        ]));
+        // A tenant admin granted at sign-up is not a platform admin
+        assert!(!has_platform_admin_scope(&[Some("admin".to_string())]));
+        assert!(!has_platform_admin_scope(&[]));
+    }
+
+    #[tokio::test]
+    async fn test_platform_admin_workflow() {
+        use ems_server::models::{ListAdminTenantsQuery, TenantStatus};
+        use ems_server::services::{AdminService, ItemService, PersonService};
+        use ems_server::utils::circuit_breaker::{CircuitBreakerStatus, CircuitState};
+
+        let database = database().await;
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let admin = AdminService::new(database.clone());
+
+        let home_tenant = create_tenant(&database, "Platform", &format!("plat-{}", suffix)).await;
+        let customer = create_tenant(&database, "Customer", &format!("cust-{}", suffix)).await;
+        let operator = create_member(
+            &database,
+            home_tenant,
+            &format!("operator-{}@example.com", suffix),
+            vec!["platform_admin"],
+            "standard",
+        )
+        .await;
+        let owner = create_member(
+            &database,
+            customer,
+            &format!("owner-{}@example.com", suffix),
+            vec!["admin"],
+            "admin",
+        )
+        .await;
+        let planner_email = format!("planner-{}@example.com", suffix);
+        let planner = create_member(&database, customer, &planner_email, vec![], "standard").await;
+
+        assert!(admin.is_platform_admin(operator).await.unwrap());
+        assert!(!admin.is_platform_admin(owner).await.unwrap());
+        assert!(!admin.is_platform_admin(Uuid::new_v4()).await.unwrap());
+
+        ItemService::new(database.clone())
+            .create_item(
+                customer,
+                serde_json::from_value(json!({
+                    "internal_part_number": format!("ADM-{}", suffix),
+                    "manufacturer": "Acme",
+                    "context": "store",
+                    "quantity": 3
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Listing finds tenants by subdomain and reports their usage
+        let listed = admin
+            .list_tenants(ListAdminTenantsQuery {
+                status: None,
+                search: Some(suffix.to_string()),
+                limit: None,
+                offset: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+        let entry = listed.iter().find(|t| t.tenant.id == customer).unwrap();
+        assert_eq!(entry.status, TenantStatus::Active);
+        let usage = entry.usage.as_ref().unwrap();
+        assert_eq!((usage.persons, usage.items, usage.orders), (2, 1, 0));
+
+        // Operators cannot lock themselves out, and a tenant is suspended only once
+        let err = admin
+            .suspend_tenant(
+                home_tenant,
+                home_tenant,
+                serde_json::from_value(json!({ "reason": "Test" })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be suspended"));
+
+        let suspended = admin
+            .suspend_tenant(
+                home_tenant,
+                customer,
+                serde_json::from_value(json!({ "reason": "Unpaid invoices" })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(suspended.is_active, Some(false));
+        assert_eq!(
+            suspended.suspended_reason.as_deref(),
+            Some("Unpaid invoices")
+        );
+        assert!(suspended.suspended_at.is_some());
+        assert!(admin
+            .suspend_tenant(
+                home_tenant,
+                customer,
+                serde_json::from_value(json!({ "reason": "Again" })).unwrap(),
+            )
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("already suspended"));
+
+        let listed = admin
+            .list_tenants(ListAdminTenantsQuery {
+                status: Some(TenantStatus::Suspended),
+                search: Some(suffix.to_string()),
+                limit: None,
+                offset: None,
+            })
+            .await
+            .unwrap();
+        let ids: Vec<_> = listed.iter().map(|t| t.tenant.id).collect();
+        assert_eq!(ids, vec![customer]);
+
+        let reactivated = admin.reactivate_tenant(customer).await.unwrap().unwrap();
+        assert_eq!(reactivated.is_active, Some(true));
+        assert!(reactivated.suspended_at.is_none() && reactivated.suspended_reason.is_none());
+        assert!(admin
+            .reactivate_tenant(customer)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("not suspended"));
+        assert!(admin
+            .reactivate_tenant(Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none());
+
+        // Resetting the admin promotes the member and demotes the previous admin
+        let reset = admin
+            .reset_tenant_admin(
+                customer,
+                serde_json::from_value(json!({ "email": planner_email })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reset.person_id, planner);
+        assert_eq!(reset.demoted_person_ids, vec![owner]);
+
+        let persons = PersonService::new(database.clone());
+        let level = |person_id| persons.get_access_level(customer, person_id);
+        assert_eq!(level(planner).await.unwrap().to_string(), "admin");
+        assert_eq!(level(owner).await.unwrap().to_string(), "standard");
+
+        let err = admin
+            .reset_tenant_admin(
+                customer,
+                serde_json::from_value(
+                    json!({ "email": format!("operator-{}@example.com", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not a member"));
+
+        let health = admin
+            .platform_health(CircuitBreakerStatus {
+                service: "supabase".to_string(),
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                failure_threshold: 5,
+                retry_in_seconds: None,
+                last_error: None,
+            })
+            .await
+            .unwrap();
+        assert!(health.database.reachable);
+        assert!(health.tenants.total >= 2);
+        assert_eq!(
+            health.tenants.total,
+            health.tenants.active + health.tenants.suspended
+        );
+        assert!(health.persons >= 3);
+
+        let diagnostics = admin
+            .diagnostics(CircuitBreakerStatus {
+                service: "supabase".to_string(),
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                failure_threshold: 5,
+                retry_in_seconds: None,
+                last_error: None,
+            })
+            .await;
+        assert!(diagnostics.queues.is_some());
+        assert!(diagnostics.webhooks.is_some());
+        assert_eq!(diagnostics.database_pools[0].tenant_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_registry() {
+        use chrono::{Duration, Utc};
+        use ems_server::models::QueueDepths;
+        use ems_server::utils::diagnostics::{hit_rate, track_run, Diagnostics};
+
+        let diagnostics = Diagnostics::default();
+        let started_at = Utc::now();
+        diagnostics.record_run("print_queue", started_at, 12.0, &Ok(3));
+        diagnostics.record_run(
+            "print_queue",
+            started_at,
+            4.0,
+            &Err(anyhow::anyhow!("Printer unreachable")),
+        );
+        let runs = diagnostics.worker_runs();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].runs, 2);
+        assert_eq!(runs[0].failures, 1);
+        // The last success is kept alongside the last error
+        assert_eq!(runs[0].last_processed, Some(3));
+        assert_eq!(runs[0].last_success_at, Some(started_at));
+        assert_eq!(runs[0].last_error.as_deref(), Some("Printer unreachable"));
+
+        let busy = Uuid::new_v4();
+        let quiet = Uuid::new_v4();
+        for hit in [false, true, true, true] {
+            diagnostics.record_cache_lookup(busy, hit);
+        }
+        diagnostics.record_cache_lookup(quiet, false);
+        let caches = diagnostics.tenant_cache_stats();
+        assert_eq!(caches[0].tenant_id, busy);
+        assert_eq!(caches[0].hit_rate, Some(0.75));
+        assert_eq!(caches[1].hit_rate, Some(0.0));
+        assert_eq!(hit_rate(0, 0), None);
+
+        // Worker polls are recorded in the global registry
+        let outcome = track_run("diagnostics_test", async { Ok(5) }).await;
+        assert_eq!(outcome.unwrap(), 5);
+        assert!(Diagnostics::global()
+            .worker_runs()
+            .iter()
+            .any(|run| run.worker == "diagnostics_test" && run.last_processed == Some(5)));
+
+        let earlier = Utc::now() - Duration::hours(1);
+        let queues = QueueDepths {
+            print_jobs_queued: 2,
+            oldest_pending_heartbeat_at: Some(Utc::now()),
+            ..Default::default()
+        }
+        .merge(QueueDepths {
+            print_jobs_queued: 1,
+            heartbeat_events_pending: 4,
+            oldest_pending_heartbeat_at: Some(earlier),
+            ..Default::default()
+        });
+        assert_eq!(queues.print_jobs_queued, 3);
+        assert_eq!(queues.heartbeat_events_pending, 4);
+        assert_eq!(queues.oldest_pending_heartbeat_at, Some(earlier));
+    }
+
+    #[test]
+    fn test_integrity_checks() {
+        use ems_server::models::IntegrityRepair;
+        use ems_server::utils::integrity::{select_checks, INTEGRITY_CHECKS};
+
+        let all = select_checks(None).unwrap();
+        assert_eq!(all.len(), INTEGRITY_CHECKS.len());
+        let mut names: Vec<String> = all.iter().map(|check| check.name()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), INTEGRITY_CHECKS.len());
+
+        // Selected checks keep report order whatever order they were asked in
+        let selected = select_checks(Some(&[
+            "jobs.assigned_person_id".to_string(),
+            "item_bom.component_item_id".to_string(),
+        ]))
+        .unwrap();
+        assert_eq!(selected[0].name(), "item_bom.component_item_id");
+        assert_eq!(selected[1].name(), "jobs.assigned_person_id");
+
+        let err = select_checks(Some(&["items.id".to_string()])).unwrap_err();
+        assert!(err.contains("Invalid integrity check"), "{}", err);
+
+        // Rows that mean nothing without their reference are deleted, others keep their row
+        let bom = &selected[0];
+        assert_eq!(bom.repair, IntegrityRepair::Delete);
+        assert!(bom
+            .repair_sql()
+            .starts_with("DELETE FROM public.item_bom t WHERE"));
+        assert!(bom.count_sql().contains(
+            "NOT EXISTS (SELECT 1 FROM public.items r WHERE r.id = t.component_item_id)"
+        ));
+        assert!(bom.sample_sql(5).ends_with("LIMIT 5"));
+        let assigned = &selected[1];
+        assert_eq!(assigned.repair, IntegrityRepair::Clear);
+        assert!(assigned
+            .repair_sql()
+            .starts_with("UPDATE public.jobs t SET assigned_person_id = NULL WHERE"));
+    }
+
+    #[tokio::test]
+    async fn test_integrity_check_workflow() {
+        use ems_server::models::IntegrityCheckRequest;
+        use ems_server::services::{AdminService, JobService};
+
+        let database = database().await;
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let admin = AdminService::new(database.clone());
+        let tenant_id = create_tenant(&database, "Integrity", &format!("integ-{}", suffix)).await;
+
+        // jobs.item_id has no foreign key, so a job can outlive the item it builds
+        let missing_item = Uuid::new_v4();
+        let job_id = JobService::new(database.clone())
+            .create_job(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "job_number": format!("INT-{}", suffix),
+                    "item_id": missing_item,
+                    "quantity": 1,
+                    "job_type": "manufacturing"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let request = || IntegrityCheckRequest {
+            checks: Some(vec!["jobs.item_id".to_string()]),
+            repair: None,
+        };
+        let report = admin.check_integrity(request()).await.unwrap();
+        assert_eq!(report.checks_run, 1);
+        let finding = &report.findings[0];
+        assert_eq!(finding.check, "jobs.item_id");
+        assert!(finding.sample_ids.contains(&job_id) || finding.dangling > 20);
+        assert_eq!(finding.repaired, None);
+        assert!(!report.is_clean());
+
+        let repaired = admin
+            .check_integrity(IntegrityCheckRequest {
+                repair: Some(true),
+                ..request()
+            })
+            .await
+            .unwrap();
+        assert!(repaired.repaired >= 1);
+        let report = admin.check_integrity(request()).await.unwrap();
+        assert!(report.findings.is_empty() && report.is_clean());
+
+        // The job stays, without the missing item
+        let job = JobService::new(database.clone())
+            .get_job_by_id(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.item_id, None);
+
+        let err = admin
+            .check_integrity(IntegrityCheckRequest {
+                checks: Some(vec!["jobs.nothing".to_string()]),
+                repair: Some(true),
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid integrity check"));
+    }
+
+    #[test]
+    fn test_maintenance_cutoffs() {
+        use chrono::{TimeZone, Utc};
+        use ems_server::models::{CompactMachineHistoryRequest, DecommissionStaleMachinesRequest};
+        use validator::Validate;
+
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 15, 45, 0).unwrap();
+        let stale = DecommissionStaleMachinesRequest {
+            months: 1,
+            tenant_id: None,
+            dry_run: None,
+        };
+        assert_eq!(
+            stale.cutoff(now),
+            Utc.with_ymd_and_hms(2026, 2, 28, 15, 45, 0).unwrap()
+        );
+        assert!(DecommissionStaleMachinesRequest { months: 0, ..stale }
+            .validate()
+            .is_err());
+
+        // Compaction only covers whole UTC days
+        let compact = CompactMachineHistoryRequest {
+            older_than_days: 30,
+            tenant_id: None,
+        };
+        assert_eq!(
+            compact.cutoff(now),
+            Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap()
+        );
+        assert!(compact.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/maintenance/stale-machines/decommission",
+            Some(json!({ "months": 6, "dry_run": true })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_machine_maintenance_workflow() {
+        use chrono::{Duration, Utc};
+        use diesel::{ExpressionMethods, QueryDsl};
+        use ems_server::models::{
+            CompactMachineHistoryRequest, DecommissionStaleMachinesRequest, MachineStatus,
+        };
+        use ems_server::schema::{
+            machine_status_daily, machine_status_history, machine_telemetry, machines,
+        };
+        use ems_server::services::{AdminService, MachineService};
+
+        let database = database().await;
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let admin = AdminService::new(database.clone());
+        let tenant_id = create_tenant(&database, "Maintenance", &format!("maint-{}", suffix)).await;
+
+        let machine_service = MachineService::new(database.clone());
+        let machine = |name: &'static str| {
+            let machine_service = &machine_service;
+            async move {
+                machine_service
+                    .create_machine(
+                        tenant_id,
+                        serde_json::from_value(json!({
+                            "name": name, "ip": "10.0.0.80", "port": 502, "protocol": "tcp"
+                        }))
+                        .unwrap(),
+                    )
+                    .await
+                    .unwrap()
+                    .id
+            }
+        };
+        let dead = machine("Test rig").await;
+        let live = machine("Reflow oven").await;
+
+        let mut conn = database.get_connection().await.unwrap();
+        diesel::update(machines::table.filter(machines::id.eq(dead)))
+            .set(machines::last_heartbeat.eq(Utc::now() - Duration::days(400)))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::update(machines::table.filter(machines::id.eq(live)))
+            .set(machines::last_heartbeat.eq(Utc::now()))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let request = |dry_run| DecommissionStaleMachinesRequest {
+            months: 12,
+            tenant_id: Some(tenant_id),
+            dry_run: Some(dry_run),
+        };
+        let report = admin
+            .decommission_stale_machines(request(true))
+            .await
+            .unwrap();
+        assert_eq!(report.decommissioned, 0);
+        assert_eq!(report.machines.len(), 1);
+        assert_eq!(report.machines[0].id, dead);
+        let report = admin
+            .decommission_stale_machines(request(false))
+            .await
+            .unwrap();
+        assert_eq!(report.decommissioned, 1);
+        assert!(admin
+            .decommission_stale_machines(request(false))
+            .await
+            .unwrap()
+            .machines
+            .is_empty());
+
+        // Decommissioned machines only show up when listed by status
+        let listed = machine_service
+            .list_machines(tenant_id, None, None, None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, live);
+        let retired = machine_service
+            .list_machines(
+                tenant_id,
+                Some(MachineStatus::Decommissioned),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(retired.len(), 1);
+        assert_eq!(retired[0].id, dead);
+
+        // Two days before the cutoff: idle from 10:00, busy from 16:00 until the cutoff
+        let compact = CompactMachineHistoryRequest {
+            older_than_days: 30,
+            tenant_id: Some(tenant_id),
+        };
+        let cutoff = compact.cutoff(Utc::now());
+        let day = cutoff - Duration::days(2);
+        diesel::insert_into(machine_status_history::table)
+            .values(&vec![
+                (
+                    machine_status_history::machine_id.eq(live),
+                    machine_status_history::tenant_id.eq(tenant_id),
+                    machine_status_history::new_status.eq("idle"),
+                    machine_status_history::changed_at.eq(day + Duration::hours(10)),
+                ),
+                (
+                    machine_status_history::machine_id.eq(live),
+                    machine_status_history::tenant_id.eq(tenant_id),
+                    machine_status_history::new_status.eq("busy"),
+                    machine_status_history::changed_at.eq(day + Duration::hours(16)),
+                ),
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(machine_telemetry::table)
+            .values(&vec![
+                (
+                    machine_telemetry::tenant_id.eq(tenant_id),
+                    machine_telemetry::machine_id.eq(live),
+                    machine_telemetry::channel.eq("temperature"),
+                    machine_telemetry::value.eq(200.0),
+                    machine_telemetry::recorded_at.eq(day + Duration::hours(11)),
+                ),
+                (
+                    machine_telemetry::tenant_id.eq(tenant_id),
+                    machine_telemetry::machine_id.eq(live),
+                    machine_telemetry::channel.eq("temperature"),
+                    machine_telemetry::value.eq(240.0),
+                    machine_telemetry::recorded_at.eq(day + Duration::hours(12)),
+                ),
+            ])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let report = admin.compact_machine_history(compact).await.unwrap();
+        assert_eq!(report.cutoff, cutoff);
+        assert_eq!(report.compacted.telemetry_rows, 2);
+        assert_eq!(report.compacted.telemetry_days, 1);
+        assert_eq!(report.compacted.status_rows, 2);
+        // Idle and busy on the first day, busy on the second
+        assert_eq!(report.compacted.status_days, 3);
+
+        let daily: Vec<(String, i32, f64)> = machine_status_daily::table
+            .filter(machine_status_daily::machine_id.eq(live))
+            .order((machine_status_daily::day, machine_status_daily::status))
+            .select((
+                machine_status_daily::status,
+                machine_status_daily::transitions,
+                machine_status_daily::seconds,
+            ))
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            daily,
+            vec![
+                ("busy".to_string(), 1, 8.0 * 3600.0),
+                ("idle".to_string(), 1, 6.0 * 3600.0),
+                ("busy".to_string(), 0, 24.0 * 3600.0),
+            ]
+        );
+
+        // The status at the cutoff is kept, so compacting again changes nothing
+        let kept: Vec<String> = machine_status_history::table
+            .filter(machine_status_history::machine_id.eq(live))
+            .filter(machine_status_history::changed_at.le(cutoff))
+            .select(machine_status_history::new_status)
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(kept, vec!["busy".to_string()]);
+        let again = admin
+            .compact_machine_history(CompactMachineHistoryRequest {
+                older_than_days: 30,
+                tenant_id: Some(tenant_id),
+            })
+            .await
+            .unwrap();
+        assert_eq!(again.compacted.status_rows, 0);
+        assert_eq!(again.compacted.telemetry_rows, 0);
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{AuthBackendKind, NewPerson, Person},
+        schema::person,
+        services::{AuthBackend, DatabaseService, LocalAuthBackend},
+        utils::AuthUtils,
+    };
+
+    // Password hashing tests
+
+    #[test]
+    fn test_local_password_hash_round_trip() {
+        let hash = AuthUtils::hash_local_password("Secret123!").unwrap();
+        assert!(hash.starts_with("$argon2"));
+        assert!(AuthUtils::verify_local_password("Secret123!", &hash).unwrap());
+        assert!(!AuthUtils::verify_local_password("secret123!", &hash).unwrap());
+
+        // Every hash gets its own salt
+        let again = AuthUtils::hash_local_password("Secret123!").unwrap();
+        assert_ne!(hash, again);
+
+        assert!(AuthUtils::verify_local_password("Secret123!", "not-a-hash").is_err());
+    }
+
+    #[test]
+    fn test_auth_backend_kind_conversion() {
+        assert_eq!(
+            AuthBackendKind::try_from("local".to_string()).unwrap(),
+            AuthBackendKind::Local
+        );
+        assert_eq!(String::from(AuthBackendKind::Supabase), "supabase");
+        assert!(AuthBackendKind::try_from("ldap".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_person_serialization_omits_password_hash() {
+        let person = Person {
+            id: Uuid::new_v4(),
+            supabase_uid: Uuid::new_v4(),
+            name: "Local User".to_string(),
+            email: "local@example.com".to_string(),
+            phone: None,
+            global_access: Some(vec![]),
+            is_active: Some(true),
+            created_at: Some(chrono::Utc::now()),
+            updated_at: Some(chrono::Utc::now()),
+            last_login: None,
+            password_hash: Some("$argon2id$secret".to_string()),
+            email_verified_at: None,
+        };
+
+        let json = serde_json::to_value(&person).unwrap();
+        assert!(json.get("password_hash").is_none());
+        assert!(json.get("email_verified_at").is_some());
+    }
+
+    // Local backend flow
+
+    #[tokio::test]
+    async fn test_local_backend_sign_in_and_email_verification() {
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let backend = LocalAuthBackend::new(None, true, None);
+        let email = format!("local-{}@example.com", Uuid::new_v4().simple());
+
+        let person: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: backend
+                    .create_identity(&email, "Secret123!", serde_json::json!({}))
+                    .await
+                    .unwrap(),
+                name: "Local User".to_string(),
+                email: email.clone(),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        backend
+            .person_created(&mut conn, &person, "Secret123!")
+            .await
+            .unwrap();
+
+        // The right password is still refused until the address is confirmed
+        let error = backend
+            .authenticate(&mut conn, &email, "Secret123!")
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Email not verified");
+
+        let token = LocalAuthBackend::issue_verification_token(&mut conn, person.id)
+            .await
+            .unwrap();
+        let verified = backend.verify_email(&mut conn, &token).await.unwrap();
+        assert_eq!(verified.id, person.id);
+        assert!(verified.email_verified_at.is_some());
+
+        // Tokens are single use
+        let error = backend.verify_email(&mut conn, &token).await.unwrap_err();
+        assert!(error.to_string().contains("Invalid or expired"));
+
+        let signed_in = backend
+            .authenticate(&mut conn, &email, "Secret123!")
+            .await
+            .unwrap();
+        assert_eq!(signed_in.id, person.id);
+
+        let error = backend
+            .authenticate(&mut conn, &email, "wrong")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Authentication failed"));
+        let error = backend
+            .authenticate(&mut conn, &format!("unknown-{}", email), "Secret123!")
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Authentication failed"));
+
+        // Resending to a verified or unknown address is a silent no-op
+        backend
+            .resend_verification(&mut conn, &email)
+            .await
+            .unwrap();
+        backend
+            .resend_verification(&mut conn, "nobody@example.com")
+            .await
+            .unwrap();
+
+        diesel::delete(person::table.find(person.id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,530 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware::from_fn_with_state,
+        Router,
+    };
+    use chrono::Utc;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        middleware::auth::auth_middleware,
+        routes::billing::{routes, webhook_routes},
+        services::DatabaseService,
+        utils::billing::stripe_signature,
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes()
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+    }
+
+    async fn database() -> DatabaseService {
+        dotenv().ok();
+        DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.")
+    }
+
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Billing API tests
+
+    #[tokio::test]
+    async fn test_get_billing() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Billing routes require authentication, will fail without JWT token
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_open_billing_portal() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/portal",
+            Some(json!({ "return_url": "https://ems.example.com/settings" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_stripe_webhook_requires_signature() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = webhook_routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/stripe")
+            .header("Stripe-Signature", "t=1,v1=00")
+            .body(Body::from(json!({ "id": "evt_1" }).to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        // Refused whether or not STRIPE_WEBHOOK_SECRET is configured here
+        assert!(
+            response.status() == StatusCode::BAD_REQUEST
+                || response.status() == StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    // Billing helper tests
+
+    #[test]
+    fn test_verify_stripe_signature() {
+        use ems_server::utils::billing::verify_stripe_signature;
+
+        let payload = br#"{"id":"evt_1","type":"invoice.paid"}"#;
+        let now = Utc::now().timestamp();
+        let header = stripe_signature(payload, "whsec_test", now);
+
+        assert!(verify_stripe_signature(payload, &header, "whsec_test", now).is_ok());
+        // A rolled secret is accepted alongside the old one
+        let rolled = format!("{},v1={}", header, "ab".repeat(32));
+        assert!(verify_stripe_signature(payload, &rolled, "whsec_test", now + 10).is_ok());
+
+        assert!(verify_stripe_signature(payload, &header, "whsec_other", now).is_err());
+        assert!(verify_stripe_signature(b"{}", &header, "whsec_test", now).is_err());
+        let err = verify_stripe_signature(payload, &header, "whsec_test", now + 301).unwrap_err();
+        assert!(err.contains("tolerance"));
+        assert!(verify_stripe_signature(payload, "v1=abc", "whsec_test", now).is_err());
+        assert!(verify_stripe_signature(payload, "t=x,v1=zz", "whsec_test", now).is_err());
+    }
+
+    #[test]
+    fn test_subscription_state() {
+        use ems_server::models::StripeSubscription;
+        use ems_server::utils::billing::{
+            subscription_grants_access, subscription_period_end, subscription_plan,
+        };
+
+        assert_eq!(subscription_grants_access("active"), Some(true));
+        assert_eq!(subscription_grants_access("trialing"), Some(true));
+        assert_eq!(subscription_grants_access("unpaid"), Some(false));
+        assert_eq!(subscription_grants_access("canceled"), Some(false));
+        // Stripe is still retrying payment
+        assert_eq!(subscription_grants_access("past_due"), None);
+
+        let subscription: StripeSubscription = serde_json::from_value(json!({
+            "id": "sub_1",
+            "customer": "cus_1",
+            "status": "active",
+            "items": { "data": [
+                { "price": { "id": "price_1", "lookup_key": null }, "current_period_end": 1767225600 }
+            ] }
+        }))
+        .unwrap();
+        assert_eq!(subscription_plan(&subscription), "price_1");
+        assert!(!subscription.cancel_at_period_end);
+        assert_eq!(
+            subscription_period_end(&subscription).unwrap().to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stripe_billing() {
+        use axum::{routing::post, Form, Json};
+        use ems_server::models::Tenant;
+        use ems_server::services::{BillingProvider, StripeBilling};
+        use std::collections::HashMap;
+
+        // Stand-in for the Stripe API
+        let api =
+            Router::new()
+                .route(
+                    "/v1/customers",
+                    post(
+                        |headers: axum::http::HeaderMap,
+                         Form(form): Form<HashMap<String, String>>| async move {
+                            let authorized = headers
+                                .get(header::AUTHORIZATION)
+                                .is_some_and(|value| value == "Bearer sk_test");
+                            if !authorized || !headers.contains_key("Idempotency-Key") {
+                                return Err(StatusCode::UNAUTHORIZED);
+                            }
+                            Ok(Json(json!({
+                                "id": format!("cus_{}", form["metadata[subdomain]"]),
+                                "email": form.get("email"),
+                            })))
+                        },
+                    ),
+                )
+                .route(
+                    "/v1/billing_portal/sessions",
+                    post(|Form(form): Form<HashMap<String, String>>| async move {
+                        if form["customer"] == "cus_missing" {
+                            return Err(StatusCode::NOT_FOUND);
+                        }
+                        Ok(Json(json!({
+                            "url": format!("https://billing.example.com/{}", form["customer"])
+                        })))
+                    }),
+                );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, api).await.unwrap() });
+
+        let stripe = StripeBilling::new(&base_url, "sk_test".to_string()).unwrap();
+        let tenant = Tenant {
+            id: Uuid::new_v4(),
+            name: "Acme".to_string(),
+            subdomain: "acme".to_string(),
+            database_url: None,
+            settings: None,
+            is_active: Some(true),
+            created_at: None,
+            updated_at: None,
+            suspended_at: None,
+            suspended_reason: None,
+            is_sandbox: false,
+            sandbox_of: None,
+            sandbox_expires_at: None,
+        };
+
+        let customer = stripe
+            .create_customer(&tenant, Some("owner@acme.test"))
+            .await
+            .unwrap();
+        assert_eq!(customer, "cus_acme");
+        let url = stripe
+            .create_portal_session(&customer, "https://ems.example.com/settings")
+            .await
+            .unwrap();
+        assert_eq!(url, "https://billing.example.com/cus_acme");
+
+        let err = stripe
+            .create_portal_session("cus_missing", "https://ems.example.com")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Billing request failed"));
+        let unauthorized = StripeBilling::new(&base_url, "sk_wrong".to_string()).unwrap();
+        assert!(unauthorized.create_customer(&tenant, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_billing_workflow() {
+        use async_trait::async_trait;
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::{
+            AccessLevel, BillingPortalRequest, CallerContext, NewPerson, NewTenantPerson, Person,
+            SuspendTenantRequest, Tenant,
+        };
+        use ems_server::schema::{person, tenant_person};
+        use ems_server::services::{AdminService, BillingProvider, BillingService, TenantService};
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Default)]
+        struct RecordingProvider {
+            customers: Mutex<Vec<(Uuid, Option<String>)>>,
+        }
+
+        #[async_trait]
+        impl BillingProvider for RecordingProvider {
+            async fn create_customer(
+                &self,
+                tenant: &Tenant,
+                email: Option<&str>,
+            ) -> anyhow::Result<String> {
+                self.customers
+                    .lock()
+                    .unwrap()
+                    .push((tenant.id, email.map(str::to_string)));
+                Ok(format!("cus_{}", tenant.id.simple()))
+            }
+
+            async fn create_portal_session(
+                &self,
+                customer_id: &str,
+                return_url: &str,
+            ) -> anyhow::Result<String> {
+                Ok(format!("portal://{}?return={}", customer_id, return_url))
+            }
+        }
+
+        let database = database().await;
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let tenants = TenantService::new(database.clone());
+        let tenant_id = tenants
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Billed", "subdomain": format!("bill-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let email = format!("owner-{}@example.com", suffix);
+        let mut conn = database.get_connection().await.unwrap();
+        let owner: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Owner".to_string(),
+                email: email.clone(),
+                phone: None,
+                global_access: None,
+                is_active: Some(true),
+            })
+            .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(tenant_person::table)
+            .values(&NewTenantPerson {
+                person_id: owner.id,
+                tenant_id,
+                role: "internal".to_string(),
+                access_level: Some(vec![Some("admin".to_string())]),
+                is_primary: Some(true),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let admin = CallerContext {
+            person_id: owner.id,
+            access_level: AccessLevel::Admin,
+        };
+        let standard = CallerContext {
+            person_id: owner.id,
+            access_level: AccessLevel::Standard,
+        };
+        let provider = Arc::new(RecordingProvider::default());
+        let secret = "whsec_workflow";
+        let billing = BillingService::with_provider(
+            database.clone(),
+            Some(provider.clone()),
+            Some(secret.to_string()),
+            Some("https://ems.example.com/billing".to_string()),
+        );
+
+        // Tenants start on the free plan, visible to admins only
+        let view = billing.get_billing(tenant_id, &admin).await.unwrap();
+        assert_eq!(view.plan, "free");
+        assert!(!view.customer_linked && view.subscription_status.is_none());
+        let err = billing.get_billing(tenant_id, &standard).await.unwrap_err();
+        assert!(err.to_string().contains("Access denied"));
+
+        // The first portal visit creates the Stripe customer; later visits reuse it
+        let portal = billing
+            .open_portal(tenant_id, &admin, BillingPortalRequest::default())
+            .await
+            .unwrap();
+        let customer = format!("cus_{}", tenant_id.simple());
+        assert_eq!(
+            portal.url,
+            format!(
+                "portal://{}?return=https://ems.example.com/billing",
+                customer
+            )
+        );
+        billing
+            .open_portal(
+                tenant_id,
+                &admin,
+                BillingPortalRequest {
+                    return_url: Some("https://ems.example.com/other".to_string()),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            *provider.customers.lock().unwrap(),
+            vec![(tenant_id, Some(email.clone()))]
+        );
+        assert!(
+            billing
+                .get_billing(tenant_id, &admin)
+                .await
+                .unwrap()
+                .customer_linked
+        );
+
+        let deliver = |event: Value| {
+            let payload = event.to_string();
+            let header = stripe_signature(payload.as_bytes(), secret, Utc::now().timestamp());
+            let billing = &billing;
+            async move {
+                billing
+                    .handle_webhook(payload.as_bytes(), Some(&header))
+                    .await
+            }
+        };
+        let subscription = |status: &str| {
+            json!({
+                "id": "sub_1",
+                "customer": customer,
+                "status": status,
+                "current_period_end": 1767225600,
+                "cancel_at_period_end": false,
+                "items": { "data": [ { "price": { "id": "price_pro", "lookup_key": "pro" } } ] }
+            })
+        };
+        let tenant_active = || async {
+            TenantService::new(database.clone())
+                .get_tenant_by_id(tenant_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_active
+        };
+        let event_id = |name: &str| format!("evt_{}_{}", name, suffix);
+
+        // Unsigned and tampered deliveries are refused
+        let err = billing.handle_webhook(b"{}", None).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid signature"));
+        let header = stripe_signature(b"{}", secret, Utc::now().timestamp());
+        assert!(billing
+            .handle_webhook(b"{\"id\":1}", Some(&header))
+            .await
+            .is_err());
+
+        deliver(json!({
+            "id": event_id("created"),
+            "type": "customer.subscription.created",
+            "data": { "object": subscription("active") }
+        }))
+        .await
+        .unwrap();
+        let view = billing.get_billing(tenant_id, &admin).await.unwrap();
+        assert_eq!(view.plan, "pro");
+        assert_eq!(view.subscription_status.as_deref(), Some("active"));
+        assert!(view.current_period_end.is_some());
+
+        // A failed payment Stripe will retry changes nothing; one it gave up on suspends
+        let failed = |id: &str, next_attempt: Option<i64>| {
+            json!({
+                "id": event_id(id),
+                "type": "invoice.payment_failed",
+                "data": { "object": {
+                    "id": "in_1",
+                    "customer": customer,
+                    "next_payment_attempt": next_attempt
+                } }
+            })
+        };
+        deliver(failed("failed_retry", Some(1767225600)))
+            .await
+            .unwrap();
+        assert_eq!(tenant_active().await, Some(true));
+        deliver(failed("failed_final", None)).await.unwrap();
+        assert_eq!(tenant_active().await, Some(false));
+        assert!(
+            billing
+                .get_billing(tenant_id, &admin)
+                .await
+                .unwrap()
+                .suspended
+        );
+
+        let paid = json!({
+            "id": event_id("paid"),
+            "type": "invoice.paid",
+            "data": { "object": { "id": "in_1", "customer": customer, "next_payment_attempt": null } }
+        });
+        deliver(paid).await.unwrap();
+        assert_eq!(tenant_active().await, Some(true));
+        assert!(
+            !billing
+                .get_billing(tenant_id, &admin)
+                .await
+                .unwrap()
+                .suspended
+        );
+
+        // A redelivered event is only applied once
+        deliver(failed("failed_final", None)).await.unwrap();
+        assert_eq!(tenant_active().await, Some(true));
+
+        // Billing never lifts a suspension made by a platform operator
+        let operator_tenant = Uuid::new_v4();
+        AdminService::new(database.clone())
+            .suspend_tenant(
+                operator_tenant,
+                tenant_id,
+                SuspendTenantRequest {
+                    reason: "Abuse".to_string(),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        deliver(json!({
+            "id": event_id("paid_again"),
+            "type": "invoice.paid",
+            "data": { "object": { "id": "in_2", "customer": customer, "next_payment_attempt": null } }
+        }))
+        .await
+        .unwrap();
+        assert_eq!(tenant_active().await, Some(false));
+        AdminService::new(database.clone())
+            .reactivate_tenant(tenant_id)
+            .await
+            .unwrap();
+
+        // Ending the subscription drops the tenant to the free plan and suspends it
+        deliver(json!({
+            "id": event_id("deleted"),
+            "type": "customer.subscription.deleted",
+            "data": { "object": subscription("canceled") }
+        }))
+        .await
+        .unwrap();
+        let view = billing.get_billing(tenant_id, &admin).await.unwrap();
+        assert_eq!(view.plan, "free");
+        assert_eq!(view.subscription_status.as_deref(), Some("canceled"));
+        assert!(view.suspended);
+        assert_eq!(tenant_active().await, Some(false));
+
+        // Events for customers no tenant is billed as are acknowledged and ignored
+        deliver(json!({
+            "id": event_id("unknown"),
+            "type": "invoice.paid",
+            "data": { "object": { "id": "in_3", "customer": "cus_unknown", "next_payment_attempt": null } }
+        }))
+        .await
+        .unwrap();
+    }
+}
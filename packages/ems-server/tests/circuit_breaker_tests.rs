@@ -0,0 +1,215 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use std::env;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{LoginRequest, NewPerson, NewPersonCredential, Person},
+        schema::{person, person_credentials},
+        services::{AuthService, DatabaseService, SupabaseAuthBackend, SupabaseService},
+        utils::{
+            circuit_breaker::{
+                CircuitBreaker, CircuitBreakerConfig, CircuitState, ServiceUnavailable,
+            },
+            AuthUtils,
+        },
+    };
+
+    fn breaker(failure_threshold: u32, open_millis: u64) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "Test",
+            CircuitBreakerConfig {
+                failure_threshold,
+                open_duration: Duration::from_millis(open_millis),
+                call_timeout: Duration::from_millis(200),
+            },
+        )
+    }
+
+    async fn fail(breaker: &CircuitBreaker) -> anyhow::Error {
+        breaker
+            .call(async { Err::<(), _>("connection refused") })
+            .await
+            .unwrap_err()
+    }
+
+    // Breaker state tests
+
+    #[tokio::test]
+    async fn test_breaker_opens_after_threshold_and_fails_fast() {
+        let breaker = breaker(3, 60_000);
+
+        for _ in 0..2 {
+            fail(&breaker).await;
+            assert_eq!(breaker.state(), CircuitState::Closed);
+        }
+        let error = fail(&breaker).await;
+        assert!(ServiceUnavailable::is(&error));
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        // Open circuits do not call the service at all
+        let calls = AtomicU32::new(0);
+        let error = breaker
+            .call(async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>(())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(AtomicU32::load(&calls, Ordering::SeqCst), 0);
+        assert_eq!(error.to_string(), "Test unavailable: circuit open");
+
+        let status = breaker.status();
+        assert_eq!(status.consecutive_failures, 3);
+        assert!(status.retry_in_seconds.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let breaker = breaker(2, 60_000);
+
+        fail(&breaker).await;
+        breaker.call(async { Ok::<_, String>(()) }).await.unwrap();
+        fail(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_trial_closes_or_reopens() {
+        let breaker = breaker(1, 50);
+
+        fail(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        // A failed trial reopens straight away
+        fail(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+
+        // A successful trial closes the circuit
+        let value = breaker.call(async { Ok::<_, String>(7) }).await.unwrap();
+        assert_eq!(value, 7);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.status().consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_calls_time_out_as_failures() {
+        let breaker = breaker(1, 60_000);
+
+        let error = breaker
+            .call(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, String>(())
+            })
+            .await
+            .unwrap_err();
+        assert!(ServiceUnavailable::is(&error));
+        assert!(error.to_string().contains("timed out"));
+        assert_eq!(breaker.state(), CircuitState::Open);
+    }
+
+    // Degraded sign-in tests
+
+    #[tokio::test]
+    async fn test_login_with_cached_credential_while_supabase_unavailable() {
+        dotenv().ok();
+        // The test environment points SUPABASE_URL at a closed port
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut supabase = SupabaseService::new(
+            &env::var("SUPABASE_URL").unwrap_or_else(|_| "http://127.0.0.1:9".to_string()),
+            "test-anon-key",
+        )
+        .await
+        .unwrap();
+        supabase.degraded_login_max_age = Some(chrono::Duration::days(30));
+
+        let mut conn = database.get_connection().await.unwrap();
+        let email = format!("degraded-{}@example.com", Uuid::new_v4().simple());
+        let person: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Degraded Login".to_string(),
+                email: email.clone(),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let auth = AuthService::new(
+            database.clone(),
+            supabase.clone(),
+            Arc::new(SupabaseAuthBackend::new(supabase.clone())),
+        );
+        let login = |password: &str| LoginRequest {
+            email: email.clone(),
+            password: password.to_string(),
+            tenant_subdomain: None,
+        };
+
+        // Nothing cached yet: the outage is reported rather than a bad password
+        let error = auth
+            .person_only_login(login("Secret123!"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Supabase unavailable"));
+
+        diesel::insert_into(person_credentials::table)
+            .values(&NewPersonCredential {
+                person_id: person.id,
+                password_hash: AuthUtils::hash_password("Secret123!").unwrap(),
+                verified_at: Utc::now(),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let response = auth.person_only_login(login("Secret123!")).await.unwrap();
+        assert_eq!(response.person.id, person.id);
+        assert!(AuthUtils::verify_jwt_token(&response.access_token).is_ok());
+
+        let error = auth.person_only_login(login("wrong")).await.unwrap_err();
+        assert!(error.to_string().contains("Authentication failed"));
+
+        // Unknown emails are rejected as usual
+        let error = auth
+            .person_only_login(LoginRequest {
+                email: format!("unknown-{}", email),
+                ..login("Secret123!")
+            })
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Authentication failed"));
+
+        // Disabled degradation never reads the cache
+        supabase.degraded_login_max_age = None;
+        let backend = Arc::new(SupabaseAuthBackend::new(supabase.clone()));
+        let error = AuthService::new(database.clone(), supabase, backend)
+            .person_only_login(login("Secret123!"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Supabase unavailable"));
+
+        diesel::delete(person::table.find(person.id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+}
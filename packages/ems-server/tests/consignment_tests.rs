@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{ConsignmentBilling, ConsignmentStock, Quantity},
+        routes::consignment::routes,
+        utils::consignment::{billing_amount, draw_consignment, invoice_totals, unit_cost},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    fn balance(quantity: i32) -> ConsignmentStock {
+        ConsignmentStock {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
+            inventory_item_id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            owner_type: "vendor".to_string(),
+            quantity: quantity.into(),
+            unit_price: Some(0.5),
+            currency: Some("USD".to_string()),
+            notes: None,
+            last_received_date: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn billing(amount: f64, currency: Option<&str>) -> ConsignmentBilling {
+        ConsignmentBilling {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            consignment_stock_id: Uuid::new_v4(),
+            stock_movement_id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            quantity: 1.into(),
+            unit_price: amount,
+            amount,
+            currency: currency.map(str::to_string),
+            status: "pending".to_string(),
+            invoice_reference: None,
+            invoiced_at: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    // Consignment API Tests
+
+    #[tokio::test]
+    async fn test_list_consignment_stock_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request =
+            create_request_with_tenant(Method::GET, "/stock?owner_type=vendor", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Consignment routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invoice_consignment_billings_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/billings/invoice",
+            Some(json!({
+                "owner_id": Uuid::new_v4(),
+                "invoice_reference": "SB-2024-001"
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Consignment helper tests
+
+    #[test]
+    fn test_draw_consignment_after_own_stock() {
+        let balances = vec![balance(10), balance(0), balance(20)];
+
+        // Own stock covers the issue
+        assert!(draw_consignment(Quantity::from(5), &balances, Quantity::from(5)).is_empty());
+
+        // The rest comes from the oldest balance first, skipping empty ones
+        let drawn = draw_consignment(Quantity::from(5), &balances, Quantity::from(20));
+        assert_eq!(drawn.len(), 2);
+        assert_eq!(drawn[0].0.id, balances[0].id);
+        assert_eq!(drawn[0].1, Quantity::from(10));
+        assert_eq!(drawn[1].0.id, balances[2].id);
+        assert_eq!(drawn[1].1, Quantity::from(5));
+
+        // Without own stock everything is consignment stock, as far as it goes
+        let drawn = draw_consignment(Quantity::ZERO, &balances, Quantity::from(50));
+        let total: Quantity = drawn.iter().map(|(_, quantity)| *quantity).sum();
+        assert_eq!(total, Quantity::from(30));
+    }
+
+    #[test]
+    fn test_consignment_unit_cost_and_amount() {
+        assert_eq!(
+            unit_cost(Some(&json!({ "unit_cost": 1.25, "unit_price": 2.0 }))),
+            1.25
+        );
+        assert_eq!(unit_cost(Some(&json!({ "unit_price": 2.0 }))), 2.0);
+        assert_eq!(unit_cost(Some(&json!({ "unit_cost": "n/a" }))), 0.0);
+        assert_eq!(unit_cost(None), 0.0);
+
+        assert_eq!(billing_amount(Quantity::from(3), 0.335), 1.01);
+        let quantity: Quantity = serde_json::from_value(json!("0.5")).unwrap();
+        assert_eq!(billing_amount(quantity, 9.99), 5.0);
+    }
+
+    #[test]
+    fn test_invoice_totals_per_currency() {
+        let totals = invoice_totals(&[
+            billing(10.1, Some("USD")),
+            billing(0.2, Some("USD")),
+            billing(5.0, Some("EUR")),
+            billing(1.0, None),
+        ]);
+
+        assert_eq!(totals.len(), 3);
+        assert_eq!(totals["USD"], 10.3);
+        assert_eq!(totals["EUR"], 5.0);
+        assert_eq!(totals[""], 1.0);
+    }
+}
@@ -0,0 +1,312 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::models::{
+        check_dashboard, CreateDashboardRequest, DashboardLayout, DashboardWidget,
+        MachineWidgetParams, UpdateDashboardRequest, WidgetSource,
+    };
+    use ems_server::services::{DashboardService, DatabaseService, ReportService};
+    use ems_server::{routes::dashboard::routes, AppState};
+
+    fn widgets(value: Value) -> Vec<DashboardWidget> {
+        serde_json::from_value(value).expect("Widgets did not parse")
+    }
+
+    fn machines_widget(id: &str, site: &str, x: u32) -> Value {
+        json!({
+            "id": id,
+            "title": format!("Machines in {}", site),
+            "position": { "x": x, "y": 0, "w": 6, "h": 4 },
+            "kind": "machines",
+            "params": { "site": site }
+        })
+    }
+
+    #[test]
+    fn test_dashboard_widget_config() {
+        let parsed = widgets(json!([
+            machines_widget("a", "Building A", 0),
+            {
+                "id": "late",
+                "position": { "x": 6, "y": 0, "w": 6, "h": 4 },
+                "kind": "report",
+                "params": { "report": "open_order_book", "limit": 5 }
+            }
+        ]));
+        assert_eq!(
+            parsed[0].source,
+            WidgetSource::Machines(MachineWidgetParams {
+                site: Some("Building A".to_string()),
+                status: None,
+                limit: None,
+            })
+        );
+        assert_eq!(parsed[1].source.kind(), "report");
+        assert_eq!(parsed[1].source.limit(), Some(5));
+
+        // Stored widgets keep the same shape the client sent
+        let stored = serde_json::to_value(&parsed[0]).unwrap();
+        assert_eq!(stored["kind"], "machines");
+        assert_eq!(stored["params"]["site"], "Building A");
+        assert!(serde_json::from_value::<DashboardWidget>(json!({
+            "id": "x", "position": { "x": 0, "y": 0, "w": 1, "h": 1 }, "kind": "weather", "params": {}
+        }))
+        .is_err());
+
+        let layout = DashboardLayout::default();
+        assert!(check_dashboard(&layout, &parsed, ReportService::definition).is_ok());
+
+        let duplicate = widgets(json!([
+            machines_widget("a", "Building A", 0),
+            machines_widget("a", "Building B", 6)
+        ]));
+        assert!(
+            check_dashboard(&layout, &duplicate, ReportService::definition)
+                .unwrap_err()
+                .contains("Duplicate widget id")
+        );
+
+        let overflowing = widgets(json!([machines_widget("a", "Building A", 8)]));
+        assert!(check_dashboard(&layout, &overflowing, ReportService::definition).is_err());
+        assert!(check_dashboard(
+            &DashboardLayout { columns: 0 },
+            &[],
+            ReportService::definition
+        )
+        .is_err());
+
+        let bad_report = widgets(json!([{
+            "id": "margin",
+            "position": { "x": 0, "y": 0, "w": 4, "h": 2 },
+            "kind": "report",
+            "params": { "report": "job_margin", "parameters": { "from": "yesterday" } }
+        }]));
+        assert!(check_dashboard(&layout, &bad_report, ReportService::definition).is_err());
+
+        let too_many_rows = widgets(json!([{
+            "id": "orders",
+            "position": { "x": 0, "y": 0, "w": 4, "h": 2 },
+            "kind": "orders",
+            "params": { "limit": 1000 }
+        }]));
+        assert!(check_dashboard(&layout, &too_many_rows, ReportService::definition).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_routes_require_auth() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(format!("/{}/data", Uuid::new_v4()))
+            .header("X-Tenant-ID", Uuid::new_v4().to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // Dashboards belong to the signed-in user, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dashboards() {
+        use ems_server::services::{MachineService, PersonService, TenantService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "dashboards", "subdomain": format!("dashboards-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let people = PersonService::new(database.clone());
+        let person = |name: &str| {
+            let request = serde_json::from_value(json!({
+                "name": name,
+                "email": format!("{}-{}@example.com", name, suffix),
+                "role": "internal",
+                "person_type": "internal"
+            }))
+            .unwrap();
+            let people = &people;
+            async move { people.create_person(tenant_id, request).await.unwrap().id }
+        };
+        let owner_id = person("planner").await;
+        let other_id = person("operator").await;
+
+        let machines = MachineService::new(database.clone());
+        for (name, site) in [("Press", "Building A"), ("Lathe", "Building B")] {
+            machines
+                .create_machine(
+                    tenant_id,
+                    serde_json::from_value(json!({
+                        "name": name, "ip": "10.0.0.30", "port": 502, "protocol": "tcp", "site": site
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let dashboards = DashboardService::new(database.clone());
+        let create = |name: &str, is_default: Option<bool>| {
+            let request: CreateDashboardRequest = serde_json::from_value(json!({
+                "name": name,
+                "widgets": [machines_widget("a", "Building A", 0)],
+                "is_default": is_default
+            }))
+            .unwrap();
+            let dashboards = &dashboards;
+            async move {
+                dashboards
+                    .create_dashboard(tenant_id, owner_id, request)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // A user's first dashboard becomes their default; a new default replaces it
+        let floor = create("Floor", None).await;
+        assert!(floor.is_default);
+        assert_eq!(floor.layout, DashboardLayout::default());
+        let office = create("Office", None).await;
+        assert!(!office.is_default);
+        let quality = create("Quality", Some(true)).await;
+        assert!(quality.is_default);
+
+        let listed = dashboards
+            .list_dashboards(tenant_id, owner_id)
+            .await
+            .unwrap();
+        let names: Vec<_> = listed.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["Quality", "Floor", "Office"]);
+        assert_eq!(
+            dashboards
+                .get_default_dashboard(tenant_id, owner_id)
+                .await
+                .unwrap()
+                .unwrap()
+                .id,
+            quality.id
+        );
+
+        // Dashboards are private to their owner
+        assert!(dashboards
+            .list_dashboards(tenant_id, other_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(dashboards
+            .get_dashboard(tenant_id, other_id, floor.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(!dashboards
+            .delete_dashboard(tenant_id, other_id, floor.id)
+            .await
+            .unwrap());
+
+        // Data for every widget comes back in one call
+        let dashboard = dashboards
+            .get_dashboard(tenant_id, owner_id, floor.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let data = dashboards
+            .load_widget_data(tenant_id, &dashboard)
+            .await
+            .unwrap();
+        assert_eq!(data.widgets.len(), 1);
+        assert_eq!(data.widgets[0].kind, "machines");
+        let shown = data.widgets[0].data.as_ref().unwrap().as_array().unwrap();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0]["name"], "Press");
+
+        let update: UpdateDashboardRequest = serde_json::from_value(json!({
+            "name": "Shop floor",
+            "layout": { "columns": 12 },
+            "widgets": [
+                machines_widget("a", "Building B", 0),
+                {
+                    "id": "orders",
+                    "position": { "x": 6, "y": 0, "w": 6, "h": 4 },
+                    "kind": "orders",
+                    "params": { "status": "draft", "limit": 3 }
+                }
+            ],
+            "is_default": true
+        }))
+        .unwrap();
+        assert!(update.check(&dashboard, ReportService::definition).is_ok());
+        let updated = dashboards
+            .update_dashboard(tenant_id, dashboard, update)
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Shop floor");
+        assert_eq!(updated.widgets.len(), 2);
+        assert!(updated.is_default);
+        assert!(
+            !dashboards
+                .get_dashboard(tenant_id, owner_id, quality.id)
+                .await
+                .unwrap()
+                .unwrap()
+                .is_default
+        );
+
+        // Narrowing the grid is refused while widgets would no longer fit
+        let dashboard = dashboards
+            .get_dashboard(tenant_id, owner_id, floor.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let narrower: UpdateDashboardRequest =
+            serde_json::from_value(json!({ "layout": { "columns": 8 } })).unwrap();
+        assert!(narrower
+            .check(&dashboard, ReportService::definition)
+            .is_err());
+
+        let data = dashboards
+            .load_widget_data(tenant_id, &dashboard)
+            .await
+            .unwrap();
+        let ids: Vec<_> = data.widgets.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "orders"]);
+        assert!(data.widgets.iter().all(|w| w.error.is_none()));
+        let shown = data.widgets[0].data.as_ref().unwrap().as_array().unwrap();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0]["site"], "Building B");
+
+        assert!(dashboards
+            .delete_dashboard(tenant_id, owner_id, office.id)
+            .await
+            .unwrap());
+        assert!(dashboards
+            .get_dashboard(tenant_id, owner_id, office.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
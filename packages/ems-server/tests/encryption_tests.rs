@@ -0,0 +1,206 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    use ems_server::services::{EncryptionService, KeyWrapper, LocalKeyWrapper};
+    use ems_server::utils::encryption::{
+        has_secret_settings, is_sealed, is_secret_setting, sealed_version, strip_secret_settings,
+        DataKey, FieldCipher,
+    };
+
+    fn cipher(scope: Option<Uuid>, versions: &[i32]) -> FieldCipher {
+        let keys: HashMap<_, _> = versions
+            .iter()
+            .map(|version| (*version, DataKey::generate().unwrap()))
+            .collect();
+        FieldCipher::new(scope, *versions.iter().max().unwrap(), keys)
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let cipher = cipher(Some(Uuid::new_v4()), &[1]);
+
+        let sealed = cipher.seal("+44 20 7946 0000").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(sealed_version(&sealed), Some(1));
+        assert!(!sealed.contains("7946"));
+        assert_eq!(cipher.open(&sealed).unwrap(), "+44 20 7946 0000");
+
+        // Sealing twice gives different ciphertexts, and a sealed value is not sealed again
+        assert_ne!(cipher.seal("+44 20 7946 0000").unwrap(), sealed);
+        assert_eq!(cipher.seal(&sealed).unwrap(), sealed);
+
+        // Values stored before encryption was enabled are read as they are
+        assert_eq!(cipher.open("555-0100").unwrap(), "555-0100");
+    }
+
+    #[test]
+    fn test_sealed_values_are_bound_to_their_tenant() {
+        let tenant = cipher(Some(Uuid::new_v4()), &[1]);
+        let sealed = tenant.seal("secret").unwrap();
+
+        // Another tenant's or the platform's keys do not open it
+        assert!(cipher(Some(Uuid::new_v4()), &[1]).open(&sealed).is_err());
+        assert!(cipher(None, &[1]).open(&sealed).is_err());
+
+        // A changed ciphertext is rejected
+        let mut tampered = sealed.clone();
+        tampered.pop();
+        tampered.push(if sealed.ends_with('A') { 'B' } else { 'A' });
+        assert!(tenant.open(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_disabled_cipher_passes_values_through() {
+        let cipher = FieldCipher::disabled(None);
+        assert!(!cipher.enabled());
+        assert_eq!(cipher.seal("555-0100").unwrap(), "555-0100");
+        assert_eq!(cipher.open("555-0100").unwrap(), "555-0100");
+        assert_eq!(
+            cipher.seal_opt(Some("555-0100".to_string())).unwrap(),
+            Some("555-0100".to_string())
+        );
+
+        // Without the keys an encrypted value cannot be read
+        let sealed = self::cipher(None, &[1]).seal("555-0100").unwrap();
+        assert!(cipher.open(&sealed).is_err());
+    }
+
+    #[test]
+    fn test_reseal_moves_values_to_the_active_key() {
+        let scope = Some(Uuid::new_v4());
+        let old_key = DataKey::generate().unwrap();
+        let old = FieldCipher::new(scope, 1, [(1, old_key.clone())].into());
+        let sealed = old.seal("secret").unwrap();
+
+        let rotated = FieldCipher::new(
+            scope,
+            2,
+            [(1, old_key), (2, DataKey::generate().unwrap())].into(),
+        );
+        assert_eq!(rotated.open(&sealed).unwrap(), "secret");
+
+        let resealed = rotated.reseal(&sealed).unwrap();
+        assert_eq!(sealed_version(&resealed), Some(2));
+        assert_eq!(rotated.open(&resealed).unwrap(), "secret");
+        // Already on the active key, or stored in plain text
+        assert_eq!(rotated.reseal(&resealed).unwrap(), resealed);
+        assert_eq!(sealed_version(&rotated.reseal("plain").unwrap()), Some(2));
+    }
+
+    #[test]
+    fn test_secret_settings() {
+        assert!(is_secret_setting("client_secret"));
+        assert!(is_secret_setting("api_key"));
+        assert!(is_secret_setting("smtp_password"));
+        assert!(is_secret_setting("token"));
+        assert!(!is_secret_setting("tokens_per_minute"));
+        assert!(!is_secret_setting("archive_after_days"));
+
+        let settings = json!({
+            "locale": "en",
+            "oidc": { "client_id": "ems", "client_secret": "s3cret" },
+            "integrations": [{ "name": "erp", "api_key": "k-123" }],
+            "token": 42
+        });
+        assert!(has_secret_settings(&settings));
+        assert!(!has_secret_settings(
+            &json!({ "locale": "en", "token": 42 })
+        ));
+
+        let cipher = cipher(Some(Uuid::new_v4()), &[1]);
+        let sealed = cipher.seal_settings(&settings).unwrap();
+        assert_eq!(sealed["locale"], "en");
+        assert_eq!(sealed["oidc"]["client_id"], "ems");
+        assert_eq!(sealed["token"], 42);
+        assert!(is_sealed(sealed["oidc"]["client_secret"].as_str().unwrap()));
+        assert!(is_sealed(
+            sealed["integrations"][0]["api_key"].as_str().unwrap()
+        ));
+        assert_eq!(cipher.open_settings(&sealed).unwrap(), settings);
+
+        assert_eq!(
+            strip_secret_settings(&settings),
+            json!({
+                "locale": "en",
+                "oidc": { "client_id": "ems" },
+                "integrations": [{ "name": "erp" }],
+                "token": 42
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_key_wrapper() {
+        let key = DataKey::generate().unwrap();
+        let old_master = DataKey::generate().unwrap();
+        let wrapped_by_old = LocalKeyWrapper::new("2025", old_master.clone())
+            .wrap(&key)
+            .await
+            .unwrap();
+
+        // Data keys wrapped with a retired master key need it listed as a previous key
+        let current = LocalKeyWrapper::new("2026", DataKey::generate().unwrap());
+        assert!(current.unwrap("2025", &wrapped_by_old).await.is_err());
+        let current = current.with_previous("2025", old_master);
+        assert_eq!(current.key_id(), "2026");
+        assert_eq!(current.unwrap("2025", &wrapped_by_old).await.unwrap(), key);
+
+        let wrapped = current.wrap(&key).await.unwrap();
+        assert_eq!(current.unwrap("2026", &wrapped).await.unwrap(), key);
+        // Wrapped keys are bound to the master key id they were wrapped under
+        assert!(current.unwrap("2025", &wrapped).await.is_err());
+        assert_eq!(format!("{:?}", key), "DataKey(..)");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_reencrypts_tenant_secrets() {
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::services::{DatabaseService, TenantService};
+
+        dotenv::dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let settings = json!({ "oidc": { "client_id": "ems", "client_secret": "s3cret" } });
+        let fixture = FixtureBuilder::tenant()
+            .named("Encryption test")
+            .with_settings(settings.clone())
+            .build(&database)
+            .await
+            .unwrap();
+        let tenant_id = fixture.tenant.id;
+
+        let wrapper: Arc<dyn KeyWrapper> =
+            Arc::new(LocalKeyWrapper::new("test", DataKey::generate().unwrap()));
+        let encryption = EncryptionService::with_wrapper(database.clone(), Some(wrapper));
+
+        let first = encryption.rotate(Some(tenant_id)).await.unwrap();
+        let second = encryption.rotate(Some(tenant_id)).await.unwrap();
+        assert_eq!(second.version, first.version + 1);
+        assert_eq!(second.reencrypted, 1);
+
+        let stored = TenantService::new(database.clone())
+            .get_tenant_by_id(tenant_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .settings
+            .unwrap();
+        let secret = stored["oidc"]["client_secret"].as_str().unwrap();
+        assert_eq!(sealed_version(secret), Some(second.version));
+
+        let cipher = encryption.cipher(Some(tenant_id)).await.unwrap();
+        assert_eq!(cipher.active_version(), Some(second.version));
+        assert!(cipher.has_version(first.version));
+        assert_eq!(cipher.open_settings(&stored).unwrap(), settings);
+
+        TenantService::new(database.clone())
+            .delete_tenant(tenant_id)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware,
+        response::{IntoResponse, Response},
+        routing::get,
+        Json, Router,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use serde_json::json;
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::middleware::etag::{
+        body_etag, etag_matches, etag_middleware, version_etag, with_etag, CachePolicy, Revalidate,
+    };
+    use ems_server::models::DataVersion;
+
+    fn updated_at() -> DateTime<Utc> {
+        "2026-01-01T00:00:00Z".parse().unwrap()
+    }
+
+    fn list_version() -> DataVersion {
+        DataVersion::new(2, &[Some(updated_at()), None])
+    }
+
+    async fn versioned(revalidate: Revalidate) -> Response {
+        let etag = revalidate.etag(Uuid::nil(), &list_version());
+        if let Some(not_modified) = revalidate.not_modified(&etag) {
+            return not_modified;
+        }
+        with_etag(&etag, Json(json!([{ "id": 1 }, { "id": 2 }])))
+    }
+
+    fn app(policy: CachePolicy) -> Router {
+        Router::new()
+            .route(
+                "/items",
+                get(|| async { Json(json!([{ "id": 1, "updated_at": "2026-01-01" }])) })
+                    .post(|| async { Json(json!({ "id": 2 })) }),
+            )
+            .route(
+                "/missing",
+                get(|| async { StatusCode::NOT_FOUND.into_response() }),
+            )
+            .nest("/api", Router::new().route("/versioned", get(versioned)))
+            .route(
+                "/custom",
+                get(|| async { ([(header::CACHE_CONTROL, "no-store")], "custom body") }),
+            )
+            .layer(middleware::from_fn_with_state(policy, etag_middleware))
+    }
+
+    fn request(method: Method, uri: &str, if_none_match: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some(etag) = if_none_match {
+            builder = builder.header(header::IF_NONE_MATCH, etag);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    fn header_value(response: &axum::response::Response, name: header::HeaderName) -> String {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_cache_policy() {
+        assert_eq!(
+            CachePolicy::revalidate().cache_control(),
+            "private, no-cache"
+        );
+        assert_eq!(
+            CachePolicy::max_age(30).cache_control(),
+            "private, max-age=30, must-revalidate"
+        );
+        assert_eq!(CachePolicy::max_age(0), CachePolicy::revalidate());
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = body_etag(b"hello");
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 34);
+        assert_eq!(etag, body_etag(b"hello"));
+        assert_ne!(etag, body_etag(b"hello!"));
+
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("W/{}", etag), &etag));
+        assert!(etag_matches(&format!("\"other\", {}", etag), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"other\"", &etag));
+        assert!(!etag_matches("", &etag));
+    }
+
+    #[tokio::test]
+    async fn test_get_is_tagged_and_revalidated() {
+        let response = app(CachePolicy::max_age(30))
+            .oneshot(request(Method::GET, "/items", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = header_value(&response, header::ETAG);
+        assert_eq!(
+            header_value(&response, header::CACHE_CONTROL),
+            "private, max-age=30, must-revalidate"
+        );
+        assert!(header_value(&response, header::VARY).contains("X-Tenant-ID"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(etag, body_etag(&body));
+        assert!(!body.is_empty());
+
+        // The same tag comes back as 304 with no body but the caching headers
+        let response = app(CachePolicy::max_age(30))
+            .oneshot(request(Method::GET, "/items", Some(&etag)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(header_value(&response, header::ETAG), etag);
+        assert!(!header_value(&response, header::CACHE_CONTROL).is_empty());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        // A stale tag gets the full body again
+        let response = app(CachePolicy::max_age(30))
+            .oneshot(request(Method::GET, "/items", Some("\"stale\"")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, header::ETAG), etag);
+    }
+
+    #[tokio::test]
+    async fn test_only_successful_gets_are_tagged() {
+        let response = app(CachePolicy::revalidate())
+            .oneshot(request(Method::POST, "/items", Some("*")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+        let response = app(CachePolicy::revalidate())
+            .oneshot(request(Method::GET, "/missing", Some("*")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(header::ETAG).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handler_cache_control_is_kept() {
+        let response = app(CachePolicy::max_age(30))
+            .oneshot(request(Method::GET, "/custom", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, header::CACHE_CONTROL), "no-store");
+        assert_eq!(
+            header_value(&response, header::ETAG),
+            body_etag(b"custom body")
+        );
+    }
+
+    #[test]
+    fn test_data_version() {
+        let later = updated_at() + Duration::seconds(1);
+        let version = DataVersion::new(3, &[Some(updated_at()), None, Some(later)]);
+        assert_eq!(version.rows, 3);
+        assert_eq!(version.updated_at, Some(later));
+        assert_eq!(DataVersion::new(0, &[None]).updated_at, None);
+
+        let lines = DataVersion::new(4, &[Some(updated_at())]);
+        assert_eq!(
+            version.and(lines),
+            DataVersion {
+                rows: 7,
+                updated_at: Some(later)
+            }
+        );
+    }
+
+    #[test]
+    fn test_version_etag() {
+        let tenant_id = Uuid::new_v4();
+        let etag = version_etag("/item?limit=10", tenant_id, &list_version());
+        assert!(etag.starts_with("W/\"") && etag.ends_with('"'));
+        assert_eq!(
+            etag,
+            version_etag("/item?limit=10", tenant_id, &list_version())
+        );
+
+        // A changed row, a removed row, another page or another tenant gets a new tag
+        let changed = DataVersion::new(2, &[Some(updated_at() + Duration::milliseconds(1))]);
+        assert_ne!(etag, version_etag("/item?limit=10", tenant_id, &changed));
+        let removed = DataVersion::new(1, &[Some(updated_at())]);
+        assert_ne!(etag, version_etag("/item?limit=10", tenant_id, &removed));
+        assert_ne!(
+            etag,
+            version_etag("/item?limit=10&offset=10", tenant_id, &list_version())
+        );
+        assert_ne!(
+            etag,
+            version_etag("/item?limit=10", Uuid::new_v4(), &list_version())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_tags_from_data_version() {
+        let response = app(CachePolicy::revalidate())
+            .oneshot(request(Method::GET, "/api/versioned?limit=2", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = header_value(&response, header::ETAG);
+        // Scoped by the full path, not the one the nested router sees
+        assert_eq!(
+            etag,
+            version_etag("/api/versioned?limit=2", Uuid::nil(), &list_version())
+        );
+        assert_eq!(
+            header_value(&response, header::CACHE_CONTROL),
+            "private, no-cache"
+        );
+
+        // The handler answers 304 itself, and the middleware still adds the caching headers
+        let response = app(CachePolicy::revalidate())
+            .oneshot(request(Method::GET, "/api/versioned?limit=2", Some(&etag)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(header_value(&response, header::ETAG), etag);
+        assert_eq!(
+            header_value(&response, header::CACHE_CONTROL),
+            "private, no-cache"
+        );
+        assert!(header_value(&response, header::VARY).contains("X-Tenant-ID"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware::from_fn_with_state,
+        Router,
+    };
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        middleware::auth::{auth_middleware, platform_admin_middleware},
+        models::{FeatureFlag, FlagDecision},
+        routes::admin::routes,
+        services::{DatabaseService, FeatureFlagService, FeatureFlags, TenantService},
+        utils::feature_flag::{evaluate_flag, rollout_bucket},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        // Flags are managed through the platform admin API
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes()
+            .layer(from_fn_with_state(state.clone(), platform_admin_middleware))
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+    }
+
+    async fn database() -> DatabaseService {
+        dotenv().ok();
+        DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.")
+    }
+
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    fn flag(enabled: bool, rollout_percentage: i32) -> FeatureFlag {
+        FeatureFlag {
+            id: Uuid::new_v4(),
+            key: "mqtt_bridge".to_string(),
+            description: None,
+            enabled,
+            rollout_percentage,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    // Feature flag API tests
+
+    #[tokio::test]
+    async fn test_list_flags() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/flags", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Admin routes require authentication, will fail without JWT token
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_set_flag_override() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/flags/mqtt_bridge/tenants/{}", tenant_id),
+            Some(json!({ "enabled": true })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Flag evaluation tests
+
+    #[test]
+    fn test_rollout_bucket() {
+        let tenants: Vec<Uuid> = (0..2000).map(|_| Uuid::new_v4()).collect();
+
+        // Buckets are stable and differ between flags
+        let tenant = tenants[0];
+        assert_eq!(
+            rollout_bucket("mqtt_bridge", tenant),
+            rollout_bucket("mqtt_bridge", tenant)
+        );
+        assert!(tenants
+            .iter()
+            .any(|t| rollout_bucket("mqtt_bridge", *t) != rollout_bucket("new_planner", *t)));
+
+        // Roughly the given share of tenants falls inside a rollout
+        let inside = tenants
+            .iter()
+            .filter(|t| rollout_bucket("mqtt_bridge", **t) < 25)
+            .count();
+        assert!(
+            (350..650).contains(&inside),
+            "{} of 2000 inside 25%",
+            inside
+        );
+        assert!(tenants
+            .iter()
+            .all(|t| rollout_bucket("mqtt_bridge", *t) < 100));
+    }
+
+    #[test]
+    fn test_evaluate_flag() {
+        let tenant = Uuid::new_v4();
+
+        assert_eq!(
+            evaluate_flag(None, Some(true), tenant),
+            (false, FlagDecision::Unknown)
+        );
+        // The kill switch wins over overrides
+        assert_eq!(
+            evaluate_flag(Some(&flag(false, 100)), Some(true), tenant),
+            (false, FlagDecision::Disabled)
+        );
+        assert_eq!(
+            evaluate_flag(Some(&flag(true, 0)), Some(true), tenant),
+            (true, FlagDecision::Override)
+        );
+        assert_eq!(
+            evaluate_flag(Some(&flag(true, 100)), Some(false), tenant),
+            (false, FlagDecision::Override)
+        );
+        assert_eq!(
+            evaluate_flag(Some(&flag(true, 0)), None, tenant),
+            (false, FlagDecision::Rollout)
+        );
+        assert_eq!(
+            evaluate_flag(Some(&flag(true, 100)), None, tenant),
+            (true, FlagDecision::Rollout)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_feature_flag_workflow() {
+        let database = database().await;
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let key = format!("test_flag_{}", suffix);
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Flagged", "subdomain": format!("flag-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let other_tenant = Uuid::new_v4();
+
+        // A long TTL shows changes only apply at once through invalidation
+        let flags = FeatureFlags::new(database.clone(), Duration::from_secs(3600));
+        let service = FeatureFlagService::new(database.clone()).with_flags(flags.clone());
+        assert!(!flags.enabled(&key, tenant_id).await);
+
+        // New flags ship dark
+        let created = service
+            .create_flag(
+                serde_json::from_value(json!({ "key": key, "description": "Test flag" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(created.flag.enabled);
+        assert_eq!(created.flag.rollout_percentage, 0);
+        let evaluation = flags.evaluate(&key, tenant_id).await;
+        assert!(!evaluation.enabled);
+        assert_eq!(evaluation.decision, FlagDecision::Rollout);
+        assert!(service
+            .create_flag(serde_json::from_value(json!({ "key": key })).unwrap())
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("already exists"));
+
+        // A pilot tenant gets the feature through an override
+        let with_override = service
+            .set_override(
+                &key,
+                tenant_id,
+                serde_json::from_value(json!({ "enabled": true })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(with_override.overrides.len(), 1);
+        assert!(flags.enabled(&key, tenant_id).await);
+        assert!(!flags.enabled(&key, other_tenant).await);
+        let err = service
+            .set_override(
+                &key,
+                other_tenant,
+                serde_json::from_value(json!({ "enabled": true })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid feature flag override"));
+
+        // The kill switch turns it off everywhere, then a full rollout reaches everyone
+        service
+            .update_flag(
+                &key,
+                serde_json::from_value(json!({ "enabled": false })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            flags.evaluate(&key, tenant_id).await.decision,
+            FlagDecision::Disabled
+        );
+        let updated = service
+            .update_flag(
+                &key,
+                serde_json::from_value(json!({ "enabled": true, "rollout_percentage": 100 }))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.flag.description.as_deref(), Some("Test flag"));
+        assert!(flags.enabled(&key, other_tenant).await);
+
+        // Removing the override puts the tenant back on the rollout
+        assert!(service.remove_override(&key, tenant_id).await.unwrap());
+        assert!(!service.remove_override(&key, tenant_id).await.unwrap());
+        assert_eq!(
+            flags.evaluate(&key, tenant_id).await.decision,
+            FlagDecision::Rollout
+        );
+
+        let listed = service.list_flags().await.unwrap();
+        assert!(listed.iter().any(|f| f.flag.key == key));
+
+        // Instances that made no change keep their cached flags until the TTL passes
+        let elsewhere = FeatureFlags::new(database.clone(), Duration::from_secs(3600));
+        assert!(elsewhere.enabled(&key, tenant_id).await);
+        assert!(service.delete_flag(&key).await.unwrap());
+        assert!(elsewhere.enabled(&key, tenant_id).await);
+        elsewhere.invalidate();
+        assert!(!elsewhere.enabled(&key, tenant_id).await);
+        assert_eq!(
+            flags.evaluate(&key, tenant_id).await.decision,
+            FlagDecision::Unknown
+        );
+        assert!(service.get_flag(&key).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::{header::USER_AGENT, HeaderMap, HeaderValue};
+    use std::net::SocketAddr;
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{ClientFingerprint, FingerprintMode},
+        utils::{
+            fingerprint::{ClientInfo, FingerprintMatch},
+            AuthUtils,
+        },
+    };
+
+    const TABLET: &str = "Mozilla/5.0 (Linux; Android 13; SM-X200) EMS-Kiosk/2.4";
+    const LAPTOP: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:131.0) Gecko/20100101 Firefox/131.0";
+
+    fn client(user_agent: &str, ip: &str) -> ClientInfo {
+        ClientInfo {
+            user_agent: Some(user_agent.to_string()),
+            ip: Some(ip.parse().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_client_info_from_headers() {
+        let peer: SocketAddr = "10.0.0.5:51234".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(USER_AGENT, HeaderValue::from_static(TABLET));
+
+        let info = ClientInfo::from_parts(&headers, Some(peer));
+        assert_eq!(info.user_agent.as_deref(), Some(TABLET));
+        assert_eq!(info.ip, Some(peer.ip()));
+
+        // The proxy in front of the server appends the address it saw
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("192.0.2.1, 203.0.113.77"),
+        );
+        let info = ClientInfo::from_parts(&headers, Some(peer));
+        assert_eq!(info.ip, Some("203.0.113.77".parse().unwrap()));
+
+        assert_eq!(
+            ClientInfo::from_parts(&HeaderMap::new(), None),
+            ClientInfo::default()
+        );
+    }
+
+    #[test]
+    fn test_network_prefix() {
+        assert_eq!(
+            client(TABLET, "203.0.113.77").network_prefix().as_deref(),
+            Some("203.0.113.0/24")
+        );
+        assert_eq!(
+            client(TABLET, "2001:db8:85a3:8d3:1319:8a2e:370:7348")
+                .network_prefix()
+                .as_deref(),
+            Some("2001:db8:85a3::/48")
+        );
+        assert_eq!(
+            client(TABLET, "::ffff:203.0.113.77")
+                .network_prefix()
+                .as_deref(),
+            Some("203.0.113.0/24")
+        );
+        assert_eq!(ClientInfo::default().network_prefix(), None);
+    }
+
+    #[test]
+    fn test_fingerprint_comparison() {
+        let secret = "test-secret";
+        let issued = client(TABLET, "203.0.113.77").fingerprint(secret);
+
+        // Another address on the same network is the same client
+        let same = client(TABLET, "203.0.113.12").fingerprint(secret);
+        assert_eq!(
+            FingerprintMatch::compare(&issued, &same),
+            FingerprintMatch::Same
+        );
+
+        let moved = client(TABLET, "198.51.100.4").fingerprint(secret);
+        assert_eq!(
+            FingerprintMatch::compare(&issued, &moved),
+            FingerprintMatch::Partial
+        );
+        assert_eq!(
+            FingerprintMatch::changed_parts(&issued, &moved),
+            ["network"]
+        );
+
+        let other = client(LAPTOP, "198.51.100.4").fingerprint(secret);
+        assert_eq!(
+            FingerprintMatch::compare(&issued, &other),
+            FingerprintMatch::Different
+        );
+        assert_eq!(
+            FingerprintMatch::changed_parts(&issued, &other),
+            ["user_agent", "network"]
+        );
+
+        // Parts only one side knows are not compared
+        let no_agent = ClientInfo {
+            user_agent: None,
+            ip: Some("198.51.100.4".parse().unwrap()),
+        }
+        .fingerprint(secret);
+        assert_eq!(
+            FingerprintMatch::compare(&issued, &no_agent),
+            FingerprintMatch::Partial
+        );
+        assert_eq!(
+            FingerprintMatch::compare(&ClientFingerprint::default(), &other),
+            FingerprintMatch::Unknown
+        );
+
+        // Hashes are keyed, so they do not carry over between secrets
+        let rekeyed = client(TABLET, "203.0.113.77").fingerprint("other-secret");
+        assert_ne!(issued, rekeyed);
+        assert!(!issued.ua.as_ref().unwrap().contains("Android"));
+    }
+
+    #[test]
+    fn test_fingerprint_modes() {
+        use FingerprintMatch::*;
+
+        for result in [Same, Partial, Different, Unknown] {
+            assert!(!FingerprintMode::Off.rejects(result));
+            assert!(!FingerprintMode::Audit.rejects(result));
+        }
+        assert!(!FingerprintMode::Lenient.rejects(Partial));
+        assert!(FingerprintMode::Lenient.rejects(Different));
+        assert!(FingerprintMode::Strict.rejects(Partial));
+        assert!(FingerprintMode::Strict.rejects(Different));
+        assert!(!FingerprintMode::Strict.rejects(Same));
+        assert!(!FingerprintMode::Strict.rejects(Unknown));
+
+        assert_eq!(
+            FingerprintMode::try_from("lenient".to_string()).unwrap(),
+            FingerprintMode::Lenient
+        );
+        assert_eq!(String::from(FingerprintMode::Strict), "strict");
+        assert!(FingerprintMode::try_from("paranoid".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_refresh_token_carries_fingerprint() {
+        let fingerprint = AuthUtils::client_fingerprint(&client(TABLET, "203.0.113.77"));
+        let token = AuthUtils::generate_refresh_token(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Some(fingerprint.clone()),
+        )
+        .unwrap();
+        let claims = AuthUtils::verify_jwt_token(&token).unwrap();
+        assert_eq!(claims.role, "refresh");
+        assert_eq!(claims.fp, Some(fingerprint));
+
+        // Tokens issued without one, and access tokens, have no fingerprint claim
+        let token =
+            AuthUtils::generate_refresh_token(Uuid::new_v4(), Uuid::new_v4(), None).unwrap();
+        assert_eq!(AuthUtils::verify_jwt_token(&token).unwrap().fp, None);
+    }
+}
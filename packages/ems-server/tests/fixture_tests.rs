@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use dotenv::dotenv;
+    use validator::Validate;
+
+    use ems_server::{fixtures::FixtureBuilder, services::DatabaseService};
+
+    #[test]
+    fn test_fixture_requests() {
+        // Records are derived from their index alone
+        let machine = FixtureBuilder::machine_request(250);
+        assert_eq!(machine.name, "Machine 251");
+        assert_eq!(machine.ip, "10.0.1.1");
+        assert_eq!(machine.site.as_deref(), Some("Line 2"));
+        let builder = FixtureBuilder::tenant().with_key("ABC123");
+        assert_eq!(
+            serde_json::to_value(builder.item_request(7)).unwrap(),
+            serde_json::to_value(builder.item_request(7)).unwrap()
+        );
+        assert_eq!(
+            builder.item_request(99).internal_part_number,
+            Some("FX-ABC123-00100".to_string())
+        );
+
+        // Part numbers are unique across tenants, so builds with other keys get their own
+        assert_ne!(
+            FixtureBuilder::tenant()
+                .item_request(0)
+                .internal_part_number,
+            FixtureBuilder::tenant()
+                .item_request(0)
+                .internal_part_number
+        );
+
+        // Every request passes the API's validation
+        for index in [0, 1, 249, 250, 999] {
+            assert!(FixtureBuilder::machine_request(index).validate().is_ok());
+        }
+        for index in [0, 1, 49, 9999] {
+            assert!(builder.item_request(index).validate().is_ok());
+        }
+
+        let tenant = builder.tenant_request();
+        assert_eq!(tenant.subdomain, "fixture-abc123");
+        assert!(tenant.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_builder() {
+        use ems_server::models::ItemContext;
+        use ems_server::services::{ItemService, MachineService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+
+        let fixture = FixtureBuilder::tenant()
+            .with_machines(3)
+            .with_items(5)
+            .build(&database)
+            .await
+            .unwrap();
+        assert_eq!(fixture.machine_ids.len(), 3);
+        assert_eq!(fixture.item_ids.len(), 5);
+
+        let machine = MachineService::new(database.clone())
+            .get_machine_by_id(fixture.tenant.id, fixture.machine_ids[2])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(machine.name, "Machine 003");
+        let item = ItemService::new(database.clone())
+            .get_item_by_id(fixture.tenant.id, fixture.item_ids[0], ItemContext::Store)
+            .await
+            .unwrap()
+            .unwrap();
+        let key = fixture.tenant.subdomain.trim_start_matches("fixture-");
+        assert_eq!(
+            item.internal_part_number,
+            format!("FX-{}-00001", key.to_uppercase())
+        );
+
+        // A second build holds the same data in a tenant of its own
+        let other = FixtureBuilder::tenant()
+            .with_items(1)
+            .build(&database)
+            .await
+            .unwrap();
+        assert_ne!(other.tenant.id, fixture.tenant.id);
+        assert_ne!(other.tenant.subdomain, fixture.tenant.subdomain);
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::routes::frontend::{
+        self, cache_control_for, is_client_route, FrontendConfig, HASHED_ASSET_CACHE_CONTROL,
+        INDEX_CACHE_CONTROL, STATIC_FILE_CACHE_CONTROL,
+    };
+
+    const INDEX: &str = "<!doctype html><div id=\"root\"></div>";
+
+    /// A built frontend in a fresh temporary directory
+    fn static_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ems-frontend-{}", Uuid::new_v4()));
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        fs::write(dir.join("index.html"), INDEX).unwrap();
+        fs::write(dir.join("assets/index-3f2a9c.js"), "console.log(1)").unwrap();
+        fs::write(dir.join("robots.txt"), "User-agent: *").unwrap();
+        dir
+    }
+
+    fn app(dir: &PathBuf) -> Router {
+        Router::new().fallback_service(frontend::routes(FrontendConfig::new(dir)))
+    }
+
+    async fn get(app: Router, uri: &str, accept: Option<&str>) -> (StatusCode, String, String) {
+        let mut request = Request::builder().method(Method::GET).uri(uri);
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let cache_control = response
+            .headers()
+            .get(header::CACHE_CONTROL)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            cache_control,
+            String::from_utf8_lossy(&body).to_string(),
+        )
+    }
+
+    #[test]
+    fn test_fallback_rules() {
+        let config = FrontendConfig::new("static");
+        assert!(config.is_excluded("/api"));
+        assert!(config.is_excluded("/api/v1/unknown"));
+        assert!(config.is_excluded("/health/other"));
+        assert!(!config.is_excluded("/apiary"));
+        assert!(!config.is_excluded("/orders/123"));
+
+        assert!(is_client_route("/orders/123", false));
+        assert!(is_client_route("/", false));
+        assert!(!is_client_route("/logo.png", false));
+        assert!(is_client_route("/items/IPN-1.2", true));
+        assert!(!is_client_route("/assets/missing.js", true));
+
+        assert_eq!(cache_control_for("/", true), INDEX_CACHE_CONTROL);
+        assert_eq!(
+            cache_control_for("/assets/index-3f2a9c.js", false),
+            HASHED_ASSET_CACHE_CONTROL
+        );
+        assert_eq!(
+            cache_control_for("/robots.txt", false),
+            STATIC_FILE_CACHE_CONTROL
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deep_links_serve_index() {
+        let dir = static_dir();
+
+        for uri in ["/", "/orders/123", "/machines/abc/telemetry"] {
+            let (status, cache_control, body) = get(app(&dir), uri, None).await;
+            assert_eq!(status, StatusCode::OK, "{}", uri);
+            assert_eq!(cache_control, INDEX_CACHE_CONTROL, "{}", uri);
+            assert_eq!(body, INDEX, "{}", uri);
+        }
+
+        // Ids with dots still load the app when the browser navigates to them
+        let (status, _, body) = get(app(&dir), "/items/IPN-1.2", Some("text/html")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, INDEX);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_files_and_cache_headers() {
+        let dir = static_dir();
+
+        let (status, cache_control, body) = get(app(&dir), "/assets/index-3f2a9c.js", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cache_control, HASHED_ASSET_CACHE_CONTROL);
+        assert_eq!(body, "console.log(1)");
+
+        let (status, cache_control, _) = get(app(&dir), "/robots.txt", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(cache_control, STATIC_FILE_CACHE_CONTROL);
+
+        // Missing files and excluded prefixes are not answered with the app
+        let (status, cache_control, _) = get(app(&dir), "/assets/gone-1234.js", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(cache_control.is_empty());
+        let (status, _, _) = get(app(&dir), "/favicon.ico", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _, _) = get(app(&dir), "/api/v1/unknown", Some("text/html")).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/orders/123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&dir).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
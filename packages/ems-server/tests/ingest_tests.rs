@@ -0,0 +1,336 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware::from_fn_with_state,
+        Router,
+    };
+    use chrono::Utc;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        middleware::auth::auth_middleware,
+        models::IngestFieldType,
+        routes::ingest::{ingest_routes, routes},
+        services::DatabaseService,
+        utils::ingest::{check_fields, check_template, parse_field_schema, render_template},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes()
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+    }
+
+    async fn ingest_app() -> Router {
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        ingest_routes().with_state(state)
+    }
+
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Ingest source API tests
+
+    #[tokio::test]
+    async fn test_create_ingest_source() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/",
+            Some(json!({
+                "name": "Marketplace",
+                "target": "order_update",
+                "template": { "order_number": "{{/order/ref}}", "status": "{{/order/state}}" }
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Ingest source routes require authentication, will fail without JWT token
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_unknown_token() {
+        let app = ingest_app().await;
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/{}", Uuid::new_v4().simple()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(json!({ "order": { "ref": "SO-1" } }).to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_field_schema() {
+        let rules = parse_field_schema(&json!({
+            "/order/ref": "string",
+            "/order/total": "number?",
+            "/lines": "array"
+        }))
+        .unwrap();
+        assert_eq!(rules.len(), 3);
+        let total = rules.iter().find(|r| r.pointer == "/order/total").unwrap();
+        assert_eq!(total.field_type, IngestFieldType::Number);
+        assert!(!total.required);
+
+        assert!(parse_field_schema(&json!(["/order/ref"])).is_err());
+        assert!(parse_field_schema(&json!({ "order/ref": "string" })).is_err());
+        assert!(parse_field_schema(&json!({ "/order/ref": "text" })).is_err());
+        assert!(parse_field_schema(&json!({ "/order/ref": 1 })).is_err());
+
+        // Optional fields are only checked when present; null counts as missing
+        assert!(check_fields(&json!({ "order": { "ref": "SO-1" }, "lines": [] }), &rules).is_empty());
+        let problems = check_fields(
+            &json!({ "order": { "ref": null, "total": "12.50" }, "lines": {} }),
+            &rules,
+        );
+        assert_eq!(problems.len(), 3);
+        assert!(problems.contains(&"/order/ref is missing".to_string()));
+        assert!(problems.contains(&"/order/total must be number".to_string()));
+        assert!(problems.contains(&"/lines must be array".to_string()));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let payload = json!({
+            "order": { "ref": "SO-1", "state": "approved", "total": 12.5 },
+            "gateway": "gw-7"
+        });
+        let template = json!({
+            "order_number": "{{/order/ref}}",
+            "total_amount": "{{ /order/total }}",
+            "notes": "Updated by {{/gateway}} ({{/missing}})",
+            "metadata": { "source": "marketplace", "raw": "{{}}" },
+            "status": "{{/order/missing}}",
+            "tags": ["{{/order/state}}", 1]
+        });
+
+        let rendered = render_template(&template, &payload);
+        assert_eq!(rendered["order_number"], json!("SO-1"));
+        // A lone placeholder keeps the value's type
+        assert_eq!(rendered["total_amount"], json!(12.5));
+        assert_eq!(rendered["notes"], json!("Updated by gw-7 ()"));
+        assert_eq!(rendered["metadata"]["source"], json!("marketplace"));
+        assert_eq!(rendered["metadata"]["raw"], payload);
+        assert_eq!(rendered["status"], Value::Null);
+        assert_eq!(rendered["tags"], json!(["approved", 1]));
+
+        assert!(check_template(&template).is_ok());
+        assert!(check_template(&json!("{{/order/ref}}")).is_err());
+        assert!(check_template(&json!({ "order_number": "{{order/ref}}" })).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ingest_workflow() {
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::models::{AccessLevel, CallerContext, IngestEventStatus, IngestTarget};
+        use ems_server::services::{IngestService, OrderService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let fixture = FixtureBuilder::tenant()
+            .named("Ingest test")
+            .with_machines(1)
+            .build(&database)
+            .await
+            .unwrap();
+        let tenant_id = fixture.tenant.id;
+        let admin = &fixture.admin;
+        let caller = fixture.caller();
+        let machine_id = fixture.machine_ids[0];
+
+        let order_number = format!("SO-{}", fixture.tenant.subdomain);
+        let order_id = OrderService::new(database.clone())
+            .create_order(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "order_number": order_number,
+                    "order_type": "customer_order",
+                    "external_entity_id": admin.id,
+                    "external_entity_type": "customer",
+                    "order_date": Utc::now(),
+                    "total_amount": 40.0,
+                    "status": "submitted",
+                    "created_by_id": admin.id,
+                    "items": [{ "item_name": "Cable harness", "quantity": 4, "unit_price": 10.0 }]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let ingest = IngestService::new(database.clone());
+
+        // Standard callers cannot set up integrations
+        let standard = CallerContext {
+            person_id: admin.id,
+            access_level: AccessLevel::Standard,
+        };
+        assert!(ingest.list_sources(tenant_id, &standard).await.is_err());
+
+        let marketplace = ingest
+            .create_source(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({
+                    "name": "Marketplace",
+                    "target": "order_update",
+                    "field_schema": { "/order/ref": "string", "/order/state": "string" },
+                    "template": {
+                        "order_number": "{{/order/ref}}",
+                        "status": "{{/order/state}}",
+                        "notes": "Marketplace update {{/event_id}}"
+                    }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(marketplace.source.target, IngestTarget::OrderUpdate);
+
+        // The order is found by number and updated through the order service
+        let outcome = ingest
+            .receive(
+                &marketplace.token,
+                json!({ "event_id": "evt-1", "order": { "ref": order_number, "state": "approved" } }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.entity_id, order_id);
+        let order = OrderService::new(database.clone())
+            .get_order_by_id(tenant_id, order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.notes.as_deref(), Some("Marketplace update evt-1"));
+
+        // Payloads that do not match the schema, or render an invalid request, are rejected
+        let error = ingest
+            .receive(&marketplace.token, json!({ "order": { "ref": order_number } }))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("/order/state is missing"));
+        let error = ingest
+            .receive(
+                &marketplace.token,
+                json!({ "order": { "ref": order_number, "state": "lost" } }),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid ingest payload"));
+
+        let events = ingest
+            .list_events(
+                tenant_id,
+                &caller,
+                marketplace.source.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| e.status == IngestEventStatus::Rejected)
+                .count(),
+            2
+        );
+
+        // A gateway source is bound to its machine by the template
+        let gateway = ingest
+            .create_source(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({
+                    "name": "Oven gateway",
+                    "target": "machine_heartbeat",
+                    "field_schema": { "/temp": "number" },
+                    "template": {
+                        "machine_id": machine_id,
+                        "status": "busy",
+                        "payload": { "temperature": "{{/temp}}" }
+                    }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let outcome = ingest
+            .receive(&gateway.token, json!({ "temp": 182.5 }))
+            .await
+            .unwrap();
+        assert_eq!(outcome.entity_id, machine_id);
+
+        // A rotated token replaces the old one
+        let rotated = ingest
+            .rotate_token(tenant_id, &caller, gateway.source.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(ingest
+            .receive(&gateway.token, json!({ "temp": 180.0 }))
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("not found"));
+        assert!(ingest
+            .receive(&rotated.token, json!({ "temp": 180.0 }))
+            .await
+            .is_ok());
+
+        // Deactivated sources stop accepting events
+        ingest
+            .update_source(
+                tenant_id,
+                &caller,
+                gateway.source.id,
+                serde_json::from_value(json!({ "is_active": false })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(ingest
+            .receive(&rotated.token, json!({ "temp": 180.0 }))
+            .await
+            .is_err());
+    }
+}
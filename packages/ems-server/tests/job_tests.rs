@@ -617,4 +617,960 @@ mod tests {
                 || response.status() == StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    #[tokio::test]
+    async fn test_archive_job() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let job_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/archive", job_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Job routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_archive_settings() {
+        use ems_server::utils::archive::{archive_after_days, check_archive_settings};
+
+        assert_eq!(archive_after_days(None, 180), Some(180));
+        assert_eq!(
+            archive_after_days(Some(&json!({ "archive_after_days": 30 })), 180),
+            Some(30)
+        );
+        // 0 turns archiving off, for the tenant or by default
+        assert_eq!(
+            archive_after_days(Some(&json!({ "archive_after_days": 0 })), 180),
+            None
+        );
+        assert_eq!(archive_after_days(Some(&json!({})), 0), None);
+
+        assert!(check_archive_settings(Some(&json!({ "archive_after_days": 30 }))).is_ok());
+        assert!(check_archive_settings(Some(&json!({ "locale": "es" }))).is_ok());
+        assert!(check_archive_settings(Some(&json!({ "archive_after_days": -1 }))).is_err());
+        assert!(check_archive_settings(Some(&json!({ "archive_after_days": "30" }))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_closed_jobs_and_orders() {
+        use chrono::{Duration as ChronoDuration, Utc};
+        use ems_server::services::{ArchiveWorker, JobService, OrderService, TenantService};
+        use std::time::Duration;
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({
+                    "name": "Archive test",
+                    "subdomain": format!("archive-{}", suffix),
+                    "settings": { "archive_after_days": 30 }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let jobs = JobService::new(database.clone());
+        let job = |number: String, status: &str, end_date| {
+            let request = serde_json::from_value(json!({
+                "job_number": number,
+                "quantity": 1,
+                "job_type": "service",
+                "status": status,
+                "end_date": end_date
+            }))
+            .unwrap();
+            let jobs = &jobs;
+            async move { jobs.create_job(tenant_id, request).await.unwrap().id }
+        };
+        let old = job(
+            format!("SVC-OLD-{}", suffix),
+            "completed",
+            Some(Utc::now() - ChronoDuration::days(60)),
+        )
+        .await;
+        let recent = job(format!("SVC-NEW-{}", suffix), "completed", Some(Utc::now())).await;
+        let open = job(format!("SVC-OPEN-{}", suffix), "in_progress", None).await;
+
+        // Only tenants that set an archive age are archived with a default of 0
+        let worker = ArchiveWorker::new(database.clone(), Duration::from_secs(3600), 0);
+        assert!(worker.run_once().await.unwrap() >= 1);
+
+        let listed = |include_archived: bool| {
+            let jobs = &jobs;
+            async move {
+                jobs.list_jobs(tenant_id, None, None, include_archived, None, None)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|job| job.id)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let default = listed(false).await;
+        assert!(!default.contains(&old));
+        assert!(default.contains(&recent) && default.contains(&open));
+        assert_eq!(listed(true).await.len(), 3);
+
+        // Open jobs cannot be archived; closed ones can be, and unarchived again
+        assert!(jobs
+            .set_archived(tenant_id, open, true)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("cannot be archived"));
+        assert!(
+            jobs.set_archived(tenant_id, recent, true)
+                .await
+                .unwrap()
+                .unwrap()
+                .archived
+        );
+        assert!(
+            !jobs
+                .set_archived(tenant_id, old, false)
+                .await
+                .unwrap()
+                .unwrap()
+                .archived
+        );
+
+        // A job unarchived by hand is not archived again
+        worker.run_once().await.unwrap();
+        let default = listed(false).await;
+        assert!(default.contains(&old) && !default.contains(&recent));
+
+        // Orders are left out of lists the same way
+        let orders = OrderService::new(database.clone());
+        assert!(orders
+            .list_orders(tenant_id, None, None, false, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(orders
+            .set_archived(tenant_id, Uuid::new_v4(), true)
+            .await
+            .unwrap()
+            .is_none());
+
+        TenantService::new(database.clone())
+            .delete_tenant(tenant_id)
+            .await
+            .unwrap();
+    }
+
+    // Batch Record Tests
+
+    #[tokio::test]
+    async fn test_complete_job() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let job_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/complete", job_id),
+            Some(json!({ "labor_hours": 2.5 })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Job routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_batch_record() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let job_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/batch-record", job_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_batch_record_pdf() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let job_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/batch-record/pdf", job_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_batch_records() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let item_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/batch-records?item_id={}&limit=10", item_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_consumed_materials_merges_ledger_and_job_entries() {
+        use ems_server::models::Quantity;
+        use ems_server::utils::batch_record::consumed_materials;
+
+        let resistor = Uuid::new_v4();
+        let capacitor = Uuid::new_v4();
+        let entries = json!([
+            { "item_id": resistor, "quantity": 99, "lot_number": "L-1" },
+            { "item_id": resistor, "lot_number": "L-1", "serial_number": "R-7" },
+            { "item_id": capacitor, "quantity": 3, "serial_numbers": ["C-1", "C-2", "C-1"] },
+            { "item_id": "not-a-uuid", "quantity": 5 },
+            { "quantity": 5 }
+        ]);
+
+        let materials = consumed_materials(&[(resistor, Quantity::from(10))], Some(&entries));
+
+        assert_eq!(materials.len(), 2);
+        // The ledger quantity wins over the job's own count
+        assert_eq!(materials[0].item_id, resistor);
+        assert_eq!(materials[0].quantity, Quantity::from(10));
+        assert_eq!(materials[0].lot_numbers, vec!["L-1"]);
+        assert_eq!(materials[0].serial_numbers, vec!["R-7"]);
+        assert_eq!(materials[1].item_id, capacitor);
+        assert_eq!(materials[1].quantity, Quantity::from(3));
+        assert_eq!(materials[1].serial_numbers, vec!["C-1", "C-2"]);
+
+        assert!(consumed_materials(&[], None).is_empty());
+    }
+
+    #[test]
+    fn test_render_batch_record_pdf() {
+        use chrono::Utc;
+        use ems_server::models::BatchRecordResponse;
+        use ems_server::utils::batch_record::render_batch_record_pdf;
+
+        let record: BatchRecordResponse = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "job_id": Uuid::new_v4(),
+            "batch_number": "MFG-1001",
+            "item_id": null,
+            "quantity": 25,
+            "completed_at": Utc::now(),
+            "completed_by_id": null,
+            "created_at": Utc::now(),
+            "product": {
+                "item_id": null, "part_number": "PCB-100", "description": "Controller board",
+                "work_order_number": "WO-7", "production_line": "SMT-1", "started_at": null
+            },
+            "materials": [{
+                "item_id": Uuid::new_v4(), "part_number": "R-10K", "quantity": 50,
+                "lot_numbers": ["L-1"], "serial_numbers": []
+            }],
+            "machines": [{
+                "machine_id": Uuid::new_v4(), "name": "Pick and place", "started_at": null,
+                "ended_at": null,
+                "firmware": [{
+                    "asset_id": Uuid::new_v4(), "name": "pnp-fw", "version": "2.1.0",
+                    "checksum": "abc123"
+                }]
+            }],
+            "operators": [{
+                "person_id": Uuid::new_v4(), "name": "Operator", "role": "primary",
+                "machine_id": null
+            }],
+            "inspections": []
+        }))
+        .unwrap();
+
+        let pdf = render_batch_record_pdf(&record);
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_record_assembled_on_completion() {
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::{NewPerson, Person, Quantity, RecordStockMovementRequest};
+        use ems_server::schema::person;
+        use ems_server::services::{
+            AssetService, BatchRecordService, ItemService, JobService, MachineService,
+            StockService, TenantService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Batch record test", "subdomain": format!("batch-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let operator: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Line operator".to_string(),
+                email: format!("operator-{}@example.com", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let items = ItemService::new(database.clone());
+        let item = |part_number: String, context: &str, quantity: i32| {
+            let request = serde_json::from_value(json!({
+                "internal_part_number": part_number,
+                "manufacturer": "Acme",
+                "context": context,
+                "quantity": quantity
+            }))
+            .unwrap();
+            let items = &items;
+            async move { items.create_item(tenant_id, request).await.unwrap().id }
+        };
+        let board = item(format!("PCB-{}", suffix), "finished_goods", 0).await;
+        let chip = item(format!("MCU-{}", suffix), "store", 20).await;
+
+        let jobs = JobService::new(database.clone());
+        let job_number = format!("MFG-{}", suffix);
+        let job_id = jobs
+            .create_job(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "job_number": job_number,
+                    "item_id": board,
+                    "quantity": 4,
+                    "assigned_person_id": operator.id,
+                    "job_type": "manufacturing",
+                    "status": "in_progress",
+                    "work_order_number": "WO-1",
+                    "production_line": "SMT-1"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        jobs.create_job(
+            tenant_id,
+            serde_json::from_value(json!({
+                "job_number": format!("QA-{}", suffix),
+                "quantity": 4,
+                "job_type": "qa",
+                "inspected_job_id": job_id,
+                "inspection_type": "final"
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        // A machine running firmware that follows its lineage's latest version
+        let machines = MachineService::new(database.clone());
+        let machine_id = machines
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Reflow oven", "ip": "10.0.0.20", "port": 8080, "protocol": "http"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let assets = AssetService::new(database.clone());
+        let asset_type_id = assets
+            .create_asset_type(
+                serde_json::from_value(json!({ "name": format!("fw-{}", suffix) })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let firmware_id = assets
+            .create_asset(
+                tenant_id,
+                operator.id,
+                serde_json::from_value(json!({
+                    "item_id": board,
+                    "asset_type_id": asset_type_id,
+                    "name": "oven-fw",
+                    "version": "1.0.0",
+                    "checksum": "aaa111"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        machines
+            .create_machine_asset_relationship(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({
+                    "asset_id": firmware_id,
+                    "relationship_type": "firmware",
+                    "pin_mode": "latest"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let upgraded_id = assets
+            .create_asset_version(
+                tenant_id,
+                operator.id,
+                firmware_id,
+                serde_json::from_value(json!({ "version": "1.1.0", "checksum": "bbb222" }))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+        machines
+            .create_machine_job_assignment(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({ "job_id": job_id, "status": "in_progress" }))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        machines
+            .create_machine_operator_assignment(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({
+                    "person_id": operator.id,
+                    "assignment_type": "primary"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        StockService::new(database.clone())
+            .record_movement(
+                tenant_id,
+                chip,
+                Some(operator.id),
+                serde_json::from_value::<RecordStockMovementRequest>(json!({
+                    "context": "store",
+                    "movement_type": "issue",
+                    "quantity": 8,
+                    "reference_type": "job",
+                    "reference_id": job_id
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let completed = jobs
+            .complete_job(
+                tenant_id,
+                job_id,
+                operator.id,
+                serde_json::from_value(json!({
+                    "labor_hours": 3.0,
+                    "materials_consumed": [
+                        { "item_id": chip, "lot_number": "LOT-42", "serial_numbers": ["S1", "S2"] }
+                    ]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(completed.status.to_string(), "completed");
+
+        let batches = BatchRecordService::new(database.clone());
+        let record = batches
+            .get_batch_record(tenant_id, job_id)
+            .await
+            .unwrap()
+            .expect("completion should assemble a batch record");
+        assert_eq!(record.batch_number, job_number);
+        assert_eq!(record.item_id, Some(board));
+        assert_eq!(record.quantity, 4);
+        assert_eq!(record.completed_by_id, Some(operator.id));
+        assert_eq!(
+            record.content.product.work_order_number.as_deref(),
+            Some("WO-1")
+        );
+
+        assert_eq!(record.content.materials.len(), 1);
+        let material = &record.content.materials[0];
+        assert_eq!(material.item_id, chip);
+        assert_eq!(material.quantity, Quantity::from(8));
+        assert_eq!(material.lot_numbers, vec!["LOT-42"]);
+        assert_eq!(material.serial_numbers, vec!["S1", "S2"]);
+
+        assert_eq!(record.content.machines.len(), 1);
+        let firmware = &record.content.machines[0].firmware;
+        assert_eq!(firmware.len(), 1);
+        assert_eq!(firmware[0].asset_id, upgraded_id);
+        assert_eq!(firmware[0].version.as_deref(), Some("1.1.0"));
+        assert_eq!(firmware[0].checksum.as_deref(), Some("bbb222"));
+
+        let roles: Vec<&str> = record
+            .content
+            .operators
+            .iter()
+            .map(|o| o.role.as_str())
+            .collect();
+        assert_eq!(roles, vec!["assigned", "primary"]);
+        assert_eq!(record.content.inspections.len(), 1);
+        assert_eq!(
+            record.content.inspections[0].inspection_type.as_deref(),
+            Some("final")
+        );
+
+        let listed = batches
+            .list_batch_records(
+                tenant_id,
+                serde_json::from_value(json!({ "item_id": board })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        let (batch_number, pdf) = batches
+            .batch_record_pdf(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(batch_number, job_number);
+        assert!(pdf.starts_with(b"%PDF"));
+
+        // A job is completed, and recorded, only once
+        let error = jobs
+            .complete_job(
+                tenant_id,
+                job_id,
+                operator.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be completed"));
+    }
+
+    // Job Traveler Tests
+
+    #[tokio::test]
+    async fn test_set_job_routing() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let job_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/{}/operations", job_id),
+            Some(json!({
+                "operations": [
+                    { "name": "Cut", "standard_minutes": 30, "machine_group_id": Uuid::new_v4() },
+                    { "name": "Deburr", "standard_minutes": 10, "instructions": "Break all edges" }
+                ]
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Job routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_start_job_operation() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/operations/{}/start", Uuid::new_v4(), Uuid::new_v4()),
+            Some(json!({})),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Job routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_check_job_routing_request() {
+        use ems_server::models::SetJobRoutingRequest;
+
+        let routing: SetJobRoutingRequest = serde_json::from_value(json!({
+            "operations": [{ "name": "Cut", "standard_minutes": 30 }, { "name": "Pack" }]
+        }))
+        .unwrap();
+        assert!(routing.check().is_ok());
+        assert_eq!(routing.operations[1].standard_minutes, 0.0);
+
+        let unnamed: SetJobRoutingRequest =
+            serde_json::from_value(json!({ "operations": [{ "name": "  " }] })).unwrap();
+        assert!(unnamed.check().is_err());
+
+        let negative: SetJobRoutingRequest = serde_json::from_value(json!({
+            "operations": [{ "name": "Cut", "standard_minutes": -5 }]
+        }))
+        .unwrap();
+        assert!(negative.check().is_err());
+    }
+
+    #[test]
+    fn test_routing_progress() {
+        use chrono::{Duration, TimeZone, Utc};
+        use ems_server::utils::job_operation::{elapsed_minutes, routing_progress};
+
+        assert_eq!(routing_progress(&[]), None);
+
+        // Weighted by standard time
+        assert_eq!(
+            routing_progress(&[(30.0, true), (60.0, false), (10.0, true)]),
+            Some(40.0)
+        );
+        // Every operation counts the same without standard times
+        assert_eq!(
+            routing_progress(&[(0.0, true), (0.0, false), (0.0, false)]),
+            Some(33.3)
+        );
+        assert_eq!(routing_progress(&[(5.0, true)]), Some(100.0));
+
+        let started = Utc.with_ymd_and_hms(2025, 3, 3, 8, 0, 0).unwrap();
+        assert_eq!(
+            elapsed_minutes(started, started + Duration::seconds(2730)),
+            45.5
+        );
+        assert_eq!(elapsed_minutes(started, started), 0.0);
+    }
+
+    #[test]
+    fn test_kit_shortfall_and_substitutes() {
+        use ems_server::models::Quantity;
+        use ems_server::utils::kitting::{kit_shortfall, propose_substitutes, substitutes_cover};
+
+        let q = Quantity::from;
+        // 10 required, 2 issued and 1 substituted leave 7, of which 4 are in stock
+        assert_eq!(kit_shortfall(q(10), q(2), q(1), q(4)), (q(7), q(3)));
+        assert_eq!(kit_shortfall(q(10), q(12), q(0), q(0)), (q(0), q(0)));
+        assert_eq!(kit_shortfall(q(5), q(0), q(0), q(9)), (q(5), q(0)));
+
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let proposals = propose_substitutes(q(5), &[(a, q(2)), (b, q(4)), (c, q(0))]);
+        // Most stock first; substitutes without stock are left out
+        assert_eq!(proposals.len(), 2);
+        assert_eq!(proposals[0].item_id, b);
+        assert_eq!(proposals[0].suggested_quantity, q(4));
+        assert!(!proposals[0].covers_shortfall);
+        assert_eq!(proposals[1].item_id, a);
+        assert_eq!(proposals[1].suggested_quantity, q(1));
+        assert!(substitutes_cover(q(5), &proposals));
+
+        let proposals = propose_substitutes(q(5), &[(a, q(6))]);
+        assert!(proposals[0].covers_shortfall);
+        assert_eq!(proposals[0].suggested_quantity, q(5));
+        assert!(!substitutes_cover(
+            q(5),
+            &propose_substitutes(q(5), &[(a, q(3))])
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_job_traveler() {
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::NewPerson;
+        use ems_server::schema::person;
+        use ems_server::services::{
+            JobOperationService, JobService, MachineGroupService, TenantService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Traveler test", "subdomain": format!("traveler-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let mut conn = database.get_connection().await.unwrap();
+        let operator = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Machinist".to_string(),
+                email: format!("machinist-{}@example.com", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(person::id)
+            .get_result::<Uuid>(&mut conn)
+            .await
+            .unwrap();
+
+        let line = MachineGroupService::new(database.clone())
+            .create_group(
+                tenant_id,
+                serde_json::from_value(json!({ "name": "Machining" })).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let jobs = JobService::new(database.clone());
+        let job_id = jobs
+            .create_job(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "job_number": format!("MFG-{}", suffix),
+                    "quantity": 10,
+                    "job_type": "manufacturing"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let job = jobs
+            .get_job_by_id(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.progress_percent, None);
+
+        let operations = JobOperationService::new(database.clone());
+        let routing = operations
+            .set_routing(
+                tenant_id,
+                job_id,
+                serde_json::from_value(json!({
+                    "operations": [
+                        { "name": "Mill", "standard_minutes": 45, "machine_group_id": line.id },
+                        { "name": "Inspect", "standard_minutes": 15 }
+                    ]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(routing.operations.len(), 2);
+        assert_eq!(routing.progress_percent, Some(0.0));
+        let (mill, inspect) = (routing.operations[0].id, routing.operations[1].id);
+
+        // Operations run in routing order
+        let error = operations
+            .start_operation(
+                tenant_id,
+                job_id,
+                inspect,
+                operator,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be started"));
+
+        let started = operations
+            .start_operation(
+                tenant_id,
+                job_id,
+                mill,
+                operator,
+                serde_json::from_value(json!({ "started_at": "2025-03-03T08:00:00Z" })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(started.status.to_string(), "in_progress");
+        let job = jobs
+            .get_job_by_id(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.status.to_string(), "in_progress");
+
+        // The routing is fixed once work has started
+        let error = operations
+            .set_routing(
+                tenant_id,
+                job_id,
+                serde_json::from_value(json!({ "operations": [] })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be changed"));
+
+        let completed = operations
+            .complete_operation(
+                tenant_id,
+                job_id,
+                mill,
+                operator,
+                serde_json::from_value(json!({ "completed_at": "2025-03-03T08:50:00Z" })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(completed.actual_minutes, Some(50.0));
+
+        let routing = operations
+            .get_routing(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(routing.completed_count, 1);
+        assert_eq!(routing.progress_percent, Some(75.0));
+        assert_eq!(routing.actual_minutes, 50.0);
+        let job = jobs
+            .get_job_by_id(tenant_id, job_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.progress_percent, Some(75.0));
+    }
+
+    #[test]
+    fn test_job_dependency_cycles() {
+        use ems_server::utils::job_dependency::would_create_cycle;
+
+        let (a, b, c, d) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        // a -> b -> c
+        let edges = vec![(a, b), (b, c)];
+
+        assert!(would_create_cycle(&edges, c, a));
+        assert!(would_create_cycle(&edges, b, a));
+        assert!(would_create_cycle(&edges, a, a));
+        assert!(!would_create_cycle(&edges, a, c));
+        assert!(!would_create_cycle(&edges, d, a));
+        assert!(!would_create_cycle(&[], a, b));
+    }
+
+    #[test]
+    fn test_job_critical_path() {
+        use chrono::{Duration, TimeZone, Utc};
+        use ems_server::utils::job_dependency::{critical_path, ScheduledJob};
+
+        let t0 = Utc.with_ymd_and_hms(2025, 6, 2, 8, 0, 0).unwrap();
+        let at = |h: i64| t0 + Duration::hours(h);
+        let job = |id: Uuid, start: i64, end: i64| ScheduledJob {
+            id,
+            start: at(start),
+            end: at(end),
+        };
+        let (cut, weld, paint, pack, labels) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        // Welding is scheduled to start before cutting is done, so it is pushed back
+        let jobs = vec![
+            job(cut, 0, 4),
+            job(weld, 3, 8),
+            job(paint, 8, 10),
+            job(labels, 0, 1),
+            job(pack, 10, 11),
+        ];
+        let edges = vec![(cut, weld), (weld, paint), (paint, pack), (labels, pack)];
+        let result = critical_path(&jobs, &edges);
+
+        assert_eq!(result.path, vec![cut, weld, paint, pack]);
+        assert_eq!(result.finish, Some(at(12)));
+        assert_eq!(result.timings[&weld].earliest_start, at(4));
+        assert!(result.timings[&cut].is_critical());
+        assert!(!result.timings[&labels].is_critical());
+        assert_eq!(result.timings[&labels].slack_minutes(), 10 * 60);
+        assert!(result.is_critical_link(weld, paint));
+        assert!(!result.is_critical_link(labels, pack));
+
+        // Without dependencies the job finishing last is the whole path
+        let alone = critical_path(&jobs, &[]);
+        assert_eq!(alone.path, vec![pack]);
+        assert_eq!(alone.timings[&paint].slack_minutes(), 60);
+
+        // A cycle leaves its jobs out rather than looping
+        let cyclic = critical_path(&jobs, &[(cut, weld), (weld, cut)]);
+        assert!(!cyclic.timings.contains_key(&cut));
+        assert_eq!(cyclic.path, vec![pack]);
+
+        assert_eq!(critical_path(&[], &[]).finish, None);
+    }
 }
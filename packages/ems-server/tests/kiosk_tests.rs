@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::{Duration, Utc};
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{ClockEventType, KioskBadgeIn, KioskDowntimeRequest, MachineStatus},
+        routes::kiosk::routes,
+        utils::kiosk::{
+            clock_event_allowed, generate_kiosk_key, is_valid_pin, kiosk_key_allows, pin_lockout,
+            KIOSK_KEY_PREFIX, MAX_PIN_ATTEMPTS,
+        },
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    fn badge(badge_id: Option<&str>, person_id: Option<Uuid>, pin: Option<&str>) -> KioskBadgeIn {
+        KioskBadgeIn {
+            badge_id: badge_id.map(str::to_string),
+            person_id,
+            pin: pin.map(str::to_string),
+        }
+    }
+
+    // Kiosk API Tests
+
+    #[tokio::test]
+    async fn test_provision_kiosk_terminal_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/terminals",
+            Some(json!({
+                "name": "Line 2 kiosk",
+                "actions": ["clock", "report_downtime"]
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Kiosk routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlock_operator_badge_requires_auth() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/badges/{}/unlock", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kiosk_clock_requires_terminal() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/clock",
+            Some(json!({
+                "operator": { "badge_id": "B-1001" },
+                "event_type": "clock_in"
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::FORBIDDEN
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Kiosk helper tests
+
+    #[test]
+    fn test_kiosk_key_scope() {
+        let actions = vec!["clock".to_string(), "report_downtime".to_string()];
+
+        assert!(kiosk_key_allows(
+            &Method::GET,
+            "/api/v1/kiosk/terminal",
+            &actions
+        ));
+        assert!(kiosk_key_allows(
+            &Method::GET,
+            "/api/v1/kiosk/operators/",
+            &actions
+        ));
+        assert!(kiosk_key_allows(
+            &Method::POST,
+            "/api/v1/kiosk/clock",
+            &actions
+        ));
+        assert!(kiosk_key_allows(
+            &Method::POST,
+            "/api/v1/kiosk/downtime",
+            &actions
+        ));
+
+        // Actions the terminal does not allow
+        assert!(!kiosk_key_allows(
+            &Method::POST,
+            "/api/v1/kiosk/operations/complete",
+            &actions
+        ));
+
+        // Terminal management and everything outside the kiosk
+        assert!(!kiosk_key_allows(
+            &Method::GET,
+            "/api/v1/kiosk/terminals",
+            &actions
+        ));
+        assert!(!kiosk_key_allows(
+            &Method::PUT,
+            &format!("/api/v1/kiosk/badges/{}", Uuid::new_v4()),
+            &actions
+        ));
+        assert!(!kiosk_key_allows(
+            &Method::POST,
+            &format!("/api/v1/kiosk/badges/{}/unlock", Uuid::new_v4()),
+            &actions
+        ));
+        assert!(!kiosk_key_allows(
+            &Method::GET,
+            "/api/v1/kiosk/badges",
+            &actions
+        ));
+        assert!(!kiosk_key_allows(&Method::GET, "/api/v1/job", &actions));
+        assert!(!kiosk_key_allows(
+            &Method::GET,
+            "/api/v1/kiosk/clock",
+            &actions
+        ));
+    }
+
+    #[test]
+    fn test_kiosk_key_and_pin_format() {
+        let key = generate_kiosk_key();
+        assert!(key.starts_with(KIOSK_KEY_PREFIX));
+        assert_eq!(key.len(), KIOSK_KEY_PREFIX.len() + 64);
+        assert_ne!(key, generate_kiosk_key());
+
+        assert!(is_valid_pin("1234"));
+        assert!(is_valid_pin("123456789012"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("1234567890123"));
+        assert!(!is_valid_pin("12a4"));
+    }
+
+    #[test]
+    fn test_pin_lockout_after_max_attempts() {
+        let now = Utc::now();
+
+        assert_eq!(pin_lockout(1, now), None);
+        assert_eq!(pin_lockout(MAX_PIN_ATTEMPTS - 1, now), None);
+        assert_eq!(
+            pin_lockout(MAX_PIN_ATTEMPTS, now),
+            Some(now + Duration::minutes(15))
+        );
+    }
+
+    #[test]
+    fn test_clock_event_sequence() {
+        assert!(clock_event_allowed(None, ClockEventType::ClockIn));
+        assert!(!clock_event_allowed(None, ClockEventType::ClockOut));
+        assert!(clock_event_allowed(
+            Some(ClockEventType::ClockIn),
+            ClockEventType::ClockOut
+        ));
+        assert!(!clock_event_allowed(
+            Some(ClockEventType::ClockIn),
+            ClockEventType::ClockIn
+        ));
+        assert!(clock_event_allowed(
+            Some(ClockEventType::ClockOut),
+            ClockEventType::ClockIn
+        ));
+    }
+
+    #[test]
+    fn test_kiosk_badge_in_check() {
+        let person_id = Uuid::new_v4();
+
+        assert!(badge(Some("B-1001"), None, None).check().is_ok());
+        assert!(badge(None, Some(person_id), Some("1234")).check().is_ok());
+
+        // A person needs their PIN, and a badge stands on its own
+        assert!(badge(None, Some(person_id), None).check().is_err());
+        assert!(badge(Some("B-1001"), Some(person_id), Some("1234"))
+            .check()
+            .is_err());
+        assert!(badge(None, None, None).check().is_err());
+
+        assert_eq!(badge(Some("B-1001"), None, None).method(), "badge");
+        assert_eq!(badge(None, Some(person_id), Some("1234")).method(), "pin");
+
+        let downtime = |status| KioskDowntimeRequest {
+            operator: badge(Some("B-1001"), None, None),
+            machine_id: Uuid::new_v4(),
+            status,
+            reason: "Spindle jammed".to_string(),
+        };
+        assert!(downtime(None).check().is_ok());
+        assert!(downtime(Some(MachineStatus::Maintenance)).check().is_ok());
+        assert!(downtime(Some(MachineStatus::Busy)).check().is_err());
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        body::{Body, Bytes},
+        extract::DefaultBodyLimit,
+        http::{header, Method, Request, StatusCode},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::Value;
+    use tower::ServiceExt; // for `oneshot`
+
+    use ems_server::middleware::limits::{
+        RequestLimits, DEFAULT_MAX_BODY_BYTES, DEFAULT_REQUEST_TIMEOUT_SECONDS,
+    };
+
+    fn app(limits: RequestLimits) -> Router {
+        Router::new()
+            .route(
+                "/json",
+                post(|Json(value): Json<Value>| async move { Json(value) }),
+            )
+            .route(
+                "/upload",
+                post(|body: Bytes| async move { body.len().to_string() })
+                    .layer(DefaultBodyLimit::max(4096)),
+            )
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "done"
+                }),
+            )
+            .layer(limits.timeout_layer())
+            .layer(limits.body_limit())
+    }
+
+    fn limits() -> RequestLimits {
+        RequestLimits::new(1024, Duration::from_millis(100))
+    }
+
+    async fn post_body(uri: &str, body: String) -> StatusCode {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        app(limits()).oneshot(request).await.unwrap().status()
+    }
+
+    fn json_of_size(bytes: usize) -> String {
+        format!("\"{}\"", "x".repeat(bytes - 2))
+    }
+
+    #[test]
+    fn test_default_limits() {
+        let limits = RequestLimits::default();
+        assert_eq!(limits.max_body_bytes, DEFAULT_MAX_BODY_BYTES);
+        assert_eq!(
+            limits.timeout,
+            Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECONDS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_body_limit() {
+        assert_eq!(post_body("/json", json_of_size(1000)).await, StatusCode::OK);
+        assert_eq!(
+            post_body("/json", json_of_size(2000)).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_route_raises_body_limit() {
+        assert_eq!(
+            post_body("/upload", json_of_size(2000)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            post_body("/upload", json_of_size(8000)).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let request = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+        let response = app(limits()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+}
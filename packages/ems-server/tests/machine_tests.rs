@@ -119,6 +119,28 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_get_machine_docs() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let machine_id = Uuid::new_v4();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/docs", machine_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[tokio::test]
     async fn test_list_machines() {
         let app = app().await;
@@ -205,6 +227,136 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_delete_machine_requires_admin_access() {
+        use axum::Extension;
+        use ems_server::{
+            middleware::tenant::TenantContext,
+            models::{AccessLevel, CallerContext},
+        };
+
+        let app = app()
+            .await
+            .layer(Extension(CallerContext {
+                person_id: Uuid::new_v4(),
+                access_level: AccessLevel::Standard,
+            }))
+            .layer(Extension(TenantContext {
+                tenant_id: Uuid::new_v4(),
+            }));
+        let request = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!("/{}", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            "Access denied: standard access cannot delete machines"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_provision_machine_requires_admin_access() {
+        use axum::Extension;
+        use ems_server::{
+            middleware::tenant::TenantContext,
+            models::{AccessLevel, CallerContext},
+        };
+
+        let app = app()
+            .await
+            .layer(Extension(CallerContext {
+                person_id: Uuid::new_v4(),
+                access_level: AccessLevel::Standard,
+            }))
+            .layer(Extension(TenantContext {
+                tenant_id: Uuid::new_v4(),
+            }));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/{}/provision", Uuid::new_v4()))
+            .header("host", "ems.example.com")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            "Access denied: standard access cannot provision machines"
+        );
+    }
+
+    #[test]
+    fn test_machine_key_scope() {
+        use ems_server::utils::machine_credential::{
+            broker_topic, generate_machine_key, heartbeat_url, machine_key_allows,
+            machine_key_prefix, MACHINE_KEY_PREFIX,
+        };
+
+        let machine_id = Uuid::new_v4();
+        let key = generate_machine_key();
+        assert!(key.starts_with(MACHINE_KEY_PREFIX));
+        assert_ne!(key, generate_machine_key());
+        assert_eq!(machine_key_prefix(&key).len(), 12);
+        assert!(key.starts_with(&machine_key_prefix(&key)));
+
+        // Keys only reach their own machine's heartbeat
+        let heartbeat = format!("/api/v1/machine/{}/heartbeat", machine_id);
+        assert!(machine_key_allows(&Method::POST, &heartbeat, machine_id));
+        assert!(machine_key_allows(
+            &Method::POST,
+            &format!("{}/", heartbeat),
+            machine_id
+        ));
+        assert!(!machine_key_allows(&Method::GET, &heartbeat, machine_id));
+        assert!(!machine_key_allows(
+            &Method::POST,
+            &heartbeat,
+            Uuid::new_v4()
+        ));
+        assert!(!machine_key_allows(
+            &Method::POST,
+            &format!("/api/v1/machine/{}/provision", machine_id),
+            machine_id
+        ));
+
+        // Gateways' batches are checked entry by entry
+        assert!(machine_key_allows(
+            &Method::POST,
+            "/api/v1/machine/heartbeats/batch",
+            machine_id
+        ));
+        assert!(!machine_key_allows(
+            &Method::GET,
+            "/api/v1/machine/heartbeats/batch",
+            machine_id
+        ));
+
+        assert_eq!(
+            heartbeat_url("https://ems.example.com/", machine_id),
+            format!("https://ems.example.com{}", heartbeat)
+        );
+        let tenant_id = Uuid::new_v4();
+        assert_eq!(
+            broker_topic(tenant_id, machine_id),
+            format!("ems/{}/machines/{}/#", tenant_id, machine_id)
+        );
+    }
+
     // Heartbeat tests
 
     #[tokio::test]
@@ -237,6 +389,54 @@ mod tests {
 
     // Machine-Item relationship tests
 
+    #[tokio::test]
+    async fn test_update_heartbeats_batch() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let batch_data = json!({
+            "heartbeats": [
+                {"machine_id": Uuid::new_v4(), "status": "idle"},
+                {"machine_id": Uuid::new_v4(), "status": "busy", "payload": {"spindle_rpm": 1200}}
+            ]
+        });
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/heartbeats/batch",
+            Some(batch_data),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_heartbeats_empty_batch() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/heartbeats/batch",
+            Some(json!({ "heartbeats": [] })), // Empty batch should fail validation
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::UNPROCESSABLE_ENTITY
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[tokio::test]
     async fn test_create_machine_item_relationship() {
         let app = app().await;
@@ -339,6 +539,34 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_create_machine_asset_relationship_pinned_to_latest() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let machine_id = Uuid::new_v4();
+
+        let relationship_data = json!({
+            "asset_id": Uuid::new_v4(),
+            "relationship_type": "firmware",
+            "pin_mode": "latest"
+        });
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/assets", machine_id),
+            Some(relationship_data),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[tokio::test]
     async fn test_list_machine_asset_relationships() {
         let app = app().await;
@@ -1216,4 +1444,1910 @@ mod tests {
                 || response.status() == StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    // Capacity Planning Tests
+
+    #[tokio::test]
+    async fn test_get_capacity_plan() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/capacity?from=2025-01-06&to=2025-01-19&hours_per_day=16",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_capacity_hours_by_day() {
+        use chrono::{NaiveDate, TimeZone};
+        use ems_server::utils::capacity::hours_by_day;
+
+        let from = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 1, 7).unwrap();
+
+        // 20:00 on the 5th to 04:00 on the 8th is clipped to the window
+        let start = Utc.with_ymd_and_hms(2025, 1, 5, 20, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2025, 1, 8, 4, 0, 0).unwrap();
+        let hours = hours_by_day(start, end, from, to);
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[&from], 24.0);
+        assert_eq!(hours[&to], 24.0);
+
+        let start = Utc.with_ymd_and_hms(2025, 1, 6, 22, 30, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2025, 1, 7, 1, 0, 0).unwrap();
+        let hours = hours_by_day(start, end, from, to);
+        assert_eq!(hours[&from], 1.5);
+        assert_eq!(hours[&to], 1.0);
+    }
+
+    #[test]
+    fn test_capacity_default_availability_and_load() {
+        use chrono::NaiveDate;
+        use ems_server::utils::capacity::{default_available_hours, load_percent};
+
+        let monday = NaiveDate::from_ymd_opt(2025, 1, 6).unwrap();
+        let saturday = NaiveDate::from_ymd_opt(2025, 1, 11).unwrap();
+        assert_eq!(default_available_hours(monday, 8.0, false), 8.0);
+        assert_eq!(default_available_hours(saturday, 8.0, false), 0.0);
+        assert_eq!(default_available_hours(saturday, 8.0, true), 8.0);
+
+        assert_eq!(load_percent(12.0, 8.0), Some(150.0));
+        assert_eq!(load_percent(4.0, 0.0), None);
+    }
+
+    // Telemetry Tests
+
+    #[tokio::test]
+    async fn test_get_machine_telemetry() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let machine_id = Uuid::new_v4();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!(
+                "/{}/telemetry?channel=energy_kwh&from=2025-01-06T00:00:00Z&to=2025-01-07T00:00:00Z",
+                machine_id
+            ),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_extract_telemetry_readings() {
+        use ems_server::{models::TelemetryChannel, utils::telemetry::extract_readings};
+
+        let payload = json!({
+            "energy_kwh": 1520.5,
+            "air_pressure": "6.2",
+            "coolant_level": "low",
+            "meters": { "total_kwh": 1600 }
+        });
+
+        // Without a declaration every known channel is read from its own key
+        let readings = extract_readings(&payload, None);
+        assert_eq!(
+            readings,
+            vec![
+                (TelemetryChannel::EnergyKwh, 1520.5),
+                (TelemetryChannel::AirPressure, 6.2),
+            ]
+        );
+
+        // A declared list restricts the channels
+        let metadata = json!({ "telemetry_channels": ["air_pressure"] });
+        let readings = extract_readings(&payload, Some(&metadata));
+        assert_eq!(readings, vec![(TelemetryChannel::AirPressure, 6.2)]);
+
+        // Declared pointers locate nested values; unknown channels are ignored
+        let metadata = json!({
+            "telemetry_channels": { "energy_kwh": "/meters/total_kwh", "spindle_rpm": "/rpm" }
+        });
+        let readings = extract_readings(&payload, Some(&metadata));
+        assert_eq!(readings, vec![(TelemetryChannel::EnergyKwh, 1600.0)]);
+    }
+
+    #[test]
+    fn test_telemetry_bucket_seconds() {
+        use chrono::{Duration, TimeZone};
+        use ems_server::utils::telemetry::{bucket_seconds, MIN_BUCKET_SECONDS};
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 6, 0, 0, 0).unwrap();
+
+        // A one hour window is served at the finest resolution
+        assert_eq!(
+            bucket_seconds(from, from + Duration::hours(1), None),
+            MIN_BUCKET_SECONDS
+        );
+        // A requested bucket is kept when it stays within the point limit
+        assert_eq!(
+            bucket_seconds(from, from + Duration::hours(24), Some(900)),
+            900
+        );
+        // A 30 day window is widened to at most 500 points
+        assert_eq!(
+            bucket_seconds(from, from + Duration::days(30), Some(60)),
+            5184
+        );
+    }
+
+    // Map View Tests
+
+    #[tokio::test]
+    async fn test_get_machine_geojson() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/geojson?status=idle&site=Plant%20North",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_machine_request_requires_coordinate_pair() {
+        use ems_server::models::CreateMachineRequest;
+        use validator::Validate;
+
+        let base = json!({
+            "name": "Field Pump 7",
+            "ip": "10.0.0.7",
+            "port": 502,
+            "protocol": "tcp"
+        });
+
+        let mut data = base.clone();
+        data["latitude"] = json!(52.52);
+        let request: CreateMachineRequest = serde_json::from_value(data).unwrap();
+        assert!(request.check().is_err());
+
+        let mut data = base.clone();
+        data["latitude"] = json!(95.0);
+        data["longitude"] = json!(13.4);
+        let request: CreateMachineRequest = serde_json::from_value(data).unwrap();
+        assert!(request.validate().is_err());
+
+        let mut data = base;
+        data["latitude"] = json!(52.52);
+        data["longitude"] = json!(13.405);
+        data["site"] = json!("Plant North");
+        let request: CreateMachineRequest = serde_json::from_value(data).unwrap();
+        assert!(request.validate().is_ok() && request.check().is_ok());
+    }
+
+    #[test]
+    fn test_decommission_request_check() {
+        use ems_server::models::{DecommissionMachineRequest, UpdateMachineRequest};
+        use validator::Validate;
+
+        let request: DecommissionMachineRequest = serde_json::from_value(json!({
+            "reason": "Replaced by line 4",
+            "final_meter_readings": { "cycles": 120431.0, "hours": 8812.5 }
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok() && request.check().is_ok());
+
+        let request: DecommissionMachineRequest =
+            serde_json::from_value(json!({ "reason": "" })).unwrap();
+        assert!(request.validate().is_err());
+        let request: DecommissionMachineRequest =
+            serde_json::from_value(json!({ "reason": "   " })).unwrap();
+        assert!(request.check().is_err());
+
+        let request: DecommissionMachineRequest = serde_json::from_value(json!({
+            "reason": "Scrapped",
+            "decommissioned_at": (Utc::now() + chrono::Duration::days(1)).to_rfc3339()
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        let request: DecommissionMachineRequest = serde_json::from_value(json!({
+            "reason": "Scrapped",
+            "final_meter_readings": { "": 1.0 }
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        // Decommissioning is its own workflow, never a plain status change
+        let request: UpdateMachineRequest =
+            serde_json::from_value(json!({ "status": "decommissioned" })).unwrap();
+        assert!(request.check().is_err());
+    }
+
+    #[test]
+    fn test_machine_feature_geojson() {
+        use ems_server::models::{Machine, MachineFeature, MachineFeatureCollection};
+
+        let machine = Machine {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            name: "Field Pump 7".to_string(),
+            ip: "10.0.0.7".to_string(),
+            port: 502,
+            protocol: "tcp".to_string(),
+            status: "busy".to_string(),
+            action: None,
+            payload: None,
+            last_heartbeat: None,
+            metadata: None,
+            created_at: None,
+            updated_at: None,
+            latitude: Some(52.52),
+            longitude: Some(13.405),
+            site: Some("Plant North".to_string()),
+            decommissioned_at: None,
+            decommission_reason: None,
+            decommissioned_by: None,
+            final_meter_readings: None,
+            gateway_id: None,
+        };
+
+        let unlocated = Machine {
+            latitude: None,
+            longitude: None,
+            ..machine.clone()
+        };
+        assert!(MachineFeature::from_machine(unlocated).is_none());
+
+        let feature = MachineFeature::from_machine(machine).unwrap();
+        let collection =
+            serde_json::to_value(MachineFeatureCollection::new(vec![feature])).unwrap();
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"][0]["type"], "Feature");
+        assert_eq!(collection["features"][0]["geometry"]["type"], "Point");
+        // GeoJSON positions are longitude, latitude
+        assert_eq!(
+            collection["features"][0]["geometry"]["coordinates"],
+            json!([13.405, 52.52])
+        );
+        assert_eq!(collection["features"][0]["properties"]["status"], "busy");
+        assert_eq!(
+            collection["features"][0]["properties"]["site"],
+            "Plant North"
+        );
+    }
+
+    #[test]
+    fn test_asset_pin_mode_conversion() {
+        use ems_server::models::AssetPinMode;
+
+        assert_eq!(
+            AssetPinMode::try_from("latest".to_string()),
+            Ok(AssetPinMode::Latest)
+        );
+        assert_eq!(String::from(AssetPinMode::Specific), "specific");
+        assert!(AssetPinMode::try_from("newest".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_machine_error_status() {
+        use axum::response::IntoResponse;
+        use diesel::result::{DatabaseErrorKind, Error as DieselError};
+        use ems_server::models::{AccessDenied, AccessLevel, Permission};
+        use ems_server::services::MachineError;
+        use ems_server::utils::AppError;
+
+        let status = |error: MachineError| AppError::from(error).into_response().status();
+        let database = |kind, message: &str| {
+            MachineError::Database(DieselError::DatabaseError(
+                kind,
+                Box::new(message.to_string()),
+            ))
+        };
+
+        assert_eq!(status(MachineError::NotFound), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status(MachineError::Database(DieselError::NotFound)),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(MachineError::Unavailable("holiday".to_string())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(status(MachineError::CommandNotFound), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status(MachineError::CommandClosed("succeeded".to_string())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(MachineError::Decommissioned(Uuid::new_v4().to_string())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(MachineError::Invalid("bad status".to_string())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(database(
+                DatabaseErrorKind::UniqueViolation,
+                "duplicate key value violates unique constraint"
+            )),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(database(
+                DatabaseErrorKind::CheckViolation,
+                "new row violates check constraint"
+            )),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(database(
+                DatabaseErrorKind::Unknown,
+                "new row violates row-level security policy for table \"machines\""
+            )),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(MachineError::Other(
+                AccessDenied {
+                    permission: Permission::DeleteMachine,
+                    access_level: AccessLevel::Standard,
+                }
+                .into()
+            )),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(MachineError::Other(anyhow::anyhow!("pool exhausted"))),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Fleet Summary Tests
+
+    #[tokio::test]
+    async fn test_get_fleet_summary_route() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/summary?limit=3&stale_after_minutes=15",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fleet_summary() {
+        use ems_server::models::{HeartbeatRequest, MachineStatus, UpdateMachineRequest};
+        use ems_server::services::{MachineService, TenantService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "fleet", "subdomain": format!("fleet-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let machines = MachineService::new(database.clone());
+        let create = |name: &str, protocol: &str, status: &str, category: Option<&str>| {
+            let request = serde_json::from_value(json!({
+                "name": name,
+                "ip": "10.0.0.40",
+                "port": 502,
+                "protocol": protocol,
+                "status": status,
+                "metadata": category.map(|c| json!({ "category": c }))
+            }))
+            .unwrap();
+            let machines = &machines;
+            async move {
+                machines
+                    .create_machine(tenant_id, request)
+                    .await
+                    .unwrap()
+                    .id
+            }
+        };
+        let press = create("Press", "tcp", "idle", Some("production")).await;
+        let lathe = create("Lathe", "tcp", "busy", Some("production")).await;
+        let scale = create("Scale", "http", "idle", Some("qa")).await;
+        create("Spare", "mqtt", "offline", None).await;
+
+        // The press faulted and recovered; the lathe faulted after it
+        let set_status = |id: Uuid, status: &str| {
+            let request: UpdateMachineRequest =
+                serde_json::from_value(json!({ "status": status })).unwrap();
+            let machines = &machines;
+            async move {
+                machines
+                    .update_machine(tenant_id, id, request)
+                    .await
+                    .unwrap()
+            }
+        };
+        set_status(press, "error").await;
+        set_status(press, "idle").await;
+        set_status(lathe, "error").await;
+
+        // Only the scale and the press are left reporting without a heartbeat
+        machines
+            .update_heartbeat(
+                tenant_id,
+                scale,
+                HeartbeatRequest {
+                    status: MachineStatus::Idle,
+                    action: None,
+                    payload: None,
+                    // Heartbeats replace the metadata, so the category is sent again
+                    metadata: Some(json!({ "category": "qa" })),
+                    config_version: None,
+                    command_results: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        let summary = machines.get_fleet_summary(tenant_id, 5, 10).await.unwrap();
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.by_status.get("idle"), Some(&2));
+        assert_eq!(summary.by_status.get("error"), Some(&1));
+        assert_eq!(summary.by_status.get("offline"), Some(&1));
+        assert_eq!(summary.by_protocol.get("tcp"), Some(&2));
+        assert_eq!(summary.by_category.get("production"), Some(&2));
+        assert_eq!(summary.by_category.get("uncategorized"), Some(&1));
+
+        let faulted: Vec<Uuid> = summary.recently_faulted.iter().map(|m| m.id).collect();
+        assert_eq!(faulted, vec![lathe, press]);
+        assert_eq!(summary.recently_faulted[1].status, MachineStatus::Idle);
+
+        assert_eq!(summary.stale_heartbeat_count, 1);
+        assert_eq!(summary.stale_heartbeats[0].id, press);
+        assert!(summary.stale_heartbeats[0].last_heartbeat.is_none());
+
+        let limited = machines.get_fleet_summary(tenant_id, 1, 10).await.unwrap();
+        assert_eq!(limited.recently_faulted.len(), 1);
+        assert_eq!(limited.recently_faulted[0].id, lathe);
+
+        let empty = machines
+            .get_fleet_summary(Uuid::new_v4(), 5, 10)
+            .await
+            .unwrap();
+        assert_eq!(empty.total, 0);
+        assert!(empty.by_status.is_empty() && empty.recently_faulted.is_empty());
+    }
+
+    // Alert Rule Tests
+
+    #[tokio::test]
+    async fn test_create_alert_rule_route() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/alert-rules",
+            Some(json!({
+                "name": "Overheating",
+                "conditions": [
+                    { "type": "status", "status": "error", "for_minutes": 5 },
+                    { "type": "telemetry", "channel": "temperature", "operator": "gt", "value": 80 }
+                ],
+                "actions": [{ "type": "webhook", "url": "https://hooks.example.com/alerts" }]
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_alert_rule_requires_admin_access() {
+        use axum::Extension;
+        use ems_server::{
+            middleware::tenant::TenantContext,
+            models::{AccessLevel, CallerContext},
+        };
+
+        let app = app()
+            .await
+            .layer(Extension(CallerContext {
+                person_id: Uuid::new_v4(),
+                access_level: AccessLevel::Standard,
+            }))
+            .layer(Extension(TenantContext {
+                tenant_id: Uuid::new_v4(),
+            }));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/alert-rules")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "name": "Overheating",
+                    "conditions": [{ "type": "status", "status": "error", "for_minutes": 5 }],
+                    "actions": [{ "type": "webhook", "url": "https://hooks.example.com/alerts" }]
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            "Access denied: standard access cannot manage alert rules"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_machine_alerts_route() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/alerts?machine_id={}&open=true", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_check_alert_rule() {
+        use ems_server::models::{check_alert_rule, AlertAction, AlertCondition};
+
+        let conditions: Vec<AlertCondition> = serde_json::from_value(json!([
+            { "type": "status", "status": "error", "for_minutes": 5 }
+        ]))
+        .unwrap();
+        let actions: Vec<AlertAction> = serde_json::from_value(json!([
+            { "type": "notify", "recipients": ["ops@example.com"] },
+            { "type": "create_maintenance_order", "priority": "urgent" }
+        ]))
+        .unwrap();
+        assert!(check_alert_rule(&conditions, &actions).is_ok());
+
+        // A rule needs a condition to fire on
+        assert!(check_alert_rule(&[], &actions).is_err());
+
+        // Held conditions need a duration within a day
+        let held_too_long: Vec<AlertCondition> = serde_json::from_value(json!([
+            { "type": "status", "status": "error", "for_minutes": 2000 }
+        ]))
+        .unwrap();
+        assert!(check_alert_rule(&held_too_long, &actions).is_err());
+
+        // Anomaly baselines cover at least a few minutes and at most a day
+        let anomaly = |baseline_minutes: u32| -> Vec<AlertCondition> {
+            serde_json::from_value(json!([
+                { "type": "anomaly", "channel": "temperature", "baseline_minutes": baseline_minutes }
+            ]))
+            .unwrap()
+        };
+        assert!(check_alert_rule(&anomaly(60), &actions).is_ok());
+        assert!(check_alert_rule(&anomaly(1), &actions).is_err());
+        assert!(check_alert_rule(&anomaly(2000), &actions).is_err());
+
+        let bad_actions: Vec<AlertAction> = serde_json::from_value(json!([
+            { "type": "webhook", "url": "ftp://hooks.example.com" }
+        ]))
+        .unwrap();
+        assert!(check_alert_rule(&conditions, &bad_actions).is_err());
+
+        // Webhooks only go to public hosts
+        let webhook = |url: &str| -> Vec<AlertAction> {
+            serde_json::from_value(json!([{ "type": "webhook", "url": url }])).unwrap()
+        };
+        let hook = webhook("https://hooks.example.com/alerts");
+        assert!(check_alert_rule(&conditions, &hook).is_ok());
+        for url in [
+            "http://127.0.0.1/alerts",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5:8080/alerts",
+            "http://localhost/alerts",
+            "http://[::1]/alerts",
+        ] {
+            let err = check_alert_rule(&conditions, &webhook(url)).unwrap_err();
+            assert!(err.contains("Invalid webhook URL"), "{}: {}", url, err);
+        }
+
+        let bad_recipients: Vec<AlertAction> = serde_json::from_value(json!([
+            { "type": "notify", "recipients": ["operations"] }
+        ]))
+        .unwrap();
+        assert!(check_alert_rule(&conditions, &bad_recipients).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_alert_conditions() {
+        use chrono::{Duration, TimeZone};
+        use ems_server::{
+            models::{AlertCondition, ComparisonOperator, MachineStatus, TelemetryChannel},
+            utils::machine_alert::{evaluate_conditions, HeartbeatSnapshot},
+        };
+        use std::collections::HashMap;
+
+        let at = Utc.with_ymd_and_hms(2025, 3, 3, 10, 0, 0).unwrap();
+        let minutes_ago = |m: i64| at - Duration::minutes(m);
+        let snapshot = HeartbeatSnapshot {
+            status: MachineStatus::Error,
+            status_since: minutes_ago(7),
+            at,
+            readings: HashMap::from([(
+                TelemetryChannel::Temperature,
+                vec![
+                    (minutes_ago(12), 75.0),
+                    (minutes_ago(8), 82.0),
+                    (minutes_ago(4), 85.5),
+                    (at, 83.0),
+                ],
+            )]),
+            anomaly_sensitivity: 3.0,
+        };
+
+        let faulted = |for_minutes| AlertCondition::Status {
+            status: MachineStatus::Error,
+            for_minutes,
+        };
+        let hot = |for_minutes| AlertCondition::Telemetry {
+            channel: TelemetryChannel::Temperature,
+            operator: ComparisonOperator::Gt,
+            value: 80.0,
+            for_minutes,
+        };
+
+        // Faulted for 7 minutes, above 80 since 8 minutes ago
+        let (fired, observations) = evaluate_conditions(&[faulted(Some(5)), hot(None)], &snapshot);
+        assert!(fired);
+        assert_eq!(observations.len(), 2);
+        assert!(observations.iter().all(|o| o.matched));
+
+        // Every condition must hold for the rule to fire
+        let (fired, observations) =
+            evaluate_conditions(&[faulted(Some(10)), hot(Some(5))], &snapshot);
+        assert!(!fired);
+        assert!(!observations[0].matched);
+        assert!(observations[1].matched);
+
+        // The breach started with the 82.0 reading, so it has not held for 10 minutes
+        let (fired, _) = evaluate_conditions(&[hot(Some(10))], &snapshot);
+        assert!(!fired);
+
+        // Channels without readings never match
+        let (fired, observations) = evaluate_conditions(
+            &[AlertCondition::Telemetry {
+                channel: TelemetryChannel::CoolantLevel,
+                operator: ComparisonOperator::Lt,
+                value: 20.0,
+                for_minutes: None,
+            }],
+            &snapshot,
+        );
+        assert!(!fired);
+        assert_eq!(observations[0].detail, "No coolant_level reading");
+    }
+
+    #[test]
+    fn test_telemetry_anomaly_score() {
+        use chrono::{Duration, TimeZone};
+        use ems_server::{
+            models::{AlertCondition, MachineStatus, TelemetryChannel},
+            utils::machine_alert::{
+                anomaly_score, anomaly_sensitivity, check_anomaly_settings, evaluate_conditions,
+                HeartbeatSnapshot, DEFAULT_ANOMALY_SENSITIVITY,
+            },
+        };
+        use std::collections::HashMap;
+
+        let at = Utc.with_ymd_and_hms(2025, 3, 3, 10, 0, 0).unwrap();
+        // A temperature alternating between 70 and 72 every minute for the last half hour
+        let mut readings: Vec<_> = (1..=30)
+            .rev()
+            .map(|m| {
+                let value = if m % 2 == 0 { 70.0 } else { 72.0 };
+                (at - Duration::minutes(m), value)
+            })
+            .collect();
+        let with_latest = |readings: &Vec<_>, value| {
+            let mut readings = readings.clone();
+            readings.push((at, value));
+            readings
+        };
+
+        // Mean 71 with a standard deviation of just over 1
+        let score = anomaly_score(&with_latest(&readings, 76.0), 60).unwrap();
+        assert_eq!(score.recorded_at, at);
+        assert!((score.mean - 71.0).abs() < 1e-9);
+        assert!(score.z_score > 4.8 && score.z_score < 5.0);
+        assert!(score.is_anomaly(3.0));
+        assert!(!score.is_anomaly(5.0));
+        let below = anomaly_score(&with_latest(&readings, 66.0), 60).unwrap();
+        assert!(below.z_score < -4.8);
+        assert!(!anomaly_score(&with_latest(&readings, 71.5), 60)
+            .unwrap()
+            .is_anomaly(3.0));
+
+        // Too short a baseline, or one that never varies, scores nothing
+        assert!(anomaly_score(&with_latest(&readings, 76.0), 5).is_none());
+        assert!(anomaly_score(&[(at, 76.0)], 60).is_none());
+        let flat: Vec<_> = (1..=30)
+            .map(|m| (at - Duration::minutes(31 - m), 70.0))
+            .collect();
+        assert!(anomaly_score(&with_latest(&flat, 76.0), 60).is_none());
+
+        // Rules see the latest reading against the tenant's sensitivity
+        readings.push((at, 76.0));
+        let snapshot = |anomaly_sensitivity| HeartbeatSnapshot {
+            status: MachineStatus::Busy,
+            status_since: at - Duration::hours(2),
+            at,
+            readings: HashMap::from([(TelemetryChannel::Temperature, readings.clone())]),
+            anomaly_sensitivity,
+        };
+        let anomaly = AlertCondition::Anomaly {
+            channel: TelemetryChannel::Temperature,
+            baseline_minutes: None,
+        };
+        let (fired, observations) =
+            evaluate_conditions(std::slice::from_ref(&anomaly), &snapshot(3.0));
+        assert!(fired);
+        assert!(observations[0].detail.contains("standard deviations"));
+        assert!(!evaluate_conditions(&[anomaly], &snapshot(6.0)).0);
+        let (fired, observations) = evaluate_conditions(
+            &[AlertCondition::Anomaly {
+                channel: TelemetryChannel::AirPressure,
+                baseline_minutes: Some(30),
+            }],
+            &snapshot(3.0),
+        );
+        assert!(!fired);
+        assert_eq!(
+            observations[0].detail,
+            "Not enough air_pressure readings for a baseline"
+        );
+
+        // Tenants may set their own sensitivity
+        assert_eq!(anomaly_sensitivity(None), DEFAULT_ANOMALY_SENSITIVITY);
+        assert_eq!(
+            anomaly_sensitivity(Some(&json!({ "anomaly_sensitivity": 2.5 }))),
+            2.5
+        );
+        assert!(check_anomaly_settings(Some(&json!({ "anomaly_sensitivity": 4 }))).is_ok());
+        assert!(check_anomaly_settings(Some(&json!({ "locale": "de" }))).is_ok());
+        assert!(check_anomaly_settings(Some(&json!({ "anomaly_sensitivity": 0 }))).is_err());
+        assert!(check_anomaly_settings(Some(&json!({ "anomaly_sensitivity": "high" }))).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_machine_anomalies_route() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/anomalies?channel=temperature&limit=20", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_machine_alert_rules() {
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::models::{
+            AccessLevel, CallerContext, CreateMachineAlertRuleRequest, HeartbeatRequest,
+            ListMachineAlertsQuery, MachineStatus, TestMachineAlertRuleRequest,
+        };
+        use ems_server::services::{MachineAlertService, MachineAlertWorker, MachineService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let fixture = FixtureBuilder::tenant().build(&database).await.unwrap();
+        let (tenant_id, caller) = (fixture.tenant.id, fixture.caller());
+
+        let machines = MachineService::new(database.clone());
+        let oven = machines
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Oven",
+                    "ip": "10.0.0.50",
+                    "port": 502,
+                    "protocol": "tcp",
+                    "status": "idle"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let alerts = MachineAlertService::new(database.clone());
+        let request: CreateMachineAlertRuleRequest = serde_json::from_value(json!({
+            "name": "Overheating",
+            "machine_id": oven,
+            "conditions": [
+                { "type": "telemetry", "channel": "temperature", "operator": "gt", "value": 80 }
+            ],
+            "actions": [{ "type": "create_maintenance_order", "maintenance_type": "inspection" }]
+        }))
+        .unwrap();
+        assert!(request.check().is_ok());
+
+        // Standard access cannot add rules that act on the tenant's behalf
+        let standard = CallerContext {
+            access_level: AccessLevel::Standard,
+            ..caller.clone()
+        };
+        let denied = alerts
+            .create_rule(
+                tenant_id,
+                &standard,
+                serde_json::from_value(json!({
+                    "name": "Overheating",
+                    "conditions": [{ "type": "status", "status": "error" }]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(denied.to_string().starts_with("Access denied"));
+
+        let rule = alerts.create_rule(tenant_id, &caller, request).await.unwrap();
+        assert_eq!(rule.created_by_id, Some(caller.person_id));
+
+        let heartbeat = |temperature: f64| HeartbeatRequest {
+            status: MachineStatus::Busy,
+            action: None,
+            payload: Some(json!({ "temperature": temperature })),
+            metadata: None,
+            config_version: None,
+            command_results: None,
+        };
+        let worker =
+            MachineAlertWorker::new(database.clone(), None, std::time::Duration::from_secs(5));
+        let open_alerts = || {
+            alerts.list_alerts(
+                tenant_id,
+                ListMachineAlertsQuery {
+                    machine_id: Some(oven),
+                    rule_id: None,
+                    open: Some(true),
+                    limit: None,
+                },
+            )
+        };
+
+        // A cool oven raises nothing
+        machines
+            .update_heartbeat(tenant_id, oven, heartbeat(60.0))
+            .await
+            .unwrap();
+        worker.run_once().await.unwrap();
+        assert!(open_alerts().await.unwrap().is_empty());
+
+        // Overheating raises one alert and opens a maintenance job, however often it is reported
+        machines
+            .update_heartbeat(tenant_id, oven, heartbeat(92.0))
+            .await
+            .unwrap();
+        machines
+            .update_heartbeat(tenant_id, oven, heartbeat(95.0))
+            .await
+            .unwrap();
+        worker.run_once().await.unwrap();
+        let open = open_alerts().await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].rule_id, rule.id);
+        assert!(open[0].observations[0].matched);
+        assert_eq!(open[0].action_results.len(), 1);
+        assert!(open[0].action_results[0].succeeded);
+
+        // Testing reports the rule would fire without raising another alert
+        let tested = alerts
+            .test_rule(
+                tenant_id,
+                rule.id,
+                TestMachineAlertRuleRequest { machine_id: None },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(tested.would_fire);
+        assert_eq!(tested.actions, vec!["create_maintenance_order".to_string()]);
+
+        // Cooling down resolves the alert
+        machines
+            .update_heartbeat(tenant_id, oven, heartbeat(70.0))
+            .await
+            .unwrap();
+        worker.run_once().await.unwrap();
+        assert!(open_alerts().await.unwrap().is_empty());
+
+        assert!(alerts.delete_rule(tenant_id, &caller, rule.id).await.unwrap());
+        assert!(alerts.get_rule(tenant_id, rule.id).await.unwrap().is_none());
+    }
+
+    // Machine Group Tests
+
+    #[tokio::test]
+    async fn test_create_machine_group_route() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = ems_server::routes::machine_group::routes().with_state(state);
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/",
+            Some(json!({
+                "name": "Line 1",
+                "members": [
+                    { "machine_id": Uuid::new_v4() },
+                    { "machine_id": Uuid::new_v4(), "is_critical": false }
+                ]
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine group routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_machine_group_utilization_route() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = ems_server::routes::machine_group::routes().with_state(state);
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!(
+                "/{}/utilization?from=2025-03-01T00:00:00Z&to=2025-03-08T00:00:00Z",
+                Uuid::new_v4()
+            ),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Machine group routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_check_machine_group_request() {
+        use ems_server::models::CreateMachineGroupRequest;
+
+        let machine = Uuid::new_v4();
+        let request: CreateMachineGroupRequest = serde_json::from_value(json!({
+            "name": "Line 1",
+            "members": [{ "machine_id": machine }, { "machine_id": Uuid::new_v4() }]
+        }))
+        .unwrap();
+        assert!(request.check().is_ok());
+
+        // A machine holds one place in a line
+        let listed_twice: CreateMachineGroupRequest = serde_json::from_value(json!({
+            "name": "Line 1",
+            "members": [{ "machine_id": machine }, { "machine_id": machine }]
+        }))
+        .unwrap();
+        assert!(listed_twice.check().is_err());
+    }
+
+    #[test]
+    fn test_line_status() {
+        use ems_server::{
+            models::{LineStatus, MachineStatus},
+            utils::machine_group::line_status,
+        };
+
+        assert_eq!(
+            line_status(&[(MachineStatus::Busy, true), (MachineStatus::Idle, true)]),
+            LineStatus::Running
+        );
+        assert_eq!(
+            line_status(&[(MachineStatus::Idle, true), (MachineStatus::Idle, false)]),
+            LineStatus::Idle
+        );
+        assert_eq!(
+            line_status(&[(MachineStatus::Busy, true), (MachineStatus::Error, false)]),
+            LineStatus::Degraded
+        );
+        assert_eq!(
+            line_status(&[
+                (MachineStatus::Busy, true),
+                (MachineStatus::Maintenance, true)
+            ]),
+            LineStatus::Down
+        );
+        assert_eq!(line_status(&[]), LineStatus::Idle);
+    }
+
+    #[test]
+    fn test_machine_group_status_periods() {
+        use chrono::{Duration, TimeZone};
+        use ems_server::{
+            models::MachineStatus,
+            utils::machine_group::{hours_where, status_periods, union_hours},
+        };
+
+        let from = Utc.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+        let at = |h: i64| from + Duration::hours(h);
+        let to = at(10);
+
+        // Busy since before the window, faulted from 4h to 6h, then idle
+        let transitions = vec![
+            (at(-3), MachineStatus::Busy),
+            (at(4), MachineStatus::Error),
+            (at(6), MachineStatus::Idle),
+        ];
+        let periods = status_periods(&transitions, from, to);
+        assert_eq!(periods.len(), 3);
+        assert_eq!(periods[0].0, (from, at(4)));
+        assert_eq!(hours_where(&periods, |s| *s == MachineStatus::Busy), 4.0);
+        assert_eq!(hours_where(&periods, MachineStatus::is_down), 2.0);
+        assert_eq!(hours_where(&periods, |s| *s == MachineStatus::Idle), 4.0);
+
+        // Time before the first recorded status is not counted
+        let late = status_periods(&[(at(8), MachineStatus::Busy)], from, to);
+        assert_eq!(late, vec![((at(8), to), MachineStatus::Busy)]);
+
+        // Overlapping outages count once
+        assert_eq!(
+            union_hours(vec![(at(1), at(3)), (at(2), at(5)), (at(7), at(8))]),
+            5.0
+        );
+        assert_eq!(union_hours(Vec::new()), 0.0);
+    }
+
+    #[test]
+    fn test_status_page_token() {
+        use ems_server::utils::status_page::{
+            status_page_token, status_token_prefix, status_token_tenant,
+        };
+
+        let tenant_id = Uuid::new_v4();
+        let token = status_page_token(tenant_id);
+        assert_eq!(status_token_tenant(&token), Some(tenant_id));
+        assert_ne!(token, status_page_token(tenant_id));
+
+        // The prefix comes from the secret part, which differs between tokens
+        let prefix = status_token_prefix(&token);
+        assert_eq!(prefix.len(), 8);
+        assert!(token.split_once('.').unwrap().1.starts_with(&prefix));
+
+        assert_eq!(status_token_tenant("not-a-token"), None);
+        assert_eq!(
+            status_token_tenant(&format!("{}.abc", tenant_id.simple())),
+            None
+        );
+        assert_eq!(
+            status_token_tenant(&format!("nope.{}", "a".repeat(64))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_public_status_page() {
+        use chrono::{Duration, TimeZone};
+        use ems_server::{
+            models::{LineStatus, MachineAvailability, MachineStatus, StatusPage},
+            utils::status_page::{
+                machine_availability, public_status_page, LineState, MachineState, PlannedDowntime,
+            },
+        };
+        use std::collections::HashMap;
+
+        assert_eq!(
+            machine_availability(&MachineStatus::Busy, true),
+            MachineAvailability::Operational
+        );
+        assert_eq!(
+            machine_availability(&MachineStatus::Error, true),
+            MachineAvailability::Maintenance
+        );
+        assert_eq!(
+            machine_availability(&MachineStatus::Error, false),
+            MachineAvailability::Unavailable
+        );
+
+        let now = Utc.with_ymd_and_hms(2025, 3, 3, 12, 0, 0).unwrap();
+        let stale_before = now - Duration::minutes(10);
+        let (press, lathe, oven, retired, hidden) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let machine = |name: &str, status: MachineStatus, minutes_ago: i64| MachineState {
+            name: name.to_string(),
+            status,
+            last_heartbeat: Some(now - Duration::minutes(minutes_ago)),
+        };
+        let machines: HashMap<Uuid, MachineState> = [
+            (press, machine("Press", MachineStatus::Busy, 1)),
+            // Reports itself idle but has gone quiet
+            (lathe, machine("Lathe", MachineStatus::Idle, 30)),
+            (oven, machine("Oven", MachineStatus::Offline, 60)),
+            (
+                retired,
+                machine("Old press", MachineStatus::Decommissioned, 600),
+            ),
+            (hidden, machine("Prototype cell", MachineStatus::Busy, 1)),
+        ]
+        .into_iter()
+        .collect();
+
+        let line_id = Uuid::new_v4();
+        let lines = vec![LineState {
+            id: line_id,
+            name: "Line 1".to_string(),
+            members: vec![(press, true), (hidden, false)],
+        }];
+        let window = |machine_id: Option<Uuid>, from_h: i64, to_h: i64| PlannedDowntime {
+            machine_id,
+            starts_at: now + Duration::hours(from_h),
+            ends_at: now + Duration::hours(to_h),
+        };
+        let downtime = vec![
+            window(Some(hidden), 48, 50),
+            window(None, 72, 80),
+            window(Some(oven), -1, 2),
+            window(Some(Uuid::new_v4()), 5, 6),
+        ];
+
+        let page = StatusPage {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            enabled: true,
+            title: "Plant status".to_string(),
+            description: None,
+            machine_ids: vec![oven, press, lathe, retired, Uuid::new_v4()],
+            group_ids: vec![line_id, Uuid::new_v4()],
+            show_maintenance: true,
+            maintenance_days: 14,
+            token_prefix: None,
+            token_hash: None,
+            token_issued_at: None,
+            updated_by_id: None,
+            created_at: None,
+            updated_at: None,
+        };
+        let view = public_status_page(&page, &machines, &lines, &downtime, now, stale_before);
+
+        // Shown machines in page order; retired and deleted ones are left out
+        let shown: Vec<(&str, MachineAvailability)> = view
+            .machines
+            .iter()
+            .map(|m| (m.name.as_str(), m.status))
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                ("Oven", MachineAvailability::Maintenance),
+                ("Press", MachineAvailability::Operational),
+                ("Lathe", MachineAvailability::Unavailable),
+            ]
+        );
+
+        // Lines count every member, but do not name the ones not shown
+        assert_eq!(view.lines.len(), 1);
+        assert_eq!(view.lines[0].status, LineStatus::Running);
+
+        assert_eq!(view.maintenance.len(), 3);
+        assert_eq!(view.maintenance[0].machine.as_deref(), Some("Oven"));
+        assert!(view.maintenance[0].in_progress);
+        assert_eq!(view.maintenance[1].machine, None);
+        assert_eq!(view.maintenance[1].lines, vec!["Line 1".to_string()]);
+        assert!(view.maintenance[2].site_wide);
+        assert!(!view.maintenance[2].in_progress);
+
+        let quiet = StatusPage {
+            show_maintenance: false,
+            ..page
+        };
+        let view = public_status_page(&quiet, &machines, &lines, &downtime, now, stale_before);
+        assert!(view.maintenance.is_empty());
+        assert_eq!(view.machines.len(), 3);
+    }
+
+    // Machine Command Tests
+
+    #[test]
+    fn test_heartbeat_backoff() {
+        use ems_server::utils::machine_command::{
+            backoff_factor, reporting_interval, MAX_REPORTING_INTERVAL_SECONDS,
+        };
+
+        // Backs off once three quarters of the pool is busy, more once all of it is
+        assert_eq!(backoff_factor(0, 10), 1);
+        assert_eq!(backoff_factor(7, 10), 1);
+        assert_eq!(backoff_factor(8, 10), 2);
+        assert_eq!(backoff_factor(10, 10), 4);
+        assert_eq!(backoff_factor(0, 0), 4);
+
+        assert_eq!(reporting_interval(30, 1), 30);
+        assert_eq!(reporting_interval(30, 4), 120);
+        // Capped, unless the machine's own interval is already longer
+        assert_eq!(reporting_interval(1800, 4), MAX_REPORTING_INTERVAL_SECONDS);
+        assert_eq!(reporting_interval(7200, 2), 7200);
+    }
+
+    #[test]
+    fn test_heartbeat_queue() {
+        use chrono::Duration;
+        use ems_server::models::{HeartbeatRequest, MachineStatus, NewMachineTelemetry};
+        use ems_server::utils::heartbeat_buffer::{BufferedMachineState, HeartbeatQueue};
+
+        let heartbeat = |status| HeartbeatRequest {
+            status,
+            action: None,
+            payload: Some(json!({ "spindle_load": 42.0 })),
+            metadata: None,
+            config_version: None,
+            command_results: None,
+        };
+        let readings = |machine_id, count| {
+            (0..count)
+                .map(|i| NewMachineTelemetry {
+                    tenant_id: Uuid::new_v4(),
+                    machine_id,
+                    channel: "spindle_load".to_string(),
+                    value: i as f64,
+                    recorded_at: Utc::now(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut queue = HeartbeatQueue::new(4);
+        let machine_id = Uuid::new_v4();
+        let now = Utc::now();
+        assert!(queue.is_empty());
+
+        // A machine reporting again before the flush keeps only its newest state
+        let running = BufferedMachineState::new(machine_id, &heartbeat(MachineStatus::Busy), now);
+        assert_eq!(running.status, "busy");
+        assert!(queue.push_state(running));
+        assert!(queue.push_state(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Idle),
+            now + Duration::seconds(1)
+        )));
+        assert!(queue.push_state(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Maintenance),
+            now - Duration::seconds(1)
+        )));
+        assert_eq!(queue.len(), 1);
+
+        // Readings only go in when they all fit
+        assert!(queue.push_readings(readings(machine_id, 2)).is_ok());
+        assert_eq!(queue.backoff_factor(), 2);
+        assert_eq!(
+            queue
+                .push_readings(readings(machine_id, 2))
+                .unwrap_err()
+                .len(),
+            2
+        );
+        assert!(queue.push_readings(readings(machine_id, 1)).is_ok());
+        assert_eq!(queue.backoff_factor(), 4);
+
+        // Once full, only machines already queued are taken
+        assert!(!queue.push_state(BufferedMachineState::new(
+            Uuid::new_v4(),
+            &heartbeat(MachineStatus::Idle),
+            now
+        )));
+        assert!(queue.push_state(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Idle),
+            now + Duration::seconds(2)
+        )));
+
+        // Heartbeats queued for processing each take an entry
+        assert!(!queue.push_heartbeat(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Idle),
+            now
+        )));
+
+        let batch = queue.take();
+        assert_eq!(batch.states.len(), 1);
+        assert_eq!(batch.states[0].status, "idle");
+        assert_eq!(batch.states[0].last_heartbeat, now + Duration::seconds(2));
+        assert_eq!(batch.readings.len(), 3);
+        assert!(batch.heartbeats.is_empty());
+        assert!(queue.is_empty());
+        assert_eq!(queue.backoff_factor(), 1);
+
+        assert!(queue.push_heartbeat(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Busy),
+            now
+        )));
+        assert!(queue.push_heartbeat(BufferedMachineState::new(
+            machine_id,
+            &heartbeat(MachineStatus::Idle),
+            now + Duration::seconds(1)
+        )));
+        assert_eq!(queue.len(), 2);
+        let batch = queue.take();
+        assert!(batch.states.is_empty());
+        let statuses: Vec<&str> = batch.heartbeats.iter().map(|h| h.status.as_str()).collect();
+        assert_eq!(statuses, vec!["busy", "idle"]);
+    }
+
+    #[test]
+    fn test_machine_command_status() {
+        use ems_server::models::{HeartbeatRequest, MachineCommandStatus};
+
+        assert!(MachineCommandStatus::Pending.is_open());
+        assert!(MachineCommandStatus::Delivered.is_open());
+        assert!(!MachineCommandStatus::Succeeded.is_open());
+        assert!(!MachineCommandStatus::Expired.is_open());
+        assert_eq!(
+            MachineCommandStatus::try_from("cancelled".to_string()),
+            Ok(MachineCommandStatus::Cancelled)
+        );
+        assert_eq!(
+            MachineCommandStatus::try_from("queued".to_string()),
+            Err("Invalid machine command status: queued".to_string())
+        );
+
+        // Heartbeats from machines that know nothing of commands still parse
+        let heartbeat: HeartbeatRequest =
+            serde_json::from_value(json!({ "status": "idle" })).unwrap();
+        assert!(heartbeat.config_version.is_none());
+        assert!(heartbeat.command_results.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_machine_commands() {
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::models::{
+            AccessLevel, CallerContext, MachineCommandResult, MachineCommandStatus,
+        };
+        use ems_server::services::{MachineCommandService, MachineError};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let fixture = FixtureBuilder::tenant()
+            .with_machines(1)
+            .build(&database)
+            .await
+            .unwrap();
+        let (tenant_id, machine_id, caller) =
+            (fixture.tenant.id, fixture.machine_ids[0], fixture.caller());
+        let commands = MachineCommandService::new(database.clone());
+
+        // Standard access cannot command machines
+        let standard = CallerContext {
+            access_level: AccessLevel::Standard,
+            ..caller.clone()
+        };
+        let request = |command: &str| {
+            serde_json::from_value(json!({ "command": command, "parameters": { "delay": 5 } }))
+                .unwrap()
+        };
+        assert!(commands
+            .queue_command(tenant_id, &standard, machine_id, request("reboot"))
+            .await
+            .is_err());
+
+        let reboot = commands
+            .queue_command(tenant_id, &caller, machine_id, request("reboot"))
+            .await
+            .unwrap();
+        let calibrate = commands
+            .queue_command(tenant_id, &caller, machine_id, request("calibrate"))
+            .await
+            .unwrap();
+        assert_eq!(reboot.status, MachineCommandStatus::Pending);
+
+        commands
+            .put_config(
+                tenant_id,
+                &caller,
+                machine_id,
+                serde_json::from_value(json!({
+                    "config": { "sample_rate": 10 },
+                    "reporting_interval_seconds": 60
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Open commands go out oldest first with the config the machine does not run yet
+        let response = commands
+            .heartbeat_response(tenant_id, machine_id, None, Vec::new())
+            .await
+            .unwrap();
+        let sent: Vec<_> = response.commands.iter().map(|c| c.id).collect();
+        assert_eq!(sent, [reboot.id, calibrate.id]);
+        assert_eq!(response.config.as_ref().unwrap().version, 1);
+        assert!(response.reporting_interval_seconds >= 60);
+
+        // The machine reports the reboot and runs the config; the calibration is sent again
+        let response = commands
+            .heartbeat_response(
+                tenant_id,
+                machine_id,
+                Some(1),
+                vec![MachineCommandResult {
+                    id: reboot.id,
+                    succeeded: true,
+                    result: Some(json!({ "uptime": 0 })),
+                }],
+            )
+            .await
+            .unwrap();
+        let sent: Vec<_> = response.commands.iter().map(|c| c.id).collect();
+        assert_eq!(sent, [calibrate.id]);
+        assert!(response.config.is_none());
+
+        let listed = commands
+            .list_commands(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = |id| listed.iter().find(|c| c.id == id).unwrap();
+        assert_eq!(status(reboot.id).status, MachineCommandStatus::Succeeded);
+        assert_eq!(status(calibrate.id).status, MachineCommandStatus::Delivered);
+        assert_eq!(status(calibrate.id).delivery_count, 2);
+
+        // Closed commands cannot be cancelled
+        assert!(matches!(
+            commands
+                .cancel_command(tenant_id, &caller, machine_id, reboot.id)
+                .await,
+            Err(MachineError::CommandClosed(_))
+        ));
+        let cancelled = commands
+            .cancel_command(tenant_id, &caller, machine_id, calibrate.id)
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, MachineCommandStatus::Cancelled);
+        let response = commands
+            .heartbeat_response(tenant_id, machine_id, Some(1), Vec::new())
+            .await
+            .unwrap();
+        assert!(response.commands.is_empty());
+
+        // Unknown machines get no answer
+        assert!(matches!(
+            commands
+                .heartbeat_response(tenant_id, Uuid::new_v4(), None, Vec::new())
+                .await,
+            Err(MachineError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_production_counters() {
+        use ems_server::utils::production::{
+            counter_delta, declared_counters, read_counters, target_met, CounterMode,
+        };
+
+        // Machines report no production unless they declare counters
+        assert_eq!(declared_counters(None), None);
+        assert_eq!(
+            declared_counters(Some(&json!({ "production_counters": false }))),
+            None
+        );
+
+        let counters = declared_counters(Some(&json!({ "production_counters": true }))).unwrap();
+        assert_eq!(counters.mode, CounterMode::Increment);
+        let reading = read_counters(&json!({ "produced": 3, "scrap": "1" }), &counters);
+        assert_eq!(reading.produced, Some(3));
+        assert_eq!(reading.scrap, Some(1));
+
+        // Declared pointers locate nested totals; negative counts are skipped
+        let counters = declared_counters(Some(&json!({
+            "production_counters": { "produced": "/counters/good", "mode": "total" }
+        })))
+        .unwrap();
+        assert_eq!(counters.mode, CounterMode::Total);
+        let reading = read_counters(
+            &json!({ "counters": { "good": 120.0 }, "scrap": -2 }),
+            &counters,
+        );
+        assert_eq!(reading.produced, Some(120));
+        assert_eq!(reading.scrap, None);
+
+        // Increments count as they are; totals count from the last one and survive resets
+        assert_eq!(counter_delta(CounterMode::Increment, 5, Some(100)), 5);
+        assert_eq!(counter_delta(CounterMode::Total, 120, None), 0);
+        assert_eq!(counter_delta(CounterMode::Total, 125, Some(120)), 5);
+        assert_eq!(counter_delta(CounterMode::Total, 4, Some(125)), 4);
+
+        assert!(target_met(10, Some(10)));
+        assert!(!target_met(9, Some(10)));
+        assert!(!target_met(10, None));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_production_counts() {
+        use ems_server::models::{
+            HeartbeatRequest, ItemContext, JobAssignmentStatus, MachineStatus, Quantity,
+            StockReferenceType,
+        };
+        use ems_server::services::{
+            ItemService, JobService, MachineService, StockService, TenantService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "production", "subdomain": format!("production-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let board = ItemService::new(database.clone())
+            .create_item(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "internal_part_number": format!("PCB-{}", suffix),
+                    "manufacturer": "Acme",
+                    "context": "finished_goods",
+                    "quantity": 0
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let job_id = JobService::new(database.clone())
+            .create_job(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "job_number": format!("MFG-{}", suffix),
+                    "item_id": board,
+                    "quantity": 10,
+                    "job_type": "manufacturing"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let machines = MachineService::new(database.clone());
+        let machine_id = machines
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Pick and place", "ip": "10.0.0.60", "port": 502, "protocol": "tcp"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        machines
+            .create_machine_job_assignment(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({ "job_id": job_id, "target_quantity": 8 })).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let heartbeat = |good: i64, bad: i64| HeartbeatRequest {
+            status: MachineStatus::Busy,
+            action: None,
+            payload: Some(json!({ "counters": { "good": good, "bad": bad } })),
+            metadata: Some(json!({
+                "production_counters": {
+                    "produced": "/counters/good",
+                    "scrap": "/counters/bad",
+                    "mode": "total"
+                }
+            })),
+            config_version: None,
+            command_results: None,
+        };
+        let assignment = || async {
+            machines
+                .list_machine_job_assignments(tenant_id, machine_id)
+                .await
+                .unwrap()
+                .remove(0)
+        };
+
+        // The first totals set the baseline; the pending assignment is not started yet
+        machines
+            .update_heartbeat(tenant_id, machine_id, heartbeat(1000, 40))
+            .await
+            .unwrap();
+        let current = assignment().await;
+        assert_eq!(current.status, JobAssignmentStatus::Pending);
+        assert_eq!((current.produced_quantity, current.scrap_quantity), (0, 0));
+
+        // Parts made since start the assignment and go into finished goods
+        machines
+            .update_heartbeat(tenant_id, machine_id, heartbeat(1005, 41))
+            .await
+            .unwrap();
+        let current = assignment().await;
+        assert_eq!(current.status, JobAssignmentStatus::InProgress);
+        assert!(current.start_time.is_some());
+        assert_eq!((current.produced_quantity, current.scrap_quantity), (5, 1));
+
+        // A counter reset counts the whole new total; the target completes the assignment
+        machines
+            .update_heartbeat(tenant_id, machine_id, heartbeat(3, 0))
+            .await
+            .unwrap();
+        let current = assignment().await;
+        assert_eq!(current.status, JobAssignmentStatus::Completed);
+        assert!(current.end_time.is_some());
+        assert_eq!((current.produced_quantity, current.scrap_quantity), (8, 1));
+
+        let movements = StockService::new(database.clone())
+            .list_movements(
+                tenant_id,
+                board,
+                Some(ItemContext::FinishedGoods),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let received: Vec<_> = movements
+            .iter()
+            .filter(|movement| movement.reference_type == Some(StockReferenceType::Job))
+            .filter(|movement| movement.reference_id == Some(job_id))
+            .map(|movement| movement.quantity)
+            .collect();
+        assert_eq!(received.len(), 2);
+        assert_eq!(
+            received
+                .into_iter()
+                .fold(Quantity::from(0), |total, quantity| total + quantity),
+            Quantity::from(8)
+        );
+
+        // With the assignment complete, further parts are not counted against it
+        machines
+            .update_heartbeat(tenant_id, machine_id, heartbeat(10, 0))
+            .await
+            .unwrap();
+        assert_eq!(assignment().await.produced_quantity, 8);
+    }
+
+    // Decommissioning Tests
+
+    #[tokio::test]
+    async fn test_decommission_machine() {
+        use ems_server::models::{AccessLevel, CallerContext, HeartbeatRequest, MachineStatus};
+        use ems_server::services::{MachineError, MachineService, PersonService, TenantService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "decommission", "subdomain": format!("decommission-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let operator = PersonService::new(database.clone())
+            .create_person(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Operator",
+                    "email": format!("operator-{}@example.com", suffix),
+                    "role": "internal",
+                    "person_type": "internal"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let machines = MachineService::new(database.clone());
+        let machine_id = machines
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Wave solder", "ip": "10.0.0.90", "port": 502, "protocol": "tcp"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        machines
+            .create_machine_operator_assignment(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(
+                    json!({ "person_id": operator, "assignment_type": "primary" }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let caller = |access_level| CallerContext {
+            person_id: operator,
+            access_level,
+        };
+        let request = || {
+            serde_json::from_value(json!({
+                "reason": "Replaced by selective solder",
+                "final_meter_readings": { "cycles": 48210.0 }
+            }))
+            .unwrap()
+        };
+        assert!(machines
+            .decommission_machine(
+                tenant_id,
+                &caller(AccessLevel::Standard),
+                machine_id,
+                request()
+            )
+            .await
+            .is_err());
+
+        let decommissioned = machines
+            .decommission_machine(
+                tenant_id,
+                &caller(AccessLevel::Admin),
+                machine_id,
+                request(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(decommissioned.machine.status, MachineStatus::Decommissioned);
+        assert_eq!(decommissioned.detached_operators, 1);
+        assert_eq!(decommissioned.closed_assignments, 0);
+        assert_eq!(decommissioned.machine.decommissioned_by, Some(operator));
+        assert_eq!(
+            decommissioned.machine.final_meter_readings,
+            Some(json!({ "cycles": 48210.0 }))
+        );
+
+        // The machine stays queryable but is frozen
+        assert!(machines
+            .get_machine_by_id(tenant_id, machine_id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(machines
+            .list_machines(tenant_id, None, None, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        let heartbeat = machines
+            .update_heartbeat(
+                tenant_id,
+                machine_id,
+                HeartbeatRequest {
+                    status: MachineStatus::Idle,
+                    action: None,
+                    payload: None,
+                    metadata: None,
+                    config_version: None,
+                    command_results: None,
+                },
+            )
+            .await;
+        assert!(matches!(heartbeat, Err(MachineError::Decommissioned(_))));
+        let update = machines
+            .update_machine(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({ "status": "idle" })).unwrap(),
+            )
+            .await;
+        assert!(matches!(update, Err(MachineError::Decommissioned(_))));
+        assert!(matches!(
+            machines
+                .decommission_machine(
+                    tenant_id,
+                    &caller(AccessLevel::Admin),
+                    machine_id,
+                    request()
+                )
+                .await,
+            Err(MachineError::Decommissioned(_))
+        ));
+    }
 }
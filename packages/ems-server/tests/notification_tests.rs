@@ -0,0 +1,298 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use chrono::Utc;
+    use dotenv::dotenv;
+    use serde_json::json;
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::models::{
+        CommentResponse, EntityComment, ListNotificationsQuery, Notification,
+        NotificationEventType, NotificationResponse, WatchEntityType,
+    };
+    use ems_server::services::DatabaseService;
+    use ems_server::{routes, AppState};
+
+    #[test]
+    fn test_watch_entity_type_conversion() {
+        for entity_type in [
+            WatchEntityType::Machine,
+            WatchEntityType::Order,
+            WatchEntityType::Item,
+        ] {
+            let value: String = entity_type.into();
+            assert_eq!(WatchEntityType::try_from(value).unwrap(), entity_type);
+        }
+        assert!(WatchEntityType::try_from("vendor".to_string()).is_err());
+        assert_eq!(
+            serde_json::to_value(WatchEntityType::Order).unwrap(),
+            json!("order")
+        );
+
+        assert_eq!(
+            NotificationEventType::try_from("low_stock".to_string()).unwrap(),
+            NotificationEventType::LowStock
+        );
+        assert_eq!(
+            NotificationEventType::CommentAdded.to_string(),
+            "comment_added"
+        );
+        assert!(NotificationEventType::try_from("deleted".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_notification_responses() {
+        let machine_id = Uuid::new_v4();
+        let notification = NotificationResponse::try_from(Notification {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            person_id: Uuid::new_v4(),
+            entity_type: "machine".to_string(),
+            entity_id: machine_id,
+            event_type: "status_changed".to_string(),
+            title: "Machine Reflow oven is now error".to_string(),
+            detail: json!({ "previous_status": "busy", "status": "error" }),
+            read_at: None,
+            created_at: Utc::now(),
+        })
+        .unwrap();
+        assert_eq!(notification.entity_type, WatchEntityType::Machine);
+        assert_eq!(notification.entity_id, machine_id);
+        assert_eq!(
+            notification.event_type,
+            NotificationEventType::StatusChanged
+        );
+
+        let comment = EntityComment {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            entity_type: "shipment".to_string(),
+            entity_id: Uuid::new_v4(),
+            author_id: None,
+            body: "Left on the dock".to_string(),
+            created_at: None,
+        };
+        assert!(CommentResponse::try_from(comment).is_err());
+
+        let query: ListNotificationsQuery = serde_json::from_value(
+            json!({ "unread": true, "entity_type": "item", "event_type": "low_stock" }),
+        )
+        .unwrap();
+        assert_eq!(query.entity_type, Some(WatchEntityType::Item));
+        assert_eq!(query.event_type, Some(NotificationEventType::LowStock));
+    }
+
+    #[tokio::test]
+    async fn test_watch_routes_require_auth() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = routes::watch::routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("/machine/{}/toggle", Uuid::new_v4()))
+            .header("X-Tenant-ID", Uuid::new_v4().to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // Watching requires authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_notification_routes_require_auth() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = routes::notification::routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("/count")
+            .header("X-Tenant-ID", Uuid::new_v4().to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // Notifications require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watched_entity_notifications() {
+        use ems_server::services::{
+            MachineService, NotificationService, PersonService, TenantService, WatchService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "watches", "subdomain": format!("watches-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let people = PersonService::new(database.clone());
+        let person = |name: &'static str| {
+            let people = &people;
+            async move {
+                people
+                    .create_person(
+                        tenant_id,
+                        serde_json::from_value(json!({
+                            "name": name,
+                            "email": format!("{}-{}@example.com", name.to_lowercase(), suffix),
+                            "role": "internal",
+                            "person_type": "internal"
+                        }))
+                        .unwrap(),
+                    )
+                    .await
+                    .unwrap()
+                    .id
+            }
+        };
+        let watcher = person("Watcher").await;
+        let bystander = person("Bystander").await;
+
+        let machines = MachineService::new(database.clone());
+        let machine_id = machines
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Reflow oven", "ip": "10.0.0.70", "port": 502, "protocol": "tcp"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let watches = WatchService::new(database.clone());
+        let status = watches
+            .toggle_watching(tenant_id, watcher, WatchEntityType::Machine, machine_id)
+            .await
+            .unwrap();
+        assert!(status.watching);
+        assert_eq!(status.watcher_count, 1);
+        assert!(watches
+            .toggle_watching(tenant_id, watcher, WatchEntityType::Order, Uuid::new_v4())
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("not found"));
+
+        machines
+            .update_machine(
+                tenant_id,
+                machine_id,
+                serde_json::from_value(json!({ "status": "maintenance" })).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // Commenting notifies the other watchers but not the author
+        watches
+            .set_watching(
+                tenant_id,
+                bystander,
+                WatchEntityType::Machine,
+                machine_id,
+                true,
+            )
+            .await
+            .unwrap();
+        watches
+            .add_comment(
+                tenant_id,
+                watcher,
+                WatchEntityType::Machine,
+                machine_id,
+                serde_json::from_value(json!({ "body": "Nozzle replaced" })).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let notifications = NotificationService::new(database.clone());
+        let unread = |person_id| {
+            notifications.list_notifications(
+                tenant_id,
+                person_id,
+                serde_json::from_value(json!({ "unread": true })).unwrap(),
+            )
+        };
+        let received = unread(watcher).await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event_type, NotificationEventType::StatusChanged);
+        assert_eq!(received[0].detail["status"], "maintenance");
+        let received = unread(bystander).await.unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event_type, NotificationEventType::CommentAdded);
+
+        assert_eq!(
+            notifications
+                .unread_count(tenant_id, watcher)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(notifications
+            .mark_read(tenant_id, bystander, received[0].id)
+            .await
+            .unwrap()
+            .unwrap()
+            .read_at
+            .is_some());
+        // Only the recipient can mark a notification read
+        assert!(notifications
+            .mark_read(tenant_id, watcher, received[0].id)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            notifications
+                .mark_all_read(tenant_id, watcher)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(unread(watcher).await.unwrap().is_empty());
+
+        let status = watches
+            .set_watching(
+                tenant_id,
+                watcher,
+                WatchEntityType::Machine,
+                machine_id,
+                false,
+            )
+            .await
+            .unwrap();
+        assert!(!status.watching);
+        assert_eq!(status.watcher_count, 1);
+        assert_eq!(
+            watches
+                .list_comments(tenant_id, WatchEntityType::Machine, machine_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use ems_server::models::{NumberingEntity, OrderType, PutNumberingSequenceRequest};
+    use ems_server::utils::numbering::{check_prefix, format_number, sequence_start};
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number("PO-{YYYY}-", 4, 2025, 42), "PO-2025-0042");
+        assert_eq!(format_number("J{YY}/", 3, 2025, 7), "J25/007");
+        assert_eq!(format_number("", 1, 2025, 12), "12");
+        // Values wider than the padding are not cut
+        assert_eq!(format_number("Q-", 2, 2025, 12345), "Q-12345");
+    }
+
+    #[test]
+    fn test_sequence_start() {
+        // Carries on within the year
+        assert_eq!(sequence_start(43, Some(2025), true, 2025), 43);
+        // Restarts in a new year only when the sequence resets yearly
+        assert_eq!(sequence_start(43, Some(2024), true, 2025), 1);
+        assert_eq!(sequence_start(43, Some(2024), false, 2025), 43);
+        // A sequence never drawn from starts at its next value
+        assert_eq!(sequence_start(100, None, true, 2025), 100);
+    }
+
+    #[test]
+    fn test_prefix_check() {
+        assert!(check_prefix("PO-{YYYY}-").is_ok());
+        assert!(check_prefix("{YY}J").is_ok());
+        assert!(check_prefix("").is_ok());
+        assert_eq!(
+            check_prefix("PO-{MM}-"),
+            Err("Invalid prefix: PO-{MM}-".to_string())
+        );
+
+        let request = PutNumberingSequenceRequest {
+            prefix: Some("SO-{YYYY".to_string()),
+            padding: Some(4),
+            yearly_reset: Some(true),
+            next_value: None,
+        };
+        assert!(request.check().is_err());
+    }
+
+    #[test]
+    fn test_numbering_entities() {
+        assert_eq!(
+            NumberingEntity::from(&OrderType::PurchaseOrder),
+            NumberingEntity::PurchaseOrder
+        );
+        assert_eq!(
+            NumberingEntity::try_from("return".to_string()),
+            Ok(NumberingEntity::Return)
+        );
+        assert_eq!(
+            NumberingEntity::try_from("machine".to_string()),
+            Err("Invalid numbering entity: machine".to_string())
+        );
+        assert_eq!(
+            String::from(NumberingEntity::CustomerOrder),
+            "customer_order"
+        );
+    }
+}
@@ -0,0 +1,568 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::Utc;
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            CreateOrderReturnRequest, DispositionReturnRequest, NewPerson, NewShipment, Person,
+            Quantity, ReceiveReturnRequest, ReturnStatus, ShipmentStatus,
+        },
+        routes::order_return::routes,
+        schema::{
+            inventory_items, items, order_items, person, shipments, stock_movements, tenants,
+        },
+        services::{DatabaseService, ItemService, OrderService, ReturnService, TenantService},
+        utils::returns::{return_status, returnable_quantity, ReturnLineProgress},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Return API Tests
+
+    #[tokio::test]
+    async fn test_list_returns() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/?order_id={}&status=received", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Return routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_return() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let return_data = json!({
+            "order_id": Uuid::new_v4(),
+            "lines": [
+                { "order_item_id": Uuid::new_v4(), "quantity": 2, "reason": "damaged" }
+            ]
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/", Some(return_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Return routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_return_rejects_invalid_lines() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let return_data = json!({
+            "order_id": Uuid::new_v4(),
+            "lines": [
+                { "order_item_id": Uuid::new_v4(), "quantity": 0, "reason": "damaged" }
+            ]
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/", Some(return_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNPROCESSABLE_ENTITY
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Return progress helper tests
+
+    #[test]
+    fn test_return_status_follows_line_progress() {
+        let line = |quantity, received, dispositioned| ReturnLineProgress {
+            quantity,
+            received,
+            dispositioned,
+        };
+
+        assert_eq!(
+            return_status(&[line(2, 0, 0), line(1, 0, 0)]),
+            ReturnStatus::Requested
+        );
+        assert_eq!(
+            return_status(&[line(2, 2, 2), line(1, 0, 0)]),
+            ReturnStatus::PartiallyReceived
+        );
+        assert_eq!(
+            return_status(&[line(2, 2, 1), line(1, 1, 1)]),
+            ReturnStatus::Received
+        );
+        assert_eq!(
+            return_status(&[line(2, 2, 2), line(1, 1, 1)]),
+            ReturnStatus::Closed
+        );
+    }
+
+    #[test]
+    fn test_returnable_quantity() {
+        assert_eq!(returnable_quantity(10, 0), 10);
+        assert_eq!(returnable_quantity(10, 7), 3);
+        assert_eq!(returnable_quantity(10, 12), 0);
+    }
+
+    #[test]
+    fn test_return_request_checks() {
+        let order_item_id = Uuid::new_v4();
+        let request: CreateOrderReturnRequest = serde_json::from_value(json!({
+            "order_id": Uuid::new_v4(),
+            "lines": [
+                { "order_item_id": order_item_id, "quantity": 1, "reason": "damaged" },
+                { "order_item_id": order_item_id, "quantity": 1, "reason": "defective" }
+            ]
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        let line_id = Uuid::new_v4();
+        let request: ReceiveReturnRequest = serde_json::from_value(json!({
+            "lines": [
+                { "line_id": line_id, "quantity": 1 },
+                { "line_id": line_id, "quantity": 2 }
+            ]
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        // Only restocks go into an inventory context
+        let request: DispositionReturnRequest = serde_json::from_value(json!({
+            "line_id": line_id,
+            "disposition": "scrap",
+            "quantity": 1,
+            "context": "store"
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+    }
+
+    // RMA workflow
+
+    #[tokio::test]
+    async fn test_return_workflow() {
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "RMA test", "subdomain": format!("rma-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let creator: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "RMA Clerk".to_string(),
+                email: format!("rma-{}@example.com", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        let item_id = ItemService::new(database.clone())
+            .create_item(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "internal_part_number": format!("RMA-{}", suffix),
+                    "manufacturer": "Acme",
+                    "context": "finished_goods",
+                    "quantity": 5
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let order_id = OrderService::new(database.clone())
+            .create_order(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "order_number": format!("SO-{}", suffix),
+                    "order_type": "customer_order",
+                    "external_entity_id": creator.id,
+                    "external_entity_type": "customer",
+                    "order_date": Utc::now(),
+                    "total_amount": 60.0,
+                    "status": "approved",
+                    "created_by_id": creator.id,
+                    "items": [
+                        { "item_id": item_id, "item_name": "Widget", "quantity": 4, "unit_price": 10.0 },
+                        { "item_name": "Setup fee", "quantity": 1, "unit_price": 20.0 }
+                    ]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let lines: Vec<(Uuid, Option<Uuid>)> = order_items::table
+            .filter(order_items::order_id.eq(order_id))
+            .select((order_items::id, order_items::item_id))
+            .load(&mut conn)
+            .await
+            .unwrap();
+        let widget = lines.iter().find(|(_, item)| item.is_some()).unwrap().0;
+        let fee = lines.iter().find(|(_, item)| item.is_none()).unwrap().0;
+
+        let returns = ReturnService::new(database.clone());
+        let on_hand = || async {
+            let mut conn = database.get_connection().await.unwrap();
+            inventory_items::table
+                .filter(inventory_items::item_id.eq(item_id))
+                .select(inventory_items::quantity)
+                .first::<Option<Quantity>>(&mut conn)
+                .await
+                .unwrap()
+                .unwrap_or_default()
+        };
+        let create = |lines: Value| -> CreateOrderReturnRequest {
+            serde_json::from_value(json!({ "order_id": order_id, "lines": lines })).unwrap()
+        };
+
+        // Nothing can come back before it has left
+        let error = returns
+            .create_return(
+                tenant_id,
+                Some(creator.id),
+                create(json!([{ "order_item_id": widget, "quantity": 1, "reason": "damaged" }])),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("has not been shipped"));
+
+        diesel::insert_into(shipments::table)
+            .values(&NewShipment {
+                tenant_id,
+                order_id,
+                carrier: "webhook".to_string(),
+                service_level: None,
+                ship_to: json!({ "city": "Springfield" }),
+                notes: None,
+                created_by_id: None,
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::update(shipments::table.filter(shipments::order_id.eq(order_id)))
+            .set(shipments::status.eq(ShipmentStatus::InTransit.to_string()))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let error = returns
+            .create_return(
+                tenant_id,
+                Some(creator.id),
+                create(json!([{ "order_item_id": widget, "quantity": 5, "reason": "damaged" }])),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("4 returnable"));
+
+        let error = returns
+            .create_return(
+                tenant_id,
+                Some(creator.id),
+                create(
+                    json!([{ "order_item_id": Uuid::new_v4(), "quantity": 1, "reason": "other" }]),
+                ),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not on order"));
+
+        let rma = returns
+            .create_return(
+                tenant_id,
+                Some(creator.id),
+                create(json!([
+                    { "order_item_id": widget, "quantity": 3, "reason": "defective", "reason_notes": "Cracked housing" },
+                    { "order_item_id": fee, "quantity": 1, "reason": "other" }
+                ])),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rma.status, ReturnStatus::Requested);
+        assert!(rma.rma_number.starts_with("RMA-"));
+        assert_eq!(rma.quarantine_location, "QUARANTINE");
+        assert_eq!(rma.return_value, 50.0);
+        let widget_line = rma
+            .lines
+            .iter()
+            .find(|l| l.order_item_id == widget)
+            .unwrap()
+            .id;
+        let fee_line = rma
+            .lines
+            .iter()
+            .find(|l| l.order_item_id == fee)
+            .unwrap()
+            .id;
+
+        // Received stock waits in quarantine, outside inventory
+        let rma = returns
+            .receive_return(
+                tenant_id,
+                rma.id,
+                serde_json::from_value(json!({
+                    "quarantine_location": "DOCK-2",
+                    "lines": [{ "line_id": widget_line, "quantity": 2 }]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rma.status, ReturnStatus::PartiallyReceived);
+        assert_eq!(rma.quarantine_location, "DOCK-2");
+        assert!(rma.received_at.is_some());
+        let received = rma.lines.iter().find(|l| l.id == widget_line).unwrap();
+        assert_eq!(received.quarantined_quantity, 2);
+        assert_eq!(on_hand().await, Quantity::from(5));
+
+        let disposition = |line_id: Uuid, disposition: &str, quantity: i32| {
+            serde_json::from_value::<DispositionReturnRequest>(json!({
+                "line_id": line_id,
+                "disposition": disposition,
+                "quantity": quantity
+            }))
+            .unwrap()
+        };
+
+        let rma = returns
+            .disposition_return(
+                tenant_id,
+                Some(creator.id),
+                rma.id,
+                disposition(widget_line, "restock", 1),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(on_hand().await, Quantity::from(6));
+        let restock = &rma
+            .lines
+            .iter()
+            .find(|l| l.id == widget_line)
+            .unwrap()
+            .dispositions[0];
+        let reference: (Option<String>, Option<Uuid>) = stock_movements::table
+            .find(restock.stock_movement_id.unwrap())
+            .select((
+                stock_movements::reference_type,
+                stock_movements::reference_id,
+            ))
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(reference, (Some("return".to_string()), Some(rma.id)));
+
+        let error = returns
+            .disposition_return(
+                tenant_id,
+                Some(creator.id),
+                rma.id,
+                disposition(widget_line, "scrap", 2),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("1 in quarantine"));
+
+        let rma = returns
+            .receive_return(
+                tenant_id,
+                rma.id,
+                serde_json::from_value(json!({
+                    "lines": [
+                        { "line_id": widget_line, "quantity": 1 },
+                        { "line_id": fee_line, "quantity": 1 }
+                    ]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rma.status, ReturnStatus::Received);
+
+        let error = returns
+            .disposition_return(
+                tenant_id,
+                Some(creator.id),
+                rma.id,
+                disposition(fee_line, "restock", 1),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("no inventory item"));
+
+        for (line_id, kind) in [
+            (widget_line, "scrap"),
+            (widget_line, "repair"),
+            (fee_line, "scrap"),
+        ] {
+            returns
+                .disposition_return(
+                    tenant_id,
+                    Some(creator.id),
+                    rma.id,
+                    disposition(line_id, kind, 1),
+                )
+                .await
+                .unwrap()
+                .unwrap();
+        }
+        let rma = returns
+            .get_return(tenant_id, rma.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rma.status, ReturnStatus::Closed);
+        assert!(rma.closed_at.is_some());
+        assert_eq!(on_hand().await, Quantity::from(6));
+
+        // Credit notes are capped at the value of the returned lines
+        let credit = |number: &str, amount: f64| {
+            serde_json::from_value(json!({
+                "credit_note_number": format!("{}-{}", number, suffix),
+                "amount": amount
+            }))
+            .unwrap()
+        };
+        let rma = returns
+            .add_credit_note(tenant_id, rma.id, credit("CN-1", 45.0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rma.credited_amount, 45.0);
+        let error = returns
+            .add_credit_note(tenant_id, rma.id, credit("CN-2", 10.0))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("5.00 remaining"));
+        let error = returns
+            .add_credit_note(tenant_id, rma.id, credit("CN-1", 5.0))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("duplicate key"));
+
+        // Later returns only cover what is left of each order line
+        let error = returns
+            .create_return(
+                tenant_id,
+                None,
+                create(json!([{ "order_item_id": widget, "quantity": 2, "reason": "not_needed" }])),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("1 returnable"));
+        let second = returns
+            .create_return(
+                tenant_id,
+                None,
+                create(json!([{ "order_item_id": widget, "quantity": 1, "reason": "not_needed" }])),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let cancelled = returns
+            .cancel_return(tenant_id, second.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cancelled.status, ReturnStatus::Cancelled);
+        let error = returns
+            .cancel_return(tenant_id, second.id)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be cancelled"));
+
+        let listed = returns
+            .list_returns(
+                tenant_id,
+                serde_json::from_value(json!({ "order_id": order_id })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+
+        diesel::delete(tenants::table.find(tenant_id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::delete(items::table.find(item_id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        diesel::delete(person::table.find(creator.id))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+}
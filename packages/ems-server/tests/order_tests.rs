@@ -511,6 +511,581 @@ mod tests {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_backorder_order_line() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let order_id = Uuid::new_v4().to_string();
+        let line_item_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/items/{}/backorder", order_id, line_item_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Backordering needs the caller's identity, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_order_confirmation() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/confirmation?locale=es", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Order routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_order_confirmation() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/confirmation/send", Uuid::new_v4()),
+            Some(json!({ "recipients": ["compras@example.mx"], "locale": "es" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Sending needs the caller's identity, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_order_confirmation_documents() {
+        use chrono::{TimeZone, Utc};
+        use ems_server::models::{OrderResponse, SendOrderConfirmationRequest};
+        use ems_server::utils::branding::DocumentBranding;
+        use ems_server::utils::i18n::Locale;
+        use ems_server::utils::order_confirmation::{
+            order_confirmation_email, render_order_confirmation_pdf,
+        };
+
+        let date = Utc.with_ymd_and_hms(2026, 3, 9, 15, 0, 0).unwrap();
+        let order: OrderResponse = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "order_number": "SO-1042",
+            "order_type": "customer_order",
+            "external_entity_id": Uuid::new_v4(),
+            "external_entity_type": "customer",
+            "order_date": date,
+            "total_amount": 250.0,
+            "status": "approved",
+            "created_by_id": Uuid::new_v4(),
+            "notes": null,
+            "metadata": null,
+            "created_at": date,
+            "updated_at": date,
+            "archived": false,
+            "items": [{
+                "id": Uuid::new_v4(),
+                "item_id": null,
+                "item_name": "Control board",
+                "item_description": null,
+                "quantity": 10,
+                "unit_price": 25.0,
+                "extended_price": 250.0,
+                "notes": null,
+                "expected_date": date,
+                "backordered": false,
+                "uom_id": null,
+                "base_quantity": 10,
+                "created_at": date,
+                "updated_at": date
+            }]
+        }))
+        .unwrap();
+
+        let pdf = render_order_confirmation_pdf(
+            &order,
+            "Industrias Norte",
+            Locale::Es,
+            &DocumentBranding::default(),
+        );
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(text.contains("Industrias Norte"));
+        assert!(text.contains("Art\u{fffd}culo"));
+        assert!(text.contains("Precio unitario"));
+        assert!(text.contains("09/03/2026"));
+        assert!(text.contains("250.00"));
+        let english = render_order_confirmation_pdf(
+            &order,
+            "Industrias Norte",
+            Locale::En,
+            &DocumentBranding::default(),
+        );
+        assert!(String::from_utf8_lossy(&english).contains("Order confirmation SO-1042"));
+
+        let (subject, body) =
+            order_confirmation_email(&order, "Industrias Norte", Some("Saludos"), Locale::Es);
+        assert_eq!(subject, "Confirmación de pedido SO-1042");
+        assert_eq!(
+            body,
+            "Estimado/a Industrias Norte:\n\nGracias por su pedido. Adjuntamos la confirmación \
+             de pedido SO-1042.\n\nTotal: 250.00\n\nSaludos\n"
+        );
+
+        let request: SendOrderConfirmationRequest =
+            serde_json::from_value(json!({ "recipients": ["not-an-email"] })).unwrap();
+        assert!(request.check().is_err());
+        assert!(
+            serde_json::from_value::<SendOrderConfirmationRequest>(json!({ "locale": "fr" }))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_document_pack() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/document-pack", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Order routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_document_pack_archive() {
+        use chrono::{TimeZone, Utc};
+        use ems_server::utils::document_pack::{release_notes_path, release_notes_text};
+        use ems_server::utils::zip::{crc32, safe_file_name, ZipArchive};
+
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+
+        let modified = Utc.with_ymd_and_hms(2026, 3, 9, 15, 30, 10).unwrap();
+        let mut archive = ZipArchive::new();
+        assert_eq!(
+            archive.add_file("certificates/B-1.pdf", modified, b"one"),
+            "certificates/B-1.pdf"
+        );
+        // Names already used are numbered rather than overwritten
+        assert_eq!(
+            archive.add_file("certificates/B-1.pdf", modified, b"two"),
+            "certificates/B-1 (2).pdf"
+        );
+        assert_eq!(archive.add_file("README", modified, b""), "README");
+        assert_eq!(archive.add_file("README", modified, b""), "README (2)");
+        assert_eq!(archive.len(), 4);
+
+        let zip = archive.finish();
+        assert!(zip.starts_with(b"PK\x03\x04"));
+        let end = &zip[zip.len() - 22..];
+        assert!(end.starts_with(b"PK\x05\x06"));
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 4);
+        let directory_offset = u32::from_le_bytes([end[16], end[17], end[18], end[19]]) as usize;
+        assert!(zip[directory_offset..].starts_with(b"PK\x01\x02"));
+        // Stored entries: the contents follow their header unchanged
+        let header = 30 + "certificates/B-1.pdf".len();
+        assert_eq!(&zip[header..header + 3], b"one");
+        assert_eq!(
+            u32::from_le_bytes([zip[14], zip[15], zip[16], zip[17]]),
+            crc32(b"one")
+        );
+
+        assert_eq!(safe_file_name("SO 1042/A"), "SO_1042_A");
+        assert_eq!(safe_file_name("../etc"), "_etc");
+        assert_eq!(safe_file_name("  "), "file");
+        assert_eq!(
+            release_notes_path("Controller fw", Some("2.1.0")),
+            "firmware/Controller_fw-2.1.0.txt"
+        );
+        assert_eq!(
+            release_notes_text("Controller", Some("2.1.0"), Some(modified), " Fixes.\n"),
+            "Controller 2.1.0\nReleased 2026-03-09\n\nFixes.\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_order_confirmation_workflow() {
+        use chrono::Utc;
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::{NewPerson, Person};
+        use ems_server::schema::person;
+        use ems_server::services::{OrderService, TenantService};
+        use ems_server::utils::i18n::Locale;
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({
+                    "name": "Confirmation test",
+                    "subdomain": format!("confirm-{}", suffix),
+                    "settings": { "locale": "es" }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let customer: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Industrias Norte".to_string(),
+                email: format!("compras-{}@example.mx", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+
+        let orders = OrderService::new(database.clone());
+        let order = |number: &str, order_type: &str, status: &str| {
+            let request = serde_json::from_value(json!({
+                "order_number": format!("{}-{}", number, suffix),
+                "order_type": order_type,
+                "external_entity_id": customer.id,
+                "external_entity_type": if order_type == "purchase_order" { "vendor" } else { "customer" },
+                "order_date": Utc::now(),
+                "total_amount": 40.0,
+                "status": status,
+                "created_by_id": customer.id,
+                "items": [{
+                    "item_name": "Cable harness",
+                    "quantity": 4,
+                    "unit_price": 10.0
+                }]
+            }))
+            .unwrap();
+            let orders = &orders;
+            async move { orders.create_order(tenant_id, request).await.unwrap().id }
+        };
+        let confirmed = order("SO-A", "customer_order", "approved").await;
+        let draft = order("SO-B", "customer_order", "draft").await;
+        let purchase = order("PO-A", "purchase_order", "approved").await;
+
+        let (number, pdf) = orders
+            .confirmation_pdf(tenant_id, confirmed, Locale::Es)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(number, format!("SO-A-{}", suffix));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("Industrias Norte"));
+        assert!(text.contains("Cable harness"));
+        assert!(text.contains("Precio unitario"));
+
+        for order_id in [draft, purchase] {
+            let error = orders
+                .confirmation_pdf(tenant_id, order_id, Locale::Es)
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("cannot be confirmed"));
+        }
+        assert!(orders
+            .confirmation_pdf(tenant_id, Uuid::new_v4(), Locale::Es)
+            .await
+            .unwrap()
+            .is_none());
+
+        let error = orders
+            .send_confirmation(
+                tenant_id,
+                confirmed,
+                customer.id,
+                serde_json::from_value(json!({})).unwrap(),
+                None,
+                Locale::Es,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not configured"));
+
+        // Documents default to the locale in the tenant's settings
+        let locale = TenantService::new(database.clone())
+            .get_locale(tenant_id)
+            .await
+            .unwrap();
+        assert_eq!(locale, Locale::Es);
+    }
+
+    #[tokio::test]
+    async fn test_order_analytics_route() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/analytics?granularity=week&group_by=customer",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Order routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_order_analytics_query_check() {
+        use ems_server::models::{AnalyticsGranularity, OrderAnalyticsQuery, OrderType};
+
+        let query =
+            |value: Value| -> OrderAnalyticsQuery { serde_json::from_value(value).unwrap() };
+
+        let parsed = query(json!({ "granularity": "month", "type": "purchase_order" }));
+        assert_eq!(parsed.granularity, Some(AnalyticsGranularity::Month));
+        assert_eq!(parsed.order_type, Some(OrderType::PurchaseOrder));
+        assert!(parsed.check().is_ok());
+
+        assert!(
+            query(json!({ "from": "2026-03-01T00:00:00Z", "to": "2026-02-01T00:00:00Z" }))
+                .check()
+                .is_err()
+        );
+        // Daily buckets over several years would be too many
+        assert!(query(json!({
+            "granularity": "day", "from": "2020-01-01T00:00:00Z", "to": "2026-01-01T00:00:00Z"
+        }))
+        .check()
+        .is_err());
+        assert!(query(json!({
+            "granularity": "quarter", "from": "2020-01-01T00:00:00Z", "to": "2026-01-01T00:00:00Z"
+        }))
+        .check()
+        .is_ok());
+        assert!(
+            serde_json::from_value::<OrderAnalyticsQuery>(json!({ "group_by": "region" })).is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_order_analytics() {
+        use chrono::{DateTime, Duration, Utc};
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::{NewPerson, OrderAnalyticsQuery, Person};
+        use ems_server::schema::person;
+        use ems_server::services::{OrderService, TenantService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({
+                    "name": "Analytics test",
+                    "subdomain": format!("analytics-{}", suffix)
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let mut customers = Vec::new();
+        for name in ["Alpha Tooling", "Beta Castings"] {
+            let customer: Person = diesel::insert_into(person::table)
+                .values(&NewPerson {
+                    supabase_uid: Uuid::new_v4(),
+                    name: name.to_string(),
+                    email: format!("{}-{}@example.com", name.replace(' ', "."), suffix),
+                    phone: None,
+                    global_access: Some(vec![]),
+                    is_active: Some(true),
+                })
+                .returning(<Person as diesel::SelectableHelper<diesel::pg::Pg>>::as_returning())
+                .get_result(&mut conn)
+                .await
+                .unwrap();
+            customers.push(customer.id);
+        }
+        let (alpha, beta) = (customers[0], customers[1]);
+
+        // Monday 5 January 2026
+        let week: DateTime<Utc> = "2026-01-05T09:00:00Z".parse().unwrap();
+        let orders = OrderService::new(database.clone());
+        let order = |customer: Uuid,
+                     order_type: &str,
+                     placed: DateTime<Utc>,
+                     amount: f64,
+                     expected: DateTime<Utc>,
+                     status: &str| {
+            let request = serde_json::from_value(json!({
+                "order_number": format!("AN-{}", Uuid::new_v4().simple()),
+                "order_type": order_type,
+                "external_entity_id": customer,
+                "external_entity_type": if order_type == "purchase_order" { "vendor" } else { "customer" },
+                "order_date": placed,
+                "total_amount": amount,
+                "status": status,
+                "created_by_id": customer,
+                "items": [{
+                    "item_name": "Fixture plate",
+                    "quantity": 1,
+                    "unit_price": amount,
+                    "expected_date": expected
+                }]
+            }))
+            .unwrap();
+            let orders = &orders;
+            async move { orders.create_order(tenant_id, request).await.unwrap().id }
+        };
+        let fulfil = |order_id: Uuid| {
+            let request = serde_json::from_value(json!({ "status": "fulfilled" })).unwrap();
+            let orders = &orders;
+            async move {
+                orders
+                    .update_order(tenant_id, order_id, request)
+                    .await
+                    .unwrap();
+            }
+        };
+
+        let on_time = order(
+            alpha,
+            "customer_order",
+            week,
+            100.0,
+            week + Duration::days(3650),
+            "approved",
+        )
+        .await;
+        let late = order(
+            alpha,
+            "customer_order",
+            week + Duration::days(1),
+            50.0,
+            week,
+            "approved",
+        )
+        .await;
+        fulfil(on_time).await;
+        fulfil(late).await;
+        order(
+            beta,
+            "customer_order",
+            week + Duration::days(8),
+            30.0,
+            week,
+            "submitted",
+        )
+        .await;
+        // Drafts and other order types are left out
+        order(beta, "customer_order", week, 999.0, week, "draft").await;
+        order(beta, "purchase_order", week, 999.0, week, "approved").await;
+
+        let query = |value: Value| -> OrderAnalyticsQuery {
+            let mut value = value;
+            value["from"] = json!("2026-01-05T00:00:00Z");
+            value["to"] = json!("2026-01-26T00:00:00Z");
+            serde_json::from_value(value).unwrap()
+        };
+
+        // Every week of the window is present, including the empty one
+        let weekly = orders
+            .order_analytics(tenant_id, query(json!({ "granularity": "week" })))
+            .await
+            .unwrap();
+        assert_eq!(weekly.buckets.len(), 3);
+        let first = &weekly.buckets[0];
+        assert_eq!(
+            first.period_start,
+            "2026-01-05T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(first.order_count, 2);
+        assert_eq!(first.revenue, 150.0);
+        assert_eq!(first.fulfilled_count, 2);
+        assert!(first.avg_lead_time_days.unwrap() > 1.0);
+        assert_eq!(first.on_time_percent, Some(50.0));
+        assert_eq!(weekly.buckets[1].order_count, 1);
+        assert_eq!(weekly.buckets[1].fulfilled_count, 0);
+        assert_eq!(weekly.buckets[1].on_time_percent, None);
+        assert_eq!(weekly.buckets[2].order_count, 0);
+        assert_eq!(weekly.buckets[2].revenue, 0.0);
+
+        let monthly = orders
+            .order_analytics(tenant_id, query(json!({ "granularity": "month" })))
+            .await
+            .unwrap();
+        assert_eq!(monthly.buckets.len(), 1);
+        assert_eq!(monthly.buckets[0].order_count, 3);
+        assert_eq!(monthly.buckets[0].revenue, 180.0);
+
+        let by_customer = orders
+            .order_analytics(tenant_id, query(json!({ "group_by": "customer" })))
+            .await
+            .unwrap();
+        let groups: Vec<_> = by_customer
+            .buckets
+            .iter()
+            .map(|b| (b.group_label.as_deref().unwrap(), b.order_count, b.revenue))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![("Alpha Tooling", 2, 150.0), ("Beta Castings", 1, 30.0)]
+        );
+        assert_eq!(by_customer.buckets[1].group, Some(beta.to_string()));
+
+        // Lines with no catalogue item count as uncategorized
+        let by_category = orders
+            .order_analytics(tenant_id, query(json!({ "group_by": "item_category" })))
+            .await
+            .unwrap();
+        assert_eq!(by_category.buckets.len(), 2);
+        assert!(by_category
+            .buckets
+            .iter()
+            .all(|b| b.group.as_deref() == Some("uncategorized")));
+        assert_eq!(by_category.buckets[0].revenue, 150.0);
+
+        let purchases = orders
+            .order_analytics(tenant_id, query(json!({ "type": "purchase_order" })))
+            .await
+            .unwrap();
+        assert_eq!(
+            purchases.buckets.iter().map(|b| b.order_count).sum::<i64>(),
+            1
+        );
+    }
+
     // Edge Case and Error Tests
 
     #[tokio::test]
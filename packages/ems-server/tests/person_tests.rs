@@ -490,6 +490,31 @@ mod tests {
 
     // Distributor Person API Tests
 
+    #[tokio::test]
+    async fn test_get_vendor_scorecard() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let person_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!(
+                "/vendor/{}/scorecard?from=2026-01-01T00:00:00Z&to=2026-04-01T00:00:00Z",
+                person_id
+            ),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Person routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[tokio::test]
     async fn test_list_distributor_persons() {
         let app = app().await;
@@ -639,4 +664,702 @@ mod tests {
                 || response.status() == StatusCode::INTERNAL_SERVER_ERROR
         );
     }
+
+    // Bulk import API Tests
+
+    #[tokio::test]
+    async fn test_import_persons() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let import_data = json!({
+            "csv": "name,email,role,department\nJane Operator,jane@example.com,internal,Assembly\n",
+            "send_invites": false,
+            "assignments": [{
+                "email": "jane@example.com",
+                "machine_id": Uuid::new_v4(),
+                "assignment_type": "primary"
+            }]
+        });
+
+        let request =
+            create_request_with_tenant(Method::POST, "/import", Some(import_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Person routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_parse_person_import() {
+        use ems_server::models::{PersonImportStatus, PersonRole};
+        use ems_server::utils::person_import::parse_person_import;
+
+        let csv = "Name,Email,Role,Department,Employee ID\r\n\
+                   \"Doe, Jane\",Jane@Example.com,,Assembly,E-1\r\n\
+                   Sam Vendor,sam@example.com,vendor,,\r\n\
+                   ,missing@example.com,internal,,\r\n\
+                   Bad Role,bad@example.com,boss,,\r\n\
+                   Jane Again,jane@example.com,internal,,\r\n";
+
+        let (rows, failed) = parse_person_import(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Doe, Jane");
+        assert_eq!(rows[0].email, "jane@example.com");
+        assert_eq!(rows[0].role, PersonRole::Internal);
+        assert_eq!(rows[0].department.as_deref(), Some("Assembly"));
+        assert_eq!(rows[0].employee_id.as_deref(), Some("E-1"));
+        assert_eq!(rows[1].role, PersonRole::Vendor);
+
+        assert_eq!(failed.len(), 3);
+        assert!(failed
+            .iter()
+            .all(|r| r.status == PersonImportStatus::Failed));
+        assert_eq!(failed[0].line, 4);
+        assert_eq!(failed[0].errors, vec!["Missing name".to_string()]);
+        assert_eq!(
+            failed[1].errors,
+            vec!["Invalid person role: boss".to_string()]
+        );
+        assert_eq!(failed[2].email.as_deref(), Some("jane@example.com"));
+
+        assert!(parse_person_import("name,phone\nJane,123\n").is_err());
+        assert!(parse_person_import("").is_err());
+    }
+
+    // Duplicate detection API Tests
+
+    #[tokio::test]
+    async fn test_list_duplicate_persons() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request =
+            create_request_with_tenant(Method::GET, "/duplicates?min_score=0.9", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Person routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_persons() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let merge_data = json!({
+            "survivor_id": Uuid::new_v4(),
+            "duplicate_id": Uuid::new_v4()
+        });
+
+        let request =
+            create_request_with_tenant(Method::POST, "/merge", Some(merge_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+
+        // Person routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Access level tests
+
+    // Person routes with the extensions the tenant and auth middleware would add
+    async fn app_as(access_level: ems_server::models::AccessLevel) -> Router {
+        use axum::Extension;
+        use ems_server::{middleware::tenant::TenantContext, models::CallerContext};
+
+        app()
+            .await
+            .layer(Extension(CallerContext {
+                person_id: Uuid::new_v4(),
+                access_level,
+            }))
+            .layer(Extension(TenantContext {
+                tenant_id: Uuid::new_v4(),
+            }))
+    }
+
+    #[test]
+    fn test_access_level_permissions() {
+        use ems_server::models::{AccessLevel, CallerContext, Permission};
+
+        assert_eq!(
+            AccessLevel::highest(&[Some("standard".to_string()), Some("admin".to_string())]),
+            AccessLevel::Admin
+        );
+        assert_eq!(
+            AccessLevel::highest(&[None, Some("owner".to_string())]),
+            AccessLevel::Standard
+        );
+        assert_eq!(AccessLevel::highest(&[]), AccessLevel::Standard);
+
+        assert!(AccessLevel::Admin.allows(Permission::ChangeRoles));
+        assert!(!AccessLevel::Standard.allows(Permission::PurgePerson));
+
+        let caller = CallerContext {
+            person_id: Uuid::new_v4(),
+            access_level: AccessLevel::Standard,
+        };
+        let error = caller.require(Permission::DeleteMachine).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Access denied: standard access cannot delete machines"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_standard_caller_cannot_delete_person() {
+        use ems_server::models::AccessLevel;
+
+        let request = Request::builder()
+            .method(Method::DELETE)
+            .uri(format!("/{}", Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap();
+        let response = app_as(AccessLevel::Standard)
+            .await
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body["error"],
+            "Access denied: standard access cannot remove people"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_standard_caller_role_changes() {
+        use ems_server::models::AccessLevel;
+
+        let update = |body: Value| {
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/{}", Uuid::new_v4()))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+
+        // Changing the role needs admin access
+        let response = app_as(AccessLevel::Standard)
+            .await
+            .oneshot(update(json!({ "role": "internal" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app_as(AccessLevel::Standard)
+            .await
+            .oneshot(update(json!({ "global_access": ["admin"] })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Merging removes the duplicate, so it counts as removing a person
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/merge")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({ "survivor_id": Uuid::new_v4(), "duplicate_id": Uuid::new_v4() })
+                    .to_string(),
+            ))
+            .unwrap();
+        let response = app_as(AccessLevel::Standard)
+            .await
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // Admins get past the check; the unknown person is then reported as missing
+        let response = app_as(AccessLevel::Admin)
+            .await
+            .oneshot(update(json!({ "role": "internal" })))
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_find_person_duplicates() {
+        use chrono::{Duration, Utc};
+        use ems_server::models::{DuplicateReason, Person};
+        use ems_server::utils::duplicate::{find_person_duplicates, name_similarity, same_phone};
+
+        assert!(same_phone("+1 (555) 010-2030", "555-010-2030"));
+        assert!(!same_phone("555-010-2030", "555-010-2031"));
+        assert!(!same_phone("123", "123"));
+        assert!(name_similarity("Smith, Jane", "jane smith") > 0.99);
+        assert!(name_similarity("Jane Smith", "John Brown") < 0.5);
+
+        let person = |name: &str, email: &str, phone: Option<&str>, days_ago: i64| Person {
+            id: Uuid::new_v4(),
+            supabase_uid: Uuid::new_v4(),
+            name: name.to_string(),
+            email: email.to_string(),
+            phone: phone.map(str::to_string),
+            global_access: None,
+            is_active: Some(true),
+            last_login: None,
+            created_at: Some(Utc::now() - Duration::days(days_ago)),
+            updated_at: None,
+            password_hash: None,
+            email_verified_at: None,
+        };
+        let newer = person("Jane Smith", "JANE@example.com", None, 1);
+        let older = person("Jane Smyth", "jane@example.com", None, 10);
+        let by_phone = person("J. Brown", "jb@example.com", Some("+44 20 7946 0018"), 5);
+        let same_phone_person = person("John Brown", "john@example.org", Some("020 7946 0018"), 3);
+        let unrelated = person("Alex Doe", "alex@example.net", None, 2);
+
+        let matches = find_person_duplicates(
+            &[
+                newer.clone(),
+                older.clone(),
+                by_phone.clone(),
+                same_phone_person.clone(),
+                unrelated,
+            ],
+            0.85,
+        );
+        assert_eq!(matches.len(), 2);
+
+        let by_email = matches
+            .iter()
+            .find(|m| m.reasons.contains(&DuplicateReason::SameEmail))
+            .unwrap();
+        assert_eq!(by_email.survivor_id, older.id);
+        assert_eq!(by_email.duplicate_id, newer.id);
+        assert_eq!(by_email.score, 1.0);
+
+        let by_phone_match = matches
+            .iter()
+            .find(|m| m.reasons.contains(&DuplicateReason::SamePhone))
+            .unwrap();
+        assert_eq!(by_phone_match.survivor_id, by_phone.id);
+        assert_eq!(by_phone_match.duplicate_id, same_phone_person.id);
+    }
+
+    #[test]
+    fn test_fill_dates() {
+        use chrono::{TimeZone, Utc};
+        use ems_server::models::Quantity;
+        use ems_server::utils::scorecard::fill_dates;
+
+        let day = |n: u32| Utc.with_ymd_and_hms(2026, 3, n, 12, 0, 0).unwrap();
+        let units = |quantities: &[i32]| -> Vec<Quantity> {
+            quantities.iter().map(|&q| Quantity::from(q)).collect()
+        };
+        // Receipts are applied in date order however they are listed
+        let receipts = [
+            (day(5), Quantity::from(4)),
+            (day(1), Quantity::from(6)),
+            (day(9), Quantity::from(3)),
+        ];
+        assert_eq!(
+            fill_dates(&units(&[5, 0, 5, 4]), &receipts),
+            vec![Some(day(1)), None, Some(day(5)), None]
+        );
+        assert_eq!(
+            fill_dates(&units(&[6, 4]), &receipts),
+            vec![Some(day(1)), Some(day(5))]
+        );
+        assert_eq!(fill_dates(&units(&[2]), &[]), vec![None]);
+
+        // Fractional receipts fill lines of decimal quantities
+        let receipts = [
+            (day(1), "0.35".parse().unwrap()),
+            (day(2), "0.15".parse().unwrap()),
+        ];
+        assert_eq!(
+            fill_dates(&["0.5".parse().unwrap()], &receipts),
+            vec![Some(day(2))]
+        );
+    }
+
+    #[test]
+    fn test_delivery_outcome() {
+        use chrono::{TimeZone, Utc};
+        use ems_server::utils::scorecard::{delivery_outcome, DeliveryOutcome};
+
+        let promised = Utc.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+
+        // Later on the promised day still counts as on time
+        assert_eq!(
+            delivery_outcome(promised, Some(at(10, 23)), at(20, 0)),
+            Some(DeliveryOutcome::OnTime)
+        );
+        assert_eq!(
+            delivery_outcome(promised, Some(at(2, 8)), at(20, 0)),
+            Some(DeliveryOutcome::OnTime)
+        );
+        assert_eq!(
+            delivery_outcome(promised, Some(at(13, 1)), at(20, 0)),
+            Some(DeliveryOutcome::Late(3))
+        );
+        assert_eq!(
+            delivery_outcome(promised, None, at(11, 0)),
+            Some(DeliveryOutcome::Overdue)
+        );
+        assert_eq!(delivery_outcome(promised, None, at(10, 18)), None);
+    }
+
+    #[test]
+    fn test_vendor_scorecard_query_check() {
+        use ems_server::models::VendorScorecardQuery;
+
+        let query = |from: &str, to: &str| VendorScorecardQuery {
+            from: Some(from.parse().unwrap()),
+            to: Some(to.parse().unwrap()),
+        };
+        assert!(query("2026-01-01T00:00:00Z", "2026-04-01T00:00:00Z")
+            .check()
+            .is_ok());
+        assert!(query("2026-04-01T00:00:00Z", "2026-04-01T00:00:00Z")
+            .check()
+            .is_err());
+        assert!(VendorScorecardQuery {
+            from: None,
+            to: None
+        }
+        .check()
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_vendor_scorecard() {
+        use chrono::{Duration, Utc};
+        use diesel::prelude::*;
+        use diesel_async::RunQueryDsl;
+        use ems_server::models::{
+            NewItemPriceHistory, NewPerson, Person, Quantity, RecordStockMovementRequest,
+            VendorScorecardQuery,
+        };
+        use ems_server::schema::{inventory_items, item_price_history, person};
+        use ems_server::services::{
+            ItemService, OrderService, PersonService, QualityService, ScorecardService,
+            StockService, TenantService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+        let now = Utc::now();
+        let days_ago = |days: i64| now - Duration::days(days);
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Scorecard test", "subdomain": format!("score-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let buyer: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Buyer".to_string(),
+                email: format!("buyer-{}@example.com", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        let vendor_id = PersonService::new(database.clone())
+            .create_person(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Acme Components",
+                    "email": format!("acme-{}@example.com", suffix),
+                    "role": "vendor",
+                    "person_type": "vendor",
+                    "company": "Acme Components Ltd"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let items = ItemService::new(database.clone());
+        let item = |part_number: &str| {
+            let request = serde_json::from_value(json!({
+                "internal_part_number": format!("{}-{}", part_number, suffix),
+                "manufacturer": "Acme",
+                "context": "store",
+                "quantity": 0
+            }))
+            .unwrap();
+            let items = &items;
+            async move { items.create_item(tenant_id, request).await.unwrap().id }
+        };
+        let resistor = item("RES").await;
+        let capacitor = item("CAP").await;
+
+        // The vendor's own price wins over one recorded without a vendor
+        let inventory_item_id: Uuid = inventory_items::table
+            .filter(inventory_items::item_id.eq(resistor))
+            .select(inventory_items::id)
+            .first(&mut conn)
+            .await
+            .unwrap();
+        for (vendor, unit_price) in [(Some(vendor_id), 10.0), (None, 9.0)] {
+            let id: Uuid = diesel::insert_into(item_price_history::table)
+                .values(&NewItemPriceHistory {
+                    tenant_id,
+                    item_id: resistor,
+                    inventory_item_id,
+                    vendor_id: vendor,
+                    unit_price,
+                    price_breaks: json!([]),
+                    currency: None,
+                    lead_time: None,
+                    source: "price_list".to_string(),
+                    recorded_by_id: None,
+                })
+                .returning(item_price_history::id)
+                .get_result(&mut conn)
+                .await
+                .unwrap();
+            diesel::update(item_price_history::table.find(id))
+                .set(item_price_history::recorded_at.eq(days_ago(30)))
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        let orders = OrderService::new(database.clone());
+        let purchase_order = |number: &str, status: &str, ordered: i64, lines: Value| {
+            let request = serde_json::from_value(json!({
+                "order_number": format!("{}-{}", number, suffix),
+                "order_type": "purchase_order",
+                "external_entity_id": vendor_id,
+                "external_entity_type": "vendor",
+                "order_date": days_ago(ordered),
+                "total_amount": 0.0,
+                "status": status,
+                "created_by_id": buyer.id,
+                "items": lines
+            }))
+            .unwrap();
+            let orders = &orders;
+            async move { orders.create_order(tenant_id, request).await.unwrap().id }
+        };
+        let line = |item_id: Uuid, quantity: i32, unit_price: f64, expected: Option<i64>| {
+            json!({
+                "item_id": item_id,
+                "item_name": "Part",
+                "quantity": quantity,
+                "unit_price": unit_price,
+                "expected_date": expected.map(days_ago)
+            })
+        };
+        let first = purchase_order(
+            "PO-A",
+            "approved",
+            20,
+            json!([
+                line(resistor, 10, 11.0, Some(10)),
+                line(capacitor, 5, 5.0, Some(8))
+            ]),
+        )
+        .await;
+        purchase_order(
+            "PO-B",
+            "submitted",
+            15,
+            json!([
+                line(resistor, 4, 10.0, Some(3)),
+                line(capacitor, 2, 5.0, None),
+                line(resistor, 1, 10.0, Some(-5))
+            ]),
+        )
+        .await;
+        // Drafts are not commitments and are left out
+        purchase_order(
+            "PO-C",
+            "draft",
+            15,
+            json!([line(resistor, 100, 50.0, Some(5))]),
+        )
+        .await;
+
+        let stock = StockService::new(database.clone());
+        for (item_id, quantity, received) in
+            [(resistor, 6, 12), (resistor, 4, 10), (capacitor, 5, 5)]
+        {
+            stock
+                .record_movement(
+                    tenant_id,
+                    item_id,
+                    Some(buyer.id),
+                    serde_json::from_value::<RecordStockMovementRequest>(json!({
+                        "context": "store",
+                        "movement_type": "receipt",
+                        "quantity": quantity,
+                        "reference_type": "order",
+                        "reference_id": first,
+                        "occurred_at": days_ago(received)
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let quality = QualityService::new(database.clone());
+        let ncr = |quantity: i32| {
+            let request = serde_json::from_value(json!({
+                "title": "Resistors out of tolerance",
+                "source_type": "lot",
+                "severity": "major",
+                "item_id": resistor,
+                "lot_number": "L-1",
+                "quantity_affected": quantity,
+                "vendor_id": vendor_id
+            }))
+            .unwrap();
+            let quality = &quality;
+            async move {
+                quality
+                    .create_ncr(tenant_id, Some(buyer.id), request)
+                    .await
+                    .unwrap()
+                    .id
+            }
+        };
+        ncr(3).await;
+        let cancelled = ncr(50).await;
+        quality
+            .cancel_ncr(tenant_id, cancelled)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let scorecards = ScorecardService::new(database.clone());
+        let scorecard = scorecards
+            .vendor_scorecard(
+                tenant_id,
+                vendor_id,
+                VendorScorecardQuery {
+                    from: None,
+                    to: Some(now + Duration::hours(1)),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(scorecard.company.as_deref(), Some("Acme Components Ltd"));
+        assert_eq!(scorecard.purchase_orders, 2);
+
+        // One line on time, one three days late, one overdue; the future line is not due yet
+        let delivery = &scorecard.delivery;
+        assert_eq!(delivery.lines_scored, 3);
+        assert_eq!(
+            (delivery.on_time, delivery.late, delivery.overdue),
+            (1, 1, 1)
+        );
+        assert_eq!(delivery.average_days_late, Some(3.0));
+        assert!((delivery.on_time_rate.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(delivery.lines_without_promise_date, 1);
+
+        assert_eq!(scorecard.quality.ncrs, 1);
+        assert_eq!(scorecard.quality.major, 1);
+        assert_eq!(scorecard.quality.quantity_affected, 3);
+        assert_eq!(scorecard.quality.units_received, Quantity::from(15));
+        assert_eq!(scorecard.quality.defect_ppm, Some(200_000.0));
+
+        // Only resistor lines have a recorded price; one of them cost 1.00 a unit more
+        let price = &scorecard.price;
+        assert_eq!((price.lines, price.compared_lines), (5, 3));
+        assert_eq!(price.ordered_value, 195.0);
+        assert_eq!(price.reference_value, 150.0);
+        assert_eq!(price.variance, 10.0);
+        assert!((price.variance_percent.unwrap() - 100.0 / 15.0).abs() < 1e-9);
+
+        // A period before any activity scores nothing
+        let empty = scorecards
+            .vendor_scorecard(
+                tenant_id,
+                vendor_id,
+                VendorScorecardQuery {
+                    from: Some(days_ago(400)),
+                    to: Some(days_ago(300)),
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(empty.purchase_orders, 0);
+        assert_eq!(empty.delivery.on_time_rate, None);
+        assert_eq!(empty.quality.defect_ppm, None);
+
+        // Only vendors have a scorecard
+        for id in [buyer.id, Uuid::new_v4()] {
+            let missing = scorecards
+                .vendor_scorecard(
+                    tenant_id,
+                    id,
+                    VendorScorecardQuery {
+                        from: None,
+                        to: None,
+                    },
+                )
+                .await
+                .unwrap();
+            assert!(missing.is_none());
+        }
+    }
+
+    #[test]
+    fn test_access_level_cache_expiry_and_invalidation() {
+        use ems_server::{models::AccessLevel, services::AccessLevelCache};
+        use std::time::Duration;
+
+        let cache = AccessLevelCache::new(Duration::from_millis(50));
+        let (tenant_id, person_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        cache.insert(tenant_id, person_id, AccessLevel::Admin);
+        assert_eq!(cache.get(tenant_id, person_id), Some(AccessLevel::Admin));
+        assert_eq!(cache.get(Uuid::new_v4(), person_id), None);
+
+        cache.invalidate(tenant_id, person_id);
+        assert_eq!(cache.get(tenant_id, person_id), None);
+
+        cache.insert(tenant_id, person_id, AccessLevel::Standard);
+        std::thread::sleep(Duration::from_millis(80));
+        assert_eq!(cache.get(tenant_id, person_id), None);
+
+        // A zero TTL turns caching off
+        let disabled = AccessLevelCache::new(Duration::ZERO);
+        disabled.insert(tenant_id, person_id, AccessLevel::Admin);
+        assert_eq!(disabled.get(tenant_id, person_id), None);
+    }
 }
@@ -0,0 +1,1091 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::{Duration, Utc};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            CapaActionType, CapaStatus, CreateNcrRequest, NcrSeverity, NcrStatus, NewPerson, Person,
+        },
+        routes::quality::routes,
+        schema::person,
+        services::{DatabaseService, JobService, QualityService, TenantService},
+        utils::quality::{close_blocker, days_overdue},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // NCR API Tests
+
+    #[tokio::test]
+    async fn test_list_ncrs() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/ncrs?status=open&severity=critical&source_type=machine",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quality routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_ncr() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let ncr_data = json!({
+            "title": "Solder bridges on U3",
+            "source_type": "job",
+            "job_id": Uuid::new_v4(),
+            "severity": "major",
+            "actions": [
+                { "action_type": "containment", "description": "Quarantine the batch" }
+            ]
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/ncrs", Some(ncr_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_ncr_rejects_invalid_data() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let ncr_data = json!({
+            "title": "",
+            "source_type": "machine",
+            "machine_id": Uuid::new_v4(),
+            "severity": "minor"
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/ncrs", Some(ncr_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNPROCESSABLE_ENTITY
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_capa_action() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/ncrs/{}/actions/{}", Uuid::new_v4(), Uuid::new_v4()),
+            Some(json!({ "status": "completed", "outcome": "Stencil worn" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_quality_dashboard() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/dashboard", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_overdue_capas() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/capas/overdue?owner_id={}&limit=10", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // NCR rule tests
+
+    #[test]
+    fn test_close_blocker() {
+        use CapaActionType::*;
+        use CapaStatus::*;
+
+        assert_eq!(close_blocker(NcrSeverity::Minor, &[]), None);
+        assert_eq!(
+            close_blocker(NcrSeverity::Minor, &[(Containment, InProgress)]),
+            Some("CAPA actions still open: 1".to_string())
+        );
+        assert_eq!(
+            close_blocker(NcrSeverity::Minor, &[(Containment, Cancelled)]),
+            None
+        );
+
+        // Major and critical NCRs need the cause found and corrected
+        assert!(
+            close_blocker(NcrSeverity::Major, &[(Containment, Completed)])
+                .unwrap()
+                .contains("root cause")
+        );
+        assert!(close_blocker(
+            NcrSeverity::Critical,
+            &[(RootCause, Completed), (Corrective, Cancelled)]
+        )
+        .unwrap()
+        .contains("corrective action"));
+        assert_eq!(
+            close_blocker(
+                NcrSeverity::Critical,
+                &[(RootCause, Completed), (Corrective, Completed)]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_days_overdue() {
+        let now = Utc::now();
+        assert_eq!(days_overdue(now - Duration::days(3), now), 3);
+        assert_eq!(days_overdue(now - Duration::hours(5), now), 0);
+        assert_eq!(days_overdue(now + Duration::days(2), now), 0);
+    }
+
+    #[test]
+    fn test_ncr_request_checks() {
+        let request = |body: Value| serde_json::from_value::<CreateNcrRequest>(body).unwrap();
+
+        assert!(request(json!({
+            "title": "Scratched housings", "source_type": "job", "severity": "minor"
+        }))
+        .check()
+        .is_err());
+        assert!(request(json!({
+            "title": "Out of tolerance", "source_type": "lot", "severity": "major",
+            "item_id": Uuid::new_v4()
+        }))
+        .check()
+        .is_err());
+        assert!(request(json!({
+            "title": "Nozzle drift", "source_type": "machine", "severity": "critical",
+            "machine_id": Uuid::new_v4()
+        }))
+        .check()
+        .is_ok());
+    }
+
+    // NCR and CAPA workflow
+
+    #[tokio::test]
+    async fn test_ncr_capa_workflow() {
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenants = TenantService::new(database.clone());
+        let tenant = |name: &str| {
+            let request = serde_json::from_value(
+                json!({ "name": name, "subdomain": format!("{}-{}", name, suffix) }),
+            )
+            .unwrap();
+            let tenants = &tenants;
+            async move { tenants.create_tenant(request).await.unwrap().id }
+        };
+        let tenant_id = tenant("ncr").await;
+        let other_tenant_id = tenant("ncr-other").await;
+        let engineer: Person = diesel::insert_into(person::table)
+            .values(&NewPerson {
+                supabase_uid: Uuid::new_v4(),
+                name: "Quality Engineer".to_string(),
+                email: format!("quality-{}@example.com", suffix),
+                phone: None,
+                global_access: Some(vec![]),
+                is_active: Some(true),
+            })
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        let job_id = JobService::new(database.clone())
+            .create_job(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "job_number": format!("MFG-{}", suffix),
+                    "quantity": 50,
+                    "job_type": "manufacturing"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let quality = QualityService::new(database.clone());
+
+        // References must exist in the tenant
+        let error = quality
+            .create_ncr(
+                tenant_id,
+                Some(engineer.id),
+                serde_json::from_value(json!({
+                    "title": "Unknown lot", "source_type": "lot", "severity": "minor",
+                    "item_id": Uuid::new_v4(), "lot_number": "L-1"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Item not found"));
+
+        let ncr = quality
+            .create_ncr(
+                tenant_id,
+                Some(engineer.id),
+                serde_json::from_value(json!({
+                    "ncr_number": format!("NCR-{}", suffix),
+                    "title": "Solder bridges on U3",
+                    "source_type": "job",
+                    "job_id": job_id,
+                    "quantity_affected": 12,
+                    "severity": "major",
+                    "owner_id": engineer.id,
+                    "due_date": Utc::now() + Duration::days(14),
+                    "actions": [{
+                        "action_type": "containment",
+                        "description": "Quarantine and 100% inspect the batch",
+                        "due_date": Utc::now() - Duration::days(2)
+                    }]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ncr.status, NcrStatus::Open);
+        assert!(!ncr.overdue);
+        assert_eq!(ncr.actions.len(), 1);
+        let containment = &ncr.actions[0];
+        assert_eq!(containment.owner_id, Some(engineer.id));
+        assert!(containment.overdue);
+
+        assert!(quality
+            .get_ncr(other_tenant_id, ncr.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        // The overdue containment shows up on the dashboard
+        let dashboard = quality.dashboard(tenant_id).await.unwrap();
+        assert_eq!(dashboard.open_ncrs, 1);
+        assert_eq!(dashboard.open_ncrs_by_severity.major, 1);
+        assert_eq!(dashboard.open_capa_actions, 1);
+        assert_eq!(dashboard.overdue_capa_actions, 1);
+        assert_eq!(dashboard.overdue_capas.len(), 1);
+        assert_eq!(dashboard.overdue_capas[0].action.id, containment.id);
+        assert_eq!(dashboard.overdue_capas[0].days_overdue, 2);
+        let overdue = quality
+            .list_overdue_capas(
+                tenant_id,
+                serde_json::from_value(json!({ "owner_id": engineer.id })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].ncr_number, ncr.ncr_number);
+        assert_eq!(
+            quality
+                .dashboard(other_tenant_id)
+                .await
+                .unwrap()
+                .overdue_capas
+                .len(),
+            0
+        );
+
+        let error = quality
+            .close_ncr(tenant_id, ncr.id, Some(engineer.id))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("CAPA actions still open: 1"));
+
+        let update = |status: &str, outcome: Option<&str>| {
+            serde_json::from_value(json!({ "status": status, "outcome": outcome })).unwrap()
+        };
+        let ncr = quality
+            .update_action(
+                tenant_id,
+                ncr.id,
+                containment.id,
+                Some(engineer.id),
+                update("completed", None),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ncr.actions[0].status, CapaStatus::Completed);
+        assert_eq!(ncr.actions[0].completed_by_id, Some(engineer.id));
+        assert!(!ncr.actions[0].overdue);
+
+        // Completed actions are final
+        let error = quality
+            .update_action(
+                tenant_id,
+                ncr.id,
+                containment.id,
+                None,
+                update("open", None),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be updated"));
+
+        // A major NCR cannot close on containment alone
+        let error = quality
+            .close_ncr(tenant_id, ncr.id, Some(engineer.id))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("root cause"));
+
+        let add = |action_type: &str, description: &str| {
+            serde_json::from_value(
+                json!({ "action_type": action_type, "description": description }),
+            )
+            .unwrap()
+        };
+        let ncr = quality
+            .add_action(tenant_id, ncr.id, add("root_cause", "Analyse stencil wear"))
+            .await
+            .unwrap()
+            .unwrap();
+        let root_cause = ncr.actions[1].id;
+        assert_eq!(ncr.actions[1].action_type, CapaActionType::RootCause);
+        let error = quality
+            .update_action(
+                tenant_id,
+                ncr.id,
+                root_cause,
+                Some(engineer.id),
+                update("completed", None),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("needs an outcome"));
+        quality
+            .update_action(
+                tenant_id,
+                ncr.id,
+                root_cause,
+                Some(engineer.id),
+                update("completed", Some("Stencil worn past 20k prints")),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        let ncr = quality
+            .add_action(tenant_id, ncr.id, add("corrective", "Replace the stencil"))
+            .await
+            .unwrap()
+            .unwrap();
+        let corrective = ncr.actions[2].id;
+        quality
+            .update_action(
+                tenant_id,
+                ncr.id,
+                corrective,
+                Some(engineer.id),
+                update("completed", Some("Stencil replaced")),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let ncr = quality
+            .close_ncr(tenant_id, ncr.id, Some(engineer.id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ncr.status, NcrStatus::Closed);
+        assert_eq!(ncr.closed_by_id, Some(engineer.id));
+        assert!(ncr.closed_at.is_some());
+
+        // Closed NCRs are read-only
+        let error = quality
+            .update_ncr(
+                tenant_id,
+                ncr.id,
+                serde_json::from_value(json!({ "severity": "critical" })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be changed"));
+
+        let dashboard = quality.dashboard(tenant_id).await.unwrap();
+        assert_eq!(dashboard.open_ncrs, 0);
+        assert_eq!(dashboard.overdue_capa_actions, 0);
+
+        let listed = quality
+            .list_ncrs(
+                tenant_id,
+                serde_json::from_value(json!({ "job_id": job_id, "status": "closed" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].actions.len(), 3);
+    }
+
+    // SPC tests
+
+    #[tokio::test]
+    async fn test_create_characteristic() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/characteristics",
+            Some(json!({
+                "item_id": Uuid::new_v4(),
+                "name": "Bore diameter",
+                "unit": "mm",
+                "nominal": 12.0,
+                "lower_tolerance": 0.02,
+                "upper_tolerance": 0.02,
+                "subgroup_size": 5
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quality routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_spc_statistics() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/characteristics/{}/spc?points=50", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quality routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_characteristic_request_checks() {
+        use ems_server::models::CreateItemCharacteristicRequest;
+
+        let request =
+            |body: Value| serde_json::from_value::<CreateItemCharacteristicRequest>(body).unwrap();
+        let item_id = Uuid::new_v4();
+
+        // A specification needs at least one limit
+        assert!(
+            request(json!({ "item_id": item_id, "name": "Length", "nominal": 40.0 }))
+                .check()
+                .is_err()
+        );
+        assert!(request(json!({
+            "item_id": item_id, "name": "Flatness", "nominal": 0.0, "upper_tolerance": 0.05
+        }))
+        .check()
+        .is_ok());
+
+        // Heartbeat mappings name the machine and where the value sits in its payload
+        assert!(request(json!({
+            "item_id": item_id, "name": "Length", "nominal": 40.0, "lower_tolerance": 0.1,
+            "machine_id": Uuid::new_v4()
+        }))
+        .check()
+        .is_err());
+        assert!(request(json!({
+            "item_id": item_id, "name": "Length", "nominal": 40.0, "lower_tolerance": 0.1,
+            "machine_id": Uuid::new_v4(), "payload_pointer": "length"
+        }))
+        .check()
+        .is_err());
+        assert!(request(json!({
+            "item_id": item_id, "name": "Length", "nominal": 40.0, "lower_tolerance": 0.1,
+            "machine_id": Uuid::new_v4(), "payload_pointer": "/probe/length"
+        }))
+        .check()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_spc_control_chart_and_capability() {
+        use ems_server::utils::spc::{capability, control_chart};
+
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-3;
+
+        // Individuals: moving ranges 2, 1, 2, 1
+        let chart = control_chart(&[10.0, 12.0, 11.0, 13.0, 12.0], 1).unwrap();
+        assert!(close(chart.center_line, 11.6));
+        assert!(close(chart.range_center_line, 1.5));
+        assert!(close(chart.sigma_within, 1.5 / 1.128));
+        assert!(close(chart.upper_control_limit, 11.6 + 3.0 * 1.5 / 1.128));
+        assert_eq!(chart.ranges[0], None);
+
+        let (cp, cpk) = capability(11.6, chart.sigma_within, Some(5.0), Some(18.0));
+        assert!(close(cp.unwrap(), 13.0 / (6.0 * chart.sigma_within)));
+        assert!(close(cpk.unwrap(), 6.4 / (3.0 * chart.sigma_within)));
+        // One-sided specifications have a Cpk but no Cp
+        let (cp, cpk) = capability(11.6, chart.sigma_within, None, Some(18.0));
+        assert_eq!(cp, None);
+        assert!(cpk.is_some());
+
+        // Subgroups of two; the first measurement does not fill a subgroup
+        let chart = control_chart(&[9.0, 1.0, 3.0, 2.0, 2.0, 4.0, 2.0], 2).unwrap();
+        assert_eq!(chart.points, vec![2.0, 2.0, 3.0]);
+        assert!(close(chart.range_center_line, 4.0 / 3.0));
+        assert!(close(
+            chart.upper_control_limit - chart.center_line,
+            1.880 * 4.0 / 3.0
+        ));
+        assert!(close(chart.range_upper_control_limit, 3.267 * 4.0 / 3.0));
+
+        assert!(control_chart(&[1.0], 1).is_none());
+        assert!(control_chart(&[1.0, 2.0, 3.0], 2).is_none());
+    }
+
+    #[test]
+    fn test_spc_rule_violations() {
+        use ems_server::{models::SpcRule, utils::spc::rule_violations};
+
+        assert_eq!(
+            rule_violations(&[0.5, -0.5, 3.5], 0.0, 1.0),
+            vec![(2, SpcRule::BeyondControlLimits)]
+        );
+        assert_eq!(
+            rule_violations(&[2.5, 0.0, 2.5], 0.0, 1.0),
+            vec![(2, SpcRule::TwoOfThreeBeyondTwoSigma)]
+        );
+        assert_eq!(
+            rule_violations(&[1.5, 1.5, 0.0, 1.5, 1.5], 0.0, 1.0),
+            vec![(4, SpcRule::FourOfFiveBeyondOneSigma)]
+        );
+        assert_eq!(
+            rule_violations(&[0.1; 8], 0.0, 1.0),
+            vec![(7, SpcRule::EightOnOneSide)]
+        );
+        assert_eq!(
+            rule_violations(&[-0.3, -0.2, -0.1, 0.1, 0.2, 0.3], 0.0, 1.0),
+            vec![(5, SpcRule::SixTrending)]
+        );
+        // Runs on both sides of the center line break no rule
+        assert!(
+            rule_violations(&[0.5, -0.5, 0.5, -0.5, 0.5, -0.5, 0.5, -0.5], 0.0, 1.0).is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_spc_measurements() {
+        use ems_server::models::{HeartbeatRequest, MachineStatus};
+        use ems_server::services::{ItemService, MachineService, SpcService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "SPC test", "subdomain": format!("spc-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let shaft = ItemService::new(database.clone())
+            .create_item(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "internal_part_number": format!("SHAFT-{}", suffix),
+                    "manufacturer": "Acme",
+                    "context": "finished_goods",
+                    "quantity": 0
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let machines = MachineService::new(database.clone());
+        let machine = |name: &str, category: &str| {
+            let request = serde_json::from_value(json!({
+                "name": name,
+                "ip": "10.0.0.60",
+                "port": 502,
+                "protocol": "tcp",
+                "status": "idle",
+                "metadata": { "category": category }
+            }))
+            .unwrap();
+            let machines = &machines;
+            async move {
+                machines
+                    .create_machine(tenant_id, request)
+                    .await
+                    .unwrap()
+                    .id
+            }
+        };
+        let lathe = machine("Lathe", "production").await;
+        let gauge = machine("Gauge", "qa").await;
+
+        let spc = SpcService::new(database.clone());
+        let characteristic = |machine_id: Uuid| {
+            serde_json::from_value(json!({
+                "item_id": shaft,
+                "name": "Diameter",
+                "unit": "mm",
+                "nominal": 20.0,
+                "lower_tolerance": 0.05,
+                "upper_tolerance": 0.05,
+                "machine_id": machine_id,
+                "payload_pointer": "/probe/diameter"
+            }))
+            .unwrap()
+        };
+
+        // Only QA machines report measurements
+        let error = spc
+            .create_characteristic(tenant_id, characteristic(lathe))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not a QA machine"));
+        let diameter = spc
+            .create_characteristic(tenant_id, characteristic(gauge))
+            .await
+            .unwrap();
+        assert_eq!(diameter.lower_spec_limit, Some(19.95));
+
+        let start = Utc::now() - Duration::hours(1);
+        let values = [20.01, 19.99, 20.02, 20.0, 19.98, 20.01, 20.0, 20.09];
+        let recorded = spc
+            .record_measurements(
+                tenant_id,
+                diameter.id,
+                None,
+                serde_json::from_value(json!({
+                    "measurements": values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, v)| json!({
+                            "value": v,
+                            "measured_at": start + Duration::minutes(i as i64)
+                        }))
+                        .collect::<Vec<_>>()
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(recorded.len(), values.len());
+
+        // The gauge reports the next part in its heartbeat
+        machines
+            .update_heartbeat(
+                tenant_id,
+                gauge,
+                HeartbeatRequest {
+                    status: MachineStatus::Idle,
+                    action: None,
+                    payload: Some(json!({ "probe": { "diameter": "20.03" } })),
+                    metadata: Some(json!({ "category": "qa" })),
+                    config_version: None,
+                    command_results: None,
+                },
+            )
+            .await
+            .unwrap();
+        let measurements = spc
+            .list_measurements(
+                tenant_id,
+                diameter.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(measurements.len(), values.len() + 1);
+        assert_eq!(measurements[0].value, 20.03);
+        assert_eq!(measurements[0].machine_id, Some(gauge));
+
+        let statistics = spc
+            .get_statistics(
+                tenant_id,
+                diameter.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(statistics.measurement_count, values.len() + 1);
+        assert_eq!(statistics.points.len(), values.len() + 1);
+        assert_eq!(statistics.out_of_spec_count, 1);
+        assert!(statistics.cp.is_some() && statistics.cpk.is_some());
+        assert!(statistics.cpk <= statistics.cp);
+    }
+
+    // Calibration API Tests
+
+    #[tokio::test]
+    async fn test_record_calibration() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/calibrations",
+            Some(json!({
+                "instrument_id": Uuid::new_v4(),
+                "result": "pass",
+                "interval_days": 365,
+                "certificate_number": "CAL-2024-118"
+            })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quality routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_equipment_calibration() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/calibrations/equipment?status=overdue",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quality routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn test_calibration_request_checks() {
+        use ems_server::models::RecordCalibrationRequest;
+
+        let request =
+            |body: Value| serde_json::from_value::<RecordCalibrationRequest>(body).unwrap();
+        let calibrated_at = Utc::now();
+
+        // A calibration covers exactly one machine or instrument
+        assert!(request(json!({ "result": "pass" })).check().is_err());
+        assert!(request(json!({
+            "machine_id": Uuid::new_v4(), "instrument_id": Uuid::new_v4(), "result": "pass"
+        }))
+        .check()
+        .is_err());
+        assert!(
+            request(json!({ "instrument_id": Uuid::new_v4(), "result": "adjusted" }))
+                .check()
+                .is_ok()
+        );
+
+        // The due date is given or follows from an interval, and comes after the calibration
+        assert!(request(json!({
+            "machine_id": Uuid::new_v4(), "result": "pass",
+            "due_at": calibrated_at + Duration::days(90), "interval_days": 90
+        }))
+        .check()
+        .is_err());
+        assert!(request(json!({
+            "machine_id": Uuid::new_v4(), "result": "pass",
+            "calibrated_at": calibrated_at, "due_at": calibrated_at - Duration::days(1)
+        }))
+        .check()
+        .is_err());
+    }
+
+    #[test]
+    fn test_calibration_status() {
+        use ems_server::models::{CalibrationResult, CalibrationStatus};
+        use ems_server::utils::calibration::{calibration_status, due_date};
+
+        let now = Utc::now();
+        assert_eq!(due_date(now, 30), now + Duration::days(30));
+
+        assert_eq!(
+            calibration_status(None, now),
+            CalibrationStatus::Uncalibrated
+        );
+        assert_eq!(
+            calibration_status(
+                Some((CalibrationResult::Pass, now + Duration::days(60))),
+                now
+            ),
+            CalibrationStatus::Current
+        );
+        assert_eq!(
+            calibration_status(
+                Some((CalibrationResult::Adjusted, now + Duration::days(3))),
+                now
+            ),
+            CalibrationStatus::DueSoon
+        );
+        assert_eq!(
+            calibration_status(
+                Some((CalibrationResult::Pass, now - Duration::days(1))),
+                now
+            ),
+            CalibrationStatus::Overdue
+        );
+        assert_eq!(
+            calibration_status(
+                Some((CalibrationResult::Fail, now + Duration::days(60))),
+                now
+            ),
+            CalibrationStatus::Failed
+        );
+
+        // Uncalibrated and due soon equipment may still be used
+        assert!(!CalibrationStatus::Uncalibrated.is_out_of_calibration());
+        assert!(!CalibrationStatus::DueSoon.is_out_of_calibration());
+        assert!(CalibrationStatus::Overdue.is_out_of_calibration());
+        assert!(CalibrationStatus::Failed.is_out_of_calibration());
+    }
+
+    #[tokio::test]
+    async fn test_calibration_blocks_measurements() {
+        use ems_server::models::CalibrationStatus;
+        use ems_server::services::{
+            CalibrationService, CalibrationWorker, ItemService, SpcService,
+        };
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({
+                    "name": "Calibration test",
+                    "subdomain": format!("cal-{}", suffix)
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let bracket = ItemService::new(database.clone())
+            .create_item(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "internal_part_number": format!("BRACKET-{}", suffix),
+                    "manufacturer": "Acme",
+                    "context": "finished_goods",
+                    "quantity": 0
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let calibration = CalibrationService::new(database.clone());
+        let caliper = calibration
+            .create_instrument(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": "Digital caliper",
+                    "serial_number": format!("CAL-{}", suffix),
+                    "instrument_type": "caliper",
+                    "calibration_interval_days": 180
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(caliper.calibration_status, CalibrationStatus::Uncalibrated);
+
+        // The due date follows from the instrument's interval
+        let calibrated_at = Utc::now() - Duration::days(200);
+        let record = calibration
+            .record_calibration(
+                tenant_id,
+                None,
+                serde_json::from_value(json!({
+                    "instrument_id": caliper.id,
+                    "calibrated_at": calibrated_at,
+                    "result": "pass",
+                    "performed_by": "Metrology lab"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.due_at, calibrated_at + Duration::days(180));
+
+        let spc = SpcService::new(database.clone());
+        let width = spc
+            .create_characteristic(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "item_id": bracket,
+                    "name": "Width",
+                    "nominal": 30.0,
+                    "lower_tolerance": 0.1,
+                    "upper_tolerance": 0.1
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let measure = || {
+            serde_json::from_value(json!({
+                "measurements": [{ "value": 30.02 }],
+                "instrument_id": caliper.id
+            }))
+            .unwrap()
+        };
+
+        // Inspections with the overdue caliper are refused
+        let error = spc
+            .record_measurements(tenant_id, width.id, None, measure())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("out of calibration"));
+
+        // The calibration check flags the overdue calibration once
+        let worker = CalibrationWorker::new(database.clone(), std::time::Duration::from_secs(60));
+        assert!(worker.run_once().await.unwrap() >= 1);
+        let flagged = calibration
+            .list_calibrations(
+                tenant_id,
+                serde_json::from_value(json!({ "instrument_id": caliper.id })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(flagged[0].overdue_flagged_at.is_some());
+        let overdue = calibration
+            .list_equipment(
+                tenant_id,
+                serde_json::from_value(json!({ "status": "overdue" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].instrument_id, Some(caliper.id));
+
+        // A fresh calibration puts the caliper back in service
+        calibration
+            .record_calibration(
+                tenant_id,
+                None,
+                serde_json::from_value(
+                    json!({ "instrument_id": caliper.id, "result": "adjusted" }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let recorded = spc
+            .record_measurements(tenant_id, width.id, None, measure())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(recorded[0].instrument_id, Some(caliper.id));
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        middleware::from_fn,
+        routing::get,
+        Router,
+    };
+    use std::time::Duration;
+    use tower::ServiceExt; // for `oneshot`
+
+    use ems_server::middleware::instrumentation::query_metrics_middleware;
+    use ems_server::utils::query_metrics::{
+        current_endpoint, endpoint_label, loggable_statement, tenant_from_statement, QueryMetrics,
+        BACKGROUND_ENDPOINT, MAX_LOGGED_STATEMENT_CHARS,
+    };
+
+    #[test]
+    fn test_endpoint_label() {
+        assert_eq!(
+            endpoint_label(
+                "GET",
+                "/api/v1/machines/5f0c2d3e-8a1b-4c6d-9e7f-0a1b2c3d4e5f/jobs"
+            ),
+            "GET /api/v1/machines/:id/jobs"
+        );
+        assert_eq!(
+            endpoint_label("PUT", "/api/v1/orders/42/items/7"),
+            "PUT /api/v1/orders/:id/items/:id"
+        );
+        assert_eq!(endpoint_label("GET", "/api/v1/items"), "GET /api/v1/items");
+    }
+
+    #[test]
+    fn test_tenant_from_statement() {
+        let tenant = "5f0c2d3e-8a1b-4c6d-9e7f-0a1b2c3d4e5f";
+        assert_eq!(
+            tenant_from_statement(&format!("SET app.current_tenant_id = '{}'", tenant)),
+            Some(tenant)
+        );
+        assert_eq!(
+            tenant_from_statement(&format!("SET LOCAL app.current_tenant_id = '{}';", tenant)),
+            Some(tenant)
+        );
+        assert_eq!(
+            tenant_from_statement("SET app.current_tenant_id = ''"),
+            None
+        );
+        assert_eq!(tenant_from_statement("SET search_path = public"), None);
+        assert_eq!(tenant_from_statement("SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_loggable_statement() {
+        // Bind values are never logged
+        assert_eq!(
+            loggable_statement(
+                "SELECT * FROM person WHERE email = $1 -- binds: [\"operator@example.com\"]"
+            ),
+            "SELECT * FROM person WHERE email = $1"
+        );
+
+        let long = format!("SELECT {}", "x, ".repeat(1000));
+        let logged = loggable_statement(&long);
+        assert!(logged.ends_with("..."));
+        assert_eq!(logged.chars().count(), MAX_LOGGED_STATEMENT_CHARS + 3);
+    }
+
+    #[test]
+    fn test_query_metrics_registry() {
+        let metrics = QueryMetrics::default();
+        metrics.record("GET /items", Duration::from_millis(10), false, false);
+        metrics.record("GET /items", Duration::from_millis(30), false, true);
+        metrics.record("GET /machines", Duration::from_millis(900), true, false);
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.len(), 2);
+        // Most database time first
+        assert_eq!(stats[0].endpoint, "GET /machines");
+        assert_eq!(stats[0].slow, 1);
+        assert_eq!(stats[1].queries, 2);
+        assert_eq!(stats[1].failed, 1);
+        assert_eq!(stats[1].mean_ms, 20.0);
+        assert_eq!(stats[1].max_ms, 30.0);
+
+        metrics.reset();
+        assert!(metrics.snapshot().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_scopes_endpoint() {
+        let app = Router::new()
+            .route("/machines/:id", get(|| async { current_endpoint() }))
+            .layer(from_fn(query_metrics_middleware));
+
+        let request = Request::builder()
+            .uri("/machines/17")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "GET /machines/:id");
+
+        // Outside a request, queries count as background work
+        assert_eq!(current_endpoint(), BACKGROUND_ENDPOINT);
+    }
+}
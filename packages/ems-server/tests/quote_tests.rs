@@ -0,0 +1,498 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::{DateTime, Duration, Utc};
+    use diesel::prelude::*;
+    use diesel_async::RunQueryDsl;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            CreateQuoteRequest, NewPerson, NewTenantPerson, Person, PersonRole, QuotePricing,
+            QuoteStatus, SendQuoteRequest,
+        },
+        routes::quote::routes,
+        schema::{order_items, orders, person, quotes, tenant_person},
+        services::{DatabaseService, ItemService, QuoteService, TenantService},
+        utils::i18n::Locale,
+        utils::pdf::{encode_text, Font, PdfDocument},
+        utils::quote::{bom_cost, margin_price},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Quote API Tests
+
+    #[tokio::test]
+    async fn test_create_quote() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let quote_data = json!({
+            "customer_id": Uuid::new_v4(),
+            "items": [
+                { "item_name": "Setup fee", "quantity": 1, "unit_price": 50.0 }
+            ]
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/", Some(quote_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quote routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_quote_pdf() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/{}/pdf", Uuid::new_v4()),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Quote routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Pricing and document helper tests
+
+    #[test]
+    fn test_bom_cost_rolls_up_sub_assemblies() {
+        let (top, sub, a, b) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let boms = HashMap::from([(top, vec![(sub, 2.0), (a, 1.0)]), (sub, vec![(b, 3.0)])]);
+        let costs = HashMap::from([(a, 1.25), (b, 0.5)]);
+
+        assert_eq!(bom_cost(top, &boms, &costs), Ok(4.25));
+        assert!(bom_cost(a, &boms, &costs).unwrap_err().contains("no BOM"));
+
+        let missing = HashMap::from([(a, 1.25)]);
+        assert!(bom_cost(top, &boms, &missing)
+            .unwrap_err()
+            .contains("no cost"));
+
+        let cyclic = HashMap::from([(top, vec![(sub, 1.0)]), (sub, vec![(top, 1.0)])]);
+        assert!(bom_cost(top, &cyclic, &costs)
+            .unwrap_err()
+            .contains("cycle"));
+    }
+
+    #[test]
+    fn test_margin_price() {
+        assert_eq!(margin_price(10.0, 25.0), 12.5);
+        assert_eq!(margin_price(3.333, 10.0), 3.67);
+        assert_eq!(margin_price(8.0, 0.0), 8.0);
+    }
+
+    #[test]
+    fn test_pdf_document_structure() {
+        let mut pdf = PdfDocument::new();
+        pdf.text(50.0, 800.0, Font::Bold, 12.0, "Quote (1)");
+        pdf.add_page();
+        assert_eq!(pdf.page_count(), 2);
+
+        let bytes = pdf.render();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("(Quote \\(1\\)) Tj"));
+
+        // The xref table points at the objects it lists
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(bytes[startxref..].starts_with(b"xref"));
+
+        assert_eq!(encode_text("Café €"), b"Caf\xe9 ?".to_vec());
+    }
+
+    #[test]
+    fn test_quote_request_checks() {
+        let request = |items: Value| -> CreateQuoteRequest {
+            serde_json::from_value(json!({ "customer_id": Uuid::new_v4(), "items": items }))
+                .unwrap()
+        };
+        let item_id = Uuid::new_v4();
+
+        assert!(
+            request(json!([{ "item_name": "Labour", "quantity": 1, "unit_price": 5.0 }]))
+                .check()
+                .is_ok()
+        );
+        assert!(request(json!([{ "item_id": item_id, "quantity": 1 }]))
+            .check()
+            .is_ok());
+        // Lines without a price need an item to price from
+        assert!(request(json!([{ "item_name": "Labour", "quantity": 1 }]))
+            .check()
+            .is_err());
+        assert!(
+            request(json!([{ "item_id": item_id, "quantity": 1, "pricing": "bom_cost" }]))
+                .check()
+                .is_err()
+        );
+        assert!(
+            request(json!([{ "item_id": item_id, "quantity": 1, "margin_percent": 20.0 }]))
+                .check()
+                .is_err()
+        );
+
+        let line: CreateQuoteRequest = request(json!([{ "item_id": item_id, "quantity": 1 }]));
+        assert_eq!(line.items[0].pricing(), QuotePricing::Item);
+
+        let send: SendQuoteRequest =
+            serde_json::from_value(json!({ "recipients": ["buyer@example.com", "nope"] })).unwrap();
+        assert!(send.check().is_err());
+    }
+
+    // Quote workflow
+
+    #[tokio::test]
+    async fn test_quote_workflow() {
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let mut conn = database.get_connection().await.unwrap();
+        let suffix = &Uuid::new_v4().simple().to_string()[..12];
+
+        let tenant_id = TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(
+                    json!({ "name": "Quote test", "subdomain": format!("quote-{}", suffix) }),
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let new_person = |name: &str| NewPerson {
+            supabase_uid: Uuid::new_v4(),
+            name: name.to_string(),
+            email: format!("{}-{}@example.com", name.to_lowercase(), suffix),
+            phone: None,
+            global_access: Some(vec![]),
+            is_active: Some(true),
+        };
+        let seller: Person = diesel::insert_into(person::table)
+            .values(&new_person("Seller"))
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        let customer: Person = diesel::insert_into(person::table)
+            .values(&new_person("Customer"))
+            .returning(Person::as_returning())
+            .get_result(&mut conn)
+            .await
+            .unwrap();
+        diesel::insert_into(tenant_person::table)
+            .values(&NewTenantPerson {
+                person_id: customer.id,
+                tenant_id,
+                role: PersonRole::Customer.to_string(),
+                access_level: None,
+                is_primary: Some(false),
+            })
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let items = ItemService::new(database.clone());
+        let create_item = |part: &str, context: &str, pricing: Value| {
+            let items = &items;
+            let request = serde_json::from_value(json!({
+                "internal_part_number": format!("{}-{}", part, suffix),
+                "manufacturer": "Acme",
+                "description": format!("{} description", part),
+                "context": context,
+                "pricing": pricing
+            }))
+            .unwrap();
+            async move { items.create_item(tenant_id, request).await.unwrap().id }
+        };
+        let widget = create_item(
+            "WIDGET",
+            "finished_goods",
+            json!({ "price_breaks": [
+                { "quantity": 1, "unit_price": 12.0 },
+                { "quantity": 10, "unit_price": 10.0 }
+            ] }),
+        )
+        .await;
+        let assembly = create_item("ASSY", "finished_goods", Value::Null).await;
+        let bought = create_item("BOUGHT", "vendor", json!({ "unit_price": 2.0 })).await;
+        let stocked = create_item("STOCKED", "store", json!({ "unit_price": 1.5 })).await;
+        for (component, quantity) in [(bought, 2), (stocked, 4)] {
+            items
+                .create_bom_item(
+                    tenant_id,
+                    serde_json::from_value(json!({
+                        "parent_item_id": assembly,
+                        "component_item_id": component,
+                        "quantity": quantity
+                    }))
+                    .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let service = QuoteService::new(database.clone());
+        let create = |items: Value, valid_until: Option<DateTime<Utc>>| -> CreateQuoteRequest {
+            serde_json::from_value(json!({
+                "customer_id": customer.id,
+                "valid_until": valid_until,
+                "notes": "Prices exclude shipping",
+                "items": items
+            }))
+            .unwrap()
+        };
+        let mark_sent = |quote_id: Uuid| {
+            let database = database.clone();
+            async move {
+                let mut conn = database.get_connection().await.unwrap();
+                diesel::update(quotes::table.find(quote_id))
+                    .set(quotes::status.eq(QuoteStatus::Sent.to_string()))
+                    .execute(&mut conn)
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Bought-in parts have no selling price
+        let error = service
+            .create_quote(
+                tenant_id,
+                Some(seller.id),
+                create(json!([{ "item_id": bought, "quantity": 1 }]), None),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be priced"));
+
+        let quote = service
+            .create_quote(
+                tenant_id,
+                Some(seller.id),
+                create(
+                    json!([
+                        { "item_id": widget, "quantity": 10 },
+                        { "item_id": assembly, "quantity": 2, "pricing": "bom_cost", "margin_percent": 25.0 },
+                        { "item_name": "Setup fee", "quantity": 1, "unit_price": 30.0 }
+                    ]),
+                    Some(Utc::now() + Duration::days(30)),
+                ),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(quote.status, QuoteStatus::Draft);
+        assert!(quote.quote_number.starts_with("Q-"));
+        assert_eq!(quote.customer_name, "Customer");
+        assert!(!quote.is_expired);
+        assert_eq!(quote.items[0].unit_price, 10.0);
+        assert_eq!(quote.items[0].item_name, format!("WIDGET-{}", suffix));
+        assert_eq!(quote.items[1].unit_cost, Some(10.0));
+        assert_eq!(quote.items[1].unit_price, 12.5);
+        assert_eq!(quote.items[1].pricing, QuotePricing::BomCost);
+        assert_eq!(quote.total_amount, 155.0);
+
+        // Only sent quotes can be accepted
+        let error = service.accept_quote(tenant_id, quote.id).await.unwrap_err();
+        assert!(error.to_string().contains("cannot be accepted"));
+
+        let error = service
+            .send_quote(
+                tenant_id,
+                quote.id,
+                serde_json::from_value(json!({})).unwrap(),
+                None,
+                Locale::En,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not configured"));
+
+        let (number, pdf) = service
+            .quote_pdf(tenant_id, quote.id, Locale::En)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(number, quote.quote_number);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(String::from_utf8_lossy(&pdf).contains("155.00"));
+        let (_, pdf) = service
+            .quote_pdf(tenant_id, quote.id, Locale::Es)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(String::from_utf8_lossy(&pdf).contains("Precio unitario"));
+
+        mark_sent(quote.id).await;
+        let error = service
+            .update_quote(
+                tenant_id,
+                quote.id,
+                serde_json::from_value(json!({ "notes": "Late change" })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be updated"));
+
+        let quote = service
+            .accept_quote(tenant_id, quote.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(quote.status, QuoteStatus::Accepted);
+        assert!(quote.responded_at.is_some());
+
+        let quote = service
+            .convert_quote(
+                tenant_id,
+                seller.id,
+                quote.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(quote.status, QuoteStatus::Converted);
+        let order_id = quote.order_id.unwrap();
+
+        let (order_number, total, metadata): (String, f64, Option<Value>) = orders::table
+            .find(order_id)
+            .select((orders::order_number, orders::total_amount, orders::metadata))
+            .first(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(order_number, format!("SO-{}", quote.quote_number));
+        assert_eq!(total, 155.0);
+        assert_eq!(metadata.unwrap()["quote_id"], json!(quote.id));
+        let lines: Vec<(String, f64)> = order_items::table
+            .filter(order_items::order_id.eq(order_id))
+            .select((order_items::item_name, order_items::extended_price))
+            .load(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.contains(&("Setup fee".to_string(), 30.0)));
+
+        let error = service
+            .convert_quote(
+                tenant_id,
+                seller.id,
+                quote.id,
+                serde_json::from_value(json!({})).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("cannot be converted"));
+
+        // An expired quote can still be declined, but not accepted
+        let expired = service
+            .create_quote(
+                tenant_id,
+                Some(seller.id),
+                create(
+                    json!([{ "item_id": widget, "quantity": 1 }]),
+                    Some(Utc::now() - Duration::days(1)),
+                ),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(expired.is_expired);
+        assert_eq!(expired.total_amount, 12.0);
+        mark_sent(expired.id).await;
+
+        let error = service
+            .accept_quote(tenant_id, expired.id)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("expired"));
+
+        let rejected = service
+            .reject_quote(
+                tenant_id,
+                expired.id,
+                serde_json::from_value(json!({ "reason": "Went with another supplier" })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(rejected.status, QuoteStatus::Rejected);
+        assert_eq!(
+            rejected.rejection_reason.as_deref(),
+            Some("Went with another supplier")
+        );
+
+        let converted = service
+            .list_quotes(
+                tenant_id,
+                serde_json::from_value(json!({ "status": "converted" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(converted.len(), 1);
+        assert_eq!(converted[0].id, quote.id);
+    }
+}
@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        middleware::from_fn_with_state,
+        Router,
+    };
+    use chrono::Utc;
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        middleware::auth::auth_middleware,
+        routes::recalculation::routes,
+        services::DatabaseService,
+        utils::recalculation::{progress_percent, with_unit_cost},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes()
+            .layer(from_fn_with_state(state.clone(), auth_middleware))
+            .with_state(state)
+    }
+
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Recalculation API tests
+
+    #[tokio::test]
+    async fn test_enqueue_recalculation() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            "/",
+            Some(json!({ "kind": "inventory_valuation" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Recalculation routes require authentication, will fail without JWT token
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_recalculation_progress() {
+        assert_eq!(progress_percent(0, None), None);
+        assert_eq!(progress_percent(0, Some(0)), Some(100.0));
+        assert_eq!(progress_percent(1, Some(3)), Some(33.3));
+        assert_eq!(progress_percent(3, Some(3)), Some(100.0));
+        assert_eq!(progress_percent(5, Some(3)), Some(100.0));
+
+        // Other pricing keys are kept; an unchanged cost is not written again
+        let pricing = json!({ "unit_price": 12.0, "unit_cost": 7.5 });
+        assert_eq!(with_unit_cost(Some(&pricing), 7.5), None);
+        assert_eq!(
+            with_unit_cost(Some(&pricing), 8.25),
+            Some(json!({ "unit_price": 12.0, "unit_cost": 8.25 }))
+        );
+        assert_eq!(with_unit_cost(None, 3.0), Some(json!({ "unit_cost": 3.0 })));
+    }
+
+    #[tokio::test]
+    async fn test_recalculation_workflow() {
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::models::{
+            AccessLevel, CallerContext, RecalculationKind, RecalculationTaskStatus,
+        };
+        use ems_server::services::{OrderService, RecalculationService, RecalculationWorker};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let fixture = FixtureBuilder::tenant()
+            .named("Recalculation test")
+            .build(&database)
+            .await
+            .unwrap();
+        let tenant_id = fixture.tenant.id;
+        let admin = &fixture.admin;
+        let caller = fixture.caller();
+
+        // The order total is out of step with its lines
+        let order_id = OrderService::new(database.clone())
+            .create_order(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "order_number": format!("SO-{}", fixture.tenant.subdomain),
+                    "order_type": "customer_order",
+                    "external_entity_id": admin.id,
+                    "external_entity_type": "customer",
+                    "order_date": Utc::now(),
+                    "total_amount": 99.0,
+                    "status": "submitted",
+                    "created_by_id": admin.id,
+                    "items": [{ "item_name": "Cable harness", "quantity": 4, "unit_price": 10.0 }]
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let recalculations = RecalculationService::new(database.clone());
+
+        // Standard callers cannot queue recalculations
+        let standard = CallerContext {
+            person_id: admin.id,
+            access_level: AccessLevel::Standard,
+        };
+        assert!(recalculations
+            .enqueue(
+                tenant_id,
+                &standard,
+                serde_json::from_value(json!({ "kind": "order_totals" })).unwrap(),
+            )
+            .await
+            .is_err());
+
+        let task = recalculations
+            .enqueue(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({ "kind": "order_totals" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(task.kind, RecalculationKind::OrderTotals);
+        assert_eq!(task.status, RecalculationTaskStatus::Queued);
+        assert_eq!(task.progress_percent, None);
+
+        // Only one task of a kind can be pending
+        let error = recalculations
+            .enqueue(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({ "kind": "order_totals" })).unwrap(),
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("duplicate key"));
+
+        // A queued task is cancelled at once, and cannot be cancelled again
+        let forecast = recalculations
+            .enqueue(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({ "kind": "forecast_refresh" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        let cancelled = recalculations
+            .cancel(tenant_id, &caller, forecast.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cancelled.status, RecalculationTaskStatus::Cancelled);
+        assert!(recalculations
+            .cancel(tenant_id, &caller, forecast.id)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("cannot be cancelled"));
+
+        RecalculationWorker::new(database.clone(), Duration::from_secs(15))
+            .run_once()
+            .await
+            .unwrap();
+
+        let task = recalculations
+            .get(tenant_id, &caller, task.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.status, RecalculationTaskStatus::Completed);
+        assert_eq!(task.total, Some(1));
+        assert_eq!(task.progress_percent, Some(100.0));
+        assert_eq!(task.updated_records, 1);
+        assert!(task.finished_at.is_some());
+
+        let order = OrderService::new(database.clone())
+            .get_order_by_id(tenant_id, order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(order.total_amount, 40.0);
+
+        let tasks = recalculations
+            .list(
+                tenant_id,
+                &caller,
+                serde_json::from_value(json!({ "status": "completed" })).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+    }
+}
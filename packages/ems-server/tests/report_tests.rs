@@ -0,0 +1,397 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use std::collections::{BTreeMap, HashMap};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            next_cron_run, parse_cron_expression, CreateReportScheduleRequest, ReportKind,
+            ReportParameters, ReportResult,
+        },
+        routes::report::routes,
+        services::{csv_header, csv_rows, render_csv, DatabaseService, ReportService},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        // Try to create database service, but handle failure gracefully for tests
+        let _database = match DatabaseService::new().await {
+            Ok(db) => db,
+            Err(_) => {
+                panic!("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+            }
+        };
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    fn raw_params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    // Report API Tests
+
+    #[tokio::test]
+    async fn test_list_reports() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_report_json() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/inventory_valuation?context=store",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_report_csv() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            "/machine_downtime?format=csv&from=2024-01-01&to=2024-02-01",
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_report() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/not_a_report", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Report registry and rendering tests
+
+    #[test]
+    fn test_registry_lists_all_reports() {
+        let reports = ReportService::list_reports();
+        let keys: Vec<String> = reports.iter().map(|r| r.key.to_string()).collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                "inventory_valuation",
+                "open_order_book",
+                "machine_downtime",
+                "job_margin",
+                "consignment_balances"
+            ]
+        );
+        assert!(reports.iter().all(|r| !r.columns.is_empty()));
+        assert!(ReportService::find_report("job_margin").is_some());
+        assert!(ReportService::find_report("unknown").is_none());
+    }
+
+    #[test]
+    fn test_parse_report_parameters() {
+        let definition = ReportService::definition(ReportKind::JobMargin);
+
+        let parameters = ReportParameters::parse(
+            &definition,
+            &raw_params(&[
+                ("from", "2024-01-01"),
+                ("to", "2024-02-01T00:00:00Z"),
+                ("labor_rate", "42.5"),
+                ("ignored", "value"),
+            ]),
+        )
+        .expect("parameters should be valid");
+
+        assert_eq!(parameters.get_f64("labor_rate"), Some(42.5));
+        assert!(parameters.get_date("from").unwrap() < parameters.get_date("to").unwrap());
+        assert!(parameters.get_str("ignored").is_none());
+    }
+
+    #[test]
+    fn test_parse_report_parameters_rejects_invalid_values() {
+        let job_margin = ReportService::definition(ReportKind::JobMargin);
+        assert!(
+            ReportParameters::parse(&job_margin, &raw_params(&[("from", "yesterday")])).is_err()
+        );
+        assert!(
+            ReportParameters::parse(&job_margin, &raw_params(&[("labor_rate", "abc")])).is_err()
+        );
+
+        let inventory = ReportService::definition(ReportKind::InventoryValuation);
+        assert!(
+            ReportParameters::parse(&inventory, &raw_params(&[("context", "warehouse")])).is_err()
+        );
+        assert!(ReportParameters::parse(&inventory, &raw_params(&[("context", "store")])).is_ok());
+    }
+
+    #[test]
+    fn test_render_csv_escapes_values() {
+        let result = ReportResult {
+            report: ReportKind::InventoryValuation,
+            name: "Inventory valuation".to_string(),
+            generated_at: chrono::Utc::now(),
+            parameters: BTreeMap::new(),
+            columns: vec![
+                "internal_part_number".to_string(),
+                "location".to_string(),
+                "quantity".to_string(),
+            ],
+            rows: vec![
+                json!({ "internal_part_number": "IPN-1", "location": "Shelf \"A\", bin 2", "quantity": 5 }),
+                json!({ "internal_part_number": "IPN-2", "location": null, "quantity": 0 }),
+            ],
+        };
+
+        let csv = render_csv(&result);
+
+        assert_eq!(
+            csv,
+            "internal_part_number,location,quantity\r\nIPN-1,\"Shelf \"\"A\"\", bin 2\",5\r\nIPN-2,,0\r\n"
+        );
+
+        // Streamed exports produce the same file, a page of rows at a time
+        let header = csv_header(&result.columns);
+        assert_eq!(header, "internal_part_number,location,quantity\r\n");
+        let first = csv_rows(&result.columns, &result.rows[..1]);
+        let second = csv_rows(&result.columns, &result.rows[1..]);
+        assert_eq!(first.len(), 1);
+        assert_eq!(header + &first.concat() + &second.concat(), csv);
+    }
+
+    #[test]
+    fn test_render_csv_neutralises_formulas() {
+        let columns = vec!["name".to_string(), "quantity".to_string()];
+        let rows = [
+            json!({ "name": "=HYPERLINK(\"http://evil.example\",\"x\")", "quantity": -2 }),
+            json!({ "name": "+1", "quantity": 1 }),
+            json!({ "name": "-cmd", "quantity": 1 }),
+            json!({ "name": "@SUM(A1)", "quantity": 1 }),
+            json!({ "name": "\tTab", "quantity": 1 }),
+            json!({ "name": "\rReturn", "quantity": 1 }),
+            json!({ "name": "Resistor", "quantity": 1 }),
+        ];
+
+        let lines = csv_rows(&columns, &rows);
+
+        // Formula text opens as plain text; numbers, negative ones too, stay numeric
+        assert_eq!(
+            lines[0],
+            "\"'=HYPERLINK(\"\"http://evil.example\"\",\"\"x\"\")\",-2\r\n"
+        );
+        assert_eq!(lines[1], "'+1,1\r\n");
+        assert_eq!(lines[2], "'-cmd,1\r\n");
+        assert_eq!(lines[3], "'@SUM(A1),1\r\n");
+        assert_eq!(lines[4], "'\tTab,1\r\n");
+        assert_eq!(lines[5], "\"'\rReturn\",1\r\n");
+        assert_eq!(lines[6], "Resistor,1\r\n");
+    }
+
+    // Report schedule API Tests
+
+    #[tokio::test]
+    async fn test_list_schedules() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/schedules", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_schedule() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let schedule_data = json!({
+            "name": "Monday open orders",
+            "report": "open_order_book",
+            "cron_expression": "0 8 * * MON",
+            "timezone": "Europe/London",
+            "parameters": { "order_type": "customer_order" },
+            "recipients": ["management@example.com"],
+            "format": "csv"
+        });
+
+        let request =
+            create_request_with_tenant(Method::POST, "/schedules", Some(schedule_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_schedule() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let schedule_id = Uuid::new_v4().to_string();
+
+        let update_data = json!({
+            "cron_expression": "0 7 * * MON",
+            "is_active": false
+        });
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/schedules/{}", schedule_id),
+            Some(update_data),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_schedule_now() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let schedule_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/schedules/{}/run", schedule_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Report routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Schedule validation tests
+
+    #[test]
+    fn test_parse_cron_expression() {
+        assert!(parse_cron_expression("0 8 * * MON").is_ok());
+        assert!(parse_cron_expression("0 0 8 * * MON").is_ok());
+        assert!(parse_cron_expression("every monday").is_err());
+        assert!(parse_cron_expression("61 8 * * *").is_err());
+    }
+
+    #[test]
+    fn test_next_cron_run_uses_timezone() {
+        let after = chrono::DateTime::parse_from_rfc3339("2024-07-03T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        // Monday 08:00 in London during BST is 07:00 UTC
+        let next = next_cron_run("0 8 * * MON", "Europe/London", after)
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-07-08T07:00:00+00:00");
+
+        assert!(next_cron_run("0 8 * * MON", "Mars/Olympus", after).is_err());
+    }
+
+    #[test]
+    fn test_schedule_request_check() {
+        let definition = ReportService::definition(ReportKind::OpenOrderBook);
+        let mut request = CreateReportScheduleRequest {
+            name: "Monday open orders".to_string(),
+            report: ReportKind::OpenOrderBook,
+            cron_expression: "0 8 * * MON".to_string(),
+            timezone: Some("UTC".to_string()),
+            parameters: Some(raw_params(&[("order_type", "customer_order")])),
+            recipients: vec!["management@example.com".to_string()],
+            format: None,
+            is_active: None,
+        };
+        assert!(request.check(&definition).is_ok());
+
+        request.recipients = vec!["not-an-email".to_string()];
+        assert!(request.check(&definition).is_err());
+
+        request.recipients = vec!["management@example.com".to_string()];
+        request.parameters = Some(raw_params(&[("order_type", "bogus")]));
+        assert!(request.check(&definition).is_err());
+    }
+}
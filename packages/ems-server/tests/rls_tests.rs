@@ -0,0 +1,371 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+    use diesel::sql_types::BigInt;
+    use diesel_async::{AsyncConnection, RunQueryDsl, SimpleAsyncConnection};
+    use dotenv::dotenv;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{ListAttendanceQuery, ListOperatorShiftsQuery, RlsTableRow, RlsTableStatus},
+        services::{
+            AttendanceService, CalendarService, DatabaseService, JobService, MachineService,
+            PrintService, RlsService, SkillService, TenantService,
+        },
+    };
+
+    #[derive(diesel::QueryableByName)]
+    struct CountRow {
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    async fn database() -> DatabaseService {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        match DatabaseService::new().await {
+            Ok(db) => db,
+            Err(_) => {
+                panic!("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+            }
+        }
+    }
+
+    async fn create_tenant(database: &DatabaseService) -> Uuid {
+        let subdomain = format!("rls-{}", &Uuid::new_v4().simple().to_string()[..12]);
+        TenantService::new(database.clone())
+            .create_tenant(
+                serde_json::from_value(json!({ "name": "RLS test", "subdomain": subdomain }))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    // Policy introspection tests
+
+    #[tokio::test]
+    async fn test_rls_audit_covers_tenant_tables() {
+        let database = database().await;
+        let audit = RlsService::new(database).audit().await.unwrap();
+
+        assert!(audit.passed, "tables missing policies: {:?}", audit.failing);
+        for table in [
+            "machines",
+            "jobs",
+            "orders",
+            "operator_attendance",
+            "token_blacklist",
+        ] {
+            let status = audit.tables.iter().find(|t| t.table == table).unwrap();
+            assert!(status.tenant_scoped && status.rls_enabled && status.tenant_policy_count > 0);
+        }
+        let items = audit.tables.iter().find(|t| t.table == "items").unwrap();
+        assert!(!items.tenant_scoped);
+    }
+
+    #[test]
+    fn test_rls_table_status_flags_problems() {
+        let row = |rls_enabled, policy_count, tenant_policy_count| RlsTableRow {
+            table_name: "widgets".to_string(),
+            rls_enabled,
+            rls_forced: false,
+            policy_count,
+            tenant_policy_count,
+        };
+
+        assert!(RlsTableStatus::from_row(row(true, 1, 1), true)
+            .problems
+            .is_empty());
+        assert_eq!(
+            RlsTableStatus::from_row(row(false, 0, 0), true)
+                .problems
+                .len(),
+            2
+        );
+        assert_eq!(
+            RlsTableStatus::from_row(row(true, 1, 0), true).problems,
+            vec!["No policy filters on the current tenant".to_string()]
+        );
+        // Shared tables are reported but never fail
+        assert!(RlsTableStatus::from_row(row(false, 0, 0), false)
+            .problems
+            .is_empty());
+    }
+
+    // Every tenant-scoped table hides other tenants' rows from a role subject to RLS
+    #[tokio::test]
+    async fn test_policies_hide_rows_from_other_tenants() {
+        let database = database().await;
+        let audit = RlsService::new(database.clone()).audit().await.unwrap();
+        let mut conn = database.get_connection().await.unwrap();
+
+        conn.begin_test_transaction().await.unwrap();
+        conn.batch_execute(&format!(
+            "SET LOCAL ROLE authenticated; SET LOCAL app.current_tenant_id = '{}'",
+            Uuid::new_v4()
+        ))
+        .await
+        .unwrap();
+
+        for table in audit.tables.iter().filter(|t| t.tenant_scoped) {
+            // Tables the role cannot read at all do not leak
+            let readable = diesel::sql_query(format!(
+                "SELECT has_table_privilege('public.{}', 'SELECT')::int::int8 AS count",
+                table.table
+            ))
+            .get_result::<CountRow>(&mut conn)
+            .await
+            .unwrap();
+            if readable.count == 0 {
+                continue;
+            }
+
+            let visible = diesel::sql_query(format!(
+                "SELECT COUNT(*)::int8 AS count FROM public.{}",
+                table.table
+            ))
+            .get_result::<CountRow>(&mut conn)
+            .await
+            .unwrap();
+            assert_eq!(
+                visible.count, 0,
+                "{} leaks rows across tenants",
+                table.table
+            );
+        }
+    }
+
+    // Cross-tenant reads through the services
+
+    #[tokio::test]
+    async fn test_service_reads_are_tenant_scoped() {
+        let database = database().await;
+        let owner = create_tenant(&database).await;
+        let other = create_tenant(&database).await;
+
+        let machines = MachineService::new(database.clone());
+        let machine_id = machines
+            .create_machine(
+                owner,
+                serde_json::from_value(json!({
+                    "name": "RLS press", "ip": "10.0.0.10", "port": 8080, "protocol": "http"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let jobs = JobService::new(database.clone());
+        let job_id = jobs
+            .create_job(
+                owner,
+                serde_json::from_value(json!({
+                    "job_number": "RLS-1", "quantity": 1, "job_type": "manufacturing"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let skills = SkillService::new(database.clone());
+        let skill_id = skills
+            .create_skill(
+                owner,
+                serde_json::from_value(json!({ "name": "RLS soldering" })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        let calendar = CalendarService::new(database.clone());
+        let pattern_id = calendar
+            .create_shift_pattern(
+                owner,
+                serde_json::from_value(json!({ "name": "RLS days", "shifts": [] })).unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        calendar
+            .create_exception(
+                owner,
+                serde_json::from_value(json!({
+                    "exception_type": "holiday",
+                    "starts_at": "2025-12-25T00:00:00Z",
+                    "ends_at": "2025-12-26T00:00:00Z"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        let printers = PrintService::new(database.clone());
+        let printer_id = printers
+            .create_printer(
+                owner,
+                serde_json::from_value(json!({ "name": "RLS printer", "host": "10.0.0.20" }))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+
+        // The owner sees its records
+        assert!(machines
+            .get_machine_by_id(owner, machine_id)
+            .await
+            .unwrap()
+            .is_some());
+
+        // Another tenant sees none of them
+        assert!(machines
+            .get_machine_by_id(other, machine_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(machines
+            .list_machines(other, None, None, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(machines
+            .list_machine_job_assignments(other, machine_id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(machines
+            .get_machines_by_job_id(other, job_id, None)
+            .await
+            .unwrap()
+            .is_empty());
+        let day = NaiveDate::from_ymd_opt(2025, 12, 25).unwrap();
+        assert!(machines
+            .get_capacity_plan(other, day, day, 8.0, false)
+            .await
+            .unwrap()
+            .machines
+            .is_empty());
+        assert!(jobs.get_job_by_id(other, job_id).await.unwrap().is_none());
+        assert!(jobs
+            .list_jobs(other, None, None, true, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(skills.get_skill(other, skill_id).await.unwrap().is_none());
+        assert!(skills.list_skills(other).await.unwrap().is_empty());
+        assert!(skills
+            .list_machine_skill_requirements(other, machine_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(calendar
+            .get_shift_pattern(other, pattern_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(calendar
+            .list_shift_patterns(other)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(calendar
+            .get_machine_calendar(other, machine_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(calendar
+            .list_exceptions(other, None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(printers
+            .get_printer(other, printer_id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(printers
+            .list_printers(other, None)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let attendance = AttendanceService::new(database.clone());
+        assert!(attendance
+            .list_shifts(
+                other,
+                ListOperatorShiftsQuery {
+                    person_id: None,
+                    shift_pattern_id: Some(pattern_id),
+                    on: None,
+                },
+            )
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(attendance
+            .list_attendance(
+                other,
+                ListAttendanceQuery {
+                    person_id: None,
+                    attendance_type: None,
+                    from: None,
+                    to: Some(Utc::now()),
+                },
+            )
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Deleting the tenants cascades to their records
+        let tenants = TenantService::new(database.clone());
+        tenants.delete_tenant(owner).await.unwrap();
+        tenants.delete_tenant(other).await.unwrap();
+    }
+
+    // Tenant transactions
+
+    #[tokio::test]
+    async fn test_with_tenant_tx_rolls_back() {
+        use diesel::{ExpressionMethods, QueryDsl};
+        use ems_server::fixtures::FixtureBuilder;
+        use ems_server::schema::machines;
+
+        let database = database().await;
+        let fixture = FixtureBuilder::tenant()
+            .named("Transaction test")
+            .with_machines(1)
+            .build(&database)
+            .await
+            .unwrap();
+        let tenant_id = fixture.tenant.id;
+        let machine_id = fixture.machine_ids[0];
+
+        // A failure after the first write discards it
+        let result = database
+            .with_tenant_tx::<(), anyhow::Error, _>(tenant_id, |conn| {
+                Box::pin(async move {
+                    diesel::update(machines::table.find(machine_id))
+                        .set(machines::name.eq("Renamed"))
+                        .execute(conn)
+                        .await?;
+                    Err(anyhow::anyhow!("abort"))
+                })
+            })
+            .await;
+        assert!(result.is_err());
+
+        let machine = MachineService::new(database.clone())
+            .get_machine_by_id(tenant_id, machine_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(machine.name, "Machine 001");
+
+        TenantService::new(database.clone())
+            .delete_tenant(tenant_id)
+            .await
+            .unwrap();
+    }
+}
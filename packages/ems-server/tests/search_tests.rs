@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use dotenv::dotenv;
+    use serde_json::json;
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::models::{SuggestQuery, SuggestionRow, SuggestionType};
+    use ems_server::services::{DatabaseService, SearchService};
+    use ems_server::utils::search::{escape_like, merge_suggestions, parse_suggestion_types};
+    use ems_server::{routes::search::routes, AppState};
+
+    fn query(q: &str, types: Option<&str>, limit: Option<u32>) -> SuggestQuery {
+        SuggestQuery {
+            q: q.to_string(),
+            types: types.map(str::to_string),
+            limit,
+        }
+    }
+
+    #[test]
+    fn test_suggestion_helpers() {
+        assert_eq!(
+            parse_suggestion_types(None).unwrap(),
+            SuggestionType::ALL.to_vec()
+        );
+        assert_eq!(
+            parse_suggestion_types(Some(" Item, person,,item")).unwrap(),
+            vec![SuggestionType::Item, SuggestionType::Person]
+        );
+        assert!(parse_suggestion_types(Some("item,widget")).is_err());
+
+        assert_eq!(escape_like(r"50%_a\b"), r"50\%\_a\\b");
+
+        let row = |label: &str, score: f64| SuggestionRow {
+            id: Uuid::new_v4(),
+            label: label.to_string(),
+            detail: None,
+            score,
+        };
+        let merged = merge_suggestions(
+            vec![
+                (
+                    SuggestionType::Item,
+                    vec![row("RES-10K", 1.4), row("res-1k", 0.3)],
+                ),
+                (
+                    SuggestionType::Person,
+                    vec![row("Resa", 1.4), row("Teresa", 0.5)],
+                ),
+            ],
+            3,
+        );
+        let labels: Vec<_> = merged.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["RES-10K", "Resa", "Teresa"]);
+        assert_eq!(merged[1].suggestion_type, SuggestionType::Person);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_route_requires_auth() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("/suggest?q=res&types=item,person")
+            .header("X-Tenant-ID", Uuid::new_v4().to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // Suggestions require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_suggest() {
+        use ems_server::services::{ItemService, MachineService, PersonService, TenantService};
+
+        dotenv().ok();
+        let database = DatabaseService::new()
+            .await
+            .expect("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+
+        let tenants = TenantService::new(database.clone());
+        let tenant = |name: &str| {
+            let request = serde_json::from_value(
+                json!({ "name": name, "subdomain": format!("{}-{}", name, suffix) }),
+            )
+            .unwrap();
+            let tenants = &tenants;
+            async move { tenants.create_tenant(request).await.unwrap().id }
+        };
+        let tenant_id = tenant("search").await;
+        let other_tenant_id = tenant("search-other").await;
+
+        let items = ItemService::new(database.clone());
+        let item = |tenant_id: Uuid, part_number: String, manufacturer: &str| {
+            let request = serde_json::from_value(json!({
+                "internal_part_number": part_number,
+                "manufacturer": manufacturer,
+                "context": "store",
+                "quantity": 5
+            }))
+            .unwrap();
+            let items = &items;
+            async move { items.create_item(tenant_id, request).await.unwrap().id }
+        };
+        let exact = item(tenant_id, format!("RZ{}", suffix), "Yageo").await;
+        let longer = item(tenant_id, format!("RZ{}-10K", suffix), "Vishay").await;
+        item(tenant_id, format!("CAP-{}", suffix), "Murata").await;
+        item(other_tenant_id, format!("RZ{}-1K", suffix), "Yageo").await;
+
+        let vendor_id = PersonService::new(database.clone())
+            .create_person(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": format!("RZ{} Components", suffix),
+                    "email": format!("sales-{}@example.com", suffix),
+                    "role": "vendor",
+                    "person_type": "vendor",
+                    "company": "RZ Components Ltd"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap()
+            .id;
+        MachineService::new(database.clone())
+            .create_machine(
+                tenant_id,
+                serde_json::from_value(json!({
+                    "name": format!("Oven {}", suffix), "ip": "10.0.0.21", "port": 8080, "protocol": "http"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let search = SearchService::new(database.clone());
+
+        // The exact part number ranks first; other tenants' items never appear
+        let suggestions = search
+            .suggest(tenant_id, query(&format!("rz{}", suffix), None, None))
+            .await
+            .unwrap();
+        let ids: Vec<Uuid> = suggestions.iter().map(|s| s.id).collect();
+        assert_eq!(ids.len(), 3, "{:?}", suggestions);
+        assert_eq!(ids[0], exact);
+        assert_eq!(suggestions[0].detail.as_deref(), Some("Yageo"));
+        assert!(ids.contains(&longer));
+        assert!(ids.contains(&vendor_id));
+
+        let suggestions = search
+            .suggest(
+                tenant_id,
+                query(&format!("rz{}", suffix), Some("person"), None),
+            )
+            .await
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].suggestion_type, SuggestionType::Person);
+        assert_eq!(
+            suggestions[0].detail,
+            Some(format!("sales-{}@example.com", suffix))
+        );
+
+        // Substrings and secondary fields match too
+        let suggestions = search
+            .suggest(
+                tenant_id,
+                query(&format!("{}-10", suffix), Some("item"), None),
+            )
+            .await
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].id, longer);
+        let suggestions = search
+            .suggest(tenant_id, query(&format!("oven {}", suffix), None, None))
+            .await
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].suggestion_type, SuggestionType::Machine);
+
+        let suggestions = search
+            .suggest(tenant_id, query(&format!("rz{}", suffix), None, Some(1)))
+            .await
+            .unwrap();
+        assert_eq!(suggestions.len(), 1);
+
+        // Short text suggests nothing and wildcards only match literally
+        assert!(search
+            .suggest(tenant_id, query("r", None, None))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(search
+            .suggest(tenant_id, query("%%", None, None))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(search
+            .suggest(tenant_id, query("rz", Some("widget"), None))
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid suggestion type"));
+    }
+}
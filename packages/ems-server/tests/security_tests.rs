@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        middleware,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt; // for `oneshot`
+
+    use ems_server::middleware::security::{
+        security_headers_middleware, SecurityHeaders, DEFAULT_HSTS_MAX_AGE_SECONDS,
+    };
+
+    fn app(security: SecurityHeaders) -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route(
+                "/embeddable",
+                get(|| async {
+                    (
+                        [(header::CONTENT_SECURITY_POLICY, "frame-ancestors 'self'")],
+                        "widget",
+                    )
+                }),
+            )
+            .route("/missing", get(|| async { StatusCode::NOT_FOUND }))
+            .layer(middleware::from_fn_with_state(
+                security,
+                security_headers_middleware,
+            ))
+    }
+
+    async fn headers(security: SecurityHeaders, uri: &str) -> header::HeaderMap {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app(security)
+            .oneshot(request)
+            .await
+            .unwrap()
+            .headers()
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let headers = headers(SecurityHeaders::default(), "/ok").await;
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            "frame-ancestors 'none'"
+        );
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert_eq!(
+            headers[header::STRICT_TRANSPORT_SECURITY],
+            format!(
+                "max-age={}; includeSubDomains",
+                DEFAULT_HSTS_MAX_AGE_SECONDS
+            )
+            .as_str()
+        );
+        assert!(headers.contains_key(header::REFERRER_POLICY));
+    }
+
+    #[tokio::test]
+    async fn test_security_headers_on_errors() {
+        let headers = headers(SecurityHeaders::default(), "/missing").await;
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+    }
+
+    #[tokio::test]
+    async fn test_handler_headers_are_kept() {
+        let headers = headers(SecurityHeaders::default(), "/embeddable").await;
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            "frame-ancestors 'self'"
+        );
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    }
+
+    #[tokio::test]
+    async fn test_hsts_can_be_disabled() {
+        let headers = headers(SecurityHeaders::new(0), "/ok").await;
+        assert!(!headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+
+        let hsts = SecurityHeaders::new(600)
+            .headers()
+            .into_iter()
+            .find(|(name, _)| name == header::STRICT_TRANSPORT_SECURITY)
+            .unwrap()
+            .1;
+        assert_eq!(hsts, "max-age=600; includeSubDomains");
+    }
+}
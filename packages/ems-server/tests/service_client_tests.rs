@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::Method;
+    use uuid::Uuid;
+
+    use ems_server::models::{CreateServiceClientRequest, UpdateServiceClientRequest};
+    use ems_server::utils::service_scope::{
+        is_valid_scope, parse_scopes, required_scope, scopes_allow, SERVICE_ROLE,
+    };
+    use ems_server::utils::AuthUtils;
+
+    fn scopes(scopes: &[&str]) -> Vec<String> {
+        scopes.iter().map(|scope| scope.to_string()).collect()
+    }
+
+    #[test]
+    fn test_valid_scopes() {
+        assert!(is_valid_scope("admin"));
+        assert!(is_valid_scope("item:read"));
+        assert!(is_valid_scope("machine-groups:write"));
+        assert!(!is_valid_scope("item:delete"));
+        assert!(!is_valid_scope("item"));
+        // Tenant, billing and platform admin endpoints are never open to service clients
+        assert!(!is_valid_scope("tenants:read"));
+        assert!(!is_valid_scope("billing:write"));
+        assert!(!is_valid_scope("admin:read"));
+
+        let request = CreateServiceClientRequest {
+            name: "Analytics".to_string(),
+            scopes: scopes(&["item:read", "order:read"]),
+        };
+        assert!(request.check().is_ok());
+        let request = UpdateServiceClientRequest {
+            name: None,
+            scopes: Some(scopes(&["item:read", "tenants:write"])),
+            is_active: None,
+        };
+        assert_eq!(
+            request.check(),
+            Err("Invalid scope: tenants:write".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_scopes() {
+        assert_eq!(
+            parse_scopes(" item:read  order:write item:read "),
+            scopes(&["item:read", "order:write"])
+        );
+        assert!(parse_scopes("").is_empty());
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(
+            required_scope(&Method::GET, "/api/v1/item/123"),
+            Some("item:read".to_string())
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/api/v1/machine/123/heartbeat"),
+            Some("machine:write".to_string())
+        );
+        assert_eq!(
+            required_scope(&Method::DELETE, "/api/v1/machine-groups/1"),
+            Some("machine-groups:write".to_string())
+        );
+        assert_eq!(required_scope(&Method::GET, "/api/v1/tenants/1"), None);
+        assert_eq!(required_scope(&Method::GET, "/api/v1/admin/tenants"), None);
+        assert_eq!(required_scope(&Method::GET, "/health"), None);
+    }
+
+    #[test]
+    fn test_scopes_allow() {
+        let granted = scopes(&["item:write", "order:read"]);
+        assert!(scopes_allow(&granted, "item:write"));
+        // Write access covers reading
+        assert!(scopes_allow(&granted, "item:read"));
+        assert!(scopes_allow(&granted, "order:read"));
+        assert!(!scopes_allow(&granted, "order:write"));
+        assert!(!scopes_allow(&granted, "job:read"));
+        assert!(!scopes_allow(&scopes(&["admin"]), "item:read"));
+    }
+
+    #[test]
+    fn test_service_token_claims() {
+        let client_id = Uuid::new_v4();
+        let owner_id = Uuid::new_v4();
+        let tenant_id = Uuid::new_v4();
+        let token = AuthUtils::generate_service_token(
+            client_id,
+            owner_id,
+            tenant_id,
+            &scopes(&["item:read", "admin"]),
+            600,
+        )
+        .unwrap();
+
+        let claims = AuthUtils::verify_jwt_token(&token).unwrap();
+        assert_eq!(claims.sub, owner_id.to_string());
+        assert_eq!(claims.tenant_id, tenant_id.to_string());
+        assert_eq!(claims.role, SERVICE_ROLE);
+        assert_eq!(claims.azp, Some(client_id.to_string()));
+        assert_eq!(claims.scope.as_deref(), Some("item:read admin"));
+        assert_eq!(claims.exp - claims.iat, 600);
+
+        // User tokens carry neither
+        let token = AuthUtils::generate_refresh_token(owner_id, tenant_id, None).unwrap();
+        let claims = AuthUtils::verify_jwt_token(&token).unwrap();
+        assert_eq!(claims.azp, None);
+        assert_eq!(claims.scope, None);
+    }
+}
@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        extract::Path,
+        http::{header, HeaderMap, Method, Request, StatusCode},
+        response::Json,
+        routing::{get, post},
+        Router,
+    };
+    use chrono::{TimeZone, Utc};
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            CarrierLabelRequest, CreateShipmentRequest, OrderStatus, RecordTrackingRequest,
+            ShipmentStatus,
+        },
+        routes::shipment::routes,
+        services::{CarrierProvider, DatabaseService, WebhookCarrier},
+        utils::shipping::{fulfillment_status, tracked_status},
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        // Try to create database service, but handle failure gracefully for tests
+        let _database = match DatabaseService::new().await {
+            Ok(db) => db,
+            Err(_) => {
+                panic!("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+            }
+        };
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    // Shipment API Tests
+
+    #[tokio::test]
+    async fn test_list_shipments() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let order_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!("/?order_id={}&status=in_transit", order_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Shipment routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_shipment() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let shipment_data = json!({
+            "order_id": Uuid::new_v4(),
+            "service_level": "ground",
+            "ship_to": {
+                "name": "Acme Corp",
+                "street": "1 Main St",
+                "city": "Springfield",
+                "postal_code": "12345",
+                "country": "US"
+            },
+            "packages": [
+                { "weight_kg": 4.2, "length_cm": 40.0, "width_cm": 30.0, "height_cm": 20.0 }
+            ]
+        });
+
+        let request =
+            create_request_with_tenant(Method::POST, "/", Some(shipment_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Shipment routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_shipment_label() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let shipment_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/label", shipment_id),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Shipment routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_shipment_tracking() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let shipment_id = Uuid::new_v4().to_string();
+
+        let tracking_data = json!({
+            "events": [
+                { "status": "delivered", "location": "Springfield", "occurred_at": "2025-06-02T15:30:00Z" }
+            ]
+        });
+
+        let request = create_request_with_tenant(
+            Method::POST,
+            &format!("/{}/tracking", shipment_id),
+            Some(tracking_data),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Shipment routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Tracking and fulfillment helper tests
+
+    #[test]
+    fn test_tracked_status_follows_latest_event() {
+        let day = |d| Utc.with_ymd_and_hms(2025, 6, d, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            tracked_status(ShipmentStatus::LabelCreated, &[]),
+            ShipmentStatus::LabelCreated
+        );
+        assert_eq!(
+            tracked_status(
+                ShipmentStatus::LabelCreated,
+                &[
+                    (ShipmentStatus::Exception, day(3)),
+                    (ShipmentStatus::InTransit, day(2)),
+                ]
+            ),
+            ShipmentStatus::Exception
+        );
+        // A late in-transit scan does not undo a delivery
+        assert_eq!(
+            tracked_status(
+                ShipmentStatus::InTransit,
+                &[
+                    (ShipmentStatus::Delivered, day(3)),
+                    (ShipmentStatus::InTransit, day(4)),
+                ]
+            ),
+            ShipmentStatus::Delivered
+        );
+    }
+
+    #[test]
+    fn test_fulfillment_status() {
+        use ShipmentStatus::*;
+
+        assert_eq!(fulfillment_status(&[InTransit, LabelCreated]), None);
+        assert_eq!(
+            fulfillment_status(&[Delivered, InTransit]),
+            Some(OrderStatus::PartiallyFulfilled)
+        );
+        assert_eq!(
+            fulfillment_status(&[Delivered, Cancelled, Delivered]),
+            Some(OrderStatus::Fulfilled)
+        );
+        assert_eq!(fulfillment_status(&[Cancelled]), None);
+    }
+
+    #[test]
+    fn test_shipment_request_checks() {
+        let request: CreateShipmentRequest = serde_json::from_value(json!({
+            "order_id": Uuid::new_v4(),
+            "ship_to": "Springfield",
+            "packages": [{ "weight_kg": 1.0 }]
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        let request: RecordTrackingRequest = serde_json::from_value(json!({
+            "events": [{ "status": "cancelled", "occurred_at": "2025-06-02T15:30:00Z" }]
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_webhook_carrier() {
+        // Stand-in for a carrier bridge implementing the webhook carrier protocol
+        let bridge = Router::new()
+            .route(
+                "/labels",
+                post(
+                    |headers: HeaderMap, Json(request): Json<CarrierLabelRequest>| async move {
+                        assert_eq!(headers[header::AUTHORIZATION], "Bearer secret");
+                        Json(json!({
+                            "tracking_number": format!("1Z{}", request.order_number),
+                            "label_url": "https://labels.example.com/1.pdf"
+                        }))
+                    },
+                ),
+            )
+            .route(
+                "/tracking/:tracking_number",
+                get(|Path(tracking_number): Path<String>| async move {
+                    Json(json!({
+                        "events": [{
+                            "status": "in_transit",
+                            "description": tracking_number,
+                            "occurred_at": "2025-06-01T08:00:00Z"
+                        }]
+                    }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, bridge).await.unwrap() });
+
+        let carrier = WebhookCarrier::new(&base_url, Some("secret".to_string())).unwrap();
+        let label = carrier
+            .create_label(&CarrierLabelRequest {
+                shipment_id: Uuid::new_v4(),
+                order_number: "SO-1001".to_string(),
+                service_level: None,
+                ship_to: json!({ "city": "Springfield" }),
+                packages: Vec::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(label.tracking_number, "1ZSO-1001");
+        assert_eq!(
+            label.label_url.as_deref(),
+            Some("https://labels.example.com/1.pdf")
+        );
+
+        let events = carrier.track(&label.tracking_number).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, ShipmentStatus::InTransit);
+        assert_eq!(events[0].description.as_deref(), Some("1ZSO-1001"));
+    }
+}
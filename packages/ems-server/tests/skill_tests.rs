@@ -0,0 +1,293 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        Router,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+    use dotenv::dotenv;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+
+    use ems_server::{
+        models::{
+            skill_gaps, MachineSkillRequirementResponse, PersonSkill, SetPersonSkillRequest,
+            SkillEnforcement, SkillGapReason,
+        },
+        routes::skill::routes,
+        services::DatabaseService,
+        AppState,
+    };
+
+    async fn app() -> Router {
+        // Load environment variables for tests
+        dotenv().ok();
+
+        // Try to create database service, but handle failure gracefully for tests
+        let _database = match DatabaseService::new().await {
+            Ok(db) => db,
+            Err(_) => {
+                panic!("Database connection failed. Please ensure DATABASE_URL is set and PostgreSQL is running.");
+            }
+        };
+
+        let state = AppState::new().await.expect("Failed to create app state");
+        routes().with_state(state)
+    }
+
+    // Helper function to create test request with tenant header
+    fn create_request_with_tenant(
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        tenant_id: &str,
+    ) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-Tenant-ID", tenant_id)
+            .header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(body_value) = body {
+            request.body(Body::from(body_value.to_string())).unwrap()
+        } else {
+            request.body(Body::empty()).unwrap()
+        }
+    }
+
+    fn requirement(
+        machine_id: Uuid,
+        skill_id: Uuid,
+        name: &str,
+        enforcement: SkillEnforcement,
+    ) -> MachineSkillRequirementResponse {
+        MachineSkillRequirementResponse {
+            id: Uuid::new_v4(),
+            machine_id,
+            skill_id,
+            skill_name: name.to_string(),
+            enforcement,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn certification(
+        person_id: Uuid,
+        skill_id: Uuid,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> PersonSkill {
+        PersonSkill {
+            id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            person_id,
+            skill_id,
+            certified_at: None,
+            expires_at,
+            certificate_number: None,
+            notes: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    // Skill API Tests
+
+    #[tokio::test]
+    async fn test_list_skills() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_skill() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let skill_data = json!({
+            "name": "CNC milling",
+            "description": "Certified to operate 5-axis CNC mills"
+        });
+
+        let request = create_request_with_tenant(Method::POST, "/", Some(skill_data), &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_skill_matrix() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(Method::GET, "/matrix", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_person_skill() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let person_id = Uuid::new_v4().to_string();
+        let skill_id = Uuid::new_v4().to_string();
+
+        let certification_data = json!({
+            "certified_at": "2025-01-01T00:00:00Z",
+            "expires_at": "2027-01-01T00:00:00Z",
+            "certificate_number": "CNC-0042"
+        });
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/persons/{}/{}", person_id, skill_id),
+            Some(certification_data),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_machine_skill_requirement() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+        let machine_id = Uuid::new_v4().to_string();
+        let skill_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::PUT,
+            &format!("/machines/{}/{}", machine_id, skill_id),
+            Some(json!({ "enforcement": "warn" })),
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_operator() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request = create_request_with_tenant(
+            Method::GET,
+            &format!(
+                "/check?machine_id={}&person_id={}",
+                Uuid::new_v4(),
+                Uuid::new_v4()
+            ),
+            None,
+            &tenant_id,
+        );
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_flagged_assignments() {
+        let app = app().await;
+        let tenant_id = Uuid::new_v4().to_string();
+
+        let request =
+            create_request_with_tenant(Method::GET, "/flagged-assignments", None, &tenant_id);
+
+        let response = app.oneshot(request).await.unwrap();
+        // Skill routes require authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    // Skill matrix validation tests
+
+    #[test]
+    fn test_skill_gaps_missing_and_expired() {
+        let machine_id = Uuid::new_v4();
+        let person_id = Uuid::new_v4();
+        let (milling, welding, forklift) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+
+        let requirements = vec![
+            requirement(machine_id, milling, "Milling", SkillEnforcement::Block),
+            requirement(machine_id, welding, "Welding", SkillEnforcement::Warn),
+            requirement(machine_id, forklift, "Forklift", SkillEnforcement::Block),
+        ];
+        let certifications = vec![
+            // Valid certification without expiry
+            certification(person_id, milling, None),
+            // Certification that lapsed yesterday
+            certification(person_id, forklift, Some(now - Duration::days(1))),
+        ];
+
+        let gaps = skill_gaps(&requirements, &certifications, now);
+        assert_eq!(gaps.len(), 2);
+        assert_eq!(gaps[0].skill_id, welding);
+        assert_eq!(gaps[0].reason, SkillGapReason::Missing);
+        assert!(!gaps[0].is_blocking());
+        assert_eq!(gaps[1].skill_id, forklift);
+        assert_eq!(gaps[1].reason, SkillGapReason::Expired);
+        assert_eq!(gaps[1].expires_at, Some(now - Duration::days(1)));
+        assert!(gaps[1].is_blocking());
+
+        // A certification expiring in the future still counts
+        let certifications = vec![
+            certification(person_id, milling, Some(now + Duration::days(30))),
+            certification(person_id, welding, None),
+            certification(person_id, forklift, None),
+        ];
+        assert!(skill_gaps(&requirements, &certifications, now).is_empty());
+    }
+
+    #[test]
+    fn test_person_skill_request_checks_expiry() {
+        let request: SetPersonSkillRequest = serde_json::from_value(json!({
+            "certified_at": "2025-01-01T00:00:00Z",
+            "expires_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(request.check().is_err());
+
+        let request: SetPersonSkillRequest = serde_json::from_value(json!({
+            "expires_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(request.check().is_ok());
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use ems_server::utils::soak::{percentile, LatencyRecorder, SoakThresholds};
+    use std::time::Duration;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&samples, 50.0), 50.0);
+        assert_eq!(percentile(&samples, 90.0), 90.0);
+        assert_eq!(percentile(&samples, 99.0), 99.0);
+        assert_eq!(percentile(&samples, 100.0), 100.0);
+        // Nearest rank never falls below the first sample
+        assert_eq!(percentile(&samples, 0.0), 1.0);
+        assert_eq!(percentile(&[7.0], 99.0), 7.0);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_recorder_report() {
+        let recorder = LatencyRecorder::default();
+        for ms in 1..=10 {
+            recorder.record("heartbeat", Duration::from_millis(ms), ms != 10);
+        }
+        recorder.record("login", Duration::from_millis(200), true);
+
+        let report = recorder.report(Duration::from_secs(2), 1, 5);
+        assert_eq!(report.tenants, 1);
+        assert_eq!(report.machines, 5);
+        // Operations come in name order
+        let names: Vec<_> = report
+            .operations
+            .iter()
+            .map(|op| op.operation.as_str())
+            .collect();
+        assert_eq!(names, ["heartbeat", "login"]);
+
+        let heartbeat = &report.operations[0];
+        assert_eq!(heartbeat.requests, 10);
+        assert_eq!(heartbeat.errors, 1);
+        assert!((heartbeat.error_rate - 0.1).abs() < 1e-9);
+        assert!((heartbeat.throughput - 5.0).abs() < 1e-9);
+        assert!((heartbeat.p50_ms - 5.0).abs() < 1e-6);
+        assert!((heartbeat.p99_ms - 10.0).abs() < 1e-6);
+        assert!((heartbeat.max_ms - 10.0).abs() < 1e-6);
+
+        let table = report.to_table();
+        assert!(table.contains("heartbeat"));
+        assert!(table.contains("login"));
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let recorder = LatencyRecorder::default();
+        recorder.record("list_items", Duration::from_millis(300), true);
+        recorder.record("list_items", Duration::from_millis(100), false);
+        let report = recorder.report(Duration::from_secs(1), 1, 0);
+
+        // No limits, no violations
+        assert!(SoakThresholds::default().violations(&report).is_empty());
+
+        let thresholds = SoakThresholds {
+            max_p99_ms: Some(250.0),
+            max_error_rate: Some(0.01),
+        };
+        let violations = thresholds.violations(&report);
+        assert_eq!(violations.len(), 2);
+        assert!(violations[0].starts_with("list_items p99"));
+        assert!(violations[1].starts_with("list_items error rate"));
+
+        let lenient = SoakThresholds {
+            max_p99_ms: Some(500.0),
+            max_error_rate: Some(0.5),
+        };
+        assert!(lenient.violations(&report).is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::body::Body;
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use ems_server::services::DatabaseService;
+    use ems_server::utils::streaming::{stream_json_array, stream_lines};
+
+    async fn collect(body: Body) -> Vec<u8> {
+        axum::body::to_bytes(body, usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    /// Streams `rows` in pages of `page_size`, recording each (limit, offset) fetched
+    async fn stream_rows(
+        rows: Vec<u32>,
+        page_size: u32,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> (Value, Vec<(u32, u32)>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let body = stream_json_array(
+            Uuid::nil(),
+            page_size,
+            limit,
+            offset,
+            move |limit, offset| {
+                recorded.lock().unwrap().push((limit, offset));
+                let page: Vec<u32> = rows
+                    .iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .copied()
+                    .collect();
+                async move { Ok(page) }
+            },
+        )
+        .await
+        .unwrap();
+        let bytes = collect(body).await;
+        let calls = calls.lock().unwrap().clone();
+        (serde_json::from_slice(&bytes).unwrap(), calls)
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_array_pages() {
+        let rows: Vec<u32> = (0..7).collect();
+
+        let (list, calls) = stream_rows(rows.clone(), 3, None, None).await;
+        assert_eq!(list, serde_json::json!([0, 1, 2, 3, 4, 5, 6]));
+        assert_eq!(calls, vec![(3, 0), (3, 3), (3, 6)]);
+
+        // A list that fills its last page exactly needs one more (empty) page to end
+        let (list, calls) = stream_rows(rows[..6].to_vec(), 3, None, None).await;
+        assert_eq!(list.as_array().unwrap().len(), 6);
+        assert_eq!(calls, vec![(3, 0), (3, 3), (3, 6)]);
+
+        let (list, calls) = stream_rows(Vec::new(), 3, None, None).await;
+        assert_eq!(list, serde_json::json!([]));
+        assert_eq!(calls, vec![(3, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_array_limit_and_offset() {
+        let rows: Vec<u32> = (0..10).collect();
+
+        let (list, calls) = stream_rows(rows.clone(), 3, Some(4), Some(2)).await;
+        assert_eq!(list, serde_json::json!([2, 3, 4, 5]));
+        assert_eq!(calls, vec![(3, 2), (1, 5)]);
+
+        let (list, calls) = stream_rows(rows, 3, Some(0), None).await;
+        assert_eq!(list, serde_json::json!([]));
+        assert!(calls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_array_first_page_error() {
+        let result = stream_json_array(Uuid::nil(), 3, None, None, |_, _| async {
+            Err::<Vec<u32>, _>(anyhow::anyhow!("database unavailable"))
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_json_array_later_error_cuts_body() {
+        let body = stream_json_array(Uuid::nil(), 2, None, None, |_, offset| async move {
+            if offset == 0 {
+                Ok(vec![1u32, 2])
+            } else {
+                Err(anyhow::anyhow!("connection lost"))
+            }
+        })
+        .await
+        .unwrap();
+        assert!(axum::body::to_bytes(body, usize::MAX).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_pages_keep_tenant_scope() {
+        // Later pages are fetched after the handler returns, outside its tenant scope
+        let tenant_id = Uuid::new_v4();
+        let scopes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = scopes.clone();
+        let body = stream_json_array(tenant_id, 2, Some(5), None, move |limit, _| {
+            let recorded = recorded.clone();
+            async move {
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(DatabaseService::current_tenant_scope());
+                Ok(vec![0u32; limit as usize])
+            }
+        })
+        .await
+        .unwrap();
+        collect(body).await;
+
+        let scopes = scopes.lock().unwrap().clone();
+        assert_eq!(scopes.len(), 3);
+        assert!(scopes.iter().all(|scope| *scope == Some(tenant_id)));
+    }
+
+    #[tokio::test]
+    async fn test_stream_lines() {
+        let lines: Vec<String> = (0..7).map(|i| format!("line {}\r\n", i)).collect();
+        let expected = format!("head\r\n{}", lines.concat());
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        let body = stream_lines(
+            Uuid::nil(),
+            3,
+            "head\r\n".to_string(),
+            move |limit, offset| {
+                recorded.lock().unwrap().push((limit, offset));
+                let page: Vec<String> = lines
+                    .iter()
+                    .skip(offset as usize)
+                    .take(limit as usize)
+                    .cloned()
+                    .collect();
+                async move { Ok(page) }
+            },
+        )
+        .await
+        .unwrap();
+
+        let bytes = collect(body).await;
+        assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+        assert_eq!(*calls.lock().unwrap(), vec![(3, 0), (3, 3), (3, 6)]);
+
+        // Only the head when there are no rows
+        let body = stream_lines(Uuid::nil(), 3, "head\r\n".to_string(), |_, _| async {
+            Ok(Vec::new())
+        })
+        .await
+        .unwrap();
+        assert_eq!(collect(body).await, b"head\r\n");
+    }
+}
@@ -0,0 +1,342 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use std::time::Duration;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use ems_server::models::OAuthProvider;
+    use ems_server::services::{StorageBackend, SupabaseConfig, SupabaseService, SupabaseStorage};
+    use ems_server::utils::circuit_breaker::{
+        CircuitBreakerConfig, CircuitState, ServiceUnavailable,
+    };
+
+    // Responses below are recorded from the Supabase Auth (GoTrue) and Storage APIs
+
+    fn supabase(server: &MockServer) -> SupabaseService {
+        let mut config = SupabaseConfig::new(&server.uri(), "anon-key");
+        config.service_role_key = Some("service-key".to_string());
+        SupabaseService::with_config(config).unwrap()
+    }
+
+    fn session() -> serde_json::Value {
+        json!({
+            "access_token": "eyJhbGciOiJIUzI1NiJ9.session",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "v1.refresh",
+            "user": {
+                "id": "8d0c1f4e-5b7a-4c2d-9e3f-1a2b3c4d5e6f",
+                "aud": "authenticated",
+                "email": "operator@example.com"
+            }
+        })
+    }
+
+    // Auth tests
+
+    #[tokio::test]
+    async fn test_authenticate_user() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/token"))
+            .and(query_param("grant_type", "password"))
+            .and(header("apikey", "anon-key"))
+            .and(body_json(json!({
+                "email": "operator@example.com",
+                "password": "correct horse"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(session()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let session = supabase(&server)
+            .authenticate_user("operator@example.com", "correct horse")
+            .await
+            .unwrap();
+        assert_eq!(session["user"]["email"], "operator@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_user_wrong_password() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/token"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error": "invalid_grant",
+                "error_description": "Invalid login credentials"
+            })))
+            .mount(&server)
+            .await;
+
+        let supabase = supabase(&server);
+        let error = supabase
+            .authenticate_user("operator@example.com", "wrong")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Authentication failed: Invalid login credentials"
+        );
+        // Rejected credentials say nothing about Supabase's health
+        assert!(!ServiceUnavailable::is(&error));
+        assert_eq!(supabase.breaker_status().state, CircuitState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_create_user() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/admin/users"))
+            .and(header("apikey", "service-key"))
+            .and(header("authorization", "Bearer service-key"))
+            .and(body_partial_json(json!({
+                "email": "new@example.com",
+                "email_confirm": true,
+                "user_metadata": { "name": "New Operator" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "0b6f2a1c-3d4e-4f5a-8b9c-0d1e2f3a4b5c",
+                "email": "new@example.com"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let user = supabase(&server)
+            .create_user(
+                "new@example.com",
+                "s3cret-pass",
+                Some(json!({ "name": "New Operator" })),
+            )
+            .await
+            .unwrap();
+        assert_eq!(user["id"], "0b6f2a1c-3d4e-4f5a-8b9c-0d1e2f3a4b5c");
+    }
+
+    #[tokio::test]
+    async fn test_create_user_duplicate() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/admin/users"))
+            .respond_with(ResponseTemplate::new(422).set_body_json(json!({
+                "code": 422,
+                "error_code": "email_exists",
+                "msg": "A user with this email address has already been registered"
+            })))
+            .mount(&server)
+            .await;
+
+        let error = supabase(&server)
+            .create_user("taken@example.com", "s3cret-pass", None)
+            .await
+            .unwrap_err();
+        // Surfaces as a conflict rather than an internal error
+        assert_eq!(error.to_string(), "Email already registered");
+    }
+
+    #[tokio::test]
+    async fn test_create_user_without_service_key() {
+        let server = MockServer::start().await;
+        let supabase =
+            SupabaseService::with_config(SupabaseConfig::new(&server.uri(), "anon-key")).unwrap();
+
+        let error = supabase
+            .create_user("new@example.com", "s3cret-pass", None)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("SUPABASE_SERVICE_ROLE_KEY"));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_oauth_sign_in_expired_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/token"))
+            .and(query_param("grant_type", "id_token"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "code": 403,
+                "error_code": "bad_jwt",
+                "msg": "invalid JWT: unable to parse or verify signature, token has invalid claims: token is expired"
+            })))
+            .mount(&server)
+            .await;
+
+        let error = supabase(&server)
+            .oauth_sign_in(&OAuthProvider::Google, "expired-id-token")
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Authentication failed: Token expired");
+    }
+
+    #[tokio::test]
+    async fn test_verify_user_exists() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/auth/v1/admin/users"))
+            .and(query_param("email", "operator@example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "users": [{ "id": "8d0c1f4e-5b7a-4c2d-9e3f-1a2b3c4d5e6f" }],
+                "aud": "authenticated"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/auth/v1/admin/users"))
+            .and(query_param("email", "nobody@example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "users": [] })))
+            .mount(&server)
+            .await;
+
+        let supabase = supabase(&server);
+        assert!(supabase
+            .verify_user_exists("operator@example.com")
+            .await
+            .unwrap());
+        assert!(!supabase
+            .verify_user_exists("nobody@example.com")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_open_the_breaker() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/auth/v1/token"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("upstream connect error"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let mut config = SupabaseConfig::new(&server.uri(), "anon-key");
+        config.breaker = CircuitBreakerConfig {
+            failure_threshold: 2,
+            open_duration: Duration::from_secs(60),
+            call_timeout: Duration::from_secs(5),
+        };
+        let supabase = SupabaseService::with_config(config).unwrap();
+
+        for _ in 0..2 {
+            let error = supabase
+                .authenticate_user("operator@example.com", "correct horse")
+                .await
+                .unwrap_err();
+            assert!(ServiceUnavailable::is(&error));
+        }
+        assert_eq!(supabase.breaker_status().state, CircuitState::Open);
+
+        // While open, calls fail without reaching Supabase
+        let error = supabase
+            .authenticate_user("operator@example.com", "correct horse")
+            .await
+            .unwrap_err();
+        assert!(ServiceUnavailable::is(&error));
+    }
+
+    // Storage tests
+
+    #[tokio::test]
+    async fn test_image_storage() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/item-images/items/a.png"))
+            .and(header("authorization", "Bearer service-key"))
+            .and(header("x-upsert", "true"))
+            .and(header("content-type", "image/png"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "Key": "item-images/items/a.png"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/sign/item-images/items/a.png"))
+            .and(body_json(json!({ "expiresIn": 600 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "signedURL": "/object/sign/item-images/items/a.png?token=abc"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/object/item-images/items/gone.png"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "statusCode": "404",
+                "error": "not_found",
+                "message": "Object not found"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/object/item-images/items/locked.png"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "statusCode": "403",
+                "error": "Unauthorized",
+                "message": "new row violates row-level security policy"
+            })))
+            .mount(&server)
+            .await;
+
+        let storage = SupabaseStorage::new(
+            &format!("{}/", server.uri()),
+            "service-key".to_string(),
+            "item-images".to_string(),
+        )
+        .unwrap();
+
+        storage
+            .put("items/a.png", vec![0x89, 0x50, 0x4e, 0x47], "image/png")
+            .await
+            .unwrap();
+        let url = storage
+            .signed_url("items/a.png", Duration::from_secs(600))
+            .await
+            .unwrap();
+        assert_eq!(
+            url,
+            format!(
+                "{}/storage/v1/object/sign/item-images/items/a.png?token=abc",
+                server.uri()
+            )
+        );
+
+        // Already gone counts as deleted; other refusals are errors
+        storage.delete("items/gone.png").await.unwrap();
+        assert!(storage.delete("items/locked.png").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_asset_storage() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/storage/v1/object/authenticated/assets/firmware/v2.bin",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"firmware".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/storage/v1/object/authenticated/assets/firmware/missing.bin",
+            ))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "statusCode": "404",
+                "error": "not_found",
+                "message": "Object not found"
+            })))
+            .mount(&server)
+            .await;
+
+        let storage = SupabaseStorage::new(
+            &server.uri(),
+            "service-key".to_string(),
+            "assets".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(storage.get("firmware/v2.bin").await.unwrap(), b"firmware");
+        assert!(storage.get("firmware/missing.bin").await.is_err());
+    }
+}
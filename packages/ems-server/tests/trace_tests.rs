@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use dotenv::dotenv;
+    use serde_json::json;
+    use tower::ServiceExt; // for `oneshot`
+    use uuid::Uuid;
+
+    use ems_server::models::{BatchRecordResponse, TraceNodeKind, TraceQuery, TraceRelation};
+    use ems_server::utils::trace::{node_id, numbered_key, TraceGraphBuilder};
+    use ems_server::{routes::trace::routes, AppState};
+
+    #[test]
+    fn test_trace_graph_builder() {
+        let (job_id, machine_id, resistor, chip) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let lot: BatchRecordResponse = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "job_id": job_id,
+            "batch_number": "B-1001",
+            "item_id": Uuid::new_v4(),
+            "quantity": 50,
+            "completed_at": "2026-03-09T15:30:00Z",
+            "completed_by_id": null,
+            "created_at": "2026-03-09T15:30:00Z",
+            "product": {
+                "item_id": null,
+                "part_number": "PCB-1",
+                "description": null,
+                "work_order_number": null,
+                "production_line": null,
+                "started_at": null
+            },
+            "materials": [
+                {
+                    "item_id": resistor,
+                    "part_number": "RES-10K",
+                    "quantity": "100",
+                    "lot_numbers": ["R-7"],
+                    "serial_numbers": []
+                },
+                {
+                    "item_id": chip,
+                    "part_number": null,
+                    "quantity": "50",
+                    "lot_numbers": [],
+                    "serial_numbers": ["SN-1", "SN-2"]
+                }
+            ],
+            "machines": [
+                {
+                    "machine_id": machine_id,
+                    "name": "Reflow oven",
+                    "started_at": null,
+                    "ended_at": null,
+                    "firmware": []
+                }
+            ],
+            "operators": [],
+            "inspections": []
+        }))
+        .unwrap();
+
+        let mut graph = TraceGraphBuilder::new();
+        let job = graph.node(TraceNodeKind::Job, job_id, "MFG-1", Some(job_id), None);
+        let lot_node = graph.lot(&lot);
+        graph.edge(&job, &lot_node, TraceRelation::Produced);
+        // Repeated nodes and edges are kept once
+        let again = graph.lot(&lot);
+        assert_eq!(again, lot_node);
+        graph.edge(&job, &lot_node, TraceRelation::Produced);
+
+        let mut items = graph.component_items();
+        items.sort();
+        let mut expected = vec![resistor, chip];
+        expected.sort();
+        assert_eq!(items, expected);
+
+        let trace = graph.finish(vec![job.clone()]);
+        assert_eq!(trace.roots, vec![format!("job:{}", job_id)]);
+        // Job, lot, machine, two components, one component lot and two serials
+        assert_eq!(trace.nodes.len(), 8);
+        let label = |id: &str| {
+            trace
+                .nodes
+                .iter()
+                .find(|node| node.id == id)
+                .map(|node| node.label.clone())
+        };
+        assert_eq!(label(&lot_node).as_deref(), Some("B-1001"));
+        assert_eq!(
+            label(&node_id(TraceNodeKind::Component, chip)),
+            Some(chip.to_string())
+        );
+
+        let edges = |relation| {
+            trace
+                .edges
+                .iter()
+                .filter(|edge| edge.relation == relation)
+                .count()
+        };
+        assert_eq!(edges(TraceRelation::Produced), 1);
+        assert_eq!(edges(TraceRelation::BuiltOn), 1);
+        assert_eq!(edges(TraceRelation::Consumed), 5);
+        assert_eq!(edges(TraceRelation::OfItem), 3);
+
+        let serial = node_id(TraceNodeKind::Serial, numbered_key(chip, "SN-2"));
+        assert!(trace.edges.iter().any(|edge| edge.from == serial
+            && edge.to == node_id(TraceNodeKind::Component, chip)
+            && edge.relation == TraceRelation::OfItem));
+        let consumed = trace
+            .edges
+            .iter()
+            .find(|edge| edge.to == node_id(TraceNodeKind::Component, resistor))
+            .unwrap();
+        assert_eq!(consumed.quantity, Some(100.into()));
+    }
+
+    #[test]
+    fn test_trace_query() {
+        let query: TraceQuery =
+            serde_json::from_value(json!({ "entity": "serial", "id": "SN-1" })).unwrap();
+        assert!(query.item_id.is_none());
+        assert!(
+            serde_json::from_value::<TraceQuery>(json!({ "entity": "vendor", "id": "x" })).is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_route_requires_auth() {
+        dotenv().ok();
+        let state = AppState::new().await.expect("Failed to create app state");
+        let app = routes().with_state(state);
+
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri("/?entity=lot&id=B-1001")
+            .header("X-Tenant-ID", Uuid::new_v4().to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        // Tracing requires authentication, will fail without JWT token
+        assert!(
+            response.status() == StatusCode::UNAUTHORIZED
+                || response.status() == StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
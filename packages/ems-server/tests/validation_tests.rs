@@ -0,0 +1,473 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+        routing::{get, post},
+        Router,
+    };
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use tower::ServiceExt; // for `oneshot` and `ready`
+    use uuid::Uuid;
+    use validator::Validate;
+
+    use ems_server::{
+        middleware::validation::{field_errors, ValidatedJson, ValidatedQuery},
+        models::*,
+        utils::i18n::{scope_locale, Locale},
+    };
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/machines",
+                post(
+                    |ValidatedJson(payload): ValidatedJson<CreateMachineRequest>| async move {
+                        payload.name
+                    },
+                ),
+            )
+            .route(
+                "/orders",
+                post(
+                    |ValidatedJson(payload): ValidatedJson<CreateOrderRequest>| async move {
+                        payload.order_number.unwrap_or_default()
+                    },
+                ),
+            )
+            .route(
+                "/capacity",
+                get(
+                    |ValidatedQuery(params): ValidatedQuery<CapacityQuery>| async move {
+                        format!("{:?}", params.hours_per_day)
+                    },
+                ),
+            )
+    }
+
+    async fn send(request: Request<Body>) -> (StatusCode, Value) {
+        let response = app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    fn post_json(uri: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    // Names of the fields a payload is rejected for
+    fn invalid_fields<T: DeserializeOwned + Validate>(payload: Value) -> Vec<String> {
+        let request: T = serde_json::from_value(payload).expect("payload should deserialize");
+        match request.validate() {
+            Ok(()) => vec![],
+            Err(errors) => field_errors(&errors).into_keys().collect(),
+        }
+    }
+
+    // Extractor tests
+
+    #[tokio::test]
+    async fn test_invalid_payload_returns_field_errors() {
+        let (status, body) = send(post_json(
+            "/machines",
+            &json!({ "name": "", "ip": "192.168.1.100", "port": -1, "protocol": "http" })
+                .to_string(),
+        ))
+        .await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "Validation failed");
+        assert_eq!(body["fields"]["port"][0], "must be between 1 and 65535");
+        assert_eq!(
+            body["fields"]["name"][0],
+            "length must be between 1 and 100"
+        );
+        assert!(body["fields"].get("ip").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_field_errors_follow_request_locale() {
+        let request = || {
+            post_json(
+                "/machines",
+                &json!({ "name": "", "ip": "192.168.1.100", "port": -1, "protocol": "http" })
+                    .to_string(),
+            )
+        };
+
+        let (status, body) = scope_locale(Locale::Es, send(request())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["error"], "La validación falló");
+        assert_eq!(body["fields"]["port"][0], "debe estar entre 1 y 65535");
+        assert_eq!(
+            body["fields"]["name"][0],
+            "longitud debe estar entre 1 y 100"
+        );
+
+        // Outside a request scope messages stay in English
+        let (_, body) = send(request()).await;
+        assert_eq!(body["error"], "Validation failed");
+    }
+
+    #[tokio::test]
+    async fn test_app_error_follows_request_locale() {
+        use axum::response::IntoResponse;
+        use ems_server::utils::AppError;
+
+        let body = |error: AppError| async move {
+            let response = error.into_response();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()["error"].clone()
+        };
+
+        let internal = scope_locale(
+            Locale::Es,
+            body(AppError::Internal("pool exhausted".to_string())),
+        )
+        .await;
+        assert_eq!(internal, "Error interno del servidor");
+        // Service messages are passed through untranslated
+        let conflict =
+            scope_locale(Locale::Es, body(AppError::Conflict("Taken".to_string()))).await;
+        assert_eq!(conflict, "Taken");
+    }
+
+    #[tokio::test]
+    async fn test_valid_payload_reaches_handler() {
+        let response = app()
+            .oneshot(post_json(
+                "/machines",
+                &json!({ "name": "Press 1", "ip": "10.0.0.12", "port": 502, "protocol": "http" })
+                    .to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json_is_rejected_with_message() {
+        let (status, body) = send(post_json("/machines", "{\"name\": ")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("JSON"));
+
+        // Missing fields fail deserialization before any rule runs
+        let (status, body) = send(post_json("/machines", "{\"name\": \"Press\"}")).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body["error"].as_str().unwrap().contains("missing field"));
+    }
+
+    #[tokio::test]
+    async fn test_nested_items_are_validated() {
+        let order = json!({
+            "order_number": "PO-1",
+            "order_type": "purchase_order",
+            "external_entity_id": Uuid::new_v4(),
+            "external_entity_type": "vendor",
+            "order_date": "2024-01-01T00:00:00Z",
+            "total_amount": 10.0,
+            "created_by_id": Uuid::new_v4(),
+            "items": [
+                { "item_name": "Resistor", "quantity": 10, "unit_price": 0.1 },
+                { "item_name": "", "quantity": 0, "unit_price": 0.1 }
+            ]
+        });
+
+        let (status, body) = send(post_json("/orders", &order.to_string())).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["fields"]["items[1].quantity"][0], "must be at least 1");
+        assert!(body["fields"].get("items[1].item_name").is_some());
+        assert!(body["fields"].get("items[0].quantity").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_parameters_are_validated() {
+        let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let (status, body) = send(request("/capacity?hours_per_day=25")).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            body["fields"]["hours_per_day"][0],
+            "must be between 0 and 24"
+        );
+
+        let response = app()
+            .oneshot(request("/capacity?hours_per_day=8"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let (status, _) = send(request("/capacity?hours_per_day=lots")).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // Model rule tests
+
+    #[test]
+    fn test_create_models_reject_invalid_fields() {
+        assert_eq!(
+            invalid_fields::<CreatePersonRequest>(json!({
+                "name": "Jane",
+                "email": "not-an-email",
+                "role": "internal",
+                "person_type": "internal"
+            })),
+            ["email"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateItemRequest>(json!({
+                "internal_part_number": "",
+                "manufacturer": "Acme",
+                "context": "store",
+                "quantity": -1
+            })),
+            ["internal_part_number", "quantity"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateJobRequest>(json!({
+                "job_number": "J-1",
+                "quantity": 0,
+                "job_type": "manufacturing",
+                "labor_hours": -2.0,
+                "batch_size": 0
+            })),
+            ["batch_size", "labor_hours", "quantity"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateBomItemRequest>(json!({
+                "parent_item_id": Uuid::new_v4(),
+                "component_item_id": Uuid::new_v4(),
+                "quantity": 0,
+                "assembly_order": -1
+            })),
+            ["assembly_order", "quantity"]
+        );
+        assert_eq!(
+            invalid_fields::<CreatePrinterRequest>(json!({
+                "name": "Labels",
+                "host": "10.0.0.5",
+                "port": 70000
+            })),
+            ["port"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateAssetTypeRequest>(json!({ "name": "" })),
+            ["name"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateSkillRequest>(json!({ "name": "" })),
+            ["name"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateTenantRequest>(
+                json!({ "name": "Acme", "subdomain": "Not Valid" })
+            ),
+            ["subdomain"]
+        );
+        assert_eq!(
+            invalid_fields::<CreateShipmentRequest>(json!({
+                "order_id": Uuid::new_v4(),
+                "ship_to": {},
+                "packages": [{ "weight_kg": 0.0 }]
+            })),
+            ["packages[0].weight_kg"]
+        );
+    }
+
+    #[test]
+    fn test_update_models_reject_invalid_fields() {
+        assert_eq!(
+            invalid_fields::<UpdateMachineRequest>(json!({ "port": 0, "latitude": 91.0 })),
+            ["latitude", "port"]
+        );
+        assert_eq!(
+            invalid_fields::<UpdatePersonRequest>(json!({ "name": "" })),
+            ["name"]
+        );
+        assert_eq!(
+            invalid_fields::<UpdateJobRequest>(json!({
+                "setup_time_hours": -1.0,
+                "cycle_time_minutes": -1.0,
+                "travel_time_hours": -1.0,
+                "sampling_size": 0
+            })),
+            [
+                "cycle_time_minutes",
+                "sampling_size",
+                "setup_time_hours",
+                "travel_time_hours"
+            ]
+        );
+        assert_eq!(
+            invalid_fields::<UpdateOrderRequest>(json!({ "total_amount": -5.0 })),
+            ["total_amount"]
+        );
+        assert_eq!(
+            invalid_fields::<UpdateItemRequest>(json!({ "reorder_point": -1 })),
+            ["reorder_point"]
+        );
+        assert_eq!(
+            invalid_fields::<UpdateAssetRequest>(json!({ "file_size": -1 })),
+            ["file_size"]
+        );
+        assert_eq!(
+            invalid_fields::<UpdateReportScheduleRequest>(json!({ "recipients": [] })),
+            ["recipients"]
+        );
+
+        // Leaving everything out of an update is valid
+        assert!(invalid_fields::<UpdateMachineRequest>(json!({})).is_empty());
+        assert!(invalid_fields::<UpdateJobRequest>(json!({})).is_empty());
+        assert!(invalid_fields::<UpdatePersonRequest>(json!({})).is_empty());
+    }
+
+    #[test]
+    fn test_rule_expressions() {
+        use ems_server::utils::validation_rule::{parse_expression, parse_rule_expression};
+
+        let order = json!({
+            "order_type": "customer_order",
+            "total_amount": 12500.0,
+            "status": "draft",
+            "notes": "  ",
+            "line_count": 3,
+            "metadata": { "project_code": "P-17", "tags": [] }
+        });
+        let holds = |text: &str| parse_expression(text).unwrap().holds(&order);
+
+        assert!(holds("total_amount > 10000"));
+        assert!(holds("total_amount >= 12500 and line_count == 3"));
+        assert!(!holds("total_amount < 100 or status != 'draft'"));
+        assert!(holds("not (order_type == \"purchase_order\")"));
+        assert!(holds("status in [\"draft\", \"pending\"]"));
+        assert!(holds("present(metadata.project_code)"));
+
+        // Blank text, empty lists and missing fields are not present
+        assert!(!holds("present(notes)"));
+        assert!(!holds("present(metadata.tags)"));
+        assert!(!holds("present(metadata.cost_centre)"));
+        assert!(holds("metadata.cost_centre == null"));
+
+        // Values of different types never compare
+        assert!(!holds("total_amount > 'a'"));
+        assert!(holds("total_amount != '12500'"));
+
+        // `and` binds tighter than `or`
+        assert!(holds(
+            "status == 'sent' and line_count == 0 or total_amount > 0"
+        ));
+
+        // Errors say what is wrong and where
+        assert_eq!(
+            parse_expression("total_amount >").unwrap_err(),
+            "Unexpected end of expression"
+        );
+        assert_eq!(
+            parse_expression("(status == 'draft'").unwrap_err(),
+            "Expected ) at position 19"
+        );
+        assert_eq!(
+            parse_expression("status = 'draft'").unwrap_err(),
+            "Unexpected '=' at position 8"
+        );
+        assert_eq!(
+            parse_expression("status == 'draft").unwrap_err(),
+            "Unclosed text at position 11"
+        );
+        assert!(parse_expression("").is_err());
+        assert!(parse_expression(&"not ".repeat(40)).is_err());
+
+        // Rules may only refer to the entity's fields and anything under metadata
+        assert!(parse_rule_expression("present(metadata.project_code)", RuleEntity::Order).is_ok());
+        assert_eq!(
+            parse_rule_expression("present(datasheet)", RuleEntity::Order)
+                .unwrap_err()
+                .split(';')
+                .next()
+                .unwrap(),
+            "Unknown order field: datasheet"
+        );
+        assert!(parse_rule_expression("category.name == 'ic'", RuleEntity::Item).is_err());
+    }
+
+    #[test]
+    fn test_rule_violations() {
+        use chrono::Utc;
+        use ems_server::utils::validation_rule::{rule_violations, CompiledRule};
+
+        let rule =
+            |name: &str, condition: Option<&str>, requirement: &str, message: Option<&str>| {
+                CompiledRule::compile(ValidationRule {
+                    id: Uuid::new_v4(),
+                    tenant_id: Uuid::new_v4(),
+                    name: name.to_string(),
+                    entity: "item".to_string(),
+                    condition: condition.map(str::to_string),
+                    requirement: requirement.to_string(),
+                    message: message.map(str::to_string),
+                    enabled: true,
+                    created_by_id: None,
+                    created_at: Some(Utc::now()),
+                    updated_at: Some(Utc::now()),
+                })
+                .unwrap()
+            };
+        let rules = [
+            rule(
+                "IC datasheets",
+                Some("category in ['ic', 'module']"),
+                "present(datasheet)",
+                Some("Items in category ic or module need a datasheet"),
+            ),
+            rule(
+                "Manufacturer part number",
+                None,
+                "present(mfr_part_number)",
+                None,
+            ),
+        ];
+
+        let item = json!({ "category": "ic", "manufacturer": "TI", "mfr_part_number": "LM358" });
+        let violations = rule_violations(&rules, &item);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule_name, "IC datasheets");
+        assert_eq!(
+            violations[0].message,
+            "Items in category ic or module need a datasheet"
+        );
+
+        // Rules whose condition does not hold do not apply
+        let item = json!({ "category": "resistor", "manufacturer": "Yageo" });
+        let violations = rule_violations(&rules, &item);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "Manufacturer part number: requires present(mfr_part_number)"
+        );
+
+        // Every broken rule is reported, in one message
+        let item = json!({ "category": "module" });
+        let violations = RuleViolations {
+            violations: rule_violations(&rules, &item),
+        };
+        assert_eq!(
+            violations.to_string(),
+            "Items in category ic or module need a datasheet; \
+             Manufacturer part number: requires present(mfr_part_number)"
+        );
+
+        let item = json!({ "category": "ic", "datasheet": "https://example.com/ic.pdf", "mfr_part_number": "X" });
+        assert!(rule_violations(&rules, &item).is_empty());
+    }
+}